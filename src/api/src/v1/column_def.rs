@@ -17,7 +17,7 @@ use std::collections::HashMap;
 use datatypes::schema::{
     ColumnDefaultConstraint, ColumnSchema, FulltextAnalyzer, FulltextBackend, FulltextOptions,
     SkippingIndexOptions, SkippingIndexType, COMMENT_KEY, FULLTEXT_KEY, INVERTED_INDEX_KEY,
-    SKIPPING_INDEX_KEY,
+    ON_UPDATE_KEY, SKIPPING_INDEX_KEY, SST_DICTIONARY_KEY,
 };
 use greptime_proto::v1::{
     Analyzer, FulltextBackend as PbFulltextBackend, SkippingIndexType as PbSkippingIndexType,
@@ -34,6 +34,10 @@ const FULLTEXT_GRPC_KEY: &str = "fulltext";
 const INVERTED_INDEX_GRPC_KEY: &str = "inverted_index";
 /// Key used to store skip index options in gRPC column options.
 const SKIPPING_INDEX_GRPC_KEY: &str = "skipping_index";
+/// Key used to store SST dictionary encoding option in gRPC column options.
+const SST_DICTIONARY_GRPC_KEY: &str = "sst_dictionary";
+/// Key used to store the `ON UPDATE` function in gRPC column options.
+const ON_UPDATE_GRPC_KEY: &str = "on_update";
 
 /// Tries to construct a `ColumnSchema` from the given  `ColumnDef`.
 pub fn try_as_column_schema(column_def: &ColumnDef) -> Result<ColumnSchema> {
@@ -66,6 +70,12 @@ pub fn try_as_column_schema(column_def: &ColumnDef) -> Result<ColumnSchema> {
         if let Some(skipping_index) = options.options.get(SKIPPING_INDEX_GRPC_KEY) {
             metadata.insert(SKIPPING_INDEX_KEY.to_string(), skipping_index.to_owned());
         }
+        if let Some(sst_dictionary) = options.options.get(SST_DICTIONARY_GRPC_KEY) {
+            metadata.insert(SST_DICTIONARY_KEY.to_string(), sst_dictionary.to_owned());
+        }
+        if let Some(on_update) = options.options.get(ON_UPDATE_GRPC_KEY) {
+            metadata.insert(ON_UPDATE_KEY.to_string(), on_update.to_owned());
+        }
     }
 
     ColumnSchema::new(&column_def.name, data_type.into(), column_def.is_nullable)
@@ -95,6 +105,16 @@ pub fn options_from_column_schema(column_schema: &ColumnSchema) -> Option<Column
             .options
             .insert(SKIPPING_INDEX_GRPC_KEY.to_string(), skipping_index.clone());
     }
+    if let Some(sst_dictionary) = column_schema.metadata().get(SST_DICTIONARY_KEY) {
+        options
+            .options
+            .insert(SST_DICTIONARY_GRPC_KEY.to_string(), sst_dictionary.clone());
+    }
+    if let Some(on_update) = column_schema.on_update() {
+        options
+            .options
+            .insert(ON_UPDATE_GRPC_KEY.to_string(), on_update.clone());
+    }
 
     (!options.options.is_empty()).then_some(options)
 }
@@ -144,6 +164,15 @@ pub fn options_from_inverted() -> ColumnOptions {
     options
 }
 
+/// Constructs a `ColumnOptions` for the `ON UPDATE` function of a column.
+pub fn options_from_on_update(func: &str) -> ColumnOptions {
+    let mut options = ColumnOptions::default();
+    options
+        .options
+        .insert(ON_UPDATE_GRPC_KEY.to_string(), func.to_string());
+    options
+}
+
 /// Tries to construct a `FulltextAnalyzer` from the given analyzer.
 pub fn as_fulltext_option_analyzer(analyzer: Analyzer) -> FulltextAnalyzer {
     match analyzer {
@@ -195,6 +224,8 @@ mod tests {
                         "{\"enable\":true}".to_string(),
                     ),
                     (INVERTED_INDEX_GRPC_KEY.to_string(), "true".to_string()),
+                    (SST_DICTIONARY_GRPC_KEY.to_string(), "off".to_string()),
+                    (ON_UPDATE_GRPC_KEY.to_string(), "now()".to_string()),
                 ]),
             }),
         };
@@ -217,6 +248,8 @@ mod tests {
             }
         );
         assert!(schema.is_inverted_indexed());
+        assert!(!schema.is_sst_dictionary_enabled());
+        assert_eq!(schema.on_update().unwrap(), "now()");
     }
 
     #[test]
@@ -234,6 +267,8 @@ mod tests {
             })
             .unwrap();
         schema.set_inverted_index(true);
+        schema.set_sst_dictionary(false);
+        let schema = schema.with_on_update(Some("now()".to_string()));
         let options = options_from_column_schema(&schema).unwrap();
         assert_eq!(
             options.options.get(FULLTEXT_GRPC_KEY).unwrap(),
@@ -243,6 +278,8 @@ mod tests {
             options.options.get(INVERTED_INDEX_GRPC_KEY).unwrap(),
             "true"
         );
+        assert_eq!(options.options.get(SST_DICTIONARY_GRPC_KEY).unwrap(), "off");
+        assert_eq!(options.options.get(ON_UPDATE_GRPC_KEY).unwrap(), "now()");
     }
 
     #[test]
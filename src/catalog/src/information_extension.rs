@@ -25,14 +25,26 @@ use snafu::ResultExt;
 
 use crate::error;
 use crate::information_schema::InformationExtension;
+use crate::schema_limits::SchemaLimitOptions;
 
 pub struct DistributedInformationExtension {
     meta_client: MetaClientRef,
+    schema_limits: SchemaLimitOptions,
 }
 
 impl DistributedInformationExtension {
     pub fn new(meta_client: MetaClientRef) -> Self {
-        Self { meta_client }
+        Self {
+            meta_client,
+            schema_limits: SchemaLimitOptions::default(),
+        }
+    }
+
+    pub fn with_schema_limits(self, schema_limits: SchemaLimitOptions) -> Self {
+        Self {
+            schema_limits,
+            ..self
+        }
     }
 }
 
@@ -76,6 +88,8 @@ impl InformationExtension for DistributedInformationExtension {
                 end_time_ms: procedure.end_time_ms,
                 state: ProcedureState::Running,
                 lock_keys: procedure.lock_keys,
+                // The progress is not reported by metasrv yet.
+                progress: None,
             };
             result.push((status, procedure_info));
         }
@@ -98,4 +112,8 @@ impl InformationExtension for DistributedInformationExtension {
             .map_err(BoxedError::new)
             .context(crate::error::ListFlowStatsSnafu)
     }
+
+    fn schema_limits(&self) -> SchemaLimitOptions {
+        self.schema_limits.clone()
+    }
 }
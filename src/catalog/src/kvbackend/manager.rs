@@ -53,6 +53,9 @@ use crate::information_schema::{InformationExtensionRef, InformationSchemaProvid
 use crate::kvbackend::TableCacheRef;
 use crate::system_schema::pg_catalog::PGCatalogProvider;
 use crate::system_schema::SystemSchemaProvider;
+use crate::table_statistics::{
+    TableStatisticsCache, TableStatisticsCacheRef, DEFAULT_STATISTICS_STALENESS,
+};
 use crate::CatalogManager;
 
 /// Access all existing catalog, schema and tables.
@@ -74,6 +77,8 @@ pub struct KvBackendCatalogManager {
     cache_registry: LayeredCacheRegistryRef,
     /// Only available in `Standalone` mode.
     procedure_manager: Option<ProcedureManagerRef>,
+    /// Caches the statistics of tables for the query optimizer.
+    table_statistics_cache: TableStatisticsCacheRef,
 }
 
 const CATALOG_CACHE_MAX_CAPACITY: u64 = 128;
@@ -86,6 +91,10 @@ impl KvBackendCatalogManager {
         procedure_manager: Option<ProcedureManagerRef>,
     ) -> Arc<Self> {
         Arc::new_cyclic(|me| Self {
+            table_statistics_cache: Arc::new(TableStatisticsCache::new(
+                information_extension.clone(),
+                DEFAULT_STATISTICS_STALENESS,
+            )),
             information_extension,
             partition_manager: Arc::new(PartitionRuleManager::new(
                 backend.clone(),
@@ -125,6 +134,11 @@ impl KvBackendCatalogManager {
         self.information_extension.clone()
     }
 
+    /// Returns the cache of table statistics.
+    pub fn table_statistics_cache(&self) -> &TableStatisticsCacheRef {
+        &self.table_statistics_cache
+    }
+
     pub fn partition_manager(&self) -> PartitionRuleManagerRef {
         self.partition_manager.clone()
     }
@@ -34,6 +34,7 @@ pub mod information_extension;
 pub mod kvbackend;
 pub mod memory;
 mod metrics;
+pub mod schema_limits;
 pub mod system_schema;
 pub mod information_schema {
     // TODO(j0hn50n133): re-export to make it compatible with the legacy code, migrate to the new path later
@@ -41,6 +42,7 @@ pub mod information_schema {
 }
 
 pub mod table_source;
+pub mod table_statistics;
 
 #[async_trait::async_trait]
 pub trait CatalogManager: Send + Sync {
@@ -0,0 +1,39 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the names of internal columns, e.g. `__table_id` and `__tsid` of
+/// the metric engine. Logical tables can't have columns with this prefix.
+pub const RESERVED_COLUMN_PREFIX: &str = "__";
+
+/// Limits on the schemas created by DDL and by creating or altering tables on
+/// demand on ingestion. Zero means unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchemaLimitOptions {
+    /// The maximum number of columns of a table, including the physical
+    /// tables of the metric engine.
+    pub max_columns_per_table: usize,
+    /// The maximum number of tables in a schema.
+    pub max_tables_per_schema: usize,
+    /// The expected maximum number of distinct values of a tag. It's only a
+    /// hint shown in `information_schema.schema_limits` and not enforced.
+    pub max_tag_cardinality_hint: usize,
+}
+
+/// Returns whether the column name is reserved for internal columns.
+pub fn is_reserved_column_name(name: &str) -> bool {
+    name.starts_with(RESERVED_COLUMN_PREFIX)
+}
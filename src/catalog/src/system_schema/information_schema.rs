@@ -18,10 +18,11 @@ pub mod flows;
 mod information_memory_table;
 pub mod key_column_usage;
 mod partitions;
-mod procedure_info;
+pub mod procedure_info;
 pub mod region_peers;
 mod region_statistics;
 mod runtime_metrics;
+mod schema_limits;
 pub mod schemata;
 mod table_constraints;
 mod table_names;
@@ -50,6 +51,7 @@ use views::InformationSchemaViews;
 
 use self::columns::InformationSchemaColumns;
 use crate::error::{Error, Result};
+use crate::schema_limits::SchemaLimitOptions;
 use crate::system_schema::information_schema::cluster_info::InformationSchemaClusterInfo;
 use crate::system_schema::information_schema::flows::InformationSchemaFlows;
 use crate::system_schema::information_schema::information_memory_table::get_schema_columns;
@@ -209,6 +211,10 @@ impl SystemSchemaProviderInner for InformationSchemaProvider {
                     self.catalog_manager.clone(),
                 ),
             ) as _),
+            SCHEMA_LIMITS => Some(Arc::new(schema_limits::InformationSchemaSchemaLimits::new(
+                self.catalog_name.clone(),
+                self.catalog_manager.clone(),
+            )) as _),
             _ => None,
         }
     }
@@ -279,6 +285,10 @@ impl InformationSchemaProvider {
             self.build_table(TABLE_CONSTRAINTS).unwrap(),
         );
         tables.insert(FLOWS.to_string(), self.build_table(FLOWS).unwrap());
+        tables.insert(
+            SCHEMA_LIMITS.to_string(),
+            self.build_table(SCHEMA_LIMITS).unwrap(),
+        );
         // Add memory tables
         for name in MEMORY_TABLES.iter() {
             tables.insert((*name).to_string(), self.build_table(name).expect(name));
@@ -346,6 +356,11 @@ pub trait InformationExtension {
 
     /// Get the flow statistics. If no flownode is available, return `None`.
     async fn flow_stats(&self) -> std::result::Result<Option<FlowStat>, Self::Error>;
+
+    /// Gets the schema limits of the frontend.
+    fn schema_limits(&self) -> SchemaLimitOptions {
+        SchemaLimitOptions::default()
+    }
 }
 
 pub struct NoopInformationExtension;
@@ -209,6 +209,7 @@ struct InformationSchemaColumnsBuilder {
     character_set_names: StringVectorBuilder,
     collation_names: StringVectorBuilder,
     column_keys: StringVectorBuilder,
+    extras: StringVectorBuilder,
     greptime_data_types: StringVectorBuilder,
     data_types: StringVectorBuilder,
     semantic_types: StringVectorBuilder,
@@ -241,6 +242,7 @@ impl InformationSchemaColumnsBuilder {
             character_set_names: StringVectorBuilder::with_capacity(INIT_CAPACITY),
             collation_names: StringVectorBuilder::with_capacity(INIT_CAPACITY),
             column_keys: StringVectorBuilder::with_capacity(INIT_CAPACITY),
+            extras: StringVectorBuilder::with_capacity(INIT_CAPACITY),
             greptime_data_types: StringVectorBuilder::with_capacity(INIT_CAPACITY),
             data_types: StringVectorBuilder::with_capacity(INIT_CAPACITY),
             semantic_types: StringVectorBuilder::with_capacity(INIT_CAPACITY),
@@ -383,6 +385,11 @@ impl InformationSchemaColumnsBuilder {
         }
 
         self.column_keys.push(Some(column_key));
+        let extra = column_schema
+            .on_update()
+            .map(|func| format!("on update {func}"));
+        self.extras
+            .push(Some(extra.as_deref().unwrap_or(EMPTY_STR)));
         self.greptime_data_types
             .push(Some(&column_schema.data_type.name()));
         self.data_types.push(Some(&data_type));
@@ -433,7 +440,7 @@ impl InformationSchemaColumnsBuilder {
             Arc::new(self.character_set_names.finish()),
             Arc::new(self.collation_names.finish()),
             Arc::new(self.column_keys.finish()),
-            empty_string.clone(),
+            Arc::new(self.extras.finish()),
             privileges,
             empty_string,
             Arc::new(self.greptime_data_types.finish()),
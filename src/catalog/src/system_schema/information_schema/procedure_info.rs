@@ -38,12 +38,13 @@ use crate::system_schema::information_schema::{InformationTable, Predicates, PRO
 use crate::system_schema::utils;
 use crate::CatalogManager;
 
-const PROCEDURE_ID: &str = "procedure_id";
-const PROCEDURE_TYPE: &str = "procedure_type";
-const START_TIME: &str = "start_time";
-const END_TIME: &str = "end_time";
-const STATUS: &str = "status";
-const LOCK_KEYS: &str = "lock_keys";
+pub const PROCEDURE_ID: &str = "procedure_id";
+pub const PROCEDURE_TYPE: &str = "procedure_type";
+pub const START_TIME: &str = "start_time";
+pub const END_TIME: &str = "end_time";
+pub const STATUS: &str = "status";
+pub const LOCK_KEYS: &str = "lock_keys";
+pub const PROGRESS: &str = "progress";
 
 const INIT_CAPACITY: usize = 42;
 
@@ -55,6 +56,7 @@ const INIT_CAPACITY: usize = 42;
 /// - `end_time`: the ending execution time of the procedure.
 /// - `status`: the status of the procedure.
 /// - `lock_keys`: the lock keys of the procedure.
+/// - `progress`: the latest progress message reported by the procedure.
 #[derive(Debug)]
 pub(super) struct InformationSchemaProcedureInfo {
     schema: SchemaRef,
@@ -85,6 +87,7 @@ impl InformationSchemaProcedureInfo {
             ),
             ColumnSchema::new(STATUS, ConcreteDataType::string_datatype(), false),
             ColumnSchema::new(LOCK_KEYS, ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new(PROGRESS, ConcreteDataType::string_datatype(), true),
         ]))
     }
 
@@ -140,6 +143,7 @@ struct InformationSchemaProcedureInfoBuilder {
     end_times: TimestampMillisecondVectorBuilder,
     statuses: StringVectorBuilder,
     lock_keys: StringVectorBuilder,
+    progresses: StringVectorBuilder,
 }
 
 impl InformationSchemaProcedureInfoBuilder {
@@ -153,6 +157,7 @@ impl InformationSchemaProcedureInfoBuilder {
             end_times: TimestampMillisecondVectorBuilder::with_capacity(INIT_CAPACITY),
             statuses: StringVectorBuilder::with_capacity(INIT_CAPACITY),
             lock_keys: StringVectorBuilder::with_capacity(INIT_CAPACITY),
+            progresses: StringVectorBuilder::with_capacity(INIT_CAPACITY),
         }
     }
 
@@ -179,6 +184,7 @@ impl InformationSchemaProcedureInfoBuilder {
             start_time_ms,
             end_time_ms,
             lock_keys,
+            progress,
             ..
         } = procedure_info;
         let pid = id.to_string();
@@ -193,6 +199,7 @@ impl InformationSchemaProcedureInfoBuilder {
             (END_TIME, &Value::from(end_time)),
             (STATUS, &Value::from(status.clone())),
             (LOCK_KEYS, &Value::from(lock_keys.clone())),
+            (PROGRESS, &Value::from(progress.clone())),
         ];
         if !predicates.eval(&row) {
             return;
@@ -203,6 +210,7 @@ impl InformationSchemaProcedureInfoBuilder {
         self.end_times.push(Some(end_time));
         self.statuses.push(Some(&status));
         self.lock_keys.push(Some(&lock_keys));
+        self.progresses.push(progress.as_deref());
     }
 
     fn finish(&mut self) -> Result<RecordBatch> {
@@ -213,6 +221,7 @@ impl InformationSchemaProcedureInfoBuilder {
             Arc::new(self.end_times.finish()),
             Arc::new(self.statuses.finish()),
             Arc::new(self.lock_keys.finish()),
+            Arc::new(self.progresses.finish()),
         ];
         RecordBatch::new(self.schema.clone(), columns).context(CreateRecordBatchSnafu)
     }
@@ -47,6 +47,7 @@ const SST_SIZE: &str = "sst_size";
 const INDEX_SIZE: &str = "index_size";
 const ENGINE: &str = "engine";
 const REGION_ROLE: &str = "region_role";
+const SST_COMPRESSION: &str = "sst_compression";
 
 const INIT_CAPACITY: usize = 42;
 
@@ -63,6 +64,7 @@ const INIT_CAPACITY: usize = 42;
 /// - `index_size`: The sst index files size in bytes.
 /// - `engine`: The engine type.
 /// - `region_role`: The region role.
+/// - `sst_compression`: The compression codec used to write new sst files.
 #[derive(Debug)]
 pub(super) struct InformationSchemaRegionStatistics {
     schema: SchemaRef,
@@ -90,6 +92,7 @@ impl InformationSchemaRegionStatistics {
             ColumnSchema::new(INDEX_SIZE, ConcreteDataType::uint64_datatype(), true),
             ColumnSchema::new(ENGINE, ConcreteDataType::string_datatype(), true),
             ColumnSchema::new(REGION_ROLE, ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new(SST_COMPRESSION, ConcreteDataType::string_datatype(), true),
         ]))
     }
 
@@ -152,6 +155,7 @@ struct InformationSchemaRegionStatisticsBuilder {
     index_sizes: UInt64VectorBuilder,
     engines: StringVectorBuilder,
     region_roles: StringVectorBuilder,
+    sst_compressions: StringVectorBuilder,
 }
 
 impl InformationSchemaRegionStatisticsBuilder {
@@ -170,6 +174,7 @@ impl InformationSchemaRegionStatisticsBuilder {
             index_sizes: UInt64VectorBuilder::with_capacity(INIT_CAPACITY),
             engines: StringVectorBuilder::with_capacity(INIT_CAPACITY),
             region_roles: StringVectorBuilder::with_capacity(INIT_CAPACITY),
+            sst_compressions: StringVectorBuilder::with_capacity(INIT_CAPACITY),
         }
     }
 
@@ -200,6 +205,14 @@ impl InformationSchemaRegionStatisticsBuilder {
             (INDEX_SIZE, &Value::from(region_stat.index_size)),
             (ENGINE, &Value::from(region_stat.engine.as_str())),
             (REGION_ROLE, &Value::from(region_stat.role.to_string())),
+            (
+                SST_COMPRESSION,
+                &region_stat
+                    .sst_compression
+                    .as_deref()
+                    .map(Value::from)
+                    .unwrap_or(Value::Null),
+            ),
         ];
 
         if !predicate.eval(&row) {
@@ -218,6 +231,8 @@ impl InformationSchemaRegionStatisticsBuilder {
         self.index_sizes.push(Some(region_stat.index_size));
         self.engines.push(Some(&region_stat.engine));
         self.region_roles.push(Some(&region_stat.role.to_string()));
+        self.sst_compressions
+            .push(region_stat.sst_compression.as_deref());
     }
 
     fn finish(&mut self) -> Result<RecordBatch> {
@@ -233,6 +248,7 @@ impl InformationSchemaRegionStatisticsBuilder {
             Arc::new(self.index_sizes.finish()),
             Arc::new(self.engines.finish()),
             Arc::new(self.region_roles.finish()),
+            Arc::new(self.sst_compressions.finish()),
         ];
 
         RecordBatch::new(self.schema.clone(), columns).context(CreateRecordBatchSnafu)
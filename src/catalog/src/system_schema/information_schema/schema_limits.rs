@@ -0,0 +1,299 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{Arc, Weak};
+
+use arrow_schema::SchemaRef as ArrowSchemaRef;
+use common_catalog::consts::{
+    INFORMATION_SCHEMA_NAME, INFORMATION_SCHEMA_SCHEMA_LIMITS_TABLE_ID, PG_CATALOG_NAME,
+};
+use common_error::ext::BoxedError;
+use common_recordbatch::adapter::RecordBatchStreamAdapter;
+use common_recordbatch::{DfSendableRecordBatchStream, RecordBatch, SendableRecordBatchStream};
+use datafusion::execution::TaskContext;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter as DfRecordBatchStreamAdapter;
+use datafusion::physical_plan::streaming::PartitionStream as DfPartitionStream;
+use datatypes::prelude::{ConcreteDataType, ScalarVectorBuilder, VectorRef};
+use datatypes::schema::{ColumnSchema, Schema, SchemaRef};
+use datatypes::value::Value;
+use datatypes::vectors::{StringVectorBuilder, UInt64VectorBuilder};
+use futures::TryStreamExt;
+use snafu::{OptionExt, ResultExt};
+use store_api::storage::{ScanRequest, TableId};
+use table::metadata::TableType;
+
+use crate::error::{
+    CreateRecordBatchSnafu, InternalSnafu, Result, UpgradeWeakCatalogManagerRefSnafu,
+};
+use crate::information_schema::Predicates;
+use crate::schema_limits::SchemaLimitOptions;
+use crate::system_schema::information_schema::{InformationTable, SCHEMA_LIMITS};
+use crate::system_schema::utils;
+use crate::CatalogManager;
+
+const TABLE_CATALOG: &str = "table_catalog";
+const TABLE_SCHEMA: &str = "table_schema";
+const TABLE_NAME: &str = "table_name";
+const LIMIT_NAME: &str = "limit_name";
+const CURRENT_USAGE: &str = "current_usage";
+const LIMIT_VALUE: &str = "limit_value";
+
+const MAX_TABLES_PER_SCHEMA: &str = "max_tables_per_schema";
+const MAX_COLUMNS_PER_TABLE: &str = "max_columns_per_table";
+const MAX_TAG_CARDINALITY_HINT: &str = "max_tag_cardinality_hint";
+
+const INIT_CAPACITY: usize = 42;
+
+/// The `SCHEMA_LIMITS` table shows the current usage of the schema limits. Including fields:
+///
+/// - `table_catalog`: The catalog name.
+/// - `table_schema`: The schema name.
+/// - `table_name`: The table name, or NULL for the limits of a schema.
+/// - `limit_name`: The name of the limit, e.g. `max_columns_per_table`.
+/// - `current_usage`: The current usage, or NULL if it's unknown.
+/// - `limit_value`: The configured limit, or NULL if it's unlimited.
+#[derive(Debug)]
+pub(super) struct InformationSchemaSchemaLimits {
+    schema: SchemaRef,
+    catalog_name: String,
+    catalog_manager: Weak<dyn CatalogManager>,
+}
+
+impl InformationSchemaSchemaLimits {
+    pub(super) fn new(catalog_name: String, catalog_manager: Weak<dyn CatalogManager>) -> Self {
+        Self {
+            schema: Self::schema(),
+            catalog_name,
+            catalog_manager,
+        }
+    }
+
+    pub(crate) fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            ColumnSchema::new(TABLE_CATALOG, ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(TABLE_SCHEMA, ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(TABLE_NAME, ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new(LIMIT_NAME, ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(CURRENT_USAGE, ConcreteDataType::uint64_datatype(), true),
+            ColumnSchema::new(LIMIT_VALUE, ConcreteDataType::uint64_datatype(), true),
+        ]))
+    }
+
+    fn builder(&self) -> InformationSchemaSchemaLimitsBuilder {
+        InformationSchemaSchemaLimitsBuilder::new(
+            self.schema.clone(),
+            self.catalog_name.clone(),
+            self.catalog_manager.clone(),
+        )
+    }
+}
+
+impl InformationTable for InformationSchemaSchemaLimits {
+    fn table_id(&self) -> TableId {
+        INFORMATION_SCHEMA_SCHEMA_LIMITS_TABLE_ID
+    }
+
+    fn table_name(&self) -> &'static str {
+        SCHEMA_LIMITS
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn to_stream(&self, request: ScanRequest) -> Result<SendableRecordBatchStream> {
+        let schema = self.schema.arrow_schema().clone();
+        let mut builder = self.builder();
+        let stream = Box::pin(DfRecordBatchStreamAdapter::new(
+            schema,
+            futures::stream::once(async move {
+                builder
+                    .make_schema_limits(Some(request))
+                    .await
+                    .map(|x| x.into_df_record_batch())
+                    .map_err(|err| datafusion::error::DataFusionError::External(Box::new(err)))
+            }),
+        ));
+        Ok(Box::pin(
+            RecordBatchStreamAdapter::try_new(stream)
+                .map_err(BoxedError::new)
+                .context(InternalSnafu)?,
+        ))
+    }
+}
+
+struct InformationSchemaSchemaLimitsBuilder {
+    schema: SchemaRef,
+    catalog_name: String,
+    catalog_manager: Weak<dyn CatalogManager>,
+
+    catalog_names: StringVectorBuilder,
+    schema_names: StringVectorBuilder,
+    table_names: StringVectorBuilder,
+    limit_names: StringVectorBuilder,
+    current_usages: UInt64VectorBuilder,
+    limit_values: UInt64VectorBuilder,
+}
+
+impl InformationSchemaSchemaLimitsBuilder {
+    fn new(
+        schema: SchemaRef,
+        catalog_name: String,
+        catalog_manager: Weak<dyn CatalogManager>,
+    ) -> Self {
+        Self {
+            schema,
+            catalog_name,
+            catalog_manager,
+            catalog_names: StringVectorBuilder::with_capacity(INIT_CAPACITY),
+            schema_names: StringVectorBuilder::with_capacity(INIT_CAPACITY),
+            table_names: StringVectorBuilder::with_capacity(INIT_CAPACITY),
+            limit_names: StringVectorBuilder::with_capacity(INIT_CAPACITY),
+            current_usages: UInt64VectorBuilder::with_capacity(INIT_CAPACITY),
+            limit_values: UInt64VectorBuilder::with_capacity(INIT_CAPACITY),
+        }
+    }
+
+    /// Construct the `information_schema.schema_limits` virtual table
+    async fn make_schema_limits(&mut self, request: Option<ScanRequest>) -> Result<RecordBatch> {
+        let catalog_name = self.catalog_name.clone();
+        let catalog_manager = self
+            .catalog_manager
+            .upgrade()
+            .context(UpgradeWeakCatalogManagerRefSnafu)?;
+        let limits = utils::information_extension(&self.catalog_manager)?.schema_limits();
+        let predicates = Predicates::from_scan_request(&request);
+
+        for schema_name in catalog_manager.schema_names(&catalog_name, None).await? {
+            // The limits don't apply to the system schemas.
+            if schema_name == INFORMATION_SCHEMA_NAME || schema_name == PG_CATALOG_NAME {
+                continue;
+            }
+
+            let mut tables = 0;
+            let mut stream = catalog_manager.tables(&catalog_name, &schema_name, None);
+            while let Some(table) = stream.try_next().await? {
+                tables += 1;
+                let table_info = table.table_info();
+                if table_info.table_type == TableType::View {
+                    continue;
+                }
+                self.add_limit(
+                    &predicates,
+                    &schema_name,
+                    Some(&table_info.name),
+                    MAX_COLUMNS_PER_TABLE,
+                    Some(table_info.meta.schema.num_columns()),
+                    limits.max_columns_per_table,
+                );
+            }
+
+            self.add_schema_limits(&predicates, &schema_name, tables, &limits);
+        }
+
+        self.finish()
+    }
+
+    fn add_schema_limits(
+        &mut self,
+        predicates: &Predicates,
+        schema_name: &str,
+        tables: usize,
+        limits: &SchemaLimitOptions,
+    ) {
+        self.add_limit(
+            predicates,
+            schema_name,
+            None,
+            MAX_TABLES_PER_SCHEMA,
+            Some(tables),
+            limits.max_tables_per_schema,
+        );
+        // The cardinality of tags is not tracked.
+        self.add_limit(
+            predicates,
+            schema_name,
+            None,
+            MAX_TAG_CARDINALITY_HINT,
+            None,
+            limits.max_tag_cardinality_hint,
+        );
+    }
+
+    fn add_limit(
+        &mut self,
+        predicates: &Predicates,
+        schema_name: &str,
+        table_name: Option<&str>,
+        limit_name: &str,
+        current_usage: Option<usize>,
+        limit_value: usize,
+    ) {
+        let row = [
+            (TABLE_CATALOG, &Value::from(self.catalog_name.as_str())),
+            (TABLE_SCHEMA, &Value::from(schema_name)),
+            (
+                TABLE_NAME,
+                &table_name.map(Value::from).unwrap_or(Value::Null),
+            ),
+            (LIMIT_NAME, &Value::from(limit_name)),
+        ];
+
+        if !predicates.eval(&row) {
+            return;
+        }
+        self.catalog_names.push(Some(self.catalog_name.as_str()));
+        self.schema_names.push(Some(schema_name));
+        self.table_names.push(table_name);
+        self.limit_names.push(Some(limit_name));
+        self.current_usages
+            .push(current_usage.map(|usage| usage as u64));
+        // Zero means unlimited.
+        self.limit_values
+            .push((limit_value != 0).then_some(limit_value as u64));
+    }
+
+    fn finish(&mut self) -> Result<RecordBatch> {
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(self.catalog_names.finish()),
+            Arc::new(self.schema_names.finish()),
+            Arc::new(self.table_names.finish()),
+            Arc::new(self.limit_names.finish()),
+            Arc::new(self.current_usages.finish()),
+            Arc::new(self.limit_values.finish()),
+        ];
+        RecordBatch::new(self.schema.clone(), columns).context(CreateRecordBatchSnafu)
+    }
+}
+
+impl DfPartitionStream for InformationSchemaSchemaLimits {
+    fn schema(&self) -> &ArrowSchemaRef {
+        self.schema.arrow_schema()
+    }
+
+    fn execute(&self, _: Arc<TaskContext>) -> DfSendableRecordBatchStream {
+        let schema = self.schema.arrow_schema().clone();
+        let mut builder = self.builder();
+        Box::pin(DfRecordBatchStreamAdapter::new(
+            schema,
+            futures::stream::once(async move {
+                builder
+                    .make_schema_limits(None)
+                    .await
+                    .map(|x| x.into_df_record_batch())
+                    .map_err(Into::into)
+            }),
+        ))
+    }
+}
@@ -18,6 +18,7 @@ use std::sync::Arc;
 use bytes::Bytes;
 use common_catalog::format_full_table_name;
 use common_query::logical_plan::{rename_logical_plan_columns, SubstraitPlanDecoderRef};
+use common_telemetry::warn;
 use datafusion::common::{ResolvedTableReference, TableReference};
 use datafusion::datasource::view::ViewTable;
 use datafusion::datasource::{provider_as_source, TableProvider};
@@ -70,6 +71,22 @@ impl DfTableSourceProvider {
         }
     }
 
+    /// Returns the context of the query the tables are resolved for.
+    pub fn query_ctx(&self) -> &QueryContextRef {
+        &self.query_ctx
+    }
+
+    /// Returns true if the default schema of the query exists.
+    pub async fn default_schema_exists(&self) -> Result<bool> {
+        self.catalog_manager
+            .schema_exists(
+                &self.default_catalog,
+                &self.default_schema,
+                Some(&self.query_ctx),
+            )
+            .await
+    }
+
     pub fn resolve_table_ref(&self, table_ref: TableReference) -> Result<ResolvedTableReference> {
         if self.disallow_cross_catalog_query {
             match &table_ref {
@@ -117,7 +134,7 @@ impl DfTableSourceProvider {
         let provider: Arc<dyn TableProvider> = if table.table_info().table_type == TableType::View {
             self.create_view_provider(&table).await?
         } else {
-            Arc::new(DfTableProviderAdapter::new(table))
+            Arc::new(self.create_table_provider(table).await)
         };
 
         let source = provider_as_source(provider);
@@ -126,6 +143,38 @@ impl DfTableSourceProvider {
         Ok(source)
     }
 
+    /// Creates the provider of a base table with its statistics, if available.
+    async fn create_table_provider(&self, table: TableRef) -> DfTableProviderAdapter {
+        let Some(catalog_manager) = self
+            .catalog_manager
+            .as_any()
+            .downcast_ref::<KvBackendCatalogManager>()
+        else {
+            return DfTableProviderAdapter::new(table);
+        };
+
+        let statistics = match catalog_manager
+            .table_statistics_cache()
+            .table_statistics(&table)
+            .await
+        {
+            Ok(statistics) => statistics,
+            Err(e) => {
+                // The optimizer still works without statistics.
+                warn!(
+                    e; "Failed to get the statistics of table {}",
+                    table.table_info().full_table_name()
+                );
+                None
+            }
+        };
+        let provider = DfTableProviderAdapter::new(table);
+        match statistics {
+            Some(statistics) => provider.with_statistics(statistics),
+            None => provider,
+        }
+    }
+
     async fn create_view_provider(&self, table: &TableRef) -> Result<Arc<dyn TableProvider>> {
         let catalog_manager = self
             .catalog_manager
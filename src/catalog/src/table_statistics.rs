@@ -0,0 +1,135 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use common_meta::datanode::RegionStat;
+use common_time::Timestamp;
+use datatypes::value::Value;
+use store_api::storage::RegionId;
+use table::{ColumnStatistics, TableRef, TableStatistics};
+use tokio::sync::Mutex;
+
+use crate::error::Result;
+use crate::information_schema::InformationExtensionRef;
+
+/// The default bound of the staleness of the cached region statistics.
+pub const DEFAULT_STATISTICS_STALENESS: Duration = Duration::from_secs(10);
+
+type RegionStats = Arc<HashMap<RegionId, RegionStat>>;
+
+/// Caches the region statistics collected by heartbeats (or the local regions
+/// in standalone mode) and summarizes them into [TableStatistics].
+pub struct TableStatisticsCache {
+    information_extension: InformationExtensionRef,
+    staleness: Duration,
+    /// The region statistics and the time they were fetched.
+    region_stats: Mutex<Option<(Instant, RegionStats)>>,
+}
+
+pub type TableStatisticsCacheRef = Arc<TableStatisticsCache>;
+
+impl TableStatisticsCache {
+    pub fn new(information_extension: InformationExtensionRef, staleness: Duration) -> Self {
+        Self {
+            information_extension,
+            staleness,
+            region_stats: Mutex::new(None),
+        }
+    }
+
+    /// Returns the statistics of the table, or `None` if none of its regions
+    /// has reported statistics yet.
+    ///
+    /// The statistics are estimations: row counts include the duplicated and
+    /// deleted rows that haven't been compacted.
+    pub async fn table_statistics(&self, table: &TableRef) -> Result<Option<TableStatistics>> {
+        let region_stats = self.region_stats().await?;
+        let stats = table
+            .table_info()
+            .region_ids()
+            .iter()
+            .filter_map(|region_id| region_stats.get(region_id))
+            .collect::<Vec<_>>();
+        if stats.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(summarize_region_stats(table, &stats)))
+    }
+
+    async fn region_stats(&self) -> Result<RegionStats> {
+        let mut cached = self.region_stats.lock().await;
+        if let Some((fetched_at, region_stats)) = cached.as_ref() {
+            if fetched_at.elapsed() < self.staleness {
+                return Ok(region_stats.clone());
+            }
+        }
+
+        let region_stats = Arc::new(
+            self.information_extension
+                .region_stats()
+                .await?
+                .into_iter()
+                .map(|stat| (stat.id, stat))
+                .collect::<HashMap<_, _>>(),
+        );
+        *cached = Some((Instant::now(), region_stats.clone()));
+        Ok(region_stats)
+    }
+}
+
+fn summarize_region_stats(table: &TableRef, stats: &[&RegionStat]) -> TableStatistics {
+    let num_rows = stats.iter().map(|stat| stat.num_rows).sum::<u64>();
+    let total_byte_size = stats
+        .iter()
+        .map(|stat| stat.sst_size + stat.memtable_size)
+        .sum::<u64>();
+    let time_range = stats
+        .iter()
+        .filter_map(|stat| stat.time_range)
+        .reduce(|(min, max), (start, end)| (min.min(start), max.max(end)));
+
+    let schema = table.schema();
+    let time_index = schema.timestamp_index();
+    let column_statistics = schema
+        .column_schemas()
+        .iter()
+        .enumerate()
+        .map(|(idx, column_schema)| {
+            if Some(idx) != time_index {
+                return ColumnStatistics::default();
+            }
+            let Some(unit) = column_schema.data_type.as_timestamp().map(|ty| ty.unit()) else {
+                return ColumnStatistics::default();
+            };
+            ColumnStatistics {
+                // The time index is not nullable.
+                null_count: Some(0),
+                min_value: time_range.map(|(min, _)| Value::Timestamp(Timestamp::new(min, unit))),
+                max_value: time_range.map(|(_, max)| Value::Timestamp(Timestamp::new(max, unit))),
+                distinct_count: None,
+            }
+        })
+        .collect();
+
+    TableStatistics {
+        num_rows: Some(num_rows as usize),
+        total_byte_size: Some(total_byte_size as usize),
+        column_statistics: Some(column_statistics),
+        is_exact: false,
+    }
+}
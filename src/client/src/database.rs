@@ -33,7 +33,7 @@ use common_telemetry::tracing_context::W3cTrace;
 use futures_util::StreamExt;
 use prost::Message;
 use snafu::{ensure, ResultExt};
-use tonic::metadata::AsciiMetadataKey;
+use tonic::metadata::{AsciiMetadataKey, MetadataMap};
 use tonic::transport::Channel;
 
 use crate::error::{
@@ -197,6 +197,21 @@ impl Database {
         .await
     }
 
+    /// Executes the sql with the given gRPC metadata, such as `x-greptime-timezone`,
+    /// `x-greptime-timeout` or `traceparent`.
+    pub async fn sql_with_metadata<S>(&self, sql: S, metadata: &[(&str, &str)]) -> Result<Output>
+    where
+        S: AsRef<str>,
+    {
+        self.do_get_with_metadata(
+            Request::Query(QueryRequest {
+                query: Some(Query::Sql(sql.as_ref().to_string())),
+            }),
+            metadata,
+        )
+        .await
+    }
+
     pub async fn logical_plan(&self, logical_plan: Vec<u8>) -> Result<Output> {
         self.do_get(Request::Query(QueryRequest {
             query: Some(Query::LogicalPlan(logical_plan)),
@@ -219,10 +234,19 @@ impl Database {
     }
 
     async fn do_get(&self, request: Request) -> Result<Output> {
+        self.do_get_with_metadata(request, &[]).await
+    }
+
+    async fn do_get_with_metadata(
+        &self,
+        request: Request,
+        metadata: &[(&str, &str)],
+    ) -> Result<Output> {
         let request = self.to_rpc_request(request);
-        let request = Ticket {
+        let mut request = tonic::Request::new(Ticket {
             ticket: request.encode_to_vec().into(),
-        };
+        });
+        insert_metadata(request.metadata_mut(), metadata)?;
 
         let mut client = self.client.make_flight_client()?;
 
@@ -312,6 +336,25 @@ impl Database {
     }
 }
 
+fn insert_metadata(metadata: &mut MetadataMap, entries: &[(&str, &str)]) -> Result<()> {
+    for (key, value) in entries {
+        let key = AsciiMetadataKey::from_bytes(key.as_bytes()).map_err(|_| {
+            InvalidAsciiSnafu {
+                value: key.to_string(),
+            }
+            .build()
+        })?;
+        let value = value.parse().map_err(|_| {
+            InvalidAsciiSnafu {
+                value: value.to_string(),
+            }
+            .build()
+        })?;
+        metadata.insert(key, value);
+    }
+    Ok(())
+}
+
 #[derive(Default, Debug, Clone)]
 struct FlightContext {
     auth_header: Option<AuthHeader>,
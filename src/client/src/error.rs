@@ -39,6 +39,37 @@ pub enum Error {
         source: BoxedError,
     },
 
+    #[snafu(display("Failed to do Flight action, code: {}", tonic_code))]
+    FlightAction {
+        addr: String,
+        tonic_code: Code,
+        source: BoxedError,
+    },
+
+    #[snafu(display("Datanode {} doesn't support region action {}", addr, action))]
+    UnsupportedRegionAction {
+        addr: String,
+        action: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Failed to encode region action"))]
+    EncodeRegionAction {
+        #[snafu(source)]
+        error: serde_json::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Failed to decode the result of region action"))]
+    DecodeRegionActionResult {
+        #[snafu(source)]
+        error: serde_json::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Failed to convert FlightData"))]
     ConvertFlightData {
         #[snafu(implicit)]
@@ -124,16 +155,20 @@ impl ErrorExt for Error {
         match self {
             Error::IllegalFlightMessages { .. }
             | Error::MissingField { .. }
-            | Error::IllegalDatabaseResponse { .. } => StatusCode::Internal,
+            | Error::IllegalDatabaseResponse { .. }
+            | Error::EncodeRegionAction { .. }
+            | Error::DecodeRegionActionResult { .. } => StatusCode::Internal,
 
             Error::Server { code, .. } => *code,
             Error::FlightGet { source, .. }
+            | Error::FlightAction { source, .. }
             | Error::RegionServer { source, .. }
             | Error::FlowServer { source, .. } => source.status_code(),
             Error::CreateChannel { source, .. }
             | Error::ConvertFlightData { source, .. }
             | Error::CreateTlsChannel { source, .. } => source.status_code(),
             Error::IllegalGrpcClientState { .. } => StatusCode::Unexpected,
+            Error::UnsupportedRegionAction { .. } => StatusCode::Unsupported,
 
             Error::InvalidAscii { .. } => StatusCode::InvalidArguments,
         }
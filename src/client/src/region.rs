@@ -12,34 +12,44 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use api::region::RegionResponse;
 use api::v1::region::RegionRequest;
 use api::v1::ResponseHeader;
 use arc_swap::ArcSwapOption;
-use arrow_flight::Ticket;
+use arrow_flight::{Action, Ticket};
 use async_stream::stream;
 use async_trait::async_trait;
 use common_error::ext::{BoxedError, ErrorExt};
 use common_error::status_code::StatusCode;
 use common_grpc::flight::{FlightDecoder, FlightMessage};
 use common_meta::error::{self as meta_error, Result as MetaResult};
-use common_meta::node_manager::Datanode;
+use common_meta::node_manager::{AffectedRows, Datanode, RegionAction, REGION_ACTION_TYPE};
 use common_query::request::QueryRequest;
 use common_recordbatch::error::ExternalSnafu;
 use common_recordbatch::{RecordBatchStreamWrapper, SendableRecordBatchStream};
 use common_telemetry::error;
 use common_telemetry::tracing_context::TracingContext;
+use common_time::range::TimestampRange;
 use prost::Message;
 use query::query_engine::DefaultSerializer;
+use serde::de::DeserializeOwned;
 use snafu::{location, OptionExt, ResultExt};
+use store_api::manifest::ManifestVersion;
+use store_api::region_engine::{
+    LabelValuesRequest, RegionManifestSnapshot, SeriesCardinality, SeriesCardinalityRequest,
+};
+use store_api::storage::RegionId;
 use substrait::{DFLogicalSubstraitConvertor, SubstraitPlan};
 use tokio_stream::StreamExt;
+use tonic::{Code, Status};
 
 use crate::error::{
-    self, ConvertFlightDataSnafu, FlightGetSnafu, IllegalDatabaseResponseSnafu,
-    IllegalFlightMessagesSnafu, MissingFieldSnafu, Result, ServerSnafu,
+    self, ConvertFlightDataSnafu, DecodeRegionActionResultSnafu, EncodeRegionActionSnafu,
+    FlightActionSnafu, FlightGetSnafu, IllegalDatabaseResponseSnafu, IllegalFlightMessagesSnafu,
+    MissingFieldSnafu, Result, ServerSnafu, UnsupportedRegionActionSnafu,
 };
 use crate::{metrics, Client, Error};
 
@@ -85,6 +95,83 @@ impl Datanode for RegionRequester {
             .map_err(BoxedError::new)
             .context(meta_error::ExternalSnafu)
     }
+
+    async fn label_values(
+        &self,
+        region_id: RegionId,
+        request: LabelValuesRequest,
+    ) -> MetaResult<Option<Vec<String>>> {
+        self.do_action_inner(RegionAction::LabelValues { region_id, request })
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn series_cardinality(
+        &self,
+        region_id: RegionId,
+        request: SeriesCardinalityRequest,
+    ) -> MetaResult<Option<SeriesCardinality>> {
+        self.do_action_inner(RegionAction::SeriesCardinality { region_id, request })
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn region_manifest(
+        &self,
+        region_id: RegionId,
+    ) -> MetaResult<Option<RegionManifestSnapshot>> {
+        self.do_action_inner(RegionAction::RegionManifest { region_id })
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn checkpoint_region(&self, region_id: RegionId) -> MetaResult<Option<ManifestVersion>> {
+        self.do_action_inner(RegionAction::CheckpointRegion { region_id })
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn producer_watermarks(
+        &self,
+        region_id: RegionId,
+    ) -> MetaResult<Option<HashMap<String, u64>>> {
+        self.do_action_inner(RegionAction::ProducerWatermarks { region_id })
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn region_sequence(&self, region_id: RegionId) -> MetaResult<Option<u64>> {
+        self.do_action_inner(RegionAction::RegionSequence { region_id })
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn build_index(&self, region_id: RegionId) -> MetaResult<AffectedRows> {
+        self.do_action_inner(RegionAction::BuildIndex { region_id })
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn truncate_range(
+        &self,
+        region_id: RegionId,
+        time_range: TimestampRange,
+    ) -> MetaResult<AffectedRows> {
+        self.do_action_inner(RegionAction::TruncateRange {
+            region_id,
+            time_range,
+        })
+        .await
+        .map_err(BoxedError::new)
+        .context(meta_error::ExternalSnafu)
+    }
 }
 
 impl RegionRequester {
@@ -183,6 +270,62 @@ impl RegionRequester {
         Ok(Box::pin(record_batch_stream))
     }
 
+    /// Sends the [RegionAction] to the datanode and decodes its result. Returns an
+    /// [UnsupportedRegionAction](Error::UnsupportedRegionAction) error if the datanode
+    /// doesn't serve the action, e.g. datanodes of earlier versions.
+    async fn do_action_inner<T: DeserializeOwned>(&self, action: RegionAction) -> Result<T> {
+        let action_name = action.as_ref().to_string();
+        let action = Action {
+            r#type: REGION_ACTION_TYPE.to_string(),
+            body: serde_json::to_vec(&action)
+                .context(EncodeRegionActionSnafu)?
+                .into(),
+        };
+
+        let mut flight_client = self.client.make_flight_client()?;
+        let addr = flight_client.addr().to_string();
+        let to_error = |e: Status| {
+            let tonic_code = e.code();
+            let e: error::Error = e.into();
+            let code = e.status_code();
+            let msg = e.to_string();
+            error!(
+                e; "Failed to do Flight action, addr: {}, code: {}",
+                addr,
+                tonic_code
+            );
+            ServerSnafu { code, msg }
+                .fail::<()>()
+                .map_err(BoxedError::new)
+                .with_context(|_| FlightActionSnafu {
+                    tonic_code,
+                    addr: addr.clone(),
+                })
+                .unwrap_err()
+        };
+
+        let response = match flight_client.mut_inner().do_action(action).await {
+            Ok(response) => response,
+            Err(e) if e.code() == Code::Unimplemented => {
+                return UnsupportedRegionActionSnafu {
+                    addr: addr.clone(),
+                    action: action_name,
+                }
+                .fail();
+            }
+            Err(e) => return Err(to_error(e)),
+        };
+        let result = response
+            .into_inner()
+            .message()
+            .await
+            .map_err(to_error)?
+            .context(IllegalFlightMessagesSnafu {
+                reason: "Expect the response not to be empty",
+            })?;
+        serde_json::from_slice(&result.body).context(DecodeRegionActionResultSnafu)
+    }
+
     async fn handle_inner(&self, request: RegionRequest) -> Result<RegionResponse> {
         let request_type = request
             .body
@@ -323,6 +323,7 @@ impl StartCommand {
 
         let services = DatanodeServiceBuilder::new(&opts)
             .with_default_grpc_server(&datanode.region_server())
+            .with_drain_state(datanode.region_server().drain_state())
             .enable_http_service()
             .build()
             .await
@@ -329,8 +329,10 @@ impl StartCommand {
             .build(),
         );
 
-        let information_extension =
-            Arc::new(DistributedInformationExtension::new(meta_client.clone()));
+        let information_extension = Arc::new(
+            DistributedInformationExtension::new(meta_client.clone())
+                .with_schema_limits(opts.schema_limits.clone()),
+        );
         let catalog_manager = KvBackendCatalogManager::new(
             information_extension,
             cached_meta_backend.clone(),
@@ -14,12 +14,14 @@
 
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use std::{fs, path};
 
 use async_trait::async_trait;
 use cache::{build_fundamental_cache_registry, with_default_composite_cache_registry};
 use catalog::information_schema::InformationExtension;
 use catalog::kvbackend::KvBackendCatalogManager;
+use catalog::schema_limits::SchemaLimitOptions;
 use clap::Parser;
 use client::api::v1::meta::RegionRole;
 use common_base::readable_size::ReadableSize;
@@ -69,6 +71,8 @@ use frontend::service_config::{
 };
 use meta_srv::metasrv::{FLOW_ID_SEQ, TABLE_ID_SEQ};
 use mito2::config::MitoConfig;
+use partition::splitter::PartitionRowSplitter;
+use query::promql::RateFirstSamplePolicy;
 use query::stats::StatementStatistics;
 use serde::{Deserialize, Serialize};
 use servers::export_metrics::{ExportMetricsOption, ExportMetricsTask};
@@ -132,6 +136,20 @@ impl SubCommand {
 pub struct StandaloneOptions {
     pub enable_telemetry: bool,
     pub default_timezone: Option<String>,
+    pub promql_timezone: Option<String>,
+    pub promql_enable_latest_at: bool,
+    pub promql_enable_negative_offset: bool,
+    /// How far after the current time PromQL selectors with `@` can read.
+    #[serde(with = "humantime_serde")]
+    pub promql_max_at_lookahead: Option<Duration>,
+    pub promql_integer_counts: bool,
+    pub promql_created_timestamps: bool,
+    pub promql_rate_first_sample: RateFirstSamplePolicy,
+    pub promql_fill_forward: bool,
+    pub promql_propagate_nan: bool,
+    pub promql_resolve_bucket_suffix: bool,
+    #[serde(with = "humantime_serde")]
+    pub promql_staleness_delta: Option<Duration>,
     pub http: HttpOptions,
     pub grpc: GrpcOptions,
     pub mysql: MysqlOptions,
@@ -140,6 +158,7 @@ pub struct StandaloneOptions {
     pub influxdb: InfluxdbOptions,
     pub jaeger: JaegerOptions,
     pub prom_store: PromStoreOptions,
+    pub schema_limits: SchemaLimitOptions,
     pub wal: DatanodeWalConfig,
     pub storage: StorageConfig,
     pub metadata_store: KvBackendConfig,
@@ -154,6 +173,10 @@ pub struct StandaloneOptions {
     pub init_regions_in_background: bool,
     pub init_regions_parallelism: usize,
     pub max_in_flight_write_bytes: Option<ReadableSize>,
+    pub max_result_bytes: Option<ReadableSize>,
+    /// The maximum time to wait for the in-flight requests on shutdown.
+    #[serde(with = "humantime_serde")]
+    pub drain_timeout: Duration,
 }
 
 impl Default for StandaloneOptions {
@@ -161,6 +184,17 @@ impl Default for StandaloneOptions {
         Self {
             enable_telemetry: true,
             default_timezone: None,
+            promql_timezone: None,
+            promql_enable_latest_at: false,
+            promql_enable_negative_offset: false,
+            promql_max_at_lookahead: None,
+            promql_integer_counts: false,
+            promql_created_timestamps: false,
+            promql_rate_first_sample: RateFirstSamplePolicy::default(),
+            promql_fill_forward: false,
+            promql_propagate_nan: false,
+            promql_resolve_bucket_suffix: false,
+            promql_staleness_delta: None,
             http: HttpOptions::default(),
             grpc: GrpcOptions::default(),
             mysql: MysqlOptions::default(),
@@ -169,6 +203,7 @@ impl Default for StandaloneOptions {
             influxdb: InfluxdbOptions::default(),
             jaeger: JaegerOptions::default(),
             prom_store: PromStoreOptions::default(),
+            schema_limits: SchemaLimitOptions::default(),
             wal: DatanodeWalConfig::default(),
             storage: StorageConfig::default(),
             metadata_store: KvBackendConfig::default(),
@@ -185,6 +220,8 @@ impl Default for StandaloneOptions {
             init_regions_in_background: false,
             init_regions_parallelism: 16,
             max_in_flight_write_bytes: None,
+            max_result_bytes: None,
+            drain_timeout: Duration::from_secs(30),
         }
     }
 }
@@ -210,6 +247,17 @@ impl StandaloneOptions {
         let cloned_opts = self.clone();
         FrontendOptions {
             default_timezone: cloned_opts.default_timezone,
+            promql_timezone: cloned_opts.promql_timezone,
+            promql_enable_latest_at: cloned_opts.promql_enable_latest_at,
+            promql_enable_negative_offset: cloned_opts.promql_enable_negative_offset,
+            promql_max_at_lookahead: cloned_opts.promql_max_at_lookahead,
+            promql_integer_counts: cloned_opts.promql_integer_counts,
+            promql_created_timestamps: cloned_opts.promql_created_timestamps,
+            promql_rate_first_sample: cloned_opts.promql_rate_first_sample,
+            promql_fill_forward: cloned_opts.promql_fill_forward,
+            promql_propagate_nan: cloned_opts.promql_propagate_nan,
+            promql_resolve_bucket_suffix: cloned_opts.promql_resolve_bucket_suffix,
+            promql_staleness_delta: cloned_opts.promql_staleness_delta,
             http: cloned_opts.http,
             grpc: cloned_opts.grpc,
             mysql: cloned_opts.mysql,
@@ -218,12 +266,15 @@ impl StandaloneOptions {
             influxdb: cloned_opts.influxdb,
             jaeger: cloned_opts.jaeger,
             prom_store: cloned_opts.prom_store,
+            schema_limits: cloned_opts.schema_limits,
             meta_client: None,
             logging: cloned_opts.logging,
             user_provider: cloned_opts.user_provider,
             // Handle the export metrics task run by standalone to frontend for execution
             export_metrics: cloned_opts.export_metrics,
             max_in_flight_write_bytes: cloned_opts.max_in_flight_write_bytes,
+            max_result_bytes: cloned_opts.max_result_bytes,
+            drain_timeout: cloned_opts.drain_timeout,
             ..Default::default()
         }
     }
@@ -239,6 +290,7 @@ impl StandaloneOptions {
             grpc: cloned_opts.grpc,
             init_regions_in_background: cloned_opts.init_regions_in_background,
             init_regions_parallelism: cloned_opts.init_regions_parallelism,
+            drain_timeout: cloned_opts.drain_timeout,
             ..Default::default()
         }
     }
@@ -504,10 +556,13 @@ impl StartCommand {
             .await
             .context(error::StartDatanodeSnafu)?;
 
-        let information_extension = Arc::new(StandaloneInformationExtension::new(
-            datanode.region_server(),
-            procedure_manager.clone(),
-        ));
+        let information_extension = Arc::new(
+            StandaloneInformationExtension::new(
+                datanode.region_server(),
+                procedure_manager.clone(),
+            )
+            .with_schema_limits(fe_opts.schema_limits.clone()),
+        );
         let catalog_manager = KvBackendCatalogManager::new(
             information_extension.clone(),
             kv_backend.clone(),
@@ -660,6 +715,7 @@ impl StartCommand {
                     flow_metadata_manager,
                     flow_metadata_allocator,
                     region_failure_detector_controller: Arc::new(NoopRegionFailureDetectorControl),
+                    region_row_splitter: Arc::new(PartitionRowSplitter),
                 },
                 procedure_manager,
                 true,
@@ -689,6 +745,7 @@ pub struct StandaloneInformationExtension {
     procedure_manager: ProcedureManagerRef,
     start_time_ms: u64,
     flow_worker_manager: RwLock<Option<Arc<FlowWorkerManager>>>,
+    schema_limits: SchemaLimitOptions,
 }
 
 impl StandaloneInformationExtension {
@@ -698,6 +755,14 @@ impl StandaloneInformationExtension {
             procedure_manager,
             start_time_ms: common_time::util::current_time_millis() as u64,
             flow_worker_manager: RwLock::new(None),
+            schema_limits: SchemaLimitOptions::default(),
+        }
+    }
+
+    pub fn with_schema_limits(self, schema_limits: SchemaLimitOptions) -> Self {
+        Self {
+            schema_limits,
+            ..self
         }
     }
 
@@ -772,6 +837,8 @@ impl InformationExtension for StandaloneInformationExtension {
                     manifest_size: region_stat.manifest_size,
                     sst_size: region_stat.sst_size,
                     index_size: region_stat.index_size,
+                    sst_compression: region_stat.sst_compression,
+                    time_range: region_stat.time_range,
                     region_manifest: region_stat.manifest.into(),
                 }
             })
@@ -790,6 +857,10 @@ impl InformationExtension for StandaloneInformationExtension {
                 .await,
         ))
     }
+
+    fn schema_limits(&self) -> SchemaLimitOptions {
+        self.schema_limits.clone()
+    }
 }
 
 #[cfg(test)]
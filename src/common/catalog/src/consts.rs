@@ -104,6 +104,8 @@ pub const INFORMATION_SCHEMA_PROCEDURE_INFO_TABLE_ID: u32 = 34;
 pub const INFORMATION_SCHEMA_REGION_STATISTICS_TABLE_ID: u32 = 35;
 /// id for information_schema.STATISTICS
 pub const INFORMATION_SCHEMA_STATISTICS_TABLE_ID: u32 = 36;
+/// id for information_schema.schema_limits
+pub const INFORMATION_SCHEMA_SCHEMA_LIMITS_TABLE_ID: u32 = 37;
 // ----- End of information_schema tables -----
 
 /// ----- Begin of pg_catalog tables -----
@@ -33,7 +33,8 @@ use bytes::{Buf, Bytes};
 use datafusion::datasource::physical_plan::FileOpenFuture;
 use datafusion::error::{DataFusionError, Result as DataFusionResult};
 use datafusion::physical_plan::SendableRecordBatchStream;
-use futures::StreamExt;
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
 use object_store::ObjectStore;
 use snafu::ResultExt;
 use tokio_util::compat::FuturesAsyncWriteCompatExt;
@@ -142,7 +143,7 @@ pub fn open_with_decoder<T: ArrowDecoder, F: Fn() -> DataFusionResult<T>>(
     compression_type: CompressionType,
     decoder_factory: F,
 ) -> DataFusionResult<FileOpenFuture> {
-    let mut decoder = decoder_factory()?;
+    let decoder = decoder_factory()?;
     Ok(Box::pin(async move {
         let reader = object_store
             .reader(&path)
@@ -152,32 +153,43 @@ pub fn open_with_decoder<T: ArrowDecoder, F: Fn() -> DataFusionResult<T>>(
             .await
             .map_err(|e| DataFusionError::External(Box::new(e)))?;
 
-        let mut upstream = compression_type.convert_stream(reader).fuse();
+        let upstream = compression_type.convert_stream(reader);
 
-        let mut buffered = Bytes::new();
+        Ok(decode_stream(upstream, decoder))
+    }))
+}
 
-        let stream = futures::stream::poll_fn(move |cx| {
-            loop {
-                if buffered.is_empty() {
-                    if let Some(result) = futures::ready!(upstream.poll_next_unpin(cx)) {
-                        buffered = result?;
-                    };
-                }
+/// Decodes the stream of bytes into record batches with the `decoder`. An empty
+/// chunk is taken as the end of the stream.
+pub fn decode_stream<T: ArrowDecoder>(
+    upstream: impl Stream<Item = std::io::Result<Bytes>> + Send + Unpin + 'static,
+    mut decoder: T,
+) -> BoxStream<'static, result::Result<RecordBatch, ArrowError>> {
+    let mut upstream = upstream.fuse();
+
+    let mut buffered = Bytes::new();
+
+    let stream = futures::stream::poll_fn(move |cx| {
+        loop {
+            if buffered.is_empty() {
+                if let Some(result) = futures::ready!(upstream.poll_next_unpin(cx)) {
+                    buffered = result?;
+                };
+            }
 
-                let decoded = decoder.decode(buffered.as_ref())?;
+            let decoded = decoder.decode(buffered.as_ref())?;
 
-                if decoded == 0 {
-                    break;
-                } else {
-                    buffered.advance(decoded);
-                }
+            if decoded == 0 {
+                break;
+            } else {
+                buffered.advance(decoded);
             }
+        }
 
-            Poll::Ready(decoder.flush().transpose())
-        });
+        Poll::Ready(decoder.flush().transpose())
+    });
 
-        Ok(stream.boxed())
-    }))
+    stream.boxed()
 }
 
 pub async fn infer_schemas(
@@ -81,6 +81,9 @@ pub enum StatusCode {
     TableUnavailable = 4010,
     /// Database already exists.
     DatabaseAlreadyExists = 4011,
+    /// Table or the whole instance is read-only, e.g. frozen for maintenance.
+    /// Writes should be kept and retried after it's writable again.
+    TableReadonly = 4012,
     // ====== End of catalog related status code =======
 
     // ====== Begin of storage related status code =====
@@ -156,6 +159,7 @@ impl StatusCode {
             | StatusCode::FlowAlreadyExists
             | StatusCode::FlowNotFound
             | StatusCode::RegionReadonly
+            | StatusCode::TableReadonly
             | StatusCode::TableColumnNotFound
             | StatusCode::TableColumnExists
             | StatusCode::DatabaseNotFound
@@ -201,6 +205,7 @@ impl StatusCode {
             | StatusCode::RegionNotReady
             | StatusCode::RegionBusy
             | StatusCode::RegionReadonly
+            | StatusCode::TableReadonly
             | StatusCode::TableColumnNotFound
             | StatusCode::TableColumnExists
             | StatusCode::DatabaseNotFound
@@ -290,7 +295,8 @@ pub fn status_to_tonic_code(status_code: StatusCode) -> Code {
         | StatusCode::FlowNotFound => Code::NotFound,
         StatusCode::TableUnavailable
         | StatusCode::StorageUnavailable
-        | StatusCode::RegionNotReady => Code::Unavailable,
+        | StatusCode::RegionNotReady
+        | StatusCode::TableReadonly => Code::Unavailable,
         StatusCode::RuntimeResourcesExhausted
         | StatusCode::RateLimited
         | StatusCode::RegionBusy => Code::ResourceExhausted,
@@ -13,18 +13,30 @@
 // limitations under the License.
 
 mod add_region_follower;
+mod build_index;
+mod cancel_procedure;
 mod flush_compact_region;
 mod flush_compact_table;
 mod migrate_region;
+mod producer_watermarks;
+mod readonly;
+mod region_manifest;
 mod remove_region_follower;
+mod series_cardinality;
 
 use std::sync::Arc;
 
 use add_region_follower::AddRegionFollowerFunction;
+use build_index::BuildIndexFunction;
+use cancel_procedure::CancelProcedureFunction;
 use flush_compact_region::{CompactRegionFunction, FlushRegionFunction};
 use flush_compact_table::{CompactTableFunction, FlushTableFunction};
 use migrate_region::MigrateRegionFunction;
+use producer_watermarks::ProducerWatermarksFunction;
+use readonly::{IsReadonlyFunction, SetReadonlyFunction};
+use region_manifest::{CheckpointRegionFunction, RegionManifestFunction};
 use remove_region_follower::RemoveRegionFollowerFunction;
+use series_cardinality::{LabelCardinalityFunction, SeriesCountFunction, TopLabelValuesFunction};
 
 use crate::flush_flow::FlushFlowFunction;
 use crate::function_registry::FunctionRegistry;
@@ -42,6 +54,16 @@ impl AdminFunction {
         registry.register_async(Arc::new(CompactRegionFunction));
         registry.register_async(Arc::new(FlushTableFunction));
         registry.register_async(Arc::new(CompactTableFunction));
+        registry.register_async(Arc::new(RegionManifestFunction));
+        registry.register_async(Arc::new(CheckpointRegionFunction));
+        registry.register_async(Arc::new(BuildIndexFunction));
+        registry.register_async(Arc::new(ProducerWatermarksFunction));
+        registry.register_async(Arc::new(SeriesCountFunction));
+        registry.register_async(Arc::new(LabelCardinalityFunction));
+        registry.register_async(Arc::new(TopLabelValuesFunction));
+        registry.register_async(Arc::new(SetReadonlyFunction));
+        registry.register_async(Arc::new(IsReadonlyFunction));
         registry.register_async(Arc::new(FlushFlowFunction));
+        registry.register_async(Arc::new(CancelProcedureFunction));
     }
 }
@@ -0,0 +1,189 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::sync::Arc;
+
+use common_error::ext::BoxedError;
+use common_query::error::{
+    InvalidFuncArgsSnafu, MissingTableMutationHandlerSnafu, Result, TableMutationSnafu,
+    UnsupportedInputDataTypeSnafu,
+};
+use common_query::prelude::{Signature, Volatility};
+use datatypes::prelude::*;
+use datatypes::schema::ColumnSchema;
+use datatypes::vectors::{StringVector, UInt64Vector};
+use session::table_name::table_name_to_full_name;
+use snafu::{ensure, OptionExt, ResultExt};
+use table::table_name::TableName;
+
+use crate::function::{AsyncFunction, FunctionContext};
+
+const PRODUCER_WATERMARKS: &str = "producer_watermarks";
+
+/// Index of the `sequence` column in the output columns.
+const SEQUENCE_COLUMN_INDEX: usize = 2;
+
+/// A function to list the highest sequence number written by each producer to the
+/// table regions, one row per region and producer. Writes of a producer with a
+/// sequence number not higher than the watermark of the region are skipped.
+#[derive(Debug)]
+pub(crate) struct ProducerWatermarksFunction;
+
+impl fmt::Display for ProducerWatermarksFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PRODUCER_WATERMARKS")
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncFunction for ProducerWatermarksFunction {
+    fn name(&self) -> &str {
+        PRODUCER_WATERMARKS
+    }
+
+    fn return_type(&self, _input_types: &[ConcreteDataType]) -> Result<ConcreteDataType> {
+        Ok(ConcreteDataType::uint64_datatype())
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::uniform(
+            1,
+            vec![ConcreteDataType::string_datatype()],
+            Volatility::Immutable,
+        )
+    }
+
+    /// Returns the watermarks.
+    async fn eval(&self, func_ctx: FunctionContext, columns: &[VectorRef]) -> Result<VectorRef> {
+        let mut columns = self.eval_columns(func_ctx, columns).await?;
+        Ok(columns.swap_remove(SEQUENCE_COLUMN_INDEX))
+    }
+
+    fn output_columns(&self) -> Option<Vec<ColumnSchema>> {
+        Some(vec![
+            ColumnSchema::new("region_id", ConcreteDataType::uint64_datatype(), false),
+            ColumnSchema::new("producer_id", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("sequence", ConcreteDataType::uint64_datatype(), false),
+        ])
+    }
+
+    async fn eval_columns(
+        &self,
+        func_ctx: FunctionContext,
+        columns: &[VectorRef],
+    ) -> Result<Vec<VectorRef>> {
+        // Ensure under the `greptime` catalog for security
+        crate::ensure_greptime!(func_ctx);
+
+        ensure!(
+            columns.len() == 1,
+            InvalidFuncArgsSnafu {
+                err_msg: format!(
+                    "The length of the args is not correct, expect 1, have: {}",
+                    columns.len()
+                ),
+            }
+        );
+        let ValueRef::String(table_name) = columns[0].get_ref(0) else {
+            return UnsupportedInputDataTypeSnafu {
+                function: PRODUCER_WATERMARKS,
+                datatypes: columns.iter().map(|v| v.data_type()).collect::<Vec<_>>(),
+            }
+            .fail();
+        };
+
+        let query_ctx = &func_ctx.query_ctx;
+        let handler = func_ctx
+            .state
+            .table_mutation_handler
+            .as_ref()
+            .context(MissingTableMutationHandlerSnafu)?;
+        let (catalog_name, schema_name, table_name) =
+            table_name_to_full_name(table_name, query_ctx)
+                .map_err(BoxedError::new)
+                .context(TableMutationSnafu)?;
+        let mut watermarks = handler
+            .table_producer_watermarks(
+                TableName::new(catalog_name, schema_name, table_name),
+                query_ctx.clone(),
+            )
+            .await?
+            .into_iter()
+            .flat_map(|(region_id, watermarks)| {
+                watermarks
+                    .into_iter()
+                    .map(move |(producer_id, sequence)| (region_id, producer_id, sequence))
+            })
+            .collect::<Vec<_>>();
+        watermarks.sort_unstable();
+
+        let mut region_ids = Vec::with_capacity(watermarks.len());
+        let mut producer_ids = Vec::with_capacity(watermarks.len());
+        let mut sequences = Vec::with_capacity(watermarks.len());
+        for (region_id, producer_id, sequence) in watermarks {
+            region_ids.push(region_id.as_u64());
+            producer_ids.push(producer_id);
+            sequences.push(sequence);
+        }
+
+        Ok(vec![
+            Arc::new(UInt64Vector::from_vec(region_ids)),
+            Arc::new(StringVector::from(producer_ids)),
+            Arc::new(UInt64Vector::from_vec(sequences)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use store_api::storage::RegionId;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_producer_watermarks() {
+        let f = ProducerWatermarksFunction;
+        assert_eq!("producer_watermarks", f.name());
+        assert_eq!(3, f.output_columns().unwrap().len());
+        let args = vec![Arc::new(StringVector::from(vec!["test"])) as _];
+
+        let result = f
+            .eval_columns(FunctionContext::default(), &args)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            "Missing TableMutationHandler, not expected",
+            result.to_string()
+        );
+
+        let columns = f
+            .eval_columns(FunctionContext::mock(), &args)
+            .await
+            .unwrap();
+        let expect: Vec<VectorRef> = vec![
+            Arc::new(UInt64Vector::from_slice([
+                RegionId::new(1024, 0).as_u64(),
+                RegionId::new(1024, 0).as_u64(),
+                RegionId::new(1024, 1).as_u64(),
+            ])),
+            Arc::new(StringVector::from(vec!["p1", "p2", "p1"])),
+            Arc::new(UInt64Vector::from_slice([5, 3, 7])),
+        ];
+        assert_eq!(expect, columns);
+
+        let sequences = f.eval(FunctionContext::mock(), &args).await.unwrap();
+        assert_eq!(expect[SEQUENCE_COLUMN_INDEX], sequences);
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_macro::admin_fn;
+use common_query::error::{
+    InvalidFuncArgsSnafu, MissingTableMutationHandlerSnafu, Result, UnsupportedInputDataTypeSnafu,
+};
+use common_query::prelude::{Signature, Volatility};
+use datatypes::prelude::*;
+use session::context::QueryContextRef;
+use snafu::ensure;
+
+use crate::handlers::TableMutationHandlerRef;
+
+/// A function to set whether the instance is read-only, returns the previous value.
+/// Such as `set_readonly(true)`.
+///
+/// A read-only instance rejects inserts, deletes and DDLs, while queries still work.
+#[admin_fn(
+    name = SetReadonlyFunction,
+    display_name = set_readonly,
+    sig_fn = set_readonly_signature,
+    ret = boolean
+)]
+pub(crate) async fn set_readonly(
+    table_mutation_handler: &TableMutationHandlerRef,
+    _query_ctx: &QueryContextRef,
+    params: &[ValueRef<'_>],
+) -> Result<Value> {
+    ensure!(
+        params.len() == 1,
+        InvalidFuncArgsSnafu {
+            err_msg: format!(
+                "The length of the args is not correct, expect 1, have: {}",
+                params.len()
+            ),
+        }
+    );
+    let ValueRef::Boolean(readonly) = params[0] else {
+        return UnsupportedInputDataTypeSnafu {
+            function: "set_readonly",
+            datatypes: params.iter().map(|v| v.data_type()).collect::<Vec<_>>(),
+        }
+        .fail();
+    };
+
+    Ok(Value::from(table_mutation_handler.set_readonly(readonly)))
+}
+
+/// A function to return whether the instance is read-only.
+#[admin_fn(
+    name = IsReadonlyFunction,
+    display_name = is_readonly,
+    sig_fn = is_readonly_signature,
+    ret = boolean
+)]
+pub(crate) async fn is_readonly(
+    table_mutation_handler: &TableMutationHandlerRef,
+    _query_ctx: &QueryContextRef,
+    _params: &[ValueRef<'_>],
+) -> Result<Value> {
+    Ok(Value::from(table_mutation_handler.is_readonly()))
+}
+
+fn set_readonly_signature() -> Signature {
+    Signature::exact(
+        vec![ConcreteDataType::boolean_datatype()],
+        Volatility::Immutable,
+    )
+}
+
+fn is_readonly_signature() -> Signature {
+    Signature::nullary(Volatility::Immutable)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datatypes::vectors::{BooleanVector, VectorRef};
+
+    use super::*;
+    use crate::function::{AsyncFunction, FunctionContext};
+
+    #[tokio::test]
+    async fn test_set_readonly() {
+        let f = SetReadonlyFunction;
+        assert_eq!("set_readonly", f.name());
+        assert_eq!(
+            ConcreteDataType::boolean_datatype(),
+            f.return_type(&[]).unwrap()
+        );
+
+        let args = vec![Arc::new(BooleanVector::from(vec![true])) as _];
+        let result = f.eval(FunctionContext::default(), &args).await.unwrap_err();
+        assert_eq!(
+            "Missing TableMutationHandler, not expected",
+            result.to_string()
+        );
+
+        let result = f.eval(FunctionContext::mock(), &args).await.unwrap();
+        let expect: VectorRef = Arc::new(BooleanVector::from(vec![false]));
+        assert_eq!(expect, result);
+    }
+
+    #[tokio::test]
+    async fn test_is_readonly() {
+        let f = IsReadonlyFunction;
+        assert_eq!("is_readonly", f.name());
+
+        let result = f.eval(FunctionContext::mock(), &[]).await.unwrap();
+        let expect: VectorRef = Arc::new(BooleanVector::from(vec![false]));
+        assert_eq!(expect, result);
+    }
+}
@@ -0,0 +1,305 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::sync::Arc;
+
+use common_error::ext::BoxedError;
+use common_macro::admin_fn;
+use common_query::error::{
+    InvalidFuncArgsSnafu, MissingTableMutationHandlerSnafu, Result, TableMutationSnafu,
+    UnsupportedInputDataTypeSnafu,
+};
+use common_query::prelude::{Signature, Volatility};
+use common_time::timestamp::TimeUnit;
+use common_time::Timestamp;
+use datatypes::prelude::*;
+use datatypes::schema::ColumnSchema;
+use datatypes::vectors::{StringVector, TimestampNanosecondVector, UInt64Vector};
+use session::context::QueryContextRef;
+use session::table_name::table_name_to_full_name;
+use snafu::{ensure, OptionExt, ResultExt};
+use store_api::storage::RegionId;
+use table::table_name::TableName;
+
+use crate::function::{AsyncFunction, FunctionContext};
+use crate::handlers::TableMutationHandlerRef;
+use crate::helper::cast_u64;
+
+const REGION_MANIFEST: &str = "region_manifest";
+
+/// Index of the `file_path` column in the output columns.
+const FILE_PATH_COLUMN_INDEX: usize = 3;
+
+/// A function to list the SST files in the manifests of the table regions, one row
+/// per file. It only reads the manifests, so the listing is cheap and consistent with
+/// the manifest version of each region.
+#[derive(Debug)]
+pub(crate) struct RegionManifestFunction;
+
+impl fmt::Display for RegionManifestFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "REGION_MANIFEST")
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncFunction for RegionManifestFunction {
+    fn name(&self) -> &str {
+        REGION_MANIFEST
+    }
+
+    fn return_type(&self, _input_types: &[ConcreteDataType]) -> Result<ConcreteDataType> {
+        Ok(ConcreteDataType::string_datatype())
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::uniform(
+            1,
+            vec![ConcreteDataType::string_datatype()],
+            Volatility::Immutable,
+        )
+    }
+
+    /// Returns the paths of the files.
+    async fn eval(&self, func_ctx: FunctionContext, columns: &[VectorRef]) -> Result<VectorRef> {
+        let mut columns = self.eval_columns(func_ctx, columns).await?;
+        Ok(columns.swap_remove(FILE_PATH_COLUMN_INDEX))
+    }
+
+    fn output_columns(&self) -> Option<Vec<ColumnSchema>> {
+        Some(vec![
+            ColumnSchema::new("region_id", ConcreteDataType::uint64_datatype(), false),
+            ColumnSchema::new(
+                "manifest_version",
+                ConcreteDataType::uint64_datatype(),
+                false,
+            ),
+            ColumnSchema::new("file_id", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("file_path", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(
+                "time_range_min",
+                ConcreteDataType::timestamp_nanosecond_datatype(),
+                true,
+            ),
+            ColumnSchema::new(
+                "time_range_max",
+                ConcreteDataType::timestamp_nanosecond_datatype(),
+                true,
+            ),
+            ColumnSchema::new("rows", ConcreteDataType::uint64_datatype(), false),
+            ColumnSchema::new("size", ConcreteDataType::uint64_datatype(), false),
+        ])
+    }
+
+    async fn eval_columns(
+        &self,
+        func_ctx: FunctionContext,
+        columns: &[VectorRef],
+    ) -> Result<Vec<VectorRef>> {
+        // Ensure under the `greptime` catalog for security
+        crate::ensure_greptime!(func_ctx);
+
+        ensure!(
+            columns.len() == 1,
+            InvalidFuncArgsSnafu {
+                err_msg: format!(
+                    "The length of the args is not correct, expect 1, have: {}",
+                    columns.len()
+                ),
+            }
+        );
+        let ValueRef::String(table_name) = columns[0].get_ref(0) else {
+            return UnsupportedInputDataTypeSnafu {
+                function: REGION_MANIFEST,
+                datatypes: columns.iter().map(|v| v.data_type()).collect::<Vec<_>>(),
+            }
+            .fail();
+        };
+
+        let query_ctx = &func_ctx.query_ctx;
+        let handler = func_ctx
+            .state
+            .table_mutation_handler
+            .as_ref()
+            .context(MissingTableMutationHandlerSnafu)?;
+        let (catalog_name, schema_name, table_name) =
+            table_name_to_full_name(table_name, query_ctx)
+                .map_err(BoxedError::new)
+                .context(TableMutationSnafu)?;
+        let mut regions = handler
+            .table_manifest(
+                TableName::new(catalog_name, schema_name, table_name),
+                query_ctx.clone(),
+            )
+            .await?;
+        regions.sort_unstable_by_key(|(region_id, _)| *region_id);
+
+        let mut region_ids = Vec::new();
+        let mut manifest_versions = Vec::new();
+        let mut file_ids = Vec::new();
+        let mut file_paths = Vec::new();
+        let mut time_range_mins = Vec::new();
+        let mut time_range_maxs = Vec::new();
+        let mut rows = Vec::new();
+        let mut sizes = Vec::new();
+        for (region_id, snapshot) in regions {
+            for file in snapshot.files {
+                region_ids.push(region_id.as_u64());
+                manifest_versions.push(snapshot.manifest_version);
+                file_ids.push(file.file_id);
+                file_paths.push(file.file_path);
+                time_range_mins.push(to_nanos(file.time_range.0));
+                time_range_maxs.push(to_nanos(file.time_range.1));
+                rows.push(file.num_rows);
+                sizes.push(file.file_size);
+            }
+        }
+
+        Ok(vec![
+            Arc::new(UInt64Vector::from_vec(region_ids)),
+            Arc::new(UInt64Vector::from_vec(manifest_versions)),
+            Arc::new(StringVector::from(file_ids)),
+            Arc::new(StringVector::from(file_paths)),
+            Arc::new(TimestampNanosecondVector::from(time_range_mins)),
+            Arc::new(TimestampNanosecondVector::from(time_range_maxs)),
+            Arc::new(UInt64Vector::from_vec(rows)),
+            Arc::new(UInt64Vector::from_vec(sizes)),
+        ])
+    }
+}
+
+/// Converts the timestamp to nanoseconds, or `None` if it overflows.
+fn to_nanos(ts: Timestamp) -> Option<i64> {
+    ts.convert_to(TimeUnit::Nanosecond).map(|ts| ts.value())
+}
+
+/// A function to save a checkpoint of the manifest of a region, returns the
+/// manifest version of the checkpoint.
+#[admin_fn(
+    name = CheckpointRegionFunction,
+    display_name = checkpoint_region,
+    sig_fn = checkpoint_region_signature,
+    ret = uint64
+)]
+pub(crate) async fn checkpoint_region(
+    table_mutation_handler: &TableMutationHandlerRef,
+    query_ctx: &QueryContextRef,
+    params: &[ValueRef<'_>],
+) -> Result<Value> {
+    ensure!(
+        params.len() == 1,
+        InvalidFuncArgsSnafu {
+            err_msg: format!(
+                "The length of the args is not correct, expect 1, have: {}",
+                params.len()
+            ),
+        }
+    );
+
+    let Some(region_id) = cast_u64(&params[0])? else {
+        return UnsupportedInputDataTypeSnafu {
+            function: "checkpoint_region",
+            datatypes: params.iter().map(|v| v.data_type()).collect::<Vec<_>>(),
+        }
+        .fail();
+    };
+
+    let version = table_mutation_handler
+        .checkpoint_region(RegionId::from_u64(region_id), query_ctx.clone())
+        .await?;
+
+    Ok(Value::from(version))
+}
+
+fn checkpoint_region_signature() -> Signature {
+    Signature::uniform(1, ConcreteDataType::numerics(), Volatility::Immutable)
+}
+
+#[cfg(test)]
+mod tests {
+    use common_query::prelude::TypeSignature;
+
+    use super::*;
+
+    #[test]
+    fn test_region_manifest_misc() {
+        let f = RegionManifestFunction;
+        assert_eq!("region_manifest", f.name());
+        assert_eq!(8, f.output_columns().unwrap().len());
+        assert!(matches!(f.signature(),
+                         Signature {
+                             type_signature: TypeSignature::Uniform(1, valid_types),
+                             volatility: Volatility::Immutable
+                         } if valid_types == vec![ConcreteDataType::string_datatype()]));
+    }
+
+    #[tokio::test]
+    async fn test_region_manifest() {
+        let f = RegionManifestFunction;
+        let args = vec![Arc::new(StringVector::from(vec!["test"])) as _];
+
+        let result = f
+            .eval_columns(FunctionContext::default(), &args)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            "Missing TableMutationHandler, not expected",
+            result.to_string()
+        );
+
+        let columns = f
+            .eval_columns(FunctionContext::mock(), &args)
+            .await
+            .unwrap();
+        let expect: Vec<VectorRef> = vec![
+            Arc::new(UInt64Vector::from_slice([
+                RegionId::new(1024, 0).as_u64(),
+                RegionId::new(1024, 0).as_u64(),
+            ])),
+            Arc::new(UInt64Vector::from_slice([42, 42])),
+            Arc::new(StringVector::from(vec!["file_0", "file_1"])),
+            Arc::new(StringVector::from(vec![
+                "data/file_0.parquet",
+                "data/file_1.parquet",
+            ])),
+            Arc::new(TimestampNanosecondVector::from_slice([0, 10_000_000_000])),
+            Arc::new(TimestampNanosecondVector::from_slice([
+                4_000_000_000,
+                14_000_000_000,
+            ])),
+            Arc::new(UInt64Vector::from_slice([5, 5])),
+            Arc::new(UInt64Vector::from_slice([1024, 1024])),
+        ];
+        assert_eq!(expect, columns);
+
+        let paths = f.eval(FunctionContext::mock(), &args).await.unwrap();
+        assert_eq!(expect[FILE_PATH_COLUMN_INDEX], paths);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_region() {
+        let f = CheckpointRegionFunction;
+        assert_eq!("checkpoint_region", f.name());
+        assert_eq!(
+            ConcreteDataType::uint64_datatype(),
+            f.return_type(&[]).unwrap()
+        );
+
+        let args = vec![Arc::new(UInt64Vector::from_slice([99])) as _];
+        let result = f.eval(FunctionContext::mock(), &args).await.unwrap();
+        let expect: VectorRef = Arc::new(UInt64Vector::from_slice([42]));
+        assert_eq!(expect, result);
+    }
+}
@@ -0,0 +1,331 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+use common_error::ext::BoxedError;
+use common_macro::admin_fn;
+use common_query::error::{
+    InvalidFuncArgsSnafu, MissingTableMutationHandlerSnafu, Result, TableMutationSnafu,
+    UnsupportedInputDataTypeSnafu,
+};
+use common_query::prelude::{Signature, Volatility};
+use datatypes::prelude::*;
+use datatypes::schema::ColumnSchema;
+use datatypes::vectors::{StringVector, UInt64Vector};
+use session::context::QueryContextRef;
+use session::table_name::table_name_to_full_name;
+use snafu::{ensure, OptionExt, ResultExt};
+use table::table_name::TableName;
+
+use crate::function::{AsyncFunction, FunctionContext};
+use crate::handlers::TableMutationHandlerRef;
+use crate::helper::cast_u64;
+
+const TOP_LABEL_VALUES: &str = "top_label_values";
+
+/// Index of the `value` column in the output columns.
+const VALUE_COLUMN_INDEX: usize = 0;
+
+/// A function to count the series of a table, that is the number of distinct
+/// primary keys in all regions of the table.
+#[admin_fn(
+    name = SeriesCountFunction,
+    display_name = series_count,
+    sig_fn = series_count_signature,
+    ret = uint64
+)]
+pub(crate) async fn series_count(
+    table_mutation_handler: &TableMutationHandlerRef,
+    query_ctx: &QueryContextRef,
+    params: &[ValueRef<'_>],
+) -> Result<Value> {
+    ensure!(
+        params.len() == 1,
+        InvalidFuncArgsSnafu {
+            err_msg: format!(
+                "The length of the args is not correct, expect 1, have: {}",
+                params.len()
+            ),
+        }
+    );
+    let ValueRef::String(table_name) = params[0] else {
+        return UnsupportedInputDataTypeSnafu {
+            function: "series_count",
+            datatypes: params.iter().map(|v| v.data_type()).collect::<Vec<_>>(),
+        }
+        .fail();
+    };
+
+    let table_name = resolve_table_name(table_name, query_ctx)?;
+    // Rows of a series are in the same region, so the series of regions don't overlap.
+    let num_series = table_mutation_handler
+        .table_series_cardinality(table_name, None, query_ctx.clone())
+        .await?
+        .into_iter()
+        .map(|(_, cardinality)| cardinality.num_series)
+        .sum::<u64>();
+
+    Ok(Value::from(num_series))
+}
+
+/// A function to count the distinct values of a label (tag column) of a table. The
+/// values are read from the term dictionaries of the inverted index if possible.
+#[admin_fn(
+    name = LabelCardinalityFunction,
+    display_name = label_cardinality,
+    sig_fn = label_cardinality_signature,
+    ret = uint64
+)]
+pub(crate) async fn label_cardinality(
+    table_mutation_handler: &TableMutationHandlerRef,
+    query_ctx: &QueryContextRef,
+    params: &[ValueRef<'_>],
+) -> Result<Value> {
+    ensure!(
+        params.len() == 2,
+        InvalidFuncArgsSnafu {
+            err_msg: format!(
+                "The length of the args is not correct, expect 2, have: {}",
+                params.len()
+            ),
+        }
+    );
+    let (ValueRef::String(table_name), ValueRef::String(label)) = (params[0], params[1]) else {
+        return UnsupportedInputDataTypeSnafu {
+            function: "label_cardinality",
+            datatypes: params.iter().map(|v| v.data_type()).collect::<Vec<_>>(),
+        }
+        .fail();
+    };
+
+    let table_name = resolve_table_name(table_name, query_ctx)?;
+    // A value may be in several regions if the table isn't partitioned by the label.
+    let values = table_mutation_handler
+        .table_label_values(table_name, label.to_string(), query_ctx.clone())
+        .await?
+        .into_iter()
+        .flat_map(|(_, values)| values)
+        .collect::<BTreeSet<_>>();
+
+    Ok(Value::from(values.len() as u64))
+}
+
+fn series_count_signature() -> Signature {
+    Signature::uniform(
+        1,
+        vec![ConcreteDataType::string_datatype()],
+        Volatility::Immutable,
+    )
+}
+
+fn label_cardinality_signature() -> Signature {
+    Signature::uniform(
+        2,
+        vec![ConcreteDataType::string_datatype()],
+        Volatility::Immutable,
+    )
+}
+
+/// A function to list the `k` values of a label (tag column) of a table with the
+/// most series, one row per value in descending order of the number of series.
+#[derive(Debug)]
+pub(crate) struct TopLabelValuesFunction;
+
+impl fmt::Display for TopLabelValuesFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TOP_LABEL_VALUES")
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncFunction for TopLabelValuesFunction {
+    fn name(&self) -> &str {
+        TOP_LABEL_VALUES
+    }
+
+    fn return_type(&self, _input_types: &[ConcreteDataType]) -> Result<ConcreteDataType> {
+        Ok(ConcreteDataType::string_datatype())
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::any(3, Volatility::Immutable)
+    }
+
+    /// Returns the values.
+    async fn eval(&self, func_ctx: FunctionContext, columns: &[VectorRef]) -> Result<VectorRef> {
+        let mut columns = self.eval_columns(func_ctx, columns).await?;
+        Ok(columns.swap_remove(VALUE_COLUMN_INDEX))
+    }
+
+    fn output_columns(&self) -> Option<Vec<ColumnSchema>> {
+        Some(vec![
+            ColumnSchema::new("value", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("series", ConcreteDataType::uint64_datatype(), false),
+        ])
+    }
+
+    async fn eval_columns(
+        &self,
+        func_ctx: FunctionContext,
+        columns: &[VectorRef],
+    ) -> Result<Vec<VectorRef>> {
+        // Ensure under the `greptime` catalog for security
+        crate::ensure_greptime!(func_ctx);
+
+        ensure!(
+            columns.len() == 3,
+            InvalidFuncArgsSnafu {
+                err_msg: format!(
+                    "The length of the args is not correct, expect 3, have: {}",
+                    columns.len()
+                ),
+            }
+        );
+        let (ValueRef::String(table_name), ValueRef::String(label), Some(k)) = (
+            columns[0].get_ref(0),
+            columns[1].get_ref(0),
+            cast_u64(&columns[2].get_ref(0))?,
+        ) else {
+            return UnsupportedInputDataTypeSnafu {
+                function: TOP_LABEL_VALUES,
+                datatypes: columns.iter().map(|v| v.data_type()).collect::<Vec<_>>(),
+            }
+            .fail();
+        };
+
+        let query_ctx = &func_ctx.query_ctx;
+        let handler = func_ctx
+            .state
+            .table_mutation_handler
+            .as_ref()
+            .context(MissingTableMutationHandlerSnafu)?;
+        let table_name = resolve_table_name(table_name, query_ctx)?;
+        let mut series = HashMap::new();
+        for (_, cardinality) in handler
+            .table_series_cardinality(table_name, Some(label.to_string()), query_ctx.clone())
+            .await?
+        {
+            for (value, n) in cardinality.label_values {
+                *series.entry(value).or_default() += n;
+            }
+        }
+        let top = top_k(series, k as usize);
+
+        let (values, series): (Vec<_>, Vec<_>) = top.into_iter().unzip();
+        Ok(vec![
+            Arc::new(StringVector::from(values)),
+            Arc::new(UInt64Vector::from_vec(series)),
+        ])
+    }
+}
+
+fn resolve_table_name(table_name: &str, query_ctx: &QueryContextRef) -> Result<TableName> {
+    let (catalog_name, schema_name, table_name) = table_name_to_full_name(table_name, query_ctx)
+        .map_err(BoxedError::new)
+        .context(TableMutationSnafu)?;
+    Ok(TableName::new(catalog_name, schema_name, table_name))
+}
+
+/// Returns the `k` values with the most series, ties are ordered by the value.
+fn top_k(series: HashMap<String, u64>, k: usize) -> Vec<(String, u64)> {
+    let mut series = series.into_iter().collect::<Vec<_>>();
+    series.sort_unstable_by(|(a, n), (b, m)| m.cmp(n).then_with(|| a.cmp(b)));
+    series.truncate(k);
+    series
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_series_count() {
+        let f = SeriesCountFunction;
+        assert_eq!("series_count", f.name());
+        assert_eq!(
+            ConcreteDataType::uint64_datatype(),
+            f.return_type(&[]).unwrap()
+        );
+
+        let args = vec![Arc::new(StringVector::from(vec!["test"])) as _];
+        let result = f.eval(FunctionContext::mock(), &args).await.unwrap();
+        let expect: VectorRef = Arc::new(UInt64Vector::from_slice([11]));
+        assert_eq!(expect, result);
+    }
+
+    #[tokio::test]
+    async fn test_label_cardinality() {
+        let f = LabelCardinalityFunction;
+        assert_eq!("label_cardinality", f.name());
+
+        let args = vec![
+            Arc::new(StringVector::from(vec!["test"])) as _,
+            Arc::new(StringVector::from(vec!["job"])) as _,
+        ];
+        let result = f.eval(FunctionContext::mock(), &args).await.unwrap();
+        let expect: VectorRef = Arc::new(UInt64Vector::from_slice([4]));
+        assert_eq!(expect, result);
+    }
+
+    #[tokio::test]
+    async fn test_top_label_values() {
+        let f = TopLabelValuesFunction;
+        assert_eq!("top_label_values", f.name());
+        assert_eq!(2, f.output_columns().unwrap().len());
+        let args = |k: i64| {
+            vec![
+                Arc::new(StringVector::from(vec!["test"])) as _,
+                Arc::new(StringVector::from(vec!["job"])) as _,
+                Arc::new(datatypes::vectors::Int64Vector::from_slice([k])) as _,
+            ]
+        };
+
+        let result = f
+            .eval_columns(FunctionContext::default(), &args(3))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            "Missing TableMutationHandler, not expected",
+            result.to_string()
+        );
+
+        // `a` has the most series, `c` and `d` have the same number of series.
+        let columns = f
+            .eval_columns(FunctionContext::mock(), &args(3))
+            .await
+            .unwrap();
+        let expect: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(vec!["a", "b", "c"])),
+            Arc::new(UInt64Vector::from_slice([5, 3, 1])),
+        ];
+        assert_eq!(expect, columns);
+
+        let columns = f
+            .eval_columns(FunctionContext::mock(), &args(10))
+            .await
+            .unwrap();
+        let expect: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(vec!["a", "b", "c", "d"])),
+            Arc::new(UInt64Vector::from_slice([5, 3, 1, 1])),
+        ];
+        assert_eq!(expect, columns);
+
+        let values = f.eval(FunctionContext::mock(), &args(1)).await.unwrap();
+        let expect: VectorRef = Arc::new(StringVector::from(vec!["a"]));
+        assert_eq!(expect, values);
+    }
+}
@@ -18,6 +18,7 @@ use std::sync::Arc;
 use common_query::error::Result;
 use common_query::prelude::Signature;
 use datatypes::data_type::ConcreteDataType;
+use datatypes::schema::ColumnSchema;
 use datatypes::vectors::VectorRef;
 use session::context::{QueryContextBuilder, QueryContextRef};
 
@@ -83,6 +84,21 @@ pub trait AsyncFunction: fmt::Display + Sync + Send {
     /// Evaluate the function, e.g. run/execute the function.
     /// TODO(dennis): simplify the signature and refactor all the admin functions.
     async fn eval(&self, _func_ctx: FunctionContext, _columns: &[VectorRef]) -> Result<VectorRef>;
+
+    /// Returns the schemas of the output columns if the function outputs a result set
+    /// with multiple columns, which is evaluated by [AsyncFunction::eval_columns].
+    fn output_columns(&self) -> Option<Vec<ColumnSchema>> {
+        None
+    }
+
+    /// Evaluates the function to the columns of [AsyncFunction::output_columns].
+    async fn eval_columns(
+        &self,
+        func_ctx: FunctionContext,
+        columns: &[VectorRef],
+    ) -> Result<Vec<VectorRef>> {
+        Ok(vec![self.eval(func_ctx, columns).await?])
+    }
 }
 
 pub type AsyncFunctionRef = Arc<dyn AsyncFunction>;
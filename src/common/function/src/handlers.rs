@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -23,9 +24,13 @@ use common_meta::rpc::procedure::{
 };
 use common_query::error::Result;
 use common_query::Output;
+use common_time::range::TimestampRange;
 use session::context::QueryContextRef;
+use store_api::manifest::ManifestVersion;
+use store_api::region_engine::{RegionManifestSnapshot, SeriesCardinality};
 use store_api::storage::RegionId;
 use table::requests::{CompactTableRequest, DeleteRequest, FlushTableRequest, InsertRequest};
+use table::table_name::TableName;
 
 /// A trait for handling table mutations in `QueryEngine`.
 #[async_trait]
@@ -36,6 +41,15 @@ pub trait TableMutationHandler: Send + Sync {
     /// Delete rows from the table.
     async fn delete(&self, request: DeleteRequest, ctx: QueryContextRef) -> Result<AffectedRows>;
 
+    /// Removes the SST files of the table whose rows are all in the time range, returns
+    /// the number of rows in them.
+    async fn truncate_range(
+        &self,
+        table_name: TableName,
+        time_range: TimestampRange,
+        ctx: QueryContextRef,
+    ) -> Result<AffectedRows>;
+
     /// Trigger a flush task for table.
     async fn flush(&self, request: FlushTableRequest, ctx: QueryContextRef)
         -> Result<AffectedRows>;
@@ -57,6 +71,58 @@ pub trait TableMutationHandler: Send + Sync {
         region_id: RegionId,
         ctx: QueryContextRef,
     ) -> Result<AffectedRows>;
+
+    /// Lists the SST files in the manifests of the table regions.
+    async fn table_manifest(
+        &self,
+        table_name: TableName,
+        ctx: QueryContextRef,
+    ) -> Result<Vec<(RegionId, RegionManifestSnapshot)>>;
+
+    /// Counts the series of the table regions, and the series of each value of the
+    /// `label` if it's given.
+    async fn table_series_cardinality(
+        &self,
+        table_name: TableName,
+        label: Option<String>,
+        ctx: QueryContextRef,
+    ) -> Result<Vec<(RegionId, SeriesCardinality)>>;
+
+    /// Lists the distinct values of the `label` in the table regions.
+    async fn table_label_values(
+        &self,
+        table_name: TableName,
+        label: String,
+        ctx: QueryContextRef,
+    ) -> Result<Vec<(RegionId, Vec<String>)>>;
+
+    /// Lists the highest sequence number written by each producer to the table regions.
+    async fn table_producer_watermarks(
+        &self,
+        table_name: TableName,
+        ctx: QueryContextRef,
+    ) -> Result<Vec<(RegionId, HashMap<String, u64>)>>;
+
+    /// Saves a checkpoint of the manifest of a table region and returns its version.
+    async fn checkpoint_region(
+        &self,
+        region_id: RegionId,
+        ctx: QueryContextRef,
+    ) -> Result<ManifestVersion>;
+
+    /// Builds the missing indexes of the SSTs in the table regions, returns the number
+    /// of files indexed.
+    async fn build_index(
+        &self,
+        table_name: TableName,
+        ctx: QueryContextRef,
+    ) -> Result<AffectedRows>;
+
+    /// Sets whether the instance is read-only, returns the previous value.
+    fn set_readonly(&self, readonly: bool) -> bool;
+
+    /// Returns true if the instance is read-only.
+    fn is_readonly(&self) -> bool;
 }
 
 /// A trait for handling procedure service requests in `QueryEngine`.
@@ -68,6 +134,9 @@ pub trait ProcedureServiceHandler: Send + Sync {
     /// Query the procedure' state by its id
     async fn query_procedure_state(&self, pid: &str) -> Result<ProcedureStateResponse>;
 
+    /// Cancel the procedure by its id
+    async fn cancel_procedure(&self, pid: &str) -> Result<()>;
+
     /// Add a region follower to a region.
     async fn add_region_follower(&self, request: AddRegionFollowerRequest) -> Result<()>;
 
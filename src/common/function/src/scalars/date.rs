@@ -16,10 +16,12 @@ use std::sync::Arc;
 mod date_add;
 mod date_format;
 mod date_sub;
+mod to_char;
 
 use date_add::DateAddFunction;
 use date_format::DateFormatFunction;
 use date_sub::DateSubFunction;
+use to_char::ToCharFunction;
 
 use crate::function_registry::FunctionRegistry;
 
@@ -30,5 +32,6 @@ impl DateFunction {
         registry.register(Arc::new(DateAddFunction));
         registry.register(Arc::new(DateSubFunction));
         registry.register(Arc::new(DateFormatFunction));
+        registry.register(Arc::new(ToCharFunction));
     }
 }
@@ -30,6 +30,25 @@ pub struct DateFormatFunction;
 
 const NAME: &str = "date_format";
 
+/// Translates the MySQL specifiers that chrono doesn't know into chrono ones, e.g. `%i`
+/// (minutes) into `%M`. Specifiers shared by both follow chrono.
+fn translate_mysql_specifiers(format: &str) -> String {
+    let mut result = String::with_capacity(format.len());
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        result.push(c);
+        if c != '%' {
+            continue;
+        }
+        match chars.next() {
+            Some('i') => result.push('M'),
+            Some(c) => result.push(c),
+            None => {}
+        }
+    }
+    result
+}
+
 impl Function for DateFormatFunction {
     fn name(&self) -> &str {
         NAME
@@ -76,7 +95,7 @@ impl Function for DateFormatFunction {
                     let ts = left.get(i).as_timestamp();
                     let format = formats.get(i).as_string();
 
-                    let result = match (ts, format) {
+                    let result = match (ts, format.as_deref().map(translate_mysql_specifiers)) {
                         (Some(ts), Some(fmt)) => Some(
                             ts.as_formatted_string(&fmt, Some(&func_ctx.query_ctx.timezone()))
                                 .map_err(BoxedError::new)
@@ -93,7 +112,7 @@ impl Function for DateFormatFunction {
                     let date = left.get(i).as_date();
                     let format = formats.get(i).as_string();
 
-                    let result = match (date, format) {
+                    let result = match (date, format.as_deref().map(translate_mysql_specifiers)) {
                         (Some(date), Some(fmt)) => date
                             .as_formatted_string(&fmt, Some(&func_ctx.query_ctx.timezone()))
                             .map_err(BoxedError::new)
@@ -240,4 +259,20 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_translate_mysql_specifiers() {
+        assert_eq!("%Y-%m", translate_mysql_specifiers("%Y-%m"));
+        assert_eq!("%H:%M:%S", translate_mysql_specifiers("%H:%i:%S"));
+        assert_eq!("%%i %M", translate_mysql_specifiers("%%i %i"));
+        assert_eq!("%", translate_mysql_specifiers("%"));
+
+        let f = DateFormatFunction;
+        let args: Vec<VectorRef> = vec![
+            Arc::new(TimestampSecondVector::from_vec(vec![1709647629])),
+            Arc::new(StringVector::from_vec(vec!["%Y-%m-%d %H:%i"])),
+        ];
+        let vector = f.eval(&FunctionContext::default(), &args).unwrap();
+        assert_eq!(Value::from("2024-03-05 14:07"), vector.get(0));
+    }
 }
@@ -0,0 +1,448 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use common_error::ext::BoxedError;
+use common_query::error::{self, InvalidFuncArgsSnafu, Result, UnsupportedInputDataTypeSnafu};
+use common_query::prelude::Signature;
+use datatypes::prelude::{ConcreteDataType, MutableVector, ScalarVectorBuilder};
+use datatypes::vectors::{StringVectorBuilder, VectorRef};
+use snafu::{ensure, ResultExt};
+
+use crate::function::{Function, FunctionContext};
+use crate::helper;
+
+/// A function that formats timestamp/date into string by the PostgreSQL style template,
+/// e.g. `to_char(ts, 'YYYY-MM-DD HH24:MI:SS')`.
+#[derive(Clone, Debug, Default)]
+pub struct ToCharFunction;
+
+const NAME: &str = "to_char";
+
+/// Width that `Month` and `Day` are blank-padded to, unless in fill mode.
+const NAME_WIDTH: usize = 9;
+
+/// The case that a template pattern is rendered in, e.g. `MON`, `Mon` and `mon`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Case {
+    Keep,
+    Upper,
+    Lower,
+}
+
+/// A piece of a template, translated into a chrono format string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Segment {
+    format: String,
+    case: Case,
+    /// Width to blank-pad the rendered text to.
+    width: usize,
+}
+
+impl Segment {
+    fn literal(text: &str) -> Self {
+        Self {
+            format: text.replace('%', "%%"),
+            case: Case::Keep,
+            width: 0,
+        }
+    }
+}
+
+/// Template patterns and their chrono formats, in padded and fill mode (`FM`) forms.
+/// Longer patterns come first as patterns are matched greedily.
+const NUMERIC_PATTERNS: &[(&str, &str, &str)] = &[
+    ("HH24", "%H", "%-H"),
+    ("HH12", "%I", "%-I"),
+    ("IYYY", "%G", "%G"),
+    ("YYYY", "%Y", "%Y"),
+    ("DDD", "%j", "%-j"),
+    ("HH", "%I", "%-I"),
+    ("MI", "%M", "%-M"),
+    ("SS", "%S", "%-S"),
+    ("MS", "%3f", "%3f"),
+    ("US", "%6f", "%6f"),
+    ("YY", "%y", "%y"),
+    ("MM", "%m", "%-m"),
+    ("DD", "%d", "%-d"),
+    ("ID", "%u", "%u"),
+    ("IW", "%V", "%-V"),
+    ("OF", "%:z", "%:z"),
+];
+
+/// Name patterns, whose rendered case follows the case of the pattern, and whether
+/// they are blank-padded.
+const NAME_PATTERNS: &[(&str, &str, bool)] = &[
+    ("MONTH", "%B", true),
+    ("MON", "%b", false),
+    ("DAY", "%A", true),
+    ("DY", "%a", false),
+    ("TZ", "%Z", false),
+];
+
+/// Translates a PostgreSQL style template into chrono formats. Text in double
+/// quotes and characters that are not template patterns are copied as-is.
+fn translate(template: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut rest = template;
+    let mut fill_mode = false;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '"' {
+            let end = rest[1..].find('"').map(|i| i + 1).unwrap_or(rest.len());
+            literal.push_str(&rest[1..end]);
+            rest = &rest[(end + 1).min(rest.len())..];
+            continue;
+        }
+        if starts_with_ignore_case(rest, "FM") {
+            fill_mode = true;
+            rest = &rest[2..];
+            continue;
+        }
+
+        let segment = if let Some((pattern, padded, filled)) = NUMERIC_PATTERNS
+            .iter()
+            .find(|(pattern, ..)| starts_with_ignore_case(rest, pattern))
+        {
+            rest = &rest[pattern.len()..];
+            Some(Segment {
+                format: if fill_mode { filled } else { padded }.to_string(),
+                case: Case::Keep,
+                width: 0,
+            })
+        } else if let Some((pattern, format, padded)) = NAME_PATTERNS
+            .iter()
+            .find(|(pattern, ..)| starts_with_ignore_case(rest, pattern))
+        {
+            let case = name_case(&rest[..pattern.len()]);
+            rest = &rest[pattern.len()..];
+            Some(Segment {
+                format: format.to_string(),
+                case,
+                width: if *padded && !fill_mode { NAME_WIDTH } else { 0 },
+            })
+        } else if starts_with_ignore_case(rest, "AM") || starts_with_ignore_case(rest, "PM") {
+            let format = if rest.starts_with(char::is_lowercase) {
+                "%P"
+            } else {
+                "%p"
+            };
+            rest = &rest[2..];
+            Some(Segment {
+                format: format.to_string(),
+                case: Case::Keep,
+                width: 0,
+            })
+        } else {
+            None
+        };
+
+        match segment {
+            Some(segment) => {
+                if !literal.is_empty() {
+                    segments.push(Segment::literal(&literal));
+                    literal.clear();
+                }
+                segments.push(segment);
+                fill_mode = false;
+            }
+            None => {
+                literal.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !literal.is_empty() {
+        segments.push(Segment::literal(&literal));
+    }
+
+    segments
+}
+
+fn starts_with_ignore_case(s: &str, pattern: &str) -> bool {
+    s.len() >= pattern.len()
+        && s.is_char_boundary(pattern.len())
+        && s[..pattern.len()].eq_ignore_ascii_case(pattern)
+}
+
+/// `MON` renders `JAN`, `Mon` renders `Jan` and `mon` renders `jan`.
+fn name_case(pattern: &str) -> Case {
+    let mut chars = pattern.chars();
+    match (chars.next(), chars.next()) {
+        (Some(first), _) if first.is_lowercase() => Case::Lower,
+        (_, Some(second)) if second.is_uppercase() => Case::Upper,
+        _ => Case::Keep,
+    }
+}
+
+/// Renders the segments, formatting each chrono format by `format`.
+fn render(
+    segments: &[Segment],
+    mut format: impl FnMut(&str) -> Result<Option<String>>,
+) -> Result<Option<String>> {
+    let mut result = String::new();
+    for segment in segments {
+        let Some(text) = format(&segment.format)? else {
+            return Ok(None);
+        };
+        let text = match segment.case {
+            Case::Keep => text,
+            Case::Upper => text.to_uppercase(),
+            Case::Lower => text.to_lowercase(),
+        };
+        result.push_str(&format!("{:<width$}", text, width = segment.width));
+    }
+    Ok(Some(result))
+}
+
+impl Function for ToCharFunction {
+    fn name(&self) -> &str {
+        NAME
+    }
+
+    fn return_type(&self, _input_types: &[ConcreteDataType]) -> Result<ConcreteDataType> {
+        Ok(ConcreteDataType::string_datatype())
+    }
+
+    fn signature(&self) -> Signature {
+        helper::one_of_sigs2(
+            vec![
+                ConcreteDataType::date_datatype(),
+                ConcreteDataType::timestamp_second_datatype(),
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                ConcreteDataType::timestamp_microsecond_datatype(),
+                ConcreteDataType::timestamp_nanosecond_datatype(),
+            ],
+            vec![ConcreteDataType::string_datatype()],
+        )
+    }
+
+    fn eval(&self, func_ctx: &FunctionContext, columns: &[VectorRef]) -> Result<VectorRef> {
+        ensure!(
+            columns.len() == 2,
+            InvalidFuncArgsSnafu {
+                err_msg: format!(
+                    "The length of the args is not correct, expect 2, have: {}",
+                    columns.len()
+                ),
+            }
+        );
+
+        let left = &columns[0];
+        let templates = &columns[1];
+
+        let size = left.len();
+        let timezone = func_ctx.query_ctx.timezone();
+        let mut results = StringVectorBuilder::with_capacity(size);
+        // The template is usually a constant, so the last translation is reused.
+        let mut translated: Option<(String, Vec<Segment>)> = None;
+
+        for i in 0..size {
+            let Some(template) = templates.get(i).as_string() else {
+                results.push(None);
+                continue;
+            };
+            if translated
+                .as_ref()
+                .is_none_or(|(last, _)| *last != template)
+            {
+                let segments = translate(&template);
+                translated = Some((template, segments));
+            }
+            // Safety: translated above.
+            let segments = &translated.as_ref().unwrap().1;
+
+            let value = left.get(i);
+            let result = match left.data_type() {
+                ConcreteDataType::Timestamp(_) => match value.as_timestamp() {
+                    Some(ts) => render(segments, |format| {
+                        ts.as_formatted_string(format, Some(&timezone))
+                            .map(Some)
+                            .map_err(BoxedError::new)
+                            .context(error::ExecuteSnafu)
+                    })?,
+                    None => None,
+                },
+                ConcreteDataType::Date(_) => match value.as_date() {
+                    Some(date) => render(segments, |format| {
+                        date.as_formatted_string(format, Some(&timezone))
+                            .map_err(BoxedError::new)
+                            .context(error::ExecuteSnafu)
+                    })?,
+                    None => None,
+                },
+                _ => {
+                    return UnsupportedInputDataTypeSnafu {
+                        function: NAME,
+                        datatypes: columns.iter().map(|c| c.data_type()).collect::<Vec<_>>(),
+                    }
+                    .fail();
+                }
+            };
+
+            results.push(result.as_deref());
+        }
+
+        Ok(results.to_vector())
+    }
+}
+
+impl fmt::Display for ToCharFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TO_CHAR")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_time::Timezone;
+    use datatypes::prelude::ScalarVector;
+    use datatypes::value::Value;
+    use datatypes::vectors::{
+        DateVector, StringVector, TimestampMillisecondVector, TimestampNanosecondVector,
+        TimestampSecondVector,
+    };
+    use session::context::QueryContextBuilder;
+
+    use super::*;
+
+    fn eval(func_ctx: &FunctionContext, input: VectorRef, template: &str) -> Vec<Value> {
+        let templates = Arc::new(StringVector::from_vec(vec![template; input.len()]));
+        let vector = ToCharFunction.eval(func_ctx, &[input, templates]).unwrap();
+        (0..vector.len()).map(|i| vector.get(i)).collect()
+    }
+
+    fn eval_one(func_ctx: &FunctionContext, input: VectorRef, template: &str) -> String {
+        match eval(func_ctx, input, template).remove(0) {
+            Value::String(s) => s.as_utf8().to_string(),
+            v => unreachable!("{v:?}"),
+        }
+    }
+
+    #[test]
+    fn test_to_char_patterns() {
+        // 2024-03-05 14:07:09.123456789 UTC, a Tuesday.
+        let input: VectorRef = Arc::new(TimestampNanosecondVector::from_vec(vec![
+            1709647629123456789,
+        ]));
+        let func_ctx = FunctionContext::default();
+        let cases = [
+            ("YYYY-MM-DD HH24:MI:SS", "2024-03-05 14:07:09"),
+            ("yyyy-mm-dd hh24:mi:ss", "2024-03-05 14:07:09"),
+            ("YY/MM/DD", "24/03/05"),
+            ("HH12:MI AM", "02:07 PM"),
+            ("HH:MI pm", "02:07 pm"),
+            ("FMHH12:MI", "2:07"),
+            ("FMMM/FMDD", "3/5"),
+            ("SS.MS", "09.123"),
+            ("SS.US", "09.123456"),
+            ("DDD", "065"),
+            ("Month", "March    "),
+            ("FMMonth", "March"),
+            ("MONTH|", "MARCH    |"),
+            ("Mon mon MON", "Mar mar MAR"),
+            ("Day", "Tuesday  "),
+            ("FMDay, DD", "Tuesday, 05"),
+            ("Dy DY dy", "Tue TUE tue"),
+            ("IYYY-IW-ID", "2024-10-2"),
+            ("OF", "+00:00"),
+            ("\"Year\" YYYY", "Year 2024"),
+            ("100% YYYY", "100% 2024"),
+            ("YYYY年MM月", "2024年03月"),
+        ];
+        for (template, expected) in cases {
+            assert_eq!(
+                expected,
+                eval_one(&func_ctx, input.clone(), template),
+                "template: {template}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_to_char_precisions() {
+        let func_ctx = FunctionContext::default();
+        let inputs: Vec<VectorRef> = vec![
+            Arc::new(TimestampSecondVector::from_vec(vec![1709647629])),
+            Arc::new(TimestampMillisecondVector::from_vec(vec![1709647629000])),
+            Arc::new(TimestampNanosecondVector::from_vec(vec![
+                1709647629000000000,
+            ])),
+        ];
+        for input in inputs {
+            assert_eq!(
+                "2024-03-05 14:07:09",
+                eval_one(&func_ctx, input, "YYYY-MM-DD HH24:MI:SS")
+            );
+        }
+
+        let date: VectorRef = Arc::new(DateVector::from_vec(vec![19787]));
+        assert_eq!("2024-03-05", eval_one(&func_ctx, date, "YYYY-MM-DD"));
+    }
+
+    #[test]
+    fn test_to_char_null() {
+        let func_ctx = FunctionContext::default();
+        let input: VectorRef = Arc::new(TimestampSecondVector::from(vec![Some(1709647629), None]));
+        assert_eq!(
+            vec![Value::from("2024"), Value::Null],
+            eval(&func_ctx, input.clone(), "YYYY")
+        );
+
+        let templates = Arc::new(StringVector::from(vec![None, Some("YYYY")]));
+        let vector = ToCharFunction.eval(&func_ctx, &[input, templates]).unwrap();
+        assert_eq!(Value::Null, vector.get(0));
+        assert_eq!(Value::Null, vector.get(1));
+    }
+
+    #[test]
+    fn test_to_char_timezone() {
+        let input: VectorRef = Arc::new(TimestampSecondVector::from_vec(vec![1709647629]));
+        let template = "YYYY-MM-DD HH24:MI:SS OF";
+
+        let func_ctx = FunctionContext::default();
+        assert_eq!(
+            "2024-03-05 14:07:09 +00:00",
+            eval_one(&func_ctx, input.clone(), template)
+        );
+
+        let func_ctx = FunctionContext {
+            query_ctx: QueryContextBuilder::default()
+                .timezone(Timezone::from_tz_string("Asia/Shanghai").unwrap())
+                .build()
+                .into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            "2024-03-05 22:07:09 +08:00",
+            eval_one(&func_ctx, input.clone(), template)
+        );
+
+        let func_ctx = FunctionContext {
+            query_ctx: QueryContextBuilder::default()
+                .timezone(Timezone::from_tz_string("-10:00").unwrap())
+                .build()
+                .into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            "2024-03-05 04:07:09 -10:00",
+            eval_one(&func_ctx, input, template)
+        );
+    }
+}
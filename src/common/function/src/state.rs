@@ -30,6 +30,7 @@ impl FunctionState {
     /// Create a mock [`FunctionState`] for test.
     #[cfg(any(test, feature = "testing"))]
     pub fn mock() -> Self {
+        use std::collections::HashMap;
         use std::sync::Arc;
 
         use api::v1::meta::ProcedureStatus;
@@ -42,11 +43,18 @@ impl FunctionState {
         };
         use common_query::error::Result;
         use common_query::Output;
+        use common_time::range::TimestampRange;
+        use common_time::Timestamp;
         use session::context::QueryContextRef;
+        use store_api::manifest::ManifestVersion;
+        use store_api::region_engine::{
+            RegionFileEntry, RegionManifestSnapshot, SeriesCardinality,
+        };
         use store_api::storage::RegionId;
         use table::requests::{
             CompactTableRequest, DeleteRequest, FlushTableRequest, InsertRequest,
         };
+        use table::table_name::TableName;
 
         use crate::handlers::{FlowServiceHandler, ProcedureServiceHandler, TableMutationHandler};
         struct MockProcedureServiceHandler;
@@ -71,6 +79,10 @@ impl FunctionState {
                 })
             }
 
+            async fn cancel_procedure(&self, _pid: &str) -> Result<()> {
+                Ok(())
+            }
+
             async fn add_region_follower(&self, _request: AddRegionFollowerRequest) -> Result<()> {
                 Ok(())
             }
@@ -105,6 +117,15 @@ impl FunctionState {
                 Ok(ROWS)
             }
 
+            async fn truncate_range(
+                &self,
+                _table_name: TableName,
+                _time_range: TimestampRange,
+                _ctx: QueryContextRef,
+            ) -> Result<AffectedRows> {
+                Ok(ROWS)
+            }
+
             async fn flush(
                 &self,
                 _request: FlushTableRequest,
@@ -136,6 +157,132 @@ impl FunctionState {
             ) -> Result<AffectedRows> {
                 Ok(ROWS)
             }
+
+            async fn table_manifest(
+                &self,
+                _table_name: TableName,
+                _ctx: QueryContextRef,
+            ) -> Result<Vec<(RegionId, RegionManifestSnapshot)>> {
+                let files = [(0, 4_000), (10_000, 14_000)]
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (start, end))| RegionFileEntry {
+                        file_id: format!("file_{i}"),
+                        file_path: format!("data/file_{i}.parquet"),
+                        time_range: (
+                            Timestamp::new_millisecond(start),
+                            Timestamp::new_millisecond(end),
+                        ),
+                        num_rows: 5,
+                        file_size: 1024,
+                    })
+                    .collect();
+                Ok(vec![(
+                    RegionId::new(1024, 0),
+                    RegionManifestSnapshot {
+                        manifest_version: ROWS as u64,
+                        files,
+                    },
+                )])
+            }
+
+            async fn table_series_cardinality(
+                &self,
+                _table_name: TableName,
+                label: Option<String>,
+                _ctx: QueryContextRef,
+            ) -> Result<Vec<(RegionId, SeriesCardinality)>> {
+                // The series of `a` are skewed to the first region.
+                let regions = [
+                    (6, vec![("a", 4), ("b", 1)]),
+                    (5, vec![("a", 1), ("b", 2), ("c", 1), ("d", 1)]),
+                ];
+                Ok(regions
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (num_series, values))| {
+                        let label_values = if label.is_some() {
+                            values
+                                .into_iter()
+                                .map(|(value, n)| (value.to_string(), n))
+                                .collect()
+                        } else {
+                            HashMap::new()
+                        };
+                        (
+                            RegionId::new(1024, i as u32),
+                            SeriesCardinality {
+                                num_series,
+                                label_values,
+                            },
+                        )
+                    })
+                    .collect())
+            }
+
+            async fn table_label_values(
+                &self,
+                _table_name: TableName,
+                _label: String,
+                _ctx: QueryContextRef,
+            ) -> Result<Vec<(RegionId, Vec<String>)>> {
+                Ok(vec![
+                    (
+                        RegionId::new(1024, 0),
+                        vec!["a".to_string(), "b".to_string()],
+                    ),
+                    (
+                        RegionId::new(1024, 1),
+                        vec![
+                            "a".to_string(),
+                            "b".to_string(),
+                            "c".to_string(),
+                            "d".to_string(),
+                        ],
+                    ),
+                ])
+            }
+
+            async fn table_producer_watermarks(
+                &self,
+                _table_name: TableName,
+                _ctx: QueryContextRef,
+            ) -> Result<Vec<(RegionId, HashMap<String, u64>)>> {
+                Ok(vec![
+                    (
+                        RegionId::new(1024, 1),
+                        HashMap::from([("p1".to_string(), 7)]),
+                    ),
+                    (
+                        RegionId::new(1024, 0),
+                        HashMap::from([("p2".to_string(), 3), ("p1".to_string(), 5)]),
+                    ),
+                ])
+            }
+
+            async fn checkpoint_region(
+                &self,
+                _region_id: RegionId,
+                _ctx: QueryContextRef,
+            ) -> Result<ManifestVersion> {
+                Ok(ROWS as u64)
+            }
+
+            async fn build_index(
+                &self,
+                _table_name: TableName,
+                _ctx: QueryContextRef,
+            ) -> Result<AffectedRows> {
+                Ok(ROWS)
+            }
+
+            fn set_readonly(&self, _readonly: bool) -> bool {
+                false
+            }
+
+            fn is_readonly(&self) -> bool {
+                false
+            }
         }
 
         #[async_trait]
@@ -92,6 +92,12 @@ pub struct RegionStat {
     pub sst_size: u64,
     /// The size of the SST index files in bytes.
     pub index_size: u64,
+    /// The compression codec used to write new SST files.
+    #[serde(default)]
+    pub sst_compression: Option<String>,
+    /// The min and max values of the time index, in the unit of the time index.
+    #[serde(default)]
+    pub time_range: Option<(i64, i64)>,
     /// The manifest infoof the region.
     pub region_manifest: RegionManifestInfo,
 }
@@ -226,6 +232,8 @@ impl From<&api::v1::meta::RegionStat> for RegionStat {
             manifest_size: region_stat.manifest_size,
             sst_size: region_stat.sst_size,
             index_size: region_stat.index_size,
+            sst_compression: region_stat.sst_compression,
+            time_range: region_stat.time_range,
             region_manifest: region_stat.manifest.into(),
         }
     }
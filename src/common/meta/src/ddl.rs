@@ -16,6 +16,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use api::v1::meta::ProcedureDetailResponse;
+use api::v1::Rows;
 use common_telemetry::tracing_context::W3cTrace;
 use store_api::storage::{RegionId, RegionNumber, TableId};
 
@@ -34,11 +35,13 @@ use crate::rpc::procedure::{
     AddRegionFollowerRequest, MigrateRegionRequest, MigrateRegionResponse, ProcedureStateResponse,
     RemoveRegionFollowerRequest,
 };
+use crate::rpc::router::RegionRoute;
 use crate::DatanodeId;
 
 pub mod alter_database;
 pub mod alter_logical_tables;
 pub mod alter_table;
+pub mod build_index;
 pub mod create_database;
 pub mod create_flow;
 pub mod create_logical_tables;
@@ -51,6 +54,7 @@ pub mod drop_table;
 pub mod drop_view;
 pub mod flow_meta;
 mod physical_table_metadata;
+pub mod repartition_table;
 pub mod table_meta;
 #[cfg(any(test, feature = "testing"))]
 pub mod test_util;
@@ -113,6 +117,9 @@ pub trait ProcedureExecutor: Send + Sync {
     ) -> Result<ProcedureStateResponse>;
 
     async fn list_procedures(&self, ctx: &ExecutorContext) -> Result<ProcedureDetailResponse>;
+
+    /// Cancel the procedure by its id
+    async fn cancel_procedure(&self, ctx: &ExecutorContext, pid: &str) -> Result<()>;
 }
 
 pub type ProcedureExecutorRef = Arc<dyn ProcedureExecutor>;
@@ -156,6 +163,40 @@ impl RegionFailureDetectorController for NoopRegionFailureDetectorControl {
     async fn deregister_failure_detectors(&self, _detecting_regions: Vec<DetectingRegion>) {}
 }
 
+pub type RegionRowSplitterRef = Arc<dyn RegionRowSplitter>;
+
+/// Splits rows into regions by the partition rule of the regions.
+///
+/// Evaluating partition rules is out of the scope of this crate, so it's used to move
+/// the rows of a table into its new regions when repartitioning the table.
+pub trait RegionRowSplitter: Send + Sync {
+    /// Splits `rows` of the table into the regions in `region_routes`.
+    fn split(
+        &self,
+        table_id: TableId,
+        region_routes: &[RegionRoute],
+        rows: Rows,
+    ) -> Result<HashMap<RegionNumber, Rows>>;
+}
+
+/// A noop implementation of [`RegionRowSplitter`], repartitioning tables is unsupported with it.
+#[derive(Debug, Clone)]
+pub struct NoopRegionRowSplitter;
+
+impl RegionRowSplitter for NoopRegionRowSplitter {
+    fn split(
+        &self,
+        _table_id: TableId,
+        _region_routes: &[RegionRoute],
+        _rows: Rows,
+    ) -> Result<HashMap<RegionNumber, Rows>> {
+        UnsupportedSnafu {
+            operation: "split rows into regions",
+        }
+        .fail()
+    }
+}
+
 /// The context of ddl.
 #[derive(Clone)]
 pub struct DdlContext {
@@ -177,6 +218,8 @@ pub struct DdlContext {
     pub flow_metadata_allocator: FlowMetadataAllocatorRef,
     /// controller of region failure detector.
     pub region_failure_detector_controller: RegionFailureDetectorControllerRef,
+    /// Splits rows into the new regions when repartitioning tables.
+    pub region_row_splitter: RegionRowSplitterRef,
 }
 
 impl DdlContext {
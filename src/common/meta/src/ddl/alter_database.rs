@@ -47,6 +47,9 @@ fn build_new_schema_value(
                     SetDatabaseOption::Ttl(ttl) => {
                         value.ttl = Some(*ttl);
                     }
+                    SetDatabaseOption::TableDefault(key, option) => {
+                        value.table_defaults.insert(key.clone(), option.clone());
+                    }
                 }
             }
         }
@@ -54,6 +57,9 @@ fn build_new_schema_value(
             for key in keys.0.iter() {
                 match key {
                     UnsetDatabaseOption::Ttl => value.ttl = None,
+                    UnsetDatabaseOption::TableDefault(key) => {
+                        value.table_defaults.remove(key);
+                    }
                 }
             }
         }
@@ -207,6 +213,7 @@ impl AlterDatabaseData {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::time::Duration;
 
     use crate::ddl::alter_database::build_new_schema_value;
@@ -234,4 +241,30 @@ mod tests {
             build_new_schema_value(current_schema_value, &unset_ttl_alter_kind).unwrap();
         assert_eq!(new_schema_value.ttl, None);
     }
+
+    #[test]
+    fn test_build_new_schema_value_with_table_defaults() {
+        let set_defaults = AlterDatabaseKind::SetDatabaseOptions(SetDatabaseOptions(vec![
+            SetDatabaseOption::TableDefault("append_mode".to_string(), "true".to_string()),
+            SetDatabaseOption::TableDefault("ttl".to_string(), "7d".to_string()),
+        ]));
+        let new_schema_value =
+            build_new_schema_value(SchemaNameValue::default(), &set_defaults).unwrap();
+        assert_eq!(
+            new_schema_value.table_defaults,
+            BTreeMap::from([
+                ("append_mode".to_string(), "true".to_string()),
+                ("ttl".to_string(), "7d".to_string()),
+            ])
+        );
+
+        let unset_default = AlterDatabaseKind::UnsetDatabaseOptions(UnsetDatabaseOptions(vec![
+            UnsetDatabaseOption::TableDefault("ttl".to_string()),
+        ]));
+        let new_schema_value = build_new_schema_value(new_schema_value, &unset_default).unwrap();
+        assert_eq!(
+            new_schema_value.table_defaults,
+            BTreeMap::from([("append_mode".to_string(), "true".to_string())])
+        );
+    }
 }
@@ -0,0 +1,206 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use common_procedure::error::{FromJsonSnafu, Result as ProcedureResult, ToJsonSnafu};
+use common_procedure::{Context as ProcedureContext, LockKey, Procedure, Status};
+use common_telemetry::info;
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt};
+use store_api::storage::RegionId;
+use strum::AsRefStr;
+use table::metadata::TableId;
+use table::table_reference::TableReference;
+
+use crate::ddl::utils::{add_peer_context_if_needed, handle_retry_error};
+use crate::ddl::DdlContext;
+use crate::error::{self, Result};
+use crate::lock_key::{CatalogLock, SchemaLock, TableLock};
+use crate::metrics;
+use crate::node_manager::AffectedRows;
+use crate::rpc::ddl::BuildIndexTask;
+use crate::rpc::router::{find_region_leader, RegionRoute};
+
+/// Builds the missing indexes of the existing SSTs of a table region by region.
+///
+/// The progress is persisted and reported after each region, so the procedure resumes
+/// from the first region whose indexes aren't built yet after a restart. Building the
+/// indexes of a region again only indexes the files that still miss them.
+pub struct BuildIndexProcedure {
+    context: DdlContext,
+    data: BuildIndexData,
+}
+
+impl BuildIndexProcedure {
+    pub const TYPE_NAME: &'static str = "metasrv-procedure::BuildIndex";
+
+    pub fn new(task: BuildIndexTask, context: DdlContext) -> Self {
+        Self {
+            context,
+            data: BuildIndexData::new(task),
+        }
+    }
+
+    pub fn from_json(json: &str, context: DdlContext) -> ProcedureResult<Self> {
+        let data = serde_json::from_str(json).context(FromJsonSnafu)?;
+
+        Ok(Self { context, data })
+    }
+
+    /// Returns the state of the procedure.
+    pub fn state(&self) -> &BuildIndexState {
+        &self.data.state
+    }
+
+    /// Returns the number of regions whose indexes have been built.
+    pub fn built_regions(&self) -> usize {
+        self.data.built_regions
+    }
+
+    /// Returns the region routes of the table, or of its physical table if the table is
+    /// a logical table.
+    async fn get_region_routes(&self) -> Result<Vec<RegionRoute>> {
+        let (_, physical_table_route) = self
+            .context
+            .table_metadata_manager
+            .table_route_manager()
+            .get_physical_table_route(self.data.table_id())
+            .await?;
+
+        Ok(physical_table_route.region_routes)
+    }
+
+    /// Loads the regions of the table.
+    pub(crate) async fn on_prepare(&mut self) -> Result<Status> {
+        let region_routes = self.get_region_routes().await?;
+        self.data.region_ids = region_routes.iter().map(|route| route.region.id).collect();
+        self.data.state = BuildIndexState::BuildIndex;
+
+        Ok(Status::executing(true))
+    }
+
+    /// Builds the indexes of the next region, the leader of the region is looked up on
+    /// each step as it may change between steps.
+    pub(crate) async fn on_build_index(&mut self, ctx: &ProcedureContext) -> Result<Status> {
+        let total = self.data.region_ids.len();
+        let Some(region_id) = self.data.region_ids.get(self.data.built_regions).copied() else {
+            info!(
+                "Built indexes of {} regions of table {}, {} files indexed",
+                total,
+                self.data.table_ref(),
+                self.data.indexed_files
+            );
+            return Ok(Status::done_with_output(self.data.indexed_files));
+        };
+
+        let region_routes = self.get_region_routes().await?;
+        let peer = find_region_leader(&region_routes, region_id.region_number()).context(
+            error::NoLeaderSnafu {
+                table_id: region_id.table_id(),
+            },
+        )?;
+        let indexed_files = self
+            .context
+            .node_manager
+            .datanode(&peer)
+            .await
+            .build_index(region_id)
+            .await
+            .map_err(add_peer_context_if_needed(peer))?;
+
+        self.data.built_regions += 1;
+        self.data.indexed_files += indexed_files;
+        ctx.report_progress(format!(
+            "built indexes of {}/{} regions, {} files indexed",
+            self.data.built_regions, total, self.data.indexed_files
+        ));
+
+        Ok(Status::executing(true))
+    }
+}
+
+#[async_trait]
+impl Procedure for BuildIndexProcedure {
+    fn type_name(&self) -> &str {
+        Self::TYPE_NAME
+    }
+
+    async fn execute(&mut self, ctx: &ProcedureContext) -> ProcedureResult<Status> {
+        let state = &self.data.state;
+
+        let _timer = metrics::METRIC_META_PROCEDURE_BUILD_INDEX
+            .with_label_values(&[state.as_ref()])
+            .start_timer();
+
+        match state {
+            BuildIndexState::Prepare => self.on_prepare().await,
+            BuildIndexState::BuildIndex => self.on_build_index(ctx).await,
+        }
+        .map_err(handle_retry_error)
+    }
+
+    fn dump(&self) -> ProcedureResult<String> {
+        serde_json::to_string(&self.data).context(ToJsonSnafu)
+    }
+
+    fn lock_key(&self) -> LockKey {
+        let table_ref = self.data.table_ref();
+
+        LockKey::new(vec![
+            CatalogLock::Read(table_ref.catalog).into(),
+            SchemaLock::read(table_ref.catalog, table_ref.schema).into(),
+            TableLock::Read(self.data.table_id()).into(),
+        ])
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, AsRefStr, PartialEq)]
+pub enum BuildIndexState {
+    /// Loads the regions of the table.
+    Prepare,
+    /// Builds the indexes of the regions one by one.
+    BuildIndex,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildIndexData {
+    state: BuildIndexState,
+    task: BuildIndexTask,
+    /// The regions to build the indexes of, loaded when preparing.
+    region_ids: Vec<RegionId>,
+    /// The number of regions whose indexes have been built.
+    built_regions: usize,
+    /// The number of files indexed.
+    indexed_files: AffectedRows,
+}
+
+impl BuildIndexData {
+    fn new(task: BuildIndexTask) -> Self {
+        Self {
+            state: BuildIndexState::Prepare,
+            task,
+            region_ids: vec![],
+            built_regions: 0,
+            indexed_files: 0,
+        }
+    }
+
+    fn table_ref(&self) -> TableReference {
+        self.task.table_ref()
+    }
+
+    fn table_id(&self) -> TableId {
+        self.task.table_id
+    }
+}
@@ -15,16 +15,19 @@
 use std::collections::HashMap;
 
 use api::v1::region::region_request::Body as PbRegionRequest;
-use api::v1::region::{RegionRequest, RegionRequestHeader};
+use api::v1::region::{DropRequest as PbDropRegionRequest, RegionRequest, RegionRequestHeader};
 use async_trait::async_trait;
-use common_error::ext::BoxedError;
+use common_error::ext::{BoxedError, ErrorExt};
+use common_error::status_code::StatusCode;
 use common_procedure::error::{
-    ExternalSnafu, FromJsonSnafu, Result as ProcedureResult, ToJsonSnafu,
+    Error as ProcedureError, ExternalSnafu, FromJsonSnafu, Result as ProcedureResult, ToJsonSnafu,
 };
 use common_procedure::{Context as ProcedureContext, LockKey, Procedure, Status};
-use common_telemetry::info;
 use common_telemetry::tracing_context::TracingContext;
+use common_telemetry::{info, warn};
 use futures::future::join_all;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use snafu::{ensure, OptionExt, ResultExt};
 use store_api::storage::{RegionId, RegionNumber};
@@ -182,7 +185,7 @@ impl CreateTableProcedure {
     ///   - [Code::Cancelled](tonic::status::Code::Cancelled)
     ///   - [Code::DeadlineExceeded](tonic::status::Code::DeadlineExceeded)
     ///   - [Code::Unavailable](tonic::status::Code::Unavailable)
-    pub async fn on_datanode_create_regions(&mut self) -> Result<Status> {
+    pub async fn on_datanode_create_regions(&mut self, ctx: &ProcedureContext) -> Result<Status> {
         let table_route = self.table_route()?.clone();
         let request_builder = self.new_region_request_builder(None)?;
         // Registers opening regions
@@ -192,12 +195,13 @@ impl CreateTableProcedure {
         if !guards.is_empty() {
             self.creator.opening_regions = guards;
         }
-        self.create_regions(&table_route.region_routes, request_builder)
+        self.create_regions(ctx, &table_route.region_routes, request_builder)
             .await
     }
 
     async fn create_regions(
         &mut self,
+        ctx: &ProcedureContext,
         region_routes: &[RegionRoute],
         request_builder: CreateRequestBuilder,
     ) -> Result<Status> {
@@ -246,10 +250,28 @@ impl CreateTableProcedure {
             }
         }
 
-        join_all(create_region_tasks)
-            .await
+        // Reports the progress as regions are created, as a table may have many partitions.
+        let total = create_region_tasks.len();
+        ctx.report_progress(format!("created 0/{total} regions"));
+        let mut create_region_tasks = create_region_tasks
             .into_iter()
-            .collect::<Result<Vec<_>>>()?;
+            .collect::<FuturesUnordered<_>>();
+        let mut created = 0;
+        let mut first_error = None;
+        while let Some(result) = create_region_tasks.next().await {
+            match result {
+                Ok(_) => {
+                    created += 1;
+                    ctx.report_progress(format!("created {created}/{total} regions"));
+                }
+                Err(e) => {
+                    let _ = first_error.get_or_insert(e);
+                }
+            }
+        }
+        if let Some(e) = first_error {
+            return Err(e);
+        }
 
         self.creator.data.state = CreateTableState::CreateMetadata;
 
@@ -257,6 +279,53 @@ impl CreateTableProcedure {
         Ok(Status::executing(true))
     }
 
+    /// Drops the regions that may have been created, as the table metadata isn't
+    /// written yet. Regions that are not created are skipped.
+    async fn on_rollback(&mut self) -> Result<()> {
+        let Some(table_route) = &self.creator.data.table_route else {
+            return Ok(());
+        };
+        let table_id = self.table_id();
+        let region_routes = &table_route.region_routes;
+
+        let mut drop_region_tasks = Vec::with_capacity(region_routes.len());
+        for datanode in find_leaders(region_routes) {
+            let requester = self.context.node_manager.datanode(&datanode).await;
+
+            for region_number in find_leader_regions(region_routes, &datanode) {
+                let request = RegionRequest {
+                    header: Some(RegionRequestHeader {
+                        tracing_context: TracingContext::from_current_span().to_w3c(),
+                        ..Default::default()
+                    }),
+                    body: Some(PbRegionRequest::Drop(PbDropRegionRequest {
+                        region_id: RegionId::new(table_id, region_number).as_u64(),
+                        fast_path: false,
+                    })),
+                };
+
+                let datanode = datanode.clone();
+                let requester = requester.clone();
+                drop_region_tasks.push(async move {
+                    if let Err(err) = requester.handle(request).await {
+                        if err.status_code() != StatusCode::RegionNotFound {
+                            return Err(add_peer_context_if_needed(datanode)(err));
+                        }
+                    }
+                    Ok(())
+                });
+            }
+        }
+
+        join_all(drop_region_tasks)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        self.creator.opening_regions.clear();
+        Ok(())
+    }
+
     /// Creates table metadata
     ///
     /// Abort(not-retry):
@@ -307,7 +376,7 @@ impl Procedure for CreateTableProcedure {
         Ok(())
     }
 
-    async fn execute(&mut self, _ctx: &ProcedureContext) -> ProcedureResult<Status> {
+    async fn execute(&mut self, ctx: &ProcedureContext) -> ProcedureResult<Status> {
         let state = &self.creator.data.state;
 
         let _timer = metrics::METRIC_META_PROCEDURE_CREATE_TABLE
@@ -316,12 +385,34 @@ impl Procedure for CreateTableProcedure {
 
         match state {
             CreateTableState::Prepare => self.on_prepare().await,
-            CreateTableState::DatanodeCreateRegions => self.on_datanode_create_regions().await,
-            CreateTableState::CreateMetadata => self.on_create_metadata().await,
+            CreateTableState::DatanodeCreateRegions => self.on_datanode_create_regions(ctx).await,
+            CreateTableState::CreateMetadata => {
+                ctx.report_progress("creating table metadata");
+                self.on_create_metadata().await
+            }
         }
         .map_err(handle_retry_error)
     }
 
+    fn cancel_supported(&self) -> bool {
+        // Can't cancel the procedure once all regions are created.
+        self.creator.data.state != CreateTableState::CreateMetadata
+    }
+
+    fn rollback_supported(&self) -> bool {
+        // Only regions are created before the metadata, which can be dropped.
+        self.creator.data.state == CreateTableState::DatanodeCreateRegions
+    }
+
+    async fn rollback(&mut self, _: &ProcedureContext) -> ProcedureResult<()> {
+        warn!(
+            "Rolling back the create table procedure, table: {}",
+            self.table_id()
+        );
+
+        self.on_rollback().await.map_err(ProcedureError::external)
+    }
+
     fn dump(&self) -> ProcedureResult<String> {
         serde_json::to_string(&self.creator.data).context(ToJsonSnafu)
     }
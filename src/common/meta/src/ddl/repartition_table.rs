@@ -0,0 +1,1105 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use api::v1::region::region_request::Body as PbRegionRequest;
+use api::v1::region::{
+    DropRequest as PbDropRegionRequest, InsertRequest as PbInsertRegionRequest,
+    InsertRequests as PbInsertRegionRequests, RegionRequest, RegionRequestHeader,
+};
+use api::v1::{ColumnSchema, QueryContext as PbQueryContext, Rows, SnapshotSequences};
+use async_trait::async_trait;
+use common_catalog::consts::MITO_ENGINE;
+use common_error::ext::{BoxedError, ErrorExt};
+use common_error::status_code::StatusCode;
+use common_procedure::error::{
+    Error as ProcedureError, ExternalSnafu, FromJsonSnafu, Result as ProcedureResult, ToJsonSnafu,
+};
+use common_procedure::{Context as ProcedureContext, LockKey, Procedure, Status};
+use common_query::request::QueryRequest;
+use common_recordbatch::{RecordBatch, SendableRecordBatchStream};
+use common_telemetry::tracing_context::TracingContext;
+use common_telemetry::{debug, info, warn};
+use common_time::timestamp::TimeUnit;
+use datafusion_common::Column;
+use datafusion_expr::logical_plan::builder::table_scan;
+use datafusion_expr::{Expr as DfExpr, LogicalPlan};
+use datatypes::schema::Schema;
+use datatypes::value::{timestamp_to_scalar_value, Value};
+use futures::future::join_all;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use snafu::{ensure, OptionExt, ResultExt};
+use store_api::mito_engine_options::APPEND_MODE_KEY;
+use store_api::region_request::READONLY_KEY;
+use store_api::storage::{RegionId, RegionNumber, ScanHints, SequenceNumber, SCAN_HINTS_KEY};
+use strum::AsRefStr;
+use table::metadata::TableId;
+use table::requests::REPARTITIONING_KEY;
+use table::table_reference::TableReference;
+
+use crate::cache_invalidator::Context;
+use crate::ddl::create_table_template::{build_template, CreateRequestBuilder};
+use crate::ddl::utils::{
+    add_peer_context_if_needed, convert_region_routes_to_detecting_regions, handle_retry_error,
+    region_storage_path,
+};
+use crate::ddl::{DdlContext, TableMetadata};
+use crate::error::{
+    self, BuildRegionScanPlanSnafu, ConvertRawTableInfoSnafu, ReadRegionDataSnafu, Result,
+    TableInfoNotFoundSnafu, TableRouteNotFoundSnafu, UnexpectedLogicalRouteTableSnafu,
+    UnsupportedSnafu,
+};
+use crate::instruction::CacheIdent;
+use crate::key::datanode_table::RegionInfo;
+use crate::key::table_info::TableInfoValue;
+use crate::key::table_route::TableRouteValue;
+use crate::key::DeserializedValueWithBytes;
+use crate::lock_key::{CatalogLock, SchemaLock, TableLock};
+use crate::metrics;
+use crate::peer::Peer;
+use crate::region_keeper::OperatingRegionGuard;
+use crate::rpc::ddl::RepartitionTableTask;
+use crate::rpc::router::{
+    find_leader_regions, find_leaders, find_region_leader, operating_leader_regions, RegionRoute,
+};
+
+/// The default max number of rows to copy into the new regions per second.
+pub const DEFAULT_REPARTITION_ROWS_PER_SECOND: u64 = 100_000;
+/// The default max number of rows to copy from an old region in a step.
+pub const DEFAULT_REPARTITION_ROWS_PER_STEP: usize = 10_000;
+/// Key of the query context extension that overrides the max number of rows to copy
+/// per second when repartitioning a table, zero means no limit.
+pub const REPARTITION_ROWS_PER_SECOND_KEY: &str = "repartition_rows_per_second";
+
+/// Repartitions a table into a new set of regions.
+///
+/// The table stays writable while the rows of the old regions are copied into the new
+/// regions, only deletes are rejected by its `repartitioning` option. Each old region
+/// is copied from a snapshot, page by page in the order of the time index. The rows
+/// written after the snapshot are caught up afterwards, then the table is made
+/// read-only shortly to catch up the last rows before it's switched to the new regions.
+/// Reads are served by the old regions until the switch. The old regions are dropped
+/// at last.
+///
+/// The progress is persisted after each page, so the procedure resumes from the
+/// timestamp it stopped at after a restart. Copying rows again is idempotent as the
+/// rows with the same primary key and timestamp are deduplicated. If the procedure
+/// fails before the switch, the new regions are dropped and the table options are
+/// restored.
+pub struct RepartitionTableProcedure {
+    context: DdlContext,
+    data: RepartitionTableData,
+    /// The guards of the new regions.
+    opening_regions: Vec<OperatingRegionGuard>,
+}
+
+impl RepartitionTableProcedure {
+    pub const TYPE_NAME: &'static str = "metasrv-procedure::RepartitionTable";
+
+    pub fn new(task: RepartitionTableTask, context: DdlContext) -> Self {
+        Self {
+            context,
+            data: RepartitionTableData::new(task),
+            opening_regions: vec![],
+        }
+    }
+
+    pub fn from_json(json: &str, context: DdlContext) -> ProcedureResult<Self> {
+        let data = serde_json::from_str(json).context(FromJsonSnafu)?;
+
+        Ok(Self {
+            context,
+            data,
+            opening_regions: vec![],
+        })
+    }
+
+    /// Returns the state of the procedure.
+    pub fn state(&self) -> &RepartitionTableState {
+        &self.data.state
+    }
+
+    /// Returns the number of old regions whose rows have been copied.
+    pub fn copied_regions(&self) -> usize {
+        self.data.copied_regions
+    }
+
+    /// Returns the cursors to copy the old regions.
+    pub fn cursors(&self) -> &[RegionCopyCursor] {
+        &self.data.cursors
+    }
+
+    async fn get_table_metadata(
+        &self,
+    ) -> Result<(
+        DeserializedValueWithBytes<TableInfoValue>,
+        DeserializedValueWithBytes<TableRouteValue>,
+    )> {
+        let table_id = self.data.table_id();
+        let (table_info_value, table_route_value) = self
+            .context
+            .table_metadata_manager
+            .get_full_table_info(table_id)
+            .await?;
+
+        let table_info_value = table_info_value.with_context(|| TableInfoNotFoundSnafu {
+            table: self.data.table_ref().to_string(),
+        })?;
+        let table_route_value = table_route_value.context(TableRouteNotFoundSnafu { table_id })?;
+
+        Ok((table_info_value, table_route_value))
+    }
+
+    /// Checks whether the table can be repartitioned and allocates the new regions.
+    ///
+    /// Abort(non-retry):
+    /// - The table is a logical table, or isn't a mito table.
+    /// - The table is in append mode or read-only.
+    async fn on_prepare(&mut self) -> Result<Status> {
+        let (table_info_value, table_route_value) = self.get_table_metadata().await?;
+        ensure!(
+            table_route_value.is_physical(),
+            UnexpectedLogicalRouteTableSnafu {
+                err_msg: format!(
+                    "{} is a non-physical TableRouteValue.",
+                    self.data.table_ref()
+                ),
+            }
+        );
+
+        let table_meta = &table_info_value.table_info.meta;
+        ensure!(
+            table_meta.engine == MITO_ENGINE,
+            UnsupportedSnafu {
+                operation: format!("repartition table of engine {}", table_meta.engine),
+            }
+        );
+        // The rows copied before a restart would be duplicated in append mode.
+        ensure!(
+            table_meta
+                .options
+                .extra_options
+                .get(APPEND_MODE_KEY)
+                .is_none_or(|v| v != "true"),
+            UnsupportedSnafu {
+                operation: "repartition table in append mode",
+            }
+        );
+        // The table is made read-only before the switch, then writable again.
+        ensure!(
+            !table_meta.options.readonly(),
+            UnsupportedSnafu {
+                operation: "repartition read-only table",
+            }
+        );
+
+        let old_region_routes = table_route_value.region_routes()?.clone();
+        let first_region_number = old_region_routes
+            .iter()
+            .map(|route| route.region.id.region_number())
+            .max()
+            .map_or(0, |region_number| region_number + 1);
+        let TableMetadata {
+            table_route,
+            region_wal_options,
+            ..
+        } = self
+            .context
+            .table_metadata_allocator
+            .create_repartition(
+                self.data.table_id(),
+                first_region_number,
+                &self.data.task.create_table,
+            )
+            .await?;
+
+        self.data.old_region_routes = old_region_routes;
+        self.data.new_region_routes = table_route.region_routes;
+        self.data.new_region_wal_options = region_wal_options;
+        self.data.state = RepartitionTableState::CreateRegions;
+
+        Ok(Status::executing(true))
+    }
+
+    /// Registers the new regions as operating regions if they aren't registered yet.
+    fn register_opening_regions(&mut self) -> Result<()> {
+        if !self.opening_regions.is_empty() {
+            return Ok(());
+        }
+
+        for (region_id, datanode_id) in operating_leader_regions(&self.data.new_region_routes) {
+            let guard = self
+                .context
+                .memory_region_keeper
+                .register(datanode_id, region_id)
+                .context(error::RegionOperatingRaceSnafu {
+                    region_id,
+                    peer_id: datanode_id,
+                })?;
+            self.opening_regions.push(guard);
+        }
+
+        Ok(())
+    }
+
+    /// Creates the new regions on datanodes.
+    async fn on_create_regions(&mut self) -> Result<Status> {
+        self.register_opening_regions()?;
+
+        let table_id = self.data.table_id();
+        let create_table = &self.data.task.create_table.create_table;
+        let request_builder = CreateRequestBuilder::new(build_template(create_table)?, None);
+        let storage_path =
+            region_storage_path(&create_table.catalog_name, &create_table.schema_name);
+
+        let region_routes = &self.data.new_region_routes;
+        let mut create_region_tasks = Vec::with_capacity(region_routes.len());
+        for datanode in find_leaders(region_routes) {
+            let requester = self.context.node_manager.datanode(&datanode).await;
+
+            for region_number in find_leader_regions(region_routes, &datanode) {
+                let region_id = RegionId::new(table_id, region_number);
+                let create_region_request = request_builder.build_one(
+                    region_id,
+                    storage_path.clone(),
+                    &self.data.new_region_wal_options,
+                )?;
+                let request = RegionRequest {
+                    header: Some(new_region_request_header()),
+                    body: Some(PbRegionRequest::Create(create_region_request)),
+                };
+
+                let datanode = datanode.clone();
+                let requester = requester.clone();
+                create_region_tasks.push(async move {
+                    requester
+                        .handle(request)
+                        .await
+                        .map_err(add_peer_context_if_needed(datanode))
+                });
+            }
+        }
+
+        join_all(create_region_tasks)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        self.data.state = RepartitionTableState::BlockDeletes;
+
+        Ok(Status::executing(true))
+    }
+
+    /// Rejects deletes to the table, then takes the snapshots of the old regions to copy.
+    ///
+    /// Rows written after the snapshots are caught up by their sequences, which can't
+    /// tell the rows deleted after the snapshots.
+    async fn on_block_deletes(&mut self) -> Result<Status> {
+        self.update_table_options(|options| {
+            options.insert(REPARTITIONING_KEY.to_string(), true.to_string());
+        })
+        .await?;
+
+        let mut cursors = Vec::with_capacity(self.data.old_region_routes.len());
+        for old_region_route in &self.data.old_region_routes {
+            let (region_id, peer) = self.data.old_region(old_region_route)?;
+            let sequence = self.region_sequence(&peer, region_id).await?;
+            cursors.push(RegionCopyCursor {
+                sequence,
+                start_timestamp: None,
+            });
+        }
+        self.data.cursors = cursors;
+        self.data.state = RepartitionTableState::CopyData;
+
+        Ok(Status::executing(true))
+    }
+
+    /// Copies a page of rows of the next old region into the new regions, the progress
+    /// is persisted once the page is copied.
+    ///
+    /// The page is the first `rows_per_step` rows from the cursor in the order of the
+    /// time index, its rows of the last timestamp are copied in the next page as they
+    /// may be incomplete. If all rows of the page have the same timestamp, all rows of
+    /// that timestamp are copied at once.
+    async fn on_copy_data(&mut self, ctx: &ProcedureContext) -> Result<Status> {
+        let total = self.data.old_region_routes.len();
+        let Some(old_region_route) = self.data.old_region_routes.get(self.data.copied_regions)
+        else {
+            info!(
+                "Copied {} rows of {} regions into the new regions of table {}",
+                self.data.copied_rows,
+                total,
+                self.data.table_ref()
+            );
+            self.data.caught_up_regions = 0;
+            self.data.state = RepartitionTableState::CatchUp;
+            return Ok(Status::executing(true));
+        };
+
+        let (region_id, peer) = self.data.old_region(old_region_route)?;
+        let cursor = self.data.cursors[self.data.copied_regions].clone();
+        let (time_index, unit) = self.data.time_index()?;
+        let time_index_expr = DfExpr::Column(Column::from_name(&time_index));
+        let timestamp_expr =
+            |timestamp| DfExpr::Literal(timestamp_to_scalar_value(unit, Some(timestamp)));
+        let header = self
+            .data
+            .scan_header(region_id, cursor.sequence, ScanHints::default());
+        let rows_per_step = self.data.task.rows_per_step.max(1);
+
+        let started = Instant::now();
+        let filter = cursor
+            .start_timestamp
+            .map(|timestamp| time_index_expr.clone().gt_eq(timestamp_expr(timestamp)));
+        let plan = self
+            .data
+            .scan_plan(region_id, filter, Some(rows_per_step))?;
+        let stream = self
+            .query_region(&peer, region_id, header.clone(), plan)
+            .await?;
+        let batches = common_recordbatch::util::collect(stream)
+            .await
+            .context(ReadRegionDataSnafu { region_id })?;
+        let num_rows = batches.iter().map(|batch| batch.num_rows()).sum::<usize>();
+
+        let mut copied_rows = 0;
+        if num_rows < rows_per_step {
+            for batch in batches {
+                copied_rows += self.copy_batch(batch).await?;
+            }
+            debug!(
+                "Copied the rows of region {region_id} into the new regions, cursor: {cursor:?}"
+            );
+            self.data.copied_regions += 1;
+        } else {
+            let mut timestamps = Vec::with_capacity(batches.len());
+            for batch in &batches {
+                timestamps.push(batch_timestamps(batch, &time_index)?);
+            }
+            // Safety: the page isn't empty.
+            let last_timestamp = *timestamps.iter().flatten().last().unwrap();
+            for (batch, timestamps) in batches.into_iter().zip(timestamps) {
+                // Rows are sorted by the time index.
+                let len = timestamps.partition_point(|timestamp| *timestamp < last_timestamp);
+                let batch = batch
+                    .slice(0, len)
+                    .context(ReadRegionDataSnafu { region_id })?;
+                copied_rows += self.copy_batch(batch).await?;
+            }
+
+            let next_timestamp = if copied_rows > 0 {
+                Some(last_timestamp)
+            } else {
+                let filter = time_index_expr.eq(timestamp_expr(last_timestamp));
+                let plan = self.data.scan_plan(region_id, Some(filter), None)?;
+                let stream = self.query_region(&peer, region_id, header, plan).await?;
+                copied_rows += self.copy_stream(region_id, stream).await?;
+                last_timestamp.checked_add(1)
+            };
+            match next_timestamp {
+                Some(timestamp) => {
+                    self.data.cursors[self.data.copied_regions].start_timestamp = Some(timestamp)
+                }
+                None => self.data.copied_regions += 1,
+            }
+        }
+
+        self.data.copied_rows += copied_rows;
+        ctx.report_progress(format!(
+            "copied {}/{} regions, {} rows",
+            self.data.copied_regions, total, self.data.copied_rows
+        ));
+        throttle(started, copied_rows, self.data.task.rows_per_second).await;
+
+        Ok(Status::executing(true))
+    }
+
+    /// Copies the rows written to the next old region since its last snapshot into the
+    /// new regions, then moves the snapshot of the region forward.
+    async fn on_catch_up(&mut self, ctx: &ProcedureContext) -> Result<Status> {
+        let total = self.data.old_region_routes.len();
+        let Some(old_region_route) = self.data.old_region_routes.get(self.data.caught_up_regions)
+        else {
+            self.data.state = match self.data.state {
+                RepartitionTableState::CatchUp => RepartitionTableState::SetReadonly,
+                _ => RepartitionTableState::SwitchRegions,
+            };
+            return Ok(Status::executing(true));
+        };
+
+        let (region_id, peer) = self.data.old_region(old_region_route)?;
+        let cursor = &self.data.cursors[self.data.caught_up_regions];
+        let sequence = self.region_sequence(&peer, region_id).await?;
+        let scan_hints = ScanHints {
+            newer_than: Some(cursor.sequence),
+            ..Default::default()
+        };
+        let header = self.data.scan_header(region_id, sequence, scan_hints);
+        let plan = self.data.scan_plan(region_id, None, None)?;
+        let stream = self.query_region(&peer, region_id, header, plan).await?;
+        let copied_rows = self.copy_stream(region_id, stream).await?;
+        debug!(
+            "Caught up {copied_rows} rows of region {region_id} from sequence {} to {sequence}",
+            cursor.sequence
+        );
+
+        self.data.cursors[self.data.caught_up_regions].sequence = sequence;
+        self.data.caught_up_regions += 1;
+        self.data.copied_rows += copied_rows;
+        ctx.report_progress(format!(
+            "caught up {}/{} regions, {} rows",
+            self.data.caught_up_regions, total, self.data.copied_rows
+        ));
+
+        Ok(Status::executing(true))
+    }
+
+    /// Rejects writes to the table, so the last rows written to the old regions can be
+    /// caught up before the switch.
+    async fn on_set_readonly(&mut self) -> Result<Status> {
+        self.update_table_options(|options| {
+            options.insert(READONLY_KEY.to_string(), true.to_string());
+        })
+        .await?;
+
+        self.data.caught_up_regions = 0;
+        self.data.state = RepartitionTableState::FinalCatchUp;
+
+        Ok(Status::executing(true))
+    }
+
+    /// Returns the latest committed sequence of the region.
+    async fn region_sequence(&self, peer: &Peer, region_id: RegionId) -> Result<SequenceNumber> {
+        self.context
+            .node_manager
+            .datanode(peer)
+            .await
+            .region_sequence(region_id)
+            .await
+            .map_err(add_peer_context_if_needed(peer.clone()))?
+            .with_context(|| error::UnexpectedSnafu {
+                err_msg: format!("Region {region_id} isn't served by datanode {peer}"),
+            })
+    }
+
+    async fn query_region(
+        &self,
+        peer: &Peer,
+        region_id: RegionId,
+        header: RegionRequestHeader,
+        plan: LogicalPlan,
+    ) -> Result<SendableRecordBatchStream> {
+        self.context
+            .node_manager
+            .datanode(peer)
+            .await
+            .handle_query(QueryRequest {
+                header: Some(header),
+                region_id,
+                plan,
+            })
+            .await
+            .map_err(add_peer_context_if_needed(peer.clone()))
+    }
+
+    /// Writes the rows of the stream into the new regions, returns the number of rows.
+    async fn copy_stream(
+        &self,
+        region_id: RegionId,
+        mut stream: SendableRecordBatchStream,
+    ) -> Result<u64> {
+        let mut copied_rows = 0;
+        while let Some(batch) = stream.next().await {
+            let batch = batch.context(ReadRegionDataSnafu { region_id })?;
+            copied_rows += self.copy_batch(batch).await?;
+        }
+
+        Ok(copied_rows)
+    }
+
+    /// Writes the rows of the batch into the new regions, returns the number of rows.
+    async fn copy_batch(&self, batch: RecordBatch) -> Result<u64> {
+        let num_rows = batch.num_rows();
+        if num_rows == 0 {
+            return Ok(0);
+        }
+
+        let rows = Rows {
+            schema: self.data.column_schemas(&batch.schema)?,
+            rows: api::helper::vectors_to_rows(batch.columns().iter(), num_rows),
+        };
+        let region_rows = self.context.region_row_splitter.split(
+            self.data.table_id(),
+            &self.data.new_region_routes,
+            rows,
+        )?;
+        self.put_rows(region_rows).await?;
+
+        Ok(num_rows as u64)
+    }
+
+    /// Writes the rows into the new regions.
+    async fn put_rows(&self, region_rows: HashMap<RegionNumber, Rows>) -> Result<()> {
+        let table_id = self.data.table_id();
+        let mut requests: HashMap<Peer, Vec<PbInsertRegionRequest>> = HashMap::new();
+        for (region_number, rows) in region_rows {
+            let peer = find_region_leader(&self.data.new_region_routes, region_number)
+                .context(error::NoLeaderSnafu { table_id })?;
+            requests
+                .entry(peer)
+                .or_default()
+                .push(PbInsertRegionRequest {
+                    region_id: RegionId::new(table_id, region_number).as_u64(),
+                    rows: Some(rows),
+                });
+        }
+
+        let put_tasks = requests.into_iter().map(|(peer, requests)| async move {
+            let request = RegionRequest {
+                header: Some(new_region_request_header()),
+                body: Some(PbRegionRequest::Inserts(PbInsertRegionRequests {
+                    requests,
+                })),
+            };
+            self.context
+                .node_manager
+                .datanode(&peer)
+                .await
+                .handle(request)
+                .await
+                .map_err(add_peer_context_if_needed(peer))
+        });
+
+        join_all(put_tasks)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(())
+    }
+
+    /// Switches the table to the new regions and makes it writable again.
+    async fn on_switch_regions(&mut self) -> Result<Status> {
+        let (table_info_value, table_route_value) = self.get_table_metadata().await?;
+        // The table may be switched already before the procedure restarts.
+        let switched = table_route_value
+            .region_routes()?
+            .iter()
+            .map(|route| route.region.id)
+            .eq(self
+                .data
+                .new_region_routes
+                .iter()
+                .map(|route| route.region.id));
+        if !switched {
+            self.update_table_partitions(&table_info_value, &table_route_value)
+                .await?;
+        }
+        self.invalidate_table_cache().await?;
+
+        self.context
+            .register_failure_detectors(convert_region_routes_to_detecting_regions(
+                &self.data.new_region_routes,
+            ))
+            .await;
+        self.context
+            .deregister_failure_detectors(convert_region_routes_to_detecting_regions(
+                &self.data.old_region_routes,
+            ))
+            .await;
+        self.opening_regions.clear();
+        info!(
+            "Switched table {} from {} regions to {} regions",
+            self.data.table_ref(),
+            self.data.old_region_routes.len(),
+            self.data.new_region_routes.len()
+        );
+
+        self.data.state = RepartitionTableState::DropRegions;
+
+        Ok(Status::executing(true))
+    }
+
+    /// Updates the partitions and regions of the table in one transaction.
+    async fn update_table_partitions(
+        &self,
+        table_info_value: &DeserializedValueWithBytes<TableInfoValue>,
+        table_route_value: &DeserializedValueWithBytes<TableRouteValue>,
+    ) -> Result<()> {
+        let table_info = &table_info_value.table_info;
+        let mut new_table_info = table_info.clone();
+        new_table_info.meta.partition_key_indices.clone_from(
+            &self
+                .data
+                .task
+                .create_table
+                .table_info
+                .meta
+                .partition_key_indices,
+        );
+        new_table_info.meta.region_numbers = self
+            .data
+            .new_region_routes
+            .iter()
+            .map(|route| route.region.id.region_number())
+            .collect();
+        let options = &mut new_table_info.meta.options.extra_options;
+        options.remove(READONLY_KEY);
+        options.remove(REPARTITIONING_KEY);
+
+        let region_info = RegionInfo {
+            engine: table_info.meta.engine.clone(),
+            region_storage_path: region_storage_path(
+                &table_info.catalog_name,
+                &table_info.schema_name,
+            ),
+            region_options: new_table_info.to_region_options(),
+            region_wal_options: HashMap::new(),
+        };
+        self.context
+            .table_metadata_manager
+            .update_table_partitions(
+                table_info_value,
+                new_table_info,
+                region_info,
+                table_route_value,
+                self.data.new_region_routes.clone(),
+                &self.data.new_region_wal_options,
+            )
+            .await
+    }
+
+    /// Drops the old regions on datanodes.
+    async fn on_drop_regions(&mut self) -> Result<Status> {
+        let region_routes = &self.data.old_region_routes;
+        self.drop_regions(region_routes).await?;
+
+        self.context.leader_region_registry.batch_delete(
+            operating_leader_regions(region_routes)
+                .into_iter()
+                .map(|(region_id, _)| region_id),
+        );
+
+        Ok(Status::done())
+    }
+
+    /// Drops the regions on datanodes, regions that don't exist are skipped.
+    async fn drop_regions(&self, region_routes: &[RegionRoute]) -> Result<()> {
+        let table_id = self.data.table_id();
+
+        let mut drop_region_tasks = Vec::with_capacity(region_routes.len());
+        for datanode in find_leaders(region_routes) {
+            let requester = self.context.node_manager.datanode(&datanode).await;
+
+            for region_number in find_leader_regions(region_routes, &datanode) {
+                let request = RegionRequest {
+                    header: Some(new_region_request_header()),
+                    body: Some(PbRegionRequest::Drop(PbDropRegionRequest {
+                        region_id: RegionId::new(table_id, region_number).as_u64(),
+                        fast_path: false,
+                    })),
+                };
+
+                let datanode = datanode.clone();
+                let requester = requester.clone();
+                drop_region_tasks.push(async move {
+                    if let Err(err) = requester.handle(request).await {
+                        if err.status_code() != StatusCode::RegionNotFound {
+                            return Err(add_peer_context_if_needed(datanode)(err));
+                        }
+                    }
+                    Ok(())
+                });
+            }
+        }
+
+        join_all(drop_region_tasks)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(())
+    }
+
+    /// Drops the new regions and restores the options of the table, the table is still
+    /// served by the old regions.
+    async fn on_rollback(&mut self) -> Result<()> {
+        self.update_table_options(|options| {
+            options.remove(READONLY_KEY);
+            options.remove(REPARTITIONING_KEY);
+        })
+        .await?;
+        self.drop_regions(&self.data.new_region_routes).await?;
+
+        self.opening_regions.clear();
+        Ok(())
+    }
+
+    /// Updates the options of the table by `f`, then invalidates the table cache.
+    async fn update_table_options(
+        &self,
+        f: impl FnOnce(&mut HashMap<String, String>),
+    ) -> Result<()> {
+        let (table_info_value, _) = self.get_table_metadata().await?;
+
+        let mut new_table_info = table_info_value.table_info.clone();
+        f(&mut new_table_info.meta.options.extra_options);
+        // The options may be updated already before the procedure restarts.
+        if new_table_info != table_info_value.table_info {
+            self.context
+                .table_metadata_manager
+                .update_table_info(&table_info_value, None, new_table_info)
+                .await?;
+        }
+
+        self.invalidate_table_cache().await
+    }
+
+    async fn invalidate_table_cache(&self) -> Result<()> {
+        self.context
+            .cache_invalidator
+            .invalidate(
+                &Context::default(),
+                &[
+                    CacheIdent::TableId(self.data.table_id()),
+                    CacheIdent::TableName(self.data.task.table_name()),
+                ],
+            )
+            .await
+    }
+}
+
+#[async_trait]
+impl Procedure for RepartitionTableProcedure {
+    fn type_name(&self) -> &str {
+        Self::TYPE_NAME
+    }
+
+    fn recover(&mut self) -> ProcedureResult<()> {
+        // Only registers the new regions if they may be created but not switched to.
+        if matches!(
+            self.data.state,
+            RepartitionTableState::CreateRegions
+                | RepartitionTableState::BlockDeletes
+                | RepartitionTableState::CopyData
+                | RepartitionTableState::CatchUp
+                | RepartitionTableState::SetReadonly
+                | RepartitionTableState::FinalCatchUp
+                | RepartitionTableState::SwitchRegions
+        ) {
+            self.register_opening_regions()
+                .map_err(BoxedError::new)
+                .context(ExternalSnafu {
+                    clean_poisons: false,
+                })?;
+        }
+
+        Ok(())
+    }
+
+    async fn execute(&mut self, ctx: &ProcedureContext) -> ProcedureResult<Status> {
+        let state = &self.data.state;
+
+        let _timer = metrics::METRIC_META_PROCEDURE_REPARTITION_TABLE
+            .with_label_values(&[state.as_ref()])
+            .start_timer();
+
+        match state {
+            RepartitionTableState::Prepare => self.on_prepare().await,
+            RepartitionTableState::CreateRegions => self.on_create_regions().await,
+            RepartitionTableState::BlockDeletes => self.on_block_deletes().await,
+            RepartitionTableState::CopyData => self.on_copy_data(ctx).await,
+            RepartitionTableState::CatchUp | RepartitionTableState::FinalCatchUp => {
+                self.on_catch_up(ctx).await
+            }
+            RepartitionTableState::SetReadonly => self.on_set_readonly().await,
+            RepartitionTableState::SwitchRegions => self.on_switch_regions().await,
+            RepartitionTableState::DropRegions => self.on_drop_regions().await,
+        }
+        .map_err(handle_retry_error)
+    }
+
+    fn rollback_supported(&self) -> bool {
+        // The table is served by the old regions until it's switched to the new regions.
+        matches!(
+            self.data.state,
+            RepartitionTableState::CreateRegions
+                | RepartitionTableState::BlockDeletes
+                | RepartitionTableState::CopyData
+                | RepartitionTableState::CatchUp
+                | RepartitionTableState::SetReadonly
+                | RepartitionTableState::FinalCatchUp
+        )
+    }
+
+    async fn rollback(&mut self, _: &ProcedureContext) -> ProcedureResult<()> {
+        warn!(
+            "Rolling back the repartition table procedure, table: {}",
+            self.data.table_ref()
+        );
+
+        self.on_rollback().await.map_err(ProcedureError::external)
+    }
+
+    fn dump(&self) -> ProcedureResult<String> {
+        serde_json::to_string(&self.data).context(ToJsonSnafu)
+    }
+
+    fn lock_key(&self) -> LockKey {
+        let table_ref = self.data.table_ref();
+
+        LockKey::new(vec![
+            CatalogLock::Read(table_ref.catalog).into(),
+            SchemaLock::read(table_ref.catalog, table_ref.schema).into(),
+            TableLock::Write(self.data.table_id()).into(),
+        ])
+    }
+}
+
+/// Sleeps until the rate of copying `copied_rows` rows since `started` is no more than
+/// `rows_per_second`, zero means no limit.
+async fn throttle(started: Instant, copied_rows: u64, rows_per_second: u64) {
+    if rows_per_second == 0 {
+        return;
+    }
+
+    let expected = Duration::from_secs_f64(copied_rows as f64 / rows_per_second as f64);
+    let elapsed = started.elapsed();
+    if expected > elapsed {
+        tokio::time::sleep(expected - elapsed).await;
+    }
+}
+
+/// Returns the timestamps of the `time_index` column of the batch.
+fn batch_timestamps(batch: &RecordBatch, time_index: &str) -> Result<Vec<i64>> {
+    let column = batch
+        .column_by_name(time_index)
+        .with_context(|| error::UnexpectedSnafu {
+            err_msg: format!("Time index {time_index} isn't in the scanned rows"),
+        })?;
+    (0..column.len())
+        .map(|i| match column.get(i) {
+            Value::Timestamp(timestamp) => Ok(timestamp.value()),
+            value => error::UnexpectedSnafu {
+                err_msg: format!("Invalid value of time index {time_index}: {value:?}"),
+            }
+            .fail(),
+        })
+        .collect()
+}
+
+fn new_region_request_header() -> RegionRequestHeader {
+    RegionRequestHeader {
+        tracing_context: TracingContext::from_current_span().to_w3c(),
+        ..Default::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, AsRefStr, PartialEq)]
+pub enum RepartitionTableState {
+    /// Checks the table and allocates the new regions.
+    Prepare,
+    /// Creates the new regions on datanodes.
+    CreateRegions,
+    /// Rejects deletes to the table and takes the snapshots of the old regions.
+    BlockDeletes,
+    /// Copies the rows of the old regions into the new regions.
+    CopyData,
+    /// Copies the rows written during the copy into the new regions.
+    CatchUp,
+    /// Rejects writes to the table.
+    SetReadonly,
+    /// Copies the rows written during the last catch-up into the new regions.
+    FinalCatchUp,
+    /// Switches the table to the new regions.
+    SwitchRegions,
+    /// Drops the old regions on datanodes.
+    DropRegions,
+}
+
+/// Where to resume copying an old region.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RegionCopyCursor {
+    /// The snapshot of the region to copy, the rows written after it are caught up.
+    pub sequence: SequenceNumber,
+    /// The timestamp to copy the rows from, in the unit of the time index. `None`
+    /// stands for the first row.
+    pub start_timestamp: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepartitionTableData {
+    state: RepartitionTableState,
+    task: RepartitionTableTask,
+    /// The regions to replace, empty stands for not loaded yet.
+    old_region_routes: Vec<RegionRoute>,
+    /// The regions to create, empty stands for not allocated yet.
+    new_region_routes: Vec<RegionRoute>,
+    /// The encoded wal options of the new regions.
+    new_region_wal_options: HashMap<RegionNumber, String>,
+    /// The cursors of the old regions, empty stands for not taken yet.
+    cursors: Vec<RegionCopyCursor>,
+    /// The number of old regions whose rows have been copied.
+    copied_regions: usize,
+    /// The number of old regions caught up in the current catch-up.
+    caught_up_regions: usize,
+    /// The number of rows copied into the new regions.
+    copied_rows: u64,
+}
+
+impl RepartitionTableData {
+    fn new(task: RepartitionTableTask) -> Self {
+        Self {
+            state: RepartitionTableState::Prepare,
+            task,
+            old_region_routes: vec![],
+            new_region_routes: vec![],
+            new_region_wal_options: HashMap::new(),
+            cursors: vec![],
+            copied_regions: 0,
+            caught_up_regions: 0,
+            copied_rows: 0,
+        }
+    }
+
+    fn table_ref(&self) -> TableReference {
+        self.task.table_ref()
+    }
+
+    fn table_id(&self) -> TableId {
+        self.task.table_id
+    }
+
+    /// Returns the id and the leader of the old region.
+    fn old_region(&self, region_route: &RegionRoute) -> Result<(RegionId, Peer)> {
+        let peer = region_route
+            .leader_peer
+            .clone()
+            .context(error::NoLeaderSnafu {
+                table_id: self.table_id(),
+            })?;
+
+        Ok((region_route.region.id, peer))
+    }
+
+    fn schema(&self) -> Result<Schema> {
+        Schema::try_from(self.task.create_table.table_info.meta.schema.clone())
+            .context(ConvertRawTableInfoSnafu)
+    }
+
+    /// Returns the name and the unit of the time index.
+    fn time_index(&self) -> Result<(String, TimeUnit)> {
+        let schema = self.schema()?;
+        let time_index = schema
+            .timestamp_column()
+            .and_then(|column| {
+                let unit = column.data_type.as_timestamp()?.unit();
+                Some((column.name.clone(), unit))
+            })
+            .with_context(|| error::UnexpectedSnafu {
+                err_msg: format!("Table {} has no time index", self.table_ref()),
+            })?;
+
+        Ok(time_index)
+    }
+
+    /// Returns the plan to scan the rows of the old region matching the `filter`. If
+    /// `limit` is set, only the first `limit` rows in the order of the time index are
+    /// returned.
+    fn scan_plan(
+        &self,
+        region_id: RegionId,
+        filter: Option<DfExpr>,
+        limit: Option<usize>,
+    ) -> Result<LogicalPlan> {
+        let table_info = &self.task.create_table.table_info;
+        let schema = self.schema()?;
+        let (time_index, _) = self.time_index()?;
+
+        let mut builder = table_scan(Some(table_info.name.as_str()), schema.arrow_schema(), None);
+        if let Some(filter) = filter {
+            builder = builder.and_then(|builder| builder.filter(filter));
+        }
+        if let Some(limit) = limit {
+            let sort_expr = DfExpr::Column(Column::from_name(time_index)).sort(true, false);
+            builder = builder
+                .and_then(|builder| builder.sort(vec![sort_expr]))
+                .and_then(|builder| builder.limit(0, Some(limit)));
+        }
+
+        builder
+            .and_then(|builder| builder.build())
+            .context(BuildRegionScanPlanSnafu { region_id })
+    }
+
+    /// Returns the header to scan the old region at the snapshot `sequence` with the
+    /// `scan_hints`.
+    fn scan_header(
+        &self,
+        region_id: RegionId,
+        sequence: SequenceNumber,
+        scan_hints: ScanHints,
+    ) -> RegionRequestHeader {
+        let table_ref = self.table_ref();
+        let mut extensions = HashMap::new();
+        if !scan_hints.is_empty() {
+            extensions.insert(SCAN_HINTS_KEY.to_string(), scan_hints.to_string());
+        }
+
+        RegionRequestHeader {
+            query_context: Some(PbQueryContext {
+                current_catalog: table_ref.catalog.to_string(),
+                current_schema: table_ref.schema.to_string(),
+                extensions,
+                snapshot_seqs: Some(SnapshotSequences {
+                    snapshot_seqs: HashMap::from([(region_id.as_u64(), sequence)]),
+                }),
+                ..Default::default()
+            }),
+            ..new_region_request_header()
+        }
+    }
+
+    /// Returns the schema of the rows to write from the schema of the scanned batches.
+    fn column_schemas(&self, schema: &datatypes::schema::SchemaRef) -> Result<Vec<ColumnSchema>> {
+        let column_defs = &self.task.create_table.create_table.column_defs;
+        schema
+            .column_schemas()
+            .iter()
+            .map(|column_schema| {
+                let column_def = column_defs
+                    .iter()
+                    .find(|c| c.name == column_schema.name)
+                    .with_context(|| error::UnexpectedSnafu {
+                        err_msg: format!(
+                            "Column {} of the region isn't in table {}",
+                            column_schema.name,
+                            self.table_ref()
+                        ),
+                    })?;
+                Ok(ColumnSchema {
+                    column_name: column_def.name.clone(),
+                    datatype: column_def.data_type,
+                    semantic_type: column_def.semantic_type,
+                    datatype_extension: column_def.datatype_extension,
+                    options: column_def.options.clone(),
+                })
+            })
+            .collect()
+    }
+}
@@ -108,9 +108,12 @@ impl TableMetadataAllocator {
         allocate_region_wal_options(region_numbers, &self.wal_options_allocator, skip_wal)
     }
 
+    /// Creates the routes of the regions of the table, their region numbers start from
+    /// `first_region_number`.
     async fn create_table_route(
         &self,
         table_id: TableId,
+        first_region_number: RegionNumber,
         task: &CreateTableTask,
     ) -> Result<PhysicalTableRouteValue> {
         let regions = task.partitions.len();
@@ -128,7 +131,7 @@ impl TableMetadataAllocator {
             .enumerate()
             .map(|(i, partition)| {
                 let region = Region {
-                    id: RegionId::new(table_id, i as u32),
+                    id: RegionId::new(table_id, first_region_number + i as u32),
                     partition: Some(partition.clone().into()),
                     ..Default::default()
                 };
@@ -158,7 +161,7 @@ impl TableMetadataAllocator {
 
     pub async fn create(&self, task: &CreateTableTask) -> Result<TableMetadata> {
         let table_id = self.allocate_table_id(&task.create_table.table_id).await?;
-        let table_route = self.create_table_route(table_id, task).await?;
+        let table_route = self.create_table_route(table_id, 0, task).await?;
 
         let region_wal_options =
             self.create_wal_options(&table_route, task.table_info.meta.options.skip_wal)?;
@@ -174,6 +177,33 @@ impl TableMetadataAllocator {
             region_wal_options,
         })
     }
+
+    /// Allocates the new regions of the table `table_id` for repartitioning it. The new
+    /// region numbers start from `first_region_number` to keep apart from the old ones.
+    pub(crate) async fn create_repartition(
+        &self,
+        table_id: TableId,
+        first_region_number: RegionNumber,
+        task: &CreateTableTask,
+    ) -> Result<TableMetadata> {
+        let table_route = self
+            .create_table_route(table_id, first_region_number, task)
+            .await?;
+
+        let region_wal_options =
+            self.create_wal_options(&table_route, task.table_info.meta.options.skip_wal)?;
+
+        debug!(
+            "Allocated region wal options {:?} for repartitioning table {}",
+            region_wal_options, table_id
+        );
+
+        Ok(TableMetadata {
+            table_id,
+            table_route,
+            region_wal_options,
+        })
+    }
 }
 
 pub type PeerAllocatorRef = Arc<dyn PeerAllocator>;
@@ -14,6 +14,7 @@
 
 mod alter_logical_tables;
 mod alter_table;
+mod build_index;
 mod create_flow;
 mod create_logical_tables;
 mod create_table;
@@ -22,3 +23,4 @@ mod drop_database;
 mod drop_flow;
 mod drop_table;
 mod drop_view;
+mod repartition_table;
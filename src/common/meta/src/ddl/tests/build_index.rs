@@ -0,0 +1,171 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use api::region::RegionResponse;
+use api::v1::region::RegionRequest;
+use common_procedure::error::Result as ProcedureResult;
+use common_procedure::{
+    Context as ProcedureContext, ContextProvider, PoisonKey, Procedure, ProcedureId,
+    ProcedureState, Status,
+};
+use common_procedure_test::execute_procedure_until;
+use common_query::request::QueryRequest;
+use common_recordbatch::SendableRecordBatchStream;
+use store_api::storage::{RegionId, TableId};
+
+use crate::ddl::build_index::{BuildIndexProcedure, BuildIndexState};
+use crate::ddl::test_util::create_table::test_create_table_task;
+use crate::ddl::DdlContext;
+use crate::error::Result;
+use crate::key::table_route::TableRouteValue;
+use crate::node_manager::AffectedRows;
+use crate::peer::Peer;
+use crate::rpc::ddl::BuildIndexTask;
+use crate::rpc::router::{Region, RegionRoute};
+use crate::test_util::{new_ddl_context, MockDatanodeHandler, MockDatanodeManager};
+
+/// A datanode that indexes `region_number + 1` files of each region, and records the
+/// regions it builds indexes of.
+#[derive(Clone, Default)]
+struct BuildIndexDatanodeHandler {
+    built_regions: Arc<Mutex<Vec<(Peer, RegionId)>>>,
+}
+
+#[async_trait::async_trait]
+impl MockDatanodeHandler for BuildIndexDatanodeHandler {
+    async fn handle(&self, _peer: &Peer, _request: RegionRequest) -> Result<RegionResponse> {
+        unreachable!()
+    }
+
+    async fn handle_query(
+        &self,
+        _peer: &Peer,
+        _request: QueryRequest,
+    ) -> Result<SendableRecordBatchStream> {
+        unreachable!()
+    }
+
+    async fn build_index(&self, peer: &Peer, region_id: RegionId) -> Result<AffectedRows> {
+        self.built_regions
+            .lock()
+            .unwrap()
+            .push((peer.clone(), region_id));
+        Ok(region_id.region_number() as AffectedRows + 1)
+    }
+}
+
+/// A context provider that records the reported progress.
+#[derive(Default)]
+struct ProgressRecorder {
+    progresses: Mutex<Vec<String>>,
+}
+
+#[async_trait::async_trait]
+impl ContextProvider for ProgressRecorder {
+    async fn procedure_state(
+        &self,
+        _procedure_id: ProcedureId,
+    ) -> ProcedureResult<Option<ProcedureState>> {
+        Ok(None)
+    }
+
+    async fn try_put_poison(
+        &self,
+        _key: &PoisonKey,
+        _procedure_id: ProcedureId,
+    ) -> ProcedureResult<()> {
+        Ok(())
+    }
+
+    fn report_progress(&self, _procedure_id: ProcedureId, message: String) {
+        self.progresses.lock().unwrap().push(message);
+    }
+}
+
+/// Creates a table of 3 regions, the leader of region `n` is datanode `n % 2`.
+async fn prepare_table(table_id: TableId, ddl_context: &DdlContext) {
+    let task = test_create_table_task("foo", table_id);
+    let region_routes = (0..3)
+        .map(|region_number| RegionRoute {
+            region: Region::new_test(RegionId::new(table_id, region_number)),
+            leader_peer: Some(Peer::empty((region_number % 2) as u64)),
+            ..Default::default()
+        })
+        .collect();
+    ddl_context
+        .table_metadata_manager
+        .create_table_metadata(
+            task.table_info,
+            TableRouteValue::physical(region_routes),
+            HashMap::new(),
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_build_index() {
+    let table_id = 1024;
+    let datanode_handler = BuildIndexDatanodeHandler::default();
+    let node_manager = Arc::new(MockDatanodeManager::new(datanode_handler.clone()));
+    let ddl_context = new_ddl_context(node_manager);
+    prepare_table(table_id, &ddl_context).await;
+
+    let task = BuildIndexTask {
+        table_id,
+        table_name: test_create_table_task("foo", table_id).table_name(),
+    };
+    let mut procedure = BuildIndexProcedure::new(task, ddl_context.clone());
+    execute_procedure_until(&mut procedure, |p| p.built_regions() == 1).await;
+    assert_eq!(procedure.state(), &BuildIndexState::BuildIndex);
+
+    // Resumes the procedure as if the metasrv restarted.
+    let json = procedure.dump().unwrap();
+    drop(procedure);
+    let mut procedure = BuildIndexProcedure::from_json(&json, ddl_context).unwrap();
+    assert_eq!(procedure.built_regions(), 1);
+    let provider = Arc::new(ProgressRecorder::default());
+    let ctx = ProcedureContext {
+        procedure_id: ProcedureId::random(),
+        provider: provider.clone(),
+    };
+    let output = loop {
+        if let Status::Done { output } = procedure.execute(&ctx).await.unwrap() {
+            break output.unwrap();
+        }
+    };
+    assert_eq!(*output.downcast_ref::<AffectedRows>().unwrap(), 1 + 2 + 3);
+    assert_eq!(
+        *provider.progresses.lock().unwrap(),
+        vec![
+            "built indexes of 2/3 regions, 3 files indexed",
+            "built indexes of 3/3 regions, 6 files indexed",
+        ]
+    );
+
+    // Each region is built once on its leader.
+    let built_regions = datanode_handler.built_regions.lock().unwrap().clone();
+    assert_eq!(
+        built_regions,
+        (0..3)
+            .map(|region_number| (
+                Peer::empty((region_number % 2) as u64),
+                RegionId::new(table_id, region_number)
+            ))
+            .collect::<Vec<_>>()
+    );
+}
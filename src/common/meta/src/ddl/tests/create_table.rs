@@ -17,6 +17,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use api::v1::meta::Partition;
+use api::v1::region::region_request;
 use api::v1::{ColumnDataType, SemanticType};
 use common_error::ext::ErrorExt;
 use common_error::status_code::StatusCode;
@@ -25,6 +26,7 @@ use common_procedure_test::{
     execute_procedure_until, execute_procedure_until_done, MockContextProvider,
 };
 use store_api::storage::RegionId;
+use tokio::sync::mpsc;
 
 use crate::ddl::create_table::{CreateTableProcedure, CreateTableState};
 use crate::ddl::test_util::columns::TestColumnDefBuilder;
@@ -32,7 +34,8 @@ use crate::ddl::test_util::create_table::{
     build_raw_table_info_from_expr, TestCreateTableExprBuilder,
 };
 use crate::ddl::test_util::datanode_handler::{
-    NaiveDatanodeHandler, RetryErrorDatanodeHandler, UnexpectedErrorDatanodeHandler,
+    DatanodeWatcher, NaiveDatanodeHandler, RetryErrorDatanodeHandler,
+    UnexpectedErrorDatanodeHandler,
 };
 use crate::error::Error;
 use crate::key::table_route::TableRouteValue;
@@ -280,3 +283,45 @@ async fn test_memory_region_keeper_guard_dropped_on_procedure_done() {
         .memory_region_keeper
         .contains(datanode_id, region_id));
 }
+
+#[tokio::test]
+async fn test_on_rollback() {
+    let (tx, mut rx) = mpsc::channel(8);
+    let node_manager = Arc::new(MockDatanodeManager::new(DatanodeWatcher(tx)));
+    let ddl_context = new_ddl_context(node_manager);
+    let task = test_create_table_task("foo");
+    let mut procedure = CreateTableProcedure::new(task, ddl_context);
+    // Nothing to roll back before the table is allocated.
+    assert!(procedure.cancel_supported());
+    assert!(!procedure.rollback_supported());
+
+    procedure.on_prepare().await.unwrap();
+    assert!(procedure.cancel_supported());
+    assert!(procedure.rollback_supported());
+    let ctx = ProcedureContext {
+        procedure_id: ProcedureId::random(),
+        provider: Arc::new(MockContextProvider::default()),
+    };
+    // Drops the regions that may have been created.
+    procedure.rollback(&ctx).await.unwrap();
+    let (peer, request) = rx.try_recv().unwrap();
+    assert_eq!(peer.id, 0);
+    let Some(region_request::Body::Drop(request)) = request.body else {
+        unreachable!();
+    };
+    assert_eq!(
+        RegionId::from_u64(request.region_id),
+        RegionId::new(procedure.table_id(), 0)
+    );
+    assert!(rx.try_recv().is_err());
+    assert!(procedure.creator.opening_regions.is_empty());
+
+    // Can't cancel or roll back once all regions are created.
+    procedure.execute(&ctx).await.unwrap();
+    assert_eq!(
+        procedure.creator.data.state,
+        CreateTableState::CreateMetadata
+    );
+    assert!(!procedure.cancel_supported());
+    assert!(!procedure.rollback_supported());
+}
@@ -0,0 +1,554 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use api::region::RegionResponse;
+use api::v1::meta::Partition;
+use api::v1::region::{region_request, RegionRequest};
+use api::v1::value::ValueData;
+use api::v1::{ColumnDataType, Rows, SemanticType};
+use common_catalog::consts::MITO_ENGINE;
+use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
+use common_procedure::Procedure;
+use common_procedure_test::{
+    execute_procedure_until, execute_procedure_until_done, new_test_procedure_context,
+};
+use common_query::request::QueryRequest;
+use common_recordbatch::{RecordBatch, RecordBatches, SendableRecordBatchStream};
+use datafusion::datasource::{provider_as_source, MemTable};
+use datafusion::prelude::SessionContext;
+use datafusion_common::tree_node::{Transformed, TreeNode};
+use datafusion_expr::LogicalPlan;
+use datatypes::schema::{Schema, SchemaRef};
+use datatypes::vectors::{Float64Vector, StringVector, TimestampMillisecondVector};
+use store_api::storage::{
+    RegionId, RegionNumber, ScanHints, SequenceNumber, TableId, SCAN_HINTS_KEY,
+};
+use table::metadata::RawTableInfo;
+
+use crate::ddl::repartition_table::{
+    RegionCopyCursor, RepartitionTableProcedure, RepartitionTableState,
+};
+use crate::ddl::test_util::columns::TestColumnDefBuilder;
+use crate::ddl::test_util::create_table::{
+    build_raw_table_info_from_expr, TestCreateTableExprBuilder,
+};
+use crate::ddl::{DdlContext, RegionRowSplitter};
+use crate::error::Result;
+use crate::key::table_route::TableRouteValue;
+use crate::peer::Peer;
+use crate::rpc::ddl::{CreateTableTask, RepartitionTableTask};
+use crate::rpc::router::{Region, RegionRoute};
+use crate::test_util::{new_ddl_context, MockDatanodeHandler, MockDatanodeManager};
+
+/// The sequence of the old regions when the procedure takes their snapshots.
+const SNAPSHOT_SEQUENCE: SequenceNumber = 100;
+
+/// A datanode that executes the scan plans on the given rows of the regions, and
+/// records the requests it receives.
+#[derive(Clone)]
+struct RegionRowsDatanodeHandler {
+    schema: SchemaRef,
+    region_batches: Arc<HashMap<RegionId, Vec<RecordBatch>>>,
+    /// The rows written to the regions after a sequence.
+    newer_batches: Arc<Mutex<HashMap<(RegionId, SequenceNumber), Vec<RecordBatch>>>>,
+    sequence: Arc<AtomicU64>,
+    requests: Arc<Mutex<Vec<RegionRequest>>>,
+    /// The snapshot sequences of the scans.
+    scanned_sequences: Arc<Mutex<Vec<SequenceNumber>>>,
+}
+
+impl RegionRowsDatanodeHandler {
+    fn new(schema: SchemaRef, region_batches: HashMap<RegionId, Vec<RecordBatch>>) -> Self {
+        Self {
+            schema,
+            region_batches: Arc::new(region_batches),
+            newer_batches: Arc::new(Mutex::new(HashMap::new())),
+            sequence: Arc::new(AtomicU64::new(SNAPSHOT_SEQUENCE)),
+            requests: Arc::new(Mutex::new(vec![])),
+            scanned_sequences: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Writes rows to the region after the current sequence, then moves the sequence to
+    /// `sequence`.
+    fn write_rows(&self, region_id: RegionId, hosts: &[&str], sequence: SequenceNumber) {
+        let current = self.sequence.swap(sequence, Ordering::Relaxed);
+        self.newer_batches
+            .lock()
+            .unwrap()
+            .insert((region_id, current), vec![test_batch(&self.schema, hosts)]);
+    }
+
+    /// Returns the number of rows written into each region.
+    fn written_rows(&self) -> HashMap<RegionId, usize> {
+        let mut written_rows = HashMap::new();
+        for request in self.requests.lock().unwrap().iter() {
+            if let Some(region_request::Body::Inserts(inserts)) = &request.body {
+                for insert in &inserts.requests {
+                    *written_rows
+                        .entry(RegionId::from_u64(insert.region_id))
+                        .or_default() += insert.rows.as_ref().map_or(0, |rows| rows.rows.len());
+                }
+            }
+        }
+        written_rows
+    }
+
+    /// Returns the regions of the requests matching `f`.
+    fn requested_regions(&self, f: impl Fn(&region_request::Body) -> Option<u64>) -> Vec<RegionId> {
+        let mut region_ids = self
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|request| request.body.as_ref().and_then(&f))
+            .map(RegionId::from_u64)
+            .collect::<Vec<_>>();
+        region_ids.sort();
+        region_ids
+    }
+
+    fn created_regions(&self) -> Vec<RegionId> {
+        self.requested_regions(|body| match body {
+            region_request::Body::Create(create) => Some(create.region_id),
+            _ => None,
+        })
+    }
+
+    fn dropped_regions(&self) -> Vec<RegionId> {
+        self.requested_regions(|body| match body {
+            region_request::Body::Drop(drop) => Some(drop.region_id),
+            _ => None,
+        })
+    }
+
+    /// Executes the plan on the batches.
+    async fn execute_plan(&self, plan: LogicalPlan, batches: Vec<RecordBatch>) -> RecordBatches {
+        let batches = batches
+            .into_iter()
+            .map(|batch| batch.into_df_record_batch())
+            .collect();
+        let table = MemTable::try_new(self.schema.arrow_schema().clone(), vec![batches]).unwrap();
+        let source = provider_as_source(Arc::new(table));
+        let plan = plan
+            .transform_up(|plan| match plan {
+                LogicalPlan::TableScan(mut scan) => {
+                    scan.source = source.clone();
+                    Ok(Transformed::yes(LogicalPlan::TableScan(scan)))
+                }
+                plan => Ok(Transformed::no(plan)),
+            })
+            .unwrap()
+            .data;
+
+        let batches = SessionContext::new()
+            .execute_logical_plan(plan)
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|batch| RecordBatch::try_from_df_record_batch(self.schema.clone(), batch).unwrap())
+            .collect();
+        RecordBatches::try_new(self.schema.clone(), batches).unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+impl MockDatanodeHandler for RegionRowsDatanodeHandler {
+    async fn handle(&self, _peer: &Peer, request: RegionRequest) -> Result<RegionResponse> {
+        self.requests.lock().unwrap().push(request);
+        Ok(RegionResponse::new(0))
+    }
+
+    async fn handle_query(
+        &self,
+        _peer: &Peer,
+        request: QueryRequest,
+    ) -> Result<SendableRecordBatchStream> {
+        let region_id = request.region_id;
+        let query_context = request.header.unwrap().query_context.unwrap();
+        let snapshot_seqs = query_context.snapshot_seqs.unwrap().snapshot_seqs;
+        self.scanned_sequences
+            .lock()
+            .unwrap()
+            .push(snapshot_seqs[&region_id.as_u64()]);
+
+        let batches = match query_context.extensions.get(SCAN_HINTS_KEY) {
+            Some(hints) => {
+                let newer_than = ScanHints::parse(hints).0.newer_than.unwrap();
+                self.newer_batches
+                    .lock()
+                    .unwrap()
+                    .get(&(region_id, newer_than))
+                    .cloned()
+            }
+            None => self.region_batches.get(&region_id).cloned(),
+        };
+        Ok(self
+            .execute_plan(request.plan, batches.unwrap_or_default())
+            .await
+            .as_stream())
+    }
+
+    async fn region_sequence(&self, _peer: &Peer, _region_id: RegionId) -> Result<Option<u64>> {
+        Ok(Some(self.sequence.load(Ordering::Relaxed)))
+    }
+}
+
+/// Splits rows into regions by the length of the `host`.
+struct HostLengthRowSplitter;
+
+impl RegionRowSplitter for HostLengthRowSplitter {
+    fn split(
+        &self,
+        _table_id: TableId,
+        region_routes: &[RegionRoute],
+        rows: Rows,
+    ) -> Result<HashMap<RegionNumber, Rows>> {
+        let Rows { schema, rows } = rows;
+        let host_index = schema
+            .iter()
+            .position(|column| column.column_name == "host")
+            .unwrap();
+
+        let mut region_rows: HashMap<RegionNumber, Rows> = HashMap::new();
+        for row in rows {
+            let Some(ValueData::StringValue(host)) = &row.values[host_index].value_data else {
+                unreachable!()
+            };
+            let region_number = region_routes[host.len() % region_routes.len()]
+                .region
+                .id
+                .region_number();
+            region_rows
+                .entry(region_number)
+                .or_insert_with(|| Rows {
+                    schema: schema.clone(),
+                    rows: vec![],
+                })
+                .rows
+                .push(row);
+        }
+        Ok(region_rows)
+    }
+}
+
+fn test_create_table_task(
+    table_id: TableId,
+    regions: usize,
+    table_options: HashMap<String, String>,
+) -> CreateTableTask {
+    let create_table = TestCreateTableExprBuilder::default()
+        .column_defs([
+            TestColumnDefBuilder::default()
+                .name("ts")
+                .data_type(ColumnDataType::TimestampMillisecond)
+                .semantic_type(SemanticType::Timestamp)
+                .build()
+                .unwrap()
+                .into(),
+            TestColumnDefBuilder::default()
+                .name("host")
+                .data_type(ColumnDataType::String)
+                .semantic_type(SemanticType::Tag)
+                .build()
+                .unwrap()
+                .into(),
+            TestColumnDefBuilder::default()
+                .name("cpu")
+                .data_type(ColumnDataType::Float64)
+                .semantic_type(SemanticType::Field)
+                .build()
+                .unwrap()
+                .into(),
+        ])
+        .table_id(table_id)
+        .time_index("ts")
+        .primary_keys(["host".into()])
+        .table_name("foo")
+        .table_options(table_options)
+        .engine(MITO_ENGINE)
+        .build()
+        .unwrap()
+        .into();
+    let mut table_info = build_raw_table_info_from_expr(&create_table);
+    table_info.meta.partition_key_indices = vec![1];
+    CreateTableTask {
+        create_table,
+        partitions: vec![
+            Partition {
+                column_list: vec![b"host".to_vec()],
+                value_list: vec![],
+            };
+            regions
+        ],
+        table_info,
+    }
+}
+
+fn test_batch(schema: &SchemaRef, hosts: &[&str]) -> RecordBatch {
+    let rows = hosts.len();
+    RecordBatch::new(
+        schema.clone(),
+        vec![
+            Arc::new(TimestampMillisecondVector::from_vec(
+                (0..rows as i64).collect(),
+            )) as _,
+            Arc::new(StringVector::from(hosts.to_vec())) as _,
+            Arc::new(Float64Vector::from_vec(vec![1.0; rows])) as _,
+        ],
+    )
+    .unwrap()
+}
+
+/// Creates a table of 2 regions, each of them has 3 rows.
+async fn prepare_table(
+    table_id: TableId,
+    table_options: HashMap<String, String>,
+) -> (DdlContext, RegionRowsDatanodeHandler) {
+    let task = test_create_table_task(table_id, 2, table_options);
+    let schema = Arc::new(Schema::try_from(task.table_info.meta.schema.clone()).unwrap());
+    let datanode_handler = RegionRowsDatanodeHandler::new(
+        schema.clone(),
+        HashMap::from([
+            (
+                RegionId::new(table_id, 0),
+                vec![test_batch(&schema, &["a", "bb", "ccc"])],
+            ),
+            (
+                RegionId::new(table_id, 1),
+                vec![
+                    test_batch(&schema, &["dddd"]),
+                    test_batch(&schema, &["eeeee", "ffffff"]),
+                ],
+            ),
+        ]),
+    );
+    let node_manager = Arc::new(MockDatanodeManager::new(datanode_handler.clone()));
+    let mut ddl_context = new_ddl_context(node_manager);
+    ddl_context.region_row_splitter = Arc::new(HostLengthRowSplitter);
+
+    let region_routes = (0..2)
+        .map(|region_number| RegionRoute {
+            region: Region::new_test(RegionId::new(table_id, region_number)),
+            leader_peer: Some(Peer::empty(1)),
+            ..Default::default()
+        })
+        .collect();
+    ddl_context
+        .table_metadata_manager
+        .create_table_metadata(
+            task.table_info,
+            TableRouteValue::physical(region_routes),
+            HashMap::new(),
+        )
+        .await
+        .unwrap();
+
+    (ddl_context, datanode_handler)
+}
+
+async fn region_numbers(ddl_context: &DdlContext, table_id: TableId) -> Vec<RegionNumber> {
+    let table_route = ddl_context
+        .table_metadata_manager
+        .table_route_manager()
+        .table_route_storage()
+        .get(table_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut region_numbers = table_route
+        .region_routes()
+        .unwrap()
+        .iter()
+        .map(|route| route.region.id.region_number())
+        .collect::<Vec<_>>();
+    region_numbers.sort();
+    region_numbers
+}
+
+async fn table_info(ddl_context: &DdlContext, table_id: TableId) -> RawTableInfo {
+    ddl_context
+        .table_metadata_manager
+        .table_info_manager()
+        .get(table_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .into_inner()
+        .table_info
+}
+
+/// Returns whether the table is read-only and whether it's being repartitioned.
+async fn table_flags(ddl_context: &DdlContext, table_id: TableId) -> (bool, bool) {
+    let options = table_info(ddl_context, table_id).await.meta.options;
+    (options.readonly(), options.repartitioning())
+}
+
+fn test_repartition_task(table_id: TableId, rows_per_second: u64) -> RepartitionTableTask {
+    RepartitionTableTask {
+        table_id,
+        create_table: test_create_table_task(table_id, 4, HashMap::new()),
+        rows_per_second,
+        rows_per_step: 2,
+    }
+}
+
+#[tokio::test]
+async fn test_on_prepare_append_mode_table() {
+    let table_id = 1024;
+    let table_options = HashMap::from([("append_mode".to_string(), "true".to_string())]);
+    let (ddl_context, _) = prepare_table(table_id, table_options.clone()).await;
+
+    let task = RepartitionTableTask {
+        table_id,
+        create_table: test_create_table_task(table_id, 4, table_options),
+        rows_per_second: 0,
+        rows_per_step: 2,
+    };
+    let mut procedure = RepartitionTableProcedure::new(task, ddl_context);
+    let err = procedure
+        .execute(&new_test_procedure_context())
+        .await
+        .unwrap_err();
+    assert_eq!(err.status_code(), StatusCode::Unsupported);
+}
+
+#[tokio::test]
+async fn test_repartition_table() {
+    let table_id = 1024;
+    let (ddl_context, datanode_handler) = prepare_table(table_id, HashMap::new()).await;
+
+    let task = test_repartition_task(table_id, 100);
+    let mut procedure = RepartitionTableProcedure::new(task, ddl_context.clone());
+    execute_procedure_until(&mut procedure, |p| {
+        p.state() == &RepartitionTableState::CopyData
+    })
+    .await;
+    let new_region_ids = (2..6)
+        .map(|region_number| RegionId::new(table_id, region_number))
+        .collect::<Vec<_>>();
+    assert_eq!(datanode_handler.created_regions(), new_region_ids);
+    // Only deletes are rejected during the copy.
+    assert_eq!(table_flags(&ddl_context, table_id).await, (false, true));
+    let cursor = RegionCopyCursor {
+        sequence: SNAPSHOT_SEQUENCE,
+        start_timestamp: None,
+    };
+    assert_eq!(procedure.cursors(), &[cursor.clone(), cursor]);
+
+    // Rows are written to the old regions during the copy.
+    let old_region_ids = [RegionId::new(table_id, 0), RegionId::new(table_id, 1)];
+    datanode_handler.write_rows(old_region_ids[0], &["ggggggg"], 200);
+
+    // Copies the first page of the first old region, the row of the last timestamp of
+    // the page is copied in the next page.
+    let now = Instant::now();
+    execute_procedure_until(&mut procedure, |p| p.cursors()[0].start_timestamp.is_some()).await;
+    // 1 row at 100 rows per second.
+    assert!(now.elapsed() >= Duration::from_millis(10));
+    assert_eq!(procedure.cursors()[0].start_timestamp, Some(1));
+    assert_eq!(datanode_handler.written_rows().values().sum::<usize>(), 1);
+    // The table is still served by the old regions with all rows.
+    assert_eq!(region_numbers(&ddl_context, table_id).await, vec![0, 1]);
+
+    // Resumes the procedure as if the metasrv restarted.
+    let json = procedure.dump().unwrap();
+    drop(procedure);
+    let mut procedure = RepartitionTableProcedure::from_json(&json, ddl_context.clone()).unwrap();
+    procedure.recover().unwrap();
+    assert_eq!(procedure.state(), &RepartitionTableState::CopyData);
+    assert_eq!(procedure.cursors()[0].start_timestamp, Some(1));
+    execute_procedure_until(&mut procedure, |p| {
+        p.state() == &RepartitionTableState::CatchUp
+    })
+    .await;
+    // The rows of the snapshots are copied once, all rows of the second region have
+    // the same timestamp in its first page.
+    assert_eq!(datanode_handler.written_rows().values().sum::<usize>(), 6);
+    assert_eq!(procedure.cursors()[1].start_timestamp, Some(1));
+    assert!(datanode_handler
+        .scanned_sequences
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|sequence| *sequence == SNAPSHOT_SEQUENCE));
+    assert_eq!(table_flags(&ddl_context, table_id).await, (false, true));
+
+    // Rows are written to the old regions during the catch-up.
+    datanode_handler.write_rows(old_region_ids[1], &["hh"], 300);
+    execute_procedure_until(&mut procedure, |p| {
+        p.state() == &RepartitionTableState::FinalCatchUp
+    })
+    .await;
+    assert_eq!(table_flags(&ddl_context, table_id).await, (true, true));
+    // The row written during the copy is caught up.
+    assert_eq!(datanode_handler.written_rows().values().sum::<usize>(), 7);
+    execute_procedure_until_done(&mut procedure).await;
+
+    // The row written during the catch-up is caught up while the table is read-only.
+    let written_rows = datanode_handler.written_rows();
+    assert_eq!(written_rows.values().sum::<usize>(), 8);
+    assert!(written_rows
+        .keys()
+        .all(|region_id| new_region_ids.contains(region_id)));
+    assert_eq!(
+        region_numbers(&ddl_context, table_id).await,
+        vec![2, 3, 4, 5]
+    );
+    assert_eq!(table_flags(&ddl_context, table_id).await, (false, false));
+    let table_info = table_info(&ddl_context, table_id).await;
+    assert_eq!(table_info.meta.region_numbers, vec![2, 3, 4, 5]);
+    assert_eq!(table_info.meta.partition_key_indices, vec![1]);
+    assert_eq!(datanode_handler.dropped_regions(), old_region_ids);
+    assert!(ddl_context.memory_region_keeper.is_empty());
+}
+
+#[tokio::test]
+async fn test_repartition_table_rollback() {
+    let table_id = 1024;
+    let (ddl_context, datanode_handler) = prepare_table(table_id, HashMap::new()).await;
+
+    let task = test_repartition_task(table_id, 0);
+    let mut procedure = RepartitionTableProcedure::new(task, ddl_context.clone());
+    execute_procedure_until(&mut procedure, |p| {
+        p.state() == &RepartitionTableState::FinalCatchUp
+    })
+    .await;
+    assert_eq!(table_flags(&ddl_context, table_id).await, (true, true));
+    assert!(procedure.rollback_supported());
+
+    procedure
+        .rollback(&new_test_procedure_context())
+        .await
+        .unwrap();
+    // The new regions are dropped and the table is served by the old regions.
+    assert_eq!(
+        datanode_handler.dropped_regions(),
+        (2..6)
+            .map(|region_number| RegionId::new(table_id, region_number))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(region_numbers(&ddl_context, table_id).await, vec![0, 1]);
+    assert_eq!(table_flags(&ddl_context, table_id).await, (false, false));
+    assert!(ddl_context.memory_region_keeper.is_empty());
+}
@@ -27,6 +27,7 @@ use store_api::storage::TableId;
 use crate::ddl::alter_database::AlterDatabaseProcedure;
 use crate::ddl::alter_logical_tables::AlterLogicalTablesProcedure;
 use crate::ddl::alter_table::AlterTableProcedure;
+use crate::ddl::build_index::BuildIndexProcedure;
 use crate::ddl::create_database::CreateDatabaseProcedure;
 use crate::ddl::create_flow::CreateFlowProcedure;
 use crate::ddl::create_logical_tables::CreateLogicalTablesProcedure;
@@ -36,26 +37,29 @@ use crate::ddl::drop_database::DropDatabaseProcedure;
 use crate::ddl::drop_flow::DropFlowProcedure;
 use crate::ddl::drop_table::DropTableProcedure;
 use crate::ddl::drop_view::DropViewProcedure;
+use crate::ddl::repartition_table::RepartitionTableProcedure;
 use crate::ddl::truncate_table::TruncateTableProcedure;
 use crate::ddl::{utils, DdlContext, ExecutorContext, ProcedureExecutor};
 use crate::error::{
-    EmptyDdlTasksSnafu, ParseProcedureIdSnafu, ProcedureNotFoundSnafu, ProcedureOutputSnafu,
-    QueryProcedureSnafu, RegisterProcedureLoaderSnafu, Result, SubmitProcedureSnafu,
-    TableInfoNotFoundSnafu, TableNotFoundSnafu, TableRouteNotFoundSnafu,
+    CancelProcedureSnafu, EmptyDdlTasksSnafu, ParseProcedureIdSnafu, ProcedureNotFoundSnafu,
+    ProcedureOutputSnafu, QueryProcedureSnafu, RegisterProcedureLoaderSnafu, Result,
+    SubmitProcedureSnafu, TableInfoNotFoundSnafu, TableNotFoundSnafu, TableRouteNotFoundSnafu,
     UnexpectedLogicalRouteTableSnafu, UnsupportedSnafu, WaitProcedureSnafu,
 };
 use crate::key::table_info::TableInfoValue;
 use crate::key::table_name::TableNameKey;
 use crate::key::{DeserializedValueWithBytes, TableMetadataManagerRef};
+use crate::node_manager::AffectedRows;
 use crate::rpc::ddl::DdlTask::{
-    AlterDatabase, AlterLogicalTables, AlterTable, CreateDatabase, CreateFlow, CreateLogicalTables,
-    CreateTable, CreateView, DropDatabase, DropFlow, DropLogicalTables, DropTable, DropView,
-    TruncateTable,
+    AlterDatabase, AlterLogicalTables, AlterTable, BuildIndex, CreateDatabase, CreateFlow,
+    CreateLogicalTables, CreateTable, CreateView, DropDatabase, DropFlow, DropLogicalTables,
+    DropTable, DropView, RepartitionTable, TruncateTable,
 };
 use crate::rpc::ddl::{
-    AlterDatabaseTask, AlterTableTask, CreateDatabaseTask, CreateFlowTask, CreateTableTask,
-    CreateViewTask, DropDatabaseTask, DropFlowTask, DropTableTask, DropViewTask, QueryContext,
-    SubmitDdlTaskRequest, SubmitDdlTaskResponse, TruncateTableTask,
+    AlterDatabaseTask, AlterTableTask, BuildIndexTask, CreateDatabaseTask, CreateFlowTask,
+    CreateTableTask, CreateViewTask, DropDatabaseTask, DropFlowTask, DropTableTask, DropViewTask,
+    QueryContext, RepartitionTableTask, SubmitDdlTaskRequest, SubmitDdlTaskResponse,
+    TruncateTableTask,
 };
 use crate::rpc::procedure;
 use crate::rpc::procedure::{MigrateRegionRequest, MigrateRegionResponse, ProcedureStateResponse};
@@ -136,7 +140,9 @@ impl DdlManager {
             TruncateTableProcedure,
             CreateDatabaseProcedure,
             DropDatabaseProcedure,
-            DropViewProcedure
+            DropViewProcedure,
+            RepartitionTableProcedure,
+            BuildIndexProcedure
         );
 
         for (type_name, loader_factory) in loaders {
@@ -352,6 +358,34 @@ impl DdlManager {
         self.submit_procedure(procedure_with_id).await
     }
 
+    /// Submits and executes a repartition table task.
+    #[tracing::instrument(skip_all)]
+    pub async fn submit_repartition_table_task(
+        &self,
+        repartition_table_task: RepartitionTableTask,
+    ) -> Result<(ProcedureId, Option<Output>)> {
+        let context = self.create_context();
+        let procedure = RepartitionTableProcedure::new(repartition_table_task, context);
+
+        let procedure_with_id = ProcedureWithId::with_random_id(Box::new(procedure));
+
+        self.submit_procedure(procedure_with_id).await
+    }
+
+    /// Submits and executes a build index task.
+    #[tracing::instrument(skip_all)]
+    pub async fn submit_build_index_task(
+        &self,
+        build_index_task: BuildIndexTask,
+    ) -> Result<(ProcedureId, Option<Output>)> {
+        let context = self.create_context();
+        let procedure = BuildIndexProcedure::new(build_index_task, context);
+
+        let procedure_with_id = ProcedureWithId::with_random_id(Box::new(procedure));
+
+        self.submit_procedure(procedure_with_id).await
+    }
+
     async fn submit_procedure(
         &self,
         procedure_with_id: ProcedureWithId,
@@ -403,6 +437,52 @@ async fn handle_truncate_table_task(
     })
 }
 
+async fn handle_repartition_table_task(
+    ddl_manager: &DdlManager,
+    repartition_table_task: RepartitionTableTask,
+) -> Result<SubmitDdlTaskResponse> {
+    let table_id = repartition_table_task.table_id;
+    let (id, _) = ddl_manager
+        .submit_repartition_table_task(repartition_table_task)
+        .await?;
+
+    info!("Table: {table_id} is repartitioned via procedure_id {id:?}");
+
+    Ok(SubmitDdlTaskResponse {
+        key: id.to_string().into(),
+        ..Default::default()
+    })
+}
+
+async fn handle_build_index_task(
+    ddl_manager: &DdlManager,
+    build_index_task: BuildIndexTask,
+) -> Result<SubmitDdlTaskResponse> {
+    let table_id = build_index_task.table_id;
+    let (id, output) = ddl_manager
+        .submit_build_index_task(build_index_task)
+        .await?;
+
+    let procedure_id = id.to_string();
+    let output = output.context(ProcedureOutputSnafu {
+        procedure_id: &procedure_id,
+        err_msg: "empty output",
+    })?;
+    let affected_rows = *(output
+        .downcast_ref::<AffectedRows>()
+        .context(ProcedureOutputSnafu {
+            procedure_id: &procedure_id,
+            err_msg: "downcast to `AffectedRows`",
+        })?);
+    info!("Indexes of table: {table_id} are built via procedure_id {id:?}");
+
+    Ok(SubmitDdlTaskResponse {
+        key: procedure_id.into(),
+        affected_rows,
+        ..Default::default()
+    })
+}
+
 async fn handle_alter_table_task(
     ddl_manager: &DdlManager,
     alter_table_task: AlterTableTask,
@@ -486,6 +566,7 @@ async fn handle_create_table_task(
     Ok(SubmitDdlTaskResponse {
         key: procedure_id.into(),
         table_ids: vec![table_id],
+        ..Default::default()
     })
 }
 
@@ -528,6 +609,7 @@ async fn handle_create_logical_table_tasks(
     Ok(SubmitDdlTaskResponse {
         key: procedure_id.into(),
         table_ids,
+        ..Default::default()
     })
 }
 
@@ -727,6 +809,7 @@ async fn handle_create_view_task(
     Ok(SubmitDdlTaskResponse {
         key: procedure_id.into(),
         table_ids: vec![view_id],
+        ..Default::default()
     })
 }
 
@@ -757,6 +840,12 @@ impl ProcedureExecutor for DdlManager {
                 TruncateTable(truncate_table_task) => {
                     handle_truncate_table_task(self, truncate_table_task).await
                 }
+                RepartitionTable(repartition_table_task) => {
+                    handle_repartition_table_task(self, repartition_table_task).await
+                }
+                BuildIndex(build_index_task) => {
+                    handle_build_index_task(self, build_index_task).await
+                }
                 CreateLogicalTables(create_table_tasks) => {
                     handle_create_logical_table_tasks(self, create_table_tasks).await
                 }
@@ -827,6 +916,16 @@ impl ProcedureExecutor for DdlManager {
             .context(QueryProcedureSnafu)?;
         Ok(procedure::procedure_details_to_pb_response(metas))
     }
+
+    async fn cancel_procedure(&self, _ctx: &ExecutorContext, pid: &str) -> Result<()> {
+        let pid =
+            ProcedureId::parse_str(pid).with_context(|_| ParseProcedureIdSnafu { key: pid })?;
+
+        self.procedure_manager
+            .cancel_procedure(pid)
+            .await
+            .context(CancelProcedureSnafu)
+    }
 }
 
 #[cfg(test)]
@@ -839,12 +938,14 @@ mod tests {
     use super::DdlManager;
     use crate::cache_invalidator::DummyCacheInvalidator;
     use crate::ddl::alter_table::AlterTableProcedure;
+    use crate::ddl::build_index::BuildIndexProcedure;
     use crate::ddl::create_table::CreateTableProcedure;
     use crate::ddl::drop_table::DropTableProcedure;
     use crate::ddl::flow_meta::FlowMetadataAllocator;
+    use crate::ddl::repartition_table::RepartitionTableProcedure;
     use crate::ddl::table_meta::TableMetadataAllocator;
     use crate::ddl::truncate_table::TruncateTableProcedure;
-    use crate::ddl::{DdlContext, NoopRegionFailureDetectorControl};
+    use crate::ddl::{DdlContext, NoopRegionFailureDetectorControl, NoopRegionRowSplitter};
     use crate::key::flow::FlowMetadataManager;
     use crate::key::TableMetadataManager;
     use crate::kv_backend::memory::MemoryKvBackend;
@@ -902,6 +1003,7 @@ mod tests {
                 memory_region_keeper: Arc::new(MemoryRegionKeeper::default()),
                 leader_region_registry: Arc::new(LeaderRegionRegistry::default()),
                 region_failure_detector_controller: Arc::new(NoopRegionFailureDetectorControl),
+                region_row_splitter: Arc::new(NoopRegionRowSplitter),
             },
             procedure_manager.clone(),
             true,
@@ -912,6 +1014,8 @@ mod tests {
             AlterTableProcedure::TYPE_NAME,
             DropTableProcedure::TYPE_NAME,
             TruncateTableProcedure::TYPE_NAME,
+            RepartitionTableProcedure::TYPE_NAME,
+            BuildIndexProcedure::TYPE_NAME,
         ];
 
         for loader in expected_loaders {
@@ -117,6 +117,13 @@ pub enum Error {
         source: common_procedure::Error,
     },
 
+    #[snafu(display("Failed to cancel procedure"))]
+    CancelProcedure {
+        #[snafu(implicit)]
+        location: Location,
+        source: common_procedure::Error,
+    },
+
     #[snafu(display("Procedure not found: {pid}"))]
     ProcedureNotFound {
         #[snafu(implicit)]
@@ -446,6 +453,23 @@ pub enum Error {
         source: BoxedError,
     },
 
+    #[snafu(display("Failed to build the plan to scan region: {}", region_id))]
+    BuildRegionScanPlan {
+        region_id: RegionId,
+        #[snafu(source)]
+        error: datafusion_common::DataFusionError,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Failed to read data from region: {}", region_id))]
+    ReadRegionData {
+        region_id: RegionId,
+        source: common_recordbatch::error::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Retry later"))]
     RetryLater { source: BoxedError },
 
@@ -797,6 +821,7 @@ impl ErrorExt for Error {
             | EtcdTxnFailed { .. }
             | ConnectEtcd { .. }
             | MoveValues { .. }
+            | BuildRegionScanPlan { .. }
             | GetCache { .. }
             | SerializeToJson { .. }
             | DeserializeFromJson { .. } => StatusCode::Internal,
@@ -864,6 +889,7 @@ impl ErrorExt for Error {
 
             SubmitProcedure { source, .. }
             | QueryProcedure { source, .. }
+            | CancelProcedure { source, .. }
             | WaitProcedure { source, .. }
             | StartProcedureManager { source, .. }
             | StopProcedureManager { source, .. } => source.status_code(),
@@ -871,6 +897,7 @@ impl ErrorExt for Error {
             External { source, .. } => source.status_code(),
             ResponseExceededSizeLimit { source, .. } => source.status_code(),
             OperateDatanode { source, .. } => source.status_code(),
+            ReadRegionData { source, .. } => source.status_code(),
             Table { source, .. } => source.status_code(),
             RetryLater { source, .. } => source.status_code(),
             AbortProcedure { source, .. } => source.status_code(),
@@ -1204,6 +1204,78 @@ impl TableMetadataManager {
         Ok(())
     }
 
+    /// Replaces the regions of a repartitioned table by `new_region_routes` and updates
+    /// its table info in the same transaction, so the partition columns in the table
+    /// info always match the partition rule of the regions.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_table_partitions(
+        &self,
+        current_table_info_value: &DeserializedValueWithBytes<TableInfoValue>,
+        new_table_info: RawTableInfo,
+        region_info: RegionInfo,
+        current_table_route_value: &DeserializedValueWithBytes<TableRouteValue>,
+        new_region_routes: Vec<RegionRoute>,
+        new_region_wal_options: &HashMap<RegionNumber, String>,
+    ) -> Result<()> {
+        let table_id = current_table_info_value.table_info.ident.table_id;
+        let new_table_info_value = current_table_info_value.update(new_table_info);
+        let new_region_options = new_table_info_value.table_info.to_region_options();
+
+        // Updates the table info.
+        let (update_table_info_txn, on_update_table_info_failure) = self
+            .table_info_manager()
+            .build_update_txn(table_id, current_table_info_value, &new_table_info_value)?;
+
+        // Updates the datanode table key value pairs.
+        let current_region_distribution =
+            region_distribution(current_table_route_value.region_routes()?);
+        let new_region_distribution = region_distribution(&new_region_routes);
+        let update_datanode_table_txn = self.datanode_table_manager().build_update_txn(
+            table_id,
+            region_info,
+            current_region_distribution,
+            new_region_distribution,
+            &new_region_options,
+            new_region_wal_options,
+        )?;
+
+        // Updates the table route.
+        let new_table_route_value = current_table_route_value.update(new_region_routes)?;
+        let (update_table_route_txn, on_update_table_route_failure) = self
+            .table_route_manager()
+            .table_route_storage()
+            .build_update_txn(table_id, current_table_route_value, &new_table_route_value)?;
+
+        let txn = Txn::merge_all(vec![
+            update_table_info_txn,
+            update_datanode_table_txn,
+            update_table_route_txn,
+        ]);
+
+        let mut r = self.kv_backend.txn(txn).await?;
+
+        // Checks whether metadata was already updated.
+        if !r.succeeded {
+            let mut set = TxnOpGetResponseSet::from(&mut r.responses);
+            let remote_table_info = on_update_table_info_failure(&mut set)?
+                .context(error::UnexpectedSnafu {
+                    err_msg: "Reads the empty table info in comparing operation of the updating table partitions",
+                })?
+                .into_inner();
+            let remote_table_route = on_update_table_route_failure(&mut set)?
+                .context(error::UnexpectedSnafu {
+                    err_msg: "Reads the empty table route in comparing operation of the updating table partitions",
+                })?
+                .into_inner();
+
+            let op_name = "the updating table partitions";
+            ensure_values!(remote_table_info, new_table_info_value, op_name);
+            ensure_values!(remote_table_route, new_table_route_value, op_name);
+        }
+
+        Ok(())
+    }
+
     /// Updates the leader status of the [RegionRoute].
     pub async fn update_leader_region_status<F>(
         &self,
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
 
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
@@ -35,6 +35,9 @@ use crate::rpc::store::RangeRequest;
 use crate::rpc::KeyValue;
 
 const OPT_KEY_TTL: &str = "ttl";
+/// The prefix of the database options that are inherited by the tables created
+/// in the database, e.g. `table_defaults.append_mode`.
+pub const OPT_KEY_TABLE_DEFAULTS_PREFIX: &str = "table_defaults.";
 
 /// The schema name key, indices all schema names belong to the {catalog_name}
 ///
@@ -58,6 +61,20 @@ impl Default for SchemaNameKey<'_> {
 pub struct SchemaNameValue {
     #[serde(default)]
     pub ttl: Option<DatabaseTimeToLive>,
+    /// Default options of the tables created in the schema, keyed by the table
+    /// option name (without the `table_defaults.` prefix).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub table_defaults: BTreeMap<String, String>,
+}
+
+impl SchemaNameValue {
+    /// Fills the table `options` with the table defaults of the schema. Options
+    /// that are already set are kept.
+    pub fn apply_table_defaults(&self, options: &mut HashMap<String, String>) {
+        for (key, value) in &self.table_defaults {
+            options.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
 }
 
 impl Display for SchemaNameValue {
@@ -65,6 +82,12 @@ impl Display for SchemaNameValue {
         if let Some(ttl) = self.ttl.map(|i| i.to_string()) {
             write!(f, "ttl='{}'", ttl)?;
         }
+        for (i, (key, value)) in self.table_defaults.iter().enumerate() {
+            if i > 0 || self.ttl.is_some() {
+                write!(f, ", ")?;
+            }
+            write!(f, "{OPT_KEY_TABLE_DEFAULTS_PREFIX}{key}='{value}'")?;
+        }
 
         Ok(())
     }
@@ -87,7 +110,17 @@ impl TryFrom<&HashMap<String, String>> for SchemaNameValue {
             })
             .transpose()?
             .map(|ttl| ttl.into());
-        Ok(Self { ttl })
+        let table_defaults = value
+            .iter()
+            .filter_map(|(key, value)| {
+                key.strip_prefix(OPT_KEY_TABLE_DEFAULTS_PREFIX)
+                    .map(|key| (key.to_string(), value.clone()))
+            })
+            .collect();
+        Ok(Self {
+            ttl,
+            table_defaults,
+        })
     }
 }
 
@@ -97,6 +130,12 @@ impl From<SchemaNameValue> for HashMap<String, String> {
         if let Some(ttl) = value.ttl.map(|ttl| ttl.to_string()) {
             opts.insert(OPT_KEY_TTL.to_string(), ttl);
         }
+        opts.extend(
+            value
+                .table_defaults
+                .into_iter()
+                .map(|(key, value)| (format!("{OPT_KEY_TABLE_DEFAULTS_PREFIX}{key}"), value)),
+        );
         opts
     }
 }
@@ -316,18 +355,69 @@ mod tests {
 
     #[test]
     fn test_display_schema_value() {
-        let schema_value = SchemaNameValue { ttl: None };
+        let schema_value = SchemaNameValue::default();
         assert_eq!("", schema_value.to_string());
 
         let schema_value = SchemaNameValue {
             ttl: Some(Duration::from_secs(9).into()),
+            ..Default::default()
         };
         assert_eq!("ttl='9s'", schema_value.to_string());
 
         let schema_value = SchemaNameValue {
             ttl: Some(Duration::from_secs(0).into()),
+            ..Default::default()
         };
         assert_eq!("ttl='forever'", schema_value.to_string());
+
+        let schema_value = SchemaNameValue {
+            ttl: Some(Duration::from_secs(9).into()),
+            table_defaults: BTreeMap::from([
+                ("append_mode".to_string(), "true".to_string()),
+                ("ttl".to_string(), "7d".to_string()),
+            ]),
+        };
+        assert_eq!(
+            "ttl='9s', table_defaults.append_mode='true', table_defaults.ttl='7d'",
+            schema_value.to_string()
+        );
+    }
+
+    #[test]
+    fn test_table_defaults() {
+        let opts = HashMap::from([
+            ("ttl".to_string(), "1d".to_string()),
+            ("table_defaults.append_mode".to_string(), "true".to_string()),
+            ("table_defaults.ttl".to_string(), "7d".to_string()),
+        ]);
+        let value = SchemaNameValue::try_from(&opts).unwrap();
+        assert_eq!(Some(Duration::from_secs(86400).into()), value.ttl);
+        assert_eq!(
+            BTreeMap::from([
+                ("append_mode".to_string(), "true".to_string()),
+                ("ttl".to_string(), "7d".to_string()),
+            ]),
+            value.table_defaults
+        );
+        assert_eq!(opts, HashMap::from(value.clone()));
+
+        let mut table_options = HashMap::from([("ttl".to_string(), "1h".to_string())]);
+        value.apply_table_defaults(&mut table_options);
+        assert_eq!(
+            HashMap::from([
+                ("append_mode".to_string(), "true".to_string()),
+                ("ttl".to_string(), "1h".to_string()),
+            ]),
+            table_options
+        );
+
+        // The value written before the table defaults are supported.
+        let parsed = SchemaNameValue::try_from_raw_value(
+            serde_json::json!({"ttl": "10s"}).to_string().as_bytes(),
+        )
+        .unwrap()
+        .unwrap();
+        assert!(parsed.table_defaults.is_empty());
     }
 
     #[test]
@@ -341,6 +431,7 @@ mod tests {
 
         let value = SchemaNameValue {
             ttl: Some(Duration::from_secs(10).into()),
+            ..Default::default()
         };
         let mut opts: HashMap<String, String> = HashMap::new();
         opts.insert("ttl".to_string(), "10s".to_string());
@@ -355,6 +446,7 @@ mod tests {
 
         let forever = SchemaNameValue {
             ttl: Some(Default::default()),
+            ..Default::default()
         };
         let parsed = SchemaNameValue::try_from_raw_value(
             serde_json::json!({"ttl": "forever"}).to_string().as_bytes(),
@@ -396,6 +488,7 @@ mod tests {
         let current_schema_value = manager.get(schema_key).await.unwrap().unwrap();
         let new_schema_value = SchemaNameValue {
             ttl: Some(Duration::from_secs(10).into()),
+            ..Default::default()
         };
         manager
             .update(schema_key, &current_schema_value, &new_schema_value)
@@ -410,9 +503,11 @@ mod tests {
 
         let new_schema_value = SchemaNameValue {
             ttl: Some(Duration::from_secs(40).into()),
+            ..Default::default()
         };
         let incorrect_schema_value = SchemaNameValue {
             ttl: Some(Duration::from_secs(20).into()),
+            ..Default::default()
         }
         .try_as_raw_value()
         .unwrap();
@@ -425,7 +520,7 @@ mod tests {
             .unwrap_err();
 
         let current_schema_value = manager.get(schema_key).await.unwrap().unwrap();
-        let new_schema_value = SchemaNameValue { ttl: None };
+        let new_schema_value = SchemaNameValue::default();
         manager
             .update(schema_key, &current_schema_value, &new_schema_value)
             .await
@@ -87,6 +87,18 @@ lazy_static! {
         &["step"]
     )
     .unwrap();
+    pub static ref METRIC_META_PROCEDURE_REPARTITION_TABLE: HistogramVec = register_histogram_vec!(
+        "greptime_meta_procedure_repartition_table",
+        "meta procedure repartition table",
+        &["step"]
+    )
+    .unwrap();
+    pub static ref METRIC_META_PROCEDURE_BUILD_INDEX: HistogramVec = register_histogram_vec!(
+        "greptime_meta_procedure_build_index",
+        "meta procedure build index",
+        &["step"]
+    )
+    .unwrap();
     /// Cache container cache get counter.
     pub static ref CACHE_CONTAINER_CACHE_GET: IntCounterVec = register_int_counter_vec!(
         "greptime_meta_cache_container_cache_get",
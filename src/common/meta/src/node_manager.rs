@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use api::region::RegionResponse;
@@ -20,8 +21,16 @@ use api::v1::region::{InsertRequests, RegionRequest};
 pub use common_base::AffectedRows;
 use common_query::request::QueryRequest;
 use common_recordbatch::SendableRecordBatchStream;
+use common_time::range::TimestampRange;
+use serde::{Deserialize, Serialize};
+use store_api::manifest::ManifestVersion;
+use store_api::region_engine::{
+    LabelValuesRequest, RegionManifestSnapshot, SeriesCardinality, SeriesCardinalityRequest,
+};
+use store_api::storage::RegionId;
+use strum::AsRefStr;
 
-use crate::error::Result;
+use crate::error::{Result, UnsupportedSnafu};
 use crate::peer::Peer;
 
 /// The trait for handling requests to datanode.
@@ -32,10 +41,142 @@ pub trait Datanode: Send + Sync {
 
     /// Handles query requests
     async fn handle_query(&self, request: QueryRequest) -> Result<SendableRecordBatchStream>;
+
+    /// Returns the distinct values of a tag column in the region without scanning
+    /// the data, or `None` if the region can't serve the request this way.
+    ///
+    /// This and the following region methods fail with [StatusCode::Unsupported] if
+    /// the datanode doesn't serve them, e.g. datanodes of earlier versions.
+    ///
+    /// [StatusCode::Unsupported]: common_error::status_code::StatusCode::Unsupported
+    async fn label_values(
+        &self,
+        _region_id: RegionId,
+        _request: LabelValuesRequest,
+    ) -> Result<Option<Vec<String>>> {
+        UnsupportedSnafu {
+            operation: "label_values",
+        }
+        .fail()
+    }
+
+    /// Counts the series of the region, or returns `None` if the region can't serve
+    /// the request.
+    async fn series_cardinality(
+        &self,
+        _region_id: RegionId,
+        _request: SeriesCardinalityRequest,
+    ) -> Result<Option<SeriesCardinality>> {
+        UnsupportedSnafu {
+            operation: "series_cardinality",
+        }
+        .fail()
+    }
+
+    /// Returns the SST files in the manifest of the region, or `None` if the region
+    /// can't serve the request.
+    async fn region_manifest(
+        &self,
+        _region_id: RegionId,
+    ) -> Result<Option<RegionManifestSnapshot>> {
+        UnsupportedSnafu {
+            operation: "region_manifest",
+        }
+        .fail()
+    }
+
+    /// Saves a checkpoint of the manifest of the region and returns its version, or
+    /// `None` if the region can't serve the request.
+    async fn checkpoint_region(&self, _region_id: RegionId) -> Result<Option<ManifestVersion>> {
+        UnsupportedSnafu {
+            operation: "checkpoint_region",
+        }
+        .fail()
+    }
+
+    /// Returns the latest committed sequence of the region, or `None` if the region
+    /// can't serve the request.
+    async fn region_sequence(&self, _region_id: RegionId) -> Result<Option<u64>> {
+        UnsupportedSnafu {
+            operation: "region_sequence",
+        }
+        .fail()
+    }
+
+    /// Returns the highest sequence number written to the region by each producer, or
+    /// `None` if the region can't serve the request.
+    async fn producer_watermarks(
+        &self,
+        _region_id: RegionId,
+    ) -> Result<Option<HashMap<String, u64>>> {
+        UnsupportedSnafu {
+            operation: "producer_watermarks",
+        }
+        .fail()
+    }
+
+    /// Builds the missing indexes of the SSTs in the region and returns the number of
+    /// files indexed.
+    async fn build_index(&self, _region_id: RegionId) -> Result<AffectedRows> {
+        UnsupportedSnafu {
+            operation: "build_index",
+        }
+        .fail()
+    }
+
+    /// Removes the SST files of the region whose rows are all in the time range and
+    /// returns the number of rows in them. Rows in memtables and in files partially in
+    /// the range are kept.
+    async fn truncate_range(
+        &self,
+        _region_id: RegionId,
+        _time_range: TimestampRange,
+    ) -> Result<AffectedRows> {
+        UnsupportedSnafu {
+            operation: "truncate_range",
+        }
+        .fail()
+    }
 }
 
 pub type DatanodeRef = Arc<dyn Datanode>;
 
+/// Type of the Flight actions that carry a [RegionAction].
+pub const REGION_ACTION_TYPE: &str = "region_action";
+
+/// A request to a region that the region request protocol doesn't carry. It's sent
+/// to the datanode in JSON as the body of a Flight action, and the datanode replies
+/// with the JSON encoded result of the corresponding [Datanode] method. Datanodes
+/// that don't know the action reply with `Unimplemented`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, AsRefStr)]
+pub enum RegionAction {
+    /// See [Datanode::label_values].
+    LabelValues {
+        region_id: RegionId,
+        request: LabelValuesRequest,
+    },
+    /// See [Datanode::series_cardinality].
+    SeriesCardinality {
+        region_id: RegionId,
+        request: SeriesCardinalityRequest,
+    },
+    /// See [Datanode::region_manifest].
+    RegionManifest { region_id: RegionId },
+    /// See [Datanode::checkpoint_region].
+    CheckpointRegion { region_id: RegionId },
+    /// See [Datanode::producer_watermarks].
+    ProducerWatermarks { region_id: RegionId },
+    /// See [Datanode::region_sequence].
+    RegionSequence { region_id: RegionId },
+    /// See [Datanode::build_index].
+    BuildIndex { region_id: RegionId },
+    /// See [Datanode::truncate_range].
+    TruncateRange {
+        region_id: RegionId,
+        time_range: TimestampRange,
+    },
+}
+
 /// The trait for handling requests to flownode
 #[async_trait::async_trait]
 pub trait Flownode: Send + Sync {
@@ -35,6 +35,7 @@ use api::v1::{
 };
 use base64::engine::general_purpose;
 use base64::Engine as _;
+use common_base::AffectedRows;
 use common_time::DatabaseTimeToLive;
 use prost::Message;
 use serde::{Deserialize, Serialize};
@@ -42,10 +43,12 @@ use serde_with::{serde_as, DefaultOnNull};
 use session::context::QueryContextRef;
 use snafu::{OptionExt, ResultExt};
 use table::metadata::{RawTableInfo, TableId};
+use table::requests::validate_table_option;
 use table::table_name::TableName;
 use table::table_reference::TableReference;
 
 use crate::error::{self, InvalidSetDatabaseOptionSnafu, InvalidUnsetDatabaseOptionSnafu, Result};
+use crate::key::schema_name::OPT_KEY_TABLE_DEFAULTS_PREFIX;
 use crate::key::FlowId;
 
 /// DDL tasks
@@ -65,6 +68,8 @@ pub enum DdlTask {
     DropFlow(DropFlowTask),
     CreateView(CreateViewTask),
     DropView(DropViewTask),
+    RepartitionTable(RepartitionTableTask),
+    BuildIndex(BuildIndexTask),
 }
 
 impl DdlTask {
@@ -185,6 +190,29 @@ impl DdlTask {
             view_info,
         })
     }
+
+    /// Creates a [`DdlTask`] to repartition a table.
+    pub fn new_repartition_table(
+        table_id: TableId,
+        create_table: CreateTableTask,
+        rows_per_second: u64,
+        rows_per_step: usize,
+    ) -> Self {
+        DdlTask::RepartitionTable(RepartitionTableTask {
+            table_id,
+            create_table,
+            rows_per_second,
+            rows_per_step,
+        })
+    }
+
+    /// Creates a [`DdlTask`] to build the missing indexes of a table.
+    pub fn new_build_index(table_id: TableId, table_name: TableName) -> Self {
+        DdlTask::BuildIndex(BuildIndexTask {
+            table_id,
+            table_name,
+        })
+    }
 }
 
 impl TryFrom<Task> for DdlTask {
@@ -289,6 +317,18 @@ impl TryFrom<SubmitDdlTaskRequest> for PbDdlTaskRequest {
             DdlTask::DropFlow(task) => Task::DropFlowTask(task.into()),
             DdlTask::CreateView(task) => Task::CreateViewTask(task.try_into()?),
             DdlTask::DropView(task) => Task::DropViewTask(task.into()),
+            DdlTask::RepartitionTable(_) => {
+                return error::UnsupportedSnafu {
+                    operation: "repartition table on a remote metasrv",
+                }
+                .fail();
+            }
+            DdlTask::BuildIndex(_) => {
+                return error::UnsupportedSnafu {
+                    operation: "build index on a remote metasrv",
+                }
+                .fail();
+            }
         };
 
         Ok(Self {
@@ -304,6 +344,8 @@ pub struct SubmitDdlTaskResponse {
     pub key: Vec<u8>,
     // `table_id`s for `CREATE TABLE` or `CREATE LOGICAL TABLES` task.
     pub table_ids: Vec<TableId>,
+    // The number of files indexed for `BUILD INDEX` task.
+    pub affected_rows: AffectedRows,
 }
 
 impl TryFrom<PbDdlTaskResponse> for SubmitDdlTaskResponse {
@@ -314,6 +356,7 @@ impl TryFrom<PbDdlTaskResponse> for SubmitDdlTaskResponse {
         Ok(Self {
             key: resp.pid.map(|pid| pid.key).unwrap_or_default(),
             table_ids,
+            ..Default::default()
         })
     }
 }
@@ -782,6 +825,42 @@ impl<'de> Deserialize<'de> for AlterTableTask {
     }
 }
 
+/// Moves the data of a table into a new set of regions.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct RepartitionTableTask {
+    pub table_id: TableId,
+    /// The table with its new partitions, the new regions are created from it.
+    pub create_table: CreateTableTask,
+    /// The max number of rows to copy into the new regions per second.
+    pub rows_per_second: u64,
+    /// The max number of rows to copy from an old region in a step, the progress is
+    /// persisted after each step.
+    pub rows_per_step: usize,
+}
+
+impl RepartitionTableTask {
+    pub fn table_ref(&self) -> TableReference {
+        self.create_table.table_ref()
+    }
+
+    pub fn table_name(&self) -> TableName {
+        self.create_table.table_name()
+    }
+}
+
+/// Builds the missing indexes of the existing SSTs of a table.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct BuildIndexTask {
+    pub table_id: TableId,
+    pub table_name: TableName,
+}
+
+impl BuildIndexTask {
+    pub fn table_ref(&self) -> TableReference {
+        self.table_name.table_ref()
+    }
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub struct TruncateTableTask {
     pub catalog: String,
@@ -1002,6 +1081,13 @@ impl TryFrom<PbAlterDatabaseKind> for AlterDatabaseKind {
 
 const TTL_KEY: &str = "ttl";
 
+/// Returns the table option of a `table_defaults.{option}` database option key,
+/// or `None` if the key is not a valid table default.
+fn table_default_key(key: &str) -> Option<&str> {
+    key.strip_prefix(OPT_KEY_TABLE_DEFAULTS_PREFIX)
+        .filter(|table_key| validate_table_option(table_key))
+}
+
 impl TryFrom<PbOption> for SetDatabaseOption {
     type Error = error::Error;
 
@@ -1013,7 +1099,13 @@ impl TryFrom<PbOption> for SetDatabaseOption {
 
                 Ok(SetDatabaseOption::Ttl(ttl))
             }
-            _ => InvalidSetDatabaseOptionSnafu { key, value }.fail(),
+            key_lower => match table_default_key(key_lower) {
+                Some(table_key) => Ok(SetDatabaseOption::TableDefault(
+                    table_key.to_string(),
+                    value,
+                )),
+                None => InvalidSetDatabaseOptionSnafu { key, value }.fail(),
+            },
         }
     }
 }
@@ -1021,11 +1113,15 @@ impl TryFrom<PbOption> for SetDatabaseOption {
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum SetDatabaseOption {
     Ttl(DatabaseTimeToLive),
+    /// Sets the default value of a table option (key, value).
+    TableDefault(String, String),
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum UnsetDatabaseOption {
     Ttl,
+    /// Unsets the default value of a table option.
+    TableDefault(String),
 }
 
 impl TryFrom<&str> for UnsetDatabaseOption {
@@ -1034,7 +1130,10 @@ impl TryFrom<&str> for UnsetDatabaseOption {
     fn try_from(key: &str) -> Result<Self> {
         match key.to_ascii_lowercase().as_str() {
             TTL_KEY => Ok(UnsetDatabaseOption::Ttl),
-            _ => InvalidUnsetDatabaseOptionSnafu { key }.fail(),
+            key_lower => match table_default_key(key_lower) {
+                Some(table_key) => Ok(UnsetDatabaseOption::TableDefault(table_key.to_string())),
+                None => InvalidUnsetDatabaseOptionSnafu { key }.fail(),
+            },
         }
     }
 }
@@ -1256,7 +1355,7 @@ mod tests {
     use table::metadata::{RawTableInfo, RawTableMeta, TableType};
     use table::test_util::table_info::test_table_info;
 
-    use super::{AlterTableTask, CreateTableTask};
+    use super::{AlterTableTask, CreateTableTask, SetDatabaseOption, UnsetDatabaseOption};
 
     #[test]
     fn test_basic_ser_de_create_table_task() {
@@ -1390,4 +1489,28 @@ mod tests {
         );
         assert_eq!(create_table_task.table_info.meta.value_indices, vec![1]);
     }
+
+    #[test]
+    fn test_table_default_database_options() {
+        let option = SetDatabaseOption::try_from(api::v1::Option {
+            key: "table_defaults.append_mode".to_string(),
+            value: "true".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            SetDatabaseOption::TableDefault("append_mode".to_string(), "true".to_string()),
+            option
+        );
+        assert!(SetDatabaseOption::try_from(api::v1::Option {
+            key: "table_defaults.foo".to_string(),
+            value: "bar".to_string(),
+        })
+        .is_err());
+
+        assert_eq!(
+            UnsetDatabaseOption::TableDefault("ttl".to_string()),
+            UnsetDatabaseOption::try_from("table_defaults.ttl").unwrap()
+        );
+        assert!(UnsetDatabaseOption::try_from("table_defaults.").is_err());
+    }
 }
@@ -20,11 +20,12 @@ use api::v1::region::{InsertRequests, RegionRequest};
 pub use common_base::AffectedRows;
 use common_query::request::QueryRequest;
 use common_recordbatch::SendableRecordBatchStream;
+use store_api::storage::RegionId;
 
 use crate::cache_invalidator::DummyCacheInvalidator;
 use crate::ddl::flow_meta::FlowMetadataAllocator;
 use crate::ddl::table_meta::TableMetadataAllocator;
-use crate::ddl::{DdlContext, NoopRegionFailureDetectorControl};
+use crate::ddl::{DdlContext, NoopRegionFailureDetectorControl, NoopRegionRowSplitter};
 use crate::error::Result;
 use crate::key::flow::FlowMetadataManager;
 use crate::key::TableMetadataManager;
@@ -49,6 +50,14 @@ pub trait MockDatanodeHandler: Sync + Send + Clone {
         peer: &Peer,
         request: QueryRequest,
     ) -> Result<SendableRecordBatchStream>;
+
+    async fn build_index(&self, _peer: &Peer, _region_id: RegionId) -> Result<AffectedRows> {
+        unimplemented!()
+    }
+
+    async fn region_sequence(&self, _peer: &Peer, _region_id: RegionId) -> Result<Option<u64>> {
+        unimplemented!()
+    }
 }
 
 #[async_trait::async_trait]
@@ -106,6 +115,14 @@ impl<T: MockDatanodeHandler> Datanode for MockNode<T> {
     async fn handle_query(&self, request: QueryRequest) -> Result<SendableRecordBatchStream> {
         self.handler.handle_query(&self.peer, request).await
     }
+
+    async fn build_index(&self, region_id: RegionId) -> Result<AffectedRows> {
+        self.handler.build_index(&self.peer, region_id).await
+    }
+
+    async fn region_sequence(&self, region_id: RegionId) -> Result<Option<u64>> {
+        self.handler.region_sequence(&self.peer, region_id).await
+    }
 }
 
 #[async_trait::async_trait]
@@ -184,6 +201,7 @@ pub fn new_ddl_context_with_kv_backend(
         flow_metadata_allocator,
         flow_metadata_manager,
         region_failure_detector_controller: Arc::new(NoopRegionFailureDetectorControl),
+        region_row_splitter: Arc::new(NoopRegionRowSplitter),
     }
 }
 
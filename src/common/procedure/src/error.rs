@@ -223,6 +223,33 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Procedure {} of type {} doesn't support cancellation",
+        procedure_id,
+        type_name
+    ))]
+    CancelNotSupported {
+        procedure_id: ProcedureId,
+        type_name: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Procedure {} is not running, state: {}", procedure_id, state))]
+    ProcedureNotRunning {
+        procedure_id: ProcedureId,
+        state: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Procedure {} is cancelled", procedure_id))]
+    ProcedureCancelled {
+        procedure_id: ProcedureId,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Poison key not defined, key: '{key}', procedure_id: '{procedure_id}'"))]
     PoisonKeyNotDefined {
         key: PoisonKey,
@@ -256,9 +283,13 @@ impl ErrorExt for Error {
 
             Error::RetryTimesExceeded { .. }
             | Error::RollbackTimesExceeded { .. }
-            | Error::ManagerNotStart { .. } => StatusCode::IllegalState,
+            | Error::ManagerNotStart { .. }
+            | Error::ProcedureNotRunning { .. } => StatusCode::IllegalState,
 
-            Error::RollbackNotSupported { .. } => StatusCode::Unsupported,
+            Error::RollbackNotSupported { .. } | Error::CancelNotSupported { .. } => {
+                StatusCode::Unsupported
+            }
+            Error::ProcedureCancelled { .. } => StatusCode::Cancelled,
             Error::LoaderConflict { .. } | Error::DuplicateProcedure { .. } => {
                 StatusCode::InvalidArguments
             }
@@ -32,8 +32,9 @@ use tokio::sync::{Mutex as TokioMutex, Notify};
 
 use self::rwlock::KeyRwLock;
 use crate::error::{
-    self, DuplicateProcedureSnafu, Error, LoaderConflictSnafu, ManagerNotStartSnafu,
-    PoisonKeyNotDefinedSnafu, ProcedureNotFoundSnafu, Result, StartRemoveOutdatedMetaTaskSnafu,
+    self, CancelNotSupportedSnafu, DuplicateProcedureSnafu, Error, LoaderConflictSnafu,
+    ManagerNotStartSnafu, PoisonKeyNotDefinedSnafu, ProcedureNotFoundSnafu,
+    ProcedureNotRunningSnafu, Result, StartRemoveOutdatedMetaTaskSnafu,
     StopRemoveOutdatedMetaTaskSnafu, TooManyRunningProceduresSnafu,
 };
 use crate::local::runner::Runner;
@@ -80,6 +81,12 @@ pub(crate) struct ProcedureMeta {
     start_time_ms: AtomicI64,
     /// End execution time of this procedure.
     end_time_ms: AtomicI64,
+    /// Whether this procedure can be cancelled.
+    cancel_supported: bool,
+    /// Whether users requested to cancel this procedure.
+    cancelled: AtomicBool,
+    /// The latest progress message reported by this procedure.
+    progress: Mutex<Option<String>>,
 }
 
 impl ProcedureMeta {
@@ -90,6 +97,7 @@ impl ProcedureMeta {
         lock_key: LockKey,
         poison_keys: PoisonKeys,
         type_name: &str,
+        cancel_supported: bool,
     ) -> ProcedureMeta {
         let (state_sender, state_receiver) = watch::channel(procedure_state);
         ProcedureMeta {
@@ -104,6 +112,9 @@ impl ProcedureMeta {
             start_time_ms: AtomicI64::new(0),
             end_time_ms: AtomicI64::new(0),
             type_name: type_name.to_string(),
+            cancel_supported,
+            cancelled: AtomicBool::new(false),
+            progress: Mutex::new(None),
         }
     }
 
@@ -146,6 +157,16 @@ impl ProcedureMeta {
         self.end_time_ms
             .store(common_time::util::current_time_millis(), Ordering::Relaxed);
     }
+
+    /// Returns true if users requested to cancel the procedure.
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns the latest progress message.
+    fn progress(&self) -> Option<String> {
+        self.progress.lock().unwrap().clone()
+    }
 }
 
 /// Reference counted pointer to [ProcedureMeta].
@@ -199,6 +220,13 @@ impl ContextProvider for ManagerContext {
         let procedure_id = procedure_id.to_string();
         self.poison_manager.try_put_poison(key, procedure_id).await
     }
+
+    fn report_progress(&self, procedure_id: ProcedureId, message: String) {
+        let procedures = self.procedures.read().unwrap();
+        if let Some(meta) = procedures.get(&procedure_id) {
+            *meta.progress.lock().unwrap() = Some(message);
+        }
+    }
 }
 
 impl ManagerContext {
@@ -283,10 +311,38 @@ impl ManagerContext {
                 end_time_ms: meta.end_time_ms.load(Ordering::Relaxed),
                 state: meta.state(),
                 lock_keys: meta.lock_key.get_keys(),
+                progress: meta.progress(),
             })
             .collect()
     }
 
+    /// Marks the procedure with specific `procedure_id` as cancelled.
+    fn cancel(&self, procedure_id: ProcedureId) -> Result<()> {
+        let procedures = self.procedures.read().unwrap();
+        let meta = procedures
+            .get(&procedure_id)
+            .context(ProcedureNotFoundSnafu { procedure_id })?;
+        ensure!(
+            meta.cancel_supported,
+            CancelNotSupportedSnafu {
+                procedure_id,
+                type_name: &meta.type_name,
+            }
+        );
+        let state = meta.state();
+        ensure!(
+            state.is_running() || state.is_retrying(),
+            ProcedureNotRunningSnafu {
+                procedure_id,
+                state: state.as_str_name(),
+            }
+        );
+
+        info!("Cancel procedure {}-{}", meta.type_name, procedure_id);
+        meta.cancelled.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
     /// Returns the [Watcher] of specific `procedure_id`.
     fn watcher(&self, procedure_id: ProcedureId) -> Option<Watcher> {
         let procedures = self.procedures.read().unwrap();
@@ -508,6 +564,7 @@ impl LocalManager {
             procedure.lock_key(),
             procedure.poison_keys(),
             procedure.type_name(),
+            procedure.cancel_supported(),
         ));
         let runner = Runner {
             meta: meta.clone(),
@@ -722,6 +779,10 @@ impl ProcedureManager for LocalManager {
     async fn list_procedures(&self) -> Result<Vec<ProcedureInfo>> {
         Ok(self.manager_ctx.list_procedure())
     }
+
+    async fn cancel_procedure(&self, procedure_id: ProcedureId) -> Result<()> {
+        self.manager_ctx.cancel(procedure_id)
+    }
 }
 
 struct RemoveOutdatedMetaFunction {
@@ -758,6 +819,7 @@ pub(crate) mod test_util {
             LockKey::default(),
             PoisonKeys::default(),
             "ProcedureAdapter",
+            false,
         )
     }
 
@@ -1108,6 +1170,139 @@ mod tests {
         assert!(watcher.borrow().is_failed());
     }
 
+    #[tokio::test]
+    async fn test_cancel_procedure() {
+        let dir = create_temp_dir("cancel");
+        let config = ManagerConfig {
+            parent_path: "data/".to_string(),
+            max_retry_times: 3,
+            retry_delay: Duration::from_millis(500),
+            ..Default::default()
+        };
+        let state_store = Arc::new(ObjectStateStore::new(test_util::new_object_store(&dir)));
+        let poison_manager = Arc::new(InMemoryPoisonStore::new());
+        let manager = LocalManager::new(config, state_store, poison_manager);
+        manager.manager_ctx.start();
+
+        /// A procedure that never finishes by itself.
+        #[derive(Debug)]
+        struct SlowProcedure {
+            step: usize,
+            cancel_supported: bool,
+            rolled_back: Arc<AtomicBool>,
+        }
+
+        #[async_trait]
+        impl Procedure for SlowProcedure {
+            fn type_name(&self) -> &str {
+                "SlowProcedure"
+            }
+
+            async fn execute(&mut self, ctx: &Context) -> Result<Status> {
+                self.step += 1;
+                ctx.report_progress(format!("step {}", self.step));
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Ok(Status::executing(false))
+            }
+
+            async fn rollback(&mut self, _: &Context) -> Result<()> {
+                self.rolled_back.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+
+            fn rollback_supported(&self) -> bool {
+                true
+            }
+
+            fn cancel_supported(&self) -> bool {
+                self.cancel_supported
+            }
+
+            fn dump(&self) -> Result<String> {
+                Ok(String::new())
+            }
+
+            fn lock_key(&self) -> LockKey {
+                LockKey::default()
+            }
+        }
+
+        let manager = &manager;
+        let submit = |cancel_supported| async move {
+            let procedure_id = ProcedureId::random();
+            let rolled_back = Arc::new(AtomicBool::new(false));
+            let procedure = SlowProcedure {
+                step: 0,
+                cancel_supported,
+                rolled_back: rolled_back.clone(),
+            };
+            let watcher = manager
+                .submit(ProcedureWithId {
+                    id: procedure_id,
+                    procedure: Box::new(procedure),
+                })
+                .await
+                .unwrap();
+            (procedure_id, watcher, rolled_back)
+        };
+
+        let (procedure_id, mut watcher, rolled_back) = submit(true).await;
+        // Observes the running procedure with its progress.
+        timeout(Duration::from_secs(5), async {
+            loop {
+                let procedures = manager.list_procedures().await.unwrap();
+                let info = procedures.iter().find(|p| p.id == procedure_id).unwrap();
+                assert!(info.state.is_running());
+                if info.progress.is_some() {
+                    assert_eq!("SlowProcedure", info.type_name);
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        manager.cancel_procedure(procedure_id).await.unwrap();
+        timeout(Duration::from_secs(5), crate::watcher::wait(&mut watcher))
+            .await
+            .unwrap()
+            .unwrap_err();
+        let state = manager
+            .procedure_state(procedure_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_matches!(
+            state.error().unwrap().as_ref(),
+            Error::ProcedureCancelled { .. }
+        );
+        assert!(rolled_back.load(Ordering::Relaxed));
+        // The procedure is already finished.
+        let err = manager.cancel_procedure(procedure_id).await.unwrap_err();
+        assert_matches!(err, Error::ProcedureNotRunning { .. });
+
+        // The procedure doesn't support cancellation.
+        let (procedure_id, _watcher, _) = submit(false).await;
+        let err = manager.cancel_procedure(procedure_id).await.unwrap_err();
+        assert_matches!(err, Error::CancelNotSupported { .. });
+        assert!(manager
+            .procedure_state(procedure_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .is_running());
+
+        let err = manager
+            .cancel_procedure(ProcedureId::random())
+            .await
+            .unwrap_err();
+        assert_matches!(err, Error::ProcedureNotFound { .. });
+
+        // Stops the procedure that can't be cancelled.
+        manager.stop().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_procedure_manager_stopped() {
         let dir = create_temp_dir("procedure_manager_stopped");
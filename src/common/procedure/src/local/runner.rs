@@ -240,6 +240,27 @@ impl Runner {
                 ProcedureState::Failed { .. } => return,
                 ProcedureState::Poisoned { .. } => return,
             }
+            // The procedure may refuse to be cancelled after some steps.
+            if self.meta.is_cancelled()
+                && self.procedure.cancel_supported()
+                && matches!(
+                    self.meta.state(),
+                    ProcedureState::Running | ProcedureState::Retrying { .. }
+                )
+            {
+                info!(
+                    "Procedure {}-{} is cancelled",
+                    self.procedure.type_name(),
+                    self.meta.id
+                );
+                self.meta
+                    .set_state(ProcedureState::prepare_rollback(Arc::new(
+                        error::ProcedureCancelledSnafu {
+                            procedure_id: self.meta.id,
+                        }
+                        .build(),
+                    )));
+            }
             self.execute_once(ctx).await;
         }
     }
@@ -419,6 +440,7 @@ impl Runner {
             procedure.lock_key(),
             procedure.poison_keys(),
             procedure.type_name(),
+            procedure.cancel_supported(),
         ));
         let runner = Runner {
             meta: meta.clone(),
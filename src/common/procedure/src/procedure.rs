@@ -144,6 +144,9 @@ pub trait ContextProvider: Send + Sync {
     /// This method is used to mark a resource as being operated on by a procedure.
     /// If the poison key already exists with a different value, the operation will fail.
     async fn try_put_poison(&self, key: &PoisonKey, procedure_id: ProcedureId) -> Result<()>;
+
+    /// Updates the progress message of a procedure.
+    fn report_progress(&self, _procedure_id: ProcedureId, _message: String) {}
 }
 
 /// Reference-counted pointer to [ContextProvider].
@@ -158,6 +161,14 @@ pub struct Context {
     pub provider: ContextProviderRef,
 }
 
+impl Context {
+    /// Reports the progress of the procedure, which is shown in the procedure list.
+    pub fn report_progress(&self, message: impl Into<String>) {
+        self.provider
+            .report_progress(self.procedure_id, message.into());
+    }
+}
+
 /// A `Procedure` represents an operation or a set of operations to be performed step-by-step.
 #[async_trait]
 pub trait Procedure: Send {
@@ -181,6 +192,16 @@ pub trait Procedure: Send {
         false
     }
 
+    /// Indicates whether the procedure can be cancelled by users.
+    ///
+    /// A cancelled procedure stops before its next step and rolls back
+    /// if [Procedure::rollback_supported]. The framework checks it again before
+    /// stopping the procedure, so the procedure can ignore the cancellation once
+    /// it passes a step that can't be undone.
+    fn cancel_supported(&self) -> bool {
+        false
+    }
+
     /// Dump the state of the procedure to a string.
     fn dump(&self) -> Result<String>;
 
@@ -216,6 +237,10 @@ impl<T: Procedure + ?Sized> Procedure for Box<T> {
         (**self).rollback_supported()
     }
 
+    fn cancel_supported(&self) -> bool {
+        (**self).cancel_supported()
+    }
+
     fn dump(&self) -> Result<String> {
         (**self).dump()
     }
@@ -551,6 +576,12 @@ pub trait ProcedureManager: Send + Sync + 'static {
 
     /// Returns the details of the procedure.
     async fn list_procedures(&self) -> Result<Vec<ProcedureInfo>>;
+
+    /// Requests to cancel a running procedure.
+    ///
+    /// The procedure stops before executing its next step, so this method
+    /// doesn't wait for the procedure to finish.
+    async fn cancel_procedure(&self, procedure_id: ProcedureId) -> Result<()>;
 }
 
 /// Ref-counted pointer to the [ProcedureManager].
@@ -570,6 +601,8 @@ pub struct ProcedureInfo {
     pub state: ProcedureState,
     /// Lock keys of this procedure.
     pub lock_keys: Vec<String>,
+    /// The latest progress message reported by this procedure.
+    pub progress: Option<String>,
 }
 
 #[cfg(test)]
@@ -54,6 +54,8 @@ pub struct OutputMeta {
     /// May exist for query output. One can retrieve execution metrics from this plan.
     pub plan: Option<Arc<dyn ExecutionPlan>>,
     pub cost: OutputCost,
+    /// Number of rows rejected by writes, e.g. rows out of the time bounds of tables.
+    pub rejected_rows: OutputRows,
 }
 
 impl Output {
@@ -106,18 +108,32 @@ impl Debug for OutputData {
 
 impl OutputMeta {
     pub fn new(plan: Option<Arc<dyn ExecutionPlan>>, cost: usize) -> Self {
-        Self { plan, cost }
+        Self {
+            plan,
+            cost,
+            rejected_rows: 0,
+        }
     }
 
     pub fn new_with_plan(plan: Arc<dyn ExecutionPlan>) -> Self {
         Self {
             plan: Some(plan),
             cost: 0,
+            rejected_rows: 0,
         }
     }
 
     pub fn new_with_cost(cost: usize) -> Self {
-        Self { plan: None, cost }
+        Self {
+            plan: None,
+            cost,
+            rejected_rows: 0,
+        }
+    }
+
+    pub fn with_rejected_rows(mut self, rejected_rows: OutputRows) -> Self {
+        self.rejected_rows = rejected_rows;
+        self
     }
 }
 
@@ -25,5 +25,8 @@ pub const GREPTIME_TIMESTAMP: &str = "greptime_timestamp";
 pub const GREPTIME_VALUE: &str = "greptime_value";
 /// Default counter column name for OTLP metrics.
 pub const GREPTIME_COUNT: &str = "greptime_count";
+/// The column of the time counters were created at, i.e. the OTLP start time of
+/// cumulative sums.
+pub const GREPTIME_CREATED: &str = "greptime_created";
 /// Default physical table name
 pub const GREPTIME_PHYSICAL_TABLE: &str = "greptime_physical_table";
@@ -168,6 +168,14 @@ pub enum Error {
         #[snafu(source)]
         error: tokio::time::error::Elapsed,
     },
+    #[snafu(display(
+        "The query result exceeds the limit of {limit} bytes, see `max_result_bytes`"
+    ))]
+    ResultSizeExceeded {
+        limit: usize,
+        #[snafu(implicit)]
+        location: Location,
+    },
     #[snafu(display("RecordBatch slice index overflow: {visit_index} > {size}"))]
     RecordBatchSliceIndexOverflow {
         #[snafu(implicit)]
@@ -207,6 +215,8 @@ impl ErrorExt for Error {
             }
 
             Error::StreamTimeout { .. } => StatusCode::Cancelled,
+
+            Error::ResultSizeExceeded { .. } => StatusCode::RuntimeResourcesExhausted,
         }
     }
 
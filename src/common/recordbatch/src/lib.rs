@@ -261,6 +261,71 @@ impl<S: Stream<Item = Result<RecordBatch>> + Unpin> Stream for RecordBatchStream
     }
 }
 
+/// A stream that fails once the total size of the record batches it has yielded
+/// exceeds the limit, to protect the server from queries returning too much data.
+pub struct ResultSizeLimitedStream {
+    stream: SendableRecordBatchStream,
+    /// Maximum total bytes of the yielded batches.
+    limit: usize,
+    /// Total bytes of the batches yielded so far.
+    yielded: usize,
+    /// Whether the stream is terminated by the error.
+    exceeded: bool,
+}
+
+impl ResultSizeLimitedStream {
+    /// Creates a stream limiting the total bytes of batches of `stream` to `limit`.
+    pub fn new(stream: SendableRecordBatchStream, limit: usize) -> Self {
+        Self {
+            stream,
+            limit,
+            yielded: 0,
+            exceeded: false,
+        }
+    }
+}
+
+impl RecordBatchStream for ResultSizeLimitedStream {
+    fn name(&self) -> &str {
+        self.stream.name()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.stream.schema()
+    }
+
+    fn output_ordering(&self) -> Option<&[OrderOption]> {
+        self.stream.output_ordering()
+    }
+
+    fn metrics(&self) -> Option<RecordBatchMetrics> {
+        self.stream.metrics()
+    }
+}
+
+impl Stream for ResultSizeLimitedStream {
+    type Item = Result<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.exceeded {
+            return Poll::Ready(None);
+        }
+        match Pin::new(&mut self.stream).poll_next(ctx) {
+            Poll::Ready(Some(Ok(batch))) => {
+                self.yielded += batch.df_record_batch().get_array_memory_size();
+                if self.yielded > self.limit {
+                    self.exceeded = true;
+                    return Poll::Ready(Some(
+                        error::ResultSizeExceededSnafu { limit: self.limit }.fail(),
+                    ));
+                }
+                Poll::Ready(Some(Ok(batch)))
+            }
+            other => other,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -268,9 +333,43 @@ mod tests {
     use datatypes::prelude::{ConcreteDataType, VectorRef};
     use datatypes::schema::{ColumnSchema, Schema};
     use datatypes::vectors::{BooleanVector, Int32Vector, StringVector};
+    use futures::StreamExt;
 
     use super::*;
 
+    #[tokio::test]
+    async fn test_result_size_limited_stream() {
+        let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+            "a",
+            ConcreteDataType::int32_datatype(),
+            false,
+        )]));
+        let batches = (0..4)
+            .map(|_| {
+                let v: VectorRef = Arc::new(Int32Vector::from_vec(vec![0; 1024]));
+                RecordBatch::new(schema.clone(), vec![v]).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let batch_size = batches[0].df_record_batch().get_array_memory_size();
+        let new_stream = |limit| {
+            let batches = RecordBatches::try_new(schema.clone(), batches.clone()).unwrap();
+            ResultSizeLimitedStream::new(batches.as_stream(), limit)
+        };
+
+        let mut stream = new_stream(batch_size * 2);
+        assert!(stream.try_next().await.unwrap().is_some());
+        assert!(stream.try_next().await.unwrap().is_some());
+        let err = stream.try_next().await.unwrap_err();
+        assert!(
+            matches!(err, error::Error::ResultSizeExceeded { .. }),
+            "{err:?}"
+        );
+        assert!(stream.next().await.is_none());
+
+        let stream = new_stream(batch_size * 4);
+        assert_eq!(4, stream.try_collect::<Vec<_>>().await.unwrap().len());
+    }
+
     #[test]
     fn test_recordbatches_try_from_columns() {
         let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
@@ -18,10 +18,15 @@ pub mod logging;
 mod macros;
 pub mod metric;
 mod panic_hook;
+pub mod self_tracing;
 pub mod tracing_context;
 mod tracing_sampler;
 
 pub use logging::{init_default_ut_logging, init_global_logging, RELOAD_HANDLE};
 pub use metric::dump_metrics;
 pub use panic_hook::set_panic_hook;
+pub use self_tracing::{
+    register_self_trace_handler, SelfSpan, SelfSpanValue, SelfTraceHandler, SelfTraceHandlerRef,
+    SelfTracingOptions, SELF_TRACE_EXPORT_SPAN,
+};
 pub use {common_error, tracing, tracing_subscriber};
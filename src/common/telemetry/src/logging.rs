@@ -18,10 +18,11 @@ use std::sync::{Arc, Mutex, Once};
 use std::time::Duration;
 
 use once_cell::sync::{Lazy, OnceCell};
+use opentelemetry::trace::TracerProvider as _;
 use opentelemetry::{global, KeyValue};
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::propagation::TraceContextPropagator;
-use opentelemetry_sdk::trace::Sampler;
+use opentelemetry_sdk::trace::{Sampler, TracerProvider};
 use opentelemetry_semantic_conventions::resource;
 use serde::{Deserialize, Serialize};
 use tracing_appender::non_blocking::WorkerGuard;
@@ -33,6 +34,7 @@ use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::{filter, EnvFilter, Registry};
 
+use crate::self_tracing::{SelfTraceExporter, SelfTraceSampler, SelfTracingOptions};
 use crate::tracing_sampler::{create_sampler, TracingSampleOptions};
 
 pub const DEFAULT_OTLP_ENDPOINT: &str = "http://localhost:4317";
@@ -71,6 +73,9 @@ pub struct LoggingOptions {
 
     /// The logging options of slow query.
     pub slow_query: SlowQueryOptions,
+
+    /// The options of writing the spans into the node's own trace table.
+    pub self_tracing: SelfTracingOptions,
 }
 
 /// The options of slow query.
@@ -103,6 +108,7 @@ impl PartialEq for LoggingOptions {
             && self.otlp_endpoint == other.otlp_endpoint
             && self.tracing_sample_ratio == other.tracing_sample_ratio
             && self.append_stdout == other.append_stdout
+            && self.self_tracing == other.self_tracing
     }
 }
 
@@ -119,6 +125,7 @@ impl Default for LoggingOptions {
             tracing_sample_ratio: None,
             append_stdout: true,
             slow_query: SlowQueryOptions::default(),
+            self_tracing: SelfTracingOptions::default(),
             // Rotation hourly, 24 files per day, keeps info log files of 30 days
             max_log_files: 720,
         }
@@ -381,15 +388,19 @@ pub fn init_global_logging(
             .with(err_file_logging_layer)
             .with(slow_query_logging_layer);
 
-        if opts.enable_otlp_tracing {
+        if opts.enable_otlp_tracing || opts.self_tracing.enable {
             global::set_text_map_propagator(TraceContextPropagator::new());
 
-            let sampler = opts
-                .tracing_sample_ratio
-                .as_ref()
-                .map(create_sampler)
-                .map(Sampler::ParentBased)
-                .unwrap_or(Sampler::ParentBased(Box::new(Sampler::AlwaysOn)));
+            // Without OTLP, only the traces written into the trace table are sampled.
+            let root_sampler = if opts.enable_otlp_tracing {
+                opts.tracing_sample_ratio
+                    .as_ref()
+                    .map(create_sampler)
+                    .unwrap_or(Box::new(Sampler::AlwaysOn))
+            } else {
+                Box::new(Sampler::TraceIdRatioBased(opts.self_tracing.sample_ratio))
+            };
+            let sampler = Sampler::ParentBased(Box::new(SelfTraceSampler::new(root_sampler)));
 
             let trace_config = opentelemetry_sdk::trace::config()
                 .with_sampler(sampler)
@@ -403,25 +414,36 @@ pub fn init_global_logging(
                     KeyValue::new(resource::PROCESS_PID, std::process::id().to_string()),
                 ]));
 
-            let exporter = opentelemetry_otlp::new_exporter().tonic().with_endpoint(
-                opts.otlp_endpoint
-                    .as_ref()
-                    .map(|e| {
-                        if e.starts_with("http") {
-                            e.to_string()
-                        } else {
-                            format!("http://{}", e)
-                        }
-                    })
-                    .unwrap_or(DEFAULT_OTLP_ENDPOINT.to_string()),
-            );
-
-            let tracer = opentelemetry_otlp::new_pipeline()
-                .tracing()
-                .with_exporter(exporter)
-                .with_trace_config(trace_config)
-                .install_batch(opentelemetry_sdk::runtime::Tokio)
-                .expect("otlp tracer install failed");
+            let mut provider = TracerProvider::builder().with_config(trace_config);
+            if opts.enable_otlp_tracing {
+                let exporter = opentelemetry_otlp::new_exporter()
+                    .tonic()
+                    .with_endpoint(
+                        opts.otlp_endpoint
+                            .as_ref()
+                            .map(|e| {
+                                if e.starts_with("http") {
+                                    e.to_string()
+                                } else {
+                                    format!("http://{}", e)
+                                }
+                            })
+                            .unwrap_or(DEFAULT_OTLP_ENDPOINT.to_string()),
+                    )
+                    .build_span_exporter()
+                    .expect("otlp exporter build failed");
+                provider =
+                    provider.with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio);
+            }
+            if opts.self_tracing.enable {
+                provider = provider.with_batch_exporter(
+                    SelfTraceExporter::new(opts.self_tracing.sample_ratio),
+                    opentelemetry_sdk::runtime::Tokio,
+                );
+            }
+            let provider = provider.build();
+            let tracer = provider.tracer(app_name.to_string());
+            let _ = global::set_tracer_provider(provider);
 
             tracing::subscriber::set_global_default(
                 subscriber.with(tracing_opentelemetry::layer().with_tracer(tracer)),
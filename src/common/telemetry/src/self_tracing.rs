@@ -0,0 +1,266 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Self-observability: exports the spans of the node to a handler, which writes
+//! them into the node's own trace table.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use once_cell::sync::Lazy;
+use opentelemetry::trace::{
+    Link, SamplingDecision, SamplingResult, SpanId, SpanKind, Status, TraceId, TraceState,
+};
+use opentelemetry::{KeyValue, Value};
+use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+use opentelemetry_sdk::trace::ShouldSample;
+use serde::{Deserialize, Serialize};
+
+use crate::tracing_sampler::sample_based_on_probability;
+
+/// The name of the root span of writing the self traces. The spans of the writes
+/// are not traced, otherwise every write would trace another one.
+pub const SELF_TRACE_EXPORT_SPAN: &str = "self_trace_export";
+
+/// The options of tracing the node into its own trace table.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SelfTracingOptions {
+    /// Whether to write the spans of the node into its own trace table.
+    pub enable: bool,
+    /// The ratio of traces to write, in the range of [0, 1].
+    pub sample_ratio: f64,
+}
+
+impl Default for SelfTracingOptions {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            sample_ratio: 1.0,
+        }
+    }
+}
+
+/// The value of an attribute of a [SelfSpan].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelfSpanValue {
+    Bool(bool),
+    I64(i64),
+    F64(f64),
+    String(String),
+}
+
+/// A finished span of the node.
+#[derive(Debug, Clone)]
+pub struct SelfSpan {
+    pub trace_id: [u8; 16],
+    pub span_id: [u8; 8],
+    /// None if the span is the root of its trace.
+    pub parent_span_id: Option<[u8; 8]>,
+    pub name: String,
+    pub start_time: SystemTime,
+    pub end_time: SystemTime,
+    pub attributes: Vec<(String, SelfSpanValue)>,
+    /// The error message if the span failed.
+    pub error: Option<String>,
+}
+
+impl From<SpanData> for SelfSpan {
+    fn from(span: SpanData) -> Self {
+        let attributes = span
+            .attributes
+            .into_iter()
+            .map(|KeyValue { key, value }| {
+                let value = match value {
+                    Value::Bool(value) => SelfSpanValue::Bool(value),
+                    Value::I64(value) => SelfSpanValue::I64(value),
+                    Value::F64(value) => SelfSpanValue::F64(value),
+                    value => SelfSpanValue::String(value.to_string()),
+                };
+                (key.to_string(), value)
+            })
+            .collect();
+        let error = match span.status {
+            Status::Error { description } => Some(description.to_string()),
+            Status::Unset | Status::Ok => None,
+        };
+
+        Self {
+            trace_id: span.span_context.trace_id().to_bytes(),
+            span_id: span.span_context.span_id().to_bytes(),
+            parent_span_id: (span.parent_span_id != SpanId::INVALID)
+                .then(|| span.parent_span_id.to_bytes()),
+            name: span.name.to_string(),
+            start_time: span.start_time,
+            end_time: span.end_time,
+            attributes,
+            error,
+        }
+    }
+}
+
+/// Writes the finished spans of the node, usually into its trace table.
+pub trait SelfTraceHandler: Send + Sync {
+    /// Handles a batch of finished spans. It's called by the exporter and should
+    /// not block.
+    fn handle(&self, spans: Vec<SelfSpan>);
+}
+
+pub type SelfTraceHandlerRef = Arc<dyn SelfTraceHandler>;
+
+static SELF_TRACE_HANDLER: Lazy<RwLock<Option<SelfTraceHandlerRef>>> =
+    Lazy::new(|| RwLock::new(None));
+
+/// Registers the handler of the spans exported when self tracing is enabled.
+/// The spans finished before the registration are dropped.
+pub fn register_self_trace_handler(handler: SelfTraceHandlerRef) {
+    *SELF_TRACE_HANDLER.write().unwrap() = Some(handler);
+}
+
+/// Exports the sampled spans to the registered [SelfTraceHandler].
+#[derive(Debug)]
+pub(crate) struct SelfTraceExporter {
+    sample_ratio: f64,
+}
+
+impl SelfTraceExporter {
+    pub(crate) fn new(sample_ratio: f64) -> Self {
+        Self { sample_ratio }
+    }
+}
+
+impl SpanExporter for SelfTraceExporter {
+    fn export(
+        &mut self,
+        batch: Vec<SpanData>,
+    ) -> Pin<Box<dyn Future<Output = ExportResult> + Send + 'static>> {
+        let handler = SELF_TRACE_HANDLER.read().unwrap().clone();
+        if let Some(handler) = handler {
+            // Sample by the trace id, so a trace is either written or dropped as a whole.
+            let spans = batch
+                .into_iter()
+                .filter(|span| {
+                    sample_based_on_probability(self.sample_ratio, span.span_context.trace_id())
+                        == SamplingDecision::RecordAndSample
+                })
+                .map(SelfSpan::from)
+                .collect::<Vec<_>>();
+            if !spans.is_empty() {
+                handler.handle(spans);
+            }
+        }
+        Box::pin(std::future::ready(Ok(())))
+    }
+}
+
+/// Samples the root spans with the inner sampler, except the spans of writing
+/// the self traces, see [SELF_TRACE_EXPORT_SPAN].
+#[derive(Debug, Clone)]
+pub(crate) struct SelfTraceSampler {
+    inner: Box<dyn ShouldSample>,
+}
+
+impl SelfTraceSampler {
+    pub(crate) fn new(inner: Box<dyn ShouldSample>) -> Self {
+        Self { inner }
+    }
+}
+
+impl ShouldSample for SelfTraceSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&opentelemetry::Context>,
+        trace_id: TraceId,
+        name: &str,
+        span_kind: &SpanKind,
+        attributes: &[KeyValue],
+        links: &[Link],
+    ) -> SamplingResult {
+        if name == SELF_TRACE_EXPORT_SPAN {
+            return SamplingResult {
+                decision: SamplingDecision::Drop,
+                attributes: Vec::new(),
+                trace_state: TraceState::default(),
+            };
+        }
+        self.inner
+            .should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_sdk::trace::{config, Sampler, TracerProvider};
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Registry;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct CollectHandler {
+        spans: Mutex<Vec<SelfSpan>>,
+    }
+
+    impl SelfTraceHandler for CollectHandler {
+        fn handle(&self, spans: Vec<SelfSpan>) {
+            self.spans.lock().unwrap().extend(spans);
+        }
+    }
+
+    #[test]
+    fn test_export_span_tree() {
+        let handler = Arc::new(CollectHandler::default());
+        register_self_trace_handler(handler.clone());
+
+        let provider = TracerProvider::builder()
+            .with_config(config().with_sampler(Sampler::ParentBased(Box::new(
+                SelfTraceSampler::new(Box::new(Sampler::AlwaysOn)),
+            ))))
+            .with_simple_exporter(SelfTraceExporter::new(1.0))
+            .build();
+        let subscriber = Registry::default()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info_span!("execute", table = "monitor").in_scope(|| {
+                let scan = tracing::info_span!("scan", rows = tracing::field::Empty);
+                scan.record("rows", 42);
+            });
+            // the writes of self traces are not traced
+            tracing::info_span!(SELF_TRACE_EXPORT_SPAN).in_scope(|| {
+                let _ = tracing::info_span!("insert").entered();
+            });
+        });
+        let _ = provider.force_flush();
+
+        let spans = handler.spans.lock().unwrap().clone();
+        assert_eq!(2, spans.len(), "{spans:?}");
+        let execute = spans.iter().find(|span| span.name == "execute").unwrap();
+        let scan = spans.iter().find(|span| span.name == "scan").unwrap();
+        assert_eq!(None, execute.parent_span_id);
+        assert_eq!(Some(execute.span_id), scan.parent_span_id);
+        assert_eq!(execute.trace_id, scan.trace_id);
+        assert!(execute.attributes.contains(&(
+            "table".to_string(),
+            SelfSpanValue::String("monitor".to_string())
+        )));
+        assert!(scan
+            .attributes
+            .contains(&("rows".to_string(), SelfSpanValue::I64(42))));
+    }
+}
@@ -103,3 +103,36 @@ impl TracingContext {
         Self::from_w3c(&fields)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[test]
+    fn test_attach_to_w3c_context() {
+        let tracer = TracerProvider::builder().build().tracer("test");
+        let subscriber =
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let fields = W3cTrace::from([(
+                "traceparent".to_string(),
+                "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01".to_string(),
+            )]);
+            let context = TracingContext::from_w3c(&fields);
+
+            let span = context.attach(tracing::info_span!("parent"));
+            let _guard = span.enter();
+            let child = TracingContext::from_span(&tracing::info_span!("child")).to_w3c();
+
+            // The child span belongs to the remote trace, and is not the remote span itself.
+            let traceparent = &child["traceparent"];
+            assert!(traceparent.starts_with("00-0af7651916cd43dd8448eb211c80319c-"));
+            assert!(!traceparent.contains("b7ad6b7169203331"));
+        });
+    }
+}
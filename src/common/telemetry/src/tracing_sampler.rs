@@ -130,7 +130,7 @@ impl ShouldSample for TracingSampleOptions {
 /// The code here mainly refers to the relevant implementation of
 /// [opentelemetry](https://github.com/open-telemetry/opentelemetry-rust/blob/ef4701055cc39d3448d5e5392812ded00cdd4476/opentelemetry-sdk/src/trace/sampler.rs#L229),
 /// and determines whether the span needs to be collected based on the `TraceId` and sampling rate (i.e. `prob`).
-fn sample_based_on_probability(prob: f64, trace_id: TraceId) -> SamplingDecision {
+pub(crate) fn sample_based_on_probability(prob: f64, trace_id: TraceId) -> SamplingDecision {
     if prob >= 1.0 {
         SamplingDecision::RecordAndSample
     } else {
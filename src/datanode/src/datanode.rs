@@ -80,6 +80,7 @@ pub struct Datanode {
     leases_notifier: Option<Arc<Notify>>,
     plugins: Plugins,
     export_metrics_task: Option<ExportMetricsTask>,
+    drain_timeout: Duration,
 }
 
 impl Datanode {
@@ -129,7 +130,11 @@ impl Datanode {
         self.services = services;
     }
 
+    /// Shuts down the datanode gracefully. It stops accepting new region requests
+    /// and drains the in-flight ones before shutting down the services.
     pub async fn shutdown(&self) -> Result<()> {
+        self.region_server.drain(self.drain_timeout).await;
+
         self.services
             .shutdown_all()
             .await
@@ -300,6 +305,7 @@ impl DatanodeBuilder {
             leases_notifier,
             plugins: self.plugins.clone(),
             export_metrics_task,
+            drain_timeout: self.opts.drain_timeout,
         })
     }
 
@@ -282,6 +282,12 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Datanode is shutting down"))]
+    ShuttingDown {
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Region {} is busy", region_id))]
     RegionBusy {
         region_id: RegionId,
@@ -434,7 +440,7 @@ impl ErrorExt for Error {
             }
 
             RegionNotFound { .. } => StatusCode::RegionNotFound,
-            RegionNotReady { .. } => StatusCode::RegionNotReady,
+            RegionNotReady { .. } | ShuttingDown { .. } => StatusCode::RegionNotReady,
             RegionBusy { .. } => StatusCode::RegionBusy,
 
             StartServer { source, .. } | ShutdownServer { source, .. } => source.status_code(),
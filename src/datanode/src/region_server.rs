@@ -19,13 +19,14 @@ use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 use api::region::RegionResponse;
-use api::v1::region::{region_request, RegionResponse as RegionResponseV1};
+use api::v1::region::{region_request, RegionRequestHeader, RegionResponse as RegionResponseV1};
 use api::v1::{ResponseHeader, Status};
-use arrow_flight::{FlightData, Ticket};
+use arrow_flight::{Action, FlightData, Ticket};
 use async_trait::async_trait;
 use bytes::Bytes;
 use common_error::ext::BoxedError;
 use common_error::status_code::StatusCode;
+use common_meta::node_manager::{RegionAction, REGION_ACTION_TYPE};
 use common_query::request::QueryRequest;
 use common_query::OutputData;
 use common_recordbatch::SendableRecordBatchStream;
@@ -33,6 +34,7 @@ use common_runtime::Runtime;
 use common_telemetry::tracing::{self, info_span};
 use common_telemetry::tracing_context::{FutureExt, TracingContext};
 use common_telemetry::{debug, error, info, warn};
+use common_time::range::TimestampRange;
 use dashmap::DashMap;
 use datafusion::datasource::{provider_as_source, TableProvider};
 use datafusion::error::Result as DfResult;
@@ -46,22 +48,27 @@ pub use query::dummy_catalog::{
     DummyCatalogList, DummyTableProviderFactory, TableProviderFactoryRef,
 };
 use query::QueryEngineRef;
+use servers::drain::{DrainStateRef, InflightGuard};
 use servers::error::{self as servers_error, ExecuteGrpcRequestSnafu, Result as ServerResult};
 use servers::grpc::flight::{FlightCraft, FlightRecordBatchStream, TonicStream};
 use servers::grpc::region_server::RegionServerHandler;
 use session::context::{QueryContextBuilder, QueryContextRef};
 use snafu::{ensure, OptionExt, ResultExt};
+use store_api::manifest::ManifestVersion;
 use store_api::metric_engine_consts::{
     FILE_ENGINE_NAME, LOGICAL_TABLE_METADATA_KEY, METRIC_ENGINE_NAME,
 };
 use store_api::region_engine::{
-    RegionEngineRef, RegionManifestInfo, RegionRole, RegionStatistic, SetRegionRoleStateResponse,
+    LabelValuesRequest, RegionEngineRef, RegionManifestInfo, RegionManifestSnapshot, RegionRole,
+    RegionStatistic, SeriesCardinality, SeriesCardinalityRequest, SetRegionRoleStateResponse,
     SettableRegionRoleState,
 };
 use store_api::region_request::{
-    AffectedRows, BatchRegionDdlRequest, RegionCloseRequest, RegionOpenRequest, RegionRequest,
+    AffectedRows, BatchRegionDdlRequest, IdempotencyKey, RegionBuildIndexRequest,
+    RegionCloseRequest, RegionFlushRequest, RegionOpenRequest, RegionRequest,
+    RegionTruncateRangeRequest,
 };
-use store_api::storage::RegionId;
+use store_api::storage::{RegionId, SequenceNumber};
 use tokio::sync::{Semaphore, SemaphorePermit};
 use tokio::time::timeout;
 use tonic::{Request, Response, Result as TonicResult};
@@ -71,7 +78,7 @@ use crate::error::{
     ConcurrentQueryLimiterTimeoutSnafu, DataFusionSnafu, DecodeLogicalPlanSnafu,
     ExecuteLogicalPlanSnafu, FindLogicalRegionsSnafu, HandleBatchDdlRequestSnafu,
     HandleBatchOpenRequestSnafu, HandleRegionRequestSnafu, NewPlanDecoderSnafu,
-    RegionEngineNotFoundSnafu, RegionNotFoundSnafu, RegionNotReadySnafu, Result,
+    RegionEngineNotFoundSnafu, RegionNotFoundSnafu, RegionNotReadySnafu, Result, ShuttingDownSnafu,
     StopRegionEngineSnafu, UnexpectedSnafu, UnsupportedOutputSnafu,
 };
 use crate::event_listener::RegionServerEventListenerRef;
@@ -188,6 +195,7 @@ impl RegionServer {
         &self,
         request: api::v1::region::QueryRequest,
     ) -> Result<SendableRecordBatchStream> {
+        let guard = self.enter()?;
         let _permit = if let Some(p) = &self.inner.parallelism {
             Some(p.acquire().await?)
         } else {
@@ -223,10 +231,12 @@ impl RegionServer {
                 plan,
             })
             .await
+            .map(|stream| guard.attach_stream(stream))
     }
 
     #[tracing::instrument(skip_all)]
     pub async fn handle_read(&self, request: QueryRequest) -> Result<SendableRecordBatchStream> {
+        let guard = self.enter()?;
         let _permit = if let Some(p) = &self.inner.parallelism {
             Some(p.acquire().await?)
         } else {
@@ -266,6 +276,7 @@ impl RegionServer {
         self.inner
             .handle_read(QueryRequest { plan, ..request })
             .await
+            .map(|stream| guard.attach_stream(stream))
     }
 
     /// Returns all opened and reportable regions.
@@ -324,6 +335,132 @@ impl RegionServer {
             .with_context(|_| HandleRegionRequestSnafu { region_id })
     }
 
+    /// Returns the distinct values of a tag column in the region without scanning
+    /// the data, or `None` if the engine of the region can't serve the request.
+    pub async fn label_values(
+        &self,
+        region_id: RegionId,
+        request: LabelValuesRequest,
+    ) -> Result<Option<Vec<String>>> {
+        let engine = self
+            .inner
+            .region_map
+            .get(&region_id)
+            .with_context(|| RegionNotFoundSnafu { region_id })?;
+        engine
+            .label_values(region_id, request)
+            .await
+            .with_context(|_| HandleRegionRequestSnafu { region_id })
+    }
+
+    /// Counts the series of the region, or returns `None` if the engine of the region
+    /// doesn't support it.
+    pub async fn series_cardinality(
+        &self,
+        region_id: RegionId,
+        request: SeriesCardinalityRequest,
+    ) -> Result<Option<SeriesCardinality>> {
+        let engine = self
+            .inner
+            .region_map
+            .get(&region_id)
+            .with_context(|| RegionNotFoundSnafu { region_id })?;
+        engine
+            .series_cardinality(region_id, request)
+            .await
+            .with_context(|_| HandleRegionRequestSnafu { region_id })
+    }
+
+    /// Returns the SST files in the manifest of the region, or `None` if the engine
+    /// of the region doesn't support it.
+    pub async fn region_manifest(
+        &self,
+        region_id: RegionId,
+    ) -> Result<Option<RegionManifestSnapshot>> {
+        let engine = self
+            .inner
+            .region_map
+            .get(&region_id)
+            .with_context(|| RegionNotFoundSnafu { region_id })?;
+        engine
+            .region_manifest(region_id)
+            .await
+            .with_context(|_| HandleRegionRequestSnafu { region_id })
+    }
+
+    /// Returns the watermarks of the producers writing to the region, or `None` if the
+    /// engine of the region doesn't deduplicate writes.
+    pub async fn producer_watermarks(
+        &self,
+        region_id: RegionId,
+    ) -> Result<Option<HashMap<String, u64>>> {
+        let engine = self
+            .inner
+            .region_map
+            .get(&region_id)
+            .with_context(|| RegionNotFoundSnafu { region_id })?;
+        engine
+            .producer_watermarks(region_id)
+            .await
+            .with_context(|_| HandleRegionRequestSnafu { region_id })
+    }
+
+    /// Returns the latest committed sequence of the region, or `None` if the engine
+    /// of the region doesn't have sequences.
+    pub async fn region_sequence(&self, region_id: RegionId) -> Result<Option<SequenceNumber>> {
+        let engine = self
+            .inner
+            .region_map
+            .get(&region_id)
+            .with_context(|| RegionNotFoundSnafu { region_id })?;
+        engine
+            .get_last_seq_num(region_id)
+            .await
+            .with_context(|_| HandleRegionRequestSnafu { region_id })
+    }
+
+    /// Saves a checkpoint of the manifest of the region and returns its version, or
+    /// `None` if the engine of the region doesn't support it.
+    pub async fn checkpoint_region(&self, region_id: RegionId) -> Result<Option<ManifestVersion>> {
+        let engine = self
+            .inner
+            .region_map
+            .get(&region_id)
+            .with_context(|| RegionNotFoundSnafu { region_id })?;
+        engine
+            .checkpoint_region(region_id)
+            .await
+            .with_context(|_| HandleRegionRequestSnafu { region_id })
+    }
+
+    /// Builds the missing indexes of the SSTs in the region, returns the number of files
+    /// indexed.
+    pub async fn build_index(&self, region_id: RegionId) -> Result<AffectedRows> {
+        let response = self
+            .handle_request(
+                region_id,
+                RegionRequest::BuildIndex(RegionBuildIndexRequest::default()),
+            )
+            .await?;
+        Ok(response.affected_rows)
+    }
+
+    /// Removes the SST files of the region whose rows are all in the time range, returns
+    /// the number of rows in them.
+    pub async fn truncate_range(
+        &self,
+        region_id: RegionId,
+        time_range: TimestampRange,
+    ) -> Result<AffectedRows> {
+        let response = self
+            .handle_request(
+                region_id,
+                RegionRequest::TruncateRange(RegionTruncateRangeRequest { time_range }),
+            )
+            .await?;
+        Ok(response.affected_rows)
+    }
+
     /// Set region role state gracefully.
     ///
     /// For [SettableRegionRoleState::Follower]:
@@ -363,6 +500,66 @@ impl RegionServer {
         self.inner.stop().await
     }
 
+    /// Returns the state of draining the in-flight requests of the region server.
+    pub fn drain_state(&self) -> DrainStateRef {
+        self.inner.drain_state.clone()
+    }
+
+    /// Stops accepting new region requests and waits for the in-flight ones to finish
+    /// up to `timeout`. Then flushes the memtables and checkpoints the manifests of the
+    /// leader regions, so they are opened quickly without replaying the WAL.
+    pub async fn drain(&self, timeout: Duration) {
+        let drain_state = &self.inner.drain_state;
+        info!(
+            "Draining region server, in-flight requests: {}",
+            drain_state.inflight()
+        );
+        if !drain_state.drain(timeout).await {
+            warn!(
+                "Region server is not drained in {:?}, in-flight requests: {}",
+                timeout,
+                drain_state.inflight()
+            );
+        }
+
+        let regions = self
+            .inner
+            .region_map
+            .iter()
+            .filter(|x| {
+                matches!(x.value(), RegionEngineWithStatus::Ready(_))
+                    && x.value().name() != FILE_ENGINE_NAME
+                    && x.value().role(*x.key()) == Some(RegionRole::Leader)
+            })
+            .map(|x| (*x.key(), x.value().clone().into_engine()))
+            .collect::<Vec<_>>();
+        let num_regions = regions.len();
+        let tasks = regions.into_iter().map(|(region_id, engine)| async move {
+            if let Err(e) = engine
+                .handle_request(
+                    region_id,
+                    RegionRequest::Flush(RegionFlushRequest {
+                        row_group_size: None,
+                    }),
+                )
+                .await
+            {
+                warn!(e; "Failed to flush region {region_id} on shutdown");
+                return;
+            }
+            if let Err(e) = engine.checkpoint_region(region_id).await {
+                warn!(e; "Failed to checkpoint region {region_id} on shutdown");
+            }
+        });
+        futures_util::future::join_all(tasks).await;
+        info!("Flushed {num_regions} regions on shutdown");
+    }
+
+    /// Registers an in-flight request, fails if the region server is draining.
+    fn enter(&self) -> Result<InflightGuard> {
+        self.inner.drain_state.enter().context(ShuttingDownSnafu)
+    }
+
     #[cfg(test)]
     /// Registers a region for test purpose.
     pub(crate) fn register_test_region(&self, region_id: RegionId, engine: RegionEngineRef) {
@@ -390,13 +587,19 @@ impl RegionServer {
 
     async fn handle_requests_in_parallel(
         &self,
+        header: &RegionRequestHeader,
         request: region_request::Body,
     ) -> Result<RegionResponse> {
+        let idempotency_key =
+            IdempotencyKey::from_header(Some(header)).context(BuildRegionRequestsSnafu)?;
         let requests =
             RegionRequest::try_from_request_body(request).context(BuildRegionRequestsSnafu)?;
         let tracing_context = TracingContext::from_current_span();
 
-        let join_tasks = requests.into_iter().map(|(region_id, req)| {
+        let join_tasks = requests.into_iter().map(|(region_id, mut req)| {
+            if let RegionRequest::Put(put) = &mut req {
+                put.idempotency_key = idempotency_key.clone();
+            }
             let self_to_move = self;
             let span = tracing_context.attach(info_span!(
                 "RegionServer::handle_region_request",
@@ -456,13 +659,21 @@ impl RegionServer {
 
 #[async_trait]
 impl RegionServerHandler for RegionServer {
-    async fn handle(&self, request: region_request::Body) -> ServerResult<RegionResponseV1> {
+    async fn handle(
+        &self,
+        header: RegionRequestHeader,
+        request: region_request::Body,
+    ) -> ServerResult<RegionResponseV1> {
+        let _guard = self
+            .enter()
+            .map_err(BoxedError::new)
+            .context(ExecuteGrpcRequestSnafu)?;
         let response = match &request {
             region_request::Body::Creates(_)
             | region_request::Body::Drops(_)
             | region_request::Body::Alters(_) => self.handle_batch_ddl_requests(request).await,
             region_request::Body::Inserts(_) | region_request::Body::Deletes(_) => {
-                self.handle_requests_in_parallel(request).await
+                self.handle_requests_in_parallel(&header, request).await
             }
             _ => self.handle_requests_in_serial(request).await,
         }
@@ -505,6 +716,54 @@ impl FlightCraft for RegionServer {
         let stream = Box::pin(FlightRecordBatchStream::new(result, tracing_context));
         Ok(Response::new(stream))
     }
+
+    async fn do_action(
+        &self,
+        request: Request<Action>,
+    ) -> TonicResult<Response<TonicStream<arrow_flight::Result>>> {
+        let action = request.into_inner();
+        if action.r#type != REGION_ACTION_TYPE {
+            return Err(tonic::Status::unimplemented(format!(
+                "Unknown Flight action: {}",
+                action.r#type
+            )));
+        }
+        // Actions added in later versions are unknown to this datanode.
+        let action: RegionAction = serde_json::from_slice(&action.body)
+            .map_err(|e| tonic::Status::unimplemented(format!("Unknown region action: {e}")))?;
+
+        let body = match action {
+            RegionAction::LabelValues { region_id, request } => {
+                serde_json::to_vec(&self.label_values(region_id, request).await?)
+            }
+            RegionAction::SeriesCardinality { region_id, request } => {
+                serde_json::to_vec(&self.series_cardinality(region_id, request).await?)
+            }
+            RegionAction::RegionManifest { region_id } => {
+                serde_json::to_vec(&self.region_manifest(region_id).await?)
+            }
+            RegionAction::CheckpointRegion { region_id } => {
+                serde_json::to_vec(&self.checkpoint_region(region_id).await?)
+            }
+            RegionAction::ProducerWatermarks { region_id } => {
+                serde_json::to_vec(&self.producer_watermarks(region_id).await?)
+            }
+            RegionAction::RegionSequence { region_id } => {
+                serde_json::to_vec(&self.region_sequence(region_id).await?)
+            }
+            RegionAction::BuildIndex { region_id } => {
+                serde_json::to_vec(&self.build_index(region_id).await?)
+            }
+            RegionAction::TruncateRange {
+                region_id,
+                time_range,
+            } => serde_json::to_vec(&self.truncate_range(region_id, time_range).await?),
+        }
+        .context(servers_error::ToJsonSnafu)?;
+
+        let stream = futures::stream::iter([Ok(arrow_flight::Result { body: body.into() })]);
+        Ok(Response::new(Box::pin(stream)))
+    }
 }
 
 #[derive(Clone)]
@@ -550,6 +809,7 @@ struct RegionServerInner {
     // The number of queries allowed to be executed at the same time.
     // Act as last line of defense on datanode to prevent query overloading.
     parallelism: Option<RegionServerParallelism>,
+    drain_state: DrainStateRef,
 }
 
 struct RegionServerParallelism {
@@ -615,6 +875,7 @@ impl RegionServerInner {
             event_listener,
             table_provider_factory,
             parallelism,
+            drain_state: DrainStateRef::default(),
         }
     }
 
@@ -879,7 +1140,9 @@ impl RegionServerInner {
             | RegionRequest::Alter(_)
             | RegionRequest::Flush(_)
             | RegionRequest::Compact(_)
-            | RegionRequest::Truncate(_) => RegionChange::None,
+            | RegionRequest::Truncate(_)
+            | RegionRequest::TruncateRange(_)
+            | RegionRequest::BuildIndex(_) => RegionChange::None,
             RegionRequest::Catchup(_) => RegionChange::Catchup,
         };
 
@@ -1152,6 +1415,8 @@ mod tests {
     use std::assert_matches::assert_matches;
 
     use common_error::ext::ErrorExt;
+    use common_time::Timestamp;
+    use futures_util::StreamExt;
     use mito2::test_util::CreateRequestBuilder;
     use store_api::region_engine::RegionEngine;
     use store_api::region_request::{RegionDropRequest, RegionOpenRequest, RegionTruncateRequest};
@@ -1295,6 +1560,66 @@ mod tests {
         assert_eq!(err.status_code(), StatusCode::RegionNotReady);
     }
 
+    #[tokio::test]
+    async fn test_region_action() {
+        common_telemetry::init_default_ut_logging();
+
+        let mut mock_region_server = mock_region_server();
+        let (engine, _receiver) = MockRegionEngine::new(MITO_ENGINE_NAME);
+        mock_region_server.register_engine(engine.clone());
+        let region_id = RegionId::new(1024, 1);
+        mock_region_server
+            .inner
+            .region_map
+            .insert(region_id, RegionEngineWithStatus::Ready(engine));
+
+        let action = RegionAction::LabelValues {
+            region_id,
+            request: LabelValuesRequest {
+                column_name: "host".to_string(),
+                start: Timestamp::new_millisecond(0),
+                end: Timestamp::new_millisecond(1000),
+            },
+        };
+        let response = mock_region_server
+            .do_action(Request::new(Action {
+                r#type: REGION_ACTION_TYPE.to_string(),
+                body: serde_json::to_vec(&action).unwrap().into(),
+            }))
+            .await
+            .unwrap();
+        let results = response
+            .into_inner()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<TonicResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(1, results.len());
+        // The mock engine doesn't serve label values.
+        let values: Option<Vec<String>> = serde_json::from_slice(&results[0].body).unwrap();
+        assert!(values.is_none());
+
+        let status = mock_region_server
+            .do_action(Request::new(Action {
+                r#type: "unknown".to_string(),
+                body: Bytes::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(tonic::Code::Unimplemented, status.code());
+
+        // Region actions of later versions.
+        let status = mock_region_server
+            .do_action(Request::new(Action {
+                r#type: REGION_ACTION_TYPE.to_string(),
+                body: Bytes::from_static(br#"{"UnknownAction":{"region_id":4398046511105}}"#),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(tonic::Code::Unimplemented, status.code());
+    }
+
     #[tokio::test]
     async fn test_region_request_failed() {
         common_telemetry::init_default_ut_logging();
@@ -1496,6 +1821,65 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_drain_region_server() {
+        common_telemetry::init_default_ut_logging();
+
+        let mut mock_region_server = mock_region_server();
+        let (engine, mut receiver) =
+            MockRegionEngine::with_custom_apply_fn(MITO_ENGINE_NAME, |engine| {
+                engine.handle_request_delay = Some(Duration::from_millis(300));
+            });
+        mock_region_server.register_engine(engine.clone());
+        let region_id = RegionId::new(1024, 1);
+        mock_region_server
+            .inner
+            .region_map
+            .insert(region_id, RegionEngineWithStatus::Ready(engine));
+        let flush_body = || {
+            region_request::Body::Flush(api::v1::region::FlushRequest {
+                region_id: region_id.as_u64(),
+            })
+        };
+
+        // A slow request is in flight when the drain starts.
+        let slow_request = {
+            let region_server = mock_region_server.clone();
+            let body = flush_body();
+            tokio::spawn(async move {
+                region_server
+                    .handle(RegionRequestHeader::default(), body)
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let drain = {
+            let region_server = mock_region_server.clone();
+            tokio::spawn(async move { region_server.drain(Duration::from_secs(10)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(mock_region_server.drain_state().is_draining());
+
+        // New requests are rejected with a retryable status.
+        let err = mock_region_server
+            .handle(RegionRequestHeader::default(), flush_body())
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::RegionNotReady, err.status_code());
+        assert!(err.status_code().is_retryable());
+
+        // The slow request completes, then the region is flushed.
+        slow_request.await.unwrap().unwrap();
+        drain.await.unwrap();
+        assert_eq!(0, mock_region_server.drain_state().inflight());
+        for _ in 0..2 {
+            let (id, request) = receiver.recv().await.unwrap();
+            assert_eq!(region_id, id);
+            assert_matches!(request, RegionRequest::Flush(_));
+        }
+        assert!(receiver.try_recv().is_err());
+    }
+
     #[tokio::test]
     async fn test_region_server_parallelism() {
         let p = RegionServerParallelism::from_opts(2, Duration::from_millis(1)).unwrap();
@@ -16,6 +16,7 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 
 use common_config::Configurable;
+use servers::drain::DrainStateRef;
 use servers::grpc::builder::GrpcServerBuilder;
 use servers::grpc::{GrpcServer, GrpcServerConfig};
 use servers::http::HttpServerBuilder;
@@ -31,6 +32,7 @@ pub struct DatanodeServiceBuilder<'a> {
     opts: &'a DatanodeOptions,
     grpc_server: Option<GrpcServer>,
     enable_http_service: bool,
+    drain_state: Option<DrainStateRef>,
 }
 
 impl<'a> DatanodeServiceBuilder<'a> {
@@ -39,6 +41,7 @@ impl<'a> DatanodeServiceBuilder<'a> {
             opts,
             grpc_server: None,
             enable_http_service: false,
+            drain_state: None,
         }
     }
 
@@ -62,6 +65,14 @@ impl<'a> DatanodeServiceBuilder<'a> {
         }
     }
 
+    /// Reports the readiness of the HTTP service by the `drain_state`.
+    pub fn with_drain_state(self, drain_state: DrainStateRef) -> Self {
+        Self {
+            drain_state: Some(drain_state),
+            ..self
+        }
+    }
+
     pub async fn build(mut self) -> Result<ServerHandlers> {
         let handlers = ServerHandlers::default();
 
@@ -74,10 +85,13 @@ impl<'a> DatanodeServiceBuilder<'a> {
         }
 
         if self.enable_http_service {
-            let http_server = HttpServerBuilder::new(self.opts.http.clone())
+            let mut builder = HttpServerBuilder::new(self.opts.http.clone())
                 .with_metrics_handler(MetricsHandler)
-                .with_greptime_config_options(self.opts.to_toml().context(TomlFormatSnafu)?)
-                .build();
+                .with_greptime_config_options(self.opts.to_toml().context(TomlFormatSnafu)?);
+            if let Some(drain_state) = self.drain_state.take() {
+                builder = builder.with_drain_state(drain_state);
+            }
+            let http_server = builder.build();
             let addr: SocketAddr = self.opts.http.addr.parse().context(ParseAddrSnafu {
                 addr: &self.opts.http.addr,
             })?;
@@ -22,9 +22,11 @@ use common_error::ext::BoxedError;
 use common_function::function::FunctionRef;
 use common_function::scalars::aggregate::AggregateFunctionMetaRef;
 use common_query::Output;
+use common_recordbatch::SendableRecordBatchStream;
 use common_runtime::runtime::{BuilderBuild, RuntimeTrait};
 use common_runtime::Runtime;
 use datafusion_expr::LogicalPlan;
+use promql_parser::parser::EvalStmt;
 use query::dataframe::DataFrame;
 use query::planner::LogicalPlanner;
 use query::query_engine::{DescribeResult, QueryEngineState};
@@ -76,6 +78,14 @@ impl QueryEngine for MockQueryEngine {
         unimplemented!()
     }
 
+    async fn stream_promql(
+        &self,
+        _stmt: EvalStmt,
+        _query_ctx: QueryContextRef,
+    ) -> query::error::Result<SendableRecordBatchStream> {
+        unimplemented!()
+    }
+
     fn register_aggregate_function(&self, _func: AggregateFunctionMetaRef) {}
 
     fn register_function(&self, _func: FunctionRef) {}
@@ -32,8 +32,8 @@ pub use crate::schema::column_schema::{
     SkippingIndexOptions, SkippingIndexType, COLUMN_FULLTEXT_CHANGE_OPT_KEY_ENABLE,
     COLUMN_FULLTEXT_OPT_KEY_ANALYZER, COLUMN_FULLTEXT_OPT_KEY_BACKEND,
     COLUMN_FULLTEXT_OPT_KEY_CASE_SENSITIVE, COLUMN_SKIPPING_INDEX_OPT_KEY_GRANULARITY,
-    COLUMN_SKIPPING_INDEX_OPT_KEY_TYPE, COMMENT_KEY, FULLTEXT_KEY, INVERTED_INDEX_KEY,
-    SKIPPING_INDEX_KEY, TIME_INDEX_KEY,
+    COLUMN_SKIPPING_INDEX_OPT_KEY_TYPE, COLUMN_SST_OPT_KEY_DICTIONARY, COMMENT_KEY, FULLTEXT_KEY,
+    INVERTED_INDEX_KEY, ON_UPDATE_KEY, SKIPPING_INDEX_KEY, SST_DICTIONARY_KEY, TIME_INDEX_KEY,
 };
 pub use crate::schema::constraint::ColumnDefaultConstraint;
 pub use crate::schema::raw::RawSchema;
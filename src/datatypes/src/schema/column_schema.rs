@@ -41,6 +41,10 @@ pub const FULLTEXT_KEY: &str = "greptime:fulltext";
 pub const INVERTED_INDEX_KEY: &str = "greptime:inverted_index";
 /// Key used to store skip options in arrow field's metadata.
 pub const SKIPPING_INDEX_KEY: &str = "greptime:skipping_index";
+/// Key used to store whether the column uses dictionary encoding in SST files.
+pub const SST_DICTIONARY_KEY: &str = "greptime:sst_dictionary";
+/// Key used to store the function that fills the column on every write that omits it.
+pub const ON_UPDATE_KEY: &str = "greptime:on_update";
 
 /// Keys used in fulltext options
 pub const COLUMN_FULLTEXT_CHANGE_OPT_KEY_ENABLE: &str = "enable";
@@ -52,6 +56,9 @@ pub const COLUMN_FULLTEXT_OPT_KEY_BACKEND: &str = "backend";
 pub const COLUMN_SKIPPING_INDEX_OPT_KEY_GRANULARITY: &str = "granularity";
 pub const COLUMN_SKIPPING_INDEX_OPT_KEY_TYPE: &str = "type";
 
+/// Keys used in column SST options
+pub const COLUMN_SST_OPT_KEY_DICTIONARY: &str = "dictionary";
+
 pub const DEFAULT_GRANULARITY: u32 = 10240;
 
 /// Schema of a column, used as an immutable struct.
@@ -147,6 +154,26 @@ impl ColumnSchema {
         self.metadata.get(COMMENT_KEY)
     }
 
+    /// Returns the function that fills the column on every write that omits it,
+    /// e.g. `now()` for `ON UPDATE now()`.
+    #[inline]
+    pub fn on_update(&self) -> Option<&String> {
+        self.metadata.get(ON_UPDATE_KEY)
+    }
+
+    /// Sets the function that fills the column on every write that omits it.
+    pub fn with_on_update(mut self, func: Option<String>) -> Self {
+        match func {
+            Some(func) => {
+                let _ = self.metadata.insert(ON_UPDATE_KEY.to_string(), func);
+            }
+            None => {
+                let _ = self.metadata.remove(ON_UPDATE_KEY);
+            }
+        }
+        self
+    }
+
     pub fn with_time_index(mut self, is_time_index: bool) -> Self {
         self.is_time_index = is_time_index;
         if is_time_index {
@@ -206,6 +233,36 @@ impl ColumnSchema {
         self.metadata.contains_key(INVERTED_INDEX_KEY)
     }
 
+    /// Set whether the column uses dictionary encoding in SST files.
+    /// Dictionary encoding is enabled by default.
+    pub fn set_sst_dictionary(&mut self, enabled: bool) {
+        match enabled {
+            true => {
+                self.metadata.remove(SST_DICTIONARY_KEY);
+            }
+            false => {
+                self.metadata
+                    .insert(SST_DICTIONARY_KEY.to_string(), "off".to_string());
+            }
+        }
+    }
+
+    /// Set whether the column uses dictionary encoding in SST files.
+    /// Similar to [set_sst_dictionary] but take the ownership and return a owned value.
+    ///
+    /// [set_sst_dictionary]: Self::set_sst_dictionary
+    pub fn with_sst_dictionary(mut self, enabled: bool) -> Self {
+        self.set_sst_dictionary(enabled);
+        self
+    }
+
+    pub fn is_sst_dictionary_enabled(&self) -> bool {
+        self.metadata
+            .get(SST_DICTIONARY_KEY)
+            .map(|v| !v.eq_ignore_ascii_case("off"))
+            .unwrap_or(true)
+    }
+
     /// Set default constraint.
     ///
     /// If a default constraint exists for the column, this method will
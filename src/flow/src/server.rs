@@ -19,6 +19,7 @@ use std::sync::Arc;
 
 use api::v1::{RowDeleteRequests, RowInsertRequests};
 use cache::{TABLE_FLOWNODE_SET_CACHE_NAME, TABLE_ROUTE_CACHE_NAME};
+use catalog::schema_limits::SchemaLimitOptions;
 use catalog::CatalogManagerRef;
 use common_base::Plugins;
 use common_error::ext::BoxedError;
@@ -35,6 +36,7 @@ use greptime_proto::v1::flow::{flow_server, FlowRequest, FlowResponse, InsertReq
 use itertools::Itertools;
 use operator::delete::Deleter;
 use operator::insert::Inserter;
+use operator::readonly::ReadonlyStateRef;
 use operator::statement::StatementExecutor;
 use partition::manager::PartitionRuleManager;
 use query::{QueryEngine, QueryEngineFactory};
@@ -581,17 +583,20 @@ impl FrontendInvoker {
                 name: TABLE_FLOWNODE_SET_CACHE_NAME,
             })?;
 
+        let readonly_state = ReadonlyStateRef::default();
         let inserter = Arc::new(Inserter::new(
             catalog_manager.clone(),
             partition_manager.clone(),
             node_manager.clone(),
             table_flownode_cache,
+            readonly_state.clone(),
         ));
 
         let deleter = Arc::new(Deleter::new(
             catalog_manager.clone(),
             partition_manager.clone(),
             node_manager.clone(),
+            readonly_state,
         ));
 
         let query_engine = flow_worker_manager.query_engine.clone();
@@ -604,6 +609,7 @@ impl FrontendInvoker {
             layered_cache_registry.clone(),
             inserter.clone(),
             table_route_cache,
+            SchemaLimitOptions::default(),
         ));
 
         let invoker = FrontendInvoker::new(inserter, deleter, statement_executor);
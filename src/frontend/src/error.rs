@@ -245,6 +245,13 @@ pub enum Error {
         source: query::promql::error::Error,
     },
 
+    #[snafu(display("Failed to create logical plan for prometheus series query"))]
+    PrometheusSeriesQueryPlan {
+        #[snafu(implicit)]
+        location: Location,
+        source: query::promql::error::Error,
+    },
+
     #[snafu(display("Failed to describe schema for given statement"))]
     DescribeStatement {
         #[snafu(implicit)]
@@ -304,6 +311,14 @@ pub enum Error {
     #[snafu(display("Invalid auth config"))]
     IllegalAuthConfig { source: auth::error::Error },
 
+    #[snafu(display("Invalid PromQL timezone: {}", timezone))]
+    InvalidPromqlTimezone {
+        timezone: String,
+        #[snafu(implicit)]
+        location: Location,
+        source: common_time::error::Error,
+    },
+
     #[snafu(display("Failed to serialize options to TOML"))]
     TomlFormat {
         #[snafu(implicit)]
@@ -341,6 +356,12 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Frontend is shutting down"))]
+    ShuttingDown {
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Failed to decode logical plan from substrait"))]
     SubstraitDecodeLogicalPlan {
         #[snafu(implicit)]
@@ -364,6 +385,7 @@ impl ErrorExt for Error {
             | Error::ColumnNotFound { .. }
             | Error::UnsupportedFormat { .. }
             | Error::IllegalAuthConfig { .. }
+            | Error::InvalidPromqlTimezone { .. }
             | Error::ColumnNoneDefaultValue { .. }
             | Error::IncompleteGrpcRequest { .. }
             | Error::InvalidTlsConfig { .. } => StatusCode::InvalidArguments,
@@ -382,7 +404,8 @@ impl ErrorExt for Error {
 
             Error::SubstraitDecodeLogicalPlan { source, .. } => source.status_code(),
 
-            Error::PrometheusLabelValuesQueryPlan { source, .. } => source.status_code(),
+            Error::PrometheusLabelValuesQueryPlan { source, .. }
+            | Error::PrometheusSeriesQueryPlan { source, .. } => source.status_code(),
 
             Error::CollectRecordbatch { .. } => StatusCode::EngineExecuteQuery,
 
@@ -423,6 +446,8 @@ impl ErrorExt for Error {
             Error::TableOperation { source, .. } => source.status_code(),
 
             Error::InFlightWriteBytesExceeded { .. } => StatusCode::RateLimited,
+
+            Error::ShuttingDown { .. } => StatusCode::TableUnavailable,
         }
     }
 
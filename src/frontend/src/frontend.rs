@@ -13,12 +13,16 @@
 // limitations under the License.
 
 use std::sync::Arc;
+use std::time::Duration;
 
+use catalog::schema_limits::SchemaLimitOptions;
 use common_base::readable_size::ReadableSize;
 use common_config::config::Configurable;
 use common_options::datanode::DatanodeClientOptions;
 use common_telemetry::logging::{LoggingOptions, TracingOptions};
+use common_telemetry::{register_self_trace_handler, warn};
 use meta_client::MetaClientOptions;
+use query::promql::RateFirstSamplePolicy;
 use serde::{Deserialize, Serialize};
 use servers::export_metrics::{ExportMetricsOption, ExportMetricsTask};
 use servers::grpc::GrpcOptions;
@@ -31,6 +35,7 @@ use crate::error;
 use crate::error::Result;
 use crate::heartbeat::HeartbeatTask;
 use crate::instance::prom_store::ExportMetricHandler;
+use crate::instance::self_trace::SelfTraceWriter;
 use crate::instance::Instance;
 use crate::service_config::{
     InfluxdbOptions, JaegerOptions, MysqlOptions, OpentsdbOptions, OtlpOptions, PostgresOptions,
@@ -42,6 +47,21 @@ use crate::service_config::{
 pub struct FrontendOptions {
     pub node_id: Option<String>,
     pub default_timezone: Option<String>,
+    pub promql_timezone: Option<String>,
+    pub promql_enable_latest_at: bool,
+    pub promql_enable_negative_offset: bool,
+    /// How far after the current time PromQL selectors with `@` can read.
+    #[serde(with = "humantime_serde")]
+    pub promql_max_at_lookahead: Option<Duration>,
+    pub promql_integer_counts: bool,
+    pub promql_created_timestamps: bool,
+    pub promql_rate_first_sample: RateFirstSamplePolicy,
+    pub promql_fill_forward: bool,
+    pub promql_propagate_nan: bool,
+    pub promql_resolve_bucket_suffix: bool,
+    /// How long PromQL series keep their last value after staleness markers.
+    #[serde(with = "humantime_serde")]
+    pub promql_staleness_delta: Option<Duration>,
     pub heartbeat: HeartbeatOptions,
     pub http: HttpOptions,
     pub grpc: GrpcOptions,
@@ -50,6 +70,7 @@ pub struct FrontendOptions {
     pub opentsdb: OpentsdbOptions,
     pub influxdb: InfluxdbOptions,
     pub prom_store: PromStoreOptions,
+    pub schema_limits: SchemaLimitOptions,
     pub jaeger: JaegerOptions,
     pub otlp: OtlpOptions,
     pub meta_client: Option<MetaClientOptions>,
@@ -59,6 +80,11 @@ pub struct FrontendOptions {
     pub export_metrics: ExportMetricsOption,
     pub tracing: TracingOptions,
     pub max_in_flight_write_bytes: Option<ReadableSize>,
+    /// The maximum bytes of the result of a query.
+    pub max_result_bytes: Option<ReadableSize>,
+    /// The maximum time to wait for the in-flight queries on shutdown.
+    #[serde(with = "humantime_serde")]
+    pub drain_timeout: Duration,
 }
 
 impl Default for FrontendOptions {
@@ -66,6 +92,17 @@ impl Default for FrontendOptions {
         Self {
             node_id: None,
             default_timezone: None,
+            promql_timezone: None,
+            promql_enable_latest_at: false,
+            promql_enable_negative_offset: false,
+            promql_max_at_lookahead: None,
+            promql_integer_counts: false,
+            promql_created_timestamps: false,
+            promql_rate_first_sample: RateFirstSamplePolicy::default(),
+            promql_fill_forward: false,
+            promql_propagate_nan: false,
+            promql_resolve_bucket_suffix: false,
+            promql_staleness_delta: None,
             heartbeat: HeartbeatOptions::frontend_default(),
             http: HttpOptions::default(),
             grpc: GrpcOptions::default(),
@@ -75,6 +112,7 @@ impl Default for FrontendOptions {
             influxdb: InfluxdbOptions::default(),
             jaeger: JaegerOptions::default(),
             prom_store: PromStoreOptions::default(),
+            schema_limits: SchemaLimitOptions::default(),
             otlp: OtlpOptions::default(),
             meta_client: None,
             logging: LoggingOptions::default(),
@@ -83,6 +121,8 @@ impl Default for FrontendOptions {
             export_metrics: ExportMetricsOption::default(),
             tracing: TracingOptions::default(),
             max_in_flight_write_bytes: None,
+            max_result_bytes: None,
+            drain_timeout: Duration::from_secs(30),
         }
     }
 }
@@ -108,6 +148,13 @@ impl Frontend {
             t.start().await?;
         }
 
+        if let Err(e) = self.instance.statement_executor().load_prom_rollups().await {
+            warn!(e; "Failed to load the PromQL rollups of flows");
+        }
+
+        // The spans are only exported to the handler if self tracing is enabled.
+        register_self_trace_handler(Arc::new(SelfTraceWriter::new(self.instance.clone())));
+
         if let Some(t) = self.export_metrics_task.as_ref() {
             if t.send_by_handler {
                 let inserter = self.instance.inserter().clone();
@@ -125,7 +172,11 @@ impl Frontend {
             .context(error::StartServerSnafu)
     }
 
+    /// Shuts down the frontend gracefully. It stops accepting new queries and
+    /// drains the in-flight ones before shutting down the servers.
     pub async fn shutdown(&self) -> Result<()> {
+        self.instance.drain().await;
+
         self.servers
             .shutdown_all()
             .await
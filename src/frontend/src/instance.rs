@@ -23,10 +23,11 @@ mod otlp;
 pub mod prom_store;
 mod promql;
 mod region_query;
+pub mod self_trace;
 pub mod standalone;
 
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use async_trait::async_trait;
 use auth::{PermissionChecker, PermissionCheckerRef, PermissionReq};
@@ -37,17 +38,19 @@ use common_config::KvBackendConfig;
 use common_error::ext::{BoxedError, ErrorExt};
 use common_meta::key::TableMetadataManagerRef;
 use common_meta::kv_backend::KvBackendRef;
+use common_meta::node_manager::NodeManagerRef;
 use common_meta::state_store::KvStateStore;
 use common_procedure::local::{LocalManager, ManagerConfig};
 use common_procedure::options::ProcedureConfig;
 use common_procedure::ProcedureManagerRef;
 use common_query::Output;
-use common_telemetry::{debug, error, info, tracing};
+use common_telemetry::{debug, error, info, tracing, warn};
 use datafusion_expr::LogicalPlan;
 use log_store::raft_engine::RaftEngineBackend;
 use operator::delete::DeleterRef;
 use operator::insert::InserterRef;
 use operator::statement::{StatementExecutor, StatementExecutorRef};
+use partition::manager::PartitionRuleManagerRef;
 use pipeline::pipeline_operator::PipelineOperator;
 use prometheus::HistogramTimer;
 use promql_parser::label::Matcher;
@@ -57,8 +60,10 @@ use query::query_engine::options::{validate_catalog_and_schema, QueryOptions};
 use query::query_engine::DescribeResult;
 use query::stats::StatementStatistics;
 use query::QueryEngineRef;
+use servers::drain::{DrainStateRef, InflightGuard};
 use servers::error as server_error;
 use servers::error::{AuthSnafu, ExecuteQuerySnafu, ParsePromQLSnafu};
+use servers::http::prometheus::TsdbStatus;
 use servers::interceptor::{
     PromQueryInterceptor, PromQueryInterceptorRef, SqlQueryInterceptor, SqlQueryInterceptorRef,
 };
@@ -71,13 +76,15 @@ use sql::dialect::Dialect;
 use sql::parser::{ParseOptions, ParserContext};
 use sql::statements::copy::{CopyDatabase, CopyTable};
 use sql::statements::statement::Statement;
+use sql::statements::table_sample::take_table_sample;
 use sqlparser::ast::ObjectName;
 pub use standalone::StandaloneDatanodeManager;
+use store_api::storage::{ScanHints, ScanSample, SCAN_HINTS_KEY};
 
 use crate::error::{
     self, Error, ExecLogicalPlanSnafu, ExecutePromqlSnafu, ExternalSnafu, InvalidSqlSnafu,
-    ParseSqlSnafu, PermissionSnafu, PlanStatementSnafu, Result, SqlExecInterceptedSnafu,
-    TableOperationSnafu,
+    ParseSqlSnafu, PermissionSnafu, PlanStatementSnafu, Result, ShuttingDownSnafu,
+    SqlExecInterceptedSnafu, TableOperationSnafu,
 };
 use crate::limiter::LimiterRef;
 
@@ -96,6 +103,10 @@ pub struct Instance {
     table_metadata_manager: TableMetadataManagerRef,
     stats: StatementStatistics,
     limiter: Option<LimiterRef>,
+    partition_manager: PartitionRuleManagerRef,
+    node_manager: NodeManagerRef,
+    drain_state: DrainStateRef,
+    drain_timeout: Duration,
 }
 
 impl Instance {
@@ -153,12 +164,80 @@ impl Instance {
     pub fn inserter(&self) -> &InserterRef {
         &self.inserter
     }
+
+    pub fn drain_state(&self) -> &DrainStateRef {
+        &self.drain_state
+    }
+
+    /// Stops accepting new queries and waits for the in-flight ones to finish
+    /// up to the drain timeout.
+    pub async fn drain(&self) {
+        info!(
+            "Draining frontend, in-flight queries: {}",
+            self.drain_state.inflight()
+        );
+        if !self.drain_state.drain(self.drain_timeout).await {
+            warn!(
+                "Frontend is not drained in {:?}, in-flight queries: {}",
+                self.drain_timeout,
+                self.drain_state.inflight()
+            );
+        }
+    }
+
+    /// Registers an in-flight query, fails if the frontend is draining.
+    pub(crate) fn enter(&self) -> Result<InflightGuard> {
+        self.drain_state.enter().context(ShuttingDownSnafu)
+    }
 }
 
 fn parse_stmt(sql: &str, dialect: &(dyn Dialect + Send + Sync)) -> Result<Vec<Statement>> {
     ParserContext::create_with_dialect(sql, dialect, ParseOptions::default()).context(ParseSqlSnafu)
 }
 
+/// Returns the hints of each statement, or no hints if they can't be matched
+/// with the `num_stmts` statements.
+fn parse_hints(
+    sql: &str,
+    dialect: &(dyn Dialect + Send + Sync),
+    num_stmts: usize,
+) -> Vec<Option<String>> {
+    match ParserContext::parse_hints(sql, dialect) {
+        Ok(hints) if hints.len() == num_stmts => hints,
+        _ => vec![None; num_stmts],
+    }
+}
+
+/// Returns a query context with the scan hints parsed from `hint` and the table
+/// sample taken from the `stmt`. Unknown hints are ignored with a warning.
+fn with_scan_hints(
+    query_ctx: &QueryContextRef,
+    hint: Option<&str>,
+    stmt: &mut Statement,
+) -> Result<QueryContextRef> {
+    let mut scan_hints = ScanHints::default();
+    if let Some(hint) = hint {
+        let (hints, unknown) = ScanHints::parse(hint);
+        if !unknown.is_empty() {
+            warn!("Ignore unknown hints: {:?}", unknown);
+        }
+        scan_hints = hints;
+    }
+    if let Some(sample) = take_table_sample(stmt).context(ParseSqlSnafu)? {
+        scan_hints.sample = Some(ScanSample {
+            fraction: sample.fraction,
+            seed: sample.seed.unwrap_or_default(),
+        });
+    }
+    if scan_hints.is_empty() {
+        return Ok(query_ctx.clone());
+    }
+
+    let mut query_ctx = (**query_ctx).clone();
+    query_ctx.set_extension(SCAN_HINTS_KEY, scan_hints.to_string());
+    Ok(Arc::new(query_ctx))
+}
+
 impl Instance {
     async fn query_statement(&self, stmt: Statement, query_ctx: QueryContextRef) -> Result<Output> {
         check_permission(self.plugins.clone(), &stmt, &query_ctx)?;
@@ -222,6 +301,10 @@ impl SqlQueryHandler for Instance {
 
     #[tracing::instrument(skip_all)]
     async fn do_query(&self, query: &str, query_ctx: QueryContextRef) -> Vec<Result<Output>> {
+        let guard = match self.enter() {
+            Ok(guard) => guard,
+            Err(e) => return vec![Err(e)],
+        };
         let query_interceptor_opt = self.plugins.get::<SqlQueryInterceptorRef<Error>>();
         let query_interceptor = query_interceptor_opt.as_ref();
         let query = match query_interceptor.pre_parsing(query, query_ctx.clone()) {
@@ -236,8 +319,16 @@ impl SqlQueryHandler for Instance {
             .and_then(|stmts| query_interceptor.post_parsing(stmts, query_ctx.clone()))
         {
             Ok(stmts) => {
+                let hints = parse_hints(query.as_ref(), query_ctx.sql_dialect(), stmts.len());
                 let mut results = Vec::with_capacity(stmts.len());
-                for stmt in stmts {
+                for (mut stmt, hint) in stmts.into_iter().zip(hints) {
+                    let query_ctx = match with_scan_hints(&query_ctx, hint.as_deref(), &mut stmt) {
+                        Ok(query_ctx) => query_ctx,
+                        Err(e) => {
+                            results.push(Err(e));
+                            break;
+                        }
+                    };
                     if let Err(e) = checker
                         .check_permission(
                             query_ctx.current_user(),
@@ -251,8 +342,9 @@ impl SqlQueryHandler for Instance {
 
                     match self.query_statement(stmt.clone(), query_ctx.clone()).await {
                         Ok(output) => {
-                            let output_result =
-                                query_interceptor.post_execute(output, query_ctx.clone());
+                            let output_result = query_interceptor
+                                .post_execute(output, query_ctx.clone())
+                                .map(|output| guard.clone().attach(output));
                             results.push(output_result);
                         }
                         Err(e) => {
@@ -358,6 +450,10 @@ impl PrometheusHandler for Instance {
         query: &PromQuery,
         query_ctx: QueryContextRef,
     ) -> server_error::Result<Output> {
+        let guard = self
+            .enter()
+            .map_err(BoxedError::new)
+            .context(ExecuteQuerySnafu)?;
         let interceptor = self
             .plugins
             .get::<PromQueryInterceptorRef<server_error::Error>>();
@@ -392,7 +488,7 @@ impl PrometheusHandler for Instance {
             .map_err(BoxedError::new)
             .context(ExecuteQuerySnafu)?;
 
-        Ok(interceptor.post_execute(output, query_ctx)?)
+        Ok(guard.attach(interceptor.post_execute(output, query_ctx)?))
     }
 
     async fn query_metric_names(
@@ -421,6 +517,17 @@ impl PrometheusHandler for Instance {
             .context(ExecuteQuerySnafu)
     }
 
+    async fn tsdb_status(
+        &self,
+        limit: usize,
+        ctx: &QueryContextRef,
+    ) -> server_error::Result<TsdbStatus> {
+        self.handle_tsdb_status(limit, ctx)
+            .await
+            .map_err(BoxedError::new)
+            .context(ExecuteQuerySnafu)
+    }
+
     fn catalog_manager(&self) -> CatalogManagerRef {
         self.catalog_manager.clone()
     }
@@ -539,6 +646,7 @@ pub fn check_permission(
         Statement::ShowFlows(stmt) => {
             validate_db_permission!(stmt, query_ctx);
         }
+        Statement::ShowProcedures(_stmt) => {}
         Statement::ShowStatus(_stmt) => {}
         Statement::ShowSearchPath(_stmt) => {}
         Statement::DescribeTable(stmt) => {
@@ -613,6 +721,7 @@ mod tests {
         let plugins: Plugins = Plugins::new();
         plugins.insert(QueryOptions {
             disallow_cross_catalog_query: true,
+            ..Default::default()
         });
 
         let sql = r#"
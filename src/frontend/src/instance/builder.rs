@@ -28,6 +28,7 @@ use operator::delete::Deleter;
 use operator::flow::FlowServiceOperator;
 use operator::insert::Inserter;
 use operator::procedure::ProcedureServiceOperator;
+use operator::readonly::ReadonlyStateRef;
 use operator::request::Requester;
 use operator::statement::{StatementExecutor, StatementExecutorRef};
 use operator::table::TableMutationOperator;
@@ -36,6 +37,7 @@ use pipeline::pipeline_operator::PipelineOperator;
 use query::region_query::RegionQueryHandlerFactoryRef;
 use query::stats::StatementStatistics;
 use query::QueryEngineFactory;
+use servers::drain::DrainStateRef;
 use snafu::OptionExt;
 
 use crate::error::{self, Result};
@@ -128,21 +130,25 @@ impl FrontendBuilder {
                     name: TABLE_FLOWNODE_SET_CACHE_NAME,
                 })?;
 
+        let readonly_state = ReadonlyStateRef::default();
         let inserter = Arc::new(Inserter::new(
             self.catalog_manager.clone(),
             partition_manager.clone(),
             node_manager.clone(),
             table_flownode_cache,
+            readonly_state.clone(),
         ));
         let deleter = Arc::new(Deleter::new(
             self.catalog_manager.clone(),
             partition_manager.clone(),
             node_manager.clone(),
+            readonly_state,
         ));
         let requester = Arc::new(Requester::new(
             self.catalog_manager.clone(),
-            partition_manager,
+            partition_manager.clone(),
             node_manager.clone(),
+            self.procedure_executor.clone(),
         ));
         let table_mutation_handler = Arc::new(TableMutationOperator::new(
             inserter.clone(),
@@ -177,6 +183,7 @@ impl FrontendBuilder {
             local_cache_invalidator,
             inserter.clone(),
             table_route_cache,
+            self.options.schema_limits.clone(),
         ));
 
         let pipeline_operator = Arc::new(PipelineOperator::new(
@@ -207,6 +214,10 @@ impl FrontendBuilder {
             table_metadata_manager: Arc::new(TableMetadataManager::new(kv_backend)),
             stats: self.stats,
             limiter,
+            partition_manager,
+            node_manager,
+            drain_state: DrainStateRef::default(),
+            drain_timeout: self.options.drain_timeout,
         })
     }
 }
@@ -260,6 +260,7 @@ impl Instance {
         requests: InsertRequests,
         ctx: QueryContextRef,
     ) -> Result<Output> {
+        let _guard = self.enter()?;
         self.inserter
             .handle_column_inserts(requests, ctx, self.statement_executor.as_ref())
             .await
@@ -272,6 +273,7 @@ impl Instance {
         requests: RowInsertRequests,
         ctx: QueryContextRef,
     ) -> Result<Output> {
+        let _guard = self.enter()?;
         self.inserter
             .handle_row_inserts(requests, ctx, self.statement_executor.as_ref())
             .await
@@ -284,6 +286,7 @@ impl Instance {
         requests: RowInsertRequests,
         ctx: QueryContextRef,
     ) -> Result<Output> {
+        let _guard = self.enter()?;
         self.inserter
             .handle_last_non_null_inserts(requests, ctx, self.statement_executor.as_ref())
             .await
@@ -297,6 +300,7 @@ impl Instance {
         ctx: QueryContextRef,
         physical_table: String,
     ) -> Result<Output> {
+        let _guard = self.enter()?;
         self.inserter
             .handle_metric_row_inserts(requests, ctx, &self.statement_executor, physical_table)
             .await
@@ -309,6 +313,7 @@ impl Instance {
         requests: DeleteRequests,
         ctx: QueryContextRef,
     ) -> Result<Output> {
+        let _guard = self.enter()?;
         self.deleter
             .handle_column_deletes(requests, ctx)
             .await
@@ -321,6 +326,7 @@ impl Instance {
         requests: RowDeleteRequests,
         ctx: QueryContextRef,
     ) -> Result<Output> {
+        let _guard = self.enter()?;
         self.deleter
             .handle_row_deletes(requests, ctx)
             .await
@@ -111,6 +111,10 @@ impl Instance {
         log: RowInsertRequests,
         ctx: QueryContextRef,
     ) -> ServerResult<Output> {
+        let _inflight_guard = self
+            .enter()
+            .map_err(BoxedError::new)
+            .context(ExecuteGrpcRequestSnafu)?;
         let _guard = if let Some(limiter) = &self.limiter {
             let result = limiter.limit_row_inserts(&log);
             if result.is_none() {
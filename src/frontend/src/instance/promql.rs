@@ -12,28 +12,37 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::time::SystemTime;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use catalog::information_schema::TABLES;
 use client::OutputData;
-use common_catalog::consts::INFORMATION_SCHEMA_NAME;
+use common_catalog::consts::{INFORMATION_SCHEMA_NAME, MITO_ENGINE};
 use common_catalog::format_full_table_name;
 use common_recordbatch::util;
-use common_telemetry::tracing;
+use common_telemetry::{tracing, warn};
+use common_time::Timestamp;
 use datatypes::prelude::Value;
+use futures::StreamExt;
 use promql_parser::label::{Matcher, Matchers};
 use query::promql;
 use query::promql::planner::PromPlanner;
+use servers::http::prometheus::{HeadStats, TsdbStat, TsdbStatus};
 use servers::prometheus;
 use session::context::QueryContextRef;
+use session::ReadPreference;
 use snafu::{OptionExt, ResultExt};
+use store_api::metric_engine_consts::PHYSICAL_TABLE_METADATA_KEY;
+use store_api::region_engine::LabelValuesRequest;
+use table::TableRef;
 
 use crate::error::{
-    CatalogSnafu, CollectRecordbatchSnafu, ExecLogicalPlanSnafu,
-    PrometheusLabelValuesQueryPlanSnafu, PrometheusMetricNamesQueryPlanSnafu, ReadTableSnafu,
-    Result, TableNotFoundSnafu,
+    CatalogSnafu, CollectRecordbatchSnafu, ExecLogicalPlanSnafu, FindRegionPeerSnafu,
+    PrometheusLabelValuesQueryPlanSnafu, PrometheusMetricNamesQueryPlanSnafu,
+    PrometheusSeriesQueryPlanSnafu, ReadTableSnafu, Result, TableNotFoundSnafu,
 };
 use crate::instance::Instance;
+use crate::metrics::PROMQL_LABEL_VALUES_REQUESTS;
 
 impl Instance {
     /// Handles metric names query request, returns the names.
@@ -124,6 +133,22 @@ impl Instance {
                 table_name: format_full_table_name(ctx.current_catalog(), &table_schema, &metric),
             })?;
 
+        // Only the time range can be served by the dictionary.
+        if matchers.is_empty() {
+            if let Some(values) = self
+                .dictionary_label_values(&table, &label_name, start, end)
+                .await?
+            {
+                PROMQL_LABEL_VALUES_REQUESTS
+                    .with_label_values(&["dictionary"])
+                    .inc();
+                return Ok(values);
+            }
+        }
+        PROMQL_LABEL_VALUES_REQUESTS
+            .with_label_values(&["scan"])
+            .inc();
+
         let dataframe = self
             .query_engine
             .read_table(table.clone())
@@ -175,4 +200,160 @@ impl Instance {
 
         Ok(results)
     }
+
+    /// Handles TSDB status request, returns the cardinality statistics of the series
+    /// of the metrics in the current database.
+    ///
+    /// The series of each metric are its distinct label sets, read by a distinct
+    /// scan of the tag columns of the table.
+    #[tracing::instrument(skip_all)]
+    pub(crate) async fn handle_tsdb_status(
+        &self,
+        limit: usize,
+        ctx: &QueryContextRef,
+    ) -> Result<TsdbStatus> {
+        let catalog = ctx.current_catalog();
+        let schema = ctx.current_schema();
+        let mut tables = self.catalog_manager.tables(catalog, &schema, Some(ctx));
+
+        let mut num_series = 0;
+        let mut series_by_metric = Vec::new();
+        let mut values_by_label: HashMap<String, HashSet<String>> = HashMap::new();
+        let mut series_by_pair: HashMap<String, u64> = HashMap::new();
+        while let Some(table) = tables.next().await {
+            let table = table.context(CatalogSnafu)?;
+            let table_info = table.table_info();
+            // Physical tables only store the series of their logical tables.
+            if table_info
+                .meta
+                .options
+                .extra_options
+                .contains_key(PHYSICAL_TABLE_METADATA_KEY)
+            {
+                continue;
+            }
+            let labels = table_info
+                .meta
+                .row_key_column_names()
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let dataframe = self
+                .query_engine
+                .read_table(table.clone())
+                .with_context(|_| ReadTableSnafu {
+                    table_name: table_info.full_table_name(),
+                })?;
+            let logical_plan = promql::label_values::rewrite_series_query(
+                table.clone(),
+                dataframe.into_logical_plan(),
+            )
+            .context(PrometheusSeriesQueryPlanSnafu)?;
+            let output = self
+                .query_engine
+                .execute(logical_plan, ctx.clone())
+                .await
+                .context(ExecLogicalPlanSnafu)?;
+            let batches = match output.data {
+                OutputData::Stream(stream) => util::collect(stream)
+                    .await
+                    .context(CollectRecordbatchSnafu)?,
+                OutputData::RecordBatches(rbs) => rbs.take(),
+                _ => unreachable!("should not happen"),
+            };
+
+            let mut metric_series = 0;
+            for batch in batches {
+                metric_series += batch.num_rows() as u64;
+                // The columns are the labels, ensured by `rewrite_series_query`.
+                for (label, values) in labels.iter().zip(batch.columns()) {
+                    for i in 0..values.len() {
+                        let Value::String(value) = values.get(i) else {
+                            continue;
+                        };
+                        let value = value.into_string();
+                        *series_by_pair
+                            .entry(format!("{label}={value}"))
+                            .or_default() += 1;
+                        values_by_label
+                            .entry(label.clone())
+                            .or_default()
+                            .insert(value);
+                    }
+                }
+            }
+            num_series += metric_series;
+            series_by_metric.push((table_info.name.clone(), metric_series));
+        }
+
+        Ok(TsdbStatus {
+            head_stats: HeadStats { num_series },
+            series_count_by_metric_name: TsdbStat::top_k(series_by_metric, limit),
+            label_value_count_by_label_name: TsdbStat::top_k(
+                values_by_label
+                    .into_iter()
+                    .map(|(label, values)| (label, values.len() as u64)),
+                limit,
+            ),
+            series_count_by_label_value_pair: TsdbStat::top_k(series_by_pair, limit),
+        })
+    }
+
+    /// Unions the label values from the dictionaries of all regions of the table
+    /// without scanning the data. Returns `None` if any region can't serve it.
+    async fn dictionary_label_values(
+        &self,
+        table: &TableRef,
+        label_name: &str,
+        start: SystemTime,
+        end: SystemTime,
+    ) -> Result<Option<Vec<String>>> {
+        let table_info = table.table_info();
+        // Regions of logical tables are shared by all logical tables of the physical table.
+        if table_info.meta.engine != MITO_ENGINE {
+            return Ok(None);
+        }
+        let schema = &table_info.meta.schema;
+        let is_tag = schema
+            .column_index_by_name(label_name)
+            .is_some_and(|index| table_info.meta.primary_key_indices.contains(&index));
+        if !is_tag {
+            return Ok(None);
+        }
+
+        let request = LabelValuesRequest {
+            column_name: label_name.to_string(),
+            start: Timestamp::new_millisecond(to_millis(start)),
+            end: Timestamp::new_millisecond(to_millis(end)),
+        };
+        let mut values = BTreeSet::new();
+        for region_id in table_info.region_ids() {
+            let peer = self
+                .partition_manager
+                .find_region_leader(region_id)
+                .await
+                .context(FindRegionPeerSnafu {
+                    region_id,
+                    read_preference: ReadPreference::Leader,
+                })?;
+            let datanode = self.node_manager.datanode(&peer).await;
+            match datanode.label_values(region_id, request.clone()).await {
+                Ok(Some(region_values)) => values.extend(region_values),
+                Ok(None) => return Ok(None),
+                Err(e) => {
+                    warn!(e; "Failed to get label values of region {}, fallback to scan", region_id);
+                    return Ok(None);
+                }
+            }
+        }
+
+        Ok(Some(values.into_iter().collect()))
+    }
+}
+
+fn to_millis(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_millis() as i64,
+        Err(e) => -(e.duration().as_millis() as i64),
+    }
 }
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -19,11 +20,13 @@ use common_error::ext::BoxedError;
 use common_meta::node_manager::NodeManagerRef;
 use common_query::request::QueryRequest;
 use common_recordbatch::SendableRecordBatchStream;
+use futures::future;
 use partition::manager::PartitionRuleManagerRef;
 use query::error::{RegionQuerySnafu, Result as QueryResult};
 use query::region_query::RegionQueryHandler;
 use session::ReadPreference;
 use snafu::ResultExt;
+use store_api::storage::RegionId;
 
 use crate::error::{FindRegionPeerSnafu, RequestQuerySnafu, Result};
 
@@ -56,6 +59,13 @@ impl RegionQueryHandler for FrontendRegionQueryHandler {
             .map_err(BoxedError::new)
             .context(RegionQuerySnafu)
     }
+
+    async fn region_sequences(&self, region_ids: &[RegionId]) -> QueryResult<HashMap<u64, u64>> {
+        self.region_sequences_inner(region_ids)
+            .await
+            .map_err(BoxedError::new)
+            .context(RegionQuerySnafu)
+    }
 }
 
 impl FrontendRegionQueryHandler {
@@ -82,4 +92,29 @@ impl FrontendRegionQueryHandler {
             .await
             .context(RequestQuerySnafu)
     }
+
+    async fn region_sequences_inner(&self, region_ids: &[RegionId]) -> Result<HashMap<u64, u64>> {
+        let tasks = region_ids.iter().map(|region_id| async move {
+            let region_id = *region_id;
+            let peer = self
+                .partition_manager
+                .find_region_leader(region_id)
+                .await
+                .context(FindRegionPeerSnafu {
+                    region_id,
+                    read_preference: ReadPreference::Leader,
+                })?;
+            let sequence = self
+                .node_manager
+                .datanode(&peer)
+                .await
+                .region_sequence(region_id)
+                .await
+                .context(RequestQuerySnafu)?;
+            Ok(sequence.map(|sequence| (region_id.as_u64(), sequence)))
+        });
+
+        let sequences = future::try_join_all(tasks).await?;
+        Ok(sequences.into_iter().flatten().collect())
+    }
 }
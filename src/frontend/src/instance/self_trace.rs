@@ -0,0 +1,197 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use common_catalog::consts::TRACE_TABLE_NAME;
+use common_telemetry::tracing::Instrument;
+use common_telemetry::{
+    tracing, warn, SelfSpan, SelfSpanValue, SelfTraceHandler, SELF_TRACE_EXPORT_SPAN,
+};
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use opentelemetry_proto::tonic::common::v1::{any_value, AnyValue, InstrumentationScope, KeyValue};
+use opentelemetry_proto::tonic::resource::v1::Resource;
+use opentelemetry_proto::tonic::trace::v1::{
+    span, status, ResourceSpans, ScopeSpans, Span, Status,
+};
+use pipeline::{GreptimePipelineParams, PipelineWay};
+use servers::otlp::trace::KEY_SERVICE_NAME;
+use servers::query_handler::{OpenTelemetryProtocolHandler, PipelineHandlerRef};
+use session::context::QueryContext;
+
+use crate::instance::Instance;
+
+/// The service name of the spans of the node in its trace table.
+pub const SELF_TRACE_SERVICE_NAME: &str = "greptimedb";
+
+/// Writes the spans of the node into its trace table, like the traces received
+/// from OTLP, so they can be queried with the Jaeger APIs.
+pub struct SelfTraceWriter {
+    instance: Arc<Instance>,
+}
+
+impl SelfTraceWriter {
+    pub fn new(instance: Arc<Instance>) -> Self {
+        Self { instance }
+    }
+}
+
+impl SelfTraceHandler for SelfTraceWriter {
+    fn handle(&self, spans: Vec<SelfSpan>) {
+        let instance = self.instance.clone();
+        let _handle = common_runtime::spawn_global(
+            async move {
+                let pipeline_handler: PipelineHandlerRef = instance.clone();
+                if let Err(e) = instance
+                    .traces(
+                        pipeline_handler,
+                        to_export_request(spans),
+                        PipelineWay::OtlpTraceDirectV1,
+                        GreptimePipelineParams::default(),
+                        TRACE_TABLE_NAME.to_string(),
+                        QueryContext::arc(),
+                    )
+                    .await
+                {
+                    warn!(e; "Failed to write the self traces");
+                }
+            }
+            // The root span of the write, which drops the spans of the write itself.
+            .instrument(tracing::info_span!(parent: None, SELF_TRACE_EXPORT_SPAN)),
+        );
+    }
+}
+
+fn to_export_request(spans: Vec<SelfSpan>) -> ExportTraceServiceRequest {
+    ExportTraceServiceRequest {
+        resource_spans: vec![ResourceSpans {
+            resource: Some(Resource {
+                attributes: vec![key_value(
+                    KEY_SERVICE_NAME,
+                    any_value::Value::StringValue(SELF_TRACE_SERVICE_NAME.to_string()),
+                )],
+                dropped_attributes_count: 0,
+            }),
+            scope_spans: vec![ScopeSpans {
+                scope: Some(InstrumentationScope {
+                    name: SELF_TRACE_SERVICE_NAME.to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    ..Default::default()
+                }),
+                spans: spans.into_iter().map(to_otlp_span).collect(),
+                schema_url: String::new(),
+            }],
+            schema_url: String::new(),
+        }],
+    }
+}
+
+fn to_otlp_span(span: SelfSpan) -> Span {
+    let attributes = span
+        .attributes
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                SelfSpanValue::Bool(value) => any_value::Value::BoolValue(value),
+                SelfSpanValue::I64(value) => any_value::Value::IntValue(value),
+                SelfSpanValue::F64(value) => any_value::Value::DoubleValue(value),
+                SelfSpanValue::String(value) => any_value::Value::StringValue(value),
+            };
+            key_value(&key, value)
+        })
+        .collect();
+    let status = span.error.map(|message| Status {
+        message,
+        code: status::StatusCode::Error as i32,
+    });
+
+    Span {
+        trace_id: span.trace_id.to_vec(),
+        span_id: span.span_id.to_vec(),
+        parent_span_id: span
+            .parent_span_id
+            .map(|id| id.to_vec())
+            .unwrap_or_default(),
+        name: span.name,
+        kind: span::SpanKind::Internal as i32,
+        start_time_unix_nano: unix_nanos(span.start_time),
+        end_time_unix_nano: unix_nanos(span.end_time),
+        attributes,
+        status,
+        ..Default::default()
+    }
+}
+
+fn key_value(key: &str, value: any_value::Value) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue { value: Some(value) }),
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use servers::otlp::trace::span::parse;
+
+    use super::*;
+
+    #[test]
+    fn test_to_export_request() {
+        let start_time = UNIX_EPOCH + Duration::from_secs(1);
+        let root = SelfSpan {
+            trace_id: [1; 16],
+            span_id: [2; 8],
+            parent_span_id: None,
+            name: "execute_stmt".to_string(),
+            start_time,
+            end_time: start_time + Duration::from_millis(800),
+            attributes: vec![(
+                "table".to_string(),
+                SelfSpanValue::String("monitor".to_string()),
+            )],
+            error: None,
+        };
+        let scan = SelfSpan {
+            span_id: [3; 8],
+            parent_span_id: Some([2; 8]),
+            name: "seq_scan".to_string(),
+            attributes: vec![("rows".to_string(), SelfSpanValue::I64(42))],
+            error: Some("timeout".to_string()),
+            ..root.clone()
+        };
+
+        let spans = parse(to_export_request(vec![root, scan]));
+        assert_eq!(2, spans.len());
+        assert_eq!(
+            Some(SELF_TRACE_SERVICE_NAME),
+            spans[0].service_name.as_deref()
+        );
+        assert_eq!("01010101010101010101010101010101", spans[0].trace_id);
+        assert_eq!(None, spans[0].parent_span_id);
+        assert_eq!(1_000_000_000, spans[0].start_in_nanosecond);
+        assert_eq!(1_800_000_000, spans[0].end_in_nanosecond);
+        assert_eq!(Some("0202020202020202"), spans[1].parent_span_id.as_deref());
+        assert_eq!("seq_scan", spans[1].span_name);
+        assert_eq!("STATUS_CODE_ERROR", spans[1].span_status_code);
+    }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use api::region::RegionResponse;
@@ -20,15 +21,21 @@ use async_trait::async_trait;
 use client::region::check_response_header;
 use common_error::ext::BoxedError;
 use common_meta::error::{self as meta_error, Result as MetaResult};
-use common_meta::node_manager::{Datanode, DatanodeRef, FlownodeRef, NodeManager};
+use common_meta::node_manager::{AffectedRows, Datanode, DatanodeRef, FlownodeRef, NodeManager};
 use common_meta::peer::Peer;
 use common_query::request::QueryRequest;
 use common_recordbatch::SendableRecordBatchStream;
 use common_telemetry::tracing;
 use common_telemetry::tracing_context::{FutureExt, TracingContext};
+use common_time::range::TimestampRange;
 use datanode::region_server::RegionServer;
 use servers::grpc::region_server::RegionServerHandler;
 use snafu::{OptionExt, ResultExt};
+use store_api::manifest::ManifestVersion;
+use store_api::region_engine::{
+    LabelValuesRequest, RegionManifestSnapshot, SeriesCardinality, SeriesCardinalityRequest,
+};
+use store_api::storage::RegionId;
 
 use crate::error::{InvalidRegionRequestSnafu, InvokeRegionServerSnafu, Result};
 
@@ -64,7 +71,7 @@ impl RegionInvoker {
         })?;
 
         self.region_server
-            .handle(body)
+            .handle(request.header.unwrap_or_default(), body)
             .await
             .context(InvokeRegionServerSnafu)
     }
@@ -105,4 +112,86 @@ impl Datanode for RegionInvoker {
             .map_err(BoxedError::new)
             .context(meta_error::ExternalSnafu)
     }
+
+    async fn label_values(
+        &self,
+        region_id: RegionId,
+        request: LabelValuesRequest,
+    ) -> MetaResult<Option<Vec<String>>> {
+        self.region_server
+            .label_values(region_id, request)
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn series_cardinality(
+        &self,
+        region_id: RegionId,
+        request: SeriesCardinalityRequest,
+    ) -> MetaResult<Option<SeriesCardinality>> {
+        self.region_server
+            .series_cardinality(region_id, request)
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn region_manifest(
+        &self,
+        region_id: RegionId,
+    ) -> MetaResult<Option<RegionManifestSnapshot>> {
+        self.region_server
+            .region_manifest(region_id)
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn checkpoint_region(&self, region_id: RegionId) -> MetaResult<Option<ManifestVersion>> {
+        self.region_server
+            .checkpoint_region(region_id)
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn region_sequence(&self, region_id: RegionId) -> MetaResult<Option<u64>> {
+        self.region_server
+            .region_sequence(region_id)
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn producer_watermarks(
+        &self,
+        region_id: RegionId,
+    ) -> MetaResult<Option<HashMap<String, u64>>> {
+        self.region_server
+            .producer_watermarks(region_id)
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn build_index(&self, region_id: RegionId) -> MetaResult<AffectedRows> {
+        self.region_server
+            .build_index(region_id)
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+
+    async fn truncate_range(
+        &self,
+        region_id: RegionId,
+        time_range: TimestampRange,
+    ) -> MetaResult<AffectedRows> {
+        self.region_server
+            .truncate_range(region_id, time_range)
+            .await
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
 }
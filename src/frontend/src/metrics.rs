@@ -37,6 +37,14 @@ lazy_static! {
         &["db"]
     )
     .unwrap();
+    /// The number of promql label values requests, by whether they are served
+    /// from the label values dictionary or by scanning the table.
+    pub static ref PROMQL_LABEL_VALUES_REQUESTS: IntCounterVec = register_int_counter_vec!(
+        "greptime_frontend_promql_label_values_requests",
+        "frontend promql label values requests",
+        &["source"]
+    )
+    .unwrap();
 
     /// The number of OpenTelemetry metrics send by frontend node.
     pub static ref OTLP_METRICS_ROWS: IntCounter = register_int_counter!(
@@ -226,12 +226,15 @@ where
                     ServerSqlQueryHandlerAdapter::arc(instance.clone()),
                     user_provider.clone(),
                 )),
-                Arc::new(MysqlSpawnConfig::new(
-                    opts.tls.should_force_tls(),
-                    tls_server_config,
-                    opts.keep_alive.as_secs(),
-                    opts.reject_no_database.unwrap_or(false),
-                )),
+                Arc::new(
+                    MysqlSpawnConfig::new(
+                        opts.tls.should_force_tls(),
+                        tls_server_config,
+                        opts.keep_alive.as_secs(),
+                        opts.reject_no_database.unwrap_or(false),
+                    )
+                    .with_connection_options(opts.connection),
+                ),
             );
             handlers.insert((mysql_server, mysql_addr)).await;
         }
@@ -247,14 +250,17 @@ where
 
             maybe_watch_tls_config(tls_server_config.clone()).context(StartServerSnafu)?;
 
-            let pg_server = Box::new(PostgresServer::new(
-                ServerSqlQueryHandlerAdapter::arc(instance.clone()),
-                opts.tls.should_force_tls(),
-                tls_server_config,
-                opts.keep_alive.as_secs(),
-                common_runtime::global_runtime(),
-                user_provider.clone(),
-            )) as Box<dyn Server>;
+            let pg_server = Box::new(
+                PostgresServer::new(
+                    ServerSqlQueryHandlerAdapter::arc(instance.clone()),
+                    opts.tls.should_force_tls(),
+                    tls_server_config,
+                    opts.keep_alive.as_secs(),
+                    common_runtime::global_runtime(),
+                    user_provider.clone(),
+                )
+                .with_connection_options(opts.connection),
+            ) as Box<dyn Server>;
 
             handlers.insert((pg_server, pg_addr)).await;
         }
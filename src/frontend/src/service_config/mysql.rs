@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use serde::{Deserialize, Serialize};
+use servers::connection::ConnectionOptions;
 use servers::tls::TlsOption;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -29,6 +30,9 @@ pub struct MysqlOptions {
     #[serde(default = "Default::default")]
     #[serde(with = "humantime_serde")]
     pub keep_alive: std::time::Duration,
+    /// Limits of the client connections.
+    #[serde(default = "Default::default")]
+    pub connection: ConnectionOptions,
 }
 
 impl Default for MysqlOptions {
@@ -40,6 +44,7 @@ impl Default for MysqlOptions {
             tls: TlsOption::default(),
             reject_no_database: None,
             keep_alive: std::time::Duration::from_secs(0),
+            connection: ConnectionOptions::default(),
         }
     }
 }
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use serde::{Deserialize, Serialize};
+use servers::connection::ConnectionOptions;
 use servers::tls::TlsOption;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -28,6 +29,9 @@ pub struct PostgresOptions {
     #[serde(default = "Default::default")]
     #[serde(with = "humantime_serde")]
     pub keep_alive: std::time::Duration,
+    /// Limits of the client connections.
+    #[serde(default = "Default::default")]
+    pub connection: ConnectionOptions,
 }
 
 impl Default for PostgresOptions {
@@ -38,6 +42,7 @@ impl Default for PostgresOptions {
             runtime_size: 2,
             tls: Default::default(),
             keep_alive: std::time::Duration::from_secs(0),
+            connection: ConnectionOptions::default(),
         }
     }
 }
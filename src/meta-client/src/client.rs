@@ -330,6 +330,14 @@ impl ProcedureExecutor for MetaClient {
             .map_err(BoxedError::new)
             .context(meta_error::ExternalSnafu)
     }
+
+    async fn cancel_procedure(&self, _ctx: &ExecutorContext, pid: &str) -> MetaResult<()> {
+        // The procedure service of metasrv has no RPC to cancel procedures yet.
+        UnsupportedSnafu {
+            operation: format!("cancelling procedure {pid} on a remote metasrv"),
+        }
+        .fail()
+    }
 }
 
 #[async_trait::async_trait]
@@ -745,6 +753,21 @@ mod tests {
         assert!(matches!(res.err(), Some(error::Error::NotStarted { .. })));
     }
 
+    #[tokio::test]
+    async fn test_cancel_procedure_unsupported() {
+        let meta_client = MetaClientBuilder::new(0, Role::Frontend)
+            .enable_procedure()
+            .build();
+        let err = ProcedureExecutor::cancel_procedure(
+            &meta_client,
+            &ExecutorContext::default(),
+            "fa3f1e1b-6e3b-4e9c-9a6f-0b5d6d3f2c10",
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, meta_error::Error::Unsupported { .. }));
+    }
+
     #[tokio::test]
     async fn test_ask_leader() {
         let tc = new_client("test_ask_leader").await;
@@ -122,6 +122,8 @@ mod tests {
             manifest_size: 0,
             sst_size: 0,
             index_size: 0,
+            sst_compression: None,
+            time_range: None,
         }
     }
 
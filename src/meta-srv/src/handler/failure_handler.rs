@@ -98,6 +98,8 @@ mod tests {
                 manifest_size: 0,
                 sst_size: 0,
                 index_size: 0,
+                sst_compression: None,
+                time_range: None,
                 region_manifest: RegionManifestInfo::Mito {
                     manifest_version: 0,
                     flushed_entry_id: 0,
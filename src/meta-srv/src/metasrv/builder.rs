@@ -22,7 +22,8 @@ use common_grpc::channel_manager::ChannelConfig;
 use common_meta::ddl::flow_meta::FlowMetadataAllocator;
 use common_meta::ddl::table_meta::{TableMetadataAllocator, TableMetadataAllocatorRef};
 use common_meta::ddl::{
-    DdlContext, NoopRegionFailureDetectorControl, RegionFailureDetectorControllerRef,
+    DdlContext, NoopRegionFailureDetectorControl, NoopRegionRowSplitter,
+    RegionFailureDetectorControllerRef,
 };
 use common_meta::ddl_manager::DdlManager;
 use common_meta::distributed_time_constants;
@@ -339,6 +340,8 @@ impl MetasrvBuilder {
                     flow_metadata_manager: flow_metadata_manager.clone(),
                     flow_metadata_allocator: flow_metadata_allocator.clone(),
                     region_failure_detector_controller,
+                    // Repartition tasks can't be submitted to the metasrv yet.
+                    region_row_splitter: Arc::new(NoopRegionRowSplitter),
                 },
                 procedure_manager.clone(),
                 true,
@@ -32,6 +32,7 @@ use common_meta::node_manager::NodeManagerRef;
 use common_meta::rpc::ddl::CreateTableTask;
 use common_meta::rpc::router::{find_leaders, RegionRoute};
 use common_procedure::Status;
+use common_procedure_test::new_test_procedure_context;
 use store_api::storage::RegionId;
 
 use crate::procedure::utils::mock::EchoRegionServer;
@@ -223,7 +224,10 @@ async fn test_on_datanode_create_regions() {
         }
     });
 
-    let status = procedure.on_datanode_create_regions().await.unwrap();
+    let status = procedure
+        .on_datanode_create_regions(&new_test_procedure_context())
+        .await
+        .unwrap();
     assert!(matches!(
         status,
         Status::Executing {
@@ -18,7 +18,7 @@ pub mod mock {
     use std::sync::Arc;
 
     use api::v1::region::region_server::RegionServer;
-    use api::v1::region::{region_request, RegionResponse};
+    use api::v1::region::{region_request, RegionRequestHeader, RegionResponse};
     use api::v1::{ResponseHeader, Status as PbStatus};
     use async_trait::async_trait;
     use client::Client;
@@ -90,6 +90,7 @@ pub mod mock {
     impl RegionServerHandler for EchoRegionServer {
         async fn handle(
             &self,
+            _header: RegionRequestHeader,
             request: region_request::Body,
         ) -> servers::error::Result<RegionResponse> {
             self.received_requests.send(request).await.unwrap();
@@ -116,7 +117,7 @@ pub mod test_data {
     use common_catalog::consts::MITO2_ENGINE;
     use common_meta::ddl::flow_meta::FlowMetadataAllocator;
     use common_meta::ddl::table_meta::TableMetadataAllocator;
-    use common_meta::ddl::{DdlContext, NoopRegionFailureDetectorControl};
+    use common_meta::ddl::{DdlContext, NoopRegionFailureDetectorControl, NoopRegionRowSplitter};
     use common_meta::key::flow::FlowMetadataManager;
     use common_meta::key::TableMetadataManager;
     use common_meta::kv_backend::memory::MemoryKvBackend;
@@ -230,6 +231,7 @@ pub mod test_data {
             memory_region_keeper: Arc::new(MemoryRegionKeeper::new()),
             leader_region_registry: Arc::new(LeaderRegionRegistry::default()),
             region_failure_detector_controller: Arc::new(NoopRegionFailureDetectorControl),
+            region_row_splitter: Arc::new(NoopRegionRowSplitter),
         }
     }
 }
@@ -192,7 +192,7 @@ impl RegionEngine for MetricEngine {
                     .alter_regions(vec![(region_id, alter)], &mut extension_return_value)
                     .await
             }
-            RegionRequest::Compact(_) => {
+            RegionRequest::Compact(_) | RegionRequest::BuildIndex(_) => {
                 if self.inner.is_physical_region(region_id) {
                     self.inner
                         .mito
@@ -205,7 +205,10 @@ impl RegionEngine for MetricEngine {
                 }
             }
             RegionRequest::Flush(req) => self.inner.flush_region(region_id, req).await,
-            RegionRequest::Truncate(_) => UnsupportedRegionRequestSnafu { request }.fail(),
+            // Files of the physical region are shared by all logical regions.
+            RegionRequest::Truncate(_) | RegionRequest::TruncateRange(_) => {
+                UnsupportedRegionRequestSnafu { request }.fail()
+            }
             RegionRequest::Delete(_) => {
                 if self.inner.is_physical_region(region_id) {
                     self.inner
@@ -272,6 +275,10 @@ impl RegionEngine for MetricEngine {
                     manifest_size: metadata_stat.manifest_size + data_stat.manifest_size,
                     sst_size: metadata_stat.sst_size + data_stat.sst_size,
                     index_size: metadata_stat.index_size + data_stat.index_size,
+                    // Reports the codec of the data region that holds the user's data.
+                    sst_compression: data_stat.sst_compression.clone(),
+                    // The time range of the metadata region is meaningless.
+                    time_range: data_stat.time_range,
                     manifest: RegionManifestInfo::Metric {
                         data_flushed_entry_id: data_stat.manifest.data_flushed_entry_id(),
                         data_manifest_version: data_stat.manifest.data_manifest_version(),
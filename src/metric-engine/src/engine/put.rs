@@ -190,6 +190,7 @@ mod tests {
         let request = RegionRequest::Put(RegionPutRequest {
             rows: Rows { schema, rows },
             hint: None,
+            idempotency_key: None,
         });
 
         // write data
@@ -264,6 +265,7 @@ mod tests {
         let request = RegionRequest::Put(RegionPutRequest {
             rows: Rows { schema, rows },
             hint: None,
+            idempotency_key: None,
         });
 
         // write data
@@ -286,6 +288,7 @@ mod tests {
         let request = RegionRequest::Put(RegionPutRequest {
             rows: Rows { schema, rows },
             hint: None,
+            idempotency_key: None,
         });
 
         engine
@@ -306,6 +309,7 @@ mod tests {
         let request = RegionRequest::Put(RegionPutRequest {
             rows: Rows { schema, rows },
             hint: None,
+            idempotency_key: None,
         });
 
         engine
@@ -433,7 +433,11 @@ impl MetadataRegion {
                 .collect(),
         };
 
-        RegionPutRequest { rows, hint: None }
+        RegionPutRequest {
+            rows,
+            hint: None,
+            idempotency_key: None,
+        }
     }
 
     fn build_delete_request(keys: &[String]) -> RegionDeleteRequest {
@@ -92,6 +92,11 @@ impl AccessLayer {
         &self.puffin_manager_factory
     }
 
+    /// Returns the intermediate manager.
+    pub fn intermediate_manager(&self) -> &IntermediateManager {
+        &self.intermediate_manager
+    }
+
     /// Deletes a SST file (and its index file if it has one) with given file id.
     pub(crate) async fn delete_sst(&self, file_meta: &FileMeta) -> Result<()> {
         let path = location::sst_file_path(&self.region_dir, file_meta.file_id);
@@ -194,6 +199,8 @@ impl AccessLayer {
 pub enum OperationType {
     Flush,
     Compact,
+    /// Builds indexes for existing SSTs. It only writes index files.
+    BuildIndex,
 }
 
 /// Contents to build a SST.
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::num::NonZero;
 use std::sync::Arc;
 use std::time::Duration;
@@ -36,7 +37,8 @@ use crate::error::{EmptyRegionDirSnafu, JoinSnafu, ObjectStoreNotFoundSnafu, Res
 use crate::manifest::action::{RegionEdit, RegionMetaAction, RegionMetaActionList};
 use crate::manifest::manager::{RegionManifestManager, RegionManifestOptions};
 use crate::manifest::storage::manifest_compress_type;
-use crate::read::Source;
+use crate::read::sort_by::{resolve_sort_by, SortByReader};
+use crate::read::{BoxedBatchReader, Source};
 use crate::region::opener::new_manifest_dir;
 use crate::region::options::RegionOptions;
 use crate::region::version::VersionRef;
@@ -284,8 +286,26 @@ impl Compactor for DefaultCompactor {
 
         for output in picker_output.outputs.drain(..) {
             compacted_inputs.extend(output.inputs.iter().map(|f| f.meta_ref().clone()));
+            let append_mode = compaction_region.current_version.options.append_mode;
+            let sort_by = &compaction_region.region_options.sst.compaction_sort_by;
+            // Ignores an invalid sort order so the compaction still works.
+            let sort_by_ids = if append_mode {
+                resolve_sort_by(&compaction_region.region_metadata, sort_by)
+                    .inspect_err(|e| {
+                        warn!(e; "Ignore compaction.sort_by of region {}", compaction_region.region_id)
+                    })
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
             let write_opts = WriteOptions {
                 write_buffer_size: compaction_region.engine_config.sst_write_buffer_size,
+                compression: compaction_region.region_options.sst.compression,
+                sort_by: if sort_by_ids.is_empty() {
+                    Vec::new()
+                } else {
+                    sort_by.clone()
+                },
                 ..Default::default()
             };
 
@@ -299,7 +319,6 @@ impl Compactor for DefaultCompactor {
                 .options
                 .index_options
                 .clone();
-            let append_mode = compaction_region.current_version.options.append_mode;
             let merge_mode = compaction_region.current_version.options.merge_mode();
             let inverted_index_config = compaction_region.engine_config.inverted_index.clone();
             let fulltext_index_config = compaction_region.engine_config.fulltext_index.clone();
@@ -329,6 +348,11 @@ impl Compactor for DefaultCompactor {
                 }
                 .build_sst_reader()
                 .await?;
+                let reader: BoxedBatchReader = if sort_by_ids.is_empty() {
+                    reader
+                } else {
+                    Box::new(SortByReader::new(reader, sort_by_ids))
+                };
                 let output_files = sst_layer
                     .write_sst(
                         SstWriteRequest {
@@ -409,11 +433,13 @@ impl Compactor for DefaultCompactor {
         let edit = RegionEdit {
             files_to_add: merge_output.files_to_add,
             files_to_remove: merge_output.files_to_remove,
+            files_to_update: vec![],
             compaction_time_window: merge_output
                 .compaction_time_window
                 .map(|seconds| Duration::from_secs(seconds as u64)),
             flushed_entry_id: None,
             flushed_sequence: None,
+            producer_watermarks: HashMap::new(),
         };
 
         let action_list = RegionMetaActionList::with_action(RegionMetaAction::Edit(edit.clone()));
@@ -249,6 +249,7 @@ mod tests {
                 index_options: Default::default(),
                 memtable: None,
                 merge_mode: None,
+                sst: Default::default(),
             },
             compaction_time_window: None,
         }
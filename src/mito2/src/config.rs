@@ -73,6 +73,9 @@ pub struct MitoConfig {
     pub max_background_compactions: usize,
     /// Max number of running background purge jobs (default: number of cpu cores).
     pub max_background_purges: usize,
+    /// Max number of running background jobs that build indexes for existing SSTs
+    /// (default: 1/8 of cpu cores).
+    pub max_background_index_builds: usize,
 
     // Flush configs:
     /// Interval to auto flush a region if it has not flushed yet (default 30 min).
@@ -139,6 +142,7 @@ impl Default for MitoConfig {
             max_background_flushes: divide_num_cpus(2),
             max_background_compactions: divide_num_cpus(4),
             max_background_purges: common_config::utils::get_cpus(),
+            max_background_index_builds: divide_num_cpus(8),
             auto_flush_interval: Duration::from_secs(30 * 60),
             global_write_buffer_size: ReadableSize::gb(1),
             global_write_buffer_reject_size: ReadableSize::gb(2),
@@ -207,6 +211,13 @@ impl MitoConfig {
             );
             self.max_background_purges = common_config::utils::get_cpus();
         }
+        if self.max_background_index_builds == 0 {
+            warn!(
+                "Sanitize max background index builds 0 to {}",
+                divide_num_cpus(8)
+            );
+            self.max_background_index_builds = divide_num_cpus(8);
+        }
 
         if self.global_write_buffer_reject_size <= self.global_write_buffer_size {
             self.global_write_buffer_reject_size = self.global_write_buffer_size * 2;
@@ -315,6 +326,10 @@ pub struct IndexConfig {
     pub content_cache_size: ReadableSize,
     /// Page size for inverted index content.
     pub content_cache_page_size: ReadableSize,
+
+    /// Max bytes per second to read from SSTs when building indexes for existing
+    /// SSTs. Setting it to 0 to disable the limit.
+    pub build_throughput_limit: ReadableSize,
 }
 
 impl Default for IndexConfig {
@@ -327,6 +342,7 @@ impl Default for IndexConfig {
             metadata_cache_size: ReadableSize::mb(64),
             content_cache_size: ReadableSize::mb(128),
             content_cache_page_size: ReadableSize::kb(64),
+            build_throughput_limit: ReadableSize(0),
         }
     }
 }
@@ -415,6 +431,9 @@ pub struct InvertedIndexConfig {
     pub create_on_compaction: Mode,
     /// Whether to apply the index on query: automatically or never.
     pub apply_on_query: Mode,
+    /// Whether to serve label values queries from the term dictionaries of the index:
+    /// automatically or never.
+    pub apply_on_label_values: Mode,
 
     /// Memory threshold for performing an external sort during index creation.
     pub mem_threshold_on_create: MemoryThreshold,
@@ -435,6 +454,7 @@ impl Default for InvertedIndexConfig {
             create_on_flush: Mode::Auto,
             create_on_compaction: Mode::Auto,
             apply_on_query: Mode::Auto,
+            apply_on_label_values: Mode::Disable,
             mem_threshold_on_create: MemoryThreshold::Auto,
             write_buffer_size: ReadableSize::mb(8),
             intermediate_path: String::new(),
@@ -38,6 +38,10 @@ mod edit_region_test;
 mod filter_deleted_test;
 #[cfg(test)]
 mod flush_test;
+#[cfg(test)]
+mod index_build_test;
+#[cfg(test)]
+mod label_values_test;
 #[cfg(any(test, feature = "test"))]
 pub mod listener;
 #[cfg(test)]
@@ -47,16 +51,26 @@ mod open_test;
 #[cfg(test)]
 mod parallel_test;
 #[cfg(test)]
+mod producer_watermark_test;
+#[cfg(test)]
 mod projection_test;
 #[cfg(test)]
 mod prune_test;
 #[cfg(test)]
+mod region_manifest_test;
+#[cfg(test)]
 mod row_selector_test;
 #[cfg(test)]
+mod scan_hint_test;
+#[cfg(test)]
+mod series_cardinality_test;
+#[cfg(test)]
 mod set_role_state_test;
 #[cfg(test)]
 mod sync_test;
 #[cfg(test)]
+mod truncate_range_test;
+#[cfg(test)]
 mod truncate_test;
 
 use std::any::Any;
@@ -81,8 +95,9 @@ use store_api::logstore::LogStore;
 use store_api::manifest::ManifestVersion;
 use store_api::metadata::RegionMetadataRef;
 use store_api::region_engine::{
-    BatchResponses, RegionEngine, RegionManifestInfo, RegionRole, RegionScannerRef,
-    RegionStatistic, SetRegionRoleStateResponse, SettableRegionRoleState,
+    BatchResponses, LabelValuesRequest, RegionEngine, RegionFileEntry, RegionManifestInfo,
+    RegionManifestSnapshot, RegionRole, RegionScannerRef, RegionStatistic, SeriesCardinality,
+    SeriesCardinalityRequest, SetRegionRoleStateResponse, SettableRegionRoleState,
 };
 use store_api::region_request::{AffectedRows, RegionOpenRequest, RegionRequest};
 use store_api::storage::{RegionId, ScanRequest, SequenceNumber};
@@ -91,13 +106,16 @@ use tokio::sync::{oneshot, Semaphore};
 use crate::cache::CacheStrategy;
 use crate::config::MitoConfig;
 use crate::error::{
-    InvalidRequestSnafu, JoinSnafu, MitoManifestInfoSnafu, RecvSnafu, RegionNotFoundSnafu, Result,
-    SerdeJsonSnafu,
+    ExternalSnafu, FlushableRegionStateSnafu, InvalidRequestSnafu, JoinSnafu,
+    MitoManifestInfoSnafu, RecvSnafu, RegionNotFoundSnafu, Result, SerdeJsonSnafu,
 };
 use crate::manifest::action::RegionEdit;
 use crate::metrics::HANDLE_REQUEST_ELAPSED;
+use crate::read::label_values::{LabelValuesDictionary, DEFAULT_LABEL_VALUES_CACHE_SIZE};
 use crate::read::scan_region::{ScanRegion, Scanner};
+use crate::read::series_cardinality;
 use crate::request::{RegionEditRequest, WorkerRequest};
+use crate::sst::location;
 use crate::wal::entry_distributor::{
     build_wal_entry_distributor_and_receivers, DEFAULT_ENTRY_RECEIVER_BUFFER_SIZE,
 };
@@ -230,14 +248,18 @@ impl MitoEngine {
 fn is_valid_region_edit(edit: &RegionEdit) -> bool {
     !edit.files_to_add.is_empty()
         && edit.files_to_remove.is_empty()
+        && edit.files_to_update.is_empty()
+        && edit.producer_watermarks.is_empty()
         && matches!(
             edit,
             RegionEdit {
                 files_to_add: _,
                 files_to_remove: _,
+                files_to_update: _,
                 compaction_time_window: None,
                 flushed_entry_id: None,
                 flushed_sequence: None,
+                producer_watermarks: _,
             }
         )
 }
@@ -250,6 +272,8 @@ struct EngineInner {
     config: Arc<MitoConfig>,
     /// The Wal raw entry reader.
     wal_raw_entry_reader: Arc<dyn RawEntryReader>,
+    /// Serves label values from the inverted index.
+    label_values_dictionary: LabelValuesDictionary,
 }
 
 type TopicGroupedRegionOpenRequests = HashMap<String, Vec<(RegionId, RegionOpenRequest)>>;
@@ -307,6 +331,7 @@ impl EngineInner {
             .await?,
             config,
             wal_raw_entry_reader,
+            label_values_dictionary: LabelValuesDictionary::new(DEFAULT_LABEL_VALUES_CACHE_SIZE),
         })
     }
 
@@ -451,6 +476,8 @@ impl EngineInner {
         // Get cache.
         let cache_manager = self.workers.cache_manager();
 
+        // Hints of the query override the config.
+        let hints = request.scan_hints.clone();
         let scan_region = ScanRegion::new(
             version,
             region.access_layer.clone(),
@@ -458,14 +485,123 @@ impl EngineInner {
             CacheStrategy::EnableAll(cache_manager),
         )
         .with_parallel_scan_channel_size(self.config.parallel_scan_channel_size)
-        .with_ignore_inverted_index(self.config.inverted_index.apply_on_query.disabled())
-        .with_ignore_fulltext_index(self.config.fulltext_index.apply_on_query.disabled())
-        .with_ignore_bloom_filter(self.config.bloom_filter_index.apply_on_query.disabled())
+        .with_ignore_inverted_index(
+            self.config.inverted_index.apply_on_query.disabled() || hints.no_inverted_index,
+        )
+        .with_ignore_fulltext_index(
+            self.config.fulltext_index.apply_on_query.disabled() || hints.no_fulltext_index,
+        )
+        .with_ignore_bloom_filter(
+            self.config.bloom_filter_index.apply_on_query.disabled() || hints.no_bloom_filter,
+        )
         .with_start_time(query_start);
 
         Ok(scan_region)
     }
 
+    /// Returns the label values of a region from the inverted index, or `None`
+    /// if it's disabled or the index can't serve the request.
+    async fn label_values(
+        &self,
+        region_id: RegionId,
+        request: LabelValuesRequest,
+    ) -> Result<Option<Vec<String>>> {
+        if self.config.inverted_index.apply_on_label_values.disabled() {
+            return Ok(None);
+        }
+
+        // Reading a region doesn't need to go through the region worker thread.
+        let region = self
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })?;
+        let version = region.version();
+        let cache_manager = self.workers.cache_manager();
+        self.label_values_dictionary
+            .label_values(&version, &region.access_layer, &cache_manager, &request)
+            .await
+    }
+
+    /// Counts the series of a region by scanning a row of each series.
+    async fn series_cardinality(
+        &self,
+        region_id: RegionId,
+        request: SeriesCardinalityRequest,
+    ) -> Result<SeriesCardinality> {
+        let region = self
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })?;
+        let scan_request = series_cardinality::build_scan_request(&region.metadata(), &request)?;
+        let stream = self
+            .scan_region(region_id, scan_request)?
+            .scanner()?
+            .scan()
+            .await
+            .context(ExternalSnafu {
+                context: "scan series",
+            })?;
+        series_cardinality::count_series(stream, &request).await
+    }
+
+    /// Returns the SST files in the manifest of a region.
+    async fn region_manifest(&self, region_id: RegionId) -> Result<RegionManifestSnapshot> {
+        let region = self
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })?;
+        let manifest = region.manifest_ctx.manifest().await;
+        let mut files = manifest
+            .files
+            .values()
+            .map(|meta| RegionFileEntry {
+                file_id: meta.file_id.to_string(),
+                file_path: location::sst_file_path(region.region_dir(), meta.file_id),
+                time_range: meta.time_range,
+                num_rows: meta.num_rows,
+                file_size: meta.file_size,
+            })
+            .collect::<Vec<_>>();
+        files.sort_unstable_by(|a, b| {
+            a.time_range
+                .0
+                .cmp(&b.time_range.0)
+                .then_with(|| a.file_id.cmp(&b.file_id))
+        });
+
+        Ok(RegionManifestSnapshot {
+            manifest_version: manifest.manifest_version,
+            files,
+        })
+    }
+
+    /// Returns the watermarks of the producers writing to a region.
+    fn producer_watermarks(&self, region_id: RegionId) -> Result<HashMap<String, u64>> {
+        let region = self
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })?;
+        let watermarks = region.version_control.current().producer_watermarks;
+        Ok((*watermarks).clone())
+    }
+
+    /// Saves a checkpoint of the manifest of a leader region.
+    async fn checkpoint_region(&self, region_id: RegionId) -> Result<ManifestVersion> {
+        let region = self
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })?;
+        ensure!(
+            region.is_flushable(),
+            FlushableRegionStateSnafu {
+                region_id,
+                state: region.state(),
+            }
+        );
+
+        region.manifest_ctx.checkpoint().await
+    }
+
     /// Converts the [`RegionRole`].
     fn set_region_role(&self, region_id: RegionId, role: RegionRole) -> Result<()> {
         let region = self
@@ -593,6 +729,61 @@ impl RegionEngine for MitoEngine {
         self.inner.get_metadata(region_id).map_err(BoxedError::new)
     }
 
+    async fn label_values(
+        &self,
+        region_id: RegionId,
+        request: LabelValuesRequest,
+    ) -> Result<Option<Vec<String>>, BoxedError> {
+        self.inner
+            .label_values(region_id, request)
+            .await
+            .map_err(BoxedError::new)
+    }
+
+    async fn series_cardinality(
+        &self,
+        region_id: RegionId,
+        request: SeriesCardinalityRequest,
+    ) -> Result<Option<SeriesCardinality>, BoxedError> {
+        self.inner
+            .series_cardinality(region_id, request)
+            .await
+            .map(Some)
+            .map_err(BoxedError::new)
+    }
+
+    async fn region_manifest(
+        &self,
+        region_id: RegionId,
+    ) -> Result<Option<RegionManifestSnapshot>, BoxedError> {
+        self.inner
+            .region_manifest(region_id)
+            .await
+            .map(Some)
+            .map_err(BoxedError::new)
+    }
+
+    async fn checkpoint_region(
+        &self,
+        region_id: RegionId,
+    ) -> Result<Option<ManifestVersion>, BoxedError> {
+        self.inner
+            .checkpoint_region(region_id)
+            .await
+            .map(Some)
+            .map_err(BoxedError::new)
+    }
+
+    async fn producer_watermarks(
+        &self,
+        region_id: RegionId,
+    ) -> Result<Option<HashMap<String, u64>>, BoxedError> {
+        self.inner
+            .producer_watermarks(region_id)
+            .map(Some)
+            .map_err(BoxedError::new)
+    }
+
     /// Stop the engine.
     ///
     /// Stopping the engine doesn't stop the underlying log store as other components might
@@ -681,6 +872,9 @@ impl MitoEngine {
                 .await?,
                 config,
                 wal_raw_entry_reader,
+                label_values_dictionary: LabelValuesDictionary::new(
+                    DEFAULT_LABEL_VALUES_CACHE_SIZE,
+                ),
             }),
         })
     }
@@ -704,9 +898,11 @@ mod tests {
         let edit = RegionEdit {
             files_to_add: vec![FileMeta::default()],
             files_to_remove: vec![],
+            files_to_update: vec![],
             compaction_time_window: None,
             flushed_entry_id: None,
             flushed_sequence: None,
+            producer_watermarks: HashMap::new(),
         };
         assert!(is_valid_region_edit(&edit));
 
@@ -714,9 +910,11 @@ mod tests {
         let edit = RegionEdit {
             files_to_add: vec![],
             files_to_remove: vec![],
+            files_to_update: vec![],
             compaction_time_window: None,
             flushed_entry_id: None,
             flushed_sequence: None,
+            producer_watermarks: HashMap::new(),
         };
         assert!(!is_valid_region_edit(&edit));
 
@@ -724,9 +922,11 @@ mod tests {
         let edit = RegionEdit {
             files_to_add: vec![FileMeta::default()],
             files_to_remove: vec![FileMeta::default()],
+            files_to_update: vec![],
             compaction_time_window: None,
             flushed_entry_id: None,
             flushed_sequence: None,
+            producer_watermarks: HashMap::new(),
         };
         assert!(!is_valid_region_edit(&edit));
 
@@ -734,25 +934,31 @@ mod tests {
         let edit = RegionEdit {
             files_to_add: vec![FileMeta::default()],
             files_to_remove: vec![],
+            files_to_update: vec![],
             compaction_time_window: Some(Duration::from_secs(1)),
             flushed_entry_id: None,
             flushed_sequence: None,
+            producer_watermarks: HashMap::new(),
         };
         assert!(!is_valid_region_edit(&edit));
         let edit = RegionEdit {
             files_to_add: vec![FileMeta::default()],
             files_to_remove: vec![],
+            files_to_update: vec![],
             compaction_time_window: None,
             flushed_entry_id: Some(1),
             flushed_sequence: None,
+            producer_watermarks: HashMap::new(),
         };
         assert!(!is_valid_region_edit(&edit));
         let edit = RegionEdit {
             files_to_add: vec![FileMeta::default()],
             files_to_remove: vec![],
+            files_to_update: vec![],
             compaction_time_window: None,
             flushed_entry_id: None,
             flushed_sequence: Some(1),
+            producer_watermarks: HashMap::new(),
         };
         assert!(!is_valid_region_edit(&edit));
     }
@@ -532,7 +532,11 @@ async fn test_absent_and_invalid_columns() {
     let err = engine
         .handle_request(
             region_id,
-            RegionRequest::Put(RegionPutRequest { rows, hint: None }),
+            RegionRequest::Put(RegionPutRequest {
+                rows,
+                hint: None,
+                idempotency_key: None,
+            }),
         )
         .await
         .unwrap_err();
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
@@ -92,9 +93,11 @@ async fn test_edit_region_schedule_compaction() {
             ..Default::default()
         }],
         files_to_remove: vec![],
+        files_to_update: vec![],
         compaction_time_window: None,
         flushed_entry_id: None,
         flushed_sequence: None,
+        producer_watermarks: HashMap::new(),
     };
     engine
         .edit_region(region.region_id, new_edit())
@@ -179,9 +182,11 @@ async fn test_edit_region_fill_cache() {
             ..Default::default()
         }],
         files_to_remove: vec![],
+        files_to_update: vec![],
         compaction_time_window: None,
         flushed_entry_id: None,
         flushed_sequence: None,
+        producer_watermarks: HashMap::new(),
     };
     engine.edit_region(region.region_id, edit).await.unwrap();
 
@@ -229,9 +234,11 @@ async fn test_edit_region_concurrently() {
                 let edit = RegionEdit {
                     files_to_add: vec![sst],
                     files_to_remove: vec![],
+                    files_to_update: vec![],
                     compaction_time_window: None,
                     flushed_entry_id: None,
                     flushed_sequence: None,
+                    producer_watermarks: HashMap::new(),
                 };
                 engine
                     .edit_region(self.region.region_id, edit)
@@ -0,0 +1,161 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use api::v1::Rows;
+use common_recordbatch::RecordBatches;
+use datafusion_common::ScalarValue;
+use datafusion_expr::{col, lit};
+use store_api::region_engine::RegionEngine;
+use store_api::region_request::{
+    AlterKind, ApiSetIndexOptions, RegionAlterRequest, RegionBuildIndexRequest, RegionOpenRequest,
+    RegionRequest,
+};
+use store_api::storage::{RegionId, ScanRequest};
+
+use crate::config::MitoConfig;
+use crate::engine::MitoEngine;
+use crate::metrics::READ_ROW_GROUPS_TOTAL;
+use crate::sst::file::{FileId, IndexType};
+use crate::test_util::{
+    build_rows, flush_region, put_rows, rows_schema, CreateRequestBuilder, TestEnv,
+};
+
+/// Returns ids and available indexes of files in the region.
+fn region_files(engine: &MitoEngine, region_id: RegionId) -> Vec<(FileId, Vec<IndexType>)> {
+    let region = engine.get_region(region_id).unwrap();
+    let version = region.version();
+    version
+        .ssts
+        .levels()
+        .iter()
+        .flat_map(|level| level.files())
+        .map(|file| (file.file_id(), file.meta_ref().available_indexes.to_vec()))
+        .collect()
+}
+
+async fn scan_tag(engine: &MitoEngine, region_id: RegionId, tag: &str) -> String {
+    let request = ScanRequest {
+        filters: vec![col("tag_0").eq(lit(ScalarValue::Utf8(Some(tag.to_string()))))],
+        ..Default::default()
+    };
+    let stream = engine.scan_to_stream(region_id, request).await.unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    batches.pretty_print().unwrap()
+}
+
+#[tokio::test]
+async fn test_build_index_for_existing_files() {
+    common_telemetry::init_default_ut_logging();
+
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let region_dir = request.region_dir.clone();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    // Flushes 3 row groups to a file without indexes.
+    put_rows(
+        &engine,
+        region_id,
+        Rows {
+            schema: column_schemas,
+            rows: build_rows(0, 15),
+        },
+    )
+    .await;
+    flush_region(&engine, region_id, Some(5)).await;
+    let files = region_files(&engine, region_id);
+    assert_eq!(1, files.len());
+    assert!(files[0].1.is_empty());
+
+    // Nothing to build as the region has no index.
+    let output = engine
+        .handle_request(
+            region_id,
+            RegionRequest::BuildIndex(RegionBuildIndexRequest::default()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(0, output.affected_rows);
+    assert!(region_files(&engine, region_id)[0].1.is_empty());
+
+    // Setting the index schedules a job to build it for existing files.
+    engine
+        .handle_request(
+            region_id,
+            RegionRequest::Alter(RegionAlterRequest {
+                kind: AlterKind::SetIndex {
+                    options: ApiSetIndexOptions::Inverted {
+                        column_name: "tag_0".to_string(),
+                    },
+                },
+            }),
+        )
+        .await
+        .unwrap();
+    // Waits for the scheduled job.
+    engine
+        .handle_request(
+            region_id,
+            RegionRequest::BuildIndex(RegionBuildIndexRequest::default()),
+        )
+        .await
+        .unwrap();
+
+    // The file is not rewritten.
+    let indexed_files = region_files(&engine, region_id);
+    assert_eq!(files[0].0, indexed_files[0].0);
+    assert_eq!(vec![IndexType::InvertedIndex], indexed_files[0].1);
+
+    let filtered = READ_ROW_GROUPS_TOTAL
+        .with_label_values(&["inverted_index_filtered"])
+        .get();
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| 3     | 3.0     | 1970-01-01T00:00:03 |
++-------+---------+---------------------+";
+    assert_eq!(expected, scan_tag(&engine, region_id, "3").await);
+    // The index prunes the other 2 row groups.
+    let filtered_now = READ_ROW_GROUPS_TOTAL
+        .with_label_values(&["inverted_index_filtered"])
+        .get();
+    assert!(filtered_now - filtered >= 2);
+
+    // Indexes are recorded in the manifest.
+    let engine = env.reopen_engine(engine, MitoConfig::default()).await;
+    engine
+        .handle_request(
+            region_id,
+            RegionRequest::Open(RegionOpenRequest {
+                engine: String::new(),
+                region_dir,
+                options: HashMap::default(),
+                skip_wal_replay: false,
+            }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(indexed_files, region_files(&engine, region_id));
+    assert_eq!(expected, scan_tag(&engine, region_id, "3").await);
+}
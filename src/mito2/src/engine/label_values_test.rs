@@ -0,0 +1,213 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeSet;
+
+use api::v1::{Rows, SemanticType};
+use common_recordbatch::RecordBatches;
+use common_time::Timestamp;
+use datafusion_common::ScalarValue;
+use datafusion_expr::{col, lit};
+use datatypes::value::Value;
+use store_api::region_engine::{LabelValuesRequest, RegionEngine};
+use store_api::region_request::{RegionCreateRequest, RegionRequest};
+use store_api::storage::{RegionId, ScanRequest};
+
+use crate::config::{InvertedIndexConfig, MitoConfig, Mode};
+use crate::engine::MitoEngine;
+use crate::test_util::{
+    build_rows, flush_region, put_rows, rows_schema, CreateRequestBuilder, TestEnv,
+};
+
+/// Returns a create request with inverted index on the tag column.
+fn indexed_create_request() -> RegionCreateRequest {
+    let mut request = CreateRequestBuilder::new().build();
+    for column in &mut request.column_metadatas {
+        if column.semantic_type == SemanticType::Tag {
+            column.column_schema = column.column_schema.clone().with_inverted_index(true);
+        }
+    }
+    request
+}
+
+fn label_values_config() -> MitoConfig {
+    MitoConfig {
+        inverted_index: InvertedIndexConfig {
+            apply_on_label_values: Mode::Auto,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn new_request(start_ms: i64, end_ms: i64) -> LabelValuesRequest {
+    LabelValuesRequest {
+        column_name: "tag_0".to_string(),
+        start: Timestamp::new_millisecond(start_ms),
+        end: Timestamp::new_millisecond(end_ms),
+    }
+}
+
+/// Returns the distinct values of `tag_0` within the time range by scanning the region.
+async fn scan_label_values(
+    engine: &MitoEngine,
+    region_id: RegionId,
+    start_ms: i64,
+    end_ms: i64,
+) -> Vec<String> {
+    let request = ScanRequest {
+        projection: Some(vec![0]),
+        filters: vec![
+            col("ts").gt_eq(lit(ScalarValue::TimestampMillisecond(Some(start_ms), None))),
+            col("ts").lt_eq(lit(ScalarValue::TimestampMillisecond(Some(end_ms), None))),
+        ],
+        ..Default::default()
+    };
+    let stream = engine.scan_to_stream(region_id, request).await.unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    let mut values = BTreeSet::new();
+    for batch in batches.iter() {
+        let column = batch.column(0);
+        for i in 0..column.len() {
+            if let Value::String(value) = column.get(i) {
+                values.insert(value.into_string());
+            }
+        }
+    }
+    values.into_iter().collect()
+}
+
+async fn assert_same_label_values(
+    engine: &MitoEngine,
+    region_id: RegionId,
+    start_ms: i64,
+    end_ms: i64,
+) {
+    let from_dictionary = engine
+        .label_values(region_id, new_request(start_ms, end_ms))
+        .await
+        .unwrap()
+        .unwrap();
+    let from_scan = scan_label_values(engine, region_id, start_ms, end_ms).await;
+    assert_eq!(from_scan, from_dictionary);
+}
+
+#[tokio::test]
+async fn test_label_values_from_dictionary() {
+    common_telemetry::init_default_ut_logging();
+
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(label_values_config()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = indexed_create_request();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    // Two files with time ranges [0s, 4s] and [10s, 14s].
+    for (start, end) in [(0, 5), (10, 15)] {
+        put_rows(
+            &engine,
+            region_id,
+            Rows {
+                schema: column_schemas.clone(),
+                rows: build_rows(start, end),
+            },
+        )
+        .await;
+        flush_region(&engine, region_id, None).await;
+    }
+    // Rows in the memtable within [20s, 22s].
+    put_rows(
+        &engine,
+        region_id,
+        Rows {
+            schema: column_schemas,
+            rows: build_rows(20, 23),
+        },
+    )
+    .await;
+
+    // The whole region.
+    assert_same_label_values(&engine, region_id, 0, 30_000).await;
+    // Ranges aligned to the files and the memtable.
+    assert_same_label_values(&engine, region_id, 0, 4_000).await;
+    assert_same_label_values(&engine, region_id, 10_000, 14_000).await;
+    assert_same_label_values(&engine, region_id, 20_000, 22_000).await;
+    assert_same_label_values(&engine, region_id, 5_000, 9_000).await;
+
+    let values = engine
+        .label_values(region_id, new_request(10_000, 22_000))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(vec!["10", "11", "12", "13", "14", "20", "21", "22"], values);
+}
+
+#[tokio::test]
+async fn test_label_values_without_index() {
+    common_telemetry::init_default_ut_logging();
+
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(label_values_config()).await;
+
+    // The tag column has no inverted index.
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+    put_rows(
+        &engine,
+        region_id,
+        Rows {
+            schema: column_schemas,
+            rows: build_rows(0, 5),
+        },
+    )
+    .await;
+    flush_region(&engine, region_id, None).await;
+
+    let values = engine
+        .label_values(region_id, new_request(0, 10_000))
+        .await
+        .unwrap();
+    assert!(values.is_none());
+}
+
+#[tokio::test]
+async fn test_label_values_disabled() {
+    common_telemetry::init_default_ut_logging();
+
+    let mut env = TestEnv::new();
+    // The dictionary is disabled by default.
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    engine
+        .handle_request(region_id, RegionRequest::Create(indexed_create_request()))
+        .await
+        .unwrap();
+
+    let values = engine
+        .label_values(region_id, new_request(0, 10_000))
+        .await
+        .unwrap();
+    assert!(values.is_none());
+}
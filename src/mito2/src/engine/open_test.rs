@@ -132,6 +132,7 @@ async fn test_engine_open_readonly() {
             RegionRequest::Put(RegionPutRequest {
                 rows: rows.clone(),
                 hint: None,
+                idempotency_key: None,
             }),
         )
         .await
@@ -0,0 +1,139 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for skipping rows delivered again by their producers.
+
+use std::collections::HashMap;
+
+use api::v1::Rows;
+use common_recordbatch::RecordBatches;
+use store_api::region_engine::RegionEngine;
+use store_api::region_request::{AffectedRows, IdempotencyKey, RegionPutRequest, RegionRequest};
+use store_api::storage::{RegionId, ScanRequest};
+
+use crate::config::MitoConfig;
+use crate::engine::MitoEngine;
+use crate::test_util::{
+    build_rows, flush_region, reopen_region, rows_schema, CreateRequestBuilder, TestEnv,
+};
+
+async fn put_rows_with_key(
+    engine: &MitoEngine,
+    region_id: RegionId,
+    rows: Rows,
+    producer_id: &str,
+    sequence: u64,
+) -> AffectedRows {
+    engine
+        .handle_request(
+            region_id,
+            RegionRequest::Put(RegionPutRequest {
+                rows,
+                hint: None,
+                idempotency_key: Some(IdempotencyKey {
+                    producer_id: producer_id.to_string(),
+                    sequence,
+                }),
+            }),
+        )
+        .await
+        .unwrap()
+        .affected_rows
+}
+
+async fn count_rows(engine: &MitoEngine, region_id: RegionId) -> usize {
+    let stream = engine
+        .scan_to_stream(region_id, ScanRequest::default())
+        .await
+        .unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    batches.iter().map(|batch| batch.num_rows()).sum()
+}
+
+async fn watermarks(engine: &MitoEngine, region_id: RegionId) -> HashMap<String, u64> {
+    engine
+        .producer_watermarks(region_id)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_skip_rows_written_by_producer() {
+    common_telemetry::init_default_ut_logging();
+
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    // Rows delivered again would be visible under append mode.
+    let request = CreateRequestBuilder::new()
+        .insert_option("append_mode", "true")
+        .build();
+    let region_dir = request.region_dir.clone();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+    let rows = |start, end| Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows(start, end),
+    };
+
+    assert_eq!(
+        3,
+        put_rows_with_key(&engine, region_id, rows(0, 3), "p1", 1).await
+    );
+    // Delivers the same rows again.
+    assert_eq!(
+        0,
+        put_rows_with_key(&engine, region_id, rows(0, 3), "p1", 1).await
+    );
+    assert_eq!(
+        2,
+        put_rows_with_key(&engine, region_id, rows(3, 5), "p1", 2).await
+    );
+    // Sequence numbers of different producers are independent.
+    assert_eq!(
+        1,
+        put_rows_with_key(&engine, region_id, rows(5, 6), "p2", 1).await
+    );
+    assert_eq!(
+        0,
+        put_rows_with_key(&engine, region_id, rows(0, 3), "p1", 1).await
+    );
+    assert_eq!(6, count_rows(&engine, region_id).await);
+    let expected = HashMap::from([("p1".to_string(), 2), ("p2".to_string(), 1)]);
+    assert_eq!(expected, watermarks(&engine, region_id).await);
+
+    // Recovers the watermarks from the WAL.
+    reopen_region(&engine, region_id, region_dir.clone(), true, HashMap::new()).await;
+    assert_eq!(expected, watermarks(&engine, region_id).await);
+    assert_eq!(6, count_rows(&engine, region_id).await);
+    assert_eq!(
+        0,
+        put_rows_with_key(&engine, region_id, rows(3, 5), "p1", 2).await
+    );
+
+    // Recovers the watermarks from the manifest after the WAL is obsolete.
+    flush_region(&engine, region_id, None).await;
+    reopen_region(&engine, region_id, region_dir, true, HashMap::new()).await;
+    assert_eq!(expected, watermarks(&engine, region_id).await);
+    assert_eq!(
+        0,
+        put_rows_with_key(&engine, region_id, rows(5, 6), "p2", 1).await
+    );
+    assert_eq!(6, count_rows(&engine, region_id).await);
+}
@@ -0,0 +1,110 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use api::v1::Rows;
+use common_time::Timestamp;
+use object_store::util::join_path;
+use store_api::region_engine::{RegionEngine, RegionRole};
+use store_api::region_request::RegionRequest;
+use store_api::storage::RegionId;
+
+use crate::config::MitoConfig;
+use crate::test_util::{
+    build_rows, flush_region, put_rows, reopen_region, rows_schema, CreateRequestBuilder, TestEnv,
+};
+
+#[tokio::test]
+async fn test_region_manifest_lists_files() {
+    common_telemetry::init_default_ut_logging();
+
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let region_dir = request.region_dir.clone();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    // Two files with time ranges [0s, 4s] and [10s, 14s].
+    for (start, end) in [(0, 5), (10, 15)] {
+        put_rows(
+            &engine,
+            region_id,
+            Rows {
+                schema: column_schemas.clone(),
+                rows: build_rows(start, end),
+            },
+        )
+        .await;
+        flush_region(&engine, region_id, None).await;
+    }
+
+    let snapshot = engine.region_manifest(region_id).await.unwrap().unwrap();
+    assert_eq!(2, snapshot.files.len());
+    let time_ranges = snapshot
+        .files
+        .iter()
+        .map(|file| file.time_range)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            (
+                Timestamp::new_millisecond(0),
+                Timestamp::new_millisecond(4_000)
+            ),
+            (
+                Timestamp::new_millisecond(10_000),
+                Timestamp::new_millisecond(14_000)
+            ),
+        ],
+        time_ranges
+    );
+    let object_store = env.get_object_store().unwrap();
+    for file in &snapshot.files {
+        assert_eq!(5, file.num_rows);
+        assert!(file.file_size > 0);
+        assert!(file.file_path.starts_with(&region_dir));
+        assert!(file.file_path.contains(&file.file_id));
+        assert!(object_store.exists(&file.file_path).await.unwrap());
+    }
+
+    // Checkpoints the manifest at its current version.
+    let version = engine.checkpoint_region(region_id).await.unwrap().unwrap();
+    assert_eq!(snapshot.manifest_version, version);
+    let manifest_dir = join_path(&region_dir, "manifest");
+    assert!(object_store
+        .exists(&join_path(&manifest_dir, "_last_checkpoint"))
+        .await
+        .unwrap());
+    // Checkpoints again without any change.
+    let version = engine.checkpoint_region(region_id).await.unwrap().unwrap();
+    assert_eq!(snapshot.manifest_version, version);
+
+    // The region recovers the same files from the checkpoint.
+    reopen_region(&engine, region_id, region_dir, true, HashMap::new()).await;
+    let reopened = engine.region_manifest(region_id).await.unwrap().unwrap();
+    assert_eq!(snapshot.files, reopened.files);
+
+    // Followers can't checkpoint the manifest.
+    engine
+        .set_region_role(region_id, RegionRole::Follower)
+        .unwrap();
+    assert!(engine.checkpoint_region(region_id).await.is_err());
+}
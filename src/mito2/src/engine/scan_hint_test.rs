@@ -0,0 +1,244 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use api::v1::{Rows, SemanticType};
+use common_recordbatch::RecordBatches;
+use datafusion::physical_plan::metrics::ExecutionPlanMetricsSet;
+use datafusion::physical_plan::{DisplayAs, DisplayFormatType};
+use datafusion_expr::{col, lit};
+use store_api::region_engine::{RegionEngine, RegionScannerRef};
+use store_api::region_request::RegionRequest;
+use store_api::storage::{RegionId, ScanHints, ScanRequest, ScanSample};
+
+use crate::config::MitoConfig;
+use crate::engine::MitoEngine;
+use crate::test_util::{
+    build_rows, flush_region, put_rows, rows_schema, CreateRequestBuilder, TestEnv,
+};
+
+/// Formats the scanner with verbose metrics.
+struct VerboseScanner<'a>(&'a RegionScannerRef);
+
+impl fmt::Display for VerboseScanner<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_as(DisplayFormatType::Verbose, f)
+    }
+}
+
+/// Scans the region and returns the number of rows and the number of rows
+/// filtered by the inverted index.
+async fn scan_with_hints(
+    engine: &MitoEngine,
+    region_id: RegionId,
+    scan_hints: ScanHints,
+) -> (usize, usize) {
+    let request = ScanRequest {
+        filters: vec![col("tag_0").eq(lit("3"))],
+        scan_hints,
+        ..Default::default()
+    };
+    let scanner = engine.handle_query(region_id, request).await.unwrap();
+    let metrics_set = ExecutionPlanMetricsSet::new();
+    let mut num_rows = 0;
+    for partition in 0..scanner.properties().num_partitions() {
+        let stream = scanner.scan_partition(&metrics_set, partition).unwrap();
+        let batches = RecordBatches::try_collect(stream).await.unwrap();
+        num_rows += batches.iter().map(|batch| batch.num_rows()).sum::<usize>();
+    }
+
+    let metrics = VerboseScanner(&scanner).to_string();
+    let rows_inverted_filtered = metrics
+        .split("rows_inverted_filtered=")
+        .skip(1)
+        .map(|s| {
+            let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+            s[..end].parse::<usize>().unwrap()
+        })
+        .sum();
+    (num_rows, rows_inverted_filtered)
+}
+
+#[tokio::test]
+async fn test_scan_hint_no_inverted_index() {
+    common_telemetry::init_default_ut_logging();
+
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let mut request = CreateRequestBuilder::new().build();
+    for column in &mut request.column_metadatas {
+        if column.semantic_type == SemanticType::Tag {
+            column.column_schema = column.column_schema.clone().with_inverted_index(true);
+        }
+    }
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    put_rows(
+        &engine,
+        region_id,
+        Rows {
+            schema: column_schemas,
+            rows: build_rows(0, 15),
+        },
+    )
+    .await;
+    flush_region(&engine, region_id, Some(5)).await;
+
+    let (num_rows, rows_inverted_filtered) =
+        scan_with_hints(&engine, region_id, ScanHints::default()).await;
+    assert_eq!(1, num_rows);
+    assert!(rows_inverted_filtered > 0);
+
+    // The index is bypassed but the result is the same.
+    let hints = ScanHints {
+        no_inverted_index: true,
+        ..Default::default()
+    };
+    let (num_rows, rows_inverted_filtered) = scan_with_hints(&engine, region_id, hints).await;
+    assert_eq!(1, num_rows);
+    assert_eq!(0, rows_inverted_filtered);
+}
+
+/// Scans the region with the sample and returns the output.
+async fn scan_sample(engine: &MitoEngine, region_id: RegionId, sample: ScanSample) -> String {
+    let request = ScanRequest {
+        scan_hints: ScanHints {
+            sample: Some(sample),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let stream = engine.scan_to_stream(region_id, request).await.unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    batches.pretty_print().unwrap()
+}
+
+#[tokio::test]
+async fn test_scan_hint_sample() {
+    common_telemetry::init_default_ut_logging();
+
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    put_rows(
+        &engine,
+        region_id,
+        Rows {
+            schema: column_schemas,
+            rows: build_rows(0, 1000),
+        },
+    )
+    .await;
+    // 100 row groups.
+    flush_region(&engine, region_id, Some(10)).await;
+
+    let sample = ScanSample {
+        fraction: 0.3,
+        seed: 42,
+    };
+    let output = scan_sample(&engine, region_id, sample).await;
+    // Lines of the header and borders.
+    let num_rows = output.lines().count() - 4;
+    assert!((150..450).contains(&num_rows), "sampled {num_rows} rows");
+    // The same seed reads the same rows.
+    assert_eq!(output, scan_sample(&engine, region_id, sample).await);
+    // Another seed reads other rows.
+    let other = ScanSample { seed: 7, ..sample };
+    assert_ne!(output, scan_sample(&engine, region_id, other).await);
+
+    let all = ScanSample {
+        fraction: 1.0,
+        seed: 42,
+    };
+    let output = scan_sample(&engine, region_id, all).await;
+    assert_eq!(1000, output.lines().count() - 4);
+}
+
+/// Scans the region with the hints and returns the output.
+async fn scan_to_string(engine: &MitoEngine, region_id: RegionId, scan_hints: ScanHints) -> String {
+    let request = ScanRequest {
+        scan_hints,
+        ..Default::default()
+    };
+    let stream = engine.scan_to_stream(region_id, request).await.unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    batches.pretty_print().unwrap()
+}
+
+#[tokio::test]
+async fn test_scan_hint_newer_than() {
+    common_telemetry::init_default_ut_logging();
+
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    let rows = |start, end| Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows(start, end),
+    };
+    put_rows(&engine, region_id, rows(0, 5)).await;
+    flush_region(&engine, region_id, None).await;
+    put_rows(&engine, region_id, rows(5, 8)).await;
+    let sequence = engine.get_last_seq_num(region_id).await.unwrap().unwrap();
+    flush_region(&engine, region_id, None).await;
+    // Overwrites a row in the files and writes new rows.
+    put_rows(&engine, region_id, rows(2, 3)).await;
+    put_rows(&engine, region_id, rows(8, 10)).await;
+
+    let hints = ScanHints {
+        newer_than: Some(sequence),
+        ..Default::default()
+    };
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| 2     | 2.0     | 1970-01-01T00:00:02 |
+| 8     | 8.0     | 1970-01-01T00:00:08 |
+| 9     | 9.0     | 1970-01-01T00:00:09 |
++-------+---------+---------------------+";
+    assert_eq!(
+        expected,
+        scan_to_string(&engine, region_id, hints.clone()).await
+    );
+    // The same rows after flushing them.
+    flush_region(&engine, region_id, None).await;
+    assert_eq!(expected, scan_to_string(&engine, region_id, hints).await);
+
+    let output = scan_to_string(&engine, region_id, ScanHints::default()).await;
+    assert_eq!(10, output.lines().count() - 4);
+}
@@ -0,0 +1,128 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use api::v1::value::ValueData;
+use api::v1::{Row, Rows};
+use store_api::region_engine::{RegionEngine, SeriesCardinalityRequest};
+use store_api::region_request::RegionRequest;
+use store_api::storage::RegionId;
+
+use crate::config::MitoConfig;
+use crate::test_util::{flush_region, put_rows, rows_schema, CreateRequestBuilder, TestEnv};
+
+/// Builds a row of each `(tag_0, tag_1)` at the timestamp `ts` in seconds.
+fn build_series_rows(series: &[(&str, String)], ts: i64) -> Vec<Row> {
+    series
+        .iter()
+        .map(|(job, instance)| Row {
+            values: vec![
+                api::v1::Value {
+                    value_data: Some(ValueData::StringValue(job.to_string())),
+                },
+                api::v1::Value {
+                    value_data: Some(ValueData::StringValue(instance.clone())),
+                },
+                api::v1::Value {
+                    value_data: Some(ValueData::F64Value(ts as f64)),
+                },
+                api::v1::Value {
+                    value_data: Some(ValueData::TimestampMillisecondValue(ts * 1000)),
+                },
+            ],
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_series_cardinality() {
+    common_telemetry::init_default_ut_logging();
+
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().tag_num(2).build();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    // 5 series of job `a`, 2 series of job `b` and 1 series of job `c`.
+    let series = [("a", 5), ("b", 2), ("c", 1)]
+        .into_iter()
+        .flat_map(|(job, n)| (0..n).map(move |i| (job, format!("{job}-{i}"))))
+        .collect::<Vec<_>>();
+    // All series are in the SST and the memtable.
+    for ts in 0..3 {
+        put_rows(
+            &engine,
+            region_id,
+            Rows {
+                schema: column_schemas.clone(),
+                rows: build_series_rows(&series, ts),
+            },
+        )
+        .await;
+    }
+    flush_region(&engine, region_id, None).await;
+    put_rows(
+        &engine,
+        region_id,
+        Rows {
+            schema: column_schemas,
+            rows: build_series_rows(&series, 3),
+        },
+    )
+    .await;
+
+    let cardinality = engine
+        .series_cardinality(region_id, SeriesCardinalityRequest::default())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(8, cardinality.num_series);
+    assert!(cardinality.label_values.is_empty());
+
+    let cardinality = engine
+        .series_cardinality(
+            region_id,
+            SeriesCardinalityRequest {
+                label: Some("tag_0".to_string()),
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(8, cardinality.num_series);
+    let expected = HashMap::from([
+        ("a".to_string(), 5),
+        ("b".to_string(), 2),
+        ("c".to_string(), 1),
+    ]);
+    assert_eq!(expected, cardinality.label_values);
+
+    // Only tags have label values.
+    assert!(engine
+        .series_cardinality(
+            region_id,
+            SeriesCardinalityRequest {
+                label: Some("field_0".to_string()),
+            },
+        )
+        .await
+        .is_err());
+}
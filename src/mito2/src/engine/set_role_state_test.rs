@@ -77,6 +77,7 @@ async fn test_set_role_state_gracefully() {
                 RegionRequest::Put(RegionPutRequest {
                     rows: rows.clone(),
                     hint: None,
+                    idempotency_key: None,
                 }),
             )
             .await
@@ -160,6 +161,7 @@ async fn test_write_downgrading_region() {
             RegionRequest::Put(RegionPutRequest {
                 rows: rows.clone(),
                 hint: None,
+                idempotency_key: None,
             }),
         )
         .await
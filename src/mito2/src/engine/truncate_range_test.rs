@@ -0,0 +1,185 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use api::v1::{ColumnSchema, Rows};
+use common_recordbatch::RecordBatches;
+use common_time::range::TimestampRange;
+use common_time::timestamp::TimeUnit;
+use datatypes::prelude::ScalarVector;
+use datatypes::vectors::TimestampMillisecondVector;
+use store_api::region_engine::RegionEngine;
+use store_api::region_request::{
+    RegionCompactRequest, RegionOpenRequest, RegionRequest, RegionTruncateRangeRequest,
+};
+use store_api::storage::{RegionId, ScanRequest};
+
+use crate::config::MitoConfig;
+use crate::engine::MitoEngine;
+use crate::test_util::{
+    build_delete_rows_for_key, build_rows_for_key, delete_rows, delete_rows_schema, flush_region,
+    put_rows, rows_schema, CreateRequestBuilder, TestEnv,
+};
+
+async fn put_and_flush(
+    engine: &MitoEngine,
+    region_id: RegionId,
+    column_schemas: &[ColumnSchema],
+    rows: Range<usize>,
+) {
+    let rows = Rows {
+        schema: column_schemas.to_vec(),
+        rows: build_rows_for_key("a", rows.start, rows.end, 0),
+    };
+    put_rows(engine, region_id, rows).await;
+    flush_region(engine, region_id, None).await;
+}
+
+/// Returns the number of files and the timestamps in seconds of the region.
+async fn scan_files_and_ts(engine: &MitoEngine, region_id: RegionId) -> (usize, Vec<i64>) {
+    let scanner = engine.scanner(region_id, ScanRequest::default()).unwrap();
+    let num_files = scanner.num_files();
+    let stream = scanner.scan().await.unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    let mut timestamps = Vec::new();
+    for batch in batches {
+        let ts_col = batch
+            .column_by_name("ts")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<TimestampMillisecondVector>()
+            .unwrap();
+        timestamps.extend(ts_col.iter_data().map(|t| t.unwrap().0.value() / 1000));
+    }
+    (num_files, timestamps)
+}
+
+async fn truncate_range(
+    engine: &MitoEngine,
+    region_id: RegionId,
+    start_sec: i64,
+    end_sec: i64,
+) -> usize {
+    let time_range = TimestampRange::with_unit(start_sec, end_sec, TimeUnit::Second).unwrap();
+    engine
+        .handle_request(
+            region_id,
+            RegionRequest::TruncateRange(RegionTruncateRangeRequest { time_range }),
+        )
+        .await
+        .unwrap()
+        .affected_rows
+}
+
+#[tokio::test]
+async fn test_truncate_range_removes_covered_files() {
+    common_telemetry::init_default_ut_logging();
+    let mut env = TestEnv::with_prefix("truncate-range");
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    env.get_schema_metadata_manager()
+        .register_region_table_info(
+            region_id.table_id(),
+            "test_table",
+            "test_catalog",
+            "test_schema",
+            None,
+            env.get_kv_backend(),
+        )
+        .await;
+
+    let request = CreateRequestBuilder::new()
+        .insert_option("compaction.type", "twcs")
+        .insert_option("compaction.twcs.max_active_window_runs", "1")
+        .insert_option("compaction.twcs.max_inactive_window_runs", "1")
+        .build();
+    let region_dir = request.region_dir.clone();
+    let column_schemas = rows_schema(&request);
+    let delete_schema = delete_rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    // Files: [0..9], [10..19], [20..29].
+    put_and_flush(&engine, region_id, &column_schemas, 0..10).await;
+    put_and_flush(&engine, region_id, &column_schemas, 10..20).await;
+    put_and_flush(&engine, region_id, &column_schemas, 20..30).await;
+    // Memtable: [5..14].
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows_for_key("a", 5, 15, 100),
+    };
+    put_rows(&engine, region_id, rows).await;
+    let (num_files, _) = scan_files_and_ts(&engine, region_id).await;
+    assert_eq!(3, num_files);
+
+    // Nothing is covered.
+    assert_eq!(0, truncate_range(&engine, region_id, 1, 19).await);
+    assert_eq!(3, scan_files_and_ts(&engine, region_id).await.0);
+
+    // Removes the first two files, the last file is partially covered.
+    assert_eq!(20, truncate_range(&engine, region_id, 0, 25).await);
+    let (num_files, timestamps) = scan_files_and_ts(&engine, region_id).await;
+    assert_eq!(1, num_files);
+    // Rows in the memtable are kept.
+    let expected = (5..15).chain(20..30).collect::<Vec<_>>();
+    assert_eq!(expected, timestamps);
+
+    // The edit survives reopening the region.
+    let engine = env.reopen_engine(engine, MitoConfig::default()).await;
+    engine
+        .handle_request(
+            region_id,
+            RegionRequest::Open(RegionOpenRequest {
+                engine: String::new(),
+                region_dir,
+                options: HashMap::default(),
+                skip_wal_replay: false,
+            }),
+        )
+        .await
+        .unwrap();
+    let (num_files, timestamps) = scan_files_and_ts(&engine, region_id).await;
+    assert_eq!(1, num_files);
+    assert_eq!(expected, timestamps);
+
+    // Deletes the remaining rows in the range row by row.
+    for range in [5..15, 20..25] {
+        let rows = Rows {
+            schema: delete_schema.clone(),
+            rows: build_delete_rows_for_key("a", range.start, range.end),
+        };
+        delete_rows(&engine, region_id, rows).await;
+    }
+    let expected = (25..30).collect::<Vec<_>>();
+    assert_eq!(expected, scan_files_and_ts(&engine, region_id).await.1);
+
+    // The partially covered file merges with the deletes.
+    flush_region(&engine, region_id, None).await;
+    let result = engine
+        .handle_request(
+            region_id,
+            RegionRequest::Compact(RegionCompactRequest::default()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(0, result.affected_rows);
+    let (num_files, timestamps) = scan_files_and_ts(&engine, region_id).await;
+    assert_eq!(1, num_files);
+    assert_eq!(expected, timestamps);
+}
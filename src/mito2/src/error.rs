@@ -471,6 +471,14 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to build index for region {}", region_id))]
+    BuildIndex {
+        region_id: RegionId,
+        source: Arc<Error>,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display(
         "Failed to compat readers for region {}, reason: {}",
         region_id,
@@ -595,6 +603,13 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to read terms of inverted index"))]
+    ReadIndexTerms {
+        source: index::inverted_index::error::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Failed to apply bloom filter index"))]
     ApplyBloomFilterIndex {
         source: index::bloom_filter::error::Error,
@@ -1118,7 +1133,7 @@ impl ErrorExt for Error {
             RegionClosed { .. } => StatusCode::Cancelled,
             RegionTruncated { .. } => StatusCode::Cancelled,
             RejectWrite { .. } => StatusCode::StorageUnavailable,
-            CompactRegion { source, .. } => source.status_code(),
+            CompactRegion { source, .. } | BuildIndex { source, .. } => source.status_code(),
             CompatReader { .. } => StatusCode::Unexpected,
             InvalidRegionRequest { source, .. } => source.status_code(),
             RegionState { .. } | UpdateManifest { .. } => StatusCode::RegionNotReady,
@@ -1131,6 +1146,7 @@ impl ErrorExt for Error {
             BuildIndexApplier { source, .. }
             | PushIndexValue { source, .. }
             | ApplyInvertedIndex { source, .. }
+            | ReadIndexTerms { source, .. }
             | IndexFinish { source, .. } => source.status_code(),
             PuffinReadBlob { source, .. }
             | PuffinAddBlob { source, .. }
@@ -331,6 +331,7 @@ impl RegionFlushTask {
 
         let mut write_opts = WriteOptions {
             write_buffer_size: self.engine_config.sst_write_buffer_size,
+            compression: version.options.sst.compression,
             ..Default::default()
         };
         if let Some(row_group_size) = self.row_group_size {
@@ -406,10 +407,12 @@ impl RegionFlushTask {
         let edit = RegionEdit {
             files_to_add: file_metas,
             files_to_remove: Vec::new(),
+            files_to_update: vec![],
             compaction_time_window: None,
             // The last entry has been flushed.
             flushed_entry_id: Some(version_data.last_entry_id),
             flushed_sequence: Some(version_data.committed_sequence),
+            producer_watermarks: (*version_data.producer_watermarks).clone(),
         };
         info!("Applying {edit:?} to region {}", self.region_id);
 
@@ -928,9 +931,11 @@ mod tests {
             RegionEdit {
                 files_to_add: Vec::new(),
                 files_to_remove: Vec::new(),
+                files_to_update: vec![],
                 compaction_time_window: None,
                 flushed_entry_id: None,
                 flushed_sequence: None,
+                producer_watermarks: HashMap::new(),
             },
             &[0],
             builder.file_purger(),
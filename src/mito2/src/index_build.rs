@@ -0,0 +1,522 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Builds indexes for existing SSTs in background.
+//!
+//! Index options of columns only affect SSTs written after they are set. The index
+//! build job reads SSTs without index files and writes index files for them, without
+//! rewriting the data. Then it updates metadata of these files in the manifest so
+//! readers can use the new indexes.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use common_base::readable_size::ReadableSize;
+use common_telemetry::{error, info};
+use snafu::ResultExt;
+use store_api::metadata::{RegionMetadata, RegionMetadataBuilder, RegionMetadataRef};
+use store_api::storage::RegionId;
+use tokio::sync::mpsc;
+
+use crate::access_layer::{AccessLayerRef, OperationType, RegionFilePathFactory};
+use crate::cache::{CacheManagerRef, CacheStrategy};
+use crate::config::MitoConfig;
+use crate::error::{
+    BuildIndexSnafu, Error, IndexOptionsSnafu, InvalidMetadataSnafu, RegionNotFoundSnafu, Result,
+};
+use crate::manifest::action::{RegionEdit, RegionMetaAction, RegionMetaActionList};
+use crate::read::BatchReader;
+use crate::region::options::IndexOptions;
+use crate::region::{ManifestContextRef, MitoRegionRef, RegionLeaderState};
+use crate::request::{
+    BackgroundNotify, IndexBuildFailed, IndexBuildFinished, OptionOutputTx, OutputTx, WorkerRequest,
+};
+use crate::schedule::scheduler::{Job, SchedulerRef};
+use crate::sst::file::{FileHandle, FileMeta};
+use crate::sst::index::{IndexerBuilder, IndexerBuilderImpl};
+use crate::sst::parquet::DEFAULT_ROW_GROUP_SIZE;
+
+/// A task to build indexes for SSTs of a region.
+pub(crate) struct IndexBuildTask {
+    region_id: RegionId,
+    /// Files to build indexes for.
+    files: Vec<FileHandle>,
+    /// Current metadata of the region.
+    metadata: RegionMetadataRef,
+    index_options: IndexOptions,
+    access_layer: AccessLayerRef,
+    manifest_ctx: ManifestContextRef,
+    cache_manager: CacheManagerRef,
+    engine_config: Arc<MitoConfig>,
+    /// Request sender to notify the worker.
+    request_sender: mpsc::Sender<WorkerRequest>,
+}
+
+impl IndexBuildTask {
+    fn into_index_build_job(self) -> Job {
+        Box::pin(async move {
+            self.do_build().await;
+        })
+    }
+
+    /// Runs the index build task.
+    async fn do_build(self) {
+        let start = Instant::now();
+        let notify = match self.build_indexes().await {
+            Ok(edit) => {
+                info!(
+                    "Built indexes for {} files of region {}, cost: {:?}",
+                    edit.files_to_update.len(),
+                    self.region_id,
+                    start.elapsed()
+                );
+                BackgroundNotify::IndexBuildFinished(IndexBuildFinished {
+                    region_id: self.region_id,
+                    edit,
+                })
+            }
+            Err(e) => {
+                error!(e; "Failed to build indexes for region {}", self.region_id);
+                BackgroundNotify::IndexBuildFailed(IndexBuildFailed {
+                    region_id: self.region_id,
+                    err: Arc::new(e),
+                })
+            }
+        };
+
+        if let Err(e) = self
+            .request_sender
+            .send(WorkerRequest::Background {
+                region_id: self.region_id,
+                notify,
+            })
+            .await
+        {
+            error!(
+                "Failed to notify index build job status for region {}, request: {:?}",
+                self.region_id, e.0
+            );
+        }
+    }
+
+    /// Builds indexes for all files and writes the edit to the manifest.
+    async fn build_indexes(&self) -> Result<RegionEdit> {
+        let mut throttle = Throttle::new(self.engine_config.index.build_throughput_limit);
+        let mut files_to_update = Vec::with_capacity(self.files.len());
+        for file in &self.files {
+            // The compaction writes indexes for the output files.
+            if file.compacting() {
+                continue;
+            }
+            if let Some(file_meta) = self.build_file_index(file, &mut throttle).await? {
+                files_to_update.push(file_meta);
+            }
+        }
+
+        let edit = RegionEdit {
+            files_to_add: vec![],
+            files_to_remove: vec![],
+            files_to_update,
+            compaction_time_window: None,
+            flushed_entry_id: None,
+            flushed_sequence: None,
+            producer_watermarks: HashMap::new(),
+        };
+        if edit.files_to_update.is_empty() {
+            return Ok(edit);
+        }
+
+        let action_list = RegionMetaActionList::with_action(RegionMetaAction::Edit(edit.clone()));
+        // We might leave index files if the task fails to update the manifest. They are
+        // overwritten by the next task or deleted with the SST.
+        self.manifest_ctx
+            .update_manifest(RegionLeaderState::Writable, action_list)
+            .await?;
+
+        Ok(edit)
+    }
+
+    /// Builds indexes for the `file`. Returns the new metadata of the file if any index is built.
+    async fn build_file_index(
+        &self,
+        file: &FileHandle,
+        throttle: &mut Throttle,
+    ) -> Result<Option<FileMeta>> {
+        let mut reader = self
+            .access_layer
+            .read_sst(file.clone())
+            .cache(CacheStrategy::Compaction(self.cache_manager.clone()))
+            .build()
+            .await?;
+        let row_group_size = reader
+            .parquet_metadata()
+            .row_groups()
+            .first()
+            .map(|row_group| row_group.num_rows() as usize)
+            .unwrap_or(DEFAULT_ROW_GROUP_SIZE);
+        let metadata = index_metadata(reader.metadata(), &self.metadata)?;
+
+        let indexer_builder = IndexerBuilderImpl {
+            op_type: OperationType::BuildIndex,
+            metadata: Arc::new(metadata),
+            row_group_size,
+            puffin_manager: self.access_layer.puffin_manager_factory().build(
+                self.access_layer.object_store().clone(),
+                RegionFilePathFactory::new(self.access_layer.region_dir().to_string()),
+            ),
+            intermediate_manager: self.access_layer.intermediate_manager().clone(),
+            index_options: self.index_options.clone(),
+            inverted_index_config: self.engine_config.inverted_index.clone(),
+            fulltext_index_config: self.engine_config.fulltext_index.clone(),
+            bloom_filter_index_config: self.engine_config.bloom_filter_index.clone(),
+        };
+        let mut indexer = indexer_builder.build(file.file_id()).await;
+
+        loop {
+            let mut batch = match reader.next_batch().await {
+                Ok(Some(batch)) => batch,
+                Ok(None) => break,
+                Err(e) => {
+                    indexer.abort().await;
+                    return Err(e);
+                }
+            };
+            throttle.acquire(batch.memory_size()).await;
+            indexer.update(&mut batch).await;
+        }
+
+        let output = indexer.finish().await;
+        if output.file_size == 0 {
+            return Ok(None);
+        }
+
+        let mut file_meta = file.meta_ref().clone();
+        file_meta.available_indexes = output.build_available_indexes();
+        file_meta.index_file_size = output.file_size;
+        Ok(Some(file_meta))
+    }
+}
+
+/// Returns the metadata to build indexes for a SST with `sst_metadata`.
+///
+/// Indexes are built according to the index options of the region, but only for
+/// columns stored in the SST with the same data type.
+fn index_metadata(
+    sst_metadata: &RegionMetadataRef,
+    region_metadata: &RegionMetadata,
+) -> Result<RegionMetadata> {
+    let mut metadata = RegionMetadata::clone(sst_metadata);
+    for column in &mut metadata.column_metadatas {
+        let Some(region_column) = region_metadata.column_by_id(column.column_id) else {
+            continue;
+        };
+        if !column.is_same_datatype(region_column) {
+            continue;
+        }
+
+        let column_name = column.column_schema.name.clone();
+        let region_schema = &region_column.column_schema;
+        let schema = &mut column.column_schema;
+        schema.set_inverted_index(region_schema.is_inverted_indexed());
+        if let Some(options) = region_schema
+            .fulltext_options()
+            .context(IndexOptionsSnafu {
+                column_name: &column_name,
+            })?
+        {
+            schema
+                .set_fulltext_options(&options)
+                .context(IndexOptionsSnafu {
+                    column_name: &column_name,
+                })?;
+        }
+        let skipping_options =
+            region_schema
+                .skipping_index_options()
+                .context(IndexOptionsSnafu {
+                    column_name: &column_name,
+                })?;
+        let result = match skipping_options {
+            Some(options) => schema.set_skipping_options(&options),
+            None => schema.unset_skipping_options(),
+        };
+        result.context(IndexOptionsSnafu { column_name })?;
+    }
+
+    RegionMetadataBuilder::from_existing(metadata)
+        .build()
+        .context(InvalidMetadataSnafu)
+}
+
+/// Returns true if the `new` metadata enables any index for columns in the `old` metadata.
+pub(crate) fn index_enabled(old: &RegionMetadata, new: &RegionMetadata) -> bool {
+    new.column_metadatas.iter().any(|column| {
+        let Some(old_column) = old.column_by_id(column.column_id) else {
+            // New columns have no data in existing SSTs.
+            return false;
+        };
+        let (schema, old_schema) = (&column.column_schema, &old_column.column_schema);
+        (schema.is_inverted_indexed() && !old_schema.is_inverted_indexed())
+            || (schema.is_fulltext_indexed() && !old_schema.is_fulltext_indexed())
+            || (schema.is_skipping_indexed() && !old_schema.is_skipping_indexed())
+    })
+}
+
+/// Limits bytes to read per second.
+struct Throttle {
+    /// Max bytes per second. 0 means no limit.
+    bytes_per_sec: u64,
+    start: Instant,
+    bytes: u64,
+}
+
+impl Throttle {
+    fn new(limit: ReadableSize) -> Throttle {
+        Throttle {
+            bytes_per_sec: limit.as_bytes(),
+            start: Instant::now(),
+            bytes: 0,
+        }
+    }
+
+    /// Waits until reading `bytes` more bytes doesn't exceed the limit.
+    async fn acquire(&mut self, bytes: usize) {
+        if self.bytes_per_sec == 0 {
+            return;
+        }
+
+        self.bytes += bytes as u64;
+        let expect = Duration::from_secs_f64(self.bytes as f64 / self.bytes_per_sec as f64);
+        let elapsed = self.start.elapsed();
+        if expect > elapsed {
+            tokio::time::sleep(expect - elapsed).await;
+        }
+    }
+}
+
+/// Status of index building of a region.
+#[derive(Default)]
+struct IndexBuildStatus {
+    /// Waiters of the running job.
+    waiters: Vec<OutputTx>,
+    /// Waiters of the next job. The next job runs after the running job finishes as
+    /// index options of the region might change.
+    pending: Option<Vec<OutputTx>>,
+}
+
+/// Schedules index build jobs of a worker.
+pub(crate) struct IndexBuildScheduler {
+    /// Regions building indexes.
+    region_status: HashMap<RegionId, IndexBuildStatus>,
+    /// Background job scheduler.
+    scheduler: SchedulerRef,
+    /// Request sender of the worker that this scheduler belongs to.
+    request_sender: mpsc::Sender<WorkerRequest>,
+    cache_manager: CacheManagerRef,
+    engine_config: Arc<MitoConfig>,
+}
+
+impl IndexBuildScheduler {
+    pub(crate) fn new(
+        scheduler: SchedulerRef,
+        request_sender: mpsc::Sender<WorkerRequest>,
+        cache_manager: CacheManagerRef,
+        engine_config: Arc<MitoConfig>,
+    ) -> Self {
+        Self {
+            region_status: HashMap::new(),
+            scheduler,
+            request_sender,
+            cache_manager,
+            engine_config,
+        }
+    }
+
+    /// Schedules a job to build indexes for SSTs of the `region` that don't have
+    /// index files. The `waiter` gets the number of files indexed.
+    pub(crate) fn schedule_index_build(
+        &mut self,
+        region: &MitoRegionRef,
+        mut waiter: OptionOutputTx,
+    ) {
+        if let Some(status) = self.region_status.get_mut(&region.region_id) {
+            // A job is running, the new job runs after it.
+            let pending = status.pending.get_or_insert_with(Vec::new);
+            pending.extend(waiter.take_inner());
+            return;
+        }
+
+        let waiters = waiter.take_inner().into_iter().collect();
+        self.schedule_job(region, waiters);
+    }
+
+    /// Notifies the scheduler that the job of the region has finished.
+    pub(crate) fn on_index_build_finished(&mut self, region: &MitoRegionRef, num_files: usize) {
+        let Some(status) = self.region_status.remove(&region.region_id) else {
+            return;
+        };
+        for waiter in status.waiters {
+            waiter.send(Ok(num_files));
+        }
+
+        if let Some(pending) = status.pending {
+            self.schedule_job(region, pending);
+        }
+    }
+
+    /// Notifies the scheduler that the job of the region has failed.
+    pub(crate) fn on_index_build_failed(&mut self, region_id: RegionId, err: Arc<Error>) {
+        let Some(status) = self.region_status.remove(&region_id) else {
+            return;
+        };
+        for waiter in status
+            .waiters
+            .into_iter()
+            .chain(status.pending.into_iter().flatten())
+        {
+            waiter.send(Err(err.clone()).context(BuildIndexSnafu { region_id }));
+        }
+    }
+
+    /// Notifies the scheduler that the region is removed from the worker.
+    pub(crate) fn on_region_removed(&mut self, region_id: RegionId) {
+        self.on_index_build_failed(
+            region_id,
+            Arc::new(RegionNotFoundSnafu { region_id }.build()),
+        );
+    }
+
+    fn schedule_job(&mut self, region: &MitoRegionRef, waiters: Vec<OutputTx>) {
+        let version = region.version();
+        let files: Vec<_> = version
+            .ssts
+            .levels()
+            .iter()
+            .flat_map(|level| level.files())
+            .filter(|file| !file.meta_ref().exists_index() && !file.compacting())
+            .cloned()
+            .collect();
+        if files.is_empty() {
+            for waiter in waiters {
+                waiter.send(Ok(0));
+            }
+            return;
+        }
+
+        info!(
+            "Schedule index build for {} files of region {}",
+            files.len(),
+            region.region_id
+        );
+        let task = IndexBuildTask {
+            region_id: region.region_id,
+            files,
+            metadata: version.metadata.clone(),
+            index_options: version.options.index_options.clone(),
+            access_layer: region.access_layer.clone(),
+            manifest_ctx: region.manifest_ctx.clone(),
+            cache_manager: self.cache_manager.clone(),
+            engine_config: self.engine_config.clone(),
+            request_sender: self.request_sender.clone(),
+        };
+        if let Err(e) = self.scheduler.schedule(task.into_index_build_job()) {
+            error!(e; "Failed to schedule index build job for region {}", region.region_id);
+            let err = Arc::new(e);
+            for waiter in waiters {
+                waiter.send(Err(err.clone()).context(BuildIndexSnafu {
+                    region_id: region.region_id,
+                }));
+            }
+            return;
+        }
+
+        self.region_status.insert(
+            region.region_id,
+            IndexBuildStatus {
+                waiters,
+                pending: None,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::SemanticType;
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::ColumnSchema;
+    use store_api::metadata::ColumnMetadata;
+
+    use super::*;
+
+    fn new_metadata(tag_inverted: bool, field_type: ConcreteDataType) -> RegionMetadata {
+        let mut builder = RegionMetadataBuilder::new(RegionId::new(1, 1));
+        builder
+            .push_column_metadata(ColumnMetadata {
+                column_schema: ColumnSchema::new("tag", ConcreteDataType::string_datatype(), true)
+                    .with_inverted_index(tag_inverted),
+                semantic_type: SemanticType::Tag,
+                column_id: 1,
+            })
+            .push_column_metadata(ColumnMetadata {
+                column_schema: ColumnSchema::new("field", field_type, true),
+                semantic_type: SemanticType::Field,
+                column_id: 2,
+            })
+            .push_column_metadata(ColumnMetadata {
+                column_schema: ColumnSchema::new(
+                    "ts",
+                    ConcreteDataType::timestamp_millisecond_datatype(),
+                    false,
+                ),
+                semantic_type: SemanticType::Timestamp,
+                column_id: 3,
+            })
+            .primary_key(vec![1]);
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_index_metadata() {
+        let sst_metadata = Arc::new(new_metadata(false, ConcreteDataType::string_datatype()));
+        let mut region_metadata = new_metadata(true, ConcreteDataType::int64_datatype());
+        region_metadata.column_metadatas[1]
+            .column_schema
+            .set_inverted_index(true);
+
+        let metadata = index_metadata(&sst_metadata, &region_metadata).unwrap();
+        assert!(metadata
+            .column_by_id(1)
+            .unwrap()
+            .column_schema
+            .is_inverted_indexed());
+        // The field has a different type in the SST.
+        assert!(!metadata
+            .column_by_id(2)
+            .unwrap()
+            .column_schema
+            .is_inverted_indexed());
+        assert_eq!(sst_metadata.schema_version, metadata.schema_version);
+    }
+
+    #[test]
+    fn test_index_enabled() {
+        let old = new_metadata(false, ConcreteDataType::string_datatype());
+        let new = new_metadata(true, ConcreteDataType::string_datatype());
+        assert!(index_enabled(&old, &new));
+        assert!(!index_enabled(&new, &old));
+        assert!(!index_enabled(&new, &new));
+    }
+}
@@ -30,6 +30,7 @@ pub mod config;
 pub mod engine;
 pub mod error;
 pub mod flush;
+mod index_build;
 pub mod manifest;
 pub mod memtable;
 mod metrics;
@@ -51,10 +51,17 @@ pub struct RegionChange {
 pub struct RegionEdit {
     pub files_to_add: Vec<FileMeta>,
     pub files_to_remove: Vec<FileMeta>,
+    /// Files whose metadata changes while their data stays the same, e.g. after
+    /// indexes are built for them. Files no longer in the region are ignored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files_to_update: Vec<FileMeta>,
     #[serde(with = "humantime_serde")]
     pub compaction_time_window: Option<Duration>,
     pub flushed_entry_id: Option<EntryId>,
     pub flushed_sequence: Option<SequenceNumber>,
+    /// Watermarks of the producers when the data is flushed.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub producer_watermarks: HashMap<String, u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -90,6 +97,10 @@ pub struct RegionManifest {
     /// Inferred compaction time window.
     #[serde(with = "humantime_serde")]
     pub compaction_time_window: Option<Duration>,
+    /// The highest sequence number of flushed data written by each producer, see
+    /// [IdempotencyKey](store_api::region_request::IdempotencyKey).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub producer_watermarks: HashMap<String, u64>,
 }
 
 #[derive(Debug, Default)]
@@ -101,6 +112,7 @@ pub struct RegionManifestBuilder {
     manifest_version: ManifestVersion,
     truncated_entry_id: Option<EntryId>,
     compaction_time_window: Option<Duration>,
+    producer_watermarks: HashMap<String, u64>,
 }
 
 impl RegionManifestBuilder {
@@ -115,6 +127,7 @@ impl RegionManifestBuilder {
                 flushed_sequence: s.flushed_sequence,
                 truncated_entry_id: s.truncated_entry_id,
                 compaction_time_window: s.compaction_time_window,
+                producer_watermarks: s.producer_watermarks,
             }
         } else {
             Default::default()
@@ -134,6 +147,11 @@ impl RegionManifestBuilder {
         for file in edit.files_to_remove {
             self.files.remove(&file.file_id);
         }
+        for file in edit.files_to_update {
+            if let Some(meta) = self.files.get_mut(&file.file_id) {
+                *meta = file;
+            }
+        }
         if let Some(flushed_entry_id) = edit.flushed_entry_id {
             self.flushed_entry_id = self.flushed_entry_id.max(flushed_entry_id);
         }
@@ -143,6 +161,10 @@ impl RegionManifestBuilder {
         if let Some(window) = edit.compaction_time_window {
             self.compaction_time_window = Some(window);
         }
+        for (producer_id, sequence) in edit.producer_watermarks {
+            let watermark = self.producer_watermarks.entry(producer_id).or_default();
+            *watermark = (*watermark).max(sequence);
+        }
     }
 
     pub fn apply_truncate(&mut self, manifest_version: ManifestVersion, truncate: RegionTruncate) {
@@ -168,6 +190,7 @@ impl RegionManifestBuilder {
             manifest_version: self.manifest_version,
             truncated_entry_id: self.truncated_entry_id,
             compaction_time_window: self.compaction_time_window,
+            producer_watermarks: self.producer_watermarks,
         })
     }
 }
@@ -20,6 +20,7 @@ use common_telemetry::{error, info};
 use store_api::manifest::{ManifestVersion, MIN_VERSION};
 use store_api::storage::RegionId;
 
+use crate::error::Result;
 use crate::manifest::action::{RegionCheckpoint, RegionManifest};
 use crate::manifest::manager::RegionManifestOptions;
 use crate::manifest::storage::ManifestObjectStore;
@@ -46,40 +47,34 @@ impl Inner {
             x.store(false, Ordering::Relaxed);
         });
 
+        if let Err(e) = self.save_checkpoint(checkpoint).await {
+            error!(e; "Failed to do checkpoint for region {}", self.region_id());
+        }
+    }
+
+    /// Saves the checkpoint and deletes the actions compacted by the checkpoint.
+    async fn save_checkpoint(&self, checkpoint: RegionCheckpoint) -> Result<()> {
         let _t = MANIFEST_OP_ELAPSED
             .with_label_values(&["checkpoint"])
             .start_timer();
 
         let region_id = self.region_id();
         let version = checkpoint.last_version();
-        let checkpoint = match checkpoint.encode() {
-            Ok(checkpoint) => checkpoint,
-            Err(e) => {
-                error!(e; "Failed to encode checkpoint {:?}", checkpoint);
-                return;
-            }
-        };
-        if let Err(e) = self
-            .manifest_store
+        let checkpoint = checkpoint.encode()?;
+        self.manifest_store
             .save_checkpoint(version, &checkpoint)
-            .await
-        {
-            error!(e; "Failed to save checkpoint for region {}", region_id);
-            return;
-        }
-
-        if let Err(e) = self.manifest_store.delete_until(version, true).await {
-            error!(e; "Failed to delete manifest actions until version {} for region {}", version, region_id);
-            return;
-        }
+            .await?;
+        self.manifest_store.delete_until(version, true).await?;
 
         self.last_checkpoint_version
-            .store(version, Ordering::Relaxed);
+            .fetch_max(version, Ordering::Relaxed);
 
         info!(
             "Checkpoint for region {} success, version: {}",
             region_id, version
         );
+
+        Ok(())
     }
 
     fn region_id(&self) -> RegionId {
@@ -161,6 +156,38 @@ impl Checkpointer {
         self.do_checkpoint(checkpoint);
     }
 
+    /// Saves a checkpoint of the manifest in place and returns the version of
+    /// the checkpoint. It doesn't save a new one if the last checkpoint is
+    /// already at the version of the manifest.
+    pub(crate) async fn checkpoint(&self, manifest: &RegionManifest) -> Result<ManifestVersion> {
+        let last_checkpoint_version = self.last_checkpoint_version();
+        let end_version = manifest.manifest_version;
+        if last_checkpoint_version >= end_version {
+            return Ok(last_checkpoint_version);
+        }
+
+        let start_version = if last_checkpoint_version == 0 {
+            MIN_VERSION
+        } else {
+            last_checkpoint_version + 1
+        };
+        info!(
+            "Start doing manual checkpoint for region {}, compacted version: [{}, {}]",
+            self.inner.region_id(),
+            start_version,
+            end_version,
+        );
+
+        let checkpoint = RegionCheckpoint {
+            last_version: end_version,
+            compacted_actions: (end_version - start_version + 1) as usize,
+            checkpoint: Some(manifest.clone()),
+        };
+        self.inner.save_checkpoint(checkpoint).await?;
+
+        Ok(end_version)
+    }
+
     fn do_checkpoint(&self, checkpoint: RegionCheckpoint) {
         self.inner.set_doing_checkpoint();
 
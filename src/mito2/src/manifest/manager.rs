@@ -483,6 +483,18 @@ impl RegionManifestManager {
         self.manifest.clone()
     }
 
+    /// Saves a checkpoint of the current manifest and returns its version.
+    pub async fn checkpoint(&self) -> Result<ManifestVersion> {
+        ensure!(
+            !self.stopped,
+            RegionStoppedSnafu {
+                region_id: self.manifest.metadata.region_id,
+            }
+        );
+
+        self.checkpointer.checkpoint(self.manifest.as_ref()).await
+    }
+
     /// Returns total manifest size.
     pub fn manifest_usage(&self) -> u64 {
         self.store.total_manifest_size()
@@ -576,6 +588,7 @@ impl RegionManifestManager {
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
     use std::time::Duration;
 
     use api::v1::SemanticType;
@@ -733,9 +746,11 @@ mod test {
                     RegionEdit {
                         files_to_add: vec![],
                         files_to_remove: vec![],
+                        files_to_update: vec![],
                         compaction_time_window: None,
                         flushed_entry_id: None,
                         flushed_sequence: None,
+                        producer_watermarks: HashMap::new(),
                     },
                 )]))
                 .await
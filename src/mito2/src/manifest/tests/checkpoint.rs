@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::assert_matches::assert_matches;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -71,9 +72,11 @@ fn nop_action() -> RegionMetaActionList {
     RegionMetaActionList::new(vec![RegionMetaAction::Edit(RegionEdit {
         files_to_add: vec![],
         files_to_remove: vec![],
+        files_to_update: vec![],
         compaction_time_window: None,
         flushed_entry_id: None,
         flushed_sequence: None,
+        producer_watermarks: HashMap::new(),
     })])
 }
 
@@ -242,9 +245,11 @@ async fn checkpoint_with_different_compression_types() {
         let action = RegionMetaActionList::new(vec![RegionMetaAction::Edit(RegionEdit {
             files_to_add: vec![file_meta],
             files_to_remove: vec![],
+            files_to_update: vec![],
             compaction_time_window: None,
             flushed_entry_id: None,
             flushed_sequence: None,
+            producer_watermarks: HashMap::new(),
         })]);
         actions.push(action);
     }
@@ -301,9 +306,11 @@ fn generate_action_lists(num: usize) -> (Vec<FileId>, Vec<RegionMetaActionList>)
         let action = RegionMetaActionList::new(vec![RegionMetaAction::Edit(RegionEdit {
             files_to_add: vec![file_meta],
             files_to_remove: vec![],
+            files_to_update: vec![],
             compaction_time_window: None,
             flushed_entry_id: None,
             flushed_sequence: None,
+            producer_watermarks: HashMap::new(),
         })]);
         actions.push(action);
     }
@@ -17,6 +17,7 @@
 use std::sync::Arc;
 use std::time::Duration;
 
+use common_time::Timestamp;
 use smallvec::SmallVec;
 use store_api::metadata::RegionMetadataRef;
 
@@ -63,6 +64,14 @@ impl MemtableVersion {
         mems
     }
 
+    /// Returns the time range of rows in memtables, or `None` if all memtables are empty.
+    pub(crate) fn time_range(&self) -> Option<(Timestamp, Timestamp)> {
+        self.list_memtables()
+            .iter()
+            .filter_map(|mem| mem.stats().time_range())
+            .reduce(|(min, max), (start, end)| (min.min(start), max.max(end)))
+    }
+
     /// Returns a new [MemtableVersion] which switches the old mutable memtable to immutable
     /// memtable.
     ///
@@ -16,6 +16,7 @@
 
 pub mod compat;
 pub mod dedup;
+pub(crate) mod label_values;
 pub mod last_row;
 pub mod merge;
 pub mod projection;
@@ -24,6 +25,8 @@ pub(crate) mod range;
 pub(crate) mod scan_region;
 pub(crate) mod scan_util;
 pub(crate) mod seq_scan;
+pub(crate) mod series_cardinality;
+pub(crate) mod sort_by;
 pub(crate) mod unordered_scan;
 
 use std::collections::{HashMap, HashSet};
@@ -36,7 +39,7 @@ use common_time::Timestamp;
 use datafusion_common::arrow::array::UInt8Array;
 use datatypes::arrow;
 use datatypes::arrow::array::{Array, ArrayRef, UInt64Array};
-use datatypes::arrow::compute::SortOptions;
+use datatypes::arrow::compute::{SortColumn, SortOptions};
 use datatypes::arrow::row::{RowConverter, SortField};
 use datatypes::prelude::{ConcreteDataType, DataType, ScalarVector};
 use datatypes::types::TimestampType;
@@ -339,13 +342,14 @@ impl Batch {
 
     /// Filters rows by the given `sequence`. Only preserves rows with sequence less than or equal to `sequence`.
     pub fn filter_by_sequence(&mut self, sequence: Option<SequenceNumber>) -> Result<()> {
-        let seq = match (sequence, self.last_sequence()) {
+        let seqs = self.sequences.as_arrow();
+        // Rows are ordered by timestamp so the last sequence isn't always the max one.
+        let seq = match (sequence, arrow::compute::max(seqs)) {
             (None, _) | (_, None) => return Ok(()),
-            (Some(sequence), Some(last_sequence)) if sequence >= last_sequence => return Ok(()),
+            (Some(sequence), Some(max_sequence)) if sequence >= max_sequence => return Ok(()),
             (Some(sequence), Some(_)) => sequence,
         };
 
-        let seqs = self.sequences.as_arrow();
         let sequence = UInt64Array::new_scalar(seq);
         let predicate = datafusion_common::arrow::compute::kernels::cmp::lt_eq(seqs, &sequence)
             .context(ComputeArrowSnafu)?;
@@ -355,6 +359,24 @@ impl Batch {
         Ok(())
     }
 
+    /// Filters rows by the given `sequence`. Only preserves rows with sequence greater than `sequence`.
+    pub fn filter_newer_than(&mut self, sequence: Option<SequenceNumber>) -> Result<()> {
+        let seqs = self.sequences.as_arrow();
+        let seq = match (sequence, arrow::compute::min(seqs)) {
+            (None, _) | (_, None) => return Ok(()),
+            (Some(sequence), Some(min_sequence)) if sequence < min_sequence => return Ok(()),
+            (Some(sequence), Some(_)) => sequence,
+        };
+
+        let sequence = UInt64Array::new_scalar(seq);
+        let predicate = datafusion_common::arrow::compute::kernels::cmp::gt(seqs, &sequence)
+            .context(ComputeArrowSnafu)?;
+        let predicate = BooleanVector::from(predicate);
+        self.filter(&predicate)?;
+
+        Ok(())
+    }
+
     /// Sorts rows in the batch. If `dedup` is true, it also removes
     /// duplicated rows according to primary keys.
     ///
@@ -408,6 +430,30 @@ impl Batch {
         self.take_in_place(&indices)
     }
 
+    /// Sorts rows in the batch by the field columns of `column_ids` and then by
+    /// the timestamp. Nulls are ordered first. Fields not in the batch are ignored.
+    pub(crate) fn sort_by_fields(&mut self, column_ids: &[ColumnId]) -> Result<()> {
+        let mut columns = column_ids
+            .iter()
+            .filter_map(|column_id| {
+                self.fields
+                    .iter()
+                    .find(|column| column.column_id == *column_id)
+            })
+            .map(|column| SortColumn {
+                values: column.data.to_arrow_array(),
+                options: None,
+            })
+            .collect::<Vec<_>>();
+        columns.push(SortColumn {
+            values: self.timestamps.to_arrow_array(),
+            options: None,
+        });
+        let indices =
+            arrow::compute::lexsort_to_indices(&columns, None).context(ComputeArrowSnafu)?;
+        self.take_in_place(&UInt32Vector::from(indices))
+    }
+
     /// Returns the estimated memory size of the batch.
     pub fn memory_size(&self) -> usize {
         let mut size = std::mem::size_of::<Self>();
@@ -1280,6 +1326,17 @@ mod tests {
         batch.filter_by_sequence(None).unwrap();
         assert_eq!(expect, batch);
 
+        // The max sequence isn't the last one.
+        let mut batch = new_batch(
+            &[1, 2, 3],
+            &[14, 12, 11],
+            &[OpType::Put, OpType::Put, OpType::Put],
+            &[21, 22, 23],
+        );
+        batch.filter_by_sequence(Some(12)).unwrap();
+        let expect = new_batch(&[2, 3], &[12, 11], &[OpType::Put, OpType::Put], &[22, 23]);
+        assert_eq!(expect, batch);
+
         // Filter a empty batch
         let mut batch = new_batch(&[], &[], &[], &[]);
         batch.filter_by_sequence(Some(10)).unwrap();
@@ -1291,6 +1348,45 @@ mod tests {
         assert!(batch.is_empty());
     }
 
+    #[test]
+    fn test_filter_newer_than() {
+        // The min sequence isn't the first one.
+        let mut batch = new_batch(
+            &[1, 2, 3, 4],
+            &[12, 14, 11, 13],
+            &[OpType::Put, OpType::Put, OpType::Put, OpType::Delete],
+            &[21, 22, 23, 24],
+        );
+        batch.filter_newer_than(Some(12)).unwrap();
+        let expect = new_batch(
+            &[2, 4],
+            &[14, 13],
+            &[OpType::Put, OpType::Delete],
+            &[22, 24],
+        );
+        assert_eq!(expect, batch);
+
+        // Filters to empty.
+        let mut batch = new_batch(&[1, 2], &[11, 12], &[OpType::Put, OpType::Put], &[21, 22]);
+        batch.filter_newer_than(Some(12)).unwrap();
+        assert!(batch.is_empty());
+
+        // All rows are newer.
+        let mut batch = new_batch(&[1, 2], &[11, 12], &[OpType::Put, OpType::Put], &[21, 22]);
+        let expect = batch.clone();
+        batch.filter_newer_than(Some(10)).unwrap();
+        assert_eq!(expect, batch);
+
+        // None filter.
+        batch.filter_newer_than(None).unwrap();
+        assert_eq!(expect, batch);
+
+        // Filter a empty batch
+        let mut batch = new_batch(&[], &[], &[], &[]);
+        batch.filter_newer_than(Some(10)).unwrap();
+        assert!(batch.is_empty());
+    }
+
     #[test]
     fn test_filter() {
         // Filters put only.
@@ -0,0 +1,270 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Label values of a region served from the term dictionaries of the inverted index.
+//!
+//! The term dictionary (the FST) of the inverted index of a SST file contains all
+//! distinct values of an indexed tag column in the file. The [LabelValuesDictionary]
+//! unions the terms of the files whose time ranges overlap the requested range with
+//! the primary keys in the memtables, so it doesn't have to scan data pages. The
+//! result is a superset of the values within the requested range, since it doesn't
+//! filter rows by time within a file and doesn't take deletions into account.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use api::v1::SemanticType;
+use common_base::range_read::RangeReader;
+use common_time::Timestamp;
+use datatypes::data_type::ConcreteDataType;
+use datatypes::value::Value;
+use fst::Streamer;
+use index::inverted_index::format::reader::{InvertedIndexBlobReader, InvertedIndexReader};
+use moka::sync::Cache;
+use puffin::puffin_manager::{PuffinManager, PuffinReader};
+use snafu::ResultExt;
+use store_api::metadata::RegionMetadata;
+use store_api::region_engine::LabelValuesRequest;
+use store_api::storage::ColumnId;
+
+use crate::access_layer::{AccessLayerRef, RegionFilePathFactory};
+use crate::cache::index::inverted_index::CachedInvertedIndexBlobReader;
+use crate::cache::CacheManagerRef;
+use crate::error::{
+    MetadataSnafu, PuffinBuildReaderSnafu, PuffinReadBlobSnafu, ReadIndexTermsSnafu, Result,
+};
+use crate::memtable::MemtableRef;
+use crate::region::version::VersionRef;
+use crate::row_converter::{build_primary_key_codec, CompositeValues};
+use crate::sst::file::{FileHandle, FileId};
+use crate::sst::index::inverted_index::INDEX_BLOB_TYPE;
+
+/// Default capacity in bytes of the cached terms.
+pub(crate) const DEFAULT_LABEL_VALUES_CACHE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Terms of an indexed column in a SST file.
+type FileTerms = Arc<Vec<String>>;
+
+/// Serves label values from the term dictionaries of SST files and the memtables.
+///
+/// SST files are immutable so the terms of a file are cached until they are evicted.
+/// New files are loaded on demand.
+pub(crate) struct LabelValuesDictionary {
+    cache: Cache<(FileId, ColumnId), FileTerms>,
+}
+
+impl LabelValuesDictionary {
+    /// Creates a dictionary that caches at most `capacity` bytes of terms.
+    pub(crate) fn new(capacity: u64) -> Self {
+        let cache = Cache::builder()
+            .max_capacity(capacity)
+            .weigher(|(file_id, _), terms: &FileTerms| {
+                (file_id.as_bytes().len() + terms.iter().map(|t| t.len()).sum::<usize>()) as u32
+            })
+            .build();
+        Self { cache }
+    }
+
+    /// Returns the distinct values of the tag column in `request`, or `None` if
+    /// any of the files in the time range doesn't have an inverted index of the
+    /// column.
+    pub(crate) async fn label_values(
+        &self,
+        version: &VersionRef,
+        access_layer: &AccessLayerRef,
+        cache_manager: &CacheManagerRef,
+        request: &LabelValuesRequest,
+    ) -> Result<Option<Vec<String>>> {
+        let Some(column_id) = indexed_string_tag(version, &request.column_name) else {
+            return Ok(None);
+        };
+
+        let mut values = BTreeSet::new();
+        let files = version
+            .ssts
+            .levels()
+            .iter()
+            .flat_map(|level| level.files())
+            .filter(|file| overlaps(file.time_range(), request));
+        for file in files {
+            if !file.meta_ref().inverted_index_available() {
+                return Ok(None);
+            }
+            let Some(terms) = self
+                .file_terms(file, column_id, access_layer, cache_manager)
+                .await?
+            else {
+                return Ok(None);
+            };
+            values.extend(terms.iter().cloned());
+        }
+
+        let metadata = &version.metadata;
+        for memtable in version.memtables.list_memtables() {
+            let in_range = memtable
+                .stats()
+                .time_range()
+                .is_some_and(|range| overlaps(range, request));
+            if in_range {
+                collect_memtable_values(metadata, &memtable, column_id, &mut values)?;
+            }
+        }
+
+        Ok(Some(values.into_iter().collect()))
+    }
+
+    /// Returns the terms of the column in the index of `file`, or `None` if the
+    /// column isn't indexed in the file.
+    async fn file_terms(
+        &self,
+        file: &FileHandle,
+        column_id: ColumnId,
+        access_layer: &AccessLayerRef,
+        cache_manager: &CacheManagerRef,
+    ) -> Result<Option<FileTerms>> {
+        let key = (file.file_id(), column_id);
+        if let Some(terms) = self.cache.get(&key) {
+            return Ok(Some(terms));
+        }
+
+        let puffin_manager = access_layer
+            .puffin_manager_factory()
+            .build(
+                access_layer.object_store().clone(),
+                RegionFilePathFactory::new(access_layer.region_dir().to_string()),
+            )
+            .with_puffin_metadata_cache(cache_manager.puffin_metadata_cache().cloned());
+        let blob = puffin_manager
+            .reader(&file.file_id())
+            .await
+            .context(PuffinBuildReaderSnafu)?
+            .with_file_size_hint(Some(file.meta_ref().index_file_size()))
+            .blob(INDEX_BLOB_TYPE)
+            .await
+            .context(PuffinReadBlobSnafu)?
+            .reader()
+            .await
+            .context(PuffinBuildReaderSnafu)?;
+
+        let terms = if let Some(index_cache) = cache_manager.inverted_index_cache() {
+            let blob_size = blob.metadata().await.context(MetadataSnafu)?.content_length;
+            let reader = CachedInvertedIndexBlobReader::new(
+                file.file_id(),
+                blob_size,
+                InvertedIndexBlobReader::new(blob),
+                index_cache.clone(),
+            );
+            read_terms(&reader, column_id).await?
+        } else {
+            read_terms(&InvertedIndexBlobReader::new(blob), column_id).await?
+        };
+
+        let Some(terms) = terms else {
+            return Ok(None);
+        };
+        let terms = Arc::new(terms);
+        self.cache.insert(key, terms.clone());
+        Ok(Some(terms))
+    }
+}
+
+/// Returns the id of the column if it's a string tag with inverted index.
+fn indexed_string_tag(version: &VersionRef, column_name: &str) -> Option<ColumnId> {
+    let metadata = &version.metadata;
+    let column = metadata.column_by_name(column_name)?;
+    let column_id = column.column_id;
+    // Only strings are stored as is in the index, see `IndexValueCodec`.
+    if column.semantic_type != SemanticType::Tag
+        || !matches!(column.column_schema.data_type, ConcreteDataType::String(_))
+    {
+        return None;
+    }
+
+    metadata
+        .inverted_indexed_column_ids(
+            version
+                .options
+                .index_options
+                .inverted_index
+                .ignore_column_ids
+                .iter(),
+        )
+        .contains(&column_id)
+        .then_some(column_id)
+}
+
+/// Returns true if the inclusive time `range` overlaps the range of the request.
+fn overlaps((start, end): (Timestamp, Timestamp), request: &LabelValuesRequest) -> bool {
+    start <= request.end && request.start <= end
+}
+
+/// Reads the terms of the column from the index.
+async fn read_terms(
+    reader: &impl InvertedIndexReader,
+    column_id: ColumnId,
+) -> Result<Option<Vec<String>>> {
+    let metas = reader.metadata().await.context(ReadIndexTermsSnafu)?;
+    let Some(meta) = metas.metas.get(&column_id.to_string()) else {
+        return Ok(None);
+    };
+
+    let fst = reader
+        .fst(
+            meta.base_offset + meta.relative_fst_offset as u64,
+            meta.fst_size,
+        )
+        .await
+        .context(ReadIndexTermsSnafu)?;
+    let mut terms = Vec::with_capacity(fst.len());
+    let mut stream = fst.stream();
+    while let Some((term, _)) = stream.next() {
+        terms.push(String::from_utf8_lossy(term).into_owned());
+    }
+    Ok(Some(terms))
+}
+
+/// Collects the values of the column from the primary keys in the memtable.
+fn collect_memtable_values(
+    metadata: &RegionMetadata,
+    memtable: &MemtableRef,
+    column_id: ColumnId,
+    values: &mut BTreeSet<String>,
+) -> Result<()> {
+    let codec = build_primary_key_codec(metadata);
+    let mut last_key = None;
+    // Only reads the primary keys.
+    for batch in memtable.iter(Some(&[]), None, None)? {
+        let batch = batch?;
+        if last_key.as_deref() == Some(batch.primary_key()) {
+            continue;
+        }
+        last_key = Some(batch.primary_key().to_vec());
+
+        let pk_values = match batch.pk_values() {
+            Some(pk_values) => pk_values.clone(),
+            None => codec.decode(batch.primary_key())?,
+        };
+        let value = match &pk_values {
+            CompositeValues::Dense(dense) => dense
+                .iter()
+                .find(|(id, _)| *id == column_id)
+                .map(|(_, value)| value),
+            CompositeValues::Sparse(sparse) => sparse.get(&column_id),
+        };
+        if let Some(Value::String(value)) = value {
+            values.insert(value.as_utf8().to_string());
+        }
+    }
+    Ok(())
+}
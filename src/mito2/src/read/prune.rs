@@ -108,8 +108,15 @@ impl PruneReader {
         Ok(None)
     }
 
-    /// Prunes batches by the pushed down predicate.
-    fn prune(&mut self, batch: Batch) -> Result<Option<Batch>> {
+    /// Prunes batches by the sequence to read and the pushed down predicate.
+    fn prune(&mut self, mut batch: Batch) -> Result<Option<Batch>> {
+        if self.context.sequence().is_some() {
+            batch.filter_by_sequence(self.context.sequence())?;
+            if batch.is_empty() {
+                return Ok(None);
+            }
+        }
+
         // fast path
         if self.context.filters().is_empty() {
             return Ok(Some(batch));
@@ -31,7 +31,9 @@ use datafusion_expr::Expr;
 use smallvec::SmallVec;
 use store_api::metadata::RegionMetadata;
 use store_api::region_engine::{PartitionRange, RegionScannerRef};
-use store_api::storage::{ScanRequest, TimeSeriesDistribution, TimeSeriesRowSelector};
+use store_api::storage::{
+    ScanRequest, ScanSample, SequenceNumber, TimeSeriesDistribution, TimeSeriesRowSelector,
+};
 use table::predicate::{build_time_range_predicate, Predicate};
 use tokio::sync::{mpsc, Semaphore};
 use tokio_stream::wrappers::ReceiverStream;
@@ -303,17 +305,32 @@ impl ScanRegion {
     fn scan_input(mut self, filter_deleted: bool) -> Result<ScanInput> {
         let time_range = self.build_time_range_predicate();
 
+        // Sources that only have rows not newer than the hinted sequence don't affect
+        // the output if rows are not merged with older rows of the same key.
+        let newer_than = self.request.scan_hints.newer_than;
+        let prune_older = newer_than.is_some()
+            && (self.version.options.append_mode || self.merge_mode() == MergeMode::LastRow);
+
         let ssts = &self.version.ssts;
         let mut files = Vec::new();
         for level in ssts.levels() {
             for file in level.files.values() {
                 // Finds SST files in range.
-                if file_in_range(file, &time_range) {
-                    files.push(file.clone());
+                if !file_in_range(file, &time_range) {
+                    continue;
+                }
+                // The max sequence of files written by compaction is unknown.
+                if prune_older
+                    && file
+                        .meta_ref()
+                        .sequence
+                        .is_some_and(|max_sequence| Some(max_sequence.get()) <= newer_than)
+                {
+                    continue;
                 }
-                // There is no need to check and prune for file's sequence here as the sequence number is usually very new,
-                // unless the timing is too good, or the sequence number wouldn't be in file.
-                // and the batch will be filtered out by tree reader anyway.
+                // Files can't be pruned by the sequence as we only know their max sequences.
+                // Rows newer than the sequence are filtered out while reading the files.
+                files.push(file.clone());
             }
         }
 
@@ -326,6 +343,9 @@ impl ScanRegion {
                     return false;
                 }
                 let stats = mem.stats();
+                if prune_older && Some(stats.max_sequence()) <= newer_than {
+                    return false;
+                }
                 // Safety: the memtable is not empty.
                 let (start, end) = stats.time_range().unwrap();
 
@@ -383,9 +403,14 @@ impl ScanRegion {
             .with_start_time(self.start_time)
             .with_append_mode(self.version.options.append_mode)
             .with_filter_deleted(filter_deleted)
-            .with_merge_mode(self.version.options.merge_mode())
+            .with_merge_mode(self.merge_mode())
             .with_series_row_selector(self.request.series_row_selector)
-            .with_distribution(self.request.distribution);
+            .with_distribution(self.request.distribution)
+            // Memtables are always read in full. They are usually small compared to files
+            // but it biases the sample towards recent data.
+            .with_sample(self.request.scan_hints.sample)
+            .with_sequence(self.request.sequence)
+            .with_newer_than(newer_than);
         Ok(input)
     }
 
@@ -401,9 +426,19 @@ impl ScanRegion {
         build_time_range_predicate(&time_index.column_schema.name, unit, &self.request.filters)
     }
 
+    /// Returns the merge mode hinted by the request, or the merge mode of the region.
+    fn merge_mode(&self) -> MergeMode {
+        self.request
+            .scan_hints
+            .merge_mode
+            .as_deref()
+            .and_then(|mode| mode.parse().ok())
+            .unwrap_or_else(|| self.version.options.merge_mode())
+    }
+
     /// Remove field filters if the merge mode is [MergeMode::LastNonNull].
     fn maybe_remove_field_filters(&mut self) {
-        if self.version.options.merge_mode() != MergeMode::LastNonNull {
+        if self.merge_mode() != MergeMode::LastNonNull {
             return;
         }
 
@@ -567,6 +602,12 @@ pub(crate) struct ScanInput {
     pub(crate) series_row_selector: Option<TimeSeriesRowSelector>,
     /// Hint for the required distribution of the scanner.
     pub(crate) distribution: Option<TimeSeriesDistribution>,
+    /// Only reads a sample of the row groups in files.
+    pub(crate) sample: Option<ScanSample>,
+    /// Only reads rows whose sequences are less than or equal to it, if set.
+    pub(crate) sequence: Option<SequenceNumber>,
+    /// Only returns rows whose sequences are greater than it after merging rows, if set.
+    pub(crate) newer_than: Option<SequenceNumber>,
 }
 
 impl ScanInput {
@@ -592,6 +633,9 @@ impl ScanInput {
             merge_mode: MergeMode::default(),
             series_row_selector: None,
             distribution: None,
+            sample: None,
+            sequence: None,
+            newer_than: None,
         }
     }
 
@@ -714,6 +758,27 @@ impl ScanInput {
         self
     }
 
+    /// Sets the sample to read.
+    #[must_use]
+    pub(crate) fn with_sample(mut self, sample: Option<ScanSample>) -> Self {
+        self.sample = sample;
+        self
+    }
+
+    /// Sets the sequence to read.
+    #[must_use]
+    pub(crate) fn with_sequence(mut self, sequence: Option<SequenceNumber>) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Sets the sequence that returned rows must be newer than.
+    #[must_use]
+    pub(crate) fn with_newer_than(mut self, newer_than: Option<SequenceNumber>) -> Self {
+        self.newer_than = newer_than;
+        self
+    }
+
     /// Sets the time series row selector.
     #[must_use]
     pub(crate) fn with_series_row_selector(
@@ -774,6 +839,7 @@ impl ScanInput {
             .bloom_filter_index_applier(self.bloom_filter_index_applier.clone())
             .fulltext_index_applier(self.fulltext_index_applier.clone())
             .expected_metadata(Some(self.mapper.metadata().clone()))
+            .sample(self.sample)
             .build_reader_input(reader_metrics)
             .await;
         let (mut file_range_ctx, row_groups) = match res {
@@ -799,6 +865,16 @@ impl ScanInput {
             )?;
             file_range_ctx.set_compat_batch(Some(compat));
         }
+        // Files flushed after the sequence is taken may contain newer rows. The max
+        // sequence of files written by compaction is unknown.
+        let has_newer_rows = match (self.sequence, file.meta_ref().sequence) {
+            (Some(sequence), Some(max_sequence)) => max_sequence.get() > sequence,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if has_newer_rows {
+            file_range_ctx.set_sequence(self.sequence);
+        }
         Ok(FileRangeBuilder::new(Arc::new(file_range_ctx), row_groups))
     }
 
@@ -942,6 +1018,9 @@ impl StreamContext {
         if let Some(distribution) = &self.input.distribution {
             write!(f, ", distribution={}", distribution)?;
         }
+        if let Some(sample) = &self.input.sample {
+            write!(f, ", sample={}", sample.fraction)?;
+        }
 
         if verbose {
             self.format_verbose_content(f)?;
@@ -20,6 +20,7 @@ use std::time::{Duration, Instant};
 
 use async_stream::try_stream;
 use common_telemetry::debug;
+use common_telemetry::tracing::{field, info_span, Span};
 use datafusion::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricBuilder, Time};
 use futures::Stream;
 use prometheus::IntGauge;
@@ -300,6 +301,8 @@ struct PartitionMetricsInner {
     /// Verbose scan metrics that only log to debug logs by default.
     metrics: Mutex<ScanMetricsSet>,
     in_progress_scan: IntGauge,
+    /// The span of the scan, which ends when the partition finishes.
+    span: Span,
 
     // Normal metrics that always report to the [ExecutionPlanMetricsSet]:
     /// Duration to build file ranges.
@@ -327,6 +330,11 @@ impl Drop for PartitionMetricsInner {
         let metrics = self.metrics.lock().unwrap();
         metrics.observe_metrics();
         self.in_progress_scan.dec();
+        self.span.record("rows", metrics.num_rows);
+        self.span.record("batches", metrics.num_batches);
+        self.span.record("mem_ranges", metrics.num_mem_ranges);
+        self.span.record("file_ranges", metrics.num_file_ranges);
+        self.span.record("sst_rows", metrics.num_sst_rows);
 
         debug!(
             "{} finished, region_id: {}, partition: {}, metrics: {:?}",
@@ -382,6 +390,17 @@ impl PartitionMetrics {
             query_start,
             metrics: Mutex::new(metrics),
             in_progress_scan,
+            span: info_span!(
+                "PartitionScan",
+                region_id = %region_id,
+                partition,
+                scanner = scanner_type,
+                rows = field::Empty,
+                batches = field::Empty,
+                mem_ranges = field::Empty,
+                file_ranges = field::Empty,
+                sst_rows = field::Empty,
+            ),
             build_parts_cost: MetricBuilder::new(metrics_set)
                 .subset_time("build_parts_cost", partition),
             build_reader_cost: MetricBuilder::new(metrics_set)
@@ -19,6 +19,7 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use async_stream::try_stream;
+use async_trait::async_trait;
 use common_error::ext::BoxedError;
 use common_recordbatch::error::ExternalSnafu;
 use common_recordbatch::util::ChainedRecordBatchStream;
@@ -30,7 +31,7 @@ use datatypes::schema::SchemaRef;
 use snafu::ResultExt;
 use store_api::metadata::RegionMetadataRef;
 use store_api::region_engine::{PartitionRange, PrepareRequest, RegionScanner, ScannerProperties};
-use store_api::storage::{TimeSeriesDistribution, TimeSeriesRowSelector};
+use store_api::storage::{SequenceNumber, TimeSeriesDistribution, TimeSeriesRowSelector};
 use tokio::sync::Semaphore;
 
 use crate::error::{PartitionOutOfRangeSnafu, Result};
@@ -42,7 +43,7 @@ use crate::read::scan_region::{ScanInput, StreamContext};
 use crate::read::scan_util::{
     scan_file_ranges, scan_mem_ranges, PartitionMetrics, PartitionMetricsList,
 };
-use crate::read::{BatchReader, BoxedBatchReader, ScannerMetrics, Source};
+use crate::read::{Batch, BatchReader, BoxedBatchReader, ScannerMetrics, Source};
 use crate::region::options::MergeMode;
 
 /// Scans a region and returns rows in a sorted sequence.
@@ -182,6 +183,12 @@ impl SeqScan {
             Box::new(reader) as _
         };
 
+        let reader = match stream_ctx.input.newer_than {
+            // Filters rows after merging so rows of a key are merged with older rows first.
+            Some(sequence) => Box::new(NewerThanReader { reader, sequence }) as _,
+            None => reader,
+        };
+
         let reader = match &stream_ctx.input.series_row_selector {
             Some(TimeSeriesRowSelector::LastRow) => Box::new(LastRowReader::new(reader)) as _,
             None => reader,
@@ -551,6 +558,25 @@ fn build_sources(
     }
 }
 
+/// Reader to only return rows whose sequences are greater than the `sequence`.
+struct NewerThanReader {
+    reader: BoxedBatchReader,
+    sequence: SequenceNumber,
+}
+
+#[async_trait]
+impl BatchReader for NewerThanReader {
+    async fn next_batch(&mut self) -> Result<Option<Batch>> {
+        while let Some(mut batch) = self.reader.next_batch().await? {
+            batch.filter_newer_than(Some(self.sequence))?;
+            if !batch.is_empty() {
+                return Ok(Some(batch));
+            }
+        }
+        Ok(None)
+    }
+}
+
 #[cfg(test)]
 impl SeqScan {
     /// Returns the input.
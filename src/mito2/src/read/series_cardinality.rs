@@ -0,0 +1,93 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Series cardinality of a region.
+//!
+//! A series is a distinct primary key of the region. The series are counted by
+//! scanning the region with the [TimeSeriesRowSelector::LastRow] selector, which
+//! yields one row of each series, and only reading the time index and the tag
+//! column to group the series by.
+
+use api::v1::SemanticType;
+use common_error::ext::BoxedError;
+use common_recordbatch::SendableRecordBatchStream;
+use datatypes::value::Value;
+use futures::TryStreamExt;
+use snafu::{ensure, OptionExt, ResultExt};
+use store_api::metadata::RegionMetadata;
+use store_api::region_engine::{SeriesCardinality, SeriesCardinalityRequest};
+use store_api::storage::{ScanRequest, TimeSeriesRowSelector};
+
+use crate::error::{ExternalSnafu, InvalidRequestSnafu, Result};
+
+/// Builds the request to scan one row of each series. The label of the request
+/// is the first column of the scan if it's present.
+pub(crate) fn build_scan_request(
+    metadata: &RegionMetadata,
+    request: &SeriesCardinalityRequest,
+) -> Result<ScanRequest> {
+    let mut projection = Vec::with_capacity(2);
+    if let Some(label) = &request.label {
+        let index = metadata
+            .column_index_by_name(label)
+            .with_context(|| InvalidRequestSnafu {
+                region_id: metadata.region_id,
+                reason: format!("column {label} not found"),
+            })?;
+        ensure!(
+            metadata.column_metadatas[index].semantic_type == SemanticType::Tag,
+            InvalidRequestSnafu {
+                region_id: metadata.region_id,
+                reason: format!("column {label} is not a tag"),
+            }
+        );
+        projection.push(index);
+    }
+    projection.push(metadata.time_index_column_pos());
+
+    Ok(ScanRequest {
+        projection: Some(projection),
+        series_row_selector: Some(TimeSeriesRowSelector::LastRow),
+        ..Default::default()
+    })
+}
+
+/// Counts the series in the `stream` of the request built by [build_scan_request].
+pub(crate) async fn count_series(
+    stream: SendableRecordBatchStream,
+    request: &SeriesCardinalityRequest,
+) -> Result<SeriesCardinality> {
+    let mut stream = stream.map_err(BoxedError::new);
+    let mut cardinality = SeriesCardinality::default();
+    while let Some(batch) = stream.try_next().await.context(ExternalSnafu {
+        context: "count series",
+    })? {
+        cardinality.num_series += batch.num_rows() as u64;
+        if request.label.is_none() {
+            continue;
+        }
+
+        let labels = batch.column(0);
+        for i in 0..labels.len() {
+            let value = match labels.get(i) {
+                Value::Null => continue,
+                Value::String(value) => value.as_utf8().to_string(),
+                value => value.to_string(),
+            };
+            *cardinality.label_values.entry(value).or_default() += 1;
+        }
+    }
+
+    Ok(cardinality)
+}
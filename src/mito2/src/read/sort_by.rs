@@ -0,0 +1,182 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities to order rows of each time series by user specified columns.
+
+use std::mem;
+
+use api::v1::SemanticType;
+use async_trait::async_trait;
+use snafu::{ensure, OptionExt};
+use store_api::metadata::RegionMetadata;
+use store_api::storage::ColumnId;
+
+use crate::error::{InvalidRegionOptionsSnafu, Result};
+use crate::read::{Batch, BatchReader, BoxedBatchReader};
+
+/// Resolves the `compaction.sort_by` columns to the ids of the field columns to
+/// sort by. The time index must be the last column and it's always the last key.
+pub(crate) fn resolve_sort_by(
+    metadata: &RegionMetadata,
+    columns: &[String],
+) -> Result<Vec<ColumnId>> {
+    let Some((last, fields)) = columns.split_last() else {
+        return Ok(Vec::new());
+    };
+    ensure!(
+        *last == metadata.time_index_column().column_schema.name,
+        InvalidRegionOptionsSnafu {
+            reason: format!(
+                "the last column of compaction.sort_by must be the time index, found {last}"
+            ),
+        }
+    );
+
+    fields
+        .iter()
+        .map(|name| {
+            let column =
+                metadata
+                    .column_by_name(name)
+                    .with_context(|| InvalidRegionOptionsSnafu {
+                        reason: format!("column {name} in compaction.sort_by not found"),
+                    })?;
+            // Rows are ordered by the primary key first, so tags can't be sort keys.
+            ensure!(
+                column.semantic_type == SemanticType::Field,
+                InvalidRegionOptionsSnafu {
+                    reason: format!("column {name} in compaction.sort_by is not a field"),
+                }
+            );
+            Ok(column.column_id)
+        })
+        .collect()
+}
+
+/// Reader to order rows of each time series by the given field columns and then
+/// by the timestamp. It keeps the order of time series.
+///
+/// It buffers all rows of a time series so it's only used by compaction. As rows
+/// are no longer ordered by the timestamp, the input must not need deduplication.
+pub(crate) struct SortByReader {
+    /// Inner reader.
+    reader: BoxedBatchReader,
+    /// Ids of field columns to sort by.
+    column_ids: Vec<ColumnId>,
+    /// Batches of the current time series.
+    buffer: Vec<Batch>,
+}
+
+impl SortByReader {
+    /// Creates a new `SortByReader`.
+    pub(crate) fn new(reader: BoxedBatchReader, column_ids: Vec<ColumnId>) -> Self {
+        Self {
+            reader,
+            column_ids,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Sorts and returns the buffered time series.
+    fn sort_buffer(&mut self) -> Result<Option<Batch>> {
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+        let mut batch = Batch::concat(mem::take(&mut self.buffer))?;
+        batch.sort_by_fields(&self.column_ids)?;
+        Ok(Some(batch))
+    }
+}
+
+#[async_trait]
+impl BatchReader for SortByReader {
+    async fn next_batch(&mut self) -> Result<Option<Batch>> {
+        while let Some(batch) = self.reader.next_batch().await? {
+            if batch.is_empty() {
+                continue;
+            }
+            let is_next_series = self
+                .buffer
+                .first()
+                .is_some_and(|buffered| buffered.primary_key() != batch.primary_key());
+            if is_next_series {
+                let sorted = self.sort_buffer()?;
+                self.buffer.push(batch);
+                return Ok(sorted);
+            }
+            self.buffer.push(batch);
+        }
+
+        self.sort_buffer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::OpType;
+
+    use super::*;
+    use crate::test_util::sst_util::{new_primary_key, sst_region_metadata};
+    use crate::test_util::{check_reader_result, new_batch_builder, VecBatchReader};
+
+    fn new_batch(tags: &[&str], timestamps: &[i64], fields: &[u64]) -> Batch {
+        new_batch_builder(
+            &new_primary_key(tags),
+            timestamps,
+            &vec![1; timestamps.len()],
+            &vec![OpType::Put; timestamps.len()],
+            2,
+            fields,
+        )
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_sort_by() {
+        let metadata = sst_region_metadata();
+        let columns = |columns: &[&str]| columns.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+
+        assert!(resolve_sort_by(&metadata, &[]).unwrap().is_empty());
+        assert!(resolve_sort_by(&metadata, &columns(&["ts"]))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            vec![2],
+            resolve_sort_by(&metadata, &columns(&["field_0", "ts"])).unwrap()
+        );
+        resolve_sort_by(&metadata, &columns(&["field_0"])).unwrap_err();
+        resolve_sort_by(&metadata, &columns(&["tag_0", "ts"])).unwrap_err();
+        resolve_sort_by(&metadata, &columns(&["unknown", "ts"])).unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_sort_by_reader() {
+        let input = vec![
+            new_batch(&["a", "b"], &[1, 2, 3], &[30, 10, 20]),
+            new_batch(&["a", "b"], &[4, 5], &[10, 30]),
+            new_batch(&["c", "d"], &[1, 2], &[2, 1]),
+        ];
+        let reader = VecBatchReader::new(&input);
+        let mut reader = SortByReader::new(Box::new(reader), vec![2]);
+        check_reader_result(
+            &mut reader,
+            &[
+                new_batch(&["a", "b"], &[2, 4, 3, 1, 5], &[10, 10, 20, 30, 30]),
+                new_batch(&["c", "d"], &[2, 1], &[1, 2]),
+            ],
+        )
+        .await;
+    }
+}
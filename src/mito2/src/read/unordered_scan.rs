@@ -175,12 +175,17 @@ impl UnorderedScan {
                     range_builder_list.clone(),
                 );
                 for await batch in stream {
-                    let batch = batch.map_err(BoxedError::new).context(ExternalSnafu)?;
+                    let mut batch = batch.map_err(BoxedError::new).context(ExternalSnafu)?;
+                    // Rows are not merged so they can be filtered one by one.
+                    batch
+                        .filter_newer_than(stream_ctx.input.newer_than)
+                        .map_err(BoxedError::new)
+                        .context(ExternalSnafu)?;
                     metrics.scan_cost += fetch_start.elapsed();
                     metrics.num_batches += 1;
                     metrics.num_rows += batch.num_rows();
 
-                    debug_assert!(!batch.is_empty());
+                    debug_assert!(stream_ctx.input.newer_than.is_some() || !batch.is_empty());
                     if batch.is_empty() {
                         continue;
                     }
@@ -293,6 +293,13 @@ impl MitoRegion {
         let num_rows = version.ssts.num_rows() + version.memtables.num_rows();
         let manifest_version = self.stats.manifest_version();
         let flushed_entry_id = version.flushed_entry_id;
+        let time_range = match (version.ssts.time_range(), version.memtables.time_range()) {
+            (Some((sst_min, sst_max)), Some((mem_min, mem_max))) => {
+                Some((sst_min.min(mem_min), sst_max.max(mem_max)))
+            }
+            (sst_range, mem_range) => sst_range.or(mem_range),
+        }
+        .map(|(min, max)| (min.value(), max.value()));
 
         RegionStatistic {
             num_rows,
@@ -301,6 +308,8 @@ impl MitoRegion {
             manifest_size: manifest_usage,
             sst_size: sst_usage,
             index_size: index_usage,
+            sst_compression: Some(version.options.sst.compression.to_string()),
+            time_range,
             manifest: RegionManifestInfo::Mito {
                 manifest_version,
                 flushed_entry_id,
@@ -362,6 +371,16 @@ impl ManifestContext {
             .manifest_version
     }
 
+    pub(crate) async fn manifest(&self) -> Arc<RegionManifest> {
+        self.manifest_manager.read().await.manifest()
+    }
+
+    /// Saves a checkpoint of the manifest and returns the version of the checkpoint.
+    pub(crate) async fn checkpoint(&self) -> Result<ManifestVersion> {
+        // Holds the write lock so the manifest doesn't change during the checkpoint.
+        self.manifest_manager.write().await.checkpoint().await
+    }
+
     pub(crate) async fn has_update(&self) -> Result<bool> {
         self.manifest_manager.read().await.has_update().await
     }
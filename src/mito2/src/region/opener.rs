@@ -54,7 +54,7 @@ use crate::region::version::{VersionBuilder, VersionControl, VersionControlRef};
 use crate::region::{
     ManifestContext, ManifestStats, MitoRegion, RegionLeaderState, RegionRoleState,
 };
-use crate::region_write_ctx::RegionWriteCtx;
+use crate::region_write_ctx::{decode_idempotency_key, RegionWriteCtx};
 use crate::request::OptionOutputTx;
 use crate::schedule::scheduler::SchedulerRef;
 use crate::sst::file_purger::LocalFilePurger;
@@ -413,6 +413,7 @@ impl RegionOpener {
             .build();
         let flushed_entry_id = version.flushed_entry_id;
         let version_control = Arc::new(VersionControl::new(version));
+        version_control.update_producer_watermarks(manifest.producer_watermarks.clone());
         if !self.skip_wal_replay {
             info!(
                 "Start replaying memtable at flushed_entry_id + 1: {} for region {}, manifest version: {}",
@@ -633,6 +634,10 @@ where
         let mut region_write_ctx =
             RegionWriteCtx::new(region_id, version_control, provider.clone());
         for mutation in entry.mutations {
+            if let Some(key) = decode_idempotency_key(&mutation) {
+                region_write_ctx.push_idempotency_key(key);
+                continue;
+            }
             rows_replayed += mutation
                 .rows
                 .as_ref()
@@ -17,17 +17,21 @@
 //! If we add options in this mod, we also need to modify [store_api::mito_engine_options].
 
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 use std::time::Duration;
 
 use common_base::readable_size::ReadableSize;
 use common_time::TimeToLive;
 use common_wal::options::{WalOptions, WAL_OPTIONS_KEY};
+use parquet::basic::{Compression, ZstdLevel};
 use serde::de::Error as _;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use serde_with::{serde_as, with_prefix, DisplayFromStr, NoneAsEmptyString};
 use snafu::{ensure, ResultExt};
 use store_api::codec::PrimaryKeyEncoding;
+use store_api::mito_engine_options::COMPACTION_SORT_BY;
 use store_api::storage::ColumnId;
 use strum::EnumString;
 
@@ -73,6 +77,8 @@ pub struct RegionOptions {
     /// The mode to merge duplicate rows.
     /// Only takes effect when `append_mode` is `false`.
     pub merge_mode: Option<MergeMode>,
+    /// SST options.
+    pub sst: SstOptions,
 }
 
 impl RegionOptions {
@@ -86,6 +92,12 @@ impl RegionOptions {
                 }
             );
         }
+        ensure!(
+            self.append_mode || self.sst.compaction_sort_by.is_empty(),
+            InvalidRegionOptionsSnafu {
+                reason: "compaction.sort_by is only allowed when append_mode is enabled",
+            }
+        );
         Ok(())
     }
 
@@ -136,6 +148,7 @@ impl TryFrom<&HashMap<String, String>> for RegionOptions {
         )?;
 
         let index_options: IndexOptions = serde_json::from_str(&json).context(JsonOptionsSnafu)?;
+        let sst: SstOptions = serde_json::from_str(&json).context(JsonOptionsSnafu)?;
         let memtable = if validate_enum_options(options_map, "memtable.type")? {
             Some(serde_json::from_str(&json).context(JsonOptionsSnafu)?)
         } else {
@@ -151,6 +164,7 @@ impl TryFrom<&HashMap<String, String>> for RegionOptions {
             index_options,
             memtable,
             merge_mode: options.merge_mode,
+            sst,
         };
         opts.validate()?;
 
@@ -318,6 +332,106 @@ impl Default for InvertedIndexOptions {
     }
 }
 
+/// Options for SST files.
+///
+/// They only take effect on SST files written afterward, e.g. by flush and compaction.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SstOptions {
+    /// Compression codec of SST files.
+    #[serde(rename = "sst.compression")]
+    #[serde_as(as = "DisplayFromStr")]
+    pub compression: SstCompression,
+    /// Columns to order the rows of each series by in SSTs written by compaction,
+    /// e.g. `service, ts`. The time index must be the last one and others must be
+    /// fields. Row groups then cover narrow ranges of the first column, so the
+    /// min-max statistics can prune them for filters on it.
+    ///
+    /// Only allowed in append mode as rows are no longer ordered by the time index.
+    #[serde(
+        rename = "compaction.sort_by",
+        deserialize_with = "deserialize_sort_by",
+        serialize_with = "serialize_sort_by"
+    )]
+    pub compaction_sort_by: Vec<String>,
+}
+
+/// Compression codec of SST files.
+///
+/// The textual form is `uncompressed`, `snappy`, `lz4`, `zstd` or `zstd(<level>)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SstCompression {
+    Uncompressed,
+    Snappy,
+    Lz4,
+    /// Zstd with an optional compression level. Uses the default level of zstd
+    /// if the level is not specified.
+    Zstd(Option<i32>),
+}
+
+impl Default for SstCompression {
+    fn default() -> Self {
+        SstCompression::Zstd(None)
+    }
+}
+
+impl SstCompression {
+    /// Returns the compression codec for the parquet writer.
+    pub(crate) fn to_parquet_compression(self) -> Compression {
+        match self {
+            SstCompression::Uncompressed => Compression::UNCOMPRESSED,
+            SstCompression::Snappy => Compression::SNAPPY,
+            SstCompression::Lz4 => Compression::LZ4_RAW,
+            SstCompression::Zstd(level) => {
+                // The level is validated while parsing.
+                let level = level
+                    .and_then(|level| ZstdLevel::try_new(level).ok())
+                    .unwrap_or_default();
+                Compression::ZSTD(level)
+            }
+        }
+    }
+}
+
+impl fmt::Display for SstCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SstCompression::Uncompressed => write!(f, "uncompressed"),
+            SstCompression::Snappy => write!(f, "snappy"),
+            SstCompression::Lz4 => write!(f, "lz4"),
+            SstCompression::Zstd(None) => write!(f, "zstd"),
+            SstCompression::Zstd(Some(level)) => write!(f, "zstd({level})"),
+        }
+    }
+}
+
+impl FromStr for SstCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let value = s.trim().to_ascii_lowercase();
+        match value.as_str() {
+            "uncompressed" | "none" => return Ok(SstCompression::Uncompressed),
+            "snappy" => return Ok(SstCompression::Snappy),
+            "lz4" => return Ok(SstCompression::Lz4),
+            "zstd" => return Ok(SstCompression::Zstd(None)),
+            _ => {}
+        }
+
+        let level = value
+            .strip_prefix("zstd(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or_else(|| format!("unknown sst compression: {s}"))?;
+        let level = level
+            .trim()
+            .parse::<i32>()
+            .map_err(|_| format!("invalid zstd level: {level}"))?;
+        ZstdLevel::try_new(level).map_err(|e| format!("invalid zstd level {level}: {e}"))?;
+        Ok(SstCompression::Zstd(Some(level)))
+    }
+}
+
 /// Options for region level memtable.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "memtable.type", rename_all = "snake_case")]
@@ -405,6 +519,25 @@ where
     serializer.serialize_str(&s)
 }
 
+fn deserialize_sort_by<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = Deserialize::deserialize(deserializer)?;
+    Ok(s.split(',')
+        .map(|column| column.trim())
+        .filter(|column| !column.is_empty())
+        .map(|column| column.to_string())
+        .collect())
+}
+
+fn serialize_sort_by<S>(columns: &[String], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&columns.join(","))
+}
+
 /// Converts the `options` map to a json object.
 ///
 /// Replaces "null" strings by `null` json values.
@@ -437,7 +570,7 @@ fn validate_enum_options(
     for key in options_map.keys() {
         if key == enum_tag_key {
             has_tag = true;
-        } else if key.starts_with(enum_type) {
+        } else if key.starts_with(enum_type) && key != COMPACTION_SORT_BY {
             has_other_options = true;
         }
     }
@@ -611,6 +744,58 @@ mod tests {
         assert_eq!(StatusCode::InvalidArguments, err.status_code());
     }
 
+    #[test]
+    fn test_with_sst_compression() {
+        let options = RegionOptions::try_from(&HashMap::new()).unwrap();
+        assert_eq!(SstCompression::Zstd(None), options.sst.compression);
+
+        let cases = [
+            ("uncompressed", SstCompression::Uncompressed),
+            ("snappy", SstCompression::Snappy),
+            ("LZ4", SstCompression::Lz4),
+            ("zstd", SstCompression::Zstd(None)),
+            ("zstd(3)", SstCompression::Zstd(Some(3))),
+        ];
+        for (value, expect) in cases {
+            let map = make_map(&[("sst.compression", value)]);
+            let options = RegionOptions::try_from(&map).unwrap();
+            assert_eq!(expect, options.sst.compression);
+        }
+
+        for value in ["gzip", "zstd(100)", "zstd(abc)", "zstd(3"] {
+            let map = make_map(&[("sst.compression", value)]);
+            let err = RegionOptions::try_from(&map).unwrap_err();
+            assert_eq!(StatusCode::InvalidArguments, err.status_code());
+        }
+    }
+
+    #[test]
+    fn test_with_compaction_sort_by() {
+        let map = make_map(&[
+            ("compaction.sort_by", "service, ts"),
+            ("append_mode", "true"),
+        ]);
+        let options = RegionOptions::try_from(&map).unwrap();
+        assert_eq!(
+            vec!["service".to_string(), "ts".to_string()],
+            options.sst.compaction_sort_by
+        );
+        // It doesn't require the compaction type.
+        assert_eq!(CompactionOptions::default(), options.compaction);
+
+        let map = make_map(&[("compaction.sort_by", "service, ts")]);
+        let err = RegionOptions::try_from(&map).unwrap_err();
+        assert_eq!(StatusCode::InvalidArguments, err.status_code());
+    }
+
+    #[test]
+    fn test_sst_compression_display() {
+        for value in ["uncompressed", "snappy", "lz4", "zstd", "zstd(3)"] {
+            let compression = SstCompression::from_str(value).unwrap();
+            assert_eq!(value, compression.to_string());
+        }
+    }
+
     #[test]
     fn test_with_all() {
         let wal_options = WalOptions::Kafka(KafkaWalOptions {
@@ -640,6 +825,7 @@ mod tests {
             ("memtable.partition_tree.data_freeze_threshold", "2048"),
             ("memtable.partition_tree.fork_dictionary_bytes", "128M"),
             ("merge_mode", "last_non_null"),
+            ("sst.compression", "zstd(3)"),
         ]);
         let options = RegionOptions::try_from(&map).unwrap();
         let expect = RegionOptions {
@@ -670,6 +856,10 @@ mod tests {
                 primary_key_encoding: PrimaryKeyEncoding::Dense,
             })),
             merge_mode: Some(MergeMode::LastNonNull),
+            sst: SstOptions {
+                compression: SstCompression::Zstd(Some(3)),
+                ..Default::default()
+            },
         };
         assert_eq!(expect, options);
     }
@@ -706,6 +896,10 @@ mod tests {
                 primary_key_encoding: PrimaryKeyEncoding::Dense,
             })),
             merge_mode: Some(MergeMode::LastNonNull),
+            sst: SstOptions {
+                compression: SstCompression::Lz4,
+                compaction_sort_by: vec!["service".to_string(), "ts".to_string()],
+            },
         };
         let region_options_json_str = serde_json::to_string(&options).unwrap();
         let got: RegionOptions = serde_json::from_str(&region_options_json_str).unwrap();
@@ -775,6 +969,7 @@ mod tests {
                 primary_key_encoding: PrimaryKeyEncoding::Dense,
             })),
             merge_mode: Some(MergeMode::LastNonNull),
+            sst: SstOptions::default(),
         };
         assert_eq!(options, got);
     }
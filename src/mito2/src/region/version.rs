@@ -23,6 +23,7 @@
 //! Reason: data may be flushed/compacted and some data with old sequence may be removed
 //! and became invisible between step 1 and 2, so need to acquire version at first.
 
+use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
@@ -62,6 +63,7 @@ impl VersionControl {
                 committed_sequence: flushed_sequence,
                 last_entry_id: flushed_entry_id,
                 is_dropped: false,
+                producer_watermarks: Arc::new(HashMap::new()),
             }),
         }
     }
@@ -78,6 +80,19 @@ impl VersionControl {
         data.last_entry_id = entry_id;
     }
 
+    /// Raises the watermarks of the producers to the given sequence numbers.
+    pub(crate) fn update_producer_watermarks(
+        &self,
+        watermarks: impl IntoIterator<Item = (String, u64)>,
+    ) {
+        let mut data = self.data.write().unwrap();
+        let producer_watermarks = Arc::make_mut(&mut data.producer_watermarks);
+        for (producer_id, sequence) in watermarks {
+            let watermark = producer_watermarks.entry(producer_id).or_default();
+            *watermark = (*watermark).max(sequence);
+        }
+    }
+
     /// Sequence number of last committed data.
     pub(crate) fn committed_sequence(&self) -> SequenceNumber {
         self.data.read().unwrap().committed_sequence
@@ -241,6 +256,9 @@ pub(crate) struct VersionControlData {
     pub(crate) last_entry_id: EntryId,
     /// Marker of whether this region is dropped/dropping
     pub(crate) is_dropped: bool,
+    /// The highest sequence number written by each producer, see
+    /// [IdempotencyKey](store_api::region_request::IdempotencyKey).
+    pub(crate) producer_watermarks: Arc<HashMap<String, u64>>,
 }
 
 /// Static metadata of a region.
@@ -370,10 +388,14 @@ impl VersionBuilder {
         if let Some(window) = edit.compaction_time_window {
             self.compaction_time_window = Some(window);
         }
-        if !edit.files_to_add.is_empty() || !edit.files_to_remove.is_empty() {
+        if !edit.files_to_add.is_empty()
+            || !edit.files_to_remove.is_empty()
+            || !edit.files_to_update.is_empty()
+        {
             let mut ssts = (*self.ssts).clone();
             ssts.add_files(file_purger, edit.files_to_add.into_iter());
             ssts.remove_files(edit.files_to_remove.into_iter());
+            ssts.update_files(edit.files_to_update.into_iter());
             self.ssts = Arc::new(ssts);
         }
 
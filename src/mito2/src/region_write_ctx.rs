@@ -15,11 +15,16 @@
 use std::mem;
 use std::sync::Arc;
 
-use api::v1::{Mutation, OpType, Rows, WalEntry, WriteHint};
+use api::v1::value::ValueData;
+use api::v1::{
+    ColumnDataType, ColumnSchema, Mutation, OpType, Row, Rows, SemanticType, Value, WalEntry,
+    WriteHint,
+};
 use futures::stream::{FuturesUnordered, StreamExt};
 use snafu::ResultExt;
 use store_api::logstore::provider::Provider;
 use store_api::logstore::LogStore;
+use store_api::region_request::IdempotencyKey;
 use store_api::storage::{RegionId, SequenceNumber};
 
 use crate::error::{Error, Result, WriteGroupSnafu};
@@ -67,6 +72,68 @@ impl Drop for WriteNotify {
     }
 }
 
+/// Column names of the mutation that records an [IdempotencyKey] in the WAL.
+const PRODUCER_ID_COLUMN: &str = "__producer_id";
+const PRODUCER_SEQUENCE_COLUMN: &str = "__producer_sequence";
+
+/// Returns a mutation that records the key in the WAL entry of the rows it identifies,
+/// so the watermark of the producer is recovered with the rows while replaying the WAL.
+fn idempotency_key_mutation(key: &IdempotencyKey, sequence: SequenceNumber) -> Mutation {
+    let column = |name: &str, datatype: ColumnDataType| ColumnSchema {
+        column_name: name.to_string(),
+        datatype: datatype as i32,
+        semantic_type: SemanticType::Field as i32,
+        ..Default::default()
+    };
+    Mutation {
+        op_type: OpType::Put as i32,
+        sequence,
+        rows: Some(Rows {
+            schema: vec![
+                column(PRODUCER_ID_COLUMN, ColumnDataType::String),
+                column(PRODUCER_SEQUENCE_COLUMN, ColumnDataType::Uint64),
+            ],
+            rows: vec![Row {
+                values: vec![
+                    Value {
+                        value_data: Some(ValueData::StringValue(key.producer_id.clone())),
+                    },
+                    Value {
+                        value_data: Some(ValueData::U64Value(key.sequence)),
+                    },
+                ],
+            }],
+        }),
+        write_hint: None,
+    }
+}
+
+/// Returns the [IdempotencyKey] recorded by the mutation, or `None` if the mutation
+/// contains rows to write.
+pub(crate) fn decode_idempotency_key(mutation: &Mutation) -> Option<IdempotencyKey> {
+    let rows = mutation.rows.as_ref()?;
+    let [producer_id, sequence] = rows.schema.as_slice() else {
+        return None;
+    };
+    if producer_id.column_name != PRODUCER_ID_COLUMN
+        || sequence.column_name != PRODUCER_SEQUENCE_COLUMN
+    {
+        return None;
+    }
+    let [producer_id, sequence] = rows.rows.first()?.values.as_slice() else {
+        return None;
+    };
+    match (&producer_id.value_data, &sequence.value_data) {
+        (Some(ValueData::StringValue(producer_id)), Some(ValueData::U64Value(sequence))) => {
+            Some(IdempotencyKey {
+                producer_id: producer_id.clone(),
+                sequence: *sequence,
+            })
+        }
+        _ => None,
+    }
+}
+
 /// Context to keep region metadata and buffer write requests.
 pub(crate) struct RegionWriteCtx {
     /// Id of region to write.
@@ -94,6 +161,9 @@ pub(crate) struct RegionWriteCtx {
     notifiers: Vec<WriteNotify>,
     /// The write operation is failed and we should not write to the mutable memtable.
     failed: bool,
+    /// Idempotency keys of the mutations, and the index of the mutation each key
+    /// identifies.
+    idempotency_keys: Vec<(usize, IdempotencyKey)>,
 
     // Metrics:
     /// Rows to put.
@@ -126,6 +196,7 @@ impl RegionWriteCtx {
             provider,
             notifiers: Vec::new(),
             failed: false,
+            idempotency_keys: Vec::new(),
             put_num: 0,
             delete_num: 0,
         }
@@ -162,6 +233,35 @@ impl RegionWriteCtx {
         }
     }
 
+    /// Returns true if the rows identified by the key are already written, i.e. the
+    /// watermark of its producer is not lower than its sequence number.
+    pub(crate) fn is_written(&self, key: &IdempotencyKey) -> bool {
+        let written = self.idempotency_keys.iter().any(|(_, pending)| {
+            pending.producer_id == key.producer_id && pending.sequence >= key.sequence
+        });
+        written
+            || self
+                .version_control
+                .current()
+                .producer_watermarks
+                .get(&key.producer_id)
+                .is_some_and(|watermark| *watermark >= key.sequence)
+    }
+
+    /// Identifies the last pushed mutation by the key. The watermark of the producer is
+    /// raised once the mutation is written to the memtable.
+    pub(crate) fn push_idempotency_key(&mut self, key: IdempotencyKey) {
+        debug_assert!(!self.wal_entry.mutations.is_empty());
+        let index = self.wal_entry.mutations.len() - 1;
+        self.wal_entry
+            .mutations
+            .push(idempotency_key_mutation(&key, self.next_sequence));
+        // The mutation has no rows to write.
+        self.notifiers
+            .push(WriteNotify::new(OptionOutputTx::none(), 0));
+        self.idempotency_keys.push((index, key));
+    }
+
     /// Encode and add WAL entry to the writer.
     pub(crate) fn add_wal_entry<S: LogStore>(
         &mut self,
@@ -210,6 +310,9 @@ impl RegionWriteCtx {
             .into_iter()
             .enumerate()
             .filter_map(|(i, mutation)| {
+                if decode_idempotency_key(&mutation).is_some() {
+                    return None;
+                }
                 let kvs = KeyValues::new(&self.version.metadata, mutation)?;
                 Some((i, kvs))
             })
@@ -242,5 +345,15 @@ impl RegionWriteCtx {
         // to decrease `next_sequence` and `next_entry_id` by 1.
         self.version_control
             .set_sequence_and_entry_id(self.next_sequence - 1, self.next_entry_id - 1);
+
+        // Only raises the watermarks of the rows written, so producers can retry the others.
+        let watermarks = mem::take(&mut self.idempotency_keys)
+            .into_iter()
+            .filter(|(i, _)| self.notifiers[*i].err.is_none())
+            .map(|(_, key)| (key.producer_id, key.sequence))
+            .collect::<Vec<_>>();
+        if !watermarks.is_empty() {
+            self.version_control.update_producer_watermarks(watermarks);
+        }
     }
 }
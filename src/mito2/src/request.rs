@@ -35,9 +35,10 @@ use store_api::manifest::ManifestVersion;
 use store_api::metadata::{ColumnMetadata, RegionMetadata, RegionMetadataRef};
 use store_api::region_engine::{SetRegionRoleStateResponse, SettableRegionRoleState};
 use store_api::region_request::{
-    AffectedRows, RegionAlterRequest, RegionCatchupRequest, RegionCloseRequest,
-    RegionCompactRequest, RegionCreateRequest, RegionFlushRequest, RegionOpenRequest,
-    RegionRequest, RegionTruncateRequest,
+    AffectedRows, IdempotencyKey, RegionAlterRequest, RegionBuildIndexRequest,
+    RegionCatchupRequest, RegionCloseRequest, RegionCompactRequest, RegionCreateRequest,
+    RegionFlushRequest, RegionOpenRequest, RegionRequest, RegionTruncateRangeRequest,
+    RegionTruncateRequest,
 };
 use store_api::storage::{RegionId, SequenceNumber};
 use tokio::sync::oneshot::{self, Receiver, Sender};
@@ -67,6 +68,8 @@ pub struct WriteRequest {
     has_null: Vec<bool>,
     /// Write hint.
     pub hint: Option<WriteHint>,
+    /// Idempotency key of the rows.
+    pub idempotency_key: Option<IdempotencyKey>,
     /// Region metadata on the time of this request is created.
     pub(crate) region_metadata: Option<RegionMetadataRef>,
 }
@@ -124,6 +127,7 @@ impl WriteRequest {
             name_to_index,
             has_null,
             hint: None,
+            idempotency_key: None,
             region_metadata,
         })
     }
@@ -134,6 +138,12 @@ impl WriteRequest {
         self
     }
 
+    /// Sets the idempotency key.
+    pub fn with_idempotency_key(mut self, idempotency_key: Option<IdempotencyKey>) -> Self {
+        self.idempotency_key = idempotency_key;
+        self
+    }
+
     /// Returns the encoding hint.
     pub fn primary_key_encoding(&self) -> PrimaryKeyEncoding {
         infer_primary_key_encoding_from_hint(self.hint.as_ref())
@@ -599,7 +609,8 @@ impl WorkerRequest {
             RegionRequest::Put(v) => {
                 let mut write_request =
                     WriteRequest::new(region_id, OpType::Put, v.rows, region_metadata.clone())?
-                        .with_hint(v.hint);
+                        .with_hint(v.hint)
+                        .with_idempotency_key(v.idempotency_key);
                 if write_request.primary_key_encoding() == PrimaryKeyEncoding::Dense
                     && let Some(region_metadata) = &region_metadata
                 {
@@ -663,11 +674,21 @@ impl WorkerRequest {
                 sender: sender.into(),
                 request: DdlRequest::Truncate(v),
             }),
+            RegionRequest::TruncateRange(v) => WorkerRequest::Ddl(SenderDdlRequest {
+                region_id,
+                sender: sender.into(),
+                request: DdlRequest::TruncateRange(v),
+            }),
             RegionRequest::Catchup(v) => WorkerRequest::Ddl(SenderDdlRequest {
                 region_id,
                 sender: sender.into(),
                 request: DdlRequest::Catchup(v),
             }),
+            RegionRequest::BuildIndex(v) => WorkerRequest::Ddl(SenderDdlRequest {
+                region_id,
+                sender: sender.into(),
+                request: DdlRequest::BuildIndex(v),
+            }),
         };
 
         Ok((worker_request, receiver))
@@ -716,7 +737,9 @@ pub(crate) enum DdlRequest {
     Flush(RegionFlushRequest),
     Compact(RegionCompactRequest),
     Truncate(RegionTruncateRequest),
+    TruncateRange(RegionTruncateRangeRequest),
     Catchup(RegionCatchupRequest),
+    BuildIndex(RegionBuildIndexRequest),
 }
 
 /// Sender and Ddl request.
@@ -747,6 +770,10 @@ pub(crate) enum BackgroundNotify {
     RegionChange(RegionChangeResult),
     /// Region edit result.
     RegionEdit(RegionEditResult),
+    /// Index build has finished.
+    IndexBuildFinished(IndexBuildFinished),
+    /// Index build has failed.
+    IndexBuildFailed(IndexBuildFailed),
 }
 
 /// Notifies a flush job is finished.
@@ -838,6 +865,24 @@ pub(crate) struct CompactionFailed {
     pub(crate) err: Arc<Error>,
 }
 
+/// Notifies an index build job has finished.
+#[derive(Debug)]
+pub(crate) struct IndexBuildFinished {
+    /// Region id.
+    pub(crate) region_id: RegionId,
+    /// Region edit to apply. It only updates metadata of files that have new indexes.
+    pub(crate) edit: RegionEdit,
+}
+
+/// Notifies an index build job has failed.
+#[derive(Debug)]
+pub(crate) struct IndexBuildFailed {
+    /// Region id.
+    pub(crate) region_id: RegionId,
+    /// The error source of the failure.
+    pub(crate) err: Arc<Error>,
+}
+
 /// Notifies the truncate result of a region.
 #[derive(Debug)]
 pub(crate) struct TruncateResult {
@@ -226,17 +226,43 @@ impl FileHandle {
         self.inner.meta.time_range
     }
 
+    /// Returns a new handle of the same file with updated metadata.
+    ///
+    /// The new handle shares the compacting and deleted states with this handle, so
+    /// the file is only purged after readers of both handles finish.
+    pub(crate) fn with_meta(&self, meta: FileMeta) -> FileHandle {
+        debug_assert_eq!(self.file_id(), meta.file_id);
+        FileHandle {
+            inner: Arc::new(FileHandleInner {
+                meta,
+                compacting: AtomicBool::new(false),
+                deleted: AtomicBool::new(false),
+                file_purger: self.inner.file_purger.clone(),
+                previous: Some(self.clone()),
+            }),
+        }
+    }
+
     /// Mark the file as deleted and will delete it on drop asynchronously
     pub fn mark_deleted(&self) {
-        self.inner.deleted.store(true, Ordering::Relaxed);
+        match &self.inner.previous {
+            Some(previous) => previous.mark_deleted(),
+            None => self.inner.deleted.store(true, Ordering::Relaxed),
+        }
     }
 
     pub fn compacting(&self) -> bool {
-        self.inner.compacting.load(Ordering::Relaxed)
+        match &self.inner.previous {
+            Some(previous) => previous.compacting(),
+            None => self.inner.compacting.load(Ordering::Relaxed),
+        }
     }
 
     pub fn set_compacting(&self, compacting: bool) {
-        self.inner.compacting.store(compacting, Ordering::Relaxed);
+        match &self.inner.previous {
+            Some(previous) => previous.set_compacting(compacting),
+            None => self.inner.compacting.store(compacting, Ordering::Relaxed),
+        }
     }
 
     /// Returns a reference to the [FileMeta].
@@ -265,6 +291,9 @@ struct FileHandleInner {
     compacting: AtomicBool,
     deleted: AtomicBool,
     file_purger: FilePurgerRef,
+    /// Handle of the same file before its metadata is updated. It owns the compacting
+    /// and deleted states of the file.
+    previous: Option<FileHandle>,
 }
 
 impl Drop for FileHandleInner {
@@ -284,6 +313,7 @@ impl FileHandleInner {
             compacting: AtomicBool::new(false),
             deleted: AtomicBool::new(false),
             file_purger,
+            previous: None,
         }
     }
 }
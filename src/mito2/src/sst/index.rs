@@ -216,7 +216,9 @@ impl IndexerBuilderImpl {
     fn build_inverted_indexer(&self, file_id: FileId) -> Option<InvertedIndexer> {
         let create = match self.op_type {
             OperationType::Flush => self.inverted_index_config.create_on_flush.auto(),
-            OperationType::Compact => self.inverted_index_config.create_on_compaction.auto(),
+            OperationType::Compact | OperationType::BuildIndex => {
+                self.inverted_index_config.create_on_compaction.auto()
+            }
         };
 
         if !create {
@@ -276,7 +278,9 @@ impl IndexerBuilderImpl {
     async fn build_fulltext_indexer(&self, file_id: FileId) -> Option<FulltextIndexer> {
         let create = match self.op_type {
             OperationType::Flush => self.fulltext_index_config.create_on_flush.auto(),
-            OperationType::Compact => self.fulltext_index_config.create_on_compaction.auto(),
+            OperationType::Compact | OperationType::BuildIndex => {
+                self.fulltext_index_config.create_on_compaction.auto()
+            }
         };
 
         if !create {
@@ -330,7 +334,9 @@ impl IndexerBuilderImpl {
     fn build_bloom_filter_indexer(&self, file_id: FileId) -> Option<BloomFilterIndexer> {
         let create = match self.op_type {
             OperationType::Flush => self.bloom_filter_index_config.create_on_flush.auto(),
-            OperationType::Compact => self.bloom_filter_index_config.create_on_compaction.auto(),
+            OperationType::Compact | OperationType::BuildIndex => {
+                self.bloom_filter_index_config.create_on_compaction.auto()
+            }
         };
 
         if !create {
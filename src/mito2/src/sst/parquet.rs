@@ -19,6 +19,7 @@ use std::sync::Arc;
 use common_base::readable_size::ReadableSize;
 use parquet::file::metadata::ParquetMetaData;
 
+use crate::region::options::SstCompression;
 use crate::sst::file::{FileId, FileTimeRange};
 use crate::sst::index::IndexOutput;
 use crate::sst::DEFAULT_WRITE_BUFFER_SIZE;
@@ -36,6 +37,9 @@ pub mod writer;
 
 /// Key of metadata in parquet SST.
 pub const PARQUET_METADATA_KEY: &str = "greptime:metadata";
+/// Key of the comma separated columns that rows of each time series in the SST
+/// are ordered by. Rows are ordered by the timestamp if it's absent.
+pub const PARQUET_SORT_BY_KEY: &str = "greptime:sort_by";
 
 /// Default batch size to read parquet files.
 pub(crate) const DEFAULT_READ_BATCH_SIZE: usize = 1024;
@@ -49,6 +53,11 @@ pub struct WriteOptions {
     pub write_buffer_size: ReadableSize,
     /// Row group size.
     pub row_group_size: usize,
+    /// Compression codec of the SST.
+    pub compression: SstCompression,
+    /// Columns that rows of each time series are ordered by, or empty if they
+    /// are ordered by the timestamp.
+    pub sort_by: Vec<String>,
 }
 
 impl Default for WriteOptions {
@@ -56,6 +65,8 @@ impl Default for WriteOptions {
         WriteOptions {
             write_buffer_size: DEFAULT_WRITE_BUFFER_SIZE,
             row_group_size: DEFAULT_ROW_GROUP_SIZE,
+            compression: SstCompression::default(),
+            sort_by: Vec::new(),
         }
     }
 }
@@ -84,6 +95,7 @@ pub struct SstInfo {
 mod tests {
     use std::sync::Arc;
 
+    use api::v1::OpType;
     use common_time::Timestamp;
     use datafusion_common::{Column, ScalarValue};
     use datafusion_expr::{BinaryExpr, Expr, Operator};
@@ -94,12 +106,15 @@ mod tests {
     use parquet::basic::{Compression, Encoding, ZstdLevel};
     use parquet::file::metadata::KeyValue;
     use parquet::file::properties::WriterProperties;
+    use store_api::metadata::RegionMetadata;
     use table::predicate::Predicate;
     use tokio_util::compat::FuturesAsyncWriteCompatExt;
 
     use super::*;
     use crate::access_layer::FilePathProvider;
     use crate::cache::{CacheManager, CacheStrategy, PageKey};
+    use crate::read::sort_by::SortByReader;
+    use crate::read::Source;
     use crate::sst::index::{Indexer, IndexerBuilder};
     use crate::sst::parquet::format::WriteFormat;
     use crate::sst::parquet::reader::ParquetReaderBuilder;
@@ -107,9 +122,9 @@ mod tests {
     use crate::sst::{location, DEFAULT_WRITE_CONCURRENCY};
     use crate::test_util::sst_util::{
         assert_parquet_metadata_eq, build_test_binary_test_region_metadata, new_batch_by_range,
-        new_batch_with_binary, new_source, sst_file_handle, sst_region_metadata,
+        new_batch_with_binary, new_primary_key, new_source, sst_file_handle, sst_region_metadata,
     };
-    use crate::test_util::{check_reader_result, TestEnv};
+    use crate::test_util::{check_reader_result, new_batch_builder, TestEnv, VecBatchReader};
 
     const FILE_DIR: &str = "/";
 
@@ -308,6 +323,93 @@ mod tests {
         assert_parquet_metadata_eq(writer_metadata, reader_metadata)
     }
 
+    async fn write_and_read_metadata(
+        metadata: RegionMetadata,
+        write_opts: &WriteOptions,
+    ) -> Arc<ParquetMetaData> {
+        let mut env = TestEnv::new();
+        let object_store = env.init_object_store_manager();
+        let handle = sst_file_handle(0, 1000);
+        let source = new_source(&[
+            new_batch_by_range(&["a", "d"], 0, 60),
+            new_batch_by_range(&["b", "f"], 0, 40),
+        ]);
+
+        let mut writer = ParquetWriter::new_with_object_store(
+            object_store.clone(),
+            Arc::new(metadata),
+            NoopIndexBuilder,
+            FixedPathProvider {
+                file_id: handle.file_id(),
+            },
+        )
+        .await;
+        writer
+            .write_all(source, None, write_opts)
+            .await
+            .unwrap()
+            .remove(0);
+
+        // Reads the footer from the file instead of using the metadata returned by the writer.
+        let builder = ParquetReaderBuilder::new(FILE_DIR.to_string(), handle, object_store);
+        let reader = builder.build().await.unwrap();
+        reader.parquet_metadata()
+    }
+
+    #[tokio::test]
+    async fn test_write_with_compression() {
+        let cases = [
+            (
+                SstCompression::Zstd(Some(3)),
+                Compression::ZSTD(ZstdLevel::try_new(3).unwrap()),
+            ),
+            (SstCompression::Lz4, Compression::LZ4_RAW),
+            (SstCompression::Snappy, Compression::SNAPPY),
+            (SstCompression::Uncompressed, Compression::UNCOMPRESSED),
+        ];
+        for (compression, expected) in cases {
+            let write_opts = WriteOptions {
+                compression,
+                ..Default::default()
+            };
+            let parquet_meta = write_and_read_metadata(sst_region_metadata(), &write_opts).await;
+            for row_group in parquet_meta.row_groups() {
+                for column in row_group.columns() {
+                    assert_eq!(
+                        expected,
+                        column.compression(),
+                        "column: {}",
+                        column.column_path()
+                    );
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_without_field_dictionary() {
+        let mut metadata = sst_region_metadata();
+        let field = metadata
+            .column_metadatas
+            .iter_mut()
+            .find(|c| c.column_schema.name == "field_0")
+            .unwrap();
+        field.column_schema.set_sst_dictionary(false);
+
+        let parquet_meta = write_and_read_metadata(metadata, &WriteOptions::default()).await;
+        let row_group = parquet_meta.row_group(0);
+        for column in row_group.columns() {
+            let name = column.column_path().string();
+            if name == "field_0" {
+                assert!(column.dictionary_page_offset().is_none());
+                assert!(!column.encodings().contains(&Encoding::RLE_DICTIONARY));
+            } else if name == "__primary_key" {
+                // Other columns keep the default dictionary encoding.
+                assert!(column.dictionary_page_offset().is_some());
+            }
+        }
+    }
+
     #[tokio::test]
     async fn test_read_with_tag_filter() {
         let mut env = TestEnv::new();
@@ -443,6 +545,71 @@ mod tests {
         check_reader_result(&mut reader, &[new_batch_by_range(&["b", "h"], 150, 200)]).await;
     }
 
+    #[tokio::test]
+    async fn test_write_sorted_by_field() {
+        let mut env = TestEnv::new();
+        let object_store = env.init_object_store_manager();
+        let handle = sst_file_handle(0, 1000);
+        let metadata = Arc::new(sst_region_metadata());
+        let pk = new_primary_key(&["a", "d"]);
+        let input = new_batch_builder(
+            &pk,
+            &[0, 1, 2, 3],
+            &[1; 4],
+            &[OpType::Put; 4],
+            2,
+            &[3, 1, 2, 1],
+        )
+        .build()
+        .unwrap();
+        let reader = SortByReader::new(Box::new(VecBatchReader::new(&[input])), vec![2]);
+        let write_opts = WriteOptions {
+            sort_by: vec!["field_0".to_string(), "ts".to_string()],
+            ..Default::default()
+        };
+        let mut writer = ParquetWriter::new_with_object_store(
+            object_store.clone(),
+            metadata.clone(),
+            NoopIndexBuilder,
+            FixedPathProvider {
+                file_id: handle.file_id(),
+            },
+        )
+        .await;
+        let info = writer
+            .write_all(Source::Reader(Box::new(reader)), None, &write_opts)
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!(
+            (Timestamp::new_millisecond(0), Timestamp::new_millisecond(3)),
+            info.time_range
+        );
+
+        let builder = ParquetReaderBuilder::new(FILE_DIR.to_string(), handle.clone(), object_store);
+        let mut reader = builder.build().await.unwrap();
+        let sort_by = reader
+            .parquet_metadata()
+            .file_metadata()
+            .key_value_metadata()
+            .unwrap()
+            .iter()
+            .find(|kv| kv.key == PARQUET_SORT_BY_KEY)
+            .and_then(|kv| kv.value.clone());
+        assert_eq!(Some("field_0,ts".to_string()), sort_by);
+        let expect = new_batch_builder(
+            &pk,
+            &[1, 3, 2, 0],
+            &[1; 4],
+            &[OpType::Put; 4],
+            2,
+            &[1, 1, 2, 3],
+        )
+        .build()
+        .unwrap();
+        check_reader_result(&mut reader, &[expect]).await;
+    }
+
     #[tokio::test]
     async fn test_read_large_binary() {
         let mut env = TestEnv::new();
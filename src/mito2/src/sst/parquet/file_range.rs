@@ -24,7 +24,7 @@ use datatypes::arrow::array::BooleanArray;
 use datatypes::arrow::buffer::BooleanBuffer;
 use parquet::arrow::arrow_reader::RowSelection;
 use snafu::{OptionExt, ResultExt};
-use store_api::storage::TimeSeriesRowSelector;
+use store_api::storage::{SequenceNumber, TimeSeriesRowSelector};
 
 use crate::error::{
     DecodeStatsSnafu, FieldTypeMismatchSnafu, FilterRecordBatchSnafu, Result, StatsNotPresentSnafu,
@@ -103,7 +103,8 @@ impl FileRange {
                     error!(e; "Failed to decode min value of op_type, fallback to RowGroupReader");
                 })
                 .unwrap_or(true);
-            put_only && self.select_all()
+            // The cached last rows may be newer than the sequence to read.
+            put_only && self.select_all() && self.context.sequence().is_none()
         } else {
             // No selector provided, use RowGroupReader
             false
@@ -146,6 +147,8 @@ pub(crate) struct FileRangeContext {
     reader_builder: RowGroupReaderBuilder,
     /// Base of the context.
     base: RangeBase,
+    /// Only reads rows whose sequences are less than or equal to it, if set.
+    sequence: Option<SequenceNumber>,
 }
 
 pub(crate) type FileRangeContextRef = Arc<FileRangeContext>;
@@ -166,6 +169,7 @@ impl FileRangeContext {
                 codec,
                 compat_batch: None,
             },
+            sequence: None,
         }
     }
 
@@ -199,6 +203,16 @@ impl FileRangeContext {
         self.base.compat_batch = compat;
     }
 
+    /// Returns the sequence to filter rows by.
+    pub(crate) fn sequence(&self) -> Option<SequenceNumber> {
+        self.sequence
+    }
+
+    /// Sets the sequence to filter rows by.
+    pub(crate) fn set_sequence(&mut self, sequence: Option<SequenceNumber>) {
+        self.sequence = sequence;
+    }
+
     /// TRY THE BEST to perform pushed down predicate precisely on the input batch.
     /// Return the filtered batch. If the entire batch is filtered out, return None.
     pub(crate) fn precise_filter(&self, input: Batch) -> Result<Option<Batch>> {
@@ -35,7 +35,7 @@ use parquet::file::metadata::ParquetMetaData;
 use parquet::format::KeyValue;
 use snafu::{OptionExt, ResultExt};
 use store_api::metadata::{RegionMetadata, RegionMetadataRef};
-use store_api::storage::ColumnId;
+use store_api::storage::{ColumnId, ScanSample};
 use table::predicate::Predicate;
 
 use crate::cache::CacheStrategy;
@@ -50,7 +50,7 @@ use crate::metrics::{
 use crate::read::prune::{PruneReader, Source};
 use crate::read::{Batch, BatchReader};
 use crate::row_converter::build_primary_key_codec;
-use crate::sst::file::FileHandle;
+use crate::sst::file::{FileHandle, FileId};
 use crate::sst::index::bloom_filter::applier::BloomFilterIndexApplierRef;
 use crate::sst::index::fulltext_index::applier::FulltextIndexApplierRef;
 use crate::sst::index::inverted_index::applier::InvertedIndexApplierRef;
@@ -87,6 +87,8 @@ pub struct ParquetReaderBuilder {
     /// This is usually the latest metadata of the region. The reader use
     /// it get the correct column id of a column by name.
     expected_metadata: Option<RegionMetadataRef>,
+    /// Only reads a sample of row groups.
+    sample: Option<ScanSample>,
 }
 
 impl ParquetReaderBuilder {
@@ -107,6 +109,7 @@ impl ParquetReaderBuilder {
             bloom_filter_index_applier: None,
             fulltext_index_applier: None,
             expected_metadata: None,
+            sample: None,
         }
     }
 
@@ -170,6 +173,13 @@ impl ParquetReaderBuilder {
         self
     }
 
+    /// Only reads a sample of row groups.
+    #[must_use]
+    pub(crate) fn sample(mut self, sample: Option<ScanSample>) -> Self {
+        self.sample = sample;
+        self
+    }
+
     /// Builds a [ParquetReader].
     ///
     /// This needs to perform IO operation.
@@ -347,7 +357,16 @@ impl ParquetReaderBuilder {
         metrics.rg_total += num_row_groups;
         metrics.rows_total += num_rows as usize;
 
-        let mut output = (0..num_row_groups).map(|i| (i, None)).collect();
+        let mut output: BTreeMap<_, _> = (0..num_row_groups).map(|i| (i, None)).collect();
+
+        // Samples before pruning so we don't need to load indexes of skipped row groups.
+        if let Some(sample) = &self.sample {
+            let file_id = self.file_handle.file_id();
+            output.retain(|row_group, _| sample_row_group(sample, file_id, *row_group));
+            if output.is_empty() {
+                return output;
+            }
+        }
 
         self.prune_row_groups_by_fulltext_index(row_group_size, parquet_meta, &mut output, metrics)
             .await;
@@ -737,6 +756,40 @@ impl ParquetReaderBuilder {
     }
 }
 
+/// Returns whether to read the row group `row_group` of the file `file_id` in the `sample`.
+///
+/// It hashes the file id, the row group index and the seed so the same seed always
+/// picks the same row groups of a file. Since files are rewritten by compaction, the
+/// sample may change after compaction even with the same seed.
+///
+/// Caveats of sampling row groups instead of rows:
+/// - Rows in a row group are sorted by primary key and time, so rows of the same
+///   series and time window tend to be picked or skipped together.
+/// - The number of rows read is only close to the fraction if there are many row
+///   groups, and row groups of a file can have different numbers of rows.
+fn sample_row_group(sample: &ScanSample, file_id: FileId, row_group: usize) -> bool {
+    let (high, low) = file_id.as_bytes().split_at(8);
+    let mut hash = sample.seed;
+    for word in [
+        u64::from_le_bytes(high.try_into().unwrap()),
+        u64::from_le_bytes(low.try_into().unwrap()),
+        row_group as u64,
+    ] {
+        hash = splitmix64(hash ^ word);
+    }
+    // Maps the hash to [0, 1).
+    let point = (hash >> 11) as f64 / (1u64 << 53) as f64;
+    point < sample.fraction
+}
+
+/// The finalizer of the SplitMix64 generator, it mixes bits of the input well.
+fn splitmix64(x: u64) -> u64 {
+    let mut x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
 /// Metrics of filtering rows groups and rows.
 #[derive(Debug, Default, Clone, Copy)]
 pub(crate) struct ReaderFilterMetrics {
@@ -1542,4 +1595,38 @@ mod tests {
         assert_eq!(filtered_row_groups, 2);
         assert_eq!(filtered_rows, 10);
     }
+
+    #[test]
+    fn test_sample_row_group() {
+        let file_id = FileId::random();
+        let sample = ScanSample {
+            fraction: 0.1,
+            seed: 1,
+        };
+        let picked: Vec<_> = (0..10000)
+            .filter(|i| sample_row_group(&sample, file_id, *i))
+            .collect();
+        assert!(
+            (800..1200).contains(&picked.len()),
+            "picked {} row groups",
+            picked.len()
+        );
+        // Same seed picks the same row groups.
+        let picked_again: Vec<_> = (0..10000)
+            .filter(|i| sample_row_group(&sample, file_id, *i))
+            .collect();
+        assert_eq!(picked, picked_again);
+        // Another seed picks other row groups.
+        let other = ScanSample { seed: 2, ..sample };
+        let picked_other: Vec<_> = (0..10000)
+            .filter(|i| sample_row_group(&other, file_id, *i))
+            .collect();
+        assert_ne!(picked, picked_other);
+
+        let all = ScanSample {
+            fraction: 1.0,
+            seed: 1,
+        };
+        assert!((0..1000).all(|i| sample_row_group(&all, file_id, i)));
+    }
 }
@@ -24,7 +24,7 @@ use common_time::Timestamp;
 use datatypes::arrow::datatypes::SchemaRef;
 use object_store::{FuturesAsyncWriter, ObjectStore};
 use parquet::arrow::AsyncArrowWriter;
-use parquet::basic::{Compression, Encoding, ZstdLevel};
+use parquet::basic::Encoding;
 use parquet::file::metadata::KeyValue;
 use parquet::file::properties::{WriterProperties, WriterPropertiesBuilder};
 use parquet::schema::types::ColumnPath;
@@ -43,7 +43,7 @@ use crate::sst::file::FileId;
 use crate::sst::index::{Indexer, IndexerBuilder};
 use crate::sst::parquet::format::WriteFormat;
 use crate::sst::parquet::helper::parse_parquet_metadata;
-use crate::sst::parquet::{SstInfo, WriteOptions, PARQUET_METADATA_KEY};
+use crate::sst::parquet::{SstInfo, WriteOptions, PARQUET_METADATA_KEY, PARQUET_SORT_BY_KEY};
 use crate::sst::{DEFAULT_WRITE_BUFFER_SIZE, DEFAULT_WRITE_CONCURRENCY};
 
 /// Parquet SST writer.
@@ -226,11 +226,21 @@ where
             .clone()]);
         let seq_col = ColumnPath::new(vec![SEQUENCE_COLUMN_NAME.to_string()]);
 
-        builder
+        let builder = builder
             .set_column_encoding(seq_col.clone(), Encoding::DELTA_BINARY_PACKED)
             .set_column_dictionary_enabled(seq_col, false)
             .set_column_encoding(ts_col.clone(), Encoding::DELTA_BINARY_PACKED)
-            .set_column_dictionary_enabled(ts_col, false)
+            .set_column_dictionary_enabled(ts_col, false);
+
+        // Tags are encoded into the primary key so only field columns are stored
+        // as individual parquet columns.
+        region_metadata
+            .field_columns()
+            .filter(|column| !column.column_schema.is_sst_dictionary_enabled())
+            .fold(builder, |builder, column| {
+                let path = ColumnPath::new(vec![column.column_schema.name.clone()]);
+                builder.set_column_dictionary_enabled(path, false)
+            })
     }
 
     async fn write_next_batch(
@@ -261,12 +271,18 @@ where
             Ok(w)
         } else {
             let json = self.metadata.to_json().context(InvalidMetadataSnafu)?;
-            let key_value_meta = KeyValue::new(PARQUET_METADATA_KEY.to_string(), json);
+            let mut key_value_meta = vec![KeyValue::new(PARQUET_METADATA_KEY.to_string(), json)];
+            if !opts.sort_by.is_empty() {
+                key_value_meta.push(KeyValue::new(
+                    PARQUET_SORT_BY_KEY.to_string(),
+                    opts.sort_by.join(","),
+                ));
+            }
 
             // TODO(yingwen): Find and set proper column encoding for internal columns: op type and tsid.
             let props_builder = WriterProperties::builder()
-                .set_key_value_metadata(Some(vec![key_value_meta]))
-                .set_compression(Compression::ZSTD(ZstdLevel::default()))
+                .set_key_value_metadata(Some(key_value_meta))
+                .set_compression(opts.compression.to_parquet_compression())
                 .set_encoding(Encoding::PLAIN)
                 .set_max_row_group_size(opts.row_group_size);
 
@@ -303,10 +319,18 @@ impl SourceStats {
         }
 
         self.num_rows += batch.num_rows();
+        // Rows may be ordered by other columns, see [WriteOptions::sort_by].
         // Safety: batch is not empty.
+        let unit = batch
+            .timestamps()
+            .data_type()
+            .as_timestamp()
+            .unwrap()
+            .unit();
+        let timestamps = batch.timestamps_native().unwrap();
         let (min_in_batch, max_in_batch) = (
-            batch.first_timestamp().unwrap(),
-            batch.last_timestamp().unwrap(),
+            Timestamp::new(*timestamps.iter().min().unwrap(), unit),
+            Timestamp::new(*timestamps.iter().max().unwrap(), unit),
         );
         if let Some(time_range) = &mut self.time_range {
             time_range.0 = time_range.0.min(min_in_batch);
@@ -62,6 +62,20 @@ impl SstVersion {
         }
     }
 
+    /// Replaces the metadata of files that are still in the version. Files
+    /// not in the version are ignored.
+    ///
+    /// # Panics
+    /// Panics if level of [FileMeta] is greater than [MAX_LEVEL].
+    pub(crate) fn update_files(&mut self, files_to_update: impl Iterator<Item = FileMeta>) {
+        for file in files_to_update {
+            let level = file.level;
+            if let Some(handle) = self.levels[level as usize].files.get_mut(&file.file_id) {
+                *handle = handle.with_meta(file);
+            }
+        }
+    }
+
     /// Remove files from the version.
     ///
     /// # Panics
@@ -119,6 +133,15 @@ impl SstVersion {
             .sum()
     }
 
+    /// Returns the time range of rows in SST files, or `None` if there is no file.
+    pub(crate) fn time_range(&self) -> Option<(Timestamp, Timestamp)> {
+        self.levels
+            .iter()
+            .flat_map(|level_meta| level_meta.files.values())
+            .map(|file_handle| file_handle.meta_ref().time_range)
+            .reduce(|(min, max), (start, end)| (min.min(start), max.max(end)))
+    }
+
     /// Returns SST index files'space occupied in current version.
     pub(crate) fn index_usage(&self) -> u64 {
         self.levels
@@ -227,4 +250,38 @@ mod tests {
             assert!(added_files.contains_key(&f.file_id));
         });
     }
+
+    #[test]
+    fn test_update_files() {
+        let purger = new_noop_file_purger();
+
+        let file = FileMeta {
+            file_id: FileId::random(),
+            ..Default::default()
+        };
+        let mut version = SstVersion::new();
+        version.add_files(purger, std::iter::once(file.clone()));
+        let handle = version.levels()[0].files[&file.file_id].clone();
+        handle.set_compacting(true);
+
+        let updated = FileMeta {
+            index_file_size: 1024,
+            ..file.clone()
+        };
+        // Files not in the version are ignored.
+        let missing = FileMeta {
+            file_id: FileId::random(),
+            ..Default::default()
+        };
+        version.update_files([updated, missing].into_iter());
+
+        let files = &version.levels()[0].files;
+        assert_eq!(1, files.len());
+        let new_handle = &files[&file.file_id];
+        assert_eq!(1024, new_handle.index_size());
+        // The new handle shares the states of the file with the old one.
+        assert!(new_handle.compacting());
+        new_handle.set_compacting(false);
+        assert!(!handle.compacting());
+    }
 }
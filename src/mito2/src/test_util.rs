@@ -1056,7 +1056,11 @@ pub async fn put_rows(engine: &MitoEngine, region_id: RegionId, rows: Rows) {
     let result = engine
         .handle_request(
             region_id,
-            RegionRequest::Put(RegionPutRequest { rows, hint: None }),
+            RegionRequest::Put(RegionPutRequest {
+                rows,
+                hint: None,
+                idempotency_key: None,
+            }),
         )
         .await
         .unwrap();
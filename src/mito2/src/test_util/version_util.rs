@@ -205,9 +205,11 @@ pub(crate) fn apply_edit(
         RegionEdit {
             files_to_add,
             files_to_remove: files_to_remove.to_vec(),
+            files_to_update: vec![],
             compaction_time_window: None,
             flushed_entry_id: None,
             flushed_sequence: None,
+            producer_watermarks: HashMap::new(),
         },
         &[],
         purger,
@@ -15,6 +15,7 @@
 //! Structs and utilities for writing regions.
 
 mod handle_alter;
+mod handle_build_index;
 mod handle_catchup;
 mod handle_close;
 mod handle_compaction;
@@ -52,6 +53,7 @@ use crate::compaction::CompactionScheduler;
 use crate::config::MitoConfig;
 use crate::error::{CreateDirSnafu, JoinSnafu, Result, WorkerStoppedSnafu};
 use crate::flush::{FlushScheduler, WriteBufferManagerImpl, WriteBufferManagerRef};
+use crate::index_build::IndexBuildScheduler;
 use crate::memtable::MemtableBuilderProvider;
 use crate::metrics::{REGION_COUNT, WRITE_STALL_TOTAL};
 use crate::region::{MitoRegionRef, OpeningRegions, OpeningRegionsRef, RegionMap, RegionMapRef};
@@ -120,6 +122,8 @@ pub(crate) struct WorkerGroup {
     flush_job_pool: SchedulerRef,
     /// Compaction background job pool.
     compact_job_pool: SchedulerRef,
+    /// Index build background job pool.
+    index_build_job_pool: SchedulerRef,
     /// Scheduler for file purgers.
     purge_scheduler: SchedulerRef,
     /// Cache.
@@ -154,6 +158,8 @@ impl WorkerGroup {
             .with_buffer_size(Some(config.index.write_buffer_size.as_bytes() as _));
         let flush_job_pool = Arc::new(LocalScheduler::new(config.max_background_flushes));
         let compact_job_pool = Arc::new(LocalScheduler::new(config.max_background_compactions));
+        let index_build_job_pool =
+            Arc::new(LocalScheduler::new(config.max_background_index_builds));
         // We use another scheduler to avoid purge jobs blocking other jobs.
         let purge_scheduler = Arc::new(LocalScheduler::new(config.max_background_purges));
         let write_cache = write_cache_from_config(
@@ -187,6 +193,7 @@ impl WorkerGroup {
                     write_buffer_manager: write_buffer_manager.clone(),
                     flush_job_pool: flush_job_pool.clone(),
                     compact_job_pool: compact_job_pool.clone(),
+                    index_build_job_pool: index_build_job_pool.clone(),
                     purge_scheduler: purge_scheduler.clone(),
                     listener: WorkerListener::default(),
                     cache_manager: cache_manager.clone(),
@@ -206,6 +213,7 @@ impl WorkerGroup {
             workers,
             flush_job_pool,
             compact_job_pool,
+            index_build_job_pool,
             purge_scheduler,
             cache_manager,
         })
@@ -220,6 +228,8 @@ impl WorkerGroup {
         self.compact_job_pool.stop(true).await?;
         // Stops the scheduler gracefully.
         self.flush_job_pool.stop(true).await?;
+        // Stops the scheduler gracefully.
+        self.index_build_job_pool.stop(true).await?;
         // Stops the purge scheduler gracefully.
         self.purge_scheduler.stop(true).await?;
 
@@ -291,6 +301,8 @@ impl WorkerGroup {
         });
         let flush_job_pool = Arc::new(LocalScheduler::new(config.max_background_flushes));
         let compact_job_pool = Arc::new(LocalScheduler::new(config.max_background_compactions));
+        let index_build_job_pool =
+            Arc::new(LocalScheduler::new(config.max_background_index_builds));
         let purge_scheduler = Arc::new(LocalScheduler::new(config.max_background_flushes));
         let puffin_manager_factory = PuffinManagerFactory::new(
             &config.index.aux_path,
@@ -327,6 +339,7 @@ impl WorkerGroup {
                     write_buffer_manager: write_buffer_manager.clone(),
                     flush_job_pool: flush_job_pool.clone(),
                     compact_job_pool: compact_job_pool.clone(),
+                    index_build_job_pool: index_build_job_pool.clone(),
                     purge_scheduler: purge_scheduler.clone(),
                     listener: WorkerListener::new(listener.clone()),
                     cache_manager: cache_manager.clone(),
@@ -346,6 +359,7 @@ impl WorkerGroup {
             workers,
             flush_job_pool,
             compact_job_pool,
+            index_build_job_pool,
             purge_scheduler,
             cache_manager,
         })
@@ -403,6 +417,7 @@ struct WorkerStarter<S> {
     write_buffer_manager: WriteBufferManagerRef,
     compact_job_pool: SchedulerRef,
     flush_job_pool: SchedulerRef,
+    index_build_job_pool: SchedulerRef,
     purge_scheduler: SchedulerRef,
     listener: WorkerListener,
     cache_manager: CacheManagerRef,
@@ -449,10 +464,16 @@ impl<S: LogStore> WorkerStarter<S> {
                 self.compact_job_pool,
                 sender.clone(),
                 self.cache_manager.clone(),
-                self.config,
+                self.config.clone(),
                 self.listener.clone(),
                 self.plugins.clone(),
             ),
+            index_build_scheduler: IndexBuildScheduler::new(
+                self.index_build_job_pool,
+                sender.clone(),
+                self.cache_manager.clone(),
+                self.config,
+            ),
             stalled_requests: StalledRequests::default(),
             listener: self.listener,
             cache_manager: self.cache_manager,
@@ -658,6 +679,8 @@ struct RegionWorkerLoop<S> {
     flush_scheduler: FlushScheduler,
     /// Scheduler for compaction tasks.
     compaction_scheduler: CompactionScheduler,
+    /// Scheduler for index build tasks.
+    index_build_scheduler: IndexBuildScheduler,
     /// Stalled write requests.
     stalled_requests: StalledRequests,
     /// Event listener for tests.
@@ -874,7 +897,16 @@ impl<S: LogStore> RegionWorkerLoop<S> {
                         .await;
                     continue;
                 }
+                DdlRequest::TruncateRange(req) => {
+                    self.handle_truncate_range_request(ddl.region_id, req, ddl.sender)
+                        .await;
+                    continue;
+                }
                 DdlRequest::Catchup(req) => self.handle_catchup_request(ddl.region_id, req).await,
+                DdlRequest::BuildIndex(_) => {
+                    self.handle_build_index_request(ddl.region_id, ddl.sender);
+                    continue;
+                }
             };
 
             ddl.sender.send(res);
@@ -915,6 +947,10 @@ impl<S: LogStore> RegionWorkerLoop<S> {
                 self.handle_manifest_region_change_result(req).await
             }
             BackgroundNotify::RegionEdit(req) => self.handle_region_edit_result(req).await,
+            BackgroundNotify::IndexBuildFinished(req) => {
+                self.handle_index_build_finished(region_id, req)
+            }
+            BackgroundNotify::IndexBuildFailed(req) => self.handle_index_build_failed(req),
         }
     }
 
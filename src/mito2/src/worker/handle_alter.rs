@@ -182,6 +182,8 @@ impl<S> RegionWorkerLoop<S> {
                         region.region_id,
                     )?;
                 }
+                // Only the frontend rejects writes to read-only tables.
+                SetRegionOption::Readonly(_) => {}
             }
         }
         region.version_control.alter_options(current_options);
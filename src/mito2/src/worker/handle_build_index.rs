@@ -0,0 +1,64 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Handles index build requests.
+
+use store_api::storage::RegionId;
+
+use crate::request::{IndexBuildFailed, IndexBuildFinished, OptionOutputTx};
+use crate::worker::RegionWorkerLoop;
+
+impl<S> RegionWorkerLoop<S> {
+    /// Handles the request to build indexes for SSTs of the region.
+    pub(crate) fn handle_build_index_request(
+        &mut self,
+        region_id: RegionId,
+        mut sender: OptionOutputTx,
+    ) {
+        let Some(region) = self.regions.writable_region_or(region_id, &mut sender) else {
+            return;
+        };
+
+        self.index_build_scheduler
+            .schedule_index_build(&region, sender);
+    }
+
+    /// Handles index build finished, applies the edit to the region version.
+    pub(crate) fn handle_index_build_finished(
+        &mut self,
+        region_id: RegionId,
+        request: IndexBuildFinished,
+    ) {
+        let Some(region) = self.regions.get_region(region_id) else {
+            self.index_build_scheduler.on_region_removed(region_id);
+            return;
+        };
+
+        let num_files = request.edit.files_to_update.len();
+        if num_files > 0 {
+            region
+                .version_control
+                .apply_edit(request.edit, &[], region.file_purger.clone());
+        }
+
+        self.index_build_scheduler
+            .on_index_build_finished(&region, num_files);
+    }
+
+    /// Handles index build failure.
+    pub(crate) fn handle_index_build_failed(&mut self, request: IndexBuildFailed) {
+        self.index_build_scheduler
+            .on_index_build_failed(request.region_id, request.err);
+    }
+}
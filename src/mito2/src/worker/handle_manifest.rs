@@ -26,6 +26,7 @@ use store_api::storage::RegionId;
 use crate::cache::file_cache::{FileType, IndexKey};
 use crate::cache::CacheManagerRef;
 use crate::error::{RegionBusySnafu, RegionNotFoundSnafu, Result};
+use crate::index_build::index_enabled;
 use crate::manifest::action::{
     RegionChange, RegionEdit, RegionMetaAction, RegionMetaActionList, RegionTruncate,
 };
@@ -98,7 +99,9 @@ impl<S: LogStore> RegionWorkerLoop<S> {
             }
         };
 
+        let mut need_index_build = false;
         if change_result.result.is_ok() {
+            need_index_build = index_enabled(&region.metadata(), &change_result.new_meta);
             // Apply the metadata to region's version.
             region
                 .version_control
@@ -119,6 +122,12 @@ impl<S: LogStore> RegionWorkerLoop<S> {
         // Handles the stalled requests.
         self.handle_region_stalled_requests(&change_result.region_id)
             .await;
+
+        if need_index_build {
+            // Builds the new indexes for existing SSTs.
+            self.index_build_scheduler
+                .schedule_index_build(&region, OptionOutputTx::none());
+        }
     }
 
     /// Handles region sync request.
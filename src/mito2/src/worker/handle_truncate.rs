@@ -14,14 +14,19 @@
 
 //! Handling truncate related requests.
 
+use std::collections::HashMap;
+
 use common_telemetry::info;
+use snafu::ResultExt;
 use store_api::logstore::LogStore;
+use store_api::region_request::RegionTruncateRangeRequest;
 use store_api::storage::RegionId;
+use tokio::sync::oneshot;
 
-use crate::error::RegionNotFoundSnafu;
-use crate::manifest::action::RegionTruncate;
+use crate::error::{RecvSnafu, RegionNotFoundSnafu};
+use crate::manifest::action::{RegionEdit, RegionTruncate};
 use crate::region::RegionLeaderState;
-use crate::request::{OptionOutputTx, TruncateResult};
+use crate::request::{OptionOutputTx, RegionEditRequest, TruncateResult};
 use crate::worker::RegionWorkerLoop;
 
 impl<S: LogStore> RegionWorkerLoop<S> {
@@ -49,6 +54,80 @@ impl<S: LogStore> RegionWorkerLoop<S> {
         self.handle_manifest_truncate_action(region, truncate, sender);
     }
 
+    /// Removes the SST files whose time ranges are within the time range of the
+    /// request by a region edit.
+    pub(crate) async fn handle_truncate_range_request(
+        &mut self,
+        region_id: RegionId,
+        request: RegionTruncateRangeRequest,
+        mut sender: OptionOutputTx,
+    ) {
+        let Some(region) = self.regions.writable_region_or(region_id, &mut sender) else {
+            return;
+        };
+
+        let version = region.version();
+        // Skips files under compaction, otherwise the output of the compaction
+        // brings their rows back.
+        let files = version
+            .ssts
+            .levels()
+            .iter()
+            .flat_map(|level| level.files())
+            .filter(|file| {
+                let (start, end) = file.time_range();
+                !file.compacting()
+                    && request.time_range.contains(&start)
+                    && request.time_range.contains(&end)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if files.is_empty() {
+            sender.send(Ok(0));
+            return;
+        }
+
+        let removed_rows = files.iter().map(|file| file.num_rows()).sum::<usize>();
+        info!(
+            "Try to truncate time range {:?} of region {}, removing {} files with {} rows",
+            request.time_range,
+            region_id,
+            files.len(),
+            removed_rows
+        );
+        // Prevents compactions from picking the files before the edit is applied.
+        for file in &files {
+            file.set_compacting(true);
+        }
+
+        let edit = RegionEdit {
+            files_to_add: vec![],
+            files_to_remove: files.iter().map(|file| file.meta_ref().clone()).collect(),
+            files_to_update: vec![],
+            compaction_time_window: None,
+            flushed_entry_id: None,
+            flushed_sequence: None,
+            producer_watermarks: HashMap::new(),
+        };
+        let (tx, rx) = oneshot::channel();
+        self.handle_region_edit(RegionEditRequest {
+            region_id,
+            edit,
+            tx,
+        })
+        .await;
+
+        common_runtime::spawn_global(async move {
+            let result = rx.await.context(RecvSnafu).and_then(|result| result);
+            if result.is_err() {
+                for file in &files {
+                    file.set_compacting(false);
+                }
+            }
+            sender.send(result.map(|_| removed_rows));
+        });
+    }
+
     /// Handles truncate result.
     pub(crate) async fn handle_truncate_result(&mut self, truncate_result: TruncateResult) {
         let region_id = truncate_result.region_id;
@@ -286,6 +286,19 @@ impl<S> RegionWorkerLoop<S> {
                 }
             }
 
+            let idempotency_key = sender_req.request.idempotency_key.take();
+            if let Some(key) = &idempotency_key {
+                if region_ctx.is_written(key) {
+                    // Acknowledges the rows delivered again without writing them.
+                    debug!(
+                        "Skip rows already written by producer {} at sequence {}, region: {}",
+                        key.producer_id, key.sequence, region_id
+                    );
+                    sender_req.sender.send(Ok(0));
+                    continue;
+                }
+            }
+
             // Collect requests by region.
             region_ctx.push_mutation(
                 sender_req.request.op_type as i32,
@@ -293,6 +306,9 @@ impl<S> RegionWorkerLoop<S> {
                 sender_req.request.hint,
                 sender_req.sender,
             );
+            if let Some(key) = idempotency_key {
+                region_ctx.push_idempotency_key(key);
+            }
         }
 
         region_ctxs
@@ -19,21 +19,28 @@ use std::{iter, mem};
 use api::v1::region::{DeleteRequests as RegionDeleteRequests, RegionRequestHeader};
 use api::v1::{DeleteRequests, RowDeleteRequests};
 use catalog::CatalogManagerRef;
+use common_catalog::consts::MITO_ENGINE;
+use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
 use common_meta::node_manager::{AffectedRows, NodeManagerRef};
 use common_meta::peer::Peer;
 use common_query::Output;
 use common_telemetry::tracing_context::TracingContext;
+use common_time::range::TimestampRange;
 use futures_util::future;
 use partition::manager::PartitionRuleManagerRef;
 use session::context::QueryContextRef;
 use snafu::{ensure, OptionExt, ResultExt};
+use store_api::mito_engine_options::APPEND_MODE_KEY;
 use table::requests::DeleteRequest as TableDeleteRequest;
 use table::TableRef;
 
 use crate::error::{
     CatalogSnafu, FindRegionLeaderSnafu, InvalidDeleteRequestSnafu, JoinTaskSnafu,
-    MissingTimeIndexColumnSnafu, RequestDeletesSnafu, Result, TableNotFoundSnafu,
+    MissingTimeIndexColumnSnafu, RequestDeletesSnafu, RequestRegionSnafu, Result,
+    TableNotFoundSnafu,
 };
+use crate::readonly::{ensure_table_deletable, ReadonlyStateRef};
 use crate::region_req_factory::RegionRequestFactory;
 use crate::req_convert::common::preprocess_row_delete_requests;
 use crate::req_convert::delete::{ColumnToRow, RowToRegion, TableToRegion};
@@ -42,6 +49,7 @@ pub struct Deleter {
     catalog_manager: CatalogManagerRef,
     partition_manager: PartitionRuleManagerRef,
     node_manager: NodeManagerRef,
+    readonly_state: ReadonlyStateRef,
 }
 
 pub type DeleterRef = Arc<Deleter>;
@@ -51,11 +59,13 @@ impl Deleter {
         catalog_manager: CatalogManagerRef,
         partition_manager: PartitionRuleManagerRef,
         node_manager: NodeManagerRef,
+        readonly_state: ReadonlyStateRef,
     ) -> Self {
         Self {
             catalog_manager,
             partition_manager,
             node_manager,
+            readonly_state,
         }
     }
 
@@ -107,6 +117,7 @@ impl Deleter {
         let table = request.table_name.as_str();
         let table = self.get_table(catalog, schema, table).await?;
         let table_info = table.table_info();
+        ensure_table_deletable(&table_info)?;
 
         let deletes = TableToRegion::new(&table_info, &self.partition_manager)
             .convert(request)
@@ -115,6 +126,59 @@ impl Deleter {
         let affected_rows = self.do_request(deletes, &ctx).await?;
         Ok(affected_rows as _)
     }
+
+    /// Removes the SST files of the table whose rows are all in the time range, so the
+    /// rows in them needn't be deleted one by one. Returns the number of rows in the
+    /// removed files. Tables whose files can't be removed, i.e. tables in append mode
+    /// or of engines other than mito, are skipped.
+    pub async fn handle_table_truncate_range(
+        &self,
+        catalog: &str,
+        schema: &str,
+        table: &str,
+        time_range: TimestampRange,
+    ) -> Result<AffectedRows> {
+        let table = self.get_table(catalog, schema, table).await?;
+        let table_info = table.table_info();
+        ensure_table_deletable(&table_info)?;
+        self.readonly_state.ensure_writable()?;
+
+        let append_mode = table_info
+            .meta
+            .options
+            .extra_options
+            .get(APPEND_MODE_KEY)
+            .is_some_and(|v| v.eq_ignore_ascii_case("true"));
+        if table_info.meta.engine != MITO_ENGINE || append_mode {
+            return Ok(0);
+        }
+
+        let tasks = table_info
+            .region_ids()
+            .into_iter()
+            .map(|region_id| async move {
+                let peer = self
+                    .partition_manager
+                    .find_region_leader(region_id)
+                    .await
+                    .context(FindRegionLeaderSnafu)?;
+                match self
+                    .node_manager
+                    .datanode(&peer)
+                    .await
+                    .truncate_range(region_id, time_range)
+                    .await
+                {
+                    Ok(affected_rows) => Ok(affected_rows),
+                    // The rows are already deleted one by one, the files are removed by
+                    // compaction on datanodes that can't remove them.
+                    Err(e) if e.status_code() == StatusCode::Unsupported => Ok(0),
+                    Err(e) => Err(e).context(RequestRegionSnafu),
+                }
+            });
+        let affected_rows = future::try_join_all(tasks).await?;
+        Ok(affected_rows.into_iter().sum())
+    }
 }
 
 impl Deleter {
@@ -123,6 +187,8 @@ impl Deleter {
         requests: RegionDeleteRequests,
         ctx: &QueryContextRef,
     ) -> Result<AffectedRows> {
+        self.readonly_state.ensure_writable()?;
+
         let request_factory = RegionRequestFactory::new(RegionRequestHeader {
             tracing_context: TracingContext::from_current_span().to_w3c(),
             dbname: ctx.get_db_string(),
@@ -182,6 +248,7 @@ impl Deleter {
             let catalog = ctx.current_catalog();
             let schema = ctx.current_schema();
             let table = self.get_table(catalog, &schema, &req.table_name).await?;
+            ensure_table_deletable(&table.table_info())?;
             let key_column_names = self.key_column_names(&table)?;
 
             let rows = req.rows.as_mut().unwrap();
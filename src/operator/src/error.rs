@@ -337,6 +337,26 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Table `{table_name}` is read-only"))]
+    TableReadonly {
+        table_name: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Table `{table_name}` is being repartitioned, deletes are rejected"))]
+    TableRepartitioning {
+        table_name: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("The instance is read-only"))]
+    InstanceReadonly {
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Table occurs error"))]
     Table {
         #[snafu(implicit)]
@@ -351,6 +371,20 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Cannot insert {} into column {} of type {} without losing data, cast it explicitly",
+        from,
+        column,
+        to
+    ))]
+    NarrowingInsertCast {
+        column: String,
+        from: String,
+        to: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Failed to execute statement"))]
     ExecuteStatement {
         #[snafu(implicit)]
@@ -703,6 +737,49 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Table `{table_name}` would have {columns} columns, exceeding the limit `max_columns_per_table` = {limit}"
+    ))]
+    TooManyColumns {
+        table_name: String,
+        columns: usize,
+        limit: usize,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Schema `{schema}` would have {tables} tables, exceeding the limit `max_tables_per_schema` = {limit}"
+    ))]
+    TooManyTables {
+        schema: String,
+        tables: usize,
+        limit: usize,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Column name `{column}` of table `{table_name}` is reserved, names starting with `{prefix}` are used by internal columns"
+    ))]
+    ReservedColumnName {
+        table_name: String,
+        column: String,
+        prefix: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid default filter `{filter}` of table `{table_name}`"))]
+    InvalidDefaultFilter {
+        table_name: String,
+        filter: String,
+        #[snafu(source)]
+        error: datafusion_common::DataFusionError,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Empty {} expr", name))]
     EmptyDdlExpr {
         name: String,
@@ -815,6 +892,7 @@ impl ErrorExt for Error {
             | Error::SchemaExists { .. }
             | Error::SchemaInUse { .. }
             | Error::ColumnNotFound { .. }
+            | Error::NarrowingInsertCast { .. }
             | Error::BuildRegex { .. }
             | Error::InvalidSchema { .. }
             | Error::ProjectSchema { .. }
@@ -828,6 +906,10 @@ impl ErrorExt for Error {
             | Error::InvalidTableName { .. }
             | Error::InvalidViewName { .. }
             | Error::InvalidFlowName { .. }
+            | Error::TooManyColumns { .. }
+            | Error::TooManyTables { .. }
+            | Error::ReservedColumnName { .. }
+            | Error::InvalidDefaultFilter { .. }
             | Error::InvalidView { .. }
             | Error::InvalidExpr { .. }
             | Error::AdminFunctionNotFound { .. }
@@ -846,6 +928,10 @@ impl ErrorExt for Error {
                 StatusCode::TableAlreadyExists
             }
 
+            Error::TableReadonly { .. }
+            | Error::TableRepartitioning { .. }
+            | Error::InstanceReadonly { .. } => StatusCode::TableReadonly,
+
             Error::NotSupported { .. }
             | Error::ShowCreateTableBaseOnly { .. }
             | Error::SchemaReadOnly { .. } => StatusCode::Unsupported,
@@ -506,6 +506,14 @@ pub(crate) fn to_alter_table_expr(
             }
             .fail();
         }
+        // Repartitioning has to move the existing data to the new regions, which is done
+        // by `StatementExecutor::repartition_table` instead of the alter table procedure.
+        AlterTableOperation::Repartition { .. } => {
+            return NotSupportedSnafu {
+                feat: "ALTER TABLE ... PARTITION ON",
+            }
+            .fail();
+        }
         AlterTableOperation::AddColumns { add_columns } => AlterTableKind::AddColumns(AddColumns {
             add_columns: add_columns
                 .into_iter()
@@ -752,7 +760,7 @@ pub fn to_create_flow_task_expr(
         expire_after: create_flow.expire_after.map(|value| ExpireAfter { value }),
         comment: create_flow.comment.unwrap_or_default(),
         sql: create_flow.query.to_string(),
-        flow_options: HashMap::new(),
+        flow_options: create_flow.flow_options.into_map(),
     })
 }
 
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod alter_coalescer;
+
 use std::sync::Arc;
 
 use ahash::{HashMap, HashMapExt, HashSet, HashSetExt};
@@ -22,7 +24,7 @@ use api::v1::region::{
     RegionRequestHeader,
 };
 use api::v1::{
-    AlterTableExpr, ColumnDataType, ColumnSchema, CreateTableExpr, InsertRequests,
+    AddColumns, AlterTableExpr, ColumnDataType, ColumnSchema, CreateTableExpr, InsertRequests,
     RowInsertRequest, RowInsertRequests, SemanticType,
 };
 use catalog::CatalogManagerRef;
@@ -31,6 +33,7 @@ use common_catalog::consts::{
     default_engine, trace_services_table_name, PARENT_SPAN_ID_COLUMN, SERVICE_NAME_COLUMN,
     TRACE_ID_COLUMN, TRACE_TABLE_NAME, TRACE_TABLE_NAME_SESSION_KEY,
 };
+use common_error::ext::ErrorExt;
 use common_grpc_expr::util::ColumnExpr;
 use common_meta::cache::TableFlownodeSetCacheRef;
 use common_meta::node_manager::{AffectedRows, NodeManagerRef};
@@ -67,10 +70,15 @@ use crate::error::{
     JoinTaskSnafu, RequestInsertsSnafu, Result, TableNotFoundSnafu,
 };
 use crate::expr_helper;
+use crate::insert::alter_coalescer::{
+    AlterCoalescer, CoalescedAlter, DEFAULT_ALTER_COALESCE_WINDOW,
+};
+use crate::readonly::{ensure_table_writable, ReadonlyStateRef};
 use crate::region_req_factory::RegionRequestFactory;
 use crate::req_convert::common::preprocess_row_insert_requests;
 use crate::req_convert::insert::{
-    fill_reqs_with_impure_default, ColumnToRow, RowToRegion, StatementToRegion, TableToRegion,
+    enforce_write_time_bounds, fill_reqs_with_impure_default, ColumnToRow, RowToRegion,
+    StatementToRegion, TableToRegion,
 };
 use crate::statement::StatementExecutor;
 
@@ -79,10 +87,15 @@ pub struct Inserter {
     partition_manager: PartitionRuleManagerRef,
     node_manager: NodeManagerRef,
     table_flownode_set_cache: TableFlownodeSetCacheRef,
+    alter_coalescer: AlterCoalescer,
+    readonly_state: ReadonlyStateRef,
 }
 
 pub type InserterRef = Arc<Inserter>;
 
+/// Max number of retries of a failed on-demand alter.
+const MAX_ALTER_ON_DEMAND_RETRIES: usize = 3;
+
 /// Hint for the table type to create automatically.
 #[derive(Clone)]
 enum AutoCreateTableType {
@@ -131,15 +144,23 @@ impl Inserter {
         partition_manager: PartitionRuleManagerRef,
         node_manager: NodeManagerRef,
         table_flownode_set_cache: TableFlownodeSetCacheRef,
+        readonly_state: ReadonlyStateRef,
     ) -> Self {
         Self {
             catalog_manager,
             partition_manager,
             node_manager,
             table_flownode_set_cache,
+            alter_coalescer: AlterCoalescer::new(DEFAULT_ALTER_COALESCE_WINDOW),
+            readonly_state,
         }
     }
 
+    /// Returns the read-only state of the instance.
+    pub fn readonly_state(&self) -> &ReadonlyStateRef {
+        &self.readonly_state
+    }
+
     pub async fn handle_column_inserts(
         &self,
         requests: InsertRequests,
@@ -346,8 +367,30 @@ impl Inserter {
         table_infos: &HashMap<TableId, Arc<TableInfo>>,
         ctx: &QueryContextRef,
     ) -> Result<Output> {
+        self.readonly_state.ensure_writable()?;
+        for table_info in table_infos.values() {
+            ensure_table_writable(table_info)?;
+        }
+
         // Fill impure default values in the request
-        let requests = fill_reqs_with_impure_default(table_infos, requests)?;
+        let mut requests = fill_reqs_with_impure_default(table_infos, requests)?;
+        let rejected = enforce_write_time_bounds(
+            table_infos,
+            &mut requests,
+            common_time::util::current_time_millis(),
+        );
+        let rejected_rows = rejected.values().sum::<usize>();
+        if rejected_rows > 0 {
+            crate::metrics::DIST_INGEST_REJECTED_ROW_COUNT.inc_by(rejected_rows as u64);
+            let tables = rejected
+                .iter()
+                .map(|(table, rows)| format!("{table}: {rows}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            ctx.set_warning(format!(
+                "Rejected {rejected_rows} rows out of the write time bounds of tables ({tables})"
+            ));
+        }
 
         let write_cost = write_meter!(
             ctx.current_catalog(),
@@ -355,10 +398,11 @@ impl Inserter {
             requests,
             ctx.channel() as u8
         );
+        // Datanodes read the idempotency key of the rows from the query context.
         let request_factory = RegionRequestFactory::new(RegionRequestHeader {
             tracing_context: TracingContext::from_current_span().to_w3c(),
             dbname: ctx.get_db_string(),
-            ..Default::default()
+            query_context: Some(ctx.as_ref().into()),
         });
 
         let InstantAndNormalInsertRequests {
@@ -404,7 +448,7 @@ impl Inserter {
         crate::metrics::DIST_INGEST_ROW_COUNT.inc_by(affected_rows as u64);
         Ok(Output::new(
             OutputData::AffectedRows(affected_rows),
-            OutputMeta::new_with_cost(write_cost as _),
+            OutputMeta::new_with_cost(write_cost as _).with_rejected_rows(rejected_rows),
         ))
     }
 
@@ -508,6 +552,8 @@ impl Inserter {
             match self.get_table(catalog, &schema, &req.table_name).await? {
                 Some(table) => {
                     let table_info = table.table_info();
+                    // Rejects before altering the read-only table.
+                    ensure_table_writable(&table_info)?;
                     if table_info.is_ttl_instant_table() {
                         instant_table_ids.insert(table_info.table_id());
                     }
@@ -543,9 +589,7 @@ impl Inserter {
                     }
                 }
                 if !alter_tables.is_empty() {
-                    // Alter logical tables in batch.
-                    statement_executor
-                        .alter_logical_tables(alter_tables, ctx.clone())
+                    self.alter_tables_on_demand(alter_tables, true, ctx, statement_executor)
                         .await?;
                 }
             }
@@ -564,11 +608,8 @@ impl Inserter {
                     }
                     table_infos.insert(table_info.table_id(), table.table_info());
                 }
-                for alter_expr in alter_tables.into_iter() {
-                    statement_executor
-                        .alter_table_inner(alter_expr, ctx.clone())
-                        .await?;
-                }
+                self.alter_tables_on_demand(alter_tables, false, ctx, statement_executor)
+                    .await?;
             }
 
             AutoCreateTableType::Trace => {
@@ -636,11 +677,8 @@ impl Inserter {
                         table_infos.insert(table_info.table_id(), table.table_info());
                     }
                 }
-                for alter_expr in alter_tables.into_iter() {
-                    statement_executor
-                        .alter_table_inner(alter_expr, ctx.clone())
-                        .await?;
-                }
+                self.alter_tables_on_demand(alter_tables, false, ctx, statement_executor)
+                    .await?;
             }
         }
 
@@ -650,6 +688,112 @@ impl Inserter {
         })
     }
 
+    /// Alters tables to add new columns. Alters of the same table from concurrent
+    /// requests are merged into one.
+    async fn alter_tables_on_demand(
+        &self,
+        alter_exprs: Vec<AlterTableExpr>,
+        is_logical: bool,
+        ctx: &QueryContextRef,
+        statement_executor: &StatementExecutor,
+    ) -> Result<()> {
+        future::try_join_all(alter_exprs.into_iter().map(|alter_expr| {
+            self.alter_table_on_demand(alter_expr, is_logical, ctx, statement_executor)
+        }))
+        .await?;
+        Ok(())
+    }
+
+    async fn alter_table_on_demand(
+        &self,
+        alter_expr: AlterTableExpr,
+        is_logical: bool,
+        ctx: &QueryContextRef,
+        statement_executor: &StatementExecutor,
+    ) -> Result<()> {
+        match self.alter_coalescer.join(alter_expr.clone()) {
+            CoalescedAlter::Leader(mut leader) => {
+                let merged_expr = leader.collect().await;
+                let result = self
+                    .alter_table_with_retry(merged_expr, is_logical, ctx, statement_executor)
+                    .await;
+                leader.finish(result.is_ok());
+                result
+            }
+            CoalescedAlter::Follower(follower) => {
+                crate::metrics::ALTER_ON_DEMAND_COALESCED.inc();
+                if follower.wait().await {
+                    return Ok(());
+                }
+                // The merged alter failed, alters the table by this request itself.
+                self.alter_table_with_retry(alter_expr, is_logical, ctx, statement_executor)
+                    .await
+            }
+        }
+    }
+
+    /// Alters the table to add columns. On failure, it checks the refreshed table
+    /// schema and retries with the columns that are still missing if the failure
+    /// is retryable or caused by a concurrent alter.
+    async fn alter_table_with_retry(
+        &self,
+        mut alter_expr: AlterTableExpr,
+        is_logical: bool,
+        ctx: &QueryContextRef,
+        statement_executor: &StatementExecutor,
+    ) -> Result<()> {
+        let mut retries = 0;
+        loop {
+            let version = self
+                .get_altering_table(&alter_expr)
+                .await?
+                .table_info()
+                .ident
+                .version;
+            crate::metrics::ALTER_ON_DEMAND_SUBMITTED.inc();
+            let result = if is_logical {
+                statement_executor
+                    .alter_logical_tables(vec![alter_expr.clone()], ctx.clone())
+                    .await
+            } else {
+                statement_executor
+                    .alter_table_inner(alter_expr.clone(), ctx.clone())
+                    .await
+            };
+            let Err(err) = result else {
+                return Ok(());
+            };
+
+            let table = self.get_altering_table(&alter_expr).await?;
+            // Other requests may have added the columns.
+            let Some(missing_columns_expr) = missing_columns_alter_expr(&alter_expr, &table) else {
+                return Ok(());
+            };
+            let conflicted = table.table_info().ident.version != version;
+            if retries >= MAX_ALTER_ON_DEMAND_RETRIES
+                || !(conflicted || err.status_code().is_retryable())
+            {
+                return Err(err);
+            }
+            retries += 1;
+            crate::metrics::ALTER_ON_DEMAND_RETRIED.inc();
+            warn!(err; "Failed to alter table {} on demand, retries: {}", alter_expr.table_name, retries);
+            alter_expr = missing_columns_expr;
+        }
+    }
+
+    async fn get_altering_table(&self, alter_expr: &AlterTableExpr) -> Result<TableRef> {
+        self.get_table(
+            &alter_expr.catalog_name,
+            &alter_expr.schema_name,
+            &alter_expr.table_name,
+        )
+        .await?
+        .context(TableNotFoundSnafu {
+            table_name: &alter_expr.table_name,
+        })
+    }
+
     async fn create_physical_table_on_demand(
         &self,
         ctx: &QueryContextRef,
@@ -887,6 +1031,39 @@ impl Inserter {
     }
 }
 
+/// Returns an alter expr with the columns in `alter_expr` that are not in the
+/// table, or `None` if all the columns exist.
+fn missing_columns_alter_expr(
+    alter_expr: &AlterTableExpr,
+    table: &TableRef,
+) -> Option<AlterTableExpr> {
+    let Some(Kind::AddColumns(add_columns)) = &alter_expr.kind else {
+        return None;
+    };
+    let schema = table.schema();
+    let missing_columns = add_columns
+        .add_columns
+        .iter()
+        .filter(|add_column| {
+            add_column
+                .column_def
+                .as_ref()
+                .is_some_and(|def| schema.column_schema_by_name(&def.name).is_none())
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    if missing_columns.is_empty() {
+        return None;
+    }
+
+    Some(AlterTableExpr {
+        kind: Some(Kind::AddColumns(AddColumns {
+            add_columns: missing_columns,
+        })),
+        ..alter_expr.clone()
+    })
+}
+
 fn validate_column_count_match(requests: &RowInsertRequests) -> Result<()> {
     for request in &requests.inserts {
         let rows = request.rows.as_ref().unwrap();
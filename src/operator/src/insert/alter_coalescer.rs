@@ -0,0 +1,257 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Coalesces the on-demand alters of the same table.
+//!
+//! When many concurrent writes introduce new columns to the same table, each
+//! of them would submit its own `ADD COLUMNS` procedure and most of them fail
+//! because of conflicts. The [AlterCoalescer] merges the alters of the same
+//! table submitted within a short window into a single one. The first request
+//! becomes the leader and submits the merged alter, the others wait for its
+//! result.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use api::v1::alter_table_expr::Kind;
+use api::v1::AlterTableExpr;
+use table::table_name::TableName;
+use tokio::sync::watch;
+
+/// Default window to collect alters of the same table.
+pub(crate) const DEFAULT_ALTER_COALESCE_WINDOW: Duration = Duration::from_millis(10);
+
+/// Result of an alter shared with the waiters. `None` means the alter is
+/// still in progress.
+type AlterResultReceiver = watch::Receiver<Option<bool>>;
+
+struct PendingAlter {
+    /// The merged alter expr.
+    expr: AlterTableExpr,
+    /// Notifies the waiters whether the merged alter succeeded.
+    done: watch::Sender<Option<bool>>,
+}
+
+pub(crate) struct AlterCoalescer {
+    window: Duration,
+    pending: Mutex<HashMap<TableName, PendingAlter>>,
+}
+
+/// The role of a request in a coalesced alter.
+pub(crate) enum CoalescedAlter<'a> {
+    /// The request submits the merged alter.
+    Leader(AlterLeader<'a>),
+    /// The request waits for the merged alter submitted by the leader.
+    Follower(AlterFollower),
+}
+
+impl AlterCoalescer {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Joins the pending alter of the table in `expr`, or starts a new one if
+    /// there is no pending alter.
+    pub(crate) fn join(&self, expr: AlterTableExpr) -> CoalescedAlter<'_> {
+        let table_name = TableName::new(&expr.catalog_name, &expr.schema_name, &expr.table_name);
+        let mut pending = self.pending.lock().unwrap();
+        match pending.entry(table_name.clone()) {
+            Entry::Occupied(mut entry) => {
+                merge_add_columns(&mut entry.get_mut().expr, expr);
+                CoalescedAlter::Follower(AlterFollower {
+                    done: entry.get().done.subscribe(),
+                })
+            }
+            Entry::Vacant(entry) => {
+                let (done, _) = watch::channel(None);
+                let _ = entry.insert(PendingAlter { expr, done });
+                CoalescedAlter::Leader(AlterLeader {
+                    coalescer: self,
+                    table_name,
+                    collected: false,
+                    done: None,
+                })
+            }
+        }
+    }
+}
+
+/// Merges the columns to add in `other` into `expr`, skipping columns that
+/// are already in `expr`.
+fn merge_add_columns(expr: &mut AlterTableExpr, other: AlterTableExpr) {
+    let (Some(Kind::AddColumns(add_columns)), Some(Kind::AddColumns(other))) =
+        (&mut expr.kind, other.kind)
+    else {
+        return;
+    };
+
+    for add_column in other.add_columns {
+        let name = add_column.column_def.as_ref().map(|def| def.name.as_str());
+        if !add_columns
+            .add_columns
+            .iter()
+            .any(|c| c.column_def.as_ref().map(|def| def.name.as_str()) == name)
+        {
+            add_columns.add_columns.push(add_column);
+        }
+    }
+}
+
+/// The request that submits the merged alter of a table.
+pub(crate) struct AlterLeader<'a> {
+    coalescer: &'a AlterCoalescer,
+    table_name: TableName,
+    collected: bool,
+    done: Option<watch::Sender<Option<bool>>>,
+}
+
+impl AlterLeader<'_> {
+    /// Waits for the coalescing window and returns the merged alter expr.
+    /// Requests joining later will start a new alter.
+    pub(crate) async fn collect(&mut self) -> AlterTableExpr {
+        tokio::time::sleep(self.coalescer.window).await;
+
+        // Safety: only the leader removes the pending alter it started.
+        let pending = self
+            .coalescer
+            .pending
+            .lock()
+            .unwrap()
+            .remove(&self.table_name)
+            .unwrap();
+        self.collected = true;
+        self.done = Some(pending.done);
+        pending.expr
+    }
+
+    /// Notifies the followers about the result of the merged alter.
+    pub(crate) fn finish(mut self, succeeded: bool) {
+        if let Some(done) = self.done.take() {
+            let _ = done.send(Some(succeeded));
+        }
+    }
+}
+
+impl Drop for AlterLeader<'_> {
+    fn drop(&mut self) {
+        // The leader is cancelled before collecting the alter. Removes the
+        // pending alter so the followers stop waiting.
+        if !self.collected {
+            let _ = self
+                .coalescer
+                .pending
+                .lock()
+                .unwrap()
+                .remove(&self.table_name);
+        }
+    }
+}
+
+/// The request that waits for the merged alter submitted by the leader.
+pub(crate) struct AlterFollower {
+    done: AlterResultReceiver,
+}
+
+impl AlterFollower {
+    /// Waits for the merged alter and returns whether it succeeded. Returns
+    /// false if the leader is cancelled.
+    pub(crate) async fn wait(mut self) -> bool {
+        self.done
+            .wait_for(|result| result.is_some())
+            .await
+            .map(|result| result.unwrap_or(false))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::{AddColumn, AddColumns, ColumnDef};
+
+    use super::*;
+
+    fn new_alter_expr(columns: &[&str]) -> AlterTableExpr {
+        AlterTableExpr {
+            catalog_name: "greptime".to_string(),
+            schema_name: "public".to_string(),
+            table_name: "foo".to_string(),
+            kind: Some(Kind::AddColumns(AddColumns {
+                add_columns: columns
+                    .iter()
+                    .map(|name| AddColumn {
+                        column_def: Some(ColumnDef {
+                            name: name.to_string(),
+                            ..Default::default()
+                        }),
+                        location: None,
+                        add_if_not_exists: true,
+                    })
+                    .collect(),
+            })),
+        }
+    }
+
+    fn column_names(expr: &AlterTableExpr) -> Vec<String> {
+        let Some(Kind::AddColumns(add_columns)) = &expr.kind else {
+            unreachable!()
+        };
+        add_columns
+            .add_columns
+            .iter()
+            .map(|c| c.column_def.as_ref().unwrap().name.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_alters() {
+        let coalescer = AlterCoalescer::new(Duration::from_millis(1));
+        let CoalescedAlter::Leader(mut leader) = coalescer.join(new_alter_expr(&["a", "b"])) else {
+            unreachable!()
+        };
+        let CoalescedAlter::Follower(follower) = coalescer.join(new_alter_expr(&["b", "c"])) else {
+            unreachable!()
+        };
+
+        let expr = leader.collect().await;
+        assert_eq!(vec!["a", "b", "c"], column_names(&expr));
+
+        // Starts a new alter after the leader collects the pending one.
+        let CoalescedAlter::Leader(_) = coalescer.join(new_alter_expr(&["d"])) else {
+            unreachable!()
+        };
+
+        leader.finish(true);
+        assert!(follower.wait().await);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_leader() {
+        let coalescer = AlterCoalescer::new(Duration::from_millis(1));
+        let CoalescedAlter::Leader(leader) = coalescer.join(new_alter_expr(&["a"])) else {
+            unreachable!()
+        };
+        let CoalescedAlter::Follower(follower) = coalescer.join(new_alter_expr(&["b"])) else {
+            unreachable!()
+        };
+
+        drop(leader);
+        assert!(!follower.wait().await);
+        assert!(coalescer.pending.lock().unwrap().is_empty());
+    }
+}
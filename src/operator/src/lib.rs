@@ -22,6 +22,7 @@ pub mod flow;
 pub mod insert;
 pub mod metrics;
 pub mod procedure;
+pub mod readonly;
 pub mod region_req_factory;
 pub mod req_convert;
 pub mod request;
@@ -36,6 +36,11 @@ lazy_static! {
         "table operator ingest rows"
     )
     .unwrap();
+    pub static ref DIST_INGEST_REJECTED_ROW_COUNT: IntCounter = register_int_counter!(
+        "greptime_table_operator_ingest_rejected_rows",
+        "table operator ingest rows rejected by write time bounds"
+    )
+    .unwrap();
     pub static ref DIST_MIRROR_ROW_COUNT: IntCounter = register_int_counter!(
         "greptime_table_operator_mirror_rows",
         "table operator mirror rows"
@@ -62,4 +67,19 @@ lazy_static! {
         &["table_type"]
     )
     .unwrap();
+    pub static ref ALTER_ON_DEMAND_SUBMITTED: IntCounter = register_int_counter!(
+        "greptime_table_operator_alter_on_demand_submitted",
+        "table operator alter procedures submitted on demand"
+    )
+    .unwrap();
+    pub static ref ALTER_ON_DEMAND_COALESCED: IntCounter = register_int_counter!(
+        "greptime_table_operator_alter_on_demand_coalesced",
+        "table operator alters on demand merged into another request's alter"
+    )
+    .unwrap();
+    pub static ref ALTER_ON_DEMAND_RETRIED: IntCounter = register_int_counter!(
+        "greptime_table_operator_alter_on_demand_retried",
+        "table operator alters on demand retried after failure"
+    )
+    .unwrap();
 }
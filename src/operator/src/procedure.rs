@@ -65,6 +65,14 @@ impl ProcedureServiceHandler for ProcedureServiceOperator {
             .context(query_error::ProcedureServiceSnafu)
     }
 
+    async fn cancel_procedure(&self, pid: &str) -> QueryResult<()> {
+        self.procedure_executor
+            .cancel_procedure(&ExecutorContext::default(), pid)
+            .await
+            .map_err(BoxedError::new)
+            .context(query_error::ProcedureServiceSnafu)
+    }
+
     async fn add_region_follower(&self, request: AddRegionFollowerRequest) -> QueryResult<()> {
         self.procedure_executor
             .add_region_follower(&ExecutorContext::default(), request)
@@ -0,0 +1,98 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Read-only mode of the instance and of tables, e.g. to freeze writes in
+//! maintenance windows while keeping reads working.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use snafu::ensure;
+use table::metadata::TableInfo;
+
+use crate::error::{InstanceReadonlySnafu, Result, TableReadonlySnafu, TableRepartitioningSnafu};
+
+pub type ReadonlyStateRef = Arc<ReadonlyState>;
+
+/// Whether the instance rejects writes and DDLs. It's only kept in memory, so
+/// it's reset on restart.
+#[derive(Debug, Default)]
+pub struct ReadonlyState {
+    readonly: AtomicBool,
+}
+
+impl ReadonlyState {
+    /// Returns true if the instance is read-only.
+    pub fn is_readonly(&self) -> bool {
+        self.readonly.load(Ordering::Acquire)
+    }
+
+    /// Sets whether the instance is read-only, returns the previous value.
+    pub fn set_readonly(&self, readonly: bool) -> bool {
+        self.readonly.swap(readonly, Ordering::AcqRel)
+    }
+
+    /// Returns an error if the instance is read-only.
+    pub fn ensure_writable(&self) -> Result<()> {
+        ensure!(!self.is_readonly(), InstanceReadonlySnafu);
+        Ok(())
+    }
+}
+
+/// Returns an error if the table is read-only, i.e. its `readonly` option is set.
+pub fn ensure_table_writable(table_info: &TableInfo) -> Result<()> {
+    ensure!(
+        !table_info.meta.options.readonly(),
+        TableReadonlySnafu {
+            table_name: table_info.full_table_name(),
+        }
+    );
+    Ok(())
+}
+
+/// Returns an error if rows of the table can't be deleted, i.e. the table is read-only
+/// or being repartitioned.
+pub fn ensure_table_deletable(table_info: &TableInfo) -> Result<()> {
+    ensure_table_writable(table_info)?;
+    ensure!(
+        !table_info.meta.options.repartitioning(),
+        TableRepartitioningSnafu {
+            table_name: table_info.full_table_name(),
+        }
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use common_error::ext::ErrorExt;
+    use common_error::status_code::StatusCode;
+
+    use super::*;
+
+    #[test]
+    fn test_readonly_state() {
+        let state = ReadonlyState::default();
+        assert!(!state.is_readonly());
+        state.ensure_writable().unwrap();
+
+        assert!(!state.set_readonly(true));
+        assert!(state.is_readonly());
+        let err = state.ensure_writable().unwrap_err();
+        assert_eq!(StatusCode::TableReadonly, err.status_code());
+
+        assert!(state.set_readonly(false));
+        state.ensure_writable().unwrap();
+    }
+}
@@ -0,0 +1,233 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Enforces the `write.time_bounds` option of tables on insert requests, so
+//! rows from a misconfigured client don't break the pruning and TTL of tables.
+
+use std::sync::Arc;
+
+use ahash::{HashMap, HashMapExt};
+use api::v1::value::ValueData;
+use api::v1::{Rows, SemanticType};
+use common_telemetry::warn;
+use common_time::Timestamp;
+use store_api::storage::{RegionId, TableId};
+use table::metadata::TableInfo;
+use table::requests::WriteTimeBoundsMode;
+
+use crate::insert::InstantAndNormalInsertRequests;
+
+/// Enforces the `write.time_bounds` of tables on the rows of `inserts` written at
+/// `now_millis`. Returns the number of rejected rows of each table, which are
+/// removed from the requests.
+pub fn enforce_write_time_bounds(
+    table_infos: &HashMap<TableId, Arc<TableInfo>>,
+    inserts: &mut InstantAndNormalInsertRequests,
+    now_millis: i64,
+) -> HashMap<String, usize> {
+    let mut rejected = HashMap::new();
+    let requests = inserts
+        .normal_requests
+        .requests
+        .iter_mut()
+        .chain(inserts.instant_requests.requests.iter_mut());
+    for request in requests {
+        let table_id = RegionId::from_u64(request.region_id).table_id();
+        let (Some(table_info), Some(rows)) = (table_infos.get(&table_id), &mut request.rows) else {
+            continue;
+        };
+        let Some((bounds, mode)) = table_info.meta.options.write_time_bounds() else {
+            continue;
+        };
+        let (lower, upper) = bounds.millis_range(now_millis);
+        let out_of_bounds = enforce_rows(
+            rows,
+            lower.map(Timestamp::new_millisecond),
+            upper.map(Timestamp::new_millisecond),
+            mode,
+        );
+        if out_of_bounds == 0 {
+            continue;
+        }
+
+        let table_name = table_info.full_table_name();
+        match mode {
+            WriteTimeBoundsMode::Reject => {
+                *rejected.entry(table_name).or_default() += out_of_bounds;
+            }
+            WriteTimeBoundsMode::Clamp => {
+                warn!("Clamped {out_of_bounds} rows out of the write time bounds of table {table_name}");
+            }
+            WriteTimeBoundsMode::Warn => {
+                warn!(
+                    "Wrote {out_of_bounds} rows out of the write time bounds of table {table_name}"
+                );
+            }
+        }
+    }
+
+    if !rejected.is_empty() {
+        // Removes the requests whose rows are all rejected.
+        let has_rows = |rows: &Option<Rows>| rows.as_ref().map_or(true, |r| !r.rows.is_empty());
+        inserts
+            .normal_requests
+            .requests
+            .retain(|request| has_rows(&request.rows));
+        inserts
+            .instant_requests
+            .requests
+            .retain(|request| has_rows(&request.rows));
+    }
+    rejected
+}
+
+/// Applies the `mode` to rows whose timestamps are out of the inclusive bounds,
+/// returns the number of such rows.
+fn enforce_rows(
+    rows: &mut Rows,
+    lower: Option<Timestamp>,
+    upper: Option<Timestamp>,
+    mode: WriteTimeBoundsMode,
+) -> usize {
+    let Some(ts_index) = rows
+        .schema
+        .iter()
+        .position(|column| column.semantic_type == SemanticType::Timestamp as i32)
+    else {
+        return 0;
+    };
+
+    let mut out_of_bounds = 0;
+    rows.rows.retain_mut(|row| {
+        let Some(value) = row
+            .values
+            .get_mut(ts_index)
+            .and_then(|value| value.value_data.as_mut())
+        else {
+            return true;
+        };
+        let Some(ts) = timestamp_of(value) else {
+            return true;
+        };
+        let clamped = match (lower, upper) {
+            (Some(lower), _) if ts < lower => lower.convert_to_ceil(ts.unit()),
+            (_, Some(upper)) if ts > upper => upper.convert_to(ts.unit()),
+            _ => return true,
+        };
+        out_of_bounds += 1;
+        match mode {
+            WriteTimeBoundsMode::Reject => false,
+            WriteTimeBoundsMode::Clamp => {
+                // The bound may not fit in the unit of the timestamp, which
+                // is then closer to the bound than any other timestamp.
+                if let Some(clamped) = clamped {
+                    set_timestamp(value, clamped.value());
+                }
+                true
+            }
+            WriteTimeBoundsMode::Warn => true,
+        }
+    });
+    out_of_bounds
+}
+
+fn timestamp_of(value: &ValueData) -> Option<Timestamp> {
+    match value {
+        ValueData::TimestampSecondValue(v) => Some(Timestamp::new_second(*v)),
+        ValueData::TimestampMillisecondValue(v) => Some(Timestamp::new_millisecond(*v)),
+        ValueData::TimestampMicrosecondValue(v) => Some(Timestamp::new_microsecond(*v)),
+        ValueData::TimestampNanosecondValue(v) => Some(Timestamp::new_nanosecond(*v)),
+        _ => None,
+    }
+}
+
+fn set_timestamp(value: &mut ValueData, ts: i64) {
+    match value {
+        ValueData::TimestampSecondValue(v)
+        | ValueData::TimestampMillisecondValue(v)
+        | ValueData::TimestampMicrosecondValue(v)
+        | ValueData::TimestampNanosecondValue(v) => *v = ts,
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::{ColumnDataType, ColumnSchema, Row, Value};
+
+    use super::*;
+
+    fn new_rows(timestamps: &[i64]) -> Rows {
+        Rows {
+            schema: vec![ColumnSchema {
+                column_name: "ts".to_string(),
+                datatype: ColumnDataType::TimestampSecond as i32,
+                semantic_type: SemanticType::Timestamp as i32,
+                ..Default::default()
+            }],
+            rows: timestamps
+                .iter()
+                .map(|ts| Row {
+                    values: vec![Value {
+                        value_data: Some(ValueData::TimestampSecondValue(*ts)),
+                    }],
+                })
+                .collect(),
+        }
+    }
+
+    fn timestamps(rows: &Rows) -> Vec<i64> {
+        rows.rows
+            .iter()
+            .map(|row| match row.values[0].value_data {
+                Some(ValueData::TimestampSecondValue(ts)) => ts,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_enforce_rows() {
+        let lower = Some(Timestamp::new_millisecond(10_500));
+        let upper = Some(Timestamp::new_millisecond(20_500));
+
+        let mut rows = new_rows(&[5, 11, 20, 30]);
+        assert_eq!(
+            2,
+            enforce_rows(&mut rows, lower, upper, WriteTimeBoundsMode::Reject)
+        );
+        assert_eq!(vec![11, 20], timestamps(&rows));
+
+        let mut rows = new_rows(&[5, 11, 20, 30]);
+        assert_eq!(
+            2,
+            enforce_rows(&mut rows, lower, upper, WriteTimeBoundsMode::Clamp)
+        );
+        assert_eq!(vec![11, 11, 20, 20], timestamps(&rows));
+
+        let mut rows = new_rows(&[5, 11, 20, 30]);
+        assert_eq!(
+            2,
+            enforce_rows(&mut rows, lower, upper, WriteTimeBoundsMode::Warn)
+        );
+        assert_eq!(vec![5, 11, 20, 30], timestamps(&rows));
+
+        let mut rows = new_rows(&[5, 30]);
+        assert_eq!(
+            0,
+            enforce_rows(&mut rows, None, None, WriteTimeBoundsMode::Reject)
+        );
+        assert_eq!(vec![5, 30], timestamps(&rows));
+    }
+}
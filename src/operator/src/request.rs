@@ -12,26 +12,39 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use api::v1::region::region_request::Body as RegionRequestBody;
 use api::v1::region::{CompactRequest, FlushRequest, RegionRequestHeader};
 use catalog::CatalogManagerRef;
 use common_catalog::build_db_string;
-use common_meta::node_manager::{AffectedRows, NodeManagerRef};
+use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
+use common_meta::ddl::{ExecutorContext, ProcedureExecutorRef};
+use common_meta::node_manager::{AffectedRows, DatanodeRef, NodeManagerRef};
 use common_meta::peer::Peer;
+use common_meta::rpc::ddl::{DdlTask, SubmitDdlTaskRequest};
 use common_telemetry::tracing_context::TracingContext;
 use common_telemetry::{error, info};
+use common_time::Timestamp;
 use futures_util::future;
 use partition::manager::{PartitionInfo, PartitionRuleManagerRef};
 use session::context::QueryContextRef;
 use snafu::prelude::*;
+use store_api::manifest::ManifestVersion;
+use store_api::region_engine::{
+    LabelValuesRequest, RegionManifestSnapshot, SeriesCardinality, SeriesCardinalityRequest,
+};
 use store_api::storage::RegionId;
 use table::requests::{CompactTableRequest, FlushTableRequest};
+use table::table_name::TableName;
+use table::TableRef;
 
 use crate::error::{
-    CatalogSnafu, FindRegionLeaderSnafu, FindTablePartitionRuleSnafu, JoinTaskSnafu,
-    RequestRegionSnafu, Result, TableNotFoundSnafu, UnsupportedRegionRequestSnafu,
+    CatalogSnafu, ExecuteDdlSnafu, FindRegionLeaderSnafu, FindTablePartitionRuleSnafu,
+    JoinTaskSnafu, NotSupportedSnafu, RequestRegionSnafu, Result, TableNotFoundSnafu,
+    UnsupportedRegionRequestSnafu,
 };
 use crate::region_req_factory::RegionRequestFactory;
 
@@ -40,6 +53,7 @@ pub struct Requester {
     catalog_manager: CatalogManagerRef,
     partition_manager: PartitionRuleManagerRef,
     node_manager: NodeManagerRef,
+    procedure_executor: ProcedureExecutorRef,
 }
 
 pub type RequesterRef = Arc<Requester>;
@@ -49,11 +63,13 @@ impl Requester {
         catalog_manager: CatalogManagerRef,
         partition_manager: PartitionRuleManagerRef,
         node_manager: NodeManagerRef,
+        procedure_executor: ProcedureExecutorRef,
     ) -> Self {
         Self {
             catalog_manager,
             partition_manager,
             node_manager,
+            procedure_executor,
         }
     }
 
@@ -152,9 +168,237 @@ impl Requester {
         info!("Handle region manual compaction request: {region_id}");
         self.do_request(vec![request], None, &ctx).await
     }
+
+    /// Handle the request to list the SST files in the manifests of the table regions.
+    pub async fn handle_table_manifest(
+        &self,
+        catalog: &str,
+        schema: &str,
+        table_name: &str,
+    ) -> Result<Vec<(RegionId, RegionManifestSnapshot)>> {
+        let partitions = self
+            .get_table_partitions(catalog, schema, table_name)
+            .await?;
+
+        let tasks = partitions.into_iter().map(|partition| async move {
+            let region_id = partition.id;
+            let snapshot = self
+                .region_datanode(region_id)
+                .await?
+                .region_manifest(region_id)
+                .await
+                .context(RequestRegionSnafu)?
+                .with_context(|| NotSupportedSnafu {
+                    feat: format!("reading the manifest of region {region_id} from this node"),
+                })?;
+            Ok((region_id, snapshot))
+        });
+
+        future::try_join_all(tasks).await
+    }
+
+    /// Handle the request to count the series of the table regions.
+    pub async fn handle_table_series_cardinality(
+        &self,
+        catalog: &str,
+        schema: &str,
+        table_name: &str,
+        label: Option<String>,
+    ) -> Result<Vec<(RegionId, SeriesCardinality)>> {
+        let partitions = self
+            .get_table_partitions(catalog, schema, table_name)
+            .await?;
+
+        let tasks = partitions.into_iter().map(|partition| {
+            let request = SeriesCardinalityRequest {
+                label: label.clone(),
+            };
+            async move {
+                let region_id = partition.id;
+                let cardinality = self.region_series_cardinality(region_id, request).await?;
+                Ok((region_id, cardinality))
+            }
+        });
+
+        future::try_join_all(tasks).await
+    }
+
+    /// Handle the request to list the distinct values of a tag of the table regions.
+    ///
+    /// The values are read from the term dictionaries of the inverted index if the
+    /// region can serve them this way, otherwise from the series of the region.
+    pub async fn handle_table_label_values(
+        &self,
+        catalog: &str,
+        schema: &str,
+        table_name: &str,
+        label: &str,
+    ) -> Result<Vec<(RegionId, Vec<String>)>> {
+        let partitions = self
+            .get_table_partitions(catalog, schema, table_name)
+            .await?;
+
+        let tasks = partitions.into_iter().map(|partition| async move {
+            let region_id = partition.id;
+            let request = LabelValuesRequest {
+                column_name: label.to_string(),
+                start: Timestamp::MIN_SECOND,
+                end: Timestamp::MAX_SECOND,
+            };
+            let values = match self
+                .region_datanode(region_id)
+                .await?
+                .label_values(region_id, request)
+                .await
+            {
+                Ok(Some(values)) => values,
+                // Scans the series if the region or its datanode can't list the values.
+                Ok(None) => {
+                    self.region_label_values_from_series(region_id, label)
+                        .await?
+                }
+                Err(e) if e.status_code() == StatusCode::Unsupported => {
+                    self.region_label_values_from_series(region_id, label)
+                        .await?
+                }
+                Err(e) => return Err(e).context(RequestRegionSnafu),
+            };
+            Ok((region_id, values))
+        });
+
+        future::try_join_all(tasks).await
+    }
+
+    /// Handle the request to list the watermarks of the producers writing to the table regions.
+    pub async fn handle_table_producer_watermarks(
+        &self,
+        catalog: &str,
+        schema: &str,
+        table_name: &str,
+    ) -> Result<Vec<(RegionId, HashMap<String, u64>)>> {
+        let partitions = self
+            .get_table_partitions(catalog, schema, table_name)
+            .await?;
+
+        let tasks = partitions.into_iter().map(|partition| async move {
+            let region_id = partition.id;
+            let watermarks = self
+                .region_datanode(region_id)
+                .await?
+                .producer_watermarks(region_id)
+                .await
+                .context(RequestRegionSnafu)?
+                .with_context(|| NotSupportedSnafu {
+                    feat: format!(
+                        "reading the producer watermarks of region {region_id} from this node"
+                    ),
+                })?;
+            Ok((region_id, watermarks))
+        });
+
+        future::try_join_all(tasks).await
+    }
+
+    /// Handle the request to checkpoint the manifest of the region.
+    pub async fn handle_region_checkpoint(&self, region_id: RegionId) -> Result<ManifestVersion> {
+        info!("Handle region manual checkpoint request: {region_id}");
+        self.region_datanode(region_id)
+            .await?
+            .checkpoint_region(region_id)
+            .await
+            .context(RequestRegionSnafu)?
+            .with_context(|| NotSupportedSnafu {
+                feat: format!("checkpointing the manifest of region {region_id} from this node"),
+            })
+    }
+
+    /// Handle the request to build the missing indexes of the SSTs in the table regions.
+    pub async fn handle_table_build_index(
+        &self,
+        catalog: &str,
+        schema: &str,
+        table_name: &str,
+        ctx: QueryContextRef,
+    ) -> Result<AffectedRows> {
+        info!("Handle table manual index build request: {catalog}.{schema}.{table_name}");
+        let table_id = self
+            .get_table(catalog, schema, table_name)
+            .await?
+            .table_info()
+            .table_id();
+        let request = SubmitDdlTaskRequest {
+            query_context: ctx,
+            task: DdlTask::new_build_index(table_id, TableName::new(catalog, schema, table_name)),
+        };
+        // Builds the indexes in a procedure to report the progress, falls back to
+        // requesting the regions directly if the metasrv doesn't support it.
+        match self
+            .procedure_executor
+            .submit_ddl_task(&ExecutorContext::default(), request)
+            .await
+        {
+            Ok(response) => return Ok(response.affected_rows),
+            Err(e) if e.status_code() == StatusCode::Unsupported => {
+                info!("Build indexes without a procedure: {e}");
+            }
+            Err(e) => return Err(e).context(ExecuteDdlSnafu),
+        }
+
+        let partitions = self
+            .get_table_partitions(catalog, schema, table_name)
+            .await?;
+
+        let tasks = partitions.into_iter().map(|partition| async move {
+            let region_id = partition.id;
+            self.region_datanode(region_id)
+                .await?
+                .build_index(region_id)
+                .await
+                .context(RequestRegionSnafu)
+        });
+
+        let affected_rows = future::try_join_all(tasks).await?;
+        Ok(affected_rows.into_iter().sum())
+    }
 }
 
 impl Requester {
+    async fn region_series_cardinality(
+        &self,
+        region_id: RegionId,
+        request: SeriesCardinalityRequest,
+    ) -> Result<SeriesCardinality> {
+        self.region_datanode(region_id)
+            .await?
+            .series_cardinality(region_id, request)
+            .await
+            .context(RequestRegionSnafu)?
+            .with_context(|| NotSupportedSnafu {
+                feat: format!("counting the series of region {region_id} from this node"),
+            })
+    }
+
+    async fn region_label_values_from_series(
+        &self,
+        region_id: RegionId,
+        label: &str,
+    ) -> Result<Vec<String>> {
+        let request = SeriesCardinalityRequest {
+            label: Some(label.to_string()),
+        };
+        let cardinality = self.region_series_cardinality(region_id, request).await?;
+        Ok(cardinality.label_values.into_keys().collect())
+    }
+
+    async fn region_datanode(&self, region_id: RegionId) -> Result<DatanodeRef> {
+        let peer = self
+            .partition_manager
+            .find_region_leader(region_id)
+            .await
+            .context(FindRegionLeaderSnafu)?;
+        Ok(self.node_manager.datanode(&peer).await)
+    }
+
     async fn do_request(
         &self,
         requests: Vec<RegionRequestBody>,
@@ -211,21 +455,25 @@ impl Requester {
             .context(FindRegionLeaderSnafu)
     }
 
-    async fn get_table_partitions(
-        &self,
-        catalog: &str,
-        schema: &str,
-        table_name: &str,
-    ) -> Result<Vec<PartitionInfo>> {
+    async fn get_table(&self, catalog: &str, schema: &str, table_name: &str) -> Result<TableRef> {
         let table = self
             .catalog_manager
             .table(catalog, schema, table_name, None)
             .await
             .context(CatalogSnafu)?;
 
-        let table = table.with_context(|| TableNotFoundSnafu {
+        table.with_context(|| TableNotFoundSnafu {
             table_name: common_catalog::format_full_table_name(catalog, schema, table_name),
-        })?;
+        })
+    }
+
+    async fn get_table_partitions(
+        &self,
+        catalog: &str,
+        schema: &str,
+        table_name: &str,
+    ) -> Result<Vec<PartitionInfo>> {
+        let table = self.get_table(catalog, schema, table_name).await?;
         let table_info = table.table_info();
 
         self.partition_manager
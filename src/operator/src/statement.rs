@@ -21,6 +21,7 @@ mod cursor;
 mod ddl;
 mod describe;
 mod dml;
+mod schema_limits;
 mod set;
 mod show;
 mod tql;
@@ -32,6 +33,7 @@ use std::time::Duration;
 
 use async_stream::stream;
 use catalog::kvbackend::KvBackendCatalogManager;
+use catalog::schema_limits::SchemaLimitOptions;
 use catalog::CatalogManagerRef;
 use client::{OutputData, RecordBatches};
 use common_error::ext::BoxedError;
@@ -73,7 +75,8 @@ use table::table_reference::TableReference;
 use table::TableRef;
 
 use self::set::{
-    set_bytea_output, set_datestyle, set_search_path, set_timezone, validate_client_encoding,
+    set_auto_add_columns, set_bypass_default_filter, set_bytea_output, set_datestyle,
+    set_search_path, set_timezone, validate_client_encoding,
 };
 use crate::error::{
     self, CatalogSnafu, ExecLogicalPlanSnafu, ExternalSnafu, InvalidSqlSnafu, NotSupportedSnafu,
@@ -94,6 +97,7 @@ pub struct StatementExecutor {
     partition_manager: PartitionRuleManagerRef,
     cache_invalidator: CacheInvalidatorRef,
     inserter: InserterRef,
+    schema_limits: SchemaLimitOptions,
 }
 
 pub type StatementExecutorRef = Arc<StatementExecutor>;
@@ -107,6 +111,7 @@ impl StatementExecutor {
         cache_invalidator: CacheInvalidatorRef,
         inserter: InserterRef,
         table_route_cache: TableRouteCacheRef,
+        schema_limits: SchemaLimitOptions,
     ) -> Self {
         Self {
             catalog_manager,
@@ -118,6 +123,7 @@ impl StatementExecutor {
             partition_manager: Arc::new(PartitionRuleManager::new(kv_backend, table_route_cache)),
             cache_invalidator,
             inserter,
+            schema_limits,
         }
     }
 
@@ -133,7 +139,7 @@ impl StatementExecutor {
         }
     }
 
-    #[tracing::instrument(skip_all)]
+    #[tracing::instrument(skip_all, fields(db = %query_ctx.get_db_string()))]
     pub async fn execute_sql(&self, stmt: Statement, query_ctx: QueryContextRef) -> Result<Output> {
         match stmt {
             Statement::Query(_) | Statement::Explain(_) | Statement::Delete(_) => {
@@ -170,6 +176,8 @@ impl StatementExecutor {
 
             Statement::ShowFlows(stmt) => self.show_flows(stmt, query_ctx).await,
 
+            Statement::ShowProcedures(stmt) => self.show_procedures(stmt, query_ctx).await,
+
             Statement::Copy(sql::statements::copy::Copy::CopyQueryTo(stmt)) => {
                 let query_output = self
                     .plan_exec(QueryStatement::Sql(*stmt.query), query_ctx)
@@ -381,6 +389,10 @@ impl StatementExecutor {
         match var_name.as_str() {
             "READ_PREFERENCE" => set_read_preference(set_var.value, query_ctx)?,
 
+            "BYPASS_DEFAULT_FILTER" => set_bypass_default_filter(set_var.value, query_ctx)?,
+
+            "AUTO_ADD_COLUMNS" => set_auto_add_columns(set_var.value, query_ctx)?,
+
             "TIMEZONE" | "TIME_ZONE" => set_timezone(set_var.value, query_ctx)?,
 
             "BYTEA_OUTPUT" => set_bytea_output(set_var.value, query_ctx)?,
@@ -470,7 +482,7 @@ impl StatementExecutor {
             .context(PlanStatementSnafu)
     }
 
-    #[tracing::instrument(skip_all)]
+    #[tracing::instrument(skip_all, fields(db = %query_ctx.get_db_string()))]
     async fn plan_exec(&self, stmt: QueryStatement, query_ctx: QueryContextRef) -> Result<Output> {
         let timeout = derive_timeout(&stmt, &query_ctx);
         match timeout {
@@ -537,11 +549,14 @@ fn attach_timeout(output: Output, mut timeout: Duration) -> Output {
 
 /// If the relevant variables are set, the timeout is enforced for all PostgreSQL statements.
 /// For MySQL, it applies only to read-only statements.
+/// For gRPC, the timeout comes from the request metadata and applies to SQL and PromQL queries.
 fn derive_timeout(stmt: &QueryStatement, query_ctx: &QueryContextRef) -> Option<Duration> {
     let query_timeout = query_ctx.query_timeout()?;
     match (query_ctx.channel(), stmt) {
         (Channel::Mysql, QueryStatement::Sql(Statement::Query(_)))
-        | (Channel::Postgres, QueryStatement::Sql(_)) => Some(query_timeout),
+        | (Channel::Postgres, QueryStatement::Sql(_))
+        | (Channel::Grpc, QueryStatement::Sql(Statement::Query(_)))
+        | (Channel::Grpc, QueryStatement::Promql(_)) => Some(query_timeout),
         (_, _) => None,
     }
 }
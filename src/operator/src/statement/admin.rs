@@ -81,22 +81,31 @@ impl StatementExecutor {
             state: self.query_engine.engine_state().function_state(),
         };
 
-        let result = admin_func
-            .eval(func_ctx, &args)
-            .await
-            .context(error::ExecuteAdminFunctionSnafu)?;
-
-        let column_schemas = vec![ColumnSchema::new(
-            // Use statement as the result column name
-            stmt.to_string(),
-            admin_func
-                .return_type(&arg_types)
-                .context(error::ExecuteAdminFunctionSnafu)?,
-            false,
-        )];
+        let (column_schemas, columns) = if let Some(column_schemas) = admin_func.output_columns() {
+            // The function outputs a result set.
+            let columns = admin_func
+                .eval_columns(func_ctx, &args)
+                .await
+                .context(error::ExecuteAdminFunctionSnafu)?;
+            (column_schemas, columns)
+        } else {
+            let result = admin_func
+                .eval(func_ctx, &args)
+                .await
+                .context(error::ExecuteAdminFunctionSnafu)?;
+            let column_schemas = vec![ColumnSchema::new(
+                // Use statement as the result column name
+                stmt.to_string(),
+                admin_func
+                    .return_type(&arg_types)
+                    .context(error::ExecuteAdminFunctionSnafu)?,
+                false,
+            )];
+            (column_schemas, vec![result])
+        };
         let schema = Arc::new(Schema::new(column_schemas));
         let batch =
-            RecordBatch::new(schema.clone(), vec![result]).context(error::BuildRecordBatchSnafu)?;
+            RecordBatch::new(schema.clone(), columns).context(error::BuildRecordBatchSnafu)?;
         let batches =
             RecordBatches::try_new(schema, vec![batch]).context(error::BuildRecordBatchSnafu)?;
 
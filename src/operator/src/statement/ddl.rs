@@ -22,62 +22,78 @@ use api::v1::{
 };
 use catalog::CatalogManagerRef;
 use chrono::Utc;
-use common_catalog::consts::{is_readonly_schema, DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_catalog::consts::{
+    is_readonly_schema, DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, MITO_ENGINE,
+};
 use common_catalog::{format_full_flow_name, format_full_table_name};
 use common_error::ext::BoxedError;
 use common_meta::cache_invalidator::Context;
+use common_meta::ddl::repartition_table::{
+    DEFAULT_REPARTITION_ROWS_PER_SECOND, DEFAULT_REPARTITION_ROWS_PER_STEP,
+    REPARTITION_ROWS_PER_SECOND_KEY,
+};
 use common_meta::ddl::ExecutorContext;
 use common_meta::instruction::CacheIdent;
 use common_meta::key::schema_name::{SchemaName, SchemaNameKey};
 use common_meta::key::NAME_PATTERN;
 use common_meta::rpc::ddl::{
-    CreateFlowTask, DdlTask, DropFlowTask, DropViewTask, SubmitDdlTaskRequest,
+    CreateFlowTask, CreateTableTask, DdlTask, DropFlowTask, DropViewTask, SubmitDdlTaskRequest,
     SubmitDdlTaskResponse,
 };
 use common_meta::rpc::router::{Partition, Partition as MetaPartition};
 use common_query::Output;
-use common_telemetry::{debug, info, tracing};
+use common_telemetry::{debug, info, tracing, warn};
 use common_time::Timezone;
+use datafusion_common::TableReference;
+use datatypes::arrow::datatypes::SchemaRef as ArrowSchemaRef;
 use datatypes::prelude::ConcreteDataType;
 use datatypes::schema::{RawSchema, Schema};
 use datatypes::value::Value;
+use futures::TryStreamExt;
 use lazy_static::lazy_static;
 use partition::expr::{Operand, PartitionExpr, RestrictedOp};
 use partition::multi_dim::MultiDimPartitionRule;
 use partition::partition::{PartitionBound, PartitionDef};
+use query::default_filter::parse_default_filter;
 use query::parser::QueryStatement;
 use query::plan::extract_and_rewrite_full_table_names;
+use query::promql::rollup::PromRollup;
 use query::query_engine::DefaultSerializer;
 use query::sql::create_table_stmt;
 use regex::Regex;
 use session::context::QueryContextRef;
 use session::table_name::table_idents_to_full_name;
 use snafu::{ensure, OptionExt, ResultExt};
-use sql::statements::alter::{AlterDatabase, AlterTable};
+use sql::statements::alter::{AlterDatabase, AlterTable, AlterTableOperation};
 use sql::statements::create::{
     CreateExternalTable, CreateFlow, CreateTable, CreateTableLike, CreateView, Partitions,
 };
 use sql::statements::sql_value_to_value;
 use sql::statements::statement::Statement;
-use sqlparser::ast::{Expr, Ident, UnaryOperator, Value as ParserValue};
+use sqlparser::ast::{Expr, Ident, ObjectName, UnaryOperator, Value as ParserValue};
 use store_api::metric_engine_consts::{LOGICAL_TABLE_METADATA_KEY, METRIC_ENGINE_NAME};
+use store_api::region_request::{SetRegionOption, UnsetRegionOption};
 use substrait::{DFLogicalSubstraitConvertor, SubstraitPlan};
 use table::dist_table::DistTable;
 use table::metadata::{self, RawTableInfo, RawTableMeta, TableId, TableInfo, TableType};
-use table::requests::{AlterKind, AlterTableRequest, TableOptions, COMMENT_KEY};
+use table::requests::{
+    AlterKind, AlterTableRequest, TableOptions, COMMENT_KEY, SCAN_DEFAULT_FILTER_KEY,
+};
 use table::table_name::TableName;
 use table::TableRef;
 
 use crate::error::{
     self, AlterExprToRequestSnafu, CatalogSnafu, ColumnDataTypeSnafu, ColumnNotFoundSnafu,
     ConvertSchemaSnafu, CreateLogicalTablesSnafu, CreateTableInfoSnafu, DeserializePartitionSnafu,
-    EmptyDdlExprSnafu, ExtractTableNamesSnafu, FlowNotFoundSnafu, InvalidPartitionRuleSnafu,
-    InvalidPartitionSnafu, InvalidSqlSnafu, InvalidTableNameSnafu, InvalidViewNameSnafu,
-    InvalidViewStmtSnafu, ParseSqlValueSnafu, Result, SchemaInUseSnafu, SchemaNotFoundSnafu,
-    SchemaReadOnlySnafu, SubstraitCodecSnafu, TableAlreadyExistsSnafu, TableMetadataManagerSnafu,
-    TableNotFoundSnafu, UnrecognizedTableOptionSnafu, ViewAlreadyExistsSnafu,
+    EmptyDdlExprSnafu, ExtractTableNamesSnafu, FlowNotFoundSnafu, InvalidDefaultFilterSnafu,
+    InvalidPartitionRuleSnafu, InvalidPartitionSnafu, InvalidSqlSnafu, InvalidTableNameSnafu,
+    InvalidViewNameSnafu, InvalidViewStmtSnafu, ParseSqlValueSnafu, Result, SchemaInUseSnafu,
+    SchemaNotFoundSnafu, SchemaReadOnlySnafu, SubstraitCodecSnafu, TableAlreadyExistsSnafu,
+    TableMetadataManagerSnafu, TableNotFoundSnafu, UnrecognizedTableOptionSnafu,
+    ViewAlreadyExistsSnafu,
 };
 use crate::expr_helper;
+use crate::readonly::ensure_table_writable;
 use crate::statement::show::create_partitions_stmt;
 use crate::statement::StatementExecutor;
 
@@ -167,6 +183,7 @@ impl StatementExecutor {
         partitions: Option<Partitions>,
         query_ctx: QueryContextRef,
     ) -> Result<TableRef> {
+        self.ensure_writable()?;
         ensure!(
             !is_readonly_schema(&create_table.schema_name),
             SchemaReadOnlySnafu {
@@ -202,12 +219,13 @@ impl StatementExecutor {
             .await
             .context(TableMetadataManagerSnafu)?;
 
-        ensure!(
-            schema.is_some(),
-            SchemaNotFoundSnafu {
-                schema_info: &create_table.schema_name,
-            }
-        );
+        let schema = schema.context(SchemaNotFoundSnafu {
+            schema_info: &create_table.schema_name,
+        })?;
+        // The table defaults of the database are options of the mito engine.
+        if create_table.engine == MITO_ENGINE {
+            schema.apply_table_defaults(&mut create_table.table_options);
+        }
 
         // if table exists.
         if let Some(table) = self
@@ -247,9 +265,30 @@ impl StatementExecutor {
             &create_table.schema_name,
             &create_table.table_name,
         );
+        self.ensure_columns_limit(&table_name.to_string(), create_table.column_defs.len())?;
+        self.ensure_tables_limit(
+            &create_table.catalog_name,
+            &create_table.schema_name,
+            1,
+            &query_ctx,
+        )
+        .await?;
 
         let (partitions, partition_cols) = parse_partitions(create_table, partitions, &query_ctx)?;
         let mut table_info = create_table_info(create_table, partition_cols)?;
+        if create_table
+            .table_options
+            .contains_key(SCAN_DEFAULT_FILTER_KEY)
+        {
+            let schema =
+                Schema::try_from(table_info.meta.schema.clone()).context(CreateTableInfoSnafu)?;
+            self.validate_default_filter(
+                &table_info.name,
+                &create_table.table_options,
+                schema.arrow_schema(),
+                &query_ctx,
+            )?;
+        }
 
         let resp = self
             .create_table_procedure(
@@ -286,6 +325,7 @@ impl StatementExecutor {
         create_table_exprs: &[CreateTableExpr],
         query_context: QueryContextRef,
     ) -> Result<Vec<TableRef>> {
+        self.ensure_writable()?;
         let _timer = crate::metrics::DIST_CREATE_TABLES.start_timer();
         ensure!(
             !create_table_exprs.is_empty(),
@@ -303,6 +343,8 @@ impl StatementExecutor {
                 }
             );
         }
+        self.ensure_create_logical_tables_limits(create_table_exprs, &query_context)
+            .await?;
 
         let mut raw_tables_info = create_table_exprs
             .iter()
@@ -355,10 +397,70 @@ impl StatementExecutor {
         expr: CreateFlowExpr,
         query_context: QueryContextRef,
     ) -> Result<Output> {
+        let rollup = match &expr.sink_table_name {
+            Some(sink_table_name) => PromRollup::from_flow_options(
+                &expr.flow_name,
+                &sink_table_name.clone().into(),
+                &expr.flow_options,
+            )
+            .context(error::ParseQuerySnafu)?,
+            None => None,
+        };
+        let catalog_name = expr.catalog_name.clone();
+        let flow_name = expr.flow_name.clone();
         self.create_flow_procedure(expr, query_context).await?;
+
+        let rollups = self.query_engine.engine_state().promql_rollups();
+        match rollup {
+            Some((fingerprint, rollup)) => rollups.register(fingerprint, rollup),
+            // the flow may be replaced by one without rollup
+            None => rollups.unregister(&catalog_name, &flow_name),
+        }
         Ok(Output::new_with_affected_rows(0))
     }
 
+    /// Registers the rollup tables of the flows created with the `promql_rewrite`
+    /// option, so the PromQL queries of this frontend can read them.
+    pub async fn load_prom_rollups(&self) -> Result<()> {
+        let rollups = self.query_engine.engine_state().promql_rollups();
+        let catalogs = self
+            .catalog_manager
+            .catalog_names()
+            .await
+            .context(CatalogSnafu)?;
+        for catalog in catalogs {
+            let flows = self
+                .flow_metadata_manager
+                .flow_name_manager()
+                .flow_names(&catalog)
+                .await
+                .try_collect::<Vec<_>>()
+                .await
+                .context(TableMetadataManagerSnafu)?;
+            for (flow_name, flow) in flows {
+                let Some(flow_info) = self
+                    .flow_metadata_manager
+                    .flow_info_manager()
+                    .get(flow.flow_id())
+                    .await
+                    .context(TableMetadataManagerSnafu)?
+                else {
+                    continue;
+                };
+                match PromRollup::from_flow_options(
+                    &flow_name,
+                    flow_info.sink_table_name(),
+                    flow_info.options(),
+                ) {
+                    Ok(Some((fingerprint, rollup))) => rollups.register(fingerprint, rollup),
+                    Ok(None) => {}
+                    Err(e) => warn!(e; "Failed to load the PromQL rollup of flow {}", flow_name),
+                }
+            }
+        }
+        Ok(())
+    }
+
     async fn create_flow_procedure(
         &self,
         expr: CreateFlowExpr,
@@ -622,12 +724,16 @@ impl StatementExecutor {
         {
             let flow_id = flow.flow_id();
             let task = DropFlowTask {
-                catalog_name,
-                flow_name,
+                catalog_name: catalog_name.clone(),
+                flow_name: flow_name.clone(),
                 flow_id,
                 drop_if_exists,
             };
             self.drop_flow_procedure(task, query_context).await?;
+            self.query_engine
+                .engine_state()
+                .promql_rollups()
+                .unregister(&catalog_name, &flow_name);
 
             Ok(Output::new_with_affected_rows(0))
         } else if drop_if_exists {
@@ -730,6 +836,7 @@ impl StatementExecutor {
         alter_table_exprs: Vec<AlterTableExpr>,
         query_context: QueryContextRef,
     ) -> Result<Output> {
+        self.ensure_writable()?;
         let _timer = crate::metrics::DIST_ALTER_TABLES.start_timer();
         ensure!(
             !alter_table_exprs.is_empty(),
@@ -737,6 +844,8 @@ impl StatementExecutor {
                 name: "alter logical tables"
             }
         );
+        self.ensure_alter_logical_tables_limits(&alter_table_exprs, &query_context)
+            .await?;
 
         self.alter_logical_tables_procedure(alter_table_exprs, query_context)
             .await?;
@@ -763,6 +872,7 @@ impl StatementExecutor {
         drop_if_exists: bool,
         query_context: QueryContextRef,
     ) -> Result<Output> {
+        self.ensure_writable()?;
         let mut tables = Vec::with_capacity(table_names.len());
         for table_name in table_names {
             ensure!(
@@ -783,6 +893,7 @@ impl StatementExecutor {
                 .await
                 .context(CatalogSnafu)?
             {
+                ensure_table_writable(&table.table_info())?;
                 tables.push(table.table_info().table_id());
             } else if drop_if_exists {
                 // DROP TABLE IF EXISTS meets table not found - ignored
@@ -822,6 +933,7 @@ impl StatementExecutor {
         drop_if_exists: bool,
         query_context: QueryContextRef,
     ) -> Result<Output> {
+        self.ensure_writable()?;
         ensure!(
             !is_readonly_schema(&schema),
             SchemaReadOnlySnafu { name: schema }
@@ -858,6 +970,7 @@ impl StatementExecutor {
         table_name: TableName,
         query_context: QueryContextRef,
     ) -> Result<Output> {
+        self.ensure_writable()?;
         ensure!(
             !is_readonly_schema(&table_name.schema_name),
             SchemaReadOnlySnafu {
@@ -878,6 +991,7 @@ impl StatementExecutor {
             .with_context(|| TableNotFoundSnafu {
                 table_name: table_name.to_string(),
             })?;
+        ensure_table_writable(&table.table_info())?;
         let table_id = table.table_info().table_id();
         self.truncate_table_procedure(&table_name, table_id, query_context)
             .await?;
@@ -895,6 +1009,7 @@ impl StatementExecutor {
         table_id: TableId,
         table_info: Arc<TableInfo>,
         expr: AlterTableExpr,
+        query_ctx: &QueryContextRef,
     ) -> Result<bool> {
         let request: AlterTableRequest = common_grpc_expr::alter_expr_to_request(table_id, expr)
             .context(AlterExprToRequestSnafu)?;
@@ -905,6 +1020,11 @@ impl StatementExecutor {
             ..
         } = &request;
 
+        // A read-only table can only be altered to be writable again.
+        if !is_readonly_alter(alter_kind) {
+            ensure_table_writable(&table_info)?;
+        }
+
         if let AlterKind::RenameTable { new_table_name } = alter_kind {
             ensure!(
                 NAME_PATTERN_REG.is_match(new_table_name),
@@ -927,34 +1047,220 @@ impl StatementExecutor {
             }) {
                 return Ok(false);
             }
+
+            let new_columns = columns
+                .iter()
+                .map(|column| &column.column_schema.name)
+                .filter(|name| !column_names.contains(name))
+                .collect::<HashSet<_>>();
+            self.ensure_columns_limit(
+                &table_info.full_table_name(),
+                column_names.len() + new_columns.len(),
+            )?;
         }
 
-        let _ = table_info
+        let new_meta = table_info
             .meta
             .builder_with_alter_kind(table_name, &request.alter_kind)
             .context(error::TableSnafu)?
             .build()
             .context(error::BuildTableMetaSnafu { table_name })?;
+        // The columns referenced by the default filter may be dropped or modified.
+        self.validate_default_filter(
+            table_name,
+            &new_meta.options.extra_options,
+            new_meta.schema.arrow_schema(),
+            query_ctx,
+        )?;
 
         Ok(true)
     }
 
+    /// Returns an error if the instance is read-only.
+    fn ensure_writable(&self) -> Result<()> {
+        self.inserter.readonly_state().ensure_writable()
+    }
+
+    /// Ensures the `scan.default_filter` in the table `options` is a boolean
+    /// expression over the columns in `schema`.
+    fn validate_default_filter(
+        &self,
+        table_name: &str,
+        options: &HashMap<String, String>,
+        schema: &ArrowSchemaRef,
+        query_ctx: &QueryContextRef,
+    ) -> Result<()> {
+        let Some(filter) = options.get(SCAN_DEFAULT_FILTER_KEY) else {
+            return Ok(());
+        };
+        let engine_ctx = self.query_engine.engine_context(query_ctx.clone());
+        let _ = parse_default_filter(
+            engine_ctx.state(),
+            filter,
+            TableReference::bare(table_name),
+            schema,
+        )
+        .context(InvalidDefaultFilterSnafu { table_name, filter })?;
+        Ok(())
+    }
+
     #[tracing::instrument(skip_all)]
     pub async fn alter_table(
         &self,
         alter_table: AlterTable,
         query_context: QueryContextRef,
     ) -> Result<Output> {
+        if let AlterTableOperation::Repartition { partitions } = alter_table.alter_operation {
+            return self
+                .repartition_table(&alter_table.table_name, partitions, query_context)
+                .await;
+        }
+
         let expr = expr_helper::to_alter_table_expr(alter_table, &query_context)?;
         self.alter_table_inner(expr, query_context).await
     }
 
+    /// Repartitions a table into the regions of the new partitions, the rows of the
+    /// table are copied into the new regions by the repartition procedure.
+    #[tracing::instrument(skip_all)]
+    pub async fn repartition_table(
+        &self,
+        table_name: &ObjectName,
+        partitions: Partitions,
+        query_context: QueryContextRef,
+    ) -> Result<Output> {
+        self.ensure_writable()?;
+        let (catalog_name, schema_name, table_name) =
+            table_idents_to_full_name(table_name, &query_context)
+                .map_err(BoxedError::new)
+                .context(error::ExternalSnafu)?;
+        ensure!(
+            !is_readonly_schema(&schema_name),
+            SchemaReadOnlySnafu { name: &schema_name }
+        );
+
+        let table = self
+            .catalog_manager
+            .table(
+                &catalog_name,
+                &schema_name,
+                &table_name,
+                Some(&query_context),
+            )
+            .await
+            .context(CatalogSnafu)?
+            .with_context(|| TableNotFoundSnafu {
+                table_name: format_full_table_name(&catalog_name, &schema_name, &table_name),
+            })?;
+        let table_info = table.table_info();
+        ensure_table_writable(&table_info)?;
+
+        let table_id = table_info.ident.table_id;
+        let physical_table_id = self
+            .table_metadata_manager
+            .table_route_manager()
+            .get_physical_table_id(table_id)
+            .await
+            .context(TableMetadataManagerSnafu)?;
+        ensure!(
+            physical_table_id == table_id,
+            error::NotSupportedSnafu {
+                feat: "ALTER TABLE ... PARTITION ON for logical tables",
+            }
+        );
+
+        let schema = &table_info.meta.schema;
+        let primary_keys = table_info
+            .meta
+            .primary_key_indices
+            .iter()
+            .map(|i| schema.column_schemas()[*i].name.clone())
+            .collect::<Vec<_>>();
+        let create_table = CreateTableExpr {
+            catalog_name: catalog_name.clone(),
+            schema_name: schema_name.clone(),
+            table_name: table_name.clone(),
+            desc: table_info.desc.clone().unwrap_or_default(),
+            column_defs: expr_helper::column_schemas_to_defs(
+                schema.column_schemas().to_vec(),
+                &primary_keys,
+            )?,
+            time_index: schema
+                .timestamp_column()
+                .map(|c| c.name.clone())
+                .unwrap_or_default(),
+            primary_keys,
+            create_if_not_exists: false,
+            table_options: HashMap::from(&table_info.meta.options),
+            table_id: Some(api::v1::TableId { id: table_id }),
+            engine: table_info.meta.engine.clone(),
+        };
+
+        for column in &partitions.column_list {
+            ensure!(
+                schema.contains_column(&column.value),
+                ColumnNotFoundSnafu { msg: &column.value }
+            );
+        }
+        let (partitions, partition_columns) =
+            parse_partitions(&create_table, Some(partitions), &query_context)?;
+        let mut raw_table_info = RawTableInfo::from(table_info.as_ref().clone());
+        raw_table_info.meta.partition_key_indices = partition_columns
+            .iter()
+            .map(|column| {
+                schema
+                    .column_index_by_name(column)
+                    .context(ColumnNotFoundSnafu { msg: column })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let rows_per_second = match query_context.extension(REPARTITION_ROWS_PER_SECOND_KEY) {
+            Some(value) => value.parse().ok().with_context(|| error::InvalidSqlSnafu {
+                err_msg: format!("Invalid {REPARTITION_ROWS_PER_SECOND_KEY}: {value}"),
+            })?,
+            None => DEFAULT_REPARTITION_ROWS_PER_SECOND,
+        };
+
+        let request = SubmitDdlTaskRequest {
+            query_context,
+            task: DdlTask::new_repartition_table(
+                table_id,
+                CreateTableTask::new(
+                    create_table,
+                    partitions.into_iter().map(Into::into).collect(),
+                    raw_table_info,
+                ),
+                rows_per_second,
+                DEFAULT_REPARTITION_ROWS_PER_STEP,
+            ),
+        };
+        self.procedure_executor
+            .submit_ddl_task(&ExecutorContext::default(), request)
+            .await
+            .context(error::ExecuteDdlSnafu)?;
+
+        // Invalidates local cache ASAP.
+        self.cache_invalidator
+            .invalidate(
+                &Context::default(),
+                &[
+                    CacheIdent::TableId(table_id),
+                    CacheIdent::TableName(TableName::new(catalog_name, schema_name, table_name)),
+                ],
+            )
+            .await
+            .context(error::InvalidateTableCacheSnafu)?;
+
+        Ok(Output::new_with_affected_rows(0))
+    }
+
     #[tracing::instrument(skip_all)]
     pub async fn alter_table_inner(
         &self,
         expr: AlterTableExpr,
         query_context: QueryContextRef,
     ) -> Result<Output> {
+        self.ensure_writable()?;
         ensure!(
             !is_readonly_schema(&expr.schema_name),
             SchemaReadOnlySnafu {
@@ -991,7 +1297,8 @@ impl StatementExecutor {
             })?;
 
         let table_id = table.table_info().ident.table_id;
-        let need_alter = self.verify_alter(table_id, table.table_info(), expr.clone())?;
+        let need_alter =
+            self.verify_alter(table_id, table.table_info(), expr.clone(), &query_context)?;
         if !need_alter {
             return Ok(Output::new_with_affected_rows(0));
         }
@@ -1023,6 +1330,8 @@ impl StatementExecutor {
             (req, invalidate_keys)
         } else {
             // This is logical table
+            self.ensure_alter_logical_tables_limits(&[expr.clone()], &query_context)
+                .await?;
             let req = SubmitDdlTaskRequest {
                 query_context,
                 task: DdlTask::new_alter_logical_tables(vec![expr]),
@@ -1083,6 +1392,7 @@ impl StatementExecutor {
         alter_expr: AlterDatabaseExpr,
         query_context: QueryContextRef,
     ) -> Result<Output> {
+        self.ensure_writable()?;
         ensure!(
             !is_readonly_schema(&alter_expr.schema_name),
             SchemaReadOnlySnafu {
@@ -1259,6 +1569,7 @@ impl StatementExecutor {
         options: HashMap<String, String>,
         query_context: QueryContextRef,
     ) -> Result<Output> {
+        self.ensure_writable()?;
         let catalog = query_context.current_catalog();
         ensure!(
             NAME_PATTERN_REG.is_match(catalog),
@@ -1319,6 +1630,19 @@ impl StatementExecutor {
 }
 
 /// Parse partition statement [Partitions] into [MetaPartition] and partition columns.
+/// Returns true if the alter only sets or unsets the `readonly` option of the table.
+fn is_readonly_alter(alter_kind: &AlterKind) -> bool {
+    match alter_kind {
+        AlterKind::SetTableOptions { options } => options
+            .iter()
+            .all(|option| matches!(option, SetRegionOption::Readonly(_))),
+        AlterKind::UnsetTableOptions { keys } => keys
+            .iter()
+            .all(|key| matches!(key, UnsetRegionOption::Readonly)),
+        _ => false,
+    }
+}
+
 fn parse_partitions(
     create_table: &CreateTableExpr,
     partitions: Option<Partitions>,
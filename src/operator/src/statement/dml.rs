@@ -12,14 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use api::v1::alter_table_expr::Kind;
+use api::v1::{AddColumn, AddColumns, AlterTableExpr};
+use common_error::ext::BoxedError;
 use common_query::Output;
 use common_telemetry::tracing;
+use datatypes::arrow::datatypes::{DataType as ArrowDataType, TimeUnit};
+use datatypes::prelude::ConcreteDataType;
+use datatypes::schema::ColumnSchema;
 use query::parser::QueryStatement;
 use session::context::QueryContextRef;
+use session::table_name::table_idents_to_full_name;
+use snafu::{ensure, OptionExt, ResultExt};
 use sql::statements::insert::Insert;
+use sql::statements::query::Query;
 use sql::statements::statement::Statement;
 
-use crate::error::Result;
+use crate::error::{
+    CatalogSnafu, ColumnNotFoundSnafu, ConvertSchemaSnafu, ExternalSnafu, MissingInsertBodySnafu,
+    NarrowingInsertCastSnafu, ParseSqlSnafu, Result, TableNotFoundSnafu,
+};
+use crate::expr_helper;
 use crate::statement::StatementExecutor;
 
 impl StatementExecutor {
@@ -30,10 +43,250 @@ impl StatementExecutor {
             self.inserter
                 .handle_statement_insert(insert.as_ref(), &query_ctx)
                 .await
+        } else if let Some(query) = insert.select_body().context(MissingInsertBodySnafu)? {
+            self.insert_select(insert, query, query_ctx).await
         } else {
             // Slow path: insert with subquery. Execute using query engine.
             let statement = QueryStatement::Sql(Statement::Insert(insert));
             self.plan_exec(statement, query_ctx).await
         }
     }
+
+    /// Executes `INSERT INTO ... SELECT ...`.
+    ///
+    /// The select columns are only allowed to be widened into the table columns,
+    /// e.g. `float` into `double`, narrowing casts must be written explicitly.
+    /// Columns that don't exist in the table are added if the session enables
+    /// `auto_add_columns`. The rows are streamed into the table batch by batch.
+    async fn insert_select(
+        &self,
+        mut insert: Box<Insert>,
+        query: Query,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        let table_name = insert.table_name().context(ParseSqlSnafu)?;
+        let (catalog, schema, table_name) = table_idents_to_full_name(table_name, &query_ctx)
+            .map_err(BoxedError::new)
+            .context(ExternalSnafu)?;
+        let table = self
+            .catalog_manager
+            .table(&catalog, &schema, &table_name, Some(&query_ctx))
+            .await
+            .context(CatalogSnafu)?
+            .context(TableNotFoundSnafu {
+                table_name: &table_name,
+            })?;
+        let table_schema = table.schema();
+
+        let plan = self
+            .plan(
+                &QueryStatement::Sql(Statement::Query(Box::new(query))),
+                query_ctx.clone(),
+            )
+            .await?;
+        let fields = plan.schema().fields();
+
+        let columns = insert.columns();
+        let target_names = if columns.is_empty() {
+            let table_columns = table_schema.column_schemas();
+            if fields.len() < table_columns.len() {
+                // Let the query engine report the mismatch.
+                return self
+                    .plan_exec(QueryStatement::Sql(Statement::Insert(insert)), query_ctx)
+                    .await;
+            }
+            fields
+                .iter()
+                .enumerate()
+                .map(|(i, field)| {
+                    table_columns
+                        .get(i)
+                        .map(|column| column.name.clone())
+                        .unwrap_or_else(|| field.name().clone())
+                })
+                .collect::<Vec<_>>()
+        } else {
+            if columns.len() != fields.len() {
+                return self
+                    .plan_exec(QueryStatement::Sql(Statement::Insert(insert)), query_ctx)
+                    .await;
+            }
+            columns.into_iter().cloned().collect::<Vec<_>>()
+        };
+
+        let mut missing_columns = Vec::new();
+        for (name, field) in target_names.iter().zip(fields.iter()) {
+            match table_schema.column_schema_by_name(name) {
+                Some(column) => {
+                    let to = column.data_type.as_arrow_type();
+                    ensure!(
+                        is_widening_cast(field.data_type(), &to),
+                        NarrowingInsertCastSnafu {
+                            column: name,
+                            from: field.data_type().to_string(),
+                            to: to.to_string(),
+                        }
+                    );
+                }
+                None => {
+                    ensure!(
+                        query_ctx.auto_add_columns(),
+                        ColumnNotFoundSnafu {
+                            msg: format!(
+                                "{name} in table {table_name}, \
+                                 use `SET auto_add_columns = true` to add it automatically"
+                            ),
+                        }
+                    );
+                    let data_type = ConcreteDataType::try_from(field.data_type())
+                        .context(ConvertSchemaSnafu)?;
+                    missing_columns.push(ColumnSchema::new(name, data_type, true));
+                }
+            }
+        }
+
+        if !missing_columns.is_empty() {
+            let added = missing_columns
+                .iter()
+                .map(|column| column.name.clone())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let add_columns = expr_helper::column_schemas_to_defs(missing_columns, &[])?
+                .into_iter()
+                .map(|column_def| AddColumn {
+                    column_def: Some(column_def),
+                    location: None,
+                    add_if_not_exists: true,
+                })
+                .collect();
+            let expr = AlterTableExpr {
+                catalog_name: catalog,
+                schema_name: schema,
+                table_name: table_name.clone(),
+                kind: Some(Kind::AddColumns(AddColumns { add_columns })),
+            };
+            self.alter_table_inner(expr, query_ctx.clone()).await?;
+            query_ctx.set_warning(format!("Added columns {added} to table {table_name}"));
+        }
+
+        insert.set_columns(target_names);
+        let statement = QueryStatement::Sql(Statement::Insert(insert));
+        self.plan_exec(statement, query_ctx).await
+    }
+}
+
+/// Returns whether values of type `from` can be cast into `to` without losing data.
+fn is_widening_cast(from: &ArrowDataType, to: &ArrowDataType) -> bool {
+    use ArrowDataType::*;
+
+    if from == to || from.is_null() {
+        return true;
+    }
+    if let Dictionary(_, value_type) = from {
+        return is_widening_cast(value_type, to);
+    }
+
+    match (from, to) {
+        (Int8, Int16 | Int32 | Int64 | Float32 | Float64) => true,
+        (Int16, Int32 | Int64 | Float32 | Float64) => true,
+        (Int32, Int64 | Float64) => true,
+        (UInt8, UInt16 | UInt32 | UInt64 | Int16 | Int32 | Int64 | Float32 | Float64) => true,
+        (UInt16, UInt32 | UInt64 | Int32 | Int64 | Float32 | Float64) => true,
+        (UInt32, UInt64 | Int64 | Float64) => true,
+        (Float16, Float32 | Float64) => true,
+        (Float32, Float64) => true,
+        (Int8 | Int16 | Int32 | Int64 | UInt8 | UInt16 | UInt32, Timestamp(_, _)) => true,
+        (Date32, Timestamp(_, _)) => true,
+        (Timestamp(from_unit, _), Timestamp(to_unit, _)) => {
+            time_unit_rank(from_unit) <= time_unit_rank(to_unit)
+        }
+        (Utf8 | LargeUtf8 | Utf8View, Utf8 | LargeUtf8 | Utf8View) => true,
+        (Binary | LargeBinary | BinaryView, Binary | LargeBinary | BinaryView) => true,
+        (Decimal128(from_precision, from_scale), Decimal128(to_precision, to_scale)) => {
+            to_scale >= from_scale
+                && (*to_precision as i16 - *to_scale as i16)
+                    >= (*from_precision as i16 - *from_scale as i16)
+        }
+        _ => false,
+    }
+}
+
+fn time_unit_rank(unit: &TimeUnit) -> u8 {
+    match unit {
+        TimeUnit::Second => 0,
+        TimeUnit::Millisecond => 1,
+        TimeUnit::Microsecond => 2,
+        TimeUnit::Nanosecond => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datatypes::arrow::datatypes::Field;
+
+    use super::*;
+
+    #[test]
+    fn test_is_widening_cast() {
+        let widening = [
+            (ArrowDataType::Float32, ArrowDataType::Float64),
+            (ArrowDataType::Int32, ArrowDataType::Int64),
+            (ArrowDataType::UInt16, ArrowDataType::Int32),
+            (ArrowDataType::Int64, ArrowDataType::Int64),
+            (ArrowDataType::Null, ArrowDataType::Float32),
+            (ArrowDataType::Utf8, ArrowDataType::LargeUtf8),
+            (
+                ArrowDataType::Int64,
+                ArrowDataType::Timestamp(TimeUnit::Millisecond, None),
+            ),
+            (
+                ArrowDataType::Timestamp(TimeUnit::Second, None),
+                ArrowDataType::Timestamp(TimeUnit::Nanosecond, None),
+            ),
+            (
+                ArrowDataType::Decimal128(10, 2),
+                ArrowDataType::Decimal128(12, 3),
+            ),
+            (
+                ArrowDataType::Dictionary(
+                    Box::new(ArrowDataType::Int32),
+                    Box::new(ArrowDataType::Utf8),
+                ),
+                ArrowDataType::Utf8,
+            ),
+        ];
+        for (from, to) in widening {
+            assert!(is_widening_cast(&from, &to), "{from} -> {to}");
+        }
+
+        let narrowing = [
+            (ArrowDataType::Float64, ArrowDataType::Float32),
+            (ArrowDataType::Int64, ArrowDataType::Int32),
+            (ArrowDataType::Int64, ArrowDataType::Float64),
+            (ArrowDataType::Int8, ArrowDataType::UInt64),
+            (ArrowDataType::Float64, ArrowDataType::Int64),
+            (ArrowDataType::Utf8, ArrowDataType::Int64),
+            (
+                ArrowDataType::Float64,
+                ArrowDataType::Timestamp(TimeUnit::Millisecond, None),
+            ),
+            (
+                ArrowDataType::Timestamp(TimeUnit::Nanosecond, None),
+                ArrowDataType::Timestamp(TimeUnit::Millisecond, None),
+            ),
+            (
+                ArrowDataType::Decimal128(10, 2),
+                ArrowDataType::Decimal128(10, 1),
+            ),
+            (
+                ArrowDataType::List(Arc::new(Field::new("item", ArrowDataType::Int32, true))),
+                ArrowDataType::Utf8,
+            ),
+        ];
+        for (from, to) in narrowing {
+            assert!(!is_widening_cast(&from, &to), "{from} -> {to}");
+        }
+    }
 }
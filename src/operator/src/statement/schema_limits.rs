@@ -0,0 +1,266 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks of [catalog::schema_limits::SchemaLimitOptions] on creating and altering tables.
+
+use std::collections::{HashMap, HashSet};
+
+use api::v1::alter_table_expr::Kind;
+use api::v1::{AlterTableExpr, CreateTableExpr};
+use catalog::schema_limits::{is_reserved_column_name, RESERVED_COLUMN_PREFIX};
+use common_catalog::format_full_table_name;
+use session::context::QueryContextRef;
+use snafu::{ensure, ResultExt};
+use store_api::metric_engine_consts::LOGICAL_TABLE_METADATA_KEY;
+use table::TableRef;
+
+use crate::error::{
+    CatalogSnafu, ReservedColumnNameSnafu, Result, TooManyColumnsSnafu, TooManyTablesSnafu,
+};
+use crate::statement::StatementExecutor;
+
+impl StatementExecutor {
+    /// Ensures the table doesn't have more columns than `max_columns_per_table`.
+    pub(crate) fn ensure_columns_limit(&self, table_name: &str, columns: usize) -> Result<()> {
+        let limit = self.schema_limits.max_columns_per_table;
+        ensure!(
+            limit == 0 || columns <= limit,
+            TooManyColumnsSnafu {
+                table_name,
+                columns,
+                limit,
+            }
+        );
+        Ok(())
+    }
+
+    /// Ensures creating `new_tables` tables in the schema doesn't exceed
+    /// `max_tables_per_schema`.
+    pub(crate) async fn ensure_tables_limit(
+        &self,
+        catalog: &str,
+        schema: &str,
+        new_tables: usize,
+        query_ctx: &QueryContextRef,
+    ) -> Result<()> {
+        let limit = self.schema_limits.max_tables_per_schema;
+        if limit == 0 || new_tables == 0 {
+            return Ok(());
+        }
+
+        let tables = self
+            .catalog_manager
+            .table_names(catalog, schema, Some(query_ctx))
+            .await
+            .context(CatalogSnafu)?
+            .len()
+            + new_tables;
+        ensure!(
+            tables <= limit,
+            TooManyTablesSnafu {
+                schema,
+                tables,
+                limit,
+            }
+        );
+        Ok(())
+    }
+
+    /// Ensures adding `columns` of logical tables to their physical table doesn't
+    /// make the physical table exceed `max_columns_per_table`.
+    pub(crate) async fn ensure_physical_columns_limit<'a>(
+        &self,
+        catalog: &str,
+        schema: &str,
+        physical_table: &str,
+        columns: impl IntoIterator<Item = &'a str>,
+        query_ctx: &QueryContextRef,
+    ) -> Result<()> {
+        if self.schema_limits.max_columns_per_table == 0 {
+            return Ok(());
+        }
+        // The physical table will be created along with the logical tables.
+        let Some(table) = self
+            .catalog_manager
+            .table(catalog, schema, physical_table, Some(query_ctx))
+            .await
+            .context(CatalogSnafu)?
+        else {
+            return Ok(());
+        };
+
+        let schema_ref = table.schema();
+        let new_columns = columns
+            .into_iter()
+            .filter(|name| schema_ref.column_schema_by_name(name).is_none())
+            .collect::<HashSet<_>>();
+        self.ensure_columns_limit(
+            &format_full_table_name(catalog, schema, physical_table),
+            schema_ref.num_columns() + new_columns.len(),
+        )
+    }
+
+    /// Checks the reserved column names and the limits before creating logical
+    /// tables.
+    pub(crate) async fn ensure_create_logical_tables_limits(
+        &self,
+        create_table_exprs: &[CreateTableExpr],
+        query_ctx: &QueryContextRef,
+    ) -> Result<()> {
+        let mut new_tables: HashMap<(&str, &str), usize> = HashMap::new();
+        let mut physical_columns: HashMap<(&str, &str, &str), Vec<&str>> = HashMap::new();
+        for expr in create_table_exprs {
+            let columns = expr.column_defs.iter().map(|c| c.name.as_str());
+            ensure_no_reserved_columns(&expr.table_name, columns.clone())?;
+            self.ensure_columns_limit(
+                &format_full_table_name(&expr.catalog_name, &expr.schema_name, &expr.table_name),
+                expr.column_defs.len(),
+            )?;
+
+            if self.schema_limits.max_tables_per_schema != 0
+                && !self
+                    .catalog_manager
+                    .table_exists(
+                        &expr.catalog_name,
+                        &expr.schema_name,
+                        &expr.table_name,
+                        Some(query_ctx),
+                    )
+                    .await
+                    .context(CatalogSnafu)?
+            {
+                *new_tables
+                    .entry((expr.catalog_name.as_str(), expr.schema_name.as_str()))
+                    .or_default() += 1;
+            }
+            if let Some(physical_table) = expr.table_options.get(LOGICAL_TABLE_METADATA_KEY) {
+                physical_columns
+                    .entry((
+                        expr.catalog_name.as_str(),
+                        expr.schema_name.as_str(),
+                        physical_table.as_str(),
+                    ))
+                    .or_default()
+                    .extend(columns);
+            }
+        }
+
+        for ((catalog, schema), tables) in new_tables {
+            self.ensure_tables_limit(catalog, schema, tables, query_ctx)
+                .await?;
+        }
+        for ((catalog, schema, physical_table), columns) in physical_columns {
+            self.ensure_physical_columns_limit(catalog, schema, physical_table, columns, query_ctx)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Checks the reserved column names and the limits before adding columns to
+    /// logical tables. The columns are also added to their physical tables.
+    pub(crate) async fn ensure_alter_logical_tables_limits(
+        &self,
+        alter_table_exprs: &[AlterTableExpr],
+        query_ctx: &QueryContextRef,
+    ) -> Result<()> {
+        let mut physical_columns: HashMap<(&str, &str, String), Vec<&str>> = HashMap::new();
+        for expr in alter_table_exprs {
+            let Some(Kind::AddColumns(add_columns)) = &expr.kind else {
+                continue;
+            };
+            let columns = add_columns
+                .add_columns
+                .iter()
+                .filter_map(|c| c.column_def.as_ref().map(|def| def.name.as_str()));
+            ensure_no_reserved_columns(&expr.table_name, columns.clone())?;
+
+            let Some(table) = self
+                .catalog_manager
+                .table(
+                    &expr.catalog_name,
+                    &expr.schema_name,
+                    &expr.table_name,
+                    Some(query_ctx),
+                )
+                .await
+                .context(CatalogSnafu)?
+            else {
+                continue;
+            };
+            let schema = table.schema();
+            let new_columns = columns
+                .clone()
+                .filter(|name| schema.column_schema_by_name(name).is_none())
+                .collect::<HashSet<_>>();
+            self.ensure_columns_limit(
+                &format_full_table_name(&expr.catalog_name, &expr.schema_name, &expr.table_name),
+                schema.num_columns() + new_columns.len(),
+            )?;
+
+            if let Some(physical_table) = physical_table_name(&table) {
+                physical_columns
+                    .entry((
+                        expr.catalog_name.as_str(),
+                        expr.schema_name.as_str(),
+                        physical_table,
+                    ))
+                    .or_default()
+                    .extend(columns);
+            }
+        }
+
+        for ((catalog, schema, physical_table), columns) in physical_columns {
+            self.ensure_physical_columns_limit(
+                catalog,
+                schema,
+                &physical_table,
+                columns,
+                query_ctx,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the name of the physical table if `table` is a logical table of the
+/// metric engine.
+fn physical_table_name(table: &TableRef) -> Option<String> {
+    table
+        .table_info()
+        .meta
+        .options
+        .extra_options
+        .get(LOGICAL_TABLE_METADATA_KEY)
+        .cloned()
+}
+
+/// Ensures none of the `columns` of a logical table uses the prefix reserved
+/// for internal columns of the metric engine, e.g. `__table_id` and `__tsid`.
+fn ensure_no_reserved_columns<'a>(
+    table_name: &str,
+    columns: impl IntoIterator<Item = &'a str>,
+) -> Result<()> {
+    for column in columns {
+        ensure!(
+            !is_reserved_column_name(column),
+            ReservedColumnNameSnafu {
+                table_name,
+                column,
+                prefix: RESERVED_COLUMN_PREFIX,
+            }
+        );
+    }
+    Ok(())
+}
@@ -115,6 +115,51 @@ pub fn set_bytea_output(exprs: Vec<Expr>, ctx: QueryContextRef) -> Result<()> {
     Ok(())
 }
 
+/// Sets whether to skip the `scan.default_filter` of tables, e.g. to audit
+/// the rows hidden by the filters.
+pub fn set_bypass_default_filter(exprs: Vec<Expr>, ctx: QueryContextRef) -> Result<()> {
+    let bypass = parse_bool_variable("bypass_default_filter", exprs)?;
+    ctx.set_bypass_default_filter(bypass);
+    Ok(())
+}
+
+/// Sets whether `INSERT INTO ... SELECT` adds the columns missing in the target
+/// table as nullable fields.
+pub fn set_auto_add_columns(exprs: Vec<Expr>, ctx: QueryContextRef) -> Result<()> {
+    let auto_add = parse_bool_variable("auto_add_columns", exprs)?;
+    ctx.set_auto_add_columns(auto_add);
+    Ok(())
+}
+
+/// Parses the value of a boolean variable, e.g. `true`, `'on'` or `1`.
+fn parse_bool_variable(name: &str, exprs: Vec<Expr>) -> Result<bool> {
+    let Some((value, [])) = exprs.split_first() else {
+        return NotSupportedSnafu {
+            feat: format!("Set variable value must have one and only one value for {name}"),
+        }
+        .fail();
+    };
+    let parsed = match value {
+        Expr::Value(Value::Boolean(value)) => Some(*value),
+        Expr::Value(Value::Number(n, _)) => match n.as_str() {
+            "1" => Some(true),
+            "0" => Some(false),
+            _ => None,
+        },
+        Expr::Value(Value::SingleQuotedString(s))
+        | Expr::Value(Value::DoubleQuotedString(s))
+        | Expr::Identifier(Ident { value: s, .. }) => match s.to_lowercase().as_str() {
+            "true" | "on" => Some(true),
+            "false" | "off" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    };
+    parsed.with_context(|| NotSupportedSnafu {
+        feat: format!("Invalid {name} value {value} in set variable statement"),
+    })
+}
+
 pub fn set_search_path(exprs: Vec<Expr>, ctx: QueryContextRef) -> Result<()> {
     let search_expr = exprs.first().context(NotSupportedSnafu {
         feat: "No search path find in set variable statement",
@@ -25,7 +25,7 @@ use sql::ast::Ident;
 use sql::statements::create::Partitions;
 use sql::statements::show::{
     ShowColumns, ShowCreateFlow, ShowCreateView, ShowDatabases, ShowFlows, ShowIndex, ShowKind,
-    ShowRegion, ShowTableStatus, ShowTables, ShowVariables, ShowViews,
+    ShowProcedures, ShowRegion, ShowTableStatus, ShowTables, ShowVariables, ShowViews,
 };
 use sql::statements::OptionMap;
 use table::metadata::TableType;
@@ -226,6 +226,17 @@ impl StatementExecutor {
             .context(ExecuteStatementSnafu)
     }
 
+    #[tracing::instrument(skip_all)]
+    pub(super) async fn show_procedures(
+        &self,
+        stmt: ShowProcedures,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        query::sql::show_procedures(stmt, &self.query_engine, &self.catalog_manager, query_ctx)
+            .await
+            .context(ExecuteStatementSnafu)
+    }
+
     #[tracing::instrument(skip_all)]
     pub async fn show_create_flow(
         &self,
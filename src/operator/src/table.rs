@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use client::Output;
 use common_base::AffectedRows;
@@ -19,13 +21,17 @@ use common_error::ext::BoxedError;
 use common_function::handlers::TableMutationHandler;
 use common_query::error as query_error;
 use common_query::error::Result as QueryResult;
+use common_time::range::TimestampRange;
 use session::context::QueryContextRef;
 use snafu::ResultExt;
+use store_api::manifest::ManifestVersion;
+use store_api::region_engine::{RegionManifestSnapshot, SeriesCardinality};
 use store_api::storage::RegionId;
 use table::requests::{
     CompactTableRequest, DeleteRequest as TableDeleteRequest, FlushTableRequest,
     InsertRequest as TableInsertRequest,
 };
+use table::table_name::TableName;
 
 use crate::delete::DeleterRef;
 use crate::insert::InserterRef;
@@ -73,6 +79,24 @@ impl TableMutationHandler for TableMutationOperator {
             .context(query_error::TableMutationSnafu)
     }
 
+    async fn truncate_range(
+        &self,
+        table_name: TableName,
+        time_range: TimestampRange,
+        _ctx: QueryContextRef,
+    ) -> QueryResult<AffectedRows> {
+        self.deleter
+            .handle_table_truncate_range(
+                &table_name.catalog_name,
+                &table_name.schema_name,
+                &table_name.table_name,
+                time_range,
+            )
+            .await
+            .map_err(BoxedError::new)
+            .context(query_error::TableMutationSnafu)
+    }
+
     async fn flush(
         &self,
         request: FlushTableRequest,
@@ -120,4 +144,109 @@ impl TableMutationHandler for TableMutationOperator {
             .map_err(BoxedError::new)
             .context(query_error::TableMutationSnafu)
     }
+
+    async fn table_manifest(
+        &self,
+        table_name: TableName,
+        _ctx: QueryContextRef,
+    ) -> QueryResult<Vec<(RegionId, RegionManifestSnapshot)>> {
+        self.requester
+            .handle_table_manifest(
+                &table_name.catalog_name,
+                &table_name.schema_name,
+                &table_name.table_name,
+            )
+            .await
+            .map_err(BoxedError::new)
+            .context(query_error::TableMutationSnafu)
+    }
+
+    async fn table_series_cardinality(
+        &self,
+        table_name: TableName,
+        label: Option<String>,
+        _ctx: QueryContextRef,
+    ) -> QueryResult<Vec<(RegionId, SeriesCardinality)>> {
+        self.requester
+            .handle_table_series_cardinality(
+                &table_name.catalog_name,
+                &table_name.schema_name,
+                &table_name.table_name,
+                label,
+            )
+            .await
+            .map_err(BoxedError::new)
+            .context(query_error::TableMutationSnafu)
+    }
+
+    async fn table_label_values(
+        &self,
+        table_name: TableName,
+        label: String,
+        _ctx: QueryContextRef,
+    ) -> QueryResult<Vec<(RegionId, Vec<String>)>> {
+        self.requester
+            .handle_table_label_values(
+                &table_name.catalog_name,
+                &table_name.schema_name,
+                &table_name.table_name,
+                &label,
+            )
+            .await
+            .map_err(BoxedError::new)
+            .context(query_error::TableMutationSnafu)
+    }
+
+    async fn table_producer_watermarks(
+        &self,
+        table_name: TableName,
+        _ctx: QueryContextRef,
+    ) -> QueryResult<Vec<(RegionId, HashMap<String, u64>)>> {
+        self.requester
+            .handle_table_producer_watermarks(
+                &table_name.catalog_name,
+                &table_name.schema_name,
+                &table_name.table_name,
+            )
+            .await
+            .map_err(BoxedError::new)
+            .context(query_error::TableMutationSnafu)
+    }
+
+    async fn checkpoint_region(
+        &self,
+        region_id: RegionId,
+        _ctx: QueryContextRef,
+    ) -> QueryResult<ManifestVersion> {
+        self.requester
+            .handle_region_checkpoint(region_id)
+            .await
+            .map_err(BoxedError::new)
+            .context(query_error::TableMutationSnafu)
+    }
+
+    async fn build_index(
+        &self,
+        table_name: TableName,
+        ctx: QueryContextRef,
+    ) -> QueryResult<AffectedRows> {
+        self.requester
+            .handle_table_build_index(
+                &table_name.catalog_name,
+                &table_name.schema_name,
+                &table_name.table_name,
+                ctx,
+            )
+            .await
+            .map_err(BoxedError::new)
+            .context(query_error::TableMutationSnafu)
+    }
+
+    fn set_readonly(&self, readonly: bool) -> bool {
+        self.inserter.readonly_state().set_readonly(readonly)
+    }
+
+    fn is_readonly(&self) -> bool {
+        self.inserter.readonly_state().is_readonly()
+    }
 }
@@ -150,25 +150,11 @@ impl PartitionRuleManager {
     }
 
     pub async fn find_table_partition_rule(&self, table_id: TableId) -> Result<PartitionRuleRef> {
-        let partitions = self.find_table_partitions(table_id).await?;
-
-        let partition_columns = partitions[0].partition.partition_columns();
-
-        let regions = partitions
-            .iter()
-            .map(|x| x.id.region_number())
-            .collect::<Vec<RegionNumber>>();
-
-        let exprs = partitions
-            .iter()
-            .filter_map(|x| match &x.partition.partition_bounds()[0] {
-                PartitionBound::Expr(e) => Some(e.clone()),
-                _ => None,
-            })
-            .collect::<Vec<_>>();
-
-        let rule = MultiDimPartitionRule::try_new(partition_columns.clone(), regions, exprs)?;
-        Ok(Arc::new(rule) as _)
+        let region_routes = &self
+            .find_physical_table_route(table_id)
+            .await?
+            .region_routes;
+        create_partition_rule_from_region_routes(table_id, region_routes)
     }
 
     /// Find the leader of the region.
@@ -196,6 +182,37 @@ impl PartitionRuleManager {
     }
 }
 
+/// Creates the partition rule of the regions in `region_routes`, they don't have to be
+/// the current regions of the table, e.g. the new regions of a repartitioned table.
+pub fn create_partition_rule_from_region_routes(
+    table_id: TableId,
+    region_routes: &[RegionRoute],
+) -> Result<PartitionRuleRef> {
+    ensure!(
+        !region_routes.is_empty(),
+        error::FindTableRoutesSnafu { table_id }
+    );
+    let partitions = create_partitions_from_region_routes(table_id, region_routes)?;
+
+    let partition_columns = partitions[0].partition.partition_columns();
+
+    let regions = partitions
+        .iter()
+        .map(|x| x.id.region_number())
+        .collect::<Vec<RegionNumber>>();
+
+    let exprs = partitions
+        .iter()
+        .filter_map(|x| match &x.partition.partition_bounds()[0] {
+            PartitionBound::Expr(e) => Some(e.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let rule = MultiDimPartitionRule::try_new(partition_columns.clone(), regions, exprs)?;
+    Ok(Arc::new(rule) as _)
+}
+
 fn create_partitions_from_region_routes(
     table_id: TableId,
     region_routes: &[RegionRoute],
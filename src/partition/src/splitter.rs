@@ -16,10 +16,16 @@ use std::collections::HashMap;
 
 use api::helper;
 use api::v1::{ColumnSchema, Row, Rows};
+use common_error::ext::BoxedError;
+use common_meta::ddl::RegionRowSplitter;
+use common_meta::error::{self as meta_error, Result as MetaResult};
+use common_meta::rpc::router::RegionRoute;
 use datatypes::value::Value;
-use store_api::storage::RegionNumber;
+use snafu::ResultExt;
+use store_api::storage::{RegionNumber, TableId};
 
 use crate::error::Result;
+use crate::manager::create_partition_rule_from_region_routes;
 use crate::PartitionRuleRef;
 
 pub struct RowSplitter {
@@ -48,6 +54,36 @@ impl RowSplitter {
     }
 }
 
+/// Splits rows by the partition rule of the given regions. It moves the rows of a
+/// table into its new regions when repartitioning the table.
+#[derive(Debug, Default)]
+pub struct PartitionRowSplitter;
+
+impl RegionRowSplitter for PartitionRowSplitter {
+    fn split(
+        &self,
+        table_id: TableId,
+        region_routes: &[RegionRoute],
+        rows: Rows,
+    ) -> MetaResult<HashMap<RegionNumber, Rows>> {
+        // A single region has no partition rule to evaluate.
+        if let [region_route] = region_routes {
+            return Ok(HashMap::from([(
+                region_route.region.id.region_number(),
+                rows,
+            )]));
+        }
+
+        let partition_rule = create_partition_rule_from_region_routes(table_id, region_routes)
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)?;
+        RowSplitter::new(partition_rule)
+            .split(rows)
+            .map_err(BoxedError::new)
+            .context(meta_error::ExternalSnafu)
+    }
+}
+
 struct SplitReadRowHelper<'a> {
     schema: Vec<ColumnSchema>,
     rows: Vec<Row>,
@@ -71,6 +71,33 @@ impl SelectInfo {
     }
 }
 
+/// How the OpenTelemetry log transform stores map-typed log bodies. The body is
+/// always stored in the `body` string column as well.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LogBodyStructure {
+    /// Only stores the body as a string.
+    #[default]
+    String,
+    /// Flattens the keys of the body into `body.<key>` columns, nested keys are
+    /// joined by `.`.
+    Flatten,
+    /// Stores the body in the `body_json` JSON column.
+    Json,
+}
+
+impl LogBodyStructure {
+    /// Parses the structure from `flatten` or `json`, case-insensitively.
+    pub fn parse(value: &str) -> Option<Self> {
+        if value.eq_ignore_ascii_case("flatten") {
+            Some(Self::Flatten)
+        } else if value.eq_ignore_ascii_case("json") {
+            Some(Self::Json)
+        } else {
+            None
+        }
+    }
+}
+
 pub const GREPTIME_INTERNAL_IDENTITY_PIPELINE_NAME: &str = "greptime_identity";
 pub const GREPTIME_INTERNAL_TRACE_PIPELINE_V0_NAME: &str = "greptime_trace_v0";
 pub const GREPTIME_INTERNAL_TRACE_PIPELINE_V1_NAME: &str = "greptime_trace_v1";
@@ -105,7 +132,7 @@ impl PipelineDefinition {
 }
 
 pub enum PipelineWay {
-    OtlpLogDirect(Box<SelectInfo>),
+    OtlpLogDirect(Box<SelectInfo>, LogBodyStructure),
     Pipeline(PipelineDefinition),
     OtlpTraceDirectV0,
     OtlpTraceDirectV1,
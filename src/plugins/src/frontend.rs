@@ -14,8 +14,11 @@
 
 use auth::UserProviderRef;
 use common_base::Plugins;
-use frontend::error::{IllegalAuthConfigSnafu, Result};
+use common_time::Timezone;
+use frontend::error::{IllegalAuthConfigSnafu, InvalidPromqlTimezoneSnafu, Result};
 use frontend::frontend::FrontendOptions;
+use query::promql::RateFirstSamplePolicy;
+use query::query_engine::options::QueryOptions;
 use snafu::ResultExt;
 
 use crate::options::PluginOptions;
@@ -31,6 +34,68 @@ pub async fn setup_frontend_plugins(
             auth::user_provider_from_option(user_provider).context(IllegalAuthConfigSnafu)?;
         plugins.insert::<UserProviderRef>(provider);
     }
+    if let Some(timezone) = fe_opts.promql_timezone.as_ref() {
+        let timezone =
+            Timezone::from_tz_string(timezone).context(InvalidPromqlTimezoneSnafu { timezone })?;
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.promql_timezone = Some(timezone);
+        plugins.insert(query_options);
+    }
+    if fe_opts.promql_enable_latest_at {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.promql_enable_latest_at = true;
+        plugins.insert(query_options);
+    }
+    if fe_opts.promql_enable_negative_offset {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.promql_enable_negative_offset = true;
+        plugins.insert(query_options);
+    }
+    if let Some(lookahead) = fe_opts.promql_max_at_lookahead {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.promql_max_at_lookahead = Some(lookahead);
+        plugins.insert(query_options);
+    }
+    if fe_opts.promql_integer_counts {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.promql_integer_counts = true;
+        plugins.insert(query_options);
+    }
+    if fe_opts.promql_created_timestamps {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.promql_created_timestamps = true;
+        plugins.insert(query_options);
+    }
+    if fe_opts.promql_rate_first_sample != RateFirstSamplePolicy::default() {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.promql_rate_first_sample = fe_opts.promql_rate_first_sample;
+        plugins.insert(query_options);
+    }
+    if fe_opts.promql_fill_forward {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.promql_fill_forward = true;
+        plugins.insert(query_options);
+    }
+    if fe_opts.promql_propagate_nan {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.promql_propagate_nan = true;
+        plugins.insert(query_options);
+    }
+    if fe_opts.promql_resolve_bucket_suffix {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.promql_resolve_bucket_suffix = true;
+        plugins.insert(query_options);
+    }
+    if let Some(delta) = fe_opts.promql_staleness_delta {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.promql_staleness_delta = Some(delta);
+        plugins.insert(query_options);
+    }
+    if let Some(max_result_bytes) = fe_opts.max_result_bytes {
+        let mut query_options = plugins.get::<QueryOptions>().unwrap_or_default();
+        query_options.max_result_bytes = Some(max_result_bytes.as_bytes());
+        plugins.insert(query_options);
+    }
     Ok(())
 }
 
@@ -0,0 +1,94 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use datafusion::arrow::array::{Float64Array, TimestampMillisecondArray};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::DFSchema;
+use datafusion::logical_expr::{EmptyRelation, LogicalPlan};
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::prelude::SessionContext;
+use promql::extension_plan::InstantManipulate;
+
+const DAY_MS: i64 = 86_400_000;
+const STEP_MS: i64 = 15_000;
+const LOOKBACK_MS: i64 = 300_000;
+
+/// Builds a plan aligning one series of `num_samples` evenly spread over a day.
+fn build_plan(num_samples: i64) -> Arc<dyn ExecutionPlan> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Millisecond, None),
+            false,
+        ),
+        Field::new("value", DataType::Float64, true),
+    ]));
+    let gap = DAY_MS / num_samples;
+    let timestamps = (0..num_samples).map(|i| i * gap).collect::<Vec<_>>();
+    let values = (0..num_samples).map(|i| i as f64).collect::<Vec<_>>();
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(TimestampMillisecondArray::from(timestamps)),
+            Arc::new(Float64Array::from(values)),
+        ],
+    )
+    .unwrap();
+    let input = Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None).unwrap());
+
+    let placeholder = LogicalPlan::EmptyRelation(EmptyRelation {
+        produce_one_row: false,
+        schema: Arc::new(DFSchema::empty()),
+    });
+    InstantManipulate::new(
+        0,
+        DAY_MS,
+        LOOKBACK_MS,
+        STEP_MS,
+        "timestamp".to_string(),
+        Some("value".to_string()),
+        placeholder,
+    )
+    .to_execution_plan(input)
+}
+
+fn bench_instant_manipulate(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let session_context = SessionContext::default();
+
+    let mut group = c.benchmark_group("instant_manipulate");
+    for num_samples in [10, 5_760, 86_400] {
+        let plan = build_plan(num_samples);
+        group.bench_function(format!("1d_15s_step_{num_samples}_samples"), |b| {
+            b.iter(|| {
+                let result = runtime
+                    .block_on(datafusion::physical_plan::collect(
+                        plan.clone(),
+                        session_context.task_ctx(),
+                    ))
+                    .unwrap();
+                black_box(result);
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_instant_manipulate);
+criterion_main!(benches);
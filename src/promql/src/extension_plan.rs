@@ -12,7 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod absent;
+mod ema_over_steps;
 mod empty_metric;
+mod fill_forward;
 mod histogram_fold;
 mod instant_manipulate;
 mod normalize;
@@ -20,21 +23,37 @@ mod planner;
 mod range_manipulate;
 mod scalar_calculate;
 mod series_divide;
+mod stream_aggregate;
 #[cfg(test)]
 mod test_util;
+mod topk;
 mod union_distinct_on;
 
+pub use absent::{Absent, AbsentExec, AbsentStream};
 use datafusion::arrow::datatypes::{ArrowPrimitiveType, TimestampMillisecondType};
-pub use empty_metric::{build_special_time_expr, EmptyMetric, EmptyMetricExec, EmptyMetricStream};
+pub use ema_over_steps::{EmaOverSteps, EmaOverStepsExec, EmaOverStepsStream};
+pub use empty_metric::{
+    build_special_time_expr, EmptyMetric, EmptyMetricExec, EmptyMetricStream, INTERVAL_COLUMN,
+    RANGE_END_COLUMN, RANGE_START_COLUMN,
+};
+pub use fill_forward::{FillForward, FillForwardExec, FillForwardStream};
 pub use histogram_fold::{HistogramFold, HistogramFoldExec, HistogramFoldStream};
 pub use instant_manipulate::{InstantManipulate, InstantManipulateExec, InstantManipulateStream};
 pub use normalize::{SeriesNormalize, SeriesNormalizeExec, SeriesNormalizeStream};
-pub use planner::PromExtensionPlanner;
+pub use planner::{register_promql_extensions, PromExtensionPlanner, PromQueryPlanner};
 pub use range_manipulate::{RangeManipulate, RangeManipulateExec, RangeManipulateStream};
 pub use scalar_calculate::ScalarCalculate;
 pub use series_divide::{SeriesDivide, SeriesDivideExec, SeriesDivideStream};
+pub use stream_aggregate::{
+    StreamAggregate, StreamAggregateExec, StreamAggregateFunc, StreamAggregateStream,
+};
+pub use topk::{TopK, TopKExec, TopKStream};
 pub use union_distinct_on::{UnionDistinctOn, UnionDistinctOnExec, UnionDistinctOnStream};
 
 pub type Millisecond = <TimestampMillisecondType as ArrowPrimitiveType>::Native;
 
 const METRIC_NUM_SERIES: &str = "num_series";
+const METRIC_NUM_DUPLICATES: &str = "num_duplicates";
+const METRIC_ROWS_EXAMINED: &str = "rows_examined";
+const METRIC_ROWS_EMITTED: &str = "rows_emitted";
+const METRIC_PEAK_BUFFERED_ROWS: &str = "peak_buffered_rows";
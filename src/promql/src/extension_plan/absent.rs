@@ -0,0 +1,533 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use datafusion::arrow::array::{
+    ArrayRef, AsArray, Float64Array, StringArray, TimestampMillisecondArray,
+};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{
+    DataType, Field, SchemaRef, TimeUnit, TimestampMillisecondType,
+};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::{DFSchema, DFSchemaRef};
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::context::TaskContext;
+use datafusion::logical_expr::{Expr, LogicalPlan, UserDefinedLogicalNodeCore};
+use datafusion::physical_expr::EquivalenceProperties;
+use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
+use datafusion::physical_plan::metrics::{
+    BaselineMetrics, Count, ExecutionPlanMetricsSet, MetricBuilder, MetricValue, MetricsSet,
+};
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, Distribution, ExecutionPlan, Partitioning, PlanProperties,
+    RecordBatchStream, SendableRecordBatchStream,
+};
+use futures::{ready, Stream, StreamExt};
+
+use crate::extension_plan::{Millisecond, METRIC_ROWS_EMITTED};
+
+/// `Absent` emits a sample with value 1 at every step between `start` and `end`
+/// where the input has no row, like PromQL's `absent_over_time()`.
+///
+/// The input is the stepped output of the selector, and only its time index is
+/// read. The output series is labeled with `fake_labels`, which are derived from
+/// the equality matchers of the selector since there is no input series to take
+/// the labels from.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Absent {
+    start: Millisecond,
+    end: Millisecond,
+    interval: Millisecond,
+    time_index_column: String,
+    value_column: String,
+    fake_labels: Vec<(String, String)>,
+    input: LogicalPlan,
+    output_schema: DFSchemaRef,
+}
+
+impl PartialOrd for Absent {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        // Compare fields in order excluding output_schema
+        match self.start.partial_cmp(&other.start) {
+            Some(core::cmp::Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.end.partial_cmp(&other.end) {
+            Some(core::cmp::Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.interval.partial_cmp(&other.interval) {
+            Some(core::cmp::Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.time_index_column.partial_cmp(&other.time_index_column) {
+            Some(core::cmp::Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.value_column.partial_cmp(&other.value_column) {
+            Some(core::cmp::Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.fake_labels.partial_cmp(&other.fake_labels) {
+            Some(core::cmp::Ordering::Equal) => {}
+            ord => return ord,
+        }
+        self.input.partial_cmp(&other.input)
+    }
+}
+
+impl UserDefinedLogicalNodeCore for Absent {
+    fn name(&self) -> &str {
+        Self::name()
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.input]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.output_schema
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "PromAbsent: range=[{}..{}], interval=[{}], time index=[{}], labels={:?}",
+            self.start, self.end, self.interval, self.time_index_column, self.fake_labels
+        )
+    }
+
+    fn with_exprs_and_inputs(
+        &self,
+        _exprs: Vec<Expr>,
+        inputs: Vec<LogicalPlan>,
+    ) -> DataFusionResult<Self> {
+        if inputs.is_empty() {
+            return Err(DataFusionError::Internal(
+                "Absent must have at least one input".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            start: self.start,
+            end: self.end,
+            interval: self.interval,
+            time_index_column: self.time_index_column.clone(),
+            value_column: self.value_column.clone(),
+            fake_labels: self.fake_labels.clone(),
+            input: inputs.into_iter().next().unwrap(),
+            output_schema: self.output_schema.clone(),
+        })
+    }
+}
+
+impl Absent {
+    pub fn new(
+        start: Millisecond,
+        end: Millisecond,
+        interval: Millisecond,
+        time_index_column: String,
+        value_column: String,
+        fake_labels: Vec<(String, String)>,
+        input: LogicalPlan,
+    ) -> DataFusionResult<Self> {
+        // check the time index exists in input
+        input
+            .schema()
+            .qualified_field_with_unqualified_name(&time_index_column)?;
+
+        let fields = Some(Field::new(
+            &time_index_column,
+            DataType::Timestamp(TimeUnit::Millisecond, None),
+            false,
+        ))
+        .into_iter()
+        .chain(Some(Field::new(&value_column, DataType::Float64, true)))
+        .chain(
+            fake_labels
+                .iter()
+                .map(|(name, _)| Field::new(name, DataType::Utf8, true)),
+        )
+        .map(|field| (None, Arc::new(field)))
+        .collect();
+        let output_schema = Arc::new(DFSchema::new_with_metadata(fields, HashMap::new())?);
+
+        Ok(Self {
+            start,
+            end,
+            interval,
+            time_index_column,
+            value_column,
+            fake_labels,
+            input,
+            output_schema,
+        })
+    }
+
+    pub const fn name() -> &'static str {
+        "Absent"
+    }
+
+    pub fn to_execution_plan(&self, exec_input: Arc<dyn ExecutionPlan>) -> Arc<dyn ExecutionPlan> {
+        let schema: SchemaRef = Arc::new(self.output_schema.as_ref().into());
+        let properties = AbsentExec::compute_properties(schema.clone());
+        Arc::new(AbsentExec {
+            start: self.start,
+            end: self.end,
+            interval: self.interval,
+            time_index_column: self.time_index_column.clone(),
+            fake_labels: self.fake_labels.clone(),
+            schema,
+            input: exec_input,
+            metric: ExecutionPlanMetricsSet::new(),
+            properties,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct AbsentExec {
+    start: Millisecond,
+    end: Millisecond,
+    interval: Millisecond,
+    time_index_column: String,
+    fake_labels: Vec<(String, String)>,
+    schema: SchemaRef,
+    input: Arc<dyn ExecutionPlan>,
+    metric: ExecutionPlanMetricsSet,
+    properties: PlanProperties,
+}
+
+impl AbsentExec {
+    /// The output is a single partition ordered by time, and is emitted after
+    /// all input is consumed.
+    fn compute_properties(schema: SchemaRef) -> PlanProperties {
+        PlanProperties::new(
+            EquivalenceProperties::new(schema),
+            Partitioning::UnknownPartitioning(1),
+            EmissionType::Final,
+            Boundedness::Bounded,
+        )
+    }
+}
+
+impl ExecutionPlan for AbsentExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    // Whether a step is absent depends on all input rows.
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        vec![Distribution::SinglePartition]
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![false; self.children().len()]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        assert!(!children.is_empty());
+        Ok(Arc::new(Self {
+            start: self.start,
+            end: self.end,
+            interval: self.interval,
+            time_index_column: self.time_index_column.clone(),
+            fake_labels: self.fake_labels.clone(),
+            schema: self.schema.clone(),
+            input: children[0].clone(),
+            metric: self.metric.clone(),
+            properties: self.properties.clone(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let baseline_metric = BaselineMetrics::new(&self.metric, partition);
+        let rows_emitted = Count::new();
+        MetricBuilder::new(&self.metric)
+            .with_partition(partition)
+            .build(MetricValue::Count {
+                name: METRIC_ROWS_EMITTED.into(),
+                count: rows_emitted.clone(),
+            });
+
+        let input = self.input.execute(partition, context)?;
+        let time_index = input.schema().index_of(&self.time_index_column)?;
+        // a step is absent until a row of it shows up
+        let num_steps = if self.interval > 0 && self.end >= self.start {
+            ((self.end - self.start) / self.interval + 1) as usize
+        } else {
+            1
+        };
+
+        Ok(Box::pin(AbsentStream {
+            start: self.start,
+            interval: self.interval,
+            time_index,
+            fake_labels: self.fake_labels.clone(),
+            present: vec![false; num_steps],
+            done: false,
+            schema: self.schema.clone(),
+            input,
+            metric: baseline_metric,
+            rows_emitted,
+        }))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metric.clone_inner())
+    }
+
+    fn name(&self) -> &str {
+        "AbsentExec"
+    }
+}
+
+impl DisplayAs for AbsentExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(
+                    f,
+                    "PromAbsentExec: range=[{}..{}], interval=[{}], time index=[{}], labels={:?}",
+                    self.start, self.end, self.interval, self.time_index_column, self.fake_labels
+                )
+            }
+        }
+    }
+}
+
+pub struct AbsentStream {
+    start: Millisecond,
+    interval: Millisecond,
+    time_index: usize,
+    fake_labels: Vec<(String, String)>,
+    /// Whether each step has any input row.
+    present: Vec<bool>,
+    /// Whether the output is emitted.
+    done: bool,
+    schema: SchemaRef,
+    input: SendableRecordBatchStream,
+    metric: BaselineMetrics,
+    /// Number of absent steps emitted.
+    rows_emitted: Count,
+}
+
+impl RecordBatchStream for AbsentStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl Stream for AbsentStream {
+    type Item = DataFusionResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if self.done {
+                return Poll::Ready(None);
+            }
+
+            match ready!(self.input.poll_next_unpin(cx)) {
+                Some(Ok(batch)) => {
+                    let timer = self.metric.elapsed_compute().timer();
+                    let result = self.mark_present(&batch);
+                    timer.done();
+                    if let Err(e) = result {
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => {
+                    self.done = true;
+                    let timer = self.metric.elapsed_compute().timer();
+                    let output = self.build_output();
+                    timer.done();
+                    match output {
+                        Ok(Some(batch)) => {
+                            return self.metric.record_poll(Poll::Ready(Some(Ok(batch))))
+                        }
+                        Ok(None) => return Poll::Ready(None),
+                        Err(e) => return Poll::Ready(Some(Err(e))),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl AbsentStream {
+    /// Marks the steps that have rows in the batch.
+    fn mark_present(&mut self, batch: &RecordBatch) -> DataFusionResult<()> {
+        let ts_column = cast(
+            batch.column(self.time_index),
+            &DataType::Timestamp(TimeUnit::Millisecond, None),
+        )?;
+        let ts_column = ts_column.as_primitive::<TimestampMillisecondType>();
+        for ts in ts_column.iter().flatten() {
+            let offset = ts - self.start;
+            let step = if self.interval > 0 {
+                if offset % self.interval != 0 {
+                    continue;
+                }
+                offset / self.interval
+            } else {
+                offset
+            };
+            if step >= 0 && (step as usize) < self.present.len() {
+                self.present[step as usize] = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds a row for each absent step, or None if all steps are present.
+    fn build_output(&mut self) -> DataFusionResult<Option<RecordBatch>> {
+        let timestamps = self
+            .present
+            .iter()
+            .enumerate()
+            .filter(|(_, present)| !**present)
+            .map(|(step, _)| self.start + step as Millisecond * self.interval)
+            .collect::<Vec<_>>();
+        if timestamps.is_empty() {
+            return Ok(None);
+        }
+        let num_rows = timestamps.len();
+        self.rows_emitted.add(num_rows);
+
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(TimestampMillisecondArray::from(timestamps)),
+            Arc::new(Float64Array::from(vec![1.0; num_rows])),
+        ];
+        for (_, value) in &self.fake_labels {
+            columns.push(Arc::new(StringArray::from(vec![value.as_str(); num_rows])));
+        }
+        Ok(Some(RecordBatch::try_new(self.schema.clone(), columns)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::arrow::datatypes::Schema;
+    use datafusion::physical_plan::memory::MemoryExec;
+    use datafusion::prelude::SessionContext;
+
+    use super::*;
+
+    async fn do_absent_test(timestamps: Vec<i64>, expected: &str) {
+        let input_schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "timestamp",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                true,
+            ),
+            Field::new("value", DataType::Float64, true),
+        ]));
+        let num_rows = timestamps.len();
+        let data = RecordBatch::try_new(
+            input_schema.clone(),
+            vec![
+                Arc::new(TimestampMillisecondArray::from(timestamps)),
+                Arc::new(Float64Array::from(vec![1.0; num_rows])),
+            ],
+        )
+        .unwrap();
+        let memory_exec = Arc::new(MemoryExec::try_new(&[vec![data]], input_schema, None).unwrap());
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "timestamp",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                false,
+            ),
+            Field::new("value", DataType::Float64, true),
+            Field::new("job", DataType::Utf8, true),
+        ]));
+        let absent_exec = Arc::new(AbsentExec {
+            start: 0,
+            end: 40_000,
+            interval: 10_000,
+            time_index_column: "timestamp".to_string(),
+            fake_labels: vec![("job".to_string(), "x".to_string())],
+            properties: AbsentExec::compute_properties(schema.clone()),
+            schema,
+            input: memory_exec,
+            metric: ExecutionPlanMetricsSet::new(),
+        });
+        let session_context = SessionContext::default();
+        let result = datafusion::physical_plan::collect(absent_exec, session_context.task_ctx())
+            .await
+            .unwrap();
+        let result_literal = datatypes::arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string();
+
+        assert_eq!(result_literal, expected);
+    }
+
+    #[tokio::test]
+    async fn absent_steps_with_fake_labels() {
+        let expected = "+---------------------+-------+-----+\
+            \n| timestamp           | value | job |\
+            \n+---------------------+-------+-----+\
+            \n| 1970-01-01T00:00:00 | 1.0   | x   |\
+            \n| 1970-01-01T00:00:20 | 1.0   | x   |\
+            \n| 1970-01-01T00:00:40 | 1.0   | x   |\
+            \n+---------------------+-------+-----+";
+        do_absent_test(vec![10_000, 30_000, 30_000], expected).await;
+    }
+
+    #[tokio::test]
+    async fn empty_input() {
+        let expected = "+---------------------+-------+-----+\
+            \n| timestamp           | value | job |\
+            \n+---------------------+-------+-----+\
+            \n| 1970-01-01T00:00:00 | 1.0   | x   |\
+            \n| 1970-01-01T00:00:10 | 1.0   | x   |\
+            \n| 1970-01-01T00:00:20 | 1.0   | x   |\
+            \n| 1970-01-01T00:00:30 | 1.0   | x   |\
+            \n| 1970-01-01T00:00:40 | 1.0   | x   |\
+            \n+---------------------+-------+-----+";
+        do_absent_test(vec![], expected).await;
+    }
+
+    #[tokio::test]
+    async fn no_absent_step() {
+        do_absent_test(vec![0, 10_000, 20_000, 30_000, 40_000], "++\n++").await;
+    }
+}
@@ -0,0 +1,487 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use datafusion::arrow::array::{
+    Array, AsArray, Float64Array, TimestampMillisecondArray, UInt32Array,
+};
+use datafusion::arrow::compute::{concat_batches, take};
+use datafusion::arrow::datatypes::{Float64Type, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::DFSchemaRef;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::context::TaskContext;
+use datafusion::logical_expr::{Expr, LogicalPlan, UserDefinedLogicalNodeCore};
+use datafusion::physical_expr::EquivalenceProperties;
+use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
+use datafusion::physical_plan::metrics::{
+    BaselineMetrics, Count, ExecutionPlanMetricsSet, MetricBuilder, MetricValue, MetricsSet,
+};
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, Distribution, ExecutionPlan, Partitioning, PlanProperties,
+    RecordBatchStream, SendableRecordBatchStream,
+};
+use datatypes::value::OrderedF64;
+use futures::{ready, Stream, StreamExt};
+
+use crate::extension_plan::fill_forward::group_series;
+use crate::extension_plan::METRIC_NUM_SERIES;
+
+/// `EmaOverSteps` smooths the stepped values of every series with an exponential
+/// moving average (EMA).
+///
+/// For every series, identified by the tag columns, the smoothed value at the
+/// first step is the value itself, and at each following step it's
+/// `alpha * value + (1 - alpha) * previous smoothed value`. Steps without a row
+/// are skipped, the state is carried to the next step the series has. Nulls and
+/// NaNs are emitted as is and don't change the state.
+///
+/// This is not part of Prometheus. It's planned for the non-standard
+/// `ema_over_steps(alpha, v)` function for dashboards that want smoothed lines.
+/// Only Float64 field columns are smoothed.
+///
+/// The output has the same schema as the input. Series are emitted in the
+/// order they first appear in the input, and rows of a series are ordered by
+/// timestamp.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd)]
+pub struct EmaOverSteps {
+    alpha: OrderedF64,
+    time_index_column: String,
+    tag_columns: Vec<String>,
+    field_columns: Vec<String>,
+    input: LogicalPlan,
+}
+
+impl UserDefinedLogicalNodeCore for EmaOverSteps {
+    fn name(&self) -> &str {
+        Self::name()
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.input]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        self.input.schema()
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "PromEmaOverSteps: alpha=[{}], time index=[{}], tags={:?}, fields={:?}",
+            self.alpha, self.time_index_column, self.tag_columns, self.field_columns
+        )
+    }
+
+    fn with_exprs_and_inputs(
+        &self,
+        _exprs: Vec<Expr>,
+        inputs: Vec<LogicalPlan>,
+    ) -> DataFusionResult<Self> {
+        if inputs.is_empty() {
+            return Err(DataFusionError::Internal(
+                "EmaOverSteps must have at least one input".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            alpha: self.alpha,
+            time_index_column: self.time_index_column.clone(),
+            tag_columns: self.tag_columns.clone(),
+            field_columns: self.field_columns.clone(),
+            input: inputs.into_iter().next().unwrap(),
+        })
+    }
+}
+
+impl EmaOverSteps {
+    pub fn new(
+        alpha: f64,
+        time_index_column: String,
+        tag_columns: Vec<String>,
+        field_columns: Vec<String>,
+        input: LogicalPlan,
+    ) -> DataFusionResult<Self> {
+        if alpha.is_nan() || alpha <= 0.0 || alpha > 1.0 {
+            return Err(DataFusionError::Plan(format!(
+                "alpha of EmaOverSteps must be in (0, 1], got {alpha}"
+            )));
+        }
+        // check all columns exist in input
+        let input_schema = input.schema();
+        for column in Some(&time_index_column)
+            .into_iter()
+            .chain(&tag_columns)
+            .chain(&field_columns)
+        {
+            input_schema.qualified_field_with_unqualified_name(column)?;
+        }
+
+        Ok(Self {
+            alpha: alpha.into(),
+            time_index_column,
+            tag_columns,
+            field_columns,
+            input,
+        })
+    }
+
+    pub const fn name() -> &'static str {
+        "EmaOverSteps"
+    }
+
+    pub fn to_execution_plan(&self, exec_input: Arc<dyn ExecutionPlan>) -> Arc<dyn ExecutionPlan> {
+        let properties = EmaOverStepsExec::compute_properties(&exec_input);
+        Arc::new(EmaOverStepsExec {
+            alpha: *self.alpha,
+            time_index_column: self.time_index_column.clone(),
+            tag_columns: self.tag_columns.clone(),
+            field_columns: self.field_columns.clone(),
+            input: exec_input,
+            metric: ExecutionPlanMetricsSet::new(),
+            properties,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct EmaOverStepsExec {
+    alpha: f64,
+    time_index_column: String,
+    tag_columns: Vec<String>,
+    field_columns: Vec<String>,
+    input: Arc<dyn ExecutionPlan>,
+    metric: ExecutionPlanMetricsSet,
+    properties: PlanProperties,
+}
+
+impl EmaOverStepsExec {
+    /// The output is a single partition without ordering, and is emitted after
+    /// all input is consumed.
+    fn compute_properties(input: &Arc<dyn ExecutionPlan>) -> PlanProperties {
+        PlanProperties::new(
+            EquivalenceProperties::new(input.schema()),
+            Partitioning::UnknownPartitioning(1),
+            EmissionType::Final,
+            Boundedness::Bounded,
+        )
+    }
+}
+
+impl ExecutionPlan for EmaOverStepsExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    // All rows of a series must be in the same partition.
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        vec![Distribution::SinglePartition]
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![false; self.children().len()]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        assert!(!children.is_empty());
+        let input = children[0].clone();
+        let properties = Self::compute_properties(&input);
+        Ok(Arc::new(Self {
+            alpha: self.alpha,
+            time_index_column: self.time_index_column.clone(),
+            tag_columns: self.tag_columns.clone(),
+            field_columns: self.field_columns.clone(),
+            input,
+            metric: self.metric.clone(),
+            properties,
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let baseline_metric = BaselineMetrics::new(&self.metric, partition);
+        let num_series = Count::new();
+        MetricBuilder::new(&self.metric)
+            .with_partition(partition)
+            .build(MetricValue::Count {
+                name: METRIC_NUM_SERIES.into(),
+                count: num_series.clone(),
+            });
+
+        let batch_size = context.session_config().batch_size();
+        let input = self.input.execute(partition, context)?;
+        let schema = input.schema();
+        let time_index = schema.index_of(&self.time_index_column)?;
+        let tag_indices = self
+            .tag_columns
+            .iter()
+            .map(|column| Ok(schema.index_of(column)?))
+            .collect::<DataFusionResult<Vec<_>>>()?;
+        let field_indices = self
+            .field_columns
+            .iter()
+            .map(|column| Ok(schema.index_of(column)?))
+            .collect::<DataFusionResult<Vec<_>>>()?;
+
+        Ok(Box::pin(EmaOverStepsStream {
+            alpha: self.alpha,
+            time_index,
+            tag_indices,
+            field_indices,
+            batch_size,
+            buffer: vec![],
+            output: None,
+            schema,
+            input,
+            metric: baseline_metric,
+            num_series,
+        }))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metric.clone_inner())
+    }
+
+    fn name(&self) -> &str {
+        "EmaOverStepsExec"
+    }
+}
+
+impl DisplayAs for EmaOverStepsExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(
+                    f,
+                    "PromEmaOverStepsExec: alpha=[{}], time index=[{}], tags={:?}, fields={:?}",
+                    self.alpha, self.time_index_column, self.tag_columns, self.field_columns
+                )
+            }
+        }
+    }
+}
+
+pub struct EmaOverStepsStream {
+    alpha: f64,
+    time_index: usize,
+    tag_indices: Vec<usize>,
+    field_indices: Vec<usize>,
+    batch_size: usize,
+    /// Input batches buffered until the input is exhausted.
+    buffer: Vec<RecordBatch>,
+    /// The smoothed output and the offset of the next row to emit. Built after
+    /// the input is exhausted.
+    output: Option<(RecordBatch, usize)>,
+    schema: SchemaRef,
+    input: SendableRecordBatchStream,
+    metric: BaselineMetrics,
+    /// Number of series smoothed.
+    num_series: Count,
+}
+
+impl RecordBatchStream for EmaOverStepsStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl Stream for EmaOverStepsStream {
+    type Item = DataFusionResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let batch_size = self.batch_size;
+        loop {
+            if let Some((output, offset)) = &mut self.output {
+                if *offset >= output.num_rows() {
+                    return Poll::Ready(None);
+                }
+                let num_rows = (output.num_rows() - *offset).min(batch_size);
+                let batch = output.slice(*offset, num_rows);
+                *offset += num_rows;
+                return self.metric.record_poll(Poll::Ready(Some(Ok(batch))));
+            }
+
+            match ready!(self.input.poll_next_unpin(cx)) {
+                Some(Ok(batch)) => self.buffer.push(batch),
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => {
+                    let timer = std::time::Instant::now();
+                    let result = self.smooth();
+                    self.metric.elapsed_compute().add_elapsed(timer);
+                    match result {
+                        Ok(output) => self.output = Some((output, 0)),
+                        Err(e) => return Poll::Ready(Some(Err(e))),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl EmaOverStepsStream {
+    /// Smooth all buffered rows into one record batch.
+    fn smooth(&mut self) -> DataFusionResult<RecordBatch> {
+        let input = concat_batches(&self.schema, &std::mem::take(&mut self.buffer))?;
+        let ts_column = input
+            .column(self.time_index)
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .ok_or_else(|| {
+                DataFusionError::Execution(
+                    "Time index Column downcast to TimestampMillisecondArray failed".into(),
+                )
+            })?;
+
+        let series = group_series(&input, &self.tag_indices, ts_column)?;
+        self.num_series.add(series.len());
+
+        let take_indices = UInt32Array::from(series.iter().flatten().copied().collect::<Vec<_>>());
+        let mut columns = input
+            .columns()
+            .iter()
+            .map(|column| take(column, &take_indices, None))
+            .collect::<Result<Vec<_>, _>>()?;
+        for index in &self.field_indices {
+            let Some(values) = columns[*index].as_primitive_opt::<Float64Type>() else {
+                continue;
+            };
+            let mut smoothed = Vec::with_capacity(values.len());
+            let mut offset = 0;
+            for rows in &series {
+                let mut state = None;
+                smoothed.extend(
+                    values
+                        .slice(offset, rows.len())
+                        .iter()
+                        .map(|value| ema_step(self.alpha, &mut state, value)),
+                );
+                offset += rows.len();
+            }
+            columns[*index] = Arc::new(Float64Array::from(smoothed));
+        }
+
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
+
+/// Smooths the `value` of a step with the `state` of the previous steps, and
+/// updates the state.
+fn ema_step(alpha: f64, state: &mut Option<f64>, value: Option<f64>) -> Option<f64> {
+    let value = value?;
+    if value.is_nan() {
+        return Some(value);
+    }
+    let smoothed = match *state {
+        Some(previous) => alpha * value + (1.0 - alpha) * previous,
+        None => value,
+    };
+    *state = Some(smoothed);
+    Some(smoothed)
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::arrow::array::StringArray;
+    use datafusion::arrow::datatypes::{
+        ArrowPrimitiveType, DataType, Field, Schema, TimestampMillisecondType,
+    };
+    use datafusion::physical_plan::memory::MemoryExec;
+    use datafusion::prelude::SessionContext;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn smooth_per_series() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("timestamp", TimestampMillisecondType::DATA_TYPE, true),
+            Field::new("value", DataType::Float64, true),
+            Field::new("path", DataType::Utf8, true),
+        ]));
+        // Rows of the two series are interleaved and out of order, "foo" has
+        // no row at 20s and a NaN at 40s.
+        let data = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(TimestampMillisecondArray::from(vec![
+                    10_000, 0, 30_000, 10_000, 20_000, 40_000, 50_000,
+                ])),
+                Arc::new(Float64Array::from(vec![
+                    10.0,
+                    2.0,
+                    8.0,
+                    4.0,
+                    20.0,
+                    f64::NAN,
+                    1.0,
+                ])),
+                Arc::new(StringArray::from(vec![
+                    "bar", "foo", "foo", "foo", "bar", "foo", "foo",
+                ])),
+            ],
+        )
+        .unwrap();
+        let memory_exec = Arc::new(MemoryExec::try_new(&[vec![data]], schema, None).unwrap());
+        let ema_exec = Arc::new(EmaOverStepsExec {
+            alpha: 0.5,
+            time_index_column: "timestamp".to_string(),
+            tag_columns: vec!["path".to_string()],
+            field_columns: vec!["value".to_string()],
+            properties: EmaOverStepsExec::compute_properties(&(memory_exec.clone() as _)),
+            input: memory_exec,
+            metric: ExecutionPlanMetricsSet::new(),
+        });
+        let session_context = SessionContext::default();
+        let result = datafusion::physical_plan::collect(ema_exec, session_context.task_ctx())
+            .await
+            .unwrap();
+        let result_literal = datatypes::arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string();
+
+        let expected = "+---------------------+-------+------+\
+            \n| timestamp           | value | path |\
+            \n+---------------------+-------+------+\
+            \n| 1970-01-01T00:00:10 | 10.0  | bar  |\
+            \n| 1970-01-01T00:00:20 | 15.0  | bar  |\
+            \n| 1970-01-01T00:00:00 | 2.0   | foo  |\
+            \n| 1970-01-01T00:00:10 | 3.0   | foo  |\
+            \n| 1970-01-01T00:00:30 | 5.5   | foo  |\
+            \n| 1970-01-01T00:00:40 | NaN   | foo  |\
+            \n| 1970-01-01T00:00:50 | 3.25  | foo  |\
+            \n+---------------------+-------+------+";
+        assert_eq!(result_literal, expected);
+    }
+}
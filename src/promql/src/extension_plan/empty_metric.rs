@@ -19,7 +19,7 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use datafusion::arrow::array::ArrayRef;
+use datafusion::arrow::array::{ArrayRef, Int64Array};
 use datafusion::arrow::datatypes::{DataType, TimeUnit};
 use datafusion::common::arrow::datatypes::Field;
 use datafusion::common::stats::Precision;
@@ -45,17 +45,27 @@ use futures::Stream;
 
 use crate::extension_plan::Millisecond;
 
-/// Empty source plan that generate record batch with two columns:
+/// Name of the column holding the evaluation interval in milliseconds.
+pub const INTERVAL_COLUMN: &str = "__interval__";
+/// Name of the column holding the start of the evaluation range in milliseconds.
+pub const RANGE_START_COLUMN: &str = "__range_start__";
+/// Name of the column holding the end of the evaluation range in milliseconds.
+pub const RANGE_END_COLUMN: &str = "__range_end__";
+
+/// Empty source plan that generate record batch with columns:
 /// - time index column, computed from start, end and interval
-/// - value column, generated by the input expr. The expr should not
-///   reference any column except the time index column.
+/// - value columns, each generated by an input expr. The exprs should not
+///   reference any column except the time index column and the
+///   [INTERVAL_COLUMN], [RANGE_START_COLUMN] and [RANGE_END_COLUMN].
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct EmptyMetric {
     start: Millisecond,
     end: Millisecond,
     interval: Millisecond,
-    expr: Option<Expr>,
-    /// Schema that only contains the time index column.
+    /// Exprs of the value columns, in the order of the result schema.
+    exprs: Vec<Expr>,
+    /// Schema that only contains the time index column, followed by the
+    /// interval and range columns referenced by the exprs.
     /// This is for intermediate result only.
     time_index_schema: DFSchemaRef,
     /// Schema of the output record batch
@@ -63,6 +73,8 @@ pub struct EmptyMetric {
 }
 
 impl EmptyMetric {
+    /// Creates a plan with at most one value column named `field_column_name`,
+    /// which is generated by `field_expr` if present.
     pub fn new(
         start: Millisecond,
         end: Millisecond,
@@ -70,15 +82,31 @@ impl EmptyMetric {
         time_index_column_name: String,
         field_column_name: String,
         field_expr: Option<Expr>,
+    ) -> DataFusionResult<Self> {
+        let field_columns = field_expr
+            .map(|expr| vec![(field_column_name, expr)])
+            .unwrap_or_default();
+        Self::new_with_fields(start, end, interval, time_index_column_name, field_columns)
+    }
+
+    /// Creates a plan with a value column for each `(name, expr)` pair in `field_columns`.
+    pub fn new_with_fields(
+        start: Millisecond,
+        end: Millisecond,
+        interval: Millisecond,
+        time_index_column_name: String,
+        field_columns: Vec<(String, Expr)>,
     ) -> DataFusionResult<Self> {
         let qualifier = Some(TableReference::bare(""));
         let ts_only_schema = build_ts_only_schema(&time_index_column_name);
         let mut fields = vec![(qualifier.clone(), Arc::new(ts_only_schema.field(0).clone()))];
-        if let Some(field_expr) = &field_expr {
-            let field_data_type = field_expr.get_type(&ts_only_schema)?;
+        let (names, exprs): (Vec<_>, Vec<_>) = field_columns.into_iter().unzip();
+        let input_schema = build_input_schema(ts_only_schema, &exprs)?;
+        for (name, expr) in names.into_iter().zip(&exprs) {
+            let field_data_type = expr.get_type(&input_schema)?;
             fields.push((
                 qualifier.clone(),
-                Arc::new(Field::new(field_column_name, field_data_type, true)),
+                Arc::new(Field::new(name, field_data_type, true)),
             ));
         }
         let schema = Arc::new(DFSchema::new_with_metadata(fields, HashMap::new())?);
@@ -87,9 +115,9 @@ impl EmptyMetric {
             start,
             end,
             interval,
-            time_index_schema: Arc::new(ts_only_schema),
+            time_index_schema: Arc::new(input_schema),
             result_schema: schema,
-            expr: field_expr,
+            exprs,
         })
     }
 
@@ -102,13 +130,13 @@ impl EmptyMetric {
         session_state: &SessionState,
         physical_planner: &dyn PhysicalPlanner,
     ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
-        let physical_expr = self
-            .expr
-            .as_ref()
+        let physical_exprs = self
+            .exprs
+            .iter()
             .map(|expr| {
                 physical_planner.create_physical_expr(expr, &self.time_index_schema, session_state)
             })
-            .transpose()?;
+            .collect::<DataFusionResult<Vec<_>>>()?;
         let result_schema: SchemaRef = Arc::new(self.result_schema.as_ref().into());
         let properties = Arc::new(PlanProperties::new(
             EquivalenceProperties::new(result_schema.clone()),
@@ -122,7 +150,7 @@ impl EmptyMetric {
             interval: self.interval,
             time_index_schema: Arc::new(self.time_index_schema.as_ref().into()),
             result_schema,
-            expr: physical_expr,
+            exprs: physical_exprs,
             properties,
             metric: ExecutionPlanMetricsSet::new(),
         }))
@@ -143,11 +171,7 @@ impl UserDefinedLogicalNodeCore for EmptyMetric {
     }
 
     fn expressions(&self) -> Vec<Expr> {
-        if let Some(expr) = &self.expr {
-            vec![expr.clone()]
-        } else {
-            vec![]
-        }
+        self.exprs.clone()
     }
 
     fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -167,7 +191,7 @@ impl UserDefinedLogicalNodeCore for EmptyMetric {
             start: self.start,
             end: self.end,
             interval: self.interval,
-            expr: exprs.into_iter().next(),
+            exprs,
             time_index_schema: self.time_index_schema.clone(),
             result_schema: self.result_schema.clone(),
         })
@@ -189,7 +213,7 @@ impl PartialOrd for EmptyMetric {
             Some(core::cmp::Ordering::Equal) => {}
             ord => return ord,
         }
-        self.expr.partial_cmp(&other.expr)
+        self.exprs.partial_cmp(&other.exprs)
     }
 }
 
@@ -203,7 +227,7 @@ pub struct EmptyMetricExec {
     time_index_schema: SchemaRef,
     /// Schema of the output record batch
     result_schema: SchemaRef,
-    expr: Option<PhysicalExprRef>,
+    exprs: Vec<PhysicalExprRef>,
     properties: Arc<PlanProperties>,
     metric: ExecutionPlanMetricsSet,
 }
@@ -246,7 +270,7 @@ impl ExecutionPlan for EmptyMetricExec {
             start: self.start,
             end: self.end,
             interval: self.interval,
-            expr: self.expr.clone(),
+            exprs: self.exprs.clone(),
             is_first_poll: true,
             time_index_schema: self.time_index_schema.clone(),
             result_schema: self.result_schema.clone(),
@@ -290,7 +314,7 @@ pub struct EmptyMetricStream {
     start: Millisecond,
     end: Millisecond,
     interval: Millisecond,
-    expr: Option<PhysicalExprRef>,
+    exprs: Vec<PhysicalExprRef>,
     /// This stream only generate one record batch at the first poll
     is_first_poll: bool,
     /// Schema that only contains the time index column.
@@ -315,20 +339,34 @@ impl Stream for EmptyMetricStream {
             self.is_first_poll = false;
             let _timer = self.metric.elapsed_compute().timer();
 
-            // build the time index array, and a record batch that
-            // only contains that array as the input of field expr
+            // build the time index array, and a record batch that contains
+            // that array and the referenced constant columns as the input of field exprs
             let time_array = (self.start..=self.end)
                 .step_by(self.interval as _)
                 .collect::<Vec<_>>();
             let time_array = Arc::new(TimestampMillisecondArray::from(time_array));
             let num_rows = time_array.len();
+            let mut input_arrays: Vec<ArrayRef> = vec![time_array.clone()];
+            for field in self.time_index_schema.fields().iter().skip(1) {
+                let value = match field.name().as_str() {
+                    INTERVAL_COLUMN => self.interval,
+                    RANGE_START_COLUMN => self.start,
+                    RANGE_END_COLUMN => self.end,
+                    name => {
+                        return Poll::Ready(Some(Err(DataFusionError::Internal(format!(
+                            "Unexpected input column {name} of EmptyMetric"
+                        )))))
+                    }
+                };
+                input_arrays.push(Arc::new(Int64Array::from_value(value, num_rows)));
+            }
             let input_record_batch =
-                RecordBatch::try_new(self.time_index_schema.clone(), vec![time_array.clone()])
+                RecordBatch::try_new(self.time_index_schema.clone(), input_arrays)
                     .map_err(|e| DataFusionError::ArrowError(e, None))?;
             let mut result_arrays: Vec<ArrayRef> = vec![time_array];
 
-            // evaluate the field expr and get the result
-            if let Some(field_expr) = &self.expr {
+            // evaluate the field exprs and get the results
+            for field_expr in &self.exprs {
                 result_arrays.push(
                     field_expr
                         .evaluate(&input_record_batch)
@@ -363,6 +401,28 @@ fn build_ts_only_schema(column_name: &str) -> DFSchema {
     .unwrap()
 }
 
+/// Appends the interval and range columns referenced by `exprs` to the `ts_only_schema`.
+fn build_input_schema(ts_only_schema: DFSchema, exprs: &[Expr]) -> DataFusionResult<DFSchema> {
+    let column_refs = exprs
+        .iter()
+        .flat_map(|expr| expr.column_refs())
+        .collect::<Vec<_>>();
+    let extra_fields = [INTERVAL_COLUMN, RANGE_START_COLUMN, RANGE_END_COLUMN]
+        .into_iter()
+        .filter(|name| column_refs.iter().any(|column| column.name == *name))
+        .map(|name| {
+            (
+                Some(TableReference::bare("")),
+                Arc::new(Field::new(name, DataType::Int64, false)),
+            )
+        })
+        .collect::<Vec<_>>();
+    if extra_fields.is_empty() {
+        return Ok(ts_only_schema);
+    }
+    ts_only_schema.join(&DFSchema::new_with_metadata(extra_fields, HashMap::new())?)
+}
+
 // Convert timestamp column to UNIX epoch second:
 // https://prometheus.io/docs/prometheus/latest/querying/functions/#time
 pub fn build_special_time_expr(time_index_column_name: &str) -> Expr {
@@ -378,6 +438,7 @@ pub fn build_special_time_expr(time_index_column_name: &str) -> Expr {
 
 #[cfg(test)]
 mod test {
+    use datafusion::logical_expr::cast;
     use datafusion::physical_planner::DefaultPhysicalPlanner;
     use datafusion::prelude::SessionContext;
 
@@ -539,4 +600,132 @@ mod test {
         );
         assert_eq!(result_literal, expected);
     }
+
+    async fn collect_empty_metric(empty_metric: EmptyMetric) -> String {
+        let session_context = SessionContext::default();
+        let df_default_physical_planner = DefaultPhysicalPlanner::default();
+        let empty_metric_exec = empty_metric
+            .to_execution_plan(&session_context.state(), &df_default_physical_planner)
+            .unwrap();
+
+        let result =
+            datafusion::physical_plan::collect(empty_metric_exec, session_context.task_ctx())
+                .await
+                .unwrap();
+        datatypes::arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn interval_column_empty_metric_test() {
+        let expr = cast(col("time"), DataType::Int64).div(col(INTERVAL_COLUMN));
+        let empty_metric = EmptyMetric::new(
+            100,
+            130,
+            10,
+            "time".to_string(),
+            "value".to_string(),
+            Some(expr),
+        )
+        .unwrap();
+        assert_eq!(empty_metric.time_index_schema.fields().len(), 2);
+        assert_eq!(
+            empty_metric.result_schema.field(1).data_type(),
+            &DataType::Int64
+        );
+
+        let expected = String::from(
+            "+-------------------------+-------+\
+            \n| time                    | value |\
+            \n+-------------------------+-------+\
+            \n| 1970-01-01T00:00:00.100 | 10    |\
+            \n| 1970-01-01T00:00:00.110 | 11    |\
+            \n| 1970-01-01T00:00:00.120 | 12    |\
+            \n| 1970-01-01T00:00:00.130 | 13    |\
+            \n+-------------------------+-------+",
+        );
+        assert_eq!(collect_empty_metric(empty_metric).await, expected);
+    }
+
+    #[tokio::test]
+    async fn range_columns_empty_metric_test() {
+        let expr = col(RANGE_END_COLUMN) - col(RANGE_START_COLUMN);
+        let empty_metric = EmptyMetric::new(
+            0,
+            20,
+            10,
+            "time".to_string(),
+            "value".to_string(),
+            Some(expr),
+        )
+        .unwrap();
+        // the interval column isn't referenced
+        assert_eq!(empty_metric.time_index_schema.fields().len(), 3);
+
+        let expected = String::from(
+            "+-------------------------+-------+\
+            \n| time                    | value |\
+            \n+-------------------------+-------+\
+            \n| 1970-01-01T00:00:00     | 20    |\
+            \n| 1970-01-01T00:00:00.010 | 20    |\
+            \n| 1970-01-01T00:00:00.020 | 20    |\
+            \n+-------------------------+-------+",
+        );
+        assert_eq!(collect_empty_metric(empty_metric).await, expected);
+    }
+
+    #[tokio::test]
+    async fn multiple_fields_empty_metric_test() {
+        let time_expr = build_special_time_expr("time");
+        let empty_metric = EmptyMetric::new_with_fields(
+            0,
+            20,
+            10,
+            "time".to_string(),
+            vec![
+                ("seconds".to_string(), time_expr),
+                ("interval".to_string(), col(INTERVAL_COLUMN)),
+                ("constant".to_string(), lit(1.0)),
+            ],
+        )
+        .unwrap();
+        assert_eq!(empty_metric.time_index_schema.fields().len(), 2);
+        assert_eq!(empty_metric.result_schema.fields().len(), 4);
+
+        // the exprs are kept in order when rebuilt
+        let exprs = empty_metric.expressions();
+        assert_eq!(exprs.len(), 3);
+        let rebuilt = empty_metric
+            .with_exprs_and_inputs(exprs.clone(), vec![])
+            .unwrap();
+        assert_eq!(rebuilt.expressions(), exprs);
+        assert_eq!(rebuilt, empty_metric);
+
+        let expected = String::from(
+            "+-------------------------+---------+----------+----------+\
+            \n| time                    | seconds | interval | constant |\
+            \n+-------------------------+---------+----------+----------+\
+            \n| 1970-01-01T00:00:00     | 0.0     | 10       | 1.0      |\
+            \n| 1970-01-01T00:00:00.010 | 0.01    | 10       | 1.0      |\
+            \n| 1970-01-01T00:00:00.020 | 0.02    | 10       | 1.0      |\
+            \n+-------------------------+---------+----------+----------+",
+        );
+        assert_eq!(collect_empty_metric(rebuilt).await, expected);
+    }
+
+    #[test]
+    fn no_extra_columns_without_reference() {
+        let time_expr = build_special_time_expr("time");
+        let empty_metric = EmptyMetric::new(
+            0,
+            100,
+            10,
+            "time".to_string(),
+            "value".to_string(),
+            Some(time_expr),
+        )
+        .unwrap();
+        assert_eq!(empty_metric.time_index_schema.fields().len(), 1);
+    }
 }
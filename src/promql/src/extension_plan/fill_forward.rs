@@ -0,0 +1,654 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use datafusion::arrow::array::{
+    Array, ArrayRef, AsArray, Float64Array, TimestampMillisecondArray, UInt32Array,
+};
+use datafusion::arrow::compute::{concat_batches, take};
+use datafusion::arrow::datatypes::{DataType, Float64Type, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::row::{RowConverter, SortField};
+use datafusion::common::DFSchemaRef;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::context::TaskContext;
+use datafusion::logical_expr::{Expr, LogicalPlan, UserDefinedLogicalNodeCore};
+use datafusion::physical_expr::EquivalenceProperties;
+use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
+use datafusion::physical_plan::metrics::{
+    BaselineMetrics, Count, ExecutionPlanMetricsSet, MetricBuilder, MetricValue, MetricsSet,
+};
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, Distribution, ExecutionPlan, Partitioning, PlanProperties,
+    RecordBatchStream, SendableRecordBatchStream,
+};
+use futures::{ready, Stream, StreamExt};
+
+use crate::extension_plan::{Millisecond, METRIC_NUM_SERIES, METRIC_ROWS_EMITTED};
+
+/// `FillForward` fills the missing steps of a stepped output with the last
+/// known value (LOCF, last observation carried forward).
+///
+/// For every series, identified by the tag columns, a row is emitted at every
+/// timestamp between `start` and `end` with step `interval`. If the series has
+/// no row at a step, the last row before it is carried forward as long as it's
+/// not older than `lookback_delta`. Otherwise the field columns are NaN (or
+/// null if they are not Float64).
+///
+/// This is not part of Prometheus, whose stepped output leaves gaps where the
+/// expression has no value. It's a post-processing over the result of a query
+/// for dashboards that want step-interpolated lines.
+///
+/// The output has the same schema as the input. Series are emitted in the
+/// order they first appear in the input, and rows of a series are ordered by
+/// timestamp.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd)]
+pub struct FillForward {
+    start: Millisecond,
+    end: Millisecond,
+    lookback_delta: Millisecond,
+    interval: Millisecond,
+    time_index_column: String,
+    tag_columns: Vec<String>,
+    field_columns: Vec<String>,
+    input: LogicalPlan,
+}
+
+impl UserDefinedLogicalNodeCore for FillForward {
+    fn name(&self) -> &str {
+        Self::name()
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.input]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        self.input.schema()
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "PromFillForward: range=[{}..{}], lookback=[{}], interval=[{}], time index=[{}], tags={:?}, fields={:?}",
+            self.start,
+            self.end,
+            self.lookback_delta,
+            self.interval,
+            self.time_index_column,
+            self.tag_columns,
+            self.field_columns
+        )
+    }
+
+    fn with_exprs_and_inputs(
+        &self,
+        _exprs: Vec<Expr>,
+        inputs: Vec<LogicalPlan>,
+    ) -> DataFusionResult<Self> {
+        if inputs.is_empty() {
+            return Err(DataFusionError::Internal(
+                "FillForward must have at least one input".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            start: self.start,
+            end: self.end,
+            lookback_delta: self.lookback_delta,
+            interval: self.interval,
+            time_index_column: self.time_index_column.clone(),
+            tag_columns: self.tag_columns.clone(),
+            field_columns: self.field_columns.clone(),
+            input: inputs.into_iter().next().unwrap(),
+        })
+    }
+}
+
+impl FillForward {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        start: Millisecond,
+        end: Millisecond,
+        lookback_delta: Millisecond,
+        interval: Millisecond,
+        time_index_column: String,
+        tag_columns: Vec<String>,
+        field_columns: Vec<String>,
+        input: LogicalPlan,
+    ) -> DataFusionResult<Self> {
+        // check all columns exist in input
+        let input_schema = input.schema();
+        for column in Some(&time_index_column)
+            .into_iter()
+            .chain(&tag_columns)
+            .chain(&field_columns)
+        {
+            input_schema.qualified_field_with_unqualified_name(column)?;
+        }
+
+        Ok(Self {
+            start,
+            end,
+            lookback_delta,
+            interval,
+            time_index_column,
+            tag_columns,
+            field_columns,
+            input,
+        })
+    }
+
+    pub const fn name() -> &'static str {
+        "FillForward"
+    }
+
+    pub fn to_execution_plan(&self, exec_input: Arc<dyn ExecutionPlan>) -> Arc<dyn ExecutionPlan> {
+        let properties = FillForwardExec::compute_properties(&exec_input);
+        Arc::new(FillForwardExec {
+            start: self.start,
+            end: self.end,
+            lookback_delta: self.lookback_delta,
+            interval: self.interval,
+            time_index_column: self.time_index_column.clone(),
+            tag_columns: self.tag_columns.clone(),
+            field_columns: self.field_columns.clone(),
+            input: exec_input,
+            metric: ExecutionPlanMetricsSet::new(),
+            properties,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct FillForwardExec {
+    start: Millisecond,
+    end: Millisecond,
+    lookback_delta: Millisecond,
+    interval: Millisecond,
+    time_index_column: String,
+    tag_columns: Vec<String>,
+    field_columns: Vec<String>,
+    input: Arc<dyn ExecutionPlan>,
+    metric: ExecutionPlanMetricsSet,
+    properties: PlanProperties,
+}
+
+impl FillForwardExec {
+    /// The output is a single partition without ordering, and is emitted after
+    /// all input is consumed.
+    fn compute_properties(input: &Arc<dyn ExecutionPlan>) -> PlanProperties {
+        PlanProperties::new(
+            EquivalenceProperties::new(input.schema()),
+            Partitioning::UnknownPartitioning(1),
+            EmissionType::Final,
+            Boundedness::Bounded,
+        )
+    }
+}
+
+impl ExecutionPlan for FillForwardExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    // All rows of a series must be in the same partition.
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        vec![Distribution::SinglePartition]
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![false; self.children().len()]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        assert!(!children.is_empty());
+        let input = children[0].clone();
+        let properties = Self::compute_properties(&input);
+        Ok(Arc::new(Self {
+            start: self.start,
+            end: self.end,
+            lookback_delta: self.lookback_delta,
+            interval: self.interval,
+            time_index_column: self.time_index_column.clone(),
+            tag_columns: self.tag_columns.clone(),
+            field_columns: self.field_columns.clone(),
+            input,
+            metric: self.metric.clone(),
+            properties,
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let baseline_metric = BaselineMetrics::new(&self.metric, partition);
+        let metrics_builder = MetricBuilder::new(&self.metric);
+        let num_series = Count::new();
+        metrics_builder
+            .with_partition(partition)
+            .build(MetricValue::Count {
+                name: METRIC_NUM_SERIES.into(),
+                count: num_series.clone(),
+            });
+        let rows_emitted = Count::new();
+        metrics_builder
+            .with_partition(partition)
+            .build(MetricValue::Count {
+                name: METRIC_ROWS_EMITTED.into(),
+                count: rows_emitted.clone(),
+            });
+
+        let batch_size = context.session_config().batch_size();
+        let input = self.input.execute(partition, context)?;
+        let schema = input.schema();
+        let time_index = schema.index_of(&self.time_index_column)?;
+        let tag_indices = self
+            .tag_columns
+            .iter()
+            .map(|column| Ok(schema.index_of(column)?))
+            .collect::<DataFusionResult<Vec<_>>>()?;
+        let field_indices = self
+            .field_columns
+            .iter()
+            .map(|column| Ok(schema.index_of(column)?))
+            .collect::<DataFusionResult<Vec<_>>>()?;
+
+        Ok(Box::pin(FillForwardStream {
+            start: self.start,
+            end: self.end,
+            lookback_delta: self.lookback_delta,
+            interval: self.interval,
+            time_index,
+            tag_indices,
+            field_indices,
+            batch_size,
+            buffer: vec![],
+            output: None,
+            schema,
+            input,
+            metric: baseline_metric,
+            num_series,
+            rows_emitted,
+        }))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metric.clone_inner())
+    }
+
+    fn name(&self) -> &str {
+        "FillForwardExec"
+    }
+}
+
+impl DisplayAs for FillForwardExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(
+                    f,
+                    "PromFillForwardExec: range=[{}..{}], lookback=[{}], interval=[{}], time index=[{}], tags={:?}, fields={:?}",
+                    self.start,
+                    self.end,
+                    self.lookback_delta,
+                    self.interval,
+                    self.time_index_column,
+                    self.tag_columns,
+                    self.field_columns
+                )
+            }
+        }
+    }
+}
+
+pub struct FillForwardStream {
+    start: Millisecond,
+    end: Millisecond,
+    lookback_delta: Millisecond,
+    interval: Millisecond,
+    time_index: usize,
+    tag_indices: Vec<usize>,
+    field_indices: Vec<usize>,
+    batch_size: usize,
+    /// Input batches buffered until the input is exhausted.
+    buffer: Vec<RecordBatch>,
+    /// The filled output and the offset of the next row to emit. Built after
+    /// the input is exhausted.
+    output: Option<(RecordBatch, usize)>,
+    schema: SchemaRef,
+    input: SendableRecordBatchStream,
+    metric: BaselineMetrics,
+    /// Number of series filled.
+    num_series: Count,
+    /// Number of rows produced, including filled ones.
+    rows_emitted: Count,
+}
+
+impl RecordBatchStream for FillForwardStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl Stream for FillForwardStream {
+    type Item = DataFusionResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let batch_size = self.batch_size;
+        loop {
+            if let Some((output, offset)) = &mut self.output {
+                if *offset >= output.num_rows() {
+                    return Poll::Ready(None);
+                }
+                let num_rows = (output.num_rows() - *offset).min(batch_size);
+                let batch = output.slice(*offset, num_rows);
+                *offset += num_rows;
+                return self.metric.record_poll(Poll::Ready(Some(Ok(batch))));
+            }
+
+            match ready!(self.input.poll_next_unpin(cx)) {
+                Some(Ok(batch)) => self.buffer.push(batch),
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => {
+                    let timer = std::time::Instant::now();
+                    let result = self.fill();
+                    self.metric.elapsed_compute().add_elapsed(timer);
+                    match result {
+                        Ok(output) => self.output = Some((output, 0)),
+                        Err(e) => return Poll::Ready(Some(Err(e))),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl FillForwardStream {
+    /// Fill all buffered rows into one record batch.
+    fn fill(&mut self) -> DataFusionResult<RecordBatch> {
+        let input = concat_batches(&self.schema, &std::mem::take(&mut self.buffer))?;
+        let ts_column = input
+            .column(self.time_index)
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .ok_or_else(|| {
+                DataFusionError::Execution(
+                    "Time index Column downcast to TimestampMillisecondArray failed".into(),
+                )
+            })?;
+
+        let series = group_series(&input, &self.tag_indices, ts_column)?;
+        self.num_series.add(series.len());
+
+        // Row to take the tag columns from, and row to take the field columns
+        // from. The field is None if the step is beyond the lookback.
+        let mut tag_take = vec![];
+        let mut field_take = vec![];
+        let mut timestamps = vec![];
+        for rows in &series {
+            let (fields, steps) = fill_steps(
+                self.start,
+                self.end,
+                self.lookback_delta,
+                self.interval,
+                ts_column,
+                rows,
+            );
+            tag_take.extend(std::iter::repeat_n(rows[0], fields.len()));
+            field_take.extend(fields);
+            timestamps.extend(steps);
+        }
+        self.rows_emitted.add(timestamps.len());
+
+        let tag_take = UInt32Array::from(tag_take);
+        let field_take = UInt32Array::from(field_take);
+        let mut columns = Vec::with_capacity(input.num_columns());
+        for (index, column) in input.columns().iter().enumerate() {
+            let array = if index == self.time_index {
+                Arc::new(
+                    TimestampMillisecondArray::from(timestamps.clone())
+                        .with_timezone_opt(ts_column.timezone()),
+                ) as ArrayRef
+            } else if self.field_indices.contains(&index) {
+                nan_on_missing(take(column, &field_take, None)?, &field_take)
+            } else {
+                take(column, &tag_take, None)?
+            };
+            columns.push(array);
+        }
+
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
+
+/// Groups row indices by the tag columns, in the order series first appear.
+/// Rows of each series are sorted by timestamp.
+pub(super) fn group_series(
+    input: &RecordBatch,
+    tag_indices: &[usize],
+    ts_column: &TimestampMillisecondArray,
+) -> DataFusionResult<Vec<Vec<u32>>> {
+    let mut series: Vec<Vec<u32>> = vec![];
+    if tag_indices.is_empty() {
+        // all rows belong to the only series
+        if input.num_rows() > 0 {
+            series.push((0..input.num_rows() as u32).collect());
+        }
+    } else {
+        let tag_arrays = tag_indices
+            .iter()
+            .map(|index| input.column(*index).clone())
+            .collect::<Vec<_>>();
+        let converter = RowConverter::new(
+            tag_arrays
+                .iter()
+                .map(|array| SortField::new(array.data_type().clone()))
+                .collect(),
+        )?;
+        let tag_rows = converter.convert_columns(&tag_arrays)?;
+
+        let mut series_index = HashMap::new();
+        for row_index in 0..input.num_rows() {
+            let key: &[u8] = tag_rows.row(row_index).as_ref();
+            let index = *series_index
+                .entry(Box::<[u8]>::from(key))
+                .or_insert_with(|| {
+                    series.push(vec![]);
+                    series.len() - 1
+                });
+            series[index].push(row_index as u32);
+        }
+    }
+    for rows in &mut series {
+        rows.sort_by_key(|row| ts_column.value(*row as usize));
+    }
+
+    Ok(series)
+}
+
+/// Aligns the `rows` of one series, sorted by timestamp, to the steps between
+/// `start` and `end`. Returns the row of every step, which is the last row not
+/// after the step and within `lookback_delta`, and the timestamps of steps.
+fn fill_steps(
+    start: Millisecond,
+    end: Millisecond,
+    lookback_delta: Millisecond,
+    interval: Millisecond,
+    ts_column: &TimestampMillisecondArray,
+    rows: &[u32],
+) -> (Vec<Option<u32>>, Vec<Millisecond>) {
+    let mut fields = vec![];
+    let mut steps = vec![];
+    let mut cursor = 0;
+    let mut last = None;
+    let mut step = start;
+    while step <= end {
+        while cursor < rows.len() && ts_column.value(rows[cursor] as usize) <= step {
+            last = Some(rows[cursor]);
+            cursor += 1;
+        }
+        let field = last.filter(|row| step - ts_column.value(*row as usize) <= lookback_delta);
+        fields.push(field);
+        steps.push(step);
+
+        // an instant query has only one step
+        if interval <= 0 {
+            break;
+        }
+        step += interval;
+    }
+
+    (fields, steps)
+}
+
+/// Replace the nulls taken from missing rows with NaN for Float64 columns.
+/// Nulls in the input are kept.
+fn nan_on_missing(array: ArrayRef, take_indices: &UInt32Array) -> ArrayRef {
+    if array.data_type() != &DataType::Float64 || take_indices.null_count() == 0 {
+        return array;
+    }
+
+    let values = array.as_primitive::<Float64Type>();
+    let filled = values
+        .iter()
+        .zip(take_indices.iter())
+        .map(|(value, index)| match index {
+            Some(_) => value,
+            None => Some(f64::NAN),
+        })
+        .collect::<Float64Array>();
+    Arc::new(filled)
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::arrow::array::StringArray;
+    use datafusion::arrow::datatypes::{
+        ArrowPrimitiveType, Field, Schema, TimestampMillisecondType,
+    };
+    use datafusion::physical_plan::memory::MemoryExec;
+    use datafusion::prelude::SessionContext;
+
+    use super::*;
+
+    async fn do_fill_forward_test(
+        start: Millisecond,
+        end: Millisecond,
+        lookback_delta: Millisecond,
+        interval: Millisecond,
+        expected: &str,
+    ) {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("timestamp", TimestampMillisecondType::DATA_TYPE, true),
+            Field::new("value", DataType::Float64, true),
+            Field::new("path", DataType::Utf8, true),
+        ]));
+        // "foo" has gaps at 20s, 30s and 40s, "bar" starts at 20s. Rows of
+        // the two series are interleaved.
+        let data = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(TimestampMillisecondArray::from(vec![
+                    0, 10_000, 20_000, 50_000, 30_000,
+                ])),
+                Arc::new(Float64Array::from(vec![1.0, 2.0, 10.0, 5.0, 30.0])),
+                Arc::new(StringArray::from(vec!["foo", "foo", "bar", "foo", "bar"])),
+            ],
+        )
+        .unwrap();
+        let memory_exec = Arc::new(MemoryExec::try_new(&[vec![data]], schema, None).unwrap());
+        let fill_exec = Arc::new(FillForwardExec {
+            start,
+            end,
+            lookback_delta,
+            interval,
+            time_index_column: "timestamp".to_string(),
+            tag_columns: vec!["path".to_string()],
+            field_columns: vec!["value".to_string()],
+            properties: FillForwardExec::compute_properties(&(memory_exec.clone() as _)),
+            input: memory_exec,
+            metric: ExecutionPlanMetricsSet::new(),
+        });
+        let session_context = SessionContext::default();
+        let result = datafusion::physical_plan::collect(fill_exec, session_context.task_ctx())
+            .await
+            .unwrap();
+        let result_literal = datatypes::arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string();
+
+        assert_eq!(result_literal, expected);
+    }
+
+    #[tokio::test]
+    async fn carry_forward_within_lookback() {
+        let expected = "+---------------------+-------+------+\
+            \n| timestamp           | value | path |\
+            \n+---------------------+-------+------+\
+            \n| 1970-01-01T00:00:00 | 1.0   | foo  |\
+            \n| 1970-01-01T00:00:10 | 2.0   | foo  |\
+            \n| 1970-01-01T00:00:20 | 2.0   | foo  |\
+            \n| 1970-01-01T00:00:30 | 2.0   | foo  |\
+            \n| 1970-01-01T00:00:40 | NaN   | foo  |\
+            \n| 1970-01-01T00:00:50 | 5.0   | foo  |\
+            \n| 1970-01-01T00:01:00 | 5.0   | foo  |\
+            \n| 1970-01-01T00:00:00 | NaN   | bar  |\
+            \n| 1970-01-01T00:00:10 | NaN   | bar  |\
+            \n| 1970-01-01T00:00:20 | 10.0  | bar  |\
+            \n| 1970-01-01T00:00:30 | 30.0  | bar  |\
+            \n| 1970-01-01T00:00:40 | 30.0  | bar  |\
+            \n| 1970-01-01T00:00:50 | 30.0  | bar  |\
+            \n| 1970-01-01T00:01:00 | NaN   | bar  |\
+            \n+---------------------+-------+------+";
+        do_fill_forward_test(0, 60_000, 20_000, 10_000, expected).await;
+    }
+
+    #[tokio::test]
+    async fn no_carry_without_lookback() {
+        let expected = "+---------------------+-------+------+\
+            \n| timestamp           | value | path |\
+            \n+---------------------+-------+------+\
+            \n| 1970-01-01T00:00:10 | 2.0   | foo  |\
+            \n| 1970-01-01T00:00:30 | NaN   | foo  |\
+            \n| 1970-01-01T00:00:50 | 5.0   | foo  |\
+            \n| 1970-01-01T00:00:10 | NaN   | bar  |\
+            \n| 1970-01-01T00:00:30 | 30.0  | bar  |\
+            \n| 1970-01-01T00:00:50 | NaN   | bar  |\
+            \n+---------------------+-------+------+";
+        do_fill_forward_test(10_000, 50_000, 0, 20_000, expected).await;
+    }
+}
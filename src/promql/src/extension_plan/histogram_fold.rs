@@ -20,7 +20,7 @@ use std::time::Instant;
 
 use common_recordbatch::RecordBatch as GtRecordBatch;
 use common_telemetry::warn;
-use datafusion::arrow::array::AsArray;
+use datafusion::arrow::array::{Array, AsArray};
 use datafusion::arrow::compute::{self, concat_batches, SortOptions};
 use datafusion::arrow::datatypes::{DataType, Float64Type, SchemaRef};
 use datafusion::arrow::record_batch::RecordBatch;
@@ -40,7 +40,7 @@ use datafusion::physical_plan::{
 use datafusion::prelude::{Column, Expr};
 use datatypes::prelude::{ConcreteDataType, DataType as GtDataType};
 use datatypes::schema::Schema as GtSchema;
-use datatypes::value::{OrderedF64, ValueRef};
+use datatypes::value::{OrderedF64, Value, ValueRef};
 use datatypes::vectors::MutableVector;
 use futures::{ready, Stream, StreamExt};
 
@@ -55,9 +55,18 @@ use futures::{ready, Stream, StreamExt};
 ///
 /// Due to the folding or sampling, the output rows number will become `input_rows` / `bucket_num`.
 ///
+/// Buckets of one histogram are the rows sharing all columns other than `le` and
+/// `field`, i.e. one series at one timestamp. So the bucket set may change over
+/// time, like when an application adds a new bucket on redeploy. Without a time
+/// index column, a histogram also ends at the `+Inf` bucket. If the `+Inf` bucket
+/// is missing, the bucket with the largest `le` is treated as `+Inf`, as some
+/// exporters omit it.
+///
+/// A histogram whose buckets are a strict subset of the ones at both its previous
+/// and next timestamps of the same series is a partial scrape, and yields `NaN`.
+///
 /// # Requirement
 /// - Input should be sorted on `<tag list>, ts, le ASC`.
-/// - The value set of `le` should be same. I.e., buckets of every series should be same.
 ///
 /// [1]: https://prometheus.io/docs/concepts/metric_types/#histogram
 #[derive(Debug, PartialEq, Hash, Eq)]
@@ -346,13 +355,18 @@ impl ExecutionPlan for HistogramFoldExec {
         let mut normal_indices = (0..input.schema().fields().len()).collect::<HashSet<_>>();
         normal_indices.remove(&self.field_column_index);
         normal_indices.remove(&self.le_column_index);
+        let normal_indices = normal_indices.into_iter().collect::<Vec<_>>();
+        let ts_normal_index = normal_indices
+            .iter()
+            .position(|index| *index == self.ts_column_index);
         Ok(Box::pin(HistogramFoldStream {
             le_column_index: self.le_column_index,
             field_column_index: self.field_column_index,
             quantile: self.quantile,
-            normal_indices: normal_indices.into_iter().collect(),
-            bucket_size: None,
+            normal_indices,
+            ts_normal_index,
             input_buffer: vec![],
+            pending: None,
             input,
             output_schema,
             metric: baseline_metric,
@@ -432,7 +446,8 @@ pub struct HistogramFoldStream {
     quantile: f64,
     /// Columns need not folding. This indices is based on input schema
     normal_indices: Vec<usize>,
-    bucket_size: Option<usize>,
+    /// Position of the time index column in `normal_indices`, if it exists.
+    ts_normal_index: Option<usize>,
     /// Expected output batch size
     batch_size: usize,
     output_schema: SchemaRef,
@@ -440,6 +455,9 @@ pub struct HistogramFoldStream {
     // buffers
     input_buffer: Vec<RecordBatch>,
     input_buffered_rows: usize,
+    /// The last folded histogram, waiting for the next one of the same series to
+    /// tell whether it's a partial scrape.
+    pending: Option<FoldedHistogram>,
     output_buffer: Vec<Box<dyn MutableVector>>,
     output_buffered_rows: usize,
 
@@ -473,30 +491,50 @@ impl Stream for HistogramFoldStream {
                     self.metric.elapsed_compute().add_elapsed(timer);
                     break Poll::Ready(Some(result));
                 }
-                None => break Poll::Ready(self.take_output_buf()?.map(Ok)),
+                None => {
+                    // the remaining rows are the last histogram, which may lack `+Inf`
+                    self.fold_buf(true)?;
+                    break Poll::Ready(self.take_output_buf()?.map(Ok));
+                }
             }
         };
         self.metric.record_poll(poll)
     }
 }
 
+/// The buckets of one series at one timestamp.
+struct FoldedHistogram {
+    /// Values of the normal columns, in the order of `normal_indices`.
+    normal_values: Vec<Value>,
+    /// Bucket bounds sorted in ascending order.
+    bucket: Vec<f64>,
+    counters: Vec<f64>,
+    /// Bucket bounds of the previous histogram in the same series.
+    prev_bucket: Option<Vec<f64>>,
+}
+
+impl FoldedHistogram {
+    /// A histogram is partial if its bucket set is a strict subset of the ones of
+    /// both its previous and next histograms.
+    fn is_partial(&self, next_bucket: Option<&[f64]>) -> bool {
+        let is_strict_subset = |neighbor: &[f64]| {
+            self.bucket.len() < neighbor.len() && self.bucket.iter().all(|le| neighbor.contains(le))
+        };
+        match (self.prev_bucket.as_deref(), next_bucket) {
+            (Some(prev), Some(next)) => is_strict_subset(prev) && is_strict_subset(next),
+            _ => false,
+        }
+    }
+}
+
 impl HistogramFoldStream {
     /// The inner most `Result` is for `poll_next()`
     pub fn fold_input(
         &mut self,
         input: RecordBatch,
     ) -> DataFusionResult<Option<DataFusionResult<RecordBatch>>> {
-        let Some(bucket_num) = self.calculate_bucket_num(&input)? else {
-            return Ok(None);
-        };
-
-        if self.input_buffered_rows + input.num_rows() < bucket_num {
-            // not enough rows to fold
-            self.push_input_buf(input);
-            return Ok(None);
-        }
-
-        self.fold_buf(bucket_num, input)?;
+        self.push_input_buf(input);
+        self.fold_buf(false)?;
         if self.output_buffered_rows >= self.batch_size {
             return Ok(self.take_output_buf()?.map(Ok));
         }
@@ -527,79 +565,126 @@ impl HistogramFoldStream {
         Ok(builders)
     }
 
-    fn calculate_bucket_num(&mut self, batch: &RecordBatch) -> DataFusionResult<Option<usize>> {
-        if let Some(size) = self.bucket_size {
-            return Ok(Some(size));
-        }
-
-        let inf_pos = self.find_positive_inf(batch)?;
-        if inf_pos == batch.num_rows() {
-            // no positive inf found, append to buffer and wait for next batch
-            self.push_input_buf(batch.clone());
-            return Ok(None);
-        }
-
-        // else we found the positive inf.
-        // calculate the bucket size
-        let bucket_size = inf_pos + self.input_buffered_rows + 1;
-        Ok(Some(bucket_size))
-    }
-
     /// Fold record batches from input buffer and put to output buffer
-    fn fold_buf(&mut self, bucket_num: usize, input: RecordBatch) -> DataFusionResult<()> {
-        self.push_input_buf(input);
+    ///
+    /// Rows of the last histogram are kept in the input buffer if it may not be
+    /// complete yet, unless `is_end` is set.
+    fn fold_buf(&mut self, is_end: bool) -> DataFusionResult<()> {
+        if self.input_buffered_rows == 0 {
+            if is_end {
+                self.flush_pending();
+            }
+            return Ok(());
+        }
         // TODO(ruihang): this concat is avoidable.
         let batch = concat_batches(&self.input.schema(), self.input_buffer.drain(..).as_ref())?;
-        let mut remaining_rows = self.input_buffered_rows;
+        let group_ends = self.find_group_ends(&batch, is_end)?;
+        let num_rows = batch.num_rows();
         let mut cursor = 0;
 
         let gt_schema = GtSchema::try_from(self.input.schema()).unwrap();
         let batch = GtRecordBatch::try_from_df_record_batch(Arc::new(gt_schema), batch).unwrap();
 
-        while remaining_rows >= bucket_num {
-            // "sample" normal columns
-            for normal_index in &self.normal_indices {
-                let val = batch.column(*normal_index).get(cursor);
-                self.output_buffer[*normal_index].push_value_ref(val.as_value_ref());
-            }
-            // "fold" `le` and field columns
-            let le_array = batch.column(self.le_column_index);
-            let field_array = batch.column(self.field_column_index);
-            let mut bucket = vec![];
-            let mut counters = vec![];
-            for bias in 0..bucket_num {
-                let le_str_val = le_array.get(cursor + bias);
-                let le_str_val_ref = le_str_val.as_value_ref();
-                let le_str = le_str_val_ref
-                    .as_string()
-                    .unwrap()
-                    .expect("le column should not be nullable");
-                let le = le_str.parse::<f64>().unwrap();
-                bucket.push(le);
-
-                let counter = field_array
-                    .get(cursor + bias)
-                    .as_value_ref()
-                    .as_f64()
-                    .unwrap()
-                    .expect("field column should not be nullable");
-                counters.push(counter);
-            }
-            // ignore invalid data
-            let result = Self::evaluate_row(self.quantile, &bucket, &counters).unwrap_or(f64::NAN);
-            self.output_buffer[self.field_column_index].push_value_ref(ValueRef::from(result));
-            cursor += bucket_num;
-            remaining_rows -= bucket_num;
-            self.output_buffered_rows += 1;
+        let le_array = batch.column(self.le_column_index);
+        let field_array = batch.column(self.field_column_index);
+        for group_end in group_ends {
+            let normal_values = self
+                .normal_indices
+                .iter()
+                .map(|index| batch.column(*index).get(cursor))
+                .collect();
+            // parse `le` and sort the buckets numerically, as each histogram may
+            // have its own bucket set
+            let mut buckets = (cursor..group_end)
+                .map(|row| {
+                    let le_str_val = le_array.get(row);
+                    let le_str_val_ref = le_str_val.as_value_ref();
+                    let le_str = le_str_val_ref
+                        .as_string()
+                        .unwrap()
+                        .expect("le column should not be nullable");
+                    let le = le_str.parse::<f64>().unwrap();
+                    let counter = field_array
+                        .get(row)
+                        .as_value_ref()
+                        .as_f64()
+                        .unwrap()
+                        .expect("field column should not be nullable");
+                    (le, counter)
+                })
+                .collect::<Vec<_>>();
+            buckets.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let (bucket, counters) = buckets.into_iter().unzip();
+
+            self.push_folded(FoldedHistogram {
+                normal_values,
+                bucket,
+                counters,
+                prev_bucket: None,
+            });
+            cursor = group_end;
+        }
+        if is_end {
+            self.flush_pending();
         }
 
-        let remaining_input_batch = batch.into_df_record_batch().slice(cursor, remaining_rows);
+        let remaining_input_batch = batch
+            .into_df_record_batch()
+            .slice(cursor, num_rows - cursor);
         self.input_buffered_rows = remaining_input_batch.num_rows();
         self.input_buffer.push(remaining_input_batch);
 
         Ok(())
     }
 
+    /// Emit the pending histogram now that its next one is known, and hold the
+    /// new one back.
+    fn push_folded(&mut self, mut folded: FoldedHistogram) {
+        if let Some(pending) = self.pending.take() {
+            if self.is_same_series(&pending, &folded) {
+                self.emit_folded(&pending, Some(&folded.bucket));
+                folded.prev_bucket = Some(pending.bucket);
+            } else {
+                self.emit_folded(&pending, None);
+            }
+        }
+        self.pending = Some(folded);
+    }
+
+    /// Emit the pending histogram as the last one of its series.
+    fn flush_pending(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            self.emit_folded(&pending, None);
+        }
+    }
+
+    /// Evaluate the histogram and put the result to output buffer.
+    fn emit_folded(&mut self, folded: &FoldedHistogram, next_bucket: Option<&[f64]>) {
+        for (normal_index, val) in self.normal_indices.iter().zip(&folded.normal_values) {
+            self.output_buffer[*normal_index].push_value_ref(val.as_value_ref());
+        }
+        let result = if folded.is_partial(next_bucket) {
+            f64::NAN
+        } else {
+            // ignore invalid data
+            Self::evaluate_row(self.quantile, &folded.bucket, &folded.counters).unwrap_or(f64::NAN)
+        };
+        self.output_buffer[self.field_column_index].push_value_ref(ValueRef::from(result));
+        self.output_buffered_rows += 1;
+    }
+
+    /// Whether two histograms are of the same series, i.e. only differ in time.
+    fn is_same_series(&self, lhs: &FoldedHistogram, rhs: &FoldedHistogram) -> bool {
+        let Some(ts_normal_index) = self.ts_normal_index else {
+            return lhs.normal_values == rhs.normal_values;
+        };
+        lhs.normal_values
+            .iter()
+            .zip(&rhs.normal_values)
+            .enumerate()
+            .all(|(i, (l, r))| i == ts_normal_index || l == r)
+    }
+
     fn push_input_buf(&mut self, batch: RecordBatch) {
         self.input_buffered_rows += batch.num_rows();
         self.input_buffer.push(batch);
@@ -632,16 +717,12 @@ impl HistogramFoldStream {
             .map_err(|e| DataFusionError::ArrowError(e, None))
     }
 
-    /// Find the first `+Inf` which indicates the end of the bucket group
+    /// Find the (exclusive) end of every complete bucket group in the batch.
     ///
-    /// If the return value equals to batch's num_rows means the it's not found
-    /// in this batch
-    fn find_positive_inf(&self, batch: &RecordBatch) -> DataFusionResult<usize> {
-        // fuse this function. It should not be called when the
-        // bucket size is already know.
-        if let Some(bucket_size) = self.bucket_size {
-            return Ok(bucket_size);
-        }
+    /// A group ends where the value of any normal column changes. Without the time
+    /// index column, a group also ends at the `+Inf` bucket. The last group is only
+    /// complete if it ends at `+Inf` or `is_end` is set.
+    fn find_group_ends(&self, batch: &RecordBatch, is_end: bool) -> DataFusionResult<Vec<usize>> {
         let string_le_array = batch.column(self.le_column_index);
         let float_le_array = compute::cast(&string_le_array, &DataType::Float64).map_err(|e| {
             DataFusionError::Execution(format!(
@@ -658,15 +739,43 @@ impl HistogramFoldStream {
                     float_le_array.data_type()
                 ))
             })?;
-        for (i, v) in le_as_f64_array.iter().enumerate() {
-            if let Some(v) = v
-                && v == f64::INFINITY
-            {
-                return Ok(i);
+
+        let series_ranges = if self.normal_indices.is_empty() {
+            vec![0..batch.num_rows()]
+        } else {
+            let normal_columns = self
+                .normal_indices
+                .iter()
+                .map(|index| batch.column(*index).clone())
+                .collect::<Vec<_>>();
+            compute::partition(&normal_columns)
+                .map_err(|e| DataFusionError::ArrowError(e, None))?
+                .ranges()
+        };
+
+        let mut group_ends = vec![];
+        let num_series = series_ranges.len();
+        for (i, range) in series_ranges.into_iter().enumerate() {
+            let is_last = i + 1 == num_series;
+            if self.ts_normal_index.is_some() {
+                // one series at one timestamp is exactly one histogram
+                if !is_last || is_end {
+                    group_ends.push(range.end);
+                }
+                continue;
+            }
+            for row in range.clone() {
+                if le_as_f64_array.is_valid(row) && le_as_f64_array.value(row) == f64::INFINITY {
+                    group_ends.push(row + 1);
+                }
+            }
+            // the `+Inf` bucket is missing
+            if group_ends.last() != Some(&range.end) && (!is_last || is_end) {
+                group_ends.push(range.end);
             }
         }
 
-        Ok(batch.num_rows())
+        Ok(group_ends)
     }
 
     /// Evaluate the field column and return the result
@@ -675,9 +784,10 @@ impl HistogramFoldStream {
         if bucket.len() <= 1 {
             return Ok(f64::NAN);
         }
-        if bucket.last().unwrap().is_finite() {
+        // the last bucket is treated as `+Inf` even if it's finite
+        if bucket[..bucket.len() - 1].iter().any(|le| le.is_infinite()) {
             return Err(DataFusionError::Execution(
-                "last bucket should be +Inf".to_string(),
+                "only the last bucket can be +Inf".to_string(),
             ));
         }
         if bucket.len() != counter.len() {
@@ -726,8 +836,8 @@ impl HistogramFoldStream {
 mod test {
     use std::sync::Arc;
 
-    use datafusion::arrow::array::Float64Array;
-    use datafusion::arrow::datatypes::{Field, Schema};
+    use datafusion::arrow::array::{Float64Array, TimestampMillisecondArray};
+    use datafusion::arrow::datatypes::{Field, Schema, TimeUnit};
     use datafusion::common::ToDFSchema;
     use datafusion::physical_plan::memory::MemoryExec;
     use datafusion::prelude::SessionContext;
@@ -843,6 +953,197 @@ mod test {
         assert_eq!(result_literal, expected);
     }
 
+    #[tokio::test]
+    async fn fold_without_positive_inf() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("host", DataType::Utf8, true),
+            Field::new("le", DataType::Utf8, true),
+            Field::new("val", DataType::Float64, true),
+        ]));
+        let batch = |hosts: Vec<&str>, les: Vec<&str>, vals: Vec<f64>| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(StringArray::from(hosts)) as _,
+                    Arc::new(StringArray::from(les)) as _,
+                    Arc::new(Float64Array::from(vals)) as _,
+                ],
+            )
+            .unwrap()
+        };
+        // `host_1` and `host_3` have no `+Inf` bucket
+        let data = vec![
+            batch(vec!["host_1", "host_1"], vec!["0.1", "1"], vec![1.0, 1.0]),
+            batch(
+                vec!["host_1", "host_2", "host_2", "host_2", "host_3", "host_3"],
+                vec!["10", "0.1", "1", "+Inf", "1", "2"],
+                vec![4.0, 1.0, 1.0, 4.0, 1.0, 3.0],
+            ),
+            batch(vec!["host_3"], vec!["4"], vec![4.0]),
+        ];
+        let memory_exec = Arc::new(MemoryExec::try_new(&[data], schema, None).unwrap());
+        let output_schema: SchemaRef = Arc::new(
+            (*HistogramFold::convert_schema(
+                &Arc::new(memory_exec.schema().to_dfschema().unwrap()),
+                "le",
+            )
+            .unwrap()
+            .as_ref())
+            .clone()
+            .into(),
+        );
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(output_schema.clone()),
+            Partitioning::UnknownPartitioning(1),
+            EmissionType::Incremental,
+            Boundedness::Bounded,
+        );
+        let fold_exec = Arc::new(HistogramFoldExec {
+            le_column_index: 1,
+            field_column_index: 2,
+            quantile: 0.5,
+            ts_column_index: 9999, // not exist but doesn't matter
+            input: memory_exec,
+            output_schema,
+            metric: ExecutionPlanMetricsSet::new(),
+            properties,
+        });
+
+        let session_context = SessionContext::default();
+        let result = datafusion::physical_plan::collect(fold_exec, session_context.task_ctx())
+            .await
+            .unwrap();
+        let result_literal = datatypes::arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string();
+
+        // the largest `le` of `host_1` is treated as `+Inf`, same as `host_2`
+        let expected = String::from(
+            "+--------+-----+
+| host   | val |
++--------+-----+
+| host_1 | 1.0 |
+| host_2 | 1.0 |
+| host_3 | 1.5 |
++--------+-----+",
+        );
+        assert_eq!(result_literal, expected);
+    }
+
+    #[tokio::test]
+    async fn fold_changing_bucket_set() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("host", DataType::Utf8, true),
+            Field::new("ts", DataType::Timestamp(TimeUnit::Millisecond, None), true),
+            Field::new("le", DataType::Utf8, true),
+            Field::new("val", DataType::Float64, true),
+        ]));
+        let batch = |hosts: Vec<&str>, ts: Vec<i64>, les: Vec<&str>, vals: Vec<f64>| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(StringArray::from(hosts)) as _,
+                    Arc::new(TimestampMillisecondArray::from(ts)) as _,
+                    Arc::new(StringArray::from(les)) as _,
+                    Arc::new(Float64Array::from(vals)) as _,
+                ],
+            )
+            .unwrap()
+        };
+        // `host_1` adds the `le="1"` bucket at 10s, and only has two buckets
+        // scraped at 20s. Buckets at 10s are in string order.
+        let data = vec![
+            batch(
+                vec!["host_1"; 5],
+                vec![0, 0, 0, 10_000, 10_000],
+                vec!["0.5", "2", "+Inf", "+Inf", "0.5"],
+                vec![1.0, 2.0, 4.0, 4.0, 1.0],
+            ),
+            batch(
+                vec!["host_1"; 6],
+                vec![10_000, 10_000, 20_000, 20_000, 30_000, 30_000],
+                vec!["1", "2", "0.5", "2", "0.5", "1"],
+                vec![2.0, 3.0, 1.0, 3.0, 2.0, 4.0],
+            ),
+            batch(
+                vec!["host_1", "host_1", "host_2", "host_2"],
+                vec![30_000, 30_000, 0, 0],
+                vec!["2", "+Inf", "1", "+Inf"],
+                vec![6.0, 8.0, 2.0, 4.0],
+            ),
+        ];
+        let memory_exec = Arc::new(MemoryExec::try_new(&[data], schema, None).unwrap());
+        let output_schema: SchemaRef = Arc::new(
+            (*HistogramFold::convert_schema(
+                &Arc::new(memory_exec.schema().to_dfschema().unwrap()),
+                "le",
+            )
+            .unwrap()
+            .as_ref())
+            .clone()
+            .into(),
+        );
+        let properties = PlanProperties::new(
+            EquivalenceProperties::new(output_schema.clone()),
+            Partitioning::UnknownPartitioning(1),
+            EmissionType::Incremental,
+            Boundedness::Bounded,
+        );
+        let fold_exec = Arc::new(HistogramFoldExec {
+            le_column_index: 2,
+            field_column_index: 3,
+            quantile: 0.5,
+            ts_column_index: 1,
+            input: memory_exec,
+            output_schema,
+            metric: ExecutionPlanMetricsSet::new(),
+            properties,
+        });
+
+        let session_context = SessionContext::default();
+        let result = datafusion::physical_plan::collect(fold_exec, session_context.task_ctx())
+            .await
+            .unwrap();
+        let result_literal = datatypes::arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string();
+
+        // the partial scrape at 20s yields NaN
+        let expected = String::from(
+            "+--------+---------------------+-----+
+| host   | ts                  | val |
++--------+---------------------+-----+
+| host_1 | 1970-01-01T00:00:00 | 2.0 |
+| host_1 | 1970-01-01T00:00:10 | 1.0 |
+| host_1 | 1970-01-01T00:00:20 | NaN |
+| host_1 | 1970-01-01T00:00:30 | 1.0 |
+| host_2 | 1970-01-01T00:00:00 | 1.0 |
++--------+---------------------+-----+",
+        );
+        assert_eq!(result_literal, expected);
+    }
+
+    #[test]
+    fn partial_histogram() {
+        let histogram = |bucket: Vec<f64>, prev_bucket: Option<Vec<f64>>| FoldedHistogram {
+            normal_values: vec![],
+            counters: vec![0.0; bucket.len()],
+            bucket,
+            prev_bucket,
+        };
+        let full = vec![0.5, 1.0, 2.0, f64::INFINITY];
+        let partial = vec![0.5, 2.0];
+
+        assert!(histogram(partial.clone(), Some(full.clone())).is_partial(Some(&full)));
+        // at the edge of the series
+        assert!(!histogram(partial.clone(), None).is_partial(None));
+        assert!(!histogram(partial.clone(), None).is_partial(Some(&full)));
+        // buckets removed since then
+        assert!(!histogram(partial.clone(), Some(full.clone())).is_partial(Some(&partial)));
+        // buckets added since then
+        assert!(!histogram(full.clone(), Some(partial.clone())).is_partial(Some(&full)));
+    }
+
     #[test]
     fn confirm_schema() {
         let input_schema = Schema::new(vec![
@@ -950,6 +1251,17 @@ mod test {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn evaluate_without_positive_inf() {
+        let bucket = [0.0, 1.0, 2.0, 3.0, 4.0];
+        let counters = [0.0, 10.0, 20.0, 30.0, 40.0];
+        let result = HistogramFoldStream::evaluate_row(0.5, &bucket, &counters).unwrap();
+        assert_eq!(2.0, result);
+        // falls into the top bucket
+        let result = HistogramFoldStream::evaluate_row(0.9, &bucket, &counters).unwrap();
+        assert_eq!(3.0, result);
+    }
+
     #[test]
     fn evaluate_small_fraction() {
         let bucket = [0.0, 2.0, 4.0, 6.0, f64::INFINITY];
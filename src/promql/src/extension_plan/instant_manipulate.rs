@@ -13,7 +13,6 @@
 // limitations under the License.
 
 use std::any::Any;
-use std::cmp::Ordering;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
@@ -41,7 +40,9 @@ use prost::Message;
 use snafu::ResultExt;
 
 use crate::error::{DeserializeSnafu, Result};
-use crate::extension_plan::{Millisecond, METRIC_NUM_SERIES};
+use crate::extension_plan::{
+    Millisecond, METRIC_NUM_SERIES, METRIC_ROWS_EMITTED, METRIC_ROWS_EXAMINED,
+};
 use crate::metrics::PROMQL_SERIES_COUNT;
 
 /// Manipulate the input record batch to make it suitable for Instant Operator.
@@ -49,11 +50,16 @@ use crate::metrics::PROMQL_SERIES_COUNT;
 /// This plan will try to align the input time series, for every timestamp between
 /// `start` and `end` with step `interval`. Find in the `lookback` range if data
 /// is missing at the given timestamp.
+///
+/// A NaN sample is a staleness marker, which ends the series. The series keeps
+/// its last value for `staleness_delta` after the marker, within the lookback
+/// range. Prometheus ends it at the marker, like a zero `staleness_delta`.
 #[derive(Debug, PartialEq, Eq, Hash, PartialOrd)]
 pub struct InstantManipulate {
     start: Millisecond,
     end: Millisecond,
     lookback_delta: Millisecond,
+    staleness_delta: Millisecond,
     interval: Millisecond,
     time_index_column: String,
     /// A optional column for validating staleness
@@ -81,8 +87,16 @@ impl UserDefinedLogicalNodeCore for InstantManipulate {
     fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             f,
-            "PromInstantManipulate: range=[{}..{}], lookback=[{}], interval=[{}], time index=[{}]",
-            self.start, self.end, self.lookback_delta, self.interval, self.time_index_column
+            "PromInstantManipulate: range=[{}..{}], lookback=[{}], ",
+            self.start, self.end, self.lookback_delta
+        )?;
+        if self.staleness_delta > 0 {
+            write!(f, "staleness=[{}], ", self.staleness_delta)?;
+        }
+        write!(
+            f,
+            "interval=[{}], time index=[{}]",
+            self.interval, self.time_index_column
         )
     }
 
@@ -101,6 +115,7 @@ impl UserDefinedLogicalNodeCore for InstantManipulate {
             start: self.start,
             end: self.end,
             lookback_delta: self.lookback_delta,
+            staleness_delta: self.staleness_delta,
             interval: self.interval,
             time_index_column: self.time_index_column.clone(),
             field_column: self.field_column.clone(),
@@ -123,6 +138,7 @@ impl InstantManipulate {
             start,
             end,
             lookback_delta,
+            staleness_delta: 0,
             interval,
             time_index_column,
             field_column,
@@ -130,6 +146,12 @@ impl InstantManipulate {
         }
     }
 
+    /// Keeps the last value of a series for `staleness_delta` after its staleness marker.
+    pub fn with_staleness_delta(mut self, staleness_delta: Millisecond) -> Self {
+        self.staleness_delta = staleness_delta;
+        self
+    }
+
     pub const fn name() -> &'static str {
         "InstantManipulate"
     }
@@ -139,6 +161,7 @@ impl InstantManipulate {
             start: self.start,
             end: self.end,
             lookback_delta: self.lookback_delta,
+            staleness_delta: self.staleness_delta,
             interval: self.interval,
             time_index_column: self.time_index_column.clone(),
             field_column: self.field_column.clone(),
@@ -147,6 +170,7 @@ impl InstantManipulate {
         })
     }
 
+    /// The `staleness_delta` isn't serialized, as the plan isn't pushed down to datanodes.
     pub fn serialize(&self) -> Vec<u8> {
         pb::InstantManipulate {
             start: self.start,
@@ -175,6 +199,7 @@ impl InstantManipulate {
             start: pb_instant_manipulate.start,
             end: pb_instant_manipulate.end,
             lookback_delta: pb_instant_manipulate.lookback_delta,
+            staleness_delta: 0,
             interval: pb_instant_manipulate.interval,
             time_index_column: pb_instant_manipulate.time_index,
             field_column,
@@ -188,6 +213,7 @@ pub struct InstantManipulateExec {
     start: Millisecond,
     end: Millisecond,
     lookback_delta: Millisecond,
+    staleness_delta: Millisecond,
     interval: Millisecond,
     time_index_column: String,
     field_column: Option<String>,
@@ -231,6 +257,7 @@ impl ExecutionPlan for InstantManipulateExec {
             start: self.start,
             end: self.end,
             lookback_delta: self.lookback_delta,
+            staleness_delta: self.staleness_delta,
             interval: self.interval,
             time_index_column: self.time_index_column.clone(),
             field_column: self.field_column.clone(),
@@ -253,6 +280,20 @@ impl ExecutionPlan for InstantManipulateExec {
                 name: METRIC_NUM_SERIES.into(),
                 count: num_series.clone(),
             });
+        let rows_examined = Count::new();
+        metrics_builder
+            .with_partition(partition)
+            .build(MetricValue::Count {
+                name: METRIC_ROWS_EXAMINED.into(),
+                count: rows_examined.clone(),
+            });
+        let rows_emitted = Count::new();
+        metrics_builder
+            .with_partition(partition)
+            .build(MetricValue::Count {
+                name: METRIC_ROWS_EMITTED.into(),
+                count: rows_emitted.clone(),
+            });
 
         let input = self.input.execute(partition, context)?;
         let schema = input.schema();
@@ -269,6 +310,7 @@ impl ExecutionPlan for InstantManipulateExec {
             start: self.start,
             end: self.end,
             lookback_delta: self.lookback_delta,
+            staleness_delta: self.staleness_delta,
             interval: self.interval,
             time_index,
             field_index,
@@ -276,6 +318,8 @@ impl ExecutionPlan for InstantManipulateExec {
             input,
             metric: baseline_metric,
             num_series,
+            rows_examined,
+            rows_emitted,
         }))
     }
 
@@ -318,8 +362,16 @@ impl DisplayAs for InstantManipulateExec {
             DisplayFormatType::Default | DisplayFormatType::Verbose => {
                 write!(
                     f,
-                    "PromInstantManipulateExec: range=[{}..{}], lookback=[{}], interval=[{}], time index=[{}]",
-                   self.start,self.end, self.lookback_delta, self.interval, self.time_index_column
+                    "PromInstantManipulateExec: range=[{}..{}], lookback=[{}], ",
+                    self.start, self.end, self.lookback_delta
+                )?;
+                if self.staleness_delta > 0 {
+                    write!(f, "staleness=[{}], ", self.staleness_delta)?;
+                }
+                write!(
+                    f,
+                    "interval=[{}], time index=[{}]",
+                    self.interval, self.time_index_column
                 )
             }
         }
@@ -330,6 +382,7 @@ pub struct InstantManipulateStream {
     start: Millisecond,
     end: Millisecond,
     lookback_delta: Millisecond,
+    staleness_delta: Millisecond,
     interval: Millisecond,
     // Column index of TIME INDEX column's position in schema
     time_index: usize,
@@ -340,6 +393,10 @@ pub struct InstantManipulateStream {
     metric: BaselineMetrics,
     /// Number of series processed.
     num_series: Count,
+    /// Number of input timestamps compared while aligning.
+    rows_examined: Count,
+    /// Number of aligned rows produced.
+    rows_emitted: Count,
 }
 
 impl RecordBatchStream for InstantManipulateStream {
@@ -374,8 +431,6 @@ impl InstantManipulateStream {
     // refer to Go version: https://github.com/prometheus/prometheus/blob/e934d0f01158a1d55fa0ebb035346b195fcc1260/promql/engine.go#L1571
     // and the function `vectorSelectorSingle`
     pub fn manipulate(&self, input: RecordBatch) -> DataFusionResult<RecordBatch> {
-        let mut take_indices = vec![];
-
         let ts_column = input
             .column(self.time_index)
             .as_any()
@@ -391,73 +446,17 @@ impl InstantManipulateStream {
             .field_index
             .and_then(|index| input.column(index).as_any().downcast_ref::<Float64Array>());
 
-        let mut cursor = 0;
-
-        let aligned_ts_iter = (self.start..=self.end).step_by(self.interval as usize);
-        let mut aligned_ts = vec![];
-
-        // calculate the offsets to take
-        'next: for expected_ts in aligned_ts_iter {
-            // first, search toward end to see if there is matched timestamp
-            while cursor < ts_column.len() {
-                let curr = ts_column.value(cursor);
-                match curr.cmp(&expected_ts) {
-                    Ordering::Equal => {
-                        if let Some(field_column) = &field_column
-                            && field_column.value(cursor).is_nan()
-                        {
-                            // ignore the NaN value
-                        } else {
-                            take_indices.push(cursor as u64);
-                            aligned_ts.push(expected_ts);
-                        }
-                        continue 'next;
-                    }
-                    Ordering::Greater => break,
-                    Ordering::Less => {}
-                }
-                cursor += 1;
-            }
-            if cursor == ts_column.len() {
-                cursor -= 1;
-                // short cut this loop
-                if ts_column.value(cursor) + self.lookback_delta < expected_ts {
-                    break;
-                }
-            }
-
-            // then examine the value
-            let curr_ts = ts_column.value(cursor);
-            if curr_ts + self.lookback_delta < expected_ts {
-                continue;
-            }
-            if curr_ts > expected_ts {
-                // exceeds current expected timestamp, examine the previous value
-                if let Some(prev_cursor) = cursor.checked_sub(1) {
-                    let prev_ts = ts_column.value(prev_cursor);
-                    if prev_ts + self.lookback_delta >= expected_ts {
-                        // only use the point in the time range
-                        if let Some(field_column) = &field_column
-                            && field_column.value(prev_cursor).is_nan()
-                        {
-                            // if the newest value is NaN, it means the value is stale, so we should not use it
-                            continue;
-                        }
-                        // use this point
-                        take_indices.push(prev_cursor as u64);
-                        aligned_ts.push(expected_ts);
-                    }
-                }
-            } else if let Some(field_column) = &field_column
-                && field_column.value(cursor).is_nan()
-            {
-                // if the newest value is NaN, it means the value is stale, so we should not use it
-            } else {
-                // use this point
-                take_indices.push(cursor as u64);
-                aligned_ts.push(expected_ts);
-            }
-        }
+        let (take_indices, aligned_ts, rows_examined) = align_samples(
+            self.start,
+            self.end,
+            self.lookback_delta,
+            self.staleness_delta,
+            self.interval,
+            ts_column,
+            field_column,
+        );
+        self.rows_examined.add(rows_examined);
+        self.rows_emitted.add(take_indices.len());
 
         // take record batch and replace the time index column
         self.take_record_batch_optional(input, take_indices, aligned_ts)
@@ -486,9 +485,110 @@ impl InstantManipulateStream {
     }
 }
 
+/// Selects the sample of each aligned timestamp between `start` and `end`.
+///
+/// Both the timestamps and the cursor into the samples only move forward, so a
+/// series is aligned in `O(steps + rows)`. When the lookback window of a step
+/// is empty, all steps before the next sample are skipped at once.
+///
+/// A selected NaN sample is a staleness marker. Within `staleness_delta` after it,
+/// the newest sample before it in the lookback range is selected instead.
+///
+/// Returns the indices of the selected samples, their aligned timestamps, and the
+/// number of sample timestamps compared.
+fn align_samples(
+    start: Millisecond,
+    end: Millisecond,
+    lookback_delta: Millisecond,
+    staleness_delta: Millisecond,
+    interval: Millisecond,
+    ts_column: &TimestampMillisecondArray,
+    field_column: Option<&Float64Array>,
+) -> (Vec<u64>, Vec<Millisecond>, usize) {
+    let is_stale = |index: usize| field_column.is_some_and(|field| field.value(index).is_nan());
+    let timestamps = ts_column.values();
+    let mut take_indices = vec![];
+    let mut aligned_ts = vec![];
+    let mut rows_examined = 0;
+    let mut cursor = 0;
+
+    let mut expected_ts = start;
+    while expected_ts <= end {
+        // move to the first sample not before the expected timestamp
+        while cursor < timestamps.len() {
+            rows_examined += 1;
+            if timestamps[cursor] >= expected_ts {
+                break;
+            }
+            cursor += 1;
+        }
+
+        // use the matched sample, or the newest one before it in the lookback range
+        let selected = if timestamps.get(cursor) == Some(&expected_ts) {
+            Some(cursor)
+        } else {
+            cursor
+                .checked_sub(1)
+                .filter(|prev| timestamps[*prev] + lookback_delta >= expected_ts)
+        };
+
+        match selected {
+            Some(index) if is_stale(index) => {
+                // a NaN value means the series is stale, so we should not use it,
+                // but the series keeps its newest sample before it in the lookback
+                // range for the staleness delta
+                let kept = (expected_ts - timestamps[index] < staleness_delta)
+                    .then(|| (0..index).rev().find(|prev| !is_stale(*prev)))
+                    .flatten()
+                    .filter(|prev| timestamps[*prev] + lookback_delta >= expected_ts);
+                if let Some(prev) = kept {
+                    take_indices.push(prev as u64);
+                    aligned_ts.push(expected_ts);
+                }
+            }
+            Some(index) => {
+                take_indices.push(index as u64);
+                aligned_ts.push(expected_ts);
+            }
+            None => {
+                // the lookback window stays empty until the next sample
+                let Some(next_ts) = timestamps.get(cursor) else {
+                    break;
+                };
+                // jump to the first step not before the next sample
+                let skipped_steps = (next_ts - expected_ts - 1) / interval + 1;
+                match skipped_steps
+                    .checked_mul(interval)
+                    .and_then(|skipped| expected_ts.checked_add(skipped))
+                {
+                    Some(ts) => {
+                        expected_ts = ts;
+                        continue;
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        match expected_ts.checked_add(interval) {
+            Some(ts) => expected_ts = ts,
+            None => break,
+        }
+    }
+
+    (take_indices, aligned_ts, rows_examined)
+}
+
 #[cfg(test)]
 mod test {
+    use datafusion::arrow::array::StringArray;
+    use datafusion::arrow::datatypes::{
+        ArrowPrimitiveType, DataType, Field, Schema, TimestampMillisecondType,
+    };
+    use datafusion::physical_plan::memory::MemoryExec;
     use datafusion::prelude::SessionContext;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
     use super::*;
     use crate::extension_plan::test_util::{
@@ -502,6 +602,27 @@ mod test {
         interval: Millisecond,
         expected: String,
         contains_nan: bool,
+    ) {
+        do_staleness_test(
+            start,
+            end,
+            lookback_delta,
+            0,
+            interval,
+            expected,
+            contains_nan,
+        )
+        .await
+    }
+
+    async fn do_staleness_test(
+        start: Millisecond,
+        end: Millisecond,
+        lookback_delta: Millisecond,
+        staleness_delta: Millisecond,
+        interval: Millisecond,
+        expected: String,
+        contains_nan: bool,
     ) {
         let memory_exec = if contains_nan {
             Arc::new(prepare_test_data_with_nan())
@@ -512,6 +633,7 @@ mod test {
             start,
             end,
             lookback_delta,
+            staleness_delta,
             interval,
             time_index_column: TIME_INDEX_COLUMN.to_string(),
             field_column: Some("value".to_string()),
@@ -795,6 +917,45 @@ mod test {
         do_normalize_test(1, 300_001, 10_000, 10_000, expected, true).await;
     }
 
+    #[tokio::test]
+    async fn staleness_differs_from_lookback() {
+        // The series keeps its last value for 10s after the stale markers at 30s and 90s.
+        let expected = String::from(
+            "+---------------------+-------+\
+            \n| timestamp           | value |\
+            \n+---------------------+-------+\
+            \n| 1970-01-01T00:00:00 | 0.0   |\
+            \n| 1970-01-01T00:00:10 | 0.0   |\
+            \n| 1970-01-01T00:00:20 | 0.0   |\
+            \n| 1970-01-01T00:00:30 | 0.0   |\
+            \n| 1970-01-01T00:01:00 | 6.0   |\
+            \n| 1970-01-01T00:01:10 | 6.0   |\
+            \n| 1970-01-01T00:01:20 | 6.0   |\
+            \n| 1970-01-01T00:01:30 | 6.0   |\
+            \n| 1970-01-01T00:02:00 | 12.0  |\
+            \n| 1970-01-01T00:02:10 | 12.0  |\
+            \n| 1970-01-01T00:02:20 | 12.0  |\
+            \n| 1970-01-01T00:02:30 | 12.0  |\
+            \n+---------------------+-------+",
+        );
+        do_staleness_test(0, 300_000, 30_000, 10_000, 10_000, expected, true).await;
+
+        // The kept value is still limited by the lookback range.
+        let expected = String::from(
+            "+---------------------+-------+\
+            \n| timestamp           | value |\
+            \n+---------------------+-------+\
+            \n| 1970-01-01T00:00:00 | 0.0   |\
+            \n| 1970-01-01T00:00:10 | 0.0   |\
+            \n| 1970-01-01T00:01:00 | 6.0   |\
+            \n| 1970-01-01T00:01:10 | 6.0   |\
+            \n| 1970-01-01T00:02:00 | 12.0  |\
+            \n| 1970-01-01T00:02:10 | 12.0  |\
+            \n+---------------------+-------+",
+        );
+        do_staleness_test(0, 300_000, 10_000, 30_000, 10_000, expected, true).await;
+    }
+
     #[tokio::test]
     async fn ultra_large_range() {
         let expected = String::from(
@@ -808,4 +969,186 @@ mod test {
         );
         do_normalize_test(1, 900_000_000_000_000, 10_000, 10_000, expected, true).await;
     }
+
+    #[tokio::test]
+    async fn unbounded_lookback_takes_latest_sample_of_each_series() {
+        // series `foo` ends at 40s and `bar` ends at 100s, each in its own batch
+        // as divided by `SeriesDivide`
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(TIME_INDEX_COLUMN, TimestampMillisecondType::DATA_TYPE, true),
+            Field::new("value", DataType::Float64, true),
+            Field::new("path", DataType::Utf8, true),
+        ]));
+        let series = |timestamps: Vec<i64>, values: Vec<f64>, path: &str| {
+            let len = timestamps.len();
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(TimestampMillisecondArray::from(timestamps)) as _,
+                    Arc::new(Float64Array::from(values)) as _,
+                    Arc::new(StringArray::from(vec![path; len])) as _,
+                ],
+            )
+            .unwrap()
+        };
+        let foo = series(vec![0, 20_000, 40_000], vec![1.0, 2.0, 3.0], "foo");
+        let bar = series(vec![60_000, 100_000], vec![10.0, 20.0], "bar");
+        let memory_exec =
+            Arc::new(MemoryExec::try_new(&[vec![foo, bar]], schema.clone(), None).unwrap());
+
+        let manipulate_exec = Arc::new(InstantManipulateExec {
+            start: 300_000,
+            end: 300_000,
+            lookback_delta: 900_000_000_000,
+            staleness_delta: 0,
+            interval: 10_000,
+            time_index_column: TIME_INDEX_COLUMN.to_string(),
+            field_column: Some("value".to_string()),
+            input: memory_exec,
+            metric: ExecutionPlanMetricsSet::new(),
+        });
+        let session_context = SessionContext::default();
+        let result =
+            datafusion::physical_plan::collect(manipulate_exec, session_context.task_ctx())
+                .await
+                .unwrap();
+        let result_literal = datatypes::arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string();
+
+        let expected = String::from(
+            "+---------------------+-------+------+\
+            \n| timestamp           | value | path |\
+            \n+---------------------+-------+------+\
+            \n| 1970-01-01T00:05:00 | 3.0   | foo  |\
+            \n| 1970-01-01T00:05:00 | 20.0  | bar  |\
+            \n+---------------------+-------+------+",
+        );
+        assert_eq!(result_literal, expected);
+    }
+
+    /// The previous alignment algorithm, kept to verify [align_samples] against.
+    fn reference_align_samples(
+        start: Millisecond,
+        end: Millisecond,
+        lookback_delta: Millisecond,
+        interval: Millisecond,
+        ts_column: &TimestampMillisecondArray,
+        field_column: Option<&Float64Array>,
+    ) -> (Vec<u64>, Vec<Millisecond>) {
+        let mut take_indices = vec![];
+        let mut aligned_ts = vec![];
+        let mut cursor = 0;
+
+        'next: for expected_ts in (start..=end).step_by(interval as usize) {
+            while cursor < ts_column.len() {
+                let curr = ts_column.value(cursor);
+                match curr.cmp(&expected_ts) {
+                    std::cmp::Ordering::Equal => {
+                        if !field_column.is_some_and(|field| field.value(cursor).is_nan()) {
+                            take_indices.push(cursor as u64);
+                            aligned_ts.push(expected_ts);
+                        }
+                        continue 'next;
+                    }
+                    std::cmp::Ordering::Greater => break,
+                    std::cmp::Ordering::Less => {}
+                }
+                cursor += 1;
+            }
+            if cursor == ts_column.len() {
+                cursor -= 1;
+                if ts_column.value(cursor) + lookback_delta < expected_ts {
+                    break;
+                }
+            }
+
+            let curr_ts = ts_column.value(cursor);
+            if curr_ts + lookback_delta < expected_ts {
+                continue;
+            }
+            if curr_ts > expected_ts {
+                if let Some(prev_cursor) = cursor.checked_sub(1) {
+                    let prev_ts = ts_column.value(prev_cursor);
+                    if prev_ts + lookback_delta >= expected_ts {
+                        if field_column.is_some_and(|field| field.value(prev_cursor).is_nan()) {
+                            continue;
+                        }
+                        take_indices.push(prev_cursor as u64);
+                        aligned_ts.push(expected_ts);
+                    }
+                }
+            } else if !field_column.is_some_and(|field| field.value(cursor).is_nan()) {
+                take_indices.push(cursor as u64);
+                aligned_ts.push(expected_ts);
+            }
+        }
+
+        (take_indices, aligned_ts)
+    }
+
+    #[test]
+    fn align_samples_matches_reference() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..500 {
+            let len = rng.random_range(1..50);
+            let mut ts = rng.random_range(-100_000..100_000);
+            let mut timestamps = Vec::with_capacity(len);
+            let mut values = Vec::with_capacity(len);
+            for _ in 0..len {
+                timestamps.push(ts);
+                values.push(if rng.random_bool(0.1) { f64::NAN } else { 1.0 });
+                // mixes dense samples with long gaps
+                ts += if rng.random_bool(0.2) {
+                    rng.random_range(60_000..600_000)
+                } else {
+                    rng.random_range(1..20_000)
+                };
+            }
+            let ts_column = TimestampMillisecondArray::from(timestamps);
+            let field_column = Float64Array::from(values);
+
+            let start = rng.random_range(-200_000..200_000);
+            let end = start + rng.random_range(0..2_000_000);
+            let lookback_delta = rng.random_range(0..120_000);
+            let interval = rng.random_range(1..60_000);
+            for field in [None, Some(&field_column)] {
+                let (take_indices, aligned_ts, _) =
+                    align_samples(start, end, lookback_delta, 0, interval, &ts_column, field);
+                let expected = reference_align_samples(
+                    start,
+                    end,
+                    lookback_delta,
+                    interval,
+                    &ts_column,
+                    field,
+                );
+                assert_eq!((take_indices, aligned_ts), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn align_sparse_series_skips_empty_steps() {
+        // 10 samples in a day, evaluated every 15s
+        let ts_column =
+            TimestampMillisecondArray::from((0..10).map(|i| i * 8_640_000).collect::<Vec<_>>());
+        let (take_indices, aligned_ts, rows_examined) =
+            align_samples(0, 86_400_000, 300_000, 0, 15_000, &ts_column, None);
+
+        // each sample is selected by the 21 steps in its lookback window
+        assert_eq!(take_indices.len(), 210);
+        assert_eq!(aligned_ts.len(), 210);
+        assert!(rows_examined <= 210 + 10 + 10, "{rows_examined}");
+    }
+
+    #[test]
+    fn align_empty_series() {
+        let ts_column = TimestampMillisecondArray::from(Vec::<i64>::new());
+        let (take_indices, aligned_ts, rows_examined) =
+            align_samples(0, 86_400_000, 300_000, 0, 15_000, &ts_column, None);
+        assert!(take_indices.is_empty());
+        assert!(aligned_ts.is_empty());
+        assert_eq!(rows_examined, 0);
+    }
 }
@@ -17,9 +17,11 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use datafusion::arrow::array::{BooleanArray, Float64Array};
+use datafusion::arrow::array::{ArrayRef, BooleanArray, Float64Array};
 use datafusion::arrow::compute;
-use datafusion::common::{DFSchema, DFSchemaRef, Result as DataFusionResult, Statistics};
+use datafusion::common::{
+    DFSchema, DFSchemaRef, Result as DataFusionResult, ScalarValue, Statistics,
+};
 use datafusion::error::DataFusionError;
 use datafusion::execution::context::TaskContext;
 use datafusion::logical_expr::{EmptyRelation, Expr, LogicalPlan, UserDefinedLogicalNodeCore};
@@ -40,7 +42,7 @@ use prost::Message;
 use snafu::ResultExt;
 
 use crate::error::{DeserializeSnafu, Result};
-use crate::extension_plan::{Millisecond, METRIC_NUM_SERIES};
+use crate::extension_plan::{Millisecond, METRIC_NUM_DUPLICATES, METRIC_NUM_SERIES};
 use crate::metrics::PROMQL_SERIES_COUNT;
 
 /// Normalize the input record batch. Notice that for simplicity, this method assumes
@@ -49,6 +51,7 @@ use crate::metrics::PROMQL_SERIES_COUNT;
 /// Roughly speaking, this method does these things:
 /// - bias sample's timestamp by offset
 /// - sort the record batch based on timestamp column
+/// - remove duplicate timestamps of a series, keeping the last received sample
 /// - remove NaN values (optional)
 #[derive(Debug, PartialEq, Eq, Hash, PartialOrd)]
 pub struct SeriesNormalize {
@@ -233,20 +236,37 @@ impl ExecutionPlan for SeriesNormalizeExec {
                 count: num_series.clone(),
             });
 
+        let num_duplicates = Count::new();
+        metrics_builder
+            .with_partition(partition)
+            .build(MetricValue::Count {
+                name: METRIC_NUM_DUPLICATES.into(),
+                count: num_duplicates.clone(),
+            });
+
         let input = self.input.execute(partition, context)?;
         let schema = input.schema();
         let time_index = schema
             .column_with_name(&self.time_index_column_name)
             .expect("time index column not found")
             .0;
+        let tag_indices = self
+            .tag_columns
+            .iter()
+            .map(|tag| schema.index_of(tag))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
         Ok(Box::pin(SeriesNormalizeStream {
             offset: self.offset,
             time_index,
+            tag_indices,
             need_filter_out_nan: self.need_filter_out_nan,
             schema,
             input,
+            pending: None,
+            input_finished: false,
             metric: baseline_metric,
             num_series,
+            num_duplicates,
         }))
     }
 
@@ -281,16 +301,98 @@ pub struct SeriesNormalizeStream {
     offset: Millisecond,
     // Column index of TIME INDEX column's position in schema
     time_index: usize,
+    // Column indices of tag columns, which identify a series with the time index
+    tag_indices: Vec<usize>,
     need_filter_out_nan: bool,
 
     schema: SchemaRef,
     input: SendableRecordBatchStream,
+    /// The last deduplicated batch, which is held back until the next batch
+    /// arrives as the next batch may override its last sample.
+    pending: Option<RecordBatch>,
+    input_finished: bool,
     metric: BaselineMetrics,
     /// Number of series processed.
     num_series: Count,
+    /// Number of samples dropped for duplicate timestamps.
+    num_duplicates: Count,
 }
 
 impl SeriesNormalizeStream {
+    /// Removes duplicate timestamps of the same series in the `input` batch, and
+    /// returns the batch that was held back if it's complete.
+    ///
+    /// Like the last-write-wins storage, the last received sample of duplicates
+    /// is kept. The input is expected to be sorted by tags and time index, so
+    /// duplicates are adjacent.
+    fn dedup(&mut self, input: RecordBatch) -> DataFusionResult<Option<RecordBatch>> {
+        if input.num_rows() == 0 {
+            return Ok(Some(input));
+        }
+        let input = self.dedup_batch(input)?;
+        let Some(mut pending) = self.pending.replace(input) else {
+            return Ok(None);
+        };
+
+        // Safety: the pending batch and the new batch are not empty
+        let last = pending.num_rows() - 1;
+        let new_batch = self.pending.as_ref().unwrap();
+        if self.same_key(&pending, last, new_batch, 0)? {
+            pending = pending.slice(0, last);
+            self.num_duplicates.add(1);
+        }
+        if pending.num_rows() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(pending))
+    }
+
+    /// Removes duplicate timestamps of the same series inside the batch.
+    fn dedup_batch(&self, input: RecordBatch) -> DataFusionResult<RecordBatch> {
+        let num_rows = input.num_rows();
+        if num_rows < 2 {
+            return Ok(input);
+        }
+        let key_columns = self
+            .tag_indices
+            .iter()
+            .chain(Some(&self.time_index))
+            .map(|index| input.column(*index).clone())
+            .collect::<Vec<ArrayRef>>();
+        let partitions =
+            compute::partition(&key_columns).map_err(|e| DataFusionError::ArrowError(e, None))?;
+        if partitions.len() == num_rows {
+            return Ok(input);
+        }
+
+        let mut filter = vec![false; num_rows];
+        for range in partitions.ranges() {
+            filter[range.end - 1] = true;
+        }
+        self.num_duplicates.add(num_rows - partitions.len());
+        compute::filter_record_batch(&input, &BooleanArray::from(filter))
+            .map_err(|e| DataFusionError::ArrowError(e, None))
+    }
+
+    /// Returns whether the row `left_row` of `left` and the row `right_row` of `right`
+    /// are samples of the same series at the same time.
+    fn same_key(
+        &self,
+        left: &RecordBatch,
+        left_row: usize,
+        right: &RecordBatch,
+        right_row: usize,
+    ) -> DataFusionResult<bool> {
+        for index in self.tag_indices.iter().chain(Some(&self.time_index)) {
+            let left_value = ScalarValue::try_from_array(left.column(*index), left_row)?;
+            let right_value = ScalarValue::try_from_array(right.column(*index), right_row)?;
+            if left_value != right_value {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
     pub fn normalize(&self, input: RecordBatch) -> DataFusionResult<RecordBatch> {
         let ts_column = input
             .column(self.time_index)
@@ -347,21 +449,39 @@ impl Stream for SeriesNormalizeStream {
     type Item = DataFusionResult<RecordBatch>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let poll = match ready!(self.input.poll_next_unpin(cx)) {
-            Some(Ok(batch)) => {
-                self.num_series.add(1);
-                let timer = std::time::Instant::now();
-                let result = Ok(batch).and_then(|batch| self.normalize(batch));
-                self.metric.elapsed_compute().add_elapsed(timer);
-                Poll::Ready(Some(result))
-            }
-            None => {
-                PROMQL_SERIES_COUNT.observe(self.num_series.value() as f64);
-                Poll::Ready(None)
+        loop {
+            if self.input_finished {
+                let poll = match self.pending.take() {
+                    Some(batch) => Poll::Ready(Some(self.normalize(batch))),
+                    None => Poll::Ready(None),
+                };
+                return self.metric.record_poll(poll);
             }
-            Some(Err(e)) => Poll::Ready(Some(Err(e))),
-        };
-        self.metric.record_poll(poll)
+
+            let poll = match ready!(self.input.poll_next_unpin(cx)) {
+                Some(Ok(batch)) => {
+                    self.num_series.add(1);
+                    let timer = std::time::Instant::now();
+                    let result = self
+                        .dedup(batch)
+                        .and_then(|batch| batch.map(|batch| self.normalize(batch)).transpose());
+                    self.metric.elapsed_compute().add_elapsed(timer);
+                    match result {
+                        Ok(Some(batch)) => Poll::Ready(Some(Ok(batch))),
+                        // the batch is held back
+                        Ok(None) => continue,
+                        Err(e) => Poll::Ready(Some(Err(e))),
+                    }
+                }
+                None => {
+                    PROMQL_SERIES_COUNT.observe(self.num_series.value() as f64);
+                    self.input_finished = true;
+                    continue;
+                }
+                Some(Err(e)) => Poll::Ready(Some(Err(e))),
+            };
+            return self.metric.record_poll(poll);
+        }
     }
 }
 
@@ -467,4 +587,119 @@ mod test {
 
         assert_eq!(result_literal, expected);
     }
+
+    #[tokio::test]
+    async fn test_negative_and_sub_step_offset() {
+        // shifted exactly, with no rounding to the 30s step of the samples
+        for (offset, expected) in [
+            (7_000, vec![67_000, 127_000, 7_000, 37_000, 97_000]),
+            (-7_000, vec![53_000, 113_000, -7_000, 23_000, 83_000]),
+            (-60_000, vec![0, 60_000, -60_000, -30_000, 30_000]),
+        ] {
+            let normalize_exec = Arc::new(SeriesNormalizeExec {
+                offset,
+                time_index_column_name: TIME_INDEX_COLUMN.to_string(),
+                need_filter_out_nan: true,
+                input: Arc::new(prepare_test_data()),
+                metric: ExecutionPlanMetricsSet::new(),
+                tag_columns: vec!["path".to_string()],
+            });
+            let session_context = SessionContext::default();
+            let result =
+                datafusion::physical_plan::collect(normalize_exec, session_context.task_ctx())
+                    .await
+                    .unwrap();
+            let timestamps = result
+                .iter()
+                .flat_map(|batch| {
+                    batch
+                        .column(0)
+                        .as_any()
+                        .downcast_ref::<TimestampMillisecondArray>()
+                        .unwrap()
+                        .values()
+                        .to_vec()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(expected, timestamps, "offset={offset}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dedup_across_batches() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(TIME_INDEX_COLUMN, TimestampMillisecondType::DATA_TYPE, true),
+            Field::new("value", DataType::Float64, true),
+            Field::new("path", DataType::Utf8, true),
+        ]));
+        let build_batch = |timestamps: Vec<i64>, values: Vec<f64>, paths: Vec<&str>| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(TimestampMillisecondArray::from(timestamps)) as _,
+                    Arc::new(Float64Array::from(values)) as _,
+                    Arc::new(StringArray::from(paths)) as _,
+                ],
+            )
+            .unwrap()
+        };
+        // duplicates of `foo` at 60s straddle the first two batches, and `bar` at 90s
+        // isn't a duplicate of `foo` at the same time.
+        let batches = vec![
+            build_batch(
+                vec![0, 30_000, 60_000],
+                vec![1.0, 2.0, 3.0],
+                vec!["foo", "foo", "foo"],
+            ),
+            build_batch(
+                vec![60_000, 60_000, 90_000],
+                vec![4.0, 5.0, 6.0],
+                vec!["foo", "foo", "foo"],
+            ),
+            build_batch(vec![90_000, 90_000], vec![7.0, 8.0], vec!["bar", "bar"]),
+        ];
+        let memory_exec = Arc::new(MemoryExec::try_new(&[batches], schema, None).unwrap());
+        let normalize_exec = Arc::new(SeriesNormalizeExec {
+            offset: 0,
+            time_index_column_name: TIME_INDEX_COLUMN.to_string(),
+            need_filter_out_nan: false,
+            input: memory_exec,
+            tag_columns: vec!["path".to_string()],
+            metric: ExecutionPlanMetricsSet::new(),
+        });
+        let session_context = SessionContext::default();
+        let result =
+            datafusion::physical_plan::collect(normalize_exec.clone(), session_context.task_ctx())
+                .await
+                .unwrap();
+        // the layout of batches is kept
+        assert_eq!(
+            result.iter().map(|b| b.num_rows()).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        let result_literal = datatypes::arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string();
+
+        let expected = String::from(
+            "+---------------------+-------+------+\
+            \n| timestamp           | value | path |\
+            \n+---------------------+-------+------+\
+            \n| 1970-01-01T00:00:00 | 1.0   | foo  |\
+            \n| 1970-01-01T00:00:30 | 2.0   | foo  |\
+            \n| 1970-01-01T00:01:00 | 5.0   | foo  |\
+            \n| 1970-01-01T00:01:30 | 6.0   | foo  |\
+            \n| 1970-01-01T00:01:30 | 8.0   | bar  |\
+            \n+---------------------+-------+------+",
+        );
+        assert_eq!(result_literal, expected);
+
+        let num_duplicates = normalize_exec
+            .metrics()
+            .unwrap()
+            .sum_by_name(METRIC_NUM_DUPLICATES)
+            .unwrap()
+            .as_usize();
+        assert_eq!(num_duplicates, 3);
+    }
 }
@@ -16,14 +16,16 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use datafusion::error::Result as DfResult;
-use datafusion::execution::context::SessionState;
+use datafusion::execution::context::{QueryPlanner, SessionState};
+use datafusion::execution::SessionStateBuilder;
 use datafusion::logical_expr::{LogicalPlan, UserDefinedLogicalNode};
 use datafusion::physical_plan::ExecutionPlan;
-use datafusion::physical_planner::{ExtensionPlanner, PhysicalPlanner};
+use datafusion::physical_planner::{DefaultPhysicalPlanner, ExtensionPlanner, PhysicalPlanner};
 
 use crate::extension_plan::{
-    EmptyMetric, HistogramFold, InstantManipulate, RangeManipulate, ScalarCalculate, SeriesDivide,
-    SeriesNormalize, UnionDistinctOn,
+    Absent, EmaOverSteps, EmptyMetric, FillForward, HistogramFold, InstantManipulate,
+    RangeManipulate, ScalarCalculate, SeriesDivide, SeriesNormalize, StreamAggregate, TopK,
+    UnionDistinctOn,
 };
 
 pub struct PromExtensionPlanner;
@@ -52,6 +54,16 @@ impl ExtensionPlanner for PromExtensionPlanner {
             Ok(Some(node.to_execution_plan(physical_inputs[0].clone())?))
         } else if let Some(node) = node.as_any().downcast_ref::<HistogramFold>() {
             Ok(Some(node.to_execution_plan(physical_inputs[0].clone())))
+        } else if let Some(node) = node.as_any().downcast_ref::<StreamAggregate>() {
+            Ok(Some(node.to_execution_plan(physical_inputs[0].clone())))
+        } else if let Some(node) = node.as_any().downcast_ref::<TopK>() {
+            Ok(Some(node.to_execution_plan(physical_inputs[0].clone())))
+        } else if let Some(node) = node.as_any().downcast_ref::<FillForward>() {
+            Ok(Some(node.to_execution_plan(physical_inputs[0].clone())))
+        } else if let Some(node) = node.as_any().downcast_ref::<EmaOverSteps>() {
+            Ok(Some(node.to_execution_plan(physical_inputs[0].clone())))
+        } else if let Some(node) = node.as_any().downcast_ref::<Absent>() {
+            Ok(Some(node.to_execution_plan(physical_inputs[0].clone())))
         } else if let Some(node) = node.as_any().downcast_ref::<UnionDistinctOn>() {
             Ok(Some(node.to_execution_plan(
                 physical_inputs[0].clone(),
@@ -62,3 +74,30 @@ impl ExtensionPlanner for PromExtensionPlanner {
         }
     }
 }
+
+/// A [QueryPlanner] that plans the PromQL extension nodes besides the nodes of
+/// DataFusion, for running PromQL plans on a plain DataFusion session.
+#[derive(Debug, Default)]
+pub struct PromQueryPlanner;
+
+#[async_trait]
+impl QueryPlanner for PromQueryPlanner {
+    async fn create_physical_plan(
+        &self,
+        logical_plan: &LogicalPlan,
+        session_state: &SessionState,
+    ) -> DfResult<Arc<dyn ExecutionPlan>> {
+        DefaultPhysicalPlanner::with_extension_planners(vec![Arc::new(PromExtensionPlanner)])
+            .create_physical_plan(logical_plan, session_state)
+            .await
+    }
+}
+
+/// Registers what PromQL plans require to the builder of a session: the default
+/// functions of DataFusion, which the planner looks up by name, and the planner of
+/// the PromQL extension nodes. Functions only used by PromQL are embedded in plans.
+pub fn register_promql_extensions(builder: SessionStateBuilder) -> SessionStateBuilder {
+    builder
+        .with_default_features()
+        .with_query_planner(Arc::new(PromQueryPlanner))
+}
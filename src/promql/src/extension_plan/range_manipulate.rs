@@ -18,7 +18,9 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use datafusion::arrow::array::{Array, ArrayRef, Int64Array, TimestampMillisecondArray};
+use datafusion::arrow::array::{
+    Array, ArrayRef, DictionaryArray, Int64Array, TimestampMillisecondArray,
+};
 use datafusion::arrow::compute;
 use datafusion::arrow::datatypes::{Field, SchemaRef};
 use datafusion::arrow::error::ArrowError;
@@ -56,6 +58,9 @@ use crate::range_array::RangeArray;
 /// will add those extra columns:
 /// - timestamp range with type [RangeArray], which is the folded timestamp column.
 /// - end of current range with the same type as the timestamp column. (todo)
+///
+/// If the consuming range function doesn't read the timestamps, the timestamp range
+/// isn't built from the ranges but shares the keys of the value ranges.
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct RangeManipulate {
     start: Millisecond,
@@ -65,6 +70,8 @@ pub struct RangeManipulate {
 
     time_index: String,
     field_columns: Vec<String>,
+    /// Whether the consumer reads the timestamp range.
+    timestamps_required: bool,
     input: LogicalPlan,
     output_schema: DFSchemaRef,
 }
@@ -88,11 +95,28 @@ impl RangeManipulate {
             range,
             time_index,
             field_columns,
+            timestamps_required: true,
             input,
             output_schema,
         })
     }
 
+    /// Returns a copy of this plan that doesn't build the timestamp range, for
+    /// range functions that only read the values.
+    pub fn without_timestamps(&self) -> Self {
+        Self {
+            start: self.start,
+            end: self.end,
+            interval: self.interval,
+            range: self.range,
+            time_index: self.time_index.clone(),
+            field_columns: self.field_columns.clone(),
+            timestamps_required: false,
+            input: self.input.clone(),
+            output_schema: self.output_schema.clone(),
+        }
+    }
+
     pub const fn name() -> &'static str {
         "RangeManipulate"
     }
@@ -173,6 +197,7 @@ impl RangeManipulate {
             time_index_column: self.time_index.clone(),
             time_range_column: self.range_timestamp_name(),
             field_columns: self.field_columns.clone(),
+            timestamps_required: self.timestamps_required,
             input: exec_input,
             output_schema,
             metric: ExecutionPlanMetricsSet::new(),
@@ -238,6 +263,13 @@ impl PartialOrd for RangeManipulate {
             Some(core::cmp::Ordering::Equal) => {}
             ord => return ord,
         }
+        match self
+            .timestamps_required
+            .partial_cmp(&other.timestamps_required)
+        {
+            Some(core::cmp::Ordering::Equal) => {}
+            ord => return ord,
+        }
         self.input.partial_cmp(&other.input)
     }
 }
@@ -260,10 +292,15 @@ impl UserDefinedLogicalNodeCore for RangeManipulate {
     }
 
     fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let arrays = if self.timestamps_required {
+            "timestamps, values"
+        } else {
+            "values"
+        };
         write!(
             f,
-            "PromRangeManipulate: req range=[{}..{}], interval=[{}], eval range=[{}], time index=[{}], values={:?}",
-            self.start, self.end, self.interval, self.range, self.time_index, self.field_columns
+            "PromRangeManipulate: req range=[{}..{}], interval=[{}], eval range=[{}], time index=[{}], values={:?}, arrays=[{}]",
+            self.start, self.end, self.interval, self.range, self.time_index, self.field_columns, arrays
         )
     }
 
@@ -285,6 +322,7 @@ impl UserDefinedLogicalNodeCore for RangeManipulate {
             range: self.range,
             time_index: self.time_index.clone(),
             field_columns: self.field_columns.clone(),
+            timestamps_required: self.timestamps_required,
             input: inputs.into_iter().next().unwrap(),
             output_schema: self.output_schema.clone(),
         })
@@ -300,6 +338,7 @@ pub struct RangeManipulateExec {
     time_index_column: String,
     time_range_column: String,
     field_columns: Vec<String>,
+    timestamps_required: bool,
 
     input: Arc<dyn ExecutionPlan>,
     output_schema: SchemaRef,
@@ -353,6 +392,7 @@ impl ExecutionPlan for RangeManipulateExec {
             time_index_column: self.time_index_column.clone(),
             time_range_column: self.time_range_column.clone(),
             field_columns: self.field_columns.clone(),
+            timestamps_required: self.timestamps_required,
             output_schema: self.output_schema.clone(),
             input: children[0].clone(),
             metric: self.metric.clone(),
@@ -375,6 +415,7 @@ impl ExecutionPlan for RangeManipulateExec {
                 count: num_series.clone(),
             });
 
+        let batch_size = context.session_config().batch_size();
         let input = self.input.execute(partition, context)?;
         let schema = input.schema();
         let time_index = schema
@@ -400,9 +441,12 @@ impl ExecutionPlan for RangeManipulateExec {
             range: self.range,
             time_index,
             field_columns,
+            timestamps_required: self.timestamps_required,
             aligned_ts_array,
             output_schema: self.output_schema.clone(),
             input,
+            batch_size,
+            pending: None,
             metric: baseline_metric,
             num_series,
         }))
@@ -459,10 +503,18 @@ pub struct RangeManipulateStream {
     range: Millisecond,
     time_index: usize,
     field_columns: Vec<usize>,
+    /// Whether to build the timestamp range, otherwise it shares the keys of the
+    /// value ranges.
+    timestamps_required: bool,
     aligned_ts_array: ArrayRef,
 
     output_schema: SchemaRef,
     input: SendableRecordBatchStream,
+    /// Maximum number of rows in an output batch.
+    batch_size: usize,
+    /// The output of the last input batch that is not emitted yet, and the
+    /// offset of the remaining rows.
+    pending: Option<(RecordBatch, usize)>,
     metric: BaselineMetrics,
     /// Number of series processed.
     num_series: Count,
@@ -479,17 +531,22 @@ impl Stream for RangeManipulateStream {
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let poll = loop {
+            if let Some(batch) = self.next_pending_batch() {
+                break Poll::Ready(Some(Ok(batch)));
+            }
+
             match ready!(self.input.poll_next_unpin(cx)) {
                 Some(Ok(batch)) => {
                     let timer = std::time::Instant::now();
                     let result = self.manipulate(batch);
-                    if let Ok(None) = result {
-                        self.metric.elapsed_compute().add_elapsed(timer);
-                        continue;
-                    } else {
-                        self.num_series.add(1);
-                        self.metric.elapsed_compute().add_elapsed(timer);
-                        break Poll::Ready(result.transpose());
+                    self.metric.elapsed_compute().add_elapsed(timer);
+                    match result {
+                        Ok(Some(output)) => {
+                            self.num_series.add(1);
+                            self.pending = Some((output, 0));
+                        }
+                        Ok(None) => continue,
+                        Err(e) => break Poll::Ready(Some(Err(e))),
                     }
                 }
                 None => {
@@ -504,6 +561,21 @@ impl Stream for RangeManipulateStream {
 }
 
 impl RangeManipulateStream {
+    /// Takes at most `batch_size` rows from the pending output.
+    ///
+    /// Each output row carries the whole window of an aligned timestamp in the
+    /// range arrays, so slicing by rows never splits a window.
+    fn next_pending_batch(&mut self) -> Option<RecordBatch> {
+        let (output, offset) = self.pending.as_mut()?;
+        let len = self.batch_size.min(output.num_rows() - *offset);
+        let batch = output.slice(*offset, len);
+        *offset += len;
+        if *offset >= output.num_rows() {
+            self.pending = None;
+        }
+        Some(batch)
+    }
+
     // Prometheus: https://github.com/prometheus/prometheus/blob/e934d0f01158a1d55fa0ebb035346b195fcc1260/promql/engine.go#L1113-L1198
     // But they are not exactly the same, because we don't eager-evaluate on the data in this plan.
     // And the generated timestamp is not aligned to the step. It's expected to do later.
@@ -518,22 +590,26 @@ impl RangeManipulateStream {
 
         // transform columns
         let mut new_columns = input.columns().to_vec();
+        let mut value_range_keys = None;
         for index in self.field_columns.iter() {
             let _ = other_columns.remove(index);
             let column = input.column(*index);
-            let new_column = Arc::new(
-                RangeArray::from_ranges(column.clone(), ranges.clone())
-                    .map_err(|e| ArrowError::InvalidArgumentError(e.to_string()))?
-                    .into_dict(),
-            );
-            new_columns[*index] = new_column;
+            let new_column = RangeArray::from_ranges(column.clone(), ranges.clone())
+                .map_err(|e| ArrowError::InvalidArgumentError(e.to_string()))?
+                .into_dict();
+            value_range_keys.get_or_insert_with(|| new_column.keys().clone());
+            new_columns[*index] = Arc::new(new_column);
         }
 
         // push timestamp range column
-        let ts_range_column =
-            RangeArray::from_ranges(input.column(self.time_index).clone(), ranges.clone())
+        let ts_column = input.column(self.time_index).clone();
+        let ts_range_column = match value_range_keys.filter(|_| !self.timestamps_required) {
+            // all ranges are the same, so the keys are reusable
+            Some(keys) => DictionaryArray::try_new(keys, ts_column)?,
+            None => RangeArray::from_ranges(ts_column, ranges.clone())
                 .map_err(|e| ArrowError::InvalidArgumentError(e.to_string()))?
-                .into_dict();
+                .into_dict(),
+        };
         new_columns.push(Arc::new(ts_range_column));
 
         // truncate other columns
@@ -642,7 +718,9 @@ impl RangeManipulateStream {
 
 #[cfg(test)]
 mod test {
-    use datafusion::arrow::array::{ArrayRef, DictionaryArray, Float64Array, StringArray};
+    use datafusion::arrow::array::{
+        ArrayData, ArrayRef, DictionaryArray, Float64Array, StringArray,
+    };
     use datafusion::arrow::datatypes::{
         ArrowPrimitiveType, DataType, Field, Int64Type, Schema, TimestampMillisecondType,
     };
@@ -650,14 +728,22 @@ mod test {
     use datafusion::physical_expr::Partitioning;
     use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
     use datafusion::physical_plan::memory::MemoryExec;
-    use datafusion::prelude::SessionContext;
+    use datafusion::physical_plan::ColumnarValue;
+    use datafusion::prelude::{SessionConfig, SessionContext};
     use datatypes::arrow::array::TimestampMillisecondArray;
 
     use super::*;
+    use crate::functions::{extract_array, AvgOverTime};
 
     const TIME_INDEX_COLUMN: &str = "timestamp";
 
     fn prepare_test_data() -> MemoryExec {
+        let data = prepare_test_batch();
+        let schema = data.schema();
+        MemoryExec::try_new(&[vec![data]], schema, None).unwrap()
+    }
+
+    fn prepare_test_batch() -> RecordBatch {
         let schema = Arc::new(Schema::new(vec![
             Field::new(TIME_INDEX_COLUMN, TimestampMillisecondType::DATA_TYPE, true),
             Field::new("value_1", DataType::Float64, true),
@@ -671,8 +757,8 @@ mod test {
         ])) as _;
         let field_column: ArrayRef = Arc::new(Float64Array::from(vec![1.0; 10])) as _;
         let path_column = Arc::new(StringArray::from(vec!["foo"; 10])) as _;
-        let data = RecordBatch::try_new(
-            schema.clone(),
+        RecordBatch::try_new(
+            schema,
             vec![
                 timestamp_column,
                 field_column.clone(),
@@ -680,19 +766,27 @@ mod test {
                 path_column,
             ],
         )
-        .unwrap();
+        .unwrap()
+    }
 
-        MemoryExec::try_new(&[vec![data]], schema, None).unwrap()
+    fn new_range_manipulate_exec(
+        memory_exec: Arc<MemoryExec>,
+        start: Millisecond,
+        end: Millisecond,
+        interval: Millisecond,
+        range: Millisecond,
+    ) -> Arc<RangeManipulateExec> {
+        new_range_manipulate_exec_with_timestamps(memory_exec, start, end, interval, range, true)
     }
 
-    async fn do_normalize_test(
+    fn new_range_manipulate_exec_with_timestamps(
+        memory_exec: Arc<MemoryExec>,
         start: Millisecond,
         end: Millisecond,
         interval: Millisecond,
         range: Millisecond,
-        expected: String,
-    ) {
-        let memory_exec = Arc::new(prepare_test_data());
+        timestamps_required: bool,
+    ) -> Arc<RangeManipulateExec> {
         let time_index = TIME_INDEX_COLUMN.to_string();
         let field_columns = vec!["value_1".to_string(), "value_2".to_string()];
         let manipulate_output_schema = SchemaRef::new(
@@ -711,19 +805,31 @@ mod test {
             EmissionType::Incremental,
             Boundedness::Bounded,
         );
-        let normalize_exec = Arc::new(RangeManipulateExec {
+        Arc::new(RangeManipulateExec {
             start,
             end,
             interval,
             range,
             field_columns,
+            timestamps_required,
             output_schema: manipulate_output_schema,
             time_range_column: RangeManipulate::build_timestamp_range_name(&time_index),
             time_index_column: time_index,
             input: memory_exec,
             metric: ExecutionPlanMetricsSet::new(),
             properties,
-        });
+        })
+    }
+
+    async fn do_normalize_test(
+        start: Millisecond,
+        end: Millisecond,
+        interval: Millisecond,
+        range: Millisecond,
+        expected: String,
+    ) {
+        let memory_exec = Arc::new(prepare_test_data());
+        let normalize_exec = new_range_manipulate_exec(memory_exec, start, end, interval, range);
         let session_context = SessionContext::default();
         let result = datafusion::physical_plan::collect(normalize_exec, session_context.task_ctx())
             .await
@@ -809,4 +915,132 @@ mod test {
         }");
         do_normalize_test(1, 10_001, 3_000, 1_000, expected).await;
     }
+
+    /// Returns the cells of the output rows. The windows of range arrays are
+    /// taken out so batches with different dictionaries are comparable.
+    fn collect_rows(batches: &[RecordBatch]) -> Vec<Vec<ArrayData>> {
+        let mut rows = vec![];
+        for batch in batches {
+            for row in 0..batch.num_rows() {
+                let cells = batch
+                    .columns()
+                    .iter()
+                    .map(|array| {
+                        if matches!(array.data_type(), &DataType::Dictionary(..)) {
+                            let dict_array = array
+                                .as_any()
+                                .downcast_ref::<DictionaryArray<Int64Type>>()
+                                .unwrap()
+                                .clone();
+                            let range_array = RangeArray::try_new(dict_array).unwrap();
+                            range_array.get(row).unwrap().to_data()
+                        } else {
+                            array.slice(row, 1).to_data()
+                        }
+                    })
+                    .collect();
+                rows.push(cells);
+            }
+        }
+        rows
+    }
+
+    #[tokio::test]
+    async fn output_in_batches() {
+        let batch = prepare_test_batch();
+        let schema = batch.schema();
+        // Two series of 11 output rows each.
+        let memory_exec =
+            Arc::new(MemoryExec::try_new(&[vec![batch.clone(), batch]], schema, None).unwrap());
+
+        let single_batch_ctx = SessionContext::new_with_config(SessionConfig::new());
+        let exec = new_range_manipulate_exec(memory_exec.clone(), 0, 310_000, 30_000, 90_000);
+        let expected = datafusion::physical_plan::collect(exec, single_batch_ctx.task_ctx())
+            .await
+            .unwrap();
+        assert_eq!(2, expected.len());
+
+        let small_batch_ctx =
+            SessionContext::new_with_config(SessionConfig::new().with_batch_size(4));
+        let exec = new_range_manipulate_exec(memory_exec, 0, 310_000, 30_000, 90_000);
+        let result = datafusion::physical_plan::collect(exec, small_batch_ctx.task_ctx())
+            .await
+            .unwrap();
+        // Each series is split into batches of 4, 4 and 3 rows.
+        let num_rows = result.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+        assert_eq!(vec![4, 4, 3, 4, 4, 3], num_rows);
+
+        assert_eq!(collect_rows(&expected), collect_rows(&result));
+    }
+
+    /// Sums the capacity of all distinct buffers, as range arrays share buffers.
+    fn buffer_memory_size(batches: &[RecordBatch]) -> usize {
+        fn visit(data: &ArrayData, seen: &mut HashSet<*const u8>, size: &mut usize) {
+            let buffers = data
+                .buffers()
+                .iter()
+                .chain(data.nulls().map(|nulls| nulls.buffer()));
+            for buffer in buffers {
+                if seen.insert(buffer.as_ptr()) {
+                    *size += buffer.capacity();
+                }
+            }
+            for child in data.child_data() {
+                visit(child, seen, size);
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut size = 0;
+        for batch in batches {
+            for column in batch.columns() {
+                visit(&column.to_data(), &mut seen, &mut size);
+            }
+        }
+        size
+    }
+
+    async fn collect_with_timestamps(timestamps_required: bool) -> Vec<RecordBatch> {
+        let memory_exec = Arc::new(prepare_test_data());
+        let exec = new_range_manipulate_exec_with_timestamps(
+            memory_exec,
+            0,
+            310_000,
+            30_000,
+            90_000,
+            timestamps_required,
+        );
+        let session_context = SessionContext::default();
+        datafusion::physical_plan::collect(exec, session_context.task_ctx())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn prune_timestamp_range() {
+        let full = collect_with_timestamps(true).await;
+        let pruned = collect_with_timestamps(false).await;
+
+        // the timestamp range shares the keys of the value ranges
+        let full_size = buffer_memory_size(&full);
+        let pruned_size = buffer_memory_size(&pruned);
+        let keys_size = pruned[0].column(1).to_data().buffers()[0].capacity();
+        assert_eq!(full_size - pruned_size, keys_size);
+
+        // so `avg_over_time` yields the same result
+        let avg_over_time = |batches: Vec<RecordBatch>| {
+            let batch = &batches[0];
+            let args = datafusion_expr::ScalarFunctionArgs {
+                args: vec![
+                    ColumnarValue::Array(batch.column(4).clone()),
+                    ColumnarValue::Array(batch.column(1).clone()),
+                ],
+                number_rows: batch.num_rows(),
+                return_type: &DataType::Float64,
+            };
+            let result = AvgOverTime::scalar_udf().invoke_with_args(args).unwrap();
+            extract_array(&result).unwrap()
+        };
+        assert_eq!(avg_over_time(full), avg_over_time(pruned));
+    }
 }
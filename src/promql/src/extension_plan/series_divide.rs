@@ -23,11 +23,12 @@ use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::common::{DFSchema, DFSchemaRef};
 use datafusion::error::Result as DataFusionResult;
 use datafusion::execution::context::TaskContext;
+use datafusion::execution::memory_pool::{MemoryConsumer, MemoryReservation};
 use datafusion::logical_expr::{EmptyRelation, Expr, LogicalPlan, UserDefinedLogicalNodeCore};
 use datafusion::physical_expr::{LexRequirement, PhysicalSortRequirement};
 use datafusion::physical_plan::expressions::Column as ColumnExpr;
 use datafusion::physical_plan::metrics::{
-    BaselineMetrics, Count, ExecutionPlanMetricsSet, MetricBuilder, MetricValue, MetricsSet,
+    BaselineMetrics, Count, ExecutionPlanMetricsSet, Gauge, MetricBuilder, MetricValue, MetricsSet,
 };
 use datafusion::physical_plan::{
     DisplayAs, DisplayFormatType, Distribution, ExecutionPlan, PlanProperties, RecordBatchStream,
@@ -41,7 +42,7 @@ use prost::Message;
 use snafu::ResultExt;
 
 use crate::error::{DeserializeSnafu, Result};
-use crate::extension_plan::METRIC_NUM_SERIES;
+use crate::extension_plan::{METRIC_NUM_SERIES, METRIC_PEAK_BUFFERED_ROWS};
 use crate::metrics::PROMQL_SERIES_COUNT;
 
 #[derive(Debug, PartialEq, Eq, Hash, PartialOrd)]
@@ -212,6 +213,15 @@ impl ExecutionPlan for SeriesDivideExec {
                 name: METRIC_NUM_SERIES.into(),
                 count: num_series.clone(),
             });
+        let peak_buffered_rows = Gauge::new();
+        metrics_builder
+            .with_partition(partition)
+            .build(MetricValue::Gauge {
+                name: METRIC_PEAK_BUFFERED_ROWS.into(),
+                gauge: peak_buffered_rows.clone(),
+            });
+        let reservation = MemoryConsumer::new(format!("SeriesDivideStream[{partition}]"))
+            .register(context.memory_pool());
 
         let input = self.input.execute(partition, context)?;
         let schema = input.schema();
@@ -233,6 +243,9 @@ impl ExecutionPlan for SeriesDivideExec {
             metric: baseline_metric,
             num_series,
             inspect_start: 0,
+            num_buffered_rows: 0,
+            peak_buffered_rows,
+            reservation,
         }))
     }
 
@@ -256,6 +269,10 @@ impl DisplayAs for SeriesDivideExec {
 }
 
 /// Assume the input stream is ordered on the tag columns.
+///
+/// A series may span several input batches, so rows are buffered until a
+/// different tag value or the end of the input is observed, then the whole
+/// series is emitted as one batch.
 pub struct SeriesDivideStream {
     tag_indices: Vec<usize>,
     buffer: Vec<RecordBatch>,
@@ -266,6 +283,12 @@ pub struct SeriesDivideStream {
     inspect_start: usize,
     /// Number of series processed.
     num_series: Count,
+    /// Number of rows in the buffer.
+    num_buffered_rows: usize,
+    /// The most rows ever buffered.
+    peak_buffered_rows: Gauge,
+    /// Accounts the memory of buffered batches.
+    reservation: MemoryReservation,
 }
 
 impl RecordBatchStream for SeriesDivideStream {
@@ -304,6 +327,8 @@ impl Stream for SeriesDivideStream {
                         self.buffer.remove(0);
                     }
                     let result_batch = compute::concat_batches(&self.schema, &result_batches)?;
+                    self.num_buffered_rows -= result_batch.num_rows();
+                    self.resize_reservation();
 
                     self.inspect_start = 0;
                     self.num_series.add(1);
@@ -315,12 +340,14 @@ impl Stream for SeriesDivideStream {
                     let next_batch = ready!(self.as_mut().fetch_next_batch(cx)).transpose()?;
                     let timer = std::time::Instant::now();
                     if let Some(next_batch) = next_batch {
-                        self.buffer.push(next_batch);
+                        self.push_batch(next_batch)?;
                         continue;
                     } else {
                         // input stream is ended
                         let result = compute::concat_batches(&self.schema, &self.buffer)?;
                         self.buffer.clear();
+                        self.num_buffered_rows = 0;
+                        self.reservation.free();
                         self.inspect_start = 0;
                         self.num_series.add(1);
                         self.metric.elapsed_compute().add_elapsed(timer);
@@ -336,7 +363,7 @@ impl Stream for SeriesDivideStream {
                     }
                     error => return Poll::Ready(error),
                 };
-                self.buffer.push(batch);
+                self.push_batch(batch)?;
                 continue;
             }
         }
@@ -352,6 +379,29 @@ impl SeriesDivideStream {
         self.metric.record_poll(poll)
     }
 
+    /// Buffers a batch from the input. Empty batches are skipped, so every
+    /// buffered batch has a last row to compare with the next one.
+    fn push_batch(&mut self, batch: RecordBatch) -> DataFusionResult<()> {
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+        self.reservation.try_grow(batch.get_array_memory_size())?;
+        self.num_buffered_rows += batch.num_rows();
+        self.peak_buffered_rows.set_max(self.num_buffered_rows);
+        self.buffer.push(batch);
+        Ok(())
+    }
+
+    /// Shrinks the reservation to the memory of batches left in the buffer.
+    fn resize_reservation(&mut self) {
+        let size = self
+            .buffer
+            .iter()
+            .map(|batch| batch.get_array_memory_size())
+            .sum();
+        self.reservation.resize(size);
+    }
+
     /// Return the position to cut buffer.
     /// None implies the current buffer only contains one time series.
     fn find_first_diff_row(&mut self) -> DataFusionResult<Option<(usize, usize)>> {
@@ -818,4 +868,53 @@ mod test {
         // No more batches should be produced
         assert!(divide_stream.next().await.is_none());
     }
+
+    /// Divides the test data split into batches of `batch_size` rows, with an empty
+    /// batch in between, and returns the divided series.
+    async fn divide_in_batches(batch_size: usize) -> Vec<RecordBatch> {
+        let memory_exec = prepare_test_data();
+        let schema = memory_exec.schema();
+        let data = compute::concat_batches(&schema, &memory_exec.partitions()[0]).unwrap();
+        let mut batches = (0..data.num_rows())
+            .step_by(batch_size)
+            .map(|offset| data.slice(offset, batch_size.min(data.num_rows() - offset)))
+            .collect::<Vec<_>>();
+        batches.insert(1, RecordBatch::new_empty(schema.clone()));
+
+        let divide_exec = Arc::new(SeriesDivideExec {
+            tag_columns: vec!["host".to_string(), "path".to_string()],
+            input: Arc::new(MemoryExec::try_new(&[batches], schema, None).unwrap()),
+            metric: ExecutionPlanMetricsSet::new(),
+        });
+        let session_context = SessionContext::default();
+        let result =
+            datafusion::physical_plan::collect(divide_exec.clone(), session_context.task_ctx())
+                .await
+                .unwrap();
+
+        // the largest series has 7 rows
+        let peak_buffered_rows = divide_exec
+            .metrics()
+            .unwrap()
+            .sum_by_name(METRIC_PEAK_BUFFERED_ROWS)
+            .unwrap()
+            .as_usize();
+        assert!(peak_buffered_rows >= 7, "{peak_buffered_rows}");
+        assert_eq!(0, session_context.runtime_env().memory_pool.reserved());
+        result
+    }
+
+    #[tokio::test]
+    async fn series_across_small_batches() {
+        let expected = divide_in_batches(8192).await;
+        assert_eq!(
+            vec![2, 1, 5, 1, 7, 5, 2],
+            expected.iter().map(|b| b.num_rows()).collect::<Vec<_>>()
+        );
+
+        for batch_size in [1, 2] {
+            let result = divide_in_batches(batch_size).await;
+            assert_eq!(expected, result, "batch_size={batch_size}");
+        }
+    }
 }
@@ -0,0 +1,848 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use datafusion::arrow::array::{Array, ArrayRef, AsArray, Float64Array};
+use datafusion::arrow::compute::SortOptions;
+use datafusion::arrow::datatypes::{DataType, Field, Float64Type, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::row::{OwnedRow, RowConverter, SortField};
+use datafusion::common::{DFSchema, DFSchemaRef};
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::context::TaskContext;
+use datafusion::logical_expr::{Expr, LogicalPlan, UserDefinedLogicalNodeCore};
+use datafusion::physical_expr::{
+    EquivalenceProperties, LexOrdering, LexRequirement, PhysicalSortExpr, PhysicalSortRequirement,
+};
+use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
+use datafusion::physical_plan::expressions::Column as ColumnExpr;
+use datafusion::physical_plan::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, Distribution, ExecutionPlan, Partitioning, PlanProperties,
+    RecordBatchStream, SendableRecordBatchStream,
+};
+use futures::{ready, Stream, StreamExt};
+
+/// Aggregate functions supported by [`StreamAggregate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd)]
+pub enum StreamAggregateFunc {
+    Sum,
+    Avg,
+    Count,
+    Min,
+    Max,
+    Group,
+}
+
+/// `StreamAggregate` is a cross-series aggregation that works on input sorted
+/// by its group columns.
+///
+/// Unlike the hash aggregation, it only keeps the state of the group currently
+/// being consumed. Once the group key changes, the result of the previous group
+/// is finalized and buffered for output. So the memory footprint doesn't grow
+/// with the number of groups.
+///
+/// The output contains all group columns (with their original types) followed by
+/// one `Float64` column for each field column, keeping the field's name. Null
+/// values in field columns are ignored. Like Prometheus, `sum` and `avg` return
+/// NaN if any value of the group is NaN, while `min` and `max` ignore NaN values
+/// unless all values of the group are NaN.
+///
+/// # Requirement
+/// - Input should be sorted on `<group columns>`. The output keeps this order.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct StreamAggregate {
+    func: StreamAggregateFunc,
+    /// Columns to group by. The time index column should be included.
+    group_columns: Vec<String>,
+    field_columns: Vec<String>,
+    input: LogicalPlan,
+    output_schema: DFSchemaRef,
+}
+
+impl UserDefinedLogicalNodeCore for StreamAggregate {
+    fn name(&self) -> &str {
+        Self::name()
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.input]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.output_schema
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "PromStreamAggregate: func={:?}, groups={:?}, fields={:?}",
+            self.func, self.group_columns, self.field_columns
+        )
+    }
+
+    fn with_exprs_and_inputs(
+        &self,
+        _exprs: Vec<Expr>,
+        inputs: Vec<LogicalPlan>,
+    ) -> DataFusionResult<Self> {
+        if inputs.is_empty() {
+            return Err(DataFusionError::Internal(
+                "StreamAggregate must have at least one input".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            func: self.func,
+            group_columns: self.group_columns.clone(),
+            field_columns: self.field_columns.clone(),
+            input: inputs[0].clone(),
+            // This method cannot return error. Otherwise we should re-calculate
+            // the output schema
+            output_schema: self.output_schema.clone(),
+        })
+    }
+}
+
+impl PartialOrd for StreamAggregate {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        // Compare fields in order excluding output_schema
+        match self.func.partial_cmp(&other.func) {
+            Some(core::cmp::Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.group_columns.partial_cmp(&other.group_columns) {
+            Some(core::cmp::Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.field_columns.partial_cmp(&other.field_columns) {
+            Some(core::cmp::Ordering::Equal) => {}
+            ord => return ord,
+        }
+        self.input.partial_cmp(&other.input)
+    }
+}
+
+impl StreamAggregate {
+    pub fn new(
+        func: StreamAggregateFunc,
+        group_columns: Vec<String>,
+        field_columns: Vec<String>,
+        input: LogicalPlan,
+    ) -> DataFusionResult<Self> {
+        let output_schema =
+            Self::calculate_output_schema(input.schema(), &group_columns, &field_columns)?;
+        Ok(Self {
+            func,
+            group_columns,
+            field_columns,
+            input,
+            output_schema,
+        })
+    }
+
+    pub const fn name() -> &'static str {
+        "StreamAggregate"
+    }
+
+    pub fn func(&self) -> StreamAggregateFunc {
+        self.func
+    }
+
+    pub fn to_execution_plan(&self, exec_input: Arc<dyn ExecutionPlan>) -> Arc<dyn ExecutionPlan> {
+        let output_schema: SchemaRef = Arc::new(self.output_schema.as_ref().into());
+        let properties = StreamAggregateExec::compute_properties(
+            &exec_input,
+            output_schema.clone(),
+            self.group_columns.len(),
+        );
+        Arc::new(StreamAggregateExec {
+            func: self.func,
+            group_columns: self.group_columns.clone(),
+            field_columns: self.field_columns.clone(),
+            input: exec_input,
+            output_schema,
+            metric: ExecutionPlanMetricsSet::new(),
+            properties,
+        })
+    }
+
+    /// Output schema is `<group columns>, <field columns>`. Group columns keep their
+    /// definition in input, field columns become nullable `Float64`.
+    fn calculate_output_schema(
+        input_schema: &DFSchemaRef,
+        group_columns: &[String],
+        field_columns: &[String],
+    ) -> DataFusionResult<DFSchemaRef> {
+        let mut fields = Vec::with_capacity(group_columns.len() + field_columns.len());
+        for group in group_columns {
+            let (qualifier, field) = input_schema.qualified_field_with_unqualified_name(group)?;
+            fields.push((qualifier.cloned(), Arc::new(field.clone())));
+        }
+        for value in field_columns {
+            let (qualifier, _) = input_schema.qualified_field_with_unqualified_name(value)?;
+            fields.push((
+                qualifier.cloned(),
+                Arc::new(Field::new(value, DataType::Float64, true)),
+            ));
+        }
+        Ok(Arc::new(DFSchema::new_with_metadata(
+            fields,
+            HashMap::new(),
+        )?))
+    }
+}
+
+#[derive(Debug)]
+pub struct StreamAggregateExec {
+    func: StreamAggregateFunc,
+    group_columns: Vec<String>,
+    field_columns: Vec<String>,
+    input: Arc<dyn ExecutionPlan>,
+    output_schema: SchemaRef,
+    metric: ExecutionPlanMetricsSet,
+    properties: PlanProperties,
+}
+
+impl StreamAggregateExec {
+    /// The output is ordered on group columns, which are the first
+    /// `num_group_columns` columns in the output schema.
+    fn compute_properties(
+        input: &Arc<dyn ExecutionPlan>,
+        output_schema: SchemaRef,
+        num_group_columns: usize,
+    ) -> PlanProperties {
+        let ordering = (0..num_group_columns)
+            .map(|index| {
+                PhysicalSortExpr::new(
+                    Arc::new(ColumnExpr::new(output_schema.field(index).name(), index)),
+                    Self::sort_options(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let eq_properties = if ordering.is_empty() {
+            EquivalenceProperties::new(output_schema)
+        } else {
+            EquivalenceProperties::new_with_orderings(output_schema, &[LexOrdering::new(ordering)])
+        };
+
+        PlanProperties::new(
+            eq_properties,
+            Partitioning::UnknownPartitioning(input.output_partitioning().partition_count()),
+            EmissionType::Incremental,
+            Boundedness::Bounded,
+        )
+    }
+
+    const fn sort_options() -> SortOptions {
+        SortOptions {
+            descending: false,
+            nulls_first: true,
+        }
+    }
+
+    fn group_col_exprs(&self) -> Vec<Arc<ColumnExpr>> {
+        let input_schema = self.input.schema();
+        self.group_columns
+            .iter()
+            // Safety: the group column names is verified in the planning phase
+            .map(|group| Arc::new(ColumnExpr::new_with_schema(group, &input_schema).unwrap()))
+            .collect()
+    }
+}
+
+impl ExecutionPlan for StreamAggregateExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        let exprs = self
+            .group_col_exprs()
+            .into_iter()
+            .map(|expr| expr as _)
+            .collect::<Vec<_>>();
+        if exprs.is_empty() {
+            vec![Distribution::SinglePartition]
+        } else {
+            vec![Distribution::HashPartitioned(exprs)]
+        }
+    }
+
+    fn required_input_ordering(&self) -> Vec<Option<LexRequirement>> {
+        let exprs = self
+            .group_col_exprs()
+            .into_iter()
+            .map(|expr| PhysicalSortRequirement {
+                expr,
+                options: Some(Self::sort_options()),
+            })
+            .collect::<Vec<_>>();
+        if exprs.is_empty() {
+            vec![None]
+        } else {
+            vec![Some(LexRequirement::new(exprs))]
+        }
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![true; self.children().len()]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    // cannot change schema with this method
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        assert!(!children.is_empty());
+        let input = children[0].clone();
+        let properties =
+            Self::compute_properties(&input, self.output_schema.clone(), self.group_columns.len());
+        Ok(Arc::new(Self {
+            func: self.func,
+            group_columns: self.group_columns.clone(),
+            field_columns: self.field_columns.clone(),
+            input,
+            output_schema: self.output_schema.clone(),
+            metric: self.metric.clone(),
+            properties,
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let baseline_metric = BaselineMetrics::new(&self.metric, partition);
+        let batch_size = context.session_config().batch_size();
+        let input = self.input.execute(partition, context)?;
+
+        let input_schema = input.schema();
+        let group_indices = self
+            .group_columns
+            .iter()
+            .map(|group| Ok(input_schema.index_of(group)?))
+            .collect::<DataFusionResult<Vec<_>>>()?;
+        let field_indices = self
+            .field_columns
+            .iter()
+            .map(|field| Ok(input_schema.index_of(field)?))
+            .collect::<DataFusionResult<Vec<_>>>()?;
+        let row_converter = RowConverter::new(
+            group_indices
+                .iter()
+                .map(|index| SortField::new(input_schema.field(*index).data_type().clone()))
+                .collect(),
+        )?;
+
+        Ok(Box::pin(StreamAggregateStream {
+            func: self.func,
+            group_indices,
+            field_indices: field_indices.clone(),
+            row_converter,
+            batch_size,
+            current_group: None,
+            accumulators: vec![Accumulator::default(); field_indices.len()],
+            output_groups: vec![],
+            output_values: vec![vec![]; field_indices.len()],
+            finished: false,
+            schema: self.output_schema.clone(),
+            input,
+            metric: baseline_metric,
+        }))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metric.clone_inner())
+    }
+
+    fn name(&self) -> &str {
+        "StreamAggregateExec"
+    }
+}
+
+impl DisplayAs for StreamAggregateExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(
+                    f,
+                    "PromStreamAggregateExec: func={:?}, groups={:?}, fields={:?}",
+                    self.func, self.group_columns, self.field_columns
+                )
+            }
+        }
+    }
+}
+
+/// Aggregation state of one field column in one group.
+#[derive(Debug, Clone, Copy)]
+struct Accumulator {
+    /// Sum of all values. NaN propagates.
+    sum: f64,
+    /// Number of values, including NaN.
+    count: usize,
+    /// Number of non-NaN values, used by `min` and `max`.
+    value_count: usize,
+    min: f64,
+    max: f64,
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self {
+            sum: 0.0,
+            count: 0,
+            value_count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl Accumulator {
+    fn update(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        if value.is_nan() {
+            return;
+        }
+        self.value_count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn evaluate(&self, func: StreamAggregateFunc) -> Option<f64> {
+        match func {
+            StreamAggregateFunc::Count => Some(self.count as f64),
+            StreamAggregateFunc::Group => Some(1.0),
+            _ if self.count == 0 => None,
+            StreamAggregateFunc::Sum => Some(self.sum),
+            StreamAggregateFunc::Avg => Some(self.sum / self.count as f64),
+            _ if self.value_count == 0 => Some(f64::NAN),
+            StreamAggregateFunc::Min => Some(self.min),
+            StreamAggregateFunc::Max => Some(self.max),
+        }
+    }
+}
+
+/// Assume the input stream is ordered on the group columns.
+pub struct StreamAggregateStream {
+    func: StreamAggregateFunc,
+    group_indices: Vec<usize>,
+    field_indices: Vec<usize>,
+    /// Converts group columns into comparable rows.
+    row_converter: RowConverter,
+    /// Number of finished groups to buffer before emitting a batch.
+    batch_size: usize,
+    /// Key of the group being consumed.
+    current_group: Option<OwnedRow>,
+    /// One accumulator for each field column of the current group.
+    accumulators: Vec<Accumulator>,
+    /// Keys of finished groups that are not emitted yet.
+    output_groups: Vec<OwnedRow>,
+    /// Results of finished groups, one vector for each field column.
+    output_values: Vec<Vec<Option<f64>>>,
+    finished: bool,
+    schema: SchemaRef,
+    input: SendableRecordBatchStream,
+    metric: BaselineMetrics,
+}
+
+impl RecordBatchStream for StreamAggregateStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl Stream for StreamAggregateStream {
+    type Item = DataFusionResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if self.finished {
+                return Poll::Ready(None);
+            }
+            if self.output_groups.len() >= self.batch_size {
+                let timer = std::time::Instant::now();
+                let result = self.take_output();
+                self.metric.elapsed_compute().add_elapsed(timer);
+                return Poll::Ready(Some(result));
+            }
+
+            let poll = self.input.poll_next_unpin(cx);
+            match ready!(self.metric.record_poll(poll)) {
+                Some(Ok(batch)) => {
+                    let timer = std::time::Instant::now();
+                    let result = self.consume_batch(&batch);
+                    self.metric.elapsed_compute().add_elapsed(timer);
+                    if let Err(e) = result {
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => {
+                    let timer = std::time::Instant::now();
+                    self.finish_current_group();
+                    self.finished = true;
+                    if self.output_groups.is_empty() {
+                        return Poll::Ready(None);
+                    }
+                    let result = self.take_output();
+                    self.metric.elapsed_compute().add_elapsed(timer);
+                    return Poll::Ready(Some(result));
+                }
+            }
+        }
+    }
+}
+
+impl StreamAggregateStream {
+    fn consume_batch(&mut self, batch: &RecordBatch) -> DataFusionResult<()> {
+        let group_arrays = self
+            .group_indices
+            .iter()
+            .map(|index| batch.column(*index).clone())
+            .collect::<Vec<_>>();
+        let group_rows = self.row_converter.convert_columns(&group_arrays)?;
+        let field_arrays = self
+            .field_indices
+            .iter()
+            .map(|index| {
+                batch
+                    .column(*index)
+                    .as_primitive_opt::<Float64Type>()
+                    .ok_or_else(|| {
+                        DataFusionError::Execution(format!(
+                            "Expect Float64 field column, found {}",
+                            batch.column(*index).data_type()
+                        ))
+                    })
+            })
+            .collect::<DataFusionResult<Vec<_>>>()?;
+
+        for row_index in 0..batch.num_rows() {
+            let row = group_rows.row(row_index);
+            let is_same_group = self
+                .current_group
+                .as_ref()
+                .is_some_and(|current| current.row() == row);
+            if !is_same_group {
+                self.finish_current_group();
+                self.current_group = Some(row.owned());
+            }
+
+            for (accumulator, array) in self.accumulators.iter_mut().zip(&field_arrays) {
+                if array.is_valid(row_index) {
+                    accumulator.update(array.value(row_index));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finalize the current group and move its result into the output buffer.
+    fn finish_current_group(&mut self) {
+        let Some(group) = self.current_group.take() else {
+            return;
+        };
+        self.output_groups.push(group);
+        for (accumulator, values) in self
+            .accumulators
+            .iter_mut()
+            .zip(self.output_values.iter_mut())
+        {
+            values.push(accumulator.evaluate(self.func));
+            *accumulator = Accumulator::default();
+        }
+    }
+
+    /// Build a record batch from all buffered results.
+    fn take_output(&mut self) -> DataFusionResult<RecordBatch> {
+        let groups = std::mem::take(&mut self.output_groups);
+        let mut columns = self
+            .row_converter
+            .convert_rows(groups.iter().map(|group| group.row()))?;
+        for values in self.output_values.iter_mut() {
+            let values = std::mem::take(values);
+            columns.push(Arc::new(Float64Array::from(values)) as ArrayRef);
+        }
+
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::arrow::array::{StringArray, TimestampMillisecondArray};
+    use datafusion::arrow::datatypes::{Schema, TimeUnit};
+    use datafusion::common::ToDFSchema;
+    use datafusion::datasource::MemTable;
+    use datafusion::physical_plan::memory::MemoryExec;
+    use datafusion::prelude::{SessionConfig, SessionContext};
+
+    use super::*;
+
+    /// `with_nan` replaces one value of the `(host_2, 0)` group with NaN.
+    fn prepare_test_data(with_nan: bool) -> (SchemaRef, Vec<RecordBatch>) {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("host", DataType::Utf8, true),
+            Field::new("idc", DataType::Utf8, true),
+            Field::new(
+                "ts",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                false,
+            ),
+            Field::new("val", DataType::Float64, true),
+        ]));
+
+        // sorted on (host, ts), groups span across batches
+        let batch_1 = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![
+                    "host_1", "host_1", "host_1", "host_1", "host_1",
+                ])) as _,
+                Arc::new(StringArray::from(vec!["a", "b", "c", "a", "b"])) as _,
+                Arc::new(TimestampMillisecondArray::from(vec![0, 0, 0, 5000, 5000])) as _,
+                Arc::new(Float64Array::from(vec![
+                    Some(1.0),
+                    Some(2.0),
+                    Some(3.0),
+                    Some(4.0),
+                    None,
+                ])) as _,
+            ],
+        )
+        .unwrap();
+        let batch_2 = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["host_1", "host_2", "host_2"])) as _,
+                Arc::new(StringArray::from(vec!["c", "a", "b"])) as _,
+                Arc::new(TimestampMillisecondArray::from(vec![5000, 0, 0])) as _,
+                Arc::new(Float64Array::from(vec![
+                    Some(6.0),
+                    Some(10.0),
+                    Some(if with_nan { f64::NAN } else { 20.0 }),
+                ])) as _,
+            ],
+        )
+        .unwrap();
+        let batch_3 = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["host_2", "host_2", "host_3"])) as _,
+                Arc::new(StringArray::from(vec!["a", "b", "a"])) as _,
+                Arc::new(TimestampMillisecondArray::from(vec![5000, 5000, 0])) as _,
+                Arc::new(Float64Array::from(vec![None, None, Some(-1.5)])) as _,
+            ],
+        )
+        .unwrap();
+
+        (schema, vec![batch_1, batch_2, batch_3])
+    }
+
+    async fn do_stream_aggregate(
+        func: StreamAggregateFunc,
+        batch_size: usize,
+        with_nan: bool,
+    ) -> String {
+        let (schema, batches) = prepare_test_data(with_nan);
+        let memory_exec: Arc<dyn ExecutionPlan> =
+            Arc::new(MemoryExec::try_new(&[batches], schema.clone(), None).unwrap());
+        let group_columns = vec!["host".to_string(), "ts".to_string()];
+        let field_columns = vec!["val".to_string()];
+        let output_schema: SchemaRef = Arc::new(
+            (*StreamAggregate::calculate_output_schema(
+                &Arc::new(schema.to_dfschema().unwrap()),
+                &group_columns,
+                &field_columns,
+            )
+            .unwrap()
+            .as_ref())
+            .clone()
+            .into(),
+        );
+        let properties = StreamAggregateExec::compute_properties(
+            &memory_exec,
+            output_schema.clone(),
+            group_columns.len(),
+        );
+        let aggr_exec = Arc::new(StreamAggregateExec {
+            func,
+            group_columns,
+            field_columns,
+            input: memory_exec,
+            output_schema,
+            metric: ExecutionPlanMetricsSet::new(),
+            properties,
+        });
+
+        let session_context =
+            SessionContext::new_with_config(SessionConfig::new().with_batch_size(batch_size));
+        let result = datafusion::physical_plan::collect(aggr_exec, session_context.task_ctx())
+            .await
+            .unwrap();
+        datatypes::arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string()
+    }
+
+    async fn do_hash_aggregate(func: &str, with_nan: bool) -> String {
+        let (schema, batches) = prepare_test_data(with_nan);
+        let session_context = SessionContext::default();
+        let table = MemTable::try_new(schema, vec![batches]).unwrap();
+        session_context
+            .register_table("t", Arc::new(table))
+            .unwrap();
+        let result = session_context
+            .sql(&format!(
+                "SELECT host, ts, {func}(val) AS val FROM t GROUP BY host, ts ORDER BY host, ts"
+            ))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        datatypes::arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn compare_with_hash_aggregate() {
+        let cases = [
+            (StreamAggregateFunc::Sum, "sum"),
+            (StreamAggregateFunc::Avg, "avg"),
+            (StreamAggregateFunc::Min, "min"),
+            (StreamAggregateFunc::Max, "max"),
+        ];
+        for (func, df_func) in cases {
+            let expected = do_hash_aggregate(df_func, false).await;
+            // a small batch size to emit results across multiple batches
+            for batch_size in [1, 2, 8192] {
+                let result = do_stream_aggregate(func, batch_size, false).await;
+                assert_eq!(result, expected, "func: {func:?}, batch_size: {batch_size}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn compare_with_hash_aggregate_on_nan() {
+        // NaN propagates in `sum` and `avg`, in both aggregations. `min` and `max`
+        // differ on purpose, as DataFusion orders NaN as the largest value.
+        let cases = [
+            (StreamAggregateFunc::Sum, "sum"),
+            (StreamAggregateFunc::Avg, "avg"),
+        ];
+        for (func, df_func) in cases {
+            let expected = do_hash_aggregate(df_func, true).await;
+            assert!(expected.contains("NaN"), "{expected}");
+            for batch_size in [1, 2, 8192] {
+                let result = do_stream_aggregate(func, batch_size, true).await;
+                assert_eq!(result, expected, "func: {func:?}, batch_size: {batch_size}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_aggregate_count() {
+        let result = do_stream_aggregate(StreamAggregateFunc::Count, 8192, false).await;
+        let expected = String::from(
+            "+--------+---------------------+-----+\
+            \n| host   | ts                  | val |\
+            \n+--------+---------------------+-----+\
+            \n| host_1 | 1970-01-01T00:00:00 | 3.0 |\
+            \n| host_1 | 1970-01-01T00:00:05 | 2.0 |\
+            \n| host_2 | 1970-01-01T00:00:00 | 2.0 |\
+            \n| host_2 | 1970-01-01T00:00:05 | 0.0 |\
+            \n| host_3 | 1970-01-01T00:00:00 | 1.0 |\
+            \n+--------+---------------------+-----+",
+        );
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn nan_values() {
+        let mut accumulator = Accumulator::default();
+        accumulator.update(f64::NAN);
+        for func in [
+            StreamAggregateFunc::Sum,
+            StreamAggregateFunc::Avg,
+            StreamAggregateFunc::Min,
+            StreamAggregateFunc::Max,
+        ] {
+            assert!(accumulator.evaluate(func).unwrap().is_nan(), "{func:?}");
+        }
+
+        accumulator.update(1.0);
+        accumulator.update(3.0);
+        assert!(accumulator
+            .evaluate(StreamAggregateFunc::Sum)
+            .unwrap()
+            .is_nan());
+        assert!(accumulator
+            .evaluate(StreamAggregateFunc::Avg)
+            .unwrap()
+            .is_nan());
+        assert_eq!(Some(1.0), accumulator.evaluate(StreamAggregateFunc::Min));
+        assert_eq!(Some(3.0), accumulator.evaluate(StreamAggregateFunc::Max));
+        assert_eq!(Some(3.0), accumulator.evaluate(StreamAggregateFunc::Count));
+    }
+
+    #[test]
+    fn declare_output_ordering() {
+        let (schema, batches) = prepare_test_data(false);
+        let memory_exec: Arc<dyn ExecutionPlan> =
+            Arc::new(MemoryExec::try_new(&[batches], schema.clone(), None).unwrap());
+        let group_columns = vec!["host".to_string(), "ts".to_string()];
+        let field_columns = vec!["val".to_string()];
+        let output_schema: SchemaRef = Arc::new(
+            (*StreamAggregate::calculate_output_schema(
+                &Arc::new(schema.to_dfschema().unwrap()),
+                &group_columns,
+                &field_columns,
+            )
+            .unwrap()
+            .as_ref())
+            .clone()
+            .into(),
+        );
+        let properties = StreamAggregateExec::compute_properties(&memory_exec, output_schema, 2);
+        let ordering = properties.output_ordering().unwrap();
+        assert_eq!(ordering.len(), 2);
+        assert_eq!(ordering[0].to_string(), "host@0 ASC");
+        assert_eq!(ordering[1].to_string(), "ts@1 ASC");
+    }
+}
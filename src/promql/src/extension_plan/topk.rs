@@ -0,0 +1,716 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use datafusion::arrow::compute::{cast, SortOptions};
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::row::{OwnedRow, RowConverter, SortField};
+use datafusion::common::DFSchemaRef;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::context::TaskContext;
+use datafusion::logical_expr::{Expr, LogicalPlan, UserDefinedLogicalNodeCore};
+use datafusion::physical_expr::EquivalenceProperties;
+use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
+use datafusion::physical_plan::expressions::Column as ColumnExpr;
+use datafusion::physical_plan::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, Distribution, ExecutionPlan, Partitioning, PlanProperties,
+    RecordBatchStream, SendableRecordBatchStream,
+};
+use futures::{ready, Stream, StreamExt};
+
+/// `TopK` selects at most `k` rows with the largest (or the smallest if not
+/// `descending`) values in each group. It's the execution of PromQL's `topk`
+/// and `bottomk`.
+///
+/// Rows are ranked by the value column, then by the tag columns to keep the
+/// result stable. Null values are ranked first. This is the same as filtering
+/// on `row_number() OVER (PARTITION BY <group columns> ORDER BY <value>, <tags>) <= k`,
+/// but instead of sorting all rows in a group, only a bounded heap of `k` rows
+/// is kept for each group. This reduces the work from `O(n log n)` to `O(n log k)`,
+/// and the buffered rows from all input rows to `k` rows per group. Rows that
+/// can't enter the heap are dropped without being copied.
+///
+/// The output has the same schema as the input. It's not sorted: rows in the
+/// top-k of one group are emitted by rank, but groups are emitted in no
+/// particular order.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct TopK {
+    k: usize,
+    descending: bool,
+    /// Columns to group by. The time index column should be included.
+    group_columns: Vec<String>,
+    value_column: String,
+    tag_columns: Vec<String>,
+    input: LogicalPlan,
+    output_schema: DFSchemaRef,
+}
+
+impl UserDefinedLogicalNodeCore for TopK {
+    fn name(&self) -> &str {
+        Self::name()
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.input]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.output_schema
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        vec![]
+    }
+
+    fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "PromTopK: k={}, descending={}, groups={:?}, value={}, tags={:?}",
+            self.k, self.descending, self.group_columns, self.value_column, self.tag_columns
+        )
+    }
+
+    fn with_exprs_and_inputs(
+        &self,
+        _exprs: Vec<Expr>,
+        inputs: Vec<LogicalPlan>,
+    ) -> DataFusionResult<Self> {
+        if inputs.is_empty() {
+            return Err(DataFusionError::Internal(
+                "TopK must have at least one input".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            k: self.k,
+            descending: self.descending,
+            group_columns: self.group_columns.clone(),
+            value_column: self.value_column.clone(),
+            tag_columns: self.tag_columns.clone(),
+            input: inputs[0].clone(),
+            output_schema: self.output_schema.clone(),
+        })
+    }
+}
+
+impl PartialOrd for TopK {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        // Compare fields in order excluding output_schema
+        match self.k.partial_cmp(&other.k) {
+            Some(Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.descending.partial_cmp(&other.descending) {
+            Some(Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.group_columns.partial_cmp(&other.group_columns) {
+            Some(Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.value_column.partial_cmp(&other.value_column) {
+            Some(Ordering::Equal) => {}
+            ord => return ord,
+        }
+        match self.tag_columns.partial_cmp(&other.tag_columns) {
+            Some(Ordering::Equal) => {}
+            ord => return ord,
+        }
+        self.input.partial_cmp(&other.input)
+    }
+}
+
+impl TopK {
+    pub fn new(
+        k: usize,
+        descending: bool,
+        group_columns: Vec<String>,
+        value_column: String,
+        tag_columns: Vec<String>,
+        input: LogicalPlan,
+    ) -> DataFusionResult<Self> {
+        // check all columns exist in input
+        let input_schema = input.schema();
+        for column in group_columns
+            .iter()
+            .chain(Some(&value_column))
+            .chain(&tag_columns)
+        {
+            input_schema.qualified_field_with_unqualified_name(column)?;
+        }
+
+        let output_schema = input_schema.clone();
+        Ok(Self {
+            k,
+            descending,
+            group_columns,
+            value_column,
+            tag_columns,
+            input,
+            output_schema,
+        })
+    }
+
+    pub const fn name() -> &'static str {
+        "TopK"
+    }
+
+    pub fn to_execution_plan(&self, exec_input: Arc<dyn ExecutionPlan>) -> Arc<dyn ExecutionPlan> {
+        let properties = TopKExec::compute_properties(&exec_input);
+        Arc::new(TopKExec {
+            k: self.k,
+            descending: self.descending,
+            group_columns: self.group_columns.clone(),
+            value_column: self.value_column.clone(),
+            tag_columns: self.tag_columns.clone(),
+            input: exec_input,
+            metric: ExecutionPlanMetricsSet::new(),
+            properties,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct TopKExec {
+    k: usize,
+    descending: bool,
+    group_columns: Vec<String>,
+    value_column: String,
+    tag_columns: Vec<String>,
+    input: Arc<dyn ExecutionPlan>,
+    metric: ExecutionPlanMetricsSet,
+    properties: PlanProperties,
+}
+
+impl TopKExec {
+    /// The output has no ordering, and is emitted after all input is consumed.
+    fn compute_properties(input: &Arc<dyn ExecutionPlan>) -> PlanProperties {
+        PlanProperties::new(
+            EquivalenceProperties::new(input.schema()),
+            Partitioning::UnknownPartitioning(input.output_partitioning().partition_count()),
+            EmissionType::Final,
+            Boundedness::Bounded,
+        )
+    }
+
+    /// Rows are ranked by `<value>, <tags>` in this order.
+    const fn rank_sort_options(&self) -> SortOptions {
+        SortOptions {
+            descending: self.descending,
+            nulls_first: true,
+        }
+    }
+}
+
+impl ExecutionPlan for TopKExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn properties(&self) -> &PlanProperties {
+        &self.properties
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        let input_schema = self.input.schema();
+        let exprs = self
+            .group_columns
+            .iter()
+            // Safety: the group column names is verified in the planning phase
+            .map(|group| Arc::new(ColumnExpr::new_with_schema(group, &input_schema).unwrap()) as _)
+            .collect::<Vec<_>>();
+        if exprs.is_empty() {
+            vec![Distribution::SinglePartition]
+        } else {
+            vec![Distribution::HashPartitioned(exprs)]
+        }
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![false; self.children().len()]
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        assert!(!children.is_empty());
+        let input = children[0].clone();
+        let properties = Self::compute_properties(&input);
+        Ok(Arc::new(Self {
+            k: self.k,
+            descending: self.descending,
+            group_columns: self.group_columns.clone(),
+            value_column: self.value_column.clone(),
+            tag_columns: self.tag_columns.clone(),
+            input,
+            metric: self.metric.clone(),
+            properties,
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let baseline_metric = BaselineMetrics::new(&self.metric, partition);
+        let batch_size = context.session_config().batch_size();
+        let input = self.input.execute(partition, context)?;
+
+        let schema = input.schema();
+        let group_indices = self
+            .group_columns
+            .iter()
+            .map(|group| Ok(schema.index_of(group)?))
+            .collect::<DataFusionResult<Vec<_>>>()?;
+        let rank_indices = Some(&self.value_column)
+            .into_iter()
+            .chain(&self.tag_columns)
+            .map(|column| Ok(schema.index_of(column)?))
+            .collect::<DataFusionResult<Vec<_>>>()?;
+
+        let group_converter = RowConverter::new(
+            group_indices
+                .iter()
+                .map(|index| SortField::new(schema.field(*index).data_type().clone()))
+                .collect(),
+        )?;
+        let rank_converter = RowConverter::new(
+            rank_indices
+                .iter()
+                .map(|index| {
+                    SortField::new_with_options(
+                        schema.field(*index).data_type().clone(),
+                        self.rank_sort_options(),
+                    )
+                })
+                .collect(),
+        )?;
+        let row_converter = RowConverter::new(
+            schema
+                .fields()
+                .iter()
+                .map(|field| SortField::new(field.data_type().clone()))
+                .collect(),
+        )?;
+
+        Ok(Box::pin(TopKStream {
+            k: self.k,
+            group_indices,
+            rank_indices,
+            group_converter,
+            rank_converter,
+            row_converter,
+            heaps: HashMap::new(),
+            batch_size,
+            output: None,
+            schema,
+            input,
+            metric: baseline_metric,
+        }))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metric.clone_inner())
+    }
+
+    fn name(&self) -> &str {
+        "TopKExec"
+    }
+}
+
+impl DisplayAs for TopKExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(
+                    f,
+                    "PromTopKExec: k={}, descending={}, groups={:?}, value={}, tags={:?}",
+                    self.k,
+                    self.descending,
+                    self.group_columns,
+                    self.value_column,
+                    self.tag_columns
+                )
+            }
+        }
+    }
+}
+
+/// A row kept in the heap of a group, ordered by its rank.
+struct HeapItem {
+    /// Rank key of the row. Smaller is better.
+    rank: OwnedRow,
+    /// All columns of the row.
+    row: OwnedRow,
+}
+
+impl PartialEq for HeapItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.rank == other.rank
+    }
+}
+
+impl Eq for HeapItem {}
+
+impl PartialOrd for HeapItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapItem {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank.cmp(&other.rank)
+    }
+}
+
+pub struct TopKStream {
+    k: usize,
+    group_indices: Vec<usize>,
+    /// Indices of the value column and tag columns.
+    rank_indices: Vec<usize>,
+    /// Converts group columns into hashable keys.
+    group_converter: RowConverter,
+    /// Converts rank columns into comparable rows.
+    rank_converter: RowConverter,
+    /// Converts all columns into rows, to keep rows in heaps.
+    row_converter: RowConverter,
+    /// Max-heap of the top-k rows of each group. The top is the worst row in
+    /// the top-k, which is the one to evict.
+    heaps: HashMap<Box<[u8]>, BinaryHeap<HeapItem>>,
+    batch_size: usize,
+    /// Rows to emit. Built after the input is exhausted.
+    output: Option<VecDeque<OwnedRow>>,
+    schema: SchemaRef,
+    input: SendableRecordBatchStream,
+    metric: BaselineMetrics,
+}
+
+impl RecordBatchStream for TopKStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl Stream for TopKStream {
+    type Item = DataFusionResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(output) = &self.output {
+                if output.is_empty() {
+                    return Poll::Ready(None);
+                }
+                let timer = std::time::Instant::now();
+                let result = self.take_output();
+                self.metric.elapsed_compute().add_elapsed(timer);
+                return self.metric.record_poll(Poll::Ready(Some(result)));
+            }
+
+            match ready!(self.input.poll_next_unpin(cx)) {
+                Some(Ok(batch)) => {
+                    let timer = std::time::Instant::now();
+                    let result = self.consume_batch(&batch);
+                    self.metric.elapsed_compute().add_elapsed(timer);
+                    if let Err(e) = result {
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => {
+                    let timer = std::time::Instant::now();
+                    self.build_output();
+                    self.metric.elapsed_compute().add_elapsed(timer);
+                }
+            }
+        }
+    }
+}
+
+impl TopKStream {
+    fn consume_batch(&mut self, batch: &RecordBatch) -> DataFusionResult<()> {
+        if self.k == 0 {
+            return Ok(());
+        }
+
+        let group_arrays = self
+            .group_indices
+            .iter()
+            .map(|index| batch.column(*index).clone())
+            .collect::<Vec<_>>();
+        let group_rows = self.group_converter.convert_columns(&group_arrays)?;
+        let rank_arrays = self
+            .rank_indices
+            .iter()
+            .map(|index| batch.column(*index).clone())
+            .collect::<Vec<_>>();
+        let rank_rows = self.rank_converter.convert_columns(&rank_arrays)?;
+        // Only converted when some row enters a heap.
+        let mut rows = None;
+
+        for row_index in 0..batch.num_rows() {
+            let group = group_rows.row(row_index);
+            let key: &[u8] = group.as_ref();
+            if !self.heaps.contains_key(key) {
+                self.heaps
+                    .insert(key.into(), BinaryHeap::with_capacity(self.k + 1));
+            }
+            // Safety: the heap is inserted above
+            let heap = self.heaps.get_mut(key).unwrap();
+
+            let rank = rank_rows.row(row_index);
+            if heap.len() >= self.k {
+                // Safety: the heap is not empty as `k` is positive
+                if rank >= heap.peek().unwrap().rank.row() {
+                    continue;
+                }
+            }
+
+            if rows.is_none() {
+                rows = Some(self.row_converter.convert_columns(batch.columns())?);
+            }
+            // Safety: rows are converted above
+            let row = rows.as_ref().unwrap().row(row_index).owned();
+            heap.push(HeapItem {
+                rank: rank.owned(),
+                row,
+            });
+            if heap.len() > self.k {
+                heap.pop();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Drain all heaps into the output. Rows of one group are ordered by rank.
+    fn build_output(&mut self) {
+        let mut output = VecDeque::new();
+        for (_, heap) in self.heaps.drain() {
+            output.extend(heap.into_sorted_vec().into_iter().map(|item| item.row));
+        }
+        self.output = Some(output);
+    }
+
+    /// Build a record batch from at most `batch_size` rows of the output.
+    fn take_output(&mut self) -> DataFusionResult<RecordBatch> {
+        // Safety: only called after the output is built
+        let output = self.output.as_mut().unwrap();
+        let num_rows = output.len().min(self.batch_size);
+        let rows = output.drain(..num_rows).collect::<Vec<_>>();
+
+        let columns = self
+            .row_converter
+            .convert_rows(rows.iter().map(|row| row.row()))?
+            .into_iter()
+            .zip(self.schema.fields())
+            .map(|(array, field)| {
+                // Dictionary arrays are decoded as their values
+                if array.data_type() == field.data_type() {
+                    Ok(array)
+                } else {
+                    Ok(cast(&array, field.data_type())?)
+                }
+            })
+            .collect::<DataFusionResult<Vec<_>>>()?;
+
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use datafusion::arrow::array::{Float64Array, StringArray, TimestampMillisecondArray};
+    use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+    use datafusion::datasource::MemTable;
+    use datafusion::physical_plan::memory::MemoryExec;
+    use datafusion::prelude::{SessionConfig, SessionContext};
+
+    use super::*;
+
+    const NUM_SERIES: usize = 1000;
+
+    /// Builds `NUM_SERIES` series with 3 timestamps, split into batches of 100
+    /// rows. Some values are null and some values are the same.
+    fn prepare_test_data() -> (SchemaRef, Vec<RecordBatch>) {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("host", DataType::Utf8, true),
+            Field::new(
+                "ts",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                false,
+            ),
+            Field::new("val", DataType::Float64, true),
+        ]));
+
+        let mut hosts = vec![];
+        let mut timestamps = vec![];
+        let mut values = vec![];
+        for ts in [0, 5000, 10000] {
+            for series in 0..NUM_SERIES {
+                hosts.push(format!("host_{series:04}"));
+                timestamps.push(ts);
+                let value = (series * 7919 + ts as usize) % 997;
+                if value % 101 == 0 {
+                    values.push(None);
+                } else {
+                    values.push(Some((value % 500) as f64));
+                }
+            }
+        }
+
+        let batches = (0..hosts.len())
+            .step_by(100)
+            .map(|offset| {
+                RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(StringArray::from(hosts[offset..offset + 100].to_vec())) as _,
+                        Arc::new(TimestampMillisecondArray::from(
+                            timestamps[offset..offset + 100].to_vec(),
+                        )) as _,
+                        Arc::new(Float64Array::from(values[offset..offset + 100].to_vec())) as _,
+                    ],
+                )
+                .unwrap()
+            })
+            .collect();
+
+        (schema, batches)
+    }
+
+    fn order_by(descending: bool) -> String {
+        let order = if descending { "DESC" } else { "ASC" };
+        format!("val {order} NULLS FIRST, host {order} NULLS FIRST")
+    }
+
+    async fn collect_topk(k: usize, descending: bool, batch_size: usize) -> Vec<RecordBatch> {
+        let (schema, batches) = prepare_test_data();
+        let memory_exec: Arc<dyn ExecutionPlan> =
+            Arc::new(MemoryExec::try_new(&[batches], schema.clone(), None).unwrap());
+        let properties = TopKExec::compute_properties(&memory_exec);
+        let topk_exec = Arc::new(TopKExec {
+            k,
+            descending,
+            group_columns: vec!["ts".to_string()],
+            value_column: "val".to_string(),
+            tag_columns: vec!["host".to_string()],
+            input: memory_exec,
+            metric: ExecutionPlanMetricsSet::new(),
+            properties,
+        });
+
+        let session_context =
+            SessionContext::new_with_config(SessionConfig::new().with_batch_size(batch_size));
+        let result = datafusion::physical_plan::collect(topk_exec, session_context.task_ctx())
+            .await
+            .unwrap();
+        assert!(result.iter().all(|batch| batch.num_rows() <= batch_size));
+        result
+    }
+
+    async fn do_topk(k: usize, descending: bool, batch_size: usize) -> String {
+        let result = collect_topk(k, descending, batch_size).await;
+
+        // the output is not sorted, sort it to compare
+        let session_context = SessionContext::default();
+        let table = MemTable::try_new(result[0].schema(), vec![result]).unwrap();
+        session_context
+            .register_table("t", Arc::new(table))
+            .unwrap();
+        let result = session_context
+            .sql(&format!(
+                "SELECT * FROM t ORDER BY ts, {}",
+                order_by(descending)
+            ))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        datatypes::arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string()
+    }
+
+    async fn do_window_topk(k: usize, descending: bool) -> String {
+        let (schema, batches) = prepare_test_data();
+        let session_context = SessionContext::default();
+        let table = MemTable::try_new(schema, vec![batches]).unwrap();
+        session_context
+            .register_table("t", Arc::new(table))
+            .unwrap();
+        let order_by = order_by(descending);
+        let result = session_context
+            .sql(&format!(
+                "SELECT host, ts, val FROM \
+                (SELECT *, row_number() OVER (PARTITION BY ts ORDER BY {order_by}) AS rn FROM t) \
+                WHERE rn <= {k} ORDER BY ts, {order_by}"
+            ))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        datatypes::arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn compare_with_window_function() {
+        for descending in [true, false] {
+            for k in [1, 5, 20, NUM_SERIES, NUM_SERIES + 1] {
+                let expected = do_window_topk(k, descending).await;
+                for batch_size in [3, 8192] {
+                    let result = do_topk(k, descending, batch_size).await;
+                    assert_eq!(
+                        result, expected,
+                        "k: {k}, descending: {descending}, batch_size: {batch_size}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn topk_zero() {
+        let result = collect_topk(0, true, 8192).await;
+        assert!(result.iter().all(|batch| batch.num_rows() == 0));
+    }
+
+    #[test]
+    fn declare_no_output_ordering() {
+        let (schema, batches) = prepare_test_data();
+        let memory_exec: Arc<dyn ExecutionPlan> =
+            Arc::new(MemoryExec::try_new(&[batches], schema, None).unwrap());
+        let properties = TopKExec::compute_properties(&memory_exec);
+        assert!(properties.output_ordering().is_none());
+        assert_eq!(properties.emission_type, EmissionType::Final);
+    }
+}
@@ -13,9 +13,12 @@
 // limitations under the License.
 
 mod aggr_over_time;
+mod aggr_skip_nan;
+mod aggr_stddev;
 mod changes;
 mod deriv;
 mod extrapolate_rate;
+mod histogram;
 mod holt_winters;
 mod idelta;
 mod predict_linear;
@@ -26,16 +29,25 @@ mod round;
 #[cfg(test)]
 mod test_util;
 
+use std::borrow::Cow;
+
 pub use aggr_over_time::{
-    AbsentOverTime, AvgOverTime, CountOverTime, LastOverTime, MaxOverTime, MinOverTime,
-    PresentOverTime, StddevOverTime, StdvarOverTime, SumOverTime,
+    AbsentOverTime, AvgOverTime, AvgOverTimePropagateNan, CountOverTime, LastOverTime, MaxOverTime,
+    MaxOverTimePropagateNan, MinOverTime, MinOverTimePropagateNan, PresentOverTime, StddevOverTime,
+    StdvarOverTime, SumOverTime, SumOverTimePropagateNan,
 };
+pub use aggr_skip_nan::{SkipNanAggr, SkipNanAggrKind};
+pub use aggr_stddev::{StdAggr, StdAggrKind};
 pub use changes::Changes;
-use datafusion::arrow::array::{ArrayRef, Float64Array, TimestampMillisecondArray};
+use datafusion::arrow::array::{Array, ArrayRef, Float64Array, TimestampMillisecondArray};
 use datafusion::error::DataFusionError;
 use datafusion::physical_plan::ColumnarValue;
 pub use deriv::Deriv;
-pub use extrapolate_rate::{Delta, Increase, Rate};
+pub use extrapolate_rate::{Delta, Increase, Rate, RateFirstSamplePolicy};
+pub use histogram::{
+    HistogramAggr, HistogramAggrKind, HistogramAvgOverTime, HistogramCount, HistogramIDelta,
+    HistogramPack, HistogramQuantile, HistogramSum, HistogramWarningSink,
+};
 pub use holt_winters::HoltWinters;
 pub use idelta::IDelta;
 pub use predict_linear::PredictLinear;
@@ -54,6 +66,37 @@ pub(crate) fn extract_array(columnar_value: &ColumnarValue) -> Result<ArrayRef,
     }
 }
 
+/// Returns the values of the samples in a window. Nulls are not samples, so a
+/// window that only contains nulls is empty, same as a window without values.
+pub(crate) fn window_values(values: &Float64Array) -> Cow<'_, [f64]> {
+    if values.null_count() == 0 {
+        Cow::Borrowed(&values.values()[..])
+    } else {
+        Cow::Owned(values.iter().flatten().collect())
+    }
+}
+
+/// Same as [window_values], and also returns the timestamps of the samples.
+pub(crate) fn window_samples<'a>(
+    timestamps: &'a TimestampMillisecondArray,
+    values: &'a Float64Array,
+) -> (Cow<'a, [i64]>, Cow<'a, [f64]>) {
+    if values.null_count() == 0 {
+        (
+            Cow::Borrowed(&timestamps.values()[..]),
+            Cow::Borrowed(&values.values()[..]),
+        )
+    } else {
+        let (timestamps, values): (Vec<_>, Vec<_>) = timestamps
+            .values()
+            .iter()
+            .zip(values.iter())
+            .filter_map(|(ts, value)| value.map(|value| (*ts, value)))
+            .unzip();
+        (Cow::Owned(timestamps), Cow::Owned(values))
+    }
+}
+
 /// compensation(Kahan) summation algorithm - a technique for reducing the numerical error
 /// in floating-point arithmetic. The algorithm also includes the modification ("Neumaier improvement")
 /// that reduces the numerical error further in cases
@@ -72,6 +115,10 @@ pub(crate) fn compensated_sum_inc(inc: f64, sum: f64, mut compensation: f64) ->
 
 /// linear_regression performs a least-square linear regression analysis on the
 /// times and values. It return the slope and intercept based on times and values.
+///
+/// Times are normalized to seconds relative to `intercept_time` before the regression.
+/// The subtraction is done on the integer timestamps, as epoch milliseconds lose
+/// precision when converted to `f64` at large epochs.
 /// Prometheus's implementation: <https://github.com/prometheus/prometheus/blob/90b2f7a540b8a70d8d81372e6692dcbb67ccbaaa/promql/functions.go#L793-L837>
 pub(crate) fn linear_regression(
     times: &TimestampMillisecondArray,
@@ -92,7 +139,7 @@ pub(crate) fn linear_regression(
     let init_y: f64 = values.value(0);
 
     for (i, value) in values.iter().enumerate() {
-        let time = times.value(i) as f64;
+        let time = times.value(i);
         if value.is_none() {
             continue;
         }
@@ -101,7 +148,7 @@ pub(crate) fn linear_regression(
             const_y = false;
         }
         count += 1.0;
-        let x = (time - intercept_time as f64) / 1e3f64;
+        let x = time.saturating_sub(intercept_time) as f64 / 1e3f64;
         (sum_x, comp_x) = compensated_sum_inc(x, sum_x, comp_x);
         (sum_y, comp_y) = compensated_sum_inc(value, sum_y, comp_y);
         (sum_xy, comp_xy) = compensated_sum_inc(x * value, sum_xy, comp_xy);
@@ -135,7 +182,11 @@ pub(crate) fn linear_regression(
 
 #[cfg(test)]
 mod test {
+    use std::sync::Arc;
+
     use super::*;
+    use crate::functions::test_util::range_udf_results;
+    use crate::range_array::RangeArray;
 
     #[test]
     fn calculate_linear_regression_none() {
@@ -253,4 +304,103 @@ mod test {
         }
         assert_eq!(sum + c, 2.0)
     }
+
+    /// What a range function outputs for a window.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum WindowOutput {
+        /// No output row.
+        Absent,
+        Zero,
+        NonZero,
+    }
+
+    impl From<Option<f64>> for WindowOutput {
+        fn from(value: Option<f64>) -> Self {
+            match value {
+                None => WindowOutput::Absent,
+                Some(v) if v == 0.0 => WindowOutput::Zero,
+                Some(_) => WindowOutput::NonZero,
+            }
+        }
+    }
+
+    /// Checks the empty vs zero outputs of `resets`, `changes` and the counter
+    /// functions over the same windows.
+    #[test]
+    fn empty_and_zero_windows() {
+        use WindowOutput::*;
+
+        let timestamps = Arc::new(TimestampMillisecondArray::from_iter(
+            (1..=9).map(|i| Some(i * 1000)),
+        ));
+        let values = Arc::new(Float64Array::from(vec![
+            Some(1.0),
+            Some(1.0),
+            Some(1.0),
+            Some(3.0),
+            Some(1.0),
+            Some(2.0),
+            None,
+            None,
+            Some(5.0),
+        ]));
+        let windows = [
+            ("empty", (0, 0)),
+            ("single", (0, 1)),
+            ("constant", (0, 3)),
+            // [1, 3, 1, 2]
+            ("varying", (2, 4)),
+            ("only nulls", (6, 2)),
+            // [2, null, null, 5]
+            ("with nulls", (5, 4)),
+        ];
+        let ranges = windows.iter().map(|(_, range)| *range).collect::<Vec<_>>();
+        // Evaluates each window at its last timestamp.
+        let eval_ts = Arc::new(TimestampMillisecondArray::from_iter(ranges.iter().map(
+            |&(offset, len)| Some(timestamps.value((offset + len.max(1) - 1) as usize)),
+        )));
+
+        let range_length = 10_000;
+        // `rate`, `increase` and `delta` need at least two samples.
+        let cases = [
+            (
+                Resets::scalar_udf(),
+                [Absent, Zero, Zero, NonZero, Absent, Zero],
+            ),
+            (
+                Changes::scalar_udf(),
+                [Absent, Zero, Zero, NonZero, Absent, NonZero],
+            ),
+            (
+                Rate::scalar_udf(range_length),
+                [Absent, Absent, Zero, NonZero, Absent, NonZero],
+            ),
+            (
+                Increase::scalar_udf(range_length),
+                [Absent, Absent, Zero, NonZero, Absent, NonZero],
+            ),
+            (
+                Delta::scalar_udf(range_length),
+                [Absent, Absent, Zero, NonZero, Absent, NonZero],
+            ),
+        ];
+        for (udf, expected) in cases {
+            let ts_range = RangeArray::from_ranges(timestamps.clone(), ranges.clone()).unwrap();
+            let value_range = RangeArray::from_ranges(values.clone(), ranges.clone()).unwrap();
+            let extra_args = if udf.name() == Resets::name() || udf.name() == Changes::name() {
+                vec![]
+            } else {
+                vec![ColumnarValue::Array(eval_ts.clone())]
+            };
+            let outputs = range_udf_results(&udf, ts_range, value_range, extra_args);
+            for (((window, _), output), expected) in windows.iter().zip(outputs).zip(expected) {
+                assert_eq!(
+                    expected,
+                    WindowOutput::from(output),
+                    "{} over the {window} window",
+                    udf.name()
+                );
+            }
+        }
+    }
 }
@@ -26,43 +26,132 @@ use datatypes::arrow::datatypes::DataType;
 use crate::functions::{compensated_sum_inc, extract_array};
 use crate::range_array::RangeArray;
 
-/// The average value of all points in the specified interval.
+/// Folds the non-NaN values in the specified interval, like Prometheus 3. Returns
+/// the result and the number of folded values, or NaN and 0 if all values are NaN.
+fn fold_skipping_nan(values: &Float64Array, f: impl Fn(f64, f64) -> f64) -> Option<(f64, usize)> {
+    let mut result = None;
+    let mut has_nan = false;
+    for value in values.iter().flatten() {
+        if value.is_nan() {
+            has_nan = true;
+            continue;
+        }
+        result = Some(match result {
+            Some((acc, count)) => (f(acc, value), count + 1),
+            None => (value, 1),
+        });
+    }
+    result.or(has_nan.then_some((f64::NAN, 0)))
+}
+
+/// Whether any value in the specified interval is NaN.
+fn has_nan(values: &Float64Array) -> bool {
+    values.iter().flatten().any(f64::is_nan)
+}
+
+/// The average value of all non-NaN points in the specified interval.
 #[range_fn(
     name = AvgOverTime,
     ret = Float64Array,
     display_name = prom_avg_over_time
 )]
 pub fn avg_over_time(_: &TimestampMillisecondArray, values: &Float64Array) -> Option<f64> {
-    compute::sum(values).map(|result| result / values.len() as f64)
+    fold_skipping_nan(values, |acc, value| acc + value).map(|(sum, count)| {
+        if count == 0 {
+            sum
+        } else {
+            sum / count as f64
+        }
+    })
 }
 
-/// The minimum value of all points in the specified interval.
+/// The minimum value of all non-NaN points in the specified interval.
 #[range_fn(
     name = MinOverTime,
     ret = Float64Array,
     display_name = prom_min_over_time
 )]
 pub fn min_over_time(_: &TimestampMillisecondArray, values: &Float64Array) -> Option<f64> {
-    compute::min(values)
+    fold_skipping_nan(values, f64::min).map(|(min, _)| min)
 }
 
-/// The maximum value of all points in the specified interval.
+/// The maximum value of all non-NaN points in the specified interval.
 #[range_fn(
     name = MaxOverTime,
     ret = Float64Array,
     display_name = prom_max_over_time
 )]
 pub fn max_over_time(_: &TimestampMillisecondArray, values: &Float64Array) -> Option<f64> {
-    compute::max(values)
+    fold_skipping_nan(values, f64::max).map(|(max, _)| max)
 }
 
-/// The sum of all values in the specified interval.
+/// The sum of all non-NaN values in the specified interval.
 #[range_fn(
     name = SumOverTime,
     ret = Float64Array,
     display_name = prom_sum_over_time
 )]
 pub fn sum_over_time(_: &TimestampMillisecondArray, values: &Float64Array) -> Option<f64> {
+    fold_skipping_nan(values, |acc, value| acc + value).map(|(sum, _)| sum)
+}
+
+/// Same as [avg_over_time] but NaN if any point is NaN.
+#[range_fn(
+    name = AvgOverTimePropagateNan,
+    ret = Float64Array,
+    display_name = prom_avg_over_time
+)]
+pub fn avg_over_time_propagate_nan(
+    _: &TimestampMillisecondArray,
+    values: &Float64Array,
+) -> Option<f64> {
+    compute::sum(values).map(|result| result / values.len() as f64)
+}
+
+/// Same as [min_over_time] but NaN if any point is NaN.
+#[range_fn(
+    name = MinOverTimePropagateNan,
+    ret = Float64Array,
+    display_name = prom_min_over_time
+)]
+pub fn min_over_time_propagate_nan(
+    _: &TimestampMillisecondArray,
+    values: &Float64Array,
+) -> Option<f64> {
+    if has_nan(values) {
+        Some(f64::NAN)
+    } else {
+        compute::min(values)
+    }
+}
+
+/// Same as [max_over_time] but NaN if any point is NaN.
+#[range_fn(
+    name = MaxOverTimePropagateNan,
+    ret = Float64Array,
+    display_name = prom_max_over_time
+)]
+pub fn max_over_time_propagate_nan(
+    _: &TimestampMillisecondArray,
+    values: &Float64Array,
+) -> Option<f64> {
+    if has_nan(values) {
+        Some(f64::NAN)
+    } else {
+        compute::max(values)
+    }
+}
+
+/// Same as [sum_over_time] but NaN if any point is NaN.
+#[range_fn(
+    name = SumOverTimePropagateNan,
+    ret = Float64Array,
+    display_name = prom_sum_over_time
+)]
+pub fn sum_over_time_propagate_nan(
+    _: &TimestampMillisecondArray,
+    values: &Float64Array,
+) -> Option<f64> {
     compute::sum(values)
 }
 
@@ -189,7 +278,7 @@ pub fn stddev_over_time(_: &TimestampMillisecondArray, values: &Float64Array) ->
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::functions::test_util::simple_range_udf_runner;
+    use crate::functions::test_util::{range_udf_results, simple_range_udf_runner};
 
     // build timestamp range and value range arrays for test
     fn build_test_range_arrays() -> (RangeArray, RangeArray) {
@@ -491,4 +580,59 @@ mod test {
             vec![Some(0.0), Some(3.249615361854384)],
         );
     }
+
+    // build ranges of a mixed NaN window, an all-NaN window and an empty window
+    fn build_nan_range_arrays() -> (RangeArray, RangeArray) {
+        let ts_array = Arc::new(TimestampMillisecondArray::from_iter(
+            [1000i64, 2000, 3000, 4000, 5000].into_iter().map(Some),
+        ));
+        let values_array = Arc::new(Float64Array::from_iter([
+            1.0,
+            f64::NAN,
+            3.0,
+            f64::NAN,
+            f64::NAN,
+        ]));
+        let ranges = [(0, 3), (3, 2), (5, 0)];
+
+        (
+            RangeArray::from_ranges(ts_array, ranges).unwrap(),
+            RangeArray::from_ranges(values_array, ranges).unwrap(),
+        )
+    }
+
+    fn assert_nan_results(range_fn: ScalarUDF, mixed: Option<f64>, all_nan: Option<f64>) {
+        let (ts_array, value_array) = build_nan_range_arrays();
+        let results = range_udf_results(&range_fn, ts_array, value_array, vec![]);
+        let is_same = |x: Option<f64>, y: Option<f64>| match (x, y) {
+            (Some(x), Some(y)) => (x.is_nan() && y.is_nan()) || x == y,
+            (None, None) => true,
+            _ => false,
+        };
+        assert!(
+            is_same(results[0], mixed) && is_same(results[1], all_nan) && results[2].is_none(),
+            "unexpected results of {}: {results:?}",
+            range_fn.name()
+        );
+    }
+
+    #[test]
+    fn calculate_over_time_skipping_nan() {
+        assert_nan_results(AvgOverTime::scalar_udf(), Some(2.0), Some(f64::NAN));
+        assert_nan_results(MinOverTime::scalar_udf(), Some(1.0), Some(f64::NAN));
+        assert_nan_results(MaxOverTime::scalar_udf(), Some(3.0), Some(f64::NAN));
+        assert_nan_results(SumOverTime::scalar_udf(), Some(4.0), Some(f64::NAN));
+    }
+
+    #[test]
+    fn calculate_over_time_propagating_nan() {
+        for range_fn in [
+            AvgOverTimePropagateNan::scalar_udf(),
+            MinOverTimePropagateNan::scalar_udf(),
+            MaxOverTimePropagateNan::scalar_udf(),
+            SumOverTimePropagateNan::scalar_udf(),
+        ] {
+            assert_nan_results(range_fn, Some(f64::NAN), Some(f64::NAN));
+        }
+    }
 }
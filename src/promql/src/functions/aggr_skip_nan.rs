@@ -0,0 +1,260 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, ArrayRef, AsArray};
+use datafusion::error::Result as DfResult;
+use datafusion::logical_expr::function::{AccumulatorArgs, StateFieldsArgs};
+use datafusion::logical_expr::{
+    Accumulator as DfAccumulator, AggregateUDF, AggregateUDFImpl, Signature, Volatility,
+};
+use datafusion_common::ScalarValue;
+use datatypes::arrow::datatypes::{DataType, Field, Float64Type, UInt64Type};
+
+/// The PromQL aggregation operators that skip NaN samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipNanAggrKind {
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl SkipNanAggrKind {
+    /// Uses the same names as the DataFusion aggregate functions, so that the
+    /// output columns are named the same whether NaN is skipped or not.
+    fn name(&self) -> &'static str {
+        match self {
+            SkipNanAggrKind::Sum => "sum",
+            SkipNanAggrKind::Avg => "avg",
+            SkipNanAggrKind::Min => "min",
+            SkipNanAggrKind::Max => "max",
+        }
+    }
+
+    fn combine(&self, acc: f64, value: f64) -> f64 {
+        match self {
+            SkipNanAggrKind::Sum | SkipNanAggrKind::Avg => acc + value,
+            SkipNanAggrKind::Min => acc.min(value),
+            SkipNanAggrKind::Max => acc.max(value),
+        }
+    }
+}
+
+/// Aggregates Float64 values like Prometheus 3, which ignores NaN samples unless
+/// all samples are NaN, in which case the result is NaN.
+#[derive(Debug)]
+pub struct SkipNanAggr {
+    kind: SkipNanAggrKind,
+    signature: Signature,
+}
+
+impl SkipNanAggr {
+    pub fn new(kind: SkipNanAggrKind) -> Self {
+        Self {
+            kind,
+            signature: Signature::exact(vec![DataType::Float64], Volatility::Immutable),
+        }
+    }
+
+    pub fn udaf(kind: SkipNanAggrKind) -> Arc<AggregateUDF> {
+        Arc::new(AggregateUDF::new_from_impl(Self::new(kind)))
+    }
+
+    /// Whether the given aggregate function is a [SkipNanAggr].
+    pub fn is_skip_nan_aggr(udaf: &AggregateUDF) -> bool {
+        udaf.inner().as_any().is::<Self>()
+    }
+}
+
+impl AggregateUDFImpl for SkipNanAggr {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        self.kind.name()
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> DfResult<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn accumulator(&self, _acc_args: AccumulatorArgs) -> DfResult<Box<dyn DfAccumulator>> {
+        Ok(Box::new(SkipNanAccumulator::new(self.kind)))
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> DfResult<Vec<Field>> {
+        Ok(vec![
+            Field::new(format!("{}_value", args.name), DataType::Float64, true),
+            Field::new(format!("{}_count", args.name), DataType::UInt64, true),
+            Field::new(format!("{}_has_nan", args.name), DataType::Boolean, true),
+        ])
+    }
+}
+
+#[derive(Debug)]
+pub struct SkipNanAccumulator {
+    kind: SkipNanAggrKind,
+    /// Sum for `sum` and `avg`, or the extremum for `min` and `max`, of non-NaN values.
+    value: Option<f64>,
+    /// Number of non-NaN values.
+    count: u64,
+    has_nan: bool,
+}
+
+impl SkipNanAccumulator {
+    pub fn new(kind: SkipNanAggrKind) -> Self {
+        Self {
+            kind,
+            value: None,
+            count: 0,
+            has_nan: false,
+        }
+    }
+
+    fn update(&mut self, value: f64, count: u64) {
+        self.value = Some(match self.value {
+            Some(acc) => self.kind.combine(acc, value),
+            None => value,
+        });
+        self.count += count;
+    }
+}
+
+impl DfAccumulator for SkipNanAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> DfResult<()> {
+        for value in values[0].as_primitive::<Float64Type>().iter().flatten() {
+            if value.is_nan() {
+                self.has_nan = true;
+            } else {
+                self.update(value, 1);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn evaluate(&mut self) -> DfResult<ScalarValue> {
+        let result = match self.value {
+            Some(sum) if self.kind == SkipNanAggrKind::Avg => Some(sum / self.count as f64),
+            Some(value) => Some(value),
+            None if self.has_nan => Some(f64::NAN),
+            None => None,
+        };
+
+        Ok(ScalarValue::Float64(result))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+
+    fn state(&mut self) -> DfResult<Vec<ScalarValue>> {
+        Ok(vec![
+            ScalarValue::Float64(self.value),
+            ScalarValue::UInt64(Some(self.count)),
+            ScalarValue::Boolean(Some(self.has_nan)),
+        ])
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> DfResult<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+
+        let values = states[0].as_primitive::<Float64Type>();
+        let counts = states[1].as_primitive::<UInt64Type>();
+        let has_nans = states[2].as_boolean();
+        for i in 0..values.len() {
+            if has_nans.is_valid(i) && has_nans.value(i) {
+                self.has_nan = true;
+            }
+            if values.is_valid(i) {
+                self.update(values.value(i), counts.value(i));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::arrow::array::Float64Array;
+
+    use super::*;
+
+    fn aggregate(kind: SkipNanAggrKind, batches: Vec<Vec<Option<f64>>>) -> Option<f64> {
+        let mut accumulator = SkipNanAccumulator::new(kind);
+        for batch in batches {
+            let array = Arc::new(Float64Array::from(batch)) as ArrayRef;
+            accumulator.update_batch(&[array]).unwrap();
+        }
+        match accumulator.evaluate().unwrap() {
+            ScalarValue::Float64(value) => value,
+            other => panic!("unexpected result {other:?}"),
+        }
+    }
+
+    #[test]
+    fn skip_nan_in_mixed_values() {
+        let values = vec![vec![Some(1.0), Some(f64::NAN), None], vec![Some(3.0)]];
+        assert_eq!(aggregate(SkipNanAggrKind::Sum, values.clone()), Some(4.0));
+        assert_eq!(aggregate(SkipNanAggrKind::Avg, values.clone()), Some(2.0));
+        assert_eq!(aggregate(SkipNanAggrKind::Min, values.clone()), Some(1.0));
+        assert_eq!(aggregate(SkipNanAggrKind::Max, values), Some(3.0));
+    }
+
+    #[test]
+    fn nan_if_all_values_are_nan() {
+        for kind in [
+            SkipNanAggrKind::Sum,
+            SkipNanAggrKind::Avg,
+            SkipNanAggrKind::Min,
+            SkipNanAggrKind::Max,
+        ] {
+            let result = aggregate(kind, vec![vec![Some(f64::NAN), None, Some(f64::NAN)]]);
+            assert!(result.unwrap().is_nan(), "{kind:?}");
+            assert_eq!(aggregate(kind, vec![vec![None]]), None, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn merge_states() {
+        let mut left = SkipNanAccumulator::new(SkipNanAggrKind::Avg);
+        left.update_batch(&[Arc::new(Float64Array::from(vec![f64::NAN])) as ArrayRef])
+            .unwrap();
+        let mut right = SkipNanAccumulator::new(SkipNanAggrKind::Avg);
+        right
+            .update_batch(&[Arc::new(Float64Array::from(vec![2.0, 4.0])) as ArrayRef])
+            .unwrap();
+
+        let mut merged = SkipNanAccumulator::new(SkipNanAggrKind::Avg);
+        for state in [left.state().unwrap(), right.state().unwrap()] {
+            let state = state
+                .into_iter()
+                .map(|value| value.to_array().unwrap())
+                .collect::<Vec<_>>();
+            merged.merge_batch(&state).unwrap();
+        }
+        assert_eq!(merged.evaluate().unwrap(), ScalarValue::Float64(Some(3.0)));
+    }
+}
@@ -0,0 +1,281 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use datafusion::arrow::array::{Array, ArrayRef, AsArray};
+use datafusion::error::Result as DfResult;
+use datafusion::logical_expr::function::{AccumulatorArgs, StateFieldsArgs};
+use datafusion::logical_expr::{
+    Accumulator as DfAccumulator, AggregateUDF, AggregateUDFImpl, Signature, Volatility,
+};
+use datafusion_common::ScalarValue;
+use datatypes::arrow::datatypes::{DataType, Field, Float64Type, UInt64Type};
+
+/// The PromQL `stddev` and `stdvar` aggregation operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StdAggrKind {
+    Stddev,
+    Stdvar,
+}
+
+impl StdAggrKind {
+    /// Uses the same names as the DataFusion aggregate functions, so that the
+    /// output columns are named the same for Float64 and other fields.
+    fn name(&self) -> &'static str {
+        match self {
+            StdAggrKind::Stddev => "stddev_pop",
+            StdAggrKind::Stdvar => "var_pop",
+        }
+    }
+}
+
+/// Computes the population standard deviation or variance of Float64 values
+/// with Welford's online algorithm, which is stable for values of large magnitude
+/// unlike the sum of squares. Partial states are merged with Chan's formula.
+///
+/// A NaN value makes the result NaN, like Prometheus.
+#[derive(Debug)]
+pub struct StdAggr {
+    kind: StdAggrKind,
+    signature: Signature,
+}
+
+impl StdAggr {
+    pub fn new(kind: StdAggrKind) -> Self {
+        Self {
+            kind,
+            signature: Signature::exact(vec![DataType::Float64], Volatility::Immutable),
+        }
+    }
+
+    pub fn udaf(kind: StdAggrKind) -> Arc<AggregateUDF> {
+        Arc::new(AggregateUDF::new_from_impl(Self::new(kind)))
+    }
+
+    /// Whether the given aggregate function is a [StdAggr].
+    pub fn is_std_aggr(udaf: &AggregateUDF) -> bool {
+        udaf.inner().as_any().is::<Self>()
+    }
+}
+
+impl AggregateUDFImpl for StdAggr {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        self.kind.name()
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> DfResult<DataType> {
+        Ok(DataType::Float64)
+    }
+
+    fn accumulator(&self, _acc_args: AccumulatorArgs) -> DfResult<Box<dyn DfAccumulator>> {
+        Ok(Box::new(WelfordAccumulator::new(self.kind)))
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> DfResult<Vec<Field>> {
+        Ok(vec![
+            Field::new(format!("{}_count", args.name), DataType::UInt64, true),
+            Field::new(format!("{}_mean", args.name), DataType::Float64, true),
+            Field::new(format!("{}_m2", args.name), DataType::Float64, true),
+        ])
+    }
+}
+
+#[derive(Debug)]
+pub struct WelfordAccumulator {
+    kind: StdAggrKind,
+    count: u64,
+    mean: f64,
+    /// Sum of the squared deviations from the mean.
+    m2: f64,
+}
+
+impl WelfordAccumulator {
+    pub fn new(kind: StdAggrKind) -> Self {
+        Self {
+            kind,
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    fn update(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    fn merge(&mut self, count: u64, mean: f64, m2: f64) {
+        if count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            (self.count, self.mean, self.m2) = (count, mean, m2);
+            return;
+        }
+        let total = self.count + count;
+        let delta = mean - self.mean;
+        self.mean += delta * count as f64 / total as f64;
+        self.m2 += m2 + delta * delta * self.count as f64 * count as f64 / total as f64;
+        self.count = total;
+    }
+}
+
+impl DfAccumulator for WelfordAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> DfResult<()> {
+        for value in values[0].as_primitive::<Float64Type>().iter().flatten() {
+            self.update(value);
+        }
+
+        Ok(())
+    }
+
+    fn evaluate(&mut self) -> DfResult<ScalarValue> {
+        if self.count == 0 {
+            return Ok(ScalarValue::Float64(None));
+        }
+        let variance = self.m2 / self.count as f64;
+        let result = match self.kind {
+            StdAggrKind::Stddev => variance.sqrt(),
+            StdAggrKind::Stdvar => variance,
+        };
+
+        Ok(ScalarValue::Float64(Some(result)))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+
+    fn state(&mut self) -> DfResult<Vec<ScalarValue>> {
+        Ok(vec![
+            ScalarValue::UInt64(Some(self.count)),
+            ScalarValue::Float64(Some(self.mean)),
+            ScalarValue::Float64(Some(self.m2)),
+        ])
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> DfResult<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+
+        let counts = states[0].as_primitive::<UInt64Type>();
+        let means = states[1].as_primitive::<Float64Type>();
+        let m2s = states[2].as_primitive::<Float64Type>();
+        for i in 0..counts.len() {
+            if counts.is_valid(i) {
+                self.merge(counts.value(i), means.value(i), m2s.value(i));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::arrow::array::Float64Array;
+
+    use super::*;
+
+    fn aggregate(kind: StdAggrKind, batches: Vec<Vec<Option<f64>>>) -> Option<f64> {
+        let mut accumulator = WelfordAccumulator::new(kind);
+        for batch in batches {
+            let array = Arc::new(Float64Array::from(batch)) as ArrayRef;
+            accumulator.update_batch(&[array]).unwrap();
+        }
+        match accumulator.evaluate().unwrap() {
+            ScalarValue::Float64(value) => value,
+            other => panic!("unexpected result {other:?}"),
+        }
+    }
+
+    fn assert_close(expected: f64, actual: Option<f64>) {
+        let actual = actual.unwrap();
+        assert!((expected - actual).abs() < 1e-9, "{expected} != {actual}");
+    }
+
+    #[test]
+    fn population_variance() {
+        let values = vec![
+            vec![Some(2.0), Some(4.0), None, Some(4.0)],
+            vec![Some(4.0), Some(5.0), Some(5.0), Some(7.0), Some(9.0)],
+        ];
+        assert_close(4.0, aggregate(StdAggrKind::Stdvar, values.clone()));
+        assert_close(2.0, aggregate(StdAggrKind::Stddev, values));
+        assert_eq!(
+            aggregate(StdAggrKind::Stddev, vec![vec![Some(1.0)]]),
+            Some(0.0)
+        );
+        assert_eq!(aggregate(StdAggrKind::Stddev, vec![vec![None]]), None);
+        assert!(
+            aggregate(StdAggrKind::Stddev, vec![vec![Some(1.0), Some(f64::NAN)]])
+                .unwrap()
+                .is_nan()
+        );
+    }
+
+    #[test]
+    fn large_magnitude() {
+        // The sum of squares is around 1e20, far beyond the precision of f64 for
+        // deviations of 1.
+        let values = vec![(0..100)
+            .map(|i| Some(1e9 + (i % 2) as f64))
+            .collect::<Vec<_>>()];
+        assert_close(0.25, aggregate(StdAggrKind::Stdvar, values.clone()));
+        assert_close(0.5, aggregate(StdAggrKind::Stddev, values));
+    }
+
+    #[test]
+    fn merge_states() {
+        let mut left = WelfordAccumulator::new(StdAggrKind::Stdvar);
+        left.update_batch(&[Arc::new(Float64Array::from(vec![1e9 + 2.0, 1e9 + 4.0])) as ArrayRef])
+            .unwrap();
+        let mut right = WelfordAccumulator::new(StdAggrKind::Stdvar);
+        right
+            .update_batch(&[
+                Arc::new(Float64Array::from(vec![1e9 + 4.0, 1e9 + 4.0, 1e9 + 5.0])) as ArrayRef,
+            ])
+            .unwrap();
+        let empty = WelfordAccumulator::new(StdAggrKind::Stdvar);
+
+        let mut merged = WelfordAccumulator::new(StdAggrKind::Stdvar);
+        for mut accumulator in [left, empty, right] {
+            let state = accumulator
+                .state()
+                .unwrap()
+                .into_iter()
+                .map(|value| value.to_array().unwrap())
+                .collect::<Vec<_>>();
+            merged.merge_batch(&state).unwrap();
+        }
+        // 2, 4, 4, 4, 5 has mean 3.8 and variance 0.96
+        let ScalarValue::Float64(variance) = merged.evaluate().unwrap() else {
+            unreachable!()
+        };
+        assert_close(0.96, variance);
+    }
+}
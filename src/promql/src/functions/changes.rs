@@ -25,26 +25,24 @@ use datafusion::physical_plan::ColumnarValue;
 use datatypes::arrow::array::Array;
 use datatypes::arrow::datatypes::DataType;
 
-use crate::functions::extract_array;
+use crate::functions::{extract_array, window_values};
 use crate::range_array::RangeArray;
 
 /// used to count the number of value changes that occur within a specific time range
 #[range_fn(name = Changes, ret = Float64Array, display_name = prom_changes)]
 pub fn changes(_: &TimestampMillisecondArray, values: &Float64Array) -> Option<f64> {
-    if values.is_empty() {
-        None
-    } else {
-        let (first, rest) = values.values().split_first().unwrap();
-        let mut num_changes = 0;
-        let mut prev_element = first;
-        for cur_element in rest {
-            if cur_element != prev_element && !(cur_element.is_nan() && prev_element.is_nan()) {
-                num_changes += 1;
-            }
-            prev_element = cur_element;
+    // An empty window has no output, while a window with a single sample has 0 changes.
+    let values = window_values(values);
+    let (first, rest) = values.split_first()?;
+    let mut num_changes = 0;
+    let mut prev_element = first;
+    for cur_element in rest {
+        if cur_element != prev_element && !(cur_element.is_nan() && prev_element.is_nan()) {
+            num_changes += 1;
         }
-        Some(num_changes as f64)
+        prev_element = cur_element;
     }
+    Some(num_changes as f64)
 }
 
 #[cfg(test)]
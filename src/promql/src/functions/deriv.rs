@@ -102,4 +102,24 @@ mod test {
             vec![Some(0.0)],
         );
     }
+
+    #[test]
+    fn far_future_deriv() {
+        // 2^58 ms, where adjacent f64 values are 64 ms apart
+        let start = 1i64 << 58;
+        let ts_array = Arc::new(TimestampMillisecondArray::from_iter(
+            [0, 10_000, 20_000, 30_000].map(|offset| Some(start + offset)),
+        ));
+        let val_array = Arc::new(Float64Array::from_iter([0.0, 10.0, 20.0, 30.0]));
+        let range = [(0, 4)];
+        let ts_range_array = RangeArray::from_ranges(ts_array, range).unwrap();
+        let value_range_array = RangeArray::from_ranges(val_array, range).unwrap();
+
+        simple_range_udf_runner(
+            Deriv::scalar_udf(),
+            ts_range_array,
+            value_range_array,
+            vec![Some(1.0)],
+        );
+    }
 }
@@ -29,6 +29,7 @@
 
 //! Implementations of `rate`, `increase` and `delta` functions in PromQL.
 
+use std::borrow::Cow;
 use std::fmt::Display;
 use std::sync::Arc;
 
@@ -40,31 +41,60 @@ use datafusion::physical_plan::ColumnarValue;
 use datafusion_expr::create_udf;
 use datatypes::arrow::array::Array;
 use datatypes::arrow::datatypes::DataType;
+use serde::{Deserialize, Serialize};
 
 use crate::extension_plan::Millisecond;
-use crate::functions::extract_array;
+use crate::functions::{extract_array, window_samples};
 use crate::range_array::RangeArray;
 
 pub type Delta = ExtrapolatedRate<false, false>;
 pub type Rate = ExtrapolatedRate<true, true>;
 pub type Increase = ExtrapolatedRate<true, false>;
 
+/// How `rate()` and `increase()` treat the start of the range.
+///
+/// With samples at `0s, 10s, ..., 50s` and `increase(foo[1m])` evaluated at `60s`:
+/// - `PrometheusExtrapolate` takes the samples within `[0s, 60s]` and extrapolates
+///   their increase over the `50s` they cover to the whole minute.
+/// - `UsePreviousSample` takes the samples within `(0s, 60s]` and starts from the
+///   last sample at or before `0s`, like VictoriaMetrics. The result is the exact
+///   increase between these samples, and `rate()` divides it by the time between
+///   them. The previous sample is searched within the lookback delta.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RateFirstSamplePolicy {
+    #[default]
+    PrometheusExtrapolate,
+    UsePreviousSample,
+}
+
 /// Part of the `extrapolatedRate` in Promql,
 /// from <https://github.com/prometheus/prometheus/blob/v0.40.1/promql/functions.go#L66>
 #[derive(Debug)]
 pub struct ExtrapolatedRate<const IS_COUNTER: bool, const IS_RATE: bool> {
     /// Range duration in millisecond
     range_length: i64,
+    /// Whether the input ranges also contain the samples before the range, and the
+    /// last of them is the first sample instead of extrapolating to the range start.
+    use_previous_sample: bool,
 }
 
 impl<const IS_COUNTER: bool, const IS_RATE: bool> ExtrapolatedRate<IS_COUNTER, IS_RATE> {
     /// Constructor. Other public usage should use [scalar_udf()](ExtrapolatedRate::scalar_udf()) instead.
-    fn new(range_length: i64) -> Self {
-        Self { range_length }
+    fn new(range_length: i64, use_previous_sample: bool) -> Self {
+        Self {
+            range_length,
+            use_previous_sample,
+        }
     }
 
-    fn scalar_udf_with_name(name: &str, range_length: i64) -> ScalarUDF {
-        let input_types = vec![
+    fn scalar_udf_with_name(
+        name: &str,
+        range_length: i64,
+        with_created: bool,
+        use_previous_sample: bool,
+    ) -> ScalarUDF {
+        let mut input_types = vec![
             // timestamp range vector
             RangeArray::convert_data_type(DataType::Timestamp(TimeUnit::Millisecond, None)),
             // value range vector
@@ -72,18 +102,26 @@ impl<const IS_COUNTER: bool, const IS_RATE: bool> ExtrapolatedRate<IS_COUNTER, I
             // timestamp vector
             DataType::Timestamp(TimeUnit::Millisecond, None),
         ];
+        if with_created {
+            // created timestamp range vector
+            input_types.push(RangeArray::convert_data_type(DataType::Timestamp(
+                TimeUnit::Millisecond,
+                None,
+            )));
+        }
 
         create_udf(
             name,
             input_types,
             DataType::Float64,
             Volatility::Volatile,
-            Arc::new(move |input: &_| Self::new(range_length).calc(input)) as _,
+            Arc::new(move |input: &_| Self::new(range_length, use_previous_sample).calc(input))
+                as _,
         )
     }
 
     fn calc(&self, input: &[ColumnarValue]) -> Result<ColumnarValue, DataFusionError> {
-        assert_eq!(input.len(), 3);
+        assert!(input.len() == 3 || input.len() == 4);
 
         // construct matrix from input
         let ts_array = extract_array(&input[0])?;
@@ -95,6 +133,13 @@ impl<const IS_COUNTER: bool, const IS_RATE: bool> ExtrapolatedRate<IS_COUNTER, I
             .as_any()
             .downcast_ref::<TimestampMillisecondArray>()
             .unwrap();
+        let created_range = input
+            .get(3)
+            .map(|created| {
+                let created = extract_array(created)?;
+                RangeArray::try_new(created.to_data().into())
+            })
+            .transpose()?;
 
         // calculation
         let mut result_array = Vec::with_capacity(ts_range.len());
@@ -103,16 +148,42 @@ impl<const IS_COUNTER: bool, const IS_RATE: bool> ExtrapolatedRate<IS_COUNTER, I
             let timestamps = timestamps
                 .as_any()
                 .downcast_ref::<TimestampMillisecondArray>()
-                .unwrap()
-                .values();
+                .unwrap();
             let end_ts = ts.value(index);
             let values = value_range.get(index).unwrap();
-            let values = values
-                .as_any()
-                .downcast_ref::<Float64Array>()
-                .unwrap()
-                .values();
+            let values = values.as_any().downcast_ref::<Float64Array>().unwrap();
+            let (mut timestamps, mut values) = window_samples(timestamps, values);
+            if self.use_previous_sample {
+                let range_start = end_ts - self.range_length;
+                // the first sample within the range, there is no output without it
+                let first_in_range = timestamps.partition_point(|ts| *ts <= range_start);
+                if first_in_range == timestamps.len() {
+                    result_array.push(None);
+                    continue;
+                }
+                // keep the last sample before the range if there is one
+                let skipped = first_in_range.saturating_sub(1);
+                skip_samples(&mut timestamps, skipped);
+                skip_samples(&mut values, skipped);
+            }
+            if IS_COUNTER {
+                if let Some(created) = created_range
+                    .as_ref()
+                    .and_then(|range| Self::first_created(range, index))
+                {
+                    // The counter was created within the range, so it starts from a zero
+                    // sample at the created time instead of being extrapolated before.
+                    if created > end_ts - self.range_length
+                        && timestamps.first().is_some_and(|first| created < *first)
+                    {
+                        timestamps.to_mut().insert(0, created);
+                        values.to_mut().insert(0, 0.0);
+                    }
+                }
+            }
 
+            // Needs at least two samples to calculate the rate, so there is no
+            // output for empty or single sample windows.
             if values.len() < 2 {
                 result_array.push(None);
                 continue;
@@ -121,6 +192,8 @@ impl<const IS_COUNTER: bool, const IS_RATE: bool> ExtrapolatedRate<IS_COUNTER, I
             // refer to functions.go L83-L110
             let mut result_value = values.last().unwrap() - values.first().unwrap();
             if IS_COUNTER {
+                // Correct every reset in the window, not only the first one: the value
+                // before each drop is lost from the endpoint difference and added back.
                 for window in values.windows(2) {
                     let prev = window[0];
                     let curr = window[1];
@@ -130,8 +203,19 @@ impl<const IS_COUNTER: bool, const IS_RATE: bool> ExtrapolatedRate<IS_COUNTER, I
                 }
             }
 
+            if self.use_previous_sample {
+                if IS_RATE {
+                    // safety: the timestamps of the two or more samples are distinct.
+                    let sampled_interval =
+                        (timestamps.last().unwrap() - timestamps.first().unwrap()) as f64 / 1000.0;
+                    result_value /= sampled_interval;
+                }
+                result_array.push(Some(result_value));
+                continue;
+            }
+
             let mut factor = Self::extrapolate_factor(
-                timestamps,
+                &timestamps,
                 end_ts,
                 self.range_length,
                 *values.first().unwrap(),
@@ -150,6 +234,17 @@ impl<const IS_COUNTER: bool, const IS_RATE: bool> ExtrapolatedRate<IS_COUNTER, I
         Ok(result)
     }
 
+    /// Returns the first created timestamp of the samples in the range at `index`.
+    fn first_created(created_range: &RangeArray, index: usize) -> Option<Millisecond> {
+        let created = created_range.get(index)?;
+        created
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()?
+            .iter()
+            .flatten()
+            .next()
+    }
+
     fn extrapolate_factor(
         timestamps: &[Millisecond],
         range_end: Millisecond,
@@ -209,7 +304,7 @@ impl ExtrapolatedRate<false, false> {
     }
 
     pub fn scalar_udf(range_length: i64) -> ScalarUDF {
-        Self::scalar_udf_with_name(Self::name(), range_length)
+        Self::scalar_udf_with_name(Self::name(), range_length, false, false)
     }
 }
 
@@ -220,7 +315,30 @@ impl ExtrapolatedRate<true, true> {
     }
 
     pub fn scalar_udf(range_length: i64) -> ScalarUDF {
-        Self::scalar_udf_with_name(Self::name(), range_length)
+        Self::scalar_udf_with_name(Self::name(), range_length, false, false)
+    }
+
+    /// Same as [`Self::scalar_udf`], with the created timestamps of the counter as
+    /// the fourth argument. A counter created within the range starts from zero at
+    /// the created time.
+    pub fn scalar_udf_with_created(range_length: i64) -> ScalarUDF {
+        Self::scalar_udf_with_name(Self::name(), range_length, true, false)
+    }
+
+    /// Same as [`Self::scalar_udf_with_created`], but with the given
+    /// [RateFirstSamplePolicy]. With [RateFirstSamplePolicy::UsePreviousSample] the
+    /// input ranges must be extended to include the samples before the range.
+    pub fn scalar_udf_with_options(
+        range_length: i64,
+        with_created: bool,
+        policy: RateFirstSamplePolicy,
+    ) -> ScalarUDF {
+        Self::scalar_udf_with_name(
+            Self::name(),
+            range_length,
+            with_created,
+            policy == RateFirstSamplePolicy::UsePreviousSample,
+        )
     }
 }
 
@@ -231,7 +349,36 @@ impl ExtrapolatedRate<true, false> {
     }
 
     pub fn scalar_udf(range_length: i64) -> ScalarUDF {
-        Self::scalar_udf_with_name(Self::name(), range_length)
+        Self::scalar_udf_with_name(Self::name(), range_length, false, false)
+    }
+
+    /// Same as [`Self::scalar_udf`], with the created timestamps of the counter.
+    pub fn scalar_udf_with_created(range_length: i64) -> ScalarUDF {
+        Self::scalar_udf_with_name(Self::name(), range_length, true, false)
+    }
+
+    /// Same as [`Self::scalar_udf_with_created`], but with the given [RateFirstSamplePolicy].
+    pub fn scalar_udf_with_options(
+        range_length: i64,
+        with_created: bool,
+        policy: RateFirstSamplePolicy,
+    ) -> ScalarUDF {
+        Self::scalar_udf_with_name(
+            Self::name(),
+            range_length,
+            with_created,
+            policy == RateFirstSamplePolicy::UsePreviousSample,
+        )
+    }
+}
+
+/// Drops the first `n` samples.
+fn skip_samples<T: Clone>(samples: &mut Cow<'_, [T]>, n: usize) {
+    match samples {
+        Cow::Borrowed(samples) => *samples = &samples[n..],
+        Cow::Owned(samples) => {
+            samples.drain(..n);
+        }
     }
 }
 
@@ -273,7 +420,7 @@ mod test {
             ColumnarValue::Array(timestamps),
         ];
         let output = extract_array(
-            &ExtrapolatedRate::<IS_COUNTER, IS_RATE>::new(5)
+            &ExtrapolatedRate::<IS_COUNTER, IS_RATE>::new(5, false)
                 .calc(&input)
                 .unwrap(),
         )
@@ -450,6 +597,197 @@ mod test {
         );
     }
 
+    #[test]
+    fn multiple_counter_resets_in_window() {
+        let ts_array = Arc::new(TimestampMillisecondArray::from_iter(
+            [1, 2, 3, 4, 5, 6, 7, 8, 9].into_iter().map(Some),
+        ));
+        // the counter resets twice, this series should be treated like
+        // [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]
+        let values_array = Arc::new(Float64Array::from_iter([
+            1.0, 2.0, 3.0, 1.0, 2.0, 3.0, 1.0, 2.0, 3.0,
+        ]));
+        // the second window [3.0, 1.0, 2.0, 3.0, 1.0] contains both resets
+        let ranges = [(0, 5), (2, 5), (4, 5)];
+        let timestamps = Arc::new(TimestampMillisecondArray::from_iter(
+            [5, 7, 9].into_iter().map(Some),
+        )) as ArrayRef;
+
+        let ts_range = RangeArray::from_ranges(ts_array.clone(), ranges).unwrap();
+        let value_range = RangeArray::from_ranges(values_array.clone(), ranges).unwrap();
+        extrapolated_rate_runner::<true, false>(
+            ts_range,
+            value_range,
+            timestamps.clone(),
+            vec![5.0, 5.0, 5.0],
+        );
+
+        let ts_range = RangeArray::from_ranges(ts_array, ranges).unwrap();
+        let value_range = RangeArray::from_ranges(values_array, ranges).unwrap();
+        extrapolated_rate_runner::<true, true>(
+            ts_range,
+            value_range,
+            timestamps,
+            vec![1000.0, 1000.0, 1000.0],
+        );
+    }
+
+    #[test]
+    fn increase_with_created() {
+        // a counter created at 2, first sampled at 3
+        let ts_array = Arc::new(TimestampMillisecondArray::from_iter(
+            [3, 4, 5].into_iter().map(Some),
+        ));
+        let values_array = Arc::new(Float64Array::from_iter([2.0, 3.0, 4.0]));
+        let ranges = [(0, 3), (2, 1)];
+        let timestamps =
+            Arc::new(TimestampMillisecondArray::from_iter([Some(5), Some(5)])) as ArrayRef;
+        let increase = |created: Option<Vec<Option<i64>>>| {
+            let mut input = vec![
+                ColumnarValue::Array(Arc::new(
+                    RangeArray::from_ranges(ts_array.clone(), ranges)
+                        .unwrap()
+                        .into_dict(),
+                )),
+                ColumnarValue::Array(Arc::new(
+                    RangeArray::from_ranges(values_array.clone(), ranges)
+                        .unwrap()
+                        .into_dict(),
+                )),
+                ColumnarValue::Array(timestamps.clone()),
+            ];
+            if let Some(created) = created {
+                let created = Arc::new(TimestampMillisecondArray::from_iter(created));
+                input.push(ColumnarValue::Array(Arc::new(
+                    RangeArray::from_ranges(created, ranges)
+                        .unwrap()
+                        .into_dict(),
+                )));
+            }
+            let output = extract_array(&Increase::new(5, false).calc(&input).unwrap()).unwrap();
+            output
+                .as_any()
+                .downcast_ref::<Float64Array>()
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>()
+        };
+        let assert_close = |expected: f64, actual: Option<f64>| {
+            let actual = actual.unwrap();
+            assert!((expected - actual).abs() < 1e-9, "{expected} != {actual}");
+        };
+
+        // Without the created timestamp, the increase is extrapolated to the zero
+        // point of the counter, and a single sample has no increase.
+        let result = increase(None);
+        assert_close(2.5, result[0]);
+        assert_eq!(None, result[1]);
+
+        // The counter increased from zero since it was created.
+        let result = increase(Some(vec![Some(2), Some(2), Some(2)]));
+        assert_close(4.0, result[0]);
+        assert_close(4.0, result[1]);
+
+        // The counter created before the range or with unknown created time is
+        // extrapolated as usual.
+        for created in [vec![Some(0), Some(0), Some(0)], vec![None, None, None]] {
+            let result = increase(Some(created));
+            assert_close(2.5, result[0]);
+            assert_eq!(None, result[1]);
+        }
+    }
+
+    #[test]
+    fn rate_first_sample_policy() {
+        // Two counters evaluated at 60s with a 1m range starting at 0s. The first
+        // one has a sample at the range start, the second one has its previous
+        // sample at -10s.
+        let ts_array = Arc::new(TimestampMillisecondArray::from_iter(
+            [
+                -10_000, 0, 10_000, 20_000, 30_000, 40_000, 50_000, // first counter
+                -10_000, 20_000, 30_000, 40_000, 50_000, // second counter
+            ]
+            .into_iter()
+            .map(Some),
+        ));
+        let values_array = Arc::new(Float64Array::from_iter([
+            0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, // first counter
+            0.0, 3.0, 4.0, 5.0, 6.0, // second counter
+        ]));
+        let timestamps = Arc::new(TimestampMillisecondArray::from_iter([
+            Some(60_000),
+            Some(60_000),
+        ])) as ArrayRef;
+        let calc = |udf: &dyn Fn(&[ColumnarValue]) -> Result<ColumnarValue, DataFusionError>,
+                    ranges: [(u32, u32); 2]| {
+            let input = vec![
+                ColumnarValue::Array(Arc::new(
+                    RangeArray::from_ranges(ts_array.clone(), ranges)
+                        .unwrap()
+                        .into_dict(),
+                )),
+                ColumnarValue::Array(Arc::new(
+                    RangeArray::from_ranges(values_array.clone(), ranges)
+                        .unwrap()
+                        .into_dict(),
+                )),
+                ColumnarValue::Array(timestamps.clone()),
+            ];
+            extract_array(&udf(&input).unwrap())
+                .unwrap()
+                .as_any()
+                .downcast_ref::<Float64Array>()
+                .unwrap()
+                .values()
+                .to_vec()
+        };
+        let assert_close = |expected: Vec<f64>, actual: Vec<f64>| {
+            assert_eq!(expected.len(), actual.len());
+            for (expected, actual) in expected.into_iter().zip(actual) {
+                assert!((expected - actual).abs() < 1e-9, "{expected} != {actual}");
+            }
+        };
+        // the ranges only contain the samples within [0s, 60s]
+        let extrapolate_ranges = [(1, 6), (8, 4)];
+        // the ranges are extended by the lookback delta
+        let previous_sample_ranges = [(0, 7), (7, 5)];
+
+        // The increase over the 50s between the samples is extrapolated to the
+        // range, and the second counter to half a scrape interval before its first
+        // sample.
+        assert_close(
+            vec![6.0, 4.5],
+            calc(
+                &|input| Increase::new(60_000, false).calc(input),
+                extrapolate_ranges,
+            ),
+        );
+        assert_close(
+            vec![0.1, 0.075],
+            calc(
+                &|input| Rate::new(60_000, false).calc(input),
+                extrapolate_ranges,
+            ),
+        );
+
+        // The sample at 0s is the previous sample of the range (0s, 60s], and the
+        // increase is exact.
+        assert_close(
+            vec![5.0, 6.0],
+            calc(
+                &|input| Increase::new(60_000, true).calc(input),
+                previous_sample_ranges,
+            ),
+        );
+        assert_close(
+            vec![0.1, 0.1],
+            calc(
+                &|input| Rate::new(60_000, true).calc(input),
+                previous_sample_ranges,
+            ),
+        );
+    }
+
     #[test]
     fn rate_normal_input() {
         let ts_array = Arc::new(TimestampMillisecondArray::from_iter(
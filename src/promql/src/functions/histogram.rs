@@ -0,0 +1,1011 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Functions over native histograms stored in the format of [NativeHistogram].
+
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use datafusion::error::{DataFusionError, Result as DfResult};
+use datafusion::logical_expr::function::{AccumulatorArgs, StateFieldsArgs};
+use datafusion::logical_expr::{
+    Accumulator as DfAccumulator, AggregateUDF, AggregateUDFImpl, Signature,
+};
+use datafusion_common::ScalarValue;
+use datafusion_expr::{create_udf, ColumnarValue, ScalarUDF, Volatility};
+use datatypes::arrow::array::{Array, ArrayRef, AsArray, BinaryArray, BinaryBuilder, Float64Array};
+use datatypes::arrow::datatypes::{
+    DataType, Field, Float64Type, Int32Type, TimeUnit, TimestampMillisecondType, UInt64Type,
+};
+
+use crate::functions::extract_array;
+use crate::native_histogram::{
+    decode_spans, decode_values, NativeHistogram, NATIVE_HISTOGRAM_LAYOUT,
+};
+use crate::range_array::RangeArray;
+
+/// `histogram_quantile` over native histograms.
+pub struct HistogramQuantile;
+
+impl HistogramQuantile {
+    pub const fn name() -> &'static str {
+        "prom_histogram_quantile"
+    }
+
+    pub fn scalar_udf(phi: f64) -> ScalarUDF {
+        create_udf(
+            Self::name(),
+            vec![DataType::Binary],
+            DataType::Float64,
+            Volatility::Volatile,
+            Arc::new(move |input: &_| map_histograms(input, |h| h.quantile(phi))) as _,
+        )
+    }
+
+    /// `native_histogram_quantile(phi, histogram)` to query the stored histograms in SQL.
+    pub fn sql_udf() -> ScalarUDF {
+        create_udf(
+            "native_histogram_quantile",
+            vec![DataType::Float64, DataType::Binary],
+            DataType::Float64,
+            Volatility::Immutable,
+            Arc::new(|input: &[ColumnarValue]| {
+                assert_eq!(input.len(), 2);
+                let ColumnarValue::Scalar(ScalarValue::Float64(Some(phi))) = &input[0] else {
+                    return Err(DataFusionError::Execution(format!(
+                        "expect a constant quantile, found {:?}",
+                        input[0]
+                    )));
+                };
+                map_histograms(&input[1..], |h| h.quantile(*phi))
+            }) as _,
+        )
+    }
+}
+
+/// Packs the columns of the [NATIVE_HISTOGRAM_LAYOUT] of a native histogram table
+/// into [NativeHistogram]s, which the other functions take. The histogram of a row
+/// whose schema is null is null.
+pub struct HistogramPack;
+
+impl HistogramPack {
+    pub const fn name() -> &'static str {
+        "native_histogram"
+    }
+
+    pub fn scalar_udf() -> ScalarUDF {
+        create_udf(
+            Self::name(),
+            vec![
+                DataType::Int32,
+                DataType::Boolean,
+                DataType::Float64,
+                DataType::Float64,
+                DataType::Float64,
+                DataType::Float64,
+                DataType::Binary,
+                DataType::Binary,
+                DataType::Binary,
+                DataType::Binary,
+                DataType::Binary,
+            ],
+            DataType::Binary,
+            Volatility::Immutable,
+            Arc::new(Self::pack) as _,
+        )
+    }
+
+    fn pack(input: &[ColumnarValue]) -> Result<ColumnarValue, DataFusionError> {
+        assert_eq!(input.len(), NATIVE_HISTOGRAM_LAYOUT.len());
+
+        let arrays = ColumnarValue::values_to_arrays(input)?;
+        let schemas = arrays[0].as_primitive_opt::<Int32Type>();
+        let is_gauges = arrays[1].as_boolean_opt();
+        let floats = arrays[2..6]
+            .iter()
+            .map(|array| array.as_primitive_opt::<Float64Type>())
+            .collect::<Option<Vec<_>>>();
+        let binaries = arrays[6..]
+            .iter()
+            .map(|array| array.as_binary_opt::<i32>())
+            .collect::<Option<Vec<_>>>();
+        let (Some(schemas), Some(is_gauges), Some(floats), Some(binaries)) =
+            (schemas, is_gauges, floats, binaries)
+        else {
+            return Err(DataFusionError::Execution(format!(
+                "expect the native histogram layout as input, found {:?}",
+                arrays.iter().map(|a| a.data_type()).collect::<Vec<_>>()
+            )));
+        };
+        let float = |i: usize, row: usize| {
+            if floats[i].is_null(row) {
+                0.0
+            } else {
+                floats[i].value(row)
+            }
+        };
+        let empty: &[u8] = &[];
+        let binary = |i: usize, row: usize| {
+            if binaries[i].is_null(row) {
+                empty
+            } else {
+                binaries[i].value(row)
+            }
+        };
+
+        let mut builder = BinaryBuilder::new();
+        for row in 0..schemas.len() {
+            if schemas.is_null(row) {
+                builder.append_null();
+                continue;
+            }
+            let histogram = NativeHistogram {
+                schema: schemas.value(row),
+                is_gauge: !is_gauges.is_null(row) && is_gauges.value(row),
+                zero_threshold: float(0, row),
+                zero_count: float(1, row),
+                count: float(2, row),
+                sum: float(3, row),
+                positive_spans: decode_spans(binary(0, row))?,
+                positive_buckets: decode_values(binary(1, row))?,
+                negative_spans: decode_spans(binary(2, row))?,
+                negative_buckets: decode_values(binary(3, row))?,
+                custom_values: decode_values(binary(4, row))?,
+            };
+            histogram.validate()?;
+            builder.append_value(histogram.encode());
+        }
+
+        Ok(ColumnarValue::Array(Arc::new(builder.finish())))
+    }
+}
+
+/// `histogram_count` over native histograms.
+pub struct HistogramCount;
+
+impl HistogramCount {
+    pub const fn name() -> &'static str {
+        "prom_histogram_count"
+    }
+
+    pub fn scalar_udf() -> ScalarUDF {
+        create_udf(
+            Self::name(),
+            vec![DataType::Binary],
+            DataType::Float64,
+            Volatility::Immutable,
+            Arc::new(|input: &_| map_histograms(input, |h| h.count)) as _,
+        )
+    }
+}
+
+/// `histogram_sum` over native histograms.
+pub struct HistogramSum;
+
+impl HistogramSum {
+    pub const fn name() -> &'static str {
+        "prom_histogram_sum"
+    }
+
+    pub fn scalar_udf() -> ScalarUDF {
+        create_udf(
+            Self::name(),
+            vec![DataType::Binary],
+            DataType::Float64,
+            Volatility::Immutable,
+            Arc::new(|input: &_| map_histograms(input, |h| h.sum)) as _,
+        )
+    }
+}
+
+/// `avg_over_time` over native histograms. The histograms in each range are
+/// added up and divided by their count into a native histogram.
+pub struct HistogramAvgOverTime;
+
+impl HistogramAvgOverTime {
+    pub const fn name() -> &'static str {
+        "prom_histogram_avg_over_time"
+    }
+
+    pub fn scalar_udf() -> ScalarUDF {
+        create_udf(
+            Self::name(),
+            vec![
+                RangeArray::convert_data_type(DataType::Timestamp(TimeUnit::Millisecond, None)),
+                RangeArray::convert_data_type(DataType::Binary),
+            ],
+            DataType::Binary,
+            Volatility::Volatile,
+            Arc::new(Self::calc) as _,
+        )
+    }
+
+    fn calc(input: &[ColumnarValue]) -> Result<ColumnarValue, DataFusionError> {
+        assert_eq!(input.len(), 2);
+
+        let ranges = RangeArray::try_new(extract_array(&input[1])?.to_data().into())?;
+        let result = (0..ranges.len())
+            .map(|index| {
+                let range = ranges.get(index).unwrap();
+                let histograms = range.as_binary_opt::<i32>().ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "expect binary native histograms as input, found {}",
+                        range.data_type()
+                    ))
+                })?;
+                Ok(avg_histograms(histograms)?.map(|h| h.encode()))
+            })
+            .collect::<Result<BinaryArray, DataFusionError>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(result)))
+    }
+}
+
+/// `idelta` and `irate` over native histograms, the difference between the last
+/// two histograms in each range. `irate` divides the difference by the seconds
+/// between them, and takes the last histogram as the increase after a counter reset.
+#[derive(Debug)]
+pub struct HistogramIDelta<const IS_RATE: bool>;
+
+impl<const IS_RATE: bool> HistogramIDelta<IS_RATE> {
+    pub const fn name() -> &'static str {
+        if IS_RATE {
+            "prom_histogram_irate"
+        } else {
+            "prom_histogram_idelta"
+        }
+    }
+
+    pub fn scalar_udf() -> ScalarUDF {
+        create_udf(
+            Self::name(),
+            vec![
+                RangeArray::convert_data_type(DataType::Timestamp(TimeUnit::Millisecond, None)),
+                RangeArray::convert_data_type(DataType::Binary),
+            ],
+            DataType::Binary,
+            Volatility::Volatile,
+            Arc::new(Self::calc) as _,
+        )
+    }
+
+    fn calc(input: &[ColumnarValue]) -> Result<ColumnarValue, DataFusionError> {
+        assert_eq!(input.len(), 2);
+
+        let ts_ranges = RangeArray::try_new(extract_array(&input[0])?.to_data().into())?;
+        let ranges = RangeArray::try_new(extract_array(&input[1])?.to_data().into())?;
+        let result = (0..ranges.len())
+            .map(|index| {
+                let timestamps = ts_ranges.get(index).unwrap();
+                let timestamps = timestamps.as_primitive::<TimestampMillisecondType>();
+                let range = ranges.get(index).unwrap();
+                let histograms = range.as_binary_opt::<i32>().ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "expect binary native histograms as input, found {}",
+                        range.data_type()
+                    ))
+                })?;
+                // The last two non-null histograms in the range.
+                let mut samples = timestamps
+                    .values()
+                    .iter()
+                    .zip(histograms.iter())
+                    .filter_map(|(ts, bytes)| bytes.map(|bytes| (*ts, bytes)))
+                    .rev();
+                let (Some((last_ts, last)), Some((prev_ts, prev))) =
+                    (samples.next(), samples.next())
+                else {
+                    return Ok(None);
+                };
+                let last = NativeHistogram::decode(last)?;
+                let prev = NativeHistogram::decode(prev)?;
+                Self::instant_delta(last_ts - prev_ts, last, &prev).map(|h| Some(h.encode()))
+            })
+            .collect::<Result<BinaryArray, DataFusionError>>()?;
+
+        Ok(ColumnarValue::Array(Arc::new(result)))
+    }
+
+    /// Computes the result from the `last` and `prev` histograms sampled
+    /// `interval_millis` apart.
+    fn instant_delta(
+        interval_millis: i64,
+        mut last: NativeHistogram,
+        prev: &NativeHistogram,
+    ) -> Result<NativeHistogram, DataFusionError> {
+        if !IS_RATE {
+            last.sub(prev)?;
+            return Ok(last);
+        }
+
+        if !last.detect_reset(prev) {
+            last.sub(prev)?;
+        }
+        last.is_gauge = true;
+        last.div(interval_millis as f64 / 1000.0);
+        Ok(last)
+    }
+}
+
+/// Receives the warnings raised while aggregating native histograms.
+pub type HistogramWarningSink = Arc<dyn Fn(String) + Send + Sync>;
+
+/// The PromQL aggregation operators over native histograms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistogramAggrKind {
+    Sum,
+    Avg,
+}
+
+impl HistogramAggrKind {
+    /// Uses the same names as the DataFusion aggregate functions, so that the
+    /// output columns are named the same as the ones of float fields.
+    fn name(&self) -> &'static str {
+        match self {
+            HistogramAggrKind::Sum => "sum",
+            HistogramAggrKind::Avg => "avg",
+        }
+    }
+}
+
+/// `sum` or `avg` over native histograms, which outputs a native histogram.
+/// Histograms with different exponential schemas are promoted to the coarsest
+/// one, which is reported to the warning sink.
+pub struct HistogramAggr {
+    kind: HistogramAggrKind,
+    signature: Signature,
+    warning_sink: HistogramWarningSink,
+}
+
+impl fmt::Debug for HistogramAggr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HistogramAggr")
+            .field("kind", &self.kind)
+            .field("signature", &self.signature)
+            .finish()
+    }
+}
+
+impl HistogramAggr {
+    pub fn new(kind: HistogramAggrKind, warning_sink: HistogramWarningSink) -> Self {
+        Self {
+            kind,
+            signature: Signature::exact(vec![DataType::Binary], Volatility::Immutable),
+            warning_sink,
+        }
+    }
+
+    pub fn udaf(kind: HistogramAggrKind, warning_sink: HistogramWarningSink) -> Arc<AggregateUDF> {
+        Arc::new(AggregateUDF::new_from_impl(Self::new(kind, warning_sink)))
+    }
+
+    /// Whether the given aggregate function is a [HistogramAggr].
+    pub fn is_histogram_aggr(udaf: &AggregateUDF) -> bool {
+        udaf.inner().as_any().is::<Self>()
+    }
+}
+
+impl AggregateUDFImpl for HistogramAggr {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        self.kind.name()
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> DfResult<DataType> {
+        Ok(DataType::Binary)
+    }
+
+    fn accumulator(&self, _acc_args: AccumulatorArgs) -> DfResult<Box<dyn DfAccumulator>> {
+        Ok(Box::new(HistogramAccumulator::new(
+            self.kind,
+            self.warning_sink.clone(),
+        )))
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> DfResult<Vec<Field>> {
+        Ok(vec![
+            Field::new(format!("{}_histogram", args.name), DataType::Binary, true),
+            Field::new(format!("{}_count", args.name), DataType::UInt64, true),
+            Field::new(
+                format!("{}_mixed_schemas", args.name),
+                DataType::Boolean,
+                true,
+            ),
+        ])
+    }
+}
+
+pub struct HistogramAccumulator {
+    kind: HistogramAggrKind,
+    sum: Option<NativeHistogram>,
+    /// Number of histograms in the sum.
+    count: u64,
+    /// Whether histograms of different exponential schemas have been added.
+    mixed_schemas: bool,
+    warning_sink: HistogramWarningSink,
+}
+
+impl fmt::Debug for HistogramAccumulator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HistogramAccumulator")
+            .field("kind", &self.kind)
+            .field("sum", &self.sum)
+            .field("count", &self.count)
+            .field("mixed_schemas", &self.mixed_schemas)
+            .finish()
+    }
+}
+
+impl HistogramAccumulator {
+    pub fn new(kind: HistogramAggrKind, warning_sink: HistogramWarningSink) -> Self {
+        Self {
+            kind,
+            sum: None,
+            count: 0,
+            mixed_schemas: false,
+            warning_sink,
+        }
+    }
+
+    fn add(&mut self, bytes: &[u8], count: u64) -> DfResult<()> {
+        let histogram = NativeHistogram::decode(bytes)?;
+        match &mut self.sum {
+            Some(sum) => {
+                if sum.schema != histogram.schema {
+                    self.mixed_schemas = true;
+                }
+                sum.add(&histogram)?;
+            }
+            None => self.sum = Some(histogram),
+        }
+        self.count += count;
+        Ok(())
+    }
+
+    fn binary_input(array: &ArrayRef) -> DfResult<&BinaryArray> {
+        array.as_binary_opt::<i32>().ok_or_else(|| {
+            DataFusionError::Execution(format!(
+                "expect binary native histograms as input, found {}",
+                array.data_type()
+            ))
+        })
+    }
+}
+
+impl DfAccumulator for HistogramAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> DfResult<()> {
+        for bytes in Self::binary_input(&values[0])?.iter().flatten() {
+            self.add(bytes, 1)?;
+        }
+
+        Ok(())
+    }
+
+    fn evaluate(&mut self) -> DfResult<ScalarValue> {
+        if self.mixed_schemas {
+            (self.warning_sink)(format!(
+                "PromQL warning: native histograms with different schemas were promoted to the coarsest schema in {}()",
+                self.kind.name()
+            ));
+        }
+
+        let result = self.sum.clone().map(|mut result| {
+            if self.kind == HistogramAggrKind::Avg {
+                result.div(self.count as f64);
+            }
+            result.encode()
+        });
+        Ok(ScalarValue::Binary(result))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.sum.as_ref().map_or(0, |sum| {
+                (sum.positive_buckets.len() + sum.negative_buckets.len() + sum.custom_values.len())
+                    * std::mem::size_of::<f64>()
+            })
+    }
+
+    fn state(&mut self) -> DfResult<Vec<ScalarValue>> {
+        Ok(vec![
+            ScalarValue::Binary(self.sum.as_ref().map(|sum| sum.encode())),
+            ScalarValue::UInt64(Some(self.count)),
+            ScalarValue::Boolean(Some(self.mixed_schemas)),
+        ])
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> DfResult<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+
+        let histograms = Self::binary_input(&states[0])?;
+        let counts = states[1].as_primitive::<UInt64Type>();
+        let mixed_schemas = states[2].as_boolean();
+        for i in 0..histograms.len() {
+            if mixed_schemas.is_valid(i) && mixed_schemas.value(i) {
+                self.mixed_schemas = true;
+            }
+            if histograms.is_valid(i) {
+                self.add(histograms.value(i), counts.value(i))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Averages the non-null histograms, or returns `None` if there is none.
+fn avg_histograms(histograms: &BinaryArray) -> Result<Option<NativeHistogram>, DataFusionError> {
+    let mut result: Option<NativeHistogram> = None;
+    let mut count = 0;
+    for bytes in histograms.iter().flatten() {
+        let histogram = NativeHistogram::decode(bytes)?;
+        match &mut result {
+            Some(sum) => sum.add(&histogram)?,
+            None => result = Some(histogram),
+        }
+        count += 1;
+    }
+    if let Some(result) = &mut result {
+        result.div(count as f64);
+    }
+    Ok(result)
+}
+
+/// Decodes each histogram in the binary input and maps it to a float. Nulls are kept.
+fn map_histograms(
+    input: &[ColumnarValue],
+    f: impl Fn(&NativeHistogram) -> f64,
+) -> Result<ColumnarValue, DataFusionError> {
+    assert_eq!(input.len(), 1);
+
+    let array = extract_array(&input[0])?;
+    let histograms = array.as_binary_opt::<i32>().ok_or_else(|| {
+        DataFusionError::Execution(format!(
+            "expect binary native histograms as input, found {}",
+            array.data_type()
+        ))
+    })?;
+    let result = histograms
+        .iter()
+        .map(|bytes| {
+            bytes
+                .map(|bytes| NativeHistogram::decode(bytes).map(|h| f(&h)))
+                .transpose()
+        })
+        .collect::<Result<Float64Array, _>>()?;
+
+    Ok(ColumnarValue::Array(Arc::new(result)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use datafusion_expr::ScalarFunctionArgs;
+    use datatypes::arrow::array::{BooleanArray, Int32Array, TimestampMillisecondArray};
+
+    use super::*;
+    use crate::native_histogram::{encode_spans, encode_values, BucketSpan};
+
+    fn test_histogram() -> NativeHistogram {
+        NativeHistogram {
+            schema: 0,
+            zero_threshold: 0.001,
+            zero_count: 2.0,
+            count: 12.0,
+            sum: 100.0,
+            positive_spans: vec![BucketSpan {
+                offset: 0,
+                length: 5,
+            }],
+            positive_buckets: vec![2.0, 3.0, 0.0, 1.0, 4.0],
+            ..Default::default()
+        }
+    }
+
+    fn histogram_input() -> Vec<ColumnarValue> {
+        let encoded = test_histogram().encode();
+        let array = BinaryArray::from(vec![Some(encoded.as_slice()), None]);
+        vec![ColumnarValue::Array(Arc::new(array))]
+    }
+
+    fn invoke(udf: ScalarUDF, input: Vec<ColumnarValue>) -> Vec<Option<f64>> {
+        let args = ScalarFunctionArgs {
+            args: input,
+            number_rows: 2,
+            return_type: &DataType::Float64,
+        };
+        let result = udf.invoke_with_args(args).unwrap();
+        let result = extract_array(&result).unwrap();
+        result.as_primitive::<Float64Type>().iter().collect()
+    }
+
+    #[test]
+    fn test_histogram_functions() {
+        assert_eq!(
+            vec![Some(16.0), None],
+            invoke(HistogramQuantile::scalar_udf(1.0), histogram_input())
+        );
+        assert_eq!(
+            vec![Some(12.0), None],
+            invoke(HistogramCount::scalar_udf(), histogram_input())
+        );
+        assert_eq!(
+            vec![Some(100.0), None],
+            invoke(HistogramSum::scalar_udf(), histogram_input())
+        );
+
+        let mut input = histogram_input();
+        input.insert(0, ColumnarValue::Scalar(ScalarValue::Float64(Some(1.0))));
+        assert_eq!(
+            vec![Some(16.0), None],
+            invoke(HistogramQuantile::sql_udf(), input)
+        );
+    }
+
+    #[test]
+    fn test_histogram_pack() {
+        let histogram = test_histogram();
+        let positive_spans = encode_spans(&histogram.positive_spans);
+        let positive_buckets = encode_values(&histogram.positive_buckets);
+        let float = |value| Arc::new(Float64Array::from(vec![Some(value), None])) as ArrayRef;
+        let binary =
+            |value: &[u8]| Arc::new(BinaryArray::from(vec![Some(value), None])) as ArrayRef;
+        // The second row has no histogram.
+        let input = vec![
+            Arc::new(Int32Array::from(vec![Some(0), None])) as ArrayRef,
+            Arc::new(BooleanArray::from(vec![Some(false), None])),
+            float(0.001),
+            float(2.0),
+            float(12.0),
+            float(100.0),
+            binary(&positive_spans),
+            binary(&positive_buckets),
+            binary(&[]),
+            binary(&[]),
+            binary(&[]),
+        ];
+        let args = ScalarFunctionArgs {
+            args: input.into_iter().map(ColumnarValue::Array).collect(),
+            number_rows: 2,
+            return_type: &DataType::Binary,
+        };
+        let result = HistogramPack::scalar_udf().invoke_with_args(args).unwrap();
+        let result = extract_array(&result).unwrap();
+        let packed = result.as_binary::<i32>();
+        assert_eq!(histogram, NativeHistogram::decode(packed.value(0)).unwrap());
+        assert!(packed.is_null(1));
+
+        assert_eq!(
+            vec![Some(16.0), None],
+            invoke(
+                HistogramQuantile::sql_udf(),
+                vec![
+                    ColumnarValue::Scalar(ScalarValue::Float64(Some(1.0))),
+                    ColumnarValue::Array(result),
+                ]
+            )
+        );
+
+        // Truncated buckets are rejected.
+        let mut input = (0..NATIVE_HISTOGRAM_LAYOUT.len())
+            .map(|i| match i {
+                0 => Arc::new(Int32Array::from(vec![0])) as ArrayRef,
+                1 => Arc::new(BooleanArray::from(vec![false])),
+                2..=5 => Arc::new(Float64Array::from(vec![0.0])),
+                _ => Arc::new(BinaryArray::from(vec![b"".as_slice()])),
+            })
+            .collect::<Vec<_>>();
+        input[7] = Arc::new(BinaryArray::from(vec![&positive_buckets[1..]]));
+        let args = ScalarFunctionArgs {
+            args: input.into_iter().map(ColumnarValue::Array).collect(),
+            number_rows: 1,
+            return_type: &DataType::Binary,
+        };
+        assert!(HistogramPack::scalar_udf().invoke_with_args(args).is_err());
+    }
+
+    #[test]
+    fn test_invalid_histogram() {
+        let input = vec![ColumnarValue::Array(Arc::new(BinaryArray::from(vec![
+            b"invalid".as_slice(),
+        ])))];
+        let args = ScalarFunctionArgs {
+            args: input,
+            number_rows: 1,
+            return_type: &DataType::Float64,
+        };
+        assert!(HistogramCount::scalar_udf().invoke_with_args(args).is_err());
+    }
+
+    #[test]
+    fn test_histogram_avg_over_time() {
+        let first = NativeHistogram {
+            schema: 0,
+            zero_threshold: 0.001,
+            zero_count: 2.0,
+            count: 8.0,
+            sum: 40.0,
+            positive_spans: vec![BucketSpan {
+                offset: 0,
+                length: 3,
+            }],
+            positive_buckets: vec![2.0, 2.0, 2.0],
+            ..Default::default()
+        };
+        let second = NativeHistogram {
+            schema: 0,
+            zero_threshold: 0.001,
+            zero_count: 0.0,
+            count: 4.0,
+            sum: 20.0,
+            positive_spans: vec![BucketSpan {
+                offset: 1,
+                length: 3,
+            }],
+            positive_buckets: vec![2.0, 0.0, 2.0],
+            ..Default::default()
+        };
+        let ts_array = Arc::new(TimestampMillisecondArray::from(vec![1000, 2000, 3000]));
+        let values_array = Arc::new(BinaryArray::from(vec![
+            Some(first.encode().as_slice()),
+            None,
+            Some(second.encode().as_slice()),
+        ]));
+        // The first range averages both histograms, the second range only has a null.
+        let ranges = [(0, 3), (1, 1), (0, 0)];
+        let ts_range = RangeArray::from_ranges(ts_array, ranges).unwrap();
+        let values_range = RangeArray::from_ranges(values_array, ranges).unwrap();
+
+        let args = ScalarFunctionArgs {
+            args: vec![
+                ColumnarValue::Array(Arc::new(ts_range.into_dict())),
+                ColumnarValue::Array(Arc::new(values_range.into_dict())),
+            ],
+            number_rows: 3,
+            return_type: &DataType::Binary,
+        };
+        let result = HistogramAvgOverTime::scalar_udf()
+            .invoke_with_args(args)
+            .unwrap();
+        let result = extract_array(&result).unwrap();
+        let result = result.as_binary::<i32>();
+
+        let average = NativeHistogram::decode(result.value(0)).unwrap();
+        let expected = NativeHistogram {
+            schema: 0,
+            is_gauge: true,
+            zero_threshold: 0.001,
+            zero_count: 1.0,
+            count: 6.0,
+            sum: 30.0,
+            positive_spans: vec![BucketSpan {
+                offset: 0,
+                length: 4,
+            }],
+            positive_buckets: vec![1.0, 2.0, 1.0, 1.0],
+            ..Default::default()
+        };
+        assert_eq!(expected, average);
+        assert!(result.is_null(1));
+        assert!(result.is_null(2));
+    }
+
+    #[test]
+    fn test_histogram_idelta_and_irate() {
+        let histogram = |count: f64, buckets: Vec<f64>| NativeHistogram {
+            schema: 0,
+            zero_threshold: 0.001,
+            zero_count: 0.0,
+            count,
+            sum: count * 2.0,
+            positive_spans: vec![BucketSpan {
+                offset: 0,
+                length: buckets.len() as u32,
+            }],
+            positive_buckets: buckets,
+            ..Default::default()
+        };
+        let first = histogram(4.0, vec![2.0, 2.0]);
+        let second = histogram(10.0, vec![4.0, 6.0]);
+        // A counter reset.
+        let third = histogram(3.0, vec![1.0, 2.0]);
+        let ts_array = Arc::new(TimestampMillisecondArray::from(vec![
+            1000, 3000, 5000, 7000,
+        ]));
+        let values_array = Arc::new(BinaryArray::from(vec![
+            Some(first.encode().as_slice()),
+            Some(second.encode().as_slice()),
+            None,
+            Some(third.encode().as_slice()),
+        ]));
+        // The last range has a null between the last two histograms, the third
+        // range only has one histogram.
+        let ranges = [(0, 2), (1, 3), (0, 1), (0, 0)];
+
+        let invoke = |udf: ScalarUDF| {
+            let ts_range = RangeArray::from_ranges(ts_array.clone(), ranges).unwrap();
+            let values_range = RangeArray::from_ranges(values_array.clone(), ranges).unwrap();
+            let args = ScalarFunctionArgs {
+                args: vec![
+                    ColumnarValue::Array(Arc::new(ts_range.into_dict())),
+                    ColumnarValue::Array(Arc::new(values_range.into_dict())),
+                ],
+                number_rows: 4,
+                return_type: &DataType::Binary,
+            };
+            let result = udf.invoke_with_args(args).unwrap();
+            let result = extract_array(&result).unwrap();
+            let result = result.as_binary::<i32>();
+            (0..result.len())
+                .map(|i| {
+                    (!result.is_null(i)).then(|| NativeHistogram::decode(result.value(i)).unwrap())
+                })
+                .collect::<Vec<_>>()
+        };
+        let gauge = |h: NativeHistogram| NativeHistogram {
+            is_gauge: true,
+            ..h
+        };
+
+        let idelta = invoke(HistogramIDelta::<false>::scalar_udf());
+        assert_eq!(
+            vec![
+                Some(gauge(histogram(6.0, vec![2.0, 4.0]))),
+                Some(gauge(histogram(-7.0, vec![-3.0, -4.0]))),
+                None,
+                None,
+            ],
+            idelta
+        );
+
+        // The histograms are 2 seconds apart, or 4 seconds apart across the null.
+        let irate = invoke(HistogramIDelta::<true>::scalar_udf());
+        assert_eq!(
+            vec![
+                Some(gauge(histogram(3.0, vec![1.0, 2.0]))),
+                Some(gauge(histogram(0.75, vec![0.25, 0.5]))),
+                None,
+                None,
+            ],
+            irate
+        );
+    }
+
+    #[test]
+    fn test_histogram_aggr_with_mixed_schemas() {
+        let fine = NativeHistogram {
+            schema: 1,
+            zero_threshold: 0.001,
+            count: 2.0,
+            sum: 3.0,
+            positive_spans: vec![BucketSpan {
+                offset: 1,
+                length: 2,
+            }],
+            positive_buckets: vec![1.0, 1.0],
+            ..Default::default()
+        };
+        let coarse = NativeHistogram {
+            schema: 0,
+            zero_threshold: 0.001,
+            count: 2.0,
+            sum: 3.5,
+            positive_spans: vec![BucketSpan {
+                offset: 1,
+                length: 1,
+            }],
+            positive_buckets: vec![2.0],
+            ..Default::default()
+        };
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let sink: HistogramWarningSink = {
+            let warnings = warnings.clone();
+            Arc::new(move |warning| warnings.lock().unwrap().push(warning))
+        };
+
+        // Each partial accumulator only sees one schema, the mismatch is found on merge.
+        let mut states = vec![];
+        for histogram in [&fine, &coarse] {
+            let mut partial = HistogramAccumulator::new(HistogramAggrKind::Sum, sink.clone());
+            let input = BinaryArray::from(vec![Some(histogram.encode().as_slice()), None]);
+            partial.update_batch(&[Arc::new(input)]).unwrap();
+            states.push(partial.state().unwrap());
+        }
+        let state_arrays = (0..2)
+            .map(|i| ScalarValue::iter_to_array(states.iter().map(|state| state[i].clone())))
+            .collect::<DfResult<Vec<_>>>()
+            .unwrap();
+        let mut accumulator = HistogramAccumulator::new(HistogramAggrKind::Sum, sink);
+        accumulator.merge_batch(&state_arrays).unwrap();
+        let ScalarValue::Binary(Some(result)) = accumulator.evaluate().unwrap() else {
+            panic!("expect a histogram");
+        };
+
+        let expected = NativeHistogram {
+            schema: 0,
+            is_gauge: true,
+            zero_threshold: 0.001,
+            count: 4.0,
+            sum: 6.5,
+            positive_spans: vec![BucketSpan {
+                offset: 1,
+                length: 1,
+            }],
+            positive_buckets: vec![4.0],
+            ..Default::default()
+        };
+        let result = NativeHistogram::decode(&result).unwrap();
+        assert_eq!(expected, result);
+        // The median lies in the middle of (1, 2] on the logarithmic scale.
+        assert!((result.quantile(0.5) - 2f64.sqrt()).abs() < 1e-9);
+        assert_eq!(1, warnings.lock().unwrap().len());
+
+        // Histograms of the same schema don't raise warnings.
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let sink: HistogramWarningSink = {
+            let warnings = warnings.clone();
+            Arc::new(move |warning| warnings.lock().unwrap().push(warning))
+        };
+        let mut accumulator = HistogramAccumulator::new(HistogramAggrKind::Sum, sink);
+        let input = BinaryArray::from(vec![
+            Some(coarse.encode().as_slice()),
+            Some(coarse.encode().as_slice()),
+        ]);
+        accumulator.update_batch(&[Arc::new(input)]).unwrap();
+        accumulator.evaluate().unwrap();
+        assert!(warnings.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_histogram_avg_aggr() {
+        let histogram = |count: f64| NativeHistogram {
+            schema: 0,
+            count,
+            sum: count,
+            positive_spans: vec![BucketSpan {
+                offset: 0,
+                length: 1,
+            }],
+            positive_buckets: vec![count],
+            ..Default::default()
+        };
+        let mut accumulator = HistogramAccumulator::new(HistogramAggrKind::Avg, Arc::new(|_| {}));
+        let input = BinaryArray::from(vec![
+            Some(histogram(2.0).encode().as_slice()),
+            None,
+            Some(histogram(6.0).encode().as_slice()),
+        ]);
+        accumulator.update_batch(&[Arc::new(input)]).unwrap();
+        let ScalarValue::Binary(Some(result)) = accumulator.evaluate().unwrap() else {
+            panic!("expect a histogram");
+        };
+
+        let expected = NativeHistogram {
+            is_gauge: true,
+            ..histogram(4.0)
+        };
+        assert_eq!(expected, NativeHistogram::decode(&result).unwrap());
+    }
+}
@@ -256,4 +256,24 @@ mod test {
             vec![Some(82765.9090909091)],
         );
     }
+
+    #[test]
+    fn far_future_predict_linear() {
+        // 2^58 ms, where adjacent f64 values are 64 ms apart
+        let start = 1i64 << 58;
+        let ts_array = Arc::new(TimestampMillisecondArray::from_iter(
+            [0, 10_000, 20_000, 30_000].map(|offset| Some(start + offset)),
+        ));
+        let values_array = Arc::new(Float64Array::from_iter([0.0, 10.0, 20.0, 30.0]));
+        let ranges = [(0, 4)];
+        let ts_array = RangeArray::from_ranges(ts_array, ranges).unwrap();
+        let value_array = RangeArray::from_ranges(values_array, ranges).unwrap();
+        simple_range_udf_runner(
+            PredictLinear::scalar_udf(10),
+            ts_array,
+            value_array,
+            // slope is 1 per second, 30 at the evaluation time
+            vec![Some(40.0)],
+        );
+    }
 }
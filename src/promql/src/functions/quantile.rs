@@ -125,8 +125,14 @@ impl QuantileOverTime {
 }
 
 /// Refer to <https://github.com/prometheus/prometheus/blob/6e2905a4d4ff9b47b1f6d201333f5bd53633f921/promql/quantile.go#L357-L386>
+///
+/// Returns `None` for empty input so the output is absent, like Prometheus does
+/// for an empty window or group.
 pub(crate) fn quantile_impl(values: &[f64], quantile: f64) -> Option<f64> {
-    if quantile.is_nan() || values.is_empty() {
+    if values.is_empty() {
+        return None;
+    }
+    if quantile.is_nan() {
         return Some(f64::NAN);
     }
     if quantile < 0.0 {
@@ -152,13 +158,61 @@ pub(crate) fn quantile_impl(values: &[f64], quantile: f64) -> Option<f64> {
 
 #[cfg(test)]
 mod tests {
+    use datafusion::arrow::array::TimestampMillisecondArray;
+
     use super::*;
+    use crate::functions::test_util::{range_udf_results, simple_range_udf_runner};
+
+    fn build_test_range_arrays() -> (RangeArray, RangeArray) {
+        let ts_array = Arc::new(TimestampMillisecondArray::from_iter(
+            [1000i64, 3000, 5000, 7000, 9000].into_iter().map(Some),
+        ));
+        let values_array = Arc::new(Float64Array::from_iter([1.0, 2.0, 3.0, 4.0, 5.0]));
+        let ranges = [
+            (0, 3),
+            (2, 0), // empty range
+            (4, 1), // only 1 element
+        ];
+
+        (
+            RangeArray::from_ranges(ts_array, ranges).unwrap(),
+            RangeArray::from_ranges(values_array, ranges).unwrap(),
+        )
+    }
+
+    #[test]
+    fn calculate_quantile_over_time() {
+        let (ts_array, value_array) = build_test_range_arrays();
+        simple_range_udf_runner(
+            QuantileOverTime::scalar_udf(0.5),
+            ts_array,
+            value_array,
+            vec![Some(2.0), None, Some(5.0)],
+        );
+    }
+
+    #[test]
+    fn calculate_quantile_over_time_nan_quantile() {
+        let (ts_array, value_array) = build_test_range_arrays();
+        let results = range_udf_results(
+            &QuantileOverTime::scalar_udf(f64::NAN),
+            ts_array,
+            value_array,
+            vec![],
+        );
+        assert_eq!(3, results.len());
+        assert!(results[0].unwrap().is_nan());
+        // The empty window is still absent.
+        assert!(results[1].is_none());
+        assert!(results[2].unwrap().is_nan());
+    }
 
     #[test]
     fn test_quantile_impl_empty() {
         let values = &[];
         let q = 0.5;
-        assert!(quantile_impl(values, q).unwrap().is_nan());
+        assert!(quantile_impl(values, q).is_none());
+        assert!(quantile_impl(values, f64::NAN).is_none());
     }
 
     #[test]
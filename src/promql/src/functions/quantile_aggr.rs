@@ -153,10 +153,21 @@ mod tests {
 
         let result = accumulator.evaluate().unwrap();
 
-        match result {
-            ScalarValue::Float64(_) => (),
-            _ => panic!("Expected Float64 scalar value"),
-        }
+        assert_eq!(result, ScalarValue::Float64(None));
+    }
+
+    #[test]
+    fn test_quantile_accumulator_nan_quantile() {
+        let mut accumulator = QuantileAccumulator::new(f64::NAN);
+        let input = create_f64_array(vec![Some(1.0), Some(2.0), Some(3.0)]);
+
+        accumulator.update_batch(&[input]).unwrap();
+        let result = accumulator.evaluate().unwrap();
+        assert!(matches!(result, ScalarValue::Float64(Some(v)) if v.is_nan()));
+
+        // An empty group has no output even if φ is NaN.
+        let mut accumulator = QuantileAccumulator::new(f64::NAN);
+        assert_eq!(accumulator.evaluate().unwrap(), ScalarValue::Float64(None));
     }
 
     #[test]
@@ -25,26 +25,24 @@ use datafusion::physical_plan::ColumnarValue;
 use datatypes::arrow::array::Array;
 use datatypes::arrow::datatypes::DataType;
 
-use crate::functions::extract_array;
+use crate::functions::{extract_array, window_values};
 use crate::range_array::RangeArray;
 
 /// used to count the number of times the time series starts over.
 #[range_fn(name = Resets, ret = Float64Array, display_name = prom_resets)]
 pub fn resets(_: &TimestampMillisecondArray, values: &Float64Array) -> Option<f64> {
-    if values.is_empty() {
-        None
-    } else {
-        let (first, rest) = values.values().split_first().unwrap();
-        let mut num_resets = 0;
-        let mut prev_element = first;
-        for cur_element in rest {
-            if cur_element < prev_element {
-                num_resets += 1;
-            }
-            prev_element = cur_element;
+    // An empty window has no output, while a window with a single sample has 0 resets.
+    let values = window_values(values);
+    let (first, rest) = values.split_first()?;
+    let mut num_resets = 0;
+    let mut prev_element = first;
+    for cur_element in rest {
+        if cur_element < prev_element {
+            num_resets += 1;
         }
-        Some(num_resets as f64)
+        prev_element = cur_element;
     }
+    Some(num_resets as f64)
 }
 
 #[cfg(test)]
@@ -30,31 +30,42 @@ pub fn simple_range_udf_runner(
     input_value: RangeArray,
     expected: Vec<Option<f64>>,
 ) {
+    let eval_result = range_udf_results(&range_fn, input_ts, input_value, vec![]);
+    assert_eq!(eval_result.len(), expected.len());
+    assert!(eval_result
+        .iter()
+        .zip(expected.iter())
+        .all(|(x, y)| match (*x, *y) {
+            (Some(x), Some(y)) => (x - y).abs() < 0.0001,
+            (None, None) => true,
+            _ => false,
+        }));
+}
+
+/// Evaluates the range UDF with the ts range, the value range and the `extra_args`.
+pub fn range_udf_results(
+    range_fn: &ScalarUDF,
+    input_ts: RangeArray,
+    input_value: RangeArray,
+    extra_args: Vec<ColumnarValue>,
+) -> Vec<Option<f64>> {
     let num_rows = input_ts.len();
-    let input = vec![
+    let mut input = vec![
         ColumnarValue::Array(Arc::new(input_ts.into_dict())),
         ColumnarValue::Array(Arc::new(input_value.into_dict())),
     ];
+    input.extend(extra_args);
     let args = ScalarFunctionArgs {
         args: input,
         number_rows: num_rows,
         return_type: &DataType::Float64,
     };
     let value = range_fn.invoke_with_args(args).unwrap();
-    let eval_result: Vec<Option<f64>> = extract_array(&value)
+    extract_array(&value)
         .unwrap()
         .as_any()
         .downcast_ref::<Float64Array>()
         .unwrap()
         .iter()
-        .collect();
-    assert_eq!(eval_result.len(), expected.len());
-    assert!(eval_result
-        .iter()
-        .zip(expected.iter())
-        .all(|(x, y)| match (*x, *y) {
-            (Some(x), Some(y)) => (x - y).abs() < 0.0001,
-            (None, None) => true,
-            _ => false,
-        }));
+        .collect()
 }
@@ -19,4 +19,5 @@ pub mod error;
 pub mod extension_plan;
 pub mod functions;
 mod metrics;
+pub mod native_histogram;
 pub mod range_array;
@@ -0,0 +1,1129 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage representation of Prometheus native (sparse) histograms.
+//!
+//! A native histogram metric is stored in its own table, with one row per
+//! histogram sample. Each part of the histogram has its own field column, see
+//! [NATIVE_HISTOGRAM_LAYOUT]:
+//!
+//! | column                                 | type    |
+//! | -------------------------------------- | ------- |
+//! | `greptime_histogram_schema`            | int32   |
+//! | `greptime_histogram_gauge`             | boolean |
+//! | `greptime_histogram_zero_threshold`    | float64 |
+//! | `greptime_histogram_zero_count`        | float64 |
+//! | `greptime_histogram_count`             | float64 |
+//! | `greptime_histogram_sum`               | float64 |
+//! | `greptime_histogram_positive_spans`    | binary  |
+//! | `greptime_histogram_positive_buckets`  | binary  |
+//! | `greptime_histogram_negative_spans`    | binary  |
+//! | `greptime_histogram_negative_buckets`  | binary  |
+//! | `greptime_histogram_custom_values`     | binary  |
+//!
+//! Row inserts have no list type, so each list is stored as a little-endian
+//! array in a binary column, see [encode_spans] and [encode_values]: spans are
+//! `(offset: i32, length: u32)` pairs and buckets and custom values are `f64`s.
+//!
+//! Query plans pack the columns of a row into a single binary value named
+//! [NATIVE_HISTOGRAM_COLUMN], which the histogram functions take. The packed
+//! value is encoded as a little-endian byte sequence:
+//!
+//! ```text
+//! version: u8 | flags: u8 | schema: i32 | zero_threshold: f64 | zero_count: f64
+//! | count: f64 | sum: f64
+//! | positive spans: u32 len, (offset: i32, length: u32) * len
+//! | positive buckets: u32 len, f64 * len
+//! | negative spans: u32 len, (offset: i32, length: u32) * len
+//! | negative buckets: u32 len, f64 * len
+//! | custom values: u32 len, f64 * len
+//! ```
+//!
+//! Bucket deltas of integer histograms are resolved into absolute counts on
+//! ingestion, so integer and float histograms share the same layout. Version 1
+//! of the encoding has no custom values. Tables written before the histograms had
+//! their own columns store the packed value in a [NATIVE_HISTOGRAM_COLUMN] column.
+//!
+//! SQL queries read the count and sum columns directly, and estimate quantiles
+//! with `native_histogram_quantile(phi, native_histogram(<layout columns>))`.
+
+use std::collections::BTreeMap;
+
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+
+/// Name of the packed native histograms in query plans.
+pub const NATIVE_HISTOGRAM_COLUMN: &str = "greptime_histogram";
+
+pub const HISTOGRAM_SCHEMA_COLUMN: &str = "greptime_histogram_schema";
+pub const HISTOGRAM_GAUGE_COLUMN: &str = "greptime_histogram_gauge";
+pub const HISTOGRAM_ZERO_THRESHOLD_COLUMN: &str = "greptime_histogram_zero_threshold";
+pub const HISTOGRAM_ZERO_COUNT_COLUMN: &str = "greptime_histogram_zero_count";
+pub const HISTOGRAM_COUNT_COLUMN: &str = "greptime_histogram_count";
+pub const HISTOGRAM_SUM_COLUMN: &str = "greptime_histogram_sum";
+pub const HISTOGRAM_POSITIVE_SPANS_COLUMN: &str = "greptime_histogram_positive_spans";
+pub const HISTOGRAM_POSITIVE_BUCKETS_COLUMN: &str = "greptime_histogram_positive_buckets";
+pub const HISTOGRAM_NEGATIVE_SPANS_COLUMN: &str = "greptime_histogram_negative_spans";
+pub const HISTOGRAM_NEGATIVE_BUCKETS_COLUMN: &str = "greptime_histogram_negative_buckets";
+pub const HISTOGRAM_CUSTOM_VALUES_COLUMN: &str = "greptime_histogram_custom_values";
+
+/// The field columns of a native histogram table, in the order of the arguments of
+/// the function packing them.
+pub const NATIVE_HISTOGRAM_LAYOUT: [&str; 11] = [
+    HISTOGRAM_SCHEMA_COLUMN,
+    HISTOGRAM_GAUGE_COLUMN,
+    HISTOGRAM_ZERO_THRESHOLD_COLUMN,
+    HISTOGRAM_ZERO_COUNT_COLUMN,
+    HISTOGRAM_COUNT_COLUMN,
+    HISTOGRAM_SUM_COLUMN,
+    HISTOGRAM_POSITIVE_SPANS_COLUMN,
+    HISTOGRAM_POSITIVE_BUCKETS_COLUMN,
+    HISTOGRAM_NEGATIVE_SPANS_COLUMN,
+    HISTOGRAM_NEGATIVE_BUCKETS_COLUMN,
+    HISTOGRAM_CUSTOM_VALUES_COLUMN,
+];
+
+/// Smallest schema of exponential buckets.
+pub const MIN_SCHEMA: i32 = -4;
+/// Largest schema of exponential buckets.
+pub const MAX_SCHEMA: i32 = 8;
+/// Schema of native histograms with custom bucket boundaries (NHCB).
+pub const CUSTOM_BUCKETS_SCHEMA: i32 = -53;
+
+const ENCODING_VERSION: u8 = 2;
+/// The encoding version without custom values.
+const ENCODING_VERSION_V1: u8 = 1;
+const GAUGE_FLAG: u8 = 0b1;
+
+/// A span of consecutive buckets. `offset` is the gap to the previous span
+/// (or the starting bucket index for the first span).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BucketSpan {
+    pub offset: i32,
+    pub length: u32,
+}
+
+/// A bucket with its boundaries.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Bucket {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: f64,
+}
+
+/// A native histogram sample.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NativeHistogram {
+    /// Resolution of the exponential buckets. The growth factor between two
+    /// adjacent buckets is `2^(2^-schema)`. [CUSTOM_BUCKETS_SCHEMA] means the
+    /// buckets are bounded by `custom_values` instead.
+    pub schema: i32,
+    /// Whether this histogram is a gauge histogram. Counter histograms only go
+    /// up (except on counter resets).
+    pub is_gauge: bool,
+    /// Observations whose absolute value is no larger than this threshold are
+    /// counted in the zero bucket.
+    pub zero_threshold: f64,
+    pub zero_count: f64,
+    pub count: f64,
+    pub sum: f64,
+    pub positive_spans: Vec<BucketSpan>,
+    /// Absolute counts of the positive buckets.
+    pub positive_buckets: Vec<f64>,
+    pub negative_spans: Vec<BucketSpan>,
+    /// Absolute counts of the negative buckets.
+    pub negative_buckets: Vec<f64>,
+    /// Ascending upper bounds of the custom buckets, without the `+Inf` bucket.
+    /// The positive bucket at index `i` covers `(custom_values[i - 1], custom_values[i]]`,
+    /// the first one starts from `-Inf` and the one past the last value ends at `+Inf`.
+    pub custom_values: Vec<f64>,
+}
+
+impl NativeHistogram {
+    /// Whether the buckets are bounded by custom values (NHCB).
+    pub fn uses_custom_buckets(&self) -> bool {
+        self.schema == CUSTOM_BUCKETS_SCHEMA
+    }
+
+    /// Checks that the schema is supported and the spans match the buckets.
+    pub fn validate(&self) -> DataFusionResult<()> {
+        if self.uses_custom_buckets() {
+            return self.validate_custom_buckets();
+        }
+        if !self.custom_values.is_empty() {
+            return Err(DataFusionError::Execution(format!(
+                "native histogram with schema {} has custom values",
+                self.schema
+            )));
+        }
+        if !(MIN_SCHEMA..=MAX_SCHEMA).contains(&self.schema) {
+            return Err(DataFusionError::Execution(format!(
+                "native histogram schema {} is out of range [{MIN_SCHEMA}, {MAX_SCHEMA}]",
+                self.schema
+            )));
+        }
+        Self::validate_spans(&self.positive_spans, &self.positive_buckets, "positive")?;
+        Self::validate_spans(&self.negative_spans, &self.negative_buckets, "negative")
+    }
+
+    /// Custom buckets only have positive buckets, whose indexes point into the
+    /// strictly increasing custom values (or the `+Inf` bucket past them).
+    fn validate_custom_buckets(&self) -> DataFusionResult<()> {
+        if !self.negative_spans.is_empty() || !self.negative_buckets.is_empty() {
+            return Err(DataFusionError::Execution(
+                "native histogram with custom buckets has negative buckets".to_string(),
+            ));
+        }
+        if self
+            .custom_values
+            .windows(2)
+            .any(|values| values[0] >= values[1])
+        {
+            return Err(DataFusionError::Execution(
+                "custom values of native histogram are not strictly increasing".to_string(),
+            ));
+        }
+        Self::validate_spans(&self.positive_spans, &self.positive_buckets, "positive")?;
+        let max_index = self.custom_values.len() as i32;
+        if bucket_indexes(&self.positive_spans).any(|index| !(0..=max_index).contains(&index)) {
+            return Err(DataFusionError::Execution(format!(
+                "native histogram custom bucket index is out of range [0, {max_index}]"
+            )));
+        }
+        Ok(())
+    }
+
+    fn validate_spans(spans: &[BucketSpan], buckets: &[f64], side: &str) -> DataFusionResult<()> {
+        let spans_len = spans.iter().map(|s| s.length as usize).sum::<usize>();
+        if spans_len != buckets.len() {
+            return Err(DataFusionError::Execution(format!(
+                "native histogram {side} spans cover {spans_len} buckets but {} buckets are given",
+                buckets.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Encodes this histogram into the storage format.
+    pub fn encode(&self) -> Vec<u8> {
+        let spans_len = self.positive_spans.len() + self.negative_spans.len();
+        let buckets_len =
+            self.positive_buckets.len() + self.negative_buckets.len() + self.custom_values.len();
+        let mut buf = Vec::with_capacity(2 + 4 + 8 * 4 + 4 * 5 + spans_len * 8 + buckets_len * 8);
+
+        buf.push(ENCODING_VERSION);
+        buf.push(if self.is_gauge { GAUGE_FLAG } else { 0 });
+        buf.extend_from_slice(&self.schema.to_le_bytes());
+        for value in [self.zero_threshold, self.zero_count, self.count, self.sum] {
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+        for (spans, buckets) in [
+            (&self.positive_spans, &self.positive_buckets),
+            (&self.negative_spans, &self.negative_buckets),
+        ] {
+            buf.extend_from_slice(&(spans.len() as u32).to_le_bytes());
+            for span in spans {
+                buf.extend_from_slice(&span.offset.to_le_bytes());
+                buf.extend_from_slice(&span.length.to_le_bytes());
+            }
+            write_f64s(&mut buf, buckets);
+        }
+        write_f64s(&mut buf, &self.custom_values);
+
+        buf
+    }
+
+    /// Decodes a histogram from the storage format.
+    pub fn decode(bytes: &[u8]) -> DataFusionResult<Self> {
+        let mut reader = Reader { bytes };
+        let version = reader.read::<1>()?[0];
+        if version != ENCODING_VERSION && version != ENCODING_VERSION_V1 {
+            return Err(DataFusionError::Execution(format!(
+                "unsupported native histogram encoding version {version}"
+            )));
+        }
+        let flags = reader.read::<1>()?[0];
+        let schema = i32::from_le_bytes(reader.read()?);
+        let zero_threshold = reader.read_f64()?;
+        let zero_count = reader.read_f64()?;
+        let count = reader.read_f64()?;
+        let sum = reader.read_f64()?;
+        let positive_spans = reader.read_spans()?;
+        let positive_buckets = reader.read_buckets()?;
+        let negative_spans = reader.read_spans()?;
+        let negative_buckets = reader.read_buckets()?;
+        let custom_values = if version == ENCODING_VERSION_V1 {
+            vec![]
+        } else {
+            reader.read_buckets()?
+        };
+        if !reader.bytes.is_empty() {
+            return Err(DataFusionError::Execution(
+                "unexpected trailing bytes in native histogram".to_string(),
+            ));
+        }
+
+        let histogram = Self {
+            schema,
+            is_gauge: flags & GAUGE_FLAG != 0,
+            zero_threshold,
+            zero_count,
+            count,
+            sum,
+            positive_spans,
+            positive_buckets,
+            negative_spans,
+            negative_buckets,
+            custom_values,
+        };
+        histogram.validate()?;
+        Ok(histogram)
+    }
+
+    /// Returns all buckets in ascending order of their boundaries: negative
+    /// buckets, the zero bucket (if it has observations), then positive buckets.
+    pub fn buckets(&self) -> Vec<Bucket> {
+        if self.uses_custom_buckets() {
+            return bucket_indexes(&self.positive_spans)
+                .zip(self.positive_buckets.iter())
+                .map(|(index, count)| Bucket {
+                    lower: custom_bucket_upper_bound(index - 1, &self.custom_values),
+                    upper: custom_bucket_upper_bound(index, &self.custom_values),
+                    count: *count,
+                })
+                .collect();
+        }
+
+        let mut result =
+            Vec::with_capacity(self.negative_buckets.len() + 1 + self.positive_buckets.len());
+
+        let negative = bucket_indexes(&self.negative_spans)
+            .zip(self.negative_buckets.iter())
+            .map(|(index, count)| Bucket {
+                lower: -bucket_upper_bound(index, self.schema),
+                upper: -bucket_upper_bound(index - 1, self.schema),
+                count: *count,
+            })
+            .collect::<Vec<_>>();
+        result.extend(negative.into_iter().rev());
+
+        if self.zero_count > 0.0 {
+            result.push(Bucket {
+                lower: -self.zero_threshold,
+                upper: self.zero_threshold,
+                count: self.zero_count,
+            });
+        }
+
+        result.extend(
+            bucket_indexes(&self.positive_spans)
+                .zip(self.positive_buckets.iter())
+                .map(|(index, count)| Bucket {
+                    lower: bucket_upper_bound(index - 1, self.schema),
+                    upper: bucket_upper_bound(index, self.schema),
+                    count: *count,
+                }),
+        );
+
+        result
+    }
+
+    /// Estimates the `q`-quantile of the observations, following Prometheus'
+    /// `histogram_quantile` for native histograms. Observations are assumed to
+    /// be distributed exponentially within a bucket following the bucket
+    /// schema, except in the zero bucket and custom buckets where they are
+    /// distributed linearly.
+    pub fn quantile(&self, q: f64) -> f64 {
+        if q < 0.0 {
+            return f64::NEG_INFINITY;
+        }
+        if q > 1.0 {
+            return f64::INFINITY;
+        }
+        if self.count == 0.0 || q.is_nan() {
+            return f64::NAN;
+        }
+
+        let buckets = self.buckets();
+        // NaN observations are counted in `count` but not in any bucket, so the
+        // forward direction must be used if there are any (i.e. `sum` is NaN).
+        let forward = self.sum.is_nan() || q < 0.5;
+        let mut rank = if forward {
+            q * self.count
+        } else {
+            (1.0 - q) * self.count
+        };
+        let iter: Box<dyn Iterator<Item = &Bucket>> = if forward {
+            Box::new(buckets.iter())
+        } else {
+            Box::new(buckets.iter().rev())
+        };
+
+        let mut bucket = Bucket::default();
+        let mut count = 0.0;
+        for b in iter {
+            bucket = *b;
+            if bucket.count == 0.0 {
+                continue;
+            }
+            count += bucket.count;
+            if count >= rank {
+                break;
+            }
+        }
+
+        // Only custom buckets have infinite bounds.
+        if bucket.lower == f64::NEG_INFINITY {
+            if bucket.upper <= 0.0 {
+                return bucket.upper;
+            }
+            bucket.lower = 0.0;
+        } else if bucket.upper == f64::INFINITY {
+            return bucket.lower;
+        }
+
+        if !self.uses_custom_buckets() && bucket.lower < 0.0 && bucket.upper > 0.0 {
+            if self.negative_buckets.is_empty() && !self.positive_buckets.is_empty() {
+                // Only positive observations, so the zero bucket starts from 0.
+                bucket.lower = 0.0;
+            } else if self.positive_buckets.is_empty() && !self.negative_buckets.is_empty() {
+                bucket.upper = 0.0;
+            }
+        }
+
+        // Guards against accumulated floating point errors.
+        if count > self.count {
+            count = self.count;
+        }
+        // Only reachable with NaN observations.
+        if count < rank {
+            return bucket.upper;
+        }
+
+        if forward {
+            rank -= count - bucket.count;
+        } else {
+            rank = count - rank;
+        }
+        let fraction = rank / bucket.count;
+        if self.uses_custom_buckets() || (bucket.lower <= 0.0 && bucket.upper >= 0.0) {
+            return bucket.lower + (bucket.upper - bucket.lower) * fraction;
+        }
+
+        // Exponential bucket boundaries are equidistant on a logarithmic scale,
+        // so the interpolation is linear on that scale.
+        let log_lower = bucket.lower.abs().log2();
+        let log_upper = bucket.upper.abs().log2();
+        if bucket.lower > 0.0 {
+            (log_lower + (log_upper - log_lower) * fraction).exp2()
+        } else {
+            // Mirrors the positive case for negative buckets.
+            -(log_upper + (log_lower - log_upper) * (1.0 - fraction)).exp2()
+        }
+    }
+
+    /// Adds `other` into this histogram. Exponential buckets are merged under the
+    /// coarser schema of the two, and buckets within the larger zero threshold are
+    /// merged into the zero bucket. Histograms with custom buckets can only be
+    /// added to ones with the same custom values. The result is a gauge histogram.
+    pub fn add(&mut self, other: &NativeHistogram) -> DataFusionResult<()> {
+        if (self.uses_custom_buckets() || other.uses_custom_buckets())
+            && (self.schema != other.schema || self.custom_values != other.custom_values)
+        {
+            return Err(DataFusionError::Execution(
+                "cannot add native histograms with different custom buckets".to_string(),
+            ));
+        }
+
+        let schema = self.schema.min(other.schema);
+        let zero_threshold = self.zero_threshold.max(other.zero_threshold);
+        let mut zero_count = 0.0;
+        let mut positive = BTreeMap::new();
+        let mut negative = BTreeMap::new();
+        for histogram in [&*self, other] {
+            zero_count += histogram.zero_count;
+            for (spans, buckets, merged) in [
+                (
+                    &histogram.positive_spans,
+                    &histogram.positive_buckets,
+                    &mut positive,
+                ),
+                (
+                    &histogram.negative_spans,
+                    &histogram.negative_buckets,
+                    &mut negative,
+                ),
+            ] {
+                for (index, count) in bucket_indexes(spans).zip(buckets.iter()) {
+                    if histogram.uses_custom_buckets() {
+                        *merged.entry(index).or_insert(0.0) += count;
+                        continue;
+                    }
+                    let index = reduce_bucket_index(index, histogram.schema, schema);
+                    if bucket_upper_bound(index, schema) <= zero_threshold {
+                        zero_count += count;
+                    } else {
+                        *merged.entry(index).or_insert(0.0) += count;
+                    }
+                }
+            }
+        }
+
+        (self.positive_spans, self.positive_buckets) = spans_from_buckets(positive);
+        (self.negative_spans, self.negative_buckets) = spans_from_buckets(negative);
+        self.schema = schema;
+        self.is_gauge = true;
+        self.zero_threshold = zero_threshold;
+        self.zero_count = zero_count;
+        self.count += other.count;
+        self.sum += other.sum;
+        Ok(())
+    }
+
+    /// Subtracts `other` from this histogram. The buckets are merged like [Self::add].
+    pub fn sub(&mut self, other: &NativeHistogram) -> DataFusionResult<()> {
+        let mut negated = other.clone();
+        negated.div(-1.0);
+        self.add(&negated)
+    }
+
+    /// Whether this counter histogram was reset after the `previous` one, i.e. its
+    /// buckets got finer, its zero bucket got narrower, or any count went down.
+    pub fn detect_reset(&self, previous: &NativeHistogram) -> bool {
+        if self.count < previous.count
+            || self.zero_threshold < previous.zero_threshold
+            || self.schema > previous.schema
+        {
+            return true;
+        }
+        let mut diff = self.clone();
+        if diff.sub(previous).is_err() {
+            // The custom buckets are changed.
+            return true;
+        }
+        diff.zero_count < 0.0
+            || diff
+                .positive_buckets
+                .iter()
+                .chain(diff.negative_buckets.iter())
+                .any(|count| *count < 0.0)
+    }
+
+    /// Divides the counts of all buckets, the total count and the sum by `divisor`.
+    pub fn div(&mut self, divisor: f64) {
+        self.zero_count /= divisor;
+        self.count /= divisor;
+        self.sum /= divisor;
+        for count in self
+            .positive_buckets
+            .iter_mut()
+            .chain(self.negative_buckets.iter_mut())
+        {
+            *count /= divisor;
+        }
+    }
+}
+
+/// Returns the index of the bucket under the coarser `target_schema` that contains
+/// the bucket at `index` under `schema`.
+fn reduce_bucket_index(index: i32, schema: i32, target_schema: i32) -> i32 {
+    ((index - 1) >> (schema - target_schema)) + 1
+}
+
+/// Builds spans from bucket counts keyed by their absolute indexes.
+fn spans_from_buckets(buckets: BTreeMap<i32, f64>) -> (Vec<BucketSpan>, Vec<f64>) {
+    let mut spans: Vec<BucketSpan> = Vec::new();
+    let mut counts = Vec::with_capacity(buckets.len());
+    let mut next = 0;
+    for (index, count) in buckets {
+        match spans.last_mut() {
+            Some(span) if index == next => span.length += 1,
+            _ => spans.push(BucketSpan {
+                offset: index - next,
+                length: 1,
+            }),
+        }
+        next = index + 1;
+        counts.push(count);
+    }
+    (spans, counts)
+}
+
+/// Returns the upper bound of the bucket at `index` under `schema`, i.e.
+/// `2^(index * 2^-schema)`. The bucket at `index` covers `(bound(index - 1), bound(index)]`.
+pub fn bucket_upper_bound(index: i32, schema: i32) -> f64 {
+    if schema < 0 {
+        let exp = index << -schema;
+        if exp == 1024 {
+            // The last bucket that still covers regular float values.
+            return f64::MAX;
+        }
+        return 2f64.powi(exp);
+    }
+
+    let frac_index = index & ((1 << schema) - 1);
+    // `frac` is in `[0.5, 1)`.
+    let frac = (frac_index as f64 / (1 << schema) as f64).exp2() / 2.0;
+    let exp = (index >> schema) + 1;
+    if frac == 0.5 && exp == 1025 {
+        return f64::MAX;
+    }
+    // Splits the power to avoid overflowing before multiplying `frac`.
+    frac * 2.0 * 2f64.powi(exp - 1)
+}
+
+/// Returns the upper bound of the custom bucket at `index`, which is `-Inf` before
+/// the first bucket and `+Inf` past the last custom value.
+fn custom_bucket_upper_bound(index: i32, custom_values: &[f64]) -> f64 {
+    if index < 0 {
+        f64::NEG_INFINITY
+    } else {
+        custom_values
+            .get(index as usize)
+            .copied()
+            .unwrap_or(f64::INFINITY)
+    }
+}
+
+/// Encodes spans into the array stored in a spans column.
+pub fn encode_spans(spans: &[BucketSpan]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(spans.len() * 8);
+    for span in spans {
+        buf.extend_from_slice(&span.offset.to_le_bytes());
+        buf.extend_from_slice(&span.length.to_le_bytes());
+    }
+    buf
+}
+
+/// Decodes the array stored in a spans column.
+pub fn decode_spans(bytes: &[u8]) -> DataFusionResult<Vec<BucketSpan>> {
+    let mut reader = Reader { bytes };
+    let mut spans = Vec::with_capacity(bytes.len() / 8);
+    while !reader.bytes.is_empty() {
+        spans.push(BucketSpan {
+            offset: i32::from_le_bytes(reader.read()?),
+            length: u32::from_le_bytes(reader.read()?),
+        });
+    }
+    Ok(spans)
+}
+
+/// Encodes bucket counts or custom values into the array stored in their column.
+pub fn encode_values(values: &[f64]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+/// Decodes the array stored in a buckets or custom values column.
+pub fn decode_values(bytes: &[u8]) -> DataFusionResult<Vec<f64>> {
+    let mut reader = Reader { bytes };
+    let mut values = Vec::with_capacity(bytes.len() / 8);
+    while !reader.bytes.is_empty() {
+        values.push(reader.read_f64()?);
+    }
+    Ok(values)
+}
+
+fn write_f64s(buf: &mut Vec<u8>, values: &[f64]) {
+    buf.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    for value in values {
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// Expands spans into the absolute index of each bucket.
+fn bucket_indexes(spans: &[BucketSpan]) -> impl Iterator<Item = i32> + '_ {
+    let mut next = 0;
+    spans.iter().flat_map(move |span| {
+        let start = next + span.offset;
+        next = start + span.length as i32;
+        start..next
+    })
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn read<const N: usize>(&mut self) -> DataFusionResult<[u8; N]> {
+        if self.bytes.len() < N {
+            return Err(DataFusionError::Execution(
+                "truncated native histogram".to_string(),
+            ));
+        }
+        let (head, tail) = self.bytes.split_at(N);
+        self.bytes = tail;
+        Ok(head.try_into().unwrap())
+    }
+
+    fn read_f64(&mut self) -> DataFusionResult<f64> {
+        Ok(f64::from_le_bytes(self.read()?))
+    }
+
+    fn read_len(&mut self) -> DataFusionResult<usize> {
+        let len = u32::from_le_bytes(self.read()?) as usize;
+        // Each element takes 8 bytes. Rejects lengths that cannot fit to avoid
+        // allocating huge buffers for corrupted data.
+        if len > self.bytes.len() / 8 {
+            return Err(DataFusionError::Execution(
+                "truncated native histogram".to_string(),
+            ));
+        }
+        Ok(len)
+    }
+
+    fn read_spans(&mut self) -> DataFusionResult<Vec<BucketSpan>> {
+        let len = self.read_len()?;
+        (0..len)
+            .map(|_| {
+                Ok(BucketSpan {
+                    offset: i32::from_le_bytes(self.read()?),
+                    length: u32::from_le_bytes(self.read()?),
+                })
+            })
+            .collect()
+    }
+
+    fn read_buckets(&mut self) -> DataFusionResult<Vec<f64>> {
+        let len = self.read_len()?;
+        (0..len).map(|_| self.read_f64()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `{{schema:0 count:12 sum:100 z_bucket:2 z_bucket_w:0.001 buckets:[2 3 0 1 4]}}`
+    /// from Prometheus' `native_histograms.test`.
+    fn reference_histogram() -> NativeHistogram {
+        NativeHistogram {
+            schema: 0,
+            is_gauge: false,
+            zero_threshold: 0.001,
+            zero_count: 2.0,
+            count: 12.0,
+            sum: 100.0,
+            positive_spans: vec![BucketSpan {
+                offset: 0,
+                length: 5,
+            }],
+            positive_buckets: vec![2.0, 3.0, 0.0, 1.0, 4.0],
+            negative_spans: vec![],
+            negative_buckets: vec![],
+            custom_values: vec![],
+        }
+    }
+
+    /// `{{schema:-53 sum:5 count:4 custom_values:[5 10 15] buckets:[1 2 0 1]}}`, i.e.
+    /// observations in `(-Inf, 5]`, `(5, 10]`, `(10, 15]` and `(15, +Inf]`.
+    fn custom_buckets_histogram() -> NativeHistogram {
+        NativeHistogram {
+            schema: CUSTOM_BUCKETS_SCHEMA,
+            count: 4.0,
+            sum: 5.0,
+            positive_spans: vec![BucketSpan {
+                offset: 0,
+                length: 4,
+            }],
+            positive_buckets: vec![1.0, 2.0, 0.0, 1.0],
+            custom_values: vec![5.0, 10.0, 15.0],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let mut histogram = reference_histogram();
+        histogram.is_gauge = true;
+        histogram.negative_spans = vec![
+            BucketSpan {
+                offset: -2,
+                length: 1,
+            },
+            BucketSpan {
+                offset: 3,
+                length: 2,
+            },
+        ];
+        histogram.negative_buckets = vec![1.0, 0.5, 7.0];
+
+        let encoded = histogram.encode();
+        assert_eq!(histogram, NativeHistogram::decode(&encoded).unwrap());
+
+        assert!(NativeHistogram::decode(&encoded[..encoded.len() - 1]).is_err());
+        assert!(NativeHistogram::decode(&[]).is_err());
+
+        histogram.negative_buckets.pop();
+        assert!(NativeHistogram::decode(&histogram.encode()).is_err());
+
+        let histogram = custom_buckets_histogram();
+        assert_eq!(
+            histogram,
+            NativeHistogram::decode(&histogram.encode()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_encode_decode_lists() {
+        let histogram = reference_histogram();
+        let spans = encode_spans(&histogram.positive_spans);
+        assert_eq!(8, spans.len());
+        assert_eq!(histogram.positive_spans, decode_spans(&spans).unwrap());
+        assert!(decode_spans(&spans[..7]).is_err());
+        assert!(decode_spans(&[]).unwrap().is_empty());
+
+        let buckets = encode_values(&histogram.positive_buckets);
+        assert_eq!(histogram.positive_buckets, decode_values(&buckets).unwrap());
+        assert!(decode_values(&buckets[..buckets.len() - 1]).is_err());
+        assert!(decode_values(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_decode_v1() {
+        let histogram = reference_histogram();
+        // Version 1 has no trailing custom values.
+        let mut encoded = histogram.encode();
+        encoded.truncate(encoded.len() - 4);
+        encoded[0] = ENCODING_VERSION_V1;
+        assert_eq!(histogram, NativeHistogram::decode(&encoded).unwrap());
+    }
+
+    #[test]
+    fn test_validate_custom_buckets() {
+        assert!(custom_buckets_histogram().validate().is_ok());
+
+        // The `+Inf` bucket is the last one.
+        let mut histogram = custom_buckets_histogram();
+        histogram.positive_spans[0].length = 3;
+        histogram.positive_buckets.pop();
+        histogram.positive_spans.push(BucketSpan {
+            offset: 1,
+            length: 1,
+        });
+        histogram.positive_buckets.push(1.0);
+        assert!(histogram.validate().is_err());
+
+        let mut histogram = custom_buckets_histogram();
+        histogram.custom_values = vec![5.0, 5.0, 15.0];
+        assert!(histogram.validate().is_err());
+
+        let mut histogram = custom_buckets_histogram();
+        histogram.negative_spans = histogram.positive_spans.clone();
+        histogram.negative_buckets = histogram.positive_buckets.clone();
+        assert!(histogram.validate().is_err());
+
+        let mut histogram = reference_histogram();
+        histogram.custom_values = vec![1.0];
+        assert!(histogram.validate().is_err());
+    }
+
+    #[test]
+    fn test_bucket_upper_bound() {
+        assert_eq!(1.0, bucket_upper_bound(0, 0));
+        assert_eq!(2.0, bucket_upper_bound(1, 0));
+        assert_eq!(0.5, bucket_upper_bound(-1, 0));
+        assert_eq!(16.0, bucket_upper_bound(1, -2));
+        assert!((bucket_upper_bound(1, 1) - 2f64.sqrt()).abs() < 1e-15);
+        assert!((bucket_upper_bound(3, 2) - 2f64.powf(0.75)).abs() < 1e-15);
+        assert_eq!(4.0, bucket_upper_bound(8, 2));
+        assert_eq!(f64::MAX, bucket_upper_bound(1024, 0));
+    }
+
+    #[test]
+    fn test_buckets() {
+        let histogram = NativeHistogram {
+            schema: 0,
+            zero_threshold: 0.001,
+            zero_count: 1.0,
+            count: 4.0,
+            positive_spans: vec![
+                BucketSpan {
+                    offset: 1,
+                    length: 1,
+                },
+                BucketSpan {
+                    offset: 1,
+                    length: 1,
+                },
+            ],
+            positive_buckets: vec![1.0, 1.0],
+            negative_spans: vec![BucketSpan {
+                offset: 0,
+                length: 1,
+            }],
+            negative_buckets: vec![1.0],
+            ..Default::default()
+        };
+        let bounds = histogram
+            .buckets()
+            .iter()
+            .map(|b| (b.lower, b.upper))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![(-1.0, -0.5), (-0.001, 0.001), (1.0, 2.0), (4.0, 8.0)],
+            bounds
+        );
+    }
+
+    #[test]
+    fn test_quantile_reference() {
+        let histogram = reference_histogram();
+        // Expected values are from Prometheus' `native_histograms.test`.
+        let cases = [
+            (1.001, f64::INFINITY),
+            (1.0, 16.0),
+            (0.99, 15.67072476139083),
+            (0.9, 12.99603834169977),
+            (0.6, 4.594793419988138),
+            (0.5, 1.5874010519681994),
+            (0.1, 0.0006000000000000001),
+            (0.0, 0.0),
+            (-1.0, f64::NEG_INFINITY),
+        ];
+        for (q, expected) in cases {
+            let actual = histogram.quantile(q);
+            assert!(
+                actual == expected || (actual - expected).abs() < 1e-12,
+                "q: {q}, expected: {expected}, actual: {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_quantile_special_cases() {
+        let mut histogram = reference_histogram();
+        assert!(histogram.quantile(f64::NAN).is_nan());
+
+        // NaN observations are counted but not bucketed.
+        histogram.sum = f64::NAN;
+        histogram.count = 13.0;
+        assert_eq!(16.0, histogram.quantile(1.0));
+
+        histogram.count = 0.0;
+        assert!(histogram.quantile(0.5).is_nan());
+
+        // Only negative observations: the zero bucket ends at 0.
+        let histogram = NativeHistogram {
+            zero_threshold: 0.001,
+            zero_count: 2.0,
+            count: 4.0,
+            sum: -3.0,
+            negative_spans: vec![BucketSpan {
+                offset: 1,
+                length: 1,
+            }],
+            negative_buckets: vec![2.0],
+            ..Default::default()
+        };
+        assert!((histogram.quantile(0.75) + 0.0005).abs() < 1e-15);
+        assert!((histogram.quantile(0.25) + 2f64.sqrt()).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_quantile_schema_interpolation() {
+        // Buckets `(2^-0.25, 1]`, `(1, 2^0.25]`, `(2^0.25, 2^0.5]` and `(2^0.5, 2^0.75]`.
+        let histogram = NativeHistogram {
+            schema: 2,
+            count: 10.0,
+            sum: 12.0,
+            positive_spans: vec![BucketSpan {
+                offset: 0,
+                length: 4,
+            }],
+            positive_buckets: vec![1.0, 2.0, 3.0, 4.0],
+            ..Default::default()
+        };
+        let cases = [
+            // 1.5 of the 2 observations in `(1, 2^0.25]`.
+            (0.25, 2f64.powf(0.25 * 0.75)),
+            // 2 of the 3 observations in `(2^0.25, 2^0.5]`.
+            (0.5, 2f64.powf(0.25 + 0.25 * 2.0 / 3.0)),
+            // 3 of the 4 observations in `(2^0.5, 2^0.75]`.
+            (0.9, 2f64.powf(0.5 + 0.25 * 0.75)),
+        ];
+        for (q, expected) in cases {
+            let actual = histogram.quantile(q);
+            assert!(
+                (actual - expected).abs() < 1e-12,
+                "q: {q}, expected: {expected}, actual: {actual}"
+            );
+        }
+
+        // Negative buckets mirror the positive ones.
+        let negative = NativeHistogram {
+            sum: -12.0,
+            positive_spans: vec![],
+            positive_buckets: vec![],
+            negative_spans: histogram.positive_spans.clone(),
+            negative_buckets: histogram.positive_buckets.clone(),
+            ..histogram.clone()
+        };
+        assert!((negative.quantile(0.5) + histogram.quantile(0.5)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_quantile_negative_buckets() {
+        // `{{schema:0 count:24 sum:0 z_bucket:4 z_bucket_w:0.001 buckets:[2 3 0 1 4]
+        // n_buckets:[2 3 0 1 4]}}`, with a gap instead of the empty negative bucket.
+        let histogram = NativeHistogram {
+            zero_count: 4.0,
+            count: 24.0,
+            sum: 0.0,
+            negative_spans: vec![
+                BucketSpan {
+                    offset: 0,
+                    length: 2,
+                },
+                BucketSpan {
+                    offset: 1,
+                    length: 2,
+                },
+            ],
+            negative_buckets: vec![2.0, 3.0, 1.0, 4.0],
+            ..reference_histogram()
+        };
+        let cases = [
+            (0.0, -16.0),
+            // 2.4 of the 4 observations in `[-16, -8)`.
+            (0.1, -(2f64.powf(3.4))),
+            // 1.6 of the 2 observations in `[-1, -0.5)`.
+            (0.4, -(2f64.powf(-0.8))),
+            // 0.8 of the 4 observations in the zero bucket.
+            (0.45, -0.0006),
+            (0.5, 0.0),
+            (0.6, 2f64.powf(-0.8)),
+            (0.9, 2f64.powf(3.4)),
+            (1.0, 16.0),
+        ];
+        for (q, expected) in cases {
+            let actual = histogram.quantile(q);
+            assert!(
+                (actual - expected).abs() < 1e-12,
+                "q: {q}, expected: {expected}, actual: {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_custom_buckets_quantile() {
+        let histogram = custom_buckets_histogram();
+        let bounds = histogram
+            .buckets()
+            .iter()
+            .map(|b| (b.lower, b.upper))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (f64::NEG_INFINITY, 5.0),
+                (5.0, 10.0),
+                (10.0, 15.0),
+                (15.0, f64::INFINITY)
+            ],
+            bounds
+        );
+
+        // Observations are distributed linearly within the custom boundaries, the
+        // first bucket starts from 0 and the `+Inf` bucket collapses to its lower bound.
+        let cases = [
+            (0.1, 2.0),
+            (0.25, 5.0),
+            (0.5, 7.5),
+            (0.6, 8.5),
+            (0.75, 15.0),
+            (1.0, 15.0),
+        ];
+        for (q, expected) in cases {
+            let actual = histogram.quantile(q);
+            assert!(
+                (actual - expected).abs() < 1e-12,
+                "q: {q}, expected: {expected}, actual: {actual}"
+            );
+        }
+
+        // The first bucket doesn't start from 0 if it has a negative upper bound.
+        let histogram = NativeHistogram {
+            custom_values: vec![-10.0, 0.0, 10.0],
+            positive_buckets: vec![2.0, 1.0, 1.0, 0.0],
+            ..custom_buckets_histogram()
+        };
+        assert_eq!(-10.0, histogram.quantile(0.25));
+        assert!((histogram.quantile(0.625) + 5.0).abs() < 1e-12);
+        assert!((histogram.quantile(0.875) - 5.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_add_and_div() {
+        let mut histogram = reference_histogram();
+        let other = NativeHistogram {
+            schema: 1,
+            zero_threshold: 0.001,
+            zero_count: 1.0,
+            count: 4.0,
+            sum: 10.0,
+            positive_spans: vec![BucketSpan {
+                offset: 1,
+                length: 2,
+            }],
+            positive_buckets: vec![1.0, 1.0],
+            negative_spans: vec![BucketSpan {
+                offset: 3,
+                length: 1,
+            }],
+            negative_buckets: vec![1.0],
+            ..Default::default()
+        };
+        histogram.add(&other).unwrap();
+        // Buckets of schema 1 are merged pairwise into the buckets of schema 0.
+        let expected = NativeHistogram {
+            schema: 0,
+            is_gauge: true,
+            zero_threshold: 0.001,
+            zero_count: 3.0,
+            count: 16.0,
+            sum: 110.0,
+            positive_spans: vec![BucketSpan {
+                offset: 0,
+                length: 5,
+            }],
+            positive_buckets: vec![2.0, 5.0, 0.0, 1.0, 4.0],
+            negative_spans: vec![BucketSpan {
+                offset: 2,
+                length: 1,
+            }],
+            negative_buckets: vec![1.0],
+            custom_values: vec![],
+        };
+        assert_eq!(expected, histogram);
+
+        histogram.div(2.0);
+        assert_eq!(8.0, histogram.count);
+        assert_eq!(55.0, histogram.sum);
+        assert_eq!(1.5, histogram.zero_count);
+        assert_eq!(vec![1.0, 2.5, 0.0, 0.5, 2.0], histogram.positive_buckets);
+        assert_eq!(vec![0.5], histogram.negative_buckets);
+
+        let mut histogram = custom_buckets_histogram();
+        histogram.add(&custom_buckets_histogram()).unwrap();
+        assert_eq!(vec![2.0, 4.0, 0.0, 2.0], histogram.positive_buckets);
+        let other = NativeHistogram {
+            custom_values: vec![1.0, 2.0, 3.0],
+            ..custom_buckets_histogram()
+        };
+        assert!(histogram.add(&other).is_err());
+        assert!(histogram.add(&reference_histogram()).is_err());
+    }
+}
@@ -29,20 +29,31 @@ use common_function::function::FunctionRef;
 use common_function::scalars::aggregate::AggregateFunctionMetaRef;
 use common_query::{Output, OutputData, OutputMeta};
 use common_recordbatch::adapter::RecordBatchStreamAdapter;
-use common_recordbatch::{EmptyRecordBatchStream, SendableRecordBatchStream};
+use common_recordbatch::{
+    EmptyRecordBatchStream, ResultSizeLimitedStream, SendableRecordBatchStream,
+};
 use common_telemetry::tracing;
+use common_time::range::TimestampRange;
 use datafusion::physical_plan::analyze::AnalyzeExec;
 use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
 use datafusion::physical_plan::ExecutionPlan;
 use datafusion_common::ResolvedTableReference;
-use datafusion_expr::{DmlStatement, LogicalPlan as DfLogicalPlan, LogicalPlan, WriteOp};
+use datafusion_expr::utils::split_conjunction;
+use datafusion_expr::{
+    BinaryExpr, DmlStatement, Expr, LogicalPlan as DfLogicalPlan, LogicalPlan, Operator, WriteOp,
+};
+use datatypes::arrow::datatypes::DataType;
 use datatypes::prelude::VectorRef;
-use datatypes::schema::Schema;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::value::scalar_value_to_timestamp;
 use futures_util::StreamExt;
+use promql_parser::parser::EvalStmt;
 use session::context::QueryContextRef;
 use snafu::{ensure, OptionExt, ResultExt};
 use sqlparser::ast::AnalyzeFormat;
+use table::predicate::build_time_range_predicate;
 use table::requests::{DeleteRequest, InsertRequest};
+use table::table_name::TableName;
 use table::TableRef;
 
 use crate::analyze::DistAnalyzeExec;
@@ -56,6 +67,7 @@ use crate::error::{
 };
 use crate::executor::QueryExecutor;
 use crate::metrics::{OnDone, QUERY_STAGE_ELAPSED};
+use crate::parser::QueryStatement;
 use crate::physical_wrapper::PhysicalPlanWrapperRef;
 use crate::planner::{DfLogicalPlanner, LogicalPlanner};
 use crate::query_engine::{DescribeResult, QueryEngineContext, QueryEngineState};
@@ -77,6 +89,19 @@ impl DatafusionQueryEngine {
         plan: LogicalPlan,
         query_ctx: QueryContextRef,
     ) -> Result<Output> {
+        let (stream, physical_plan) = self.exec_query_plan_to_stream(plan, query_ctx).await?;
+        Ok(Output::new(
+            OutputData::Stream(stream),
+            OutputMeta::new_with_plan(physical_plan),
+        ))
+    }
+
+    /// Executes the query plan, returns the result stream and the executed physical plan.
+    async fn exec_query_plan_to_stream(
+        &self,
+        plan: LogicalPlan,
+        query_ctx: QueryContextRef,
+    ) -> Result<(SendableRecordBatchStream, Arc<dyn ExecutionPlan>)> {
         let mut ctx = self.engine_context(query_ctx.clone());
 
         // `create_physical_plan` will optimize logical plan internally
@@ -89,10 +114,11 @@ impl DatafusionQueryEngine {
             optimized_physical_plan
         };
 
-        Ok(Output::new(
-            OutputData::Stream(self.execute_stream(&ctx, &physical_plan)?),
-            OutputMeta::new_with_plan(physical_plan),
-        ))
+        let mut stream = self.execute_stream(&ctx, &physical_plan)?;
+        if let Some(limit) = self.state.max_result_bytes() {
+            stream = Box::pin(ResultSizeLimitedStream::new(stream, limit as usize));
+        }
+        Ok((stream, physical_plan))
     }
 
     #[tracing::instrument(skip_all)]
@@ -117,6 +143,9 @@ impl DatafusionQueryEngine {
         let table_name = dml.table_name.resolve(default_catalog, default_schema);
         let table = self.find_table(&table_name, &query_ctx).await?;
 
+        let mut affected_rows = 0;
+        let mut insert_cost = 0;
+
         let output = self
             .exec_query_plan((*dml.input).clone(), query_ctx.clone())
             .await?;
@@ -126,9 +155,6 @@ impl DatafusionQueryEngine {
             _ => unreachable!(),
         };
 
-        let mut affected_rows = 0;
-        let mut insert_cost = 0;
-
         while let Some(batch) = stream.next().await {
             let batch = batch.context(CreateRecordBatchSnafu)?;
             let column_vectors = batch
@@ -154,12 +180,66 @@ impl DatafusionQueryEngine {
                 _ => unreachable!("guarded by the 'ensure!' at the beginning"),
             }
         }
+
+        // The rows are deleted first, so the affected rows only count the deleted rows
+        // once, while the removed files may also hold overwritten rows.
+        if matches!(dml.op, WriteOp::Delete) {
+            self.truncate_deleted_range(&table_name, &table, &dml.input, query_ctx.clone())
+                .await?;
+        }
         Ok(Output::new(
             OutputData::AffectedRows(affected_rows),
             OutputMeta::new_with_cost(insert_cost),
         ))
     }
 
+    /// Removes the SST files of the table whose rows are all deleted if the DELETE only
+    /// selects rows by ranges of the time index, e.g. `ts < '2024-01-01'`, so the space
+    /// of these files is reclaimed without waiting for compaction.
+    #[tracing::instrument(skip_all)]
+    async fn truncate_deleted_range(
+        &self,
+        table_name: &ResolvedTableReference,
+        table: &TableRef,
+        input: &LogicalPlan,
+        query_ctx: QueryContextRef,
+    ) -> Result<()> {
+        ensure!(
+            !is_readonly_schema(&table_name.schema),
+            TableReadOnlySnafu {
+                table: table_name.table.to_string()
+            }
+        );
+
+        let Some(time_range) = table
+            .schema()
+            .timestamp_column()
+            .and_then(|ts_column| delete_time_range(ts_column, input))
+        else {
+            return Ok(());
+        };
+        if time_range.is_empty() {
+            return Ok(());
+        }
+
+        let _ = self
+            .state
+            .table_mutation_handler()
+            .context(MissingTableMutationHandlerSnafu)?
+            .truncate_range(
+                TableName::new(
+                    table_name.catalog.to_string(),
+                    table_name.schema.to_string(),
+                    table_name.table.to_string(),
+                ),
+                time_range,
+                query_ctx,
+            )
+            .await
+            .context(TableMutationSnafu)?;
+        Ok(())
+    }
+
     #[tracing::instrument(skip_all)]
     async fn delete(
         &self,
@@ -447,6 +527,19 @@ impl QueryEngine for DatafusionQueryEngine {
         }
     }
 
+    async fn stream_promql(
+        &self,
+        stmt: EvalStmt,
+        query_ctx: QueryContextRef,
+    ) -> Result<SendableRecordBatchStream> {
+        let plan = self
+            .planner()
+            .plan(&QueryStatement::Promql(stmt), query_ctx.clone())
+            .await?;
+        let (stream, _) = self.exec_query_plan_to_stream(plan, query_ctx).await?;
+        Ok(stream)
+    }
+
     /// Note in SQL queries, aggregate names are looked up using
     /// lowercase unless the query uses quotes. For example,
     ///
@@ -552,22 +645,81 @@ impl QueryExecutor for DatafusionQueryEngine {
     }
 }
 
+/// Returns the time range of the rows deleted by a DELETE with the `input` plan, if
+/// it only selects rows by comparing the time index with timestamps of the same unit,
+/// so the range has exactly the rows to delete.
+fn delete_time_range(ts_column: &ColumnSchema, input: &LogicalPlan) -> Option<TimestampRange> {
+    let unit = ts_column.data_type.as_timestamp()?.unit();
+
+    let mut filters = Vec::new();
+    let mut plan = input;
+    loop {
+        match plan {
+            LogicalPlan::Filter(filter) => {
+                filters.push(&filter.predicate);
+                plan = &filter.input;
+            }
+            LogicalPlan::TableScan(scan) => {
+                filters.extend(&scan.filters);
+                break;
+            }
+            _ => return None,
+        }
+    }
+
+    let mut time_range = TimestampRange::min_to_max();
+    for expr in filters.into_iter().flat_map(split_conjunction) {
+        let Expr::BinaryExpr(BinaryExpr { left, op, right }) = expr else {
+            return None;
+        };
+        if !matches!(
+            op,
+            Operator::Eq | Operator::Lt | Operator::LtEq | Operator::Gt | Operator::GtEq
+        ) {
+            return None;
+        }
+        let (column, value) = match (left.as_ref(), right.as_ref()) {
+            (Expr::Column(column), Expr::Literal(value))
+            | (Expr::Literal(value), Expr::Column(column)) => (column, value),
+            _ => return None,
+        };
+        if column.name != ts_column.name || !matches!(value.data_type(), DataType::Timestamp(..)) {
+            return None;
+        }
+        // Timestamps of other units may be rounded to a wider range.
+        if scalar_value_to_timestamp(value, None)?.unit() != unit {
+            return None;
+        }
+        time_range = time_range.and(&build_time_range_predicate(
+            &ts_column.name,
+            unit,
+            &[expr.clone()],
+        ));
+    }
+    Some(time_range)
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
     use catalog::RegisterTableRequest;
     use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, NUMBERS_TABLE_ID};
-    use common_recordbatch::util;
+    use common_recordbatch::{util, RecordBatch};
     use datafusion::prelude::{col, lit};
     use datatypes::prelude::ConcreteDataType;
     use datatypes::schema::ColumnSchema;
-    use datatypes::vectors::{Helper, UInt32Vector, UInt64Vector, VectorRef};
+    use datatypes::vectors::{
+        Float64Vector, Helper, StringVector, TimestampMillisecondVector, UInt32Vector,
+        UInt64Vector, VectorRef,
+    };
     use session::context::{QueryContext, QueryContextBuilder};
     use table::table::numbers::{NumbersTable, NUMBERS_TABLE_NAME};
+    use table::test_util::MemTable;
 
     use super::*;
-    use crate::parser::QueryLanguageParser;
+    use crate::parser::{PromQuery, QueryLanguageParser};
+    use crate::query_engine::options::QueryOptions;
     use crate::query_engine::{QueryEngineFactory, QueryEngineRef};
 
     async fn create_test_engine() -> QueryEngineRef {
@@ -584,6 +736,67 @@ mod tests {
         QueryEngineFactory::new(catalog_manager, None, None, None, None, false).query_engine()
     }
 
+    #[tokio::test]
+    async fn test_max_result_bytes() {
+        let catalog_manager = catalog::memory::new_memory_catalog_manager().unwrap();
+        let req = RegisterTableRequest {
+            catalog: DEFAULT_CATALOG_NAME.to_string(),
+            schema: DEFAULT_SCHEMA_NAME.to_string(),
+            table_name: NUMBERS_TABLE_NAME.to_string(),
+            table_id: NUMBERS_TABLE_ID,
+            table: NumbersTable::table(NUMBERS_TABLE_ID),
+        };
+        catalog_manager.register_table_sync(req).unwrap();
+        let plugins = Plugins::new();
+        plugins.insert(QueryOptions {
+            max_result_bytes: Some(16 * 1024),
+            ..Default::default()
+        });
+        let engine = QueryEngineFactory::new_with_plugins(
+            catalog_manager,
+            None,
+            None,
+            None,
+            None,
+            false,
+            plugins,
+        )
+        .query_engine();
+
+        let execute = |sql| {
+            let engine = engine.clone();
+            async move {
+                let stmt = QueryLanguageParser::parse_sql(sql, &QueryContext::arc()).unwrap();
+                let plan = engine
+                    .planner()
+                    .plan(&stmt, QueryContext::arc())
+                    .await
+                    .unwrap();
+                let output = engine.execute(plan, QueryContext::arc()).await.unwrap();
+                let OutputData::Stream(stream) = output.data else {
+                    unreachable!()
+                };
+                util::collect(stream).await
+            }
+        };
+
+        // A small result is under the limit.
+        let batches = execute("select count(*) from numbers").await.unwrap();
+        assert_eq!(1, batches.len());
+
+        // 10000 rows of two u32 columns exceed the limit.
+        let err = execute("select * from numbers n1, numbers n2")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                common_recordbatch::error::Error::ResultSizeExceeded { limit: 16384, .. }
+            ),
+            "{err:?}"
+        );
+    }
+
     #[tokio::test]
     async fn test_sql_to_plan() {
         let engine = create_test_engine().await;
@@ -643,6 +856,68 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_stream_promql() {
+        let catalog_manager = catalog::memory::new_memory_catalog_manager().unwrap();
+        let schema = Arc::new(datatypes::schema::Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+            ColumnSchema::new("val", ConcreteDataType::float64_datatype(), true),
+        ]));
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(vec!["a", "a", "a", "b", "b", "b"])),
+            Arc::new(TimestampMillisecondVector::from_vec(vec![
+                0, 5_000, 10_000, 0, 5_000, 10_000,
+            ])),
+            Arc::new(Float64Vector::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0])),
+        ];
+        let recordbatch = RecordBatch::new(schema, columns).unwrap();
+        let table = MemTable::table_with_primary_keys("metric", recordbatch, 1024, vec![0]);
+        catalog_manager
+            .register_table_sync(RegisterTableRequest {
+                catalog: DEFAULT_CATALOG_NAME.to_string(),
+                schema: DEFAULT_SCHEMA_NAME.to_string(),
+                table_name: "metric".to_string(),
+                table_id: 1024,
+                table,
+            })
+            .unwrap();
+        let engine =
+            QueryEngineFactory::new(catalog_manager, None, None, None, None, false).query_engine();
+
+        let query = PromQuery {
+            query: "metric".to_string(),
+            start: "0".to_string(),
+            end: "10".to_string(),
+            step: "5s".to_string(),
+            ..Default::default()
+        };
+        let QueryStatement::Promql(stmt) =
+            QueryLanguageParser::parse_promql(&query, &QueryContext::arc()).unwrap()
+        else {
+            unreachable!()
+        };
+        let mut stream = engine
+            .stream_promql(stmt, QueryContext::arc())
+            .await
+            .unwrap();
+
+        let mut num_batches = 0;
+        let mut num_rows = 0;
+        while let Some(batch) = stream.next().await {
+            let batch = batch.unwrap();
+            num_batches += 1;
+            num_rows += batch.num_rows();
+        }
+        assert!(num_batches > 0);
+        assert_eq!(num_rows, 6);
+    }
+
     #[tokio::test]
     async fn test_read_table() {
         let engine = create_test_engine().await;
@@ -711,4 +986,74 @@ mod tests {
         );
         assert_eq!("Limit: skip=0, fetch=20\n  Aggregate: groupBy=[[]], aggr=[[sum(CAST(numbers.number AS UInt64))]]\n    TableScan: numbers projection=[number]", format!("{}", logical_plan.display_indent()));
     }
+
+    #[test]
+    fn test_delete_time_range() {
+        use common_time::timestamp::TimeUnit;
+        use common_time::Timestamp;
+        use datafusion_common::ScalarValue;
+        use datafusion_expr::logical_plan::builder::table_scan;
+
+        let ts_column = ColumnSchema::new(
+            "ts",
+            ConcreteDataType::timestamp_millisecond_datatype(),
+            false,
+        );
+        let schema = Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), false),
+            ts_column.clone(),
+        ]);
+        let ts = |v| lit(ScalarValue::TimestampMillisecond(Some(v), None));
+        let plan = |filter: Option<Expr>| {
+            let builder = table_scan(Some("t"), schema.arrow_schema(), None).unwrap();
+            match filter {
+                Some(filter) => builder.filter(filter).unwrap().build().unwrap(),
+                None => builder.build().unwrap(),
+            }
+        };
+
+        assert_eq!(
+            Some(TimestampRange::min_to_max()),
+            delete_time_range(&ts_column, &plan(None))
+        );
+        assert_eq!(
+            Some(TimestampRange::until_end(
+                Timestamp::new_millisecond(1000),
+                false
+            )),
+            delete_time_range(&ts_column, &plan(Some(col("ts").lt(ts(1000)))))
+        );
+        assert_eq!(
+            TimestampRange::with_unit(1000, 2000, TimeUnit::Millisecond),
+            delete_time_range(
+                &ts_column,
+                &plan(Some(col("ts").gt_eq(ts(1000)).and(col("ts").lt(ts(2000)))))
+            )
+        );
+
+        // Filters on other columns, timestamps of other units or disjunctions.
+        assert_eq!(
+            None,
+            delete_time_range(
+                &ts_column,
+                &plan(Some(col("ts").lt(ts(1000)).and(col("host").eq(lit("a")))))
+            )
+        );
+        assert_eq!(
+            None,
+            delete_time_range(
+                &ts_column,
+                &plan(Some(
+                    col("ts").lt(lit(ScalarValue::TimestampSecond(Some(1), None)))
+                ))
+            )
+        );
+        assert_eq!(
+            None,
+            delete_time_range(
+                &ts_column,
+                &plan(Some(col("ts").lt(ts(1000)).or(col("ts").gt(ts(2000)))))
+            )
+        );
+    }
 }
@@ -0,0 +1,245 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Applies the `scan.default_filter` option of tables to their scans.
+//!
+//! A table with the option, e.g. `'scan.default_filter' = 'deleted = false'`, only
+//! exposes the rows matching the filter to queries, like a row-level security
+//! policy. The filter is ANDed into every scan of the table at the logical plan
+//! level, so it's pushed down and used to prune data like any other filter.
+//! Sessions can skip the filters by `SET BYPASS_DEFAULT_FILTER = true`.
+
+use arrow_schema::{DataType, SchemaRef};
+use datafusion::datasource::DefaultTableSource;
+use datafusion::execution::context::SessionState;
+use datafusion_common::tree_node::{Transformed, TreeNode, TreeNodeRecursion};
+use datafusion_common::{plan_err, Column, DFSchema, Result, TableReference};
+use datafusion_expr::{Expr, ExprSchemable, LogicalPlan, LogicalPlanBuilder, TableScan};
+use session::context::QueryContextRef;
+use table::requests::SCAN_DEFAULT_FILTER_KEY;
+use table::table::adapter::DfTableProviderAdapter;
+
+/// Parses the default filter `sql` of a table scanned as `table_name` and
+/// ensures it's a boolean expression over the columns in `schema`.
+pub fn parse_default_filter(
+    state: &SessionState,
+    sql: &str,
+    table_name: TableReference,
+    schema: &SchemaRef,
+) -> Result<Expr> {
+    let df_schema = DFSchema::try_from_qualified_schema(table_name, schema)?;
+    let expr = state.create_logical_expr(sql, &df_schema)?;
+    let data_type = expr.get_type(&df_schema)?;
+    if data_type != DataType::Boolean {
+        return plan_err!(
+            "The {SCAN_DEFAULT_FILTER_KEY} '{sql}' must be a boolean expression, got {data_type}"
+        );
+    }
+    Ok(expr)
+}
+
+/// Wraps the scans of tables that have a default filter with the filter,
+/// unless the session bypasses them.
+pub(crate) fn apply_default_filters(
+    plan: LogicalPlan,
+    state: &SessionState,
+    query_ctx: &QueryContextRef,
+) -> Result<LogicalPlan> {
+    if query_ctx.bypass_default_filter() {
+        return Ok(plan);
+    }
+
+    plan.transform_down(|plan| match plan {
+        LogicalPlan::TableScan(scan) => match default_filter(&scan) {
+            Some(sql) => {
+                let plan = filter_scan(scan, &sql, state)?;
+                // Don't visit the scan inside the filter again.
+                Ok(Transformed::new(plan, true, TreeNodeRecursion::Jump))
+            }
+            None => Ok(Transformed::no(LogicalPlan::TableScan(scan))),
+        },
+        _ => Ok(Transformed::no(plan)),
+    })
+    .map(|x| x.data)
+}
+
+/// Returns the default filter of the scanned table.
+fn default_filter(scan: &TableScan) -> Option<String> {
+    let adapter = scan
+        .source
+        .as_any()
+        .downcast_ref::<DefaultTableSource>()?
+        .table_provider
+        .as_any()
+        .downcast_ref::<DfTableProviderAdapter>()?;
+    adapter
+        .table()
+        .table_info()
+        .meta
+        .options
+        .extra_options
+        .get(SCAN_DEFAULT_FILTER_KEY)
+        .cloned()
+}
+
+/// Filters the output of the `scan` by `sql`. The filter may reference columns
+/// that are not projected, so the scan reads all columns and the projection is
+/// applied after the filter. Same for the fetch.
+fn filter_scan(scan: TableScan, sql: &str, state: &SessionState) -> Result<LogicalPlan> {
+    let TableScan {
+        table_name,
+        source,
+        projection,
+        filters,
+        fetch,
+        ..
+    } = scan;
+    let predicate = parse_default_filter(state, sql, table_name.clone(), &source.schema())?;
+    let projected_columns = projection.map(|projection| {
+        let schema = source.schema();
+        projection
+            .into_iter()
+            .map(|i| {
+                Expr::Column(Column::new(
+                    Some(table_name.clone()),
+                    schema.field(i).name(),
+                ))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let scan = TableScan::try_new(table_name, source, None, filters, None)?;
+    let mut builder = LogicalPlanBuilder::from(LogicalPlan::TableScan(scan)).filter(predicate)?;
+    if let Some(columns) = projected_columns {
+        builder = builder.project(columns)?;
+    }
+    if let Some(fetch) = fetch {
+        builder = builder.limit(0, Some(fetch))?;
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::datasource::provider_as_source;
+    use datafusion_expr::{col, lit};
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::{ColumnSchema, SchemaBuilder};
+    use session::context::QueryContext;
+    use table::metadata::{TableInfoBuilder, TableMetaBuilder};
+    use table::requests::TableOptions;
+    use table::test_util::EmptyTable;
+
+    use super::*;
+    use crate::QueryEngineFactory;
+
+    fn new_scan(default_filter: Option<&str>, projection: Option<Vec<usize>>) -> LogicalPlan {
+        let schema = Arc::new(
+            SchemaBuilder::try_from_columns(vec![
+                ColumnSchema::new("host", ConcreteDataType::string_datatype(), true),
+                ColumnSchema::new("deleted", ConcreteDataType::boolean_datatype(), true),
+                ColumnSchema::new(
+                    "ts",
+                    ConcreteDataType::timestamp_millisecond_datatype(),
+                    false,
+                )
+                .with_time_index(true),
+            ])
+            .unwrap()
+            .build()
+            .unwrap(),
+        );
+        let mut options = TableOptions::default();
+        if let Some(filter) = default_filter {
+            options
+                .extra_options
+                .insert(SCAN_DEFAULT_FILTER_KEY.to_string(), filter.to_string());
+        }
+        let meta = TableMetaBuilder::empty()
+            .schema(schema)
+            .primary_key_indices(vec![0])
+            .value_indices(vec![1])
+            .next_column_id(3)
+            .options(options)
+            .build()
+            .unwrap();
+        let info = TableInfoBuilder::default()
+            .name("t")
+            .meta(meta)
+            .build()
+            .unwrap();
+        let source = provider_as_source(Arc::new(DfTableProviderAdapter::new(
+            EmptyTable::from_table_info(&info),
+        )));
+        LogicalPlanBuilder::scan("t", source, projection)
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    fn session_state() -> SessionState {
+        let catalog_manager = catalog::memory::new_memory_catalog_manager().unwrap();
+        let engine =
+            QueryEngineFactory::new(catalog_manager, None, None, None, None, false).query_engine();
+        engine.engine_state().session_state()
+    }
+
+    #[test]
+    fn test_apply_default_filter() {
+        let state = session_state();
+        let query_ctx = QueryContext::arc();
+
+        let plan = new_scan(Some("deleted = false"), Some(vec![0]));
+        let plan = apply_default_filters(plan, &state, &query_ctx).unwrap();
+        let expected = "Projection: t.host\
+        \n  Filter: t.deleted = Boolean(false)\
+        \n    TableScan: t";
+        assert_eq!(expected, plan.to_string());
+
+        // Tables without default filter are untouched.
+        let plan = new_scan(None, None);
+        let plan = apply_default_filters(plan, &state, &query_ctx).unwrap();
+        assert_eq!("TableScan: t", plan.to_string());
+    }
+
+    #[test]
+    fn test_bypass_default_filter() {
+        let state = session_state();
+        let query_ctx = QueryContext::arc();
+        query_ctx.set_bypass_default_filter(true);
+
+        let plan = new_scan(Some("deleted = false"), None);
+        let plan = apply_default_filters(plan, &state, &query_ctx).unwrap();
+        assert_eq!("TableScan: t", plan.to_string());
+    }
+
+    #[test]
+    fn test_parse_default_filter() {
+        let state = session_state();
+        let LogicalPlan::TableScan(scan) = new_scan(None, None) else {
+            unreachable!()
+        };
+        let schema = scan.source.schema();
+
+        let expr = parse_default_filter(&state, "deleted = false", "t".into(), &schema).unwrap();
+        assert_eq!(col("t.deleted").eq(lit(false)), expr);
+
+        // Unknown column.
+        assert!(parse_default_filter(&state, "removed = false", "t".into(), &schema).is_err());
+        // Not a predicate.
+        assert!(parse_default_filter(&state, "host", "t".into(), &schema).is_err());
+    }
+}
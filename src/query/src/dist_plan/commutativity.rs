@@ -19,6 +19,7 @@ use datafusion_expr::{Expr, LogicalPlan, UserDefinedLogicalNode};
 use promql::extension_plan::{
     EmptyMetric, InstantManipulate, RangeManipulate, SeriesDivide, SeriesNormalize,
 };
+use promql::functions::{HistogramAggr, SkipNanAggr, StdAggr};
 
 use crate::dist_plan::merge_sort::{merge_sort_transformer, MergeSortLogicalPlan};
 use crate::dist_plan::MergeScanLogicalPlan;
@@ -55,6 +56,20 @@ impl Categorizer {
             LogicalPlan::Filter(filter) => Self::check_expr(&filter.predicate),
             LogicalPlan::Window(_) => Commutativity::Unimplemented,
             LogicalPlan::Aggregate(aggr) => {
+                // PromQL aggregators that skip NaN, use Welford's algorithm or aggregate
+                // native histograms are named after the DataFusion ones, datanodes would decode
+                // them as the latter.
+                let has_promql_aggr = aggr.aggr_expr.iter().any(|expr| match expr {
+                    Expr::AggregateFunction(func) => {
+                        SkipNanAggr::is_skip_nan_aggr(&func.func)
+                            || StdAggr::is_std_aggr(&func.func)
+                            || HistogramAggr::is_histogram_aggr(&func.func)
+                    }
+                    _ => false,
+                });
+                if has_promql_aggr {
+                    return Commutativity::Unimplemented;
+                }
                 if Self::check_partition(&aggr.group_expr, &partition_cols) {
                     return Commutativity::Commutative;
                 }
@@ -28,6 +28,7 @@ use common_recordbatch::error::ExternalSnafu;
 use common_recordbatch::{
     DfSendableRecordBatchStream, RecordBatch, RecordBatchStreamWrapper, SendableRecordBatchStream,
 };
+use common_telemetry::tracing::{field, info_span};
 use common_telemetry::tracing_context::TracingContext;
 use datafusion::execution::{SessionState, TaskContext};
 use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
@@ -37,7 +38,7 @@ use datafusion::physical_plan::metrics::{
 use datafusion::physical_plan::{
     DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, PlanProperties,
 };
-use datafusion_common::{Column as ColumnExpr, Result};
+use datafusion_common::{Column as ColumnExpr, Result, Statistics};
 use datafusion_expr::{Expr, Extension, LogicalPlan, UserDefinedLogicalNodeCore};
 use datafusion_physical_expr::expressions::Column;
 use datafusion_physical_expr::{
@@ -50,7 +51,7 @@ use meter_core::data::ReadItem;
 use meter_macros::read_meter;
 use session::context::QueryContextRef;
 use snafu::ResultExt;
-use store_api::storage::RegionId;
+use store_api::storage::{RegionId, SCAN_HINTS_KEY};
 use table::table_name::TableName;
 use tokio::time::Instant;
 
@@ -147,6 +148,8 @@ pub struct MergeScanExec {
     query_ctx: QueryContextRef,
     target_partition: usize,
     partition_cols: Vec<String>,
+    /// The estimated statistics of the output, if the input plan is a plain scan.
+    statistics: Option<Statistics>,
 }
 
 impl std::fmt::Debug for MergeScanExec {
@@ -243,9 +246,16 @@ impl MergeScanExec {
             query_ctx,
             target_partition,
             partition_cols,
+            statistics: None,
         })
     }
 
+    /// Sets the estimated statistics of the output for the optimizer.
+    pub fn with_statistics(mut self, statistics: Statistics) -> Self {
+        self.statistics = Some(statistics);
+        self
+    }
+
     pub fn to_stream(
         &self,
         context: Arc<TaskContext>,
@@ -264,6 +274,20 @@ impl MergeScanExec {
         let tracing_context = TracingContext::from_json(context.session_id().as_str());
         let current_channel = self.query_ctx.channel();
         let read_preference = self.query_ctx.read_preference();
+        let partition_regions = regions
+            .iter()
+            .skip(partition)
+            .step_by(target_partition)
+            .count();
+        // The span of this partition lives until the stream is dropped.
+        let partition_span = tracing_context.attach(info_span!(
+            "MergeScanExec::partition",
+            table = %self.table,
+            partition,
+            regions = partition_regions,
+            rows = field::Empty,
+            bytes = field::Empty,
+        ));
 
         let stream = Box::pin(stream!({
             // only report metrics once for each MergeScan
@@ -274,6 +298,7 @@ impl MergeScanExec {
             let _finish_timer = metric.finish_time().timer();
             let mut ready_timer = metric.ready_time().timer();
             let mut first_consume_timer = Some(metric.first_consume_time().timer());
+            let (mut partition_rows, mut partition_bytes) = (0, 0);
 
             for region_id in regions
                 .iter()
@@ -281,9 +306,16 @@ impl MergeScanExec {
                 .step_by(target_partition)
                 .copied()
             {
+                let region_span = TracingContext::from_span(&partition_span).attach(info_span!(
+                    "MergeScanExec::region",
+                    region_id = %region_id,
+                    rows = field::Empty,
+                    bytes = field::Empty,
+                ));
+                let (mut region_rows, mut region_bytes) = (0, 0);
                 let request = QueryRequest {
                     header: Some(RegionRequestHeader {
-                        tracing_context: tracing_context.to_w3c(),
+                        tracing_context: TracingContext::from_span(&region_span).to_w3c(),
                         dbname: dbname.clone(),
                         query_context: Some(query_ctx.as_ref().into()),
                     }),
@@ -314,6 +346,8 @@ impl MergeScanExec {
                     // to remove metadata and correct column name
                     let batch = RecordBatch::new(schema.clone(), batch.columns().iter().cloned())?;
                     metric.record_output_batch_rows(batch.num_rows());
+                    region_rows += batch.num_rows();
+                    region_bytes += batch.df_record_batch().get_array_memory_size();
                     if let Some(mut first_consume_timer) = first_consume_timer.take() {
                         first_consume_timer.stop();
                     }
@@ -345,7 +379,13 @@ impl MergeScanExec {
                 }
 
                 MERGE_SCAN_POLL_ELAPSED.observe(poll_duration.as_secs_f64());
+                region_span.record("rows", region_rows);
+                region_span.record("bytes", region_bytes);
+                partition_rows += region_rows;
+                partition_bytes += region_bytes;
             }
+            partition_span.record("rows", partition_rows);
+            partition_span.record("bytes", partition_bytes);
         }));
 
         Ok(Box::pin(RecordBatchStreamWrapper {
@@ -416,6 +456,10 @@ impl MergeScanExec {
         self.target_partition
     }
 
+    pub fn table(&self) -> &TableName {
+        &self.table
+    }
+
     pub fn region_count(&self) -> usize {
         self.regions.len()
     }
@@ -464,15 +508,29 @@ impl ExecutionPlan for MergeScanExec {
     fn name(&self) -> &str {
         "MergeScanExec"
     }
+
+    fn statistics(&self) -> Result<Statistics> {
+        Ok(self
+            .statistics
+            .clone()
+            .unwrap_or_else(|| Statistics::new_unknown(&self.arrow_schema)))
+    }
 }
 
 impl DisplayAs for MergeScanExec {
-    fn fmt_as(&self, _t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "MergeScanExec: peers=[")?;
         for region_id in self.regions.iter() {
             write!(f, "{}, ", region_id)?;
         }
-        write!(f, "]")
+        write!(f, "]")?;
+        if let Some(hints) = self.query_ctx.extension(SCAN_HINTS_KEY) {
+            write!(f, ", scan_hints=[{hints}]")?;
+        }
+        if let (DisplayFormatType::Verbose, Some(statistics)) = (t, &self.statistics) {
+            write!(f, ", statistics=[{statistics}]")?;
+        }
+        Ok(())
     }
 }
 
@@ -16,18 +16,20 @@
 
 use std::sync::Arc;
 
+use arrow_schema::Schema as ArrowSchema;
 use async_trait::async_trait;
 use catalog::CatalogManagerRef;
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_telemetry::warn;
 use datafusion::common::Result;
 use datafusion::datasource::DefaultTableSource;
 use datafusion::execution::context::SessionState;
 use datafusion::physical_plan::ExecutionPlan;
 use datafusion::physical_planner::{ExtensionPlanner, PhysicalPlanner};
 use datafusion_common::tree_node::{TreeNode, TreeNodeRecursion, TreeNodeVisitor};
-use datafusion_common::{DataFusionError, TableReference};
+use datafusion_common::{ColumnStatistics, DataFusionError, Statistics, TableReference};
 use datafusion_expr::{LogicalPlan, UserDefinedLogicalNode};
-use session::context::QueryContext;
+use session::context::{QueryContext, QueryContextRef};
 use snafu::{OptionExt, ResultExt};
 use store_api::storage::RegionId;
 pub use table::metadata::TableType;
@@ -161,7 +163,9 @@ impl ExtensionPlanner for DistExtensionPlanner {
             .config()
             .get_extension()
             .unwrap_or_else(QueryContext::arc);
-        let merge_scan_plan = MergeScanExec::new(
+        self.capture_snapshots(&table_name, &regions, &query_ctx)
+            .await;
+        let mut merge_scan_plan = MergeScanExec::new(
             session_state,
             table_name,
             regions,
@@ -172,6 +176,9 @@ impl ExtensionPlanner for DistExtensionPlanner {
             session_state.config().target_partitions(),
             merge_scan.partition_cols().to_vec(),
         )?;
+        if let Some(statistics) = Self::scan_statistics(input_plan, &schema) {
+            merge_scan_plan = merge_scan_plan.with_statistics(statistics);
+        }
         Ok(Some(Arc::new(merge_scan_plan) as _))
     }
 }
@@ -184,6 +191,44 @@ impl DistExtensionPlanner {
         Ok(extractor.table_name)
     }
 
+    /// Returns the statistics of the table if the plan only scans, filters and projects
+    /// the table, with the columns of the `output_schema`. The statistics are estimations
+    /// as the filters are not taken into account.
+    fn scan_statistics(plan: &LogicalPlan, output_schema: &ArrowSchema) -> Option<Statistics> {
+        let scan = match plan {
+            LogicalPlan::TableScan(scan) => scan,
+            LogicalPlan::Projection(_) | LogicalPlan::Filter(_) | LogicalPlan::SubqueryAlias(_) => {
+                return Self::scan_statistics(plan.inputs().first()?, output_schema);
+            }
+            _ => return None,
+        };
+        let provider = &scan
+            .source
+            .as_any()
+            .downcast_ref::<DefaultTableSource>()?
+            .table_provider;
+        let table_statistics = provider.statistics()?;
+        let table_schema = provider.schema();
+
+        // Aligns the column statistics with the output columns.
+        let column_statistics = output_schema
+            .fields()
+            .iter()
+            .map(|field| {
+                table_schema
+                    .index_of(field.name())
+                    .ok()
+                    .and_then(|idx| table_statistics.column_statistics.get(idx).cloned())
+                    .unwrap_or_else(ColumnStatistics::new_unknown)
+            })
+            .collect();
+        Some(Statistics {
+            num_rows: table_statistics.num_rows.to_inexact(),
+            total_byte_size: table_statistics.total_byte_size.to_inexact(),
+            column_statistics,
+        })
+    }
+
     async fn get_regions(&self, table_name: &TableName) -> Result<Vec<RegionId>> {
         let table = self
             .catalog_manager
@@ -201,6 +246,32 @@ impl DistExtensionPlanner {
         Ok(table.table_info().region_ids())
     }
 
+    /// Captures the latest sequences of the regions into the query context, so every
+    /// scan of a region in the query, e.g. both sides of a self join, reads the same
+    /// snapshot no matter when it's executed. Sequences captured by earlier scans of
+    /// the query are kept.
+    async fn capture_snapshots(
+        &self,
+        table_name: &TableName,
+        regions: &[RegionId],
+        query_ctx: &QueryContextRef,
+    ) {
+        let snapshots = query_ctx.snapshots();
+        let regions = regions
+            .iter()
+            .filter(|region_id| !snapshots.contains_key(&region_id.as_u64()))
+            .copied()
+            .collect::<Vec<_>>();
+        if regions.is_empty() {
+            return;
+        }
+        match self.region_query_handler.region_sequences(&regions).await {
+            Ok(sequences) => query_ctx.set_snapshots(sequences),
+            // Scans without snapshots still read the latest data.
+            Err(e) => warn!(e; "Failed to capture the snapshot of table {table_name}"),
+        }
+    }
+
     /// Input logical plan is analyzed. Thus only call logical optimizer to optimize it.
     fn optimize_input_logical_plan(
         &self,
@@ -277,3 +348,165 @@ impl TreeNodeVisitor<'_> for TableNameExtractor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use common_recordbatch::{RecordBatch, SendableRecordBatchStream};
+    use datafusion::datasource::provider_as_source;
+    use datafusion::execution::SessionStateBuilder;
+    use datafusion::physical_optimizer::join_selection::JoinSelection;
+    use datafusion::physical_optimizer::PhysicalOptimizerRule;
+    use datafusion::physical_plan::joins::{HashJoinExec, PartitionMode};
+    use datafusion_common::config::ConfigOptions;
+    use datafusion_common::stats::Precision;
+    use datafusion_common::JoinType;
+    use datafusion_expr::LogicalPlanBuilder;
+    use datafusion_physical_expr::expressions::Column;
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::{ColumnSchema, Schema};
+    use datatypes::vectors::{StringVector, TimestampMillisecondVector};
+    use session::ReadPreference;
+    use table::test_util::MemTable;
+    use table::TableStatistics;
+
+    use super::*;
+    use crate::error::Result as QueryResult;
+    use crate::region_query::RegionQueryHandler;
+
+    struct NoopRegionQueryHandler;
+
+    #[async_trait]
+    impl RegionQueryHandler for NoopRegionQueryHandler {
+        async fn do_get(
+            &self,
+            _read_preference: ReadPreference,
+            _request: common_query::request::QueryRequest,
+        ) -> QueryResult<SendableRecordBatchStream> {
+            unreachable!()
+        }
+    }
+
+    fn merge_scan(
+        session_state: &SessionState,
+        table_name: &str,
+        statistics: Option<TableStatistics>,
+    ) -> Arc<dyn ExecutionPlan> {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+        ]));
+        let recordbatch = RecordBatch::new(
+            schema,
+            vec![
+                Arc::new(StringVector::from(vec!["host1"])) as _,
+                Arc::new(TimestampMillisecondVector::from_vec(vec![1000])) as _,
+            ],
+        )
+        .unwrap();
+        let table = MemTable::table(table_name, recordbatch);
+        let regions = table.table_info().region_ids();
+        let mut provider = DfTableProviderAdapter::new(table);
+        if let Some(statistics) = statistics {
+            provider = provider.with_statistics(statistics);
+        }
+        let plan =
+            LogicalPlanBuilder::scan(table_name, provider_as_source(Arc::new(provider)), None)
+                .unwrap()
+                .build()
+                .unwrap();
+
+        let schema = plan.schema().as_ref().into();
+        let mut merge_scan = MergeScanExec::new(
+            session_state,
+            TableName::new(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, table_name),
+            regions,
+            plan.clone(),
+            &schema,
+            Arc::new(NoopRegionQueryHandler),
+            QueryContext::arc(),
+            1,
+            vec![],
+        )
+        .unwrap();
+        if let Some(statistics) = DistExtensionPlanner::scan_statistics(&plan, &schema) {
+            merge_scan = merge_scan.with_statistics(statistics);
+        }
+        Arc::new(merge_scan)
+    }
+
+    fn statistics(num_rows: usize) -> TableStatistics {
+        TableStatistics {
+            num_rows: Some(num_rows),
+            total_byte_size: Some(num_rows * 64),
+            column_statistics: None,
+            is_exact: false,
+        }
+    }
+
+    /// Returns the name of the table on the build (left) side of the join.
+    fn build_side(session_state: &SessionState, metrics: Option<TableStatistics>) -> String {
+        let hosts = merge_scan(session_state, "hosts", Some(statistics(10)));
+        let metrics = merge_scan(session_state, "metrics", metrics);
+        // The big table is on the build side, as written in the query.
+        let join = HashJoinExec::try_new(
+            metrics,
+            hosts,
+            vec![(
+                Arc::new(Column::new("host", 0)) as _,
+                Arc::new(Column::new("host", 0)) as _,
+            )],
+            None,
+            &JoinType::Inner,
+            None,
+            PartitionMode::CollectLeft,
+            false,
+        )
+        .unwrap();
+
+        let mut plan = JoinSelection::new()
+            .optimize(Arc::new(join), &ConfigOptions::default())
+            .unwrap();
+        loop {
+            if let Some(join) = plan.as_any().downcast_ref::<HashJoinExec>() {
+                let build = join
+                    .left()
+                    .as_any()
+                    .downcast_ref::<MergeScanExec>()
+                    .unwrap();
+                return build.table().table_name.clone();
+            }
+            plan = plan.children()[0].clone();
+        }
+    }
+
+    #[test]
+    fn test_join_side_by_statistics() {
+        let session_state = SessionStateBuilder::new().with_default_features().build();
+
+        // Without statistics of the metrics table, the join keeps the order.
+        assert_eq!("metrics", build_side(&session_state, None));
+        // Builds the hash table with the small table once statistics are available.
+        assert_eq!(
+            "hosts",
+            build_side(&session_state, Some(statistics(1_000_000)))
+        );
+    }
+
+    #[test]
+    fn test_scan_statistics() {
+        let session_state = SessionStateBuilder::new().with_default_features().build();
+        let plan = merge_scan(&session_state, "metrics", Some(statistics(100)));
+        let statistics = plan.statistics().unwrap();
+        assert_eq!(Precision::Inexact(100), statistics.num_rows);
+        assert_eq!(Precision::Inexact(6400), statistics.total_byte_size);
+        assert_eq!(2, statistics.column_statistics.len());
+
+        let plan = merge_scan(&session_state, "metrics", None);
+        assert_eq!(Precision::Absent, plan.statistics().unwrap().num_rows);
+    }
+}
@@ -31,7 +31,9 @@ use datatypes::arrow::datatypes::SchemaRef;
 use snafu::ResultExt;
 use store_api::metadata::RegionMetadataRef;
 use store_api::region_engine::RegionEngineRef;
-use store_api::storage::{RegionId, ScanRequest, TimeSeriesDistribution, TimeSeriesRowSelector};
+use store_api::storage::{
+    RegionId, ScanHints, ScanRequest, TimeSeriesDistribution, TimeSeriesRowSelector, SCAN_HINTS_KEY,
+};
 use table::table::scan::RegionScanExec;
 
 use crate::error::{GetRegionMetadataSnafu, Result};
@@ -273,13 +275,14 @@ impl TableProviderFactory for DummyTableProviderFactory {
                     region_id,
                 })?;
 
-        let scan_request = ctx
-            .and_then(|c| c.get_snapshot(region_id.as_u64()))
-            .map(|seq| ScanRequest {
-                sequence: Some(seq),
-                ..Default::default()
-            })
-            .unwrap_or_default();
+        let scan_request = ScanRequest {
+            sequence: ctx.and_then(|c| c.get_snapshot(region_id.as_u64())),
+            scan_hints: ctx
+                .and_then(|c| c.extension(SCAN_HINTS_KEY))
+                .map(|hints| ScanHints::parse(hints).0)
+                .unwrap_or_default(),
+            ..Default::default()
+        };
 
         Ok(Arc::new(DummyTableProvider {
             region_id,
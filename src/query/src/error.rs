@@ -93,6 +93,14 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Invalid PromQL rewrite of flow {}: {}", flow_name, reason))]
+    InvalidPromRewrite {
+        flow_name: String,
+        reason: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Illegal access to catalog: {} and schema: {}", catalog, schema))]
     QueryAccessDenied {
         catalog: String,
@@ -172,6 +180,26 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display(
+        "Invalid range query, end time {}s is before start time {}s",
+        end,
+        start
+    ))]
+    InvalidQueryRange {
+        /// Start time in seconds since the unix epoch.
+        start: f64,
+        /// End time in seconds since the unix epoch.
+        end: f64,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid range query, step must be positive"))]
+    ZeroQueryStep {
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Missing required field: {}", name))]
     MissingRequiredField {
         name: String,
@@ -340,11 +368,14 @@ impl ErrorExt for Error {
             | ParseTimestamp { .. }
             | ParseFloat { .. }
             | MissingRequiredField { .. }
+            | InvalidQueryRange { .. }
+            | ZeroQueryStep { .. }
             | BuildRegex { .. }
             | ConvertSchema { .. }
             | AddSystemTimeOverflow { .. }
             | ColumnSchemaIncompatible { .. }
             | UnsupportedVariable { .. }
+            | InvalidPromRewrite { .. }
             | ColumnSchemaNoDefault { .. } => StatusCode::InvalidArguments,
 
             BuildBackend { .. } | ListObjects { .. } => StatusCode::StorageUnavailable,
@@ -22,6 +22,7 @@
 mod analyze;
 pub mod dataframe;
 pub mod datafusion;
+pub mod default_filter;
 pub mod dist_plan;
 pub mod dummy_catalog;
 pub mod error;
@@ -52,6 +52,17 @@ lazy_static! {
         "query merge scan regions"
     )
     .unwrap();
+    /// Lookups of the PromQL plan cache.
+    pub static ref PROMQL_PLAN_CACHE_ACCESS: IntCounterVec = register_int_counter_vec!(
+        "greptime_query_promql_plan_cache_access",
+        "query promql plan cache access",
+        &["result"]
+    )
+    .unwrap();
+    pub static ref PROMQL_PLAN_CACHE_HIT: IntCounter = PROMQL_PLAN_CACHE_ACCESS
+        .with_label_values(&["hit"]);
+    pub static ref PROMQL_PLAN_CACHE_MISS: IntCounter = PROMQL_PLAN_CACHE_ACCESS
+        .with_label_values(&["miss"]);
     pub static ref MERGE_SCAN_ERRORS_TOTAL: IntCounter = register_int_counter!(
         "greptime_query_merge_scan_errors_total",
         "query merge scan errors total"
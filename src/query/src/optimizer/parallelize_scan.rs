@@ -76,7 +76,9 @@ impl ParallelizeScan {
 
                     let ranges = region_scan_exec.get_partition_ranges();
                     let total_range_num = ranges.len();
-                    let expected_partition_num = config.execution.target_partitions;
+                    let expected_partition_num = region_scan_exec
+                        .parallelism()
+                        .unwrap_or(config.execution.target_partitions);
 
                     // assign ranges to each partition
                     let mut partition_ranges =
@@ -13,27 +13,32 @@
 // limitations under the License.
 
 use std::any::Any;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use chrono::DateTime;
 use common_error::ext::{BoxedError, PlainError};
 use common_error::status_code::StatusCode;
 use common_telemetry::tracing;
+use promql::extension_plan::{INTERVAL_COLUMN, RANGE_END_COLUMN, RANGE_START_COLUMN};
 use promql_parser::parser::ast::{Extension as NodeExtension, ExtensionExpr};
 use promql_parser::parser::value::ValueType;
 use promql_parser::parser::Expr::Extension;
-use promql_parser::parser::{EvalStmt, Expr};
+use promql_parser::parser::{
+    token, AtModifier, BinaryExpr, Call, EvalStmt, Expr, NumberLiteral, ParenExpr,
+};
 use session::context::QueryContextRef;
-use snafu::{OptionExt, ResultExt};
+use snafu::{ensure, OptionExt, ResultExt};
 use sql::dialect::GreptimeDbDialect;
 use sql::parser::{ParseOptions, ParserContext};
 use sql::statements::statement::Statement;
 
 use crate::error::{
-    AddSystemTimeOverflowSnafu, MultipleStatementsSnafu, ParseFloatSnafu, ParseTimestampSnafu,
-    QueryParseSnafu, Result, UnimplementedSnafu,
+    AddSystemTimeOverflowSnafu, InvalidQueryRangeSnafu, MissingRequiredFieldSnafu,
+    MultipleStatementsSnafu, ParseFloatSnafu, ParseTimestampSnafu, QueryParseSnafu, Result,
+    UnimplementedSnafu, ZeroQueryStepSnafu,
 };
 use crate::metrics::{PARSE_PROMQL_ELAPSED, PARSE_SQL_ELAPSED};
 
@@ -43,6 +48,18 @@ pub const EXPLAIN_VERBOSE_NODE_NAME: &str = "EXPLAIN VERBOSE";
 pub const ANALYZE_NODE_NAME: &str = "ANALYZE";
 pub const ANALYZE_VERBOSE_NODE_NAME: &str = "ANALYZE VERBOSE";
 
+/// The placeholder timestamp in seconds (0001-01-01T00:00:00Z) that the non-standard
+/// `@ latest()` modifier is parsed into, as the upstream parser doesn't know it.
+const LATEST_AT_PLACEHOLDER_SECS: u64 = 62_135_596_800;
+
+/// The name of the non-standard `ema_over_steps(alpha, v)` function, see
+/// [ema_over_steps_alpha].
+pub const EMA_OVER_STEPS_FUNCTION: &str = "ema_over_steps";
+
+/// The placeholder that the alpha of `ema_over_steps()` is added to when it's parsed
+/// into `histogram_quantile()`, as the upstream parser doesn't know the function.
+const EMA_OVER_STEPS_PLACEHOLDER: u64 = 9_007_199_254_740_991;
+
 #[derive(Debug, Clone)]
 pub enum QueryStatement {
     Sql(Statement),
@@ -92,6 +109,16 @@ impl QueryStatement {
     }
 }
 
+/// The query context extension to return the stored samples of a vector selector
+/// instead of the values aligned to the steps, see
+/// [PromPlannerOptions::raw_samples](crate::promql::planner::PromPlannerOptions::raw_samples).
+pub const PROMQL_RAW_SAMPLES_KEY: &str = "promql_raw_samples";
+
+/// The query context extension to return the `__name__` label of the result series
+/// as a column, see
+/// [PromPlannerOptions::metric_name_column](crate::promql::planner::PromPlannerOptions::metric_name_column).
+pub const PROMQL_METRIC_NAME_COLUMN_KEY: &str = "promql_metric_name_column";
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PromQuery {
     pub query: String,
@@ -113,6 +140,150 @@ impl Default for PromQuery {
     }
 }
 
+/// A PromQL range query with validated parameters. An instant query is a range
+/// query whose start and end are the same.
+///
+/// Use [RangeQuery::builder] to create it.
+#[derive(Debug, Clone)]
+pub struct RangeQuery {
+    expr: Expr,
+    start: SystemTime,
+    end: SystemTime,
+    step: Duration,
+    lookback_delta: Duration,
+}
+
+impl RangeQuery {
+    pub fn builder() -> RangeQueryBuilder {
+        RangeQueryBuilder::default()
+    }
+
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    pub fn start(&self) -> SystemTime {
+        self.start
+    }
+
+    pub fn end(&self) -> SystemTime {
+        self.end
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    pub fn lookback_delta(&self) -> Duration {
+        self.lookback_delta
+    }
+}
+
+impl From<RangeQuery> for EvalStmt {
+    fn from(query: RangeQuery) -> Self {
+        EvalStmt {
+            expr: query.expr,
+            start: query.start,
+            end: query.end,
+            interval: query.step,
+            lookback_delta: query.lookback_delta,
+        }
+    }
+}
+
+/// Builder of [RangeQuery]. The query, start, end and step are required, the
+/// lookback delta is [DEFAULT_LOOKBACK_STRING] by default.
+#[derive(Debug, Default)]
+pub struct RangeQueryBuilder {
+    query: Option<String>,
+    start: Option<SystemTime>,
+    end: Option<SystemTime>,
+    step: Option<Duration>,
+    lookback_delta: Option<Duration>,
+}
+
+impl RangeQueryBuilder {
+    /// Sets the PromQL expression to parse.
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.query = Some(query.into());
+        self
+    }
+
+    pub fn start(mut self, start: SystemTime) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    pub fn end(mut self, end: SystemTime) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    pub fn step(mut self, step: Duration) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    pub fn lookback_delta(mut self, lookback_delta: Duration) -> Self {
+        self.lookback_delta = Some(lookback_delta);
+        self
+    }
+
+    /// Validates the parameters and parses the query.
+    ///
+    /// Returns an error if a required parameter is missing, the end is before the
+    /// start, the step is zero or the query is not valid PromQL.
+    pub fn build(self) -> Result<RangeQuery> {
+        let query = self
+            .query
+            .context(MissingRequiredFieldSnafu { name: "query" })?;
+        let start = self
+            .start
+            .context(MissingRequiredFieldSnafu { name: "start" })?;
+        let end = self
+            .end
+            .context(MissingRequiredFieldSnafu { name: "end" })?;
+        let step = self
+            .step
+            .context(MissingRequiredFieldSnafu { name: "step" })?;
+        let lookback_delta = match self.lookback_delta {
+            Some(lookback_delta) => lookback_delta,
+            // Safety: the default lookback is a valid duration.
+            None => promql_parser::util::parse_duration(DEFAULT_LOOKBACK_STRING).unwrap(),
+        };
+        ensure!(
+            start <= end,
+            InvalidQueryRangeSnafu {
+                start: unix_secs(start),
+                end: unix_secs(end),
+            }
+        );
+        ensure!(!step.is_zero(), ZeroQueryStepSnafu);
+
+        let range = end.duration_since(start).unwrap_or_default();
+        let rewritten = rewrite_grafana_variables(&query, step, range);
+        let expr = promql_parser::parser::parse(&rewrite_non_standard(&rewritten))
+            .map_err(|msg| BoxedError::new(PlainError::new(msg, StatusCode::InvalidArguments)))
+            .context(QueryParseSnafu { query: &query })?;
+
+        Ok(RangeQuery {
+            expr,
+            start,
+            end,
+            step,
+            lookback_delta,
+        })
+    }
+}
+
+/// Returns the seconds of `time` since the unix epoch, negative if it's before.
+fn unix_secs(time: SystemTime) -> f64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    }
+}
+
 /// Query language parser, supports parsing SQL and PromQL
 pub struct QueryLanguageParser {}
 
@@ -139,12 +310,6 @@ impl QueryLanguageParser {
     pub fn parse_promql(query: &PromQuery, _query_ctx: &QueryContextRef) -> Result<QueryStatement> {
         let _timer = PARSE_PROMQL_ELAPSED.start_timer();
 
-        let expr = promql_parser::parser::parse(&query.query)
-            .map_err(|msg| BoxedError::new(PlainError::new(msg, StatusCode::InvalidArguments)))
-            .context(QueryParseSnafu {
-                query: &query.query,
-            })?;
-
         let start = Self::parse_promql_timestamp(&query.start)
             .map_err(BoxedError::new)
             .context(QueryParseSnafu {
@@ -177,15 +342,15 @@ impl QueryLanguageParser {
                 query: &query.query,
             })?;
 
-        let eval_stmt = EvalStmt {
-            expr,
-            start,
-            end,
-            interval: step,
-            lookback_delta,
-        };
+        let range_query = RangeQuery::builder()
+            .query(&query.query)
+            .start(start)
+            .end(end)
+            .step(step)
+            .lookback_delta(lookback_delta)
+            .build()?;
 
-        Ok(QueryStatement::Promql(eval_stmt))
+        Ok(QueryStatement::Promql(range_query.into()))
     }
 
     pub fn parse_promql_timestamp(timestamp: &str) -> Result<SystemTime> {
@@ -213,6 +378,251 @@ impl QueryLanguageParser {
     }
 }
 
+/// Returns true if `at` is the non-standard `@ latest()` modifier, which resolves to
+/// the latest sample time of each selected series instead of a fixed timestamp.
+///
+/// Prometheus doesn't have this modifier. Unlike other `@` modifiers, every series
+/// is evaluated at its own latest sample before the query end, without the lookback
+/// delta, and only instant vector selectors support it. It's only planned when the
+/// `promql_enable_latest_at` option is on.
+pub fn is_latest_at(at: &AtModifier) -> bool {
+    match at {
+        AtModifier::At(time) => {
+            UNIX_EPOCH.duration_since(*time).ok()
+                == Some(Duration::from_secs(LATEST_AT_PLACEHOLDER_SECS))
+        }
+        AtModifier::Start | AtModifier::End => false,
+    }
+}
+
+/// Rewrites `@ latest()` outside string literals to `@` the placeholder timestamp.
+pub(crate) fn rewrite_latest_at(query: &str) -> Cow<'_, str> {
+    let mut result = String::new();
+    let mut copied = 0;
+    let mut quote = None;
+    let mut chars = query.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                let _ = chars.next();
+            }
+            (Some(q), c) if q == c => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '@') => {
+                let rest = &query[i + 1..];
+                let Some(len) = latest_call_len(rest) else {
+                    continue;
+                };
+                result.push_str(&query[copied..=i]);
+                result.push_str(&format!(" -{LATEST_AT_PLACEHOLDER_SECS}"));
+                copied = i + 1 + len;
+                // skip the `latest()` call
+                while chars.offset() < copied {
+                    let _ = chars.next();
+                }
+            }
+            (None, _) => {}
+        }
+    }
+
+    if copied == 0 {
+        return Cow::Borrowed(query);
+    }
+    result.push_str(&query[copied..]);
+    Cow::Owned(result)
+}
+
+/// Grafana's global variables that can be evaluated natively, ordered so that a
+/// variable comes before the ones that are its prefix.
+///
+/// Numeric variables are rewritten into selectors of the columns `EmptyMetric`
+/// provides, while durations are rewritten into duration literals.
+const GRAFANA_VARIABLES: [&str; 5] = [
+    "$__interval_ms",
+    "$__interval",
+    "$__range_ms",
+    "$__range_s",
+    "$__range",
+];
+
+/// Rewrites Grafana's `$__interval` and `$__range` variables outside string literals,
+/// so dashboards can send their queries without substituting them.
+pub(crate) fn rewrite_grafana_variables(
+    query: &str,
+    interval: Duration,
+    range: Duration,
+) -> Cow<'_, str> {
+    if !query.contains("$__") {
+        return Cow::Borrowed(query);
+    }
+
+    let mut result = String::new();
+    let mut copied = 0;
+    let mut quote = None;
+    let mut chars = query.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                let _ = chars.next();
+            }
+            (Some(q), c) if q == c => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '$') => {
+                let rest = &query[i..];
+                let Some(variable) = GRAFANA_VARIABLES.into_iter().find(|variable| {
+                    rest.strip_prefix(variable).is_some_and(|after| {
+                        !after.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+                    })
+                }) else {
+                    continue;
+                };
+                result.push_str(&query[copied..i]);
+                let replacement = match variable {
+                    "$__interval_ms" => INTERVAL_COLUMN.to_string(),
+                    "$__interval" => format!("{}ms", interval.as_millis()),
+                    "$__range_ms" => format!("({RANGE_END_COLUMN} - {RANGE_START_COLUMN})"),
+                    "$__range_s" => {
+                        format!("(({RANGE_END_COLUMN} - {RANGE_START_COLUMN}) / 1000)")
+                    }
+                    _ => format!("{}ms", range.as_millis()),
+                };
+                result.push_str(&replacement);
+                copied = i + variable.len();
+                while chars.offset() < copied {
+                    let _ = chars.next();
+                }
+            }
+            (None, _) => {}
+        }
+    }
+
+    if copied == 0 {
+        return Cow::Borrowed(query);
+    }
+    result.push_str(&query[copied..]);
+    Cow::Owned(result)
+}
+
+/// Returns the length of the `latest()` call at the beginning of `s`, including
+/// the leading whitespaces.
+fn latest_call_len(s: &str) -> Option<usize> {
+    let rest = s.trim_start();
+    let rest = rest.strip_prefix("latest")?.trim_start();
+    let rest = rest.strip_prefix('(')?.trim_start();
+    let rest = rest.strip_prefix(')')?;
+    Some(s.len() - rest.len())
+}
+
+/// Returns the alpha of the non-standard `ema_over_steps(alpha, v)` function if
+/// `call` is one, which smooths every series of `v` over the steps with an
+/// exponential moving average.
+///
+/// Prometheus doesn't have this function. Like `holt_winters()`, `alpha` is the
+/// smoothing factor in (0, 1] and higher values follow the input more closely.
+/// Unlike it, the average is taken over the evaluated steps of an instant vector,
+/// rather than the samples in a range.
+pub fn ema_over_steps_alpha(call: &Call) -> Option<&Expr> {
+    if call.func.name != "histogram_quantile" {
+        return None;
+    }
+    let Expr::Binary(BinaryExpr { lhs, op, rhs, .. }) = call.args.args.first()?.as_ref() else {
+        return None;
+    };
+    match (lhs.as_ref(), rhs.as_ref()) {
+        (Expr::NumberLiteral(NumberLiteral { val }), Expr::Paren(ParenExpr { expr }))
+            if *val == EMA_OVER_STEPS_PLACEHOLDER as f64 && op.id() == token::T_ADD =>
+        {
+            Some(expr)
+        }
+        _ => None,
+    }
+}
+
+/// Rewrites `ema_over_steps(alpha, v)` outside string literals to
+/// `histogram_quantile(<placeholder> + (alpha), v)`.
+pub(crate) fn rewrite_ema_over_steps(query: &str) -> Cow<'_, str> {
+    if !query.contains(EMA_OVER_STEPS_FUNCTION) {
+        return Cow::Borrowed(query);
+    }
+
+    let mut result = String::with_capacity(query.len());
+    let mut rewritten = false;
+    let mut quote = None;
+    let mut depth = 0usize;
+    // the depths of the rewritten calls whose alpha isn't closed yet
+    let mut pending = vec![];
+    let mut chars = query.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                result.push(c);
+                if let Some((_, escaped)) = chars.next() {
+                    result.push(escaped);
+                }
+                continue;
+            }
+            (Some(q), c) if q == c => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth = depth.saturating_sub(1),
+            (None, ',') if pending.last() == Some(&depth) => {
+                let _ = pending.pop();
+                result.push(')');
+            }
+            (None, _) => {
+                if let Some(len) = ema_over_steps_call_len(query, i) {
+                    result.push_str(&format!(
+                        "histogram_quantile({EMA_OVER_STEPS_PLACEHOLDER} + ("
+                    ));
+                    rewritten = true;
+                    depth += 1;
+                    pending.push(depth);
+                    // skip the `ema_over_steps(`
+                    while chars.offset() < i + len {
+                        let _ = chars.next();
+                    }
+                    continue;
+                }
+            }
+        }
+        result.push(c);
+    }
+
+    if rewritten {
+        Cow::Owned(result)
+    } else {
+        Cow::Borrowed(query)
+    }
+}
+
+/// Returns the length of `ema_over_steps(` if it starts at `start` of `query`.
+fn ema_over_steps_call_len(query: &str, start: usize) -> Option<usize> {
+    let is_identifier = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == ':';
+    if query[..start]
+        .chars()
+        .next_back()
+        .is_some_and(is_identifier)
+    {
+        return None;
+    }
+    let s = &query[start..];
+    let rest = s.strip_prefix(EMA_OVER_STEPS_FUNCTION)?.trim_start();
+    let rest = rest.strip_prefix('(')?;
+    Some(s.len() - rest.len())
+}
+
+/// Rewrites the non-standard syntax, `@ latest()` and `ema_over_steps()`, to what
+/// the upstream parser accepts.
+pub(crate) fn rewrite_non_standard(query: &str) -> Cow<'_, str> {
+    match rewrite_latest_at(query) {
+        Cow::Borrowed(query) => rewrite_ema_over_steps(query),
+        Cow::Owned(query) => Cow::Owned(rewrite_ema_over_steps(&query).into_owned()),
+    }
+}
+
 macro_rules! define_node_ast_extension {
     ($name:ident, $name_expr:ident, $expr_type:ty, $extension_name:expr) => {
         /// The implementation of the `$name_expr` extension AST node
@@ -272,9 +682,11 @@ define_node_ast_extension!(
 
 #[cfg(test)]
 mod test {
+    use common_error::ext::ErrorExt;
     use session::context::QueryContext;
 
     use super::*;
+    use crate::error::Error;
 
     // Detailed logic tests are covered in the parser crate.
     #[test]
@@ -376,4 +788,179 @@ mod test {
         let result = QueryLanguageParser::parse_promql(&promql, &QueryContext::arc()).unwrap();
         assert_eq!(format!("{result:?}"), expected);
     }
+
+    #[test]
+    fn build_range_query() {
+        let start = UNIX_EPOCH + Duration::from_secs(100);
+        let end = UNIX_EPOCH + Duration::from_secs(200);
+        let builder = || {
+            RangeQuery::builder()
+                .query("rate(http_requests_total[$__interval])")
+                .start(start)
+                .end(end)
+                .step(Duration::from_secs(10))
+        };
+
+        let query = builder().build().unwrap();
+        assert_eq!(start, query.start());
+        assert_eq!(end, query.end());
+        assert_eq!(Duration::from_secs(10), query.step());
+        assert_eq!(Duration::from_secs(300), query.lookback_delta());
+        assert_eq!("rate(http_requests_total[10s])", query.expr().prettify());
+
+        // Instant query.
+        let query = builder()
+            .end(start)
+            .lookback_delta(Duration::from_secs(60))
+            .build()
+            .unwrap();
+        assert_eq!(query.start(), query.end());
+        let stmt: EvalStmt = query.into();
+        assert_eq!(Duration::from_secs(60), stmt.lookback_delta);
+
+        let err = builder().start(end).end(start).build().unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidQueryRange { start, end, .. } if start == 200.0 && end == 100.0),
+            "{err:?}"
+        );
+        assert_eq!(StatusCode::InvalidArguments, err.status_code());
+
+        let err = builder().step(Duration::ZERO).build().unwrap_err();
+        assert!(matches!(err, Error::ZeroQueryStep { .. }), "{err:?}");
+        assert_eq!(StatusCode::InvalidArguments, err.status_code());
+
+        let err = RangeQuery::builder()
+            .query("up")
+            .start(start)
+            .step(Duration::from_secs(10))
+            .build()
+            .unwrap_err();
+        assert!(
+            matches!(&err, Error::MissingRequiredField { name, .. } if name == "end"),
+            "{err:?}"
+        );
+
+        let err = builder().query("sum(").build().unwrap_err();
+        assert!(matches!(err, Error::QueryParse { .. }), "{err:?}");
+
+        // Also validated when parsing the strings.
+        let promql = PromQuery {
+            query: "up".to_string(),
+            start: "200".to_string(),
+            end: "100".to_string(),
+            step: "10s".to_string(),
+            lookback: "5m".to_string(),
+        };
+        let err = QueryLanguageParser::parse_promql(&promql, &QueryContext::arc()).unwrap_err();
+        assert!(matches!(err, Error::InvalidQueryRange { .. }), "{err:?}");
+    }
+
+    #[test]
+    fn parse_promql_grafana_variables() {
+        let interval = Duration::from_secs(30);
+        let range = Duration::from_secs(3600);
+        assert_eq!(
+            "rate(foo[30000ms]) * __interval__",
+            rewrite_grafana_variables("rate(foo[$__interval]) * $__interval_ms", interval, range)
+        );
+        assert_eq!(
+            "sum_over_time(foo[3600000ms]) / ((__range_end__ - __range_start__) / 1000)",
+            rewrite_grafana_variables("sum_over_time(foo[$__range]) / $__range_s", interval, range)
+        );
+        // string literals and unknown variables are kept as-is
+        assert!(matches!(
+            rewrite_grafana_variables(r#"foo{a="$__interval"} + $__intervals"#, interval, range),
+            Cow::Borrowed(r#"foo{a="$__interval"} + $__intervals"#)
+        ));
+
+        let promql = PromQuery {
+            query: "time() / $__interval_ms".to_string(),
+            start: "0".to_string(),
+            end: "60".to_string(),
+            step: "15s".to_string(),
+            ..Default::default()
+        };
+        let QueryStatement::Promql(stmt) =
+            QueryLanguageParser::parse_promql(&promql, &QueryContext::arc()).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!("time() / __interval__", stmt.expr.to_string());
+    }
+
+    #[test]
+    fn parse_promql_latest_at() {
+        assert_eq!(
+            "foo @ -62135596800 + bar",
+            rewrite_latest_at("foo @ latest() + bar")
+        );
+        assert_eq!(
+            "rate(foo[5m] @ -62135596800)",
+            rewrite_latest_at("rate(foo[5m] @latest ( ))")
+        );
+        // string literals are kept as-is
+        assert_eq!(
+            r#"foo{a="@ latest()"} @ -62135596800"#,
+            rewrite_latest_at(r#"foo{a="@ latest()"} @ latest()"#)
+        );
+        assert!(matches!(
+            rewrite_latest_at("foo @ end()"),
+            Cow::Borrowed("foo @ end()")
+        ));
+
+        let promql = PromQuery {
+            query: "foo @ latest() offset 1m".to_string(),
+            ..Default::default()
+        };
+        let QueryStatement::Promql(stmt) =
+            QueryLanguageParser::parse_promql(&promql, &QueryContext::arc()).unwrap()
+        else {
+            unreachable!()
+        };
+        let Expr::VectorSelector(selector) = stmt.expr else {
+            unreachable!()
+        };
+        assert!(is_latest_at(selector.at.as_ref().unwrap()));
+        assert!(!is_latest_at(&AtModifier::End));
+        assert!(!is_latest_at(&AtModifier::At(UNIX_EPOCH)));
+    }
+
+    #[test]
+    fn parse_promql_ema_over_steps() {
+        assert_eq!(
+            "histogram_quantile(9007199254740991 + (0.5), rate(foo[5m]))",
+            rewrite_ema_over_steps("ema_over_steps(0.5, rate(foo[5m]))")
+        );
+        // nested calls, and names or string literals containing the function are kept
+        assert_eq!(
+            r#"histogram_quantile(9007199254740991 + ( 0.3), histogram_quantile(9007199254740991 + (1 - 0.5), my_ema_over_steps{a="ema_over_steps(1, b)"}))"#,
+            rewrite_ema_over_steps(
+                r#"ema_over_steps ( 0.3, ema_over_steps(1 - 0.5, my_ema_over_steps{a="ema_over_steps(1, b)"}))"#
+            )
+        );
+        assert!(matches!(
+            rewrite_ema_over_steps("my_ema_over_steps"),
+            Cow::Borrowed("my_ema_over_steps")
+        ));
+
+        let promql = PromQuery {
+            query: "ema_over_steps(0.5, foo @ latest())".to_string(),
+            ..Default::default()
+        };
+        let QueryStatement::Promql(stmt) =
+            QueryLanguageParser::parse_promql(&promql, &QueryContext::arc()).unwrap()
+        else {
+            unreachable!()
+        };
+        let Expr::Call(call) = &stmt.expr else {
+            unreachable!()
+        };
+        assert_eq!("0.5", ema_over_steps_alpha(call).unwrap().to_string());
+        let Expr::Call(call) =
+            promql_parser::parser::parse("histogram_quantile(0.5, foo)").unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(ema_over_steps_alpha(&call).is_none());
+    }
 }
@@ -31,10 +31,13 @@ use snafu::ResultExt;
 use sql::ast::Expr as SqlExpr;
 use sql::statements::statement::Statement;
 
+use crate::default_filter::apply_default_filters;
 use crate::error::{PlanSqlSnafu, QueryPlanSnafu, Result, SqlSnafu};
 use crate::log_query::planner::LogQueryPlanner;
-use crate::parser::QueryStatement;
-use crate::promql::planner::PromPlanner;
+use crate::parser::{QueryStatement, PROMQL_METRIC_NAME_COLUMN_KEY, PROMQL_RAW_SAMPLES_KEY};
+use crate::promql::plan_cache::PlanCacheKey;
+use crate::promql::planner::{PromPlanner, PromPlannerOptions};
+use crate::promql::rollup::VersionedPromRollups;
 use crate::query_engine::{DefaultPlanDecoder, QueryEngineState};
 use crate::range_select::plan_rewrite::RangePlanRewriter;
 use crate::{DfContextProviderAdapter, QueryEngineContext};
@@ -156,8 +159,49 @@ impl DfLogicalPlanner {
         Ok(sql_to_rel.sql_to_expr(sql.into(), schema, &mut PlannerContext::new())?)
     }
 
-    #[tracing::instrument(skip_all)]
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            query = %stmt.expr,
+            step_ms = stmt.interval.as_millis() as u64,
+            cache_hit = tracing::field::Empty,
+        )
+    )]
     async fn plan_pql(&self, stmt: &EvalStmt, query_ctx: QueryContextRef) -> Result<LogicalPlan> {
+        let (version, rollups) = self
+            .engine_state
+            .promql_rollups()
+            .rollups(query_ctx.current_catalog(), &query_ctx.current_schema());
+        let options = PromPlannerOptions {
+            timezone: self.engine_state.promql_timezone(),
+            enable_latest_at: self.engine_state.promql_enable_latest_at(),
+            enable_negative_offset: self.engine_state.promql_enable_negative_offset(),
+            max_at_lookahead: self.engine_state.promql_max_at_lookahead(),
+            integer_counts: self.engine_state.promql_integer_counts(),
+            created_timestamps: self.engine_state.promql_created_timestamps(),
+            rate_first_sample: self.engine_state.promql_rate_first_sample(),
+            fill_forward: self.engine_state.promql_fill_forward(),
+            propagate_nan: self.engine_state.promql_propagate_nan(),
+            raw_samples: query_ctx.extension(PROMQL_RAW_SAMPLES_KEY) == Some("true"),
+            resolve_bucket_suffix: self.engine_state.promql_resolve_bucket_suffix(),
+            staleness_delta: self.engine_state.promql_staleness_delta(),
+            metric_name_column: query_ctx.extension(PROMQL_METRIC_NAME_COLUMN_KEY) == Some("true"),
+            rollups: VersionedPromRollups {
+                version,
+                rollups: Arc::new(rollups),
+            },
+        };
+        let plan_cache = self.engine_state.promql_plan_cache();
+        let cache_key = PlanCacheKey::new(stmt, &query_ctx, &options);
+        if let Some(plan) = plan_cache
+            .get(&cache_key, self.engine_state.catalog_manager(), &query_ctx)
+            .await?
+        {
+            tracing::Span::current().record("cache_hit", true);
+            return Ok(plan);
+        }
+        tracing::Span::current().record("cache_hit", false);
+
         let plan_decoder = Arc::new(DefaultPlanDecoder::new(
             self.session_state.clone(),
             &query_ctx,
@@ -165,17 +209,27 @@ impl DfLogicalPlanner {
         let table_provider = DfTableSourceProvider::new(
             self.engine_state.catalog_manager().clone(),
             self.engine_state.disallow_cross_catalog_query(),
-            query_ctx,
+            query_ctx.clone(),
             plan_decoder,
             self.session_state
                 .config_options()
                 .sql_parser
                 .enable_ident_normalization,
         );
-        PromPlanner::stmt_to_plan(table_provider, stmt, &self.session_state)
-            .await
-            .map_err(BoxedError::new)
-            .context(QueryPlanSnafu)
+        let plan = PromPlanner::stmt_to_plan_with_options(
+            table_provider,
+            stmt,
+            &options,
+            &self.session_state,
+        )
+        .await
+        .map_err(BoxedError::new)
+        .context(QueryPlanSnafu)?;
+        // The warnings raised by the planner would be lost on cache hits.
+        if query_ctx.warning().is_none() {
+            plan_cache.insert(cache_key, &plan);
+        }
+        Ok(plan)
     }
 
     #[tracing::instrument(skip_all)]
@@ -188,10 +242,15 @@ impl DfLogicalPlanner {
 impl LogicalPlanner for DfLogicalPlanner {
     #[tracing::instrument(skip_all)]
     async fn plan(&self, stmt: &QueryStatement, query_ctx: QueryContextRef) -> Result<LogicalPlan> {
-        match stmt {
-            QueryStatement::Sql(stmt) => self.plan_sql(stmt, query_ctx).await,
-            QueryStatement::Promql(stmt) => self.plan_pql(stmt, query_ctx).await,
-        }
+        let plan = match stmt {
+            QueryStatement::Sql(stmt) => self.plan_sql(stmt, query_ctx.clone()).await?,
+            QueryStatement::Promql(stmt) => self.plan_pql(stmt, query_ctx.clone()).await?,
+        };
+        Ok(apply_default_filters(
+            plan,
+            &self.session_state,
+            &query_ctx,
+        )?)
     }
 
     async fn plan_logs_query(
@@ -206,7 +265,7 @@ impl LogicalPlanner for DfLogicalPlanner {
         let table_provider = DfTableSourceProvider::new(
             self.engine_state.catalog_manager().clone(),
             self.engine_state.disallow_cross_catalog_query(),
-            query_ctx,
+            query_ctx.clone(),
             plan_decoder,
             self.session_state
                 .config_options()
@@ -215,11 +274,16 @@ impl LogicalPlanner for DfLogicalPlanner {
         );
 
         let mut planner = LogQueryPlanner::new(table_provider, self.session_state.clone());
-        planner
+        let plan = planner
             .query_to_plan(query)
             .await
             .map_err(BoxedError::new)
-            .context(QueryPlanSnafu)
+            .context(QueryPlanSnafu)?;
+        Ok(apply_default_filters(
+            plan,
+            &self.session_state,
+            &query_ctx,
+        )?)
     }
 
     fn optimize(&self, plan: LogicalPlan) -> Result<LogicalPlan> {
@@ -12,6 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod analysis;
 pub mod error;
 pub mod label_values;
+pub mod plan_cache;
 pub mod planner;
+pub mod rollup;
+
+/// The extension nodes of PromQL plans, re-exported for embedding the planner.
+pub use promql::extension_plan;
+pub use promql::extension_plan::register_promql_extensions;
+/// How PromQL `rate()` and `increase()` treat the start of the range.
+pub use promql::functions::RateFirstSamplePolicy;
@@ -0,0 +1,314 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Static analysis of PromQL queries, e.g. for authorizing or caching a query
+//! before planning it.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use promql::extension_plan::Millisecond;
+use promql_parser::label::{MatchOp, METRIC_NAME};
+use promql_parser::parser::value::ValueType;
+use promql_parser::parser::{
+    AggregateExpr, AtModifier, BinaryExpr, Call, Expr as PromExpr, MatrixSelector, Offset,
+    ParenExpr, SubqueryExpr, UnaryExpr, VectorSelector,
+};
+
+use crate::parser::{is_latest_at, rewrite_non_standard};
+use crate::promql::error::{ParsePromQLSnafu, Result};
+
+/// What a PromQL query reads, returned by [analyze_query].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct QueryAnalysis {
+    /// Distinct vector selectors in the query, including the ones in matrix selectors.
+    pub selectors: Vec<VectorSelector>,
+    /// The smallest and largest timestamps in milliseconds the query reads, or
+    /// `None` if the query doesn't read any data.
+    pub time_range: Option<(Millisecond, Millisecond)>,
+}
+
+/// The metadata of a PromQL expression, returned by [validate].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExprMetadata {
+    /// The type of the evaluation result.
+    pub result_type: ValueType,
+    /// Distinct names of the metrics the expression selects, in the order they
+    /// appear. Selectors without an exact metric name are not included.
+    pub metrics: Vec<String>,
+}
+
+/// Parses and type-checks `query` without planning or reading any data.
+pub fn validate(query: &str) -> Result<ExprMetadata> {
+    let expr = promql_parser::parser::parse(&rewrite_non_standard(query)).map_err(|reason| {
+        ParsePromQLSnafu {
+            query: query.to_string(),
+            reason,
+        }
+        .build()
+    })?;
+    // The selectors don't depend on the evaluation range.
+    let analysis = analyze_query(&expr, UNIX_EPOCH, UNIX_EPOCH, Duration::ZERO);
+    let mut metrics: Vec<String> = Vec::new();
+    for selector in &analysis.selectors {
+        let name = selector.name.clone().or_else(|| {
+            selector
+                .matchers
+                .matchers
+                .iter()
+                .find(|m| m.name == METRIC_NAME && matches!(m.op, MatchOp::Equal))
+                .map(|m| m.value.clone())
+        });
+        if let Some(name) = name
+            && !metrics.contains(&name)
+        {
+            metrics.push(name);
+        }
+    }
+
+    Ok(ExprMetadata {
+        result_type: expr.value_type(),
+        metrics,
+    })
+}
+
+/// Analyzes the selectors and the time range that `expr` reads when it's
+/// evaluated from `start` to `end`. Offsets, `@` modifiers, ranges of matrix
+/// selectors and subqueries, and the lookback delta of instant selectors are
+/// taken into account.
+pub fn analyze_query(
+    expr: &PromExpr,
+    start: SystemTime,
+    end: SystemTime,
+    lookback_delta: Duration,
+) -> QueryAnalysis {
+    let mut analyzer = Analyzer {
+        query_start: to_millis(start),
+        query_end: to_millis(end),
+        lookback_delta: lookback_delta.as_millis() as _,
+        analysis: QueryAnalysis::default(),
+    };
+    analyzer.analyze(expr, analyzer.query_start, analyzer.query_end);
+    analyzer.analysis
+}
+
+struct Analyzer {
+    query_start: Millisecond,
+    query_end: Millisecond,
+    lookback_delta: Millisecond,
+    analysis: QueryAnalysis,
+}
+
+impl Analyzer {
+    /// Analyzes `expr` evaluated within `[start, end]`.
+    fn analyze(&mut self, expr: &PromExpr, start: Millisecond, end: Millisecond) {
+        match expr {
+            PromExpr::Aggregate(AggregateExpr { expr, param, .. }) => {
+                if let Some(param) = param {
+                    self.analyze(param, start, end);
+                }
+                self.analyze(expr, start, end);
+            }
+            PromExpr::Unary(UnaryExpr { expr }) | PromExpr::Paren(ParenExpr { expr }) => {
+                self.analyze(expr, start, end)
+            }
+            PromExpr::Binary(BinaryExpr { lhs, rhs, .. }) => {
+                self.analyze(lhs, start, end);
+                self.analyze(rhs, start, end);
+            }
+            PromExpr::Subquery(SubqueryExpr {
+                expr,
+                offset,
+                at,
+                range,
+                ..
+            }) => {
+                let (start, end) = self.shift(start, end, offset, at);
+                self.analyze(expr, start - range.as_millis() as Millisecond, end);
+            }
+            PromExpr::VectorSelector(selector) => {
+                self.add_selector(selector, start, end, self.lookback_delta)
+            }
+            PromExpr::MatrixSelector(MatrixSelector { vs, range }) => {
+                self.add_selector(vs, start, end, range.as_millis() as _)
+            }
+            PromExpr::Call(Call { args, .. }) => {
+                for arg in &args.args {
+                    self.analyze(arg, start, end);
+                }
+            }
+            PromExpr::Extension(ext) => {
+                for child in ext.expr.children().iter() {
+                    self.analyze(child, start, end);
+                }
+            }
+            PromExpr::NumberLiteral(_) | PromExpr::StringLiteral(_) => {}
+        }
+    }
+
+    /// Records the selector that reads `range` before each evaluation timestamp
+    /// within `[start, end]`.
+    fn add_selector(
+        &mut self,
+        selector: &VectorSelector,
+        start: Millisecond,
+        end: Millisecond,
+        range: Millisecond,
+    ) {
+        if !self.analysis.selectors.contains(selector) {
+            self.analysis.selectors.push(selector.clone());
+        }
+
+        let (start, end) = self.shift(start, end, &selector.offset, &selector.at);
+        // `@ latest()` reads the latest sample however old it is
+        let start = if selector.at.as_ref().is_some_and(is_latest_at) {
+            Millisecond::MIN
+        } else {
+            start - range
+        };
+        self.analysis.time_range = Some(match self.analysis.time_range {
+            Some((min, max)) => (min.min(start), max.max(end)),
+            None => (start, end),
+        });
+    }
+
+    /// Applies the `@` modifier and the offset to the evaluation range.
+    fn shift(
+        &self,
+        start: Millisecond,
+        end: Millisecond,
+        offset: &Option<Offset>,
+        at: &Option<AtModifier>,
+    ) -> (Millisecond, Millisecond) {
+        let (start, end) = match at {
+            Some(AtModifier::Start) => (self.query_start, self.query_start),
+            Some(AtModifier::End) => (self.query_end, self.query_end),
+            Some(at) if is_latest_at(at) => (self.query_end, self.query_end),
+            Some(AtModifier::At(time)) => (to_millis(*time), to_millis(*time)),
+            None => (start, end),
+        };
+        let offset = match offset {
+            Some(Offset::Pos(duration)) => duration.as_millis() as Millisecond,
+            Some(Offset::Neg(duration)) => -(duration.as_millis() as Millisecond),
+            None => 0,
+        };
+        (start - offset, end - offset)
+    }
+}
+
+fn to_millis(time: SystemTime) -> Millisecond {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_millis() as _,
+        Err(e) => -(e.duration().as_millis() as Millisecond),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::rewrite_latest_at;
+
+    const LOOKBACK: Duration = Duration::from_secs(300);
+
+    fn analyze(query: &str, start_secs: u64, end_secs: u64) -> QueryAnalysis {
+        let expr = promql_parser::parser::parse(&rewrite_latest_at(query)).unwrap();
+        analyze_query(
+            &expr,
+            UNIX_EPOCH + Duration::from_secs(start_secs),
+            UNIX_EPOCH + Duration::from_secs(end_secs),
+            LOOKBACK,
+        )
+    }
+
+    fn selector_names(analysis: &QueryAnalysis) -> Vec<&str> {
+        analysis
+            .selectors
+            .iter()
+            .map(|s| s.name.as_deref().unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn test_analyze_offset() {
+        let analysis = analyze("rate(foo[5m] offset 1h)", 7200, 10800);
+        assert_eq!(vec!["foo"], selector_names(&analysis));
+        // Reads from `start - 1h - 5m` to `end - 1h`.
+        assert_eq!(Some((3_300_000, 7_200_000)), analysis.time_range);
+
+        let analysis = analyze("foo offset -10m", 7200, 10800);
+        assert_eq!(Some((7_500_000, 11_400_000)), analysis.time_range);
+    }
+
+    #[test]
+    fn test_analyze_at() {
+        let analysis = analyze("foo @ 1000 + bar @ end()", 7200, 10800);
+        assert_eq!(vec!["foo", "bar"], selector_names(&analysis));
+        assert_eq!(Some((700_000, 10_800_000)), analysis.time_range);
+
+        let analysis = analyze("sum_over_time(foo[10m] @ start() offset 5m)", 7200, 10800);
+        assert_eq!(Some((6_300_000, 6_900_000)), analysis.time_range);
+    }
+
+    #[test]
+    fn test_analyze_latest_at() {
+        let analysis = analyze("foo @ latest() + bar", 7200, 10800);
+        assert_eq!(vec!["foo", "bar"], selector_names(&analysis));
+        // The latest sample of `foo` may be read however old it is.
+        assert_eq!(Some((Millisecond::MIN, 10_800_000)), analysis.time_range);
+    }
+
+    #[test]
+    fn test_analyze_subquery() {
+        let analysis = analyze(
+            r#"max_over_time(rate(foo{a="b"}[1m])[30m:1m] offset 1h) / on() foo{a="b"}"#,
+            7200,
+            10800,
+        );
+        // The selectors are deduplicated.
+        assert_eq!(1, analysis.selectors.len());
+        // The subquery starts 30m earlier and the rate reads 1m more.
+        assert_eq!(Some((1_740_000, 10_800_000)), analysis.time_range);
+    }
+
+    #[test]
+    fn test_validate() {
+        let metadata = validate("1 + 2 * time()").unwrap();
+        assert_eq!(ValueType::Scalar, metadata.result_type);
+        assert!(metadata.metrics.is_empty());
+
+        let metadata =
+            validate(r#"sum by (job) (rate(foo[5m])) / on(job) {__name__="bar"} + foo"#).unwrap();
+        assert_eq!(ValueType::Vector, metadata.result_type);
+        assert_eq!(vec!["foo", "bar"], metadata.metrics);
+
+        let metadata = validate("foo[5m] offset 1h").unwrap();
+        assert_eq!(ValueType::Matrix, metadata.result_type);
+        assert_eq!(vec!["foo"], metadata.metrics);
+
+        let metadata = validate("foo @ latest()").unwrap();
+        assert_eq!(ValueType::Vector, metadata.result_type);
+        assert_eq!(vec!["foo"], metadata.metrics);
+
+        // Type errors are reported without reading any data.
+        assert!(validate("rate(foo)").is_err());
+        assert!(validate("sum(foo[5m])").is_err());
+        assert!(validate("foo{").is_err());
+    }
+
+    #[test]
+    fn test_analyze_no_selector() {
+        let analysis = analyze("vector(1) + time()", 7200, 10800);
+        assert!(analysis.selectors.is_empty());
+        assert_eq!(None, analysis.time_range);
+    }
+}
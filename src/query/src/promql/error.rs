@@ -27,6 +27,14 @@ use snafu::{Location, Snafu};
 #[snafu(visibility(pub))]
 #[stack_trace_debug]
 pub enum Error {
+    #[snafu(display("Failed to parse PromQL query `{query}`: {reason}"))]
+    ParsePromQL {
+        query: String,
+        reason: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Unsupported expr type: {}", name))]
     UnsupportedExpr {
         name: String,
@@ -192,6 +200,45 @@ pub enum Error {
         #[snafu(implicit)]
         location: Location,
     },
+
+    #[snafu(display("Unsupported field type {field_type}, expect float or int"))]
+    UnsupportedFieldType {
+        field_type: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display(
+        "The non-standard `@ latest()` modifier is disabled, set `promql_enable_latest_at` to enable it"
+    ))]
+    LatestAtDisabled {
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Negative offsets are disabled, set `promql_enable_negative_offset` to enable them"
+    ))]
+    NegativeOffsetDisabled {
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("The `@ latest()` modifier is only supported on instant vector selectors"))]
+    UnsupportedLatestAt {
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display(
+        "A selector with the `@` modifier reads samples until {end}, which is more than {lookahead}ms after the current time, see `promql_max_at_lookahead`"
+    ))]
+    AtLookaheadExceeded {
+        end: i64,
+        lookahead: i64,
+        #[snafu(implicit)]
+        location: Location,
+    },
 }
 
 impl ErrorExt for Error {
@@ -211,7 +258,10 @@ impl ErrorExt for Error {
             | UnsupportedVectorMatch { .. }
             | CombineTableColumnMismatch { .. }
             | UnexpectedPlanExpr { .. }
-            | UnsupportedMatcherOp { .. } => StatusCode::InvalidArguments,
+            | UnsupportedMatcherOp { .. }
+            | UnsupportedFieldType { .. }
+            | UnsupportedLatestAt { .. }
+            | AtLookaheadExceeded { .. } => StatusCode::InvalidArguments,
 
             UnknownTable { .. } => StatusCode::Internal,
 
@@ -221,9 +271,13 @@ impl ErrorExt for Error {
 
             TableNameNotFound { .. } => StatusCode::TableNotFound,
 
-            MultipleMetricMatchers { .. } | NoMetricMatcher { .. } => StatusCode::InvalidSyntax,
+            ParsePromQL { .. } | MultipleMetricMatchers { .. } | NoMetricMatcher { .. } => {
+                StatusCode::InvalidSyntax
+            }
 
-            MultiFieldsNotSupported { .. } => StatusCode::Unsupported,
+            MultiFieldsNotSupported { .. }
+            | LatestAtDisabled { .. }
+            | NegativeOffsetDisabled { .. } => StatusCode::Unsupported,
             Catalog { source, .. } => source.status_code(),
         }
     }
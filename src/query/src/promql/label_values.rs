@@ -17,7 +17,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use datafusion_common::{Column, ScalarValue};
 use datafusion_expr::expr::Alias;
 use datafusion_expr::utils::conjunction;
-use datafusion_expr::{col, Cast, Expr, LogicalPlan, LogicalPlanBuilder};
+use datafusion_expr::{col, lit, Cast, Expr, LogicalPlan, LogicalPlanBuilder};
 use datafusion_sql::TableReference;
 use datatypes::arrow::datatypes::{DataType as ArrowDataType, TimeUnit as ArrowTimeUnit};
 use datatypes::prelude::ConcreteDataType;
@@ -105,3 +105,27 @@ pub fn rewrite_label_values_query(
 
     Ok(logical_plan)
 }
+
+/// Rewrite the scan of a table to the query of the distinct label sets, that is
+/// a row of the tag columns of each series.
+///
+/// A table without tag columns has one series if it isn't empty.
+pub fn rewrite_series_query(table: TableRef, scan_plan: LogicalPlan) -> Result<LogicalPlan> {
+    let table_info = table.table_info();
+    let mut labels = table_info
+        .meta
+        .row_key_column_names()
+        .map(|name| col(Column::from_name(name.clone())))
+        .collect::<Vec<_>>();
+    if labels.is_empty() {
+        labels.push(lit(1).alias("series"));
+    }
+
+    LogicalPlanBuilder::from(scan_plan)
+        .project(labels)
+        .context(DataFusionPlanningSnafu)?
+        .distinct()
+        .context(DataFusionPlanningSnafu)?
+        .build()
+        .context(DataFusionPlanningSnafu)
+}
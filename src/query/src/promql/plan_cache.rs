@@ -0,0 +1,356 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A cache of the logical plans of PromQL queries, so repeated queries only
+//! re-run the execution.
+
+use std::time::{Duration, SystemTime};
+
+use catalog::CatalogManagerRef;
+use datafusion::common::tree_node::{TreeNode, TreeNodeRecursion};
+use datafusion::datasource::source_as_provider;
+use datafusion_expr::{Expr, LogicalPlan};
+use moka::policy::EvictionPolicy;
+use moka::sync::Cache;
+use promql::functions::HistogramAggr;
+use promql_parser::parser::EvalStmt;
+use session::context::QueryContextRef;
+use snafu::ResultExt;
+use table::metadata::{TableId, TableVersion};
+use table::table::adapter::DfTableProviderAdapter;
+
+use crate::error::{CatalogSnafu, Result};
+use crate::metrics::{PROMQL_PLAN_CACHE_HIT, PROMQL_PLAN_CACHE_MISS};
+use crate::promql::planner::PromPlannerOptions;
+
+/// Default number of plans kept in the cache.
+pub const DEFAULT_PROMQL_PLAN_CACHE_SIZE: u64 = 1024;
+
+/// Everything a PromQL logical plan depends on besides the tables it scans.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct PlanCacheKey {
+    /// The query text formatted from the parsed expression, so queries that only
+    /// differ in whitespace or quoting share the same entry.
+    query: String,
+    start: SystemTime,
+    end: SystemTime,
+    interval: Duration,
+    lookback_delta: Duration,
+    catalog: String,
+    schema: String,
+    session_timezone: String,
+    options: PromPlannerOptions,
+}
+
+impl PlanCacheKey {
+    pub(crate) fn new(
+        stmt: &EvalStmt,
+        query_ctx: &QueryContextRef,
+        options: &PromPlannerOptions,
+    ) -> Self {
+        Self {
+            query: stmt.expr.to_string(),
+            start: stmt.start,
+            end: stmt.end,
+            interval: stmt.interval,
+            lookback_delta: stmt.lookback_delta,
+            catalog: query_ctx.current_catalog().to_string(),
+            schema: query_ctx.current_schema(),
+            session_timezone: query_ctx.timezone().to_string(),
+            options: options.clone(),
+        }
+    }
+}
+
+/// The identity and schema version of a table scanned by a cached plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TableVersionInfo {
+    catalog: String,
+    schema: String,
+    table_name: String,
+    table_id: TableId,
+    version: TableVersion,
+}
+
+#[derive(Debug, Clone)]
+struct CachedPlan {
+    plan: LogicalPlan,
+    tables: Vec<TableVersionInfo>,
+}
+
+/// A bounded LRU cache of PromQL logical plans.
+///
+/// Entries are validated against the catalog on lookup: if any table the plan
+/// scans was altered or recreated since the plan was built, the entry is a miss.
+#[derive(Clone)]
+pub struct PromPlanCache {
+    cache: Cache<PlanCacheKey, CachedPlan>,
+}
+
+impl Default for PromPlanCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_PROMQL_PLAN_CACHE_SIZE)
+    }
+}
+
+impl PromPlanCache {
+    pub fn new(capacity: u64) -> Self {
+        let cache = Cache::builder()
+            .max_capacity(capacity)
+            .eviction_policy(EvictionPolicy::lru())
+            .build();
+        Self { cache }
+    }
+
+    /// Returns the cached plan of `key` if the tables it scans are unchanged.
+    pub(crate) async fn get(
+        &self,
+        key: &PlanCacheKey,
+        catalog_manager: &CatalogManagerRef,
+        query_ctx: &QueryContextRef,
+    ) -> Result<Option<LogicalPlan>> {
+        let Some(cached) = self.cache.get(key) else {
+            PROMQL_PLAN_CACHE_MISS.inc();
+            return Ok(None);
+        };
+
+        for expected in &cached.tables {
+            let table = catalog_manager
+                .table(
+                    &expected.catalog,
+                    &expected.schema,
+                    &expected.table_name,
+                    Some(query_ctx),
+                )
+                .await
+                .context(CatalogSnafu)?;
+            let unchanged = table.is_some_and(|table| {
+                let info = table.table_info();
+                info.ident.table_id == expected.table_id && info.ident.version == expected.version
+            });
+            if !unchanged {
+                self.cache.invalidate(key);
+                PROMQL_PLAN_CACHE_MISS.inc();
+                return Ok(None);
+            }
+        }
+
+        PROMQL_PLAN_CACHE_HIT.inc();
+        Ok(Some(cached.plan))
+    }
+
+    /// Caches the `plan` of `key`. Plans scanning sources other than tables are
+    /// not cached as their versions can't be tracked, neither are plans reporting
+    /// warnings to the query context they were planned with.
+    pub(crate) fn insert(&self, key: PlanCacheKey, plan: &LogicalPlan) {
+        if reports_warnings(plan) {
+            return;
+        }
+        if let Some(tables) = scanned_tables(plan) {
+            self.cache.insert(
+                key,
+                CachedPlan {
+                    plan: plan.clone(),
+                    tables,
+                },
+            );
+        }
+    }
+}
+
+/// Collects the tables scanned by `plan`, returns None if some scan isn't a table.
+fn scanned_tables(plan: &LogicalPlan) -> Option<Vec<TableVersionInfo>> {
+    let mut tables = Vec::new();
+    let mut all_tables = true;
+    let _ = plan.apply(|node| {
+        if let LogicalPlan::TableScan(scan) = node {
+            let info = source_as_provider(&scan.source).ok().and_then(|provider| {
+                provider
+                    .as_any()
+                    .downcast_ref::<DfTableProviderAdapter>()
+                    .map(|adapter| adapter.table().table_info())
+            });
+            let Some(info) = info else {
+                all_tables = false;
+                return Ok(TreeNodeRecursion::Stop);
+            };
+            tables.push(TableVersionInfo {
+                catalog: info.catalog_name.clone(),
+                schema: info.schema_name.clone(),
+                table_name: info.name.clone(),
+                table_id: info.ident.table_id,
+                version: info.ident.version,
+            });
+        }
+        Ok(TreeNodeRecursion::Continue)
+    });
+    all_tables.then_some(tables)
+}
+
+/// Whether `plan` aggregates native histograms, whose warnings are raised during
+/// execution to the query context captured in planning.
+fn reports_warnings(plan: &LogicalPlan) -> bool {
+    plan.exists(|node| {
+        Ok(match node {
+            LogicalPlan::Aggregate(aggr) => aggr.aggr_expr.iter().any(|expr| match expr {
+                Expr::AggregateFunction(func) => HistogramAggr::is_histogram_aggr(&func.func),
+                _ => false,
+            }),
+            _ => false,
+        })
+    })
+    .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use catalog::memory::MemoryCatalogManager;
+    use catalog::{DeregisterTableRequest, RegisterTableRequest};
+    use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+    use common_recordbatch::RecordBatch;
+    use datatypes::prelude::{ConcreteDataType, VectorRef};
+    use datatypes::schema::{ColumnSchema, Schema};
+    use datatypes::vectors::{Float64Vector, StringVector, TimestampMillisecondVector};
+    use session::context::QueryContext;
+    use table::metadata::TableInfo;
+    use table::test_util::{EmptyTable, MemTable};
+    use table::TableRef;
+
+    use super::*;
+    use crate::parser::{PromQuery, QueryLanguageParser, QueryStatement};
+    use crate::QueryEngineFactory;
+
+    fn metric_table() -> TableRef {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+            ColumnSchema::new("val", ConcreteDataType::float64_datatype(), true),
+        ]));
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(vec!["a", "b"])),
+            Arc::new(TimestampMillisecondVector::from_vec(vec![0, 0])),
+            Arc::new(Float64Vector::from_vec(vec![1.0, 2.0])),
+        ];
+        let recordbatch = RecordBatch::new(schema, columns).unwrap();
+        MemTable::table_with_primary_keys("metric", recordbatch, 1024, vec![0])
+    }
+
+    fn register_table(catalog_manager: &MemoryCatalogManager, table: TableRef) {
+        let _ = catalog_manager
+            .register_table_sync(RegisterTableRequest {
+                catalog: DEFAULT_CATALOG_NAME.to_string(),
+                schema: DEFAULT_SCHEMA_NAME.to_string(),
+                table_name: "metric".to_string(),
+                table_id: 1024,
+                table,
+            })
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_plan_cache_invalidation() {
+        let catalog_manager = MemoryCatalogManager::with_default_setup();
+        let table = metric_table();
+        register_table(&catalog_manager, table.clone());
+        let engine =
+            QueryEngineFactory::new(catalog_manager.clone(), None, None, None, None, false)
+                .query_engine();
+        let state = engine.engine_state();
+        let query_ctx = QueryContext::arc();
+
+        let query = PromQuery {
+            query: "sum by (host) (rate(metric[5m]))".to_string(),
+            start: "0".to_string(),
+            end: "600".to_string(),
+            step: "60s".to_string(),
+            ..Default::default()
+        };
+        let QueryStatement::Promql(stmt) =
+            QueryLanguageParser::parse_promql(&query, &query_ctx).unwrap()
+        else {
+            unreachable!()
+        };
+        let options = PromPlannerOptions {
+            timezone: state.promql_timezone(),
+            enable_latest_at: state.promql_enable_latest_at(),
+            enable_negative_offset: state.promql_enable_negative_offset(),
+            max_at_lookahead: state.promql_max_at_lookahead(),
+            integer_counts: state.promql_integer_counts(),
+            created_timestamps: state.promql_created_timestamps(),
+            rate_first_sample: state.promql_rate_first_sample(),
+            fill_forward: state.promql_fill_forward(),
+            propagate_nan: state.promql_propagate_nan(),
+            resolve_bucket_suffix: state.promql_resolve_bucket_suffix(),
+            staleness_delta: state.promql_staleness_delta(),
+            ..Default::default()
+        };
+        let key = PlanCacheKey::new(&stmt, &query_ctx, &options);
+        let cache = state.promql_plan_cache();
+        let catalog_manager_ref = state.catalog_manager().clone();
+
+        let statement = QueryStatement::Promql(stmt);
+        let first = engine
+            .planner()
+            .plan(&statement, query_ctx.clone())
+            .await
+            .unwrap();
+        let cached = cache
+            .get(&key, &catalog_manager_ref, &query_ctx)
+            .await
+            .unwrap();
+        assert!(cached.is_some());
+        let second = engine
+            .planner()
+            .plan(&statement, query_ctx.clone())
+            .await
+            .unwrap();
+        assert_eq!(first, second);
+
+        // Altering the table bumps its version, so the cached plan is stale.
+        let mut info = TableInfo::clone(&table.table_info());
+        info.ident.version += 1;
+        catalog_manager
+            .deregister_table_sync(DeregisterTableRequest {
+                catalog: DEFAULT_CATALOG_NAME.to_string(),
+                schema: DEFAULT_SCHEMA_NAME.to_string(),
+                table_name: "metric".to_string(),
+            })
+            .unwrap();
+        register_table(&catalog_manager, EmptyTable::from_table_info(&info));
+        let cached = cache
+            .get(&key, &catalog_manager_ref, &query_ctx)
+            .await
+            .unwrap();
+        assert!(cached.is_none());
+
+        // The query is planned again and cached with the new version.
+        let _ = engine
+            .planner()
+            .plan(&statement, query_ctx.clone())
+            .await
+            .unwrap();
+        let cached = cache
+            .get(&key, &catalog_manager_ref, &query_ctx)
+            .await
+            .unwrap();
+        assert!(cached.is_some());
+    }
+}
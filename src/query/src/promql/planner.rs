@@ -12,15 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
 use std::sync::Arc;
-use std::time::UNIX_EPOCH;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use arrow::datatypes::IntervalDayTime;
+use arrow::datatypes::{IntervalDayTime, IntervalMonthDayNano};
 use async_recursion::async_recursion;
 use catalog::table_source::DfTableSourceProvider;
-use common_query::prelude::GREPTIME_VALUE;
-use datafusion::common::DFSchemaRef;
+use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
+use common_query::prelude::{GREPTIME_CREATED, GREPTIME_VALUE};
+use common_time::util::current_time_millis;
+use common_time::Timezone;
+use datafusion::common::tree_node::{Transformed, TreeNode};
+use datafusion::common::{DFSchema, DFSchemaRef};
 use datafusion::datasource::DefaultTableSource;
 use datafusion::execution::context::SessionState;
 use datafusion::functions_aggregate::average::avg_udaf;
@@ -30,35 +35,46 @@ use datafusion::functions_aggregate::min_max::{max_udaf, min_udaf};
 use datafusion::functions_aggregate::stddev::stddev_pop_udaf;
 use datafusion::functions_aggregate::sum::sum_udaf;
 use datafusion::functions_aggregate::variance::var_pop_udaf;
-use datafusion::functions_window::row_number::RowNumber;
-use datafusion::logical_expr::expr::{AggregateFunction, Alias, ScalarFunction, WindowFunction};
+use datafusion::logical_expr::expr::{AggregateFunction, Alias, ScalarFunction, TryCast};
 use datafusion::logical_expr::expr_rewriter::normalize_cols;
 use datafusion::logical_expr::{
-    BinaryExpr, Cast, Extension, LogicalPlan, LogicalPlanBuilder, Operator,
-    ScalarUDF as ScalarUdfDef, WindowFrame, WindowFunctionDefinition,
+    BinaryExpr, Cast, EmptyRelation, Extension, LogicalPlan, LogicalPlanBuilder, Operator,
+    ScalarUDF as ScalarUdfDef,
 };
 use datafusion::prelude as df_prelude;
 use datafusion::prelude::{Column, Expr as DfExpr, JoinType};
 use datafusion::scalar::ScalarValue;
 use datafusion::sql::TableReference;
 use datafusion_expr::utils::conjunction;
-use datafusion_expr::{col, lit, SortExpr};
-use datatypes::arrow::datatypes::{DataType as ArrowDataType, TimeUnit as ArrowTimeUnit};
+use datafusion_expr::SortExpr;
+use datatypes::arrow::datatypes::{
+    DataType as ArrowDataType, Field as ArrowField, Schema as ArrowSchema,
+    TimeUnit as ArrowTimeUnit,
+};
 use datatypes::data_type::ConcreteDataType;
+use datatypes::schema::SchemaRef;
 use itertools::Itertools;
 use promql::extension_plan::{
-    build_special_time_expr, EmptyMetric, HistogramFold, InstantManipulate, Millisecond,
-    RangeManipulate, ScalarCalculate, SeriesDivide, SeriesNormalize, UnionDistinctOn,
+    build_special_time_expr, Absent, EmaOverSteps, EmptyMetric, FillForward, HistogramFold,
+    InstantManipulate, Millisecond, RangeManipulate, ScalarCalculate, SeriesDivide,
+    SeriesNormalize, StreamAggregate, StreamAggregateFunc, TopK, UnionDistinctOn, INTERVAL_COLUMN,
+    RANGE_END_COLUMN, RANGE_START_COLUMN,
 };
 use promql::functions::{
-    quantile_udaf, AbsentOverTime, AvgOverTime, Changes, CountOverTime, Delta, Deriv, HoltWinters,
-    IDelta, Increase, LastOverTime, MaxOverTime, MinOverTime, PredictLinear, PresentOverTime,
-    QuantileOverTime, Rate, Resets, Round, StddevOverTime, StdvarOverTime, SumOverTime,
+    quantile_udaf, AvgOverTime, AvgOverTimePropagateNan, Changes, CountOverTime, Delta, Deriv,
+    HistogramAggr, HistogramAggrKind, HistogramAvgOverTime, HistogramCount, HistogramIDelta,
+    HistogramPack, HistogramQuantile, HistogramSum, HoltWinters, IDelta, Increase, LastOverTime,
+    MaxOverTime, MaxOverTimePropagateNan, MinOverTime, MinOverTimePropagateNan, PredictLinear,
+    PresentOverTime, QuantileOverTime, Rate, RateFirstSamplePolicy, Resets, Round, SkipNanAggr,
+    SkipNanAggrKind, StdAggr, StdAggrKind, StddevOverTime, StdvarOverTime, SumOverTime,
+    SumOverTimePropagateNan,
 };
+use promql::native_histogram::{NATIVE_HISTOGRAM_COLUMN, NATIVE_HISTOGRAM_LAYOUT};
+use promql::range_array::RangeArray;
 use promql_parser::label::{MatchOp, Matcher, Matchers, METRIC_NAME};
 use promql_parser::parser::token::TokenType;
 use promql_parser::parser::{
-    token, AggregateExpr, BinModifier, BinaryExpr as PromBinaryExpr, Call, EvalStmt,
+    token, AggregateExpr, AtModifier, BinModifier, BinaryExpr as PromBinaryExpr, Call, EvalStmt,
     Expr as PromExpr, Function, FunctionArgs as PromFunctionArgs, LabelModifier, MatrixSelector,
     NumberLiteral, Offset, ParenExpr, StringLiteral, SubqueryExpr, UnaryExpr,
     VectorMatchCardinality, VectorSelector,
@@ -69,15 +85,22 @@ use store_api::metric_engine_consts::{
 };
 use table::table::adapter::DfTableProviderAdapter;
 
+use crate::parser::{ema_over_steps_alpha, is_latest_at, EMA_OVER_STEPS_FUNCTION};
 use crate::promql::error::{
-    CatalogSnafu, ColumnNotFoundSnafu, CombineTableColumnMismatchSnafu, DataFusionPlanningSnafu,
-    ExpectRangeSelectorSnafu, FunctionInvalidArgumentSnafu, InvalidTimeRangeSnafu,
-    MultiFieldsNotSupportedSnafu, MultipleMetricMatchersSnafu, MultipleVectorSnafu,
-    NoMetricMatcherSnafu, PromqlPlanNodeSnafu, Result, TableNameNotFoundSnafu,
+    AtLookaheadExceededSnafu, CatalogSnafu, ColumnNotFoundSnafu, CombineTableColumnMismatchSnafu,
+    DataFusionPlanningSnafu, ExpectRangeSelectorSnafu, FunctionInvalidArgumentSnafu,
+    InvalidTimeRangeSnafu, LatestAtDisabledSnafu, MultiFieldsNotSupportedSnafu,
+    MultipleMetricMatchersSnafu, MultipleVectorSnafu, NegativeOffsetDisabledSnafu,
+    NoMetricMatcherSnafu, ParsePromQLSnafu, PromqlPlanNodeSnafu, Result, TableNameNotFoundSnafu,
     TimeIndexNotFoundSnafu, UnexpectedPlanExprSnafu, UnexpectedTokenSnafu, UnknownTableSnafu,
-    UnsupportedExprSnafu, UnsupportedMatcherOpSnafu, UnsupportedVectorMatchSnafu,
-    ValueNotFoundSnafu, ZeroRangeSelectorSnafu,
+    UnsupportedExprSnafu, UnsupportedFieldTypeSnafu, UnsupportedLatestAtSnafu,
+    UnsupportedMatcherOpSnafu, UnsupportedVectorMatchSnafu, ValueNotFoundSnafu,
+    ZeroRangeSelectorSnafu,
 };
+use crate::promql::rollup::{fingerprint, PromRollup, PromRollups, VersionedPromRollups};
+
+/// The lookback delta of selectors with the `@ latest()` modifier, 100 years.
+const LATEST_AT_LOOKBACK_DELTA: Millisecond = 100 * 365 * 24 * 60 * 60 * 1000;
 
 /// `time()` function in PromQL.
 const SPECIAL_TIME_FUNCTION: &str = "time";
@@ -85,6 +108,39 @@ const SPECIAL_TIME_FUNCTION: &str = "time";
 const SCALAR_FUNCTION: &str = "scalar";
 /// `histogram_quantile` function in PromQL
 const SPECIAL_HISTOGRAM_QUANTILE: &str = "histogram_quantile";
+/// Suffix of the bucket series of classic histograms.
+const BUCKET_SUFFIX: &str = "_bucket";
+/// Functions transforming each float sample. Like Prometheus, they drop the series
+/// of native histograms.
+const FLOAT_TRANSFORM_FUNCTIONS: &[&str] = &[
+    "abs",
+    "ceil",
+    "floor",
+    "exp",
+    "sqrt",
+    "ln",
+    "log2",
+    "log10",
+    "sgn",
+    "round",
+    "clamp",
+    "clamp_min",
+    "clamp_max",
+    "acos",
+    "acosh",
+    "asin",
+    "asinh",
+    "atan",
+    "atanh",
+    "cos",
+    "cosh",
+    "sin",
+    "sinh",
+    "tan",
+    "tanh",
+    "deg",
+    "rad",
+];
 /// `vector` function in PromQL
 const SPECIAL_VECTOR_FUNCTION: &str = "vector";
 /// `le` column for conventional histogram.
@@ -98,16 +154,53 @@ const DEFAULT_FIELD_COLUMN: &str = "value";
 /// Special modifier to project field columns under multi-field mode
 const FIELD_COLUMN_MATCHER: &str = "__field__";
 
+/// Special modifier to select field columns by data type, either `float` or `int`
+const FIELD_TYPE_MATCHER: &str = "__field_type__";
+
 /// Special modifier for cross schema query
 const SCHEMA_COLUMN_MATCHER: &str = "__schema__";
 const DB_COLUMN_MATCHER: &str = "__database__";
 
+/// Special argument of `sort_by_label` and `sort_by_label_desc` to compare
+/// numeric label values by their numbers instead of lexically.
+const NUMERIC_SORT_MODIFIER: &str = "__numeric__";
+
 /// Threshold for scatter scan mode
 const MAX_SCATTER_POINTS: i64 = 400;
 
 /// Interval 1 hour in millisecond
 const INTERVAL_1H: i64 = 60 * 60 * 1000;
 
+/// Data type class of field columns, used to select the value columns of a selector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldType {
+    Float,
+    /// Integers and booleans, which are cast to float in range selectors.
+    Int,
+}
+
+impl FieldType {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "float" => Some(Self::Float),
+            "int" => Some(Self::Int),
+            _ => None,
+        }
+    }
+
+    /// Returns the class of the data type, or None if it's not a plain number,
+    /// e.g. binary columns holding native histograms.
+    fn of(data_type: &ConcreteDataType) -> Option<Self> {
+        if data_type.is_float() {
+            Some(Self::Float)
+        } else if data_type.is_numeric() || data_type.is_boolean() {
+            Some(Self::Int)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 struct PromPlannerContext {
     // query parameters
@@ -115,6 +208,10 @@ struct PromPlannerContext {
     end: Millisecond,
     interval: Millisecond,
     lookback_delta: Millisecond,
+    /// The start and end of the query that `@ start()` and `@ end()` refer to,
+    /// while `start` and `end` are shifted in subqueries.
+    query_start: Millisecond,
+    query_end: Millisecond,
 
     // planner states
     table_name: Option<String>,
@@ -122,18 +219,58 @@ struct PromPlannerContext {
     field_columns: Vec<String>,
     tag_columns: Vec<String>,
     field_column_matcher: Option<Vec<Matcher>>,
+    /// The data type of field columns chosen by the `__field_type__` matcher.
+    field_type: Option<FieldType>,
     schema_name: Option<String>,
     /// The range in millisecond of range selector. None if there is no range selector.
     range: Option<Millisecond>,
+    /// Whether the next range selector reads the created timestamps of counters.
+    read_created: bool,
+    /// The column of created timestamps read along with the samples of the range selector.
+    created_column: Option<String>,
+    /// Whether the metric of the current selector doesn't exist, so the selector
+    /// matches no series.
+    metric_absent: bool,
+    /// Whether the ranges of the next range selector also contain the samples within
+    /// the lookback delta before them, for [RateFirstSamplePolicy::UsePreviousSample].
+    read_previous_sample: bool,
+    /// The timezone calendar functions like `hour()` are evaluated in. None means UTC.
+    timezone: Option<Arc<str>>,
+    /// Whether the non-standard `@ latest()` modifier is allowed.
+    enable_latest_at: bool,
+    /// Whether selectors and subqueries may have negative offsets.
+    enable_negative_offset: bool,
+    /// How far after the current time selectors with `@` can read. None means unlimited.
+    max_at_lookahead: Option<Millisecond>,
+    /// Whether the counting functions return integers.
+    integer_counts: bool,
+    /// Whether `rate()` and `increase()` use the created timestamps of counters.
+    created_timestamps: bool,
+    /// How `rate()` and `increase()` treat the start of the range.
+    rate_first_sample: RateFirstSamplePolicy,
+    /// Whether NaN samples are propagated instead of skipped by aggregations.
+    propagate_nan: bool,
+    /// Whether to plan the stored samples instead of the values aligned to steps.
+    raw_samples: bool,
+    /// Whether `histogram_quantile()` reads the `_bucket` series of the given base name.
+    resolve_bucket_suffix: bool,
+    /// How long a series keeps its last value after a staleness marker.
+    staleness_delta: Millisecond,
+    /// The rollup tables that can be read instead of evaluating expressions.
+    rollups: Arc<PromRollups>,
 }
 
 impl PromPlannerContext {
     fn from_eval_stmt(stmt: &EvalStmt) -> Self {
+        let start = stmt.start.duration_since(UNIX_EPOCH).unwrap().as_millis() as _;
+        let end = stmt.end.duration_since(UNIX_EPOCH).unwrap().as_millis() as _;
         Self {
-            start: stmt.start.duration_since(UNIX_EPOCH).unwrap().as_millis() as _,
-            end: stmt.end.duration_since(UNIX_EPOCH).unwrap().as_millis() as _,
+            start,
+            end,
             interval: stmt.interval.as_millis() as _,
             lookback_delta: stmt.lookback_delta.as_millis() as _,
+            query_start: start,
+            query_end: end,
             ..Default::default()
         }
     }
@@ -145,8 +282,11 @@ impl PromPlannerContext {
         self.field_columns = vec![];
         self.tag_columns = vec![];
         self.field_column_matcher = None;
+        self.field_type = None;
         self.schema_name = None;
         self.range = None;
+        self.created_column = None;
+        self.metric_absent = false;
     }
 
     /// Reset table name and schema to empty
@@ -166,6 +306,66 @@ pub struct PromPlanner {
     ctx: PromPlannerContext,
 }
 
+/// Options of [PromPlanner] that are not part of the PromQL query.
+///
+/// Plans only depend on the query and these options, so they are part of the
+/// key of the [PromPlanCache](crate::promql::plan_cache::PromPlanCache).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PromPlannerOptions {
+    /// The timezone calendar functions like `hour()` and `day_of_week()` are
+    /// evaluated in. None means UTC.
+    pub timezone: Option<Timezone>,
+    /// Whether to allow the non-standard `@ latest()` modifier, see [is_latest_at].
+    pub enable_latest_at: bool,
+    /// Whether to allow negative offsets like `foo offset -1m`, which read samples
+    /// after the evaluation time. Prometheus hides them behind a feature flag.
+    pub enable_negative_offset: bool,
+    /// The maximum time after the current time that selectors with an `@` modifier
+    /// can read, including a negative offset. Planning fails if a selector reads
+    /// later samples. None means unlimited.
+    pub max_at_lookahead: Option<Duration>,
+    /// Whether the counting functions `count_over_time()`, `changes()` and `resets()`
+    /// return Int64 instead of Float64 like Prometheus. The `count` aggregation
+    /// always returns Int64.
+    pub integer_counts: bool,
+    /// Whether `rate()` and `increase()` start counters created within the range
+    /// from zero at their created timestamps in the `greptime_created` column,
+    /// instead of extrapolating before the counter existed.
+    pub created_timestamps: bool,
+    /// How `rate()` and `increase()` treat the start of the range. By default they
+    /// extrapolate to the range start like Prometheus, see [RateFirstSamplePolicy].
+    pub rate_first_sample: RateFirstSamplePolicy,
+    /// Whether to fill the missing steps of the result with the last known value
+    /// of each series within the lookback window, see [FillForward]. Prometheus
+    /// leaves them empty.
+    pub fill_forward: bool,
+    /// Whether the `sum`, `avg`, `min` and `max` aggregations and their `*_over_time()`
+    /// functions propagate NaN samples. By default they skip NaN samples unless all
+    /// samples are NaN, like Prometheus 3.
+    pub propagate_nan: bool,
+    /// Whether to return the stored samples of a vector selector within the
+    /// evaluation range instead of the values aligned to the steps. Only plain
+    /// vector selectors are supported in this mode.
+    pub raw_samples: bool,
+    /// Whether to return the `__name__` label of the result series as a column.
+    /// Selectors keep the metric name, while functions, aggregations and arithmetic
+    /// drop it like Prometheus.
+    pub metric_name_column: bool,
+    /// Whether `histogram_quantile()` reads the `<name>_bucket` series when it's
+    /// given the base name of a classic histogram, like `http_request_duration_seconds`,
+    /// and only the bucket series exists. By default the bucket series must be
+    /// selected explicitly like Prometheus.
+    pub resolve_bucket_suffix: bool,
+    /// How long a series keeps its last value within the lookback window after a
+    /// staleness marker, i.e. a NaN sample. None ends the series at the marker
+    /// like Prometheus, independent of the lookback delta.
+    pub staleness_delta: Option<Duration>,
+    /// The rollup tables pre-computed by flows, see [PromRollup]. Sub-expressions
+    /// with a rollup table whose timestamps are the steps of the evaluation read
+    /// the rollup table instead.
+    pub rollups: VersionedPromRollups,
+}
+
 /// Unescapes the value of the matcher
 pub fn normalize_matcher(mut matcher: Matcher) -> Matcher {
     if let Ok(unescaped_value) = unescaper::unescape(&matcher.value) {
@@ -175,17 +375,140 @@ pub fn normalize_matcher(mut matcher: Matcher) -> Matcher {
 }
 
 impl PromPlanner {
+    /// Parses the PromQL `query` and plans it as a range query from `start` to `end`
+    /// by `step`, with the given `lookback` delta. Tables of metrics are resolved by
+    /// the `table_provider`.
+    ///
+    /// It's the entry point for embedding the planner without going through the HTTP
+    /// API. The session to execute the plan must be built by
+    /// [register_promql_extensions](promql::extension_plan::register_promql_extensions).
+    pub async fn plan_str(
+        table_provider: DfTableSourceProvider,
+        query: &str,
+        start: SystemTime,
+        end: SystemTime,
+        step: Duration,
+        lookback: Duration,
+        session_state: &SessionState,
+    ) -> Result<LogicalPlan> {
+        let expr = promql_parser::parser::parse(query).map_err(|reason| {
+            ParsePromQLSnafu {
+                query,
+                reason: reason.to_string(),
+            }
+            .build()
+        })?;
+        let stmt = EvalStmt {
+            expr,
+            start,
+            end,
+            interval: step,
+            lookback_delta: lookback,
+        };
+        Self::stmt_to_plan(table_provider, &stmt, session_state).await
+    }
+
     pub async fn stmt_to_plan(
         table_provider: DfTableSourceProvider,
         stmt: &EvalStmt,
         session_state: &SessionState,
     ) -> Result<LogicalPlan> {
+        Self::stmt_to_plan_with_options(
+            table_provider,
+            stmt,
+            &PromPlannerOptions::default(),
+            session_state,
+        )
+        .await
+    }
+
+    /// Same as [`Self::stmt_to_plan`], but planned with the given [PromPlannerOptions].
+    pub async fn stmt_to_plan_with_options(
+        table_provider: DfTableSourceProvider,
+        stmt: &EvalStmt,
+        options: &PromPlannerOptions,
+        session_state: &SessionState,
+    ) -> Result<LogicalPlan> {
+        let mut ctx = PromPlannerContext::from_eval_stmt(stmt);
+        ctx.timezone = options.timezone.as_ref().map(|tz| tz.to_string().into());
+        ctx.enable_latest_at = options.enable_latest_at;
+        ctx.enable_negative_offset = options.enable_negative_offset;
+        ctx.max_at_lookahead = options
+            .max_at_lookahead
+            .map(|lookahead| lookahead.as_millis() as _);
+        ctx.integer_counts = options.integer_counts;
+        ctx.created_timestamps = options.created_timestamps;
+        ctx.rate_first_sample = options.rate_first_sample;
+        ctx.propagate_nan = options.propagate_nan;
+        ctx.raw_samples = options.raw_samples;
+        ctx.resolve_bucket_suffix = options.resolve_bucket_suffix;
+        ctx.staleness_delta = options
+            .staleness_delta
+            .map(|delta| delta.as_millis() as _)
+            .unwrap_or_default();
+        ctx.rollups = options.rollups.rollups.clone();
         let mut planner = Self {
             table_provider,
-            ctx: PromPlannerContext::from_eval_stmt(stmt),
+            ctx,
+        };
+
+        let plan = if options.raw_samples {
+            planner.prom_raw_samples_to_plan(&stmt.expr).await?
+        } else {
+            let plan = planner.prom_expr_to_plan(&stmt.expr, session_state).await?;
+            if options.fill_forward {
+                planner.fill_forward(plan)?
+            } else {
+                plan
+            }
+        };
+        if options.metric_name_column {
+            Self::add_metric_name_column(&stmt.expr, plan)
+        } else {
+            Ok(plan)
+        }
+    }
+
+    /// Fill the missing steps of the result with [FillForward]. Results not from
+    /// a metric like scalars and strings already have all steps, they are returned
+    /// as is.
+    fn fill_forward(&self, plan: LogicalPlan) -> Result<LogicalPlan> {
+        if self
+            .ctx
+            .table_name
+            .as_deref()
+            .unwrap_or_default()
+            .is_empty()
+        {
+            return Ok(plan);
+        }
+        let Some(time_index_column) = self.ctx.time_index_column.clone() else {
+            return Ok(plan);
         };
+        let schema = plan.schema();
+        let has_all_columns = Some(&time_index_column)
+            .into_iter()
+            .chain(&self.ctx.tag_columns)
+            .chain(&self.ctx.field_columns)
+            .all(|column| schema.has_column_with_unqualified_name(column));
+        if self.ctx.field_columns.is_empty() || !has_all_columns {
+            return Ok(plan);
+        }
 
-        planner.prom_expr_to_plan(&stmt.expr, session_state).await
+        let fill_forward = FillForward::new(
+            self.ctx.start,
+            self.ctx.end,
+            self.ctx.lookback_delta,
+            self.ctx.interval,
+            time_index_column,
+            self.ctx.tag_columns.clone(),
+            self.ctx.field_columns.clone(),
+            plan,
+        )
+        .context(DataFusionPlanningSnafu)?;
+        Ok(LogicalPlan::Extension(Extension {
+            node: Arc::new(fill_forward),
+        }))
     }
 
     #[async_recursion]
@@ -194,6 +517,15 @@ impl PromPlanner {
         prom_expr: &PromExpr,
         session_state: &SessionState,
     ) -> Result<LogicalPlan> {
+        if let Some(timestamp) = self.step_invariant_timestamp(prom_expr) {
+            return self
+                .prom_step_invariant_expr_to_plan(session_state, prom_expr, timestamp)
+                .await;
+        }
+        if let Some(rollup) = self.find_rollup(prom_expr) {
+            return self.prom_rollup_to_plan(&rollup).await;
+        }
+
         let res = match prom_expr {
             PromExpr::Aggregate(expr) => self.prom_aggr_expr_to_plan(session_state, expr).await?,
             PromExpr::Unary(expr) => self.prom_unary_expr_to_plan(session_state, expr).await?,
@@ -218,32 +550,213 @@ impl PromPlanner {
         Ok(res)
     }
 
+    /// Returns the rollup table with a sample at each step of the evaluation that
+    /// pre-computes the given expr. Prefers the rollup with the largest step.
+    fn find_rollup(&self, prom_expr: &PromExpr) -> Option<PromRollup> {
+        if self.ctx.rollups.is_empty()
+            || matches!(
+                prom_expr,
+                PromExpr::VectorSelector(_)
+                    | PromExpr::MatrixSelector(_)
+                    | PromExpr::NumberLiteral(_)
+                    | PromExpr::StringLiteral(_)
+            )
+        {
+            return None;
+        }
+        self.ctx
+            .rollups
+            .get(&fingerprint(prom_expr))?
+            .iter()
+            .filter(|rollup| rollup.is_aligned(self.ctx.start, self.ctx.interval))
+            .max_by_key(|rollup| rollup.step)
+            .cloned()
+    }
+
+    /// Plans a vector selector on the rollup table. Only the samples at the steps
+    /// are selected, so the steps the rollup table has no sample for stay empty
+    /// like the expression it pre-computes.
+    async fn prom_rollup_to_plan(&mut self, rollup: &PromRollup) -> Result<LogicalPlan> {
+        let selector = VectorSelector {
+            name: Some(rollup.table.table_name.clone()),
+            matchers: Matchers::new(vec![Matcher::new(
+                MatchOp::Equal,
+                SCHEMA_COLUMN_MATCHER,
+                &rollup.table.schema_name,
+            )]),
+            offset: None,
+            at: None,
+        };
+        let lookback_delta = self.ctx.lookback_delta;
+        self.ctx.lookback_delta = 1;
+        let plan = self.prom_vector_selector_to_plan(&selector).await;
+        self.ctx.lookback_delta = lookback_delta;
+        plan
+    }
+
+    /// Returns the timestamp of the `@` modifier if the given expr is evaluated at a
+    /// fixed time, i.e. a vector selector or a range function call over a matrix
+    /// selector or a subquery with `@`.
+    ///
+    /// Returns `None` if the evaluation range is already that single timestamp.
+    fn step_invariant_timestamp(&self, prom_expr: &PromExpr) -> Option<Millisecond> {
+        let at = match prom_expr {
+            PromExpr::VectorSelector(VectorSelector { at, .. }) => at.as_ref(),
+            PromExpr::Call(Call { args, .. }) => {
+                args.args.iter().find_map(|arg| match arg.as_ref() {
+                    PromExpr::MatrixSelector(MatrixSelector { vs, .. }) => vs.at.as_ref(),
+                    PromExpr::Subquery(SubqueryExpr { at, .. }) => at.as_ref(),
+                    _ => None,
+                })
+            }
+            _ => None,
+        }?;
+
+        let timestamp = self.at_timestamp(at);
+        if self.ctx.start == timestamp && self.ctx.end == timestamp {
+            None
+        } else {
+            Some(timestamp)
+        }
+    }
+
+    /// Returns the timestamp the `@` modifier pins the evaluation to.
+    fn at_timestamp(&self, at: &AtModifier) -> Millisecond {
+        match at {
+            AtModifier::Start => self.ctx.query_start,
+            AtModifier::End => self.ctx.query_end,
+            // evaluated at the end, looking back to the latest sample of each series
+            // in `prom_vector_selector_to_plan`
+            at if is_latest_at(at) => self.ctx.end,
+            AtModifier::At(time) => match time.duration_since(UNIX_EPOCH) {
+                Ok(duration) => duration.as_millis() as _,
+                Err(e) => -(e.duration().as_millis() as Millisecond),
+            },
+        }
+    }
+
+    /// Plans the expr at the single `timestamp` and repeats the result on every
+    /// step of the evaluation range.
+    ///
+    /// The `offset` of selectors is still applied by [SeriesNormalize] on the inner plan.
+    async fn prom_step_invariant_expr_to_plan(
+        &mut self,
+        session_state: &SessionState,
+        prom_expr: &PromExpr,
+        timestamp: Millisecond,
+    ) -> Result<LogicalPlan> {
+        let (start, end) = (self.ctx.start, self.ctx.end);
+        self.ctx.start = timestamp;
+        self.ctx.end = timestamp;
+        let input = self.prom_expr_to_plan(prom_expr, session_state).await;
+        self.ctx.start = start;
+        self.ctx.end = end;
+        let input = input?;
+
+        let time_index = self
+            .ctx
+            .time_index_column
+            .clone()
+            .expect("time index should be set in `setup_context`");
+        let steps = LogicalPlan::Extension(Extension {
+            node: Arc::new(
+                EmptyMetric::new(
+                    self.ctx.start,
+                    self.ctx.end,
+                    self.ctx.interval,
+                    time_index.clone(),
+                    DEFAULT_FIELD_COLUMN.to_string(),
+                    None,
+                )
+                .context(DataFusionPlanningSnafu)?,
+            ),
+        });
+
+        // replace the time index column with the one of evaluation steps,
+        // and keep the original column order
+        let mut other_columns = Vec::with_capacity(input.schema().fields().len());
+        let mut project_exprs = Vec::with_capacity(input.schema().fields().len());
+        for (qualifier, field) in input.schema().iter() {
+            if field.name() == &time_index {
+                project_exprs.push(
+                    DfExpr::Column(Column::new(Some(TableReference::bare("")), &time_index))
+                        .alias_qualified(qualifier.cloned(), &time_index),
+                );
+            } else {
+                let column = DfExpr::Column(Column::from((qualifier, field.as_ref())));
+                other_columns.push(column.clone());
+                project_exprs.push(column);
+            }
+        }
+
+        LogicalPlanBuilder::from(input)
+            .project(other_columns)
+            .context(DataFusionPlanningSnafu)?
+            .cross_join(steps)
+            .context(DataFusionPlanningSnafu)?
+            .project(project_exprs)
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)
+    }
+
     async fn prom_subquery_expr_to_plan(
         &mut self,
         session_state: &SessionState,
         subquery_expr: &SubqueryExpr,
     ) -> Result<LogicalPlan> {
         let SubqueryExpr {
-            expr, range, step, ..
+            expr,
+            range,
+            offset,
+            step,
+            at,
         } = subquery_expr;
+        ensure!(
+            !at.as_ref().is_some_and(is_latest_at),
+            UnsupportedLatestAtSnafu
+        );
+        self.check_offset(offset)?;
+
+        // The `@` modifier pins the evaluation of the subquery to a single timestamp.
+        // Range functions over it are repeated on every step by `prom_expr_to_plan`.
+        let (eval_start, eval_end) = match at {
+            Some(at) => {
+                let timestamp = self.at_timestamp(at);
+                (timestamp, timestamp)
+            }
+            None => (self.ctx.start, self.ctx.end),
+        };
 
         let current_interval = self.ctx.interval;
         if let Some(step) = step {
             self.ctx.interval = step.as_millis() as _;
         }
+        // evaluate the inner expr `offset` earlier, then shift its timestamps back
+        // to the steps of the evaluation
+        let offset_duration = Self::offset_duration(offset);
         let current_start = self.ctx.start;
-        self.ctx.start -= range.as_millis() as i64 - self.ctx.interval;
+        let current_end = self.ctx.end;
+        self.ctx.start =
+            eval_start - (range.as_millis() as i64 - self.ctx.interval + offset_duration);
+        self.ctx.end = eval_end - offset_duration;
         let input = self.prom_expr_to_plan(expr, session_state).await?;
         self.ctx.interval = current_interval;
         self.ctx.start = current_start;
+        self.ctx.end = current_end;
+        let input = if offset_duration == 0 {
+            input
+        } else {
+            self.shift_time_index(input, offset_duration)?
+        };
 
         ensure!(!range.is_zero(), ZeroRangeSelectorSnafu);
         let range_ms = range.as_millis() as _;
         self.ctx.range = Some(range_ms);
 
         let manipulate = RangeManipulate::new(
-            self.ctx.start,
-            self.ctx.end,
+            eval_start,
+            eval_end,
             self.ctx.interval,
             range_ms,
             self.ctx
@@ -260,6 +773,181 @@ impl PromPlanner {
         }))
     }
 
+    /// Returns the [StreamAggregateFunc] of the aggregation and the names of its group
+    /// columns if it can be aggregated as a stream. Only Float64 fields are supported,
+    /// with the NaN handling of Prometheus: `sum` and `avg` propagate NaN like the hash
+    /// aggregation with `propagate_nan`, `min` and `max` skip NaN like [SkipNanAggr].
+    fn stream_aggregate_func(
+        &self,
+        op: TokenType,
+        group_exprs: &[DfExpr],
+        field_columns: &[String],
+        input: &LogicalPlan,
+    ) -> Option<(StreamAggregateFunc, Vec<String>)> {
+        if field_columns.is_empty() {
+            return None;
+        }
+        let func = match (op.id(), self.ctx.propagate_nan) {
+            (token::T_SUM, true) => StreamAggregateFunc::Sum,
+            (token::T_AVG, true) => StreamAggregateFunc::Avg,
+            (token::T_MIN, false) => StreamAggregateFunc::Min,
+            (token::T_MAX, false) => StreamAggregateFunc::Max,
+            _ => return None,
+        };
+        let all_float = field_columns.iter().all(|col| {
+            input
+                .schema()
+                .field_with_unqualified_name(col)
+                .is_ok_and(|field| field.data_type() == &ArrowDataType::Float64)
+        });
+        if !all_float {
+            return None;
+        }
+
+        let group_columns = group_exprs
+            .iter()
+            .map(|expr| expr.try_as_col().map(|col| col.name.clone()))
+            .collect::<Option<Vec<_>>>()?;
+        Some((func, group_columns))
+    }
+
+    /// Returns the columns the output of `plan` is sorted on in ascending order with
+    /// nulls first, like the series sorted for [SeriesDivide], if the order is known.
+    fn sorted_columns(plan: &LogicalPlan) -> Option<Vec<String>> {
+        match plan {
+            LogicalPlan::Sort(sort) => sort
+                .expr
+                .iter()
+                .map(|sort_expr| {
+                    sort_expr
+                        .expr
+                        .try_as_col()
+                        .filter(|_| sort_expr.asc && sort_expr.nulls_first)
+                        .map(|col| col.name.clone())
+                })
+                .collect(),
+            LogicalPlan::Filter(filter) => Self::sorted_columns(&filter.input),
+            LogicalPlan::Projection(projection) => {
+                let columns = Self::sorted_columns(&projection.input)?;
+                // the sort columns must be projected as-is
+                columns
+                    .iter()
+                    .all(|name| {
+                        projection
+                            .expr
+                            .iter()
+                            .any(|expr| expr.try_as_col().is_some_and(|col| &col.name == name))
+                    })
+                    .then_some(columns)
+            }
+            LogicalPlan::Extension(extension) => {
+                let name = extension.node.name();
+                let keeps_order = name == SeriesDivide::name()
+                    || name == SeriesNormalize::name()
+                    || name == InstantManipulate::name()
+                    || name == RangeManipulate::name();
+                if keeps_order {
+                    Self::sorted_columns(extension.node.inputs()[0])
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Plans the aggregation as a [StreamAggregate], which emits the result of a group
+    /// as soon as its rows are consumed. The input is sorted on the group columns unless
+    /// it is already, e.g. the series of a selector grouped by all their tags. The fields
+    /// are renamed after the aggregation like the hash aggregation, see
+    /// [Self::create_aggregate_exprs].
+    fn create_stream_aggregate_plan(
+        &self,
+        func: StreamAggregateFunc,
+        group_columns: Vec<String>,
+        field_columns: Vec<String>,
+        input: LogicalPlan,
+    ) -> Result<LogicalPlan> {
+        let is_sorted = Self::sorted_columns(&input)
+            .is_some_and(|sorted_columns| sorted_columns.starts_with(&group_columns));
+        let input = if is_sorted || group_columns.is_empty() {
+            input
+        } else {
+            let sort_exprs = group_columns
+                .iter()
+                .map(|name| {
+                    let column = input
+                        .schema()
+                        .qualified_field_with_unqualified_name(name)
+                        .map(Column::from)
+                        .context(DataFusionPlanningSnafu)?;
+                    // the order required by `StreamAggregateExec`
+                    Ok(DfExpr::Column(column).sort(true, true))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            LogicalPlanBuilder::from(input)
+                .sort(sort_exprs)
+                .context(DataFusionPlanningSnafu)?
+                .build()
+                .context(DataFusionPlanningSnafu)?
+        };
+
+        let num_group_columns = group_columns.len();
+        let aggregate = StreamAggregate::new(func, group_columns, field_columns, input)
+            .context(DataFusionPlanningSnafu)?;
+        let plan = LogicalPlan::Extension(Extension {
+            node: Arc::new(aggregate),
+        });
+        let project_exprs = plan
+            .schema()
+            .iter()
+            .enumerate()
+            .map(|(index, (qualifier, field))| {
+                let column = DfExpr::Column(Column::from((qualifier, field.as_ref())));
+                match index.checked_sub(num_group_columns) {
+                    Some(field_index) => column.alias(&self.ctx.field_columns[field_index]),
+                    None => column,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        LogicalPlanBuilder::from(plan)
+            .project(project_exprs)
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)
+    }
+
+    /// Shifts the time index of the plan later by `offset` milliseconds.
+    fn shift_time_index(&self, input: LogicalPlan, offset: Millisecond) -> Result<LogicalPlan> {
+        let time_index = self
+            .ctx
+            .time_index_column
+            .clone()
+            .expect("time index should be set in `setup_context`");
+        let shift = DfExpr::Literal(ScalarValue::IntervalMonthDayNano(Some(
+            IntervalMonthDayNano::new(0, 0, offset * 1_000_000),
+        )));
+        let project_exprs = input
+            .schema()
+            .iter()
+            .map(|(qualifier, field)| {
+                let column = DfExpr::Column(Column::from((qualifier, field.as_ref())));
+                if field.name() == &time_index {
+                    (column + shift.clone()).alias_qualified(qualifier.cloned(), &time_index)
+                } else {
+                    column
+                }
+            })
+            .collect::<Vec<_>>();
+
+        LogicalPlanBuilder::from(input)
+            .project(project_exprs)
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)
+    }
+
     async fn prom_aggr_expr_to_plan(
         &mut self,
         session_state: &SessionState,
@@ -281,10 +969,23 @@ impl PromPlanner {
                 // calculate columns to group by
                 // Need to append time index column into group by columns
                 let mut group_exprs = self.agg_modifier_to_col(input.schema(), modifier, true)?;
+                let input_field_columns = self.ctx.field_columns.clone();
                 // convert op and value columns to aggregate exprs
                 let (aggr_exprs, prev_field_exprs) =
                     self.create_aggregate_exprs(*op, param, &input)?;
 
+                // aggregate group by group on the input sorted by the group columns
+                if let Some((func, group_columns)) =
+                    self.stream_aggregate_func(*op, &group_exprs, &input_field_columns, &input)
+                {
+                    return self.create_stream_aggregate_plan(
+                        func,
+                        group_columns,
+                        input_field_columns,
+                        input,
+                    );
+                }
+
                 // create plan
                 let builder = LogicalPlanBuilder::from(input);
                 let builder = if op.id() == token::T_COUNT_VALUES {
@@ -334,49 +1035,49 @@ impl PromPlanner {
             ..
         } = aggr_expr;
 
+        ensure!(
+            self.ctx.field_columns.len() == 1,
+            UnsupportedExprSnafu {
+                name: "topk or bottomk on multi-value input"
+            }
+        );
+
         let group_exprs = self.agg_modifier_to_col(input.schema(), modifier, false)?;
 
         let val = Self::get_param_value_as_f64(*op, param)?;
+        // k less than 1 selects nothing, `as` casting saturates negative and NaN values to 0.
+        let k = val as usize;
 
-        // convert op and value columns to window exprs.
-        let window_exprs = self.create_window_exprs(*op, group_exprs.clone(), &input)?;
-
-        let rank_columns: Vec<_> = window_exprs
+        let asc = matches!(op.id(), token::T_BOTTOMK);
+        let group_columns = group_exprs
             .iter()
-            .map(|expr| expr.schema_name().to_string())
+            .filter_map(|expr| expr.try_as_col().map(|col| col.name.clone()))
             .collect();
+        let value_column = self.ctx.field_columns[0].clone();
 
-        // Create ranks filter with `Operator::Or`.
-        // Safety: at least one rank column
-        let filter: DfExpr = rank_columns
-            .iter()
-            .fold(None, |expr, rank| {
-                let predicate = DfExpr::BinaryExpr(BinaryExpr {
-                    left: Box::new(col(rank)),
-                    op: Operator::LtEq,
-                    right: Box::new(lit(val)),
-                });
-
-                match expr {
-                    None => Some(predicate),
-                    Some(expr) => Some(DfExpr::BinaryExpr(BinaryExpr {
-                        left: Box::new(expr),
-                        op: Operator::Or,
-                        right: Box::new(predicate),
-                    })),
-                }
-            })
-            .unwrap();
-
-        let rank_columns: Vec<_> = rank_columns.into_iter().map(col).collect();
-
-        let mut new_group_exprs = group_exprs.clone();
-        // Order by ranks
-        new_group_exprs.extend(rank_columns);
+        let topk = TopK::new(
+            k,
+            !asc,
+            group_columns,
+            value_column.clone(),
+            self.ctx.tag_columns.clone(),
+            input,
+        )
+        .context(DataFusionPlanningSnafu)?;
 
-        let group_sort_expr = new_group_exprs
+        // `TopK` doesn't sort groups. Order by groups, then the rank in each group, which
+        // is the value and then tags.
+        let group_sort_expr = group_exprs
             .into_iter()
-            .map(|expr| expr.sort(true, false));
+            .map(|expr| expr.sort(true, false))
+            .chain(Some(
+                DfExpr::Column(Column::from_name(value_column)).sort(asc, true),
+            ))
+            .chain(
+                self.create_tag_column_exprs()?
+                    .into_iter()
+                    .map(|expr| expr.sort(asc, true)),
+            );
 
         let project_fields = self
             .create_field_column_exprs()?
@@ -384,17 +1085,15 @@ impl PromPlanner {
             .chain(self.create_tag_column_exprs()?)
             .chain(Some(self.create_time_index_column_expr()?));
 
-        LogicalPlanBuilder::from(input)
-            .window(window_exprs)
-            .context(DataFusionPlanningSnafu)?
-            .filter(filter)
-            .context(DataFusionPlanningSnafu)?
-            .sort(group_sort_expr)
-            .context(DataFusionPlanningSnafu)?
-            .project(project_fields)
-            .context(DataFusionPlanningSnafu)?
-            .build()
-            .context(DataFusionPlanningSnafu)
+        LogicalPlanBuilder::from(LogicalPlan::Extension(Extension {
+            node: Arc::new(topk),
+        }))
+        .sort(group_sort_expr)
+        .context(DataFusionPlanningSnafu)?
+        .project(project_fields)
+        .context(DataFusionPlanningSnafu)?
+        .build()
+        .context(DataFusionPlanningSnafu)
     }
 
     async fn prom_unary_expr_to_plan(
@@ -430,6 +1129,16 @@ impl PromPlanner {
             false
         };
         let is_comparison_op = Self::is_token_a_comparison_op(*op);
+        // comparisons keep the operand values, arithmetic yields float samples
+        let cast_operand = |input: LogicalPlan, field_columns: &[String]| {
+            if is_comparison_op {
+                Ok(input)
+            } else {
+                Self::cast_operand_to_float(input, field_columns)
+            }
+        };
+        let lhs_may_be_nan = Self::may_be_nan(lhs);
+        let rhs_may_be_nan = Self::may_be_nan(rhs);
 
         // we should build a filter plan here if the op is comparison op and need not
         // to return 0/1. Otherwise, we should build a projection plan
@@ -441,7 +1150,8 @@ impl PromPlanner {
                 self.ctx.time_index_column = Some(DEFAULT_TIME_INDEX_COLUMN.to_string());
                 self.ctx.field_columns = vec![DEFAULT_FIELD_COLUMN.to_string()];
                 self.ctx.reset_table_name_and_schema();
-                let field_expr_builder = Self::prom_token_to_binary_expr_builder(*op)?;
+                let field_expr_builder =
+                    Self::prom_token_to_binary_expr_builder(*op, lhs_may_be_nan, rhs_may_be_nan)?;
                 let mut field_expr = field_expr_builder(lhs, rhs)?;
 
                 if is_comparison_op && should_return_bool {
@@ -468,6 +1178,7 @@ impl PromPlanner {
             // lhs is a literal, rhs is a column
             (Some(mut expr), None) => {
                 let input = self.prom_expr_to_plan(rhs, session_state).await?;
+                let input = cast_operand(input, &self.ctx.field_columns)?;
                 // check if the literal is a special time expr
                 if let Some(time_expr) = Self::try_build_special_time_expr(
                     lhs,
@@ -475,8 +1186,10 @@ impl PromPlanner {
                 ) {
                     expr = time_expr
                 }
+                let expr = self.inline_step_columns(expr)?;
                 let bin_expr_builder = |col: &String| {
-                    let binary_expr_builder = Self::prom_token_to_binary_expr_builder(*op)?;
+                    let binary_expr_builder =
+                        Self::prom_token_to_binary_expr_builder(*op, lhs_may_be_nan, true)?;
                     let mut binary_expr =
                         binary_expr_builder(expr.clone(), DfExpr::Column(col.into()))?;
 
@@ -497,6 +1210,7 @@ impl PromPlanner {
             // lhs is a column, rhs is a literal
             (None, Some(mut expr)) => {
                 let input = self.prom_expr_to_plan(lhs, session_state).await?;
+                let input = cast_operand(input, &self.ctx.field_columns)?;
                 // check if the literal is a special time expr
                 if let Some(time_expr) = Self::try_build_special_time_expr(
                     rhs,
@@ -504,8 +1218,10 @@ impl PromPlanner {
                 ) {
                     expr = time_expr
                 }
+                let expr = self.inline_step_columns(expr)?;
                 let bin_expr_builder = |col: &String| {
-                    let binary_expr_builder = Self::prom_token_to_binary_expr_builder(*op)?;
+                    let binary_expr_builder =
+                        Self::prom_token_to_binary_expr_builder(*op, true, rhs_may_be_nan)?;
                     let mut binary_expr =
                         binary_expr_builder(DfExpr::Column(col.into()), expr.clone())?;
 
@@ -556,6 +1272,8 @@ impl PromPlanner {
                 }
 
                 // normal join
+                let left_input = cast_operand(left_input, &left_field_columns)?;
+                let right_input = cast_operand(right_input, &right_field_columns)?;
                 if left_table_ref == right_table_ref {
                     // rename table references to avoid ambiguity
                     left_table_ref = TableReference::bare("lhs");
@@ -571,6 +1289,10 @@ impl PromPlanner {
                     } else {
                         self.ctx.table_name = Some("rhs".to_string());
                     }
+                } else if self.ctx.tag_columns.is_empty() {
+                    // The right plan is from another table but has no tag, e.g.
+                    // `foo / scalar(sum(bar))`, keeps the tag columns of the left plan.
+                    self.ctx = left_context.clone();
                 }
                 let mut field_columns = left_field_columns.iter().zip(right_field_columns.iter());
 
@@ -586,6 +1308,31 @@ impl PromPlanner {
                     left_context.tag_columns.is_empty() || right_context.tag_columns.is_empty(),
                     modifier,
                 )?;
+                let has_tags =
+                    !left_context.tag_columns.is_empty() && !right_context.tag_columns.is_empty();
+                let join_plan = match modifier.as_ref().map(|modifier| &modifier.card) {
+                    Some(VectorMatchCardinality::ManyToOne(labels)) if has_tags => self
+                        .project_group_labels(
+                            join_plan,
+                            (&left_context, &left_table_ref),
+                            (&right_context, &right_table_ref),
+                            &labels.labels,
+                            true,
+                            &left_field_columns,
+                            &right_field_columns,
+                        )?,
+                    Some(VectorMatchCardinality::OneToMany(labels)) if has_tags => self
+                        .project_group_labels(
+                            join_plan,
+                            (&right_context, &right_table_ref),
+                            (&left_context, &left_table_ref),
+                            &labels.labels,
+                            false,
+                            &left_field_columns,
+                            &right_field_columns,
+                        )?,
+                    _ => join_plan,
+                };
                 let join_plan_schema = join_plan.schema().clone();
 
                 let bin_expr_builder = |_: &String| {
@@ -599,7 +1346,8 @@ impl PromPlanner {
                         .context(DataFusionPlanningSnafu)?
                         .into();
 
-                    let binary_expr_builder = Self::prom_token_to_binary_expr_builder(*op)?;
+                    let binary_expr_builder =
+                        Self::prom_token_to_binary_expr_builder(*op, true, true)?;
                     let mut binary_expr =
                         binary_expr_builder(DfExpr::Column(left_col), DfExpr::Column(right_col))?;
                     if is_comparison_op && should_return_bool {
@@ -642,12 +1390,11 @@ impl PromPlanner {
         Ok(plan)
     }
 
-    fn prom_string_lit_to_plan(&mut self, string_literal: &StringLiteral) -> Result<LogicalPlan> {
-        let StringLiteral { val } = string_literal;
+    /// Plans the selector of a Grafana variable, see [Self::step_column].
+    fn prom_step_column_to_plan(&mut self, column: &str) -> Result<LogicalPlan> {
         self.ctx.time_index_column = Some(DEFAULT_TIME_INDEX_COLUMN.to_string());
         self.ctx.field_columns = vec![DEFAULT_FIELD_COLUMN.to_string()];
         self.ctx.reset_table_name_and_schema();
-        let literal_expr = df_prelude::lit(val.to_string());
 
         let plan = LogicalPlan::Extension(Extension {
             node: Arc::new(
@@ -657,7 +1404,7 @@ impl PromPlanner {
                     self.ctx.interval,
                     SPECIAL_TIME_FUNCTION.to_string(),
                     DEFAULT_FIELD_COLUMN.to_string(),
-                    Some(literal_expr),
+                    Some(Self::build_step_column_expr(column)),
                 )
                 .context(DataFusionPlanningSnafu)?,
             ),
@@ -665,25 +1412,71 @@ impl PromPlanner {
         Ok(plan)
     }
 
-    async fn prom_vector_selector_to_plan(
-        &mut self,
-        vector_selector: &VectorSelector,
-    ) -> Result<LogicalPlan> {
-        let VectorSelector {
-            name,
-            offset,
-            matchers,
-            at: _,
-        } = vector_selector;
+    fn prom_string_lit_to_plan(&mut self, string_literal: &StringLiteral) -> Result<LogicalPlan> {
+        let StringLiteral { val } = string_literal;
+        self.ctx.time_index_column = Some(DEFAULT_TIME_INDEX_COLUMN.to_string());
+        self.ctx.field_columns = vec![DEFAULT_FIELD_COLUMN.to_string()];
+        self.ctx.reset_table_name_and_schema();
+        let literal_expr = df_prelude::lit(val.to_string());
+
+        let plan = LogicalPlan::Extension(Extension {
+            node: Arc::new(
+                EmptyMetric::new(
+                    self.ctx.start,
+                    self.ctx.end,
+                    self.ctx.interval,
+                    SPECIAL_TIME_FUNCTION.to_string(),
+                    DEFAULT_FIELD_COLUMN.to_string(),
+                    Some(literal_expr),
+                )
+                .context(DataFusionPlanningSnafu)?,
+            ),
+        });
+        Ok(plan)
+    }
+
+    async fn prom_vector_selector_to_plan(
+        &mut self,
+        vector_selector: &VectorSelector,
+    ) -> Result<LogicalPlan> {
+        if let Some(column) = Self::step_column(vector_selector) {
+            return self.prom_step_column_to_plan(column);
+        }
+        let VectorSelector {
+            name,
+            offset,
+            matchers,
+            // handled by `prom_step_invariant_expr_to_plan`
+            at,
+        } = vector_selector;
+        let latest_at = at.as_ref().is_some_and(is_latest_at);
+        ensure!(
+            !latest_at || self.ctx.enable_latest_at,
+            LatestAtDisabledSnafu
+        );
+        self.check_offset(offset)?;
+        self.check_at_lookahead(at, offset)?;
+
         let matchers = self.preprocess_label_matchers(matchers, name)?;
         self.setup_context().await?;
+        // `@ latest()` takes the latest sample of each series, which may be older
+        // than the lookback delta.
+        let lookback_delta = if latest_at {
+            LATEST_AT_LOOKBACK_DELTA
+        } else {
+            self.ctx.lookback_delta
+        };
+        let current_lookback_delta =
+            std::mem::replace(&mut self.ctx.lookback_delta, lookback_delta);
         let normalize = self
             .selector_to_series_normalize_plan(offset, matchers, false)
-            .await?;
+            .await;
+        self.ctx.lookback_delta = current_lookback_delta;
+        let normalize = normalize?;
         let manipulate = InstantManipulate::new(
             self.ctx.start,
             self.ctx.end,
-            self.ctx.lookback_delta,
+            lookback_delta,
             self.ctx.interval,
             self.ctx
                 .time_index_column
@@ -691,12 +1484,37 @@ impl PromPlanner {
                 .expect("time index should be set in `setup_context`"),
             self.ctx.field_columns.first().cloned(),
             normalize,
-        );
+        )
+        .with_staleness_delta(self.ctx.staleness_delta);
         Ok(LogicalPlan::Extension(Extension {
             node: Arc::new(manipulate),
         }))
     }
 
+    /// Plans the stored samples of a vector selector within the evaluation range,
+    /// without aligning them to the steps or looking back before the start.
+    async fn prom_raw_samples_to_plan(&mut self, expr: &PromExpr) -> Result<LogicalPlan> {
+        let selector = match expr {
+            PromExpr::VectorSelector(selector)
+                if selector.at.is_none() && Self::step_column(selector).is_none() =>
+            {
+                selector
+            }
+            _ => {
+                return UnsupportedExprSnafu {
+                    name: format!("raw samples of `{expr}`, only vector selectors are supported"),
+                }
+                .fail()
+            }
+        };
+
+        let matchers = self.preprocess_label_matchers(&selector.matchers, &selector.name)?;
+        self.setup_context().await?;
+        self.ctx.lookback_delta = 0;
+        self.selector_to_series_normalize_plan(&selector.offset, matchers, false)
+            .await
+    }
+
     async fn prom_matrix_selector_to_plan(
         &mut self,
         matrix_selector: &MatrixSelector,
@@ -706,8 +1524,14 @@ impl PromPlanner {
             name,
             offset,
             matchers,
-            ..
+            at,
         } = vs;
+        ensure!(
+            !at.as_ref().is_some_and(is_latest_at),
+            UnsupportedLatestAtSnafu
+        );
+        self.check_offset(offset)?;
+        self.check_at_lookahead(at, offset)?;
         let matchers = self.preprocess_label_matchers(matchers, name)?;
         self.setup_context().await?;
 
@@ -718,17 +1542,29 @@ impl PromPlanner {
         let normalize = self
             .selector_to_series_normalize_plan(offset, matchers, true)
             .await?;
+        // the samples before the range are read within the time index filter, which
+        // covers the lookback delta
+        let manipulate_range = if self.ctx.read_previous_sample {
+            range_ms + self.ctx.lookback_delta
+        } else {
+            range_ms
+        };
         let manipulate = RangeManipulate::new(
             self.ctx.start,
             self.ctx.end,
             self.ctx.interval,
             // TODO(ruihang): convert via Timestamp datatypes to support different time units
-            range_ms,
+            manipulate_range,
             self.ctx
                 .time_index_column
                 .clone()
                 .expect("time index should be set in `setup_context`"),
-            self.ctx.field_columns.clone(),
+            self.ctx
+                .field_columns
+                .iter()
+                .chain(&self.ctx.created_column)
+                .cloned()
+                .collect(),
             normalize,
         )
         .context(DataFusionPlanningSnafu)?;
@@ -744,6 +1580,11 @@ impl PromPlanner {
         call_expr: &Call,
     ) -> Result<LogicalPlan> {
         let Call { func, args } = call_expr;
+        if let Some(alpha) = ema_over_steps_alpha(call_expr) {
+            return self
+                .create_ema_over_steps_plan(alpha, args, session_state)
+                .await;
+        }
         // some special functions that are not expression but a plan
         match func.name {
             SPECIAL_HISTOGRAM_QUANTILE => {
@@ -754,10 +1595,22 @@ impl PromPlanner {
             _ => {}
         }
 
+        let absent_labels =
+            (func.name == "absent_over_time").then(|| Self::create_absent_labels(args));
+
         // transform function arguments
         let args = self.create_function_args(&args.args)?;
         let input = if let Some(prom_expr) = &args.input {
-            self.prom_expr_to_plan(prom_expr, session_state).await?
+            self.ctx.read_created = self.ctx.created_timestamps
+                && matches!(func.name, "rate" | "increase")
+                && matches!(prom_expr, PromExpr::MatrixSelector(_));
+            self.ctx.read_previous_sample = self.ctx.rate_first_sample
+                == RateFirstSamplePolicy::UsePreviousSample
+                && matches!(func.name, "rate" | "increase")
+                && matches!(prom_expr, PromExpr::MatrixSelector(_));
+            let input = self.prom_expr_to_plan(prom_expr, session_state).await;
+            self.ctx.read_created = false;
+            Self::prune_range_timestamps(func.name, input?)
         } else {
             self.ctx.time_index_column = Some(SPECIAL_TIME_FUNCTION.to_string());
             self.ctx.reset_table_name_and_schema();
@@ -776,10 +1629,28 @@ impl PromPlanner {
             })
         };
         let mut func_exprs =
-            self.create_function_expr(func, args.literals.clone(), session_state)?;
+            self.create_function_expr(func, args.literals.clone(), input.schema(), session_state)?;
+        // the created timestamps and previous samples are only read by the function
+        self.ctx.created_column = None;
+        self.ctx.read_previous_sample = false;
         func_exprs.insert(0, self.create_time_index_column_expr()?);
         func_exprs.extend_from_slice(&self.create_tag_column_exprs()?);
 
+        // The label generated by `label_join` and `label_replace` is a tag rather than
+        // a value. It may be null when the label is deleted, which shouldn't filter
+        // out the sample.
+        if matches!(func.name, "label_join" | "label_replace")
+            && let Some(DfExpr::Literal(ScalarValue::Utf8(Some(dst_label)))) = args.literals.first()
+            && let Some(pos) = self
+                .ctx
+                .field_columns
+                .iter()
+                .position(|field| field == dst_label)
+        {
+            let dst_label = self.ctx.field_columns.remove(pos);
+            self.ctx.tag_columns.push(dst_label);
+        }
+
         let builder = LogicalPlanBuilder::from(input)
             .project(func_exprs)
             .context(DataFusionPlanningSnafu)?
@@ -811,7 +1682,99 @@ impl PromPlanner {
             _ => builder,
         };
 
-        builder.build().context(DataFusionPlanningSnafu)
+        let plan = builder.build().context(DataFusionPlanningSnafu)?;
+        match absent_labels {
+            Some(labels) => self.create_absent_plan(labels, plan),
+            None => Ok(plan),
+        }
+    }
+
+    /// The labels of the `absent_over_time()` output. Like Prometheus, they are the
+    /// equality matchers of the selector, except the metric name and labels that
+    /// have other matchers.
+    fn create_absent_labels(args: &PromFunctionArgs) -> Vec<(String, String)> {
+        let Some(PromExpr::MatrixSelector(MatrixSelector { vs, .. })) =
+            args.args.first().map(|arg| arg.as_ref())
+        else {
+            return vec![];
+        };
+        let mut labels = BTreeMap::new();
+        for matcher in &vs.matchers.matchers {
+            if matcher.name == METRIC_NAME {
+                continue;
+            }
+            if matcher.op == MatchOp::Equal && !labels.contains_key(&matcher.name) {
+                let _ = labels.insert(matcher.name.clone(), matcher.value.clone());
+            } else {
+                let _ = labels.remove(&matcher.name);
+            }
+        }
+        labels.into_iter().collect()
+    }
+
+    /// Create an [Absent] plan over the stepped output of `present_over_time()`,
+    /// which emits 1 at the steps without any series.
+    fn create_absent_plan(
+        &mut self,
+        labels: Vec<(String, String)>,
+        input: LogicalPlan,
+    ) -> Result<LogicalPlan> {
+        let time_index_column =
+            self.ctx
+                .time_index_column
+                .clone()
+                .with_context(|| TimeIndexNotFoundSnafu {
+                    table: self.ctx.table_name.clone().unwrap_or_default(),
+                })?;
+        let absent = Absent::new(
+            self.ctx.start,
+            self.ctx.end,
+            self.ctx.interval,
+            time_index_column,
+            DEFAULT_FIELD_COLUMN.to_string(),
+            labels.clone(),
+            input,
+        )
+        .context(DataFusionPlanningSnafu)?;
+
+        self.ctx.reset_table_name_and_schema();
+        self.ctx.tag_columns = labels.into_iter().map(|(name, _)| name).collect();
+        self.ctx.field_columns = vec![DEFAULT_FIELD_COLUMN.to_string()];
+
+        Ok(LogicalPlan::Extension(Extension {
+            node: Arc::new(absent),
+        }))
+    }
+
+    /// Stops the input [RangeManipulate] from building the timestamp range if
+    /// the range function only reads the values.
+    fn prune_range_timestamps(func_name: &str, input: LogicalPlan) -> LogicalPlan {
+        if !matches!(
+            func_name,
+            "avg_over_time"
+                | "min_over_time"
+                | "max_over_time"
+                | "sum_over_time"
+                | "count_over_time"
+                | "last_over_time"
+                | "absent_over_time"
+                | "present_over_time"
+                | "stddev_over_time"
+                | "stdvar_over_time"
+                | "changes"
+                | "resets"
+        ) {
+            return input;
+        }
+        let LogicalPlan::Extension(Extension { node }) = &input else {
+            return input;
+        };
+        let Some(manipulate) = node.as_any().downcast_ref::<RangeManipulate>() else {
+            return input;
+        };
+        LogicalPlan::Extension(Extension {
+            node: Arc::new(manipulate.without_timestamps()),
+        })
     }
 
     async fn prom_ext_expr_to_plan(
@@ -903,6 +1866,20 @@ impl PromPlanner {
                     .field_column_matcher
                     .get_or_insert_default()
                     .push(matcher.clone());
+            } else if matcher.name == FIELD_TYPE_MATCHER {
+                ensure!(
+                    matcher.op == MatchOp::Equal,
+                    UnsupportedMatcherOpSnafu {
+                        matcher: matcher.name.to_string(),
+                        matcher_op: matcher.op.to_string(),
+                    }
+                );
+                let field_type = FieldType::parse(&matcher.value).with_context(|| {
+                    UnsupportedFieldTypeSnafu {
+                        field_type: matcher.value.clone(),
+                    }
+                })?;
+                self.ctx.field_type = Some(field_type);
             } else if matcher.name == SCHEMA_COLUMN_MATCHER || matcher.name == DB_COLUMN_MATCHER {
                 ensure!(
                     matcher.op == MatchOp::Equal,
@@ -930,15 +1907,13 @@ impl PromPlanner {
     ) -> Result<LogicalPlan> {
         // make table scan plan
         let table_ref = self.table_ref()?;
-        let mut table_scan = self.create_table_scan_plan(table_ref.clone()).await?;
+        let mut table_scan = self
+            .create_table_scan_plan(table_ref.clone(), is_range_selector)
+            .await?;
         let table_schema = table_scan.schema();
 
         // make filter exprs
-        let offset_duration = match offset {
-            Some(Offset::Pos(duration)) => duration.as_millis() as Millisecond,
-            Some(Offset::Neg(duration)) => -(duration.as_millis() as Millisecond),
-            None => 0,
-        };
+        let offset_duration = Self::offset_duration(offset);
         let mut scan_filters = Self::matchers_to_expr(label_matchers.clone(), table_schema)?;
         if let Some(time_index_filter) = self.build_time_index_filter(offset_duration)? {
             scan_filters.push(time_index_filter);
@@ -1010,8 +1985,11 @@ impl PromPlanner {
                 .filter(|col| result_set.contains(col))
                 .collect();
 
-            let exprs = result_set
-                .into_iter()
+            let exprs = self
+                .ctx
+                .field_columns
+                .iter()
+                .chain(&self.ctx.created_column)
                 .map(|col| DfExpr::Column(Column::new_unqualified(col)))
                 .chain(self.create_tag_column_exprs()?)
                 .chain(Some(self.create_time_index_column_expr()?))
@@ -1197,6 +2175,54 @@ impl PromPlanner {
         Ok(table_ref)
     }
 
+    /// Checks that the read window of a selector with an `@` modifier, which is
+    /// already evaluated at the resolved timestamp, doesn't end later than the
+    /// maximum lookahead after the current time.
+    fn check_at_lookahead(&self, at: &Option<AtModifier>, offset: &Option<Offset>) -> Result<()> {
+        let Some(lookahead) = self.ctx.max_at_lookahead else {
+            return Ok(());
+        };
+        if at.is_none() {
+            return Ok(());
+        }
+        let end = self.ctx.end - Self::offset_duration(offset);
+        ensure!(
+            end <= current_time_millis().saturating_add(lookahead),
+            AtLookaheadExceededSnafu { end, lookahead }
+        );
+        Ok(())
+    }
+
+    /// The [RateFirstSamplePolicy] of `rate()` and `increase()` over the planned range.
+    /// Only ranges of selectors read the previous samples, the others are extrapolated.
+    fn rate_first_sample_policy(&self) -> RateFirstSamplePolicy {
+        if self.ctx.read_previous_sample {
+            RateFirstSamplePolicy::UsePreviousSample
+        } else {
+            RateFirstSamplePolicy::PrometheusExtrapolate
+        }
+    }
+
+    /// Checks that a negative offset is only used when it's enabled.
+    fn check_offset(&self, offset: &Option<Offset>) -> Result<()> {
+        ensure!(
+            self.ctx.enable_negative_offset || !matches!(offset, Some(Offset::Neg(_))),
+            NegativeOffsetDisabledSnafu
+        );
+        Ok(())
+    }
+
+    /// Returns the offset in milliseconds, negative for a negative offset.
+    fn offset_duration(offset: &Option<Offset>) -> Millisecond {
+        match offset {
+            Some(Offset::Pos(duration)) => duration.as_millis() as Millisecond,
+            Some(Offset::Neg(duration)) => -(duration.as_millis() as Millisecond),
+            None => 0,
+        }
+    }
+
+    /// Builds the filter of the samples read for the evaluation. The read window is
+    /// shifted by the offset, which may move it far outside the evaluation range.
     fn build_time_index_filter(&self, offset_duration: i64) -> Result<Option<DfExpr>> {
         let start = self.ctx.start;
         let end = self.ctx.end;
@@ -1210,7 +2236,10 @@ impl PromPlanner {
         let num_points = (end - start) / interval;
 
         // Scan a continuous time range
-        if (end - start) / interval > MAX_SCATTER_POINTS || interval <= INTERVAL_1H {
+        if self.ctx.raw_samples
+            || (end - start) / interval > MAX_SCATTER_POINTS
+            || interval <= INTERVAL_1H
+        {
             let single_time_range = time_index_expr
                 .clone()
                 .gt_eq(DfExpr::Literal(ScalarValue::TimestampMillisecond(
@@ -1227,8 +2256,8 @@ impl PromPlanner {
         }
 
         // Otherwise scan scatter ranges separately
-        let mut filters = Vec::with_capacity(num_points as usize);
-        for timestamp in (start..end).step_by(interval as usize) {
+        let mut filters = Vec::with_capacity(num_points as usize + 1);
+        for timestamp in (start..=end).step_by(interval as usize) {
             filters.push(
                 time_index_expr
                     .clone()
@@ -1252,7 +2281,19 @@ impl PromPlanner {
     ///
     /// # Panic
     /// If the filter is empty
-    async fn create_table_scan_plan(&mut self, table_ref: TableReference) -> Result<LogicalPlan> {
+    /// Scans the table, casting columns whose types PromQL can't consume directly: the
+    /// time index to millisecond, field columns to float64 (see [Self::need_float_cast])
+    /// and a non-string `le` tag to string. The columns of native histograms are packed
+    /// into the [NATIVE_HISTOGRAM_COLUMN].
+    async fn create_table_scan_plan(
+        &mut self,
+        table_ref: TableReference,
+        is_range_selector: bool,
+    ) -> Result<LogicalPlan> {
+        if self.ctx.metric_absent {
+            return Self::create_absent_metric_plan(table_ref);
+        }
+
         let provider = self
             .table_provider
             .resolve_table(table_ref.clone())
@@ -1281,29 +2322,92 @@ impl PromPlanner {
             .build()
             .context(DataFusionPlanningSnafu)?;
 
-        if !is_time_index_ms {
-            // cast to ms if time_index not in Millisecond precision
+        let scan_schema = scan_plan.schema().clone();
+        let is_float_cast_needed = |col: &String| {
+            scan_schema
+                .field_with_unqualified_name(col)
+                .is_ok_and(|field| Self::need_float_cast(field.data_type(), is_range_selector))
+        };
+        let is_le_cast_needed = self.ctx.has_le_tag()
+            && scan_schema
+                .field_with_unqualified_name(LE_COLUMN_NAME)
+                .is_ok_and(|field| field.data_type() != &ArrowDataType::Utf8);
+        let is_histogram_pack_needed = self
+            .ctx
+            .field_columns
+            .iter()
+            .any(|col| col == NATIVE_HISTOGRAM_COLUMN)
+            && scan_schema
+                .field_with_unqualified_name(NATIVE_HISTOGRAM_COLUMN)
+                .is_err();
+        let cast_column = |col: &str, data_type: ArrowDataType| {
+            DfExpr::Alias(Alias {
+                expr: Box::new(DfExpr::Cast(Cast {
+                    expr: Box::new(DfExpr::Column(Column::from_name(col))),
+                    data_type,
+                })),
+                relation: Some(table_ref.clone()),
+                name: col.to_string(),
+            })
+        };
+
+        if !is_time_index_ms
+            || is_le_cast_needed
+            || is_histogram_pack_needed
+            || self.ctx.field_columns.iter().any(is_float_cast_needed)
+        {
+            let time_index =
+                self.ctx
+                    .time_index_column
+                    .as_ref()
+                    .with_context(|| TimeIndexNotFoundSnafu {
+                        table: table_ref.to_quoted_string(),
+                    })?;
             let expr: Vec<_> = self
                 .ctx
                 .field_columns
                 .iter()
-                .map(|col| DfExpr::Column(Column::new(Some(table_ref.clone()), col.clone())))
-                .chain(self.create_tag_column_exprs()?)
-                .chain(Some(DfExpr::Alias(Alias {
-                    expr: Box::new(DfExpr::Cast(Cast {
-                        expr: Box::new(self.create_time_index_column_expr()?),
-                        data_type: ArrowDataType::Timestamp(ArrowTimeUnit::Millisecond, None),
-                    })),
-                    relation: Some(table_ref.clone()),
-                    name: self
-                        .ctx
-                        .time_index_column
-                        .as_ref()
-                        .with_context(|| TimeIndexNotFoundSnafu {
-                            table: table_ref.to_quoted_string(),
-                        })?
-                        .clone(),
-                })))
+                .map(|col| {
+                    if is_histogram_pack_needed && col == NATIVE_HISTOGRAM_COLUMN {
+                        DfExpr::Alias(Alias {
+                            expr: Box::new(DfExpr::ScalarFunction(ScalarFunction {
+                                func: Arc::new(HistogramPack::scalar_udf()),
+                                args: NATIVE_HISTOGRAM_LAYOUT
+                                    .iter()
+                                    .map(|col| DfExpr::Column(Column::from_name(*col)))
+                                    .collect(),
+                            })),
+                            relation: Some(table_ref.clone()),
+                            name: col.clone(),
+                        })
+                    } else if is_float_cast_needed(col) {
+                        cast_column(col, ArrowDataType::Float64)
+                    } else {
+                        DfExpr::Column(Column::new(Some(table_ref.clone()), col.clone()))
+                    }
+                })
+                .chain(self.ctx.tag_columns.iter().map(|col| {
+                    if is_le_cast_needed && col == LE_COLUMN_NAME {
+                        cast_column(col, ArrowDataType::Utf8)
+                    } else {
+                        DfExpr::Column(Column::from_name(col))
+                    }
+                }))
+                .chain(
+                    self.ctx
+                        .created_column
+                        .iter()
+                        .map(|col| DfExpr::Column(Column::from_name(col))),
+                )
+                .chain(Some(if is_time_index_ms {
+                    self.create_time_index_column_expr()?
+                } else {
+                    // cast to ms if time_index not in Millisecond precision
+                    cast_column(
+                        time_index,
+                        ArrowDataType::Timestamp(ArrowTimeUnit::Millisecond, None),
+                    )
+                }))
                 .collect::<Vec<_>>();
             scan_plan = LogicalPlanBuilder::from(scan_plan)
                 .project(expr)
@@ -1318,14 +2422,170 @@ impl PromPlanner {
         Ok(result)
     }
 
+    /// Whether a field column of the given type needs to be cast to float64, the value
+    /// type of PromQL. Booleans are always cast, to 0 and 1. Other numeric types are only
+    /// cast in range selectors, as range functions are defined over float64 values, while
+    /// instant vectors keep their original type (e.g. integer labels in `count_values`).
+    /// Binary columns hold native histograms and are left as-is.
+    fn need_float_cast(data_type: &ArrowDataType, is_range_selector: bool) -> bool {
+        match data_type {
+            ArrowDataType::Boolean => true,
+            ArrowDataType::Float64 => false,
+            data_type => is_range_selector && data_type.is_numeric(),
+        }
+    }
+
+    /// Casts the given field columns of an arithmetic operand to float64 where their type
+    /// isn't, so arithmetic on integer instant vectors yields float samples like
+    /// Prometheus (e.g. `int_metric + 1.5`).
+    fn cast_operand_to_float(input: LogicalPlan, field_columns: &[String]) -> Result<LogicalPlan> {
+        let is_float_cast_needed = |field: &ArrowField| {
+            field_columns.contains(field.name()) && Self::need_float_cast(field.data_type(), true)
+        };
+        if !input
+            .schema()
+            .fields()
+            .iter()
+            .any(|field| is_float_cast_needed(field))
+        {
+            return Ok(input);
+        }
+
+        let project_exprs = input
+            .schema()
+            .iter()
+            .map(|(qualifier, field)| {
+                let column = DfExpr::Column(Column::from((qualifier, field.as_ref())));
+                if is_float_cast_needed(field) {
+                    DfExpr::Cast(Cast {
+                        expr: Box::new(column),
+                        data_type: ArrowDataType::Float64,
+                    })
+                    .alias_qualified(qualifier.cloned(), field.name())
+                } else {
+                    column
+                }
+            })
+            .collect::<Vec<_>>();
+        LogicalPlanBuilder::from(input)
+            .project(project_exprs)
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)
+    }
+
+    /// Selects the field columns of a selector by their data types. Columns of the type
+    /// given by the `__field_type__` matcher are selected if present. Otherwise integer
+    /// columns are left out when there are float columns, so that selectors over tables
+    /// with both pick the float ones regardless of the column order. `__field__` matchers
+    /// choose from all the field columns.
+    fn select_field_columns(
+        &self,
+        columns: Vec<String>,
+        schema: &SchemaRef,
+        table_ref: &TableReference,
+    ) -> Result<Vec<String>> {
+        let field_type_of = |col: &String| {
+            schema
+                .column_schema_by_name(col)
+                .and_then(|column| FieldType::of(&column.data_type))
+        };
+
+        if let Some(field_type) = self.ctx.field_type {
+            let columns: Vec<_> = columns
+                .into_iter()
+                .filter(|col| field_type_of(col) == Some(field_type))
+                .collect();
+            ensure!(
+                !columns.is_empty(),
+                ValueNotFoundSnafu {
+                    table: table_ref.to_quoted_string(),
+                }
+            );
+            return Ok(columns);
+        }
+
+        if self.ctx.field_column_matcher.is_none()
+            && columns
+                .iter()
+                .any(|col| field_type_of(col) == Some(FieldType::Float))
+        {
+            return Ok(columns
+                .into_iter()
+                .filter(|col| field_type_of(col) != Some(FieldType::Int))
+                .collect());
+        }
+
+        Ok(columns)
+    }
+
+    /// Replaces the columns of the [NATIVE_HISTOGRAM_LAYOUT] with the
+    /// [NATIVE_HISTOGRAM_COLUMN] packing them at the position of the first one, so a
+    /// native histogram table has a single value column.
+    fn fold_native_histogram_layout(columns: Vec<String>) -> Vec<String> {
+        let is_layout = |col: &String| NATIVE_HISTOGRAM_LAYOUT.contains(&col.as_str());
+        if columns.iter().filter(|col| is_layout(col)).count() != NATIVE_HISTOGRAM_LAYOUT.len() {
+            return columns;
+        }
+
+        let position = columns.iter().position(is_layout).unwrap();
+        let mut columns: Vec<_> = columns.into_iter().filter(|col| !is_layout(col)).collect();
+        columns.insert(position, NATIVE_HISTOGRAM_COLUMN.to_string());
+        columns
+    }
+
+    /// Returns true if the missing table of a selector is an absent metric of the
+    /// current schema. Tables of an explicit `__schema__` or `__database__` and
+    /// names that aren't metric names are still not found.
+    async fn is_absent_metric(&self, table_ref: &TableReference) -> bool {
+        self.ctx.schema_name.is_none()
+            && is_valid_metric_name(table_ref.table())
+            && self
+                .table_provider
+                .default_schema_exists()
+                .await
+                .unwrap_or(false)
+    }
+
+    /// Plans an absent metric as an empty relation with the time index and the
+    /// field column set by [Self::setup_context].
+    fn create_absent_metric_plan(table_ref: TableReference) -> Result<LogicalPlan> {
+        let schema = ArrowSchema::new(vec![
+            ArrowField::new(
+                DEFAULT_TIME_INDEX_COLUMN,
+                ArrowDataType::Timestamp(ArrowTimeUnit::Millisecond, None),
+                false,
+            ),
+            ArrowField::new(DEFAULT_FIELD_COLUMN, ArrowDataType::Float64, true),
+        ]);
+        let schema = DFSchema::try_from_qualified_schema(table_ref, &schema)
+            .context(DataFusionPlanningSnafu)?;
+        Ok(LogicalPlan::EmptyRelation(EmptyRelation {
+            produce_one_row: false,
+            schema: Arc::new(schema),
+        }))
+    }
+
     /// Setup [PromPlannerContext]'s state fields.
     async fn setup_context(&mut self) -> Result<()> {
         let table_ref = self.table_ref()?;
-        let table = self
-            .table_provider
-            .resolve_table(table_ref.clone())
-            .await
-            .context(CatalogSnafu)?
+        let table_source = match self.table_provider.resolve_table(table_ref.clone()).await {
+            Ok(table_source) => table_source,
+            // Like Prometheus, an absent metric is a selector without matching series
+            // rather than an error.
+            Err(e)
+                if e.status_code() == StatusCode::TableNotFound
+                    && self.is_absent_metric(&table_ref).await =>
+            {
+                self.ctx.metric_absent = true;
+                self.ctx.time_index_column = Some(DEFAULT_TIME_INDEX_COLUMN.to_string());
+                self.ctx.field_columns = vec![DEFAULT_FIELD_COLUMN.to_string()];
+                self.ctx.tag_columns = vec![];
+                return Ok(());
+            }
+            Err(e) => return Err(e).context(CatalogSnafu),
+        };
+        let table = table_source
             .as_any()
             .downcast_ref::<DefaultTableSource>()
             .context(UnknownTableSnafu)?
@@ -1346,14 +2606,28 @@ impl PromPlanner {
             .clone();
         self.ctx.time_index_column = Some(time_index);
 
-        // set values columns
+        // set values columns, the created timestamps of counters are not values
         let values = table
             .table_info()
             .meta
             .field_column_names()
+            .filter(|col| *col != GREPTIME_CREATED)
             .cloned()
             .collect();
-        self.ctx.field_columns = values;
+        let values = Self::fold_native_histogram_layout(values);
+        self.ctx.field_columns = self.select_field_columns(values, &table.schema(), &table_ref)?;
+        self.ctx.created_column = self
+            .ctx
+            .read_created
+            .then(|| {
+                table
+                    .schema()
+                    .column_schema_by_name(GREPTIME_CREATED)
+                    .cloned()
+            })
+            .flatten()
+            .filter(|column| column.data_type == ConcreteDataType::timestamp_millisecond_datatype())
+            .map(|column| column.name);
 
         // set primary key (tag) columns
         let tags = table
@@ -1412,6 +2686,7 @@ impl PromPlanner {
         &mut self,
         func: &Function,
         other_input_exprs: Vec<DfExpr>,
+        input_schema: &DFSchemaRef,
         session_state: &SessionState,
     ) -> Result<Vec<DfExpr>> {
         // TODO(ruihang): check function args list
@@ -1420,28 +2695,88 @@ impl PromPlanner {
         // TODO(ruihang): set this according to in-param list
         let field_column_pos = 0;
         let mut exprs = Vec::with_capacity(self.ctx.field_columns.len());
+        // Like Prometheus, functions transforming float samples drop the series of
+        // native histograms. Their values are replaced by nulls, which are filtered
+        // out later.
+        let histogram_columns = if FLOAT_TRANSFORM_FUNCTIONS.contains(&func.name) {
+            self.ctx
+                .field_columns
+                .iter()
+                .filter(|col| {
+                    input_schema
+                        .field_with_unqualified_name(col)
+                        .is_ok_and(|field| field.data_type() == &ArrowDataType::Binary)
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        } else {
+            vec![]
+        };
+        if !histogram_columns.is_empty() {
+            self.table_provider.query_ctx().set_warning(format!(
+                "PromQL warning: ignored native histograms in {}()",
+                func.name
+            ));
+        }
         let scalar_func = match func.name {
-            "increase" => ScalarFunc::ExtrapolateUdf(Arc::new(Increase::scalar_udf(
-                self.ctx.range.context(ExpectRangeSelectorSnafu)?,
-            ))),
-            "rate" => ScalarFunc::ExtrapolateUdf(Arc::new(Rate::scalar_udf(
-                self.ctx.range.context(ExpectRangeSelectorSnafu)?,
-            ))),
+            _ if !histogram_columns.is_empty()
+                && histogram_columns.len() == self.ctx.field_columns.len() =>
+            {
+                ScalarFunc::GeneratedExpr
+            }
+            "increase" => {
+                let range = self.ctx.range.context(ExpectRangeSelectorSnafu)?;
+                ScalarFunc::ExtrapolateUdf(Arc::new(Increase::scalar_udf_with_options(
+                    range,
+                    self.ctx.created_column.is_some(),
+                    self.rate_first_sample_policy(),
+                )))
+            }
+            "rate" => {
+                let range = self.ctx.range.context(ExpectRangeSelectorSnafu)?;
+                ScalarFunc::ExtrapolateUdf(Arc::new(Rate::scalar_udf_with_options(
+                    range,
+                    self.ctx.created_column.is_some(),
+                    self.rate_first_sample_policy(),
+                )))
+            }
             "delta" => ScalarFunc::ExtrapolateUdf(Arc::new(Delta::scalar_udf(
                 self.ctx.range.context(ExpectRangeSelectorSnafu)?,
             ))),
+            "idelta" if self.has_native_histogram_ranges(input_schema) => {
+                ScalarFunc::Udf(Arc::new(HistogramIDelta::<false>::scalar_udf()))
+            }
+            "irate" if self.has_native_histogram_ranges(input_schema) => {
+                ScalarFunc::Udf(Arc::new(HistogramIDelta::<true>::scalar_udf()))
+            }
             "idelta" => ScalarFunc::Udf(Arc::new(IDelta::<false>::scalar_udf())),
             "irate" => ScalarFunc::Udf(Arc::new(IDelta::<true>::scalar_udf())),
             "resets" => ScalarFunc::Udf(Arc::new(Resets::scalar_udf())),
             "changes" => ScalarFunc::Udf(Arc::new(Changes::scalar_udf())),
             "deriv" => ScalarFunc::Udf(Arc::new(Deriv::scalar_udf())),
+            "avg_over_time" if self.has_native_histogram_ranges(input_schema) => {
+                ScalarFunc::Udf(Arc::new(HistogramAvgOverTime::scalar_udf()))
+            }
+            "avg_over_time" if self.ctx.propagate_nan => {
+                ScalarFunc::Udf(Arc::new(AvgOverTimePropagateNan::scalar_udf()))
+            }
+            "min_over_time" if self.ctx.propagate_nan => {
+                ScalarFunc::Udf(Arc::new(MinOverTimePropagateNan::scalar_udf()))
+            }
+            "max_over_time" if self.ctx.propagate_nan => {
+                ScalarFunc::Udf(Arc::new(MaxOverTimePropagateNan::scalar_udf()))
+            }
+            "sum_over_time" if self.ctx.propagate_nan => {
+                ScalarFunc::Udf(Arc::new(SumOverTimePropagateNan::scalar_udf()))
+            }
             "avg_over_time" => ScalarFunc::Udf(Arc::new(AvgOverTime::scalar_udf())),
             "min_over_time" => ScalarFunc::Udf(Arc::new(MinOverTime::scalar_udf())),
             "max_over_time" => ScalarFunc::Udf(Arc::new(MaxOverTime::scalar_udf())),
             "sum_over_time" => ScalarFunc::Udf(Arc::new(SumOverTime::scalar_udf())),
             "count_over_time" => ScalarFunc::Udf(Arc::new(CountOverTime::scalar_udf())),
             "last_over_time" => ScalarFunc::Udf(Arc::new(LastOverTime::scalar_udf())),
-            "absent_over_time" => ScalarFunc::Udf(Arc::new(AbsentOverTime::scalar_udf())),
+            // the absent steps are found by the [Absent] plan over the present ones
+            "absent_over_time" => ScalarFunc::Udf(Arc::new(PresentOverTime::scalar_udf())),
             "present_over_time" => ScalarFunc::Udf(Arc::new(PresentOverTime::scalar_udf())),
             "stddev_over_time" => ScalarFunc::Udf(Arc::new(StddevOverTime::scalar_udf())),
             "stdvar_over_time" => ScalarFunc::Udf(Arc::new(StdvarOverTime::scalar_udf())),
@@ -1495,6 +2830,26 @@ impl PromPlanner {
                 ));
                 ScalarFunc::GeneratedExpr
             }
+            "timestamp" => {
+                // The time index of the input is the step time after instant manipulation,
+                // which is also the time column of generated series like `vector()`.
+                let time_index = self.ctx.time_index_column.as_ref().with_context(|| {
+                    TimeIndexNotFoundSnafu {
+                        table: self.ctx.table_name.clone().unwrap_or_default(),
+                    }
+                })?;
+                for value in &self.ctx.field_columns {
+                    let expr = df_prelude::when(
+                        DfExpr::Column(Column::from_name(value)).is_not_null(),
+                        build_special_time_expr(time_index),
+                    )
+                    .end()
+                    .context(DataFusionPlanningSnafu)?
+                    .alias(format!("timestamp({value})"));
+                    exprs.push(expr);
+                }
+                ScalarFunc::GeneratedExpr
+            }
             "minute" => {
                 // date_part('minute', time_index)
                 let expr = self.date_part_on_time_index("minute")?;
@@ -1556,7 +2911,7 @@ impl PromPlanner {
                 });
                 let date_trunc_expr = DfExpr::ScalarFunction(ScalarFunction {
                     func: datafusion_functions::datetime::date_trunc(),
-                    args: vec![month_lit_expr, self.create_time_index_column_expr()?],
+                    args: vec![month_lit_expr, self.create_calendar_time_index_expr()?],
                 });
                 let date_trunc_plus_interval_expr = DfExpr::BinaryExpr(BinaryExpr {
                     left: Box::new(date_trunc_expr),
@@ -1573,8 +2928,11 @@ impl PromPlanner {
             }
 
             "label_join" => {
-                let (concat_expr, dst_label) =
-                    Self::build_concat_labels_expr(&mut other_input_exprs, session_state)?;
+                let (concat_expr, dst_label) = Self::build_concat_labels_expr(
+                    &mut other_input_exprs,
+                    self.ctx.table_name.as_deref(),
+                    session_state,
+                )?;
 
                 // Reserve the current field columns except the `dst_label`.
                 for value in &self.ctx.field_columns {
@@ -1593,8 +2951,11 @@ impl PromPlanner {
                 ScalarFunc::GeneratedExpr
             }
             "label_replace" => {
-                let (replace_expr, dst_label) =
-                    Self::build_regexp_replace_label_expr(&mut other_input_exprs, session_state)?;
+                let (replace_expr, dst_label) = Self::build_regexp_replace_label_expr(
+                    &mut other_input_exprs,
+                    self.ctx.table_name.as_deref(),
+                    session_state,
+                )?;
 
                 // Reserve the current field columns except the `dst_label`.
                 for value in &self.ctx.field_columns {
@@ -1607,8 +2968,8 @@ impl PromPlanner {
                 // Remove it from tag columns
                 self.ctx.tag_columns.retain(|tag| *tag != dst_label);
 
-                // Add the new label expr
-                exprs.push(replace_expr);
+                // Add the new label expr, unless the label is deleted from all series
+                exprs.extend(replace_expr);
 
                 ScalarFunc::GeneratedExpr
             }
@@ -1622,6 +2983,8 @@ impl PromPlanner {
 
                 ScalarFunc::GeneratedExpr
             }
+            "histogram_count" => ScalarFunc::DataFusionUdf(Arc::new(HistogramCount::scalar_udf())),
+            "histogram_sum" => ScalarFunc::DataFusionUdf(Arc::new(HistogramSum::scalar_udf())),
             "round" => {
                 let nearest = match other_input_exprs.pop_front() {
                     Some(DfExpr::Literal(ScalarValue::Float64(Some(t)))) => t,
@@ -1656,6 +3019,14 @@ impl PromPlanner {
         for value in &self.ctx.field_columns {
             let col_expr = DfExpr::Column(Column::from_name(value));
 
+            if histogram_columns.contains(value) {
+                exprs.push(
+                    DfExpr::Literal(ScalarValue::Float64(None))
+                        .alias(format!("{}({value})", func.name)),
+                );
+                continue;
+            }
+
             match scalar_func.clone() {
                 ScalarFunc::DataFusionBuiltin(func) => {
                     other_input_exprs.insert(field_column_pos, col_expr);
@@ -1701,11 +3072,20 @@ impl PromPlanner {
                     other_input_exprs.insert(field_column_pos + 1, col_expr);
                     other_input_exprs
                         .insert(field_column_pos + 2, self.create_time_index_column_expr()?);
+                    if let Some(created) = &self.ctx.created_column {
+                        other_input_exprs.insert(
+                            field_column_pos + 3,
+                            DfExpr::Column(Column::from_name(created)),
+                        );
+                    }
                     let fn_expr = DfExpr::ScalarFunction(ScalarFunction {
                         func,
                         args: other_input_exprs.clone().into(),
                     });
                     exprs.push(fn_expr);
+                    if self.ctx.created_column.is_some() {
+                        let _ = other_input_exprs.remove(field_column_pos + 3);
+                    }
                     let _ = other_input_exprs.remove(field_column_pos + 2);
                     let _ = other_input_exprs.remove(field_column_pos + 1);
                     let _ = other_input_exprs.remove(field_column_pos);
@@ -1714,6 +3094,19 @@ impl PromPlanner {
             }
         }
 
+        if self.ctx.integer_counts && matches!(func.name, "count_over_time" | "changes" | "resets")
+        {
+            exprs = exprs
+                .into_iter()
+                .map(|expr| {
+                    DfExpr::Cast(Cast {
+                        expr: Box::new(expr),
+                        data_type: ArrowDataType::Int64,
+                    })
+                })
+                .collect();
+        }
+
         // update value columns' name, and alias them to remove qualifiers
         let mut new_field_columns = Vec::with_capacity(exprs.len());
 
@@ -1722,7 +3115,7 @@ impl PromPlanner {
             .map(|expr| {
                 let display_name = expr.schema_name().to_string();
                 new_field_columns.push(display_name.clone());
-                Ok(expr.alias(display_name))
+                Ok(expr.unalias().alias(display_name))
             })
             .collect::<std::result::Result<Vec<_>, _>>()
             .context(DataFusionPlanningSnafu)?;
@@ -1733,10 +3126,17 @@ impl PromPlanner {
     }
 
     /// Build expr for `label_replace` function
+    ///
+    /// Same as Prometheus, the `dst_label` is deleted when the replacement
+    /// results in an empty string. Returns `None` as expr if it's deleted
+    /// from all series.
+    ///
+    /// The `src_label` can be `__name__`, whose value is the `metric_name`.
     fn build_regexp_replace_label_expr(
         other_input_exprs: &mut VecDeque<DfExpr>,
+        metric_name: Option<&str>,
         session_state: &SessionState,
-    ) -> Result<(DfExpr, String)> {
+    ) -> Result<(Option<DfExpr>, String)> {
         // label_replace(vector, dst_label, replacement, src_label, regex)
         let dst_label = match other_input_exprs.pop_front() {
             Some(DfExpr::Literal(ScalarValue::Utf8(Some(d)))) => d,
@@ -1767,6 +3167,30 @@ impl PromPlanner {
             .fail()?,
         };
 
+        // The value of an absent `src_label` is always empty, and the metric
+        // name is the same for all series, so the result can be computed here.
+        let constant_src = if src_label.is_empty() {
+            Some("")
+        } else if src_label == METRIC_NAME {
+            Some(metric_name.unwrap_or_default())
+        } else {
+            None
+        };
+        if let Some(src_value) = constant_src
+            && let Ok(re) = regex::Regex::new(&format!("^(?:{regex})$"))
+            && let Some(captures) = re.captures(src_value)
+        {
+            let mut value = String::new();
+            captures.expand(&replacement, &mut value);
+            if value.is_empty() {
+                return Ok((None, dst_label));
+            }
+            return Ok((
+                Some(DfExpr::Literal(ScalarValue::Utf8(Some(value))).alias(&dst_label)),
+                dst_label,
+            ));
+        }
+
         let func = session_state
             .scalar_functions()
             .get("regexp_replace")
@@ -1774,30 +3198,54 @@ impl PromPlanner {
                 name: "regexp_replace",
             })?;
 
-        // regexp_replace(src_label, regex, replacement)
+        // nullif(regexp_replace(src_label, regex, replacement), '')
         let args = vec![
-            if src_label.is_empty() {
-                DfExpr::Literal(ScalarValue::Null)
-            } else {
-                DfExpr::Column(Column::from_name(src_label))
-            },
+            Self::label_value_expr(src_label, metric_name),
             DfExpr::Literal(ScalarValue::Utf8(Some(regex))),
             DfExpr::Literal(ScalarValue::Utf8(Some(replacement))),
         ];
+        let replace_expr = DfExpr::ScalarFunction(ScalarFunction {
+            func: func.clone(),
+            args,
+        });
 
         Ok((
-            DfExpr::ScalarFunction(ScalarFunction {
-                func: func.clone(),
-                args,
-            })
-            .alias(&dst_label),
+            Some(
+                DfExpr::ScalarFunction(ScalarFunction {
+                    func: datafusion_functions::core::nullif(),
+                    args: vec![
+                        replace_expr,
+                        DfExpr::Literal(ScalarValue::Utf8(Some(String::new()))),
+                    ],
+                })
+                .alias(&dst_label),
+            ),
             dst_label,
         ))
     }
 
+    /// Build expr of the value of `label` in label functions. An empty label
+    /// is absent, and `__name__` is the metric name.
+    fn label_value_expr(label: &str, metric_name: Option<&str>) -> DfExpr {
+        if label == METRIC_NAME {
+            return match metric_name.filter(|name| !name.is_empty()) {
+                Some(name) => DfExpr::Literal(ScalarValue::Utf8(Some(name.to_string()))),
+                None => DfExpr::Literal(ScalarValue::Null),
+            };
+        }
+        if label.is_empty() {
+            DfExpr::Literal(ScalarValue::Null)
+        } else {
+            DfExpr::Column(Column::from_name(label))
+        }
+    }
+
     /// Build expr for `label_join` function
+    ///
+    /// The source labels can contain `__name__`, whose value is the `metric_name`.
     fn build_concat_labels_expr(
         other_input_exprs: &mut VecDeque<DfExpr>,
+        metric_name: Option<&str>,
         session_state: &SessionState,
     ) -> Result<(DfExpr, String)> {
         // label_join(vector, dst_label, separator, src_label_1, src_label_2, ...)
@@ -1823,11 +3271,7 @@ impl PromPlanner {
                 // Cast source label into column
                 match expr {
                     DfExpr::Literal(ScalarValue::Utf8(Some(label))) => {
-                        if label.is_empty() {
-                            Ok(DfExpr::Literal(ScalarValue::Null))
-                        } else {
-                            Ok(DfExpr::Column(Column::from_name(label)))
-                        }
+                        Ok(Self::label_value_expr(&label, metric_name))
                     }
                     other => UnexpectedPlanExprSnafu {
                         desc: format!(
@@ -1875,6 +3319,26 @@ impl PromPlanner {
         )))
     }
 
+    /// Create the time index expr for calendar functions. If a timezone is set, the time
+    /// index is reinterpreted as a timestamp in that timezone, so that calendar fields are
+    /// extracted in its local time. The timezone database resolves DST transitions.
+    fn create_calendar_time_index_expr(&self) -> Result<DfExpr> {
+        let time_index = self.create_time_index_column_expr()?;
+        let Some(timezone) = &self.ctx.timezone else {
+            return Ok(time_index);
+        };
+
+        // Casting a timestamp to a timestamp with another timezone shifts the value, so the
+        // time index is cast through its raw milliseconds to only attach the timezone.
+        Ok(DfExpr::Cast(Cast {
+            expr: Box::new(DfExpr::Cast(Cast {
+                expr: Box::new(time_index),
+                data_type: ArrowDataType::Int64,
+            })),
+            data_type: ArrowDataType::Timestamp(ArrowTimeUnit::Millisecond, Some(timezone.clone())),
+        }))
+    }
+
     fn create_tag_column_exprs(&self) -> Result<Vec<DfExpr>> {
         let mut result = Vec::with_capacity(self.ctx.tag_columns.len());
         for tag in &self.ctx.tag_columns {
@@ -1917,22 +3381,43 @@ impl PromPlanner {
         tags: Vec<DfExpr>,
         asc: bool,
     ) -> Result<Vec<SortExpr>> {
-        ensure!(
-            !tags.is_empty(),
-            FunctionInvalidArgumentSnafu { fn_name: func }
-        );
-
-        tags.iter()
-            .map(|col| match col {
+        let mut labels = Vec::with_capacity(tags.len());
+        let mut numeric = false;
+        for tag in tags {
+            match tag {
                 DfExpr::Literal(ScalarValue::Utf8(Some(label))) => {
-                    Ok(DfExpr::Column(Column::from_name(label)).sort(asc, false))
+                    if label == NUMERIC_SORT_MODIFIER {
+                        numeric = true;
+                    } else {
+                        labels.push(label);
+                    }
                 }
                 other => UnexpectedPlanExprSnafu {
                     desc: format!("expected label string literal, but found {:?}", other),
                 }
-                .fail(),
-            })
-            .collect::<Result<Vec<_>>>()
+                .fail()?,
+            }
+        }
+        ensure!(
+            !labels.is_empty(),
+            FunctionInvalidArgumentSnafu { fn_name: func }
+        );
+
+        let mut sort_exprs = Vec::with_capacity(labels.len() * 2);
+        for label in labels {
+            let column = DfExpr::Column(Column::from_name(label));
+            if numeric {
+                // Non-numeric values can't be casted and are placed after numeric
+                // values in ascending order, then sorted lexically.
+                let number = DfExpr::TryCast(TryCast::new(
+                    Box::new(column.clone()),
+                    ArrowDataType::Float64,
+                ));
+                sort_exprs.push(number.sort(asc, !asc));
+            }
+            sort_exprs.push(column.sort(asc, false));
+        }
+        Ok(sort_exprs)
     }
 
     fn create_empty_values_filter_expr(&self) -> Result<DfExpr> {
@@ -1968,6 +3453,36 @@ impl PromPlanner {
         param: &Option<Box<PromExpr>>,
         input_plan: &LogicalPlan,
     ) -> Result<(Vec<DfExpr>, Vec<DfExpr>)> {
+        let skip_nan_kind = if self.ctx.propagate_nan {
+            None
+        } else {
+            match op.id() {
+                token::T_SUM => Some(SkipNanAggrKind::Sum),
+                token::T_AVG => Some(SkipNanAggrKind::Avg),
+                token::T_MIN => Some(SkipNanAggrKind::Min),
+                token::T_MAX => Some(SkipNanAggrKind::Max),
+                _ => None,
+            }
+        };
+        // Float64 fields use the PromQL aggregators, see `SkipNanAggr` and `StdAggr`
+        let float_aggr = match op.id() {
+            token::T_STDDEV => Some(StdAggr::udaf(StdAggrKind::Stddev)),
+            token::T_STDVAR => Some(StdAggr::udaf(StdAggrKind::Stdvar)),
+            _ => skip_nan_kind.map(SkipNanAggr::udaf),
+        };
+        // Binary fields hold native histograms, which can only be summed or averaged.
+        let histogram_kind = match op.id() {
+            token::T_SUM => Some(HistogramAggrKind::Sum),
+            token::T_AVG => Some(HistogramAggrKind::Avg),
+            _ => None,
+        };
+        let histogram_aggr = histogram_kind.map(|kind| {
+            let query_ctx = self.table_provider.query_ctx().clone();
+            HistogramAggr::udaf(
+                kind,
+                Arc::new(move |warning| query_ctx.set_warning(warning)),
+            )
+        });
         let aggr = match op.id() {
             token::T_SUM => sum_udaf(),
             token::T_QUANTILE => {
@@ -1994,8 +3509,22 @@ impl PromPlanner {
             .field_columns
             .iter()
             .map(|col| {
+                // NaN only exists in Float64 fields and native histograms in Binary fields,
+                // other types keep the DataFusion aggregators to keep their output types.
+                let data_type = input_plan
+                    .schema()
+                    .field_with_unqualified_name(col)
+                    .map(|field| field.data_type().clone())
+                    .ok();
+                let func = match (&float_aggr, &histogram_aggr, data_type) {
+                    (Some(float_aggr), _, Some(ArrowDataType::Float64)) => float_aggr.clone(),
+                    (_, Some(histogram_aggr), Some(ArrowDataType::Binary)) => {
+                        histogram_aggr.clone()
+                    }
+                    _ => aggr.clone(),
+                };
                 Ok(DfExpr::AggregateFunction(AggregateFunction {
-                    func: aggr.clone(),
+                    func,
                     args: vec![DfExpr::Column(Column::from_name(col))],
                     distinct: false,
                     filter: None,
@@ -2071,59 +3600,6 @@ impl PromPlanner {
         Ok(*val)
     }
 
-    /// Create [DfExpr::WindowFunction] expr for each value column with given window function.
-    ///
-    fn create_window_exprs(
-        &mut self,
-        op: TokenType,
-        group_exprs: Vec<DfExpr>,
-        input_plan: &LogicalPlan,
-    ) -> Result<Vec<DfExpr>> {
-        ensure!(
-            self.ctx.field_columns.len() == 1,
-            UnsupportedExprSnafu {
-                name: "topk or bottomk on multi-value input"
-            }
-        );
-
-        assert!(matches!(op.id(), token::T_TOPK | token::T_BOTTOMK));
-
-        let asc = matches!(op.id(), token::T_BOTTOMK);
-
-        let tag_sort_exprs = self
-            .create_tag_column_exprs()?
-            .into_iter()
-            .map(|expr| expr.sort(asc, true));
-
-        // perform window operation to each value column
-        let exprs: Vec<DfExpr> = self
-            .ctx
-            .field_columns
-            .iter()
-            .map(|col| {
-                let mut sort_exprs = Vec::with_capacity(self.ctx.tag_columns.len() + 1);
-                // Order by value in the specific order
-                sort_exprs.push(DfExpr::Column(Column::from(col)).sort(asc, true));
-                // Then tags if the values are equal,
-                // Try to ensure the relative stability of the output results.
-                sort_exprs.extend(tag_sort_exprs.clone());
-
-                DfExpr::WindowFunction(WindowFunction {
-                    fun: WindowFunctionDefinition::WindowUDF(Arc::new(RowNumber::new().into())),
-                    args: vec![],
-                    partition_by: group_exprs.clone(),
-                    order_by: sort_exprs,
-                    window_frame: WindowFrame::new(Some(true)),
-                    null_treatment: None,
-                })
-            })
-            .collect();
-
-        let normalized_exprs =
-            normalize_cols(exprs.iter().cloned(), input_plan).context(DataFusionPlanningSnafu)?;
-        Ok(normalized_exprs)
-    }
-
     /// Create a [SPECIAL_HISTOGRAM_QUANTILE] plan.
     async fn create_histogram_plan(
         &mut self,
@@ -2141,9 +3617,16 @@ impl PromPlanner {
                 fn_name: SPECIAL_HISTOGRAM_QUANTILE.to_string(),
             }
         })?;
-        let input = args.args[1].as_ref().clone();
+        let mut input = args.args[1].as_ref().clone();
+        if self.ctx.resolve_bucket_suffix {
+            self.resolve_bucket_series(&mut input).await;
+        }
         let input_plan = self.prom_expr_to_plan(&input, session_state).await?;
 
+        if let Some(histogram_column) = self.native_histogram_column(&input_plan) {
+            return self.create_native_histogram_quantile_plan(phi, histogram_column, input_plan);
+        }
+
         if !self.ctx.has_le_tag() {
             return ColumnNotFoundSnafu {
                 col: LE_COLUMN_NAME.to_string(),
@@ -2183,6 +3666,128 @@ impl PromPlanner {
         }))
     }
 
+    /// Renames the selectors in the input of `histogram_quantile()` that select the
+    /// base name of a classic histogram to its `_bucket` series. A selector is only
+    /// renamed if its table doesn't exist while the bucket table does.
+    async fn resolve_bucket_series(&mut self, expr: &mut PromExpr) {
+        let mut selectors = vec![];
+        Self::collect_vector_selectors(expr, &mut selectors);
+        for vs in selectors {
+            let Some(name) = &vs.name else {
+                continue;
+            };
+            if name.ends_with(BUCKET_SUFFIX) {
+                continue;
+            }
+            let schema = vs
+                .matchers
+                .matchers
+                .iter()
+                .find(|m| {
+                    (m.name == SCHEMA_COLUMN_MATCHER || m.name == DB_COLUMN_MATCHER)
+                        && m.op == MatchOp::Equal
+                })
+                .map(|m| m.value.clone());
+            let bucket_name = format!("{name}{BUCKET_SUFFIX}");
+            if !self.table_exists(schema.as_deref(), name).await
+                && self.table_exists(schema.as_deref(), &bucket_name).await
+            {
+                vs.name = Some(bucket_name);
+            }
+        }
+    }
+
+    /// Collects the vector selectors in the expression, including the ones of
+    /// matrix selectors.
+    fn collect_vector_selectors<'a>(
+        expr: &'a mut PromExpr,
+        selectors: &mut Vec<&'a mut VectorSelector>,
+    ) {
+        match expr {
+            PromExpr::VectorSelector(vs) => selectors.push(vs),
+            PromExpr::MatrixSelector(ms) => selectors.push(&mut ms.vs),
+            PromExpr::Paren(ParenExpr { expr })
+            | PromExpr::Subquery(SubqueryExpr { expr, .. })
+            | PromExpr::Unary(UnaryExpr { expr })
+            | PromExpr::Aggregate(AggregateExpr { expr, .. }) => {
+                Self::collect_vector_selectors(expr, selectors)
+            }
+            PromExpr::Call(Call { args, .. }) => {
+                for arg in &mut args.args {
+                    Self::collect_vector_selectors(arg, selectors);
+                }
+            }
+            PromExpr::Binary(PromBinaryExpr { lhs, rhs, .. }) => {
+                Self::collect_vector_selectors(lhs, selectors);
+                Self::collect_vector_selectors(rhs, selectors);
+            }
+            PromExpr::NumberLiteral(_) | PromExpr::StringLiteral(_) | PromExpr::Extension(_) => {}
+        }
+    }
+
+    /// Returns true if the table exists in the given schema, or the current schema if None.
+    async fn table_exists(&mut self, schema: Option<&str>, table: &str) -> bool {
+        let table_ref = match schema {
+            Some(schema) => TableReference::partial(schema, table),
+            None => TableReference::bare(table),
+        };
+        self.table_provider.resolve_table(table_ref).await.is_ok()
+    }
+
+    /// Returns the field column that stores native histograms, if any.
+    fn native_histogram_column(&self, input_plan: &LogicalPlan) -> Option<String> {
+        let schema = input_plan.schema();
+        self.ctx
+            .field_columns
+            .iter()
+            .find(|col| {
+                schema
+                    .field_with_unqualified_name(col)
+                    .is_ok_and(|field| field.data_type() == &ArrowDataType::Binary)
+            })
+            .cloned()
+    }
+
+    /// Whether any field column holds ranges of native histograms, i.e. the output
+    /// of range selectors over native histograms.
+    fn has_native_histogram_ranges(&self, schema: &DFSchemaRef) -> bool {
+        let range_type = RangeArray::convert_data_type(ArrowDataType::Binary);
+        self.ctx.field_columns.iter().any(|col| {
+            schema
+                .field_with_unqualified_name(col)
+                .is_ok_and(|field| field.data_type() == &range_type)
+        })
+    }
+
+    /// Create a [SPECIAL_HISTOGRAM_QUANTILE] plan over native histograms. Unlike
+    /// conventional histograms, each row holds all buckets so the quantile is
+    /// computed row by row.
+    fn create_native_histogram_quantile_plan(
+        &mut self,
+        phi: f64,
+        histogram_column: String,
+        input_plan: LogicalPlan,
+    ) -> Result<LogicalPlan> {
+        let quantile_expr = DfExpr::ScalarFunction(ScalarFunction {
+            func: Arc::new(HistogramQuantile::scalar_udf(phi)),
+            args: vec![DfExpr::Column(Column::from_name(histogram_column))],
+        });
+        let field_column = quantile_expr.schema_name().to_string();
+        // each row already holds all buckets, so a `le` label is meaningless here
+        self.ctx.tag_columns.retain(|col| col != LE_COLUMN_NAME);
+
+        let mut exprs = vec![self.create_time_index_column_expr()?];
+        exprs.extend(self.create_tag_column_exprs()?);
+        exprs.push(quantile_expr.alias(&field_column));
+        self.ctx.field_columns = vec![field_column];
+
+        LogicalPlanBuilder::from(input_plan)
+            .project(exprs)
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)
+    }
+
     /// Create a [SPECIAL_VECTOR_FUNCTION] plan
     async fn create_vector_plan(&mut self, args: &PromFunctionArgs) -> Result<LogicalPlan> {
         if args.args.len() != 1 {
@@ -2217,6 +3822,47 @@ impl PromPlanner {
         }))
     }
 
+    /// Create an [EmaOverSteps] plan for the non-standard `ema_over_steps(alpha, v)`,
+    /// see [ema_over_steps_alpha].
+    async fn create_ema_over_steps_plan(
+        &mut self,
+        alpha: &PromExpr,
+        args: &PromFunctionArgs,
+        session_state: &SessionState,
+    ) -> Result<LogicalPlan> {
+        let alpha = Self::try_build_float_literal(alpha)
+            .filter(|alpha| *alpha > 0.0 && *alpha <= 1.0)
+            .with_context(|| FunctionInvalidArgumentSnafu {
+                fn_name: EMA_OVER_STEPS_FUNCTION.to_string(),
+            })?;
+        ensure!(
+            args.len() == 2,
+            FunctionInvalidArgumentSnafu {
+                fn_name: EMA_OVER_STEPS_FUNCTION
+            }
+        );
+        let input = self.prom_expr_to_plan(&args.args[1], session_state).await?;
+        let time_index_column =
+            self.ctx
+                .time_index_column
+                .clone()
+                .with_context(|| TimeIndexNotFoundSnafu {
+                    table: self.ctx.table_name.clone().unwrap_or_default(),
+                })?;
+        let ema = EmaOverSteps::new(
+            alpha,
+            time_index_column,
+            self.ctx.tag_columns.clone(),
+            self.ctx.field_columns.clone(),
+            input,
+        )
+        .context(DataFusionPlanningSnafu)?;
+
+        Ok(LogicalPlan::Extension(Extension {
+            node: Arc::new(ema),
+        }))
+    }
+
     /// Create a [SCALAR_FUNCTION] plan
     async fn create_scalar_plan(
         &mut self,
@@ -2272,8 +3918,10 @@ impl PromPlanner {
                 let scalar_value = ScalarValue::Utf8(Some(val.to_string()));
                 Some(DfExpr::Literal(scalar_value))
             }
-            PromExpr::VectorSelector(_)
-            | PromExpr::MatrixSelector(_)
+            PromExpr::VectorSelector(selector) => {
+                Self::step_column(selector).map(Self::build_step_column_expr)
+            }
+            PromExpr::MatrixSelector(_)
             | PromExpr::Extension(_)
             | PromExpr::Aggregate(_)
             | PromExpr::Subquery(_) => None,
@@ -2293,10 +3941,14 @@ impl PromPlanner {
                 op,
                 modifier,
             }) => {
+                let lhs_may_be_nan = Self::may_be_nan(lhs);
+                let rhs_may_be_nan = Self::may_be_nan(rhs);
                 let lhs = Self::try_build_literal_expr(lhs)?;
                 let rhs = Self::try_build_literal_expr(rhs)?;
                 let is_comparison_op = Self::is_token_a_comparison_op(*op);
-                let expr_builder = Self::prom_token_to_binary_expr_builder(*op).ok()?;
+                let expr_builder =
+                    Self::prom_token_to_binary_expr_builder(*op, lhs_may_be_nan, rhs_may_be_nan)
+                        .ok()?;
                 let expr = expr_builder(lhs, rhs).ok()?;
 
                 let should_return_bool = if let Some(m) = modifier {
@@ -2316,6 +3968,48 @@ impl PromPlanner {
         }
     }
 
+    /// Returns the [EmptyMetric] column the selector is planned into, if it's one of
+    /// the selectors Grafana's `$__interval_ms`, `$__range_ms` and `$__range_s`
+    /// variables are rewritten into by the parser.
+    fn step_column(selector: &VectorSelector) -> Option<&'static str> {
+        if !selector.matchers.matchers.is_empty()
+            || selector.offset.is_some()
+            || selector.at.is_some()
+        {
+            return None;
+        }
+        [INTERVAL_COLUMN, RANGE_START_COLUMN, RANGE_END_COLUMN]
+            .into_iter()
+            .find(|column| selector.name.as_deref() == Some(*column))
+    }
+
+    /// Build the float expr of the millisecond values in a step column of [EmptyMetric].
+    fn build_step_column_expr(column: &str) -> DfExpr {
+        DfExpr::Cast(Cast {
+            expr: Box::new(DfExpr::Column(Column::from_name(column))),
+            data_type: ArrowDataType::Float64,
+        })
+    }
+
+    /// Replaces the step columns in the literal `expr` with their values, as only
+    /// [EmptyMetric] provides these columns.
+    fn inline_step_columns(&self, expr: DfExpr) -> Result<DfExpr> {
+        expr.transform(|expr| {
+            let DfExpr::Column(column) = &expr else {
+                return Ok(Transformed::no(expr));
+            };
+            let value = match column.name.as_str() {
+                INTERVAL_COLUMN => self.ctx.interval,
+                RANGE_START_COLUMN => self.ctx.start,
+                RANGE_END_COLUMN => self.ctx.end,
+                _ => return Ok(Transformed::no(expr)),
+            };
+            Ok(Transformed::yes(df_prelude::lit(value)))
+        })
+        .map(|transformed| transformed.data)
+        .context(DataFusionPlanningSnafu)
+    }
+
     fn try_build_special_time_expr(expr: &PromExpr, time_index_col: &str) -> Option<DfExpr> {
         match expr {
             PromExpr::Call(Call { func, .. }) => {
@@ -2348,42 +4042,188 @@ impl PromPlanner {
         }
     }
 
-    /// Return a lambda to build binary expression from token.
-    /// Because some binary operator are function in DataFusion like `atan2` or `^`.
-    #[allow(clippy::type_complexity)]
-    fn prom_token_to_binary_expr_builder(
-        token: TokenType,
-    ) -> Result<Box<dyn Fn(DfExpr, DfExpr) -> Result<DfExpr>>> {
-        match token.id() {
-            token::T_ADD => Ok(Box::new(|lhs, rhs| Ok(lhs + rhs))),
-            token::T_SUB => Ok(Box::new(|lhs, rhs| Ok(lhs - rhs))),
-            token::T_MUL => Ok(Box::new(|lhs, rhs| Ok(lhs * rhs))),
-            token::T_DIV => Ok(Box::new(|lhs, rhs| Ok(lhs / rhs))),
-            token::T_MOD => Ok(Box::new(|lhs: DfExpr, rhs| Ok(lhs % rhs))),
-            token::T_EQLC => Ok(Box::new(|lhs, rhs| Ok(lhs.eq(rhs)))),
-            token::T_NEQ => Ok(Box::new(|lhs, rhs| Ok(lhs.not_eq(rhs)))),
-            token::T_GTR => Ok(Box::new(|lhs, rhs| Ok(lhs.gt(rhs)))),
-            token::T_LSS => Ok(Box::new(|lhs, rhs| Ok(lhs.lt(rhs)))),
-            token::T_GTE => Ok(Box::new(|lhs, rhs| Ok(lhs.gt_eq(rhs)))),
-            token::T_LTE => Ok(Box::new(|lhs, rhs| Ok(lhs.lt_eq(rhs)))),
-            token::T_POW => Ok(Box::new(|lhs, rhs| {
-                Ok(DfExpr::ScalarFunction(ScalarFunction {
-                    func: datafusion_functions::math::power(),
-                    args: vec![lhs, rhs],
-                }))
-            })),
-            token::T_ATAN2 => Ok(Box::new(|lhs, rhs| {
-                Ok(DfExpr::ScalarFunction(ScalarFunction {
-                    func: datafusion_functions::math::atan2(),
-                    args: vec![lhs, rhs],
-                }))
-            })),
-            _ => UnexpectedTokenSnafu { token }.fail(),
+    /// Check if the given expr may be evaluated to NaN. Only number literals and `time()`
+    /// are known to be not NaN.
+    fn may_be_nan(expr: &PromExpr) -> bool {
+        if let Some(val) = Self::try_build_float_literal(expr) {
+            return val.is_nan();
         }
+        !matches!(expr, PromExpr::Call(Call { func, .. }) if func.name == SPECIAL_TIME_FUNCTION)
     }
 
-    /// Check if the given op is a [comparison operator](https://prometheus.io/docs/prometheus/latest/querying/operators/#comparison-binary-operators).
-    fn is_token_a_comparison_op(token: TokenType) -> bool {
+    /// Returns the `__name__` label of the series returned by the expression. Like
+    /// Prometheus, functions, aggregations and arithmetic drop the metric name unless
+    /// they only select or reorder the input samples.
+    fn result_metric_name(expr: &PromExpr) -> Option<String> {
+        match expr {
+            PromExpr::VectorSelector(vs) => Self::selector_metric_name(vs),
+            PromExpr::MatrixSelector(ms) => Self::selector_metric_name(&ms.vs),
+            PromExpr::Paren(ParenExpr { expr }) | PromExpr::Subquery(SubqueryExpr { expr, .. }) => {
+                Self::result_metric_name(expr)
+            }
+            PromExpr::Call(Call { func, args }) => match func.name {
+                "last_over_time" | "first_over_time" | "label_replace" | "label_join" | "sort"
+                | "sort_desc" | "sort_by_label" | "sort_by_label_desc" => args
+                    .args
+                    .first()
+                    .and_then(|arg| Self::result_metric_name(arg)),
+                _ => None,
+            },
+            PromExpr::Aggregate(AggregateExpr {
+                op, expr, modifier, ..
+            }) => {
+                let keeps_name = match op.id() {
+                    token::T_TOPK | token::T_BOTTOMK => true,
+                    token::T_COUNT_VALUES => false,
+                    _ => matches!(
+                        modifier,
+                        Some(LabelModifier::Include(labels))
+                            if labels.labels.iter().any(|label| label == METRIC_NAME)
+                    ),
+                };
+                keeps_name.then(|| Self::result_metric_name(expr)).flatten()
+            }
+            PromExpr::Binary(PromBinaryExpr {
+                lhs,
+                rhs,
+                op,
+                modifier,
+            }) => {
+                let return_bool = modifier.as_ref().is_some_and(|m| m.return_bool);
+                if Self::is_token_a_set_op(*op) {
+                    let lhs_name = Self::result_metric_name(lhs);
+                    if op.id() == token::T_LOR && lhs_name != Self::result_metric_name(rhs) {
+                        // The series of both sides are returned.
+                        return None;
+                    }
+                    lhs_name
+                } else if Self::is_token_a_comparison_op(*op) && !return_bool {
+                    // Filters keep the samples of the vector side.
+                    match Self::try_build_literal_expr(lhs) {
+                        Some(_) => Self::result_metric_name(rhs),
+                        None => Self::result_metric_name(lhs),
+                    }
+                } else {
+                    None
+                }
+            }
+            PromExpr::Unary(_)
+            | PromExpr::NumberLiteral(_)
+            | PromExpr::StringLiteral(_)
+            | PromExpr::Extension(_) => None,
+        }
+    }
+
+    /// Returns the metric name of a selector, from its name or `__name__` matcher.
+    fn selector_metric_name(vs: &VectorSelector) -> Option<String> {
+        vs.name.clone().or_else(|| {
+            vs.matchers
+                .find_matchers(METRIC_NAME)
+                .into_iter()
+                .find(|matcher| matcher.op == MatchOp::Equal)
+                .map(|matcher| matcher.value)
+        })
+    }
+
+    /// Adds the metric name returned by the expression as the `__name__` column,
+    /// unless the plan already has a `__name__` column like the one generated by
+    /// `label_replace()`.
+    fn add_metric_name_column(expr: &PromExpr, plan: LogicalPlan) -> Result<LogicalPlan> {
+        let Some(metric_name) = Self::result_metric_name(expr) else {
+            return Ok(plan);
+        };
+        if plan.schema().has_column_with_unqualified_name(METRIC_NAME) {
+            return Ok(plan);
+        }
+        let exprs = plan
+            .schema()
+            .columns()
+            .into_iter()
+            .map(DfExpr::Column)
+            .chain([df_prelude::lit(metric_name).alias(METRIC_NAME)])
+            .collect::<Vec<_>>();
+        LogicalPlanBuilder::from(plan)
+            .project(exprs)
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)
+    }
+
+    /// Build `isnan(expr)`.
+    fn is_nan_expr(expr: DfExpr) -> DfExpr {
+        DfExpr::ScalarFunction(ScalarFunction {
+            func: datafusion_functions::math::isnan(),
+            args: vec![DfExpr::Cast(Cast {
+                expr: Box::new(expr),
+                data_type: ArrowDataType::Float64,
+            })],
+        })
+    }
+
+    /// Return a lambda to build binary expression from token.
+    /// Because some binary operator are function in DataFusion like `atan2` or `^`.
+    ///
+    /// Comparison operators follow the IEEE 754 semantics like Prometheus: NaN compares
+    /// false to everything and is not equal to itself. DataFusion compares floats in their
+    /// total order instead, where NaN equals NaN and is greater than any other value, so
+    /// NaN checks are added for operands that may be NaN.
+    #[allow(clippy::type_complexity)]
+    fn prom_token_to_binary_expr_builder(
+        token: TokenType,
+        lhs_may_be_nan: bool,
+        rhs_may_be_nan: bool,
+    ) -> Result<Box<dyn Fn(DfExpr, DfExpr) -> Result<DfExpr>>> {
+        // in total order, a value that is not NaN only equals to values that are not NaN.
+        // So equality only needs to be checked if both sides may be NaN.
+        let both_may_be_nan = lhs_may_be_nan && rhs_may_be_nan;
+        match token.id() {
+            token::T_ADD => Ok(Box::new(|lhs, rhs| Ok(lhs + rhs))),
+            token::T_SUB => Ok(Box::new(|lhs, rhs| Ok(lhs - rhs))),
+            token::T_MUL => Ok(Box::new(|lhs, rhs| Ok(lhs * rhs))),
+            token::T_DIV => Ok(Box::new(|lhs, rhs| Ok(lhs / rhs))),
+            token::T_MOD => Ok(Box::new(|lhs: DfExpr, rhs| Ok(lhs % rhs))),
+            token::T_EQLC if both_may_be_nan => Ok(Box::new(|lhs, rhs| {
+                Ok(lhs.clone().eq(rhs).and(!Self::is_nan_expr(lhs)))
+            })),
+            token::T_EQLC => Ok(Box::new(|lhs, rhs| Ok(lhs.eq(rhs)))),
+            token::T_NEQ if both_may_be_nan => Ok(Box::new(|lhs, rhs| {
+                Ok(lhs.clone().not_eq(rhs).or(Self::is_nan_expr(lhs)))
+            })),
+            token::T_NEQ => Ok(Box::new(|lhs, rhs| Ok(lhs.not_eq(rhs)))),
+            token::T_GTR if lhs_may_be_nan => Ok(Box::new(|lhs, rhs| {
+                Ok(lhs.clone().gt(rhs).and(!Self::is_nan_expr(lhs)))
+            })),
+            token::T_GTR => Ok(Box::new(|lhs, rhs| Ok(lhs.gt(rhs)))),
+            token::T_LSS if rhs_may_be_nan => Ok(Box::new(|lhs, rhs| {
+                Ok(lhs.lt(rhs.clone()).and(!Self::is_nan_expr(rhs)))
+            })),
+            token::T_LSS => Ok(Box::new(|lhs, rhs| Ok(lhs.lt(rhs)))),
+            token::T_GTE if lhs_may_be_nan => Ok(Box::new(|lhs, rhs| {
+                Ok(lhs.clone().gt_eq(rhs).and(!Self::is_nan_expr(lhs)))
+            })),
+            token::T_GTE => Ok(Box::new(|lhs, rhs| Ok(lhs.gt_eq(rhs)))),
+            token::T_LTE if rhs_may_be_nan => Ok(Box::new(|lhs, rhs| {
+                Ok(lhs.lt_eq(rhs.clone()).and(!Self::is_nan_expr(rhs)))
+            })),
+            token::T_LTE => Ok(Box::new(|lhs, rhs| Ok(lhs.lt_eq(rhs)))),
+            token::T_POW => Ok(Box::new(|lhs, rhs| {
+                Ok(DfExpr::ScalarFunction(ScalarFunction {
+                    func: datafusion_functions::math::power(),
+                    args: vec![lhs, rhs],
+                }))
+            })),
+            token::T_ATAN2 => Ok(Box::new(|lhs, rhs| {
+                Ok(DfExpr::ScalarFunction(ScalarFunction {
+                    func: datafusion_functions::math::atan2(),
+                    args: vec![lhs, rhs],
+                }))
+            })),
+            _ => UnexpectedTokenSnafu { token }.fail(),
+        }
+    }
+
+    /// Check if the given op is a [comparison operator](https://prometheus.io/docs/prometheus/latest/querying/operators/#comparison-binary-operators).
+    fn is_token_a_comparison_op(token: TokenType) -> bool {
         matches!(
             token.id(),
             token::T_EQLC
@@ -2492,6 +4332,87 @@ impl PromPlanner {
             .context(DataFusionPlanningSnafu)
     }
 
+    /// Projects the join plan of `group_left` or `group_right`. Like Prometheus, the
+    /// result series take the labels of the "many" side, and the `labels` copied from
+    /// the "one" side, which is none for an empty list. The labels and the time index
+    /// are qualified by the table of the many side, which becomes the context, while
+    /// the field columns of both sides keep their qualifiers.
+    fn project_group_labels(
+        &mut self,
+        join_plan: LogicalPlan,
+        many: (&PromPlannerContext, &TableReference),
+        one: (&PromPlannerContext, &TableReference),
+        labels: &[String],
+        is_group_left: bool,
+        left_field_columns: &[String],
+        right_field_columns: &[String],
+    ) -> Result<LogicalPlan> {
+        let (many_context, many_table_ref) = many;
+        let (one_context, one_table_ref) = one;
+        let qualifier = TableReference::bare(many_table_ref.table());
+        let time_index_column =
+            many_context
+                .time_index_column
+                .clone()
+                .with_context(|| TimeIndexNotFoundSnafu {
+                    table: many_table_ref.to_string(),
+                })?;
+
+        let mut tag_columns = vec![];
+        let mut exprs = vec![];
+        for tag in &many_context.tag_columns {
+            if !labels.contains(tag) {
+                exprs.push(
+                    DfExpr::Column(Column::new(Some(many_table_ref.clone()), tag))
+                        .alias_qualified(Some(qualifier.clone()), tag),
+                );
+                tag_columns.push(tag.clone());
+            }
+        }
+        // A label missing on the one side is removed from the result.
+        for label in labels {
+            if one_context.tag_columns.contains(label) {
+                exprs.push(
+                    DfExpr::Column(Column::new(Some(one_table_ref.clone()), label))
+                        .alias_qualified(Some(qualifier.clone()), label),
+                );
+                tag_columns.push(label.clone());
+            }
+        }
+        exprs.push(
+            DfExpr::Column(Column::new(
+                Some(many_table_ref.clone()),
+                &time_index_column,
+            ))
+            .alias_qualified(Some(qualifier), &time_index_column),
+        );
+        let (left_table_ref, right_table_ref) = if is_group_left {
+            (many_table_ref, one_table_ref)
+        } else {
+            (one_table_ref, many_table_ref)
+        };
+        exprs.extend(
+            left_field_columns
+                .iter()
+                .map(|col| DfExpr::Column(Column::new(Some(left_table_ref.clone()), col))),
+        );
+        exprs.extend(
+            right_field_columns
+                .iter()
+                .map(|col| DfExpr::Column(Column::new(Some(right_table_ref.clone()), col))),
+        );
+
+        self.ctx = many_context.clone();
+        self.ctx.table_name = Some(many_table_ref.table().to_string());
+        self.ctx.tag_columns = tag_columns;
+
+        LogicalPlanBuilder::from(join_plan)
+            .project(exprs)
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)
+    }
+
     /// Build a set operator (AND/OR/UNLESS)
     fn set_op_on_non_field_columns(
         &mut self,
@@ -2756,6 +4677,7 @@ impl PromPlanner {
             if col == left_field_col && left_field_col != right_field_col {
                 // alias field in right side if necessary to handle different field name
                 DfExpr::Column(Column::new(right_qualifier.clone(), right_field_col))
+                    .alias(left_field_col)
             } else if tags_not_in_right.contains(col) {
                 DfExpr::Literal(ScalarValue::Utf8(None)).alias(col.to_string())
             } else {
@@ -2906,15 +4828,7 @@ impl PromPlanner {
     /// time index column in context is set
     fn date_part_on_time_index(&self, date_part: &str) -> Result<DfExpr> {
         let lit_expr = DfExpr::Literal(ScalarValue::Utf8(Some(date_part.to_string())));
-        let input_expr = datafusion::logical_expr::col(
-            self.ctx
-                .time_index_column
-                .as_ref()
-                // table name doesn't matters here
-                .with_context(|| TimeIndexNotFoundSnafu {
-                    table: "<doesn't matter>",
-                })?,
-        );
+        let input_expr = self.create_calendar_time_index_expr()?;
         let fn_expr = DfExpr::ScalarFunction(ScalarFunction {
             func: datafusion_functions::datetime::date_part(),
             args: vec![lit_expr, input_expr],
@@ -2942,6 +4856,16 @@ enum ScalarFunc {
     GeneratedExpr,
 }
 
+/// Returns true if `name` matches the metric name pattern of Prometheus,
+/// `[a-zA-Z_:][a-zA-Z0-9_:]*`.
+fn is_valid_metric_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+}
+
 #[cfg(test)]
 mod test {
     use std::time::{Duration, UNIX_EPOCH};
@@ -2950,6 +4874,7 @@ mod test {
     use catalog::RegisterTableRequest;
     use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
     use common_query::test_util::DummyDecoder;
+    use datafusion::common::tree_node::TreeNodeRecursion;
     use datafusion::execution::SessionStateBuilder;
     use datatypes::prelude::ConcreteDataType;
     use datatypes::schema::{ColumnSchema, Schema};
@@ -3030,6 +4955,68 @@ mod test {
         )
     }
 
+    /// Evaluates `hour()` over the given UTC timestamps, in the given timezone.
+    async fn eval_hour_in_timezone(timestamps: Vec<i64>, timezone: Option<&str>) -> Vec<f64> {
+        use datafusion::common::DFSchema;
+        use datafusion::prelude::SessionContext;
+        use datatypes::arrow::array::{AsArray, TimestampMillisecondArray};
+        use datatypes::arrow::compute::cast;
+        use datatypes::arrow::datatypes::{Field, Float64Type, Schema as ArrowSchema};
+        use datatypes::arrow::record_batch::RecordBatch;
+
+        let planner = PromPlanner {
+            table_provider: build_test_table_provider(&[], 0, 0).await,
+            ctx: PromPlannerContext {
+                time_index_column: Some("timestamp".to_string()),
+                timezone: timezone.map(Into::into),
+                ..Default::default()
+            },
+        };
+        let expr = planner.date_part_on_time_index("hour").unwrap();
+
+        let schema = Arc::new(ArrowSchema::new(vec![Field::new(
+            "timestamp",
+            ArrowDataType::Timestamp(ArrowTimeUnit::Millisecond, None),
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(TimestampMillisecondArray::from(timestamps))],
+        )
+        .unwrap();
+        let df_schema = DFSchema::try_from(schema.as_ref().clone()).unwrap();
+        let physical_expr = SessionContext::new()
+            .create_physical_expr(expr, &df_schema)
+            .unwrap();
+        let result = physical_expr
+            .evaluate(&batch)
+            .unwrap()
+            .into_array(batch.num_rows())
+            .unwrap();
+        let result = cast(&result, &ArrowDataType::Float64).unwrap();
+        result.as_primitive::<Float64Type>().values().to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_hour_in_timezone() {
+        // 2024-03-31T00:30:00Z, 2024-03-31T01:30:00Z and 2024-07-01T12:00:00Z.
+        // Europe/Berlin switches from CET (+01:00) to CEST (+02:00) at 2024-03-31T01:00:00Z.
+        let timestamps = vec![1711845000000, 1711848600000, 1719835200000];
+
+        assert_eq!(
+            vec![0.0, 1.0, 12.0],
+            eval_hour_in_timezone(timestamps.clone(), None).await
+        );
+        assert_eq!(
+            vec![1.0, 3.0, 14.0],
+            eval_hour_in_timezone(timestamps.clone(), Some("Europe/Berlin")).await
+        );
+        assert_eq!(
+            vec![9.0, 10.0, 21.0],
+            eval_hour_in_timezone(timestamps, Some("+09:00")).await
+        );
+    }
+
     async fn build_test_table_provider_with_fields(
         table_name_tuples: &[(String, String)],
         tags: &[&str],
@@ -3208,12 +5195,6 @@ mod test {
         do_single_instant_function_call("sqrt", "sqrt").await;
     }
 
-    #[tokio::test]
-    #[should_panic]
-    async fn single_timestamp() {
-        do_single_instant_function_call("timestamp", "").await;
-    }
-
     #[tokio::test]
     async fn single_acos() {
         do_single_instant_function_call("acos", "acos").await;
@@ -3489,6 +5470,11 @@ mod test {
     }
 
     async fn indie_query_plan_compare(query: &str, expected: String) {
+        let plan = indie_query_plan(query).await;
+        assert_eq!(plan.display_indent_schema().to_string(), expected);
+    }
+
+    async fn indie_query_plan(query: &str) -> LogicalPlan {
         let prom_expr = parser::parse(query).unwrap();
         let eval_stmt = EvalStmt {
             expr: prom_expr,
@@ -3512,11 +5498,9 @@ mod test {
             1,
         )
         .await;
-        let plan = PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
+        PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
             .await
-            .unwrap();
-
-        assert_eq!(plan.display_indent_schema().to_string(), expected);
+            .unwrap()
     }
 
     #[tokio::test]
@@ -3542,6 +5526,21 @@ mod test {
         indie_query_plan_compare(query, expected).await;
     }
 
+    #[tokio::test]
+    async fn grafana_step_variables() {
+        // both are literals, evaluated by `EmptyMetric` with the interval column
+        let query = "time() / __interval__";
+        let expected = String::from("EmptyMetric: range=[0..100000000], interval=[5000] [time:Timestamp(Millisecond, None), value:Float64;N]");
+        indie_query_plan_compare(query, expected).await;
+
+        // the range is inlined when the other side is a column
+        let plan = indie_query_plan("some_metric / (__range_end__ - __range_start__)").await;
+        let plan_str = plan.display_indent_schema().to_string();
+        assert!(plan_str.contains("CAST(Int64(100000000) AS Float64)"));
+        assert!(plan_str.contains("CAST(Int64(0) AS Float64)"));
+        assert!(!plan_str.contains(RANGE_END_COLUMN));
+    }
+
     #[tokio::test]
     async fn simple_bool_grammar() {
         let query = "some_metric != bool 1.2345";
@@ -3593,7 +5592,7 @@ mod test {
         let expected = String::from(
             "Filter: prom_increase(timestamp_range,field_0,timestamp) IS NOT NULL [timestamp:Timestamp(Millisecond, None), prom_increase(timestamp_range,field_0,timestamp):Float64;N, tag_0:Utf8]\
             \n  Projection: some_metric.timestamp, prom_increase(timestamp_range, field_0, some_metric.timestamp) AS prom_increase(timestamp_range,field_0,timestamp), some_metric.tag_0 [timestamp:Timestamp(Millisecond, None), prom_increase(timestamp_range,field_0,timestamp):Float64;N, tag_0:Utf8]\
-            \n    PromRangeManipulate: req range=[0..100000000], interval=[5000], eval range=[300000], time index=[timestamp], values=[\"field_0\"] [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Dictionary(Int64, Float64);N, timestamp_range:Dictionary(Int64, Timestamp(Millisecond, None))]\
+            \n    PromRangeManipulate: req range=[0..100000000], interval=[5000], eval range=[300000], time index=[timestamp], values=[\"field_0\"], arrays=[timestamps, values] [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Dictionary(Int64, Float64);N, timestamp_range:Dictionary(Int64, Timestamp(Millisecond, None))]\
             \n      PromSeriesNormalize: offset=[0], time index=[timestamp], filter NaN: [true] [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
             \n        PromSeriesDivide: tags=[\"tag_0\"] [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
             \n          Sort: some_metric.tag_0 ASC NULLS FIRST, some_metric.timestamp ASC NULLS FIRST [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
@@ -3619,13 +5618,59 @@ mod test {
         indie_query_plan_compare(query, expected).await;
     }
 
+    #[tokio::test]
+    async fn nan_comparison_on_value() {
+        let cases = [
+            (
+                "some_metric == NaN",
+                "some_metric.field_0 = Float64(NaN) AND NOT isnan(CAST(some_metric.field_0 AS Float64))",
+            ),
+            (
+                "NaN == some_metric",
+                "Float64(NaN) = some_metric.field_0 AND NOT isnan(CAST(Float64(NaN) AS Float64))",
+            ),
+            (
+                "some_metric != NaN",
+                "some_metric.field_0 != Float64(NaN) OR isnan(CAST(some_metric.field_0 AS Float64))",
+            ),
+            (
+                "NaN != some_metric",
+                "Float64(NaN) != some_metric.field_0 OR isnan(CAST(Float64(NaN) AS Float64))",
+            ),
+            (
+                "some_metric > 1.2345",
+                "some_metric.field_0 > Float64(1.2345) AND NOT isnan(CAST(some_metric.field_0 AS Float64))",
+            ),
+            (
+                "some_metric < NaN",
+                "some_metric.field_0 < Float64(NaN) AND NOT isnan(CAST(Float64(NaN) AS Float64))",
+            ),
+            (
+                "1.2345 != some_metric",
+                "Float64(1.2345) != some_metric.field_0",
+            ),
+        ];
+
+        for (query, filter) in cases {
+            let expected = format!(
+                "Filter: {filter} [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
+            \n  PromInstantManipulate: range=[0..100000000], lookback=[1000], interval=[5000], time index=[timestamp] [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
+            \n    PromSeriesDivide: tags=[\"tag_0\"] [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
+            \n      Sort: some_metric.tag_0 ASC NULLS FIRST, some_metric.timestamp ASC NULLS FIRST [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
+            \n        Filter: some_metric.timestamp >= TimestampMillisecond(-1000, None) AND some_metric.timestamp <= TimestampMillisecond(100001000, None) [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
+            \n          TableScan: some_metric [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]",
+            );
+            indie_query_plan_compare(query, expected).await;
+        }
+    }
+
     #[tokio::test]
     async fn count_over_time() {
         let query = "count_over_time(some_metric[5m])";
         let expected = String::from(
             "Filter: prom_count_over_time(timestamp_range,field_0) IS NOT NULL [timestamp:Timestamp(Millisecond, None), prom_count_over_time(timestamp_range,field_0):Float64;N, tag_0:Utf8]\
             \n  Projection: some_metric.timestamp, prom_count_over_time(timestamp_range, field_0) AS prom_count_over_time(timestamp_range,field_0), some_metric.tag_0 [timestamp:Timestamp(Millisecond, None), prom_count_over_time(timestamp_range,field_0):Float64;N, tag_0:Utf8]\
-            \n    PromRangeManipulate: req range=[0..100000000], interval=[5000], eval range=[300000], time index=[timestamp], values=[\"field_0\"] [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Dictionary(Int64, Float64);N, timestamp_range:Dictionary(Int64, Timestamp(Millisecond, None))]\
+            \n    PromRangeManipulate: req range=[0..100000000], interval=[5000], eval range=[300000], time index=[timestamp], values=[\"field_0\"], arrays=[values] [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Dictionary(Int64, Float64);N, timestamp_range:Dictionary(Int64, Timestamp(Millisecond, None))]\
             \n      PromSeriesNormalize: offset=[0], time index=[timestamp], filter NaN: [true] [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
             \n        PromSeriesDivide: tags=[\"tag_0\"] [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
             \n          Sort: some_metric.tag_0 ASC NULLS FIRST, some_metric.timestamp ASC NULLS FIRST [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
@@ -3718,9 +5763,12 @@ mod test {
     }
 
     #[tokio::test]
-    async fn test_parse_and_operator() {
-        let mut eval_stmt = EvalStmt {
-            expr: PromExpr::NumberLiteral(NumberLiteral { val: 1.0 }),
+    async fn test_absent_over_time_labels() {
+        let eval_stmt = EvalStmt {
+            expr: parser::parse(
+                r#"absent_over_time(foo{__name__="foo", job="x", instance=~"a.*", instance="b"}[5m])"#,
+            )
+            .unwrap(),
             start: UNIX_EPOCH,
             end: UNIX_EPOCH
                 .checked_add(Duration::from_secs(100_000))
@@ -3728,38 +5776,74 @@ mod test {
             interval: Duration::from_secs(5),
             lookback_delta: Duration::from_secs(1),
         };
+        let table_provider = build_test_table_provider_with_fields(
+            &[(DEFAULT_SCHEMA_NAME.to_string(), "foo".to_string())],
+            &["job", "instance"],
+        )
+        .await;
+        let plan = PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
+            .await
+            .unwrap();
 
-        let cases = [
-            r#"count (max by (persistentvolumeclaim,namespace) (kubelet_volume_stats_used_bytes{namespace=~".+"} ) and (max by (persistentvolumeclaim,namespace) (kubelet_volume_stats_used_bytes{namespace=~".+"} )) / (max by (persistentvolumeclaim,namespace) (kubelet_volume_stats_capacity_bytes{namespace=~".+"} )) >= (80 / 100)) or vector (0)"#,
-            r#"count (max by (persistentvolumeclaim,namespace) (kubelet_volume_stats_used_bytes{namespace=~".+"} ) unless (max by (persistentvolumeclaim,namespace) (kubelet_volume_stats_used_bytes{namespace=~".+"} )) / (max by (persistentvolumeclaim,namespace) (kubelet_volume_stats_capacity_bytes{namespace=~".+"} )) >= (80 / 100)) or vector (0)"#,
-        ];
+        // `instance` has a non-equality matcher so it's not a label of the output
+        assert!(plan
+            .display()
+            .to_string()
+            .starts_with(r#"PromAbsent: range=[0..100000000], interval=[5000], time index=[greptime_timestamp], labels=[("job", "x")]"#));
+        assert_eq!(
+            plan.schema().field_names(),
+            vec!["greptime_timestamp", "value", "job"]
+        );
+    }
 
-        for case in cases {
-            let prom_expr = parser::parse(case).unwrap();
-            eval_stmt.expr = prom_expr;
-            let table_provider = build_test_table_provider_with_fields(
+    #[tokio::test]
+    async fn test_vector_by_computed_scalar() {
+        for query in [
+            "some_metric / scalar(sum(some_alt_metric))",
+            "scalar(sum(some_alt_metric)) * some_metric",
+        ] {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(
                 &[
+                    (DEFAULT_SCHEMA_NAME.to_string(), "some_metric".to_string()),
                     (
                         DEFAULT_SCHEMA_NAME.to_string(),
-                        "kubelet_volume_stats_used_bytes".to_string(),
-                    ),
-                    (
-                        DEFAULT_SCHEMA_NAME.to_string(),
-                        "kubelet_volume_stats_capacity_bytes".to_string(),
+                        "some_alt_metric".to_string(),
                     ),
                 ],
-                &["namespace", "persistentvolumeclaim"],
+                1,
+                1,
             )
             .await;
-            // Should be ok
-            let _ = PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
-                .await
-                .unwrap();
+            let plan =
+                PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
+                    .await
+                    .unwrap();
+
+            // The result keeps the tag of the vector and joins the scalar on time index.
+            let field_names = plan.schema().field_names();
+            assert_eq!(3, field_names.len(), "query: {query}");
+            assert_eq!(
+                vec!["some_metric.tag_0", "some_metric.timestamp"],
+                field_names[..2],
+                "query: {query}"
+            );
+            let plan_str = plan.display_indent().to_string();
+            assert!(plan_str.contains("ScalarCalculate"), "query: {query}");
+            assert!(!plan_str.contains("tag_0 = "), "query: {query}");
         }
     }
 
     #[tokio::test]
-    async fn test_nested_binary_op() {
+    async fn test_histogram_quantile_drops_le() {
         let mut eval_stmt = EvalStmt {
             expr: PromExpr::NumberLiteral(NumberLiteral { val: 1.0 }),
             start: UNIX_EPOCH,
@@ -3769,17 +5853,1438 @@ mod test {
             interval: Duration::from_secs(5),
             lookback_delta: Duration::from_secs(1),
         };
+        for case in [
+            "histogram_quantile(0.9, http_request_duration_bucket)",
+            "sum by (host) (histogram_quantile(0.9, sum by (host, le) (http_request_duration_bucket)))",
+        ] {
+            eval_stmt.expr = parser::parse(case).unwrap();
+            let table_provider = build_test_table_provider_with_fields(
+                &[(
+                    DEFAULT_SCHEMA_NAME.to_string(),
+                    "http_request_duration_bucket".to_string(),
+                )],
+                &["host", "le"],
+            )
+            .await;
+            let plan =
+                PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
+                    .await
+                    .unwrap();
+            let schema = plan.schema();
+            assert!(
+                schema.field_with_unqualified_name(LE_COLUMN_NAME).is_err(),
+                "`le` should be dropped from {case}: {schema:?}"
+            );
+            assert!(schema.field_with_unqualified_name("host").is_ok());
+        }
+    }
 
-        let case = r#"sum(rate(nginx_ingress_controller_requests{job=~".*"}[2m])) -
-        (
-            sum(rate(nginx_ingress_controller_requests{namespace=~".*"}[2m]))
-            or
-            vector(0)
-        )"#;
-
-        let prom_expr = parser::parse(case).unwrap();
-        eval_stmt.expr = prom_expr;
-        let table_provider = build_test_table_provider_with_fields(
+    #[tokio::test]
+    async fn test_histogram_quantile_resolve_bucket_suffix() {
+        async fn plan(query: &str, resolve_bucket_suffix: bool) -> Result<LogicalPlan> {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider_with_fields(
+                &[(
+                    DEFAULT_SCHEMA_NAME.to_string(),
+                    "http_request_duration_bucket".to_string(),
+                )],
+                &["host", "le"],
+            )
+            .await;
+            let options = PromPlannerOptions {
+                resolve_bucket_suffix,
+                ..Default::default()
+            };
+            PromPlanner::stmt_to_plan_with_options(
+                table_provider,
+                &eval_stmt,
+                &options,
+                &build_session_state(),
+            )
+            .await
+        }
+
+        let query = "histogram_quantile(0.9, sum by (le) (rate(http_request_duration[5m])))";
+        // The bucket series must be selected explicitly by default.
+        plan(query, false).await.unwrap_err();
+
+        let resolved = plan(query, true).await.unwrap();
+        let explicit = plan(
+            "histogram_quantile(0.9, sum by (le) (rate(http_request_duration_bucket[5m])))",
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            explicit.display_indent().to_string(),
+            resolved.display_indent().to_string()
+        );
+    }
+
+    /// The columns of the [NATIVE_HISTOGRAM_LAYOUT].
+    fn native_histogram_layout_columns() -> Vec<ColumnSchema> {
+        NATIVE_HISTOGRAM_LAYOUT
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let data_type = match i {
+                    0 => ConcreteDataType::int32_datatype(),
+                    1 => ConcreteDataType::boolean_datatype(),
+                    2..=5 => ConcreteDataType::float64_datatype(),
+                    _ => ConcreteDataType::binary_datatype(),
+                };
+                ColumnSchema::new(*name, data_type, true)
+            })
+            .collect()
+    }
+
+    async fn build_native_histogram_table_provider(table_name: &str) -> DfTableSourceProvider {
+        let catalog_list = MemoryCatalogManager::with_default_setup();
+        let mut columns = vec![
+            ColumnSchema::new("job", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(
+                "greptime_timestamp",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+        ];
+        columns.extend(native_histogram_layout_columns());
+        let value_indices = (2..columns.len()).collect();
+        let table_meta = TableMetaBuilder::empty()
+            .schema(Arc::new(Schema::new(columns)))
+            .primary_key_indices(vec![0])
+            .value_indices(value_indices)
+            .next_column_id(1024)
+            .build()
+            .unwrap();
+        let table_info = TableInfoBuilder::default()
+            .name(table_name.to_string())
+            .meta(table_meta)
+            .build()
+            .unwrap();
+        assert!(catalog_list
+            .register_table_sync(RegisterTableRequest {
+                catalog: DEFAULT_CATALOG_NAME.to_string(),
+                schema: DEFAULT_SCHEMA_NAME.to_string(),
+                table_name: table_name.to_string(),
+                table_id: 1024,
+                table: EmptyTable::from_table_info(&table_info),
+            })
+            .is_ok());
+
+        DfTableSourceProvider::new(
+            catalog_list,
+            false,
+            QueryContext::arc(),
+            DummyDecoder::arc(),
+            false,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_native_histogram_functions() {
+        let cases = [
+            (
+                "histogram_quantile(0.9, http_latency)",
+                "prom_histogram_quantile(greptime_histogram)",
+            ),
+            (
+                "histogram_count(http_latency)",
+                "prom_histogram_count(greptime_histogram)",
+            ),
+            (
+                "histogram_sum(http_latency)",
+                "prom_histogram_sum(greptime_histogram)",
+            ),
+            (
+                "avg_over_time(http_latency[5m])",
+                "prom_histogram_avg_over_time(greptime_timestamp_range,greptime_histogram)",
+            ),
+            (
+                "irate(http_latency[5m])",
+                "prom_histogram_irate(greptime_timestamp_range,greptime_histogram)",
+            ),
+            (
+                "idelta(http_latency[5m])",
+                "prom_histogram_idelta(greptime_timestamp_range,greptime_histogram)",
+            ),
+        ];
+
+        for (query, expected) in cases {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_native_histogram_table_provider("http_latency").await;
+            let plan =
+                PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
+                    .await
+                    .unwrap();
+            let plan_str = plan.display_indent_schema().to_string();
+            assert!(plan_str.contains(expected), "{query}: {plan_str}");
+            assert!(!plan_str.contains("HistogramFold"), "{query}: {plan_str}");
+            // The layout columns are packed when scanned.
+            assert!(
+                plan_str.contains(&format!(
+                    "native_histogram({}) AS greptime_histogram",
+                    NATIVE_HISTOGRAM_LAYOUT
+                        .map(|col| format!("http_latency.{col}"))
+                        .join(", ")
+                )),
+                "{query}: {plan_str}"
+            );
+        }
+    }
+
+    /// Executes `query` at time 0 over native histograms of `(job, instance)` series,
+    /// returns the output batches and the query context.
+    async fn execute_native_histogram_query(
+        series: Vec<(&str, &str, promql::native_histogram::NativeHistogram)>,
+        query: &str,
+    ) -> (
+        Vec<datafusion::arrow::record_batch::RecordBatch>,
+        session::context::QueryContextRef,
+    ) {
+        use datafusion::physical_plan::collect;
+        use datatypes::prelude::VectorRef;
+        use datatypes::vectors::{
+            BinaryVector, BooleanVector, Float64Vector, Int32Vector, StringVector,
+            TimestampMillisecondVector,
+        };
+        use promql::extension_plan::register_promql_extensions;
+        use promql::native_histogram::{encode_spans, encode_values};
+        use table::test_util::MemTable;
+
+        let mut schema = vec![
+            ColumnSchema::new("job", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new("instance", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(
+                "greptime_timestamp",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+        ];
+        schema.extend(native_histogram_layout_columns());
+        let schema = Arc::new(Schema::new(schema));
+        let histograms = series.iter().map(|(_, _, h)| h).collect::<Vec<_>>();
+        let floats = |f: fn(&promql::native_histogram::NativeHistogram) -> f64| {
+            Arc::new(Float64Vector::from_vec(
+                histograms.iter().map(|h| f(h)).collect(),
+            )) as VectorRef
+        };
+        let binaries = |f: fn(&promql::native_histogram::NativeHistogram) -> Vec<u8>| {
+            Arc::new(BinaryVector::from(
+                histograms.iter().map(|h| f(h)).collect::<Vec<_>>(),
+            )) as VectorRef
+        };
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(
+                series.iter().map(|(job, _, _)| *job).collect::<Vec<_>>(),
+            )),
+            Arc::new(StringVector::from(
+                series
+                    .iter()
+                    .map(|(_, instance, _)| *instance)
+                    .collect::<Vec<_>>(),
+            )),
+            Arc::new(TimestampMillisecondVector::from_vec(vec![0; series.len()])),
+            Arc::new(Int32Vector::from_vec(
+                histograms.iter().map(|h| h.schema).collect(),
+            )),
+            Arc::new(BooleanVector::from(
+                histograms.iter().map(|h| h.is_gauge).collect::<Vec<_>>(),
+            )),
+            floats(|h| h.zero_threshold),
+            floats(|h| h.zero_count),
+            floats(|h| h.count),
+            floats(|h| h.sum),
+            binaries(|h| encode_spans(&h.positive_spans)),
+            binaries(|h| encode_values(&h.positive_buckets)),
+            binaries(|h| encode_spans(&h.negative_spans)),
+            binaries(|h| encode_values(&h.negative_buckets)),
+            binaries(|h| encode_values(&h.custom_values)),
+        ];
+        let recordbatch = common_recordbatch::RecordBatch::new(schema, columns).unwrap();
+        let table =
+            MemTable::table_with_primary_keys("http_latency", recordbatch, 1024, vec![0, 1]);
+        let catalog_manager = MemoryCatalogManager::with_default_setup();
+        catalog_manager
+            .register_table_sync(RegisterTableRequest {
+                catalog: DEFAULT_CATALOG_NAME.to_string(),
+                schema: DEFAULT_SCHEMA_NAME.to_string(),
+                table_name: "http_latency".to_string(),
+                table_id: 1024,
+                table,
+            })
+            .unwrap();
+        let table_provider = DfTableSourceProvider::new(
+            catalog_manager,
+            false,
+            QueryContext::arc(),
+            DummyDecoder::arc(),
+            false,
+        );
+        let query_ctx = table_provider.query_ctx().clone();
+
+        let session_state = register_promql_extensions(SessionStateBuilder::new()).build();
+        let plan = PromPlanner::plan_str(
+            table_provider,
+            query,
+            UNIX_EPOCH,
+            UNIX_EPOCH,
+            Duration::from_secs(60),
+            Duration::from_secs(300),
+            &session_state,
+        )
+        .await
+        .unwrap();
+        // The schemas are only known when the histograms are aggregated.
+        assert!(query_ctx.warning().is_none());
+
+        let physical_plan = session_state.create_physical_plan(&plan).await.unwrap();
+        let batches = collect(physical_plan, session_state.task_ctx())
+            .await
+            .unwrap();
+        (batches, query_ctx)
+    }
+
+    #[tokio::test]
+    async fn test_histogram_quantile_over_sum_of_mixed_schemas() {
+        use datafusion::arrow::array::{Array, AsArray};
+        use datafusion::arrow::datatypes::Float64Type;
+        use promql::native_histogram::{BucketSpan, NativeHistogram};
+
+        // Both histograms cover (1, 2] with 2 observations, in different schemas.
+        let fine = NativeHistogram {
+            schema: 1,
+            zero_threshold: 0.001,
+            count: 2.0,
+            sum: 3.0,
+            positive_spans: vec![BucketSpan {
+                offset: 1,
+                length: 2,
+            }],
+            positive_buckets: vec![1.0, 1.0],
+            ..Default::default()
+        };
+        let coarse = NativeHistogram {
+            schema: 0,
+            zero_threshold: 0.001,
+            count: 2.0,
+            sum: 3.5,
+            positive_spans: vec![BucketSpan {
+                offset: 1,
+                length: 1,
+            }],
+            positive_buckets: vec![2.0],
+            ..Default::default()
+        };
+        let (batches, query_ctx) = execute_native_histogram_query(
+            vec![("api", "a", fine), ("api", "b", coarse)],
+            "histogram_quantile(0.5, sum(http_latency))",
+        )
+        .await;
+        let quantiles = batches
+            .iter()
+            .flat_map(|batch| {
+                let column = batch
+                    .columns()
+                    .iter()
+                    .find(|column| column.data_type() == &ArrowDataType::Float64)
+                    .unwrap();
+                column.as_primitive::<Float64Type>().values().to_vec()
+            })
+            .collect::<Vec<_>>();
+        // The median lies in the middle of (1, 2] on the logarithmic scale.
+        assert_eq!(1, quantiles.len());
+        assert!((quantiles[0] - 2f64.sqrt()).abs() < 1e-9, "{quantiles:?}");
+        assert_eq!(
+            Some(
+                "PromQL warning: native histograms with different schemas were promoted to the coarsest schema in sum()"
+                    .to_string()
+            ),
+            query_ctx.warning()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sum_and_avg_native_histograms_by_label() {
+        use datafusion::arrow::array::{Array, AsArray};
+        use promql::native_histogram::{BucketSpan, NativeHistogram};
+
+        let histogram = |count: f64| NativeHistogram {
+            schema: 0,
+            zero_threshold: 0.001,
+            count,
+            sum: count * 1.5,
+            positive_spans: vec![BucketSpan {
+                offset: 1,
+                length: 1,
+            }],
+            positive_buckets: vec![count],
+            ..Default::default()
+        };
+        let series = vec![
+            ("api", "a", histogram(2.0)),
+            ("api", "b", histogram(4.0)),
+            ("db", "a", histogram(8.0)),
+        ];
+
+        for (query, expected) in [
+            ("sum by (job) (http_latency)", [("api", 6.0), ("db", 8.0)]),
+            ("avg by (job) (http_latency)", [("api", 3.0), ("db", 8.0)]),
+        ] {
+            let (batches, query_ctx) = execute_native_histogram_query(series.clone(), query).await;
+            let mut counts = vec![];
+            for batch in &batches {
+                let jobs = batch
+                    .column_by_name("job")
+                    .unwrap()
+                    .as_string::<i32>()
+                    .clone();
+                // The aggregated field is still a native histogram.
+                let histograms = batch
+                    .columns()
+                    .iter()
+                    .find(|column| column.data_type() == &ArrowDataType::Binary)
+                    .unwrap()
+                    .as_binary::<i32>()
+                    .clone();
+                for i in 0..batch.num_rows() {
+                    let histogram = NativeHistogram::decode(histograms.value(i)).unwrap();
+                    assert_eq!(histogram.count, histogram.positive_buckets[0]);
+                    counts.push((jobs.value(i).to_string(), histogram.count));
+                }
+            }
+            counts.sort_by(|a, b| a.0.cmp(&b.0));
+            let expected = expected
+                .iter()
+                .map(|(job, count)| (job.to_string(), *count))
+                .collect::<Vec<_>>();
+            assert_eq!(expected, counts, "{query}");
+            assert!(query_ctx.warning().is_none(), "{query}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_float_functions_drop_native_histograms() {
+        for func in ["abs", "sgn", "round", "clamp_min"] {
+            let query = if func == "clamp_min" {
+                format!("{func}(http_latency, 0)")
+            } else {
+                format!("{func}(http_latency)")
+            };
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(&query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_native_histogram_table_provider("http_latency").await;
+            let query_ctx = table_provider.query_ctx().clone();
+            let plan =
+                PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
+                    .await
+                    .unwrap();
+
+            // The histogram samples are replaced by nulls, which are filtered out.
+            let plan_str = plan.display_indent_schema().to_string();
+            assert!(
+                plan_str.contains(&format!("NULL AS {func}({NATIVE_HISTOGRAM_COLUMN})")),
+                "{query}: {plan_str}"
+            );
+            assert!(plan_str.contains("IS NOT NULL"), "{query}: {plan_str}");
+            assert_eq!(
+                Some(format!(
+                    "PromQL warning: ignored native histograms in {func}()"
+                )),
+                query_ctx.warning()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_offset_larger_than_range() {
+        // a 1 hour range starting at 2 days, with a 1 day offset
+        let cases = [
+            (
+                "some_metric offset 1d",
+                vec![
+                    "some_metric.timestamp >= TimestampMillisecond(86399000, None) AND some_metric.timestamp <= TimestampMillisecond(90001000, None)",
+                    "PromSeriesNormalize: offset=[86400000]",
+                    "PromInstantManipulate: range=[172800000..176400000], lookback=[1000], interval=[300000]",
+                ],
+            ),
+            (
+                "max_over_time(some_metric[10m:5m] offset 1d)",
+                vec![
+                    "PromInstantManipulate: range=[86100000..90000000], lookback=[1000], interval=[300000]",
+                    "+ IntervalMonthDayNano",
+                    "PromRangeManipulate: req range=[172800000..176400000], interval=[300000], eval range=[600000]",
+                ],
+            ),
+            // the steps are scanned separately with an interval larger than 1 hour
+            (
+                "some_metric offset 1d",
+                vec![
+                    "some_metric.timestamp >= TimestampMillisecond(86399000, None) AND some_metric.timestamp <= TimestampMillisecond(86401000, None) OR some_metric.timestamp >= TimestampMillisecond(93599000, None) AND some_metric.timestamp <= TimestampMillisecond(93601000, None)",
+                ],
+            ),
+        ];
+
+        for (i, (query, expected)) in cases.into_iter().enumerate() {
+            let (end, interval) = if i < 2 {
+                (176_400, 300)
+            } else {
+                (180_000, 7200)
+            };
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(172_800))
+                    .unwrap(),
+                end: UNIX_EPOCH.checked_add(Duration::from_secs(end)).unwrap(),
+                interval: Duration::from_secs(interval),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(
+                &[(DEFAULT_SCHEMA_NAME.to_string(), "some_metric".to_string())],
+                1,
+                1,
+            )
+            .await;
+            let plan =
+                PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
+                    .await
+                    .unwrap();
+            let plan_str = plan.display_indent_schema().to_string();
+            for expected in expected {
+                assert!(plan_str.contains(expected), "{query}: {plan_str}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_negative_and_sub_step_offset() {
+        async fn plan(query: &str, enable_negative_offset: bool) -> Result<String> {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH.checked_add(Duration::from_secs(3600)).unwrap(),
+                end: UNIX_EPOCH.checked_add(Duration::from_secs(7200)).unwrap(),
+                interval: Duration::from_secs(15),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(
+                &[(DEFAULT_SCHEMA_NAME.to_string(), "some_metric".to_string())],
+                1,
+                1,
+            )
+            .await;
+            let options = PromPlannerOptions {
+                enable_negative_offset,
+                ..Default::default()
+            };
+            PromPlanner::stmt_to_plan_with_options(
+                table_provider,
+                &eval_stmt,
+                &options,
+                &build_session_state(),
+            )
+            .await
+            .map(|plan| plan.display_indent_schema().to_string())
+        }
+
+        // an offset smaller than the 15s step is kept as is
+        let plan_str = plan("some_metric offset 7s", false).await.unwrap();
+        assert!(
+            plan_str.contains("PromSeriesNormalize: offset=[7000]"),
+            "{plan_str}"
+        );
+        assert!(
+            plan_str.contains("some_metric.timestamp >= TimestampMillisecond(3592000, None) AND some_metric.timestamp <= TimestampMillisecond(7194000, None)"),
+            "{plan_str}"
+        );
+
+        for query in [
+            "some_metric offset -1m",
+            "rate(some_metric[5m] offset -1m)",
+            "max_over_time(some_metric[10m:1m] offset -1m)",
+        ] {
+            let err = plan(query, false).await.unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    crate::promql::error::Error::NegativeOffsetDisabled { .. }
+                ),
+                "{query}: {err:?}"
+            );
+            plan(query, true).await.unwrap();
+        }
+
+        // a negative offset reads samples after the evaluation time
+        let plan_str = plan("some_metric offset -7s", true).await.unwrap();
+        assert!(
+            plan_str.contains("PromSeriesNormalize: offset=[-7000]"),
+            "{plan_str}"
+        );
+        assert!(
+            plan_str.contains("some_metric.timestamp >= TimestampMillisecond(3606000, None) AND some_metric.timestamp <= TimestampMillisecond(7208000, None)"),
+            "{plan_str}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_at_modifier_in_aggregation() {
+        let cases = [
+            (
+                "sum(rate(some_metric[5m] @ 100))",
+                vec![
+                    "PromRangeManipulate: req range=[100000..100000], interval=[5000], eval range=[300000]",
+                    "PromSeriesNormalize: offset=[0]",
+                ],
+            ),
+            (
+                "sum(rate(some_metric[5m] @ 100 offset 1m))",
+                vec![
+                    "PromRangeManipulate: req range=[100000..100000], interval=[5000], eval range=[300000]",
+                    "PromSeriesNormalize: offset=[60000]",
+                ],
+            ),
+            (
+                "sum by (tag_0) (some_metric @ end())",
+                vec![
+                    "PromInstantManipulate: range=[100000000..100000000], lookback=[1000], interval=[5000]",
+                ],
+            ),
+        ];
+
+        for (query, expected) in cases {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(
+                &[(DEFAULT_SCHEMA_NAME.to_string(), "some_metric".to_string())],
+                1,
+                1,
+            )
+            .await;
+            let plan =
+                PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
+                    .await
+                    .unwrap();
+            let plan_str = plan.display_indent_schema().to_string();
+            for expected in expected {
+                assert!(plan_str.contains(expected), "{query}: {plan_str}");
+            }
+            // the result at the `@` timestamp is repeated on every step
+            assert!(
+                plan_str.contains("EmptyMetric: range=[0..100000000], interval=[5000]"),
+                "{query}: {plan_str}"
+            );
+            // the aggregation is applied after repeating the result
+            let aggr = plan_str.find("Aggregate:").unwrap();
+            let steps = plan_str.find("EmptyMetric:").unwrap();
+            assert!(aggr < steps, "{query}: {plan_str}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_integer_counts() {
+        async fn plan(query: &str, integer_counts: bool) -> LogicalPlan {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(
+                &[(DEFAULT_SCHEMA_NAME.to_string(), "some_metric".to_string())],
+                1,
+                1,
+            )
+            .await;
+            let options = PromPlannerOptions {
+                integer_counts,
+                ..Default::default()
+            };
+            PromPlanner::stmt_to_plan_with_options(
+                table_provider,
+                &eval_stmt,
+                &options,
+                &build_session_state(),
+            )
+            .await
+            .unwrap()
+        }
+
+        for query in [
+            "count_over_time(some_metric[5m])",
+            "changes(some_metric[5m])",
+            "resets(some_metric[5m])",
+        ] {
+            let field_type = |plan: &LogicalPlan| plan.schema().field(1).data_type().clone();
+            assert_eq!(
+                field_type(&plan(query, false).await),
+                ArrowDataType::Float64,
+                "{query}"
+            );
+            assert_eq!(
+                field_type(&plan(query, true).await),
+                ArrowDataType::Int64,
+                "{query}"
+            );
+        }
+        // other range functions are not affected
+        let plan = plan("sum_over_time(some_metric[5m])", true).await;
+        assert_eq!(plan.schema().field(1).data_type(), &ArrowDataType::Float64);
+    }
+
+    #[tokio::test]
+    async fn test_raw_samples() {
+        async fn plan(query: &str) -> Result<LogicalPlan> {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(
+                &[(DEFAULT_SCHEMA_NAME.to_string(), "some_metric".to_string())],
+                1,
+                1,
+            )
+            .await;
+            let options = PromPlannerOptions {
+                raw_samples: true,
+                ..Default::default()
+            };
+            PromPlanner::stmt_to_plan_with_options(
+                table_provider,
+                &eval_stmt,
+                &options,
+                &build_session_state(),
+            )
+            .await
+        }
+
+        let expected = String::from(
+            "PromSeriesDivide: tags=[\"tag_0\"] [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
+            \n  Sort: some_metric.tag_0 ASC NULLS FIRST, some_metric.timestamp ASC NULLS FIRST [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
+            \n    Filter: some_metric.tag_0 = Utf8(\"bar\") AND some_metric.timestamp >= TimestampMillisecond(0, None) AND some_metric.timestamp <= TimestampMillisecond(100000000, None) [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
+            \n      TableScan: some_metric [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]",
+        );
+        let plan = plan(r#"some_metric{tag_0="bar"}"#).await.unwrap();
+        assert_eq!(plan.display_indent_schema().to_string(), expected);
+
+        for query in [
+            "sum(some_metric)",
+            "rate(some_metric[5m])",
+            "some_metric @ 100",
+            "1 + some_metric",
+        ] {
+            assert!(plan(query).await.is_err(), "query: {query}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metric_name_column() {
+        async fn plan(query: &str) -> LogicalPlan {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(
+                &[(DEFAULT_SCHEMA_NAME.to_string(), "some_metric".to_string())],
+                1,
+                1,
+            )
+            .await;
+            let options = PromPlannerOptions {
+                metric_name_column: true,
+                ..Default::default()
+            };
+            PromPlanner::stmt_to_plan_with_options(
+                table_provider,
+                &eval_stmt,
+                &options,
+                &build_session_state(),
+            )
+            .await
+            .unwrap()
+        }
+
+        // Selectors and functions only selecting the samples keep the metric name.
+        for query in [
+            r#"some_metric{tag_0="bar"}"#,
+            r#"{__name__="some_metric"}"#,
+            "last_over_time(some_metric[5m])",
+            "sort_desc(some_metric)",
+            "topk(1, some_metric)",
+            "sum by (__name__, tag_0) (some_metric)",
+            "some_metric > 1",
+            "1 < some_metric",
+            "some_metric and some_metric",
+        ] {
+            let plan = plan(query).await;
+            let LogicalPlan::Projection(projection) = &plan else {
+                panic!("query: {query}, plan: {plan}");
+            };
+            let name_expr = projection.expr.last().unwrap();
+            assert_eq!(
+                r#"Utf8("some_metric") AS __name__"#,
+                name_expr.to_string(),
+                "query: {query}"
+            );
+        }
+
+        // The metric name is dropped by other functions, aggregations and arithmetic.
+        for query in [
+            "rate(some_metric[5m])",
+            "abs(some_metric)",
+            "sum(some_metric)",
+            "sum by (tag_0) (some_metric)",
+            "some_metric + 1",
+            "some_metric > bool 1",
+            "-some_metric",
+            "1 + 1",
+        ] {
+            let plan = plan(query).await;
+            assert!(
+                !plan.schema().has_column_with_unqualified_name(METRIC_NAME),
+                "query: {query}, plan: {plan}"
+            );
+        }
+
+        // The `__name__` label set by the query isn't overridden.
+        let plan =
+            plan(r#"label_replace(some_metric, "__name__", "renamed", "tag_0", ".*")"#).await;
+        let name_columns = plan
+            .schema()
+            .fields()
+            .iter()
+            .filter(|field| field.name() == METRIC_NAME)
+            .count();
+        assert_eq!(1, name_columns, "plan: {plan}");
+    }
+
+    #[tokio::test]
+    async fn test_propagate_nan() {
+        async fn plan(query: &str, propagate_nan: bool) -> LogicalPlan {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(
+                &[(DEFAULT_SCHEMA_NAME.to_string(), "some_metric".to_string())],
+                1,
+                1,
+            )
+            .await;
+            let options = PromPlannerOptions {
+                propagate_nan,
+                ..Default::default()
+            };
+            PromPlanner::stmt_to_plan_with_options(
+                table_provider,
+                &eval_stmt,
+                &options,
+                &build_session_state(),
+            )
+            .await
+            .unwrap()
+        }
+
+        fn skip_nan_aggrs(plan: &LogicalPlan) -> Vec<bool> {
+            let mut skip_nan = vec![];
+            plan.apply(|node| {
+                match node {
+                    LogicalPlan::Aggregate(aggr) => {
+                        for expr in &aggr.aggr_expr {
+                            if let DfExpr::AggregateFunction(func) = expr {
+                                skip_nan.push(SkipNanAggr::is_skip_nan_aggr(&func.func));
+                            }
+                        }
+                    }
+                    // stream aggregates skip NaN in `min` and `max` only
+                    LogicalPlan::Extension(extension) => {
+                        if let Some(aggr) =
+                            extension.node.as_any().downcast_ref::<StreamAggregate>()
+                        {
+                            skip_nan.push(matches!(
+                                aggr.func(),
+                                StreamAggregateFunc::Min | StreamAggregateFunc::Max
+                            ));
+                        }
+                    }
+                    _ => {}
+                }
+                Ok(TreeNodeRecursion::Continue)
+            })
+            .unwrap();
+            skip_nan
+        }
+
+        for query in [
+            "sum(some_metric)",
+            "avg by (tag_0) (some_metric)",
+            "min(some_metric)",
+            "max(some_metric)",
+        ] {
+            let skipping = plan(query, false).await;
+            assert_eq!(skip_nan_aggrs(&skipping), vec![true], "{query}");
+            let propagating = plan(query, true).await;
+            assert_eq!(skip_nan_aggrs(&propagating), vec![false], "{query}");
+            // output columns are named the same
+            assert_eq!(
+                skipping.schema().field_names(),
+                propagating.schema().field_names()
+            );
+        }
+        // other aggregators are not affected
+        let plan = plan("stddev(some_metric)", false).await;
+        assert_eq!(skip_nan_aggrs(&plan), vec![false]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_aggregate() {
+        async fn plan(query: &str, propagate_nan: bool) -> LogicalPlan {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(
+                &[(DEFAULT_SCHEMA_NAME.to_string(), "some_metric".to_string())],
+                2,
+                2,
+            )
+            .await;
+            let options = PromPlannerOptions {
+                propagate_nan,
+                ..Default::default()
+            };
+            PromPlanner::stmt_to_plan_with_options(
+                table_provider,
+                &eval_stmt,
+                &options,
+                &build_session_state(),
+            )
+            .await
+            .unwrap()
+        }
+
+        // the series are sorted on all their tags after `SeriesDivide`, other inputs are
+        // sorted on the group columns first. `sum` and `avg` stream when NaN propagates,
+        // `min` and `max` when NaN is skipped, like the hash aggregation.
+        for (query, propagate_nan, func, groups, needs_sort) in [
+            (
+                "sum by (tag_0, tag_1) (some_metric)",
+                true,
+                "Sum",
+                "\"tag_0\", \"tag_1\", ",
+                false,
+            ),
+            (
+                "avg without () (some_metric)",
+                true,
+                "Avg",
+                "\"tag_0\", \"tag_1\", ",
+                false,
+            ),
+            (
+                "max by (tag_0, tag_1) (rate(some_metric[5m]))",
+                false,
+                "Max",
+                "\"tag_0\", \"tag_1\", ",
+                false,
+            ),
+            (
+                "min by (tag_0, tag_1) (some_metric offset 1m)",
+                false,
+                "Min",
+                "\"tag_0\", \"tag_1\", ",
+                false,
+            ),
+            ("sum(some_metric)", true, "Sum", "", true),
+            (
+                "sum by (tag_0) (some_metric)",
+                true,
+                "Sum",
+                "\"tag_0\", ",
+                true,
+            ),
+            (
+                "max by (tag_1, tag_0) (some_metric)",
+                false,
+                "Max",
+                "\"tag_1\", \"tag_0\", ",
+                true,
+            ),
+            (
+                "min by (tag_1) (some_metric + some_metric)",
+                false,
+                "Min",
+                "\"tag_1\", ",
+                true,
+            ),
+        ] {
+            let streaming = plan(query, propagate_nan).await;
+            let plan_str = streaming.display_indent_schema().to_string();
+            let expected =
+                format!("PromStreamAggregate: func={func}, groups=[{groups}\"timestamp\"]");
+            assert!(plan_str.contains(&expected), "{query}: {plan_str}");
+            assert!(
+                !plan_str.contains("Aggregate: groupBy"),
+                "{query}: {plan_str}"
+            );
+            assert_eq!(
+                needs_sort,
+                plan_str
+                    .lines()
+                    .skip_while(|line| !line.contains("PromStreamAggregate"))
+                    .nth(1)
+                    .is_some_and(|line| line.trim_start().starts_with("Sort: ")),
+                "{query}: {plan_str}"
+            );
+
+            // the hash aggregation has the same output columns
+            let hashing = plan(query, !propagate_nan).await;
+            let hash_plan_str = hashing.display_indent_schema().to_string();
+            assert!(
+                !hash_plan_str.contains("PromStreamAggregate"),
+                "{query}: {hash_plan_str}"
+            );
+            assert_eq!(
+                streaming.schema().field_names(),
+                hashing.schema().field_names(),
+                "{query}"
+            );
+        }
+
+        // other aggregators use the hash aggregation
+        for query in [
+            "count by (tag_0, tag_1) (some_metric)",
+            "stddev by (tag_0) (some_metric)",
+        ] {
+            for propagate_nan in [false, true] {
+                let plan_str = plan(query, propagate_nan)
+                    .await
+                    .display_indent_schema()
+                    .to_string();
+                assert!(
+                    !plan_str.contains("PromStreamAggregate"),
+                    "{query}: {plan_str}"
+                );
+                assert!(
+                    plan_str.contains("Aggregate: groupBy"),
+                    "{query}: {plan_str}"
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fill_forward() {
+        async fn plan(query: &str, fill_forward: bool) -> String {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(
+                &[(DEFAULT_SCHEMA_NAME.to_string(), "some_metric".to_string())],
+                1,
+                1,
+            )
+            .await;
+            let options = PromPlannerOptions {
+                fill_forward,
+                ..Default::default()
+            };
+            PromPlanner::stmt_to_plan_with_options(
+                table_provider,
+                &eval_stmt,
+                &options,
+                &build_session_state(),
+            )
+            .await
+            .unwrap()
+            .display_indent_schema()
+            .to_string()
+        }
+
+        let query = "sum by (tag_0) (rate(some_metric[5m]))";
+        assert!(!plan(query, false).await.contains("PromFillForward"));
+        let plan_str = plan(query, true).await;
+        assert!(
+            plan_str.starts_with("PromFillForward: range=[0..100000000], lookback=[1000], interval=[5000], time index=[timestamp], tags=[\"tag_0\"], fields=[\"sum(prom_rate("),
+            "{plan_str}"
+        );
+        // scalars are not filled
+        assert!(!plan("1 + 1", true).await.contains("PromFillForward"));
+    }
+
+    #[tokio::test]
+    async fn test_latest_at_modifier() {
+        async fn plan(query: &str, enable_latest_at: bool) -> Result<String> {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(&crate::parser::rewrite_latest_at(query)).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(
+                &[(DEFAULT_SCHEMA_NAME.to_string(), "some_metric".to_string())],
+                1,
+                1,
+            )
+            .await;
+            let options = PromPlannerOptions {
+                enable_latest_at,
+                ..Default::default()
+            };
+            PromPlanner::stmt_to_plan_with_options(
+                table_provider,
+                &eval_stmt,
+                &options,
+                &build_session_state(),
+            )
+            .await
+            .map(|plan| plan.display_indent_schema().to_string())
+        }
+
+        let query = "sum by (tag_0) (some_metric @ latest())";
+        let err = plan(query, false).await.unwrap_err();
+        assert!(
+            matches!(err, crate::promql::error::Error::LatestAtDisabled { .. }),
+            "{err:?}"
+        );
+
+        let plan_str = plan(query, true).await.unwrap();
+        // each series looks back to its latest sample before the end
+        assert!(
+            plan_str.contains(
+                "PromInstantManipulate: range=[100000000..100000000], lookback=[3153600000000], interval=[5000]"
+            ),
+            "{plan_str}"
+        );
+        // and the result is repeated on every step
+        assert!(
+            plan_str.contains("EmptyMetric: range=[0..100000000], interval=[5000]"),
+            "{plan_str}"
+        );
+
+        for query in [
+            "rate(some_metric[5m] @ latest())",
+            "max_over_time(some_metric[10m:1m] @ latest())",
+        ] {
+            let err = plan(query, true).await.unwrap_err();
+            assert!(
+                matches!(err, crate::promql::error::Error::UnsupportedLatestAt { .. }),
+                "{query}: {err:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_max_at_lookahead() {
+        async fn plan(query: &str, max_at_lookahead: Option<Duration>) -> Result<String> {
+            // a range query ending a day later
+            let start = std::time::SystemTime::now();
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start,
+                end: start.checked_add(Duration::from_secs(86_400)).unwrap(),
+                interval: Duration::from_secs(60),
+                lookback_delta: Duration::from_secs(300),
+            };
+            let table_provider = build_test_table_provider(
+                &[(DEFAULT_SCHEMA_NAME.to_string(), "some_metric".to_string())],
+                1,
+                1,
+            )
+            .await;
+            let options = PromPlannerOptions {
+                max_at_lookahead,
+                enable_negative_offset: true,
+                ..Default::default()
+            };
+            PromPlanner::stmt_to_plan_with_options(
+                table_provider,
+                &eval_stmt,
+                &options,
+                &build_session_state(),
+            )
+            .await
+            .map(|plan| plan.display_indent_schema().to_string())
+        }
+
+        let lookahead = Some(Duration::from_secs(3600));
+        for query in [
+            "some_metric @ end()",
+            "sum by (tag_0) (rate(some_metric[5m] @ end()))",
+            // a negative offset reads even later
+            "some_metric @ start() offset -2h",
+        ] {
+            let err = plan(query, lookahead).await.unwrap_err();
+            assert!(
+                matches!(err, crate::promql::error::Error::AtLookaheadExceeded { .. }),
+                "{query}: {err:?}"
+            );
+            // unlimited by default
+            plan(query, None).await.unwrap();
+        }
+
+        for query in [
+            // only selectors with `@` are limited
+            "some_metric",
+            "some_metric @ start()",
+            "some_metric @ end() offset 1d",
+        ] {
+            plan(query, lookahead).await.unwrap();
+        }
+    }
+
+    async fn build_counter_table_provider() -> DfTableSourceProvider {
+        let catalog_list = MemoryCatalogManager::with_default_setup();
+        let columns = vec![
+            ColumnSchema::new(
+                "tag".to_string(),
+                ConcreteDataType::string_datatype(),
+                false,
+            ),
+            ColumnSchema::new(
+                "timestamp".to_string(),
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+            ColumnSchema::new(
+                GREPTIME_VALUE.to_string(),
+                ConcreteDataType::float64_datatype(),
+                true,
+            ),
+            ColumnSchema::new(
+                GREPTIME_CREATED.to_string(),
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                true,
+            ),
+        ];
+        let schema = Arc::new(Schema::new(columns));
+        let table_meta = TableMetaBuilder::empty()
+            .schema(schema)
+            .primary_key_indices(vec![0])
+            .value_indices(vec![2, 3])
+            .next_column_id(1024)
+            .build()
+            .unwrap();
+        let table_info = TableInfoBuilder::default()
+            .name("metrics".to_string())
+            .meta(table_meta)
+            .build()
+            .unwrap();
+        let table = EmptyTable::from_table_info(&table_info);
+        assert!(catalog_list
+            .register_table_sync(RegisterTableRequest {
+                catalog: DEFAULT_CATALOG_NAME.to_string(),
+                schema: DEFAULT_SCHEMA_NAME.to_string(),
+                table_name: "metrics".to_string(),
+                table_id: 1024,
+                table,
+            })
+            .is_ok());
+
+        DfTableSourceProvider::new(
+            catalog_list,
+            false,
+            QueryContext::arc(),
+            DummyDecoder::arc(),
+            true,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_created_timestamps() {
+        async fn plan(query: &str, created_timestamps: bool) -> LogicalPlan {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let options = PromPlannerOptions {
+                created_timestamps,
+                ..Default::default()
+            };
+            PromPlanner::stmt_to_plan_with_options(
+                build_counter_table_provider().await,
+                &eval_stmt,
+                &options,
+                &build_session_state(),
+            )
+            .await
+            .unwrap()
+        }
+        let function_line = |plan: &LogicalPlan, name: &str| {
+            let plan_str = plan.display_indent_schema().to_string();
+            plan_str
+                .lines()
+                .find(|line| line.contains(name))
+                .unwrap_or_else(|| panic!("{plan_str}"))
+                .to_string()
+        };
+
+        for (query, name) in [
+            ("increase(metrics[5m])", "prom_increase("),
+            ("rate(metrics[5m])", "prom_rate("),
+        ] {
+            // the created timestamps are not values
+            let without_created = plan(query, false).await;
+            assert_eq!(3, without_created.schema().fields().len());
+            assert!(!function_line(&without_created, name).contains(GREPTIME_CREATED));
+
+            // and only read by the counter functions when enabled
+            let with_created = plan(query, true).await;
+            assert_eq!(
+                without_created.schema().fields().len(),
+                with_created.schema().fields().len()
+            );
+            assert!(
+                function_line(&with_created, name).contains(GREPTIME_CREATED),
+                "{query}"
+            );
+            assert!(function_line(&with_created, "PromRangeManipulate")
+                .contains("values=[\"greptime_value\", \"greptime_created\"]"));
+        }
+
+        // other functions don't read them
+        let plan_str = plan("delta(metrics[5m])", true)
+            .await
+            .display_indent_schema()
+            .to_string();
+        assert!(
+            plan_str.contains("values=[\"greptime_value\"], arrays="),
+            "{plan_str}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_and_operator() {
+        let mut eval_stmt = EvalStmt {
+            expr: PromExpr::NumberLiteral(NumberLiteral { val: 1.0 }),
+            start: UNIX_EPOCH,
+            end: UNIX_EPOCH
+                .checked_add(Duration::from_secs(100_000))
+                .unwrap(),
+            interval: Duration::from_secs(5),
+            lookback_delta: Duration::from_secs(1),
+        };
+
+        let cases = [
+            r#"count (max by (persistentvolumeclaim,namespace) (kubelet_volume_stats_used_bytes{namespace=~".+"} ) and (max by (persistentvolumeclaim,namespace) (kubelet_volume_stats_used_bytes{namespace=~".+"} )) / (max by (persistentvolumeclaim,namespace) (kubelet_volume_stats_capacity_bytes{namespace=~".+"} )) >= (80 / 100)) or vector (0)"#,
+            r#"count (max by (persistentvolumeclaim,namespace) (kubelet_volume_stats_used_bytes{namespace=~".+"} ) unless (max by (persistentvolumeclaim,namespace) (kubelet_volume_stats_used_bytes{namespace=~".+"} )) / (max by (persistentvolumeclaim,namespace) (kubelet_volume_stats_capacity_bytes{namespace=~".+"} )) >= (80 / 100)) or vector (0)"#,
+        ];
+
+        for case in cases {
+            let prom_expr = parser::parse(case).unwrap();
+            eval_stmt.expr = prom_expr;
+            let table_provider = build_test_table_provider_with_fields(
+                &[
+                    (
+                        DEFAULT_SCHEMA_NAME.to_string(),
+                        "kubelet_volume_stats_used_bytes".to_string(),
+                    ),
+                    (
+                        DEFAULT_SCHEMA_NAME.to_string(),
+                        "kubelet_volume_stats_capacity_bytes".to_string(),
+                    ),
+                ],
+                &["namespace", "persistentvolumeclaim"],
+            )
+            .await;
+            // Should be ok
+            let _ = PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nested_binary_op() {
+        let mut eval_stmt = EvalStmt {
+            expr: PromExpr::NumberLiteral(NumberLiteral { val: 1.0 }),
+            start: UNIX_EPOCH,
+            end: UNIX_EPOCH
+                .checked_add(Duration::from_secs(100_000))
+                .unwrap(),
+            interval: Duration::from_secs(5),
+            lookback_delta: Duration::from_secs(1),
+        };
+
+        let case = r#"sum(rate(nginx_ingress_controller_requests{job=~".*"}[2m])) -
+        (
+            sum(rate(nginx_ingress_controller_requests{namespace=~".*"}[2m]))
+            or
+            vector(0)
+        )"#;
+
+        let prom_expr = parser::parse(case).unwrap();
+        eval_stmt.expr = prom_expr;
+        let table_provider = build_test_table_provider_with_fields(
             &[(
                 DEFAULT_SCHEMA_NAME.to_string(),
                 "nginx_ingress_controller_requests".to_string(),
@@ -3793,6 +7298,71 @@ mod test {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_group_left_right() {
+        async fn plan(query: &str) -> LogicalPlan {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(
+                &[
+                    (DEFAULT_SCHEMA_NAME.to_string(), "foo".to_string()),
+                    (DEFAULT_SCHEMA_NAME.to_string(), "bar".to_string()),
+                ],
+                2,
+                1,
+            )
+            .await;
+            PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
+                .await
+                .unwrap()
+        }
+
+        // The result takes the labels of the many side and copies none.
+        let plan = plan("foo * on(tag_0) group_left() bar").await;
+        assert_eq!(
+            vec![
+                "foo.tag_0",
+                "foo.tag_1",
+                "foo.timestamp",
+                "foo.field_0 * bar.field_0"
+            ],
+            plan.schema().field_names()
+        );
+        let plan = plan("foo * on(tag_0) group_right() bar").await;
+        assert_eq!(
+            vec![
+                "bar.tag_0",
+                "bar.tag_1",
+                "bar.timestamp",
+                "foo.field_0 * bar.field_0"
+            ],
+            plan.schema().field_names()
+        );
+
+        // Copies `tag_1` from the one side.
+        let plan = plan("foo * on(tag_0) group_left(tag_1) bar").await;
+        assert_eq!(
+            vec![
+                "foo.tag_0",
+                "foo.tag_1",
+                "foo.timestamp",
+                "foo.field_0 * bar.field_0"
+            ],
+            plan.schema().field_names()
+        );
+        assert!(plan
+            .display_indent()
+            .to_string()
+            .contains("bar.tag_1 AS foo.tag_1"));
+    }
+
     #[tokio::test]
     async fn value_matcher() {
         // template
@@ -4115,19 +7685,290 @@ mod test {
             },
             &build_session_state(),
         )
-        .await
-        .unwrap();
-        assert_eq!(plan.display_indent_schema().to_string(),
-        "Filter: prom_avg_over_time(timestamp_range,field) IS NOT NULL [timestamp:Timestamp(Millisecond, None), prom_avg_over_time(timestamp_range,field):Float64;N, tag:Utf8]\
-        \n  Projection: metrics.timestamp, prom_avg_over_time(timestamp_range, field) AS prom_avg_over_time(timestamp_range,field), metrics.tag [timestamp:Timestamp(Millisecond, None), prom_avg_over_time(timestamp_range,field):Float64;N, tag:Utf8]\
-        \n    PromRangeManipulate: req range=[0..100000000], interval=[5000], eval range=[5000], time index=[timestamp], values=[\"field\"] [field:Dictionary(Int64, Float64);N, tag:Utf8, timestamp:Timestamp(Millisecond, None), timestamp_range:Dictionary(Int64, Timestamp(Millisecond, None))]\
-        \n      PromSeriesNormalize: offset=[0], time index=[timestamp], filter NaN: [true] [field:Float64;N, tag:Utf8, timestamp:Timestamp(Millisecond, None)]\
-        \n        PromSeriesDivide: tags=[\"tag\"] [field:Float64;N, tag:Utf8, timestamp:Timestamp(Millisecond, None)]\
-        \n          Sort: metrics.tag ASC NULLS FIRST, metrics.timestamp ASC NULLS FIRST [field:Float64;N, tag:Utf8, timestamp:Timestamp(Millisecond, None)]\
-        \n            Filter: metrics.tag = Utf8(\"1\") AND metrics.timestamp >= TimestampMillisecond(-6000, None) AND metrics.timestamp <= TimestampMillisecond(100001000, None) [field:Float64;N, tag:Utf8, timestamp:Timestamp(Millisecond, None)]\
-        \n              Projection: metrics.field, metrics.tag, CAST(metrics.timestamp AS Timestamp(Millisecond, None)) AS timestamp [field:Float64;N, tag:Utf8, timestamp:Timestamp(Millisecond, None)]\
-        \n                TableScan: metrics [tag:Utf8, timestamp:Timestamp(Nanosecond, None), field:Float64;N]"
+        .await
+        .unwrap();
+        assert_eq!(plan.display_indent_schema().to_string(),
+        "Filter: prom_avg_over_time(timestamp_range,field) IS NOT NULL [timestamp:Timestamp(Millisecond, None), prom_avg_over_time(timestamp_range,field):Float64;N, tag:Utf8]\
+        \n  Projection: metrics.timestamp, prom_avg_over_time(timestamp_range, field) AS prom_avg_over_time(timestamp_range,field), metrics.tag [timestamp:Timestamp(Millisecond, None), prom_avg_over_time(timestamp_range,field):Float64;N, tag:Utf8]\
+        \n    PromRangeManipulate: req range=[0..100000000], interval=[5000], eval range=[5000], time index=[timestamp], values=[\"field\"], arrays=[values] [field:Dictionary(Int64, Float64);N, tag:Utf8, timestamp:Timestamp(Millisecond, None), timestamp_range:Dictionary(Int64, Timestamp(Millisecond, None))]\
+        \n      PromSeriesNormalize: offset=[0], time index=[timestamp], filter NaN: [true] [field:Float64;N, tag:Utf8, timestamp:Timestamp(Millisecond, None)]\
+        \n        PromSeriesDivide: tags=[\"tag\"] [field:Float64;N, tag:Utf8, timestamp:Timestamp(Millisecond, None)]\
+        \n          Sort: metrics.tag ASC NULLS FIRST, metrics.timestamp ASC NULLS FIRST [field:Float64;N, tag:Utf8, timestamp:Timestamp(Millisecond, None)]\
+        \n            Filter: metrics.tag = Utf8(\"1\") AND metrics.timestamp >= TimestampMillisecond(-6000, None) AND metrics.timestamp <= TimestampMillisecond(100001000, None) [field:Float64;N, tag:Utf8, timestamp:Timestamp(Millisecond, None)]\
+        \n              Projection: metrics.field, metrics.tag, CAST(metrics.timestamp AS Timestamp(Millisecond, None)) AS timestamp [field:Float64;N, tag:Utf8, timestamp:Timestamp(Millisecond, None)]\
+        \n                TableScan: metrics [tag:Utf8, timestamp:Timestamp(Nanosecond, None), field:Float64;N]"
+        );
+    }
+
+    async fn build_table_provider_with_field_type(
+        field_type: ConcreteDataType,
+    ) -> DfTableSourceProvider {
+        let catalog_list = MemoryCatalogManager::with_default_setup();
+        let columns = vec![
+            ColumnSchema::new(
+                "tag".to_string(),
+                ConcreteDataType::string_datatype(),
+                false,
+            ),
+            ColumnSchema::new(
+                "timestamp".to_string(),
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+            ColumnSchema::new("field".to_string(), field_type, true),
+        ];
+        let schema = Arc::new(Schema::new(columns));
+        let table_meta = TableMetaBuilder::empty()
+            .schema(schema)
+            .primary_key_indices(vec![0])
+            .value_indices(vec![2])
+            .next_column_id(1024)
+            .build()
+            .unwrap();
+        let table_info = TableInfoBuilder::default()
+            .name("metrics".to_string())
+            .meta(table_meta)
+            .build()
+            .unwrap();
+        let table = EmptyTable::from_table_info(&table_info);
+        assert!(catalog_list
+            .register_table_sync(RegisterTableRequest {
+                catalog: DEFAULT_CATALOG_NAME.to_string(),
+                schema: DEFAULT_SCHEMA_NAME.to_string(),
+                table_name: "metrics".to_string(),
+                table_id: 1024,
+                table,
+            })
+            .is_ok());
+
+        DfTableSourceProvider::new(
+            catalog_list,
+            false,
+            QueryContext::arc(),
+            DummyDecoder::arc(),
+            true,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_non_float_field() {
+        let field_types = [
+            ConcreteDataType::int64_datatype(),
+            ConcreteDataType::uint64_datatype(),
+            ConcreteDataType::float32_datatype(),
+            ConcreteDataType::boolean_datatype(),
+        ];
+        for field_type in &field_types {
+            let plan = PromPlanner::stmt_to_plan(
+                build_table_provider_with_field_type(field_type.clone()).await,
+                &EvalStmt {
+                    expr: parser::parse("sum(rate(metrics[5s]))").unwrap(),
+                    start: UNIX_EPOCH,
+                    end: UNIX_EPOCH
+                        .checked_add(Duration::from_secs(100_000))
+                        .unwrap(),
+                    interval: Duration::from_secs(5),
+                    lookback_delta: Duration::from_secs(1),
+                },
+                &build_session_state(),
+            )
+            .await
+            .unwrap();
+            let plan_str = plan.display_indent_schema().to_string();
+            assert!(
+                plan_str.contains("Projection: CAST(metrics.field AS Float64) AS field, metrics.tag, metrics.timestamp [field:Float64;N, tag:Utf8, timestamp:Timestamp(Millisecond, None)]"),
+                "{field_type:?}: {plan_str}"
+            );
+            assert_eq!(
+                &ArrowDataType::Float64,
+                plan.schema().field(1).data_type(),
+                "{field_type:?}"
+            );
+        }
+
+        // instant vectors keep integer values, while booleans are always mapped to 0 and 1
+        for (field_type, cast) in [
+            (ConcreteDataType::int64_datatype(), false),
+            (ConcreteDataType::boolean_datatype(), true),
+        ] {
+            let plan = PromPlanner::stmt_to_plan(
+                build_table_provider_with_field_type(field_type.clone()).await,
+                &EvalStmt {
+                    expr: parser::parse("sum(metrics)").unwrap(),
+                    start: UNIX_EPOCH,
+                    end: UNIX_EPOCH
+                        .checked_add(Duration::from_secs(100_000))
+                        .unwrap(),
+                    interval: Duration::from_secs(5),
+                    lookback_delta: Duration::from_secs(1),
+                },
+                &build_session_state(),
+            )
+            .await
+            .unwrap();
+            let plan_str = plan.display_indent_schema().to_string();
+            assert_eq!(
+                cast,
+                plan_str.contains("CAST(metrics.field AS Float64) AS field"),
+                "{field_type:?}: {plan_str}"
+            );
+        }
+
+        // arithmetic casts its operands, so they can be combined with float operands
+        for field_type in &field_types {
+            let plan = PromPlanner::stmt_to_plan(
+                build_table_provider_with_field_type(field_type.clone()).await,
+                &EvalStmt {
+                    expr: parser::parse("metrics + 1.5").unwrap(),
+                    start: UNIX_EPOCH,
+                    end: UNIX_EPOCH
+                        .checked_add(Duration::from_secs(100_000))
+                        .unwrap(),
+                    interval: Duration::from_secs(5),
+                    lookback_delta: Duration::from_secs(1),
+                },
+                &build_session_state(),
+            )
+            .await
+            .unwrap();
+            let plan_str = plan.display_indent_schema().to_string();
+            assert!(
+                plan_str.contains("CAST(metrics.field AS Float64) AS field"),
+                "{field_type:?}: {plan_str}"
+            );
+            let value = plan
+                .schema()
+                .fields()
+                .iter()
+                .find(|field| field.name().ends_with("+ Float64(1.5)"))
+                .unwrap_or_else(|| panic!("{field_type:?}: {plan_str}"));
+            assert_eq!(&ArrowDataType::Float64, value.data_type(), "{field_type:?}");
+        }
+    }
+
+    async fn build_table_provider_with_int_and_float_fields() -> DfTableSourceProvider {
+        let catalog_list = MemoryCatalogManager::with_default_setup();
+        let columns = vec![
+            ColumnSchema::new(
+                "tag".to_string(),
+                ConcreteDataType::string_datatype(),
+                false,
+            ),
+            ColumnSchema::new(
+                "timestamp".to_string(),
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+            // the int column comes first
+            ColumnSchema::new(
+                "int_field".to_string(),
+                ConcreteDataType::int64_datatype(),
+                true,
+            ),
+            ColumnSchema::new(
+                "float_field".to_string(),
+                ConcreteDataType::float64_datatype(),
+                true,
+            ),
+        ];
+        let schema = Arc::new(Schema::new(columns));
+        let table_meta = TableMetaBuilder::empty()
+            .schema(schema)
+            .primary_key_indices(vec![0])
+            .value_indices(vec![2, 3])
+            .next_column_id(1024)
+            .build()
+            .unwrap();
+        let table_info = TableInfoBuilder::default()
+            .name("metrics".to_string())
+            .meta(table_meta)
+            .build()
+            .unwrap();
+        let table = EmptyTable::from_table_info(&table_info);
+        assert!(catalog_list
+            .register_table_sync(RegisterTableRequest {
+                catalog: DEFAULT_CATALOG_NAME.to_string(),
+                schema: DEFAULT_SCHEMA_NAME.to_string(),
+                table_name: "metrics".to_string(),
+                table_id: 1024,
+                table,
+            })
+            .is_ok());
+
+        DfTableSourceProvider::new(
+            catalog_list,
+            false,
+            QueryContext::arc(),
+            DummyDecoder::arc(),
+            true,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_select_field_by_type() {
+        let plan = |query: &str| {
+            let expr = parser::parse(query).unwrap();
+            async move {
+                PromPlanner::stmt_to_plan(
+                    build_table_provider_with_int_and_float_fields().await,
+                    &EvalStmt {
+                        expr,
+                        start: UNIX_EPOCH,
+                        end: UNIX_EPOCH
+                            .checked_add(Duration::from_secs(100_000))
+                            .unwrap(),
+                        interval: Duration::from_secs(5),
+                        lookback_delta: Duration::from_secs(1),
+                    },
+                    &build_session_state(),
+                )
+                .await
+            }
+        };
+        let field_names = |plan: LogicalPlan| {
+            plan.schema()
+                .fields()
+                .iter()
+                .map(|field| field.name().clone())
+                .filter(|name| name.contains("field"))
+                .collect::<Vec<_>>()
+        };
+
+        // float columns are preferred
+        let result = plan("metrics").await.unwrap();
+        assert_eq!(vec!["float_field"], field_names(result));
+        let result = plan("rate(metrics[5s])").await.unwrap();
+        assert_eq!(
+            vec!["prom_rate(timestamp_range,float_field,timestamp)"],
+            field_names(result)
+        );
+
+        // int columns are selected by `__field_type__` and cast in range selectors
+        let result = plan(r#"metrics{__field_type__="int"}"#).await.unwrap();
+        assert_eq!(vec!["int_field"], field_names(result));
+        let result = plan(r#"rate(metrics{__field_type__="int"}[5s])"#)
+            .await
+            .unwrap();
+        let plan_str = result.display_indent_schema().to_string();
+        assert!(
+            plan_str.contains("CAST(metrics.int_field AS Float64) AS int_field"),
+            "{plan_str}"
+        );
+        assert_eq!(
+            vec!["prom_rate(timestamp_range,int_field,timestamp)"],
+            field_names(result)
         );
+
+        // `__field__` chooses from all field columns
+        let result = plan(r#"metrics{__field__="int_field"}"#).await.unwrap();
+        assert_eq!(vec!["int_field"], field_names(result));
+        let result = plan(r#"metrics{__field__=~".*"}"#).await.unwrap();
+        assert_eq!(vec!["int_field", "float_field"], field_names(result));
+
+        assert!(plan(r#"metrics{__field_type__="string"}"#).await.is_err());
+        assert!(plan(r#"metrics{__field_type__=~"int"}"#).await.is_err());
     }
 
     #[tokio::test]
@@ -4181,7 +8022,7 @@ mod test {
             .await
             .unwrap();
 
-        let expected = "Filter: field_0 IS NOT NULL AND foo IS NOT NULL [timestamp:Timestamp(Millisecond, None), field_0:Float64;N, foo:Utf8;N, tag_0:Utf8, tag_1:Utf8, tag_2:Utf8, tag_3:Utf8]\
+        let expected = "Filter: field_0 IS NOT NULL [timestamp:Timestamp(Millisecond, None), field_0:Float64;N, foo:Utf8;N, tag_0:Utf8, tag_1:Utf8, tag_2:Utf8, tag_3:Utf8]\
         \n  Projection: up.timestamp, up.field_0 AS field_0, concat_ws(Utf8(\",\"), up.tag_1, up.tag_2, up.tag_3) AS foo AS foo, up.tag_0, up.tag_1, up.tag_2, up.tag_3 [timestamp:Timestamp(Millisecond, None), field_0:Float64;N, foo:Utf8;N, tag_0:Utf8, tag_1:Utf8, tag_2:Utf8, tag_3:Utf8]\
         \n    PromInstantManipulate: range=[0..100000000], lookback=[1000], interval=[5000], time index=[timestamp] [tag_0:Utf8, tag_1:Utf8, tag_2:Utf8, tag_3:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
         \n      PromSeriesDivide: tags=[\"tag_0\", \"tag_1\", \"tag_2\", \"tag_3\"] [tag_0:Utf8, tag_1:Utf8, tag_2:Utf8, tag_3:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
@@ -4215,8 +8056,8 @@ mod test {
             .await
             .unwrap();
 
-        let expected = "Filter: field_0 IS NOT NULL AND foo IS NOT NULL [timestamp:Timestamp(Millisecond, None), field_0:Float64;N, foo:Utf8;N, tag_0:Utf8]\
-        \n  Projection: up.timestamp, up.field_0 AS field_0, regexp_replace(up.tag_0, Utf8(\"(.*):.*\"), Utf8(\"$1\")) AS foo AS foo, up.tag_0 [timestamp:Timestamp(Millisecond, None), field_0:Float64;N, foo:Utf8;N, tag_0:Utf8]\
+        let expected = "Filter: field_0 IS NOT NULL [timestamp:Timestamp(Millisecond, None), field_0:Float64;N, foo:Utf8;N, tag_0:Utf8]\
+        \n  Projection: up.timestamp, up.field_0 AS field_0, nullif(regexp_replace(up.tag_0, Utf8(\"(.*):.*\"), Utf8(\"$1\")), Utf8(\"\")) AS foo AS foo, up.tag_0 [timestamp:Timestamp(Millisecond, None), field_0:Float64;N, foo:Utf8;N, tag_0:Utf8]\
         \n    PromInstantManipulate: range=[0..100000000], lookback=[1000], interval=[5000], time index=[timestamp] [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
         \n      PromSeriesDivide: tags=[\"tag_0\"] [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
         \n        Sort: up.tag_0 ASC NULLS FIRST, up.timestamp ASC NULLS FIRST [tag_0:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N]\
@@ -4226,6 +8067,47 @@ mod test {
         assert_eq!(plan.display_indent_schema().to_string(), expected);
     }
 
+    #[tokio::test]
+    async fn test_label_functions_on_metric_name() {
+        let cases = [
+            (
+                r#"label_replace(http_requests_total, "svc", "$1", "__name__", "(.*)_total")"#,
+                r#"Utf8("http_requests") AS svc"#,
+            ),
+            (
+                r#"label_join(http_requests_total, "foo", "-", "__name__", "tag_0")"#,
+                r#"concat_ws(Utf8("-"), Utf8("http_requests_total"), http_requests_total.tag_0) AS foo"#,
+            ),
+        ];
+
+        for (query, expected) in cases {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH
+                    .checked_add(Duration::from_secs(100_000))
+                    .unwrap(),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(
+                &[(
+                    DEFAULT_SCHEMA_NAME.to_string(),
+                    "http_requests_total".to_string(),
+                )],
+                1,
+                1,
+            )
+            .await;
+            let plan =
+                PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
+                    .await
+                    .unwrap();
+            let plan = plan.display_indent_schema().to_string();
+            assert!(plan.contains(expected), "{query}: {plan}");
+        }
+    }
+
     #[tokio::test]
     async fn test_matchers_to_expr() {
         let mut eval_stmt = EvalStmt {
@@ -4297,16 +8179,15 @@ mod test {
             .await
             .unwrap();
         let expected = "Projection: sum(prometheus_tsdb_head_series.greptime_value), prometheus_tsdb_head_series.ip, prometheus_tsdb_head_series.greptime_timestamp [sum(prometheus_tsdb_head_series.greptime_value):Float64;N, ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None)]\
-        \n  Sort: prometheus_tsdb_head_series.greptime_timestamp ASC NULLS LAST, row_number() PARTITION BY [prometheus_tsdb_head_series.greptime_timestamp] ORDER BY [sum(prometheus_tsdb_head_series.greptime_value) DESC NULLS FIRST, prometheus_tsdb_head_series.ip DESC NULLS FIRST] ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW ASC NULLS LAST [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), sum(prometheus_tsdb_head_series.greptime_value):Float64;N, row_number() PARTITION BY [prometheus_tsdb_head_series.greptime_timestamp] ORDER BY [sum(prometheus_tsdb_head_series.greptime_value) DESC NULLS FIRST, prometheus_tsdb_head_series.ip DESC NULLS FIRST] ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW:UInt64]\
-        \n    Filter: row_number() PARTITION BY [prometheus_tsdb_head_series.greptime_timestamp] ORDER BY [sum(prometheus_tsdb_head_series.greptime_value) DESC NULLS FIRST, prometheus_tsdb_head_series.ip DESC NULLS FIRST] ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW <= Float64(10) [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), sum(prometheus_tsdb_head_series.greptime_value):Float64;N, row_number() PARTITION BY [prometheus_tsdb_head_series.greptime_timestamp] ORDER BY [sum(prometheus_tsdb_head_series.greptime_value) DESC NULLS FIRST, prometheus_tsdb_head_series.ip DESC NULLS FIRST] ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW:UInt64]\
-        \n      WindowAggr: windowExpr=[[row_number() PARTITION BY [prometheus_tsdb_head_series.greptime_timestamp] ORDER BY [sum(prometheus_tsdb_head_series.greptime_value) DESC NULLS FIRST, prometheus_tsdb_head_series.ip DESC NULLS FIRST] ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW]] [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), sum(prometheus_tsdb_head_series.greptime_value):Float64;N, row_number() PARTITION BY [prometheus_tsdb_head_series.greptime_timestamp] ORDER BY [sum(prometheus_tsdb_head_series.greptime_value) DESC NULLS FIRST, prometheus_tsdb_head_series.ip DESC NULLS FIRST] ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW:UInt64]\
-        \n        Sort: prometheus_tsdb_head_series.ip ASC NULLS LAST, prometheus_tsdb_head_series.greptime_timestamp ASC NULLS LAST [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), sum(prometheus_tsdb_head_series.greptime_value):Float64;N]\
-        \n          Aggregate: groupBy=[[prometheus_tsdb_head_series.ip, prometheus_tsdb_head_series.greptime_timestamp]], aggr=[[sum(prometheus_tsdb_head_series.greptime_value)]] [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), sum(prometheus_tsdb_head_series.greptime_value):Float64;N]\
-        \n            PromInstantManipulate: range=[0..100000000], lookback=[1000], interval=[5000], time index=[greptime_timestamp] [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), greptime_value:Float64;N]\
-        \n              PromSeriesDivide: tags=[\"ip\"] [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), greptime_value:Float64;N]\
-        \n                Sort: prometheus_tsdb_head_series.ip ASC NULLS FIRST, prometheus_tsdb_head_series.greptime_timestamp ASC NULLS FIRST [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), greptime_value:Float64;N]\
-        \n                  Filter: prometheus_tsdb_head_series.ip ~ Utf8(\"(10\\.0\\.160\\.237:8080|10\\.0\\.160\\.237:9090)\") AND prometheus_tsdb_head_series.greptime_timestamp >= TimestampMillisecond(-1000, None) AND prometheus_tsdb_head_series.greptime_timestamp <= TimestampMillisecond(100001000, None) [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), greptime_value:Float64;N]\
-        \n                    TableScan: prometheus_tsdb_head_series [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), greptime_value:Float64;N]";
+        \n  Sort: prometheus_tsdb_head_series.greptime_timestamp ASC NULLS LAST, sum(prometheus_tsdb_head_series.greptime_value) DESC NULLS FIRST, prometheus_tsdb_head_series.ip DESC NULLS FIRST [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), sum(prometheus_tsdb_head_series.greptime_value):Float64;N]\
+        \n    PromTopK: k=10, descending=true, groups=[\"greptime_timestamp\"], value=sum(prometheus_tsdb_head_series.greptime_value), tags=[\"ip\"] [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), sum(prometheus_tsdb_head_series.greptime_value):Float64;N]\
+        \n      Sort: prometheus_tsdb_head_series.ip ASC NULLS LAST, prometheus_tsdb_head_series.greptime_timestamp ASC NULLS LAST [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), sum(prometheus_tsdb_head_series.greptime_value):Float64;N]\
+        \n        Aggregate: groupBy=[[prometheus_tsdb_head_series.ip, prometheus_tsdb_head_series.greptime_timestamp]], aggr=[[sum(prometheus_tsdb_head_series.greptime_value)]] [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), sum(prometheus_tsdb_head_series.greptime_value):Float64;N]\
+        \n          PromInstantManipulate: range=[0..100000000], lookback=[1000], interval=[5000], time index=[greptime_timestamp] [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), greptime_value:Float64;N]\
+        \n            PromSeriesDivide: tags=[\"ip\"] [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), greptime_value:Float64;N]\
+        \n              Sort: prometheus_tsdb_head_series.ip ASC NULLS FIRST, prometheus_tsdb_head_series.greptime_timestamp ASC NULLS FIRST [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), greptime_value:Float64;N]\
+        \n                Filter: prometheus_tsdb_head_series.ip ~ Utf8(\"(10\\.0\\.160\\.237:8080|10\\.0\\.160\\.237:9090)\") AND prometheus_tsdb_head_series.greptime_timestamp >= TimestampMillisecond(-1000, None) AND prometheus_tsdb_head_series.greptime_timestamp <= TimestampMillisecond(100001000, None) [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), greptime_value:Float64;N]\
+        \n                  TableScan: prometheus_tsdb_head_series [ip:Utf8, greptime_timestamp:Timestamp(Millisecond, None), greptime_value:Float64;N]";
 
         assert_eq!(plan.display_indent_schema().to_string(), expected);
     }
@@ -4402,4 +8283,455 @@ mod test {
 
         assert_eq!(plan.display_indent_schema().to_string(), expected);
     }
+
+    #[tokio::test]
+    async fn test_plan_str_and_execute() {
+        use datafusion::arrow::array::{Array, AsArray};
+        use datafusion::arrow::datatypes::Float64Type;
+        use datafusion::physical_plan::collect;
+        use datatypes::prelude::VectorRef;
+        use datatypes::vectors::{Float64Vector, StringVector, TimestampMillisecondVector};
+        use promql::extension_plan::register_promql_extensions;
+        use table::test_util::MemTable;
+
+        // A counter increasing by 1 per second.
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+            ColumnSchema::new("val", ConcreteDataType::float64_datatype(), true),
+        ]));
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(vec!["a"; 5])),
+            Arc::new(TimestampMillisecondVector::from_vec(vec![
+                0, 30_000, 60_000, 90_000, 120_000,
+            ])),
+            Arc::new(Float64Vector::from_vec(vec![0.0, 30.0, 60.0, 90.0, 120.0])),
+        ];
+        let recordbatch = common_recordbatch::RecordBatch::new(schema, columns).unwrap();
+        let table = MemTable::table_with_primary_keys("metric", recordbatch, 1024, vec![0]);
+        let catalog_manager = MemoryCatalogManager::with_default_setup();
+        catalog_manager
+            .register_table_sync(RegisterTableRequest {
+                catalog: DEFAULT_CATALOG_NAME.to_string(),
+                schema: DEFAULT_SCHEMA_NAME.to_string(),
+                table_name: "metric".to_string(),
+                table_id: 1024,
+                table,
+            })
+            .unwrap();
+        let table_provider = DfTableSourceProvider::new(
+            catalog_manager,
+            false,
+            QueryContext::arc(),
+            DummyDecoder::arc(),
+            false,
+        );
+
+        let session_state = register_promql_extensions(SessionStateBuilder::new()).build();
+        let plan = PromPlanner::plan_str(
+            table_provider,
+            "rate(metric[1m])",
+            UNIX_EPOCH,
+            UNIX_EPOCH + Duration::from_secs(120),
+            Duration::from_secs(60),
+            Duration::from_secs(300),
+            &session_state,
+        )
+        .await
+        .unwrap();
+
+        let physical_plan = session_state.create_physical_plan(&plan).await.unwrap();
+        let batches = collect(physical_plan, session_state.task_ctx())
+            .await
+            .unwrap();
+        let rates = batches
+            .iter()
+            .flat_map(|batch| {
+                let column = batch
+                    .columns()
+                    .iter()
+                    .find(|column| column.data_type() == &ArrowDataType::Float64)
+                    .unwrap();
+                column.as_primitive::<Float64Type>().values().to_vec()
+            })
+            .collect::<Vec<_>>();
+        assert!(!rates.is_empty());
+        for rate in rates {
+            assert!((rate - 1.0).abs() < 1e-9, "unexpected rate {rate}");
+        }
+
+        let err = PromPlanner::plan_str(
+            build_test_table_provider(&[], 0, 0).await,
+            "rate(metric[1m]",
+            UNIX_EPOCH,
+            UNIX_EPOCH,
+            Duration::from_secs(60),
+            Duration::from_secs(300),
+            &session_state,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(err, crate::promql::error::Error::ParsePromQL { .. }),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_first_sample_policy() {
+        use datafusion::arrow::array::AsArray;
+        use datafusion::arrow::datatypes::Float64Type;
+        use datafusion::physical_plan::collect;
+        use datatypes::prelude::VectorRef;
+        use datatypes::vectors::{Float64Vector, StringVector, TimestampMillisecondVector};
+        use promql::extension_plan::register_promql_extensions;
+        use table::test_util::MemTable;
+
+        // A counter scraped every 10s from 50s to 110s, evaluated at 120s, so the
+        // range of `[1m]` starts at the sample at 60s.
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+            ColumnSchema::new("val", ConcreteDataType::float64_datatype(), true),
+        ]));
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(vec!["a"; 7])),
+            Arc::new(TimestampMillisecondVector::from_vec(
+                (5..=11).map(|i| i * 10_000).collect(),
+            )),
+            Arc::new(Float64Vector::from_vec(vec![
+                0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0,
+            ])),
+        ];
+        let recordbatch = common_recordbatch::RecordBatch::new(schema, columns).unwrap();
+        let table = MemTable::table_with_primary_keys("metric", recordbatch, 1024, vec![0]);
+        let catalog_manager = MemoryCatalogManager::with_default_setup();
+        catalog_manager
+            .register_table_sync(RegisterTableRequest {
+                catalog: DEFAULT_CATALOG_NAME.to_string(),
+                schema: DEFAULT_SCHEMA_NAME.to_string(),
+                table_name: "metric".to_string(),
+                table_id: 1024,
+                table,
+            })
+            .unwrap();
+        let session_state = register_promql_extensions(SessionStateBuilder::new()).build();
+
+        let execute = |query: &str, rate_first_sample: RateFirstSamplePolicy| {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH + Duration::from_secs(120),
+                end: UNIX_EPOCH + Duration::from_secs(120),
+                interval: Duration::from_secs(60),
+                lookback_delta: Duration::from_secs(300),
+            };
+            let table_provider = DfTableSourceProvider::new(
+                catalog_manager.clone(),
+                false,
+                QueryContext::arc(),
+                DummyDecoder::arc(),
+                false,
+            );
+            let session_state = session_state.clone();
+            async move {
+                let options = PromPlannerOptions {
+                    rate_first_sample,
+                    ..Default::default()
+                };
+                let plan = PromPlanner::stmt_to_plan_with_options(
+                    table_provider,
+                    &eval_stmt,
+                    &options,
+                    &session_state,
+                )
+                .await
+                .unwrap();
+                let physical_plan = session_state.create_physical_plan(&plan).await.unwrap();
+                collect(physical_plan, session_state.task_ctx())
+                    .await
+                    .unwrap()
+                    .iter()
+                    .flat_map(|batch| {
+                        let column = batch
+                            .columns()
+                            .iter()
+                            .find(|column| column.data_type() == &ArrowDataType::Float64)
+                            .unwrap();
+                        column.as_primitive::<Float64Type>().values().to_vec()
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+        let assert_close = |expected: f64, actual: Vec<f64>| {
+            assert_eq!(1, actual.len(), "{actual:?}");
+            assert!(
+                (expected - actual[0]).abs() < 1e-9,
+                "{expected} != {actual:?}"
+            );
+        };
+
+        // The increase of 5 between the samples at 60s and 110s is extrapolated to
+        // the end of the range.
+        let policy = RateFirstSamplePolicy::PrometheusExtrapolate;
+        assert_close(6.0, execute("increase(metric[1m])", policy).await);
+        assert_close(0.1, execute("rate(metric[1m])", policy).await);
+
+        // The sample at 60s is the previous sample of the range, and the increase
+        // since it is exact.
+        let policy = RateFirstSamplePolicy::UsePreviousSample;
+        assert_close(5.0, execute("increase(metric[1m])", policy).await);
+        assert_close(0.1, execute("rate(metric[1m])", policy).await);
+        // the other functions are not affected
+        assert_close(6.0, execute("delta(metric[1m])", policy).await);
+    }
+
+    #[tokio::test]
+    async fn test_subquery_at_modifier() {
+        use datafusion::arrow::array::AsArray;
+        use datafusion::arrow::datatypes::Float64Type;
+        use datafusion::physical_plan::collect;
+        use datatypes::prelude::VectorRef;
+        use datatypes::vectors::{Float64Vector, StringVector, TimestampMillisecondVector};
+        use promql::extension_plan::register_promql_extensions;
+        use table::test_util::MemTable;
+
+        // A gauge sampled every minute from 0s to 600s, whose value is the minute.
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), false),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+            ColumnSchema::new("val", ConcreteDataType::float64_datatype(), true),
+        ]));
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(vec!["a"; 11])),
+            Arc::new(TimestampMillisecondVector::from_vec(
+                (0..=10).map(|i| i * 60_000).collect(),
+            )),
+            Arc::new(Float64Vector::from_vec(
+                (0..=10).map(|i| i as f64).collect(),
+            )),
+        ];
+        let recordbatch = common_recordbatch::RecordBatch::new(schema, columns).unwrap();
+        let table = MemTable::table_with_primary_keys("metric", recordbatch, 1024, vec![0]);
+        let catalog_manager = MemoryCatalogManager::with_default_setup();
+        catalog_manager
+            .register_table_sync(RegisterTableRequest {
+                catalog: DEFAULT_CATALOG_NAME.to_string(),
+                schema: DEFAULT_SCHEMA_NAME.to_string(),
+                table_name: "metric".to_string(),
+                table_id: 1024,
+                table,
+            })
+            .unwrap();
+        let session_state = register_promql_extensions(SessionStateBuilder::new()).build();
+
+        // Evaluates the query at 300s, 450s and 600s.
+        let execute = |query: &str| {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH + Duration::from_secs(300),
+                end: UNIX_EPOCH + Duration::from_secs(600),
+                interval: Duration::from_secs(150),
+                lookback_delta: Duration::from_secs(300),
+            };
+            let table_provider = DfTableSourceProvider::new(
+                catalog_manager.clone(),
+                false,
+                QueryContext::arc(),
+                DummyDecoder::arc(),
+                false,
+            );
+            let session_state = session_state.clone();
+            async move {
+                let plan = PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &session_state)
+                    .await
+                    .unwrap();
+                let physical_plan = session_state.create_physical_plan(&plan).await.unwrap();
+                let mut values = collect(physical_plan, session_state.task_ctx())
+                    .await
+                    .unwrap()
+                    .iter()
+                    .flat_map(|batch| {
+                        let column = batch
+                            .columns()
+                            .iter()
+                            .find(|column| column.data_type() == &ArrowDataType::Float64)
+                            .unwrap();
+                        column.as_primitive::<Float64Type>().values().to_vec()
+                    })
+                    .collect::<Vec<_>>();
+                values.sort_by(f64::total_cmp);
+                values
+            }
+        };
+
+        // The subquery steps at 240s and 300s are in the range of each evaluation.
+        assert_eq!(
+            vec![5.0, 7.0, 10.0],
+            execute("max_over_time(metric[2m:1m])").await
+        );
+        // The subquery is pinned to the start, the max of the samples at 240s and 300s.
+        assert_eq!(
+            vec![5.0, 5.0, 5.0],
+            execute("max_over_time(metric[2m:1m] @ start())").await
+        );
+        assert_eq!(
+            vec![10.0, 10.0, 10.0],
+            execute("max_over_time(metric[2m:1m] @ end())").await
+        );
+        assert_eq!(
+            vec![3.0, 3.0, 3.0],
+            execute("max_over_time(metric[2m:1m] @ 180)").await
+        );
+        // `@ start()` refers to the start of the query even in an outer subquery,
+        // whose evaluation starts earlier.
+        assert_eq!(
+            vec![5.0, 5.0, 5.0],
+            execute("max_over_time((metric @ start())[2m:1m])").await
+        );
+    }
+
+    /// Evaluates `query` from 10s to 30s every 5s without any table.
+    async fn collect_without_tables(
+        query: &str,
+    ) -> Vec<datafusion::arrow::record_batch::RecordBatch> {
+        use datafusion::physical_plan::collect;
+        use promql::extension_plan::register_promql_extensions;
+
+        let eval_stmt = EvalStmt {
+            expr: parser::parse(&crate::parser::rewrite_non_standard(query)).unwrap(),
+            start: UNIX_EPOCH + Duration::from_secs(10),
+            end: UNIX_EPOCH + Duration::from_secs(30),
+            interval: Duration::from_secs(5),
+            lookback_delta: Duration::from_secs(1),
+        };
+        let table_provider = DfTableSourceProvider::new(
+            MemoryCatalogManager::with_default_setup(),
+            false,
+            QueryContext::arc(),
+            DummyDecoder::arc(),
+            false,
+        );
+        let session_state = register_promql_extensions(SessionStateBuilder::new()).build();
+        let plan = PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &session_state)
+            .await
+            .unwrap();
+        let physical_plan = session_state.create_physical_plan(&plan).await.unwrap();
+        collect(physical_plan, session_state.task_ctx())
+            .await
+            .unwrap()
+    }
+
+    /// Evaluates `query` like [collect_without_tables], returns the values of the
+    /// `column`.
+    async fn execute_without_tables(query: &str, column: &str) -> Vec<f64> {
+        use datafusion::arrow::array::AsArray;
+        use datafusion::arrow::datatypes::Float64Type;
+
+        collect_without_tables(query)
+            .await
+            .iter()
+            .flat_map(|batch| {
+                let column = batch.column_by_name(column).unwrap();
+                column.as_primitive::<Float64Type>().values().to_vec()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_absent_metric() {
+        // An absent metric matches no series, so do the functions and aggregations
+        // over it.
+        for query in [
+            "absent_metric",
+            r#"absent_metric{host="a"}"#,
+            "rate(absent_metric[5m])",
+            "sum(absent_metric)",
+            r#"sum by (host) (rate(absent_metric{host="a"}[5m]))"#,
+            "absent_metric + 1",
+        ] {
+            let num_rows = collect_without_tables(query)
+                .await
+                .iter()
+                .map(|batch| batch.num_rows())
+                .sum::<usize>();
+            assert_eq!(0, num_rows, "{query}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_missing_table_of_other_schema() {
+        // Only the absent metrics of the current schema match no series.
+        for query in [
+            r#"absent_metric{__schema__="public"}"#,
+            r#"absent_metric{__database__="not_exist"}"#,
+            r#"{__name__="other_schema.absent_metric"}"#,
+        ] {
+            let eval_stmt = EvalStmt {
+                expr: parser::parse(query).unwrap(),
+                start: UNIX_EPOCH,
+                end: UNIX_EPOCH + Duration::from_secs(100),
+                interval: Duration::from_secs(5),
+                lookback_delta: Duration::from_secs(1),
+            };
+            let table_provider = build_test_table_provider(&[], 0, 0).await;
+            let err = PromPlanner::stmt_to_plan(table_provider, &eval_stmt, &build_session_state())
+                .await
+                .unwrap_err();
+            assert_eq!(StatusCode::TableNotFound, err.status_code(), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_is_valid_metric_name() {
+        assert!(is_valid_metric_name("http_requests_total"));
+        assert!(is_valid_metric_name(":job:rate5m"));
+        assert!(!is_valid_metric_name("AnotherSchema.MemTotal"));
+        assert!(!is_valid_metric_name("5xx"));
+        assert!(!is_valid_metric_name(""));
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_of_vector() {
+        // The timestamps in seconds of the steps.
+        assert_eq!(
+            vec![10.0, 15.0, 20.0, 25.0, 30.0],
+            execute_without_tables("timestamp(vector(1))", "timestamp(greptime_value)").await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ema_over_steps() {
+        assert_eq!(
+            vec![10.0, 12.5, 16.25, 20.625, 25.3125],
+            execute_without_tables(
+                "ema_over_steps(0.5, timestamp(vector(1)))",
+                "timestamp(greptime_value)"
+            )
+            .await
+        );
+        // alpha 1 follows the input
+        assert_eq!(
+            vec![10.0, 15.0, 20.0, 25.0, 30.0],
+            execute_without_tables(
+                "ema_over_steps(1, timestamp(vector(1)))",
+                "timestamp(greptime_value)"
+            )
+            .await
+        );
+    }
 }
@@ -0,0 +1,281 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rollup tables pre-computed by flows from PromQL expressions.
+//!
+//! A flow created with the `promql_rewrite` option declares that its sink table
+//! holds the result of the PromQL expression at every `promql_rewrite_step`. The
+//! PromQL planner reads the sink table instead of evaluating the expression when
+//! the query contains it and the steps of the query are samples of the sink table.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use promql_parser::parser::Expr as PromExpr;
+use snafu::{ensure, OptionExt};
+use sql::statements::create::{FLOW_OPT_KEY_PROMQL_REWRITE, FLOW_OPT_KEY_PROMQL_REWRITE_STEP};
+use table::table_name::TableName;
+
+use crate::error::{InvalidPromRewriteSnafu, Result};
+
+/// The rollup tables of a database, keyed by the [fingerprint] of their expressions.
+pub type PromRollups = HashMap<String, Vec<PromRollup>>;
+
+/// The rollups of a database at a version of the [PromRollupRegistry].
+///
+/// They are compared by the version only, as the registry bumps it on every change.
+#[derive(Debug, Clone, Default)]
+pub struct VersionedPromRollups {
+    pub version: u64,
+    pub rollups: Arc<PromRollups>,
+}
+
+impl PartialEq for VersionedPromRollups {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version
+    }
+}
+
+impl Eq for VersionedPromRollups {}
+
+impl Hash for VersionedPromRollups {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.version.hash(state);
+    }
+}
+
+/// A rollup table written by a flow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromRollup {
+    pub flow_name: String,
+    pub table: TableName,
+    /// The interval between the timestamps of the rollup table.
+    pub step: Duration,
+}
+
+impl PromRollup {
+    /// Returns the fingerprint of the expression and the rollup declared by the
+    /// options of a flow, or `None` if the flow doesn't declare a rollup.
+    pub fn from_flow_options(
+        flow_name: &str,
+        sink_table: &TableName,
+        options: &HashMap<String, String>,
+    ) -> Result<Option<(String, PromRollup)>> {
+        let Some(query) = options.get(FLOW_OPT_KEY_PROMQL_REWRITE) else {
+            return Ok(None);
+        };
+        let expr = promql_parser::parser::parse(query).map_err(|reason| {
+            InvalidPromRewriteSnafu {
+                flow_name,
+                reason: format!("invalid PromQL expression `{query}`: {reason}"),
+            }
+            .build()
+        })?;
+        let step = options
+            .get(FLOW_OPT_KEY_PROMQL_REWRITE_STEP)
+            .with_context(|| InvalidPromRewriteSnafu {
+                flow_name,
+                reason: format!("`{FLOW_OPT_KEY_PROMQL_REWRITE_STEP}` is required"),
+            })?;
+        let step = humantime::parse_duration(step).map_err(|e| {
+            InvalidPromRewriteSnafu {
+                flow_name,
+                reason: format!("invalid step `{step}`: {e}"),
+            }
+            .build()
+        })?;
+        ensure!(
+            step.as_millis() > 0,
+            InvalidPromRewriteSnafu {
+                flow_name,
+                reason: "the step must be at least 1ms",
+            }
+        );
+
+        Ok(Some((
+            fingerprint(&expr),
+            PromRollup {
+                flow_name: flow_name.to_string(),
+                table: sink_table.clone(),
+                step,
+            },
+        )))
+    }
+
+    /// Returns true if every step of the evaluation is a timestamp of the rollup.
+    pub fn is_aligned(&self, start: i64, interval: i64) -> bool {
+        let step = self.step.as_millis() as i64;
+        interval % step == 0 && start % step == 0
+    }
+}
+
+/// Returns the fingerprint of a PromQL expression. Expressions that only differ
+/// in whitespace or quoting have the same fingerprint.
+pub fn fingerprint(expr: &PromExpr) -> String {
+    expr.to_string()
+}
+
+/// The rollup tables of all databases, maintained by `CREATE FLOW` and `DROP FLOW`.
+///
+/// A rollup only applies to queries in the database of its table.
+#[derive(Debug, Clone, Default)]
+pub struct PromRollupRegistry {
+    inner: Arc<RwLock<RegistryInner>>,
+}
+
+#[derive(Debug, Default)]
+struct RegistryInner {
+    /// (catalog, schema) -> rollups
+    rollups: HashMap<(String, String), PromRollups>,
+    /// Bumped on every change, so cached plans using stale rollups are not reused.
+    version: u64,
+}
+
+impl PromRollupRegistry {
+    /// Registers the rollup of a flow, replacing the previous one of the same flow.
+    pub fn register(&self, fingerprint: String, rollup: PromRollup) {
+        let mut inner = self.inner.write().unwrap();
+        inner.remove_flow(&rollup.table.catalog_name, &rollup.flow_name);
+        let key = (
+            rollup.table.catalog_name.clone(),
+            rollup.table.schema_name.clone(),
+        );
+        inner
+            .rollups
+            .entry(key)
+            .or_default()
+            .entry(fingerprint)
+            .or_default()
+            .push(rollup);
+        inner.version += 1;
+    }
+
+    /// Removes the rollup of the flow if there is one.
+    pub fn unregister(&self, catalog: &str, flow_name: &str) {
+        let mut inner = self.inner.write().unwrap();
+        if inner.remove_flow(catalog, flow_name) {
+            inner.version += 1;
+        }
+    }
+
+    /// Returns the version of the registry and the rollups of the database.
+    pub fn rollups(&self, catalog: &str, schema: &str) -> (u64, PromRollups) {
+        let inner = self.inner.read().unwrap();
+        let rollups = inner
+            .rollups
+            .get(&(catalog.to_string(), schema.to_string()))
+            .cloned()
+            .unwrap_or_default();
+        (inner.version, rollups)
+    }
+}
+
+impl RegistryInner {
+    /// Removes the rollup of the flow, returns true if it exists.
+    fn remove_flow(&mut self, catalog: &str, flow_name: &str) -> bool {
+        let mut removed = false;
+        for ((rollup_catalog, _), rollups) in self.rollups.iter_mut() {
+            if rollup_catalog != catalog {
+                continue;
+            }
+            for candidates in rollups.values_mut() {
+                let len = candidates.len();
+                candidates.retain(|rollup| rollup.flow_name != flow_name);
+                removed |= candidates.len() != len;
+            }
+            rollups.retain(|_, candidates| !candidates.is_empty());
+        }
+        self.rollups.retain(|_, rollups| !rollups.is_empty());
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(query: &str, step: &str) -> HashMap<String, String> {
+        HashMap::from([
+            (FLOW_OPT_KEY_PROMQL_REWRITE.to_string(), query.to_string()),
+            (
+                FLOW_OPT_KEY_PROMQL_REWRITE_STEP.to_string(),
+                step.to_string(),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_from_flow_options() {
+        let table = TableName::new("greptime", "public", "job_rate");
+        let (fingerprint, rollup) = PromRollup::from_flow_options(
+            "f",
+            &table,
+            &options("sum by (job) (rate(http_requests_total[5m]))", "1m"),
+        )
+        .unwrap()
+        .unwrap();
+        let expr =
+            promql_parser::parser::parse("sum by(job)(rate(http_requests_total[5m]))").unwrap();
+        assert_eq!(super::fingerprint(&expr), fingerprint);
+        assert_eq!(Duration::from_secs(60), rollup.step);
+        assert!(rollup.is_aligned(120_000, 60_000));
+        assert!(rollup.is_aligned(0, 300_000));
+        assert!(!rollup.is_aligned(0, 30_000));
+        assert!(!rollup.is_aligned(30_000, 60_000));
+
+        assert!(PromRollup::from_flow_options("f", &table, &HashMap::new())
+            .unwrap()
+            .is_none());
+        assert!(PromRollup::from_flow_options("f", &table, &options("sum(", "1m")).is_err());
+        assert!(PromRollup::from_flow_options("f", &table, &options("up", "1x")).is_err());
+        assert!(PromRollup::from_flow_options("f", &table, &options("up", "0s")).is_err());
+        let mut no_step = options("up", "1m");
+        no_step.remove(FLOW_OPT_KEY_PROMQL_REWRITE_STEP);
+        assert!(PromRollup::from_flow_options("f", &table, &no_step).is_err());
+    }
+
+    #[test]
+    fn test_registry() {
+        let registry = PromRollupRegistry::default();
+        let rollup = |flow: &str, table: &str| PromRollup {
+            flow_name: flow.to_string(),
+            table: TableName::new("greptime", "public", table),
+            step: Duration::from_secs(60),
+        };
+
+        registry.register("a".to_string(), rollup("f1", "t1"));
+        registry.register("a".to_string(), rollup("f2", "t2"));
+        let (version, rollups) = registry.rollups("greptime", "public");
+        assert_eq!(2, version);
+        assert_eq!(vec![rollup("f1", "t1"), rollup("f2", "t2")], rollups["a"]);
+        assert!(registry.rollups("greptime", "other").1.is_empty());
+
+        // replaces the rollup of the same flow
+        registry.register("b".to_string(), rollup("f1", "t3"));
+        let (_, rollups) = registry.rollups("greptime", "public");
+        assert_eq!(vec![rollup("f2", "t2")], rollups["a"]);
+        assert_eq!(vec![rollup("f1", "t3")], rollups["b"]);
+
+        registry.unregister("greptime", "f2");
+        let (version, rollups) = registry.rollups("greptime", "public");
+        assert_eq!(4, version);
+        assert!(!rollups.contains_key("a"));
+
+        // unknown flows don't change the version
+        registry.unregister("greptime", "f3");
+        assert_eq!(4, registry.rollups("greptime", "public").0);
+    }
+}
@@ -29,9 +29,11 @@ use common_function::handlers::{
 };
 use common_function::scalars::aggregate::AggregateFunctionMetaRef;
 use common_query::Output;
+use common_recordbatch::SendableRecordBatchStream;
 use datafusion_expr::LogicalPlan;
 use datatypes::schema::Schema;
 pub use default_serializer::{DefaultPlanDecoder, DefaultSerializer};
+use promql_parser::parser::EvalStmt;
 use session::context::QueryContextRef;
 use table::TableRef;
 
@@ -74,6 +76,14 @@ pub trait QueryEngine: Send + Sync {
     /// Execute the given [`LogicalPlan`].
     async fn execute(&self, plan: LogicalPlan, query_ctx: QueryContextRef) -> Result<Output>;
 
+    /// Plan and execute the given PromQL statement, returning the result as a
+    /// stream so the caller can consume it batch by batch.
+    async fn stream_promql(
+        &self,
+        stmt: EvalStmt,
+        query_ctx: QueryContextRef,
+    ) -> Result<SendableRecordBatchStream>;
+
     /// Register an aggregate function.
     ///
     /// # Panics
@@ -12,14 +12,44 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
+use common_time::Timezone;
 use session::context::QueryContextRef;
 use snafu::ensure;
 
 use crate::error::{QueryAccessDeniedSnafu, Result};
+use crate::promql::RateFirstSamplePolicy;
 
 #[derive(Default, Clone)]
 pub struct QueryOptions {
     pub disallow_cross_catalog_query: bool,
+    /// The timezone PromQL calendar functions like `hour()` are evaluated in. None means UTC.
+    pub promql_timezone: Option<Timezone>,
+    /// Whether to allow the non-standard PromQL `@ latest()` modifier.
+    pub promql_enable_latest_at: bool,
+    /// Whether to allow PromQL selectors and subqueries with negative offsets.
+    pub promql_enable_negative_offset: bool,
+    /// How far after the current time PromQL selectors with `@` can read. None means unlimited.
+    pub promql_max_at_lookahead: Option<Duration>,
+    /// Whether PromQL counting functions like `count_over_time()` return integers
+    /// instead of floats.
+    pub promql_integer_counts: bool,
+    /// Whether PromQL `rate()` and `increase()` use the created timestamps of counters.
+    pub promql_created_timestamps: bool,
+    /// How PromQL `rate()` and `increase()` treat the start of the range.
+    pub promql_rate_first_sample: RateFirstSamplePolicy,
+    /// Whether to fill the missing steps of PromQL results with the last known value.
+    pub promql_fill_forward: bool,
+    /// Whether PromQL aggregations propagate NaN samples instead of skipping them.
+    pub promql_propagate_nan: bool,
+    /// Whether PromQL `histogram_quantile()` reads the `_bucket` series of a base name.
+    pub promql_resolve_bucket_suffix: bool,
+    /// How long PromQL series keep their last value after staleness markers. None
+    /// ends them at the markers.
+    pub promql_staleness_delta: Option<Duration>,
+    /// The maximum bytes of the result of a query. None means unlimited.
+    pub max_result_bytes: Option<u64>,
 }
 
 // TODO(shuiyisong): remove one method after #559 is done
@@ -15,6 +15,7 @@
 use std::collections::HashMap;
 use std::fmt;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use async_trait::async_trait;
 use catalog::CatalogManagerRef;
@@ -26,6 +27,7 @@ use common_function::handlers::{
 use common_function::scalars::aggregate::AggregateFunctionMetaRef;
 use common_function::state::FunctionState;
 use common_telemetry::warn;
+use common_time::Timezone;
 use datafusion::dataframe::DataFrame;
 use datafusion::error::Result as DfResult;
 use datafusion::execution::context::{QueryPlanner, SessionConfig, SessionContext, SessionState};
@@ -41,6 +43,7 @@ use datafusion_optimizer::analyzer::count_wildcard_rule::CountWildcardRule;
 use datafusion_optimizer::analyzer::{Analyzer, AnalyzerRule};
 use datafusion_optimizer::optimizer::Optimizer;
 use promql::extension_plan::PromExtensionPlanner;
+use promql::functions::{HistogramPack, HistogramQuantile};
 use table::table::adapter::DfTableProviderAdapter;
 use table::TableRef;
 
@@ -54,6 +57,9 @@ use crate::optimizer::string_normalization::StringNormalizationRule;
 use crate::optimizer::type_conversion::TypeConversionRule;
 use crate::optimizer::windowed_sort::WindowedSortPhysicalRule;
 use crate::optimizer::ExtensionAnalyzerRule;
+use crate::promql::plan_cache::PromPlanCache;
+use crate::promql::rollup::PromRollupRegistry;
+use crate::promql::RateFirstSamplePolicy;
 use crate::query_engine::options::QueryOptions;
 use crate::query_engine::DefaultSerializer;
 use crate::range_select::planner::RangeSelectPlanner;
@@ -69,6 +75,8 @@ pub struct QueryEngineState {
     udf_functions: Arc<RwLock<HashMap<String, FunctionRef>>>,
     aggregate_functions: Arc<RwLock<HashMap<String, AggregateFunctionMetaRef>>>,
     extension_rules: Vec<Arc<dyn ExtensionAnalyzerRule + Send + Sync>>,
+    promql_plan_cache: PromPlanCache,
+    promql_rollups: PromRollupRegistry,
     plugins: Plugins,
 }
 
@@ -161,6 +169,9 @@ impl QueryEngineState {
             .build();
 
         let df_context = SessionContext::new_with_state(session_state);
+        // SQL packs the columns of native histograms to estimate their quantiles.
+        df_context.register_udf(HistogramPack::scalar_udf());
+        df_context.register_udf(HistogramQuantile::sql_udf());
 
         Self {
             df_context,
@@ -172,6 +183,8 @@ impl QueryEngineState {
             }),
             aggregate_functions: Arc::new(RwLock::new(HashMap::new())),
             extension_rules,
+            promql_plan_cache: PromPlanCache::default(),
+            promql_rollups: PromRollupRegistry::default(),
             plugins,
             udf_functions: Arc::new(RwLock::new(HashMap::new())),
         }
@@ -299,6 +312,89 @@ impl QueryEngineState {
             .unwrap_or(false)
     }
 
+    pub(crate) fn promql_timezone(&self) -> Option<Timezone> {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| x.promql_timezone.clone())
+            .flatten()
+    }
+
+    pub(crate) fn promql_enable_latest_at(&self) -> bool {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| x.promql_enable_latest_at)
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn promql_enable_negative_offset(&self) -> bool {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| x.promql_enable_negative_offset)
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn promql_max_at_lookahead(&self) -> Option<Duration> {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| x.promql_max_at_lookahead)
+            .flatten()
+    }
+
+    pub(crate) fn promql_integer_counts(&self) -> bool {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| x.promql_integer_counts)
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn promql_created_timestamps(&self) -> bool {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| x.promql_created_timestamps)
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn promql_rate_first_sample(&self) -> RateFirstSamplePolicy {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| x.promql_rate_first_sample)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn promql_fill_forward(&self) -> bool {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| x.promql_fill_forward)
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn promql_propagate_nan(&self) -> bool {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| x.promql_propagate_nan)
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn promql_resolve_bucket_suffix(&self) -> bool {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| x.promql_resolve_bucket_suffix)
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn promql_staleness_delta(&self) -> Option<Duration> {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| x.promql_staleness_delta)
+            .flatten()
+    }
+
+    pub(crate) fn max_result_bytes(&self) -> Option<u64> {
+        self.plugins
+            .map::<QueryOptions, _, _>(|x| x.max_result_bytes)
+            .flatten()
+    }
+
+    /// Returns the cache of PromQL logical plans shared by all queries.
+    pub(crate) fn promql_plan_cache(&self) -> &PromPlanCache {
+        &self.promql_plan_cache
+    }
+
+    /// Returns the rollup tables the PromQL planner reads instead of evaluating
+    /// the expressions they pre-compute.
+    pub fn promql_rollups(&self) -> &PromRollupRegistry {
+        &self.promql_rollups
+    }
+
     pub fn session_state(&self) -> SessionState {
         self.df_context.state()
     }
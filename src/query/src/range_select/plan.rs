@@ -40,12 +40,14 @@ use datafusion_common::hash_utils::create_hashes;
 use datafusion_common::{DFSchema, DFSchemaRef, DataFusionError, ScalarValue};
 use datafusion_expr::utils::{exprlist_to_fields, COUNT_STAR_EXPANSION};
 use datafusion_expr::{
-    lit, Accumulator, Expr, ExprSchemable, LogicalPlan, UserDefinedLogicalNodeCore,
+    lit, Accumulator, Expr, ExprSchemable, LogicalPlan, LogicalPlanBuilder,
+    UserDefinedLogicalNodeCore,
 };
 use datafusion_physical_expr::aggregate::{AggregateExprBuilder, AggregateFunctionExpr};
+use datafusion_physical_expr::expressions::Column as ColumnExpr;
 use datafusion_physical_expr::{
-    create_physical_expr, Distribution, EquivalenceProperties, LexOrdering, Partitioning,
-    PhysicalExpr, PhysicalSortExpr,
+    create_physical_expr, Distribution, EquivalenceProperties, LexOrdering, LexRequirement,
+    Partitioning, PhysicalExpr, PhysicalSortExpr, PhysicalSortRequirement,
 };
 use datatypes::arrow::array::{
     Array, ArrayRef, TimestampMillisecondArray, TimestampMillisecondBuilder, UInt32Builder,
@@ -270,6 +272,30 @@ impl Display for RangeFn {
     }
 }
 
+/// `ORDER BY <time index> [ASC|DESC] LIMIT n` pushed down to a [RangeSelect].
+///
+/// The range select reads its input in the order of the time index and stops
+/// reading once there are at least `limit` output rows whose align slots can't
+/// receive any more input rows. The sort and limit above the range select are
+/// still required to pick the final rows from the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Hash)]
+pub struct RangeFetch {
+    pub descending: bool,
+    /// The number of rows to fetch, including the rows to skip.
+    pub limit: usize,
+}
+
+impl Display for RangeFetch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            self.limit,
+            if self.descending { "DESC" } else { "ASC" }
+        )
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct RangeSelect {
     /// The incoming logical plan
@@ -291,6 +317,8 @@ pub struct RangeSelect {
     /// `schema_before_project  ----  schema_project ----> schema`
     /// if `schema_project==None` then `schema_before_project==schema`
     pub schema_before_project: DFSchemaRef,
+    /// The `ORDER BY <time index> LIMIT n` pushed down to the range select.
+    pub fetch: Option<RangeFetch>,
 }
 
 impl PartialOrd for RangeSelect {
@@ -324,7 +352,11 @@ impl PartialOrd for RangeSelect {
             Some(Ordering::Equal) => {}
             ord => return ord,
         }
-        self.schema_project.partial_cmp(&other.schema_project)
+        match self.schema_project.partial_cmp(&other.schema_project) {
+            Some(Ordering::Equal) => {}
+            ord => return ord,
+        }
+        self.fetch.partial_cmp(&other.fetch)
     }
 }
 
@@ -437,6 +469,32 @@ impl RangeSelect {
             by,
             schema_project,
             schema_before_project,
+            fetch: None,
+        })
+    }
+
+    /// Pushes `fetch` down to the range select, the input is sorted by the time
+    /// index in the order of `fetch`.
+    pub fn with_fetch(&self, fetch: RangeFetch) -> DfResult<Self> {
+        let input = LogicalPlanBuilder::from(self.input.as_ref().clone())
+            .sort(vec![self
+                .time_expr
+                .clone()
+                .sort(!fetch.descending, fetch.descending)])?
+            .build()?;
+        Ok(Self {
+            input: Arc::new(input),
+            range_expr: self.range_expr.clone(),
+            align: self.align,
+            align_to: self.align_to,
+            time_index: self.time_index.clone(),
+            time_expr: self.time_expr.clone(),
+            by: self.by.clone(),
+            schema: self.schema.clone(),
+            by_schema: self.by_schema.clone(),
+            schema_project: self.schema_project.clone(),
+            schema_before_project: self.schema_before_project.clone(),
+            fetch: Some(fetch),
         })
     }
 }
@@ -480,7 +538,11 @@ impl UserDefinedLogicalNodeCore for RangeSelect {
                 .collect::<Vec<_>>()
                 .join(", "),
             self.time_index
-        )
+        )?;
+        if let Some(fetch) = &self.fetch {
+            write!(f, ", fetch={fetch}")?;
+        }
+        Ok(())
     }
 
     fn with_exprs_and_inputs(
@@ -525,6 +587,7 @@ impl UserDefinedLogicalNodeCore for RangeSelect {
             by_schema: self.by_schema.clone(),
             schema_project: self.schema_project.clone(),
             schema_before_project: self.schema_before_project.clone(),
+            fetch: self.fetch,
         })
     }
 }
@@ -704,6 +767,7 @@ impl RangeSelect {
             metric: ExecutionPlanMetricsSet::new(),
             schema_before_project,
             schema_project: self.schema_project.clone(),
+            fetch: self.fetch,
             cache,
         }))
     }
@@ -760,9 +824,27 @@ pub struct RangeSelectExec {
     metric: ExecutionPlanMetricsSet,
     schema_project: Option<Vec<usize>>,
     schema_before_project: SchemaRef,
+    fetch: Option<RangeFetch>,
     cache: PlanProperties,
 }
 
+impl RangeSelectExec {
+    /// Returns the ordering of the time index the input must follow to fetch
+    /// rows early.
+    fn fetch_ordering(&self) -> Option<LexRequirement> {
+        let fetch = self.fetch?;
+        let time_index =
+            ColumnExpr::new_with_schema(&self.time_index, &self.input.schema()).ok()?;
+        Some(LexRequirement::new(vec![PhysicalSortRequirement {
+            expr: Arc::new(time_index),
+            options: Some(SortOptions {
+                descending: fetch.descending,
+                nulls_first: fetch.descending,
+            }),
+        }]))
+    }
+}
+
 impl DisplayAs for RangeSelectExec {
     fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match t {
@@ -780,6 +862,9 @@ impl DisplayAs for RangeSelectExec {
                     by.join(", "),
                     self.time_index,
                 )?;
+                if let Some(fetch) = &self.fetch {
+                    write!(f, ", fetch={fetch}")?;
+                }
             }
         }
         Ok(())
@@ -799,6 +884,10 @@ impl ExecutionPlan for RangeSelectExec {
         vec![Distribution::SinglePartition]
     }
 
+    fn required_input_ordering(&self) -> Vec<Option<LexRequirement>> {
+        vec![self.fetch_ordering()]
+    }
+
     fn properties(&self) -> &PlanProperties {
         &self.cache
     }
@@ -824,6 +913,7 @@ impl ExecutionPlan for RangeSelectExec {
             metric: self.metric.clone(),
             schema_before_project: self.schema_before_project.clone(),
             schema_project: self.schema_project.clone(),
+            fetch: self.fetch,
             cache: self.cache.clone(),
         }))
     }
@@ -842,6 +932,8 @@ impl ExecutionPlan for RangeSelectExec {
                 "time index column not found".into(),
             ))?
             .0;
+        // Only fetches early if the input is sorted by the time index.
+        let fetch = self.fetch_ordering().and(self.fetch);
         let row_converter = RowConverter::new(
             self.by_schema
                 .fields()
@@ -866,6 +958,8 @@ impl ExecutionPlan for RangeSelectExec {
             metric: baseline_metric,
             schema_project: self.schema_project.clone(),
             schema_before_project: self.schema_before_project.clone(),
+            fetch,
+            fetch_bound: None,
         }))
     }
 
@@ -909,6 +1003,10 @@ struct RangeSelectStream {
     metric: BaselineMetrics,
     schema_project: Option<Vec<usize>>,
     schema_before_project: SchemaRef,
+    /// The fetch pushed down, the input is sorted by the time index if it's set.
+    fetch: Option<RangeFetch>,
+    /// The last timestamp read from the sorted input.
+    fetch_bound: Option<Millisecond>,
 }
 
 #[derive(Debug)]
@@ -998,6 +1096,14 @@ impl RangeSelectStream {
                     "Time index Column downcast to TimestampMillisecondArray failed".into(),
                 )
             })?;
+        if let Some(fetch) = &self.fetch {
+            let bound = if fetch.descending {
+                compute::min(ts_column_ref)
+            } else {
+                compute::max(ts_column_ref)
+            };
+            self.fetch_bound = bound.or(self.fetch_bound);
+        }
         for i in 0..self.range_exec.len() {
             let args = self.evaluate_many(&batch, &self.range_exec[i].expressions())?;
             // use self.modify_map record (hash, align_ts) => [row_nums]
@@ -1060,6 +1166,53 @@ impl RangeSelectStream {
         Ok(())
     }
 
+    /// Returns true if there are enough rows to fetch in the align slots that
+    /// can't receive more input rows, and removes the other align slots.
+    ///
+    /// Input rows are sorted by the time index, so the following rows are not
+    /// after (DESC) or not before (ASC) the `fetch_bound`. A slot `align_ts`
+    /// covers the rows in `[align_ts, align_ts + range)`.
+    fn try_finish_fetch(&mut self) -> bool {
+        let (Some(fetch), Some(bound)) = (self.fetch, self.fetch_bound) else {
+            return false;
+        };
+        let max_range = self
+            .range_exec
+            .iter()
+            .map(|range| range.range)
+            .max()
+            .unwrap_or_default();
+        // The first incomplete slot (DESC) or the first slot after the complete
+        // slots (ASC).
+        let split_ts = if fetch.descending {
+            bound.saturating_add(1)
+        } else {
+            bound.saturating_sub(max_range).saturating_add(1)
+        };
+        let num_complete_rows = self
+            .series_map
+            .values()
+            .map(|series| {
+                if fetch.descending {
+                    series.align_ts_accumulator.range(split_ts..).count()
+                } else {
+                    series.align_ts_accumulator.range(..split_ts).count()
+                }
+            })
+            .sum::<usize>();
+        if num_complete_rows < fetch.limit {
+            return false;
+        }
+
+        for series in self.series_map.values_mut() {
+            let after = series.align_ts_accumulator.split_off(&split_ts);
+            if fetch.descending {
+                series.align_ts_accumulator = after;
+            }
+        }
+        true
+    }
+
     fn generate_output(&mut self) -> DfResult<RecordBatch> {
         let _timer = self.metric.elapsed_compute().timer();
         if self.series_map.is_empty() {
@@ -1181,6 +1334,9 @@ impl Stream for RangeSelectStream {
                                 );
                                 return Poll::Ready(Some(Err(e)));
                             }
+                            if self.try_finish_fetch() {
+                                self.exec_state = ExecutionState::ProducingOutput;
+                            }
                         }
                         // inner had error, return to caller
                         Some(Err(e)) => return Poll::Ready(Some(Err(e))),
@@ -1250,7 +1406,7 @@ mod test {
     use datafusion::prelude::SessionContext;
     use datafusion_physical_expr::expressions::Column;
     use datafusion_physical_expr::PhysicalSortExpr;
-    use datatypes::arrow::array::TimestampMillisecondArray;
+    use datatypes::arrow::array::{Int64Array, TimestampMillisecondArray};
     use datatypes::arrow_array::StringArray;
 
     use super::*;
@@ -1387,6 +1543,7 @@ mod test {
             schema_project: None,
             by_schema: Arc::new(Schema::new(vec![Field::new("host", DataType::Utf8, true)])),
             metric: ExecutionPlanMetricsSet::new(),
+            fetch: None,
             cache,
         });
         let sort_exec = SortExec::new(
@@ -1696,6 +1853,182 @@ mod test {
         .await;
     }
 
+    /// Runs `MIN(value) RANGE 10s` aligned to 5s over two hosts with a row every
+    /// 5s, the input is sorted by the time index and has a batch per timestamp.
+    /// Returns the output of the range select and the output sorted by
+    /// `timestamp, host` with the limit.
+    async fn do_range_fetch_test(
+        fetch: Option<RangeFetch>,
+        descending: bool,
+        limit: usize,
+    ) -> (usize, String) {
+        let input_schema = Arc::new(Schema::new(vec![
+            Field::new(TIME_INDEX_COLUMN, TimestampMillisecondType::DATA_TYPE, true),
+            Field::new("value", DataType::Int64, true),
+            Field::new("host", DataType::Utf8, true),
+        ]));
+        let mut timestamps = vec![0, 5_000, 10_000, 15_000, 20_000];
+        if descending {
+            timestamps.reverse();
+        }
+        let batches = timestamps
+            .into_iter()
+            .map(|ts| {
+                RecordBatch::try_new(
+                    input_schema.clone(),
+                    vec![
+                        Arc::new(TimestampMillisecondArray::from(vec![ts, ts])),
+                        Arc::new(Int64Array::from(vec![ts / 1000, ts / 1000 + 1])),
+                        Arc::new(StringArray::from(vec!["host1", "host2"])),
+                    ],
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let memory_exec =
+            Arc::new(MemoryExec::try_new(&[batches], input_schema.clone(), None).unwrap());
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("MIN(value)", DataType::Int64, true),
+            Field::new(TIME_INDEX_COLUMN, TimestampMillisecondType::DATA_TYPE, true),
+            Field::new("host", DataType::Utf8, true),
+        ]));
+        let cache = PlanProperties::new(
+            EquivalenceProperties::new(schema.clone()),
+            Partitioning::UnknownPartitioning(1),
+            EmissionType::Incremental,
+            Boundedness::Bounded,
+        );
+        let range_select_exec = Arc::new(RangeSelectExec {
+            input: memory_exec,
+            range_exec: vec![RangeFnExec {
+                expr: Arc::new(
+                    AggregateExprBuilder::new(
+                        min_max::min_udaf(),
+                        vec![Arc::new(Column::new("value", 1))],
+                    )
+                    .schema(input_schema)
+                    .alias("MIN(value)")
+                    .build()
+                    .unwrap(),
+                ),
+                range: 10_000,
+                fill: None,
+                need_cast: None,
+            }],
+            align: 5_000,
+            align_to: 0,
+            by: vec![Arc::new(Column::new("host", 2))],
+            time_index: TIME_INDEX_COLUMN.to_string(),
+            schema: schema.clone(),
+            schema_before_project: schema.clone(),
+            schema_project: None,
+            by_schema: Arc::new(Schema::new(vec![Field::new("host", DataType::Utf8, true)])),
+            metric: ExecutionPlanMetricsSet::new(),
+            fetch,
+            cache,
+        });
+        let session_context = SessionContext::default();
+        let num_rows = datafusion::physical_plan::collect(
+            range_select_exec.clone(),
+            session_context.task_ctx(),
+        )
+        .await
+        .unwrap()
+        .iter()
+        .map(|batch| batch.num_rows())
+        .sum();
+
+        let sort_exec = SortExec::new(
+            LexOrdering::new(vec![
+                PhysicalSortExpr {
+                    expr: Arc::new(Column::new(TIME_INDEX_COLUMN, 1)),
+                    options: SortOptions {
+                        descending,
+                        nulls_first: descending,
+                    },
+                },
+                PhysicalSortExpr {
+                    expr: Arc::new(Column::new("host", 2)),
+                    options: SortOptions {
+                        descending: false,
+                        nulls_first: true,
+                    },
+                },
+            ]),
+            range_select_exec,
+        )
+        .with_fetch(Some(limit));
+        let result =
+            datafusion::physical_plan::collect(Arc::new(sort_exec), session_context.task_ctx())
+                .await
+                .unwrap();
+        let result_literal = arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string();
+
+        (num_rows, result_literal)
+    }
+
+    #[tokio::test]
+    async fn range_fetch_desc() {
+        // The limit ends between the two hosts of the slot 15s.
+        let fetch = RangeFetch {
+            descending: true,
+            limit: 3,
+        };
+        let (num_rows, expected) = do_range_fetch_test(None, true, 3).await;
+        assert_eq!(12, num_rows);
+        let (num_rows, result) = do_range_fetch_test(Some(fetch), true, 3).await;
+        // Only reads the slots 20s and 15s.
+        assert_eq!(4, num_rows);
+        assert_eq!(expected, result);
+        assert_eq!(
+            "+------------+---------------------+-------+\
+            \n| MIN(value) | timestamp           | host  |\
+            \n+------------+---------------------+-------+\
+            \n| 20         | 1970-01-01T00:00:20 | host1 |\
+            \n| 21         | 1970-01-01T00:00:20 | host2 |\
+            \n| 15         | 1970-01-01T00:00:15 | host1 |\
+            \n+------------+---------------------+-------+",
+            result
+        );
+    }
+
+    #[tokio::test]
+    async fn range_fetch_asc() {
+        // The limit ends between the two hosts of the slot 0s.
+        let fetch = RangeFetch {
+            descending: false,
+            limit: 3,
+        };
+        let (num_rows, expected) = do_range_fetch_test(None, false, 3).await;
+        assert_eq!(12, num_rows);
+        let (num_rows, result) = do_range_fetch_test(Some(fetch), false, 3).await;
+        // Only reads the slots -5s and 0s.
+        assert_eq!(4, num_rows);
+        assert_eq!(expected, result);
+        assert_eq!(
+            "+------------+---------------------+-------+\
+            \n| MIN(value) | timestamp           | host  |\
+            \n+------------+---------------------+-------+\
+            \n| 0          | 1969-12-31T23:59:55 | host1 |\
+            \n| 1          | 1969-12-31T23:59:55 | host2 |\
+            \n| 0          | 1970-01-01T00:00:00 | host1 |\
+            \n+------------+---------------------+-------+",
+            result
+        );
+
+        // Fetches all rows if the limit is larger than the output.
+        let fetch = RangeFetch {
+            descending: false,
+            limit: 20,
+        };
+        let (num_rows, result) = do_range_fetch_test(Some(fetch), false, 20).await;
+        assert_eq!(12, num_rows);
+        assert_eq!(do_range_fetch_test(None, false, 20).await.1, result);
+    }
+
     #[test]
     fn fill_test() {
         assert!(Fill::try_from_str("", &DataType::UInt8).unwrap().is_none());
@@ -33,7 +33,7 @@ use datafusion_expr::expr::WildcardOptions;
 use datafusion_expr::simplify::SimplifyContext;
 use datafusion_expr::{
     Aggregate, Analyze, Cast, Distinct, DistinctOn, Explain, Expr, ExprSchemable, Extension,
-    LogicalPlan, LogicalPlanBuilder, Projection,
+    FetchType, LogicalPlan, LogicalPlanBuilder, Projection, SkipType, SortExpr,
 };
 use datafusion_optimizer::simplify_expressions::ExprSimplifier;
 use datatypes::prelude::ConcreteDataType;
@@ -46,7 +46,7 @@ use crate::error::{
     CatalogSnafu, RangeQuerySnafu, Result, TimeIndexNotFoundSnafu, UnknownTableSnafu,
 };
 use crate::plan::ExtractExpr;
-use crate::range_select::plan::{Fill, RangeFn, RangeSelect};
+use crate::range_select::plan::{Fill, RangeFetch, RangeFn, RangeSelect};
 
 /// `RangeExprRewriter` will recursively search certain `Expr`, find all `range_fn` scalar udf contained in `Expr`,
 /// and collect the information required by the RangeSelect query,
@@ -340,7 +340,7 @@ impl RangePlanRewriter {
 
     pub async fn rewrite(&mut self, plan: LogicalPlan) -> Result<LogicalPlan> {
         match self.rewrite_logical_plan(&plan).await? {
-            Some(new_plan) => Ok(new_plan),
+            Some(new_plan) => Ok(push_down_range_fetch(new_plan)?),
             None => Ok(plan),
         }
     }
@@ -542,6 +542,96 @@ impl RangePlanRewriter {
     }
 }
 
+/// Pushes `ORDER BY <time index> [ASC|DESC] LIMIT n` down to the [RangeSelect]
+/// below, the sort and limit are kept to pick the final rows.
+fn push_down_range_fetch(plan: LogicalPlan) -> DFResult<LogicalPlan> {
+    plan.transform_down(|plan| {
+        let LogicalPlan::Limit(limit) = &plan else {
+            return Ok(Transformed::no(plan));
+        };
+        let (SkipType::Literal(skip), FetchType::Literal(Some(fetch))) =
+            (limit.get_skip_type()?, limit.get_fetch_type()?)
+        else {
+            return Ok(Transformed::no(plan));
+        };
+        let LogicalPlan::Sort(sort) = limit.input.as_ref() else {
+            return Ok(Transformed::no(plan));
+        };
+        let Some(SortExpr {
+            expr: Expr::Column(column),
+            asc,
+            ..
+        }) = sort.expr.first()
+        else {
+            return Ok(Transformed::no(plan));
+        };
+        let fetch = RangeFetch {
+            descending: !asc,
+            limit: skip.saturating_add(fetch),
+        };
+        match push_down_fetch_to_range(&sort.input, column.clone(), fetch)? {
+            Some(sort_input) => {
+                let sort = limit
+                    .input
+                    .with_new_exprs(limit.input.expressions(), vec![sort_input])?;
+                let plan = plan.with_new_exprs(plan.expressions(), vec![sort])?;
+                Ok(Transformed::yes(plan))
+            }
+            None => Ok(Transformed::no(plan)),
+        }
+    })
+    .map(|plan| plan.data)
+}
+
+/// Pushes `fetch` down to the [RangeSelect] through projections, if `column`
+/// is the time index of the range select and no range expr is filled.
+fn push_down_fetch_to_range(
+    plan: &LogicalPlan,
+    column: Column,
+    fetch: RangeFetch,
+) -> DFResult<Option<LogicalPlan>> {
+    match plan {
+        LogicalPlan::Projection(projection) => {
+            let Ok(index) = projection.schema.index_of_column(&column) else {
+                return Ok(None);
+            };
+            let column = match &projection.expr[index] {
+                Expr::Column(column) => column.clone(),
+                Expr::Alias(alias) => match alias.expr.as_ref() {
+                    Expr::Column(column) => column.clone(),
+                    _ => return Ok(None),
+                },
+                _ => return Ok(None),
+            };
+            let Some(input) = push_down_fetch_to_range(&projection.input, column, fetch)? else {
+                return Ok(None);
+            };
+            plan.with_new_exprs(plan.expressions(), vec![input])
+                .map(Some)
+        }
+        LogicalPlan::Extension(Extension { node }) => {
+            let Some(range_select) = node.as_any().downcast_ref::<RangeSelect>() else {
+                return Ok(None);
+            };
+            // Filled rows are generated from the rows before and after them.
+            if range_select.fetch.is_some()
+                || column.name != range_select.time_index
+                || range_select.schema.index_of_column(&column).is_err()
+                || range_select
+                    .range_expr
+                    .iter()
+                    .any(|range_fn| range_fn.fill.is_some())
+            {
+                return Ok(None);
+            }
+            Ok(Some(LogicalPlan::Extension(Extension {
+                node: Arc::new(range_select.with_fetch(fetch)?),
+            })))
+        }
+        _ => Ok(None),
+    }
+}
+
 fn have_range_in_exprs(exprs: &[Expr]) -> bool {
     exprs.iter().any(|expr| {
         let mut find_range = false;
@@ -801,6 +891,34 @@ mod test {
         query_plan_compare(query, expected).await;
     }
 
+    #[tokio::test]
+    async fn range_order_by_limit() {
+        let query = r#"SELECT timestamp, tag_0, min(field_0) RANGE '5m' FROM test ALIGN '1h' by (tag_0) ORDER BY timestamp DESC LIMIT 10;"#;
+        let expected = String::from(
+            "Limit: skip=0, fetch=10 [timestamp:Timestamp(Millisecond, None), tag_0:Utf8, min(test.field_0) RANGE 5m:Float64;N]\
+            \n  Sort: test.timestamp DESC NULLS FIRST [timestamp:Timestamp(Millisecond, None), tag_0:Utf8, min(test.field_0) RANGE 5m:Float64;N]\
+            \n    RangeSelect: range_exprs=[min(test.field_0) RANGE 5m], align=3600000ms, align_to=0ms, align_by=[test.tag_0], time_index=timestamp, fetch=10 DESC [timestamp:Timestamp(Millisecond, None), tag_0:Utf8, min(test.field_0) RANGE 5m:Float64;N]\
+            \n      Sort: test.timestamp DESC NULLS FIRST [tag_0:Utf8, tag_1:Utf8, tag_2:Utf8, tag_3:Utf8, tag_4:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N, field_1:Float64;N, field_2:Float64;N, field_3:Float64;N, field_4:Float64;N]\
+            \n        TableScan: test [tag_0:Utf8, tag_1:Utf8, tag_2:Utf8, tag_3:Utf8, tag_4:Utf8, timestamp:Timestamp(Millisecond, None), field_0:Float64;N, field_1:Float64;N, field_2:Float64;N, field_3:Float64;N, field_4:Float64;N]"
+        );
+        query_plan_compare(query, expected).await;
+
+        // The skipped rows are fetched too.
+        let query = r#"SELECT timestamp AS ts, min(field_0) RANGE '5m' FROM test ALIGN '1h' by (tag_0) ORDER BY ts LIMIT 10 OFFSET 5;"#;
+        let plan = do_query(query).await.unwrap().display_indent().to_string();
+        assert!(plan.contains("fetch=15 ASC"), "{plan}");
+
+        // Not sorted by the time index.
+        let query = r#"SELECT timestamp, tag_0, min(field_0) RANGE '5m' FROM test ALIGN '1h' by (tag_0) ORDER BY tag_0, timestamp LIMIT 10;"#;
+        let plan = do_query(query).await.unwrap().display_indent().to_string();
+        assert!(!plan.contains("fetch="), "{plan}");
+
+        // Filled rows depend on the following rows.
+        let query = r#"SELECT timestamp, tag_0, min(field_0) RANGE '5m' FILL NULL FROM test ALIGN '1h' by (tag_0) ORDER BY timestamp DESC LIMIT 10;"#;
+        let plan = do_query(query).await.unwrap().display_indent().to_string();
+        assert!(!plan.contains("fetch="), "{plan}");
+    }
+
     #[tokio::test]
     async fn range_nest_range_err() {
         let query = r#"SELECT sum(avg(field_0 + field_1) RANGE '5m' + 1) RANGE '5m' + 1 FROM test ALIGN '1h' by (tag_0,tag_1);"#;
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -20,6 +21,7 @@ use common_query::request::QueryRequest;
 use common_recordbatch::SendableRecordBatchStream;
 use partition::manager::PartitionRuleManagerRef;
 use session::ReadPreference;
+use store_api::storage::RegionId;
 
 use crate::error::Result;
 
@@ -42,6 +44,12 @@ pub trait RegionQueryHandler: Send + Sync {
         read_preference: ReadPreference,
         request: QueryRequest,
     ) -> Result<SendableRecordBatchStream>;
+
+    /// Returns the latest committed sequences of the regions, keyed by the region id.
+    /// Regions whose sequences are unavailable are absent.
+    async fn region_sequences(&self, _region_ids: &[RegionId]) -> Result<HashMap<u64, u64>> {
+        Ok(HashMap::new())
+    }
 }
 
 pub type RegionQueryHandlerRef = Arc<dyn RegionQueryHandler>;
@@ -18,8 +18,9 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use catalog::information_schema::{
-    columns, flows, key_column_usage, region_peers, schemata, tables, CHARACTER_SETS, COLLATIONS,
-    COLUMNS, FLOWS, KEY_COLUMN_USAGE, REGION_PEERS, SCHEMATA, TABLES, VIEWS,
+    columns, flows, key_column_usage, procedure_info, region_peers, schemata, tables,
+    CHARACTER_SETS, COLLATIONS, COLUMNS, FLOWS, KEY_COLUMN_USAGE, PROCEDURE_INFO, REGION_PEERS,
+    SCHEMATA, TABLES, VIEWS,
 };
 use catalog::CatalogManagerRef;
 use common_catalog::consts::{
@@ -50,15 +51,17 @@ use itertools::Itertools;
 use object_store::ObjectStore;
 use once_cell::sync::Lazy;
 use regex::Regex;
-use session::context::{Channel, QueryContextRef};
+use session::context::{Channel, ConnectionLimits, QueryContextRef};
 pub use show_create_table::create_table_stmt;
 use snafu::{ensure, OptionExt, ResultExt};
 use sql::ast::Ident;
 use sql::parser::ParserContext;
-use sql::statements::create::{CreateDatabase, CreateFlow, CreateView, Partitions};
+use sql::statements::create::{
+    validate_flow_option, CreateDatabase, CreateFlow, CreateView, Partitions,
+};
 use sql::statements::show::{
-    ShowColumns, ShowDatabases, ShowFlows, ShowIndex, ShowKind, ShowRegion, ShowTableStatus,
-    ShowTables, ShowVariables, ShowViews,
+    ShowColumns, ShowDatabases, ShowFlows, ShowIndex, ShowKind, ShowProcedures, ShowRegion,
+    ShowTableStatus, ShowTables, ShowVariables, ShowViews,
 };
 use sql::statements::statement::Statement;
 use sql::statements::OptionMap;
@@ -77,6 +80,12 @@ const OPTIONS_COLUMN: &str = "Options";
 const TABLES_COLUMN: &str = "Tables";
 const VIEWS_COLUMN: &str = "Views";
 const FLOWS_COLUMN: &str = "Flows";
+const PROCEDURE_ID_COLUMN: &str = "Id";
+const PROCEDURE_TYPE_COLUMN: &str = "Type";
+const PROCEDURE_STATUS_COLUMN: &str = "Status";
+const PROCEDURE_START_TIME_COLUMN: &str = "Start_time";
+const PROCEDURE_END_TIME_COLUMN: &str = "End_time";
+const PROCEDURE_PROGRESS_COLUMN: &str = "Progress";
 const FIELD_COLUMN: &str = "Field";
 const TABLE_TYPE_COLUMN: &str = "Table_type";
 const COLUMN_NAME_COLUMN: &str = "Column";
@@ -715,6 +724,11 @@ pub async fn show_charsets(
     .await
 }
 
+/// Returns the connection limits of the server that the session connects to.
+fn connection_limits(query_ctx: &QueryContextRef) -> &ConnectionLimits {
+    query_ctx.configuration_parameter().connection_limits()
+}
+
 pub fn show_variable(stmt: ShowVariables, query_ctx: QueryContextRef) -> Result<Output> {
     let variable = stmt.variable.to_string().to_uppercase();
     let value = match variable.as_str() {
@@ -742,6 +756,35 @@ pub fn show_variable(stmt: ShowVariables, query_ctx: QueryContextRef) -> Result<
                 return UnsupportedVariableSnafu { name: variable }.fail();
             }
         }
+        "MAX_CONNECTIONS" => connection_limits(&query_ctx).max_connections.to_string(),
+        "MAX_USER_CONNECTIONS" => connection_limits(&query_ctx)
+            .max_user_connections
+            .to_string(),
+        "MAX_PREPARED_STMT_COUNT" => connection_limits(&query_ctx)
+            .max_prepared_statements
+            .to_string(),
+        "WAIT_TIMEOUT" => {
+            if query_ctx.channel() == Channel::Mysql {
+                connection_limits(&query_ctx)
+                    .idle_timeout
+                    .as_secs()
+                    .to_string()
+            } else {
+                return UnsupportedVariableSnafu { name: variable }.fail();
+            }
+        }
+        "IDLE_SESSION_TIMEOUT" => {
+            if query_ctx.channel() == Channel::Postgres {
+                let mut timeout = connection_limits(&query_ctx)
+                    .idle_timeout
+                    .as_millis()
+                    .to_string();
+                timeout.push_str("ms");
+                timeout
+            } else {
+                return UnsupportedVariableSnafu { name: variable }.fail();
+            }
+        }
         _ => return UnsupportedVariableSnafu { name: variable }.fail(),
     };
     let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
@@ -974,6 +1017,42 @@ pub async fn show_flows(
     .await
 }
 
+/// Execute [`ShowProcedures`] statement and return the [`Output`] if success.
+pub async fn show_procedures(
+    stmt: ShowProcedures,
+    query_engine: &QueryEngineRef,
+    catalog_manager: &CatalogManagerRef,
+    query_ctx: QueryContextRef,
+) -> Result<Output> {
+    let projects = vec![
+        (procedure_info::PROCEDURE_ID, PROCEDURE_ID_COLUMN),
+        (procedure_info::PROCEDURE_TYPE, PROCEDURE_TYPE_COLUMN),
+        (procedure_info::STATUS, PROCEDURE_STATUS_COLUMN),
+        (procedure_info::START_TIME, PROCEDURE_START_TIME_COLUMN),
+        (procedure_info::END_TIME, PROCEDURE_END_TIME_COLUMN),
+        (procedure_info::PROGRESS, PROCEDURE_PROGRESS_COLUMN),
+    ];
+    let like_field = Some(procedure_info::PROCEDURE_TYPE);
+    let sort = vec![
+        col(procedure_info::START_TIME).sort(true, true),
+        col(procedure_info::PROCEDURE_ID).sort(true, true),
+    ];
+
+    query_from_information_schema_table(
+        query_engine,
+        catalog_manager,
+        query_ctx,
+        PROCEDURE_INFO,
+        vec![],
+        projects,
+        vec![],
+        like_field,
+        sort,
+        stmt.kind,
+    )
+    .await
+}
+
 pub fn show_create_flow(
     flow_name: ObjectName,
     flow_val: FlowInfoValue,
@@ -990,6 +1069,13 @@ pub fn show_create_flow(
         Some(flow_val.comment().clone())
     };
 
+    let flow_options = flow_val
+        .options()
+        .iter()
+        .filter(|(k, _)| validate_flow_option(k))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect::<HashMap<_, _>>();
+
     let stmt = CreateFlow {
         flow_name,
         sink_table_name: ObjectName(vec![Ident::new(&flow_val.sink_table_name().table_name)]),
@@ -999,6 +1085,7 @@ pub fn show_create_flow(
         if_not_exists: true,
         expire_after: flow_val.expire_after(),
         comment,
+        flow_options: flow_options.into(),
         query,
     };
 
@@ -1252,6 +1339,7 @@ fn parse_file_table_format(options: &HashMap<String, String>) -> Result<Box<dyn
 #[cfg(test)]
 mod test {
     use std::sync::Arc;
+    use std::time::Duration;
 
     use common_query::{Output, OutputData};
     use common_recordbatch::{RecordBatch, RecordBatches};
@@ -1260,7 +1348,9 @@ mod test {
     use datatypes::prelude::ConcreteDataType;
     use datatypes::schema::{ColumnDefaultConstraint, ColumnSchema, Schema, SchemaRef};
     use datatypes::vectors::{StringVector, TimestampMillisecondVector, UInt32Vector, VectorRef};
-    use session::context::QueryContextBuilder;
+    use session::context::{
+        Channel, ConfigurationVariables, ConnectionLimits, QueryContextBuilder,
+    };
     use snafu::ResultExt;
     use sql::ast::{Ident, ObjectName};
     use sql::statements::show::ShowVariables;
@@ -1363,6 +1453,53 @@ mod test {
         assert!(exec_show_variable("SYSTEM TIME ZONE", "Asia/Shanghai").is_err());
     }
 
+    #[test]
+    fn test_show_connection_limits() {
+        let limits = ConnectionLimits {
+            max_connections: 100,
+            max_user_connections: 10,
+            idle_timeout: Duration::from_secs(600),
+            max_prepared_statements: 256,
+        };
+        let show = |variable: &str, channel: Channel| {
+            let stmt = ShowVariables {
+                variable: ObjectName(vec![Ident::new(variable)]),
+            };
+            let ctx = Arc::new(
+                QueryContextBuilder::default()
+                    .configuration_parameter(Arc::new(
+                        ConfigurationVariables::new().with_connection_limits(limits),
+                    ))
+                    .channel(channel)
+                    .build(),
+            );
+            show_variable(stmt, ctx).map(|output| {
+                let OutputData::RecordBatches(record) = output.data else {
+                    unreachable!()
+                };
+                let record = record.take().first().cloned().unwrap();
+                record.column(0).get(0).to_string()
+            })
+        };
+
+        assert_eq!("100", show("MAX_CONNECTIONS", Channel::Mysql).unwrap());
+        assert_eq!(
+            "10",
+            show("MAX_USER_CONNECTIONS", Channel::Postgres).unwrap()
+        );
+        assert_eq!(
+            "256",
+            show("MAX_PREPARED_STMT_COUNT", Channel::Mysql).unwrap()
+        );
+        assert_eq!("600", show("WAIT_TIMEOUT", Channel::Mysql).unwrap());
+        assert_eq!(
+            "600000ms",
+            show("IDLE_SESSION_TIMEOUT", Channel::Postgres).unwrap()
+        );
+        assert!(show("WAIT_TIMEOUT", Channel::Postgres).is_err());
+        assert!(show("IDLE_SESSION_TIMEOUT", Channel::Mysql).is_err());
+    }
+
     fn exec_show_variable(variable: &str, tz: &str) -> Result<String> {
         let stmt = ShowVariables {
             variable: ObjectName(vec![Ident::new(variable)]),
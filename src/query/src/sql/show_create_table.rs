@@ -20,7 +20,7 @@ use common_meta::SchemaOptions;
 use datatypes::schema::{
     ColumnDefaultConstraint, ColumnSchema, SchemaRef, COLUMN_FULLTEXT_OPT_KEY_ANALYZER,
     COLUMN_FULLTEXT_OPT_KEY_CASE_SENSITIVE, COLUMN_SKIPPING_INDEX_OPT_KEY_GRANULARITY,
-    COLUMN_SKIPPING_INDEX_OPT_KEY_TYPE, COMMENT_KEY,
+    COLUMN_SKIPPING_INDEX_OPT_KEY_TYPE, COLUMN_SST_OPT_KEY_DICTIONARY, COMMENT_KEY,
 };
 use snafu::ResultExt;
 use sql::ast::{ColumnDef, ColumnOption, ColumnOptionDef, Expr, Ident, ObjectName};
@@ -95,6 +95,11 @@ fn create_column(column_schema: &ColumnSchema, quote_style: char) -> Result<Colu
         options.push(column_option_def(ColumnOption::Default(expr)));
     }
 
+    if let Some(func) = column_schema.on_update() {
+        let expr = ParserContext::parse_function(func, &GreptimeDbDialect {}).context(SqlSnafu)?;
+        options.push(column_option_def(ColumnOption::OnUpdate(expr)));
+    }
+
     if let Some(c) = column_schema.metadata().get(COMMENT_KEY) {
         options.push(column_option_def(ColumnOption::Comment(c.to_string())));
     }
@@ -138,6 +143,11 @@ fn create_column(column_schema: &ColumnSchema, quote_style: char) -> Result<Colu
         extensions.inverted_index_options = Some(HashMap::new().into());
     }
 
+    if !column_schema.is_sst_dictionary_enabled() {
+        let map = HashMap::from([(COLUMN_SST_OPT_KEY_DICTIONARY.to_string(), "off".to_string())]);
+        extensions.sst_options = Some(map.into());
+    }
+
     Ok(Column {
         column_def: ColumnDef {
             name: Ident::with_quote(quote_style, name),
@@ -264,6 +274,16 @@ mod tests {
                     ..Default::default()
                 })
                 .unwrap(),
+            ColumnSchema::new(
+                "updated_at",
+                ConcreteDataType::timestamp_datatype(TimeUnit::Millisecond),
+                true,
+            )
+            .with_default_constraint(Some(ColumnDefaultConstraint::Function(String::from(
+                "now()",
+            ))))
+            .unwrap()
+            .with_on_update(Some("now()".to_string())),
             ColumnSchema::new(
                 "ts",
                 ConcreteDataType::timestamp_datatype(TimeUnit::Millisecond),
@@ -328,6 +348,7 @@ CREATE TABLE IF NOT EXISTS "system_metrics" (
   "cpu" DOUBLE NULL,
   "disk" FLOAT NULL,
   "msg" STRING NULL FULLTEXT INDEX WITH(analyzer = 'English', case_sensitive = 'false'),
+  "updated_at" TIMESTAMP(3) NULL DEFAULT now() ON UPDATE now(),
   "ts" TIMESTAMP(3) NOT NULL DEFAULT current_timestamp(),
   TIME INDEX ("ts"),
   PRIMARY KEY ("id", "host")
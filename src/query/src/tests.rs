@@ -22,6 +22,7 @@ use crate::parser::QueryLanguageParser;
 use crate::{QueryEngineFactory, QueryEngineRef};
 
 mod my_sum_udaf_example;
+mod promql_conformance;
 mod query_engine_test;
 mod time_range_filter_test;
 
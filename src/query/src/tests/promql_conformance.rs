@@ -0,0 +1,379 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conformance tests running the `load`/`eval` scripts of Prometheus'
+//! `promql/testdata` through our planner and engine.
+//!
+//! The scripts under `testdata` are always run. Set `PROMQL_TESTDATA_DIR` to
+//! a checkout of the upstream scripts to run them as well, which is expected to
+//! report failures until the gaps are closed or skipped.
+//!
+//! Results are compared sample by sample with the same tolerance as Prometheus.
+//! Metric names aren't compared as our results don't carry them, and empty
+//! labels are the same as missing ones. Evals relying on unsupported features
+//! are skipped and listed in the report, along with [SKIPPED_EVALS].
+
+mod fixture;
+mod script;
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use common_query::OutputData;
+use common_recordbatch::{util, RecordBatch};
+use datatypes::arrow::array::{Array, AsArray};
+use datatypes::arrow::compute::cast;
+use datatypes::arrow::datatypes::{DataType, Float64Type, Int64Type, TimeUnit};
+use session::context::QueryContext;
+
+use self::fixture::Fixture;
+use self::script::{Command, Eval, EvalRange, Expectation, Labels, Series, METRIC_NAME};
+use crate::parser::{PromQuery, QueryLanguageParser};
+use crate::QueryEngineFactory;
+
+/// Evals known to deviate from Prometheus, as `(script, query, reason)`.
+const SKIPPED_EVALS: &[(&str, &str, &str)] = &[(
+    "selectors.test",
+    "nonexistent_metric",
+    "selecting a metric without a table fails instead of returning an empty result",
+)];
+
+/// Relative tolerance of float comparison, same as Prometheus.
+const EPSILON: f64 = 1e-6;
+
+/// Lookback delta of the evals, same as Prometheus' default.
+const LOOKBACK: &str = "5m";
+
+/// The outcome of running scripts.
+#[derive(Default)]
+struct Report {
+    passed: usize,
+    failed: Vec<String>,
+    skipped: Vec<String>,
+}
+
+impl Report {
+    fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} passed, {} failed, {} skipped",
+            self.passed,
+            self.failed.len(),
+            self.skipped.len()
+        );
+        for skipped in &self.skipped {
+            let _ = write!(summary, "\n  skipped {skipped}");
+        }
+        for failed in &self.failed {
+            let _ = write!(summary, "\n  failed {failed}");
+        }
+        summary
+    }
+}
+
+async fn run_script(name: &str, script: &str, report: &mut Report) {
+    let commands = match script::parse_script(script) {
+        Ok(commands) => commands,
+        Err(e) => {
+            report.failed.push(format!("{name}: {e}"));
+            return;
+        }
+    };
+
+    let mut fixture = Fixture::default();
+    for command in commands {
+        match command {
+            Command::Clear => fixture = Fixture::default(),
+            Command::Load(load) => fixture.load(&load),
+            Command::Unsupported { line, reason } => {
+                report.skipped.push(format!("{name}:{line}: {reason}"));
+            }
+            Command::Eval(eval) => {
+                let location = format!("{name}:{} `{}`", eval.line, eval.query);
+                if let Some(reason) = &fixture.unsupported {
+                    report
+                        .skipped
+                        .push(format!("{location}: loaded data has {reason}"));
+                    continue;
+                }
+                if let Some((_, _, reason)) = SKIPPED_EVALS
+                    .iter()
+                    .find(|(script, query, _)| *script == name && *query == eval.query)
+                {
+                    report.skipped.push(format!("{location}: {reason}"));
+                    continue;
+                }
+                match check_eval(&fixture, &eval).await {
+                    Ok(()) => report.passed += 1,
+                    Err(e) => report.failed.push(format!("{location}: {e}")),
+                }
+            }
+        }
+    }
+}
+
+async fn check_eval(fixture: &Fixture, eval: &Eval) -> Result<(), String> {
+    let result = evaluate(fixture, eval).await;
+    match (&eval.expectation, result) {
+        (Expectation::Fail, Ok(_)) => Err("expect failure but succeeded".to_string()),
+        (Expectation::Fail, Err(_)) => Ok(()),
+        (Expectation::Series { .. }, Err(e)) => Err(e),
+        (Expectation::Series { ordered, series }, Ok(actual)) => {
+            compare(eval.range, *ordered, series, actual)
+        }
+    }
+}
+
+/// A series in the result, with labels that are neither empty nor the metric name.
+struct ActualSeries {
+    labels: Labels,
+    samples: BTreeMap<i64, f64>,
+}
+
+/// Runs the eval like the Prometheus HTTP API, and collects the result by series
+/// in the order of their first appearance.
+async fn evaluate(fixture: &Fixture, eval: &Eval) -> Result<Vec<ActualSeries>, String> {
+    let (start_ms, end_ms, step_ms) = match eval.range {
+        EvalRange::Instant { at_ms } => (at_ms, at_ms, 1000),
+        EvalRange::Range {
+            start_ms,
+            end_ms,
+            step_ms,
+        } => (start_ms, end_ms, step_ms),
+    };
+    let query = PromQuery {
+        query: eval.query.clone(),
+        start: format!("{}", start_ms as f64 / 1000.0),
+        end: format!("{}", end_ms as f64 / 1000.0),
+        step: format!("{step_ms}ms"),
+        lookback: LOOKBACK.to_string(),
+    };
+
+    let query_ctx = QueryContext::arc();
+    let engine = QueryEngineFactory::new(fixture.catalog_manager(), None, None, None, None, false)
+        .query_engine();
+    let stmt = QueryLanguageParser::parse_promql(&query, &query_ctx).map_err(|e| e.to_string())?;
+    let plan = engine
+        .planner()
+        .plan(&stmt, query_ctx.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+    let output = engine
+        .execute(plan, query_ctx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let batches = match output.data {
+        OutputData::Stream(stream) => util::collect(stream).await.map_err(|e| e.to_string())?,
+        OutputData::RecordBatches(batches) => batches.take(),
+        OutputData::AffectedRows(_) => return Err("unexpected affected rows".to_string()),
+    };
+
+    let mut result: Vec<ActualSeries> = Vec::new();
+    for batch in batches {
+        for (labels, timestamp, value) in batch_samples(&batch)? {
+            match result.iter_mut().find(|series| series.labels == labels) {
+                Some(series) => {
+                    let _ = series.samples.insert(timestamp, value);
+                }
+                None => result.push(ActualSeries {
+                    labels,
+                    samples: BTreeMap::from([(timestamp, value)]),
+                }),
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Extracts `(labels, timestamp, value)` of each row. String columns are labels,
+/// the timestamp column is the time and the only other column is the value.
+fn batch_samples(batch: &RecordBatch) -> Result<Vec<(Labels, i64, f64)>, String> {
+    let batch = batch.df_record_batch();
+    let schema = batch.schema();
+    let mut label_columns = Vec::new();
+    let mut timestamps = None;
+    let mut values = None;
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        let cast_to = |data_type: &DataType| cast(column, data_type).map_err(|e| e.to_string());
+        match field.data_type() {
+            DataType::Utf8
+            | DataType::LargeUtf8
+            | DataType::Utf8View
+            | DataType::Dictionary(..) => {
+                label_columns.push((field.name().clone(), cast_to(&DataType::Utf8)?));
+            }
+            DataType::Timestamp(..) if timestamps.is_none() => {
+                let column = cast_to(&DataType::Timestamp(TimeUnit::Millisecond, None))?;
+                timestamps = Some(cast(&column, &DataType::Int64).map_err(|e| e.to_string())?);
+            }
+            _ if values.is_none() => values = Some(cast_to(&DataType::Float64)?),
+            data_type => {
+                return Err(format!(
+                    "unexpected column {}: {data_type} in result",
+                    field.name()
+                ))
+            }
+        }
+    }
+    let (Some(timestamps), Some(values)) = (timestamps, values) else {
+        return Err(format!("result has no timestamp or value column: {schema}"));
+    };
+    let timestamps = timestamps.as_primitive::<Int64Type>();
+    let values = values.as_primitive::<Float64Type>();
+
+    let mut samples = Vec::with_capacity(batch.num_rows());
+    for row in 0..batch.num_rows() {
+        if values.is_null(row) {
+            continue;
+        }
+        let labels = label_columns
+            .iter()
+            .filter(|(_, column)| column.is_valid(row))
+            .map(|(name, column)| (name.clone(), column.as_string::<i32>().value(row)))
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(name, value)| (name, value.to_string()))
+            .collect();
+        samples.push((labels, timestamps.value(row), values.value(row)));
+    }
+    Ok(samples)
+}
+
+fn compare(
+    range: EvalRange,
+    ordered: bool,
+    expected: &[Series],
+    actual: Vec<ActualSeries>,
+) -> Result<(), String> {
+    let (start_ms, step_ms) = match range {
+        EvalRange::Instant { at_ms } => (at_ms, 0),
+        EvalRange::Range {
+            start_ms, step_ms, ..
+        } => (start_ms, step_ms),
+    };
+
+    let mut unmatched = actual;
+    for (i, expected) in expected.iter().enumerate() {
+        let labels = expected
+            .labels
+            .iter()
+            .filter(|(name, value)| *name != METRIC_NAME && !value.is_empty())
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Labels>();
+        let Some(pos) = unmatched.iter().position(|series| series.labels == labels) else {
+            return Err(format!("missing series {labels:?}"));
+        };
+        if ordered && pos != 0 {
+            return Err(format!("series {labels:?} is expected at position {i}"));
+        }
+        let actual = unmatched.remove(pos);
+
+        let expected_samples = expected
+            .values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| value.map(|value| (start_ms + i as i64 * step_ms, value)))
+            .collect::<BTreeMap<_, _>>();
+        let matches = expected_samples.len() == actual.samples.len()
+            && expected_samples
+                .iter()
+                .zip(actual.samples.iter())
+                .all(|((t1, v1), (t2, v2))| t1 == t2 && almost_equal(*v1, *v2));
+        if !matches {
+            return Err(format!(
+                "series {labels:?} expects {expected_samples:?} but got {:?}",
+                actual.samples
+            ));
+        }
+    }
+    if let Some(series) = unmatched.first() {
+        return Err(format!(
+            "unexpected series {:?} with {:?}",
+            series.labels, series.samples
+        ));
+    }
+    Ok(())
+}
+
+/// Same as `almost.Equal` of Prometheus.
+fn almost_equal(a: f64, b: f64) -> bool {
+    if a.is_nan() && b.is_nan() {
+        return true;
+    }
+    if a == b {
+        return true;
+    }
+    let abs_sum = a.abs() + b.abs();
+    let diff = (a - b).abs();
+    if a == 0.0 || b == 0.0 || abs_sum < f64::MIN_POSITIVE {
+        return diff < EPSILON * f64::MIN_POSITIVE;
+    }
+    diff / abs_sum.min(f64::MAX) < EPSILON
+}
+
+async fn run_dir(dir: &Path, report: &mut Report) {
+    let mut paths = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "test"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    for path in paths {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let script = std::fs::read_to_string(&path).unwrap();
+        run_script(&name, &script, report).await;
+    }
+}
+
+#[tokio::test]
+async fn test_promql_conformance() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/promql_conformance/testdata");
+    let mut report = Report::default();
+    run_dir(&dir, &mut report).await;
+
+    assert!(report.failed.is_empty(), "{}", report.summary());
+    // Keeps the skip list in sync with the scripts.
+    for (script, query, _) in SKIPPED_EVALS {
+        assert!(
+            report
+                .skipped
+                .iter()
+                .any(|skipped| skipped.starts_with(&format!("{script}:"))
+                    && skipped.contains(&format!("`{query}`"))),
+            "{script}: `{query}` is skipped but not found"
+        );
+    }
+}
+
+/// Runs the upstream scripts in `PROMQL_TESTDATA_DIR` if set. It only reports the
+/// result, as some upstream cases are expected to fail.
+#[tokio::test]
+async fn test_upstream_promql_conformance() {
+    let Ok(dir) = std::env::var("PROMQL_TESTDATA_DIR") else {
+        return;
+    };
+    common_telemetry::init_default_ut_logging();
+    let mut report = Report::default();
+    run_dir(Path::new(&dir), &mut report).await;
+    common_telemetry::info!("{}", report.summary());
+}
+
+#[test]
+fn test_almost_equal() {
+    assert!(almost_equal(f64::NAN, f64::NAN));
+    assert!(almost_equal(f64::INFINITY, f64::INFINITY));
+    assert!(!almost_equal(f64::INFINITY, f64::NEG_INFINITY));
+    assert!(almost_equal(1.0, 1.0 + 1e-9));
+    assert!(!almost_equal(1.0, 1.0 + 1e-5));
+    assert!(!almost_equal(0.0, 1e-300));
+}
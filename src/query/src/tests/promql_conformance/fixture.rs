@@ -0,0 +1,138 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Loads the series of test scripts into in-memory tables, one table per metric.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use catalog::memory::MemoryCatalogManager;
+use catalog::RegisterTableRequest;
+use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_recordbatch::RecordBatch;
+use datatypes::prelude::{ConcreteDataType, VectorRef};
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::{Float64Vector, StringVector, TimestampMillisecondVector};
+use table::test_util::MemTable;
+
+use super::script::{Labels, Load, METRIC_NAME};
+
+/// Name of the time index column of the tables.
+pub const TIMESTAMP_COLUMN: &str = "greptime_timestamp";
+/// Name of the field column of the tables.
+pub const VALUE_COLUMN: &str = "greptime_value";
+
+/// Samples of a series, keyed by their timestamps in milliseconds.
+type Samples = BTreeMap<i64, f64>;
+
+/// The series loaded since the last `clear`.
+#[derive(Default)]
+pub struct Fixture {
+    series: BTreeMap<Labels, Samples>,
+    /// Why some loaded series are missing, which makes evals unreliable.
+    pub unsupported: Option<String>,
+}
+
+impl Fixture {
+    pub fn load(&mut self, load: &Load) {
+        if let Some(reason) = &load.unsupported {
+            self.unsupported = Some(reason.clone());
+        }
+        for series in &load.series {
+            let samples = self.series.entry(series.labels.clone()).or_default();
+            for (i, value) in series.values.iter().enumerate() {
+                if let Some(value) = value {
+                    let _ = samples.insert(i as i64 * load.interval_ms, *value);
+                }
+            }
+        }
+    }
+
+    /// Builds a catalog with a table for each metric. The labels of all series of
+    /// a metric are tags, where series without a label have an empty value like
+    /// Prometheus.
+    pub fn catalog_manager(&self) -> Arc<MemoryCatalogManager> {
+        let mut metrics: BTreeMap<&str, Vec<(&Labels, &Samples)>> = BTreeMap::new();
+        for (labels, samples) in &self.series {
+            let name = labels.get(METRIC_NAME).map(String::as_str).unwrap_or("");
+            metrics.entry(name).or_default().push((labels, samples));
+        }
+
+        let catalog_manager = MemoryCatalogManager::with_default_setup();
+        for (table_id, (name, series)) in (1024..).zip(metrics) {
+            let tags = series
+                .iter()
+                .flat_map(|(labels, _)| labels.keys())
+                .filter(|label| *label != METRIC_NAME)
+                .collect::<BTreeSet<_>>();
+
+            let mut tag_values = vec![Vec::new(); tags.len()];
+            let mut timestamps = Vec::new();
+            let mut values = Vec::new();
+            for (labels, samples) in series {
+                for (timestamp, value) in samples {
+                    for (tag, column) in tags.iter().zip(tag_values.iter_mut()) {
+                        column.push(labels.get(*tag).cloned().unwrap_or_default());
+                    }
+                    timestamps.push(*timestamp);
+                    values.push(*value);
+                }
+            }
+
+            let mut column_schemas = tags
+                .iter()
+                .map(|tag| ColumnSchema::new(*tag, ConcreteDataType::string_datatype(), false))
+                .collect::<Vec<_>>();
+            column_schemas.push(
+                ColumnSchema::new(
+                    TIMESTAMP_COLUMN,
+                    ConcreteDataType::timestamp_millisecond_datatype(),
+                    false,
+                )
+                .with_time_index(true),
+            );
+            column_schemas.push(ColumnSchema::new(
+                VALUE_COLUMN,
+                ConcreteDataType::float64_datatype(),
+                true,
+            ));
+
+            let mut columns = tag_values
+                .into_iter()
+                .map(|column| Arc::new(StringVector::from(column)) as VectorRef)
+                .collect::<Vec<_>>();
+            columns.push(Arc::new(TimestampMillisecondVector::from_vec(timestamps)));
+            columns.push(Arc::new(Float64Vector::from_vec(values)));
+
+            let recordbatch =
+                RecordBatch::new(Arc::new(Schema::new(column_schemas)), columns).unwrap();
+            let table = MemTable::table_with_primary_keys(
+                name,
+                recordbatch,
+                table_id,
+                (0..tags.len()).collect(),
+            );
+            assert!(catalog_manager
+                .register_table_sync(RegisterTableRequest {
+                    catalog: DEFAULT_CATALOG_NAME.to_string(),
+                    schema: DEFAULT_SCHEMA_NAME.to_string(),
+                    table_name: name.to_string(),
+                    table_id,
+                    table,
+                })
+                .unwrap());
+        }
+        catalog_manager
+    }
+}
@@ -0,0 +1,526 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser of the `load`/`eval` scripts of Prometheus' `promql/testdata`.
+
+use std::collections::BTreeMap;
+
+use promql_parser::label::MatchOp;
+use promql_parser::parser::{self, Expr};
+
+/// Label name of the metric name.
+pub const METRIC_NAME: &str = "__name__";
+
+/// Labels of a series, including the metric name.
+pub type Labels = BTreeMap<String, String>;
+
+/// A command of a test script.
+#[derive(Debug)]
+pub enum Command {
+    /// Drops all the loaded series.
+    Clear,
+    Load(Load),
+    Eval(Eval),
+    /// A command the harness doesn't support. It's reported as skipped.
+    Unsupported {
+        line: usize,
+        reason: String,
+    },
+}
+
+/// `load <interval>` followed by series, whose samples are `interval` apart from 0.
+#[derive(Debug)]
+pub struct Load {
+    pub line: usize,
+    pub interval_ms: i64,
+    pub series: Vec<Series>,
+    /// Why the series can't be loaded, e.g. stale markers. Evals depending on
+    /// them are skipped.
+    pub unsupported: Option<String>,
+}
+
+/// A series with one value per step, `None` if the step has no sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub labels: Labels,
+    pub values: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvalRange {
+    Instant {
+        at_ms: i64,
+    },
+    Range {
+        start_ms: i64,
+        end_ms: i64,
+        step_ms: i64,
+    },
+}
+
+#[derive(Debug)]
+pub enum Expectation {
+    /// The query fails to parse or evaluate.
+    Fail,
+    /// The series of the result. `ordered` checks the order of instant results.
+    Series { ordered: bool, series: Vec<Series> },
+}
+
+/// `eval instant at <time> <query>` or
+/// `eval range from <start> to <end> step <step> <query>`, followed by the
+/// expected result.
+#[derive(Debug)]
+pub struct Eval {
+    pub line: usize,
+    pub query: String,
+    pub range: EvalRange,
+    pub expectation: Expectation,
+}
+
+/// Parses a test script into commands. Errors are reported with the line number.
+pub fn parse_script(script: &str) -> Result<Vec<Command>, String> {
+    let lines = script.lines().collect::<Vec<_>>();
+    let mut commands = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        let line_no = i + 1;
+        i += 1;
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // The indented lines following a command belong to it.
+        let body_start = i;
+        while i < lines.len() && lines[i].starts_with([' ', '\t']) && !lines[i].trim().is_empty() {
+            i += 1;
+        }
+        let body = lines[body_start..i]
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.starts_with('#'))
+            .collect::<Vec<_>>();
+
+        let (command, rest) = next_word(line);
+        let command = match command {
+            "clear" => Command::Clear,
+            "load" => parse_load(line_no, rest, &body)?,
+            "eval" | "eval_ordered" | "eval_fail" | "eval_warn" | "eval_info" => {
+                parse_eval(line_no, command, rest, &body)?
+            }
+            _ => Command::Unsupported {
+                line: line_no,
+                reason: format!("unsupported command `{command}`"),
+            },
+        };
+        commands.push(command);
+    }
+    Ok(commands)
+}
+
+fn parse_load(line: usize, rest: &str, body: &[&str]) -> Result<Command, String> {
+    let interval_ms =
+        parse_duration_ms(rest).map_err(|e| format!("line {line}: invalid load interval: {e}"))?;
+    let mut series = Vec::with_capacity(body.len());
+    let mut unsupported = None;
+    for (offset, def) in body.iter().enumerate() {
+        let def_line = line + offset + 1;
+        if def.contains("{{") {
+            unsupported = Some("native histogram samples".to_string());
+            continue;
+        }
+        let (labels, values) = split_series(def);
+        let labels =
+            parse_labels(labels).map_err(|e| format!("line {def_line}: invalid series: {e}"))?;
+        if values.split_whitespace().any(|v| v == "stale") {
+            unsupported = Some("stale markers".to_string());
+        }
+        let values = parse_values(values, true)
+            .map_err(|e| format!("line {def_line}: invalid values: {e}"))?;
+        series.push(Series { labels, values });
+    }
+    Ok(Command::Load(Load {
+        line,
+        interval_ms,
+        series,
+        unsupported,
+    }))
+}
+
+fn parse_eval(line: usize, command: &str, rest: &str, body: &[&str]) -> Result<Command, String> {
+    let (kind, rest) = next_word(rest);
+    let (range, query) = match kind {
+        "instant" => {
+            let (at, rest) = next_word(rest);
+            if at != "at" {
+                return Err(format!("line {line}: expect `at` in instant eval"));
+            }
+            let (time, query) = next_word(rest);
+            let at_ms = parse_duration_ms(time).map_err(|e| format!("line {line}: {e}"))?;
+            (EvalRange::Instant { at_ms }, query)
+        }
+        "range" => {
+            let mut times = [0; 3];
+            let mut rest = rest;
+            for (keyword, time) in ["from", "to", "step"].into_iter().zip(times.iter_mut()) {
+                let (word, remaining) = next_word(rest);
+                if word != keyword {
+                    return Err(format!("line {line}: expect `{keyword}` in range eval"));
+                }
+                let (value, remaining) = next_word(remaining);
+                *time = parse_duration_ms(value).map_err(|e| format!("line {line}: {e}"))?;
+                rest = remaining;
+            }
+            let [start_ms, end_ms, step_ms] = times;
+            (
+                EvalRange::Range {
+                    start_ms,
+                    end_ms,
+                    step_ms,
+                },
+                rest,
+            )
+        }
+        _ => {
+            return Ok(Command::Unsupported {
+                line,
+                reason: format!("unsupported eval kind `{kind}`"),
+            })
+        }
+    };
+
+    let expectation = if command == "eval_fail" {
+        // Expected error messages aren't compared as ours differ.
+        Expectation::Fail
+    } else {
+        if let Some(expect) = body.iter().find(|l| l.starts_with("expect ")) {
+            return Ok(Command::Unsupported {
+                line,
+                reason: format!("unsupported expectation `{expect}`"),
+            });
+        }
+        if body.iter().any(|l| l.contains("{{")) {
+            return Ok(Command::Unsupported {
+                line,
+                reason: "native histogram results".to_string(),
+            });
+        }
+        let series = body
+            .iter()
+            .enumerate()
+            .map(|(offset, def)| {
+                parse_expected(def).map_err(|e| format!("line {}: {e}", line + offset + 1))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Expectation::Series {
+            ordered: command == "eval_ordered",
+            series,
+        }
+    };
+
+    Ok(Command::Eval(Eval {
+        line,
+        query: query.to_string(),
+        range,
+        expectation,
+    }))
+}
+
+/// Parses an expected series, or a scalar which has no labels.
+fn parse_expected(def: &str) -> Result<Series, String> {
+    if !def.contains(['{', ' ', '\t']) {
+        return Ok(Series {
+            labels: Labels::new(),
+            values: vec![Some(parse_number(def)?)],
+        });
+    }
+    let (labels, values) = split_series(def);
+    Ok(Series {
+        labels: parse_labels(labels)?,
+        values: parse_values(values, false)?,
+    })
+}
+
+/// Splits a series definition into the series descriptor and the values. The
+/// descriptor ends at the closing brace of the labels, or at the first space if
+/// there are no labels.
+fn split_series(def: &str) -> (&str, &str) {
+    let Some(open) = def.find('{') else {
+        let (labels, values) = next_word(def);
+        return (labels, values);
+    };
+    if def[..open].contains(char::is_whitespace) {
+        let (labels, values) = next_word(def);
+        return (labels, values);
+    }
+
+    let mut quote = None;
+    let mut chars = def[open..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                let _ = chars.next();
+            }
+            (Some(q), c) if q == c => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '}') => {
+                let end = open + i + 1;
+                return (&def[..end], def[end..].trim_start());
+            }
+            _ => {}
+        }
+    }
+    (def, "")
+}
+
+/// Parses a series descriptor like `metric{label="value"}` into labels.
+fn parse_labels(descriptor: &str) -> Result<Labels, String> {
+    if descriptor == "{}" {
+        return Ok(Labels::new());
+    }
+    let Expr::VectorSelector(selector) = parser::parse(descriptor)? else {
+        return Err(format!("`{descriptor}` is not a series descriptor"));
+    };
+    let mut labels = Labels::new();
+    if let Some(name) = selector.name {
+        let _ = labels.insert(METRIC_NAME.to_string(), name);
+    }
+    for matcher in selector.matchers.matchers {
+        if !matches!(matcher.op, MatchOp::Equal) {
+            return Err(format!("`{descriptor}` has a non-equal matcher"));
+        }
+        let _ = labels.insert(matcher.name, matcher.value);
+    }
+    Ok(labels)
+}
+
+/// Expands the values of a series, e.g. `1 _x2 0+10x3` into
+/// `1, _, _, 0, 10, 20, 30`. `stale` markers are only allowed in `load`, where
+/// they are treated as missing samples.
+fn parse_values(values: &str, allow_stale: bool) -> Result<Vec<Option<f64>>, String> {
+    let mut result = Vec::new();
+    for item in values.split_whitespace() {
+        if item == "_" {
+            result.push(None);
+            continue;
+        }
+        if item == "stale" && allow_stale {
+            result.push(None);
+            continue;
+        }
+        if let Some(times) = item.strip_prefix("_x") {
+            let times = parse_times(times)?;
+            result.extend(std::iter::repeat_n(None, times));
+            continue;
+        }
+        let Some((start_delta, times)) = item.rsplit_once('x') else {
+            result.push(Some(parse_number(item)?));
+            continue;
+        };
+        let times = parse_times(times)?;
+        // The sign of the delta is the last `+` or `-` that isn't part of
+        // the start value or an exponent.
+        let split = start_delta
+            .char_indices()
+            .skip(1)
+            .filter(|(i, c)| matches!(c, '+' | '-') && !start_delta[..*i].ends_with(['e', 'E']))
+            .map(|(i, _)| i)
+            .last();
+        let (start, delta) = match split {
+            Some(i) => (
+                parse_number(&start_delta[..i])?,
+                parse_number(&start_delta[i..])?,
+            ),
+            // `<value>x<times>` repeats the value.
+            None => (parse_number(start_delta)?, 0.0),
+        };
+        result.extend((0..=times).map(|i| Some(start + delta * i as f64)));
+    }
+    Ok(result)
+}
+
+fn parse_times(times: &str) -> Result<usize, String> {
+    times
+        .parse::<usize>()
+        .map_err(|e| format!("invalid repetition `{times}`: {e}"))
+}
+
+fn parse_number(value: &str) -> Result<f64, String> {
+    match value.trim_start_matches('+').to_lowercase().as_str() {
+        "inf" => Ok(f64::INFINITY),
+        "-inf" => Ok(f64::NEG_INFINITY),
+        "nan" => Ok(f64::NAN),
+        _ => value
+            .parse::<f64>()
+            .map_err(|e| format!("invalid number `{value}`: {e}")),
+    }
+}
+
+/// Parses a Prometheus duration like `1h5m` into milliseconds. Plain numbers are seconds.
+fn parse_duration_ms(duration: &str) -> Result<i64, String> {
+    let duration = duration.trim();
+    if let Ok(secs) = duration.parse::<i64>() {
+        return Ok(secs * 1000);
+    }
+    promql_parser::util::parse_duration(duration).map(|d| d.as_millis() as i64)
+}
+
+/// Returns the first word and the rest of `s` with leading spaces trimmed.
+fn next_word(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    match s.find(char::is_whitespace) {
+        Some(i) => (&s[..i], s[i..].trim_start()),
+        None => (s, ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(pairs: &[(&str, &str)]) -> Labels {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(
+            vec![
+                Some(1.0),
+                None,
+                None,
+                Some(0.0),
+                Some(10.0),
+                Some(20.0),
+                Some(-2.0),
+                Some(-3.0),
+                Some(5.0),
+                Some(5.0),
+                None,
+            ],
+            parse_values("1 _x2 0+10x2 -2-1x1 5x1 stale", true).unwrap()
+        );
+        assert_eq!(
+            vec![Some(1e3), Some(1e3 + 1e-2)],
+            parse_values("1e3+1e-2x1", false).unwrap()
+        );
+        assert!(parse_values("Inf -Inf NaN", false).unwrap()[2]
+            .unwrap()
+            .is_nan());
+        assert!(parse_values("foo", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let script = r#"
+# comment
+load 5m
+  http_requests{job="api", path="/{id}"}	0+10x2
+  up 1 _ 1
+
+eval instant at 10m sum by (job) (http_requests)
+  {job="api"} 20
+
+eval_ordered instant at 1h up
+  up 1
+
+eval range from 0 to 10m step 5m up
+  up 1 _ 1
+
+eval_fail instant at 0 rate(up)
+
+eval instant at 0 scalar(up)
+  1
+
+clear
+load 1m
+  metric {{schema:0 sum:1 count:1}}
+
+foo bar
+"#;
+        let commands = parse_script(script).unwrap();
+        assert_eq!(9, commands.len());
+
+        let Command::Load(load) = &commands[0] else {
+            panic!("{:?}", commands[0]);
+        };
+        assert_eq!(300_000, load.interval_ms);
+        assert_eq!(
+            Series {
+                labels: labels(&[
+                    ("__name__", "http_requests"),
+                    ("job", "api"),
+                    ("path", "/{id}")
+                ]),
+                values: vec![Some(0.0), Some(10.0), Some(20.0)],
+            },
+            load.series[0]
+        );
+        assert!(load.unsupported.is_none());
+
+        let Command::Eval(eval) = &commands[1] else {
+            panic!("{:?}", commands[1]);
+        };
+        assert_eq!("sum by (job) (http_requests)", eval.query);
+        assert_eq!(EvalRange::Instant { at_ms: 600_000 }, eval.range);
+        assert!(matches!(
+            &eval.expectation,
+            Expectation::Series { ordered: false, series } if series[0].labels == labels(&[("job", "api")])
+        ));
+
+        let Command::Eval(eval) = &commands[2] else {
+            panic!("{:?}", commands[2]);
+        };
+        assert!(matches!(
+            eval.expectation,
+            Expectation::Series { ordered: true, .. }
+        ));
+
+        let Command::Eval(eval) = &commands[3] else {
+            panic!("{:?}", commands[3]);
+        };
+        assert_eq!(
+            EvalRange::Range {
+                start_ms: 0,
+                end_ms: 600_000,
+                step_ms: 300_000
+            },
+            eval.range
+        );
+
+        let Command::Eval(eval) = &commands[4] else {
+            panic!("{:?}", commands[4]);
+        };
+        assert!(matches!(eval.expectation, Expectation::Fail));
+
+        let Command::Eval(eval) = &commands[5] else {
+            panic!("{:?}", commands[5]);
+        };
+        assert!(matches!(
+            &eval.expectation,
+            Expectation::Series { series, .. } if series[0].labels.is_empty()
+        ));
+
+        assert!(matches!(commands[6], Command::Clear));
+        let Command::Load(load) = &commands[7] else {
+            panic!("{:?}", commands[7]);
+        };
+        assert!(load.unsupported.is_some());
+        assert!(matches!(commands[8], Command::Unsupported { .. }));
+    }
+}
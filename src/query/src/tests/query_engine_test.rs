@@ -120,6 +120,7 @@ async fn test_query_validate() -> Result<()> {
     let plugins = Plugins::new();
     plugins.insert(QueryOptions {
         disallow_cross_catalog_query: true,
+        ..Default::default()
     });
 
     let factory =
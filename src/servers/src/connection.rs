@@ -0,0 +1,451 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Connection limits of the MySQL and Postgres servers.
+//!
+//! The [ConnectionManager] of a server tracks its live connections. It rejects new
+//! connections over the limits and closes the connections that have been idle for
+//! longer than the idle timeout by aborting their tasks, which drops the sockets.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use common_telemetry::{debug, info};
+use futures::future::AbortHandle;
+use parking_lot::Mutex;
+use prometheus::IntGauge;
+use serde::{Deserialize, Serialize};
+use session::context::ConnectionLimits;
+
+use crate::error::{Result, TooManyConnectionsSnafu, TooManyUserConnectionsSnafu};
+use crate::metrics::{METRIC_IDLE_CLOSED_CONNECTIONS, METRIC_REJECTED_CONNECTIONS};
+
+/// Default max number of prepared statements of a session.
+const DEFAULT_MAX_PREPARED_STATEMENTS: usize = 1024;
+/// Max interval to check idle connections.
+const MAX_REAP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Options of the connections of a server. Zero means unlimited.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ConnectionOptions {
+    /// Max number of connections of the server.
+    pub max_connections: usize,
+    /// Max number of connections of a user.
+    pub max_user_connections: usize,
+    /// Connections that don't run any query for longer than this are closed.
+    #[serde(with = "humantime_serde")]
+    pub idle_timeout: Duration,
+    /// Max number of prepared statements of a MySQL session. The least recently
+    /// used statements are evicted when a session prepares more statements.
+    pub max_prepared_statements: usize,
+}
+
+impl Default for ConnectionOptions {
+    fn default() -> Self {
+        Self {
+            max_connections: 0,
+            max_user_connections: 0,
+            idle_timeout: Duration::ZERO,
+            max_prepared_statements: DEFAULT_MAX_PREPARED_STATEMENTS,
+        }
+    }
+}
+
+impl From<&ConnectionOptions> for ConnectionLimits {
+    fn from(options: &ConnectionOptions) -> Self {
+        ConnectionLimits {
+            max_connections: options.max_connections,
+            max_user_connections: options.max_user_connections,
+            idle_timeout: options.idle_timeout,
+            max_prepared_statements: options.max_prepared_statements,
+        }
+    }
+}
+
+pub type ConnectionManagerRef = Arc<ConnectionManager>;
+
+/// Tracks the connections of a server.
+pub struct ConnectionManager {
+    protocol: &'static str,
+    options: ConnectionOptions,
+    /// Gauge of the number of live connections.
+    gauge: IntGauge,
+    connections: Mutex<Connections>,
+}
+
+#[derive(Default)]
+struct Connections {
+    next_id: u64,
+    connections: HashMap<u64, ConnectionState>,
+    /// Number of connections of each user.
+    users: HashMap<String, usize>,
+}
+
+struct ConnectionState {
+    user: Option<String>,
+    last_active: Instant,
+    /// Number of queries in progress.
+    running: usize,
+    abort_handle: AbortHandle,
+}
+
+impl ConnectionManager {
+    pub fn new(protocol: &'static str, options: ConnectionOptions, gauge: IntGauge) -> Self {
+        Self {
+            protocol,
+            options,
+            gauge,
+            connections: Mutex::default(),
+        }
+    }
+
+    pub fn options(&self) -> &ConnectionOptions {
+        &self.options
+    }
+
+    /// Returns the limits to expose to the sessions of the server.
+    pub fn limits(&self) -> ConnectionLimits {
+        (&self.options).into()
+    }
+
+    /// Returns the number of live connections.
+    pub fn num_connections(&self) -> usize {
+        self.connections.lock().connections.len()
+    }
+
+    /// Registers a new connection whose task can be aborted by `abort_handle`.
+    /// The connection is unregistered when the returned guard is dropped.
+    pub fn register(self: &Arc<Self>, abort_handle: AbortHandle) -> Result<ConnectionGuard> {
+        let mut connections = self.connections.lock();
+        let limit = self.options.max_connections;
+        if limit > 0 && connections.connections.len() >= limit {
+            METRIC_REJECTED_CONNECTIONS
+                .with_label_values(&[self.protocol, "max_connections"])
+                .inc();
+            return TooManyConnectionsSnafu {
+                protocol: self.protocol,
+                limit,
+            }
+            .fail();
+        }
+
+        let id = connections.next_id;
+        connections.next_id += 1;
+        let _ = connections.connections.insert(
+            id,
+            ConnectionState {
+                user: None,
+                last_active: Instant::now(),
+                running: 0,
+                abort_handle,
+            },
+        );
+        self.gauge.inc();
+
+        Ok(ConnectionGuard {
+            manager: self.clone(),
+            id,
+        })
+    }
+
+    /// Closes the connections that have been idle since `now - idle_timeout`,
+    /// returns the number of closed connections.
+    pub fn close_idle_connections(&self, now: Instant) -> usize {
+        let idle_timeout = self.options.idle_timeout;
+        if idle_timeout.is_zero() {
+            return 0;
+        }
+
+        let connections = self.connections.lock();
+        let mut closed = 0;
+        for (id, conn) in &connections.connections {
+            if conn.running > 0
+                || conn.abort_handle.is_aborted()
+                || now.saturating_duration_since(conn.last_active) < idle_timeout
+            {
+                continue;
+            }
+            debug!(
+                "Closing idle {} connection {}, user: {:?}",
+                self.protocol, id, conn.user
+            );
+            // The connection is unregistered once its task is dropped.
+            conn.abort_handle.abort();
+            closed += 1;
+        }
+        if closed > 0 {
+            METRIC_IDLE_CLOSED_CONNECTIONS
+                .with_label_values(&[self.protocol])
+                .inc_by(closed as u64);
+        }
+        closed
+    }
+
+    /// Starts a background task that closes idle connections periodically if
+    /// the idle timeout is set. The task stops after the manager is dropped.
+    pub fn start_idle_reaper(self: &Arc<Self>) {
+        let idle_timeout = self.options.idle_timeout;
+        if idle_timeout.is_zero() {
+            return;
+        }
+
+        info!(
+            "Closing {} connections idle for more than {:?}",
+            self.protocol, idle_timeout
+        );
+        let manager = Arc::downgrade(self);
+        let _handle = common_runtime::spawn_global(async move {
+            let mut interval = tokio::time::interval(idle_timeout.min(MAX_REAP_INTERVAL));
+            loop {
+                let _ = interval.tick().await;
+                let Some(manager) = Weak::upgrade(&manager) else {
+                    return;
+                };
+                let _ = manager.close_idle_connections(Instant::now());
+            }
+        });
+    }
+
+    fn set_user(&self, id: u64, user: &str) -> Result<()> {
+        let mut connections = self.connections.lock();
+        let Connections {
+            connections, users, ..
+        } = &mut *connections;
+        let Some(conn) = connections.get_mut(&id) else {
+            return Ok(());
+        };
+        if conn.user.as_deref() == Some(user) {
+            return Ok(());
+        }
+
+        let limit = self.options.max_user_connections;
+        if limit > 0 && users.get(user).copied().unwrap_or(0) >= limit {
+            METRIC_REJECTED_CONNECTIONS
+                .with_label_values(&[self.protocol, "max_user_connections"])
+                .inc();
+            return TooManyUserConnectionsSnafu {
+                user,
+                protocol: self.protocol,
+                limit,
+            }
+            .fail();
+        }
+
+        if let Some(prev) = conn.user.replace(user.to_string()) {
+            release_user(users, &prev);
+        }
+        *users.entry(user.to_string()).or_default() += 1;
+        Ok(())
+    }
+
+    fn begin(&self, id: u64) {
+        if let Some(conn) = self.connections.lock().connections.get_mut(&id) {
+            conn.running += 1;
+            conn.last_active = Instant::now();
+        }
+    }
+
+    fn finish(&self, id: u64) {
+        if let Some(conn) = self.connections.lock().connections.get_mut(&id) {
+            conn.running = conn.running.saturating_sub(1);
+            conn.last_active = Instant::now();
+        }
+    }
+
+    fn unregister(&self, id: u64) {
+        let mut connections = self.connections.lock();
+        let Some(conn) = connections.connections.remove(&id) else {
+            return;
+        };
+        if let Some(user) = &conn.user {
+            release_user(&mut connections.users, user);
+        }
+        self.gauge.dec();
+    }
+}
+
+fn release_user(users: &mut HashMap<String, usize>, user: &str) {
+    if let Some(count) = users.get_mut(user) {
+        *count -= 1;
+        if *count == 0 {
+            let _ = users.remove(user);
+        }
+    }
+}
+
+/// A registered connection, unregisters the connection on drop.
+pub struct ConnectionGuard {
+    manager: ConnectionManagerRef,
+    id: u64,
+}
+
+impl ConnectionGuard {
+    /// Sets the authenticated user of the connection, fails if the user has
+    /// too many connections.
+    pub fn set_user(&self, user: &str) -> Result<()> {
+        self.manager.set_user(self.id, user)
+    }
+
+    /// Marks the connection as running a query until the returned guard is
+    /// dropped. A running connection is never considered idle.
+    pub fn begin(&self) -> ActiveGuard {
+        self.manager.begin(self.id);
+        ActiveGuard {
+            manager: self.manager.clone(),
+            id: self.id,
+        }
+    }
+
+    pub fn limits(&self) -> ConnectionLimits {
+        self.manager.limits()
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.manager.unregister(self.id);
+    }
+}
+
+/// Guard of a query in progress.
+pub struct ActiveGuard {
+    manager: ConnectionManagerRef,
+    id: u64,
+}
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        self.manager.finish(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::Abortable;
+
+    use super::*;
+
+    fn new_manager(options: ConnectionOptions) -> ConnectionManagerRef {
+        let gauge = IntGauge::new("test_connections", "test connections").unwrap();
+        Arc::new(ConnectionManager::new("test", options, gauge))
+    }
+
+    /// Spawns a connection task that holds the guard until it's aborted.
+    fn spawn_connection(
+        manager: &ConnectionManagerRef,
+    ) -> Result<tokio::task::JoinHandle<Result<(), futures::future::Aborted>>> {
+        let (abort_handle, registration) = AbortHandle::new_pair();
+        let guard = manager.register(abort_handle)?;
+        Ok(tokio::spawn(Abortable::new(
+            async move {
+                let _guard = guard;
+                futures::future::pending::<()>().await
+            },
+            registration,
+        )))
+    }
+
+    #[tokio::test]
+    async fn test_close_idle_connections() {
+        let idle_timeout = Duration::from_secs(60);
+        let manager = new_manager(ConnectionOptions {
+            idle_timeout,
+            ..Default::default()
+        });
+
+        let mut tasks = Vec::new();
+        for _ in 0..3 {
+            tasks.push(spawn_connection(&manager).unwrap());
+        }
+        let start = Instant::now();
+        for _ in 0..5 {
+            tasks.push(spawn_connection(&manager).unwrap());
+        }
+        assert_eq!(8, manager.num_connections());
+        assert_eq!(8, manager.gauge.get());
+
+        // Pretends the first 3 connections are older.
+        {
+            let mut connections = manager.connections.lock();
+            for id in 0..3 {
+                connections.connections.get_mut(&id).unwrap().last_active =
+                    start - Duration::from_secs(30);
+            }
+        }
+        // Nothing is idle yet.
+        assert_eq!(0, manager.close_idle_connections(start));
+        // Only the oldest connections are reaped.
+        let now = start + Duration::from_secs(45);
+        assert_eq!(3, manager.close_idle_connections(now));
+        // Closing connections are not counted again.
+        assert_eq!(0, manager.close_idle_connections(now));
+        for task in tasks.drain(..3) {
+            assert!(task.await.unwrap().is_err());
+        }
+        assert_eq!(5, manager.num_connections());
+        assert_eq!(5, manager.gauge.get());
+
+        // Connections running queries are not idle.
+        manager.begin(3);
+        let now = start + Duration::from_secs(120);
+        assert_eq!(4, manager.close_idle_connections(now));
+        for task in tasks.drain(1..) {
+            assert!(task.await.unwrap().is_err());
+        }
+        assert_eq!(1, manager.num_connections());
+        assert_eq!(1, manager.gauge.get());
+
+        manager.finish(3);
+        assert_eq!(1, manager.close_idle_connections(now + idle_timeout));
+        assert!(tasks.remove(0).await.unwrap().is_err());
+        assert_eq!(0, manager.num_connections());
+        assert_eq!(0, manager.gauge.get());
+    }
+
+    #[tokio::test]
+    async fn test_connection_limits() {
+        let manager = new_manager(ConnectionOptions {
+            max_connections: 3,
+            max_user_connections: 2,
+            ..Default::default()
+        });
+
+        let guards = (0..3)
+            .map(|_| manager.register(AbortHandle::new_pair().0).unwrap())
+            .collect::<Vec<_>>();
+        assert!(manager.register(AbortHandle::new_pair().0).is_err());
+        assert_eq!(3, manager.gauge.get());
+
+        guards[0].set_user("alice").unwrap();
+        guards[1].set_user("alice").unwrap();
+        assert!(guards[2].set_user("alice").is_err());
+        guards[2].set_user("bob").unwrap();
+
+        // Dropping a connection releases the slots.
+        let mut guards = guards;
+        let _ = guards.remove(0);
+        assert_eq!(2, manager.gauge.get());
+        let guard = manager.register(AbortHandle::new_pair().0).unwrap();
+        guard.set_user("alice").unwrap();
+        assert_eq!(3, manager.gauge.get());
+
+        drop(guard);
+        drop(guards);
+        assert_eq!(0, manager.num_connections());
+        assert_eq!(0, manager.gauge.get());
+        assert!(manager.connections.lock().users.is_empty());
+    }
+}
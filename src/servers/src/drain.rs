@@ -0,0 +1,162 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Draining in-flight requests before a server shuts down.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_query::{Output, OutputData};
+use common_recordbatch::SendableRecordBatchStream;
+use query::metrics::OnDone;
+use tokio::sync::Notify;
+
+pub type DrainStateRef = Arc<DrainState>;
+
+/// Tracks the in-flight requests of a node. Once the node starts draining, it is
+/// not ready and rejects new requests, while the in-flight ones run to completion.
+#[derive(Debug, Default)]
+pub struct DrainState {
+    draining: AtomicBool,
+    inflight: AtomicUsize,
+    notify: Notify,
+}
+
+impl DrainState {
+    /// Returns true if the node has started draining.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of in-flight requests.
+    pub fn inflight(&self) -> usize {
+        self.inflight.load(Ordering::Acquire)
+    }
+
+    /// Registers an in-flight request, returns `None` if the node is draining.
+    /// The request is in flight until the returned guard is dropped.
+    pub fn enter(self: &Arc<Self>) -> Option<InflightGuard> {
+        // Increases the counter before checking the flag, so the drain either
+        // sees the request or the request sees the flag.
+        self.inflight.fetch_add(1, Ordering::AcqRel);
+        let guard = InflightGuard {
+            state: self.clone(),
+        };
+        if self.is_draining() {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// Stops accepting new requests and waits for the in-flight requests to finish.
+    /// Returns false if some requests are still in flight after `timeout`.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        self.draining.store(true, Ordering::Release);
+        tokio::time::timeout(timeout, async {
+            loop {
+                let notified = self.notify.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+                if self.inflight() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+
+    fn exit(&self) {
+        if self.inflight.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.notify.notify_waiters();
+        }
+    }
+}
+
+/// A guard of an in-flight request.
+#[derive(Debug)]
+pub struct InflightGuard {
+    state: DrainStateRef,
+}
+
+impl InflightGuard {
+    /// Keeps the request in flight until the `stream` is done or dropped.
+    pub fn attach_stream(self, stream: SendableRecordBatchStream) -> SendableRecordBatchStream {
+        Box::pin(OnDone::new(stream, move || drop(self)))
+    }
+
+    /// Keeps the request in flight until the stream of the `output` is done or dropped.
+    pub fn attach(self, output: Output) -> Output {
+        match output.data {
+            OutputData::AffectedRows(_) | OutputData::RecordBatches(_) => output,
+            OutputData::Stream(stream) => {
+                Output::new(OutputData::Stream(self.attach_stream(stream)), output.meta)
+            }
+        }
+    }
+}
+
+/// Cloning a guard registers another in-flight request, e.g. for each output of
+/// a request with multiple statements.
+impl Clone for InflightGuard {
+    fn clone(&self) -> Self {
+        self.state.inflight.fetch_add(1, Ordering::AcqRel);
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Drop for InflightGuard {
+    fn drop(&mut self) {
+        self.state.exit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain() {
+        let state = DrainStateRef::default();
+        let guard = state.enter().unwrap();
+        assert_eq!(1, state.inflight());
+
+        let draining = {
+            let state = state.clone();
+            tokio::spawn(async move { state.drain(Duration::from_secs(10)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(state.is_draining());
+        // New requests are rejected while the in-flight one is still running.
+        assert!(state.enter().is_none());
+        assert_eq!(1, state.inflight());
+        assert!(!draining.is_finished());
+
+        drop(guard);
+        assert!(draining.await.unwrap());
+        assert_eq!(0, state.inflight());
+    }
+
+    #[tokio::test]
+    async fn test_drain_timeout() {
+        let state = DrainStateRef::default();
+        let _guard = state.enter().unwrap();
+        assert!(!state.drain(Duration::from_millis(10)).await);
+        assert_eq!(1, state.inflight());
+    }
+}
@@ -169,6 +169,14 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to read the request body"))]
+    ReadRequestBody {
+        #[snafu(source)]
+        error: axum::extract::rejection::BytesRejection,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Invalid query: {}", reason))]
     InvalidQuery {
         reason: String,
@@ -213,6 +221,13 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Invalid OpenTSDB data point: {}", reason))]
+    InvalidOpentsdbDataPoint {
+        reason: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Failed to decode prometheus remote request"))]
     DecodePromRemoteRequest {
         #[snafu(implicit)]
@@ -588,6 +603,28 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Too many {} connections, limit: {}", protocol, limit))]
+    TooManyConnections {
+        protocol: String,
+        limit: usize,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display(
+        "User '{}' has too many {} connections, limit: {}",
+        user,
+        protocol,
+        limit
+    ))]
+    TooManyUserConnections {
+        user: String,
+        protocol: String,
+        limit: usize,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("In-flight write bytes exceeded the maximum limit"))]
     InFlightWriteBytesExceeded {
         #[snafu(implicit)]
@@ -656,9 +693,11 @@ impl ErrorExt for Error {
 
             NotSupported { .. }
             | InvalidParameter { .. }
+            | ReadRequestBody { .. }
             | InvalidQuery { .. }
             | InfluxdbLineProtocol { .. }
             | InvalidOpentsdbJsonRequest { .. }
+            | InvalidOpentsdbDataPoint { .. }
             | DecodePromRemoteRequest { .. }
             | DecodeOtlpRequest { .. }
             | CompressPromRemoteRequest { .. }
@@ -733,7 +772,9 @@ impl ErrorExt for Error {
 
             ConvertSqlValue { source, .. } => source.status_code(),
 
-            InFlightWriteBytesExceeded { .. } => StatusCode::RateLimited,
+            InFlightWriteBytesExceeded { .. }
+            | TooManyConnections { .. }
+            | TooManyUserConnections { .. } => StatusCode::RateLimited,
         }
     }
 
@@ -808,8 +849,10 @@ pub fn status_code_to_http_status(status_code: &StatusCode) -> HttpStatusCode {
 
         StatusCode::RateLimited => HttpStatusCode::TOO_MANY_REQUESTS,
 
+        // Collectors keep the data and retry later if the service is unavailable.
         StatusCode::RegionNotReady
         | StatusCode::TableUnavailable
+        | StatusCode::TableReadonly
         | StatusCode::RegionBusy
         | StatusCode::StorageUnavailable
         | StatusCode::External => HttpStatusCode::SERVICE_UNAVAILABLE,
@@ -22,9 +22,8 @@ use common_telemetry::{debug, warn};
 use futures::StreamExt;
 use tonic::{Request, Response, Status, Streaming};
 
-use crate::grpc::greptime_handler::GreptimeRequestHandler;
+use crate::grpc::greptime_handler::{GreptimeRequestHandler, RequestMetadata};
 use crate::grpc::{cancellation, TonicResult};
-use crate::hint_headers;
 
 pub(crate) struct DatabaseService {
     handler: GreptimeRequestHandler,
@@ -43,15 +42,15 @@ impl GreptimeDatabase for DatabaseService {
         request: Request<GreptimeRequest>,
     ) -> TonicResult<Response<GreptimeResponse>> {
         let remote_addr = request.remote_addr();
-        let hints = hint_headers::extract_hints(request.metadata());
+        let metadata = RequestMetadata::from_metadata(request.metadata());
         debug!(
             "GreptimeDatabase::Handle: request from {:?} with hints: {:?}",
-            remote_addr, hints
+            remote_addr, metadata.hints
         );
         let handler = self.handler.clone();
         let request_future = async move {
             let request = request.into_inner();
-            let output = handler.handle_request(request, hints).await?;
+            let output = handler.handle_request(request, metadata).await?;
             let message = match output.data {
                 OutputData::AffectedRows(rows) => GreptimeResponse {
                     header: Some(ResponseHeader {
@@ -89,10 +88,10 @@ impl GreptimeDatabase for DatabaseService {
         request: Request<Streaming<GreptimeRequest>>,
     ) -> Result<Response<GreptimeResponse>, Status> {
         let remote_addr = request.remote_addr();
-        let hints = hint_headers::extract_hints(request.metadata());
+        let metadata = RequestMetadata::from_metadata(request.metadata());
         debug!(
             "GreptimeDatabase::HandleRequests: request from {:?} with hints: {:?}",
-            remote_addr, hints
+            remote_addr, metadata.hints
         );
         let handler = self.handler.clone();
         let request_future = async move {
@@ -101,7 +100,7 @@ impl GreptimeDatabase for DatabaseService {
             let mut stream = request.into_inner();
             while let Some(request) = stream.next().await {
                 let request = request?;
-                let output = handler.handle_request(request, hints.clone()).await?;
+                let output = handler.handle_request(request, metadata.clone()).await?;
                 match output.data {
                     OutputData::AffectedRows(rows) => affected_rows += rows,
                     OutputData::Stream(_) | OutputData::RecordBatches(_) => {
@@ -35,7 +35,7 @@ use tonic::{Request, Response, Status, Streaming};
 
 use crate::error;
 pub use crate::grpc::flight::stream::FlightRecordBatchStream;
-use crate::grpc::greptime_handler::{get_request_type, GreptimeRequestHandler};
+use crate::grpc::greptime_handler::{get_request_type, GreptimeRequestHandler, RequestMetadata};
 use crate::grpc::TonicResult;
 
 pub type TonicStream<T> = Pin<Box<dyn Stream<Item = TonicResult<T>> + Send + Sync + 'static>>;
@@ -47,6 +47,13 @@ pub trait FlightCraft: Send + Sync + 'static {
         &self,
         request: Request<Ticket>,
     ) -> TonicResult<Response<TonicStream<FlightData>>>;
+
+    async fn do_action(
+        &self,
+        _request: Request<Action>,
+    ) -> TonicResult<Response<TonicStream<arrow_flight::Result>>> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
 }
 
 pub type FlightCraftRef = Arc<dyn FlightCraft>;
@@ -67,6 +74,13 @@ impl FlightCraft for FlightCraftRef {
     ) -> TonicResult<Response<TonicStream<FlightData>>> {
         (**self).do_get(request).await
     }
+
+    async fn do_action(
+        &self,
+        request: Request<Action>,
+    ) -> TonicResult<Response<TonicStream<arrow_flight::Result>>> {
+        (**self).do_action(request).await
+    }
 }
 
 #[async_trait]
@@ -136,8 +150,11 @@ impl<T: FlightCraft> FlightService for FlightCraftWrapper<T> {
 
     type DoActionStream = TonicStream<arrow_flight::Result>;
 
-    async fn do_action(&self, _: Request<Action>) -> TonicResult<Response<Self::DoActionStream>> {
-        Err(Status::unimplemented("Not yet implemented"))
+    async fn do_action(
+        &self,
+        request: Request<Action>,
+    ) -> TonicResult<Response<Self::DoActionStream>> {
+        self.0.do_action(request).await
     }
 
     type ListActionsStream = TonicStream<ActionType>;
@@ -156,6 +173,7 @@ impl FlightCraft for GreptimeRequestHandler {
         &self,
         request: Request<Ticket>,
     ) -> TonicResult<Response<TonicStream<FlightData>>> {
+        let mut metadata = RequestMetadata::from_metadata(request.metadata());
         let ticket = request.into_inner().ticket;
         let request =
             GreptimeRequest::decode(ticket.as_ref()).context(error::InvalidFlightTicketSnafu)?;
@@ -166,8 +184,13 @@ impl FlightCraft for GreptimeRequestHandler {
             protocol = "grpc",
             request_type = get_request_type(&request)
         );
+        // Continue the trace of the caller from the outermost span.
+        let span = match metadata.tracing_context.take() {
+            Some(tracing_context) => tracing_context.attach(span),
+            None => span,
+        };
         async {
-            let output = self.handle_request(request, Default::default()).await?;
+            let output = self.handle_request(request, metadata).await?;
             let stream: Pin<Box<dyn Stream<Item = Result<FlightData, Status>> + Send + Sync>> =
                 to_flight_data_stream(output, TracingContext::from_current_span());
             Ok(Response::new(stream))
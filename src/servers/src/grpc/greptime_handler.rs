@@ -14,7 +14,7 @@
 
 //! Handler for Greptime Database service. It's implemented by frontend.
 
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use api::helper::request_type;
 use api::v1::auth_header::AuthScheme;
@@ -27,17 +27,66 @@ use common_error::status_code::StatusCode;
 use common_query::Output;
 use common_runtime::runtime::RuntimeTrait;
 use common_runtime::Runtime;
-use common_telemetry::tracing_context::{FutureExt, TracingContext};
+use common_telemetry::tracing_context::{FutureExt, TracingContext, W3cTrace};
 use common_telemetry::{debug, error, tracing};
 use common_time::timezone::parse_timezone;
-use session::context::{QueryContextBuilder, QueryContextRef};
+use session::context::{Channel, QueryContextBuilder, QueryContextRef};
 use snafu::{OptionExt, ResultExt};
+use tonic::metadata::MetadataMap;
 
 use crate::error::Error::UnsupportedAuthScheme;
 use crate::error::{AuthSnafu, InvalidQuerySnafu, JoinTaskSnafu, NotFoundAuthHeaderSnafu, Result};
+use crate::hint_headers;
+use crate::http::header::constants::{GREPTIME_DB_HEADER_TIMEOUT, GREPTIME_TIMEZONE_HEADER_NAME};
 use crate::metrics::{METRIC_AUTH_FAILURE, METRIC_SERVER_GRPC_DB_REQUEST_TIMER};
 use crate::query_handler::grpc::ServerGrpcQueryHandlerRef;
 
+/// Metadata key of the W3C trace context, see <https://www.w3.org/TR/trace-context/>.
+const TRACEPARENT_KEY: &str = "traceparent";
+const TRACESTATE_KEY: &str = "tracestate";
+
+/// Per-request options of a gRPC request, carried in its metadata.
+///
+/// - `x-greptime-hints` or `x-greptime-hint-*`: hints set into the query context extensions.
+/// - `x-greptime-timezone`: timezone of the query, used if the request header doesn't set one.
+/// - `x-greptime-timeout`: timeout of the query in human readable format like `30s`.
+/// - `traceparent` and `tracestate`: the W3C trace context the request span is attached to.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct RequestMetadata {
+    pub hints: Vec<(String, String)>,
+    pub timezone: Option<String>,
+    pub timeout: Option<Duration>,
+    pub tracing_context: Option<TracingContext>,
+}
+
+impl RequestMetadata {
+    pub(crate) fn from_metadata(metadata: &MetadataMap) -> Self {
+        let timezone = metadata_value(metadata, GREPTIME_TIMEZONE_HEADER_NAME)
+            .map(|timezone| timezone.trim().to_string());
+        let timeout = metadata_value(metadata, GREPTIME_DB_HEADER_TIMEOUT)
+            .and_then(|timeout| humantime::parse_duration(timeout.trim()).ok());
+        let tracing_context = metadata_value(metadata, TRACEPARENT_KEY).map(|traceparent| {
+            let mut fields = W3cTrace::new();
+            fields.insert(TRACEPARENT_KEY.to_string(), traceparent.to_string());
+            if let Some(tracestate) = metadata_value(metadata, TRACESTATE_KEY) {
+                fields.insert(TRACESTATE_KEY.to_string(), tracestate.to_string());
+            }
+            TracingContext::from_w3c(&fields)
+        });
+
+        Self {
+            hints: hint_headers::extract_hints(metadata),
+            timezone,
+            timeout,
+            tracing_context,
+        }
+    }
+}
+
+fn metadata_value<'a>(metadata: &'a MetadataMap, key: &str) -> Option<&'a str> {
+    metadata.get(key).and_then(|value| value.to_str().ok())
+}
+
 #[derive(Clone)]
 pub struct GreptimeRequestHandler {
     handler: ServerGrpcQueryHandlerRef,
@@ -62,14 +111,19 @@ impl GreptimeRequestHandler {
     pub(crate) async fn handle_request(
         &self,
         request: GreptimeRequest,
-        hints: Vec<(String, String)>,
+        metadata: RequestMetadata,
     ) -> Result<Output> {
+        if let Some(tracing_context) = &metadata.tracing_context {
+            // Continue the trace of the caller.
+            tracing_context.attach(tracing::Span::current());
+        }
+
         let query = request.request.context(InvalidQuerySnafu {
             reason: "Expecting non-empty GreptimeRequest.",
         })?;
 
         let header = request.header.as_ref();
-        let query_ctx = create_query_context(header, hints);
+        let query_ctx = create_query_context(header, &metadata);
         let user_info = auth(self.user_provider.clone(), header, &query_ctx).await?;
         query_ctx.set_current_user(user_info);
 
@@ -169,7 +223,7 @@ pub(crate) async fn auth(
 
 pub(crate) fn create_query_context(
     header: Option<&RequestHeader>,
-    extensions: Vec<(String, String)>,
+    metadata: &RequestMetadata,
 ) -> QueryContextRef {
     let (catalog, schema) = header
         .map(|header| {
@@ -198,15 +252,24 @@ pub(crate) fn create_query_context(
                 DEFAULT_SCHEMA_NAME.to_string(),
             )
         });
-    let timezone = parse_timezone(header.map(|h| h.timezone.as_str()));
+    let timezone = header
+        .map(|h| h.timezone.as_str())
+        .filter(|timezone| !timezone.is_empty())
+        .or(metadata.timezone.as_deref());
+    let timezone = parse_timezone(timezone);
     let mut ctx_builder = QueryContextBuilder::default()
         .current_catalog(catalog)
         .current_schema(schema)
-        .timezone(timezone);
-    for (key, value) in extensions {
-        ctx_builder = ctx_builder.set_extension(key, value);
+        .timezone(timezone)
+        .channel(Channel::Grpc);
+    for (key, value) in &metadata.hints {
+        ctx_builder = ctx_builder.set_extension(key.clone(), value.clone());
+    }
+    let query_ctx: QueryContextRef = ctx_builder.build().into();
+    if let Some(timeout) = metadata.timeout {
+        query_ctx.set_query_timeout(timeout);
     }
-    ctx_builder.build().into()
+    query_ctx
 }
 
 /// Histogram timer for handling gRPC request.
@@ -247,3 +310,72 @@ impl Drop for RequestTimer {
             .observe(self.start.elapsed().as_secs_f64());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use common_time::Timezone;
+    use tonic::metadata::MetadataValue;
+
+    use super::*;
+
+    #[test]
+    fn test_request_metadata() {
+        let mut metadata = MetadataMap::new();
+        metadata.insert("x-greptime-hint-ttl", MetadataValue::from_static("3600d"));
+        metadata.insert("x-greptime-timezone", MetadataValue::from_static("+08:00"));
+        metadata.insert("x-greptime-timeout", MetadataValue::from_static("30s"));
+        metadata.insert(
+            "traceparent",
+            MetadataValue::from_static("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01"),
+        );
+
+        let request_metadata = RequestMetadata::from_metadata(&metadata);
+        assert_eq!(
+            request_metadata.hints,
+            vec![("ttl".to_string(), "3600d".to_string())]
+        );
+        assert_eq!(request_metadata.timezone.as_deref(), Some("+08:00"));
+        assert_eq!(request_metadata.timeout, Some(Duration::from_secs(30)));
+        let traceparent = request_metadata.tracing_context.unwrap().to_w3c();
+        assert_eq!(
+            traceparent["traceparent"],
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01"
+        );
+
+        let request_metadata = RequestMetadata::from_metadata(&MetadataMap::new());
+        assert!(request_metadata.hints.is_empty());
+        assert!(request_metadata.timezone.is_none());
+        assert!(request_metadata.timeout.is_none());
+        assert!(request_metadata.tracing_context.is_none());
+    }
+
+    #[test]
+    fn test_create_query_context_with_metadata() {
+        let metadata = RequestMetadata {
+            hints: vec![("ttl".to_string(), "3600d".to_string())],
+            timezone: Some("+08:00".to_string()),
+            timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        };
+
+        let query_ctx = create_query_context(None, &metadata);
+        assert_eq!(
+            query_ctx.timezone(),
+            Timezone::from_tz_string("+08:00").unwrap()
+        );
+        assert_eq!(query_ctx.query_timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(query_ctx.extension("ttl"), Some("3600d"));
+        assert_eq!(query_ctx.channel(), Channel::Grpc);
+
+        // The timezone in the request header takes precedence.
+        let header = RequestHeader {
+            timezone: "Asia/Shanghai".to_string(),
+            ..Default::default()
+        };
+        let query_ctx = create_query_context(Some(&header), &metadata);
+        assert_eq!(
+            query_ctx.timezone(),
+            Timezone::from_tz_string("Asia/Shanghai").unwrap()
+        );
+    }
+}
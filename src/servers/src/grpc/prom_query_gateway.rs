@@ -26,15 +26,15 @@ use common_error::ext::ErrorExt;
 use common_error::status_code::StatusCode;
 use common_time::util::current_time_rfc3339;
 use promql_parser::parser::value::ValueType;
-use query::parser::PromQuery;
+use query::parser::{PromQuery, PROMQL_METRIC_NAME_COLUMN_KEY};
 use session::context::QueryContext;
 use snafu::OptionExt;
 use tonic::{Request, Response};
 
 use crate::error::InvalidQuerySnafu;
-use crate::grpc::greptime_handler::{auth, create_query_context};
+use crate::grpc::greptime_handler::{auth, create_query_context, RequestMetadata};
 use crate::grpc::TonicResult;
-use crate::http::prometheus::{retrieve_metric_name_and_result_type, PrometheusJsonResponse};
+use crate::http::prometheus::{retrieve_result_type, PrometheusJsonResponse};
 use crate::prometheus_handler::PrometheusHandlerRef;
 
 pub struct PrometheusGatewayService {
@@ -77,7 +77,15 @@ impl PrometheusGateway for PrometheusGatewayService {
         };
 
         let header = inner.header.as_ref();
-        let query_ctx = create_query_context(header, Default::default());
+        // The `__name__` label of the result series is returned as a column.
+        let metadata = RequestMetadata {
+            hints: vec![(
+                PROMQL_METRIC_NAME_COLUMN_KEY.to_string(),
+                "true".to_string(),
+            )],
+            ..Default::default()
+        };
+        let query_ctx = create_query_context(header, &metadata);
         let user_info = auth(self.user_provider.clone(), header, &query_ctx).await?;
         query_ctx.set_current_user(user_info);
 
@@ -119,18 +127,15 @@ impl PrometheusGatewayService {
             .start_timer();
 
         let result = self.handler.do_query(&query, ctx).await;
-        let (metric_name, mut result_type) =
-            match retrieve_metric_name_and_result_type(&query.query) {
-                Ok((metric_name, result_type)) => (metric_name.unwrap_or_default(), result_type),
-                Err(err) => {
-                    return PrometheusJsonResponse::error(err.status_code(), err.output_msg())
-                }
-            };
+        let mut result_type = match retrieve_result_type(&query.query) {
+            Ok(result_type) => result_type,
+            Err(err) => return PrometheusJsonResponse::error(err.status_code(), err.output_msg()),
+        };
         // range query only returns matrix
         if is_range_query {
             result_type = ValueType::Matrix;
         };
 
-        PrometheusJsonResponse::from_query_result(result, metric_name, result_type).await
+        PrometheusJsonResponse::from_query_result(result, result_type).await
     }
 }
@@ -51,6 +51,7 @@ use tower_http::trace::TraceLayer;
 use self::authorize::AuthState;
 use self::result::table_result::TableResponse;
 use crate::configurator::ConfiguratorRef;
+use crate::drain::DrainStateRef;
 use crate::elasticsearch;
 use crate::error::{
     AddressBindSnafu, AlreadyStartedSnafu, Error, InternalIoSnafu, InvalidHeaderValueSnafu, Result,
@@ -60,7 +61,7 @@ use crate::http::influxdb::{influxdb_health, influxdb_ping, influxdb_write_v1, i
 use crate::http::prom_store::PromStoreState;
 use crate::http::prometheus::{
     build_info_query, format_query, instant_query, label_values_query, labels_query, parse_query,
-    range_query, series_query,
+    range_query, series_query, tsdb_status_query,
 };
 use crate::http::result::arrow_result::ArrowResponse;
 use crate::http::result::csv_result::CsvResponse;
@@ -88,6 +89,7 @@ pub mod event;
 mod extractor;
 pub mod handler;
 pub mod header;
+pub mod ingest;
 pub mod influxdb;
 pub mod jaeger;
 pub mod logs;
@@ -124,6 +126,7 @@ pub struct HttpServer {
     router: StdMutex<Router>,
     shutdown_tx: Mutex<Option<Sender<()>>>,
     user_provider: Option<UserProviderRef>,
+    drain_state: Option<DrainStateRef>,
 
     // plugins
     plugins: Plugins,
@@ -496,6 +499,7 @@ pub struct HttpServerBuilder {
     plugins: Plugins,
     user_provider: Option<UserProviderRef>,
     router: Router,
+    drain_state: Option<DrainStateRef>,
 }
 
 impl HttpServerBuilder {
@@ -505,6 +509,7 @@ impl HttpServerBuilder {
             plugins: Plugins::default(),
             user_provider: None,
             router: Router::new(),
+            drain_state: None,
         }
     }
 
@@ -598,6 +603,14 @@ impl HttpServerBuilder {
         }
     }
 
+    /// Reports not ready in `/ready` once the `drain_state` starts draining.
+    pub fn with_drain_state(self, drain_state: DrainStateRef) -> Self {
+        Self {
+            drain_state: Some(drain_state),
+            ..self
+        }
+    }
+
     pub fn with_metrics_handler(self, handler: MetricsHandler) -> Self {
         Self {
             router: self.router.merge(HttpServer::route_metrics(handler)),
@@ -687,6 +700,7 @@ impl HttpServerBuilder {
             shutdown_tx: Mutex::new(None),
             plugins: self.plugins,
             router: StdMutex::new(self.router),
+            drain_state: self.drain_state,
         }
     }
 }
@@ -706,7 +720,9 @@ impl HttpServer {
             )
             .route(
                 "/ready",
-                routing::get(handler::health).post(handler::health),
+                routing::get(handler::ready)
+                    .post(handler::ready)
+                    .with_state(self.drain_state.clone()),
             );
 
         router = router.route("/status", routing::get(handler::status));
@@ -993,6 +1009,7 @@ impl HttpServer {
                 routing::post(format_query).get(format_query),
             )
             .route("/status/buildinfo", routing::get(build_info_query))
+            .route("/status/tsdb", routing::get(tsdb_status_query))
             .route("/query", routing::post(instant_query).get(instant_query))
             .route("/query_range", routing::post(range_query).get(range_query))
             .route("/labels", routing::post(labels_query).get(labels_query))
@@ -1034,6 +1051,11 @@ impl HttpServer {
     fn route_opentsdb<S>(opentsdb_handler: OpentsdbProtocolHandlerRef) -> Router<S> {
         Router::new()
             .route("/api/put", routing::post(opentsdb::put))
+            .route("/api/version", routing::get(opentsdb::version))
+            .layer(
+                ServiceBuilder::new()
+                    .layer(RequestDecompressionLayer::new().pass_through_unaccepted(true)),
+            )
             .with_state(opentsdb_handler)
     }
 
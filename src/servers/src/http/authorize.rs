@@ -102,6 +102,9 @@ pub async fn inner_auth<B>(
         Ok(userinfo) => {
             query_ctx.set_current_user(userinfo);
             let _ = req.extensions_mut().insert(query_ctx);
+            // Handlers that switch to another database by request params, like the
+            // Prometheus APIs, need to authorize the user again.
+            let _ = req.extensions_mut().insert(user_provider);
             Ok(req)
         }
         Err(e) => {
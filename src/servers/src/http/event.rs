@@ -30,6 +30,7 @@ use common_error::ext::ErrorExt;
 use common_query::{Output, OutputData};
 use common_telemetry::{error, warn};
 use datatypes::value::column_data_to_json;
+use futures::{StreamExt, TryStreamExt};
 use headers::ContentType;
 use lazy_static::lazy_static;
 use pipeline::util::to_pipeline_version;
@@ -41,10 +42,11 @@ use snafu::{ensure, OptionExt, ResultExt};
 
 use crate::error::{
     status_code_to_http_status, Error, InvalidParameterSnafu, ParseJsonSnafu, PipelineSnafu,
-    Result, UnsupportedContentTypeSnafu,
+    ReadRequestBodySnafu, Result, UnsupportedContentTypeSnafu,
 };
 use crate::http::header::constants::GREPTIME_PIPELINE_PARAMS_HEADER;
 use crate::http::header::{CONTENT_TYPE_NDJSON_STR, CONTENT_TYPE_PROTOBUF_STR};
+use crate::http::ingest::{ingest_with_mapping, MappingOptions};
 use crate::http::result::greptime_manage_resp::GreptimedbManageResponse;
 use crate::http::result::greptime_result_v1::GreptimedbV1Response;
 use crate::http::HttpResponse;
@@ -95,6 +97,17 @@ pub struct LogIngesterQueryParams {
     /// If an error occurs while ingesting the data, the `ignore_errors` will be used to determine if the error should be ignored.
     /// If so, use the current server's timestamp as the event time.
     pub custom_time_index: Option<String>,
+    /// The format of the payload, `csv` or `ndjson`. If provided without a pipeline,
+    /// the columns of the payload are mapped to the columns of the table directly.
+    pub format: Option<String>,
+    /// The column of the payload that holds the time index, defaults to the
+    /// time index of the table.
+    pub time_column: Option<String>,
+    /// The format of the time column: `rfc3339` (default), `epoch_s`, `epoch_ms`,
+    /// `epoch_us`, `epoch_ns` or a strftime pattern.
+    pub time_format: Option<String>,
+    /// The comma separated columns used as tags when creating the table.
+    pub tags: Option<String>,
 }
 
 /// LogIngestRequest is the internal request for log ingestion. The raw log input can be transformed into multiple LogIngestRequests.
@@ -522,8 +535,25 @@ pub async fn log_ingester(
     Extension(mut query_ctx): Extension<QueryContext>,
     TypedHeader(content_type): TypedHeader<ContentType>,
     headers: HeaderMap,
-    payload: Bytes,
+    request: Request,
 ) -> Result<HttpResponse> {
+    let is_mapping = query_params.format.is_some() && query_params.pipeline_name.is_none();
+    // The payload of mapping ingestion is streamed into the table, unless it's
+    // validated as a whole.
+    let (payload, stream) = if is_mapping && log_state.log_validator.is_none() {
+        let stream = request
+            .into_body()
+            .into_data_stream()
+            .map_err(std::io::Error::other)
+            .boxed();
+        (Bytes::new(), Some(stream))
+    } else {
+        let payload = Bytes::from_request(request, &log_state)
+            .await
+            .context(ReadRequestBodySnafu)?;
+        (payload, None)
+    };
+
     // validate source and payload
     let source = query_params.source.as_deref();
     let response = match &log_state.log_validator {
@@ -540,6 +570,14 @@ pub async fn log_ingester(
         reason: "table is required",
     })?;
 
+    if is_mapping {
+        let options = MappingOptions::from_params(&query_params)?;
+        query_ctx.set_channel(Channel::Http);
+        let payload = stream.unwrap_or_else(move || futures::stream::iter([Ok(payload)]).boxed());
+        return ingest_with_mapping(handler, table_name, options, payload, Arc::new(query_ctx))
+            .await;
+    }
+
     let ignore_errors = query_params.ignore_errors.unwrap_or(false);
 
     let pipeline_name = query_params.pipeline_name.context(InvalidParameterSnafu {
@@ -18,13 +18,14 @@ use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
 use axum::http::StatusCode;
 use http::HeaderMap;
-use pipeline::{GreptimePipelineParams, SelectInfo};
+use pipeline::{GreptimePipelineParams, LogBodyStructure, SelectInfo};
 
 use crate::http::header::constants::{
     GREPTIME_LOG_EXTRACT_KEYS_HEADER_NAME, GREPTIME_LOG_PIPELINE_NAME_HEADER_NAME,
-    GREPTIME_LOG_PIPELINE_VERSION_HEADER_NAME, GREPTIME_LOG_TABLE_NAME_HEADER_NAME,
-    GREPTIME_PIPELINE_NAME_HEADER_NAME, GREPTIME_PIPELINE_PARAMS_HEADER,
-    GREPTIME_PIPELINE_VERSION_HEADER_NAME, GREPTIME_TRACE_TABLE_NAME_HEADER_NAME,
+    GREPTIME_LOG_PIPELINE_VERSION_HEADER_NAME, GREPTIME_LOG_STRUCTURE_HEADER_NAME,
+    GREPTIME_LOG_TABLE_NAME_HEADER_NAME, GREPTIME_PIPELINE_NAME_HEADER_NAME,
+    GREPTIME_PIPELINE_PARAMS_HEADER, GREPTIME_PIPELINE_VERSION_HEADER_NAME,
+    GREPTIME_TRACE_TABLE_NAME_HEADER_NAME,
 };
 
 /// Axum extractor for optional target log table name from HTTP header
@@ -88,6 +89,38 @@ where
     }
 }
 
+/// Axum extractor for how to store map-typed OTLP log bodies from HTTP header
+/// using [`GREPTIME_LOG_STRUCTURE_HEADER_NAME`] as key.
+/// See [`LogBodyStructure`] for more details.
+pub struct LogStructure(pub LogBodyStructure);
+
+impl<S> FromRequestParts<S> for LogStructure
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let structure =
+            string_value_from_header(&parts.headers, &[GREPTIME_LOG_STRUCTURE_HEADER_NAME])?;
+
+        match structure {
+            Some(structure) => LogBodyStructure::parse(&structure)
+                .map(LogStructure)
+                .ok_or_else(|| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        format!(
+                            "`{}` header must be `flatten` or `json`, found `{}`.",
+                            GREPTIME_LOG_STRUCTURE_HEADER_NAME, structure
+                        ),
+                    )
+                }),
+            None => Ok(LogStructure(LogBodyStructure::default())),
+        }
+    }
+}
+
 /// Axum extractor for optional Pipeline name and version
 /// from HTTP headers.
 pub struct PipelineInfo {
@@ -20,13 +20,14 @@ use axum::extract::{Json, Query, State};
 use axum::response::{IntoResponse, Response};
 use axum::{Extension, Form};
 use common_catalog::parse_catalog_and_schema_from_db_string;
-use common_error::ext::ErrorExt;
+use common_error::ext::{BoxedError, ErrorExt};
 use common_error::status_code::StatusCode;
 use common_plugins::GREPTIME_EXEC_WRITE_COST;
 use common_query::{Output, OutputData};
 use common_recordbatch::util;
 use common_telemetry::tracing;
-use query::parser::{PromQuery, DEFAULT_LOOKBACK_STRING};
+use query::parser::{PromQuery, DEFAULT_LOOKBACK_STRING, PROMQL_METRIC_NAME_COLUMN_KEY};
+use query::promql::error::Error as PromqlError;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use session::context::{Channel, QueryContext, QueryContextRef};
@@ -35,20 +36,24 @@ use sql::dialect::GreptimeDbDialect;
 use sql::parser::{ParseOptions, ParserContext};
 use sql::statements::statement::Statement;
 
+use crate::drain::DrainStateRef;
 use crate::error::{FailedToParseQuerySnafu, InvalidQuerySnafu, Result};
 use crate::http::header::collect_plan_metrics;
+use crate::http::prometheus::add_field_name_matcher;
 use crate::http::result::arrow_result::ArrowResponse;
 use crate::http::result::csv_result::CsvResponse;
-use crate::http::result::error_result::ErrorResponse;
+use crate::http::result::error_result::{ErrorPosition, ErrorResponse};
 use crate::http::result::greptime_result_v1::GreptimedbV1Response;
 use crate::http::result::influxdb_result_v1::InfluxdbV1Response;
 use crate::http::result::json_result::JsonResponse;
+use crate::http::result::prometheus_resp::PrometheusJsonResponse;
 use crate::http::result::table_result::TableResponse;
 use crate::http::{
     ApiState, Epoch, GreptimeOptionsConfigState, GreptimeQueryOutput, HttpRecordsOutput,
     HttpResponse, ResponseFormat,
 };
 use crate::metrics_handler::MetricsHandler;
+use crate::prom_store::FIELD_NAME_LABEL;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -92,7 +97,6 @@ pub async fn sql(
     let db = query_ctx.get_db_string();
 
     query_ctx.set_channel(Channel::Http);
-    let query_ctx = Arc::new(query_ctx);
 
     let _timer = crate::metrics::METRIC_HTTP_SQL_ELAPSED
         .with_label_values(&[db.as_str()])
@@ -251,6 +255,30 @@ pub struct PromqlQuery {
     pub step: String,
     pub lookback: Option<String>,
     pub db: Option<String>,
+    // (Optional) only query this field column of the selected tables
+    pub field: Option<String>,
+    // (Optional) result format: [`greptimedb_v1`, `prometheus`, `csv`],
+    // the default value is `greptimedb_v1`
+    pub format: Option<String>,
+}
+
+/// Result formats supported by the promql handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PromqlResponseFormat {
+    GreptimedbV1,
+    Prometheus,
+    Csv,
+}
+
+impl PromqlResponseFormat {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "greptime" | "greptimedb_v1" => Some(PromqlResponseFormat::GreptimedbV1),
+            "prometheus" => Some(PromqlResponseFormat::Prometheus),
+            "csv" => Some(PromqlResponseFormat::Csv),
+            _ => None,
+        }
+    }
 }
 
 impl From<PromqlQuery> for PromQuery {
@@ -277,28 +305,197 @@ pub async fn promql(
 ) -> Response {
     let sql_handler = &state.sql_handler;
     let exec_start = Instant::now();
+    if let Some(db) = &params.db {
+        let (catalog, schema) = parse_catalog_and_schema_from_db_string(db);
+        query_ctx.set_current_catalog(&catalog);
+        query_ctx.set_current_schema(&schema);
+    }
     let db = query_ctx.get_db_string();
 
     query_ctx.set_channel(Channel::Http);
-    let query_ctx = Arc::new(query_ctx);
 
     let _timer = crate::metrics::METRIC_HTTP_PROMQL_ELAPSED
         .with_label_values(&[db.as_str()])
         .start_timer();
 
-    let resp = if let Some((status, msg)) =
-        validate_schema(sql_handler.clone(), query_ctx.clone()).await
-    {
-        let resp = ErrorResponse::from_error_message(status, msg);
+    let error_response = |resp: ErrorResponse| {
         HttpResponse::Error(resp)
-    } else {
-        let prom_query = params.into();
-        let outputs = sql_handler.do_promql_query(&prom_query, query_ctx).await;
-        GreptimedbV1Response::from_output(outputs).await
+            .with_execution_time(exec_start.elapsed().as_millis() as u64)
+            .into_response()
+    };
+
+    let format = match &params.format {
+        None => PromqlResponseFormat::GreptimedbV1,
+        Some(format) => match PromqlResponseFormat::parse(&format.to_lowercase()) {
+            Some(format) => format,
+            None => {
+                return error_response(ErrorResponse::from_error_message(
+                    StatusCode::InvalidArguments,
+                    format!(
+                        "Unsupported format: {format}, expected one of prometheus, greptime, csv"
+                    ),
+                ))
+            }
+        },
+    };
+    if format == PromqlResponseFormat::Prometheus {
+        // The `__name__` label of the result series is returned as a column.
+        query_ctx.set_extension(PROMQL_METRIC_NAME_COLUMN_KEY, "true");
+    }
+    let query_ctx = Arc::new(query_ctx);
+
+    if let Some((status, msg)) = validate_schema(sql_handler.clone(), query_ctx.clone()).await {
+        return error_response(ErrorResponse::from_error_message(status, msg));
+    }
+
+    let mut promql_expr = match promql_parser::parser::parse(&params.query) {
+        Ok(expr) => expr,
+        Err(reason) => {
+            return error_response(ErrorResponse::from_error_message(
+                StatusCode::InvalidSyntax,
+                reason,
+            ))
+        }
+    };
+    let query = params.query.clone();
+    let field = params.field.clone();
+    let mut prom_query: PromQuery = params.into();
+    if let Some(field) = &field {
+        add_field_name_matcher(&mut promql_expr, field);
+        prom_query.query = promql_expr.to_string();
+    }
+
+    let output = sql_handler
+        .do_promql_query(&prom_query, query_ctx)
+        .await
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| {
+            InvalidQuerySnafu {
+                reason: "promql query returns no output",
+            }
+            .fail()
+        });
+    let output = match output {
+        Ok(output) => output,
+        Err(err) => {
+            let position = locate_error_in_query(&query, &err);
+            return error_response(ErrorResponse::from_error(err).with_position(position));
+        }
     };
 
-    resp.with_execution_time(exec_start.elapsed().as_millis() as u64)
-        .into_response()
+    match format {
+        PromqlResponseFormat::GreptimedbV1 => GreptimedbV1Response::from_output(vec![Ok(output)])
+            .await
+            .with_execution_time(exec_start.elapsed().as_millis() as u64)
+            .into_response(),
+        PromqlResponseFormat::Csv => CsvResponse::from_output(vec![Ok(output)])
+            .await
+            .with_execution_time(exec_start.elapsed().as_millis() as u64)
+            .into_response(),
+        PromqlResponseFormat::Prometheus => {
+            PrometheusJsonResponse::from_query_result(Ok(output), promql_expr.value_type())
+                .await
+                .into_response()
+        }
+    }
+}
+
+/// Finds the position in `query` of the selector part that the planner failed on,
+/// from the structured cause of the error. A missing field column is located at
+/// the value of its `__field__` matcher.
+fn locate_error_in_query(
+    query: &str,
+    err: &(dyn std::error::Error + 'static),
+) -> Option<ErrorPosition> {
+    match find_cause::<PromqlError>(err)? {
+        PromqlError::ColumnNotFound { col, .. } => {
+            locate_matcher_value(query, FIELD_NAME_LABEL, col)
+        }
+        _ => None,
+    }
+}
+
+/// Finds the first error of type `T` in the chain of `err`, including the errors
+/// wrapped in [BoxedError]s.
+fn find_cause<T: std::error::Error + 'static>(
+    mut err: &(dyn std::error::Error + 'static),
+) -> Option<&T> {
+    loop {
+        if let Some(cause) = err.downcast_ref::<T>() {
+            return Some(cause);
+        }
+        // The source of a boxed error skips the error it wraps.
+        if let Some(cause) = err
+            .downcast_ref::<BoxedError>()
+            .and_then(|boxed| boxed.as_any().downcast_ref::<T>())
+        {
+            return Some(cause);
+        }
+        err = err.source()?;
+    }
+}
+
+/// Returns the position of the value of the first `name` label matcher in `query`
+/// whose value is `value`, without the quotes.
+fn locate_matcher_value(query: &str, name: &str, value: &str) -> Option<ErrorPosition> {
+    let bytes = query.as_bytes();
+    let is_ident_byte = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b':';
+    let skip_whitespace = |mut idx: usize| {
+        while bytes.get(idx).is_some_and(u8::is_ascii_whitespace) {
+            idx += 1;
+        }
+        idx
+    };
+
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let b = bytes[idx];
+        if matches!(b, b'"' | b'\'' | b'`') {
+            // skip string literals, escapes don't apply to raw strings
+            idx += 1;
+            while idx < bytes.len() && bytes[idx] != b {
+                idx += if b != b'`' && bytes[idx] == b'\\' {
+                    2
+                } else {
+                    1
+                };
+            }
+            idx += 1;
+            continue;
+        }
+        if !is_ident_byte(b) {
+            idx += 1;
+            continue;
+        }
+
+        let ident_start = idx;
+        while idx < bytes.len() && is_ident_byte(bytes[idx]) {
+            idx += 1;
+        }
+        if &query[ident_start..idx] != name {
+            continue;
+        }
+        let mut cursor = skip_whitespace(idx);
+        match bytes.get(cursor..cursor + 2) {
+            Some(b"!=" | b"=~" | b"!~") => cursor += 2,
+            _ if bytes.get(cursor) == Some(&b'=') => cursor += 1,
+            _ => continue,
+        }
+        cursor = skip_whitespace(cursor);
+        let Some(&quote) = bytes.get(cursor) else {
+            continue;
+        };
+        if !matches!(quote, b'"' | b'\'' | b'`') {
+            continue;
+        }
+        let start = cursor + 1;
+        let end = start + value.len();
+        if query.get(start..end) == Some(value) && bytes.get(end) == Some(&quote) {
+            return Some(ErrorPosition { start, end });
+        }
+    }
+    None
 }
 
 /// Handler to export metrics
@@ -334,6 +531,22 @@ pub async fn health(Query(_params): Query<HealthQuery>) -> Json<HealthResponse>
     Json(HealthResponse {})
 }
 
+/// Handler to export readiness check
+///
+/// Returns status "503 Service Unavailable" once the node starts draining for shutdown.
+#[axum_macros::debug_handler]
+pub async fn ready(
+    State(drain_state): State<Option<DrainStateRef>>,
+    Query(_params): Query<HealthQuery>,
+) -> Response {
+    let status = if drain_state.is_some_and(|state| state.is_draining()) {
+        axum::http::StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        axum::http::StatusCode::OK
+    };
+    (status, Json(HealthResponse {})).into_response()
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct StatusResponse<'a> {
     pub source_time: &'a str,
@@ -46,6 +46,7 @@ pub mod constants {
     pub const GREPTIME_DB_HEADER_READ_PREFERENCE: &str = "x-greptime-read-preference";
     pub const GREPTIME_TIMEZONE_HEADER_NAME: &str = "x-greptime-timezone";
     pub const GREPTIME_DB_HEADER_ERROR_CODE: &str = common_error::GREPTIME_DB_HEADER_ERROR_CODE;
+    pub const GREPTIME_DB_HEADER_REJECTED_ROWS: &str = "x-greptime-rejected-rows";
 
     // Deprecated: pipeline is also used with trace, so we remove log from it.
     pub const GREPTIME_LOG_PIPELINE_NAME_HEADER_NAME: &str = "x-greptime-log-pipeline-name";
@@ -57,6 +58,7 @@ pub mod constants {
 
     pub const GREPTIME_LOG_TABLE_NAME_HEADER_NAME: &str = "x-greptime-log-table-name";
     pub const GREPTIME_LOG_EXTRACT_KEYS_HEADER_NAME: &str = "x-greptime-log-extract-keys";
+    pub const GREPTIME_LOG_STRUCTURE_HEADER_NAME: &str = "x-greptime-log-structure";
     pub const GREPTIME_TRACE_TABLE_NAME_HEADER_NAME: &str = "x-greptime-trace-table-name";
 
     /// The header key that contains the pipeline params.
@@ -70,6 +72,11 @@ pub static GREPTIME_DB_HEADER_EXECUTION_TIME: HeaderName =
 pub static GREPTIME_DB_HEADER_METRICS: HeaderName =
     HeaderName::from_static(constants::GREPTIME_DB_HEADER_METRICS);
 
+/// Header key of the number of rows rejected by a write, e.g. rows out of the
+/// `write.time_bounds` of tables.
+pub static GREPTIME_DB_HEADER_REJECTED_ROWS: HeaderName =
+    HeaderName::from_static(constants::GREPTIME_DB_HEADER_REJECTED_ROWS);
+
 /// Header key of `db-name`. Example format of the header value is `greptime-public`.
 pub static GREPTIME_DB_HEADER_NAME: HeaderName =
     HeaderName::from_static(constants::GREPTIME_DB_HEADER_NAME);
@@ -82,6 +89,11 @@ pub static GREPTIME_TIMEZONE_HEADER_NAME: HeaderName =
 pub static GREPTIME_DB_HEADER_READ_PREFERENCE: HeaderName =
     HeaderName::from_static(constants::GREPTIME_DB_HEADER_READ_PREFERENCE);
 
+/// Header key of the number of samples written by a Prometheus remote write,
+/// as in the remote write 2.0 spec.
+pub static PROM_REMOTE_WRITE_SAMPLES_WRITTEN: HeaderName =
+    HeaderName::from_static("x-prometheus-remote-write-samples-written");
+
 pub static CONTENT_TYPE_PROTOBUF_STR: &str = "application/x-protobuf";
 pub static CONTENT_TYPE_PROTOBUF: HeaderValue = HeaderValue::from_static(CONTENT_TYPE_PROTOBUF_STR);
 pub static CONTENT_ENCODING_SNAPPY: HeaderValue = HeaderValue::from_static("snappy");
@@ -140,6 +152,16 @@ pub fn write_cost_header_map(cost: usize) -> HeaderMap {
     header_map
 }
 
+/// Inserts the number of rows rejected by a write to the `header_map`, if any.
+pub fn insert_rejected_rows_header(header_map: &mut HeaderMap, rejected_rows: usize) {
+    if rejected_rows > 0 {
+        let _ = header_map.insert(
+            &GREPTIME_DB_HEADER_REJECTED_ROWS,
+            HeaderValue::from(rejected_rows),
+        );
+    }
+}
+
 fn collect_into_maps(name: &str, value: u64, maps: &mut [&mut HashMap<String, u64>]) {
     if name.starts_with(GREPTIME_EXEC_PREFIX) && value > 0 {
         maps.iter_mut().for_each(|map| {
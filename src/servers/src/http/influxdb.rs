@@ -18,14 +18,14 @@ use std::sync::Arc;
 use axum::extract::{Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use axum::Extension;
+use axum::{Extension, Json};
 use common_catalog::consts::DEFAULT_SCHEMA_NAME;
 use common_grpc::precision::Precision;
 use common_telemetry::tracing;
 use session::context::{Channel, QueryContext, QueryContextRef};
 
 use crate::error::{Result, TimePrecisionSnafu};
-use crate::http::header::write_cost_header_map;
+use crate::http::header::{insert_rejected_rows_header, write_cost_header_map};
 use crate::influxdb::InfluxdbRequest;
 use crate::query_handler::InfluxdbLineProtocolHandlerRef;
 
@@ -101,10 +101,18 @@ pub async fn influxdb_write(
     let request = InfluxdbRequest { precision, lines };
     let output = handler.exec(request, ctx).await?;
 
-    Ok((
-        StatusCode::NO_CONTENT,
-        write_cost_header_map(output.meta.cost),
-    ))
+    let mut header_map = write_cost_header_map(output.meta.cost);
+    let rejected_rows = output.meta.rejected_rows;
+    if rejected_rows > 0 {
+        // Like InfluxDB, reports a partial write while the other points are written.
+        insert_rejected_rows_header(&mut header_map, rejected_rows);
+        let body = serde_json::json!({
+            "error": format!("partial write: points beyond write time bounds dropped={rejected_rows}"),
+        });
+        return Ok((StatusCode::BAD_REQUEST, header_map, Json(body)).into_response());
+    }
+
+    Ok((StatusCode::NO_CONTENT, header_map).into_response())
 }
 
 fn parse_time_precision(value: &str) -> Result<Precision> {
@@ -0,0 +1,1039 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ingests CSV or NDJSON payloads by mapping their columns to the columns of
+//! the target table, without a pipeline.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::io::Cursor;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use api::helper::{value_to_grpc_value, ColumnDataTypeWrapper};
+use api::v1::{ColumnSchema, Row, RowInsertRequest, RowInsertRequests, Rows, SemanticType};
+use arrow::array::{Array, ArrayBuilder, AsArray, BinaryBuilder, UInt64Builder};
+use arrow::csv::reader::Format;
+use arrow::csv::ReaderBuilder;
+use arrow::record_batch::RecordBatch;
+use arrow_schema::{ArrowError, DataType as ArrowDataType, Field, Schema as ArrowSchema};
+use axum::body::Bytes;
+use bytes::BytesMut;
+use chrono::{DateTime, NaiveDateTime};
+use common_datasource::file_format::{decode_stream, ArrowDecoder};
+use common_query::OutputData;
+use common_time::timestamp::TimeUnit;
+use common_time::Timestamp;
+use datatypes::data_type::ConcreteDataType;
+use datatypes::types::cast::CastOption;
+use datatypes::types::cast_with_opt;
+use datatypes::value::Value;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use session::context::QueryContextRef;
+use snafu::{OptionExt, ResultExt};
+use table::Table;
+
+use crate::error::{CatalogSnafu, InternalIoSnafu, InvalidParameterSnafu, Result};
+use crate::http::event::LogIngesterQueryParams;
+use crate::http::result::greptime_result_v1::GreptimedbV1Response;
+use crate::http::{GreptimeQueryOutput, HttpResponse};
+use crate::query_handler::PipelineHandlerRef;
+
+/// The maximum number of row errors reported in the response.
+pub const MAX_REPORTED_ERRORS: usize = 100;
+
+/// The number of rows in each insert request.
+const INSERT_BATCH_SIZE: usize = 4096;
+
+/// The chunks of the request body.
+pub(crate) type PayloadStream = BoxStream<'static, std::io::Result<Bytes>>;
+
+/// The rows rejected while ingesting a payload.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IngestReport {
+    /// The number of rejected rows.
+    pub failed_rows: usize,
+    /// The errors of the first [MAX_REPORTED_ERRORS] rejected rows.
+    pub errors: Vec<RowError>,
+}
+
+impl IngestReport {
+    fn add_error(&mut self, row: usize, error: String) {
+        self.failed_rows += 1;
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(RowError { row, error });
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowError {
+    /// The 1-based row number in the payload: the line number for NDJSON and
+    /// the record number (excluding the header) for CSV.
+    pub row: usize,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IngestFormat {
+    Csv,
+    Ndjson,
+}
+
+impl FromStr for IngestFormat {
+    type Err = crate::error::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(IngestFormat::Csv),
+            "ndjson" | "json" => Ok(IngestFormat::Ndjson),
+            _ => InvalidParameterSnafu {
+                reason: format!("unsupported format: {s}, expected csv or ndjson"),
+            }
+            .fail(),
+        }
+    }
+}
+
+/// How the values of the time column are parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TimeFormat {
+    Rfc3339,
+    /// Integers since the unix epoch in the unit.
+    Epoch(TimeUnit),
+    /// A chrono strftime pattern, interpreted as UTC if it has no offset.
+    Pattern(String),
+}
+
+impl From<&str> for TimeFormat {
+    fn from(s: &str) -> Self {
+        match s {
+            "rfc3339" => TimeFormat::Rfc3339,
+            "epoch_s" => TimeFormat::Epoch(TimeUnit::Second),
+            "epoch_ms" => TimeFormat::Epoch(TimeUnit::Millisecond),
+            "epoch_us" => TimeFormat::Epoch(TimeUnit::Microsecond),
+            "epoch_ns" => TimeFormat::Epoch(TimeUnit::Nanosecond),
+            pattern => TimeFormat::Pattern(pattern.to_string()),
+        }
+    }
+}
+
+impl Display for TimeFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeFormat::Rfc3339 => write!(f, "rfc3339"),
+            TimeFormat::Epoch(unit) => write!(f, "epoch in {unit}"),
+            TimeFormat::Pattern(pattern) => write!(f, "'{pattern}'"),
+        }
+    }
+}
+
+impl TimeFormat {
+    fn parse(&self, raw: RawValue) -> std::result::Result<Timestamp, String> {
+        let timestamp = match self {
+            TimeFormat::Epoch(unit) => {
+                let value = match raw {
+                    RawValue::Json(JsonValue::Number(n)) => n.as_i64(),
+                    _ => raw.as_str().and_then(|s| s.trim().parse::<i64>().ok()),
+                };
+                value.map(|v| Timestamp::new(v, *unit))
+            }
+            TimeFormat::Rfc3339 => raw
+                .as_str()
+                .and_then(|s| DateTime::parse_from_rfc3339(s.trim()).ok())
+                .and_then(|dt| Timestamp::from_chrono_datetime(dt.naive_utc())),
+            TimeFormat::Pattern(pattern) => raw
+                .as_str()
+                .and_then(|s| {
+                    DateTime::parse_from_str(s.trim(), pattern)
+                        .map(|dt| dt.naive_utc())
+                        .or_else(|_| NaiveDateTime::parse_from_str(s.trim(), pattern))
+                        .ok()
+                })
+                .and_then(Timestamp::from_chrono_datetime),
+        };
+        timestamp.ok_or_else(|| format!("invalid timestamp {raw}, expected {self}"))
+    }
+}
+
+/// The options of mapping ingestion, parsed from [LogIngesterQueryParams].
+#[derive(Debug)]
+pub(crate) struct MappingOptions {
+    format: IngestFormat,
+    time_column: Option<String>,
+    time_format: TimeFormat,
+    tags: HashSet<String>,
+}
+
+impl MappingOptions {
+    pub(crate) fn from_params(params: &LogIngesterQueryParams) -> Result<Self> {
+        let format = params
+            .format
+            .as_deref()
+            .context(InvalidParameterSnafu {
+                reason: "format is required",
+            })?
+            .parse()?;
+        let tags = params
+            .tags
+            .as_deref()
+            .map(|tags| {
+                tags.split(',')
+                    .map(|tag| tag.trim().to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            format,
+            time_column: params.time_column.clone(),
+            time_format: params
+                .time_format
+                .as_deref()
+                .map(TimeFormat::from)
+                .unwrap_or(TimeFormat::Rfc3339),
+            tags,
+        })
+    }
+}
+
+/// A value in the payload.
+#[derive(Debug, Clone, Copy)]
+enum RawValue<'a> {
+    Null,
+    /// A CSV value, which is always a string.
+    Csv(&'a str),
+    Json(&'a JsonValue),
+}
+
+impl<'a> RawValue<'a> {
+    fn json(value: &'a JsonValue) -> Self {
+        if value.is_null() {
+            RawValue::Null
+        } else {
+            RawValue::Json(value)
+        }
+    }
+
+    fn as_str(&self) -> Option<&'a str> {
+        match self {
+            RawValue::Csv(s) => Some(s),
+            RawValue::Json(JsonValue::String(s)) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl Display for RawValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RawValue::Null => write!(f, "null"),
+            RawValue::Csv(s) => write!(f, "{s:?}"),
+            RawValue::Json(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// A batch of records parsed from the payload.
+#[derive(Debug)]
+enum Records {
+    /// CSV records with all values read as strings, and the row number of the
+    /// first record.
+    Csv {
+        batch: RecordBatch,
+        first_row: usize,
+    },
+    /// The line number and the fields of each NDJSON record, or why it can't
+    /// be parsed.
+    Ndjson(Vec<(usize, std::result::Result<Map<String, JsonValue>, String>)>),
+}
+
+impl Records {
+    /// Converts a batch decoded from the payload, `next_row` is the row number of
+    /// the first CSV record in the batch.
+    fn new(format: IngestFormat, batch: RecordBatch, next_row: &mut usize) -> Self {
+        match format {
+            IngestFormat::Csv => {
+                let first_row = *next_row;
+                *next_row += batch.num_rows();
+                Records::Csv { batch, first_row }
+            }
+            IngestFormat::Ndjson => {
+                let rows = batch
+                    .column(0)
+                    .as_primitive::<arrow::datatypes::UInt64Type>();
+                let lines = batch.column(1).as_binary::<i32>();
+                let records = rows
+                    .values()
+                    .iter()
+                    .zip(lines.iter().flatten())
+                    .map(|(row, line)| (*row as usize, parse_json_line(line)))
+                    .collect();
+                Records::Ndjson(records)
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Records::Csv { batch, .. } => batch.num_rows() == 0,
+            Records::Ndjson(rows) => rows.is_empty(),
+        }
+    }
+
+    /// Returns the column names in the order they first appear.
+    fn columns(&self) -> Vec<String> {
+        match self {
+            Records::Csv { batch, .. } => batch
+                .schema()
+                .fields()
+                .iter()
+                .map(|field| field.name().clone())
+                .collect(),
+            Records::Ndjson(rows) => {
+                let mut seen = HashSet::new();
+                rows.iter()
+                    .filter_map(|(_, fields)| fields.as_ref().ok())
+                    .flat_map(|fields| fields.keys())
+                    .filter(|name| seen.insert(*name))
+                    .cloned()
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns the non-null values of the column.
+    fn values<'a>(&'a self, column: &'a str) -> Vec<RawValue<'a>> {
+        match self {
+            Records::Csv { batch, .. } => batch
+                .column_by_name(column)
+                .map(|array| array.as_string::<i32>().iter().flatten())
+                .into_iter()
+                .flatten()
+                .map(RawValue::Csv)
+                .collect(),
+            Records::Ndjson(rows) => rows
+                .iter()
+                .filter_map(|(_, fields)| fields.as_ref().ok()?.get(column))
+                .map(RawValue::json)
+                .filter(|value| !matches!(value, RawValue::Null))
+                .collect(),
+        }
+    }
+}
+
+fn parse_json_line(line: &[u8]) -> std::result::Result<Map<String, JsonValue>, String> {
+    let line = std::str::from_utf8(line).map_err(|e| format!("invalid line: {e}"))?;
+    match serde_json::from_str::<JsonValue>(line) {
+        Ok(JsonValue::Object(fields)) => Ok(fields),
+        Ok(_) => Err("expected a JSON object".to_string()),
+        Err(e) => Err(format!("invalid JSON: {e}")),
+    }
+}
+
+/// Splits the NDJSON payload into batches of the line numbers and the lines,
+/// empty lines are skipped. The lines are parsed one by one afterwards, so a
+/// malformed line only rejects itself and the objects may have different fields.
+#[derive(Default)]
+struct NdjsonLineDecoder {
+    /// The bytes of the incomplete line at the end of the decoded bytes.
+    partial: Vec<u8>,
+    num_lines: usize,
+    rows: UInt64Builder,
+    lines: BinaryBuilder,
+}
+
+impl NdjsonLineDecoder {
+    fn push_line(&mut self, line: &[u8]) {
+        self.num_lines += 1;
+        if !line.trim_ascii().is_empty() {
+            self.rows.append_value(self.num_lines as u64);
+            self.lines.append_value(line);
+        }
+    }
+}
+
+impl ArrowDecoder for NdjsonLineDecoder {
+    fn decode(&mut self, buf: &[u8]) -> std::result::Result<usize, ArrowError> {
+        if buf.is_empty() {
+            // The last line may not end with a line break.
+            if !self.partial.is_empty() {
+                let line = std::mem::take(&mut self.partial);
+                self.push_line(&line);
+            }
+            return Ok(0);
+        }
+
+        let mut read = 0;
+        while self.rows.len() < INSERT_BATCH_SIZE {
+            let Some(len) = buf[read..].iter().position(|b| *b == b'\n') else {
+                self.partial.extend_from_slice(&buf[read..]);
+                return Ok(buf.len());
+            };
+            let line = &buf[read..read + len];
+            if self.partial.is_empty() {
+                self.push_line(line);
+            } else {
+                let mut partial = std::mem::take(&mut self.partial);
+                partial.extend_from_slice(line);
+                self.push_line(&partial);
+            }
+            read += len + 1;
+        }
+        Ok(read)
+    }
+
+    fn flush(&mut self) -> std::result::Result<Option<RecordBatch>, ArrowError> {
+        if self.rows.is_empty() {
+            return Ok(None);
+        }
+        let schema = ArrowSchema::new(vec![
+            Field::new("row", ArrowDataType::UInt64, false),
+            Field::new("line", ArrowDataType::Binary, false),
+        ]);
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(self.rows.finish()), Arc::new(self.lines.finish())],
+        )
+        .map(Some)
+    }
+}
+
+/// Decodes the payload into batches of at most [INSERT_BATCH_SIZE] records.
+///
+/// CSV values are all read as strings and empty values are nulls, the column
+/// names are read from the header before the records.
+async fn decode_payload(
+    format: IngestFormat,
+    mut payload: PayloadStream,
+) -> Result<BoxStream<'static, std::result::Result<RecordBatch, ArrowError>>> {
+    // Empty chunks are the end of the payload for the decoders.
+    let payload = match format {
+        IngestFormat::Csv => {
+            let mut head = BytesMut::new();
+            while !head.contains(&b'\n') {
+                match payload.next().await {
+                    Some(chunk) => head.extend_from_slice(&chunk.context(InternalIoSnafu)?),
+                    None => break,
+                }
+            }
+            let (header, _) = Format::default()
+                .with_header(true)
+                .infer_schema(Cursor::new(&head), Some(0))
+                .map_err(invalid_payload)?;
+            let schema = ArrowSchema::new(
+                header
+                    .fields()
+                    .iter()
+                    .map(|field| Field::new(field.name(), ArrowDataType::Utf8, true))
+                    .collect::<Vec<_>>(),
+            );
+            let decoder = ReaderBuilder::new(Arc::new(schema))
+                .with_header(true)
+                .with_batch_size(INSERT_BATCH_SIZE)
+                .build_decoder();
+
+            let head = futures::stream::iter([Ok(head.freeze())]);
+            let payload = head
+                .chain(payload)
+                .filter(|chunk| futures::future::ready(!matches!(chunk, Ok(c) if c.is_empty())));
+            decode_stream(payload, decoder)
+        }
+        IngestFormat::Ndjson => {
+            let payload = payload
+                .filter(|chunk| futures::future::ready(!matches!(chunk, Ok(c) if c.is_empty())));
+            decode_stream(payload, NdjsonLineDecoder::default())
+        }
+    };
+    Ok(payload)
+}
+
+fn invalid_payload(e: ArrowError) -> crate::error::Error {
+    InvalidParameterSnafu {
+        reason: format!("invalid payload: {e}"),
+    }
+    .build()
+}
+
+/// A column of the target table and the payload column it's mapped from.
+#[derive(Debug)]
+struct MappedColumn {
+    source: String,
+    schema: ColumnSchema,
+    data_type: ConcreteDataType,
+}
+
+/// Maps the payload `columns` to the table columns. Columns absent in the table
+/// take the types inferred from the `records` and the table is altered (or
+/// created) on insertion.
+fn map_columns(
+    options: &MappingOptions,
+    columns: &[String],
+    records: &Records,
+    table: Option<&Table>,
+) -> Result<Vec<MappedColumn>> {
+    let table_schema = table.map(|table| table.schema());
+    let table_time_index = table_schema
+        .as_ref()
+        .and_then(|schema| schema.timestamp_column())
+        .map(|column| column.name.clone());
+    let time_column = options
+        .time_column
+        .clone()
+        .or_else(|| table_time_index.clone())
+        .context(InvalidParameterSnafu {
+            reason: "time_column is required",
+        })?;
+    let primary_keys = table
+        .map(|table| {
+            table
+                .table_info()
+                .meta
+                .row_key_column_names()
+                .cloned()
+                .collect::<HashSet<_>>()
+        })
+        .unwrap_or_default();
+
+    let mut mapped = Vec::with_capacity(columns.len());
+    if !columns.contains(&time_column) {
+        return InvalidParameterSnafu {
+            reason: format!("time column {time_column} is not found in the payload"),
+        }
+        .fail();
+    }
+    for source in columns {
+        let is_time_index = *source == time_column;
+        let name = if is_time_index {
+            table_time_index.clone().unwrap_or_else(|| source.clone())
+        } else {
+            source.clone()
+        };
+        let table_column = table_schema
+            .as_ref()
+            .and_then(|schema| schema.column_schema_by_name(&name));
+        let data_type = match table_column {
+            Some(column) => column.data_type.clone(),
+            None if is_time_index => ConcreteDataType::timestamp_millisecond_datatype(),
+            None => infer_data_type(records, source),
+        };
+        let semantic_type = if is_time_index {
+            SemanticType::Timestamp
+        } else if primary_keys.contains(&name)
+            || (table_column.is_none() && options.tags.contains(&name))
+        {
+            SemanticType::Tag
+        } else {
+            SemanticType::Field
+        };
+        let (datatype, datatype_extension) = ColumnDataTypeWrapper::try_from(data_type.clone())
+            .map_err(|e| {
+                InvalidParameterSnafu {
+                    reason: format!("unsupported type of column {name}: {e}"),
+                }
+                .build()
+            })?
+            .to_parts();
+
+        mapped.push(MappedColumn {
+            source: source.clone(),
+            schema: ColumnSchema {
+                column_name: name,
+                datatype: datatype as i32,
+                semantic_type: semantic_type as i32,
+                datatype_extension,
+                options: None,
+            },
+            data_type,
+        });
+    }
+
+    Ok(mapped)
+}
+
+/// The candidate types of a new column, from the narrowest to the widest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InferredType {
+    Boolean,
+    Int64,
+    Float64,
+    String,
+}
+
+impl InferredType {
+    fn of(value: RawValue) -> Option<Self> {
+        match value {
+            RawValue::Null => None,
+            RawValue::Csv(s) => {
+                if s.parse::<i64>().is_ok() {
+                    Some(InferredType::Int64)
+                } else if s.parse::<f64>().is_ok() {
+                    Some(InferredType::Float64)
+                } else if s.parse::<bool>().is_ok() {
+                    Some(InferredType::Boolean)
+                } else {
+                    Some(InferredType::String)
+                }
+            }
+            RawValue::Json(JsonValue::Null) => None,
+            RawValue::Json(JsonValue::Bool(_)) => Some(InferredType::Boolean),
+            RawValue::Json(JsonValue::Number(n)) if n.is_i64() => Some(InferredType::Int64),
+            RawValue::Json(JsonValue::Number(_)) => Some(InferredType::Float64),
+            RawValue::Json(JsonValue::String(_) | JsonValue::Array(_) | JsonValue::Object(_)) => {
+                Some(InferredType::String)
+            }
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (InferredType::Int64, InferredType::Float64)
+            | (InferredType::Float64, InferredType::Int64) => InferredType::Float64,
+            _ => InferredType::String,
+        }
+    }
+}
+
+fn infer_data_type(records: &Records, column: &str) -> ConcreteDataType {
+    let inferred = records
+        .values(column)
+        .into_iter()
+        .filter_map(InferredType::of)
+        .reduce(InferredType::merge);
+    match inferred {
+        Some(InferredType::Boolean) => ConcreteDataType::boolean_datatype(),
+        Some(InferredType::Int64) => ConcreteDataType::int64_datatype(),
+        Some(InferredType::Float64) => ConcreteDataType::float64_datatype(),
+        Some(InferredType::String) | None => ConcreteDataType::string_datatype(),
+    }
+}
+
+fn raw_to_value(raw: RawValue, data_type: &ConcreteDataType) -> Value {
+    let json = match raw {
+        RawValue::Null => return Value::Null,
+        RawValue::Csv(s) => return Value::String(s.into()),
+        RawValue::Json(json) => json,
+    };
+    match json {
+        JsonValue::Null => Value::Null,
+        JsonValue::String(s) => Value::String(s.as_str().into()),
+        _ if data_type.is_string() => Value::String(json.to_string().into()),
+        JsonValue::Bool(b) => Value::Boolean(*b),
+        JsonValue::Number(n) => {
+            if let Some(v) = n.as_i64() {
+                Value::Int64(v)
+            } else if let Some(v) = n.as_u64() {
+                Value::UInt64(v)
+            } else {
+                n.as_f64()
+                    .map(|v| Value::Float64(v.into()))
+                    .unwrap_or(Value::Null)
+            }
+        }
+        JsonValue::Array(_) | JsonValue::Object(_) => Value::String(json.to_string().into()),
+    }
+}
+
+/// Converts a record into a row of the columns, `value` returns the value of the
+/// column at the index.
+fn convert_row<'a>(
+    columns: &[MappedColumn],
+    value: impl Fn(usize) -> RawValue<'a>,
+    time_format: &TimeFormat,
+) -> std::result::Result<Row, String> {
+    let cast_option = CastOption { strict: true };
+    let values = columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            let name = &column.schema.column_name;
+            let raw = value(index);
+            let value = if column.schema.semantic_type == SemanticType::Timestamp as i32 {
+                if matches!(raw, RawValue::Null) {
+                    return Err(format!("missing value of time column {}", column.source));
+                }
+                let unit = column
+                    .data_type
+                    .as_timestamp()
+                    .map(|ty| ty.unit())
+                    .unwrap_or(TimeUnit::Millisecond);
+                time_format
+                    .parse(raw)?
+                    .convert_to(unit)
+                    .map(Value::Timestamp)
+                    .ok_or_else(|| format!("timestamp {raw} is out of range"))?
+            } else {
+                cast_with_opt(
+                    raw_to_value(raw, &column.data_type),
+                    &column.data_type,
+                    &cast_option,
+                )
+                .map_err(|_| {
+                    format!(
+                        "invalid value {raw} for column {name} of type {}",
+                        column.data_type
+                    )
+                })?
+            };
+            Ok(value_to_grpc_value(value))
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(Row { values })
+}
+
+/// Converts the records into rows of the columns, the rejected records are
+/// added to the report.
+fn convert_records(
+    columns: &[MappedColumn],
+    records: &Records,
+    time_format: &TimeFormat,
+    report: &mut IngestReport,
+) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let mut add_row = |row: usize, result: std::result::Result<Row, String>| match result {
+        Ok(converted) => rows.push(converted),
+        Err(error) => report.add_error(row, error),
+    };
+    match records {
+        Records::Csv { batch, first_row } => {
+            let arrays = columns
+                .iter()
+                .map(|column| {
+                    batch
+                        .column_by_name(&column.source)
+                        .map(|array| array.as_string::<i32>())
+                })
+                .collect::<Vec<_>>();
+            for i in 0..batch.num_rows() {
+                let value = |index: usize| match arrays[index] {
+                    Some(array) if !array.is_null(i) => RawValue::Csv(array.value(i)),
+                    _ => RawValue::Null,
+                };
+                add_row(first_row + i, convert_row(columns, value, time_format));
+            }
+        }
+        Records::Ndjson(records) => {
+            for (row, fields) in records {
+                let result = match fields {
+                    Ok(fields) => {
+                        let value = |index: usize| {
+                            fields
+                                .get(&columns[index].source)
+                                .map(RawValue::json)
+                                .unwrap_or(RawValue::Null)
+                        };
+                        convert_row(columns, value, time_format)
+                    }
+                    Err(error) => Err(error.clone()),
+                };
+                add_row(*row, result);
+            }
+        }
+    }
+    rows
+}
+
+/// Ingests the CSV or NDJSON payload into the table, see [MappingOptions].
+///
+/// The payload is decoded and inserted in batches, so it's never buffered as a
+/// whole. The table is created from the first batch if it doesn't exist and the
+/// `auto_create_table` hint allows it, and new columns in later batches are added
+/// to it. Rows that can't be converted are skipped and reported in the response.
+/// The batches before a malformed part of the payload stay inserted.
+pub(crate) async fn ingest_with_mapping(
+    handler: PipelineHandlerRef,
+    table_name: String,
+    options: MappingOptions,
+    payload: PayloadStream,
+    query_ctx: QueryContextRef,
+) -> Result<HttpResponse> {
+    let exec_timer = std::time::Instant::now();
+
+    let mut batches = decode_payload(options.format, payload).await?;
+    let mut next_row = 1;
+    let mut columns = Vec::new();
+    let mut mapped_columns = Vec::<MappedColumn>::new();
+    let mut report = IngestReport::default();
+    let mut affected_rows = 0;
+    while let Some(batch) = batches.next().await {
+        let records = Records::new(
+            options.format,
+            batch.map_err(invalid_payload)?,
+            &mut next_row,
+        );
+        if records.is_empty() {
+            continue;
+        }
+        let new_columns = records
+            .columns()
+            .into_iter()
+            .filter(|column| !columns.contains(column))
+            .collect::<Vec<_>>();
+        if !new_columns.is_empty() {
+            columns.extend(new_columns);
+            let table = handler
+                .get_table(&table_name, &query_ctx)
+                .await
+                .context(CatalogSnafu)?;
+            mapped_columns = map_columns(&options, &columns, &records, table.as_deref())?;
+        }
+
+        let rows = convert_records(&mapped_columns, &records, &options.time_format, &mut report);
+        if rows.is_empty() {
+            continue;
+        }
+        let requests = RowInsertRequests {
+            inserts: vec![RowInsertRequest {
+                table_name: table_name.clone(),
+                rows: Some(Rows {
+                    schema: mapped_columns
+                        .iter()
+                        .map(|column| column.schema.clone())
+                        .collect(),
+                    rows,
+                }),
+            }],
+        };
+        let output = handler.insert(requests, query_ctx.clone()).await?;
+        if let OutputData::AffectedRows(rows) = output.data {
+            affected_rows += rows;
+        }
+    }
+
+    let response = GreptimedbV1Response {
+        output: vec![GreptimeQueryOutput::AffectedRows(affected_rows)],
+        execution_time_ms: exec_timer.elapsed().as_millis() as u64,
+        resp_metrics: HashMap::new(),
+        ingest_report: None,
+    }
+    .with_ingest_report(report);
+    Ok(HttpResponse::GreptimedbV1(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::value::ValueData;
+    use futures::TryStreamExt;
+
+    use super::*;
+
+    fn options(format: IngestFormat, time_format: &str) -> MappingOptions {
+        MappingOptions {
+            format,
+            time_column: Some("ts".to_string()),
+            time_format: TimeFormat::from(time_format),
+            tags: HashSet::from(["host".to_string()]),
+        }
+    }
+
+    /// Decodes the payload, split into small chunks so records span chunks.
+    async fn decode(format: IngestFormat, payload: &str) -> Vec<Records> {
+        let chunks = payload
+            .as_bytes()
+            .chunks(7)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect::<Vec<_>>();
+        let batches = decode_payload(format, futures::stream::iter(chunks).boxed())
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let mut next_row = 1;
+        batches
+            .into_iter()
+            .map(|batch| Records::new(format, batch, &mut next_row))
+            .collect()
+    }
+
+    async fn ingest(
+        options: &MappingOptions,
+        payload: &str,
+    ) -> (Vec<MappedColumn>, Vec<Row>, IngestReport) {
+        let mut batches = decode(options.format, payload).await;
+        assert_eq!(1, batches.len());
+        let records = batches.pop().unwrap();
+        let columns = map_columns(options, &records.columns(), &records, None).unwrap();
+        let mut report = IngestReport::default();
+        let rows = convert_records(&columns, &records, &options.time_format, &mut report);
+        (columns, rows, report)
+    }
+
+    fn column_types(columns: &[MappedColumn]) -> Vec<(String, ConcreteDataType, i32)> {
+        columns
+            .iter()
+            .map(|c| {
+                (
+                    c.schema.column_name.clone(),
+                    c.data_type.clone(),
+                    c.schema.semantic_type,
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_ingest_csv() {
+        let payload = "host,cpu,ts,up\n\
+                       h1,0.5,2024-01-01T00:00:00Z,true\n\
+                       h2,1,not a time,false\n\
+                       h3,,2024-01-01T00:00:01.5Z,\n";
+        let (columns, rows, report) = ingest(&options(IngestFormat::Csv, "rfc3339"), payload).await;
+
+        assert_eq!(
+            vec![
+                (
+                    "host".to_string(),
+                    ConcreteDataType::string_datatype(),
+                    SemanticType::Tag as i32
+                ),
+                (
+                    "cpu".to_string(),
+                    ConcreteDataType::float64_datatype(),
+                    SemanticType::Field as i32
+                ),
+                (
+                    "ts".to_string(),
+                    ConcreteDataType::timestamp_millisecond_datatype(),
+                    SemanticType::Timestamp as i32
+                ),
+                (
+                    "up".to_string(),
+                    ConcreteDataType::boolean_datatype(),
+                    SemanticType::Field as i32
+                ),
+            ],
+            column_types(&columns)
+        );
+        assert_eq!(2, rows.len());
+        assert_eq!(
+            vec![
+                Some(ValueData::StringValue("h3".to_string())),
+                None,
+                Some(ValueData::TimestampMillisecondValue(1704067201500)),
+                None,
+            ],
+            rows[1]
+                .values
+                .iter()
+                .map(|v| v.value_data.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(1, report.failed_rows);
+        assert_eq!(2, report.errors[0].row);
+        assert!(
+            report.errors[0].error.contains("invalid timestamp"),
+            "{:?}",
+            report
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ingest_ndjson() {
+        let payload = r#"{"host": "h1", "cpu": 1, "ts": 1704067200}
+{"host": "h2", "cpu": "high", "ts": 1704067201}
+{"host": "h3", "cpu": 2, "ts": "yesterday"}
+
+not json
+{"host": "h4", "cpu": 3.5, "ts": "1704067202", "extra": {"a": 1}}"#;
+        let (columns, rows, report) =
+            ingest(&options(IngestFormat::Ndjson, "epoch_s"), payload).await;
+
+        let types = column_types(&columns);
+        assert_eq!(
+            ("cpu".to_string(), ConcreteDataType::string_datatype()),
+            (types[1].0.clone(), types[1].1.clone())
+        );
+        assert_eq!(
+            ("extra".to_string(), ConcreteDataType::string_datatype()),
+            (types[3].0.clone(), types[3].1.clone())
+        );
+        assert_eq!(3, rows.len());
+        assert_eq!(
+            Some(ValueData::TimestampMillisecondValue(1704067202000)),
+            rows[2].values[2].value_data
+        );
+        assert_eq!(
+            Some(ValueData::StringValue(r#"{"a":1}"#.to_string())),
+            rows[2].values[3].value_data
+        );
+        assert_eq!(2, report.failed_rows);
+        assert_eq!(
+            vec![3, 5],
+            report.errors.iter().map(|e| e.row).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decode_in_batches() {
+        let mut payload = String::from("ts,v\n");
+        for i in 0..INSERT_BATCH_SIZE + 1 {
+            payload.push_str(&format!("{i},{i}\n"));
+        }
+        let batches = decode(IngestFormat::Csv, &payload).await;
+        assert_eq!(2, batches.len());
+        let Records::Csv { batch, first_row } = &batches[1] else {
+            unreachable!()
+        };
+        assert_eq!((1, INSERT_BATCH_SIZE + 1), (batch.num_rows(), *first_row));
+
+        let mut payload = String::new();
+        for i in 0..INSERT_BATCH_SIZE + 1 {
+            payload.push_str(&format!("{{\"ts\": {i}}}\n\n"));
+        }
+        let batches = decode(IngestFormat::Ndjson, &payload).await;
+        assert_eq!(2, batches.len());
+        let Records::Ndjson(rows) = &batches[1] else {
+            unreachable!()
+        };
+        assert_eq!(1, rows.len());
+        // Empty lines are counted in the line numbers.
+        assert_eq!(2 * INSERT_BATCH_SIZE + 1, rows[0].0);
+    }
+
+    #[tokio::test]
+    async fn test_ingest_report_cap() {
+        let mut payload = String::from("ts,v\n");
+        for _ in 0..MAX_REPORTED_ERRORS + 10 {
+            payload.push_str("bad,1\n");
+        }
+        let (_, rows, report) = ingest(&options(IngestFormat::Csv, "rfc3339"), &payload).await;
+        assert!(rows.is_empty());
+        assert_eq!(MAX_REPORTED_ERRORS + 10, report.failed_rows);
+        assert_eq!(MAX_REPORTED_ERRORS, report.errors.len());
+    }
+
+    #[test]
+    fn test_time_format() {
+        let ts = TimeFormat::from("%Y-%m-%d %H:%M:%S")
+            .parse(RawValue::Csv("2024-01-01 00:00:01"))
+            .unwrap();
+        assert_eq!(
+            Timestamp::new_millisecond(1704067201000),
+            ts.convert_to(TimeUnit::Millisecond).unwrap()
+        );
+        let ts = TimeFormat::from("epoch_ms")
+            .parse(RawValue::Json(&JsonValue::from(1704067201000_i64)))
+            .unwrap();
+        assert_eq!(Timestamp::new_millisecond(1704067201000), ts);
+        assert!(TimeFormat::from("rfc3339")
+            .parse(RawValue::Json(&JsonValue::from(1)))
+            .is_err());
+    }
+}
@@ -52,16 +52,28 @@ pub struct DataPointRequest {
     tags: HashMap<String, String>,
 }
 
-impl From<DataPointRequest> for DataPoint {
-    fn from(request: DataPointRequest) -> Self {
+impl TryFrom<DataPointRequest> for DataPoint {
+    type Error = error::Error;
+
+    fn try_from(request: DataPointRequest) -> Result<Self> {
         let ts_millis = DataPoint::timestamp_to_millis(request.timestamp);
 
         let tags = request.tags.into_iter().collect::<Vec<(String, String)>>();
 
-        DataPoint::new(request.metric, ts_millis, request.value, tags)
+        let data_point = DataPoint::new(request.metric, ts_millis, request.value, tags);
+        data_point.validate()?;
+        Ok(data_point)
     }
 }
 
+/// A data point parsed from the request body. The raw JSON is kept so that
+/// invalid data points can be reported back in the `details` response.
+#[derive(Debug)]
+struct ParsedDataPoint {
+    raw: serde_json::Value,
+    result: Result<(DataPointRequest, DataPoint)>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(untagged)]
 pub enum OpentsdbPutResponse {
@@ -81,16 +93,16 @@ pub async fn put(
     let summary = params.contains_key("summary");
     let details = params.contains_key("details");
 
-    let data_point_requests = parse_data_points(body).await?;
-    let data_points = data_point_requests
-        .iter()
-        .map(|point| point.clone().into())
-        .collect::<Vec<_>>();
+    let data_points = parse_data_points(body).await?;
 
     ctx.set_channel(Channel::Opentsdb);
     let ctx = Arc::new(ctx);
 
     let response = if !summary && !details {
+        let data_points = data_points
+            .into_iter()
+            .map(|parsed| parsed.result.map(|(_, data_point)| data_point))
+            .collect::<Result<Vec<_>>>()?;
         if let Err(e) = opentsdb_handler.exec(data_points, ctx.clone()).await {
             // Not debugging purpose, failed fast.
             return error::InternalSnafu {
@@ -110,11 +122,20 @@ pub async fn put(
             },
         };
 
-        for (data_point, request) in data_points.into_iter().zip(data_point_requests) {
-            let result = opentsdb_handler.exec(vec![data_point], ctx.clone()).await;
-            match result {
-                Ok(affected_rows) => response.on_success(affected_rows),
-                Err(e) => response.on_failed(request, e),
+        for parsed in data_points {
+            match parsed.result {
+                Ok((request, data_point)) => {
+                    let result = opentsdb_handler.exec(vec![data_point], ctx.clone()).await;
+                    match result {
+                        Ok(affected_rows) => response.on_success(affected_rows),
+                        Err(e) => {
+                            // Serializing a parsed data point never fails.
+                            let datapoint = serde_json::to_value(request).unwrap_or_default();
+                            response.on_failed(datapoint, e)
+                        }
+                    }
+                }
+                Err(e) => response.on_failed(parsed.raw, e),
             }
         }
         (
@@ -125,15 +146,35 @@ pub async fn put(
     Ok(response)
 }
 
-async fn parse_data_points(body: Bytes) -> Result<Vec<DataPointRequest>> {
-    let data_points = serde_json::from_slice::<OneOrMany<DataPointRequest>>(&body[..])
+/// Parses the data points in the body. Fails only if the body is not valid JSON,
+/// malformed or invalid data points are returned as errors individually.
+async fn parse_data_points(body: Bytes) -> Result<Vec<ParsedDataPoint>> {
+    let data_points = serde_json::from_slice::<OneOrMany<serde_json::Value>>(&body[..])
         .context(error::InvalidOpentsdbJsonRequestSnafu)?;
-    Ok(data_points.into())
+    Ok(Vec::from(data_points)
+        .into_iter()
+        .map(parse_data_point)
+        .collect())
+}
+
+fn parse_data_point(raw: serde_json::Value) -> ParsedDataPoint {
+    let result = DataPointRequest::deserialize(&raw)
+        .map_err(|e| {
+            error::InvalidOpentsdbDataPointSnafu {
+                reason: e.to_string(),
+            }
+            .build()
+        })
+        .and_then(|request| {
+            let data_point = DataPoint::try_from(request.clone())?;
+            Ok((request, data_point))
+        });
+    ParsedDataPoint { raw, result }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 struct OpentsdbDetailError {
-    datapoint: DataPointRequest,
+    datapoint: serde_json::Value,
     error: String,
 }
 
@@ -150,7 +191,7 @@ impl OpentsdbDebuggingResponse {
         self.success += affected_rows as i32;
     }
 
-    fn on_failed(&mut self, datapoint: DataPointRequest, error: impl ErrorExt) {
+    fn on_failed(&mut self, datapoint: serde_json::Value, error: impl ErrorExt) {
         self.failed += 1;
 
         if let Some(details) = self.errors.as_mut() {
@@ -163,6 +204,34 @@ impl OpentsdbDebuggingResponse {
     }
 }
 
+/// Version of OpenTSDB that the HTTP API is compatible with.
+const OPENTSDB_VERSION: &str = "2.4.0";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OpentsdbVersionResponse {
+    version: String,
+    short_revision: String,
+    full_revision: String,
+    branch: String,
+    timestamp: String,
+    repo_status: String,
+}
+
+// Please refer to the OpenTSDB documents of ["api/version"](http://opentsdb.net/docs/build/html/api_http/version.html)
+// for more details. Clients probe it to check the availability of the server.
+#[axum_macros::debug_handler]
+pub async fn version() -> Json<OpentsdbVersionResponse> {
+    let build_info = common_version::build_info();
+    Json(OpentsdbVersionResponse {
+        version: OPENTSDB_VERSION.to_string(),
+        short_revision: build_info.commit_short.to_string(),
+        full_revision: build_info.commit.to_string(),
+        branch: build_info.branch.to_string(),
+        timestamp: build_info.build_time.to_string(),
+        repo_status: if build_info.clean { "MINT" } else { "MODIFIED" }.to_string(),
+    })
+}
+
 #[cfg(test)]
 mod test {
 
@@ -176,7 +245,7 @@ mod test {
             value: 1.0,
             tags: HashMap::from([("foo".to_string(), "a".to_string())]),
         };
-        let data_point: DataPoint = request.into();
+        let data_point: DataPoint = request.try_into().unwrap();
         assert_eq!(data_point.metric(), "hello");
         assert_eq!(data_point.ts_millis(), 1234000);
         assert_eq!(data_point.value(), 1.0);
@@ -213,13 +282,42 @@ mod test {
         let body = Bytes::from(raw_data_point1);
         let data_points = parse_data_points(body).await.unwrap();
         assert_eq!(data_points.len(), 1);
-        assert_eq!(data_points[0], data_point1);
+        assert_eq!(data_points[0].result.as_ref().unwrap().0, data_point1);
 
         let body = Bytes::from(format!("[{raw_data_point1},{raw_data_point2}]"));
         let data_points = parse_data_points(body).await.unwrap();
         assert_eq!(data_points.len(), 2);
-        assert_eq!(data_points[0], data_point1);
-        assert_eq!(data_points[1], data_point2);
+        assert_eq!(data_points[0].result.as_ref().unwrap().0, data_point1);
+        assert_eq!(data_points[1].result.as_ref().unwrap().0, data_point2);
+
+        // Invalid data points don't fail the others.
+        let raw_data_point3 =
+            r#"{"metric": "sys.cpu.nice", "timestamp": 1346846400, "value": "x"}"#;
+        let raw_data_point4 = r#"{
+                "metric": "sys.cpu.nice",
+                "timestamp": 1346846400,
+                "value": 1,
+                "tags": {
+                    "greptime_value": "web01"
+                }
+            }"#;
+        let body = Bytes::from(format!(
+            "[{raw_data_point1},{raw_data_point3},{raw_data_point4}]"
+        ));
+        let data_points = parse_data_points(body).await.unwrap();
+        assert_eq!(data_points.len(), 3);
+        assert_eq!(data_points[0].result.as_ref().unwrap().0, data_point1);
+        assert_eq!(
+            data_points[1].raw,
+            serde_json::from_str::<serde_json::Value>(raw_data_point3).unwrap()
+        );
+        let err = data_points[1].result.as_ref().unwrap_err();
+        assert!(err.output_msg().contains("invalid type: string \"x\""));
+        let err = data_points[2].result.as_ref().unwrap_err();
+        assert_eq!(
+            err.output_msg(),
+            "Invalid OpenTSDB data point: tag greptime_value conflicts with the reserved column"
+        );
 
         let body = Bytes::from("");
         let result = parse_data_points(body).await;
@@ -36,7 +36,9 @@ use session::context::{Channel, QueryContext};
 use snafu::prelude::*;
 
 use crate::error::{self, PipelineSnafu, Result};
-use crate::http::extractor::{LogTableName, PipelineInfo, SelectInfoWrapper, TraceTableName};
+use crate::http::extractor::{
+    LogStructure, LogTableName, PipelineInfo, SelectInfoWrapper, TraceTableName,
+};
 use crate::http::header::{write_cost_header_map, CONTENT_TYPE_PROTOBUF};
 use crate::metrics::METRIC_HTTP_OPENTELEMETRY_LOGS_ELAPSED;
 use crate::query_handler::{OpenTelemetryProtocolHandlerRef, PipelineHandler};
@@ -130,6 +132,7 @@ pub async fn logs(
     pipeline_info: PipelineInfo,
     LogTableName(tablename): LogTableName,
     SelectInfoWrapper(select_info): SelectInfoWrapper,
+    LogStructure(body_structure): LogStructure,
     bytes: Bytes,
 ) -> Result<OtlpResponse<ExportLogsServiceResponse>> {
     let tablename = tablename.unwrap_or_else(|| "opentelemetry_logs".to_string());
@@ -144,7 +147,10 @@ pub async fn logs(
     let pipeline = PipelineWay::from_name_and_default(
         pipeline_info.pipeline_name.as_deref(),
         pipeline_info.pipeline_version.as_deref(),
-        Some(PipelineWay::OtlpLogDirect(Box::new(select_info))),
+        Some(PipelineWay::OtlpLogDirect(
+            Box::new(select_info),
+            body_structure,
+        )),
     )
     .context(PipelineSnafu)?;
     let pipeline_params = pipeline_info.pipeline_params;
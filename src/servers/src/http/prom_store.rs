@@ -24,6 +24,7 @@ use axum::Extension;
 use axum_extra::TypedHeader;
 use common_catalog::consts::DEFAULT_SCHEMA_NAME;
 use common_query::prelude::GREPTIME_PHYSICAL_TABLE;
+use common_query::OutputData;
 use common_telemetry::tracing;
 use hyper::HeaderMap;
 use lazy_static::lazy_static;
@@ -34,9 +35,12 @@ use session::context::{Channel, QueryContext};
 use snafu::prelude::*;
 
 use crate::error::{self, Result};
-use crate::http::header::{write_cost_header_map, GREPTIME_DB_HEADER_METRICS};
+use crate::http::header::{
+    insert_rejected_rows_header, write_cost_header_map, GREPTIME_DB_HEADER_METRICS,
+    PROM_REMOTE_WRITE_SAMPLES_WRITTEN,
+};
 use crate::prom_store::{snappy_decompress, zstd_decompress};
-use crate::proto::PromWriteRequest;
+use crate::proto::{PromWriteRequest, PROM_WRITE_REQUEST_V2_PROTO};
 use crate::query_handler::{PromStoreProtocolHandlerRef, PromStoreResponse};
 
 pub const PHYSICAL_TABLE_PARAM: &str = "physical_table";
@@ -86,6 +90,7 @@ pub async fn remote_write(
     query: Query<RemoteWriteQuery>,
     extension: Extension<QueryContext>,
     content_encoding: TypedHeader<headers::ContentEncoding>,
+    content_type: Option<TypedHeader<headers::ContentType>>,
     raw_body: Bytes,
 ) -> Result<impl IntoResponse> {
     remote_write_impl(
@@ -93,6 +98,7 @@ pub async fn remote_write(
         query,
         extension,
         content_encoding,
+        content_type,
         raw_body,
         state.is_strict_mode,
         state.prom_store_with_metric_engine,
@@ -105,6 +111,7 @@ async fn remote_write_impl(
     Query(params): Query<RemoteWriteQuery>,
     Extension(mut query_ctx): Extension<QueryContext>,
     content_encoding: TypedHeader<headers::ContentEncoding>,
+    content_type: Option<TypedHeader<headers::ContentType>>,
     body: Bytes,
     is_strict_mode: bool,
     is_metric_engine: bool,
@@ -121,7 +128,15 @@ async fn remote_write_impl(
         .start_timer();
 
     let is_zstd = content_encoding.contains(VM_ENCODING);
-    let (request, samples) = decode_remote_write_request(is_zstd, body, is_strict_mode).await?;
+    // Remote write 2.0 requests are told apart by the message name in the content type,
+    // e.g. `application/x-protobuf;proto=io.prometheus.write.v2.Request`.
+    let is_v2 = content_type.is_some_and(|TypedHeader(content_type)| {
+        content_type
+            .to_string()
+            .contains(PROM_WRITE_REQUEST_V2_PROTO)
+    });
+    let (request, samples) =
+        decode_remote_write_request(is_zstd, is_v2, body, is_strict_mode).await?;
 
     if let Some(physical_table) = params.physical_table {
         query_ctx.set_extension(PHYSICAL_TABLE_PARAM, physical_table);
@@ -130,11 +145,12 @@ async fn remote_write_impl(
 
     let output = handler.write(request, query_ctx, is_metric_engine).await?;
     crate::metrics::PROM_STORE_REMOTE_WRITE_SAMPLES.inc_by(samples as u64);
-    Ok((
-        StatusCode::NO_CONTENT,
-        write_cost_header_map(output.meta.cost),
-    )
-        .into_response())
+    let mut header_map = write_cost_header_map(output.meta.cost);
+    insert_rejected_rows_header(&mut header_map, output.meta.rejected_rows);
+    if let OutputData::AffectedRows(rows) = output.data {
+        let _ = header_map.insert(&PROM_REMOTE_WRITE_SAMPLES_WRITTEN, HeaderValue::from(rows));
+    }
+    Ok((StatusCode::NO_CONTENT, header_map).into_response())
 }
 
 impl IntoResponse for PromStoreResponse {
@@ -189,6 +205,7 @@ fn try_decompress(is_zstd: bool, body: &[u8]) -> Result<Bytes> {
 
 async fn decode_remote_write_request(
     is_zstd: bool,
+    is_v2: bool,
     body: Bytes,
     is_strict_mode: bool,
 ) -> Result<(RowInsertRequests, usize)> {
@@ -208,9 +225,12 @@ async fn decode_remote_write_request(
     };
 
     let mut request = PROM_WRITE_REQUEST_POOL.pull(PromWriteRequest::default);
-    request
-        .merge(buf, is_strict_mode)
-        .context(error::DecodePromRemoteRequestSnafu)?;
+    let result = if is_v2 {
+        request.merge_v2(buf, is_strict_mode)
+    } else {
+        request.merge(buf, is_strict_mode)
+    };
+    result.context(error::DecodePromRemoteRequestSnafu)?;
     Ok(request.as_row_insert_requests())
 }
 
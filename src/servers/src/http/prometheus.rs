@@ -16,6 +16,7 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+use auth::UserProviderRef;
 use axum::extract::{Path, Query, State};
 use axum::{Extension, Form};
 use catalog::CatalogManagerRef;
@@ -39,7 +40,10 @@ use promql_parser::parser::{
     AggregateExpr, BinaryExpr, Call, Expr as PromqlExpr, MatrixSelector, ParenExpr, SubqueryExpr,
     UnaryExpr, VectorSelector,
 };
-use query::parser::{PromQuery, QueryLanguageParser, DEFAULT_LOOKBACK_STRING};
+use query::parser::{
+    PromQuery, QueryLanguageParser, DEFAULT_LOOKBACK_STRING, PROMQL_METRIC_NAME_COLUMN_KEY,
+    PROMQL_RAW_SAMPLES_KEY,
+};
 use query::promql::planner::normalize_matcher;
 use serde::de::{self, MapAccess, Visitor};
 use serde::{Deserialize, Serialize};
@@ -52,8 +56,8 @@ use store_api::metric_engine_consts::{
 
 pub use super::result::prometheus_resp::PrometheusJsonResponse;
 use crate::error::{
-    CatalogSnafu, CollectRecordbatchSnafu, Error, InvalidQuerySnafu, ParseTimestampSnafu, Result,
-    TableNotFoundSnafu, UnexpectedResultSnafu,
+    AuthSnafu, CatalogSnafu, CollectRecordbatchSnafu, Error, InvalidQuerySnafu,
+    ParseTimestampSnafu, Result, TableNotFoundSnafu, UnexpectedResultSnafu,
 };
 use crate::http::header::collect_plan_metrics;
 use crate::prom_store::{FIELD_NAME_LABEL, METRIC_NAME_LABEL};
@@ -106,6 +110,7 @@ pub enum PrometheusResponse {
     LabelValues(Vec<String>),
     FormatQuery(String),
     BuildInfo(OwnedBuildInfo),
+    TsdbStatus(TsdbStatus),
     #[serde(skip_deserializing)]
     ParseResult(promql_parser::parser::Expr),
     #[default]
@@ -186,6 +191,83 @@ pub async fn format_query(
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct BuildInfoQuery {}
 
+/// The cardinality statistics of the series in a database, in the format of the
+/// Prometheus TSDB status.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TsdbStatus {
+    pub head_stats: HeadStats,
+    /// Metrics with the most series.
+    pub series_count_by_metric_name: Vec<TsdbStat>,
+    /// Labels with the most distinct values.
+    pub label_value_count_by_label_name: Vec<TsdbStat>,
+    /// Label value pairs `label=value` with the most series.
+    pub series_count_by_label_value_pair: Vec<TsdbStat>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HeadStats {
+    pub num_series: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct TsdbStat {
+    pub name: String,
+    pub value: u64,
+}
+
+impl TsdbStat {
+    /// Returns the `limit` stats with the largest values, ties are ordered by the name.
+    pub fn top_k(stats: impl IntoIterator<Item = (String, u64)>, limit: usize) -> Vec<TsdbStat> {
+        let mut stats = stats
+            .into_iter()
+            .map(|(name, value)| TsdbStat { name, value })
+            .collect::<Vec<_>>();
+        stats.sort_unstable_by(|a, b| b.value.cmp(&a.value).then_with(|| a.name.cmp(&b.name)));
+        stats.truncate(limit);
+        stats
+    }
+}
+
+/// The number of stats in each list of the TSDB status by default.
+const DEFAULT_TSDB_STATUS_LIMIT: usize = 10;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TsdbStatusQuery {
+    limit: Option<usize>,
+    db: Option<String>,
+}
+
+#[axum_macros::debug_handler]
+#[tracing::instrument(
+    skip_all,
+    fields(protocol = "prometheus", request_type = "tsdb_status_query")
+)]
+pub async fn tsdb_status_query(
+    State(handler): State<PrometheusHandlerRef>,
+    Extension(mut query_ctx): Extension<QueryContext>,
+    user_provider: Option<Extension<UserProviderRef>>,
+    Query(params): Query<TsdbStatusQuery>,
+) -> PrometheusJsonResponse {
+    if let Err(e) =
+        update_catalog_schema_by_db(&mut query_ctx, params.db.as_deref(), user_provider).await
+    {
+        return PrometheusJsonResponse::error(e.status_code(), e.output_msg());
+    }
+    let query_ctx = Arc::new(query_ctx);
+
+    let _timer = crate::metrics::METRIC_HTTP_PROMETHEUS_PROMQL_ELAPSED
+        .with_label_values(&[query_ctx.get_db_string().as_str(), "tsdb_status_query"])
+        .start_timer();
+
+    let limit = params.limit.unwrap_or(DEFAULT_TSDB_STATUS_LIMIT);
+    match handler.tsdb_status(limit, &query_ctx).await {
+        Ok(status) => PrometheusJsonResponse::success(PrometheusResponse::TsdbStatus(status)),
+        Err(e) => PrometheusJsonResponse::error(e.status_code(), e.output_msg()),
+    }
+}
+
 #[axum_macros::debug_handler]
 #[tracing::instrument(
     skip_all,
@@ -228,6 +310,7 @@ pub async fn instant_query(
     State(handler): State<PrometheusHandlerRef>,
     Query(params): Query<InstantQuery>,
     Extension(mut query_ctx): Extension<QueryContext>,
+    user_provider: Option<Extension<UserProviderRef>>,
     Form(form_params): Form<InstantQuery>,
 ) -> PrometheusJsonResponse {
     // Extract time from query string, or use current server time if not specified.
@@ -249,10 +332,11 @@ pub async fn instant_query(
     let promql_expr = try_call_return_response!(promql_parser::parser::parse(&prom_query.query));
 
     // update catalog and schema in query context if necessary
-    if let Some(db) = &params.db {
-        let (catalog, schema) = parse_catalog_and_schema_from_db_string(db);
-        try_update_catalog_schema(&mut query_ctx, &catalog, &schema);
+    let db = params.db.as_deref().or(form_params.db.as_deref());
+    if let Err(e) = update_catalog_schema_by_db(&mut query_ctx, db, user_provider).await {
+        return PrometheusJsonResponse::error(e.status_code(), e.output_msg());
     }
+    query_ctx.set_extension(PROMQL_METRIC_NAME_COLUMN_KEY, "true");
     let query_ctx = Arc::new(query_ctx);
 
     let _timer = crate::metrics::METRIC_HTTP_PROMETHEUS_PROMQL_ELAPSED
@@ -316,12 +400,14 @@ async fn do_instant_query(
     prom_query: &PromQuery,
     query_ctx: QueryContextRef,
 ) -> PrometheusJsonResponse {
-    let result = handler.do_query(prom_query, query_ctx).await;
-    let (metric_name, result_type) = match retrieve_metric_name_and_result_type(&prom_query.query) {
-        Ok((metric_name, result_type)) => (metric_name.unwrap_or_default(), result_type),
+    let result = handler.do_query(prom_query, query_ctx.clone()).await;
+    let result_type = match retrieve_result_type(&prom_query.query) {
+        Ok(result_type) => result_type,
         Err(err) => return PrometheusJsonResponse::error(err.status_code(), err.output_msg()),
     };
-    PrometheusJsonResponse::from_query_result(result, metric_name, result_type).await
+    PrometheusJsonResponse::from_query_result(result, result_type)
+        .await
+        .with_warning(query_ctx.warning())
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -333,6 +419,9 @@ pub struct RangeQuery {
     lookback: Option<String>,
     timeout: Option<String>,
     db: Option<String>,
+    /// Returns the stored samples within the range instead of the values
+    /// aligned to the steps. Only vector selectors are supported.
+    raw_samples: Option<bool>,
 }
 
 #[axum_macros::debug_handler]
@@ -344,6 +433,7 @@ pub async fn range_query(
     State(handler): State<PrometheusHandlerRef>,
     Query(params): Query<RangeQuery>,
     Extension(mut query_ctx): Extension<QueryContext>,
+    user_provider: Option<Extension<UserProviderRef>>,
     Form(form_params): Form<RangeQuery>,
 ) -> PrometheusJsonResponse {
     let prom_query = PromQuery {
@@ -358,12 +448,24 @@ pub async fn range_query(
     };
 
     let promql_expr = try_call_return_response!(promql_parser::parser::parse(&prom_query.query));
+    // Same as Prometheus, a string can't be evaluated over a range. Scalars
+    // are returned as a matrix of one series.
+    if matches!(promql_expr.value_type(), ValueType::String) {
+        return PrometheusJsonResponse::error(
+            StatusCode::InvalidArguments,
+            "invalid expression type \"string\" for range query, must be Scalar or instant Vector",
+        );
+    }
 
     // update catalog and schema in query context if necessary
-    if let Some(db) = &params.db {
-        let (catalog, schema) = parse_catalog_and_schema_from_db_string(db);
-        try_update_catalog_schema(&mut query_ctx, &catalog, &schema);
+    let db = params.db.as_deref().or(form_params.db.as_deref());
+    if let Err(e) = update_catalog_schema_by_db(&mut query_ctx, db, user_provider).await {
+        return PrometheusJsonResponse::error(e.status_code(), e.output_msg());
+    }
+    if params.raw_samples.or(form_params.raw_samples) == Some(true) {
+        query_ctx.set_extension(PROMQL_RAW_SAMPLES_KEY, "true");
     }
+    query_ctx.set_extension(PROMQL_METRIC_NAME_COLUMN_KEY, "true");
     let query_ctx = Arc::new(query_ctx);
     let _timer = crate::metrics::METRIC_HTTP_PROMETHEUS_PROMQL_ELAPSED
         .with_label_values(&[query_ctx.get_db_string().as_str(), "range_query"])
@@ -425,12 +527,13 @@ async fn do_range_query(
     prom_query: &PromQuery,
     query_ctx: QueryContextRef,
 ) -> PrometheusJsonResponse {
-    let result = handler.do_query(prom_query, query_ctx).await;
-    let metric_name = match retrieve_metric_name_and_result_type(&prom_query.query) {
-        Err(err) => return PrometheusJsonResponse::error(err.status_code(), err.output_msg()),
-        Ok((metric_name, _)) => metric_name.unwrap_or_default(),
-    };
-    PrometheusJsonResponse::from_query_result(result, metric_name, ValueType::Matrix).await
+    let result = handler.do_query(prom_query, query_ctx.clone()).await;
+    if let Err(err) = retrieve_result_type(&prom_query.query) {
+        return PrometheusJsonResponse::error(err.status_code(), err.output_msg());
+    }
+    PrometheusJsonResponse::from_query_result(result, ValueType::Matrix)
+        .await
+        .with_warning(query_ctx.warning())
 }
 
 #[derive(Debug, Default, Serialize)]
@@ -487,11 +590,16 @@ pub async fn labels_query(
     State(handler): State<PrometheusHandlerRef>,
     Query(params): Query<LabelsQuery>,
     Extension(mut query_ctx): Extension<QueryContext>,
+    user_provider: Option<Extension<UserProviderRef>>,
     Form(form_params): Form<LabelsQuery>,
 ) -> PrometheusJsonResponse {
-    let (catalog, schema) = get_catalog_schema(&params.db, &query_ctx);
-    try_update_catalog_schema(&mut query_ctx, &catalog, &schema);
+    let db = params.db.as_deref().or(form_params.db.as_deref());
+    if let Err(e) = update_catalog_schema_by_db(&mut query_ctx, db, user_provider).await {
+        return PrometheusJsonResponse::error(e.status_code(), e.output_msg());
+    }
     let query_ctx = Arc::new(query_ctx);
+    let catalog = query_ctx.current_catalog();
+    let schema = query_ctx.current_schema();
 
     let mut queries = params.matches.0;
     if queries.is_empty() {
@@ -503,7 +611,7 @@ pub async fn labels_query(
         .start_timer();
 
     // Fetch all tag columns. It will be used as white-list for tag names.
-    let mut labels = match get_all_column_names(&catalog, &schema, &handler.catalog_manager()).await
+    let mut labels = match get_all_column_names(catalog, &schema, &handler.catalog_manager()).await
     {
         Ok(labels) => labels,
         Err(e) => return PrometheusJsonResponse::error(e.status_code(), e.output_msg()),
@@ -774,28 +882,38 @@ fn record_batches_to_labels_name(
     Ok(())
 }
 
-pub(crate) fn retrieve_metric_name_and_result_type(
-    promql: &str,
-) -> Result<(Option<String>, ValueType)> {
+/// Returns the type of the result of the query.
+pub(crate) fn retrieve_result_type(promql: &str) -> Result<ValueType> {
     let promql_expr = promql_parser::parser::parse(promql)
         .map_err(|reason| InvalidQuerySnafu { reason }.build())?;
-    let metric_name = promql_expr_to_metric_name(&promql_expr);
-    let result_type = promql_expr.value_type();
-
-    Ok((metric_name, result_type))
+    Ok(promql_expr.value_type())
 }
 
-/// Tries to get catalog and schema from an optional db param. And retrieves
-/// them from [QueryContext] if they don't present.
-pub(crate) fn get_catalog_schema(db: &Option<String>, ctx: &QueryContext) -> (String, String) {
-    if let Some(db) = db {
-        parse_catalog_and_schema_from_db_string(db)
-    } else {
-        (
-            ctx.current_catalog().to_string(),
-            ctx.current_schema().to_string(),
-        )
+/// Switches the catalog and schema of [QueryContext] to the `db` param, which is
+/// either `schema` or `catalog-schema`. Keeps them unchanged if `db` is absent.
+///
+/// The request is only authorized on the database it's authenticated with, so the
+/// user must be authorized again to switch to another database.
+pub(crate) async fn update_catalog_schema_by_db(
+    ctx: &mut QueryContext,
+    db: Option<&str>,
+    user_provider: Option<Extension<UserProviderRef>>,
+) -> Result<()> {
+    let Some(db) = db else {
+        return Ok(());
+    };
+    let (catalog, schema) = parse_catalog_and_schema_from_db_string(db);
+    if ctx.current_catalog() == catalog && ctx.current_schema() == schema {
+        return Ok(());
+    }
+    if let Some(Extension(user_provider)) = user_provider {
+        user_provider
+            .authorize(&catalog, &schema, &ctx.current_user())
+            .await
+            .context(AuthSnafu)?;
     }
+    try_update_catalog_schema(ctx, &catalog, &schema);
+    Ok(())
 }
 
 /// Update catalog and schema in [QueryContext] if necessary.
@@ -806,16 +924,6 @@ pub(crate) fn try_update_catalog_schema(ctx: &mut QueryContext, catalog: &str, s
     }
 }
 
-fn promql_expr_to_metric_name(expr: &PromqlExpr) -> Option<String> {
-    find_metric_name_and_matchers(expr, |name, matchers| {
-        name.clone().or(matchers
-            .find_matchers(METRIC_NAME)
-            .into_iter()
-            .next()
-            .map(|m| m.value))
-    })
-}
-
 fn find_metric_name_and_matchers<E, F>(expr: &PromqlExpr, f: F) -> Option<E>
 where
     F: Fn(&Option<String>, &Matchers) -> Option<E> + Clone,
@@ -914,6 +1022,36 @@ fn update_metric_name_matcher(expr: &mut PromqlExpr, metric_name: &str) {
     }
 }
 
+/// Adds a `__field__` equal matcher to every selector in the expression, so
+/// only the given field column of the selected tables is queried.
+pub(crate) fn add_field_name_matcher(expr: &mut PromqlExpr, field: &str) {
+    match expr {
+        PromqlExpr::Aggregate(AggregateExpr { expr, .. }) => add_field_name_matcher(expr, field),
+        PromqlExpr::Unary(UnaryExpr { expr }) => add_field_name_matcher(expr, field),
+        PromqlExpr::Binary(BinaryExpr { lhs, rhs, .. }) => {
+            add_field_name_matcher(lhs, field);
+            add_field_name_matcher(rhs, field);
+        }
+        PromqlExpr::Paren(ParenExpr { expr }) => add_field_name_matcher(expr, field),
+        PromqlExpr::Subquery(SubqueryExpr { expr, .. }) => add_field_name_matcher(expr, field),
+        PromqlExpr::VectorSelector(VectorSelector { matchers, .. })
+        | PromqlExpr::MatrixSelector(MatrixSelector {
+            vs: VectorSelector { matchers, .. },
+            ..
+        }) => {
+            matchers
+                .matchers
+                .push(Matcher::new(MatchOp::Equal, FIELD_NAME_LABEL, field));
+        }
+        PromqlExpr::Call(Call { args, .. }) => {
+            args.args
+                .iter_mut()
+                .for_each(|e| add_field_name_matcher(e, field));
+        }
+        PromqlExpr::NumberLiteral(_) | PromqlExpr::StringLiteral(_) | PromqlExpr::Extension(_) => {}
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct LabelValueQuery {
     start: Option<String>,
@@ -933,11 +1071,17 @@ pub async fn label_values_query(
     State(handler): State<PrometheusHandlerRef>,
     Path(label_name): Path<String>,
     Extension(mut query_ctx): Extension<QueryContext>,
+    user_provider: Option<Extension<UserProviderRef>>,
     Query(params): Query<LabelValueQuery>,
 ) -> PrometheusJsonResponse {
-    let (catalog, schema) = get_catalog_schema(&params.db, &query_ctx);
-    try_update_catalog_schema(&mut query_ctx, &catalog, &schema);
+    if let Err(e) =
+        update_catalog_schema_by_db(&mut query_ctx, params.db.as_deref(), user_provider).await
+    {
+        return PrometheusJsonResponse::error(e.status_code(), e.output_msg());
+    }
     let query_ctx = Arc::new(query_ctx);
+    let catalog = query_ctx.current_catalog();
+    let schema = query_ctx.current_schema();
 
     let _timer = crate::metrics::METRIC_HTTP_PROMETHEUS_PROMQL_ELAPSED
         .with_label_values(&[query_ctx.get_db_string().as_str(), "label_values_query"])
@@ -945,7 +1089,7 @@ pub async fn label_values_query(
 
     if label_name == METRIC_NAME_LABEL {
         let catalog_manager = handler.catalog_manager();
-        let mut tables_stream = catalog_manager.tables(&catalog, &schema, Some(&query_ctx));
+        let mut tables_stream = catalog_manager.tables(catalog, &schema, Some(&query_ctx));
         let mut table_names = Vec::new();
         while let Some(table) = tables_stream.next().await {
             // filter out physical tables
@@ -1179,6 +1323,7 @@ pub async fn series_query(
     State(handler): State<PrometheusHandlerRef>,
     Query(params): Query<SeriesQuery>,
     Extension(mut query_ctx): Extension<QueryContext>,
+    user_provider: Option<Extension<UserProviderRef>>,
     Form(form_params): Form<SeriesQuery>,
 ) -> PrometheusJsonResponse {
     let mut queries: Vec<String> = params.matches.0;
@@ -1205,9 +1350,9 @@ pub async fn series_query(
         .unwrap_or_else(|| DEFAULT_LOOKBACK_STRING.to_string());
 
     // update catalog and schema in query context if necessary
-    if let Some(db) = &params.db {
-        let (catalog, schema) = parse_catalog_and_schema_from_db_string(db);
-        try_update_catalog_schema(&mut query_ctx, &catalog, &schema);
+    let db = params.db.as_deref().or(form_params.db.as_deref());
+    if let Err(e) = update_catalog_schema_by_db(&mut query_ctx, db, user_provider).await {
+        return PrometheusJsonResponse::error(e.status_code(), e.output_msg());
     }
     let query_ctx = Arc::new(query_ctx);
 
@@ -22,11 +22,20 @@ use serde::{Deserialize, Serialize};
 
 use crate::error::status_code_to_http_status;
 
+/// Byte range in the query text that an error refers to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorPosition {
+    pub start: usize,
+    pub end: usize,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ErrorResponse {
     code: u32,
     error: String,
     execution_time_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position: Option<ErrorPosition>,
 }
 
 impl ErrorResponse {
@@ -47,6 +56,7 @@ impl ErrorResponse {
             code: code as u32,
             error: msg,
             execution_time_ms: 0,
+            position: None,
         }
     }
 
@@ -55,6 +65,11 @@ impl ErrorResponse {
         self
     }
 
+    pub fn with_position(mut self, position: Option<ErrorPosition>) -> Self {
+        self.position = position;
+        self
+    }
+
     pub fn execution_time_ms(&self) -> u64 {
         self.execution_time_ms
     }
@@ -66,6 +81,10 @@ impl ErrorResponse {
     pub fn error(&self) -> &str {
         &self.error
     }
+
+    pub fn position(&self) -> Option<ErrorPosition> {
+        self.position
+    }
 }
 
 impl IntoResponse for ErrorResponse {
@@ -24,6 +24,7 @@ use serde_json::Value;
 use crate::http::header::{
     GREPTIME_DB_HEADER_EXECUTION_TIME, GREPTIME_DB_HEADER_FORMAT, GREPTIME_DB_HEADER_METRICS,
 };
+use crate::http::ingest::IngestReport;
 use crate::http::{handler, process_with_limit, GreptimeQueryOutput, HttpResponse, ResponseFormat};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -36,6 +37,10 @@ pub struct GreptimedbV1Response {
     #[serde(skip)]
     #[serde(default)]
     pub(crate) resp_metrics: HashMap<String, Value>,
+
+    /// The rows rejected by `/v1/ingest`, only present for mapping ingestion.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) ingest_report: Option<IngestReport>,
 }
 
 impl GreptimedbV1Response {
@@ -45,6 +50,7 @@ impl GreptimedbV1Response {
                 output,
                 execution_time_ms: 0,
                 resp_metrics,
+                ingest_report: None,
             }),
             Err(err) => HttpResponse::Error(err),
         }
@@ -63,6 +69,15 @@ impl GreptimedbV1Response {
         self.execution_time_ms
     }
 
+    pub fn with_ingest_report(mut self, ingest_report: IngestReport) -> Self {
+        self.ingest_report = Some(ingest_report);
+        self
+    }
+
+    pub fn ingest_report(&self) -> Option<&IngestReport> {
+        self.ingest_report.as_ref()
+    }
+
     pub fn with_limit(mut self, limit: usize) -> Self {
         self.output = process_with_limit(self.output, limit);
         self
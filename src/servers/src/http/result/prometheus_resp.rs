@@ -26,7 +26,6 @@ use datatypes::prelude::ConcreteDataType;
 use datatypes::scalars::ScalarVector;
 use datatypes::vectors::{Float64Vector, StringVector, TimestampMillisecondVector};
 use indexmap::IndexMap;
-use promql_parser::label::METRIC_NAME;
 use promql_parser::parser::value::ValueType;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -114,34 +113,36 @@ impl PrometheusJsonResponse {
         }
     }
 
-    /// Convert from `Result<Output>`
-    pub async fn from_query_result(
-        result: Result<Output>,
-        metric_name: String,
-        result_type: ValueType,
-    ) -> Self {
+    /// Adds the warning of the query to a successful response.
+    pub fn with_warning(mut self, warning: Option<String>) -> Self {
+        if self.error.is_none()
+            && let Some(warning) = warning
+        {
+            self.warnings.get_or_insert_default().push(warning);
+        }
+        self
+    }
+
+    /// Convert from `Result<Output>`. The `__name__` label of the series is read
+    /// from the `__name__` column of the result like other labels.
+    pub async fn from_query_result(result: Result<Output>, result_type: ValueType) -> Self {
         let response: Result<Self> = try {
             let result = result?;
-            let mut resp =
-                match result.data {
-                    OutputData::RecordBatches(batches) => Self::success(
-                        Self::record_batches_to_data(batches, metric_name, result_type)?,
-                    ),
-                    OutputData::Stream(stream) => {
-                        let record_batches = RecordBatches::try_collect(stream)
-                            .await
-                            .context(CollectRecordbatchSnafu)?;
-                        Self::success(Self::record_batches_to_data(
-                            record_batches,
-                            metric_name,
-                            result_type,
-                        )?)
-                    }
-                    OutputData::AffectedRows(_) => Self::error(
-                        StatusCode::Unexpected,
-                        "expected data result, but got affected rows",
-                    ),
-                };
+            let mut resp = match result.data {
+                OutputData::RecordBatches(batches) => {
+                    Self::success(Self::record_batches_to_data(batches, result_type)?)
+                }
+                OutputData::Stream(stream) => {
+                    let record_batches = RecordBatches::try_collect(stream)
+                        .await
+                        .context(CollectRecordbatchSnafu)?;
+                    Self::success(Self::record_batches_to_data(record_batches, result_type)?)
+                }
+                OutputData::AffectedRows(_) => Self::error(
+                    StatusCode::Unexpected,
+                    "expected data result, but got affected rows",
+                ),
+            };
 
             if let Some(physical_plan) = result.meta.plan {
                 let mut result_map = HashMap::new();
@@ -181,9 +182,12 @@ impl PrometheusJsonResponse {
     /// Convert [RecordBatches] to [PromData]
     fn record_batches_to_data(
         batches: RecordBatches,
-        metric_name: String,
         result_type: ValueType,
     ) -> Result<PrometheusResponse> {
+        if matches!(result_type, ValueType::String) {
+            return Self::record_batches_to_string_data(batches);
+        }
+
         // infer semantic type of each column from schema.
         // TODO(ruihang): wish there is a better way to do this.
         let mut timestamp_column_index = None;
@@ -229,12 +233,11 @@ impl PrometheusJsonResponse {
             reason: "no value column found".to_string(),
         })?;
 
-        let metric_name = (METRIC_NAME, metric_name.as_str());
+        let schema = batches.schema();
         // Preserves the order of output tags.
         // Tag order matters, e.g., after sorc and sort_desc, the output order must be kept.
         let mut buffer = IndexMap::<Vec<(&str, &str)>, Vec<(f64, String)>>::new();
 
-        let schema = batches.schema();
         for batch in batches.iter() {
             // prepare things...
             let tag_columns = tag_column_indices
@@ -275,9 +278,7 @@ impl PrometheusJsonResponse {
                     }
 
                     // retrieve tags
-                    // TODO(ruihang): push table name `__metric__`
-                    let mut tags = Vec::with_capacity(num_label_columns + 1);
-                    tags.push(metric_name);
+                    let mut tags = Vec::with_capacity(num_label_columns);
                     for (tag_column, tag_name) in tag_columns.iter().zip(tag_names.iter()) {
                         // TODO(ruihang): add test for NULL tag
                         if let Some(tag_value) = tag_column.get_data(row_index) {
@@ -325,9 +326,7 @@ impl PrometheusJsonResponse {
                 PromQueryResult::Scalar(ref mut v) => {
                     *v = values.pop();
                 }
-                PromQueryResult::String(ref mut _v) => {
-                    // TODO(ruihang): Not supported yet
-                }
+                PromQueryResult::String(_) => unreachable!("handled above"),
             }
         });
 
@@ -339,4 +338,54 @@ impl PrometheusJsonResponse {
 
         Ok(data)
     }
+
+    /// Convert [RecordBatches] of a string expression to [PromData]. Strings
+    /// are only valid in instant queries so only the last sample is kept.
+    fn record_batches_to_string_data(batches: RecordBatches) -> Result<PrometheusResponse> {
+        let schema = batches.schema();
+        let column_schemas = schema.column_schemas();
+        let timestamp_column_index = column_schemas
+            .iter()
+            .position(|column| {
+                matches!(
+                    column.data_type,
+                    ConcreteDataType::Timestamp(datatypes::types::TimestampType::Millisecond(_))
+                )
+            })
+            .context(UnexpectedResultSnafu {
+                reason: "no timestamp column found".to_string(),
+            })?;
+        let value_column_index = column_schemas
+            .iter()
+            .position(|column| matches!(column.data_type, ConcreteDataType::String(_)))
+            .context(UnexpectedResultSnafu {
+                reason: "no string column found".to_string(),
+            })?;
+
+        let mut value = None;
+        for batch in batches.iter() {
+            let timestamp_column = batch
+                .column(timestamp_column_index)
+                .as_any()
+                .downcast_ref::<TimestampMillisecondVector>()
+                .unwrap();
+            let value_column = batch
+                .column(value_column_index)
+                .as_any()
+                .downcast_ref::<StringVector>()
+                .unwrap();
+            for row_index in 0..batch.num_rows() {
+                if let Some(v) = value_column.get_data(row_index) {
+                    let timestamp_millis: i64 =
+                        timestamp_column.get_data(row_index).unwrap().into();
+                    value = Some((timestamp_millis as f64 / 1000.0, v.to_string()));
+                }
+            }
+        }
+
+        Ok(PrometheusResponse::PromData(PromData {
+            result_type: ValueType::String.to_string(),
+            result: PromQueryResult::String(value),
+        }))
+    }
 }
@@ -24,6 +24,8 @@ use datatypes::schema::Schema;
 
 pub mod addrs;
 pub mod configurator;
+pub mod connection;
+pub mod drain;
 pub(crate) mod elasticsearch;
 pub mod error;
 pub mod export_metrics;
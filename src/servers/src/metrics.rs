@@ -43,6 +43,7 @@ pub(crate) const METRIC_POSTGRES_EXTENDED_QUERY: &str = "extended";
 pub(crate) const METRIC_METHOD_LABEL: &str = "method";
 pub(crate) const METRIC_PATH_LABEL: &str = "path";
 pub(crate) const METRIC_RESULT_LABEL: &str = "result";
+pub(crate) const METRIC_REASON_LABEL: &str = "reason";
 
 pub(crate) const METRIC_SUCCESS_VALUE: &str = "success";
 pub(crate) const METRIC_FAILURE_VALUE: &str = "failure";
@@ -223,6 +224,28 @@ lazy_static! {
         "servers postgres connection count"
     )
     .unwrap();
+    pub static ref METRIC_MYSQL_PREPARED_STATEMENTS: IntGauge = register_int_gauge!(
+        "greptime_servers_mysql_prepared_statements",
+        "servers mysql prepared statements cached by sessions"
+    )
+    .unwrap();
+    pub static ref METRIC_MYSQL_PREPARED_EVICTED: IntCounter = register_int_counter!(
+        "greptime_servers_mysql_prepared_evicted_count",
+        "servers mysql prepared statements evicted from sessions"
+    )
+    .unwrap();
+    pub static ref METRIC_REJECTED_CONNECTIONS: IntCounterVec = register_int_counter_vec!(
+        "greptime_servers_rejected_connection_count",
+        "servers rejected connection count",
+        &[METRIC_PROTOCOL_LABEL, METRIC_REASON_LABEL]
+    )
+    .unwrap();
+    pub static ref METRIC_IDLE_CLOSED_CONNECTIONS: IntCounterVec = register_int_counter_vec!(
+        "greptime_servers_idle_closed_connection_count",
+        "servers connections closed for being idle",
+        &[METRIC_PROTOCOL_LABEL]
+    )
+    .unwrap();
     pub static ref METRIC_POSTGRES_QUERY_TIMER: HistogramVec = register_histogram_vec!(
         "greptime_servers_postgres_query_elapsed",
         "servers postgres query elapsed",
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
@@ -36,7 +36,7 @@ use opensrv_mysql::{
 use parking_lot::RwLock;
 use query::query_engine::DescribeResult;
 use rand::RngCore;
-use session::context::{Channel, QueryContextRef};
+use session::context::{Channel, ConfigurationVariables, QueryContextRef};
 use session::{Session, SessionRef};
 use snafu::{ensure, ResultExt};
 use sql::dialect::MySqlDialect;
@@ -44,8 +44,11 @@ use sql::parser::{ParseOptions, ParserContext};
 use sql::statements::statement::Statement;
 use tokio::io::AsyncWrite;
 
+use crate::connection::ConnectionGuard;
 use crate::error::{self, DataFrameSnafu, InvalidPrepareStatementSnafu, Result};
-use crate::metrics::METRIC_AUTH_FAILURE;
+use crate::metrics::{
+    METRIC_AUTH_FAILURE, METRIC_MYSQL_PREPARED_EVICTED, METRIC_MYSQL_PREPARED_STATEMENTS,
+};
 use crate::mysql::helper::{
     self, fix_placeholder_types, format_placeholder, replace_placeholders, transform_placeholders,
 };
@@ -74,14 +77,87 @@ impl Params<'_> {
     }
 }
 
+/// Prepared statements of a session, evicts the least recently used statement
+/// when the number of statements exceeds the capacity.
+#[derive(Default)]
+struct PreparedStatements {
+    /// Max number of statements, zero means unlimited.
+    capacity: usize,
+    /// Statement key to the plan and the tick of its last use.
+    plans: HashMap<String, (SqlPlan, u64)>,
+    /// Tick of last use to the statement key, ordered from the least recently used.
+    usage: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl PreparedStatements {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn insert(&mut self, key: String, plan: SqlPlan) {
+        let tick = self.next_tick();
+        match self.plans.insert(key.clone(), (plan, tick)) {
+            Some((_, prev_tick)) => {
+                let _ = self.usage.remove(&prev_tick);
+            }
+            None => METRIC_MYSQL_PREPARED_STATEMENTS.inc(),
+        }
+        let _ = self.usage.insert(tick, key);
+
+        while self.capacity > 0 && self.plans.len() > self.capacity {
+            let Some((_, evicted)) = self.usage.pop_first() else {
+                break;
+            };
+            debug!("Evict least recently used prepared statement: {}", evicted);
+            let _ = self.plans.remove(&evicted);
+            METRIC_MYSQL_PREPARED_STATEMENTS.dec();
+            METRIC_MYSQL_PREPARED_EVICTED.inc();
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<SqlPlan> {
+        let tick = self.next_tick();
+        let (plan, last_used) = self.plans.get_mut(key)?;
+        let prev_tick = std::mem::replace(last_used, tick);
+        let plan = plan.clone();
+        if let Some(key) = self.usage.remove(&prev_tick) {
+            let _ = self.usage.insert(tick, key);
+        }
+        Some(plan)
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some((_, tick)) = self.plans.remove(key) {
+            let _ = self.usage.remove(&tick);
+            METRIC_MYSQL_PREPARED_STATEMENTS.dec();
+        }
+    }
+}
+
+impl Drop for PreparedStatements {
+    fn drop(&mut self) {
+        METRIC_MYSQL_PREPARED_STATEMENTS.sub(self.plans.len() as i64);
+    }
+}
+
 // An intermediate shim for executing MySQL queries.
 pub struct MysqlInstanceShim {
     query_handler: ServerSqlQueryHandlerRef,
     salt: [u8; 20],
     session: SessionRef,
     user_provider: Option<UserProviderRef>,
-    prepared_stmts: Arc<RwLock<HashMap<String, SqlPlan>>>,
+    prepared_stmts: Arc<RwLock<PreparedStatements>>,
     prepared_stmts_counter: AtomicU32,
+    connection: ConnectionGuard,
 }
 
 impl MysqlInstanceShim {
@@ -89,6 +165,7 @@ impl MysqlInstanceShim {
         query_handler: ServerSqlQueryHandlerRef,
         user_provider: Option<UserProviderRef>,
         client_addr: SocketAddr,
+        connection: ConnectionGuard,
     ) -> MysqlInstanceShim {
         // init a random salt
         let mut bs = vec![0u8; 20];
@@ -103,17 +180,21 @@ impl MysqlInstanceShim {
             }
         }
 
+        let limits = connection.limits();
         MysqlInstanceShim {
             query_handler,
             salt: scramble,
             session: Arc::new(Session::new(
                 Some(client_addr),
                 Channel::Mysql,
-                Default::default(),
+                ConfigurationVariables::new().with_connection_limits(limits),
             )),
             user_provider,
-            prepared_stmts: Default::default(),
+            prepared_stmts: Arc::new(RwLock::new(PreparedStatements::new(
+                limits.max_prepared_statements,
+            ))),
             prepared_stmts_counter: AtomicU32::new(1),
+            connection,
         }
     }
 
@@ -156,13 +237,13 @@ impl MysqlInstanceShim {
     /// Save query and logical plan with a given statement key
     fn save_plan(&self, plan: SqlPlan, stmt_key: String) {
         let mut prepared_stmts = self.prepared_stmts.write();
-        let _ = prepared_stmts.insert(stmt_key, plan);
+        prepared_stmts.insert(stmt_key, plan);
     }
 
     /// Retrieve the query and logical plan by a given statement key
     fn plan(&self, stmt_key: &str) -> Option<SqlPlan> {
-        let guard = self.prepared_stmts.read();
-        guard.get(stmt_key).cloned()
+        let mut guard = self.prepared_stmts.write();
+        guard.get(stmt_key)
     }
 
     /// Save the prepared statement and return the parameters and result columns
@@ -314,7 +395,7 @@ impl MysqlInstanceShim {
     /// Remove the prepared statement by a given statement key
     fn do_close(&mut self, stmt_key: String) {
         let mut guard = self.prepared_stmts.write();
-        let _ = guard.remove(&stmt_key);
+        guard.remove(&stmt_key);
     }
 
     fn auth_plugin(&self) -> &str {
@@ -403,6 +484,10 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
         let user_info =
             user_info.unwrap_or_else(|| auth::userinfo_by_name(Some(username.to_string())));
 
+        if let Err(e) = self.connection.set_user(user_info.username()) {
+            warn!(e; "Reject MySQL connection");
+            return false;
+        }
         self.session.set_user_info(user_info);
 
         true
@@ -413,6 +498,7 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
         raw_query: &'a str,
         w: StatementMetaWriter<'a, W>,
     ) -> Result<()> {
+        let _active = self.connection.begin();
         let query_ctx = self.session.new_query_context();
         let stmt_id = self.prepared_stmts_counter.fetch_add(1, Ordering::Relaxed);
         let stmt_key = uuid::Uuid::from_u128(stmt_id as u128).to_string();
@@ -433,6 +519,7 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
         p: ParamParser<'a>,
         w: QueryResultWriter<'a, W>,
     ) -> Result<()> {
+        let _active = self.connection.begin();
         let query_ctx = self.session.new_query_context();
         let db = query_ctx.get_db_string();
         let _timer = crate::metrics::METRIC_MYSQL_QUERY_TIMER
@@ -458,7 +545,7 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
             }
         };
 
-        writer::write_output(w, query_ctx, outputs).await?;
+        writer::write_output(w, query_ctx, outputs, true).await?;
 
         Ok(())
     }
@@ -477,6 +564,7 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
         query: &'a str,
         writer: QueryResultWriter<'a, W>,
     ) -> Result<()> {
+        let _active = self.connection.begin();
         let query_ctx = self.session.new_query_context();
         let db = query_ctx.get_db_string();
         let _timer = crate::metrics::METRIC_MYSQL_QUERY_TIMER
@@ -492,7 +580,7 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
                     match prepare_results {
                         Ok(_) => {
                             let outputs = vec![Ok(Output::new_with_affected_rows(0))];
-                            writer::write_output(writer, query_ctx, outputs).await?;
+                            writer::write_output(writer, query_ctx, outputs, false).await?;
                             return Ok(());
                         }
                         Err(e) => {
@@ -528,7 +616,7 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
                             return Ok(());
                         }
                     };
-                    writer::write_output(writer, query_ctx, outputs).await?;
+                    writer::write_output(writer, query_ctx, outputs, false).await?;
                     return Ok(());
                 }
                 Err(e) => {
@@ -543,7 +631,7 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
                 Ok(stmt_name) => {
                     self.do_close(stmt_name);
                     let outputs = vec![Ok(Output::new_with_affected_rows(0))];
-                    writer::write_output(writer, query_ctx, outputs).await?;
+                    writer::write_output(writer, query_ctx, outputs, false).await?;
                     return Ok(());
                 }
                 Err(e) => {
@@ -556,7 +644,7 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
         }
 
         let outputs = self.do_query(query, query_ctx.clone()).await;
-        writer::write_output(writer, query_ctx, outputs).await?;
+        writer::write_output(writer, query_ctx, outputs, false).await?;
         Ok(())
     }
 
@@ -748,3 +836,45 @@ fn prepared_params(param_types: &HashMap<String, Option<ConcreteDataType>>) -> R
 
     Ok(params)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sql_plan(query: &str) -> SqlPlan {
+        SqlPlan {
+            query: query.to_string(),
+            plan: None,
+            schema: None,
+        }
+    }
+
+    #[test]
+    fn test_prepared_statements_lru() {
+        let mut stmts = PreparedStatements::new(2);
+        stmts.insert("a".to_string(), sql_plan("SELECT 1"));
+        stmts.insert("b".to_string(), sql_plan("SELECT 2"));
+        // Uses `a` so `b` becomes the least recently used.
+        assert_eq!("SELECT 1", stmts.get("a").unwrap().query);
+        stmts.insert("c".to_string(), sql_plan("SELECT 3"));
+        assert_eq!(2, stmts.plans.len());
+        assert!(stmts.get("b").is_none());
+        assert!(stmts.get("a").is_some());
+
+        // Replacing a statement doesn't evict others.
+        stmts.insert("c".to_string(), sql_plan("SELECT 4"));
+        assert_eq!("SELECT 4", stmts.get("c").unwrap().query);
+        assert!(stmts.get("a").is_some());
+
+        stmts.remove("a");
+        assert_eq!(1, stmts.plans.len());
+        assert_eq!(1, stmts.usage.len());
+
+        // Zero capacity means unlimited.
+        let mut stmts = PreparedStatements::new(0);
+        for i in 0..10 {
+            stmts.insert(i.to_string(), sql_plan("SELECT 1"));
+        }
+        assert_eq!(10, stmts.plans.len());
+    }
+}
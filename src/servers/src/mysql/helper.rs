@@ -213,7 +213,8 @@ pub fn convert_value(param: &ParamValue, t: &ConcreteDataType) -> Result<ScalarV
             Ok(ScalarValue::Date32(Some(date.val())))
         }
         ValueInner::Datetime(_) => {
-            let timestamp_millis = to_naive_datetime(param.value)
+            // MySQL datetime has at most 6 fractional digits.
+            let timestamp_micros = to_naive_datetime(param.value)
                 .map_err(|e| {
                     error::MysqlValueConversionSnafu {
                         err_msg: e.to_string(),
@@ -221,13 +222,12 @@ pub fn convert_value(param: &ParamValue, t: &ConcreteDataType) -> Result<ScalarV
                     .build()
                 })?
                 .and_utc()
-                .timestamp_millis();
+                .timestamp_micros();
 
             match t {
-                ConcreteDataType::Timestamp(_) => Ok(ScalarValue::TimestampMillisecond(
-                    Some(timestamp_millis),
-                    None,
-                )),
+                ConcreteDataType::Timestamp(ts_type) => {
+                    timestamp_to_scalar_value(Timestamp::new_microsecond(timestamp_micros), ts_type)
+                }
                 _ => error::PreparedStmtTypeMismatchSnafu {
                     expected: t,
                     actual: param.coltype,
@@ -275,20 +275,23 @@ pub fn convert_expr_to_scalar_value(param: &Expr, t: &ConcreteDataType) -> Resul
 }
 
 fn covert_bytes_to_timestamp(bytes: &[u8], ts_type: &TimestampType) -> Result<ScalarValue> {
-    let ts = Timestamp::from_str_utc(&String::from_utf8_lossy(bytes))
-        .map_err(|e| {
-            error::MysqlValueConversionSnafu {
-                err_msg: e.to_string(),
-            }
-            .build()
-        })?
-        .convert_to(ts_type.unit())
-        .ok_or_else(|| {
-            error::MysqlValueConversionSnafu {
-                err_msg: "Overflow when converting timestamp to target unit".to_string(),
-            }
-            .build()
-        })?;
+    let ts = Timestamp::from_str_utc(&String::from_utf8_lossy(bytes)).map_err(|e| {
+        error::MysqlValueConversionSnafu {
+            err_msg: e.to_string(),
+        }
+        .build()
+    })?;
+    timestamp_to_scalar_value(ts, ts_type)
+}
+
+/// Converts the timestamp to the precision of `ts_type`.
+fn timestamp_to_scalar_value(ts: Timestamp, ts_type: &TimestampType) -> Result<ScalarValue> {
+    let ts = ts.convert_to(ts_type.unit()).ok_or_else(|| {
+        error::MysqlValueConversionSnafu {
+            err_msg: "Overflow when converting timestamp to target unit".to_string(),
+        }
+        .build()
+    })?;
     match ts_type {
         TimestampType::Nanosecond(_) => {
             Ok(ScalarValue::TimestampNanosecond(Some(ts.value()), None))
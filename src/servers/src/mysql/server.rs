@@ -18,19 +18,24 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use auth::UserProviderRef;
+use common_error::ext::ErrorExt;
 use common_runtime::runtime::RuntimeTrait;
 use common_runtime::Runtime;
 use common_telemetry::{debug, warn};
+use futures::future::{AbortHandle, Abortable};
 use futures::StreamExt;
 use opensrv_mysql::{
     plain_run_with_options, secure_run_with_options, AsyncMysqlIntermediary, IntermediaryOptions,
 };
 use snafu::ensure;
 use tokio;
-use tokio::io::BufWriter;
+use tokio::io::{AsyncWriteExt, BufWriter};
 use tokio::net::TcpStream;
 use tokio_rustls::rustls::ServerConfig;
 
+use crate::connection::{
+    ConnectionGuard, ConnectionManager, ConnectionManagerRef, ConnectionOptions,
+};
 use crate::error::{Error, Result, TlsRequiredSnafu};
 use crate::mysql::handler::MysqlInstanceShim;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
@@ -39,6 +44,8 @@ use crate::tls::ReloadableTlsServerConfig;
 
 // Default size of ResultSet write buffer: 100KB
 const DEFAULT_RESULT_SET_WRITE_BUFFER_SIZE: usize = 100 * 1024;
+/// Error code of `ER_CON_COUNT_ERROR`, i.e. too many connections.
+const ER_CON_COUNT_ERROR: u16 = 1040;
 
 /// [`MysqlSpawnRef`] stores arc refs
 /// that should be passed to new [`MysqlInstanceShim`]s.
@@ -76,6 +83,8 @@ pub struct MysqlSpawnConfig {
     keep_alive_secs: u64,
     // other shim config
     reject_no_database: bool,
+    // connection limits
+    connection: ConnectionOptions,
 }
 
 impl MysqlSpawnConfig {
@@ -90,9 +99,15 @@ impl MysqlSpawnConfig {
             tls,
             keep_alive_secs,
             reject_no_database,
+            connection: ConnectionOptions::default(),
         }
     }
 
+    pub fn with_connection_options(mut self, connection: ConnectionOptions) -> Self {
+        self.connection = connection;
+        self
+    }
+
     fn tls(&self) -> Option<Arc<ServerConfig>> {
         self.tls.get_server_config()
     }
@@ -111,6 +126,7 @@ pub struct MysqlServer {
     base_server: BaseTcpServer,
     spawn_ref: Arc<MysqlSpawnRef>,
     spawn_config: Arc<MysqlSpawnConfig>,
+    connections: ConnectionManagerRef,
 }
 
 impl MysqlServer {
@@ -119,21 +135,29 @@ impl MysqlServer {
         spawn_ref: Arc<MysqlSpawnRef>,
         spawn_config: Arc<MysqlSpawnConfig>,
     ) -> Box<dyn Server> {
+        let connections = Arc::new(ConnectionManager::new(
+            "mysql",
+            spawn_config.connection,
+            crate::metrics::METRIC_MYSQL_CONNECTIONS.clone(),
+        ));
         Box::new(MysqlServer {
             base_server: BaseTcpServer::create_server("MySQL", io_runtime),
             spawn_ref,
             spawn_config,
+            connections,
         })
     }
 
     fn accept(&self, io_runtime: Runtime, stream: AbortableStream) -> impl Future<Output = ()> {
         let spawn_ref = self.spawn_ref.clone();
         let spawn_config = self.spawn_config.clone();
+        let connections = self.connections.clone();
 
         stream.for_each(move |tcp_stream| {
             let spawn_ref = spawn_ref.clone();
             let spawn_config = spawn_config.clone();
             let io_runtime = io_runtime.clone();
+            let connections = connections.clone();
 
             async move {
                 match tcp_stream {
@@ -142,27 +166,59 @@ impl MysqlServer {
                         if let Err(e) = io_stream.set_nodelay(true) {
                             warn!(e; "Failed to set TCP nodelay");
                         }
-                        io_runtime.spawn(async move {
-                            if let Err(error) =
-                                Self::handle(io_stream, spawn_ref, spawn_config).await
-                            {
-                                warn!(error; "Unexpected error when handling TcpStream");
-                            };
-                        });
+                        let (abort_handle, registration) = AbortHandle::new_pair();
+                        let connection = match connections.register(abort_handle) {
+                            Ok(connection) => connection,
+                            Err(e) => {
+                                warn!(e; "Reject MySQL connection");
+                                io_runtime.spawn(Self::reject(io_stream, e));
+                                return;
+                            }
+                        };
+                        // The connection task is aborted if the connection is idle for too long.
+                        io_runtime.spawn(Abortable::new(
+                            async move {
+                                if let Err(error) =
+                                    Self::handle(io_stream, spawn_ref, spawn_config, connection)
+                                        .await
+                                {
+                                    warn!(error; "Unexpected error when handling TcpStream");
+                                };
+                            },
+                            registration,
+                        ));
                     }
                 };
             }
         })
     }
 
+    /// Sends the error in place of the handshake and closes the connection, like
+    /// MySQL does when there are too many connections.
+    async fn reject(mut stream: TcpStream, error: Error) {
+        let message = error.output_msg();
+        let payload_len = 3 + message.len();
+        let mut packet = Vec::with_capacity(4 + payload_len);
+        packet.extend_from_slice(&(payload_len as u32).to_le_bytes()[..3]);
+        // Sequence id.
+        packet.push(0);
+        packet.push(0xff);
+        packet.extend_from_slice(&ER_CON_COUNT_ERROR.to_le_bytes());
+        packet.extend_from_slice(message.as_bytes());
+        if let Err(e) = stream.write_all(&packet).await {
+            debug!("Failed to send error to rejected MySQL connection: {}", e);
+        }
+        let _ = stream.shutdown().await;
+    }
+
     async fn handle(
         stream: TcpStream,
         spawn_ref: Arc<MysqlSpawnRef>,
         spawn_config: Arc<MysqlSpawnConfig>,
+        connection: ConnectionGuard,
     ) -> Result<()> {
         debug!("MySQL connection coming from: {}", stream.peer_addr()?);
-        crate::metrics::METRIC_MYSQL_CONNECTIONS.inc();
-        if let Err(e) = Self::do_handle(stream, spawn_ref, spawn_config).await {
+        if let Err(e) = Self::do_handle(stream, spawn_ref, spawn_config, connection).await {
             if let Error::InternalIo { error } = &e
                 && error.kind() == std::io::ErrorKind::ConnectionAborted
             {
@@ -173,7 +229,6 @@ impl MysqlServer {
                 warn!(e; "Internal error occurred during query exec, server actively close the channel to let client try next time");
             }
         }
-        crate::metrics::METRIC_MYSQL_CONNECTIONS.dec();
 
         Ok(())
     }
@@ -182,11 +237,13 @@ impl MysqlServer {
         stream: TcpStream,
         spawn_ref: Arc<MysqlSpawnRef>,
         spawn_config: Arc<MysqlSpawnConfig>,
+        connection: ConnectionGuard,
     ) -> Result<()> {
         let mut shim = MysqlInstanceShim::create(
             spawn_ref.query_handler(),
             spawn_ref.user_provider(),
             stream.peer_addr()?,
+            connection,
         );
         let (mut r, w) = stream.into_split();
         let mut w = BufWriter::with_capacity(DEFAULT_RESULT_SET_WRITE_BUFFER_SIZE, w);
@@ -227,6 +284,7 @@ impl Server for MysqlServer {
             .bind(listening, self.spawn_config.keep_alive_secs)
             .await?;
         let io_runtime = self.base_server.io_runtime();
+        self.connections.start_idle_reaper();
 
         let join_handle = common_runtime::spawn_global(self.accept(io_runtime, stream));
         self.base_server.start_with(join_handle).await?;
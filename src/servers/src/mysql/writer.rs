@@ -14,11 +14,13 @@
 
 use std::ops::Deref;
 
+use chrono::{NaiveDateTime, SubsecRound};
 use common_error::ext::ErrorExt;
 use common_error::status_code::StatusCode;
 use common_query::{Output, OutputData};
 use common_recordbatch::{RecordBatch, SendableRecordBatchStream};
 use common_telemetry::{debug, error};
+use common_time::timestamp::TimeUnit;
 use datatypes::prelude::{ConcreteDataType, Value};
 use datatypes::schema::SchemaRef;
 use datatypes::types::json_type_value_to_string;
@@ -34,12 +36,20 @@ use crate::error::{self, ConvertSqlValueSnafu, Error, Result};
 use crate::metrics::*;
 
 /// Try to write multiple output to the writer if possible.
+///
+/// `binary_protocol` is true if the outputs are the results of a prepared
+/// statement execution, whose rows are sent in the binary protocol.
 pub async fn write_output<W: AsyncWrite + Send + Sync + Unpin>(
     w: QueryResultWriter<'_, W>,
     query_context: QueryContextRef,
     outputs: Vec<Result<Output>>,
+    binary_protocol: bool,
 ) -> Result<()> {
-    let mut writer = Some(MysqlResultWriter::new(w, query_context.clone()));
+    let mut writer = Some(MysqlResultWriter::new(
+        w,
+        query_context.clone(),
+        binary_protocol,
+    ));
     for output in outputs {
         let result_writer = writer.take().context(error::InternalSnafu {
             err_msg: "Sending multiple result set is unsupported",
@@ -84,16 +94,19 @@ struct QueryResult {
 pub struct MysqlResultWriter<'a, W: AsyncWrite + Unpin> {
     writer: QueryResultWriter<'a, W>,
     query_context: QueryContextRef,
+    binary_protocol: bool,
 }
 
 impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
     pub fn new(
         writer: QueryResultWriter<'a, W>,
         query_context: QueryContextRef,
+        binary_protocol: bool,
     ) -> MysqlResultWriter<'a, W> {
         MysqlResultWriter::<'a, W> {
             writer,
             query_context,
+            binary_protocol,
         }
     }
 
@@ -111,20 +124,34 @@ impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
                         schema: stream.schema(),
                         stream,
                     };
-                    Self::write_query_result(query_result, self.writer, self.query_context).await?;
+                    Self::write_query_result(
+                        query_result,
+                        self.writer,
+                        self.query_context,
+                        self.binary_protocol,
+                    )
+                    .await?;
                 }
                 OutputData::RecordBatches(recordbatches) => {
                     let query_result = QueryResult {
                         schema: recordbatches.schema(),
                         stream: recordbatches.as_stream(),
                     };
-                    Self::write_query_result(query_result, self.writer, self.query_context).await?;
+                    Self::write_query_result(
+                        query_result,
+                        self.writer,
+                        self.query_context,
+                        self.binary_protocol,
+                    )
+                    .await?;
                 }
                 OutputData::AffectedRows(rows) => {
-                    let next_writer = Self::write_affected_rows(self.writer, rows).await?;
+                    let next_writer =
+                        Self::write_affected_rows(self.writer, rows, &self.query_context).await?;
                     return Ok(Some(MysqlResultWriter::new(
                         next_writer,
                         self.query_context,
+                        self.binary_protocol,
                     )));
                 }
             },
@@ -142,10 +169,12 @@ impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
     async fn write_affected_rows(
         w: QueryResultWriter<'a, W>,
         rows: usize,
+        query_context: &QueryContextRef,
     ) -> Result<QueryResultWriter<'a, W>> {
         let next_writer = w
             .complete_one(OkResponse {
                 affected_rows: rows as u64,
+                info: query_context.warning().unwrap_or_default(),
                 ..Default::default()
             })
             .await?;
@@ -156,6 +185,7 @@ impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
         mut query_result: QueryResult,
         writer: QueryResultWriter<'a, W>,
         query_context: QueryContextRef,
+        binary_protocol: bool,
     ) -> Result<()> {
         match create_mysql_column_def(&query_result.schema) {
             Ok(column_def) => {
@@ -170,6 +200,7 @@ impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
                                 &record_batch,
                                 query_context.clone(),
                                 &query_result.schema,
+                                binary_protocol,
                             )
                             .await?
                         }
@@ -194,6 +225,7 @@ impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
         recordbatch: &RecordBatch,
         query_context: QueryContextRef,
         schema: &SchemaRef,
+        binary_protocol: bool,
     ) -> Result<()> {
         for row in recordbatch.rows() {
             for (value, column) in row.into_iter().zip(schema.column_schemas().iter()) {
@@ -223,9 +255,17 @@ impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
                     },
                     Value::Date(v) => row_writer.write_col(v.to_chrono_date())?,
                     // convert timestamp to timezone of current connection
-                    Value::Timestamp(v) => row_writer.write_col(
-                        v.to_chrono_datetime_with_timezone(Some(&query_context.timezone())),
-                    )?,
+                    Value::Timestamp(v) => {
+                        let datetime =
+                            v.to_chrono_datetime_with_timezone(Some(&query_context.timezone()));
+                        let digits = timestamp_fractional_digits(v.unit());
+                        if binary_protocol {
+                            row_writer.write_col(datetime.map(|dt| dt.trunc_subsecs(digits)))?
+                        } else {
+                            row_writer
+                                .write_col(datetime.map(|dt| format_timestamp_text(&dt, digits)))?
+                        }
+                    }
                     Value::IntervalYearMonth(v) => row_writer.write_col(v.to_iso8601_string())?,
                     Value::IntervalDayTime(v) => row_writer.write_col(v.to_iso8601_string())?,
                     Value::IntervalMonthDayNano(v) => {
@@ -266,6 +306,27 @@ impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
     }
 }
 
+/// Returns the number of fractional second digits MySQL renders for a timestamp
+/// of `unit`. MySQL supports at most 6 digits, so nanoseconds are truncated to
+/// microseconds.
+fn timestamp_fractional_digits(unit: TimeUnit) -> u16 {
+    match unit {
+        TimeUnit::Second => 0,
+        TimeUnit::Millisecond => 3,
+        TimeUnit::Microsecond | TimeUnit::Nanosecond => 6,
+    }
+}
+
+/// Formats the datetime like MySQL's `TIMESTAMP(digits)` in the text protocol.
+fn format_timestamp_text(datetime: &NaiveDateTime, digits: u16) -> String {
+    let datetime = datetime.trunc_subsecs(digits);
+    match digits {
+        0 => datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+        3 => datetime.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+        _ => datetime.format("%Y-%m-%d %H:%M:%S%.6f").to_string(),
+    }
+}
+
 pub(crate) fn create_mysql_column(
     data_type: &ConcreteDataType,
     column_name: &str,
@@ -341,7 +402,7 @@ fn mysql_error_kind(status_code: &StatusCode) -> ErrorKind {
             ErrorKind::ER_TABLE_EXISTS_ERROR
         }
         StatusCode::RegionNotFound | StatusCode::TableNotFound => ErrorKind::ER_NO_SUCH_TABLE,
-        StatusCode::RegionReadonly => ErrorKind::ER_READ_ONLY_MODE,
+        StatusCode::RegionReadonly | StatusCode::TableReadonly => ErrorKind::ER_READ_ONLY_MODE,
         StatusCode::DatabaseNotFound => ErrorKind::ER_WRONG_DB_NAME,
         StatusCode::UserNotFound => ErrorKind::ER_NO_SUCH_USER,
         StatusCode::UnsupportedPasswordType => ErrorKind::ER_PASSWORD_FORMAT,
@@ -17,6 +17,10 @@ use common_query::prelude::{GREPTIME_TIMESTAMP, GREPTIME_VALUE};
 
 use crate::error::{self, Result};
 
+/// Maximum number of tags of a data point. Each tag becomes a primary key
+/// column of the metric table.
+pub const MAX_TAGS: usize = 256;
+
 #[derive(Debug, Clone)]
 pub struct DataPoint {
     metric: String,
@@ -97,12 +101,47 @@ impl DataPoint {
             tags.push((tagk, tagv));
         }
 
-        Ok(DataPoint {
+        let data_point = DataPoint {
             metric: metric.to_string(),
             ts_millis,
             value,
             tags,
-        })
+        };
+        data_point.validate()?;
+        Ok(data_point)
+    }
+
+    /// Validates the data point against the schema of the metric table: the
+    /// metric names the table, the tags become its primary key columns, and
+    /// the columns [GREPTIME_TIMESTAMP] and [GREPTIME_VALUE] are reserved.
+    pub fn validate(&self) -> Result<()> {
+        if self.metric.is_empty() {
+            return error::InvalidOpentsdbDataPointSnafu {
+                reason: "empty metric name",
+            }
+            .fail();
+        }
+        if self.tags.len() > MAX_TAGS {
+            return error::InvalidOpentsdbDataPointSnafu {
+                reason: format!("too many tags: {}, max: {MAX_TAGS}", self.tags.len()),
+            }
+            .fail();
+        }
+        for (tagk, tagv) in &self.tags {
+            if tagk.is_empty() || tagv.is_empty() {
+                return error::InvalidOpentsdbDataPointSnafu {
+                    reason: format!("empty tag key or value: {tagk}={tagv}"),
+                }
+                .fail();
+            }
+            if tagk == GREPTIME_TIMESTAMP || tagk == GREPTIME_VALUE {
+                return error::InvalidOpentsdbDataPointSnafu {
+                    reason: format!("tag {tagk} conflicts with the reserved column"),
+                }
+                .fail();
+            }
+        }
+        Ok(())
     }
 
     pub fn metric(&self) -> &str {
@@ -187,6 +226,8 @@ impl DataPoint {
 
 #[cfg(test)]
 mod test {
+    use common_error::ext::ErrorExt;
+
     use super::*;
 
     #[test]
@@ -244,6 +285,37 @@ mod test {
             data_point.tags,
             vec![("host".to_string(), "web01".to_string())]
         );
+
+        let result = DataPoint::try_create("put sys.procs.running 1479496100 42 greptime_value=1");
+        assert_eq!(
+            result.unwrap_err().output_msg(),
+            "Invalid OpenTSDB data point: tag greptime_value conflicts with the reserved column"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let tags = (0..MAX_TAGS)
+            .map(|i| (format!("tag{i}"), "v".to_string()))
+            .collect::<Vec<_>>();
+        let mut data_point = DataPoint::new("m".to_string(), 1000, 1.0, tags);
+        assert!(data_point.validate().is_ok());
+
+        data_point
+            .tags
+            .push(("one_more".to_string(), "v".to_string()));
+        assert!(data_point.validate().is_err());
+
+        let data_point = DataPoint::new("".to_string(), 1000, 1.0, vec![]);
+        assert!(data_point.validate().is_err());
+
+        let data_point = DataPoint::new(
+            "m".to_string(),
+            1000,
+            1.0,
+            vec![(GREPTIME_TIMESTAMP.to_string(), "v".to_string())],
+        );
+        assert!(data_point.validate().is_err());
     }
 
     #[test]
@@ -24,7 +24,7 @@ use jsonb::{Number as JsonbNumber, Value as JsonbValue};
 use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
 use opentelemetry_proto::tonic::common::v1::{any_value, AnyValue, InstrumentationScope, KeyValue};
 use opentelemetry_proto::tonic::logs::v1::{LogRecord, ResourceLogs, ScopeLogs};
-use pipeline::{GreptimePipelineParams, PipelineWay, SchemaInfo, SelectInfo};
+use pipeline::{GreptimePipelineParams, LogBodyStructure, PipelineWay, SchemaInfo, SelectInfo};
 use serde_json::{Map, Value};
 use session::context::QueryContextRef;
 use snafu::{ensure, ResultExt};
@@ -34,12 +34,17 @@ use crate::error::{
     UnsupportedJsonDataTypeForTagSnafu,
 };
 use crate::otlp::trace::attributes::OtlpAnyValue;
-use crate::otlp::utils::{bytes_to_hex_string, key_value_to_jsonb};
+use crate::otlp::utils::{any_value_to_jsonb, bytes_to_hex_string, key_value_to_jsonb};
 use crate::pipeline::run_pipeline;
 use crate::query_handler::PipelineHandlerRef;
 
 pub const LOG_TABLE_NAME: &str = "opentelemetry_logs";
 
+/// Column of the log body as a string.
+const BODY_COLUMN: &str = "body";
+/// Column of map-typed log bodies as JSON, see [LogBodyStructure::Json].
+const BODY_JSON_COLUMN: &str = "body_json";
+
 /// Convert OpenTelemetry metrics to GreptimeDB insert requests
 ///
 /// See
@@ -56,8 +61,9 @@ pub async fn to_grpc_insert_requests(
     pipeline_handler: PipelineHandlerRef,
 ) -> Result<(RowInsertRequests, usize)> {
     match pipeline {
-        PipelineWay::OtlpLogDirect(select_info) => {
-            let rows = parse_export_logs_service_request_to_rows(request, select_info)?;
+        PipelineWay::OtlpLogDirect(select_info, body_structure) => {
+            let rows =
+                parse_export_logs_service_request_to_rows(request, select_info, body_structure)?;
             let len = rows.rows.len();
             let insert_request = RowInsertRequest {
                 rows: Some(rows),
@@ -182,6 +188,34 @@ fn log_to_pipeline_value(
     Value::Object(map)
 }
 
+/// Builds the columns of the OTLP logs table written without a pipeline:
+///
+/// | Column                | Type                 | Semantic type |
+/// |-----------------------|----------------------|---------------|
+/// | `timestamp`           | TimestampNanosecond  | Timestamp     |
+/// | `trace_id`            | String               | Field         |
+/// | `span_id`             | String               | Field         |
+/// | `severity_text`       | String               | Field         |
+/// | `severity_number`     | Int32                | Field         |
+/// | `body`                | String, fulltext     | Field         |
+/// | `log_attributes`      | JSON                 | Field         |
+/// | `trace_flags`         | UInt32               | Field         |
+/// | `scope_name`          | String               | Tag           |
+/// | `scope_version`       | String               | Field         |
+/// | `scope_attributes`    | JSON                 | Field         |
+/// | `scope_schema_url`    | String               | Field         |
+/// | `resource_attributes` | JSON                 | Field         |
+/// | `resource_schema_url` | String               | Field         |
+///
+/// They are followed by the tags of the keys selected by the
+/// `x-greptime-log-extract-keys` header, and the columns of map-typed bodies
+/// chosen by the `x-greptime-log-structure` header:
+/// - `flatten`: a field column `body.<key>` for each leaf of the body. Strings,
+///   integers, doubles and booleans keep their types, bytes are stored as hex
+///   strings and arrays as JSON.
+/// - `json`: the `body_json` JSON field column.
+///
+/// Tables created by older versions only gain the new columns.
 fn build_otlp_logs_identity_schema() -> Vec<ColumnSchema> {
     [
         (
@@ -220,7 +254,7 @@ fn build_otlp_logs_identity_schema() -> Vec<ColumnSchema> {
             None,
         ),
         (
-            "body",
+            BODY_COLUMN,
             ColumnDataType::String,
             SemanticType::Field,
             None,
@@ -480,17 +514,25 @@ fn decide_column_schema_and_convert_value(
 fn parse_export_logs_service_request_to_rows(
     request: ExportLogsServiceRequest,
     select_info: Box<SelectInfo>,
+    body_structure: LogBodyStructure,
 ) -> Result<Rows> {
     let mut schemas = build_otlp_logs_identity_schema();
 
-    let mut parse_ctx = ParseContext::new(select_info);
+    let mut parse_ctx = ParseContext::new(select_info, body_structure);
     let mut rows = parse_resource(&mut parse_ctx, request.resource_logs)?;
 
     schemas.extend(parse_ctx.select_schema.schema);
-
-    rows.iter_mut().for_each(|row| {
-        row.values.resize(schemas.len(), GreptimeValue::default());
-    });
+    let body_column_start = schemas.len();
+    schemas.extend(parse_ctx.body_schema.schema);
+
+    rows.iter_mut()
+        .zip(parse_ctx.body_values)
+        .for_each(|(row, body_values)| {
+            row.values
+                .resize(body_column_start, GreptimeValue::default());
+            row.values.extend(body_values);
+            row.values.resize(schemas.len(), GreptimeValue::default());
+        });
 
     Ok(Rows {
         schema: schemas,
@@ -548,10 +590,17 @@ struct ParseContext<'a> {
     scope_version: Option<String>,
     scope_url: String,
     scope_attrs: JsonbValue<'a>,
+
+    // how to store map-typed bodies
+    body_structure: LogBodyStructure,
+    // schema infos of the body columns for current request
+    body_schema: SchemaInfo,
+    // values of the body columns of each row
+    body_values: Vec<Vec<GreptimeValue>>,
 }
 
 impl<'a> ParseContext<'a> {
-    pub fn new(select_info: Box<SelectInfo>) -> ParseContext<'a> {
+    pub fn new(select_info: Box<SelectInfo>, body_structure: LogBodyStructure) -> ParseContext<'a> {
         let len = select_info.keys.len();
         ParseContext {
             select_info,
@@ -564,6 +613,9 @@ impl<'a> ParseContext<'a> {
             scope_version: None,
             scope_url: String::new(),
             scope_attrs: JsonbValue::Null,
+            body_structure,
+            body_schema: SchemaInfo::default(),
+            body_values: vec![],
         }
     }
 }
@@ -595,6 +647,8 @@ fn parse_log(log_records: Vec<LogRecord>, parse_ctx: &mut ParseContext) -> Resul
     let mut result = Vec::with_capacity(log_records.len());
 
     for log in log_records {
+        let body_values = parse_body(log.body.as_ref(), parse_ctx)?;
+        parse_ctx.body_values.push(body_values);
         let (mut row, log_attr) = build_otlp_build_in_row(log, parse_ctx);
 
         let log_values = extract_field_from_attr_and_combine_schema(
@@ -616,6 +670,117 @@ fn parse_log(log_records: Vec<LogRecord>, parse_ctx: &mut ParseContext) -> Resul
     Ok(result)
 }
 
+/// Converts a map-typed log body to the values of the body columns, according to
+/// the [LogBodyStructure]. The values are indexed by the body schema.
+fn parse_body(body: Option<&AnyValue>, parse_ctx: &mut ParseContext) -> Result<Vec<GreptimeValue>> {
+    let Some(any_value::Value::KvlistValue(kvlist)) = body.and_then(|body| body.value.as_ref())
+    else {
+        return Ok(vec![]);
+    };
+
+    let mut columns = vec![];
+    match parse_ctx.body_structure {
+        LogBodyStructure::String => return Ok(vec![]),
+        LogBodyStructure::Flatten => flatten_body(BODY_COLUMN, &kvlist.values, &mut columns),
+        LogBodyStructure::Json => columns.push((
+            BODY_JSON_COLUMN.to_string(),
+            ColumnDataType::Binary,
+            Some(json_type_extension()),
+            ValueData::BinaryValue(key_value_to_jsonb(kvlist.values.clone()).to_vec()),
+        )),
+    }
+
+    let body_schema = &mut parse_ctx.body_schema;
+    let mut values = vec![GreptimeValue::default(); body_schema.schema.len()];
+    for (column_name, datatype, datatype_extension, value) in columns {
+        let value = GreptimeValue {
+            value_data: Some(value),
+        };
+        if let Some(index) = body_schema.index.get(&column_name) {
+            let column_schema = &body_schema.schema[*index];
+            // datatype of the same column name should be the same
+            ensure!(
+                column_schema.datatype == datatype as i32,
+                IncompatibleSchemaSnafu {
+                    column_name,
+                    datatype: column_schema.datatype().as_str_name(),
+                    expected: column_schema.datatype,
+                    actual: datatype as i32,
+                }
+            );
+            values[*index] = value;
+        } else {
+            body_schema.schema.push(ColumnSchema {
+                column_name: column_name.clone(),
+                datatype: datatype as i32,
+                semantic_type: SemanticType::Field as i32,
+                datatype_extension,
+                options: None,
+            });
+            body_schema
+                .index
+                .insert(column_name, body_schema.schema.len() - 1);
+            values.push(value);
+        }
+    }
+
+    Ok(values)
+}
+
+/// Flattens the key-values of a log body to columns named `<prefix>.<key>`.
+fn flatten_body(
+    prefix: &str,
+    key_values: &[KeyValue],
+    columns: &mut Vec<(
+        String,
+        ColumnDataType,
+        Option<ColumnDataTypeExtension>,
+        ValueData,
+    )>,
+) {
+    for kv in key_values {
+        let column_name = format!("{prefix}.{}", kv.key);
+        let Some(value) = kv.value.as_ref().and_then(|v| v.value.as_ref()) else {
+            continue;
+        };
+        let (datatype, datatype_extension, value) = match value {
+            any_value::Value::KvlistValue(kvlist) => {
+                flatten_body(&column_name, &kvlist.values, columns);
+                continue;
+            }
+            any_value::Value::StringValue(s) => (
+                ColumnDataType::String,
+                None,
+                ValueData::StringValue(s.clone()),
+            ),
+            any_value::Value::IntValue(i) => (ColumnDataType::Int64, None, ValueData::I64Value(*i)),
+            any_value::Value::DoubleValue(d) => {
+                (ColumnDataType::Float64, None, ValueData::F64Value(*d))
+            }
+            any_value::Value::BoolValue(b) => {
+                (ColumnDataType::Boolean, None, ValueData::BoolValue(*b))
+            }
+            any_value::Value::BytesValue(b) => (
+                ColumnDataType::String,
+                None,
+                ValueData::StringValue(bytes_to_hex_string(b)),
+            ),
+            any_value::Value::ArrayValue(_) => (
+                ColumnDataType::Binary,
+                Some(json_type_extension()),
+                ValueData::BinaryValue(any_value_to_jsonb(value.clone()).to_vec()),
+            ),
+        };
+        columns.push((column_name, datatype, datatype_extension, value));
+    }
+}
+
+fn json_type_extension() -> ColumnDataTypeExtension {
+    ColumnDataTypeExtension {
+        type_ext: Some(TypeExt::JsonType(JsonTypeExtension::JsonBinary.into())),
+    }
+}
+
 fn merge_values(
     log: Vec<GreptimeValue>,
     scope: &[GreptimeValue],
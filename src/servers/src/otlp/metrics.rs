@@ -12,14 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use api::v1::{RowInsertRequests, Value};
+use api::v1::value::ValueData;
+use api::v1::{ColumnDataType, RowInsertRequests, Value};
 use common_grpc::precision::Precision;
-use common_query::prelude::{GREPTIME_COUNT, GREPTIME_TIMESTAMP, GREPTIME_VALUE};
+use common_query::prelude::{GREPTIME_COUNT, GREPTIME_CREATED, GREPTIME_TIMESTAMP, GREPTIME_VALUE};
 use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
 use opentelemetry_proto::tonic::common::v1::{any_value, KeyValue};
-use opentelemetry_proto::tonic::metrics::v1::{metric, number_data_point, *};
-
-use crate::error::Result;
+use opentelemetry_proto::tonic::metrics::v1::{
+    exponential_histogram_data_point, metric, number_data_point, *,
+};
+use promql::native_histogram::{
+    BucketSpan, NativeHistogram, MAX_SCHEMA, MIN_SCHEMA, NATIVE_HISTOGRAM_LAYOUT,
+};
+
+use crate::error::{InvalidParameterSnafu, Result};
 use crate::row_writer::{self, MultiTableData, TableData};
 
 /// the default column count for table writer
@@ -83,8 +89,15 @@ fn encode_metrics(
             metric::Data::Histogram(hist) => {
                 encode_histogram(table_writer, name, hist, resource_attrs, scope_attrs)?;
             }
-            // TODO(sunng87) leave ExponentialHistogram for next release
-            metric::Data::ExponentialHistogram(_hist) => {}
+            metric::Data::ExponentialHistogram(hist) => {
+                encode_exponential_histogram(
+                    table_writer,
+                    name,
+                    hist,
+                    resource_attrs,
+                    scope_attrs,
+                )?;
+            }
         }
     }
 
@@ -199,7 +212,9 @@ fn encode_gauge(
 
 /// encode this sum metric
 ///
-/// `aggregation_temporality` and `monotonic` are ignored for now
+/// The start time of monotonic cumulative sums, i.e. counters, is stored in the
+/// `greptime_created` column, so the PromQL `rate()` and `increase()` don't
+/// extrapolate before the counter was created.
 fn encode_sum(
     table_writer: &mut MultiTableData,
     name: &str,
@@ -213,6 +228,9 @@ fn encode_sum(
         sum.data_points.len(),
     );
 
+    let is_counter = sum.is_monotonic
+        && sum.aggregation_temporality == AggregationTemporality::Cumulative as i32;
+
     for data_point in &sum.data_points {
         let mut row = table.alloc_one_row();
         write_tags_and_timestamp(
@@ -224,6 +242,20 @@ fn encode_sum(
             data_point.time_unix_nano as i64,
         )?;
         write_data_point_value(table, &mut row, GREPTIME_VALUE, &data_point.value)?;
+        // the start time is optional, 0 means unknown
+        if is_counter && data_point.start_time_unix_nano > 0 {
+            row_writer::write_fields(
+                table,
+                std::iter::once((
+                    GREPTIME_CREATED.to_string(),
+                    ColumnDataType::TimestampMillisecond,
+                    ValueData::TimestampMillisecondValue(
+                        (data_point.start_time_unix_nano / 1_000_000) as i64,
+                    ),
+                )),
+                &mut row,
+            )?;
+        }
         table.add_row(row);
     }
 
@@ -344,12 +376,113 @@ fn encode_histogram(
     Ok(())
 }
 
-#[allow(dead_code)]
-fn encode_exponential_histogram(_name: &str, _hist: &ExponentialHistogram) -> Result<()> {
-    // TODO(sunng87): implement this using a prometheus compatible way
+/// Encode exponential histogram data as Prometheus native histograms.
+///
+/// Each data point is stored as a row in the `%metric%` table, with the
+/// histogram in the columns of the [NATIVE_HISTOGRAM_LAYOUT]. Scales finer than the
+/// native histogram schemas support are downscaled by merging adjacent buckets.
+/// Delta temporality histograms are stored as gauge histograms since they don't
+/// accumulate.
+fn encode_exponential_histogram(
+    table_writer: &mut MultiTableData,
+    name: &str,
+    hist: &ExponentialHistogram,
+    resource_attrs: Option<&Vec<KeyValue>>,
+    scope_attrs: Option<&Vec<KeyValue>>,
+) -> Result<()> {
+    let table = table_writer.get_or_default_table_data(
+        normalize_otlp_name(name),
+        APPROXIMATE_COLUMN_COUNT,
+        hist.data_points.len(),
+    );
+    let is_gauge = hist.aggregation_temporality == AggregationTemporality::Delta as i32;
+
+    for data_point in &hist.data_points {
+        let histogram = to_native_histogram(data_point, is_gauge)?;
+
+        let mut row = table.alloc_one_row();
+        write_tags_and_timestamp(
+            table,
+            &mut row,
+            resource_attrs,
+            scope_attrs,
+            Some(data_point.attributes.as_ref()),
+            data_point.time_unix_nano as i64,
+        )?;
+        row_writer::write_native_histogram(table, &histogram, &mut row)?;
+        table.add_row(row);
+    }
+
     Ok(())
 }
 
+fn to_native_histogram(
+    data_point: &ExponentialHistogramDataPoint,
+    is_gauge: bool,
+) -> Result<NativeHistogram> {
+    if data_point.scale < MIN_SCHEMA {
+        return InvalidParameterSnafu {
+            reason: format!(
+                "exponential histogram scale {} is smaller than {MIN_SCHEMA}",
+                data_point.scale
+            ),
+        }
+        .fail();
+    }
+    let scale_down = (data_point.scale - MAX_SCHEMA).max(0) as u32;
+    let (positive_spans, positive_buckets) =
+        to_native_buckets(data_point.positive.as_ref(), scale_down);
+    let (negative_spans, negative_buckets) =
+        to_native_buckets(data_point.negative.as_ref(), scale_down);
+
+    Ok(NativeHistogram {
+        schema: data_point.scale.min(MAX_SCHEMA),
+        is_gauge,
+        zero_threshold: data_point.zero_threshold,
+        zero_count: data_point.zero_count as f64,
+        count: data_point.count as f64,
+        sum: data_point.sum.unwrap_or_default(),
+        positive_spans,
+        positive_buckets,
+        negative_spans,
+        negative_buckets,
+        custom_values: vec![],
+    })
+}
+
+/// Converts dense OTLP buckets to a single span of native histogram buckets.
+///
+/// OTLP bucket `i` covers `(base^i, base^(i+1)]` while native histogram bucket
+/// `i` covers `(base^(i-1), base^i]`, so indexes are shifted by one.
+fn to_native_buckets(
+    buckets: Option<&exponential_histogram_data_point::Buckets>,
+    scale_down: u32,
+) -> (Vec<BucketSpan>, Vec<f64>) {
+    let Some(buckets) = buckets else {
+        return (vec![], vec![]);
+    };
+    if buckets.bucket_counts.is_empty() {
+        return (vec![], vec![]);
+    }
+
+    let start = (buckets.offset >> scale_down) + 1;
+    let mut counts: Vec<f64> = vec![];
+    for (i, count) in buckets.bucket_counts.iter().enumerate() {
+        let index = ((buckets.offset + i as i32) >> scale_down) + 1;
+        let pos = (index - start) as usize;
+        if pos == counts.len() {
+            counts.push(0.0);
+        }
+        counts[pos] += *count as f64;
+    }
+
+    let span = BucketSpan {
+        offset: start,
+        length: counts.len() as u32,
+    };
+    (vec![span], counts)
+}
+
 fn encode_summary(
     table_writer: &mut MultiTableData,
     name: &str,
@@ -394,9 +527,12 @@ fn encode_summary(
 mod tests {
     use opentelemetry_proto::tonic::common::v1::any_value::Value as Val;
     use opentelemetry_proto::tonic::common::v1::{AnyValue, KeyValue};
+    use opentelemetry_proto::tonic::metrics::v1::exponential_histogram_data_point::Buckets;
     use opentelemetry_proto::tonic::metrics::v1::number_data_point::Value;
     use opentelemetry_proto::tonic::metrics::v1::summary_data_point::ValueAtQuantile;
-    use opentelemetry_proto::tonic::metrics::v1::{HistogramDataPoint, NumberDataPoint};
+    use opentelemetry_proto::tonic::metrics::v1::{
+        ExponentialHistogramDataPoint, HistogramDataPoint, NumberDataPoint,
+    };
 
     use super::*;
 
@@ -515,6 +651,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_encode_counter_created() {
+        let mut tables = MultiTableData::default();
+
+        let data_points = vec![
+            NumberDataPoint {
+                attributes: vec![keyvalue("host", "testserver")],
+                start_time_unix_nano: 0,
+                time_unix_nano: 100_000_000,
+                value: Some(Value::AsInt(100)),
+                ..Default::default()
+            },
+            NumberDataPoint {
+                attributes: vec![keyvalue("host", "testserver")],
+                start_time_unix_nano: 90_000_000,
+                time_unix_nano: 105_000_000,
+                value: Some(Value::AsInt(3)),
+                ..Default::default()
+            },
+        ];
+        let sum = Sum {
+            data_points: data_points.clone(),
+            aggregation_temporality: AggregationTemporality::Cumulative.into(),
+            is_monotonic: true,
+        };
+        encode_sum(&mut tables, "counter", &sum, None, None).unwrap();
+
+        let table = tables.get_or_default_table_data("counter", 0, 0);
+        assert_eq!(
+            table
+                .columns()
+                .iter()
+                .map(|c| &c.column_name)
+                .collect::<Vec<&String>>(),
+            vec![
+                "host",
+                "greptime_timestamp",
+                "greptime_value",
+                "greptime_created"
+            ]
+        );
+        let (_, rows) = std::mem::replace(table, TableData::new(0, 0)).into_schema_and_rows();
+        let created = rows
+            .into_iter()
+            .map(|row| row.values.get(3).and_then(|value| value.value_data.clone()))
+            .collect::<Vec<_>>();
+        // the unknown start time is left null
+        assert_eq!(
+            vec![None, Some(ValueData::TimestampMillisecondValue(90))],
+            created
+        );
+
+        // gauges and delta sums don't have a created time
+        let sum = Sum {
+            data_points,
+            aggregation_temporality: AggregationTemporality::Delta.into(),
+            is_monotonic: true,
+        };
+        encode_sum(&mut tables, "delta", &sum, None, None).unwrap();
+        let table = tables.get_or_default_table_data("delta", 0, 0);
+        assert_eq!(table.num_columns(), 3);
+    }
+
     #[test]
     fn test_encode_summary() {
         let mut tables = MultiTableData::default();
@@ -655,4 +854,109 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_encode_exponential_histogram() {
+        let mut tables = MultiTableData::default();
+
+        // The same observations as the Prometheus native histogram
+        // `{{schema:0 count:12 sum:100 z_bucket:2 z_bucket_w:0.001 buckets:[2 3 0 1 4]}}`.
+        let data_points = vec![ExponentialHistogramDataPoint {
+            attributes: vec![keyvalue("host", "testserver")],
+            time_unix_nano: 100,
+            count: 12,
+            sum: Some(100.),
+            scale: 0,
+            zero_count: 2,
+            zero_threshold: 0.001,
+            positive: Some(Buckets {
+                offset: -1,
+                bucket_counts: vec![2, 3, 0, 1, 4],
+            }),
+            ..Default::default()
+        }];
+        let histogram = ExponentialHistogram {
+            data_points,
+            aggregation_temporality: AggregationTemporality::Cumulative.into(),
+        };
+        encode_exponential_histogram(
+            &mut tables,
+            "latency",
+            &histogram,
+            Some(&vec![keyvalue("resource", "app")]),
+            None,
+        )
+        .unwrap();
+
+        let (requests, rows) = tables.into_row_insert_requests();
+        assert_eq!(1, rows);
+        let insert = &requests.inserts[0];
+        assert_eq!("latency", insert.table_name);
+        let rows = insert.rows.as_ref().unwrap();
+        let mut expected_names = vec!["resource", "host", "greptime_timestamp"];
+        expected_names.extend(NATIVE_HISTOGRAM_LAYOUT);
+        assert_eq!(
+            expected_names,
+            rows.schema
+                .iter()
+                .map(|c| c.column_name.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        let decoded = row_writer::read_native_histogram(rows, 0);
+        assert!(!decoded.is_gauge);
+        assert_eq!(
+            vec![BucketSpan {
+                offset: 0,
+                length: 5
+            }],
+            decoded.positive_spans
+        );
+        assert_eq!(vec![2.0, 3.0, 0.0, 1.0, 4.0], decoded.positive_buckets);
+        assert!((decoded.quantile(0.5) - 1.6666666666666665).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_exponential_histogram_downscale() {
+        let data_point = ExponentialHistogramDataPoint {
+            count: 6,
+            scale: 9,
+            positive: Some(Buckets {
+                offset: 1,
+                bucket_counts: vec![1, 2, 3],
+            }),
+            negative: Some(Buckets {
+                offset: -2,
+                bucket_counts: vec![4],
+            }),
+            ..Default::default()
+        };
+        let histogram = to_native_histogram(&data_point, true).unwrap();
+        assert_eq!(MAX_SCHEMA, histogram.schema);
+        assert!(histogram.is_gauge);
+        // OTLP buckets 1, 2, 3 are merged into native buckets 1, 2, 2.
+        assert_eq!(
+            vec![BucketSpan {
+                offset: 1,
+                length: 2
+            }],
+            histogram.positive_spans
+        );
+        assert_eq!(vec![1.0, 5.0], histogram.positive_buckets);
+        assert_eq!(
+            vec![BucketSpan {
+                offset: 0,
+                length: 1
+            }],
+            histogram.negative_spans
+        );
+        assert_eq!(vec![4.0], histogram.negative_buckets);
+        histogram.validate().unwrap();
+
+        let data_point = ExponentialHistogramDataPoint {
+            scale: MIN_SCHEMA - 1,
+            ..Default::default()
+        };
+        assert!(to_native_histogram(&data_point, false).is_err());
+    }
 }
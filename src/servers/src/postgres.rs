@@ -35,11 +35,12 @@ use pgwire::api::auth::ServerParameterProvider;
 use pgwire::api::copy::NoopCopyHandler;
 use pgwire::api::{ClientInfo, PgWireServerHandlers};
 pub use server::PostgresServer;
-use session::context::Channel;
+use session::context::{Channel, ConfigurationVariables};
 use session::Session;
 
 use self::auth_handler::PgLoginVerifier;
 use self::handler::DefaultQueryParser;
+use crate::connection::ConnectionGuard;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
 
 pub(crate) struct GreptimeDBStartupParameters {
@@ -77,6 +78,7 @@ pub struct PostgresServerHandlerInner {
 
     session: Arc<Session>,
     query_parser: Arc<DefaultQueryParser>,
+    connection: ConnectionGuard,
 }
 
 #[derive(Builder)]
@@ -119,8 +121,12 @@ impl PgWireServerHandlers for PostgresServerHandler {
 }
 
 impl MakePostgresServerHandler {
-    fn make(&self, addr: Option<SocketAddr>) -> PostgresServerHandler {
-        let session = Arc::new(Session::new(addr, Channel::Postgres, Default::default()));
+    fn make(&self, addr: Option<SocketAddr>, connection: ConnectionGuard) -> PostgresServerHandler {
+        let session = Arc::new(Session::new(
+            addr,
+            Channel::Postgres,
+            ConfigurationVariables::new().with_connection_limits(connection.limits()),
+        ));
         let handler = PostgresServerHandlerInner {
             query_handler: self.query_handler.clone(),
             login_verifier: PgLoginVerifier::new(self.user_provider.clone()),
@@ -129,6 +135,7 @@ impl MakePostgresServerHandler {
 
             session: session.clone(),
             query_parser: Arc::new(DefaultQueryParser::new(self.query_handler.clone(), session)),
+            connection,
         };
         PostgresServerHandler(Arc::new(handler))
     }
@@ -173,9 +173,12 @@ impl StartupHandler for PostgresServerHandlerInner {
                         ))
                         .await?;
                 } else {
-                    self.session.set_user_info(userinfo_by_name(
-                        client.metadata().get(super::METADATA_USER).cloned(),
-                    ));
+                    let user_info =
+                        userinfo_by_name(client.metadata().get(super::METADATA_USER).cloned());
+                    if let Err(e) = self.connection.set_user(user_info.username()) {
+                        return send_error(client, too_many_connections(e)).await;
+                    }
+                    self.session.set_user_info(user_info);
                     set_client_info(client, &self.session);
                     auth::finish_authentication(client, self.param_provider.as_ref()).await?;
                 }
@@ -192,6 +195,9 @@ impl StartupHandler for PostgresServerHandlerInner {
                 let auth_result = self.login_verifier.auth(&login_info, &pwd.password).await;
 
                 if let Ok(Some(user_info)) = auth_result {
+                    if let Err(e) = self.connection.set_user(user_info.username()) {
+                        return send_error(client, too_many_connections(e)).await;
+                    }
                     self.session.set_user_info(user_info);
                     set_client_info(client, &self.session);
                     auth::finish_authentication(client, self.param_provider.as_ref()).await?;
@@ -210,6 +216,10 @@ impl StartupHandler for PostgresServerHandlerInner {
     }
 }
 
+fn too_many_connections(error: crate::error::Error) -> ErrorInfo {
+    PgErrorCode::from(error.status_code()).to_err_info(error.output_msg())
+}
+
 async fn send_error<C>(client: &mut C, err_info: ErrorInfo) -> PgWireResult<()>
 where
     C: ClientInfo + Sink<PgWireBackendMessage> + Unpin + Send,
@@ -21,10 +21,13 @@ use async_trait::async_trait;
 use common_runtime::runtime::RuntimeTrait;
 use common_runtime::Runtime;
 use common_telemetry::{debug, warn};
+use futures::future::{AbortHandle, Abortable};
 use futures::StreamExt;
 use pgwire::tokio::process_socket;
+use tokio::io::AsyncWriteExt;
 use tokio_rustls::TlsAcceptor;
 
+use crate::connection::{ConnectionManager, ConnectionManagerRef, ConnectionOptions};
 use crate::error::Result;
 use crate::postgres::{MakePostgresServerHandler, MakePostgresServerHandlerBuilder};
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
@@ -36,6 +39,7 @@ pub struct PostgresServer {
     make_handler: Arc<MakePostgresServerHandler>,
     tls_server_config: Arc<ReloadableTlsServerConfig>,
     keep_alive_secs: u64,
+    connections: ConnectionManagerRef,
 }
 
 impl PostgresServer {
@@ -61,9 +65,15 @@ impl PostgresServer {
             make_handler,
             tls_server_config,
             keep_alive_secs,
+            connections: new_connection_manager(ConnectionOptions::default()),
         }
     }
 
+    pub fn with_connection_options(mut self, options: ConnectionOptions) -> Self {
+        self.connections = new_connection_manager(options);
+        self
+    }
+
     fn accept(
         &self,
         io_runtime: Runtime,
@@ -71,6 +81,7 @@ impl PostgresServer {
     ) -> impl Future<Output = ()> {
         let handler_maker = self.make_handler.clone();
         let tls_server_config = self.tls_server_config.clone();
+        let connections = self.connections.clone();
         accepting_stream.for_each(move |tcp_stream| {
             let io_runtime = io_runtime.clone();
 
@@ -79,11 +90,24 @@ impl PostgresServer {
                 .map(|server_config| Arc::new(TlsAcceptor::from(server_config)));
 
             let handler_maker = handler_maker.clone();
+            let connections = connections.clone();
 
             async move {
                 match tcp_stream {
                     Err(error) => debug!("Broken pipe: {}", error), // IoError doesn't impl ErrorExt.
-                    Ok(io_stream) => {
+                    Ok(mut io_stream) => {
+                        let (abort_handle, registration) = AbortHandle::new_pair();
+                        let connection = match connections.register(abort_handle) {
+                            Ok(connection) => connection,
+                            Err(e) => {
+                                warn!(e; "Reject PostgreSQL connection");
+                                let _handle = io_runtime.spawn(async move {
+                                    let _ = io_stream.shutdown().await;
+                                });
+                                return;
+                            }
+                        };
+
                         let addr = match io_stream.peer_addr() {
                             Ok(addr) => {
                                 debug!("PostgreSQL client coming from {}", addr);
@@ -95,14 +119,14 @@ impl PostgresServer {
                             }
                         };
 
-                        let _handle = io_runtime.spawn(async move {
-                            crate::metrics::METRIC_POSTGRES_CONNECTIONS.inc();
-                            let pg_handler = Arc::new(handler_maker.make(addr));
-                            let r =
-                                process_socket(io_stream, tls_acceptor.clone(), pg_handler).await;
-                            crate::metrics::METRIC_POSTGRES_CONNECTIONS.dec();
-                            r
-                        });
+                        // The connection task is aborted if the connection is idle for too long.
+                        let _handle = io_runtime.spawn(Abortable::new(
+                            async move {
+                                let pg_handler = Arc::new(handler_maker.make(addr, connection));
+                                process_socket(io_stream, tls_acceptor.clone(), pg_handler).await
+                            },
+                            registration,
+                        ));
                     }
                 };
             }
@@ -110,6 +134,14 @@ impl PostgresServer {
     }
 }
 
+fn new_connection_manager(options: ConnectionOptions) -> ConnectionManagerRef {
+    Arc::new(ConnectionManager::new(
+        "postgres",
+        options,
+        crate::metrics::METRIC_POSTGRES_CONNECTIONS.clone(),
+    ))
+}
+
 pub const POSTGRES_SERVER: &str = "POSTGRES_SERVER";
 
 #[async_trait]
@@ -125,6 +157,7 @@ impl Server for PostgresServer {
             .await?;
 
         let io_runtime = self.base_server.io_runtime();
+        self.connections.start_idle_reaper();
         let join_handle = common_runtime::spawn_global(self.accept(io_runtime, stream));
 
         self.base_server.start_with(join_handle).await?;
@@ -400,7 +400,7 @@ impl From<StatusCode> for PgErrorCode {
             StatusCode::TableColumnExists => PgErrorCode::Ec42701,
             StatusCode::DatabaseNotFound => PgErrorCode::Ec42704,
             StatusCode::DatabaseAlreadyExists => PgErrorCode::Ec42P04,
-            StatusCode::RegionReadonly => PgErrorCode::Ec25006,
+            StatusCode::RegionReadonly | StatusCode::TableReadonly => PgErrorCode::Ec25006,
 
             StatusCode::RegionNotReady | StatusCode::RegionBusy | StatusCode::TableUnavailable => {
                 PgErrorCode::Ec55000
@@ -25,8 +25,9 @@ use hashbrown::hash_map::Entry;
 use hashbrown::HashMap;
 use prost::DecodeError;
 
-use crate::proto::PromLabel;
+use crate::proto::{PromHistogram, PromLabel};
 use crate::repeated_field::Clear;
+use crate::row_writer::native_histogram_fields;
 
 /// [TablesBuilder] serves as an intermediate container to build [RowInsertRequests].
 #[derive(Default, Debug)]
@@ -86,10 +87,11 @@ impl Default for TableBuilder {
 }
 
 impl TableBuilder {
+    /// Creates a table builder with only the timestamp column. Field columns are
+    /// added on demand, so tables of native histograms don't have the float value column.
     pub(crate) fn with_capacity(cols: usize, rows: usize) -> Self {
         let mut col_indexes = HashMap::with_capacity_and_hasher(cols, Default::default());
         col_indexes.insert(GREPTIME_TIMESTAMP.to_string(), 0);
-        col_indexes.insert(GREPTIME_VALUE.to_string(), 1);
 
         let mut schema = Vec::with_capacity(cols);
         schema.push(ColumnSchema {
@@ -100,14 +102,6 @@ impl TableBuilder {
             options: None,
         });
 
-        schema.push(ColumnSchema {
-            column_name: GREPTIME_VALUE.to_string(),
-            datatype: ColumnDataType::Float64 as i32,
-            semantic_type: SemanticType::Field as i32,
-            datatype_extension: None,
-            options: None,
-        });
-
         Self {
             schema,
             rows: Vec::with_capacity(rows),
@@ -120,6 +114,24 @@ impl TableBuilder {
         self.rows.len()
     }
 
+    /// Returns the index of the field column, adds it to the schema if absent.
+    fn field_column_index(&mut self, name: &str, datatype: ColumnDataType) -> usize {
+        if let Some(index) = self.col_indexes.get(name) {
+            return *index;
+        }
+
+        let index = self.col_indexes.len();
+        self.col_indexes.insert(name.to_string(), index);
+        self.schema.push(ColumnSchema {
+            column_name: name.to_string(),
+            datatype: datatype as i32,
+            semantic_type: SemanticType::Field as i32,
+            datatype_extension: None,
+            options: None,
+        });
+        index
+    }
+
     /// Adds a set of labels and samples to table builder.
     pub(crate) fn add_labels_and_samples(
         &mut self,
@@ -127,6 +139,68 @@ impl TableBuilder {
         samples: &[Sample],
         is_strict_mode: bool,
     ) -> Result<(), DecodeError> {
+        let value_index = self.field_column_index(GREPTIME_VALUE, ColumnDataType::Float64);
+        let mut row = self.labels_to_row(labels, is_strict_mode)?;
+
+        if samples.len() == 1 {
+            let sample = &samples[0];
+            row[0].value_data = Some(ValueData::TimestampMillisecondValue(sample.timestamp));
+            row[value_index].value_data = Some(ValueData::F64Value(sample.value));
+            self.rows.push(Row { values: row });
+            return Ok(());
+        }
+        for sample in samples {
+            row[0].value_data = Some(ValueData::TimestampMillisecondValue(sample.timestamp));
+            row[value_index].value_data = Some(ValueData::F64Value(sample.value));
+            self.rows.push(Row {
+                values: row.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Adds a set of labels and native histograms to table builder.
+    pub(crate) fn add_labels_and_histograms(
+        &mut self,
+        labels: &[PromLabel],
+        histograms: &[PromHistogram],
+        is_strict_mode: bool,
+    ) -> Result<(), DecodeError> {
+        if histograms.is_empty() {
+            return Ok(());
+        }
+
+        let native_histograms = histograms
+            .iter()
+            .map(|histogram| histogram.to_native_histogram())
+            .collect::<Result<Vec<_>, _>>()?;
+        let histogram_indexes = native_histogram_fields(&native_histograms[0])
+            .map(|(name, datatype, _)| self.field_column_index(&name, datatype))
+            .collect::<Vec<_>>();
+        let mut row = self.labels_to_row(labels, is_strict_mode)?;
+        for (histogram, native_histogram) in histograms.iter().zip(&native_histograms) {
+            row[0].value_data = Some(ValueData::TimestampMillisecondValue(histogram.timestamp));
+            for (index, (_, _, value)) in histogram_indexes
+                .iter()
+                .zip(native_histogram_fields(native_histogram))
+            {
+                row[*index].value_data = Some(value);
+            }
+            self.rows.push(Row {
+                values: row.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Builds a row with the given labels. Adds tag columns for new labels.
+    fn labels_to_row(
+        &mut self,
+        labels: &[PromLabel],
+        is_strict_mode: bool,
+    ) -> Result<Vec<Value>, DecodeError> {
         let mut row = vec![Value { value_data: None }; self.col_indexes.len()];
 
         for PromLabel { name, value } in labels {
@@ -170,22 +244,7 @@ impl TableBuilder {
             }
         }
 
-        if samples.len() == 1 {
-            let sample = &samples[0];
-            row[0].value_data = Some(ValueData::TimestampMillisecondValue(sample.timestamp));
-            row[1].value_data = Some(ValueData::F64Value(sample.value));
-            self.rows.push(Row { values: row });
-            return Ok(());
-        }
-        for sample in samples {
-            row[0].value_data = Some(ValueData::TimestampMillisecondValue(sample.timestamp));
-            row[1].value_data = Some(ValueData::F64Value(sample.value));
-            self.rows.push(Row {
-                values: row.clone(),
-            });
-        }
-
-        Ok(())
+        Ok(row)
     }
 
     /// Converts [TableBuilder] to [RowInsertRequest] and clears buffered data.
@@ -213,10 +272,12 @@ mod tests {
     use api::v1::Value;
     use arrow::datatypes::ToByteSlice;
     use bytes::Bytes;
+    use promql::native_histogram::{BucketSpan, NativeHistogram, NATIVE_HISTOGRAM_LAYOUT};
     use prost::DecodeError;
 
     use crate::prom_row_builder::TableBuilder;
-    use crate::proto::PromLabel;
+    use crate::proto::{prom_histogram, PromBucketSpan, PromHistogram, PromLabel};
+    use crate::row_writer::read_native_histogram;
     #[test]
     fn test_table_builder() {
         let mut builder = TableBuilder::default();
@@ -314,4 +375,100 @@ mod tests {
         );
         assert_eq!(res, Err(DecodeError::new("invalid utf-8")));
     }
+
+    #[test]
+    fn test_table_builder_with_histograms() {
+        let mut builder = TableBuilder::default();
+        let labels = [PromLabel {
+            name: Bytes::from("tag0"),
+            value: Bytes::from("v0"),
+        }];
+        let histogram = PromHistogram {
+            count: Some(prom_histogram::Count::CountFloat(3.0)),
+            sum: 4.5,
+            schema: 1,
+            zero_count: Some(prom_histogram::ZeroCount::ZeroCountFloat(1.0)),
+            positive_spans: vec![PromBucketSpan {
+                offset: 2,
+                length: 2,
+            }],
+            positive_counts: vec![1.5, 0.5],
+            // Gauge histogram.
+            reset_hint: 3,
+            timestamp: 10,
+            ..Default::default()
+        };
+        builder
+            .add_labels_and_histograms(&labels, &[histogram.clone()], true)
+            .unwrap();
+        // Float samples of the same metric go to the same table.
+        builder
+            .add_labels_and_samples(
+                &labels,
+                &[Sample {
+                    value: 1.0,
+                    timestamp: 20,
+                }],
+                true,
+            )
+            .unwrap();
+
+        let request = builder.as_row_insert_request("test".to_string());
+        let rows = request.rows.unwrap();
+        let column_names = rows
+            .schema
+            .iter()
+            .map(|c| c.column_name.as_str())
+            .collect::<Vec<_>>();
+        let mut expected_names = vec!["greptime_timestamp"];
+        expected_names.extend(NATIVE_HISTOGRAM_LAYOUT);
+        expected_names.extend(["tag0", "greptime_value"]);
+        assert_eq!(expected_names, column_names);
+        assert_eq!(2, rows.rows.len());
+
+        assert_eq!(
+            NativeHistogram {
+                schema: 1,
+                is_gauge: true,
+                zero_threshold: 0.0,
+                zero_count: 1.0,
+                count: 3.0,
+                sum: 4.5,
+                positive_spans: vec![BucketSpan {
+                    offset: 2,
+                    length: 2
+                }],
+                positive_buckets: vec![1.5, 0.5],
+                negative_spans: vec![],
+                negative_buckets: vec![],
+                custom_values: vec![],
+            },
+            read_native_histogram(&rows, 0)
+        );
+        let mut expected_values = vec![Value {
+            value_data: Some(ValueData::TimestampMillisecondValue(20)),
+        }];
+        expected_values.extend(vec![
+            Value { value_data: None };
+            NATIVE_HISTOGRAM_LAYOUT.len()
+        ]);
+        expected_values.extend([
+            Value {
+                value_data: Some(ValueData::StringValue("v0".to_string())),
+            },
+            Value {
+                value_data: Some(ValueData::F64Value(1.0)),
+            },
+        ]);
+        assert_eq!(expected_values, rows.rows[1].values);
+
+        // Spans don't match the buckets.
+        let invalid = PromHistogram {
+            positive_counts: vec![1.0],
+            ..histogram
+        };
+        assert!(builder
+            .add_labels_and_histograms(&labels, &[invalid], true)
+            .is_err());
+    }
 }
@@ -25,6 +25,7 @@ use query::parser::PromQuery;
 use session::context::QueryContextRef;
 
 use crate::error::Result;
+use crate::http::prometheus::TsdbStatus;
 
 pub const PROMETHEUS_API_VERSION: &str = "v1";
 
@@ -51,5 +52,9 @@ pub trait PrometheusHandler {
         ctx: &QueryContextRef,
     ) -> Result<Vec<String>>;
 
+    /// Returns the cardinality statistics of the series in the database of `ctx`,
+    /// each list of statistics has at most `limit` items.
+    async fn tsdb_status(&self, limit: usize, ctx: &QueryContextRef) -> Result<TsdbStatus>;
+
     fn catalog_manager(&self) -> CatalogManagerRef;
 }
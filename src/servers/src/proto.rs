@@ -18,9 +18,10 @@ use std::slice;
 use api::prom_store::remote::Sample;
 use api::v1::RowInsertRequests;
 use bytes::{Buf, Bytes};
+use promql::native_histogram::{BucketSpan, NativeHistogram};
 use prost::encoding::message::merge;
 use prost::encoding::{decode_key, decode_varint, WireType};
-use prost::DecodeError;
+use prost::{DecodeError, Message};
 
 use crate::prom_row_builder::TablesBuilder;
 use crate::prom_store::METRIC_NAME_LABEL_BYTES;
@@ -76,6 +77,132 @@ impl PromLabel {
     }
 }
 
+/// Native histogram sample in the Prometheus remote write protocol.
+///
+/// See <https://github.com/prometheus/prometheus/blob/main/prompb/types.proto>.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PromHistogram {
+    #[prost(oneof = "prom_histogram::Count", tags = "1, 2")]
+    pub count: Option<prom_histogram::Count>,
+    #[prost(double, tag = "3")]
+    pub sum: f64,
+    #[prost(sint32, tag = "4")]
+    pub schema: i32,
+    #[prost(double, tag = "5")]
+    pub zero_threshold: f64,
+    #[prost(oneof = "prom_histogram::ZeroCount", tags = "6, 7")]
+    pub zero_count: Option<prom_histogram::ZeroCount>,
+    #[prost(message, repeated, tag = "8")]
+    pub negative_spans: Vec<PromBucketSpan>,
+    /// Deltas of bucket counts of integer histograms.
+    #[prost(sint64, repeated, tag = "9")]
+    pub negative_deltas: Vec<i64>,
+    /// Absolute bucket counts of float histograms.
+    #[prost(double, repeated, tag = "10")]
+    pub negative_counts: Vec<f64>,
+    #[prost(message, repeated, tag = "11")]
+    pub positive_spans: Vec<PromBucketSpan>,
+    #[prost(sint64, repeated, tag = "12")]
+    pub positive_deltas: Vec<i64>,
+    #[prost(double, repeated, tag = "13")]
+    pub positive_counts: Vec<f64>,
+    #[prost(int32, tag = "14")]
+    pub reset_hint: i32,
+    #[prost(int64, tag = "15")]
+    pub timestamp: i64,
+    /// Upper bounds of the custom buckets, only used with the custom buckets schema.
+    #[prost(double, repeated, tag = "16")]
+    pub custom_values: Vec<f64>,
+}
+
+pub mod prom_histogram {
+    #[derive(Clone, Copy, PartialEq, prost::Oneof)]
+    pub enum Count {
+        #[prost(uint64, tag = "1")]
+        CountInt(u64),
+        #[prost(double, tag = "2")]
+        CountFloat(f64),
+    }
+
+    #[derive(Clone, Copy, PartialEq, prost::Oneof)]
+    pub enum ZeroCount {
+        #[prost(uint64, tag = "6")]
+        ZeroCountInt(u64),
+        #[prost(double, tag = "7")]
+        ZeroCountFloat(f64),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct PromBucketSpan {
+    #[prost(sint32, tag = "1")]
+    pub offset: i32,
+    #[prost(uint32, tag = "2")]
+    pub length: u32,
+}
+
+/// `ResetHint::GAUGE` in the remote write protocol.
+const RESET_HINT_GAUGE: i32 = 3;
+
+impl PromHistogram {
+    /// Converts to the storage representation of native histograms.
+    pub fn to_native_histogram(&self) -> Result<NativeHistogram, DecodeError> {
+        let count = match self.count {
+            Some(prom_histogram::Count::CountInt(count)) => count as f64,
+            Some(prom_histogram::Count::CountFloat(count)) => count,
+            None => 0.0,
+        };
+        let zero_count = match self.zero_count {
+            Some(prom_histogram::ZeroCount::ZeroCountInt(count)) => count as f64,
+            Some(prom_histogram::ZeroCount::ZeroCountFloat(count)) => count,
+            None => 0.0,
+        };
+
+        let histogram = NativeHistogram {
+            schema: self.schema,
+            is_gauge: self.reset_hint == RESET_HINT_GAUGE,
+            zero_threshold: self.zero_threshold,
+            zero_count,
+            count,
+            sum: self.sum,
+            positive_spans: to_bucket_spans(&self.positive_spans),
+            positive_buckets: to_bucket_counts(&self.positive_deltas, &self.positive_counts),
+            negative_spans: to_bucket_spans(&self.negative_spans),
+            negative_buckets: to_bucket_counts(&self.negative_deltas, &self.negative_counts),
+            custom_values: self.custom_values.clone(),
+        };
+        histogram
+            .validate()
+            .map_err(|e| DecodeError::new(format!("invalid native histogram: {e}")))?;
+        Ok(histogram)
+    }
+}
+
+fn to_bucket_spans(spans: &[PromBucketSpan]) -> Vec<BucketSpan> {
+    spans
+        .iter()
+        .map(|span| BucketSpan {
+            offset: span.offset,
+            length: span.length,
+        })
+        .collect()
+}
+
+/// Integer histograms carry deltas between adjacent buckets while float histograms
+/// carry absolute counts.
+fn to_bucket_counts(deltas: &[i64], counts: &[f64]) -> Vec<f64> {
+    if deltas.is_empty() {
+        return counts.to_vec();
+    }
+    deltas
+        .iter()
+        .scan(0i64, |count, delta| {
+            *count += delta;
+            Some(*count as f64)
+        })
+        .collect()
+}
+
 #[inline(always)]
 fn copy_to_bytes(data: &mut Bytes, len: usize) -> Bytes {
     if len == data.remaining() {
@@ -129,11 +256,20 @@ fn merge_bytes(value: &mut Bytes, buf: &mut Bytes) -> Result<(), DecodeError> {
     Ok(())
 }
 
+fn decode_table_name(value: &Bytes, is_strict_mode: bool) -> Result<String, DecodeError> {
+    if is_strict_mode {
+        String::from_utf8(value.to_vec()).map_err(|_| DecodeError::new("invalid utf-8"))
+    } else {
+        Ok(unsafe { String::from_utf8_unchecked(value.to_vec()) })
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct PromTimeSeries {
     pub table_name: String,
     pub labels: RepeatedField<PromLabel>,
     pub samples: RepeatedField<Sample>,
+    pub histograms: Vec<PromHistogram>,
 }
 
 impl Clear for PromTimeSeries {
@@ -141,6 +277,7 @@ impl Clear for PromTimeSeries {
         self.table_name.clear();
         self.labels.clear();
         self.samples.clear();
+        self.histograms.clear();
     }
 }
 
@@ -176,15 +313,7 @@ impl PromTimeSeries {
                     return Err(DecodeError::new("delimited length exceeded"));
                 }
                 if label.name.deref() == METRIC_NAME_LABEL_BYTES {
-                    let table_name = if is_strict_mode {
-                        match String::from_utf8(label.value.to_vec()) {
-                            Ok(s) => s,
-                            Err(_) => return Err(DecodeError::new("invalid utf-8")),
-                        }
-                    } else {
-                        unsafe { String::from_utf8_unchecked(label.value.to_vec()) }
-                    };
-                    self.table_name = table_name;
+                    self.table_name = decode_table_name(&label.value, is_strict_mode)?;
                     self.labels.truncate(self.labels.len() - 1); // remove last label
                 }
                 Ok(())
@@ -201,6 +330,21 @@ impl PromTimeSeries {
             }
             // todo(hl): exemplars are skipped temporarily
             3u32 => prost::encoding::skip_field(wire_type, tag, buf, Default::default()),
+            4u32 => {
+                let mut histogram = PromHistogram::default();
+                merge(
+                    WireType::LengthDelimited,
+                    &mut histogram,
+                    buf,
+                    Default::default(),
+                )
+                .map_err(|mut error| {
+                    error.push(STRUCT_NAME, "histograms");
+                    error
+                })?;
+                self.histograms.push(histogram);
+                Ok(())
+            }
             _ => prost::encoding::skip_field(wire_type, tag, buf, Default::default()),
         }
     }
@@ -211,19 +355,28 @@ impl PromTimeSeries {
         is_strict_mode: bool,
     ) -> Result<(), DecodeError> {
         let label_num = self.labels.len();
-        let row_num = self.samples.len();
+        let row_num = self.samples.len() + self.histograms.len();
         let table_data = table_builders.get_or_create_table_builder(
             std::mem::take(&mut self.table_name),
             label_num,
             row_num,
         );
-        table_data.add_labels_and_samples(
+        // Series that only carry native histograms don't need the float value column.
+        if !self.samples.is_empty() || self.histograms.is_empty() {
+            table_data.add_labels_and_samples(
+                self.labels.as_slice(),
+                self.samples.as_slice(),
+                is_strict_mode,
+            )?;
+        }
+        table_data.add_labels_and_histograms(
             self.labels.as_slice(),
-            self.samples.as_slice(),
+            &self.histograms,
             is_strict_mode,
         )?;
         self.labels.clear();
         self.samples.clear();
+        self.histograms.clear();
 
         Ok(())
     }
@@ -285,20 +438,88 @@ impl PromWriteRequest {
         }
         Ok(())
     }
+
+    /// Decodes a remote write 2.0 request into the same tables as [Self::merge].
+    pub fn merge_v2(&mut self, buf: Bytes, is_strict_mode: bool) -> Result<(), DecodeError> {
+        let request = PromWriteRequestV2::decode(buf)?;
+        let symbol =
+            |index: u32| {
+                request.symbols.get(index as usize).cloned().ok_or_else(|| {
+                    DecodeError::new(format!("symbol reference {index} out of bounds"))
+                })
+            };
+
+        for series in request.timeseries {
+            if series.labels_refs.len() % 2 != 0 {
+                return Err(DecodeError::new("odd number of label references"));
+            }
+            for refs in series.labels_refs.chunks_exact(2) {
+                let (name, value) = (symbol(refs[0])?, symbol(refs[1])?);
+                if name.deref() == METRIC_NAME_LABEL_BYTES {
+                    self.series.table_name = decode_table_name(&value, is_strict_mode)?;
+                } else {
+                    let label = self.series.labels.push_default();
+                    label.name = name;
+                    label.value = value;
+                }
+            }
+            for sample in series.samples {
+                *self.series.samples.push_default() = sample;
+            }
+            self.series.histograms = series.histograms;
+            self.series
+                .add_to_table_data(&mut self.table_data, is_strict_mode)?;
+        }
+        Ok(())
+    }
+}
+
+/// Message name of remote write 2.0 requests, set as the `proto` parameter of
+/// their content type.
+pub const PROM_WRITE_REQUEST_V2_PROTO: &str = "io.prometheus.write.v2.Request";
+
+/// Request in the Prometheus remote write 2.0 protocol. Label names and values
+/// are references into the symbol table shared by all series.
+///
+/// See <https://github.com/prometheus/prometheus/blob/main/prompb/io/prometheus/write/v2/types.proto>.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PromWriteRequestV2 {
+    #[prost(bytes = "bytes", repeated, tag = "4")]
+    pub symbols: Vec<Bytes>,
+    #[prost(message, repeated, tag = "5")]
+    pub timeseries: Vec<PromTimeSeriesV2>,
+}
+
+/// Time series in the Prometheus remote write 2.0 protocol. Samples and native
+/// histograms have the same encoding as in 1.0.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PromTimeSeriesV2 {
+    /// References to the symbols of label names and values, in pairs.
+    #[prost(uint32, repeated, tag = "1")]
+    pub labels_refs: Vec<u32>,
+    #[prost(message, repeated, tag = "2")]
+    pub samples: Vec<Sample>,
+    #[prost(message, repeated, tag = "3")]
+    pub histograms: Vec<PromHistogram>,
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
-    use api::prom_store::remote::WriteRequest;
+    use api::prom_store::remote::{Label, Sample, WriteRequest};
     use api::v1::{Row, RowInsertRequests, Rows};
     use bytes::Bytes;
+    use promql::native_histogram::{BucketSpan, NativeHistogram, NATIVE_HISTOGRAM_LAYOUT};
     use prost::Message;
 
     use crate::prom_store::to_grpc_row_insert_requests;
-    use crate::proto::PromWriteRequest;
+    use crate::proto::{
+        prom_histogram, PromBucketSpan, PromHistogram, PromTimeSeriesV2, PromWriteRequest,
+        PromWriteRequestV2,
+    };
     use crate::repeated_field::Clear;
+    use crate::row_writer::read_native_histogram;
 
     fn sort_rows(rows: Rows) -> Rows {
         let permutation =
@@ -362,4 +583,214 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_decode_native_histogram() {
+        // `{{schema:0 count:12 sum:100 z_bucket:2 z_bucket_w:0.001 buckets:[2 3 0 1 4]}}`
+        // from Prometheus' `native_histograms.test`, encoded as an integer histogram.
+        let histogram = PromHistogram {
+            count: Some(prom_histogram::Count::CountInt(12)),
+            sum: 100.0,
+            schema: 0,
+            zero_threshold: 0.001,
+            zero_count: Some(prom_histogram::ZeroCount::ZeroCountInt(2)),
+            positive_spans: vec![PromBucketSpan {
+                offset: 0,
+                length: 5,
+            }],
+            positive_deltas: vec![2, 1, -3, 1, 3],
+            timestamp: 1000,
+            ..Default::default()
+        };
+        let mut series = vec![];
+        for (name, value) in [("__name__", "http_latency"), ("job", "api")] {
+            let label = Label {
+                name: name.to_string(),
+                value: value.to_string(),
+            };
+            prost::encoding::message::encode(1, &label, &mut series);
+        }
+        prost::encoding::message::encode(4, &histogram, &mut series);
+        let mut data = vec![];
+        prost::encoding::bytes::encode(1, &series, &mut data);
+        let data = Bytes::from(data);
+
+        let mut prom_write_request = PromWriteRequest::default();
+        prom_write_request.merge(data.clone(), true).unwrap();
+        let (requests, samples) = prom_write_request.as_row_insert_requests();
+        assert_eq!(1, samples);
+        assert_eq!(1, requests.inserts.len());
+        let insert = &requests.inserts[0];
+        assert_eq!("http_latency", insert.table_name);
+
+        let rows = insert.rows.as_ref().unwrap();
+        let column_names = rows
+            .schema
+            .iter()
+            .map(|c| c.column_name.as_str())
+            .collect::<Vec<_>>();
+        let mut expected_names = vec!["greptime_timestamp"];
+        expected_names.extend(NATIVE_HISTOGRAM_LAYOUT);
+        expected_names.push("job");
+        assert_eq!(expected_names, column_names);
+        let decoded = read_native_histogram(rows, 0);
+        assert_eq!(
+            NativeHistogram {
+                schema: 0,
+                is_gauge: false,
+                zero_threshold: 0.001,
+                zero_count: 2.0,
+                count: 12.0,
+                sum: 100.0,
+                positive_spans: vec![BucketSpan {
+                    offset: 0,
+                    length: 5
+                }],
+                positive_buckets: vec![2.0, 3.0, 0.0, 1.0, 4.0],
+                negative_spans: vec![],
+                negative_buckets: vec![],
+                custom_values: vec![],
+            },
+            decoded
+        );
+
+        // Expected values are from Prometheus' `native_histograms.test`.
+        for (q, expected) in [
+            (0.99, 15.67072476139083),
+            (0.6, 4.594793419988138),
+            (0.5, 1.5874010519681994),
+            (0.1, 0.0006000000000000001),
+        ] {
+            assert!((decoded.quantile(q) - expected).abs() < 1e-12, "q: {q}");
+        }
+
+        // Decoding again with the same request reuses the buffers.
+        prom_write_request.clear();
+        prom_write_request.merge(data, true).unwrap();
+        let (requests, samples) = prom_write_request.as_row_insert_requests();
+        assert_eq!(1, samples);
+        assert_eq!(1, requests.inserts.len());
+    }
+
+    #[test]
+    fn test_decode_custom_buckets_histogram() {
+        // `{{schema:-53 sum:5 count:4 custom_values:[5 10 15] buckets:[1 2 0 1]}}`,
+        // encoded as an integer histogram.
+        let histogram = PromHistogram {
+            count: Some(prom_histogram::Count::CountInt(4)),
+            sum: 5.0,
+            schema: -53,
+            positive_spans: vec![PromBucketSpan {
+                offset: 0,
+                length: 4,
+            }],
+            positive_deltas: vec![1, 1, -2, 1],
+            custom_values: vec![5.0, 10.0, 15.0],
+            timestamp: 1000,
+            ..Default::default()
+        };
+        let data = histogram.encode_to_vec();
+        let decoded = PromHistogram::decode(data.as_slice())
+            .unwrap()
+            .to_native_histogram()
+            .unwrap();
+        assert!(decoded.uses_custom_buckets());
+        assert_eq!(vec![5.0, 10.0, 15.0], decoded.custom_values);
+        assert_eq!(vec![1.0, 2.0, 0.0, 1.0], decoded.positive_buckets);
+        assert_eq!(7.5, decoded.quantile(0.5));
+
+        // Custom values must be increasing.
+        let histogram = PromHistogram {
+            custom_values: vec![5.0, 1.0, 15.0],
+            ..histogram
+        };
+        assert!(histogram.to_native_histogram().is_err());
+    }
+
+    #[test]
+    fn test_decode_write_request_v2() {
+        let histogram = PromHistogram {
+            count: Some(prom_histogram::Count::CountInt(12)),
+            sum: 100.0,
+            schema: 0,
+            zero_threshold: 0.001,
+            zero_count: Some(prom_histogram::ZeroCount::ZeroCountInt(2)),
+            positive_spans: vec![PromBucketSpan {
+                offset: 0,
+                length: 5,
+            }],
+            positive_deltas: vec![2, 1, -3, 1, 3],
+            timestamp: 1000,
+            ..Default::default()
+        };
+        let request = PromWriteRequestV2 {
+            symbols: ["", "__name__", "http_latency", "job", "api", "up"]
+                .into_iter()
+                .map(Bytes::from)
+                .collect(),
+            timeseries: vec![
+                PromTimeSeriesV2 {
+                    labels_refs: vec![1, 2, 3, 4],
+                    histograms: vec![histogram.clone()],
+                    ..Default::default()
+                },
+                PromTimeSeriesV2 {
+                    labels_refs: vec![1, 5, 3, 4],
+                    samples: vec![Sample {
+                        value: 1.0,
+                        timestamp: 1000,
+                    }],
+                    ..Default::default()
+                },
+            ],
+        };
+        let data = Bytes::from(request.encode_to_vec());
+
+        let mut prom_write_request = PromWriteRequest::default();
+        prom_write_request.merge_v2(data, true).unwrap();
+        let (requests, samples) = prom_write_request.as_row_insert_requests();
+        assert_eq!(2, samples);
+        let mut inserts = requests.inserts;
+        inserts.sort_by(|a, b| a.table_name.cmp(&b.table_name));
+        assert_eq!(
+            vec!["http_latency", "up"],
+            inserts
+                .iter()
+                .map(|insert| insert.table_name.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        let rows = inserts[0].rows.as_ref().unwrap();
+        let column_names = rows
+            .schema
+            .iter()
+            .map(|c| c.column_name.as_str())
+            .collect::<Vec<_>>();
+        let mut expected_names = vec!["greptime_timestamp"];
+        expected_names.extend(NATIVE_HISTOGRAM_LAYOUT);
+        expected_names.push("job");
+        assert_eq!(expected_names, column_names);
+        assert_eq!(
+            histogram.to_native_histogram().unwrap(),
+            read_native_histogram(rows, 0)
+        );
+
+        let rows = inserts[1].rows.as_ref().unwrap();
+        assert_eq!(1, rows.rows.len());
+
+        // Label references must be in pairs and within the symbols.
+        for labels_refs in [vec![1, 2, 3], vec![1, 6]] {
+            let request = PromWriteRequestV2 {
+                symbols: vec![Bytes::new(), Bytes::from("__name__"), Bytes::from("up")],
+                timeseries: vec![PromTimeSeriesV2 {
+                    labels_refs,
+                    ..Default::default()
+                }],
+            };
+            prom_write_request.clear();
+            assert!(prom_write_request
+                .merge_v2(Bytes::from(request.encode_to_vec()), true)
+                .is_err());
+        }
+    }
 }
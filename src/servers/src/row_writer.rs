@@ -24,6 +24,9 @@ use common_grpc::precision::Precision;
 use common_time::timestamp::TimeUnit;
 use common_time::timestamp::TimeUnit::Nanosecond;
 use common_time::Timestamp;
+use promql::native_histogram::{
+    encode_spans, encode_values, NativeHistogram, NATIVE_HISTOGRAM_LAYOUT,
+};
 use snafu::{ensure, OptionExt, ResultExt};
 
 use crate::error::{
@@ -200,6 +203,110 @@ pub fn write_f64(
     )
 }
 
+/// Returns the fields of a native histogram, one for each column of the
+/// [NATIVE_HISTOGRAM_LAYOUT].
+pub fn native_histogram_fields(
+    histogram: &NativeHistogram,
+) -> impl Iterator<Item = (String, ColumnDataType, ValueData)> {
+    let values = [
+        (ColumnDataType::Int32, ValueData::I32Value(histogram.schema)),
+        (
+            ColumnDataType::Boolean,
+            ValueData::BoolValue(histogram.is_gauge),
+        ),
+        (
+            ColumnDataType::Float64,
+            ValueData::F64Value(histogram.zero_threshold),
+        ),
+        (
+            ColumnDataType::Float64,
+            ValueData::F64Value(histogram.zero_count),
+        ),
+        (
+            ColumnDataType::Float64,
+            ValueData::F64Value(histogram.count),
+        ),
+        (ColumnDataType::Float64, ValueData::F64Value(histogram.sum)),
+        (
+            ColumnDataType::Binary,
+            ValueData::BinaryValue(encode_spans(&histogram.positive_spans)),
+        ),
+        (
+            ColumnDataType::Binary,
+            ValueData::BinaryValue(encode_values(&histogram.positive_buckets)),
+        ),
+        (
+            ColumnDataType::Binary,
+            ValueData::BinaryValue(encode_spans(&histogram.negative_spans)),
+        ),
+        (
+            ColumnDataType::Binary,
+            ValueData::BinaryValue(encode_values(&histogram.negative_buckets)),
+        ),
+        (
+            ColumnDataType::Binary,
+            ValueData::BinaryValue(encode_values(&histogram.custom_values)),
+        ),
+    ];
+    NATIVE_HISTOGRAM_LAYOUT
+        .into_iter()
+        .zip(values)
+        .map(|(name, (datatype, value))| (name.to_string(), datatype, value))
+}
+
+/// Write a native histogram as fields into the table data.
+pub fn write_native_histogram(
+    table_data: &mut TableData,
+    histogram: &NativeHistogram,
+    one_row: &mut Vec<Value>,
+) -> Result<()> {
+    write_fields(table_data, native_histogram_fields(histogram), one_row)
+}
+
+/// Reads the native histogram written by [native_histogram_fields] from a row.
+#[cfg(test)]
+pub(crate) fn read_native_histogram(rows: &Rows, row: usize) -> NativeHistogram {
+    use promql::native_histogram::{decode_spans, decode_values};
+
+    let mut values = NATIVE_HISTOGRAM_LAYOUT.map(|name| {
+        let index = rows
+            .schema
+            .iter()
+            .position(|column| column.column_name == name)
+            .unwrap();
+        rows.rows[row].values[index].value_data.clone().unwrap()
+    });
+    let mut take = |i: usize| std::mem::replace(&mut values[i], ValueData::I32Value(0));
+    let (
+        ValueData::I32Value(schema),
+        ValueData::BoolValue(is_gauge),
+        ValueData::F64Value(zero_threshold),
+        ValueData::F64Value(zero_count),
+        ValueData::F64Value(count),
+        ValueData::F64Value(sum),
+    ) = (take(0), take(1), take(2), take(3), take(4), take(5))
+    else {
+        unreachable!()
+    };
+    let mut binary = |i: usize| match take(i) {
+        ValueData::BinaryValue(bytes) => bytes,
+        _ => unreachable!(),
+    };
+    NativeHistogram {
+        schema,
+        is_gauge,
+        zero_threshold,
+        zero_count,
+        count,
+        sum,
+        positive_spans: decode_spans(&binary(6)).unwrap(),
+        positive_buckets: decode_values(&binary(7)).unwrap(),
+        negative_spans: decode_spans(&binary(8)).unwrap(),
+        negative_buckets: decode_values(&binary(9)).unwrap(),
+        custom_values: decode_values(&binary(10)).unwrap(),
+    }
+}
+
 fn build_json_column_schema(name: impl ToString) -> ColumnSchema {
     ColumnSchema {
         column_name: name.to_string(),
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::time::Duration;
 
 use axum::extract::{Json, Query, State};
 use axum::http::header;
@@ -21,6 +22,7 @@ use axum::Form;
 use bytes::Bytes;
 use headers::HeaderValue;
 use mime_guess::mime;
+use servers::drain::DrainStateRef;
 use servers::http::GreptimeQueryOutput::Records;
 use servers::http::{
     handler as http_handler, ApiState, GreptimeOptionsConfigState, GreptimeQueryOutput,
@@ -360,6 +362,28 @@ async fn test_health() {
     );
 }
 
+#[tokio::test]
+async fn test_ready() {
+    let response = http_handler::ready(State(None), Query(http_handler::HealthQuery {})).await;
+    assert_eq!(200_u16, response.status().as_u16());
+
+    let drain_state = DrainStateRef::default();
+    let response = http_handler::ready(
+        State(Some(drain_state.clone())),
+        Query(http_handler::HealthQuery {}),
+    )
+    .await;
+    assert_eq!(200_u16, response.status().as_u16());
+
+    assert!(drain_state.drain(Duration::from_secs(1)).await);
+    let response = http_handler::ready(
+        State(Some(drain_state)),
+        Query(http_handler::HealthQuery {}),
+    )
+    .await;
+    assert_eq!(503_u16, response.status().as_u16());
+}
+
 #[tokio::test]
 async fn test_status() {
     let hostname = hostname::get()
@@ -243,6 +243,90 @@ async fn test_opentsdb_debug_put() {
     );
 }
 
+#[tokio::test]
+async fn test_opentsdb_put_invalid_data_points() {
+    common_telemetry::init_default_ut_logging();
+
+    let (tx, mut rx) = mpsc::channel(100);
+
+    let app = make_test_app(tx);
+    let client = TestClient::new(app).await;
+
+    let body = format!(
+        r#"[{},{{"metric":"m55","timestamp":1000,"value":"abc","tags":{{"host":"web01"}}}},{{"metric":"m66","timestamp":1000,"value":1,"tags":{{"greptime_timestamp":"web01"}}}},{}]"#,
+        create_data_point("m44"),
+        create_data_point("should_failed"),
+    );
+
+    // invalid data points fail the whole request
+    let result = client
+        .post("/v1/opentsdb/api/put")
+        .body(body.clone())
+        .send()
+        .await;
+    assert_eq!(result.status(), 400);
+    assert_eq!(
+        result.text().await,
+        r#"{"error":"Invalid OpenTSDB data point: invalid type: string \"abc\", expected f64"}"#
+    );
+
+    let result = client
+        .post("/v1/opentsdb/api/put?summary")
+        .body(body.clone())
+        .send()
+        .await;
+    assert_eq!(result.status(), 200);
+    assert_eq!(result.text().await, "{\"success\":1,\"failed\":3}");
+
+    let result = client
+        .post("/v1/opentsdb/api/put?details")
+        .body(body)
+        .send()
+        .await;
+    assert_eq!(result.status(), 200);
+    let response = serde_json::from_str::<serde_json::Value>(&result.text().await).unwrap();
+    assert_eq!(
+        response,
+        serde_json::json!({
+            "success": 1,
+            "failed": 3,
+            "errors": [
+                {
+                    "datapoint": {"metric": "m55", "timestamp": 1000, "value": "abc", "tags": {"host": "web01"}},
+                    "error": "Invalid OpenTSDB data point: invalid type: string \"abc\", expected f64"
+                },
+                {
+                    "datapoint": {"metric": "m66", "timestamp": 1000, "value": 1, "tags": {"greptime_timestamp": "web01"}},
+                    "error": "Invalid OpenTSDB data point: tag greptime_timestamp conflicts with the reserved column"
+                },
+                {
+                    "datapoint": {"metric": "should_failed", "timestamp": 1000, "value": 1.0, "tags": {"host": "web01"}},
+                    "error": "Internal error: 1003"
+                }
+            ]
+        })
+    );
+
+    let mut metrics = vec![];
+    while let Ok(s) = rx.try_recv() {
+        metrics.push(s);
+    }
+    assert_eq!(metrics, vec!["m44".to_string(), "m44".to_string()]);
+}
+
+#[tokio::test]
+async fn test_opentsdb_version() {
+    let (tx, _rx) = mpsc::channel(100);
+
+    let app = make_test_app(tx);
+    let client = TestClient::new(app).await;
+
+    let result = client.get("/v1/opentsdb/api/version").send().await;
+    assert_eq!(result.status(), 200);
+    let response = serde_json::from_str::<serde_json::Value>(&result.text().await).unwrap();
+    assert_eq!(response["version"], "2.4.0");
+}
+
 fn create_data_point(metric: &str) -> String {
     format!(
         r#"{{
@@ -274,6 +274,31 @@ impl QueryContext {
         self.mutable_session_data.write().unwrap().read_preference = read_preference;
     }
 
+    /// Returns true if scans skip the default filters of tables.
+    pub fn bypass_default_filter(&self) -> bool {
+        self.mutable_session_data
+            .read()
+            .unwrap()
+            .bypass_default_filter
+    }
+
+    pub fn set_bypass_default_filter(&self, bypass: bool) {
+        self.mutable_session_data
+            .write()
+            .unwrap()
+            .bypass_default_filter = bypass;
+    }
+
+    /// Returns true if `INSERT INTO ... SELECT` adds the missing columns to the
+    /// target table.
+    pub fn auto_add_columns(&self) -> bool {
+        self.mutable_session_data.read().unwrap().auto_add_columns
+    }
+
+    pub fn set_auto_add_columns(&self, auto_add: bool) {
+        self.mutable_session_data.write().unwrap().auto_add_columns = auto_add;
+    }
+
     pub fn current_user(&self) -> UserInfoRef {
         self.mutable_session_data.read().unwrap().user_info.clone()
     }
@@ -406,6 +431,15 @@ impl QueryContext {
     pub fn get_snapshot(&self, region_id: u64) -> Option<u64> {
         self.snapshot_seqs.read().unwrap().get(&region_id).cloned()
     }
+
+    /// Sets the snapshot sequences of regions that don't have one yet, so all scans
+    /// of a region in the query read the same snapshot.
+    pub fn set_snapshots(&self, snapshots: impl IntoIterator<Item = (u64, u64)>) {
+        let mut guard = self.snapshot_seqs.write().unwrap();
+        for (region_id, sequence) in snapshots {
+            guard.entry(region_id).or_insert(sequence);
+        }
+    }
 }
 
 impl QueryContextBuilder {
@@ -533,10 +567,26 @@ impl Display for Channel {
     }
 }
 
+/// Limits of the connections of the server that the session connects to.
+///
+/// Zero means unlimited for all the limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectionLimits {
+    /// Max number of connections of the server.
+    pub max_connections: usize,
+    /// Max number of connections of a user.
+    pub max_user_connections: usize,
+    /// Connections that are idle for longer than this are closed.
+    pub idle_timeout: Duration,
+    /// Max number of prepared statements of a session.
+    pub max_prepared_statements: usize,
+}
+
 #[derive(Default, Debug)]
 pub struct ConfigurationVariables {
     postgres_bytea_output: ArcSwap<PGByteaOutputValue>,
     pg_datestyle_format: ArcSwap<(PGDateTimeStyle, PGDateOrder)>,
+    connection_limits: ConnectionLimits,
 }
 
 impl Clone for ConfigurationVariables {
@@ -544,6 +594,7 @@ impl Clone for ConfigurationVariables {
         Self {
             postgres_bytea_output: ArcSwap::new(self.postgres_bytea_output.load().clone()),
             pg_datestyle_format: ArcSwap::new(self.pg_datestyle_format.load().clone()),
+            connection_limits: self.connection_limits,
         }
     }
 }
@@ -553,6 +604,15 @@ impl ConfigurationVariables {
         Self::default()
     }
 
+    pub fn with_connection_limits(mut self, connection_limits: ConnectionLimits) -> Self {
+        self.connection_limits = connection_limits;
+        self
+    }
+
+    pub fn connection_limits(&self) -> &ConnectionLimits {
+        &self.connection_limits
+    }
+
     pub fn set_postgres_bytea_output(&self, value: PGByteaOutputValue) {
         let _ = self.postgres_bytea_output.swap(Arc::new(value));
     }
@@ -52,6 +52,10 @@ pub(crate) struct MutableInner {
     timezone: Timezone,
     query_timeout: Option<Duration>,
     read_preference: ReadPreference,
+    /// Whether to skip the `scan.default_filter` of tables.
+    bypass_default_filter: bool,
+    /// Whether `INSERT INTO ... SELECT` adds the missing columns to the target table.
+    auto_add_columns: bool,
     #[debug(skip)]
     pub(crate) cursors: HashMap<String, Arc<RecordBatchStreamCursor>>,
 }
@@ -64,6 +68,8 @@ impl Default for MutableInner {
             timezone: get_timezone(None).clone(),
             query_timeout: None,
             read_preference: ReadPreference::Leader,
+            bypass_default_filter: false,
+            auto_add_columns: false,
             cursors: HashMap::with_capacity(0),
         }
     }
@@ -181,6 +181,13 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Unrecognized flow option key: {}", key))]
+    InvalidFlowOption {
+        key: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Invalid table name: {}", name))]
     InvalidTableName {
         name: String,
@@ -369,6 +376,7 @@ impl ErrorExt for Error {
             | InvalidTableOptionValue { .. }
             | InvalidDatabaseName { .. }
             | InvalidDatabaseOption { .. }
+            | InvalidFlowOption { .. }
             | ColumnTypeMismatch { .. }
             | InvalidTableName { .. }
             | InvalidFlowName { .. }
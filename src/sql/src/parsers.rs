@@ -24,6 +24,7 @@ pub(crate) mod drop_parser;
 pub(crate) mod error;
 pub(crate) mod execute_parser;
 pub(crate) mod explain_parser;
+pub(crate) mod hint_parser;
 pub(crate) mod insert_parser;
 pub(crate) mod prepare_parser;
 pub(crate) mod query_parser;
@@ -156,8 +156,13 @@ impl ParserContext<'_> {
                                 .collect();
                             AlterTableOperation::SetTableOptions { options }
                         }
+                        Keyword::PARTITION => {
+                            // Safety: the next token is `PARTITION`.
+                            let partitions = self.parse_partitions()?.unwrap();
+                            AlterTableOperation::Repartition { partitions }
+                        }
                         _ => self.expected(
-                            "ADD or DROP or MODIFY or RENAME or SET after ALTER TABLE",
+                            "ADD or DROP or MODIFY or RENAME or SET or PARTITION after ALTER TABLE",
                             self.parser.peek_token(),
                         )?,
                     }
@@ -846,6 +851,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_alter_table_partition() {
+        let sql = "ALTER TABLE my_metric_1 PARTITION ON COLUMNS (Host) (Host < 'b', Host >= 'b')";
+        let mut result =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default())
+                .unwrap();
+        assert_eq!(1, result.len());
+
+        let statement = result.remove(0);
+        let Statement::AlterTable(alter_table) = statement else {
+            unreachable!()
+        };
+        assert_eq!("my_metric_1", alter_table.table_name().0[0].value);
+        let AlterTableOperation::Repartition { partitions } = alter_table.alter_operation() else {
+            unreachable!()
+        };
+        assert_eq!(
+            vec!["host"],
+            partitions
+                .column_list
+                .iter()
+                .map(|column| column.value.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(2, partitions.exprs.len());
+
+        let sql = "ALTER TABLE my_metric_1 PARTITION (a)";
+        let result =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default());
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_parse_alter_rename_table() {
         let sql = "ALTER TABLE test_table table_t";
@@ -853,7 +890,7 @@ mod tests {
             ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default())
                 .unwrap_err();
         let err = result.output_msg();
-        assert_eq!(err, "Invalid SQL syntax: sql parser error: Expected ADD or DROP or MODIFY or RENAME or SET after ALTER TABLE, found: table_t");
+        assert_eq!(err, "Invalid SQL syntax: sql parser error: Expected ADD or DROP or MODIFY or RENAME or SET or PARTITION after ALTER TABLE, found: table_t");
 
         let sql = "ALTER TABLE test_table RENAME table_t";
         let mut result =
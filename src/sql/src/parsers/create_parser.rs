@@ -30,17 +30,19 @@ use table::requests::validate_table_option;
 
 use crate::ast::{ColumnDef, Ident};
 use crate::error::{
-    self, InvalidColumnOptionSnafu, InvalidDatabaseOptionSnafu, InvalidIntervalSnafu,
-    InvalidSqlSnafu, InvalidTableOptionSnafu, InvalidTimeIndexSnafu, MissingTimeIndexSnafu, Result,
-    SyntaxSnafu, UnexpectedSnafu, UnsupportedSnafu,
+    self, InvalidColumnOptionSnafu, InvalidDatabaseOptionSnafu, InvalidFlowOptionSnafu,
+    InvalidIntervalSnafu, InvalidSqlSnafu, InvalidTableOptionSnafu, InvalidTimeIndexSnafu,
+    MissingTimeIndexSnafu, Result, SyntaxSnafu, UnexpectedSnafu, UnsupportedSnafu,
 };
 use crate::parser::{ParserContext, FLOW};
 use crate::parsers::utils::{
     self, validate_column_fulltext_create_option, validate_column_skipping_index_create_option,
+    validate_column_sst_create_option,
 };
 use crate::statements::create::{
-    Column, ColumnExtensions, CreateDatabase, CreateExternalTable, CreateFlow, CreateTable,
-    CreateTableLike, CreateView, Partitions, TableConstraint, VECTOR_OPT_DIM,
+    validate_flow_option, Column, ColumnExtensions, CreateDatabase, CreateExternalTable,
+    CreateFlow, CreateTable, CreateTableLike, CreateView, Partitions, TableConstraint,
+    VECTOR_OPT_DIM,
 };
 use crate::statements::statement::Statement;
 use crate::statements::transform::type_alias::get_data_type_by_alias_name;
@@ -54,10 +56,16 @@ pub const EXPIRE: &str = "EXPIRE";
 pub const AFTER: &str = "AFTER";
 pub const INVERTED: &str = "INVERTED";
 pub const SKIPPING: &str = "SKIPPING";
+pub const SST: &str = "SST";
 
 const DB_OPT_KEY_TTL: &str = "ttl";
+/// The prefix of the database options that set the default table options.
+const DB_OPT_TABLE_DEFAULTS_PREFIX: &str = "table_defaults.";
 
 fn validate_database_option(key: &str) -> bool {
+    if let Some(table_key) = key.strip_prefix(DB_OPT_TABLE_DEFAULTS_PREFIX) {
+        return validate_table_option(table_key);
+    }
     [DB_OPT_KEY_TTL].contains(&key)
 }
 
@@ -314,6 +322,17 @@ impl<'a> ParserContext<'a> {
             None
         };
 
+        let flow_options = self
+            .parser
+            .parse_options(Keyword::WITH)
+            .context(SyntaxSnafu)?
+            .into_iter()
+            .map(parse_option_string)
+            .collect::<Result<HashMap<String, String>>>()?;
+        for key in flow_options.keys() {
+            ensure!(validate_flow_option(key), InvalidFlowOptionSnafu { key });
+        }
+
         self.parser
             .expect_keyword(Keyword::AS)
             .context(SyntaxSnafu)?;
@@ -327,6 +346,7 @@ impl<'a> ParserContext<'a> {
             if_not_exists,
             expire_after,
             comment,
+            flow_options: flow_options.into(),
             query,
         }))
     }
@@ -370,7 +390,7 @@ impl<'a> ParserContext<'a> {
     }
 
     /// "PARTITION ON COLUMNS (...)" clause
-    fn parse_partitions(&mut self) -> Result<Option<Partitions>> {
+    pub(crate) fn parse_partitions(&mut self) -> Result<Option<Partitions>> {
         if !self.parser.parse_keyword(Keyword::PARTITION) {
             return Ok(None);
         }
@@ -634,6 +654,10 @@ impl<'a> ParserContext<'a> {
             Ok(Some(ColumnOption::Default(
                 parser.parse_expr().context(SyntaxSnafu)?,
             )))
+        } else if parser.parse_keywords(&[Keyword::ON, Keyword::UPDATE]) {
+            Ok(Some(ColumnOption::OnUpdate(
+                parser.parse_expr().context(SyntaxSnafu)?,
+            )))
         } else if parser.parse_keywords(&[Keyword::PRIMARY, Keyword::KEY]) {
             Ok(Some(ColumnOption::Unique {
                 is_primary: true,
@@ -668,6 +692,7 @@ impl<'a> ParserContext<'a> {
     /// This function will handle:
     /// - Vector type
     /// - Indexes
+    /// - SST options
     fn parse_column_extensions(
         parser: &mut Parser<'_>,
         column_name: &Ident,
@@ -836,6 +861,49 @@ impl<'a> ParserContext<'a> {
             is_index_declared |= true;
         }
 
+        // sst options
+        if let Token::Word(word) = parser.peek_token().token
+            && word.value.eq_ignore_ascii_case(SST)
+        {
+            parser.next_token();
+            ensure!(
+                column_extensions.sst_options.is_none(),
+                InvalidColumnOptionSnafu {
+                    name: column_name.to_string(),
+                    msg: "duplicated SST option",
+                }
+            );
+
+            let options = parser
+                .parse_options(Keyword::WITH)
+                .context(error::SyntaxSnafu)?
+                .into_iter()
+                .map(parse_option_string)
+                .collect::<Result<HashMap<String, String>>>()?;
+
+            for key in options.keys() {
+                ensure!(
+                    validate_column_sst_create_option(key),
+                    InvalidColumnOptionSnafu {
+                        name: column_name.to_string(),
+                        msg: format!("invalid SST option: {key}"),
+                    }
+                );
+            }
+
+            let options = OptionMap::from(options);
+            ensure!(
+                ColumnExtensions::parse_sst_dictionary(&options).is_some(),
+                InvalidColumnOptionSnafu {
+                    name: column_name.to_string(),
+                    msg: "SST dictionary option should be 'on' or 'off'",
+                }
+            );
+
+            column_extensions.sst_options = Some(options);
+            is_index_declared |= true;
+        }
+
         Ok(is_index_declared)
     }
 
@@ -1289,6 +1357,23 @@ mod tests {
             }
             _ => unreachable!(),
         }
+
+        let sql = "CREATE DATABASE logs with ('table_defaults.append_mode'='true', 'table_defaults.ttl'='7d');";
+        let result =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default());
+        let stmts = result.unwrap();
+        match &stmts[0] {
+            Statement::CreateDatabase(c) => {
+                assert_eq!(c.options.get("table_defaults.append_mode").unwrap(), "true");
+                assert_eq!(c.options.get("table_defaults.ttl").unwrap(), "7d");
+            }
+            _ => unreachable!(),
+        }
+
+        let sql = "CREATE DATABASE logs with ('table_defaults.foo'='bar');";
+        let result =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default());
+        assert!(result.is_err());
     }
 
     #[test]
@@ -1316,6 +1401,7 @@ SELECT max(c1), min(c2) FROM schema_2.table_2;";
             if_not_exists: true,
             expire_after: Some(300),
             comment: Some("test comment".to_string()),
+            flow_options: OptionMap::default(),
             // ignore query parse result
             query: create_task.query.clone(),
         };
@@ -1342,6 +1428,47 @@ SELECT max(c1), min(c2) FROM schema_2.table_2;";
         assert_eq!(create_task.flow_name.to_string(), "`task_2`");
     }
 
+    #[test]
+    fn test_parse_create_flow_with_options() {
+        let sql = r"
+CREATE FLOW task_3
+SINK TO job_rate
+WITH (promql_rewrite = 'sum by (job) (rate(http_requests_total[5m]))', promql_rewrite_step = '1m')
+AS
+SELECT job, sum(v) FROM http_requests_total GROUP BY job, date_bin('1 minute'::INTERVAL, ts);";
+        let stmts =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default())
+                .unwrap();
+        let Statement::CreateFlow(create_task) = &stmts[0] else {
+            unreachable!()
+        };
+        assert_eq!(
+            Some("sum by (job) (rate(http_requests_total[5m]))"),
+            create_task
+                .flow_options
+                .get("promql_rewrite")
+                .map(String::as_str)
+        );
+        assert_eq!(
+            Some("1m"),
+            create_task
+                .flow_options
+                .get("promql_rewrite_step")
+                .map(String::as_str)
+        );
+
+        let sql = r"
+CREATE FLOW task_3
+SINK TO job_rate
+WITH (foo = 'bar')
+AS
+SELECT 1;";
+        let err =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default())
+                .unwrap_err();
+        assert_eq!("Unrecognized flow option key: foo", err.to_string());
+    }
+
     #[test]
     fn test_validate_create() {
         let sql = r"
@@ -1753,6 +1880,35 @@ ENGINE=mito";
         }
     }
 
+    #[test]
+    fn test_parse_column_on_update() {
+        let sql = r"
+CREATE TABLE metadata (
+  k          STRING PRIMARY KEY,
+  ts         TIMESTAMP TIME INDEX,
+  updated_at TIMESTAMP DEFAULT now() ON UPDATE now(),
+)";
+
+        let result =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default())
+                .unwrap();
+
+        if let Statement::CreateTable(c) = &result[0] {
+            let updated_at = &c.columns[2];
+            assert_eq!(updated_at.name().to_string(), "updated_at");
+            assert!(matches!(
+                updated_at.options()[0].option,
+                ColumnOption::Default(..)
+            ));
+            assert!(matches!(
+                &updated_at.options()[1].option,
+                ColumnOption::OnUpdate(Expr::Function(f)) if f.to_string() == "now()"
+            ));
+        } else {
+            unreachable!("should be create table statement");
+        }
+    }
+
     #[test]
     fn test_parse_partitions_with_error_syntax() {
         let sql = r"
@@ -2132,6 +2288,54 @@ CREATE TABLE log (
             .contains("invalid FULLTEXT INDEX option"));
     }
 
+    #[test]
+    fn test_parse_create_table_sst_options() {
+        let sql = r"
+CREATE TABLE log (
+    ts TIMESTAMP TIME INDEX,
+    host STRING SST WITH (dictionary='off'),
+    msg STRING,
+)";
+        let result =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default())
+                .unwrap();
+
+        if let Statement::CreateTable(c) = &result[0] {
+            c.columns
+                .iter()
+                .for_each(|col| match col.name().value.as_str() {
+                    "host" => assert_eq!(Some(false), col.extensions.sst_dictionary()),
+                    _ => assert_eq!(None, col.extensions.sst_dictionary()),
+                });
+        } else {
+            panic!("should be create_table statement");
+        }
+
+        let sql = r"
+CREATE TABLE log (
+    ts TIMESTAMP TIME INDEX,
+    host STRING SST WITH (dictionary='maybe'),
+)";
+        let result =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("SST dictionary option should be 'on' or 'off'"));
+
+        let sql = r"
+CREATE TABLE log (
+    ts TIMESTAMP TIME INDEX,
+    host STRING SST WITH (compression='zstd'),
+)";
+        let result =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("invalid SST option: compression"));
+    }
+
     #[test]
     fn test_parse_create_table_skip_options() {
         let sql = r"
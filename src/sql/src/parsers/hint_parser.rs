@@ -0,0 +1,115 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snafu::ResultExt;
+use sqlparser::dialect::Dialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::ParserError;
+use sqlparser::tokenizer::{Token, Tokenizer, Whitespace};
+
+use crate::error::{Result, SyntaxSnafu};
+use crate::parser::ParserContext;
+
+/// Prefix of a comment that holds hints, e.g. `/*+ no_inverted_index */`.
+const HINT_PREFIX: char = '+';
+
+impl ParserContext<'_> {
+    /// Extracts the hints of each statement in the SQL, in the same order as the
+    /// statements returned by [ParserContext::create_with_dialect].
+    ///
+    /// Hints are written in a `/*+ ... */` comment right after the first `SELECT`
+    /// of a statement, e.g. `SELECT /*+ scan_parallelism(8) */ * FROM t`.
+    pub fn parse_hints(sql: &str, dialect: &dyn Dialect) -> Result<Vec<Option<String>>> {
+        let tokens = Tokenizer::new(dialect, sql)
+            .tokenize()
+            .map_err(ParserError::from)
+            .context(SyntaxSnafu)?;
+
+        let mut hints = Vec::new();
+        let mut in_statement = false;
+        let mut seen_select = false;
+        let mut expect_hint = false;
+        let mut statement_hint = None;
+        for token in tokens {
+            match token {
+                Token::SemiColon => {
+                    // Ignores empty statements like the parser does.
+                    if in_statement {
+                        hints.push(statement_hint.take());
+                    }
+                    in_statement = false;
+                    seen_select = false;
+                    expect_hint = false;
+                }
+                Token::Whitespace(Whitespace::MultiLineComment(comment)) => {
+                    if expect_hint {
+                        if let Some(hint) = comment.strip_prefix(HINT_PREFIX) {
+                            statement_hint = Some(hint.trim().to_string());
+                        }
+                    }
+                    expect_hint = false;
+                }
+                Token::Whitespace(_) => {}
+                Token::Word(word) if word.keyword == Keyword::SELECT && !seen_select => {
+                    in_statement = true;
+                    seen_select = true;
+                    expect_hint = true;
+                }
+                _ => {
+                    in_statement = true;
+                    expect_hint = false;
+                }
+            }
+        }
+        if in_statement {
+            hints.push(statement_hint);
+        }
+
+        Ok(hints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::GreptimeDbDialect;
+    use crate::parser::{ParseOptions, ParserContext};
+
+    #[test]
+    fn test_parse_hints() {
+        let sql = "SELECT /*+ scan_parallelism(8), no_inverted_index */ * FROM t; \
+                   ;SELECT * FROM t; \
+                   select /* not a hint */ /*+ no_index */ 1; \
+                   INSERT INTO t SELECT /*+ read_latest */ * FROM s; \
+                   SELECT * FROM (SELECT /*+ no_index */ * FROM t)";
+        let hints = ParserContext::parse_hints(sql, &GreptimeDbDialect {}).unwrap();
+        assert_eq!(
+            vec![
+                Some("scan_parallelism(8), no_inverted_index".to_string()),
+                None,
+                None,
+                Some("read_latest".to_string()),
+                None,
+            ],
+            hints
+        );
+
+        let stmts =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default())
+                .unwrap();
+        assert_eq!(stmts.len(), hints.len());
+
+        let hints = ParserContext::parse_hints("", &GreptimeDbDialect {}).unwrap();
+        assert!(hints.is_empty());
+    }
+}
@@ -22,8 +22,8 @@ use crate::error::{
 use crate::parser::ParserContext;
 use crate::statements::show::{
     ShowColumns, ShowCreateDatabase, ShowCreateFlow, ShowCreateTable, ShowCreateTableVariant,
-    ShowCreateView, ShowDatabases, ShowFlows, ShowIndex, ShowKind, ShowRegion, ShowSearchPath,
-    ShowStatus, ShowTableStatus, ShowTables, ShowVariables, ShowViews,
+    ShowCreateView, ShowDatabases, ShowFlows, ShowIndex, ShowKind, ShowProcedures, ShowRegion,
+    ShowSearchPath, ShowStatus, ShowTableStatus, ShowTables, ShowVariables, ShowViews,
 };
 use crate::statements::statement::Statement;
 
@@ -49,6 +49,10 @@ impl ParserContext<'_> {
             self.parse_show_views()
         } else if self.consume_token("FLOWS") {
             self.parse_show_flows()
+        } else if self.consume_token("PROCEDURES") {
+            Ok(Statement::ShowProcedures(ShowProcedures {
+                kind: self.parse_show_kind()?,
+            }))
         } else if self.matches_keyword(Keyword::CHARSET) {
             self.parser.next_token();
             Ok(Statement::ShowCharset(self.parse_show_kind()?))
@@ -1205,4 +1209,33 @@ mod tests {
         );
         assert_eq!(sql, stmts[0].to_string());
     }
+
+    #[test]
+    pub fn test_show_procedures() {
+        let sql = "SHOW PROCEDURES";
+        let result =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default());
+        let stmts = result.unwrap();
+        assert_eq!(1, stmts.len());
+        assert_eq!(
+            stmts[0],
+            Statement::ShowProcedures(ShowProcedures {
+                kind: ShowKind::All,
+            })
+        );
+        assert_eq!(sql, stmts[0].to_string());
+
+        let sql = "SHOW PROCEDURES WHERE Status = 'Running'";
+        let stmts =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default())
+                .unwrap();
+        assert_eq!(1, stmts.len());
+        assert_matches!(
+            &stmts[0],
+            Statement::ShowProcedures(ShowProcedures {
+                kind: ShowKind::Where(_),
+            })
+        );
+        assert_eq!(sql, stmts[0].to_string());
+    }
 }
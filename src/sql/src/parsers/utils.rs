@@ -30,7 +30,7 @@ use datatypes::arrow::datatypes::DataType;
 use datatypes::schema::{
     COLUMN_FULLTEXT_OPT_KEY_ANALYZER, COLUMN_FULLTEXT_OPT_KEY_BACKEND,
     COLUMN_FULLTEXT_OPT_KEY_CASE_SENSITIVE, COLUMN_SKIPPING_INDEX_OPT_KEY_GRANULARITY,
-    COLUMN_SKIPPING_INDEX_OPT_KEY_TYPE,
+    COLUMN_SKIPPING_INDEX_OPT_KEY_TYPE, COLUMN_SST_OPT_KEY_DICTIONARY,
 };
 use snafu::ResultExt;
 
@@ -137,3 +137,7 @@ pub fn validate_column_skipping_index_create_option(key: &str) -> bool {
     ]
     .contains(&key)
 }
+
+pub fn validate_column_sst_create_option(key: &str) -> bool {
+    [COLUMN_SST_OPT_KEY_DICTIONARY].contains(&key)
+}
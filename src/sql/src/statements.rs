@@ -27,6 +27,7 @@ pub mod query;
 pub mod set_variables;
 pub mod show;
 pub mod statement;
+pub mod table_sample;
 pub mod tql;
 pub(crate) mod transform;
 pub mod truncate;
@@ -34,12 +35,13 @@ pub mod truncate;
 use std::str::FromStr;
 
 use api::helper::ColumnDataTypeWrapper;
+use api::v1::column_def::options_from_on_update;
 use api::v1::SemanticType;
 use common_base::bytes::Bytes;
 use common_time::timezone::Timezone;
 use common_time::Timestamp;
 use datatypes::prelude::ConcreteDataType;
-use datatypes::schema::constraint::{CURRENT_TIMESTAMP, CURRENT_TIMESTAMP_FN};
+use datatypes::schema::constraint::{CURRENT_TIMESTAMP, CURRENT_TIMESTAMP_FN, NOW_FN};
 use datatypes::schema::{ColumnDefaultConstraint, ColumnSchema, COMMENT_KEY};
 use datatypes::types::{
     cast, parse_string_to_json_type_value, parse_string_to_vector_type_value, TimestampType,
@@ -54,10 +56,10 @@ use crate::ast::{
 };
 use crate::error::{
     self, ColumnTypeMismatchSnafu, ConvertSqlValueSnafu, ConvertToGrpcDataTypeSnafu,
-    ConvertValueSnafu, DatatypeSnafu, InvalidCastSnafu, InvalidSqlValueSnafu, InvalidUnaryOpSnafu,
-    ParseSqlValueSnafu, Result, SerializeColumnDefaultConstraintSnafu, SetFulltextOptionSnafu,
-    SetSkippingIndexOptionSnafu, TimestampOverflowSnafu, UnsupportedDefaultValueSnafu,
-    UnsupportedUnaryOpSnafu,
+    ConvertValueSnafu, DatatypeSnafu, InvalidCastSnafu, InvalidColumnOptionSnafu,
+    InvalidSqlValueSnafu, InvalidUnaryOpSnafu, ParseSqlValueSnafu, Result,
+    SerializeColumnDefaultConstraintSnafu, SetFulltextOptionSnafu, SetSkippingIndexOptionSnafu,
+    TimestampOverflowSnafu, UnsupportedDefaultValueSnafu, UnsupportedUnaryOpSnafu,
 };
 use crate::statements::create::Column;
 pub use crate::statements::option_map::OptionMap;
@@ -429,6 +431,64 @@ fn parse_column_default_constraint(
     }
 }
 
+/// Parses the `ON UPDATE` option of the column, returns the function to fill the column
+/// on every write that omits it.
+///
+/// Only `now()` and `current_timestamp()` are supported and the column must be a timestamp
+/// column whose default value is also one of these functions.
+fn parse_column_on_update(
+    column_name: &str,
+    data_type: &ConcreteDataType,
+    opts: &[ColumnOptionDef],
+    default_constraint: Option<&ColumnDefaultConstraint>,
+) -> Result<Option<String>> {
+    let Some(expr) = opts.iter().find_map(|o| match &o.option {
+        ColumnOption::OnUpdate(expr) => Some(expr),
+        _ => None,
+    }) else {
+        return Ok(None);
+    };
+
+    let func = match expr {
+        Expr::Function(func) => {
+            let func = format!("{func}").to_lowercase();
+            // normalize CURRENT_TIMESTAMP to CURRENT_TIMESTAMP()
+            if func == CURRENT_TIMESTAMP {
+                CURRENT_TIMESTAMP_FN.to_string()
+            } else {
+                func
+            }
+        }
+        _ => String::new(),
+    };
+    ensure!(
+        func == CURRENT_TIMESTAMP_FN || func == NOW_FN,
+        InvalidColumnOptionSnafu {
+            name: column_name,
+            msg: format!("unsupported ON UPDATE expr: {expr}, expect now() or current_timestamp()"),
+        }
+    );
+    ensure!(
+        data_type.is_timestamp(),
+        InvalidColumnOptionSnafu {
+            name: column_name,
+            msg: format!("ON UPDATE requires a timestamp column, found: {data_type}"),
+        }
+    );
+    ensure!(
+        matches!(
+            default_constraint,
+            Some(ColumnDefaultConstraint::Function(f)) if f == CURRENT_TIMESTAMP_FN || f == NOW_FN
+        ),
+        InvalidColumnOptionSnafu {
+            name: column_name,
+            msg: "ON UPDATE requires DEFAULT now() or DEFAULT current_timestamp()",
+        }
+    );
+
+    Ok(Some(func))
+}
+
 /// Return true when the `ColumnDef` options contain primary key
 pub fn has_primary_key_option(column_def: &ColumnDef) -> bool {
     column_def
@@ -461,12 +521,20 @@ pub fn column_to_schema(
     let default_constraint =
         parse_column_default_constraint(&name, &data_type, column.options(), timezone)?;
 
+    let on_update = parse_column_on_update(
+        &name,
+        &data_type,
+        column.options(),
+        default_constraint.as_ref(),
+    )?;
+
     let mut column_schema = ColumnSchema::new(name, data_type, is_nullable)
         .with_time_index(is_time_index)
         .with_default_constraint(default_constraint)
         .context(error::InvalidDefaultSnafu {
             column: &column.name().value,
-        })?;
+        })?
+        .with_on_update(on_update);
 
     if let Some(ColumnOption::Comment(c)) = column.options().iter().find_map(|o| {
         if matches!(o.option, ColumnOption::Comment(_)) {
@@ -494,6 +562,10 @@ pub fn column_to_schema(
 
     column_schema.set_inverted_index(column.extensions.inverted_index_options.is_some());
 
+    if let Some(enabled) = column.extensions.sst_dictionary() {
+        column_schema.set_sst_dictionary(enabled);
+    }
+
     Ok(column_schema)
 }
 
@@ -511,10 +583,14 @@ pub fn sql_column_def_to_grpc_column_def(
         .all(|o| !matches!(o.option, ColumnOption::NotNull));
 
     let default_constraint =
-        parse_column_default_constraint(&name, &data_type, &col.options, timezone)?
-            .map(ColumnDefaultConstraint::try_into) // serialize default constraint to bytes
-            .transpose()
-            .context(SerializeColumnDefaultConstraintSnafu)?;
+        parse_column_default_constraint(&name, &data_type, &col.options, timezone)?;
+    let options =
+        parse_column_on_update(&name, &data_type, &col.options, default_constraint.as_ref())?
+            .map(|func| options_from_on_update(&func));
+    let default_constraint = default_constraint
+        .map(ColumnDefaultConstraint::try_into) // serialize default constraint to bytes
+        .transpose()
+        .context(SerializeColumnDefaultConstraintSnafu)?;
     // convert ConcreteDataType to grpc ColumnDataTypeWrapper
     let (datatype, datatype_ext) = ColumnDataTypeWrapper::try_from(data_type.clone())
         .context(ConvertToGrpcDataTypeSnafu)?
@@ -544,7 +620,7 @@ pub fn sql_column_def_to_grpc_column_def(
         semantic_type: semantic_type as _,
         comment: String::new(),
         datatype_extension: datatype_ext,
-        options: None,
+        options,
     })
 }
 
@@ -680,6 +756,8 @@ mod tests {
 
     use super::*;
     use crate::ast::TimezoneInfo;
+    use crate::dialect::GreptimeDbDialect;
+    use crate::parser::ParserContext;
     use crate::statements::create::ColumnExtensions;
     use crate::statements::ColumnOption;
 
@@ -1432,6 +1510,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_column_to_schema_with_on_update() {
+        let now = || ParserContext::parse_function("now()", &GreptimeDbDialect {}).unwrap();
+        let mut column = Column {
+            column_def: ColumnDef {
+                name: "updated_at".into(),
+                data_type: SqlDataType::Timestamp(Some(3), TimezoneInfo::None),
+                collation: None,
+                options: vec![
+                    ColumnOptionDef {
+                        name: None,
+                        option: ColumnOption::Default(now()),
+                    },
+                    ColumnOptionDef {
+                        name: None,
+                        option: ColumnOption::OnUpdate(now()),
+                    },
+                ],
+            },
+            extensions: ColumnExtensions::default(),
+        };
+
+        let column_schema = column_to_schema(&column, "ts", None).unwrap();
+        assert_eq!(Some(&"now()".to_string()), column_schema.on_update());
+        let grpc_column_def = sql_column_def_to_grpc_column_def(&column.column_def, None).unwrap();
+        assert!(grpc_column_def.options.is_some());
+
+        // ON UPDATE requires the default value to be the current timestamp.
+        column.column_def.options.remove(0);
+        assert!(column_to_schema(&column, "ts", None).is_err());
+    }
+
     #[test]
     fn test_column_to_schema_with_fulltext() {
         let column = Column {
@@ -1458,6 +1568,7 @@ mod tests {
                 vector_options: None,
                 skipping_index_options: None,
                 inverted_index_options: None,
+                sst_options: None,
             },
         };
 
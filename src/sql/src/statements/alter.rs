@@ -22,6 +22,8 @@ use serde::Serialize;
 use sqlparser::ast::{ColumnDef, DataType, Ident, ObjectName, TableConstraint};
 use sqlparser_derive::{Visit, VisitMut};
 
+use crate::statements::create::Partitions;
+
 #[derive(Debug, Clone, PartialEq, Eq, Visit, VisitMut, Serialize)]
 pub struct AlterTable {
     pub table_name: ObjectName,
@@ -92,6 +94,10 @@ pub enum AlterTableOperation {
     UnsetIndex {
         options: UnsetIndexOperation,
     },
+    /// `PARTITION ON COLUMNS (<columns>) (<rule exprs>)`
+    Repartition {
+        partitions: Partitions,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Visit, VisitMut, Serialize)]
@@ -204,6 +210,7 @@ impl Display for AlterTableOperation {
                     write!(f, "MODIFY COLUMN {column_name} UNSET SKIPPING INDEX")
                 }
             },
+            AlterTableOperation::Repartition { partitions } => write!(f, "{partitions}"),
         }
     }
 }
@@ -487,5 +494,28 @@ ALTER TABLE monitor MODIFY COLUMN a SET INVERTED INDEX"#,
                 unreachable!();
             }
         }
+
+        let sql = "ALTER TABLE monitor PARTITION ON COLUMNS (host) (host < 'b', host >= 'b')";
+        let stmts =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default())
+                .unwrap();
+        assert_eq!(1, stmts.len());
+
+        match &stmts[0] {
+            Statement::AlterTable(set) => {
+                let new_sql = format!("\n{}", set);
+                assert_eq!(
+                    r#"
+ALTER TABLE monitor PARTITION ON COLUMNS (host) (
+  host < 'b',
+  host >= 'b'
+)"#,
+                    &new_sql
+                );
+            }
+            _ => {
+                unreachable!();
+            }
+        }
     }
 }
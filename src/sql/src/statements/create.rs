@@ -16,7 +16,7 @@ use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
 use common_catalog::consts::FILE_ENGINE;
-use datatypes::schema::{FulltextOptions, SkippingIndexOptions};
+use datatypes::schema::{FulltextOptions, SkippingIndexOptions, COLUMN_SST_OPT_KEY_DICTIONARY};
 use itertools::Itertools;
 use serde::Serialize;
 use snafu::ResultExt;
@@ -118,6 +118,8 @@ pub struct ColumnExtensions {
     ///
     /// Inverted index doesn't have options at present. There won't be any options in that map.
     pub inverted_index_options: Option<OptionMap>,
+    /// SST options, e.g. whether to use dictionary encoding.
+    pub sst_options: Option<OptionMap>,
 }
 
 impl Column {
@@ -179,6 +181,13 @@ impl Display for Column {
                 write!(f, " INVERTED INDEX")?;
             }
         }
+
+        if let Some(sst_options) = &self.extensions.sst_options {
+            if !sst_options.is_empty() {
+                let options = sst_options.kv_pairs();
+                write!(f, " SST WITH({})", format_list_comma!(options))?;
+            }
+        }
         Ok(())
     }
 }
@@ -203,6 +212,25 @@ impl ColumnExtensions {
             options.try_into().context(SetSkippingIndexOptionSnafu)?,
         ))
     }
+
+    /// Returns whether the column uses dictionary encoding in SST files.
+    /// Returns `None` if the option is not set or invalid.
+    pub fn sst_dictionary(&self) -> Option<bool> {
+        let options = self.sst_options.as_ref()?;
+        options.get(COLUMN_SST_OPT_KEY_DICTIONARY)?;
+        Self::parse_sst_dictionary(options)
+    }
+
+    /// Parses the dictionary option in SST options. It's enabled if the option is absent.
+    /// Returns `None` if the value is invalid.
+    pub fn parse_sst_dictionary(options: &OptionMap) -> Option<bool> {
+        match options.get(COLUMN_SST_OPT_KEY_DICTIONARY) {
+            None => Some(true),
+            Some(value) if value.eq_ignore_ascii_case("on") => Some(true),
+            Some(value) if value.eq_ignore_ascii_case("off") => Some(false),
+            Some(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Visit, VisitMut, Serialize)]
@@ -373,10 +401,29 @@ pub struct CreateFlow {
     pub expire_after: Option<i64>,
     /// Comment string
     pub comment: Option<String>,
+    /// Flow options in `WITH`
+    pub flow_options: OptionMap,
     /// SQL statement
     pub query: Box<Query>,
 }
 
+/// The PromQL expression the flow pre-computes into its sink table. PromQL queries
+/// containing this expression read the sink table instead.
+pub const FLOW_OPT_KEY_PROMQL_REWRITE: &str = "promql_rewrite";
+/// The interval between the timestamps the flow writes to its sink table, required by
+/// [FLOW_OPT_KEY_PROMQL_REWRITE]. Only queries whose step and start are multiples of
+/// it are rewritten.
+pub const FLOW_OPT_KEY_PROMQL_REWRITE_STEP: &str = "promql_rewrite_step";
+
+/// Returns true if the `key` is a valid option of `CREATE FLOW`.
+pub fn validate_flow_option(key: &str) -> bool {
+    [
+        FLOW_OPT_KEY_PROMQL_REWRITE,
+        FLOW_OPT_KEY_PROMQL_REWRITE_STEP,
+    ]
+    .contains(&key)
+}
+
 impl Display for CreateFlow {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "CREATE ")?;
@@ -395,6 +442,10 @@ impl Display for CreateFlow {
         if let Some(comment) = &self.comment {
             writeln!(f, "COMMENT '{}'", comment)?;
         }
+        if !self.flow_options.is_empty() {
+            let options = self.flow_options.kv_pairs();
+            writeln!(f, "WITH(\n{}\n)", format_list_indent!(options))?;
+        }
         write!(f, "AS {}", &self.query)
     }
 }
@@ -14,7 +14,8 @@
 
 use serde::Serialize;
 use sqlparser::ast::{
-    Insert as SpInsert, ObjectName, Query, SetExpr, Statement, TableObject, UnaryOperator, Values,
+    Ident, Insert as SpInsert, ObjectName, Query, SetExpr, Statement, TableObject, UnaryOperator,
+    Values,
 };
 use sqlparser::parser::ParserError;
 use sqlparser_derive::{Visit, VisitMut};
@@ -61,6 +62,20 @@ impl Insert {
         }
     }
 
+    /// Replaces the columns to insert into. The names are quoted so they are
+    /// taken as-is.
+    pub fn set_columns(&mut self, columns: Vec<String>) {
+        match &mut self.inner {
+            Statement::Insert(insert) => {
+                insert.columns = columns
+                    .into_iter()
+                    .map(|column| Ident::with_quote('"', column))
+                    .collect();
+            }
+            _ => unreachable!(),
+        }
+    }
+
     /// Extracts the literal insert statement body if possible
     pub fn values_body(&self) -> Result<Vec<Vec<Value>>> {
         match &self.inner {
@@ -108,6 +123,20 @@ impl Insert {
         }
     }
 
+    /// Returns the query that produces the rows to insert if it's not `VALUES`,
+    /// e.g. `INSERT INTO t SELECT ...`.
+    pub fn select_body(&self) -> Result<Option<GtQuery>> {
+        Ok(match &self.inner {
+            Statement::Insert(SpInsert {
+                source: Some(box query),
+                ..
+            }) if !matches!(query.body.as_ref(), SetExpr::Values(_)) => {
+                Some(query.clone().try_into()?)
+            }
+            _ => None,
+        })
+    }
+
     pub fn query_body(&self) -> Result<Option<GtQuery>> {
         Ok(match &self.inner {
             Statement::Insert(SpInsert {
@@ -253,6 +253,21 @@ impl Display for ShowFlows {
     }
 }
 
+/// SQL structure for `SHOW PROCEDURES`.
+#[derive(Debug, Clone, PartialEq, Eq, Visit, VisitMut, Serialize)]
+pub struct ShowProcedures {
+    pub kind: ShowKind,
+}
+
+impl Display for ShowProcedures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SHOW PROCEDURES")?;
+        format_kind!(self, f);
+
+        Ok(())
+    }
+}
+
 /// SQL structure for `SHOW CREATE VIEW`.
 #[derive(Debug, Clone, PartialEq, Eq, Visit, VisitMut, Serialize)]
 pub struct ShowCreateView {
@@ -0,0 +1,248 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::ControlFlow;
+
+use snafu::{ensure, OptionExt};
+use sqlparser::ast::{
+    Expr, FunctionArg, FunctionArgExpr, ObjectName, TableFactor, TableSampleKind,
+    TableSampleMethod, TableSampleUnit, Value, VisitMut, VisitorMut,
+};
+
+use crate::error::{Error, InvalidSqlSnafu, Result};
+use crate::statements::statement::Statement;
+
+/// Name of the table function to sample a table, e.g. `sample(my_table, 0.001)`
+/// or `sample(my_table, 0.001, 42)` with a seed.
+const SAMPLE_FUNCTION: &str = "sample";
+
+/// Sampling of a table, from `TABLESAMPLE SYSTEM (percent) [REPEATABLE (seed)]`
+/// or the `sample(table, fraction [, seed])` table function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableSample {
+    /// Fraction of the table to read, in `(0, 1]`.
+    pub fraction: f64,
+    /// Seed to pick the sample, `None` to use the default seed.
+    pub seed: Option<u64>,
+}
+
+/// Removes the sampling of tables from the statement and returns it, the query
+/// engine reads the sample while scanning the table.
+///
+/// Only statements that read a single table can be sampled.
+pub fn take_table_sample(stmt: &mut Statement) -> Result<Option<TableSample>> {
+    let mut visitor = TableSampleVisitor::default();
+    if let ControlFlow::Break(e) = stmt.visit(&mut visitor) {
+        return Err(e);
+    }
+
+    let Some(sample) = visitor.samples.first().copied() else {
+        return Ok(None);
+    };
+    ensure!(
+        visitor.num_tables == 1,
+        InvalidSqlSnafu {
+            msg: "table sampling is only supported in queries that read one table",
+        }
+    );
+
+    Ok(Some(sample))
+}
+
+#[derive(Default)]
+struct TableSampleVisitor {
+    num_tables: usize,
+    samples: Vec<TableSample>,
+}
+
+impl VisitorMut for TableSampleVisitor {
+    type Break = Error;
+
+    fn pre_visit_table_factor(&mut self, table_factor: &mut TableFactor) -> ControlFlow<Error> {
+        let TableFactor::Table {
+            name, args, sample, ..
+        } = table_factor
+        else {
+            return ControlFlow::Continue(());
+        };
+        self.num_tables += 1;
+
+        let result = if let Some(sample) = sample.take() {
+            parse_table_sample(sample)
+        } else if args.is_some() && is_sample_function(name) {
+            take_sample_function(name, args.take().unwrap().args)
+        } else {
+            return ControlFlow::Continue(());
+        };
+        match result {
+            Ok(sample) => {
+                self.samples.push(sample);
+                ControlFlow::Continue(())
+            }
+            Err(e) => ControlFlow::Break(e),
+        }
+    }
+}
+
+fn is_sample_function(name: &ObjectName) -> bool {
+    name.0.len() == 1 && name.0[0].value.eq_ignore_ascii_case(SAMPLE_FUNCTION)
+}
+
+/// Parses `TABLESAMPLE [SYSTEM | BLOCK] (percent) [REPEATABLE (seed)]`.
+///
+/// Row level sampling like `BERNOULLI` isn't supported as the engine samples blocks of rows.
+fn parse_table_sample(sample: TableSampleKind) -> Result<TableSample> {
+    let (TableSampleKind::BeforeTableAlias(sample) | TableSampleKind::AfterTableAlias(sample)) =
+        sample;
+    let unsupported = || InvalidSqlSnafu {
+        msg: format!("unsupported table sample: {sample}"),
+    };
+    ensure!(
+        matches!(
+            sample.name,
+            None | Some(TableSampleMethod::System) | Some(TableSampleMethod::Block)
+        ) && sample.bucket.is_none()
+            && sample.offset.is_none(),
+        unsupported()
+    );
+    let quantity = sample.quantity.as_ref().with_context(unsupported)?;
+    ensure!(
+        !matches!(quantity.unit, Some(TableSampleUnit::Rows)),
+        unsupported()
+    );
+    let percent = parse_number::<f64>(&quantity.value).with_context(unsupported)?;
+    let seed = match &sample.seed {
+        Some(seed) => match &seed.value {
+            Value::Number(n, _) => Some(n.parse::<u64>().ok().with_context(unsupported)?),
+            _ => return unsupported().fail(),
+        },
+        None => None,
+    };
+
+    new_sample(percent / 100.0, seed)
+}
+
+/// Replaces `sample(table, fraction [, seed])` by `table` and returns the sample.
+fn take_sample_function(name: &mut ObjectName, args: Vec<FunctionArg>) -> Result<TableSample> {
+    let invalid = || InvalidSqlSnafu {
+        msg: format!("{SAMPLE_FUNCTION}() expects arguments (table, fraction [, seed])"),
+    };
+    let args = args
+        .into_iter()
+        .map(|arg| match arg {
+            FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => Some(expr),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .with_context(invalid)?;
+    ensure!(args.len() == 2 || args.len() == 3, invalid());
+
+    let table = match &args[0] {
+        Expr::Identifier(ident) => ObjectName(vec![ident.clone()]),
+        Expr::CompoundIdentifier(idents) => ObjectName(idents.clone()),
+        _ => return invalid().fail(),
+    };
+    let fraction = parse_number::<f64>(&args[1]).with_context(invalid)?;
+    let seed = match args.get(2) {
+        Some(seed) => Some(parse_number::<u64>(seed).with_context(invalid)?),
+        None => None,
+    };
+
+    *name = table;
+    new_sample(fraction, seed)
+}
+
+fn parse_number<T: std::str::FromStr>(expr: &Expr) -> Option<T> {
+    match expr {
+        Expr::Value(Value::Number(n, _)) => n.parse().ok(),
+        _ => None,
+    }
+}
+
+fn new_sample(fraction: f64, seed: Option<u64>) -> Result<TableSample> {
+    ensure!(
+        fraction > 0.0 && fraction <= 1.0,
+        InvalidSqlSnafu {
+            msg: format!("sample fraction must be in (0, 1], got {fraction}"),
+        }
+    );
+
+    Ok(TableSample { fraction, seed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::GreptimeDbDialect;
+    use crate::parser::{ParseOptions, ParserContext};
+
+    fn take_sample(sql: &str) -> (Result<Option<TableSample>>, String) {
+        let mut stmt =
+            ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}, ParseOptions::default())
+                .unwrap()
+                .remove(0);
+        let sample = take_table_sample(&mut stmt);
+        (sample, stmt.to_string())
+    }
+
+    #[test]
+    fn test_take_table_sample() {
+        let (sample, sql) = take_sample("SELECT * FROM t TABLESAMPLE SYSTEM (0.1)");
+        assert_eq!(
+            Some(TableSample {
+                fraction: 0.001,
+                seed: None
+            }),
+            sample.unwrap()
+        );
+        assert_eq!("SELECT * FROM t", sql);
+
+        let (sample, sql) = take_sample("SELECT * FROM t TABLESAMPLE SYSTEM (50) REPEATABLE (42)");
+        assert_eq!(
+            Some(TableSample {
+                fraction: 0.5,
+                seed: Some(42)
+            }),
+            sample.unwrap()
+        );
+        assert_eq!("SELECT * FROM t", sql);
+
+        let (sample, sql) = take_sample("SELECT count(*) FROM sample(db.t, 0.25, 7) WHERE a > 1");
+        assert_eq!(
+            Some(TableSample {
+                fraction: 0.25,
+                seed: Some(7)
+            }),
+            sample.unwrap()
+        );
+        assert_eq!("SELECT count(*) FROM db.t WHERE a > 1", sql);
+
+        let (sample, sql) = take_sample("SELECT * FROM t");
+        assert!(sample.unwrap().is_none());
+        assert_eq!("SELECT * FROM t", sql);
+
+        for sql in [
+            "SELECT * FROM t TABLESAMPLE BERNOULLI (10)",
+            "SELECT * FROM t TABLESAMPLE SYSTEM (0)",
+            "SELECT * FROM t TABLESAMPLE SYSTEM (101)",
+            "SELECT * FROM sample(t)",
+            "SELECT * FROM sample(t, 'a')",
+            "SELECT * FROM sample(t, 0.1), s",
+            "SELECT * FROM t TABLESAMPLE SYSTEM (10) JOIN s ON t.a = s.a",
+        ] {
+            let (sample, _) = take_sample(sql);
+            assert!(sample.is_err(), "{sql}");
+        }
+    }
+}
@@ -45,6 +45,8 @@ pub const TWCS_TIME_WINDOW: &str = "compaction.twcs.time_window";
 pub const REMOTE_COMPACTION: &str = "compaction.twcs.remote_compaction";
 /// Option key for twcs fallback to local.
 pub const TWCS_FALLBACK_TO_LOCAL: &str = "compaction.twcs.fallback_to_local";
+/// Option key for columns to sort rows of SSTs written by compaction.
+pub const COMPACTION_SORT_BY: &str = "compaction.sort_by";
 /// Option key for memtable type.
 pub const MEMTABLE_TYPE: &str = "memtable.type";
 /// Option key for memtable partition tree primary key encoding.
@@ -61,6 +63,8 @@ pub const MEMTABLE_PARTITION_TREE_FORK_DICTIONARY_BYTES: &str =
     "memtable.partition_tree.fork_dictionary_bytes";
 /// Option key for skipping WAL.
 pub const SKIP_WAL_KEY: &str = "skip_wal";
+/// Option key for SST compression codec.
+pub const SST_COMPRESSION: &str = "sst.compression";
 // Note: Adding new options here should also check if this option should be removed in [metric_engine::engine::create::region_options_for_metadata_region].
 
 /// Returns true if the `key` is a valid option key for the mito engine.
@@ -76,6 +80,7 @@ pub fn is_mito_engine_option_key(key: &str) -> bool {
         TWCS_TIME_WINDOW,
         REMOTE_COMPACTION,
         TWCS_FALLBACK_TO_LOCAL,
+        COMPACTION_SORT_BY,
         "storage",
         "index.inverted_index.ignore_column_ids",
         "index.inverted_index.segment_row_count",
@@ -87,6 +92,7 @@ pub fn is_mito_engine_option_key(key: &str) -> bool {
         MEMTABLE_PARTITION_TREE_PRIMARY_KEY_ENCODING,
         APPEND_MODE_KEY,
         MERGE_MODE_KEY,
+        SST_COMPRESSION,
     ]
     .contains(&key)
 }
@@ -106,6 +112,7 @@ mod tests {
             "compaction.twcs.max_inactive_window_runs"
         ));
         assert!(is_mito_engine_option_key("compaction.twcs.time_window"));
+        assert!(is_mito_engine_option_key("compaction.sort_by"));
         assert!(is_mito_engine_option_key("storage"));
         assert!(is_mito_engine_option_key(
             "index.inverted_index.ignore_column_ids"
@@ -125,6 +132,7 @@ mod tests {
             "memtable.partition_tree.fork_dictionary_bytes"
         ));
         assert!(is_mito_engine_option_key("append_mode"));
+        assert!(is_mito_engine_option_key("sst.compression"));
         assert!(!is_mito_engine_option_key("foo"));
     }
 }
@@ -34,6 +34,7 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::Semaphore;
 
 use crate::logstore::entry;
+use crate::manifest::ManifestVersion;
 use crate::metadata::RegionMetadataRef;
 use crate::region_request::{
     BatchRegionDdlRequest, RegionOpenRequest, RegionRequest, RegionSequencesRequest,
@@ -381,6 +382,13 @@ pub struct RegionStatistic {
     /// The size of SST index files in bytes.
     #[serde(default)]
     pub index_size: u64,
+    /// The compression codec used to write new SST files.
+    #[serde(default)]
+    pub sst_compression: Option<String>,
+    /// The min and max values of the time index, in the unit of the time index.
+    /// `None` if the region is empty.
+    #[serde(default)]
+    pub time_range: Option<(i64, i64)>,
     /// The details of the region.
     #[serde(default)]
     pub manifest: RegionManifestInfo,
@@ -516,6 +524,58 @@ impl RegionStatistic {
     }
 }
 
+/// Request to get the distinct values of a tag column in a region.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelValuesRequest {
+    /// Name of the tag column.
+    pub column_name: String,
+    /// Inclusive start of the time range.
+    pub start: Timestamp,
+    /// Inclusive end of the time range.
+    pub end: Timestamp,
+}
+
+/// An SST file in the manifest of a region.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionFileEntry {
+    /// Id of the file.
+    pub file_id: String,
+    /// Path of the file in the object store.
+    pub file_path: String,
+    /// Inclusive time range of the rows in the file.
+    pub time_range: (Timestamp, Timestamp),
+    /// Number of rows in the file, 0 if it's unknown.
+    pub num_rows: u64,
+    /// Size of the file in bytes.
+    pub file_size: u64,
+}
+
+/// The SST files of a region at a version of its manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionManifestSnapshot {
+    /// Version of the manifest.
+    pub manifest_version: ManifestVersion,
+    /// Files in the manifest.
+    pub files: Vec<RegionFileEntry>,
+}
+
+/// Request to count the series of a region.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeriesCardinalityRequest {
+    /// Name of the tag column to count the series of each value of.
+    pub label: Option<String>,
+}
+
+/// Number of series in a region.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeriesCardinality {
+    /// Number of series.
+    pub num_series: u64,
+    /// Number of series of each value of the label in the request. Series whose
+    /// label is null are not counted.
+    pub label_values: HashMap<String, u64>,
+}
+
 #[async_trait]
 pub trait RegionEngine: Send + Sync {
     /// Name of this engine
@@ -604,6 +664,57 @@ pub trait RegionEngine: Send + Sync {
     /// Retrieves region's metadata.
     async fn get_metadata(&self, region_id: RegionId) -> Result<RegionMetadataRef, BoxedError>;
 
+    /// Returns the distinct values of a tag column without scanning the data of
+    /// the region, or `None` if the engine can't serve the request this way.
+    ///
+    /// The time range might be approximated, so the result may contain values of
+    /// rows out of the range.
+    async fn label_values(
+        &self,
+        _region_id: RegionId,
+        _request: LabelValuesRequest,
+    ) -> Result<Option<Vec<String>>, BoxedError> {
+        Ok(None)
+    }
+
+    /// Counts the series of the region, or returns `None` if the engine doesn't
+    /// support it.
+    async fn series_cardinality(
+        &self,
+        _region_id: RegionId,
+        _request: SeriesCardinalityRequest,
+    ) -> Result<Option<SeriesCardinality>, BoxedError> {
+        Ok(None)
+    }
+
+    /// Returns the SST files in the manifest of the region without touching the
+    /// data files, or `None` if the engine doesn't support it.
+    async fn region_manifest(
+        &self,
+        _region_id: RegionId,
+    ) -> Result<Option<RegionManifestSnapshot>, BoxedError> {
+        Ok(None)
+    }
+
+    /// Saves a checkpoint of the manifest of the region and returns the version of
+    /// the checkpoint, or `None` if the engine doesn't support it.
+    async fn checkpoint_region(
+        &self,
+        _region_id: RegionId,
+    ) -> Result<Option<ManifestVersion>, BoxedError> {
+        Ok(None)
+    }
+
+    /// Returns the highest sequence number written to the region by each producer, or
+    /// `None` if the engine doesn't deduplicate writes by their
+    /// [IdempotencyKey](crate::region_request::IdempotencyKey).
+    async fn producer_watermarks(
+        &self,
+        _region_id: RegionId,
+    ) -> Result<Option<HashMap<String, u64>>, BoxedError> {
+        Ok(None)
+    }
+
     /// Retrieves region's statistic.
     fn region_statistic(&self, region_id: RegionId) -> Option<RegionStatistic>;
 
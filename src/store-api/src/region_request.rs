@@ -23,13 +23,14 @@ use api::v1::column_def::{
 use api::v1::region::{
     alter_request, compact_request, region_request, AlterRequest, AlterRequests, CloseRequest,
     CompactRequest, CreateRequest, CreateRequests, DeleteRequests, DropRequest, DropRequests,
-    FlushRequest, InsertRequests, OpenRequest, TruncateRequest,
+    FlushRequest, InsertRequests, OpenRequest, RegionRequestHeader, TruncateRequest,
 };
 use api::v1::{
     self, set_index, Analyzer, FulltextBackend as PbFulltextBackend, Option as PbOption, Rows,
     SemanticType, SkippingIndexType as PbSkippingIndexType, WriteHint,
 };
 pub use common_base::AffectedRows;
+use common_time::range::TimestampRange;
 use common_time::TimeToLive;
 use datatypes::prelude::ConcreteDataType;
 use datatypes::schema::{FulltextOptions, SkippingIndexOptions};
@@ -125,7 +126,9 @@ pub enum RegionRequest {
     Flush(RegionFlushRequest),
     Compact(RegionCompactRequest),
     Truncate(RegionTruncateRequest),
+    TruncateRange(RegionTruncateRangeRequest),
     Catchup(RegionCatchupRequest),
+    BuildIndex(RegionBuildIndexRequest),
 }
 
 impl RegionRequest {
@@ -164,7 +167,11 @@ fn make_region_puts(inserts: InsertRequests) -> Result<Vec<(RegionId, RegionRequ
             r.rows.map(|rows| {
                 (
                     region_id,
-                    RegionRequest::Put(RegionPutRequest { rows, hint: None }),
+                    RegionRequest::Put(RegionPutRequest {
+                        rows,
+                        hint: None,
+                        idempotency_key: None,
+                    }),
                 )
             })
         })
@@ -322,6 +329,71 @@ pub struct RegionPutRequest {
     pub rows: Rows,
     /// Write hint.
     pub hint: Option<WriteHint>,
+    /// Idempotency key of the rows. Engines that don't deduplicate writes ignore it.
+    pub idempotency_key: Option<IdempotencyKey>,
+}
+
+/// Query context extension of the producer of a write, see [IdempotencyKey].
+pub const PRODUCER_ID_KEY: &str = "producer_id";
+/// Query context extension of the sequence number of a write, see [IdempotencyKey].
+pub const PRODUCER_SEQUENCE_KEY: &str = "producer_sequence";
+
+/// Identifies a write of a producer that may deliver it more than once, e.g. a
+/// pipeline with at-least-once delivery.
+///
+/// A region keeps the highest sequence number it has written for each producer, and
+/// acknowledges writes with a sequence number not higher than it without writing them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotencyKey {
+    pub producer_id: String,
+    pub sequence: u64,
+}
+
+impl IdempotencyKey {
+    /// Returns the key in the query context of the request header, or `None` if the
+    /// request doesn't carry one.
+    pub fn from_header(header: Option<&RegionRequestHeader>) -> Result<Option<Self>> {
+        match header.and_then(|header| header.query_context.as_ref()) {
+            Some(ctx) => Self::from_extensions(&ctx.extensions),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the key in the query context extensions, or `None` if there is no
+    /// [PRODUCER_ID_KEY].
+    pub fn from_extensions(extensions: &HashMap<String, String>) -> Result<Option<Self>> {
+        let Some(producer_id) = extensions.get(PRODUCER_ID_KEY) else {
+            ensure!(
+                !extensions.contains_key(PRODUCER_SEQUENCE_KEY),
+                InvalidRawRegionRequestSnafu {
+                    err: format!("`{PRODUCER_SEQUENCE_KEY}` requires `{PRODUCER_ID_KEY}`"),
+                }
+            );
+            return Ok(None);
+        };
+        ensure!(
+            !producer_id.is_empty(),
+            InvalidRawRegionRequestSnafu {
+                err: format!("`{PRODUCER_ID_KEY}` must not be empty"),
+            }
+        );
+        let sequence = extensions.get(PRODUCER_SEQUENCE_KEY).with_context(|| {
+            InvalidRawRegionRequestSnafu {
+                err: format!("`{PRODUCER_ID_KEY}` requires `{PRODUCER_SEQUENCE_KEY}`"),
+            }
+        })?;
+        let sequence = sequence
+            .parse()
+            .ok()
+            .with_context(|| InvalidRawRegionRequestSnafu {
+                err: format!("invalid `{PRODUCER_SEQUENCE_KEY}`: {sequence}"),
+            })?;
+
+        Ok(Some(Self {
+            producer_id: producer_id.clone(),
+            sequence,
+        }))
+    }
 }
 
 #[derive(Debug)]
@@ -967,11 +1039,17 @@ impl From<v1::ModifyColumnType> for ModifyColumnType {
     }
 }
 
+/// Table option to reject writes to the table, e.g. to freeze it in maintenance
+/// windows. Only the frontend enforces it, regions ignore it.
+pub const READONLY_KEY: &str = "readonly";
+
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub enum SetRegionOption {
     Ttl(Option<TimeToLive>),
     // Modifying TwscOptions with values as (option name, new value).
     Twsc(String, String),
+    /// Sets whether the table is read-only, see [READONLY_KEY].
+    Readonly(bool),
 }
 
 impl TryFrom<&PbOption> for SetRegionOption {
@@ -992,6 +1070,10 @@ impl TryFrom<&PbOption> for SetRegionOption {
             | TWCS_MAX_INACTIVE_WINDOW_RUNS
             | TWCS_MAX_OUTPUT_FILE_SIZE
             | TWCS_TIME_WINDOW => Ok(Self::Twsc(key.to_string(), value.to_string())),
+            READONLY_KEY => value
+                .parse()
+                .map(Self::Readonly)
+                .map_err(|_| InvalidSetRegionOptionRequestSnafu { key, value }.build()),
             _ => InvalidSetRegionOptionRequestSnafu { key, value }.fail(),
         }
     }
@@ -1019,6 +1101,7 @@ impl From<&UnsetRegionOption> for SetRegionOption {
                 SetRegionOption::Twsc(unset_option.to_string(), String::new())
             }
             UnsetRegionOption::Ttl => SetRegionOption::Ttl(Default::default()),
+            UnsetRegionOption::Readonly => SetRegionOption::Readonly(false),
         }
     }
 }
@@ -1035,6 +1118,7 @@ impl TryFrom<&str> for UnsetRegionOption {
             TWCS_MAX_INACTIVE_WINDOW_RUNS => Ok(Self::TwcsMaxInactiveWindowRuns),
             TWCS_MAX_OUTPUT_FILE_SIZE => Ok(Self::TwcsMaxOutputFileSize),
             TWCS_TIME_WINDOW => Ok(Self::TwcsTimeWindow),
+            READONLY_KEY => Ok(Self::Readonly),
             _ => InvalidUnsetRegionOptionRequestSnafu { key }.fail(),
         }
     }
@@ -1049,6 +1133,7 @@ pub enum UnsetRegionOption {
     TwcsMaxOutputFileSize,
     TwcsTimeWindow,
     Ttl,
+    Readonly,
 }
 
 impl UnsetRegionOption {
@@ -1061,6 +1146,7 @@ impl UnsetRegionOption {
             Self::TwcsMaxInactiveWindowRuns => TWCS_MAX_INACTIVE_WINDOW_RUNS,
             Self::TwcsMaxOutputFileSize => TWCS_MAX_OUTPUT_FILE_SIZE,
             Self::TwcsTimeWindow => TWCS_TIME_WINDOW,
+            Self::Readonly => READONLY_KEY,
         }
     }
 }
@@ -1094,6 +1180,24 @@ impl Default for RegionCompactRequest {
 #[derive(Debug)]
 pub struct RegionTruncateRequest {}
 
+/// Removes the SST files whose rows are all within the time range.
+///
+/// It only edits the manifest, rows in the time range that are in memtables or in
+/// files partially covered by the range are kept. Callers should delete them by
+/// row deletes. The affected rows of the request is the number of rows in the
+/// removed files.
+#[derive(Debug, Clone)]
+pub struct RegionTruncateRangeRequest {
+    /// The time range `[start, end)` to truncate.
+    pub time_range: TimestampRange,
+}
+
+/// Builds missing indexes for existing SST files of a region.
+///
+/// Only files that lack an index file are processed, data pages are not rewritten.
+#[derive(Debug, Clone, Default)]
+pub struct RegionBuildIndexRequest {}
+
 /// Catchup region request.
 ///
 /// Makes a readonly region to catch up to leader region changes.
@@ -1128,7 +1232,9 @@ impl fmt::Display for RegionRequest {
             RegionRequest::Flush(_) => write!(f, "Flush"),
             RegionRequest::Compact(_) => write!(f, "Compact"),
             RegionRequest::Truncate(_) => write!(f, "Truncate"),
+            RegionRequest::TruncateRange(_) => write!(f, "TruncateRange"),
             RegionRequest::Catchup(_) => write!(f, "Catchup"),
+            RegionRequest::BuildIndex(_) => write!(f, "BuildIndex"),
         }
     }
 }
@@ -1620,4 +1726,48 @@ mod tests {
         metadata.schema_version = 1;
         request.validate(&metadata).unwrap();
     }
+
+    #[test]
+    fn test_idempotency_key_from_header() {
+        let header = |extensions: &[(&str, &str)]| RegionRequestHeader {
+            query_context: Some(v1::QueryContext {
+                extensions: extensions
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(None, IdempotencyKey::from_header(None).unwrap());
+        assert_eq!(
+            None,
+            IdempotencyKey::from_header(Some(&RegionRequestHeader::default())).unwrap()
+        );
+        assert_eq!(
+            None,
+            IdempotencyKey::from_header(Some(&header(&[("ttl", "7d")]))).unwrap()
+        );
+        assert_eq!(
+            Some(IdempotencyKey {
+                producer_id: "kafka-0".to_string(),
+                sequence: 42,
+            }),
+            IdempotencyKey::from_header(Some(&header(&[
+                (PRODUCER_ID_KEY, "kafka-0"),
+                (PRODUCER_SEQUENCE_KEY, "42"),
+            ])))
+            .unwrap()
+        );
+
+        for invalid in [
+            vec![(PRODUCER_ID_KEY, "kafka-0")],
+            vec![(PRODUCER_SEQUENCE_KEY, "42")],
+            vec![(PRODUCER_ID_KEY, ""), (PRODUCER_SEQUENCE_KEY, "42")],
+            vec![(PRODUCER_ID_KEY, "kafka-0"), (PRODUCER_SEQUENCE_KEY, "-1")],
+        ] {
+            assert!(IdempotencyKey::from_header(Some(&header(&invalid))).is_err());
+        }
+    }
 }
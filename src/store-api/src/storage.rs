@@ -25,5 +25,8 @@ pub use datatypes::schema::{
 };
 
 pub use self::descriptors::*;
-pub use self::requests::{ScanRequest, TimeSeriesDistribution, TimeSeriesRowSelector};
+pub use self::requests::{
+    ScanHints, ScanRequest, ScanSample, TimeSeriesDistribution, TimeSeriesRowSelector,
+    SCAN_HINTS_KEY,
+};
 pub use self::types::SequenceNumber;
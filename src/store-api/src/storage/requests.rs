@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt;
+
 use common_recordbatch::OrderOption;
 use datafusion_expr::expr::Expr;
 use strum::Display;
@@ -36,6 +38,195 @@ pub enum TimeSeriesDistribution {
     PerSeries,
 }
 
+/// Key of the query context extension that carries the [ScanHints] of a query.
+pub const SCAN_HINTS_KEY: &str = "scan_hints";
+
+/// Per-query hints to override how regions are scanned, e.g.
+/// `scan_parallelism(8), no_inverted_index, merge_mode(last_row)`.
+///
+/// They override the table and engine defaults for that query only.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct ScanHints {
+    /// Number of partitions to scan a region, overrides the target partitions of the query.
+    pub parallelism: Option<usize>,
+    /// Doesn't apply the inverted index.
+    pub no_inverted_index: bool,
+    /// Doesn't apply the fulltext index.
+    pub no_fulltext_index: bool,
+    /// Doesn't apply the bloom filter index.
+    pub no_bloom_filter: bool,
+    /// Overrides the merge mode of the region, `last_row` or `last_non_null`.
+    pub merge_mode: Option<String>,
+    /// Only reads a sample of the region.
+    pub sample: Option<ScanSample>,
+    /// Only returns rows whose sequences are greater than it, after merging duplicate rows.
+    pub newer_than: Option<SequenceNumber>,
+}
+
+/// Samples a fraction of the data pseudo-randomly.
+///
+/// The engine samples blocks of rows (e.g. row groups) instead of rows, so it is
+/// cheap but the sample is biased if rows in a block are correlated.
+#[derive(Clone, Copy, Debug)]
+pub struct ScanSample {
+    /// Fraction of the data to read, in `(0, 1]`.
+    pub fraction: f64,
+    /// Seed to pick the blocks, the same seed always picks the same blocks.
+    pub seed: u64,
+}
+
+impl PartialEq for ScanSample {
+    fn eq(&self, other: &Self) -> bool {
+        self.fraction.to_bits() == other.fraction.to_bits() && self.seed == other.seed
+    }
+}
+
+impl Eq for ScanSample {}
+
+impl ScanSample {
+    /// Parses `fraction` or `fraction, seed`.
+    fn parse(text: &str) -> Option<ScanSample> {
+        let (fraction, seed) = match text.split_once(',') {
+            Some((fraction, seed)) => (fraction, seed.trim().parse().ok()?),
+            None => (text, 0),
+        };
+        let fraction = fraction.trim().parse::<f64>().ok()?;
+        (fraction > 0.0 && fraction <= 1.0).then_some(ScanSample { fraction, seed })
+    }
+}
+
+impl ScanHints {
+    /// Returns true if there is no hint.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Parses hints separated by commas or whitespaces. Returns the hints and
+    /// the unknown or invalid ones.
+    ///
+    /// Supported hints:
+    /// - `scan_parallelism(n)`
+    /// - `no_inverted_index`, `no_fulltext_index`, `no_bloom_filter` and `no_index` for all of them
+    /// - `merge_mode(last_row)` or `merge_mode(last_non_null)`
+    /// - `read_latest`, same as `merge_mode(last_row)`
+    /// - `sample(fraction)` or `sample(fraction, seed)`
+    /// - `newer_than(sequence)`
+    pub fn parse(text: &str) -> (ScanHints, Vec<String>) {
+        let mut hints = ScanHints::default();
+        let mut unknown = Vec::new();
+        for hint in split_hints(text) {
+            let (name, arg) = match hint.split_once('(') {
+                Some((name, arg)) => (name.trim(), arg.strip_suffix(')').map(str::trim)),
+                None => (hint, None),
+            };
+            let known = match (name.to_ascii_lowercase().as_str(), arg) {
+                ("scan_parallelism", Some(arg)) => match arg.parse::<usize>() {
+                    Ok(n) if n > 0 => {
+                        hints.parallelism = Some(n);
+                        true
+                    }
+                    _ => false,
+                },
+                ("no_inverted_index", None) => {
+                    hints.no_inverted_index = true;
+                    true
+                }
+                ("no_fulltext_index", None) => {
+                    hints.no_fulltext_index = true;
+                    true
+                }
+                ("no_bloom_filter", None) => {
+                    hints.no_bloom_filter = true;
+                    true
+                }
+                ("no_index", None) => {
+                    hints.no_inverted_index = true;
+                    hints.no_fulltext_index = true;
+                    hints.no_bloom_filter = true;
+                    true
+                }
+                ("merge_mode", Some(mode @ ("last_row" | "last_non_null"))) => {
+                    hints.merge_mode = Some(mode.to_string());
+                    true
+                }
+                ("read_latest", None) => {
+                    hints.merge_mode = Some("last_row".to_string());
+                    true
+                }
+                ("sample", Some(arg)) => match ScanSample::parse(arg) {
+                    Some(sample) => {
+                        hints.sample = Some(sample);
+                        true
+                    }
+                    None => false,
+                },
+                ("newer_than", Some(arg)) => match arg.parse::<SequenceNumber>() {
+                    Ok(sequence) => {
+                        hints.newer_than = Some(sequence);
+                        true
+                    }
+                    Err(_) => false,
+                },
+                _ => false,
+            };
+            if !known {
+                unknown.push(hint.to_string());
+            }
+        }
+
+        (hints, unknown)
+    }
+}
+
+/// Splits hints by commas and whitespaces outside parentheses.
+fn split_hints(text: &str) -> Vec<&str> {
+    let mut hints = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if depth == 0 && (c == ',' || c.is_whitespace()) => {
+                hints.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    hints.push(&text[start..]);
+
+    hints.into_iter().filter(|hint| !hint.is_empty()).collect()
+}
+
+impl fmt::Display for ScanHints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut hints = Vec::new();
+        if let Some(parallelism) = self.parallelism {
+            hints.push(format!("scan_parallelism({parallelism})"));
+        }
+        if self.no_inverted_index {
+            hints.push("no_inverted_index".to_string());
+        }
+        if self.no_fulltext_index {
+            hints.push("no_fulltext_index".to_string());
+        }
+        if self.no_bloom_filter {
+            hints.push("no_bloom_filter".to_string());
+        }
+        if let Some(merge_mode) = &self.merge_mode {
+            hints.push(format!("merge_mode({merge_mode})"));
+        }
+        if let Some(sample) = &self.sample {
+            hints.push(format!("sample({}, {})", sample.fraction, sample.seed));
+        }
+        if let Some(sequence) = self.newer_than {
+            hints.push(format!("newer_than({sequence})"));
+        }
+        write!(f, "{}", hints.join(", "))
+    }
+}
+
 #[derive(Default, Clone, Debug, PartialEq, Eq)]
 pub struct ScanRequest {
     /// Indices of columns to read, `None` to read all columns. This indices is
@@ -58,4 +249,80 @@ pub struct ScanRequest {
     pub sequence: Option<SequenceNumber>,
     /// Optional hint for the distribution of time-series data.
     pub distribution: Option<TimeSeriesDistribution>,
+    /// Per-query hints to override how the region is scanned.
+    pub scan_hints: ScanHints,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scan_hints() {
+        let (hints, unknown) =
+            ScanHints::parse("scan_parallelism(8), no_inverted_index read_latest,, foo, bar(1)");
+        assert_eq!(
+            ScanHints {
+                parallelism: Some(8),
+                no_inverted_index: true,
+                merge_mode: Some("last_row".to_string()),
+                ..Default::default()
+            },
+            hints
+        );
+        assert_eq!(vec!["foo", "bar(1)"], unknown);
+        assert_eq!(
+            "scan_parallelism(8), no_inverted_index, merge_mode(last_row)",
+            hints.to_string()
+        );
+        // Round trip.
+        assert_eq!(
+            (hints.clone(), vec![]),
+            ScanHints::parse(&hints.to_string())
+        );
+
+        let (hints, unknown) = ScanHints::parse(
+            "no_index merge_mode(last_non_null) scan_parallelism(0) merge_mode(first_row)",
+        );
+        assert_eq!(
+            ScanHints {
+                no_inverted_index: true,
+                no_fulltext_index: true,
+                no_bloom_filter: true,
+                merge_mode: Some("last_non_null".to_string()),
+                ..Default::default()
+            },
+            hints
+        );
+        assert_eq!(
+            vec!["scan_parallelism(0)", "merge_mode(first_row)"],
+            unknown
+        );
+
+        let (hints, unknown) =
+            ScanHints::parse("sample(0.001) sample(0.5, 42) sample(0) sample(2)");
+        assert_eq!(
+            Some(ScanSample {
+                fraction: 0.5,
+                seed: 42,
+            }),
+            hints.sample
+        );
+        assert_eq!(vec!["sample(0)", "sample(2)"], unknown);
+        assert_eq!("sample(0.5, 42)", hints.to_string());
+        let (hints, _) = ScanHints::parse("sample(0.001)");
+        assert_eq!(
+            (hints.clone(), vec![]),
+            ScanHints::parse(&hints.to_string())
+        );
+
+        let (hints, unknown) = ScanHints::parse("newer_than(42) newer_than(-1)");
+        assert_eq!(Some(42), hints.newer_than);
+        assert_eq!(vec!["newer_than(-1)"], unknown);
+        assert_eq!("newer_than(42)", hints.to_string());
+
+        let (hints, unknown) = ScanHints::parse("  ");
+        assert!(hints.is_empty());
+        assert!(unknown.is_empty());
+    }
 }
@@ -36,7 +36,7 @@ use store_api::storage::{ColumnDescriptor, ColumnDescriptorBuilder, ColumnId, Re
 use crate::error::{self, Result};
 use crate::requests::{
     AddColumnRequest, AlterKind, ModifyColumnTypeRequest, SetIndexOptions, TableOptions,
-    UnsetIndexOptions,
+    UnsetIndexOptions, READONLY_KEY,
 };
 
 pub type TableId = u32;
@@ -291,6 +291,15 @@ impl TableMeta {
                         new_options.extra_options.remove(key.as_str());
                     }
                 }
+                SetRegionOption::Readonly(readonly) => {
+                    if *readonly {
+                        new_options
+                            .extra_options
+                            .insert(READONLY_KEY.to_string(), true.to_string());
+                    } else {
+                        new_options.extra_options.remove(READONLY_KEY);
+                    }
+                }
             }
         }
         let mut builder = self.new_meta_builder();
@@ -28,6 +28,7 @@ use datatypes::prelude::VectorRef;
 use datatypes::schema::{ColumnSchema, FulltextOptions, SkippingIndexOptions};
 use greptime_proto::v1::region::compact_request;
 use serde::{Deserialize, Serialize};
+use snafu::OptionExt;
 use store_api::metric_engine_consts::{
     is_metric_engine_option_key, LOGICAL_TABLE_METADATA_KEY, PHYSICAL_TABLE_METADATA_KEY,
 };
@@ -46,13 +47,17 @@ pub const FILE_TABLE_FORMAT_KEY: &str = "format";
 pub const TABLE_DATA_MODEL: &str = "table_data_model";
 pub const TABLE_DATA_MODEL_TRACE_V1: &str = "greptime_trace_v1";
 
-pub const VALID_TABLE_OPTION_KEYS: [&str; 11] = [
+pub const VALID_TABLE_OPTION_KEYS: [&str; 15] = [
     // common keys:
     WRITE_BUFFER_SIZE_KEY,
     TTL_KEY,
     STORAGE_KEY,
     COMMENT_KEY,
     SKIP_WAL_KEY,
+    SCAN_DEFAULT_FILTER_KEY,
+    READONLY_KEY,
+    WRITE_TIME_BOUNDS_KEY,
+    WRITE_TIME_BOUNDS_MODE_KEY,
     // file engine keys:
     FILE_TABLE_LOCATION_KEY,
     FILE_TABLE_FORMAT_KEY,
@@ -100,6 +105,17 @@ pub const STORAGE_KEY: &str = "storage";
 pub const COMMENT_KEY: &str = "comment";
 pub const AUTO_CREATE_TABLE_KEY: &str = "auto_create_table";
 pub const SKIP_WAL_KEY: &str = store_api::mito_engine_options::SKIP_WAL_KEY;
+/// A filter expression in SQL that is applied to every scan of the table, e.g. `deleted = false`.
+pub const SCAN_DEFAULT_FILTER_KEY: &str = "scan.default_filter";
+pub const READONLY_KEY: &str = store_api::region_request::READONLY_KEY;
+/// Table option set while the table is repartitioned. Deletes are rejected as they
+/// can't be caught up by the new regions, other writes are still accepted.
+pub const REPARTITIONING_KEY: &str = "repartitioning";
+/// Bounds of the timestamps accepted by writes, relative to the time of writing,
+/// e.g. `now-30d..now+1h`. Either side may be omitted, e.g. `..now+1h`.
+pub const WRITE_TIME_BOUNDS_KEY: &str = "write.time_bounds";
+/// How writes handle rows out of the [WRITE_TIME_BOUNDS_KEY], see [WriteTimeBoundsMode].
+pub const WRITE_TIME_BOUNDS_MODE_KEY: &str = "write.time_bounds_mode";
 
 impl TableOptions {
     pub fn try_from_iter<T: ToString, U: IntoIterator<Item = (T, T)>>(
@@ -144,6 +160,30 @@ impl TableOptions {
             })?;
         }
 
+        if let Some(readonly) = kvs.get(READONLY_KEY) {
+            let _ = readonly.parse::<bool>().map_err(|_| {
+                ParseTableOptionSnafu {
+                    key: READONLY_KEY,
+                    value: readonly,
+                }
+                .build()
+            })?;
+        }
+
+        if let Some(bounds) = kvs.get(WRITE_TIME_BOUNDS_KEY) {
+            let _ = WriteTimeBounds::parse(bounds).with_context(|| ParseTableOptionSnafu {
+                key: WRITE_TIME_BOUNDS_KEY,
+                value: bounds,
+            })?;
+        }
+
+        if let Some(mode) = kvs.get(WRITE_TIME_BOUNDS_MODE_KEY) {
+            let _ = WriteTimeBoundsMode::parse(mode).with_context(|| ParseTableOptionSnafu {
+                key: WRITE_TIME_BOUNDS_MODE_KEY,
+                value: mode,
+            })?;
+        }
+
         options.extra_options = HashMap::from_iter(
             kvs.into_iter()
                 .filter(|(k, _)| k != WRITE_BUFFER_SIZE_KEY && k != TTL_KEY),
@@ -153,6 +193,109 @@ impl TableOptions {
     }
 }
 
+impl TableOptions {
+    /// Returns true if writes to the table are rejected, see [READONLY_KEY].
+    pub fn readonly(&self) -> bool {
+        self.extra_options
+            .get(READONLY_KEY)
+            .is_some_and(|readonly| readonly == "true")
+    }
+
+    /// Returns true if the table is being repartitioned, see [REPARTITIONING_KEY].
+    pub fn repartitioning(&self) -> bool {
+        self.extra_options
+            .get(REPARTITIONING_KEY)
+            .is_some_and(|repartitioning| repartitioning == "true")
+    }
+
+    /// Returns the bounds of the timestamps accepted by writes and how to handle
+    /// rows out of them, or `None` if writes accept any timestamp.
+    pub fn write_time_bounds(&self) -> Option<(WriteTimeBounds, WriteTimeBoundsMode)> {
+        let bounds = WriteTimeBounds::parse(self.extra_options.get(WRITE_TIME_BOUNDS_KEY)?)?;
+        let mode = self
+            .extra_options
+            .get(WRITE_TIME_BOUNDS_MODE_KEY)
+            .and_then(|mode| WriteTimeBoundsMode::parse(mode))
+            .unwrap_or_default();
+        Some((bounds, mode))
+    }
+}
+
+/// Bounds of the timestamps accepted by writes, as offsets to the time of writing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteTimeBounds {
+    /// Offset of the inclusive lower bound, `None` if unbounded.
+    pub lower: Option<chrono::Duration>,
+    /// Offset of the inclusive upper bound, `None` if unbounded.
+    pub upper: Option<chrono::Duration>,
+}
+
+impl WriteTimeBounds {
+    /// Parses bounds like `now-30d..now+1h`, returns `None` if it's invalid.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (lower, upper) = s.split_once("..")?;
+        let parse_bound = |bound: &str| {
+            let bound = bound.trim();
+            if bound.is_empty() {
+                return Some(None);
+            }
+            let offset = bound.strip_prefix("now")?.trim();
+            if offset.is_empty() {
+                return Some(Some(chrono::Duration::zero()));
+            }
+            let (negative, duration) = match offset.strip_prefix('-') {
+                Some(duration) => (true, duration),
+                None => (false, offset.strip_prefix('+')?),
+            };
+            let duration = humantime::parse_duration(duration.trim()).ok()?;
+            let duration = chrono::Duration::from_std(duration).ok()?;
+            Some(Some(if negative { -duration } else { duration }))
+        };
+        let bounds = Self {
+            lower: parse_bound(lower)?,
+            upper: parse_bound(upper)?,
+        };
+        if let (Some(lower), Some(upper)) = (bounds.lower, bounds.upper) {
+            if lower > upper {
+                return None;
+            }
+        }
+        Some(bounds)
+    }
+
+    /// Returns the inclusive bounds in milliseconds when writing at `now_millis`.
+    pub fn millis_range(&self, now_millis: i64) -> (Option<i64>, Option<i64>) {
+        let bound = |offset: Option<chrono::Duration>| {
+            offset.map(|offset| now_millis.saturating_add(offset.num_milliseconds()))
+        };
+        (bound(self.lower), bound(self.upper))
+    }
+}
+
+/// How writes handle rows out of the [WriteTimeBounds] of a table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WriteTimeBoundsMode {
+    /// Drops the rows and reports them to the client.
+    #[default]
+    Reject,
+    /// Moves the timestamps of the rows to the nearest bound.
+    Clamp,
+    /// Writes the rows as is and only logs them.
+    Warn,
+}
+
+impl WriteTimeBoundsMode {
+    /// Parses the mode case-insensitively, returns `None` if it's unknown.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "reject" => Some(Self::Reject),
+            "clamp" => Some(Self::Clamp),
+            "warn" => Some(Self::Warn),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for TableOptions {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut key_vals = vec![];
@@ -393,9 +536,55 @@ mod tests {
         assert!(validate_table_option(TTL_KEY));
         assert!(validate_table_option(WRITE_BUFFER_SIZE_KEY));
         assert!(validate_table_option(STORAGE_KEY));
+        assert!(validate_table_option(SCAN_DEFAULT_FILTER_KEY));
+        assert!(validate_table_option(READONLY_KEY));
         assert!(!validate_table_option("foo"));
     }
 
+    #[test]
+    fn test_write_time_bounds_options() {
+        let options = TableOptions::try_from_iter([
+            (WRITE_TIME_BOUNDS_KEY, "now-30d..now+1h"),
+            (WRITE_TIME_BOUNDS_MODE_KEY, "clamp"),
+        ])
+        .unwrap();
+        let (bounds, mode) = options.write_time_bounds().unwrap();
+        assert_eq!(WriteTimeBoundsMode::Clamp, mode);
+        assert_eq!(
+            (
+                Some(1_000_000 - 30 * 86_400_000),
+                Some(1_000_000 + 3_600_000)
+            ),
+            bounds.millis_range(1_000_000)
+        );
+
+        let options = TableOptions::try_from_iter([(WRITE_TIME_BOUNDS_KEY, "..now")]).unwrap();
+        let (bounds, mode) = options.write_time_bounds().unwrap();
+        assert_eq!(WriteTimeBoundsMode::Reject, mode);
+        assert_eq!((None, Some(1_000)), bounds.millis_range(1_000));
+        assert!(TableOptions::default().write_time_bounds().is_none());
+
+        for bounds in ["now-30d", "now*1h..", "now+1h..now-1h", "yesterday..now"] {
+            TableOptions::try_from_iter([(WRITE_TIME_BOUNDS_KEY, bounds)]).unwrap_err();
+        }
+        TableOptions::try_from_iter([(WRITE_TIME_BOUNDS_MODE_KEY, "drop")]).unwrap_err();
+    }
+
+    #[test]
+    fn test_readonly_table_options() {
+        let options = TableOptions::try_from_iter([(READONLY_KEY, "true")]).unwrap();
+        assert!(options.readonly());
+        let options = TableOptions::try_from_iter([(READONLY_KEY, "false")]).unwrap();
+        assert!(!options.readonly());
+        assert!(!TableOptions::default().readonly());
+        assert!(TableOptions::try_from_iter([(READONLY_KEY, "yes")]).is_err());
+
+        let options = TableOptions::try_from_iter([(REPARTITIONING_KEY, "true")]).unwrap();
+        assert!(options.repartitioning());
+        assert!(!options.readonly());
+        assert!(!TableOptions::default().repartitioning());
+    }
+
     #[test]
     fn test_serialize_table_options() {
         let options = TableOptions {
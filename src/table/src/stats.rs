@@ -12,6 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use datafusion_common::stats::Precision;
+use datafusion_common::{ColumnStatistics as DfColumnStatistics, Statistics};
+use datatypes::schema::Schema;
 use datatypes::value::Value;
 
 /// Statistics for a relation.
@@ -46,3 +49,53 @@ pub struct ColumnStatistics {
     pub distinct_count: Option<usize>,
     // TODO(discord9): histogram of values
 }
+
+impl TableStatistics {
+    /// Converts the statistics into DataFusion's [Statistics] of the table `schema`.
+    pub fn to_df_statistics(&self, schema: &Schema) -> Statistics {
+        let precision = |value: Option<usize>| match value {
+            Some(value) if self.is_exact => Precision::Exact(value),
+            Some(value) => Precision::Inexact(value),
+            None => Precision::Absent,
+        };
+        let column_statistics = schema
+            .column_schemas()
+            .iter()
+            .enumerate()
+            .map(|(idx, column_schema)| {
+                let Some(stats) = self
+                    .column_statistics
+                    .as_ref()
+                    .and_then(|column_statistics| column_statistics.get(idx))
+                else {
+                    return DfColumnStatistics::new_unknown();
+                };
+                let scalar = |value: &Option<Value>| {
+                    value
+                        .as_ref()
+                        .and_then(|value| value.try_to_scalar_value(&column_schema.data_type).ok())
+                        .map(|value| {
+                            if self.is_exact {
+                                Precision::Exact(value)
+                            } else {
+                                Precision::Inexact(value)
+                            }
+                        })
+                        .unwrap_or_default()
+                };
+                let mut column = DfColumnStatistics::new_unknown();
+                column.null_count = precision(stats.null_count);
+                column.max_value = scalar(&stats.max_value);
+                column.min_value = scalar(&stats.min_value);
+                column.distinct_count = precision(stats.distinct_count);
+                column
+            })
+            .collect();
+
+        Statistics {
+            num_rows: precision(self.num_rows),
+            total_byte_size: precision(self.total_byte_size),
+            column_statistics,
+        }
+    }
+}
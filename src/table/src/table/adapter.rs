@@ -22,6 +22,7 @@ use datafusion::catalog::Session;
 use datafusion::datasource::{TableProvider, TableType as DfTableType};
 use datafusion::error::Result as DfResult;
 use datafusion::physical_plan::ExecutionPlan;
+use datafusion_common::Statistics;
 use datafusion_expr::expr::Expr;
 use datafusion_expr::TableProviderFilterPushDown as DfTableProviderFilterPushDown;
 use datafusion_physical_expr::expressions::Column;
@@ -29,11 +30,14 @@ use datafusion_physical_expr::PhysicalSortExpr;
 use store_api::storage::ScanRequest;
 
 use crate::table::{TableRef, TableType};
+use crate::TableStatistics;
 
 /// Adapt greptime's [TableRef] to DataFusion's [TableProvider].
 pub struct DfTableProviderAdapter {
     table: TableRef,
     scan_req: Arc<Mutex<ScanRequest>>,
+    /// The estimated statistics of the table, for the optimizer.
+    statistics: Option<TableStatistics>,
 }
 
 impl DfTableProviderAdapter {
@@ -41,9 +45,16 @@ impl DfTableProviderAdapter {
         Self {
             table,
             scan_req: Arc::default(),
+            statistics: None,
         }
     }
 
+    /// Attaches the statistics of the table.
+    pub fn with_statistics(mut self, statistics: TableStatistics) -> Self {
+        self.statistics = Some(statistics);
+        self
+    }
+
     pub fn table(&self) -> TableRef {
         self.table.clone()
     }
@@ -88,6 +99,12 @@ impl TableProvider for DfTableProviderAdapter {
         self.table.get_column_default(column)
     }
 
+    fn statistics(&self) -> Option<Statistics> {
+        self.statistics
+            .as_ref()
+            .map(|statistics| statistics.to_df_statistics(&self.table.schema()))
+    }
+
     async fn scan(
         &self,
         _state: &dyn Session,
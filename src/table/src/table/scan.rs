@@ -59,6 +59,10 @@ pub struct RegionScanExec {
     is_partition_set: bool,
     // TODO(ruihang): handle TimeWindowed dist via this parameter
     distribution: Option<TimeSeriesDistribution>,
+    /// Number of partitions to scan the region, overrides the target partitions.
+    parallelism: Option<usize>,
+    /// Fraction of the region to sample.
+    sample_fraction: Option<f64>,
 }
 
 impl RegionScanExec {
@@ -150,6 +154,8 @@ impl RegionScanExec {
             total_rows,
             is_partition_set: false,
             distribution: request.distribution,
+            parallelism: request.scan_hints.parallelism,
+            sample_fraction: request.scan_hints.sample.map(|sample| sample.fraction),
         })
     }
 
@@ -211,6 +217,8 @@ impl RegionScanExec {
             total_rows: self.total_rows,
             is_partition_set: true,
             distribution: self.distribution,
+            parallelism: self.parallelism,
+            sample_fraction: self.sample_fraction,
         })
     }
 
@@ -218,6 +226,11 @@ impl RegionScanExec {
         self.distribution
     }
 
+    /// Returns the number of partitions to scan the region hinted by the query.
+    pub fn parallelism(&self) -> Option<usize> {
+        self.parallelism
+    }
+
     pub fn with_distinguish_partition_range(&self, distinguish_partition_range: bool) {
         let mut scanner = self.scanner.lock().unwrap();
         // set distinguish_partition_range won't fail
@@ -301,7 +314,16 @@ impl ExecutionPlan for RegionScanExec {
     }
 
     fn statistics(&self) -> DfResult<Statistics> {
-        let statistics = if self.append_mode && !self.scanner.lock().unwrap().has_predicate() {
+        let statistics = if let Some(fraction) = self.sample_fraction {
+            // The number of sampled rows is only an estimation. It mustn't be exact
+            // so the planner won't answer queries like `count(*)` by statistics.
+            let mut statistics = Statistics::new_unknown(&self.arrow_schema);
+            if self.append_mode && !self.scanner.lock().unwrap().has_predicate() {
+                statistics.num_rows =
+                    Precision::Inexact((self.total_rows as f64 * fraction).ceil() as usize);
+            }
+            statistics
+        } else if self.append_mode && !self.scanner.lock().unwrap().has_predicate() {
             let column_statistics = self
                 .arrow_schema
                 .fields
@@ -64,12 +64,51 @@ impl MemTable {
         catalog_name: String,
         schema_name: String,
         regions: Vec<RegionNumber>,
+    ) -> TableRef {
+        Self::new_with_primary_keys(
+            table_name,
+            recordbatch,
+            table_id,
+            catalog_name,
+            schema_name,
+            regions,
+            vec![],
+        )
+    }
+
+    /// Creates a table in the default catalog and schema whose primary key consists
+    /// of the columns at `primary_key_indices`, e.g. the labels of a PromQL metric.
+    pub fn table_with_primary_keys(
+        table_name: impl Into<String>,
+        recordbatch: RecordBatch,
+        table_id: TableId,
+        primary_key_indices: Vec<usize>,
+    ) -> TableRef {
+        Self::new_with_primary_keys(
+            table_name,
+            recordbatch,
+            table_id,
+            DEFAULT_CATALOG_NAME.to_string(),
+            DEFAULT_SCHEMA_NAME.to_string(),
+            vec![0],
+            primary_key_indices,
+        )
+    }
+
+    fn new_with_primary_keys(
+        table_name: impl Into<String>,
+        recordbatch: RecordBatch,
+        table_id: TableId,
+        catalog_name: String,
+        schema_name: String,
+        regions: Vec<RegionNumber>,
+        primary_key_indices: Vec<usize>,
     ) -> TableRef {
         let schema = recordbatch.schema.clone();
 
         let meta = TableMetaBuilder::empty()
             .schema(schema)
-            .primary_key_indices(vec![])
+            .primary_key_indices(primary_key_indices)
             .value_indices(vec![])
             .engine("mito".to_string())
             .next_column_id(0)
@@ -17,13 +17,15 @@ mod test {
     use std::sync::Arc;
 
     use client::OutputData;
+    use common_catalog::consts::DEFAULT_CATALOG_NAME;
     use common_recordbatch::RecordBatches;
+    use frontend::instance::Instance;
     use rstest::rstest;
     use rstest_reuse::apply;
     use servers::influxdb::InfluxdbRequest;
     use servers::query_handler::sql::SqlQueryHandler;
     use servers::query_handler::InfluxdbLineProtocolHandler;
-    use session::context::QueryContext;
+    use session::context::{QueryContext, QueryContextRef};
 
     use crate::tests::test_util::{both_instances_cases, distributed, standalone, MockInstance};
 
@@ -125,4 +127,78 @@ monitor1,host=host2 cpu=32 1663840496400340001";
 +-------------------------------+-------+------+--------+"
         );
     }
+
+    async fn show_create_table(instance: &Instance, table: &str, ctx: QueryContextRef) -> String {
+        let output = instance
+            .do_query(&format!("SHOW CREATE TABLE {table}"), ctx)
+            .await
+            .remove(0)
+            .unwrap();
+        let OutputData::RecordBatches(recordbatches) = output.data else {
+            unreachable!()
+        };
+        recordbatches.pretty_print().unwrap()
+    }
+
+    #[apply(both_instances_cases)]
+    async fn test_put_influxdb_lines_with_table_defaults(instance: Arc<dyn MockInstance>) {
+        let instance = instance.frontend();
+
+        for sql in [
+            "CREATE DATABASE logs WITH ('table_defaults.append_mode'='true', 'table_defaults.ttl'='7d')",
+            "CREATE TABLE logs.monitor2 (host STRING, cpu DOUBLE, ts TIMESTAMP TIME INDEX, PRIMARY KEY(host)) WITH (ttl='1d')",
+        ] {
+            instance
+                .do_query(sql, QueryContext::arc())
+                .await
+                .remove(0)
+                .unwrap();
+        }
+
+        let ctx = Arc::new(QueryContext::with(DEFAULT_CATALOG_NAME, "logs"));
+        let lines = r"
+monitor1,host=host1 cpu=66.6,memory=1024 1663840496100023100
+monitor1,host=host1 cpu=66.6,memory=1024 1663840496100023100";
+        let request = InfluxdbRequest {
+            precision: None,
+            lines: lines.to_string(),
+        };
+        instance.exec(request, ctx.clone()).await.unwrap();
+
+        // The auto created table inherits the table defaults of the database.
+        let create_table = show_create_table(&instance, "monitor1", ctx.clone()).await;
+        assert!(
+            create_table.contains("append_mode = 'true'"),
+            "{create_table}"
+        );
+        assert!(create_table.contains("ttl = '7days'"), "{create_table}");
+
+        // The duplicated rows are kept in append mode.
+        let output = instance
+            .do_query("SELECT count(*) FROM monitor1", ctx.clone())
+            .await
+            .remove(0)
+            .unwrap();
+        let OutputData::Stream(stream) = output.data else {
+            unreachable!()
+        };
+        let recordbatches = RecordBatches::try_collect(stream).await.unwrap();
+        assert_eq!(
+            recordbatches.pretty_print().unwrap(),
+            "\
++----------+
+| count(*) |
++----------+
+| 2        |
++----------+"
+        );
+
+        // The options of the table override the table defaults.
+        let create_table = show_create_table(&instance, "monitor2", ctx).await;
+        assert!(
+            create_table.contains("append_mode = 'true'"),
+            "{create_table}"
+        );
+        assert!(create_table.contains("ttl = '1day'"), "{create_table}");
+    }
 }
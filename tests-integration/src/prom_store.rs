@@ -18,10 +18,14 @@ mod tests {
 
     use api::prom_store::remote::label_matcher::Type as MatcherType;
     use api::prom_store::remote::{
-        Label, LabelMatcher, Query, ReadRequest, ReadResponse, Sample, WriteRequest,
+        Label, LabelMatcher, Query, ReadRequest, ReadResponse, Sample, TimeSeries, WriteRequest,
     };
+    use catalog::schema_limits::SchemaLimitOptions;
     use common_catalog::consts::DEFAULT_CATALOG_NAME;
+    use common_error::ext::ErrorExt;
+    use common_test_util::recordbatch::check_output_stream;
     use frontend::instance::Instance;
+    use futures::future;
     use prost::Message;
     use servers::http::prom_store::PHYSICAL_TABLE_PARAM;
     use servers::prom_store;
@@ -83,6 +87,192 @@ mod tests {
         .await;
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_standalone_prom_store_concurrent_new_labels() {
+        common_telemetry::init_default_ut_logging();
+        let standalone =
+            GreptimeDbStandaloneBuilder::new("test_standalone_prom_store_concurrent_new_labels")
+                .build()
+                .await;
+        let instance = standalone.fe_instance();
+        let ctx = Arc::new(QueryContext::with(DEFAULT_CATALOG_NAME, "public"));
+
+        let new_write_request = |labels: Vec<Label>, timestamp: i64| WriteRequest {
+            timeseries: vec![TimeSeries {
+                labels: [
+                    vec![Label {
+                        name: prom_store::METRIC_NAME_LABEL.to_string(),
+                        value: "concurrent_metric".to_string(),
+                    }],
+                    labels,
+                ]
+                .concat(),
+                samples: vec![Sample {
+                    value: 1.0,
+                    timestamp,
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // Creates the table first.
+        let (row_inserts, _) = to_grpc_row_insert_requests(&new_write_request(vec![], 0)).unwrap();
+        instance
+            .write(row_inserts, ctx.clone(), true)
+            .await
+            .unwrap();
+
+        let submitted = operator::metrics::ALTER_ON_DEMAND_SUBMITTED.get();
+        let writes = (0..50).map(|i| {
+            let write_request = new_write_request(
+                vec![Label {
+                    name: format!("label_{i}"),
+                    value: format!("value_{i}"),
+                }],
+                1000 + i,
+            );
+            let instance = instance.clone();
+            let ctx = ctx.clone();
+            tokio::spawn(async move {
+                let (row_inserts, _) = to_grpc_row_insert_requests(&write_request).unwrap();
+                instance.write(row_inserts, ctx, true).await
+            })
+        });
+        for result in future::join_all(writes).await {
+            result.unwrap().unwrap();
+        }
+        let submitted = operator::metrics::ALTER_ON_DEMAND_SUBMITTED.get() - submitted;
+        assert!(submitted < 25, "submitted {submitted} alters");
+
+        let output = instance
+            .do_query("SELECT count(*) FROM concurrent_metric", ctx)
+            .await
+            .remove(0)
+            .unwrap();
+        let expected = "\
++----------+
+| count(*) |
++----------+
+| 51       |
++----------+";
+        check_output_stream(output.data, expected).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_standalone_prom_store_schema_limits() {
+        common_telemetry::init_default_ut_logging();
+        let standalone =
+            GreptimeDbStandaloneBuilder::new("test_standalone_prom_store_schema_limits")
+                .with_schema_limits(SchemaLimitOptions {
+                    max_columns_per_table: 8,
+                    max_tables_per_schema: 3,
+                    max_tag_cardinality_hint: 0,
+                })
+                .build()
+                .await;
+        let instance = standalone.fe_instance();
+        instance
+            .do_query("CREATE DATABASE limits", QueryContext::arc())
+            .await
+            .remove(0)
+            .unwrap();
+        let ctx = Arc::new(QueryContext::with(DEFAULT_CATALOG_NAME, "limits"));
+
+        let write_ctx = ctx.clone();
+        let write = move |metric: &str, labels: &[&str]| {
+            let write_request = WriteRequest {
+                timeseries: vec![TimeSeries {
+                    labels: [prom_store::METRIC_NAME_LABEL]
+                        .iter()
+                        .chain(labels)
+                        .map(|name| Label {
+                            name: name.to_string(),
+                            value: if *name == prom_store::METRIC_NAME_LABEL {
+                                metric.to_string()
+                            } else {
+                                "value".to_string()
+                            },
+                        })
+                        .collect(),
+                    samples: vec![Sample {
+                        value: 1.0,
+                        timestamp: 1000,
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            };
+            let (row_inserts, _) = to_grpc_row_insert_requests(&write_request).unwrap();
+            instance.write(row_inserts, write_ctx.clone(), true)
+        };
+
+        // Creates the physical table and two logical tables with 6 columns in the
+        // physical table, including the internal columns.
+        write("metric_a", &["host"]).await.unwrap();
+        write("metric_b", &["job"]).await.unwrap();
+
+        let err = write("metric_c", &["host"]).await.unwrap_err();
+        assert_eq!(
+            "Schema `limits` would have 4 tables, exceeding the limit `max_tables_per_schema` = 3",
+            err.output_msg()
+        );
+
+        let err = write("metric_a", &["host", "a", "b", "c"])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            "Table `greptime.limits.greptime_physical_table` would have 9 columns, exceeding the limit `max_columns_per_table` = 8",
+            err.output_msg()
+        );
+
+        let err = write("metric_b", &["job", "__tsid"]).await.unwrap_err();
+        assert_eq!(
+            "Column name `__tsid` of table `metric_b` is reserved, names starting with `__` are used by internal columns",
+            err.output_msg()
+        );
+
+        // The existing tables still accept writes within the limits.
+        write("metric_a", &["host", "a"]).await.unwrap();
+        let output = instance
+            .do_query(
+                "SELECT count(*) FROM metric_a UNION ALL SELECT count(*) FROM metric_b",
+                ctx.clone(),
+            )
+            .await
+            .remove(0)
+            .unwrap();
+        let expected = "\
++----------+
+| count(*) |
++----------+
+| 2        |
+| 1        |
++----------+";
+        check_output_stream(output.data, expected).await;
+
+        let output = instance
+            .do_query(
+                "SELECT table_name, limit_name, current_usage FROM information_schema.schema_limits \
+                WHERE table_schema = 'limits' ORDER BY limit_name, table_name",
+                ctx,
+            )
+            .await
+            .remove(0)
+            .unwrap();
+        let expected = "\
++-------------------------+--------------------------+---------------+
+| table_name              | limit_name               | current_usage |
++-------------------------+--------------------------+---------------+
+| greptime_physical_table | max_columns_per_table    | 7             |
+| metric_a                | max_columns_per_table    | 4             |
+| metric_b                | max_columns_per_table    | 3             |
+|                         | max_tables_per_schema    | 3             |
+|                         | max_tag_cardinality_hint |               |
++-------------------------+--------------------------+---------------+";
+        check_output_stream(output.data, expected).await;
+    }
+
     async fn test_prom_store_remote_rw(instance: &Arc<Instance>, physical_table: Option<String>) {
         let write_request = WriteRequest {
             timeseries: prom_store::mock_timeseries(),
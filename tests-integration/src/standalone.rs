@@ -20,6 +20,7 @@ use cache::{
 };
 use catalog::information_schema::NoopInformationExtension;
 use catalog::kvbackend::KvBackendCatalogManager;
+use catalog::schema_limits::SchemaLimitOptions;
 use cmd::error::StartFlownodeSnafu;
 use cmd::standalone::StandaloneOptions;
 use common_base::Plugins;
@@ -46,6 +47,7 @@ use frontend::frontend::Frontend;
 use frontend::instance::builder::FrontendBuilder;
 use frontend::instance::{Instance, StandaloneDatanodeManager};
 use meta_srv::metasrv::{FLOW_ID_SEQ, TABLE_ID_SEQ};
+use partition::splitter::PartitionRowSplitter;
 use query::stats::StatementStatistics;
 use servers::grpc::GrpcOptions;
 use servers::server::ServerHandlers;
@@ -76,6 +78,7 @@ pub struct GreptimeDbStandaloneBuilder {
     store_providers: Option<Vec<StorageType>>,
     default_store: Option<StorageType>,
     plugin: Option<Plugins>,
+    schema_limits: SchemaLimitOptions,
 }
 
 impl GreptimeDbStandaloneBuilder {
@@ -87,6 +90,7 @@ impl GreptimeDbStandaloneBuilder {
             default_store: None,
             datanode_wal_config: DatanodeWalConfig::default(),
             metasrv_wal_config: MetasrvWalConfig::default(),
+            schema_limits: SchemaLimitOptions::default(),
         }
     }
 
@@ -116,6 +120,15 @@ impl GreptimeDbStandaloneBuilder {
         }
     }
 
+    #[cfg(test)]
+    #[must_use]
+    pub fn with_schema_limits(self, schema_limits: SchemaLimitOptions) -> Self {
+        Self {
+            schema_limits,
+            ..self
+        }
+    }
+
     #[must_use]
     pub fn with_datanode_wal_config(mut self, datanode_wal_config: DatanodeWalConfig) -> Self {
         self.datanode_wal_config = datanode_wal_config;
@@ -225,6 +238,7 @@ impl GreptimeDbStandaloneBuilder {
                     flow_metadata_manager,
                     flow_metadata_allocator,
                     region_failure_detector_controller: Arc::new(NoopRegionFailureDetectorControl),
+                    region_row_splitter: Arc::new(PartitionRowSplitter),
                 },
                 procedure_manager.clone(),
                 register_procedure_loaders,
@@ -313,6 +327,7 @@ impl GreptimeDbStandaloneBuilder {
             metadata_store: kv_backend_config,
             wal: self.metasrv_wal_config.clone().into(),
             grpc: GrpcOptions::default().with_server_addr("127.0.0.1:4001"),
+            schema_limits: self.schema_limits.clone(),
             ..StandaloneOptions::default()
         };
 
@@ -432,6 +432,7 @@ pub async fn setup_test_http_app_with_frontend_and_user_provider(
         .with_logs_handler(instance.fe_instance().clone())
         .with_otlp_handler(instance.fe_instance().clone())
         .with_jaeger_handler(instance.fe_instance().clone())
+        .with_opentsdb_handler(instance.fe_instance().clone())
         .with_greptime_config_options(instance.opts.to_toml().unwrap());
 
     if let Some(user_provider) = user_provider {
@@ -455,6 +456,14 @@ async fn run_sql(sql: &str, instance: &GreptimeDbStandalone) {
 pub async fn setup_test_prom_app_with_frontend(
     store_type: StorageType,
     name: &str,
+) -> (Router, TestGuard) {
+    setup_test_prom_app_with_frontend_and_user_provider(store_type, name, None).await
+}
+
+pub async fn setup_test_prom_app_with_frontend_and_user_provider(
+    store_type: StorageType,
+    name: &str,
+    user_provider: Option<UserProviderRef>,
 ) -> (Router, TestGuard) {
     std::env::set_var("TZ", "UTC");
 
@@ -484,6 +493,14 @@ pub async fn setup_test_prom_app_with_frontend(
     let sql = "INSERT INTO demo_metrics_with_nanos(idc, val, ts) VALUES ('idc1', 1.1, 0)";
     run_sql(sql, &instance).await;
 
+    // build the same metric with different labels in another catalog
+    let sql = "CREATE TABLE another_catalog.another_schema.phy (ts timestamp time index, val double, host string primary key) engine=metric with ('physical_metric_table' = '')";
+    run_sql(sql, &instance).await;
+    let sql = "CREATE TABLE another_catalog.another_schema.demo (ts timestamp time index, val double, host string, idc string, primary key (host, idc)) engine=metric with ('on_physical_table' = 'phy')";
+    run_sql(sql, &instance).await;
+    let sql = "INSERT INTO another_catalog.another_schema.demo(host, idc, val, ts) VALUES ('host3', 'idc3', 3.3, 0)";
+    run_sql(sql, &instance).await;
+
     let http_opts = HttpOptions {
         addr: format!("127.0.0.1:{}", ports::get_port()),
         ..Default::default()
@@ -495,8 +512,12 @@ pub async fn setup_test_prom_app_with_frontend(
         .with_logs_handler(instance.fe_instance().clone())
         .with_prom_handler(frontend_ref.clone(), true, is_strict_mode)
         .with_prometheus_handler(frontend_ref)
-        .with_greptime_config_options(instance.opts.datanode_options().to_toml().unwrap())
-        .build();
+        .with_greptime_config_options(instance.opts.datanode_options().to_toml().unwrap());
+    let http_server = match user_provider {
+        Some(user_provider) => http_server.with_user_provider(user_provider),
+        None => http_server,
+    }
+    .build();
     let app = http_server.build(http_server.make_app()).unwrap();
     (app, instance.guard)
 }
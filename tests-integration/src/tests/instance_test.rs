@@ -15,20 +15,27 @@
 use std::assert_matches::assert_matches;
 use std::env;
 use std::sync::Arc;
+use std::time::Duration;
 
 use client::{OutputData, DEFAULT_SCHEMA_NAME};
 use common_catalog::consts::DEFAULT_CATALOG_NAME;
+use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
+use common_meta::ddl::repartition_table::REPARTITION_ROWS_PER_SECOND_KEY;
 use common_query::Output;
 use common_recordbatch::util;
 use common_test_util::recordbatch::check_output_stream;
 use common_test_util::temp_dir;
+use datatypes::value::Value;
 use datatypes::vectors::{StringVector, TimestampMillisecondVector, UInt64Vector, VectorRef};
 use frontend::error::{Error, Result};
 use frontend::instance::Instance;
 use operator::error::Error as OperatorError;
 use rstest::rstest;
 use rstest_reuse::apply;
+use servers::influxdb::InfluxdbRequest;
 use servers::query_handler::sql::SqlQueryHandler;
+use servers::query_handler::InfluxdbLineProtocolHandler;
 use session::context::{QueryContext, QueryContextRef};
 
 use crate::tests::test_util::{
@@ -349,6 +356,273 @@ async fn test_execute_insert(instance: Arc<dyn MockInstance>) {
     assert!(matches!(output, OutputData::AffectedRows(2)));
 }
 
+#[apply(both_instances_cases)]
+async fn test_readonly_instance(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    let output = execute_sql(
+        &instance,
+        "create table demo(host string, cpu double, ts timestamp time index)",
+    )
+    .await
+    .data;
+    assert!(matches!(output, OutputData::AffectedRows(0)));
+    let output = execute_sql(&instance, "insert into demo values ('host1', 66.6, 1000)")
+        .await
+        .data;
+    assert!(matches!(output, OutputData::AffectedRows(1)));
+
+    let output = execute_sql(&instance, "admin set_readonly(true)").await;
+    let expected = "\
++--------------------------+
+| ADMIN set_readonly(true) |
++--------------------------+
+| false                    |
++--------------------------+";
+    check_output_stream(output.data, expected).await;
+
+    // Writes and DDLs are rejected, reads still work.
+    for sql in [
+        "insert into demo values ('host2', 88.8, 2000)",
+        "delete from demo where host = 'host1'",
+        "create table demo2(ts timestamp time index)",
+        "alter table demo add column memory double",
+        "truncate table demo",
+        "drop table demo",
+    ] {
+        let err = try_execute_sql(&instance, sql).await.unwrap_err();
+        assert_eq!(StatusCode::TableReadonly, err.status_code(), "{sql}");
+    }
+    let output = execute_sql(&instance, "select host, cpu from demo").await;
+    let expected = "\
++-------+------+
+| host  | cpu  |
++-------+------+
+| host1 | 66.6 |
++-------+------+";
+    check_output_stream(output.data, expected).await;
+
+    let output = execute_sql(&instance, "admin set_readonly(false)").await;
+    let expected = "\
++---------------------------+
+| ADMIN set_readonly(false) |
++---------------------------+
+| true                      |
++---------------------------+";
+    check_output_stream(output.data, expected).await;
+
+    let output = execute_sql(&instance, "insert into demo values ('host2', 88.8, 2000)")
+        .await
+        .data;
+    assert!(matches!(output, OutputData::AffectedRows(1)));
+}
+
+#[apply(both_instances_cases)]
+async fn test_build_index(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    let output = execute_sql(
+        &instance,
+        "create table demo(host string, cpu double, ts timestamp time index)",
+    )
+    .await
+    .data;
+    assert!(matches!(output, OutputData::AffectedRows(0)));
+    let output = execute_sql(
+        &instance,
+        "insert into demo values ('host1', 1.0, 1000), ('host3', 3.0, 3000)",
+    )
+    .await
+    .data;
+    assert!(matches!(output, OutputData::AffectedRows(2)));
+    let _ = execute_sql(&instance, "admin flush_table('demo')").await;
+
+    // Returns the scan metrics of a query for a host between the hosts in the file, so
+    // only the index can prune the row group.
+    let explain_scan = || async {
+        let output = execute_sql(
+            &instance,
+            "explain analyze verbose select * from demo where host = 'host2'",
+        )
+        .await
+        .data;
+        match output {
+            OutputData::Stream(s) => util::collect_batches(s)
+                .await
+                .unwrap()
+                .pretty_print()
+                .unwrap(),
+            OutputData::RecordBatches(batches) => batches.pretty_print().unwrap(),
+            _ => unreachable!(),
+        }
+    };
+    let output = explain_scan().await;
+    assert!(
+        output.contains("rg_inverted_filtered=0"),
+        "unexpected output: {output}"
+    );
+
+    let output = execute_sql(
+        &instance,
+        "alter table demo modify column host set inverted index",
+    )
+    .await
+    .data;
+    assert!(matches!(output, OutputData::AffectedRows(0)));
+    // Setting the index schedules a job to index the existing file, the function waits
+    // for it and finds no more file to index.
+    let output = execute_sql(&instance, "admin build_index('demo')").await;
+    let expected = "\
++---------------------------+
+| ADMIN build_index('demo') |
++---------------------------+
+| 0                         |
++---------------------------+";
+    check_output_stream(output.data, expected).await;
+
+    // The file is not rewritten but the index prunes the row group now.
+    let output = explain_scan().await;
+    assert!(
+        output.contains("rg_inverted_filtered=1"),
+        "unexpected output: {output}"
+    );
+
+    let output = execute_sql(&instance, "select * from demo order by ts").await;
+    let expected = "\
++-------+-----+---------------------+
+| host  | cpu | ts                  |
++-------+-----+---------------------+
+| host1 | 1.0 | 1970-01-01T00:00:01 |
+| host3 | 3.0 | 1970-01-01T00:00:03 |
++-------+-----+---------------------+";
+    check_output_stream(output.data, expected).await;
+}
+
+#[apply(both_instances_cases)]
+async fn test_delete_time_range(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    let output = execute_sql(
+        &instance,
+        "create table demo(host string, cpu double, ts timestamp time index)",
+    )
+    .await
+    .data;
+    assert!(matches!(output, OutputData::AffectedRows(0)));
+    for (sql, rows) in [
+        (
+            "insert into demo values ('host1', 1.0, 1000), ('host2', 2.0, 2000)",
+            2,
+        ),
+        (
+            "insert into demo values ('host3', 3.0, 5000), ('host4', 4.0, 6000)",
+            2,
+        ),
+        // Overwrites a row in another file.
+        ("insert into demo values ('host1', 1.5, 1000)", 1),
+    ] {
+        let output = execute_sql(&instance, sql).await.data;
+        assert!(matches!(output, OutputData::AffectedRows(n) if n == rows));
+        let _ = execute_sql(&instance, "admin flush_table('demo')").await;
+    }
+
+    // Returns the number of SST files of the table, one row per file.
+    let num_files = || async {
+        let output = execute_sql(&instance, "admin region_manifest('demo')")
+            .await
+            .data;
+        let batches = match output {
+            OutputData::Stream(s) => util::collect_batches(s).await.unwrap(),
+            OutputData::RecordBatches(batches) => batches,
+            _ => unreachable!(),
+        };
+        batches.iter().map(|batch| batch.num_rows()).sum::<usize>()
+    };
+    assert_eq!(3, num_files().await);
+
+    // The files of the old rows are dropped, while the overwritten row is only counted once.
+    let output = execute_sql(&instance, "delete from demo where ts < 3000")
+        .await
+        .data;
+    assert!(matches!(output, OutputData::AffectedRows(2)));
+    assert_eq!(1, num_files().await);
+
+    let output = execute_sql(&instance, "select * from demo order by ts").await;
+    let expected = "\
++-------+-----+---------------------+
+| host  | cpu | ts                  |
++-------+-----+---------------------+
+| host3 | 3.0 | 1970-01-01T00:00:05 |
+| host4 | 4.0 | 1970-01-01T00:00:06 |
++-------+-----+---------------------+";
+    check_output_stream(output.data, expected).await;
+}
+
+#[apply(both_instances_cases)]
+async fn test_write_time_bounds(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    let modes = [("reject", 1), ("clamp", 3), ("warn", 3)];
+    for (mode, _) in modes {
+        let sql = format!(
+            "create table demo_{mode}(host string primary key, cpu double, ts timestamp time index) \
+             with ('write.time_bounds' = 'now-30d..now+1h', 'write.time_bounds_mode' = '{mode}')"
+        );
+        let output = execute_sql(&instance, &sql).await.data;
+        assert!(matches!(output, OutputData::AffectedRows(0)));
+    }
+    let now = common_time::util::current_time_millis();
+
+    // Writes a row in the bounds, a far past row and a far future row by SQL.
+    for (mode, written) in modes {
+        let ctx = QueryContext::arc();
+        let sql = format!(
+            "insert into demo_{mode} values \
+             ('host1', 1.0, {now}), ('host2', 2.0, 1000), ('host3', 3.0, '2262-01-01 00:00:00')"
+        );
+        let output = execute_sql_with(&instance, &sql, ctx.clone()).await;
+        assert_matches!(output.data, OutputData::AffectedRows(rows) if rows == written);
+        assert_eq!(3 - written, output.meta.rejected_rows, "{mode}");
+        assert_eq!(mode == "reject", ctx.warning().is_some(), "{mode}");
+    }
+
+    // And by InfluxDB line protocol.
+    for (mode, written) in modes {
+        let lines = format!(
+            "demo_{mode},host=host4 cpu=4.0 {}\n\
+             demo_{mode},host=host5 cpu=5.0 1000000\n\
+             demo_{mode},host=host6 cpu=6.0 9000000000000000000",
+            now * 1_000_000
+        );
+        let request = InfluxdbRequest {
+            precision: None,
+            lines,
+        };
+        let output = instance.exec(request, QueryContext::arc()).await.unwrap();
+        assert_matches!(output.data, OutputData::AffectedRows(rows) if rows == written);
+        assert_eq!(3 - written, output.meta.rejected_rows, "{mode}");
+    }
+
+    // Only the rows of `clamp` are all in the bounds.
+    for (mode, total, in_bounds) in [("reject", 2, 2), ("clamp", 6, 6), ("warn", 6, 2)] {
+        let sql = format!(
+            "select count(*) as total, \
+             sum(case when ts >= now() - interval '31 days' and ts <= now() + interval '2 hours' then 1 else 0 end) as in_bounds \
+             from demo_{mode}"
+        );
+        let output = execute_sql(&instance, &sql).await;
+        let expected = format!(
+            "\
++-------+-----------+
+| total | in_bounds |
++-------+-----------+
+| {total}     | {in_bounds}         |
++-------+-----------+"
+        );
+        check_output_stream(output.data, &expected).await;
+    }
+}
+
 #[apply(both_instances_cases)]
 async fn test_execute_insert_by_select(instance: Arc<dyn MockInstance>) {
     let instance = instance.frontend();
@@ -1197,6 +1471,187 @@ async fn test_execute_query_external_table_json_default_ts_column(instance: Arc<
     check_output_stream(output, expect).await;
 }
 
+#[apply(standalone_instance_case)]
+async fn test_scan_snapshot_with_concurrent_writes(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    let output = execute_sql(
+        &instance,
+        "create table snapshot_demo(host string primary key, cpu double, ts timestamp time index) \
+         partition on columns (host) (host < 'm', host >= 'm')",
+    )
+    .await
+    .data;
+    assert!(matches!(output, OutputData::AffectedRows(0)));
+
+    let stop = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let writer = {
+        let instance = instance.clone();
+        let stop = stop.clone();
+        tokio::spawn(async move {
+            let mut ts = 0;
+            while !stop.load(std::sync::atomic::Ordering::Relaxed) {
+                let sql =
+                    format!("insert into snapshot_demo values ('a', 1.0, {ts}), ('z', 1.0, {ts})");
+                execute_sql(&instance, &sql).await;
+                ts += 1;
+            }
+        })
+    };
+
+    // Both scans of the self join read the same snapshot of every region, so the
+    // join matches each row exactly once.
+    let sql = "select \
+         (select count(*) from snapshot_demo x join snapshot_demo y on x.host = y.host and x.ts = y.ts) as joined, \
+         (select count(*) from snapshot_demo) as total";
+    for _ in 0..20 {
+        let output = execute_sql(&instance, sql).await.data;
+        let OutputData::Stream(stream) = output else {
+            unreachable!()
+        };
+        let batches = util::collect_batches(stream).await.unwrap();
+        let batch = batches.iter().next().unwrap();
+        let joined = batch.column(0).get(0);
+        let total = batch.column(1).get(0);
+        assert_eq!(joined, total);
+    }
+
+    stop.store(true, std::sync::atomic::Ordering::Relaxed);
+    writer.await.unwrap();
+}
+
+#[apply(standalone_instance_case)]
+async fn test_repartition_table(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    let output = execute_sql(
+        &instance,
+        "create table repartition_demo(host string primary key, cpu double, ts timestamp time index) \
+         partition on columns (host) (host < 'host_50', host >= 'host_50')",
+    )
+    .await
+    .data;
+    assert!(matches!(output, OutputData::AffectedRows(0)));
+
+    let values = (0..100)
+        .map(|i| format!("('host_{i:02}', {i}, {i})"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let output = execute_sql(
+        &instance,
+        &format!("insert into repartition_demo values {values}"),
+    )
+    .await
+    .data;
+    assert!(matches!(output, OutputData::AffectedRows(100)));
+
+    // Copies the rows slowly so the table is written and read during the copy.
+    let mut query_ctx = QueryContext::with(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME);
+    query_ctx.set_extension(REPARTITION_ROWS_PER_SECOND_KEY, "20");
+    let alter = {
+        let instance = instance.clone();
+        tokio::spawn(async move {
+            execute_sql_with(
+                &instance,
+                "alter table repartition_demo partition on columns (host) ( \
+                 host < 'host_25', \
+                 host >= 'host_25' AND host < 'host_50', \
+                 host >= 'host_50' AND host < 'host_75', \
+                 host >= 'host_75')",
+                Arc::new(query_ctx),
+            )
+            .await
+        })
+    };
+
+    // The table stays writable while the rows are copied, except for a short read-only
+    // window before the switch. Reads are served by the old regions until the table is
+    // switched to the new regions, the rows are neither missing nor duplicated.
+    let mut inserted = 0;
+    while !alter.is_finished() {
+        let sql = format!(
+            "insert into repartition_demo values ('host_{inserted:02}', 0, {})",
+            1000 + inserted
+        );
+        match try_execute_sql(&instance, &sql).await {
+            Ok(output) => {
+                assert!(matches!(output.data, OutputData::AffectedRows(1)));
+                inserted += 1;
+            }
+            Err(err) => assert_eq!(StatusCode::TableReadonly, err.status_code(), "{err:?}"),
+        }
+
+        // The old regions may be dropped after a query is planned.
+        let sql = "select count(*), sum(cpu) from repartition_demo";
+        let Ok(output) = try_execute_sql(&instance, sql).await else {
+            continue;
+        };
+        let OutputData::Stream(stream) = output.data else {
+            unreachable!()
+        };
+        let batches = util::collect_batches(stream).await.unwrap();
+        let batch = batches.iter().next().unwrap();
+        assert_eq!(Value::Int64(100 + inserted), batch.column(0).get(0));
+        assert_eq!(Value::from(4950.0), batch.column(1).get(0));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let output = alter.await.unwrap().data;
+    assert!(matches!(output, OutputData::AffectedRows(0)));
+    assert!(inserted > 0);
+
+    let output = execute_sql(
+        &instance,
+        "select count(*) from information_schema.partitions where table_name = 'repartition_demo'",
+    )
+    .await
+    .data;
+    let expected = "\
++----------+
+| count(*) |
++----------+
+| 4        |
++----------+";
+    check_output_stream(output, expected).await;
+
+    let output = execute_sql(
+        &instance,
+        "select host, cpu from repartition_demo where host in ('host_10', 'host_30', 'host_60', 'host_90') and ts < 1000 order by host",
+    )
+    .await
+    .data;
+    let expected = "\
++---------+------+
+| host    | cpu  |
++---------+------+
+| host_10 | 10.0 |
+| host_30 | 30.0 |
+| host_60 | 60.0 |
+| host_90 | 90.0 |
++---------+------+";
+    check_output_stream(output, expected).await;
+
+    // The table is writable again, the rows written during the copy are kept.
+    let output = execute_sql(
+        &instance,
+        "insert into repartition_demo values ('host_99', 99, 100), ('host_00', 0, 100)",
+    )
+    .await
+    .data;
+    assert!(matches!(output, OutputData::AffectedRows(2)));
+
+    let output = execute_sql(&instance, "select count(*), sum(cpu) from repartition_demo")
+        .await
+        .data;
+    let OutputData::Stream(stream) = output else {
+        unreachable!()
+    };
+    let batches = util::collect_batches(stream).await.unwrap();
+    let batch = batches.iter().next().unwrap();
+    assert_eq!(Value::Int64(102 + inserted), batch.column(0).get(0));
+    assert_eq!(Value::from(5049.0), batch.column(1).get(0));
+}
+
 #[apply(standalone_instance_case)]
 async fn test_rename_table(instance: Arc<dyn MockInstance>) {
     let instance = instance.frontend();
@@ -1477,6 +1932,204 @@ async fn test_insert_with_default_value(instance: Arc<dyn MockInstance>) {
     test_insert_with_default_value_for_type(instance.frontend(), "timestamp").await;
 }
 
+#[apply(both_instances_cases)]
+async fn test_insert_with_on_update(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    let output = execute_sql(
+        &instance,
+        r#"create table test_on_update(
+        k string,
+        v double,
+        ts timestamp,
+        updated_at timestamp DEFAULT now() ON UPDATE now(),
+        TIME INDEX (ts),
+        PRIMARY KEY(k)
+    ) engine=mito;"#,
+    )
+    .await
+    .data;
+    assert!(matches!(output, OutputData::AffectedRows(0)));
+
+    let output = execute_sql(&instance, "show create table test_on_update")
+        .await
+        .data;
+    let output = match output {
+        OutputData::Stream(s) => util::collect_batches(s)
+            .await
+            .unwrap()
+            .pretty_print()
+            .unwrap(),
+        _ => unreachable!(),
+    };
+    assert!(
+        output.contains(r#""updated_at" TIMESTAMP(3) NULL DEFAULT now() ON UPDATE now()"#),
+        "unexpected output: {output}"
+    );
+
+    let output = execute_sql(
+        &instance,
+        "select extra from information_schema.columns where table_name = 'test_on_update' and column_name = 'updated_at'",
+    )
+    .await
+    .data;
+    let expected = "\
++-----------------+
+| extra           |
++-----------------+
+| on update now() |
++-----------------+";
+    check_output_stream(output, expected).await;
+
+    let mut updated_at = Vec::with_capacity(2);
+    for v in [1.0, 2.0] {
+        // Upserts the same key without `updated_at`, so it should be filled by the write time.
+        let output = execute_sql(
+            &instance,
+            &format!("insert into test_on_update(k, v, ts) values ('a', {v}, 1000)"),
+        )
+        .await
+        .data;
+        assert!(matches!(output, OutputData::AffectedRows(1)));
+
+        let output = execute_sql(&instance, "select v, updated_at from test_on_update")
+            .await
+            .data;
+        let OutputData::Stream(s) = output else {
+            unreachable!()
+        };
+        let batches = util::collect(s).await.unwrap();
+        assert_eq!(1, batches.iter().map(|b| b.num_rows()).sum::<usize>());
+        assert_eq!(Value::from(v), batches[0].column(0).get(0));
+        updated_at.push(batches[0].column(1).get(0));
+
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert!(
+        updated_at[0] < updated_at[1],
+        "updated_at should advance: {updated_at:?}"
+    );
+
+    // Explicitly provided value is kept.
+    let output = execute_sql(
+        &instance,
+        "insert into test_on_update(k, v, ts, updated_at) values ('a', 3.0, 1000, 0)",
+    )
+    .await
+    .data;
+    assert!(matches!(output, OutputData::AffectedRows(1)));
+    let output = execute_sql(&instance, "select k, v, updated_at from test_on_update")
+        .await
+        .data;
+    let expected = "\
++---+-----+---------------------+
+| k | v   | updated_at          |
++---+-----+---------------------+
+| a | 3.0 | 1970-01-01T00:00:00 |
++---+-----+---------------------+";
+    check_output_stream(output, expected).await;
+}
+
+#[apply(both_instances_cases)]
+async fn test_select_with_scan_hints(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    let output = execute_sql(
+        &instance,
+        r#"create table test_scan_hints(
+        host string INVERTED INDEX,
+        cpu double,
+        ts timestamp,
+        TIME INDEX (ts),
+        PRIMARY KEY(host)
+    ) engine=mito;"#,
+    )
+    .await
+    .data;
+    assert!(matches!(output, OutputData::AffectedRows(0)));
+
+    let output = execute_sql(
+        &instance,
+        "insert into test_scan_hints values ('a', 1.0, 1000), ('b', 2.0, 2000), ('a', 3.0, 3000)",
+    )
+    .await
+    .data;
+    assert!(matches!(output, OutputData::AffectedRows(3)));
+
+    // Unknown hints are ignored.
+    let output = execute_sql(
+        &instance,
+        "select /*+ scan_parallelism(2), no_inverted_index, read_latest, unknown_hint */ host, cpu from test_scan_hints where host = 'a' order by ts",
+    )
+    .await
+    .data;
+    let expected = "\
++------+-----+
+| host | cpu |
++------+-----+
+| a    | 1.0 |
+| a    | 3.0 |
++------+-----+";
+    check_output_stream(output, expected).await;
+
+    let output = execute_sql(
+        &instance,
+        "explain select /*+ scan_parallelism(2), no_inverted_index, unknown_hint */ * from test_scan_hints",
+    )
+    .await
+    .data;
+    let output = match output {
+        OutputData::Stream(s) => util::collect_batches(s)
+            .await
+            .unwrap()
+            .pretty_print()
+            .unwrap(),
+        OutputData::RecordBatches(batches) => batches.pretty_print().unwrap(),
+        _ => unreachable!(),
+    };
+    assert!(
+        output.contains("scan_hints=[scan_parallelism(2), no_inverted_index]"),
+        "unexpected output: {output}"
+    );
+
+    // Samples all row groups.
+    for sql in [
+        "select host, cpu from test_scan_hints tablesample system (100) repeatable (1) where host = 'a' order by ts",
+        "select host, cpu from sample(test_scan_hints, 1.0) where host = 'a' order by ts",
+    ] {
+        let output = execute_sql(&instance, sql).await.data;
+        check_output_stream(output, expected).await;
+    }
+
+    let output = execute_sql(
+        &instance,
+        "explain select count(*) from sample(test_scan_hints, 0.5, 42)",
+    )
+    .await
+    .data;
+    let output = match output {
+        OutputData::Stream(s) => util::collect_batches(s)
+            .await
+            .unwrap()
+            .pretty_print()
+            .unwrap(),
+        OutputData::RecordBatches(batches) => batches.pretty_print().unwrap(),
+        _ => unreachable!(),
+    };
+    assert!(
+        output.contains("scan_hints=[sample(0.5, 42)]"),
+        "unexpected output: {output}"
+    );
+
+    // Only one table can be sampled.
+    let result = try_execute_sql(
+        &instance,
+        "select * from test_scan_hints tablesample system (10) join test_scan_hints t2 on test_scan_hints.host = t2.host",
+    )
+    .await;
+    assert!(result.is_err());
+}
+
 #[apply(both_instances_cases)]
 async fn test_use_database(instance: Arc<dyn MockInstance>) {
     let instance = instance.frontend();
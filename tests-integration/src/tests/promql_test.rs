@@ -12,16 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use common_query::Output;
+use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_query::{Output, OutputData};
+use common_recordbatch::util;
 use frontend::instance::Instance;
 use query::parser::{PromQuery, QueryLanguageParser, QueryStatement};
+use query::promql::rollup::PromRollup;
 use rstest::rstest;
 use rstest_reuse::apply;
 use servers::query_handler::sql::SqlQueryHandler;
 use session::context::QueryContext;
+use sql::statements::create::{FLOW_OPT_KEY_PROMQL_REWRITE, FLOW_OPT_KEY_PROMQL_REWRITE_STEP};
+use table::table_name::TableName;
 
 use super::test_util::{
     both_instances_cases, check_unordered_output_stream, distributed, standalone,
@@ -547,6 +553,325 @@ async fn binary_op_plain_columns(instance: Arc<dyn MockInstance>) {
     .await;
 }
 
+// # The output of absent_over_time is labeled with the equality matchers.
+// eval range from 0 to 100s step 60s absent_over_time(http_requests{job="x", group=~"c.*"}[1m])
+//   {job="x"} 1 1
+#[apply(both_instances_cases)]
+async fn absent_over_time_labels(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    create_insert_query_assert(
+        instance,
+        AGGREGATORS_CREATE_TABLE,
+        AGGREGATORS_INSERT_DATA,
+        r#"absent_over_time(http_requests{job="x", group=~"c.*"}[1m])"#,
+        UNIX_EPOCH,
+        unix_epoch_plus_100s(),
+        Duration::from_secs(60),
+        Duration::from_secs(0),
+        "+---------------------+-------+-----+\
+        \n| ts                  | value | job |\
+        \n+---------------------+-------+-----+\
+        \n| 1970-01-01T00:00:00 | 1.0   | x   |\
+        \n| 1970-01-01T00:01:00 | 1.0   | x   |\
+        \n+---------------------+-------+-----+",
+    )
+    .await;
+}
+
+// histogram_quantile(0.9, sum by (le, job) (rate(http_request_duration_seconds_bucket[5m])))
+#[apply(both_instances_cases)]
+async fn histogram_quantile_sum_by_le(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    // Counters grow linearly so the rate of each bucket is exact:
+    // - api/a: le=1 -> 1, le=2 -> 2, le=4 -> 6, le=+Inf -> 6
+    // - api/b: le=1 -> 0, le=2 -> 0, le=4 -> 4, le=+Inf -> 4
+    // - web/a: le=1 -> 2, le=2 -> 10, le=4 -> 10, le=+Inf -> 10
+    create_insert_tql_assert(
+        instance.clone(),
+        r#"create table http_request_duration_seconds_bucket (
+    ts timestamp time index,
+    job string,
+    instance string,
+    le string,
+    val double,
+    primary key (job, instance, le),
+);"#,
+        r#"insert into http_request_duration_seconds_bucket(ts, job, instance, le, val) values
+    (300000, 'api', 'a', '1', 300),
+    (300000, 'api', 'a', '2', 600),
+    (300000, 'api', 'a', '4', 1800),
+    (300000, 'api', 'a', '+Inf', 1800),
+    (450000, 'api', 'a', '1', 450),
+    (450000, 'api', 'a', '2', 900),
+    (450000, 'api', 'a', '4', 2700),
+    (450000, 'api', 'a', '+Inf', 2700),
+    (600000, 'api', 'a', '1', 600),
+    (600000, 'api', 'a', '2', 1200),
+    (600000, 'api', 'a', '4', 3600),
+    (600000, 'api', 'a', '+Inf', 3600),
+    (300000, 'api', 'b', '1', 0),
+    (300000, 'api', 'b', '2', 0),
+    (300000, 'api', 'b', '4', 1200),
+    (300000, 'api', 'b', '+Inf', 1200),
+    (450000, 'api', 'b', '1', 0),
+    (450000, 'api', 'b', '2', 0),
+    (450000, 'api', 'b', '4', 1800),
+    (450000, 'api', 'b', '+Inf', 1800),
+    (600000, 'api', 'b', '1', 0),
+    (600000, 'api', 'b', '2', 0),
+    (600000, 'api', 'b', '4', 2400),
+    (600000, 'api', 'b', '+Inf', 2400),
+    (300000, 'web', 'a', '1', 600),
+    (300000, 'web', 'a', '2', 3000),
+    (300000, 'web', 'a', '4', 3000),
+    (300000, 'web', 'a', '+Inf', 3000),
+    (450000, 'web', 'a', '1', 900),
+    (450000, 'web', 'a', '2', 4500),
+    (450000, 'web', 'a', '4', 4500),
+    (450000, 'web', 'a', '+Inf', 4500),
+    (600000, 'web', 'a', '1', 1200),
+    (600000, 'web', 'a', '2', 6000),
+    (600000, 'web', 'a', '4', 6000),
+    (600000, 'web', 'a', '+Inf', 6000);"#,
+        "tql eval (600, 600, '1s') histogram_quantile(0.9, sum by (le, job) (rate(http_request_duration_seconds_bucket[5m])))",
+        "+-----+---------------------+---------------------------------+\
+        \n| job | ts                  | sum(prom_rate(ts_range,val,ts)) |\
+        \n+-----+---------------------+---------------------------------+\
+        \n| api | 1970-01-01T00:10:00 | 3.75                            |\
+        \n| web | 1970-01-01T00:10:00 | 1.875                           |\
+        \n+-----+---------------------+---------------------------------+",
+    )
+    .await;
+
+    // The quantile can't be computed without the `le` label.
+    let result = instance
+        .do_query(
+            "tql eval (600, 600, '1s') histogram_quantile(0.9, sum by (job) (rate(http_request_duration_seconds_bucket[5m])))",
+            QueryContext::arc(),
+        )
+        .await
+        .remove(0);
+    assert!(result.is_err());
+}
+
+#[apply(both_instances_cases)]
+async fn promql_rollup_rewrite(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    // Counters of api grow by 1 + 2 per second and web by 4 per second.
+    create_insert_tql_assert(
+        instance.clone(),
+        r#"create table http_requests_total (
+    ts timestamp time index,
+    job string,
+    instance string,
+    val double,
+    primary key (job, instance),
+);"#,
+        r#"insert into http_requests_total(ts, job, instance, val) values
+    (0, 'api', 'a', 0),
+    (150000, 'api', 'a', 150),
+    (300000, 'api', 'a', 300),
+    (450000, 'api', 'a', 450),
+    (600000, 'api', 'a', 600),
+    (0, 'api', 'b', 0),
+    (150000, 'api', 'b', 300),
+    (300000, 'api', 'b', 600),
+    (450000, 'api', 'b', 900),
+    (600000, 'api', 'b', 1200),
+    (0, 'web', 'a', 0),
+    (150000, 'web', 'a', 600),
+    (300000, 'web', 'a', 1200),
+    (450000, 'web', 'a', 1800),
+    (600000, 'web', 'a', 2400);"#,
+        "tql eval (300, 600, '300s') sum by (job) (rate(http_requests_total[5m]))",
+        "+-----+---------------------+---------------------------------+\
+        \n| job | ts                  | sum(prom_rate(ts_range,val,ts)) |\
+        \n+-----+---------------------+---------------------------------+\
+        \n| api | 1970-01-01T00:05:00 | 3.0                             |\
+        \n| api | 1970-01-01T00:10:00 | 3.0                             |\
+        \n| web | 1970-01-01T00:05:00 | 4.0                             |\
+        \n| web | 1970-01-01T00:10:00 | 4.0                             |\
+        \n+-----+---------------------+---------------------------------+",
+    )
+    .await;
+
+    // The rollup table a flow with `promql_rewrite` writes every 150 seconds.
+    for sql in [
+        "create table job_rate (job string, ts timestamp time index, val double, primary key (job))",
+        "insert into job_rate(job, ts, val) values
+            ('api', 300000, 3), ('api', 450000, 3), ('api', 600000, 3),
+            ('web', 300000, 4), ('web', 450000, 4), ('web', 600000, 4)",
+    ] {
+        let _ = instance
+            .do_query(sql, QueryContext::arc())
+            .await
+            .remove(0)
+            .unwrap();
+    }
+    let (fingerprint, rollup) = PromRollup::from_flow_options(
+        "job_rate_flow",
+        &TableName::new(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, "job_rate"),
+        &HashMap::from([
+            (
+                FLOW_OPT_KEY_PROMQL_REWRITE.to_string(),
+                "sum by (job) (rate(http_requests_total[5m]))".to_string(),
+            ),
+            (
+                FLOW_OPT_KEY_PROMQL_REWRITE_STEP.to_string(),
+                "150s".to_string(),
+            ),
+        ]),
+    )
+    .unwrap()
+    .unwrap();
+    let rollups = instance.query_engine().engine_state().promql_rollups();
+    rollups.register(fingerprint, rollup);
+
+    // Aligned steps read the rollup table and return the same values.
+    let query = "tql eval (300, 600, '300s') sum by(job)(rate(http_requests_total[5m]))";
+    let output = instance
+        .do_query(query, QueryContext::arc())
+        .await
+        .remove(0)
+        .unwrap();
+    check_unordered_output_stream(
+        output,
+        "+-----+---------------------+-----+\
+        \n| job | ts                  | val |\
+        \n+-----+---------------------+-----+\
+        \n| api | 1970-01-01T00:05:00 | 3.0 |\
+        \n| api | 1970-01-01T00:10:00 | 3.0 |\
+        \n| web | 1970-01-01T00:05:00 | 4.0 |\
+        \n| web | 1970-01-01T00:10:00 | 4.0 |\
+        \n+-----+---------------------+-----+",
+    )
+    .await;
+    let explain = |query: &'static str| {
+        let instance = instance.clone();
+        async move {
+            let output = instance
+                .do_query(query, QueryContext::arc())
+                .await
+                .remove(0)
+                .unwrap();
+            match output.data {
+                OutputData::Stream(s) => util::collect_batches(s)
+                    .await
+                    .unwrap()
+                    .pretty_print()
+                    .unwrap(),
+                OutputData::RecordBatches(batches) => batches.pretty_print().unwrap(),
+                _ => unreachable!(),
+            }
+        }
+    };
+    let plan =
+        explain("tql explain (300, 600, '300s') sum by (job) (rate(http_requests_total[5m]))")
+            .await;
+    assert!(plan.contains("job_rate"), "unexpected plan: {plan}");
+
+    // Steps that are not timestamps of the rollup table evaluate the expression.
+    let plan =
+        explain("tql explain (330, 630, '300s') sum by (job) (rate(http_requests_total[5m]))")
+            .await;
+    assert!(!plan.contains("job_rate"), "unexpected plan: {plan}");
+
+    // Dropping the flow stops the rewrite.
+    rollups.unregister(DEFAULT_CATALOG_NAME, "job_rate_flow");
+    let plan =
+        explain("tql explain (300, 600, '300s') sum by (job) (rate(http_requests_total[5m]))")
+            .await;
+    assert!(!plan.contains("job_rate"), "unexpected plan: {plan}");
+}
+
+#[apply(both_instances_cases)]
+async fn offset_larger_than_range(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    // Samples of the first day are read by a 10 minutes range of the second day.
+    create_insert_tql_assert(
+        instance.clone(),
+        "create table day_metric (ts timestamp time index, host string primary key, val double);",
+        "insert into day_metric(ts, host, val) values
+            (0, 'a', 1), (300000, 'a', 2), (600000, 'a', 3),
+            (86400000, 'a', 100), (86700000, 'a', 200), (87000000, 'a', 300);",
+        "tql eval (86400, 87000, '300s') day_metric offset 1d",
+        "+---------------------+------+-----+\
+        \n| ts                  | host | val |\
+        \n+---------------------+------+-----+\
+        \n| 1970-01-02T00:00:00 | a    | 1.0 |\
+        \n| 1970-01-02T00:05:00 | a    | 2.0 |\
+        \n| 1970-01-02T00:10:00 | a    | 3.0 |\
+        \n+---------------------+------+-----+",
+    )
+    .await;
+
+    let output = instance
+        .do_query(
+            "tql eval (86400, 87000, '300s') max_over_time(day_metric[5m:5m] offset 1d)",
+            QueryContext::arc(),
+        )
+        .await
+        .remove(0)
+        .unwrap();
+    check_unordered_output_stream(
+        output,
+        "+---------------------+----------------------------------+------+\
+        \n| ts                  | prom_max_over_time(ts_range,val) | host |\
+        \n+---------------------+----------------------------------+------+\
+        \n| 1970-01-02T00:00:00 | 1.0                              | a    |\
+        \n| 1970-01-02T00:05:00 | 2.0                              | a    |\
+        \n| 1970-01-02T00:10:00 | 3.0                              | a    |\
+        \n+---------------------+----------------------------------+------+",
+    )
+    .await;
+}
+
+#[apply(both_instances_cases)]
+async fn stddev_stdvar_large_magnitude(instance: Arc<dyn MockInstance>) {
+    let instance = instance.frontend();
+
+    // The deviations are tiny compared to the values, the sum of squares would
+    // lose them.
+    create_insert_tql_assert(
+        instance.clone(),
+        "create table big_metric (ts timestamp time index, job string, instance string, val double, primary key (job, instance));",
+        "insert into big_metric(ts, job, instance, val) values
+            (0, 'api', 'a', 1000000000), (0, 'api', 'b', 1000000002),
+            (0, 'web', 'a', 1000000001), (0, 'web', 'b', 1000000004), (0, 'web', 'c', 1000000007);",
+        "tql eval (0, 0, '1s') stddev by (job) (big_metric)",
+        "+-----+---------------------+----------------------------+\
+        \n| job | ts                  | stddev_pop(big_metric.val) |\
+        \n+-----+---------------------+----------------------------+\
+        \n| api | 1970-01-01T00:00:00 | 1.0                        |\
+        \n| web | 1970-01-01T00:00:00 | 2.449489742783178          |\
+        \n+-----+---------------------+----------------------------+",
+    )
+    .await;
+
+    let output = instance
+        .do_query(
+            "tql eval (0, 0, '1s') stdvar without (instance) (big_metric)",
+            QueryContext::arc(),
+        )
+        .await
+        .remove(0)
+        .unwrap();
+    check_unordered_output_stream(
+        output,
+        "+-----+---------------------+-------------------------+\
+        \n| job | ts                  | var_pop(big_metric.val) |\
+        \n+-----+---------------------+-------------------------+\
+        \n| api | 1970-01-01T00:00:00 | 1.0                     |\
+        \n| web | 1970-01-01T00:00:00 | 6.0                     |\
+        \n+-----+---------------------+-------------------------+",
+    )
+    .await;
+}
+
 #[apply(both_instances_cases)]
 async fn cross_schema_query(instance: Arc<dyn MockInstance>) {
     let ins = instance.frontend();
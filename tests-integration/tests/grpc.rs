@@ -73,6 +73,7 @@ macro_rules! grpc_tests {
                 test_invalid_dbname,
                 test_auto_create_table,
                 test_auto_create_table_with_hints,
+                test_insert_with_idempotency_key,
                 test_insert_and_select,
                 test_dbname,
                 test_grpc_message_size_ok,
@@ -290,6 +291,78 @@ pub async fn test_auto_create_table_with_hints(store_type: StorageType) {
     guard.remove_all().await;
 }
 
+pub async fn test_insert_with_idempotency_key(store_type: StorageType) {
+    let (addr, mut guard, fe_grpc_server) =
+        setup_grpc_server(store_type, "insert_with_idempotency_key").await;
+
+    let grpc_client = Client::with_urls(vec![addr]);
+    let db = Database::new(DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, grpc_client);
+
+    let (host_col, cpu_col, mem_col, mut ts_col) = expect_data();
+    let request = |ts_col: &Column| InsertRequests {
+        inserts: vec![InsertRequest {
+            table_name: "demo".to_string(),
+            columns: vec![
+                host_col.clone(),
+                cpu_col.clone(),
+                mem_col.clone(),
+                ts_col.clone(),
+            ],
+            row_count: 4,
+        }],
+    };
+    // Rows delivered again would be visible under append mode.
+    let hints = |sequence| {
+        [
+            ("auto_create_table", "true"),
+            ("append_mode", "true"),
+            ("producer_id", "pipeline-1"),
+            ("producer_sequence", sequence),
+        ]
+    };
+
+    // The pipeline delivers the batch twice.
+    let result = db.insert_with_hints(request(&ts_col), &hints("1")).await;
+    assert_eq!(result.unwrap(), 4);
+    let result = db.insert_with_hints(request(&ts_col), &hints("1")).await;
+    assert_eq!(result.unwrap(), 0);
+
+    ts_col.values = Some(column::Values {
+        timestamp_millisecond_values: vec![200, 201, 202, 203],
+        ..Default::default()
+    });
+    let result = db.insert_with_hints(request(&ts_col), &hints("2")).await;
+    assert_eq!(result.unwrap(), 4);
+    let result = db.insert_with_hints(request(&ts_col), &hints("1")).await;
+    assert_eq!(result.unwrap(), 0);
+
+    let output = db.sql("SELECT count(*) FROM demo").await.unwrap();
+    let record_batches = match output.data {
+        OutputData::RecordBatches(record_batches) => record_batches,
+        OutputData::Stream(stream) => RecordBatches::try_collect(stream).await.unwrap(),
+        OutputData::AffectedRows(_) => unreachable!(),
+    };
+    let expected = "\
++----------+
+| count(*) |
++----------+
+| 8        |
++----------+";
+    assert_eq!(record_batches.pretty_print().unwrap(), expected);
+
+    let output = db.sql("ADMIN producer_watermarks('demo')").await.unwrap();
+    let record_batches = match output.data {
+        OutputData::RecordBatches(record_batches) => record_batches,
+        OutputData::Stream(stream) => RecordBatches::try_collect(stream).await.unwrap(),
+        OutputData::AffectedRows(_) => unreachable!(),
+    };
+    let pretty = record_batches.pretty_print().unwrap();
+    assert!(pretty.contains("| pipeline-1  | 2        |"), "{pretty}");
+
+    let _ = fe_grpc_server.shutdown().await;
+    guard.remove_all().await;
+}
+
 fn expect_data() -> (Column, Column, Column, Column) {
     // testing data:
     let expected_host_col = Column {
@@ -823,6 +896,30 @@ pub async fn test_grpc_timezone(store_type: StorageType) {
 | UTC       |
 +-----------+"
     );
+
+    // The timezone and the trace context can also be passed by the gRPC metadata.
+    let output = db
+        .sql_with_metadata(
+            "SELECT date_format(arrow_cast(0, 'Timestamp(Millisecond, None)'), '%Y-%m-%d %H:%M:%S') AS t",
+            &[
+                ("x-greptime-timezone", "Asia/Shanghai"),
+                (
+                    "traceparent",
+                    "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+                ),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        to_batch(output).await,
+        "\
++---------------------+
+| t                   |
++---------------------+
+| 1970-01-01 08:00:00 |
++---------------------+"
+    );
     let _ = fe_grpc_server.shutdown().await;
     guard.remove_all().await;
 }
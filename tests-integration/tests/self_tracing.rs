@@ -0,0 +1,97 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Self tracing installs the global tracing subscriber, so it's tested in its
+//! own binary.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use client::OutputData;
+use common_recordbatch::RecordBatches;
+use common_telemetry::logging::{LoggingOptions, TracingOptions};
+use common_telemetry::{init_global_logging, register_self_trace_handler, SelfTracingOptions};
+use frontend::instance::self_trace::{SelfTraceWriter, SELF_TRACE_SERVICE_NAME};
+use frontend::instance::Instance;
+use servers::query_handler::sql::SqlQueryHandler;
+use session::context::QueryContext;
+use tests_integration::standalone::GreptimeDbStandaloneBuilder;
+
+async fn query(instance: &Arc<Instance>, sql: &str) -> Option<String> {
+    let output = instance
+        .do_query(sql, QueryContext::arc())
+        .await
+        .remove(0)
+        .ok()?;
+    let OutputData::Stream(stream) = output.data else {
+        return None;
+    };
+    let recordbatches = RecordBatches::try_collect(stream).await.ok()?;
+    Some(recordbatches.pretty_print().unwrap())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_self_tracing_on_standalone() {
+    let _guards = init_global_logging(
+        "test_self_tracing",
+        &LoggingOptions {
+            dir: String::new(),
+            self_tracing: SelfTracingOptions {
+                enable: true,
+                sample_ratio: 1.0,
+            },
+            ..Default::default()
+        },
+        &TracingOptions::default(),
+        None,
+    );
+
+    let standalone = GreptimeDbStandaloneBuilder::new("test_self_tracing")
+        .build()
+        .await;
+    let instance = standalone.fe_instance();
+    register_self_trace_handler(Arc::new(SelfTraceWriter::new(instance.clone())));
+
+    for sql in [
+        "CREATE TABLE monitor (host STRING, cpu DOUBLE, ts TIMESTAMP TIME INDEX, PRIMARY KEY(host))",
+        "INSERT INTO monitor VALUES ('host1', 66.6, 1000), ('host2', 77.7, 2000)",
+    ] {
+        instance
+            .do_query(sql, QueryContext::arc())
+            .await
+            .remove(0)
+            .unwrap();
+    }
+    let result = query(instance, "SELECT host, cpu FROM monitor ORDER BY host")
+        .await
+        .unwrap();
+    assert!(result.contains("host2"), "{result}");
+
+    // The spans are exported in batches, so waits for them to be written.
+    let sql = format!(
+        "SELECT DISTINCT span_name FROM opentelemetry_traces \
+         WHERE service_name = '{SELF_TRACE_SERVICE_NAME}' ORDER BY span_name"
+    );
+    let mut span_names = String::new();
+    for _ in 0..60 {
+        if let Some(result) = query(instance, &sql).await {
+            span_names = result;
+            if span_names.contains("execute_sql") && span_names.contains("PartitionScan") {
+                return;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    panic!("spans of the query are not found in the trace table: {span_names}");
+}
@@ -64,6 +64,7 @@ macro_rules! sql_tests {
                 test_mysql_crud,
                 test_mysql_timezone,
                 test_mysql_async_timestamp,
+                test_mysql_timestamp_precision,
                 test_postgres_auth,
                 test_postgres_crud,
                 test_postgres_timezone,
@@ -1094,6 +1095,86 @@ pub async fn test_mysql_async_timestamp(store_type: StorageType) {
     guard.remove_all().await;
 }
 
+pub async fn test_mysql_timestamp_precision(store_type: StorageType) {
+    use mysql_async::prelude::*;
+    use mysql_async::Value;
+
+    let (addr, mut guard, fe_mysql_server) =
+        setup_mysql_server(store_type, "test_mysql_timestamp_precision").await;
+    let url = format!("mysql://{addr}/public");
+    let opts = mysql_async::Opts::from_url(&url).unwrap();
+    let mut conn = mysql_async::Conn::new(opts)
+        .await
+        .expect("create connection failure");
+
+    "create table precision_demo(i bigint, ts timestamp time index, t0 timestamp(0), t3 timestamp(3), t6 timestamp(6), t9 timestamp(9))"
+        .ignore(&mut conn)
+        .await
+        .unwrap();
+
+    // text protocol, nanoseconds are truncated to the precision of each column
+    let datetime = "'2024-01-02 03:04:05.123456789'";
+    format!(
+        "insert into precision_demo values(0, 0, {datetime}, {datetime}, {datetime}, {datetime})"
+    )
+    .ignore(&mut conn)
+    .await
+    .unwrap();
+    // binary protocol, MySQL datetime carries microseconds
+    let datetime = Value::Date(2024, 1, 2, 3, 4, 5, 123456);
+    conn.exec_drop(
+        "insert into precision_demo values(?, ?, ?, ?, ?, ?)",
+        (
+            1,
+            1000,
+            datetime.clone(),
+            datetime.clone(),
+            datetime.clone(),
+            datetime,
+        ),
+    )
+    .await
+    .unwrap();
+
+    // text protocol renders fractional digits by the precision, at most 6 digits
+    let rows: Vec<(i64, String, String, String, String)> =
+        "select i, t0, t3, t6, t9 from precision_demo order by i"
+            .fetch(&mut conn)
+            .await
+            .unwrap();
+    assert_eq!(rows.len(), 2);
+    for (_, t0, t3, t6, t9) in rows {
+        assert_eq!(t0, "2024-01-02 03:04:05");
+        assert_eq!(t3, "2024-01-02 03:04:05.123");
+        assert_eq!(t6, "2024-01-02 03:04:05.123456");
+        assert_eq!(t9, "2024-01-02 03:04:05.123456");
+    }
+
+    // binary protocol
+    let rows: Vec<mysql_async::Row> = conn
+        .exec(
+            "select i, t0, t3, t6, t9 from precision_demo where i >= ? order by i",
+            (0,),
+        )
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+    for row in rows {
+        let expected = [0, 123000, 123456, 123456];
+        for (index, micros) in expected.into_iter().enumerate() {
+            assert_eq!(
+                row.get::<Value, _>(index + 1),
+                Some(Value::Date(2024, 1, 2, 3, 4, 5, micros)),
+                "{row:?}"
+            );
+        }
+    }
+
+    drop(conn);
+    let _ = fe_mysql_server.shutdown().await;
+    guard.remove_all().await;
+}
+
 pub async fn test_mysql_prepare_stmt_insert_timestamp(store_type: StorageType) {
     let (addr, mut guard, server) =
         setup_mysql_server(store_type, "test_mysql_prepare_stmt_insert_timestamp").await;
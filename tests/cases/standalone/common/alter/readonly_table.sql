@@ -0,0 +1,27 @@
+CREATE TABLE ro(i INTEGER, j TIMESTAMP TIME INDEX, PRIMARY KEY(i));
+
+INSERT INTO ro VALUES(1, 1000), (2, 2000);
+
+ALTER TABLE ro SET 'readonly'='true';
+
+SELECT create_options FROM information_schema.tables WHERE table_name = 'ro';
+
+INSERT INTO ro VALUES(3, 3000);
+
+DELETE FROM ro WHERE i = 1;
+
+ALTER TABLE ro ADD COLUMN k STRING;
+
+TRUNCATE TABLE ro;
+
+SELECT i FROM ro ORDER BY i;
+
+ALTER TABLE ro SET 'readonly'='false';
+
+SELECT create_options FROM information_schema.tables WHERE table_name = 'ro';
+
+INSERT INTO ro VALUES(3, 3000);
+
+SELECT i FROM ro ORDER BY i;
+
+DROP TABLE ro;
@@ -26,6 +26,15 @@ SELECT date_format('2023-12-06 07:39:46.222'::DATETIME, '%Y-%m-%d %H:%M:%S:%3f')
 
 SELECT date_format('2023-12-06'::DATE, '%m-%d');
 
+--- to_char ---
+SELECT to_char('2023-12-06 07:39:46.222'::TIMESTAMP_MS, 'YYYY-MM-DD HH24:MI:SS.MS');
+
+SELECT to_char('2023-12-06 07:39:46.222'::TIMESTAMP_S, 'FMDay, DD Mon YYYY HH12:MI AM');
+
+SELECT to_char('2023-12-06'::DATE, 'YYYY/MM/DD');
+
+SELECT date_format('2023-12-06 07:39:46.222'::TIMESTAMP_MS, '%Y-%m-%d %H:%i');
+
 --- test date functions with table rows ---
 CREATE TABLE dates(d DATE, ts timestamp time index);
 
@@ -24,3 +24,13 @@ SELECT SUM(region_rows), SUM(disk_size), SUM(sst_size), SUM(index_size)
 SELECT data_length, index_length, avg_row_length, table_rows FROM INFORMATION_SCHEMA.TABLES WHERE table_name = 'test';
 
 DROP TABLE test;
+
+CREATE TABLE test_compression (
+    a int primary key,
+    ts timestamp time index,
+) WITH ('sst.compression' = 'lz4');
+
+SELECT sst_compression FROM INFORMATION_SCHEMA.REGION_STATISTICS WHERE table_id
+       IN (SELECT TABLE_ID FROM INFORMATION_SCHEMA.TABLES WHERE table_name = 'test_compression' and table_schema = 'public');
+
+DROP TABLE test_compression;
@@ -0,0 +1,30 @@
+create table src(host string, val float, ts timestamp time index);
+
+create table dst(host string, val double, ts timestamp time index);
+
+insert into src values ('host1', 1.5, 1000), ('host2', 2.5, 2000);
+
+-- float widens into double
+insert into dst select * from src;
+
+select * from dst order by ts;
+
+-- double doesn't narrow into float implicitly
+insert into src select * from dst;
+
+insert into src select host, cast(val as float), ts from dst;
+
+-- columns missing in the table are rejected by default
+insert into dst select host, val, ts, val * 2 as doubled from src;
+
+SET auto_add_columns = true;
+
+insert into dst select host, val, ts, val * 2 as doubled from src;
+
+select * from dst order by ts;
+
+SET auto_add_columns = false;
+
+drop table src;
+
+drop table dst;
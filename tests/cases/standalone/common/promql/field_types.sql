@@ -0,0 +1,78 @@
+CREATE TABLE int_counter (
+  ts timestamp(3) time index,
+  host STRING PRIMARY KEY,
+  val BIGINT,
+);
+
+INSERT INTO TABLE int_counter VALUES
+    (0,     'host1', 0),
+    (5000,  'host1', 10),
+    (10000, 'host1', 20),
+    (15000, 'host1', 30),
+    (20000, 'host1', 40);
+
+CREATE TABLE uint_counter (
+  ts timestamp(3) time index,
+  host STRING PRIMARY KEY,
+  val BIGINT UNSIGNED,
+);
+
+INSERT INTO TABLE uint_counter VALUES
+    (0,     'host1', 0),
+    (5000,  'host1', 10),
+    (10000, 'host1', 20),
+    (15000, 'host1', 30),
+    (20000, 'host1', 40);
+
+CREATE TABLE float_counter (
+  ts timestamp(3) time index,
+  host STRING PRIMARY KEY,
+  val FLOAT,
+);
+
+INSERT INTO TABLE float_counter VALUES
+    (0,     'host1', 0),
+    (5000,  'host1', 10),
+    (10000, 'host1', 20),
+    (15000, 'host1', 30),
+    (20000, 'host1', 40);
+
+CREATE TABLE bool_flag (
+  ts timestamp(3) time index,
+  host STRING PRIMARY KEY,
+  val BOOLEAN,
+);
+
+INSERT INTO TABLE bool_flag VALUES
+    (0,     'host1', true),
+    (0,     'host2', true),
+    (5000,  'host1', true),
+    (5000,  'host2', false),
+    (10000, 'host1', false),
+    (10000, 'host2', false),
+    (15000, 'host1', true),
+    (15000, 'host2', false);
+
+-- Test on field columns that are not float64
+-- SQLNESS SORT_RESULT 3 1
+TQL EVAL (0, 20, '5s') sum(rate(int_counter[10s]));
+
+-- SQLNESS SORT_RESULT 3 1
+TQL EVAL (0, 20, '5s') sum(rate(uint_counter[10s]));
+
+-- SQLNESS SORT_RESULT 3 1
+TQL EVAL (0, 20, '5s') sum(rate(float_counter[10s]));
+
+-- SQLNESS SORT_RESULT 3 1
+TQL EVAL (0, 15, '5s') sum(bool_flag);
+
+-- SQLNESS SORT_RESULT 3 1
+TQL EVAL (0, 20, '5s') int_counter + 1.5;
+
+DROP TABLE int_counter;
+
+DROP TABLE uint_counter;
+
+DROP TABLE float_counter;
+
+DROP TABLE bool_flag;
@@ -47,8 +47,11 @@ TQL EVAL (0, 15, '5s') label_replace(test{host="host2"}, "new_idc", "$2", "idc",
 -- SQLNESS SORT_RESULT 3 1
 TQL EVAL (0, 15, '5s') label_replace(test{host="host2"}, "idc", "$2", "idc", "(.*):(.*)");
 
--- test the empty source label --
--- TODO(dennis): we can't remove the label currently --
+-- the label is deleted when the replacement is empty --
+-- SQLNESS SORT_RESULT 3 1
+TQL EVAL (0, 15, '5s') label_replace(test{host="host1"}, "idc", "$1", "idc", "idc1(.*)");
+
+-- test the empty source label, the label is removed from all series --
 -- SQLNESS SORT_RESULT 3 1
 TQL EVAL (0, 15, '5s') label_replace(test{host="host2"}, "idc", "", "", "");
 
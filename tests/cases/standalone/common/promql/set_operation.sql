@@ -337,3 +337,25 @@ tql eval (3, 4, '1s') cache_hit_with_null_label / on(job) (cache_miss_with_null_
 drop table cache_hit_with_null_label;
 
 drop table cache_miss_with_null_label;
+
+-- `or` across different metrics with different time index, field and tag columns
+create table foo (ts timestamp time index, host string primary key, val double);
+
+insert into foo values (0, "a", 1.0), (0, "b", 2.0);
+
+create table bar (tt timestamp time index, host string, idc string, cpu double, primary key (host, idc));
+
+insert into bar values (0, "a", "x", 10.0), (0, "c", "y", 30.0);
+
+-- SQLNESS SORT_RESULT 3 1
+tql eval (0, 0, '1s') foo or bar;
+
+-- SQLNESS SORT_RESULT 3 1
+tql eval (0, 0, '1s') foo or ignoring(idc) bar;
+
+-- SQLNESS SORT_RESULT 3 1
+tql eval (0, 0, '1s') bar or on(host) foo;
+
+drop table foo;
+
+drop table bar;
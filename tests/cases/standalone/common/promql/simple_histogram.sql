@@ -187,3 +187,24 @@ insert into histogram4_bucket values
 tql eval (2900, 3000, '100s') histogram_quantile(0.9, histogram4_bucket);
 
 drop table histogram4_bucket;
+
+-- test with the +Inf bucket missing
+create table histogram5_bucket (
+    ts timestamp time index,
+    le string,
+    s string,
+    val double,
+    primary key (s, le),
+);
+
+insert into histogram5_bucket values
+    (3000000, "0.1", "a", 10),
+    (3000000, "1", "a", 20),
+    (3000000, "5", "a", 40);
+
+tql eval (3000, 3000, '1s') histogram_quantile(0.25, histogram5_bucket);
+
+-- the largest bucket is treated as +Inf
+tql eval (3000, 3000, '1s') histogram_quantile(0.9, histogram5_bucket);
+
+drop table histogram5_bucket;
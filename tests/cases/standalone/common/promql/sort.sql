@@ -36,3 +36,27 @@ TQL EVAL (0, 15, '5s') sort_by_label(sum(test) by (idc, host), "idc", "host");
 TQL EVAL (0, 15, '5s') sort_by_label_desc(sum(test) by (idc, host), "idc", "host");
 
 drop table test;
+
+CREATE TABLE shards (
+  ts timestamp(3) time index,
+  shard STRING,
+  val BIGINT,
+  PRIMARY KEY(shard),
+);
+
+INSERT INTO TABLE shards VALUES
+    (0, '1', 1),
+    (0, '2', 2),
+    (0, '10', 3),
+    (0, '100', 4),
+    (0, 'abc', 5);
+
+TQL EVAL (0, 0, '5s') sort_by_label(shards, "shard");
+
+TQL EVAL (0, 0, '5s') sort_by_label(shards, "shard", "__numeric__");
+
+TQL EVAL (0, 0, '5s') sort_by_label_desc(shards, "shard");
+
+TQL EVAL (0, 0, '5s') sort_by_label_desc(shards, "shard", "__numeric__");
+
+drop table shards;
@@ -0,0 +1,26 @@
+CREATE TABLE host (
+  ts timestamp(3) time index,
+  host STRING PRIMARY KEY,
+  val BIGINT,
+);
+
+INSERT INTO TABLE host VALUES
+    (0,     'host1', 0),
+    (5000,  'host1', null),
+    (10000, 'host1', 1),
+    (15000, 'host1', null),
+    (20000, 'host1', 2),
+    (0,     'host2', 3),
+    (5000,  'host2', null),
+    (10000, 'host2', 4),
+    (15000, 'host2', null),
+    (20000, 'host2', 5);
+
+-- Test ORDER BY time index and LIMIT pushed down to range query, the limit ends between the hosts of a slot
+SELECT ts, host, min(val) RANGE '10s' FROM host ALIGN '5s' ORDER BY ts DESC, host LIMIT 3;
+
+SELECT ts, host, min(val) RANGE '10s' FROM host ALIGN '5s' ORDER BY ts, host LIMIT 2 OFFSET 1;
+
+SELECT ts AS t, min(val) RANGE '10s' + 1 AS m FROM host ALIGN '5s' BY (host) ORDER BY t DESC, m LIMIT 3;
+
+DROP TABLE host;
@@ -0,0 +1,61 @@
+CREATE TABLE soft_delete (
+  host STRING INVERTED INDEX,
+  val DOUBLE,
+  deleted BOOLEAN,
+  ts TIMESTAMP TIME INDEX,
+  PRIMARY KEY (host)
+) WITH ('scan.default_filter' = 'deleted = false');
+
+INSERT INTO soft_delete VALUES
+  ('a', 1.0, false, 1000),
+  ('a', 2.0, true, 2000),
+  ('b', 3.0, false, 3000),
+  ('c', 4.0, true, 4000);
+
+ADMIN flush_table('soft_delete');
+
+INSERT INTO soft_delete VALUES
+  ('b', 5.0, true, 5000),
+  ('d', 6.0, false, 6000);
+
+SELECT * FROM soft_delete ORDER BY ts;
+
+-- The filter applies even if the column is not projected.
+SELECT host, val FROM soft_delete ORDER BY ts;
+
+-- Filters on the indexed column are combined with the default filter.
+SELECT host, val FROM soft_delete WHERE host = 'a' ORDER BY ts;
+
+SELECT host FROM soft_delete WHERE host IN ('b', 'c') ORDER BY host;
+
+SELECT host, count(val) FROM soft_delete GROUP BY host ORDER BY host;
+
+SHOW CREATE TABLE soft_delete;
+
+-- Audits the soft-deleted rows.
+SET BYPASS_DEFAULT_FILTER = true;
+
+SELECT * FROM soft_delete ORDER BY ts;
+
+SELECT host, val FROM soft_delete WHERE deleted ORDER BY ts;
+
+SET BYPASS_DEFAULT_FILTER = false;
+
+SELECT host FROM soft_delete ORDER BY ts;
+
+-- The columns of the default filter can't be dropped.
+-- SQLNESS REPLACE (Valid\sfields.*) REDACTED
+ALTER TABLE soft_delete DROP COLUMN deleted;
+
+-- SQLNESS REPLACE (Valid\sfields.*) REDACTED
+CREATE TABLE bad_filter (
+  host STRING PRIMARY KEY,
+  ts TIMESTAMP TIME INDEX,
+) WITH ('scan.default_filter' = 'removed = false');
+
+CREATE TABLE bad_filter (
+  host STRING PRIMARY KEY,
+  ts TIMESTAMP TIME INDEX,
+) WITH ('scan.default_filter' = 'host');
+
+DROP TABLE soft_delete;
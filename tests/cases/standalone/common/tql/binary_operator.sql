@@ -8,6 +8,25 @@ tql eval (0, 30, '10s'), data + (1 < bool 2);
 
 tql eval (0, 30, '10s'), data + (1 > bool 2);
 
+-- NaN is not equal to anything, including itself
+tql eval (0, 30, '10s'), data == NaN;
+
+tql eval (0, 30, '10s'), NaN == data;
+
+tql eval (0, 30, '10s'), data != NaN;
+
+tql eval (0, 30, '10s'), NaN != data;
+
+tql eval (0, 30, '10s'), data < NaN;
+
+tql eval (0, 30, '10s'), NaN >= data;
+
+tql eval (0, 0, '1s') NaN == bool NaN;
+
+tql eval (0, 0, '1s') NaN != bool NaN;
+
+tql eval (0, 0, '1s') NaN > bool 1;
+
 drop table data;
 
 -- Binary operator on table with multiple field columns